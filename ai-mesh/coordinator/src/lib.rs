@@ -32,7 +32,7 @@
 // - Uptime: availability percentage
 // ============================================================================
 
-use aether_verifiers_tee::{AttestationReport, TeeVerifier};
+use aether_verifiers_tee::{AttestationReport, MeasurementRegistry, TeeVerifier};
 use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -66,6 +66,10 @@ pub struct MeshCoordinator {
 
     /// TEE attestation verifier
     tee_verifier: TeeVerifier,
+
+    /// Governance measurement registry and the slot it was synced at; when
+    /// set, it replaces the local whitelist at registration
+    measurement_registry: Option<(MeasurementRegistry, u64)>,
 }
 
 #[derive(Debug, Clone)]
@@ -94,6 +98,7 @@ impl MeshCoordinator {
             assignments: HashMap::new(),
             reputation: HashMap::new(),
             tee_verifier,
+            measurement_registry: None,
         }
     }
 
@@ -101,6 +106,18 @@ impl MeshCoordinator {
         self.tee_verifier.add_approved_measurement(measurement);
     }
 
+    /// Admit workers attesting with devnet-signed simulated quotes. Only for
+    /// chains configured in dev mode.
+    pub fn enable_dev_mode(&mut self) {
+        self.tee_verifier.enable_dev_mode();
+    }
+
+    /// Check registrations against the on-chain registry as of `slot`.
+    /// Call again whenever the chain head advances.
+    pub fn sync_measurement_registry(&mut self, registry: MeasurementRegistry, slot: u64) {
+        self.measurement_registry = Some((registry, slot));
+    }
+
     /// Register a new worker
     pub fn register_worker(&mut self, worker: WorkerInfo) -> Result<()> {
         // Verify TEE attestation
@@ -111,9 +128,14 @@ impl MeshCoordinator {
             serde_json::from_slice(&worker.attestation).map_err(|e| {
                 anyhow::anyhow!("invalid attestation payload (expected JSON report): {e}")
             })?;
-        self.tee_verifier
-            .verify(&report, current_timestamp())
-            .map_err(|e| anyhow::anyhow!("attestation verification failed: {e}"))?;
+        let verified = match &self.measurement_registry {
+            Some((registry, slot)) => self
+                .tee_verifier
+                .verify_registered(&report, registry, *slot, current_timestamp())
+                .map(|_| ()),
+            None => self.tee_verifier.verify(&report, current_timestamp()),
+        };
+        verified.map_err(|e| anyhow::anyhow!("attestation verification failed: {e}"))?;
 
         self.workers.insert(worker.worker_id.clone(), worker);
 
@@ -138,7 +160,7 @@ impl MeshCoordinator {
         }
 
         // Sort by reputation (best first)
-        candidates.sort_by(|a, b| b.reputation_score.cmp(&a.reputation_score));
+        candidates.sort_by_key(|w| std::cmp::Reverse(w.reputation_score));
 
         let best_worker = candidates[0];
 
@@ -297,17 +319,18 @@ fn current_timestamp() -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use aether_verifiers_tee::{AttestationReport, TeeType};
+    use aether_verifiers_tee::{SimulatedSigner, TeeType};
+
+    fn dev_coordinator() -> MeshCoordinator {
+        let mut coordinator = MeshCoordinator::new();
+        coordinator.enable_dev_mode();
+        coordinator
+    }
 
     fn test_worker(id: u8, reputation: i32) -> WorkerInfo {
-        let report = AttestationReport {
-            tee_type: TeeType::Simulation,
-            measurement: vec![1u8; 48],
-            nonce: vec![2u8; 32],
-            timestamp: current_timestamp(),
-            signature: vec![3u8; 64],
-            cert_chain: vec![vec![4u8; 16]],
-        };
+        let report = SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &[2u8; 64], current_timestamp())
+            .unwrap();
         WorkerInfo {
             worker_id: vec![id],
             tee_type: "sev-snp".to_string(),
@@ -320,7 +343,7 @@ mod tests {
 
     #[test]
     fn test_register_worker() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         let worker = test_worker(1, 0);
 
         coordinator.register_worker(worker).unwrap();
@@ -328,9 +351,39 @@ mod tests {
         assert_eq!(coordinator.worker_count(), 1);
     }
 
+    #[test]
+    fn test_registration_uses_measurement_registry() {
+        use aether_verifiers_tee::{MeasurementSet, ValidityWindow};
+
+        let mut registry = MeasurementRegistry::new();
+        let id = registry.stage(MeasurementSet::new(TeeType::Simulation, vec![1u8; 48]));
+        let mut coordinator = dev_coordinator();
+
+        // The local whitelist no longer counts once a registry is synced.
+        coordinator.sync_measurement_registry(registry.clone(), 100);
+        assert!(coordinator.register_worker(test_worker(1, 0)).is_err());
+
+        let (parameter, value) = MeasurementRegistry::approve_parameter(
+            &id,
+            ValidityWindow {
+                from_slot: 0,
+                until_slot: Some(200),
+            },
+        );
+        registry
+            .apply_parameter_change(&parameter, value, 50)
+            .unwrap();
+        coordinator.sync_measurement_registry(registry.clone(), 100);
+        coordinator.register_worker(test_worker(1, 0)).unwrap();
+
+        coordinator.sync_measurement_registry(registry, 200);
+        assert!(coordinator.register_worker(test_worker(2, 0)).is_err());
+        assert_eq!(coordinator.worker_count(), 1);
+    }
+
     #[test]
     fn test_assign_job() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
 
         coordinator.register_worker(test_worker(1, 100)).unwrap();
         coordinator.register_worker(test_worker(2, 50)).unwrap();
@@ -351,7 +404,7 @@ mod tests {
 
     #[test]
     fn test_reputation_update() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         coordinator.register_worker(test_worker(1, 0)).unwrap();
 
         coordinator
@@ -364,7 +417,7 @@ mod tests {
 
     #[test]
     fn test_assign_job_marks_worker_unavailable() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         coordinator.register_worker(test_worker(1, 100)).unwrap();
 
         let reqs = JobRequirements {
@@ -386,7 +439,7 @@ mod tests {
 
     #[test]
     fn test_duplicate_job_assignment_rejected() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         coordinator.register_worker(test_worker(1, 100)).unwrap();
         coordinator.register_worker(test_worker(2, 50)).unwrap();
 
@@ -403,7 +456,7 @@ mod tests {
 
     #[test]
     fn test_complete_job_releases_worker() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         coordinator.register_worker(test_worker(1, 100)).unwrap();
 
         let reqs = JobRequirements {
@@ -425,7 +478,7 @@ mod tests {
 
     #[test]
     fn test_cancel_job_releases_worker() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         coordinator.register_worker(test_worker(1, 100)).unwrap();
 
         let reqs = JobRequirements {
@@ -441,14 +494,14 @@ mod tests {
 
     #[test]
     fn test_complete_nonexistent_job_fails() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         let err = coordinator.complete_job(&[99]).unwrap_err();
         assert!(err.to_string().contains("job not found"));
     }
 
     #[test]
     fn test_ban_low_reputation() {
-        let mut coordinator = MeshCoordinator::new();
+        let mut coordinator = dev_coordinator();
         coordinator.register_worker(test_worker(1, -90)).unwrap();
 
         coordinator
@@ -463,18 +516,19 @@ mod tests {
 #[cfg(test)]
 mod proptests {
     use super::*;
-    use aether_verifiers_tee::{AttestationReport, TeeType};
+    use aether_verifiers_tee::{AttestationReport, SimulatedSigner};
     use proptest::prelude::*;
 
+    fn dev_coordinator() -> MeshCoordinator {
+        let mut coordinator = MeshCoordinator::new();
+        coordinator.enable_dev_mode();
+        coordinator
+    }
+
     fn make_report() -> AttestationReport {
-        AttestationReport {
-            tee_type: TeeType::Simulation,
-            measurement: vec![1u8; 48],
-            nonce: vec![2u8; 32],
-            timestamp: current_timestamp(),
-            signature: vec![3u8; 64],
-            cert_chain: vec![vec![4u8; 16]],
-        }
+        SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &[2u8; 64], current_timestamp())
+            .unwrap()
     }
 
     fn make_worker(id: Vec<u8>, reputation: i32, available: bool) -> WorkerInfo {
@@ -501,7 +555,7 @@ mod proptests {
         /// Reputation score is always clamped to [-100, 1000] after any event.
         #[test]
         fn prop_reputation_clamped(initial in -100i32..=1000i32, events in proptest::collection::vec(0u8..5, 0..20)) {
-            let mut coord = MeshCoordinator::new();
+            let mut coord = dev_coordinator();
             coord.register_worker(make_worker(vec![1], initial, true)).unwrap();
 
             let event_types = [
@@ -529,7 +583,7 @@ mod proptests {
             rep_a in 50i32..=1000,
             rep_b in 50i32..=1000,
         ) {
-            let mut coord = MeshCoordinator::new();
+            let mut coord = dev_coordinator();
             coord.register_worker(make_worker(vec![1], rep_a, true)).unwrap();
             coord.register_worker(make_worker(vec![2], rep_b, true)).unwrap();
 
@@ -549,7 +603,7 @@ mod proptests {
         /// After assign+complete, available_worker_count returns to initial.
         #[test]
         fn prop_assign_complete_restores_availability(n_workers in 1usize..=8) {
-            let mut coord = MeshCoordinator::new();
+            let mut coord = dev_coordinator();
             for i in 0..n_workers {
                 coord.register_worker(make_worker(vec![i as u8], 100, true)).unwrap();
             }
@@ -568,7 +622,7 @@ mod proptests {
         /// After assign+cancel, available_worker_count returns to initial.
         #[test]
         fn prop_assign_cancel_restores_availability(n_workers in 1usize..=8) {
-            let mut coord = MeshCoordinator::new();
+            let mut coord = dev_coordinator();
             for i in 0..n_workers {
                 coord.register_worker(make_worker(vec![i as u8], 100, true)).unwrap();
             }
@@ -585,7 +639,7 @@ mod proptests {
         /// Duplicate job ID is always rejected.
         #[test]
         fn prop_duplicate_job_rejected(job_id in proptest::collection::vec(any::<u8>(), 1..=16)) {
-            let mut coord = MeshCoordinator::new();
+            let mut coord = dev_coordinator();
             coord.register_worker(make_worker(vec![1], 100, true)).unwrap();
             coord.register_worker(make_worker(vec![2], 50, true)).unwrap();
 
@@ -599,7 +653,7 @@ mod proptests {
         #[test]
         fn prop_ban_threshold(initial in -99i32..=0i32) {
             // Apply enough ChallengeLost events to push below -100
-            let mut coord = MeshCoordinator::new();
+            let mut coord = dev_coordinator();
             coord.register_worker(make_worker(vec![1], initial, true)).unwrap();
 
             // ChallengeLost = -50; two events pushes any score in [-99, 0] below -100
@@ -0,0 +1,231 @@
+use crate::monitoring::RouterMetrics;
+use crate::routing::{rank_providers, JobRequest, ProviderCandidate, RoutingDecision};
+use crate::scoring::{check_eligibility, RejectionReason};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// How one provider fared when a job was routed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CandidateRecord {
+    pub provider_id: String,
+    /// Score if the provider passed every filter.
+    pub score: Option<f64>,
+    pub rejection: Option<RejectionReason>,
+}
+
+/// A single routing decision, with every candidate that was considered.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub job_id: String,
+    pub candidates: Vec<CandidateRecord>,
+    pub chosen_provider: Option<String>,
+}
+
+/// Append-only log of routing decisions.
+///
+/// When backed by a file, each record is written as one JSON line and flushed
+/// before `append` returns; reopening the file restores the full history.
+#[derive(Debug, Default)]
+pub struct AuditLog {
+    records: Vec<AuditRecord>,
+    path: Option<PathBuf>,
+}
+
+impl AuditLog {
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// Open (or create) a JSON-lines audit file and load its existing records.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut records = Vec::new();
+        if path.exists() {
+            let file = File::open(&path)
+                .with_context(|| format!("opening audit log {}", path.display()))?;
+            for (line_no, line) in BufReader::new(file).lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let record: AuditRecord = serde_json::from_str(&line)
+                    .with_context(|| format!("corrupt audit record at line {}", line_no + 1))?;
+                records.push(record);
+            }
+        }
+        Ok(Self {
+            records,
+            path: Some(path),
+        })
+    }
+
+    /// Append a record, assigning it the next sequence number.
+    pub fn append(
+        &mut self,
+        job_id: String,
+        candidates: Vec<CandidateRecord>,
+        chosen_provider: Option<String>,
+    ) -> Result<&AuditRecord> {
+        let record = AuditRecord {
+            seq: self.records.last().map(|r| r.seq + 1).unwrap_or(0),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            job_id,
+            candidates,
+            chosen_provider,
+        };
+        if let Some(path) = &self.path {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("opening audit log {}", path.display()))?;
+            writeln!(file, "{}", serde_json::to_string(&record)?)?;
+            file.flush()?;
+        }
+        self.records.push(record);
+        Ok(self.records.last().expect("record just pushed"))
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    pub fn records(&self) -> &[AuditRecord] {
+        &self.records
+    }
+
+    /// Every decision made for a job, oldest first (re-routes produce several).
+    pub fn for_job<'a>(&'a self, job_id: &'a str) -> impl Iterator<Item = &'a AuditRecord> {
+        self.records.iter().filter(move |r| r.job_id == job_id)
+    }
+
+    /// Decisions in which `provider_id` was chosen.
+    pub fn chosen<'a>(&'a self, provider_id: &'a str) -> impl Iterator<Item = &'a AuditRecord> {
+        self.records
+            .iter()
+            .filter(move |r| r.chosen_provider.as_deref() == Some(provider_id))
+    }
+
+    /// Decisions recorded at or after `timestamp`.
+    pub fn since(&self, timestamp: u64) -> impl Iterator<Item = &AuditRecord> {
+        self.records
+            .iter()
+            .filter(move |r| r.timestamp >= timestamp)
+    }
+}
+
+/// Per-candidate scores and rejection reasons for a job, in input order.
+pub fn explain(job: &JobRequest, providers: &[ProviderCandidate]) -> Vec<CandidateRecord> {
+    let ranked = rank_providers(job, providers);
+    providers
+        .iter()
+        .map(|p| {
+            let score = ranked
+                .iter()
+                .find(|(r, _)| r.provider_id == p.provider_id)
+                .map(|(_, s)| *s);
+            CandidateRecord {
+                provider_id: p.provider_id.clone(),
+                score,
+                rejection: check_eligibility(job, p).err(),
+            }
+        })
+        .collect()
+}
+
+/// Route a job and record the decision, including unroutable outcomes.
+pub fn route_job_audited(
+    job: &JobRequest,
+    providers: &[ProviderCandidate],
+    log: &mut AuditLog,
+    metrics: &mut RouterMetrics,
+) -> Result<Option<RoutingDecision>> {
+    let decision = crate::routing::route_job_with_metrics(job, providers, metrics);
+    log.append(
+        job.job_id.clone(),
+        explain(job, providers),
+        decision.as_ref().map(|d| d.provider_id.clone()),
+    )?;
+    Ok(decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn providers() -> Vec<ProviderCandidate> {
+        vec![
+            ProviderCandidate {
+                provider_id: "offline".to_string(),
+                available: false,
+                ..ProviderCandidate::default()
+            },
+            ProviderCandidate {
+                provider_id: "expensive".to_string(),
+                price_per_unit: 1_000_000,
+                ..ProviderCandidate::default()
+            },
+            ProviderCandidate {
+                provider_id: "ok".to_string(),
+                ..ProviderCandidate::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn records_scores_and_rejections() {
+        let mut log = AuditLog::in_memory();
+        let mut metrics = RouterMetrics::new(8);
+        let job = JobRequest::default();
+
+        let decision = route_job_audited(&job, &providers(), &mut log, &mut metrics)
+            .unwrap()
+            .unwrap();
+        assert_eq!(decision.provider_id, "ok");
+
+        let record = log.for_job("job-default").next().unwrap();
+        assert_eq!(record.chosen_provider.as_deref(), Some("ok"));
+        assert_eq!(
+            record.candidates[0].rejection,
+            Some(RejectionReason::Unavailable)
+        );
+        assert_eq!(
+            record.candidates[1].rejection,
+            Some(RejectionReason::PriceTooHigh)
+        );
+        assert!(record.candidates[2].score.is_some());
+        assert_eq!(log.chosen("ok").count(), 1);
+    }
+
+    #[test]
+    fn file_backed_log_survives_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("routing-audit.jsonl");
+        let mut metrics = RouterMetrics::new(8);
+        {
+            let mut log = AuditLog::open(&path).unwrap();
+            route_job_audited(&JobRequest::default(), &providers(), &mut log, &mut metrics)
+                .unwrap();
+            route_job_audited(&JobRequest::default(), &[], &mut log, &mut metrics).unwrap();
+        }
+
+        let mut reopened = AuditLog::open(&path).unwrap();
+        assert_eq!(reopened.len(), 2);
+        assert_eq!(reopened.records()[1].chosen_provider, None);
+        let next = reopened.append("job-x".into(), vec![], None).unwrap();
+        assert_eq!(next.seq, 2);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::routing::{rank_providers, JobRequest, ProviderCandidate};
+use aether_codecs::abi::{self, job_escrow};
+use aether_types::{Address, H256};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ShardStatus {
+    Assigned,
+    Completed,
+    Failed,
+}
+
+/// One contiguous slice of a batch job's inputs, held by a single provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Shard {
+    pub index: u32,
+    /// Half-open range of input indices `[start, end)`.
+    pub input_start: u64,
+    pub input_end: u64,
+    pub provider_id: String,
+    /// Escrow milestone released to the provider when this shard completes.
+    pub payment: u128,
+    pub status: ShardStatus,
+    pub output: Option<Vec<u8>>,
+    /// Whether the milestone has been released from escrow.
+    #[serde(default)]
+    pub released: bool,
+}
+
+impl Shard {
+    pub fn job_id(&self, parent: &str) -> String {
+        format!("{parent}#shard-{}", self.index)
+    }
+
+    pub fn input_count(&self) -> u64 {
+        self.input_end - self.input_start
+    }
+}
+
+/// A batch job split across providers, with per-shard completion tracking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPlan {
+    pub job_id: String,
+    pub total_inputs: u64,
+    pub total_payment: u128,
+    pub shards: Vec<Shard>,
+}
+
+impl BatchPlan {
+    /// Split `total_inputs` across up to `max_shards` distinct top-ranked providers.
+    ///
+    /// Inputs and payment are divided proportionally; remainders go to the
+    /// earliest shards so the shard payments always sum to `total_payment`.
+    pub fn split(
+        job: &JobRequest,
+        providers: &[ProviderCandidate],
+        total_inputs: u64,
+        total_payment: u128,
+        max_shards: u32,
+    ) -> Result<Self> {
+        if total_inputs == 0 {
+            bail!("batch has no inputs");
+        }
+        if max_shards == 0 {
+            bail!("max_shards must be non-zero");
+        }
+
+        let ranked = rank_providers(job, providers);
+        if ranked.is_empty() {
+            bail!("no eligible providers for batch {}", job.job_id);
+        }
+
+        let shard_count = (ranked.len() as u64)
+            .min(max_shards as u64)
+            .min(total_inputs);
+        let base_inputs = total_inputs / shard_count;
+        let extra_inputs = total_inputs % shard_count;
+
+        let mut shards = Vec::with_capacity(shard_count as usize);
+        let mut cursor = 0u64;
+        let mut paid = 0u128;
+        for (i, (provider, _)) in ranked.iter().take(shard_count as usize).enumerate() {
+            let count = base_inputs + u64::from((i as u64) < extra_inputs);
+            let payment = if i as u64 == shard_count - 1 {
+                total_payment - paid
+            } else {
+                let Some(weighted) = total_payment.checked_mul(u128::from(count)) else {
+                    bail!(
+                        "payment {total_payment} is too large to split over {total_inputs} inputs"
+                    );
+                };
+                weighted / u128::from(total_inputs)
+            };
+            paid += payment;
+            shards.push(Shard {
+                index: i as u32,
+                input_start: cursor,
+                input_end: cursor + count,
+                provider_id: provider.provider_id.clone(),
+                payment,
+                status: ShardStatus::Assigned,
+                output: None,
+                released: false,
+            });
+            cursor += count;
+        }
+
+        Ok(Self {
+            job_id: job.job_id.clone(),
+            total_inputs,
+            total_payment,
+            shards,
+        })
+    }
+
+    pub fn complete_shard(&mut self, index: u32, output: Vec<u8>) -> Result<()> {
+        let shard = self.shard_mut(index)?;
+        if shard.status != ShardStatus::Assigned {
+            bail!("shard {index} is not awaiting results");
+        }
+        shard.status = ShardStatus::Completed;
+        shard.output = Some(output);
+        Ok(())
+    }
+
+    pub fn fail_shard(&mut self, index: u32) -> Result<()> {
+        let shard = self.shard_mut(index)?;
+        if shard.status == ShardStatus::Completed {
+            bail!("shard {index} already completed");
+        }
+        shard.status = ShardStatus::Failed;
+        Ok(())
+    }
+
+    /// Move a failed shard to a provider not already holding part of this batch.
+    pub fn reassign_failed(
+        &mut self,
+        index: u32,
+        job: &JobRequest,
+        providers: &[ProviderCandidate],
+    ) -> Result<String> {
+        let holders: Vec<String> = self.shards.iter().map(|s| s.provider_id.clone()).collect();
+        let shard = self.shard_mut(index)?;
+        if shard.status != ShardStatus::Failed {
+            bail!("shard {index} has not failed");
+        }
+        let next = rank_providers(job, providers)
+            .into_iter()
+            .find(|(p, _)| !holders.contains(&p.provider_id))
+            .map(|(p, _)| p.provider_id.clone());
+        let Some(provider_id) = next else {
+            bail!("no spare provider for shard {index}");
+        };
+        shard.provider_id = provider_id.clone();
+        shard.status = ShardStatus::Assigned;
+        Ok(provider_id)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.shards
+            .iter()
+            .all(|s| s.status == ShardStatus::Completed)
+    }
+
+    /// Milestone payouts owed for completed shards not yet released, as
+    /// `(provider_id, amount)`.
+    pub fn milestone_payouts(&self) -> Vec<(String, u128)> {
+        self.unreleased()
+            .map(|s| (s.provider_id.clone(), s.payment))
+            .collect()
+    }
+
+    /// Job escrow `RELEASE_MILESTONE` calls for the payouts owed, as
+    /// `(shard index, call data)`, paying out of the escrowed job
+    /// `escrow_job_id`. The requester sends them; `address_of` maps a
+    /// provider id to the address it is paid at. Mark each shard with
+    /// `mark_released` once its call has landed.
+    pub fn milestone_release_calls(
+        &self,
+        escrow_job_id: H256,
+        address_of: impl Fn(&str) -> Option<Address>,
+    ) -> Result<Vec<(u32, Vec<u8>)>> {
+        self.unreleased()
+            .map(|shard| {
+                let Some(provider) = address_of(&shard.provider_id) else {
+                    bail!("no payout address for provider {}", shard.provider_id);
+                };
+                let data = abi::encode_call(job_escrow::RELEASE_MILESTONE, |w| {
+                    w.put_fixed(escrow_job_id.as_bytes())
+                        .put_fixed(provider.as_bytes())
+                        .put_u128(shard.payment);
+                });
+                Ok((shard.index, data))
+            })
+            .collect()
+    }
+
+    pub fn mark_released(&mut self, index: u32) -> Result<()> {
+        let shard = self.shard_mut(index)?;
+        if shard.status != ShardStatus::Completed || shard.released {
+            bail!("shard {index} has no milestone owed");
+        }
+        shard.released = true;
+        Ok(())
+    }
+
+    /// Shard outputs in input order, once every shard has completed.
+    pub fn reassemble(&self) -> Option<Vec<Vec<u8>>> {
+        if !self.is_complete() {
+            return None;
+        }
+        self.shards.iter().map(|s| s.output.clone()).collect()
+    }
+
+    fn unreleased(&self) -> impl Iterator<Item = &Shard> {
+        self.shards
+            .iter()
+            .filter(|s| s.status == ShardStatus::Completed && !s.released)
+    }
+
+    fn shard_mut(&mut self, index: u32) -> Result<&mut Shard> {
+        self.shards
+            .get_mut(index as usize)
+            .ok_or_else(|| anyhow::anyhow!("unknown shard {index}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn providers(n: usize) -> Vec<ProviderCandidate> {
+        (0..n)
+            .map(|i| ProviderCandidate {
+                provider_id: format!("p{i}"),
+                reputation_score: 90 - i as i32,
+                ..ProviderCandidate::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_covers_inputs_and_payment() {
+        let job = JobRequest::default();
+        let plan = BatchPlan::split(&job, &providers(5), 10, 1_001, 3).unwrap();
+
+        assert_eq!(plan.shards.len(), 3);
+        let counts: Vec<u64> = plan.shards.iter().map(Shard::input_count).collect();
+        assert_eq!(counts, vec![4, 3, 3]);
+        assert_eq!(plan.shards[2].input_end, 10);
+        let paid: u128 = plan.shards.iter().map(|s| s.payment).sum();
+        assert_eq!(paid, 1_001);
+        assert_eq!(plan.shards[0].job_id(&plan.job_id), "job-default#shard-0");
+    }
+
+    #[test]
+    fn reassembles_after_failover() {
+        let job = JobRequest::default();
+        let pool = providers(3);
+        let mut plan = BatchPlan::split(&job, &pool, 4, 400, 2).unwrap();
+
+        plan.complete_shard(0, b"first".to_vec()).unwrap();
+        assert!(plan.reassemble().is_none());
+        assert_eq!(plan.milestone_payouts(), vec![("p0".to_string(), 200)]);
+
+        plan.fail_shard(1).unwrap();
+        assert_eq!(plan.reassign_failed(1, &job, &pool).unwrap(), "p2");
+        plan.complete_shard(1, b"second".to_vec()).unwrap();
+
+        assert_eq!(
+            plan.reassemble().unwrap(),
+            vec![b"first".to_vec(), b"second".to_vec()]
+        );
+        assert!(plan.complete_shard(1, vec![]).is_err());
+    }
+
+    #[test]
+    fn releases_milestones_through_the_escrow() {
+        let job = JobRequest::default();
+        let mut plan = BatchPlan::split(&job, &providers(3), 6, 600, 3).unwrap();
+        plan.complete_shard(0, b"a".to_vec()).unwrap();
+        plan.complete_shard(2, b"c".to_vec()).unwrap();
+
+        let escrow_job = H256::from([7u8; 32]);
+        let address_of = |id: &str| match id {
+            "p0" => Some(Address::from([0xa0; 20])),
+            "p2" => Some(Address::from([0xa2; 20])),
+            _ => None,
+        };
+        let calls = plan
+            .milestone_release_calls(escrow_job, address_of)
+            .unwrap();
+        assert_eq!(calls.len(), 2);
+        let (index, data) = &calls[1];
+        assert_eq!(*index, 2);
+        let (selector, mut args) = abi::decode_call(data).unwrap();
+        assert_eq!(selector, job_escrow::RELEASE_MILESTONE);
+        assert_eq!(args.take_fixed::<32>().unwrap(), [7u8; 32]);
+        assert_eq!(args.take_fixed::<20>().unwrap(), [0xa2; 20]);
+        assert_eq!(args.take_u128().unwrap(), 200);
+        args.finish().unwrap();
+
+        plan.mark_released(0).unwrap();
+        assert!(plan.mark_released(0).is_err());
+        assert!(plan.mark_released(1).is_err());
+        assert_eq!(plan.milestone_payouts(), vec![("p2".to_string(), 200)]);
+
+        plan.complete_shard(1, b"b".to_vec()).unwrap();
+        let err = plan
+            .milestone_release_calls(escrow_job, address_of)
+            .unwrap_err();
+        assert!(err.to_string().contains("p1"), "{err}");
+    }
+
+    #[test]
+    fn rejects_empty_batch() {
+        assert!(BatchPlan::split(&JobRequest::default(), &providers(1), 0, 10, 1).is_err());
+    }
+
+    #[test]
+    fn rejects_payment_that_overflows_the_split() {
+        let job = JobRequest::default();
+        let err = BatchPlan::split(&job, &providers(2), 4, u128::MAX, 2).unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+
+        // A single shard takes the whole payment without multiplying it.
+        let plan = BatchPlan::split(&job, &providers(1), 4, u128::MAX, 1).unwrap();
+        assert_eq!(plan.shards[0].payment, u128::MAX);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::monitoring::RouterMetrics;
+use crate::routing::{route_job_with_metrics, JobRequest, ProviderCandidate, RoutingDecision};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct BreakerConfig {
+    /// Consecutive failures or timeouts that trip the breaker.
+    pub failure_threshold: u32,
+    /// Slots a tripped provider is excluded before probing resumes.
+    pub cooldown_slots: u64,
+    /// Jobs allowed in flight to a half-open provider.
+    pub half_open_probes: u32,
+}
+
+impl Default for BreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            cooldown_slots: 120,
+            half_open_probes: 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BreakerState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone)]
+struct ProviderBreaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+    opened_at: u64,
+    probes_in_flight: u32,
+}
+
+impl Default for ProviderBreaker {
+    fn default() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+            opened_at: 0,
+            probes_in_flight: 0,
+        }
+    }
+}
+
+/// Per-provider circuit breakers.
+///
+/// `Closed` routes normally. After `failure_threshold` consecutive failures a
+/// provider goes `Open` and is skipped for `cooldown_slots`. It then turns
+/// `HalfOpen`, where a limited number of probe jobs decide whether it closes
+/// again (probe succeeded) or reopens (probe failed).
+#[derive(Debug, Default)]
+pub struct CircuitBreakers {
+    config: BreakerConfig,
+    breakers: HashMap<String, ProviderBreaker>,
+    transitions: HashMap<(BreakerState, BreakerState), u64>,
+}
+
+impl CircuitBreakers {
+    pub fn new(config: BreakerConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn state(&self, provider_id: &str) -> BreakerState {
+        self.breakers
+            .get(provider_id)
+            .map(|b| b.state)
+            .unwrap_or(BreakerState::Closed)
+    }
+
+    /// Number of `from -> to` transitions observed so far.
+    pub fn transitions(&self, from: BreakerState, to: BreakerState) -> u64 {
+        self.transitions.get(&(from, to)).copied().unwrap_or(0)
+    }
+
+    /// Whether a job may be routed to the provider at `now_slot`.
+    ///
+    /// An open breaker whose cool-down has elapsed moves to half-open here.
+    pub fn allows(&mut self, provider_id: &str, now_slot: u64) -> bool {
+        let config = self.config;
+        let Some(breaker) = self.breakers.get_mut(provider_id) else {
+            return true;
+        };
+        match breaker.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => {
+                if now_slot < breaker.opened_at.saturating_add(config.cooldown_slots) {
+                    return false;
+                }
+                breaker.state = BreakerState::HalfOpen;
+                breaker.probes_in_flight = 0;
+                *self
+                    .transitions
+                    .entry((BreakerState::Open, BreakerState::HalfOpen))
+                    .or_insert(0) += 1;
+                config.half_open_probes > 0
+            }
+            BreakerState::HalfOpen => breaker.probes_in_flight < config.half_open_probes,
+        }
+    }
+
+    /// Note that a job was routed to the provider (counts half-open probes).
+    pub fn on_routed(&mut self, provider_id: &str) {
+        if let Some(breaker) = self.breakers.get_mut(provider_id) {
+            if breaker.state == BreakerState::HalfOpen {
+                breaker.probes_in_flight += 1;
+            }
+        }
+    }
+
+    pub fn record_success(&mut self, provider_id: &str) {
+        let breaker = self.breakers.entry(provider_id.to_string()).or_default();
+        breaker.consecutive_failures = 0;
+        breaker.probes_in_flight = breaker.probes_in_flight.saturating_sub(1);
+        if breaker.state == BreakerState::HalfOpen {
+            breaker.state = BreakerState::Closed;
+            *self
+                .transitions
+                .entry((BreakerState::HalfOpen, BreakerState::Closed))
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Record a failed or timed-out job.
+    pub fn record_failure(&mut self, provider_id: &str, now_slot: u64) {
+        let threshold = self.config.failure_threshold;
+        let breaker = self.breakers.entry(provider_id.to_string()).or_default();
+        breaker.consecutive_failures += 1;
+        breaker.probes_in_flight = breaker.probes_in_flight.saturating_sub(1);
+        let from = breaker.state;
+        let trip = match from {
+            BreakerState::Closed => breaker.consecutive_failures >= threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if trip {
+            breaker.state = BreakerState::Open;
+            breaker.opened_at = now_slot;
+            breaker.probes_in_flight = 0;
+            *self
+                .transitions
+                .entry((from, BreakerState::Open))
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Providers currently allowed to receive work.
+    pub fn filter(
+        &mut self,
+        providers: &[ProviderCandidate],
+        now_slot: u64,
+    ) -> Vec<ProviderCandidate> {
+        providers
+            .iter()
+            .filter(|p| self.allows(&p.provider_id, now_slot))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Route a job, skipping providers whose breaker is open.
+pub fn route_job_with_breakers(
+    job: &JobRequest,
+    providers: &[ProviderCandidate],
+    breakers: &mut CircuitBreakers,
+    now_slot: u64,
+    metrics: &mut RouterMetrics,
+) -> Option<RoutingDecision> {
+    let allowed = breakers.filter(providers, now_slot);
+    let decision = route_job_with_metrics(job, &allowed, metrics)?;
+    breakers.on_routed(&decision.provider_id);
+    Some(decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BreakerConfig {
+        BreakerConfig {
+            failure_threshold: 2,
+            cooldown_slots: 10,
+            half_open_probes: 1,
+        }
+    }
+
+    #[test]
+    fn trips_after_consecutive_failures() {
+        let mut breakers = CircuitBreakers::new(config());
+        breakers.record_failure("p", 0);
+        breakers.record_success("p");
+        breakers.record_failure("p", 1);
+        assert_eq!(breakers.state("p"), BreakerState::Closed);
+
+        breakers.record_failure("p", 2);
+        assert_eq!(breakers.state("p"), BreakerState::Open);
+        assert!(!breakers.allows("p", 11));
+        assert_eq!(
+            breakers.transitions(BreakerState::Closed, BreakerState::Open),
+            1
+        );
+    }
+
+    #[test]
+    fn half_open_probe_closes_or_reopens() {
+        let mut breakers = CircuitBreakers::new(config());
+        breakers.record_failure("p", 0);
+        breakers.record_failure("p", 0);
+
+        assert!(breakers.allows("p", 10));
+        assert_eq!(breakers.state("p"), BreakerState::HalfOpen);
+        breakers.on_routed("p");
+        assert!(!breakers.allows("p", 10), "only one probe in flight");
+
+        breakers.record_failure("p", 12);
+        assert_eq!(breakers.state("p"), BreakerState::Open);
+        assert!(!breakers.allows("p", 21));
+
+        assert!(breakers.allows("p", 22));
+        breakers.on_routed("p");
+        breakers.record_success("p");
+        assert_eq!(breakers.state("p"), BreakerState::Closed);
+        assert_eq!(
+            breakers.transitions(BreakerState::HalfOpen, BreakerState::Open),
+            1
+        );
+        assert_eq!(
+            breakers.transitions(BreakerState::HalfOpen, BreakerState::Closed),
+            1
+        );
+    }
+
+    #[test]
+    fn routing_skips_open_breakers() {
+        let providers = vec![
+            ProviderCandidate {
+                provider_id: "flaky".to_string(),
+                reputation_score: 99,
+                ..ProviderCandidate::default()
+            },
+            ProviderCandidate {
+                provider_id: "steady".to_string(),
+                ..ProviderCandidate::default()
+            },
+        ];
+        let mut breakers = CircuitBreakers::new(config());
+        let mut metrics = RouterMetrics::new(8);
+        let job = JobRequest::default();
+
+        let first =
+            route_job_with_breakers(&job, &providers, &mut breakers, 0, &mut metrics).unwrap();
+        assert_eq!(first.provider_id, "flaky");
+
+        breakers.record_failure("flaky", 1);
+        breakers.record_failure("flaky", 1);
+        let second =
+            route_job_with_breakers(&job, &providers, &mut breakers, 2, &mut metrics).unwrap();
+        assert_eq!(second.provider_id, "steady");
+    }
+}
@@ -0,0 +1,305 @@
+use crate::routing::JobRequest;
+use aether_codecs::abi::{self, job_escrow};
+use aether_rpc_grpc::streaming::FirehoseStream;
+use aether_types::{Address, Block, Transaction, H256, JOB_ESCROW_PROGRAM_ID};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A job-posting transaction observed on chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobPostedEvent {
+    pub slot: u64,
+    pub tx_index: u32,
+    pub requester: Address,
+    pub model_hash: H256,
+    pub input_hash: H256,
+    pub payment: u128,
+    pub deadline_slots: u64,
+    /// Routing request for the job, keyed by its hex job id. Postings carry
+    /// no routing preferences, so those are the defaults.
+    pub job: JobRequest,
+}
+
+impl JobPostedEvent {
+    /// Decode a job escrow `POST_JOB` call; `None` for any other transaction.
+    pub fn decode(slot: u64, tx_index: u32, tx: &Transaction) -> Option<Self> {
+        if tx.program_id != Some(JOB_ESCROW_PROGRAM_ID) {
+            return None;
+        }
+        let (selector, mut args) = abi::decode_call(&tx.data).ok()?;
+        if selector != job_escrow::POST_JOB {
+            return None;
+        }
+        let job_id = H256::from(args.take_fixed::<32>().ok()?);
+        let model_hash = H256::from(args.take_fixed::<32>().ok()?);
+        let input_hash = H256::from(args.take_fixed::<32>().ok()?);
+        let payment = args.take_u128().ok()?;
+        let deadline_slots = args.take_u64().ok()?;
+        args.finish().ok()?;
+
+        Some(JobPostedEvent {
+            slot,
+            tx_index,
+            requester: tx.sender,
+            model_hash,
+            input_hash,
+            payment,
+            deadline_slots,
+            job: JobRequest {
+                job_id: format!("{job_id:?}"),
+                ..JobRequest::default()
+            },
+        })
+    }
+}
+
+/// Position of the last job handed to the router, in `(slot, tx_index)` order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub slot: u64,
+    pub tx_index: u32,
+}
+
+/// Turns firehose blocks into job-posted events, resuming from a checkpoint.
+///
+/// The checkpoint only advances after the handler accepts a job, so a crash
+/// mid-block replays the unhandled remainder on restart and never skips ahead.
+/// Events at or before the checkpoint are dropped, which keeps blocks that are
+/// redelivered after a reconnect from being routed twice.
+#[derive(Debug)]
+pub struct JobEventSubscriber {
+    checkpoint: Option<Checkpoint>,
+    checkpoint_path: Option<PathBuf>,
+}
+
+impl JobEventSubscriber {
+    pub fn in_memory() -> Self {
+        Self {
+            checkpoint: None,
+            checkpoint_path: None,
+        }
+    }
+
+    /// Resume from the checkpoint stored at `path`, if any.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let checkpoint = if path.exists() {
+            let raw = fs::read(&path)
+                .with_context(|| format!("reading router checkpoint {}", path.display()))?;
+            Some(serde_json::from_slice(&raw).context("corrupt router checkpoint")?)
+        } else {
+            None
+        };
+        Ok(Self {
+            checkpoint,
+            checkpoint_path: Some(path),
+        })
+    }
+
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        self.checkpoint
+    }
+
+    /// Feed one block, calling `on_job` for each new job posting in order.
+    ///
+    /// Returns how many jobs were handled. A handler error stops processing
+    /// and leaves the checkpoint at the last successfully handled job.
+    pub fn process_block<F>(&mut self, block: &Block, mut on_job: F) -> Result<usize>
+    where
+        F: FnMut(JobPostedEvent) -> Result<()>,
+    {
+        let slot = block.header.slot;
+        let mut handled = 0;
+        for (tx_index, tx) in block.transactions.iter().enumerate() {
+            let position = Checkpoint {
+                slot,
+                tx_index: tx_index as u32,
+            };
+            if self.checkpoint.is_some_and(|cp| position <= cp) {
+                continue;
+            }
+            // Accept/challenge/cancel calls share the program id but are
+            // not postings.
+            let Some(event) = JobPostedEvent::decode(slot, position.tx_index, tx) else {
+                continue;
+            };
+
+            on_job(event)?;
+            self.commit(position)?;
+            handled += 1;
+        }
+        Ok(handled)
+    }
+
+    /// Drain a firehose stream until it closes, routing each posted job.
+    pub async fn run<F>(&mut self, stream: &mut FirehoseStream, mut on_job: F) -> Result<()>
+    where
+        F: FnMut(JobPostedEvent) -> Result<()>,
+    {
+        while let Some(event) = stream.next().await {
+            self.process_block(&event.block, &mut on_job)?;
+        }
+        Ok(())
+    }
+
+    fn commit(&mut self, position: Checkpoint) -> Result<()> {
+        self.checkpoint = Some(position);
+        if let Some(path) = &self.checkpoint_path {
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, serde_json::to_vec(&position)?)
+                .with_context(|| format!("writing router checkpoint {}", tmp.display()))?;
+            fs::rename(&tmp, path)
+                .with_context(|| format!("committing router checkpoint {}", path.display()))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::{route_job, ProviderCandidate};
+    use aether_rpc_grpc::FirehoseServer;
+    use aether_types::{PublicKey, Signature, VrfProof};
+    use std::collections::HashSet;
+
+    fn tx(program_id: Option<H256>, data: Vec<u8>) -> Transaction {
+        Transaction {
+            nonce: 0,
+            chain_id: 1,
+            sender: Address::from_slice(&[0u8; 20]).unwrap(),
+            sender_pubkey: PublicKey::from_bytes(vec![0u8; 32]),
+            inputs: vec![],
+            outputs: vec![],
+            reads: HashSet::new(),
+            writes: HashSet::new(),
+            program_id,
+            data,
+            gas_limit: 21_000,
+            fee: 1,
+            signature: Signature::from_bytes(vec![]),
+        }
+    }
+
+    fn job_id(tag: u8) -> String {
+        format!("{:?}", H256::from([tag; 32]))
+    }
+
+    fn job_tx(tag: u8) -> Transaction {
+        let data = abi::encode_call(job_escrow::POST_JOB, |w| {
+            w.put_fixed(&[tag; 32])
+                .put_fixed(&[0xaa; 32])
+                .put_fixed(&[0xbb; 32])
+                .put_u128(5_000)
+                .put_u64(100);
+        });
+        tx(Some(JOB_ESCROW_PROGRAM_ID), data)
+    }
+
+    fn block(slot: u64, transactions: Vec<Transaction>) -> Block {
+        Block::new(
+            slot,
+            H256::zero(),
+            Address::from_slice(&[0u8; 20]).unwrap(),
+            VrfProof {
+                output: [0u8; 32],
+                proof: Vec::new(),
+            },
+            transactions,
+        )
+    }
+
+    #[test]
+    fn filters_non_job_transactions() {
+        let mut sub = JobEventSubscriber::in_memory();
+        let b = block(
+            5,
+            vec![
+                tx(None, vec![]),
+                job_tx(1),
+                tx(
+                    Some(JOB_ESCROW_PROGRAM_ID),
+                    abi::encode_call(job_escrow::ACCEPT_JOB, |w| {
+                        w.put_fixed(&[1; 32]);
+                    }),
+                ),
+                tx(
+                    Some(JOB_ESCROW_PROGRAM_ID),
+                    br#"{"job_id":"json"}"#.to_vec(),
+                ),
+                job_tx(2),
+            ],
+        );
+        let mut seen = Vec::new();
+        let handled = sub
+            .process_block(&b, |e| {
+                assert_eq!(e.model_hash, H256::from([0xaa; 32]));
+                assert_eq!(e.input_hash, H256::from([0xbb; 32]));
+                assert_eq!((e.payment, e.deadline_slots), (5_000, 100));
+                seen.push((e.tx_index, e.job.job_id));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(handled, 2);
+        assert_eq!(seen, vec![(1, job_id(1)), (4, job_id(2))]);
+        assert_eq!(
+            sub.checkpoint(),
+            Some(Checkpoint {
+                slot: 5,
+                tx_index: 4
+            })
+        );
+    }
+
+    #[test]
+    fn resumes_without_gaps_or_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("router.checkpoint");
+        let b1 = block(1, vec![job_tx(1), job_tx(2)]);
+        let b2 = block(2, vec![job_tx(3)]);
+
+        let mut sub = JobEventSubscriber::open(&path).unwrap();
+        let err = sub.process_block(&b1, |e| {
+            if e.job.job_id == job_id(2) {
+                anyhow::bail!("router crashed");
+            }
+            Ok(())
+        });
+        assert!(err.is_err());
+
+        let mut restarted = JobEventSubscriber::open(&path).unwrap();
+        let mut seen = Vec::new();
+        for b in [&b1, &b2, &b1] {
+            restarted
+                .process_block(b, |e| {
+                    seen.push(e.job.job_id);
+                    Ok(())
+                })
+                .unwrap();
+        }
+        assert_eq!(seen, vec![job_id(2), job_id(3)]);
+    }
+
+    #[tokio::test]
+    async fn routes_jobs_from_firehose() {
+        let server = FirehoseServer::new(8);
+        let mut stream = server.subscribe();
+        server.publish(block(1, vec![job_tx(9)])).unwrap();
+        drop(server);
+
+        let providers = vec![ProviderCandidate::default()];
+        let mut decisions = Vec::new();
+        let mut sub = JobEventSubscriber::in_memory();
+        sub.run(&mut stream, |e| {
+            decisions.extend(route_job(&e.job, &providers));
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].job_id, job_id(9));
+    }
+}
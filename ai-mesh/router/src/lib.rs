@@ -166,8 +166,17 @@
 // - Timeout events → Slashing triggers
 // ============================================================================
 
+pub mod audit;
+pub mod batch;
+pub mod breaker;
+pub mod events;
+pub mod load;
 pub mod monitoring;
+pub mod pricing;
 pub mod routing;
 pub mod scoring;
+pub mod simulation;
+pub mod zones;
 
-pub use routing::route_job;
+pub use pricing::{quote_job, JobQuote};
+pub use routing::{route_job, RoutingPolicy};
@@ -0,0 +1,154 @@
+use crate::monitoring::RouterMetrics;
+use crate::routing::{rank_providers, JobRequest, ProviderCandidate, RoutingDecision};
+use crate::scoring::load_factor;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Point-in-time load of a single provider.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderUtilization {
+    pub provider_id: String,
+    pub active_jobs: u32,
+    pub max_concurrent_jobs: u32,
+    /// `active_jobs / max_concurrent_jobs`, saturating at 1.0.
+    pub utilization: f64,
+}
+
+/// Router-side view of how many jobs each provider is currently running.
+///
+/// Candidate snapshots from the reputation oracle may lag behind assignments
+/// the router itself just made, so the tracker's counts take precedence when
+/// they are higher than what the candidate advertises.
+#[derive(Debug, Default, Clone)]
+pub struct LoadTracker {
+    active: HashMap<String, u32>,
+}
+
+impl LoadTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn assign(&mut self, provider_id: &str) {
+        *self.active.entry(provider_id.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn release(&mut self, provider_id: &str) {
+        if let Some(count) = self.active.get_mut(provider_id) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.active.remove(provider_id);
+            }
+        }
+    }
+
+    pub fn active_jobs(&self, provider_id: &str) -> u32 {
+        self.active.get(provider_id).copied().unwrap_or(0)
+    }
+
+    /// Copy of `providers` with `active_jobs` raised to the tracked count.
+    pub fn apply(&self, providers: &[ProviderCandidate]) -> Vec<ProviderCandidate> {
+        providers
+            .iter()
+            .map(|p| ProviderCandidate {
+                active_jobs: p.active_jobs.max(self.active_jobs(&p.provider_id)),
+                ..p.clone()
+            })
+            .collect()
+    }
+
+    /// Per-provider utilization, hottest first.
+    pub fn utilization(&self, providers: &[ProviderCandidate]) -> Vec<ProviderUtilization> {
+        let mut snapshot: Vec<ProviderUtilization> = self
+            .apply(providers)
+            .into_iter()
+            .map(|p| ProviderUtilization {
+                utilization: load_factor(&p),
+                provider_id: p.provider_id,
+                active_jobs: p.active_jobs,
+                max_concurrent_jobs: p.max_concurrent_jobs,
+            })
+            .collect();
+        snapshot.sort_by(|a, b| {
+            b.utilization
+                .partial_cmp(&a.utilization)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.provider_id.cmp(&b.provider_id))
+        });
+        snapshot
+    }
+}
+
+/// Route a job using tracked load and count the assignment against the winner.
+pub fn route_job_balanced(
+    job: &JobRequest,
+    providers: &[ProviderCandidate],
+    tracker: &mut LoadTracker,
+    metrics: &mut RouterMetrics,
+) -> Option<RoutingDecision> {
+    let loaded = tracker.apply(providers);
+    let ranked = rank_providers(job, &loaded);
+    let (provider, score) = ranked.first()?;
+
+    tracker.assign(&provider.provider_id);
+    metrics.record(job.job_id.clone(), provider.provider_id.clone(), *score);
+
+    Some(RoutingDecision {
+        job_id: job.job_id.clone(),
+        provider_id: provider.provider_id.clone(),
+        score: *score,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(id: &str, max_concurrent_jobs: u32) -> ProviderCandidate {
+        ProviderCandidate {
+            provider_id: id.to_string(),
+            max_concurrent_jobs,
+            ..ProviderCandidate::default()
+        }
+    }
+
+    #[test]
+    fn spreads_jobs_instead_of_oversubscribing() {
+        let providers = vec![provider("a", 2), provider("b", 2)];
+        let mut tracker = LoadTracker::new();
+        let mut metrics = RouterMetrics::new(16);
+
+        let mut chosen = Vec::new();
+        for i in 0..4 {
+            let job = JobRequest {
+                job_id: format!("job-{i}"),
+                ..JobRequest::default()
+            };
+            let decision = route_job_balanced(&job, &providers, &mut tracker, &mut metrics)
+                .expect("capacity available");
+            chosen.push(decision.provider_id);
+        }
+        chosen.sort();
+        assert_eq!(chosen, vec!["a", "a", "b", "b"]);
+
+        let job = JobRequest::default();
+        assert!(route_job_balanced(&job, &providers, &mut tracker, &mut metrics).is_none());
+
+        tracker.release("b");
+        let decision = route_job_balanced(&job, &providers, &mut tracker, &mut metrics).unwrap();
+        assert_eq!(decision.provider_id, "b");
+    }
+
+    #[test]
+    fn utilization_reports_hottest_first() {
+        let providers = vec![provider("a", 4), provider("b", 2)];
+        let mut tracker = LoadTracker::new();
+        tracker.assign("a");
+        tracker.assign("b");
+
+        let snapshot = tracker.utilization(&providers);
+        assert_eq!(snapshot[0].provider_id, "b");
+        assert!((snapshot[0].utilization - 0.5).abs() < 1e-9);
+        assert!((snapshot[1].utilization - 0.25).abs() < 1e-9);
+    }
+}
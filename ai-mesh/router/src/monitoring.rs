@@ -1,5 +1,6 @@
+use crate::routing::{rank_providers, JobRequest, ProviderCandidate};
 use serde::{Deserialize, Serialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,6 +16,9 @@ pub struct RouterMetrics {
     routed_jobs: u64,
     recent_events: VecDeque<RoutingEvent>,
     max_events: usize,
+    sla_violations: HashMap<String, u64>,
+    rerouted_jobs: u64,
+    refunded_jobs: u64,
 }
 
 impl RouterMetrics {
@@ -23,6 +27,9 @@ impl RouterMetrics {
             routed_jobs: 0,
             recent_events: VecDeque::new(),
             max_events,
+            sla_violations: HashMap::new(),
+            rerouted_jobs: 0,
+            refunded_jobs: 0,
         }
     }
 
@@ -47,6 +54,127 @@ impl RouterMetrics {
     pub fn recent_events(&self) -> &VecDeque<RoutingEvent> {
         &self.recent_events
     }
+
+    pub fn record_sla_violation(&mut self, provider_id: &str) {
+        *self
+            .sla_violations
+            .entry(provider_id.to_string())
+            .or_insert(0) += 1;
+    }
+
+    pub fn sla_violations(&self, provider_id: &str) -> u64 {
+        self.sla_violations.get(provider_id).copied().unwrap_or(0)
+    }
+
+    pub fn total_sla_violations(&self) -> u64 {
+        self.sla_violations.values().sum()
+    }
+
+    pub fn rerouted_jobs(&self) -> u64 {
+        self.rerouted_jobs
+    }
+
+    pub fn refunded_jobs(&self) -> u64 {
+        self.refunded_jobs
+    }
+}
+
+/// Slot duration used to convert a job's SLA into a deadline slot.
+pub const SLOT_MS: u64 = 500;
+
+/// Number of slots a provider gets to deliver a job (at least one).
+pub fn sla_slots(job: &JobRequest) -> u64 {
+    job.max_latency_ms.div_ceil(SLOT_MS).max(1)
+}
+
+/// A job currently held by a provider and watched for its deadline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobAssignment {
+    pub job: JobRequest,
+    pub provider_id: String,
+    pub deadline_slot: u64,
+    /// Providers that previously held this job and missed their deadline.
+    pub failed_providers: Vec<String>,
+}
+
+impl JobAssignment {
+    pub fn new(job: JobRequest, provider_id: String, assigned_slot: u64) -> Self {
+        let deadline_slot = assigned_slot + sla_slots(&job);
+        Self {
+            job,
+            provider_id,
+            deadline_slot,
+            failed_providers: Vec::new(),
+        }
+    }
+}
+
+/// Outcome of a single monitoring pass over an assignment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobProgress {
+    /// Deadline not reached and no VCR yet.
+    Pending,
+    /// VCR was submitted; nothing left to watch.
+    Completed,
+    /// Provider missed the deadline; the job moved to the next ranked provider.
+    /// `failed_provider` should have its bond slashed by the caller.
+    Rerouted {
+        failed_provider: String,
+        new_provider: String,
+        new_deadline_slot: u64,
+    },
+    /// Provider missed the deadline and no candidates remain; refund the requester.
+    Refunded { failed_provider: String },
+}
+
+/// Check an assignment against the current slot.
+///
+/// On a missed deadline the provider is recorded as an SLA violator, excluded
+/// from future attempts on this job, and the job is re-offered to the best
+/// remaining provider with a fresh deadline measured from `current_slot`.
+pub fn monitor_job_progress(
+    assignment: &mut JobAssignment,
+    current_slot: u64,
+    vcr_submitted: bool,
+    providers: &[ProviderCandidate],
+    metrics: &mut RouterMetrics,
+) -> JobProgress {
+    if vcr_submitted {
+        return JobProgress::Completed;
+    }
+    if current_slot <= assignment.deadline_slot {
+        return JobProgress::Pending;
+    }
+
+    let failed_provider = assignment.provider_id.clone();
+    metrics.record_sla_violation(&failed_provider);
+    assignment.failed_providers.push(failed_provider.clone());
+
+    let next = rank_providers(&assignment.job, providers)
+        .into_iter()
+        .find(|(p, _)| !assignment.failed_providers.contains(&p.provider_id));
+
+    match next {
+        Some((provider, score)) => {
+            metrics.record(
+                assignment.job.job_id.clone(),
+                provider.provider_id.clone(),
+                score,
+            );
+            metrics.rerouted_jobs += 1;
+            assignment.provider_id = provider.provider_id.clone();
+            assignment.deadline_slot = current_slot + sla_slots(&assignment.job);
+            JobProgress::Rerouted {
+                failed_provider,
+                new_provider: assignment.provider_id.clone(),
+                new_deadline_slot: assignment.deadline_slot,
+            }
+        }
+        None => {
+            metrics.refunded_jobs += 1;
+            JobProgress::Refunded { failed_provider }
+        }
+    }
 }
 
 fn now_unix_secs() -> u64 {
@@ -71,4 +199,75 @@ mod tests {
         assert_eq!(metrics.recent_events().len(), 2);
         assert_eq!(metrics.recent_events().front().unwrap().job_id, "job2");
     }
+
+    fn provider(id: &str, reputation_score: i32) -> ProviderCandidate {
+        ProviderCandidate {
+            provider_id: id.to_string(),
+            reputation_score,
+            ..ProviderCandidate::default()
+        }
+    }
+
+    #[test]
+    fn pending_until_deadline_then_reroutes() {
+        let job = JobRequest::default();
+        let providers = vec![provider("a", 90), provider("b", 80)];
+        let mut metrics = RouterMetrics::new(16);
+        let mut assignment = JobAssignment::new(job, "a".into(), 100);
+        let deadline = assignment.deadline_slot;
+        assert_eq!(deadline, 104);
+
+        assert_eq!(
+            monitor_job_progress(&mut assignment, deadline, false, &providers, &mut metrics),
+            JobProgress::Pending
+        );
+
+        let progress = monitor_job_progress(
+            &mut assignment,
+            deadline + 1,
+            false,
+            &providers,
+            &mut metrics,
+        );
+        assert_eq!(
+            progress,
+            JobProgress::Rerouted {
+                failed_provider: "a".into(),
+                new_provider: "b".into(),
+                new_deadline_slot: deadline + 1 + 4,
+            }
+        );
+        assert_eq!(assignment.provider_id, "b");
+        assert_eq!(metrics.sla_violations("a"), 1);
+        assert_eq!(metrics.rerouted_jobs(), 1);
+    }
+
+    #[test]
+    fn completed_vcr_stops_monitoring() {
+        let mut metrics = RouterMetrics::new(16);
+        let mut assignment = JobAssignment::new(JobRequest::default(), "a".into(), 0);
+        let progress = monitor_job_progress(&mut assignment, 1_000, true, &[], &mut metrics);
+        assert_eq!(progress, JobProgress::Completed);
+        assert_eq!(metrics.total_sla_violations(), 0);
+    }
+
+    #[test]
+    fn refunds_when_candidates_exhausted() {
+        let providers = vec![provider("a", 90), provider("b", 80)];
+        let mut metrics = RouterMetrics::new(16);
+        let mut assignment = JobAssignment::new(JobRequest::default(), "a".into(), 0);
+
+        let first = monitor_job_progress(&mut assignment, 10, false, &providers, &mut metrics);
+        assert!(matches!(first, JobProgress::Rerouted { .. }));
+
+        let second = monitor_job_progress(&mut assignment, 100, false, &providers, &mut metrics);
+        assert_eq!(
+            second,
+            JobProgress::Refunded {
+                failed_provider: "b".into()
+            }
+        );
+        assert_eq!(metrics.refunded_jobs(), 1);
+        assert_eq!(metrics.total_sla_violations(), 2);
+    }
 }
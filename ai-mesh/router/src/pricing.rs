@@ -0,0 +1,109 @@
+use crate::routing::{rank_providers, JobRequest, ProviderCandidate};
+use serde::{Deserialize, Serialize};
+
+/// Total AIC a provider would charge for the job's estimated units.
+pub fn effective_cost(job: &JobRequest, provider: &ProviderCandidate) -> u64 {
+    provider
+        .price_per_unit
+        .saturating_mul(job.estimated_units.max(1))
+}
+
+/// Upper bound the requester must escrow: `max_price_per_unit * estimated_units`.
+pub fn budget_cap(job: &JobRequest) -> u64 {
+    job.max_price_per_unit
+        .saturating_mul(job.estimated_units.max(1))
+}
+
+/// Pre-posting cost estimate shown to requesters.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobQuote {
+    pub job_id: String,
+    pub estimated_units: u64,
+    /// Number of providers that pass every routing filter, including price.
+    pub eligible_providers: usize,
+    /// Provider the router would pick today and its total cost.
+    pub expected_provider: String,
+    pub expected_cost: u64,
+    /// Cheapest and most expensive eligible total cost.
+    pub min_cost: u64,
+    pub max_cost: u64,
+    pub budget_cap: u64,
+}
+
+/// Estimate what a job would cost if posted now.
+///
+/// Returns `None` when no provider satisfies the job's constraints at its
+/// `max_price_per_unit`, which tells the requester to raise the budget or
+/// relax the SLA before locking funds in escrow.
+pub fn quote_job(job: &JobRequest, providers: &[ProviderCandidate]) -> Option<JobQuote> {
+    let ranked = rank_providers(job, providers);
+    let (expected, _) = ranked.first()?;
+
+    let costs = ranked.iter().map(|(p, _)| effective_cost(job, p));
+    let min_cost = costs.clone().min()?;
+    let max_cost = costs.max()?;
+
+    Some(JobQuote {
+        job_id: job.job_id.clone(),
+        estimated_units: job.estimated_units.max(1),
+        eligible_providers: ranked.len(),
+        expected_provider: expected.provider_id.clone(),
+        expected_cost: effective_cost(job, expected),
+        min_cost,
+        max_cost,
+        budget_cap: budget_cap(job),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(id: &str, reputation_score: i32, price_per_unit: u64) -> ProviderCandidate {
+        ProviderCandidate {
+            provider_id: id.to_string(),
+            reputation_score,
+            price_per_unit,
+            ..ProviderCandidate::default()
+        }
+    }
+
+    #[test]
+    fn quote_excludes_over_budget_providers() {
+        let job = JobRequest {
+            max_price_per_unit: 3_000,
+            estimated_units: 10,
+            ..JobRequest::default()
+        };
+        let providers = vec![
+            provider("premium", 99, 5_000),
+            provider("mid", 80, 2_500),
+            provider("budget", 40, 500),
+        ];
+
+        let quote = quote_job(&job, &providers).unwrap();
+        assert_eq!(quote.eligible_providers, 2);
+        assert_eq!(quote.min_cost, 5_000);
+        assert_eq!(quote.max_cost, 25_000);
+        assert_eq!(quote.budget_cap, 30_000);
+        assert!(quote.expected_cost <= quote.budget_cap);
+    }
+
+    #[test]
+    fn quote_none_when_budget_too_low() {
+        let job = JobRequest {
+            max_price_per_unit: 100,
+            ..JobRequest::default()
+        };
+        assert!(quote_job(&job, &[provider("p", 90, 1_000)]).is_none());
+    }
+
+    #[test]
+    fn effective_cost_saturates() {
+        let job = JobRequest {
+            estimated_units: u64::MAX,
+            ..JobRequest::default()
+        };
+        assert_eq!(effective_cost(&job, &provider("p", 50, 2)), u64::MAX);
+    }
+}
@@ -1,7 +1,36 @@
 use crate::monitoring::RouterMetrics;
+use crate::pricing::effective_cost;
 use crate::scoring::{score_provider, ScoreWeights};
+use crate::zones::in_requester_zone;
 use serde::{Deserialize, Serialize};
 
+/// How a job trades latency against cost when ranking providers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RoutingPolicy {
+    LatencyFirst,
+    CostFirst,
+    #[default]
+    Balanced,
+}
+
+impl RoutingPolicy {
+    pub fn weights(self) -> ScoreWeights {
+        match self {
+            RoutingPolicy::LatencyFirst => ScoreWeights {
+                reputation: 0.3,
+                latency: 0.6,
+                price: 0.1,
+            },
+            RoutingPolicy::CostFirst => ScoreWeights {
+                reputation: 0.3,
+                latency: 0.1,
+                price: 0.6,
+            },
+            RoutingPolicy::Balanced => ScoreWeights::default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobRequest {
     pub job_id: String,
@@ -9,6 +38,21 @@ pub struct JobRequest {
     pub min_reputation: i32,
     pub max_latency_ms: u64,
     pub max_price_per_unit: u64,
+    /// Requester's estimate of billable units (e.g. tokens or inferences).
+    #[serde(default = "default_estimated_units")]
+    pub estimated_units: u64,
+    #[serde(default)]
+    pub policy: RoutingPolicy,
+    /// Latency zone the requester sits in, if known.
+    #[serde(default)]
+    pub requester_zone: Option<String>,
+    /// Interactive jobs prefer providers in the requester's zone.
+    #[serde(default)]
+    pub interactive: bool,
+}
+
+fn default_estimated_units() -> u64 {
+    1
 }
 
 impl Default for JobRequest {
@@ -19,6 +63,10 @@ impl Default for JobRequest {
             min_reputation: 0,
             max_latency_ms: 2_000,
             max_price_per_unit: 100_000,
+            estimated_units: default_estimated_units(),
+            policy: RoutingPolicy::Balanced,
+            requester_zone: None,
+            interactive: false,
         }
     }
 }
@@ -33,6 +81,9 @@ pub struct ProviderCandidate {
     pub available: bool,
     pub active_jobs: u32,
     pub max_concurrent_jobs: u32,
+    /// Latency zone (e.g. "us-east"); empty when unknown.
+    #[serde(default)]
+    pub zone: String,
 }
 
 impl Default for ProviderCandidate {
@@ -46,6 +97,7 @@ impl Default for ProviderCandidate {
             available: true,
             active_jobs: 0,
             max_concurrent_jobs: 10,
+            zone: String::new(),
         }
     }
 }
@@ -68,14 +120,7 @@ pub fn route_job_with_metrics(
     providers: &[ProviderCandidate],
     metrics: &mut RouterMetrics,
 ) -> Option<RoutingDecision> {
-    let mut ranked: Vec<(&ProviderCandidate, f64)> = providers
-        .iter()
-        .filter_map(|provider| {
-            score_provider(job, provider, ScoreWeights::default()).map(|score| (provider, score))
-        })
-        .collect();
-
-    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    let ranked = rank_providers(job, providers);
     let (provider, score) = ranked.first()?;
 
     metrics.record(job.job_id.clone(), provider.provider_id.clone(), *score);
@@ -87,6 +132,30 @@ pub fn route_job_with_metrics(
     })
 }
 
+/// Eligible providers ordered best-first by score, cheaper first on ties.
+///
+/// Scores use the job's policy weights. For interactive jobs with a known
+/// requester zone, in-zone providers are ranked ahead of all others.
+pub fn rank_providers<'a>(
+    job: &JobRequest,
+    providers: &'a [ProviderCandidate],
+) -> Vec<(&'a ProviderCandidate, f64)> {
+    let mut ranked: Vec<(&ProviderCandidate, f64)> = providers
+        .iter()
+        .filter_map(|provider| {
+            score_provider(job, provider, job.policy.weights()).map(|score| (provider, score))
+        })
+        .collect();
+
+    ranked.sort_by(|a, b| {
+        in_requester_zone(job, b.0)
+            .cmp(&in_requester_zone(job, a.0))
+            .then_with(|| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| effective_cost(job, a.0).cmp(&effective_cost(job, b.0)))
+    });
+    ranked
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -100,6 +169,7 @@ mod tests {
             min_reputation: 10,
             max_latency_ms: 1_000,
             max_price_per_unit: 5_000,
+            ..JobRequest::default()
         };
 
         let providers = vec![
@@ -127,6 +197,31 @@ mod tests {
         assert_eq!(decision.provider_id, "best");
         assert_eq!(metrics.routed_jobs(), 1);
     }
+
+    #[test]
+    fn job_request_without_newer_fields_deserializes() {
+        let job: JobRequest = serde_json::from_str(
+            r#"{"job_id":"j","required_capabilities":[],"min_reputation":0,
+                "max_latency_ms":500,"max_price_per_unit":10}"#,
+        )
+        .unwrap();
+        assert_eq!(job.estimated_units, 1);
+        assert_eq!(job.policy, RoutingPolicy::Balanced);
+        assert_eq!(job.requester_zone, None);
+        assert!(!job.interactive);
+    }
+
+    #[test]
+    fn provider_candidate_without_zone_deserializes() {
+        let provider: ProviderCandidate = serde_json::from_str(
+            r#"{"provider_id":"p","capabilities":[],"reputation_score":80,
+                "avg_latency_ms":120,"price_per_unit":900,"available":true,
+                "active_jobs":1,"max_concurrent_jobs":4}"#,
+        )
+        .unwrap();
+        assert_eq!(provider.provider_id, "p");
+        assert_eq!(provider.zone, "");
+    }
 }
 
 #[cfg(test)]
@@ -144,6 +239,7 @@ mod proptests {
             available: true,
             active_jobs: 0,
             max_concurrent_jobs: 10,
+            zone: String::new(),
         }
     }
 
@@ -1,4 +1,5 @@
 use crate::routing::{JobRequest, ProviderCandidate};
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy)]
 pub struct ScoreWeights {
@@ -17,31 +18,53 @@ impl Default for ScoreWeights {
     }
 }
 
-pub fn score_provider(
+/// Why a provider was excluded from routing a job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RejectionReason {
+    Unavailable,
+    ReputationTooLow,
+    LatencyTooHigh,
+    PriceTooHigh,
+    AtCapacity,
+    MissingCapability(String),
+}
+
+/// Check the hard routing filters, returning the first one the provider fails.
+pub fn check_eligibility(
     job: &JobRequest,
     provider: &ProviderCandidate,
-    weights: ScoreWeights,
-) -> Option<f64> {
+) -> Result<(), RejectionReason> {
     if !provider.available {
-        return None;
+        return Err(RejectionReason::Unavailable);
     }
     if provider.reputation_score < job.min_reputation {
-        return None;
+        return Err(RejectionReason::ReputationTooLow);
     }
     if provider.avg_latency_ms > job.max_latency_ms {
-        return None;
+        return Err(RejectionReason::LatencyTooHigh);
     }
     if provider.price_per_unit > job.max_price_per_unit {
-        return None;
+        return Err(RejectionReason::PriceTooHigh);
     }
-
-    if !job
+    if provider.active_jobs >= provider.max_concurrent_jobs {
+        return Err(RejectionReason::AtCapacity);
+    }
+    if let Some(missing) = job
         .required_capabilities
         .iter()
-        .all(|cap| provider.capabilities.contains(cap))
+        .find(|cap| !provider.capabilities.contains(cap))
     {
-        return None;
+        return Err(RejectionReason::MissingCapability(missing.clone()));
     }
+    Ok(())
+}
+
+pub fn score_provider(
+    job: &JobRequest,
+    provider: &ProviderCandidate,
+    weights: ScoreWeights,
+) -> Option<f64> {
+    check_eligibility(job, provider).ok()?;
 
     let normalized_rep = (provider.reputation_score as f64 / 100.0).clamp(0.0, 1.0);
     let latency_ratio = provider.avg_latency_ms as f64 / job.max_latency_ms as f64;
@@ -49,17 +72,24 @@ pub fn score_provider(
     let price_ratio = provider.price_per_unit as f64 / job.max_price_per_unit as f64;
     let normalized_price = (1.0 - price_ratio).clamp(0.0, 1.0);
 
-    let load_ratio = if provider.max_concurrent_jobs == 0 {
-        1.0
-    } else {
-        provider.active_jobs as f64 / provider.max_concurrent_jobs as f64
-    };
-    let load_penalty = (1.0 - 0.5 * load_ratio).clamp(0.0, 1.0);
-
     let weighted = normalized_rep * weights.reputation
         + normalized_latency * weights.latency
         + normalized_price * weights.price;
-    Some(weighted * load_penalty)
+    Some(adjusted_score(weighted, load_factor(provider)))
+}
+
+/// Fraction of declared capacity in use, saturating at 1.0.
+/// A provider declaring zero capacity is treated as fully loaded.
+pub fn load_factor(provider: &ProviderCandidate) -> f64 {
+    if provider.max_concurrent_jobs == 0 {
+        return 1.0;
+    }
+    (provider.active_jobs as f64 / provider.max_concurrent_jobs as f64).clamp(0.0, 1.0)
+}
+
+/// Reduce a base score as load approaches capacity (halved at full load).
+pub fn adjusted_score(base_score: f64, load_factor: f64) -> f64 {
+    base_score * (1.0 - 0.5 * load_factor.clamp(0.0, 1.0))
 }
 
 #[cfg(test)]
@@ -85,6 +115,25 @@ mod tests {
         let high_score = score_provider(&job, &high, ScoreWeights::default()).unwrap();
         assert!(high_score > low_score);
     }
+
+    #[test]
+    fn load_reduces_score_and_full_capacity_excludes() {
+        let job = JobRequest::default();
+        let idle = ProviderCandidate::default();
+        let busy = ProviderCandidate {
+            active_jobs: 8,
+            ..ProviderCandidate::default()
+        };
+        let full = ProviderCandidate {
+            active_jobs: 10,
+            ..ProviderCandidate::default()
+        };
+
+        let idle_score = score_provider(&job, &idle, ScoreWeights::default()).unwrap();
+        let busy_score = score_provider(&job, &busy, ScoreWeights::default()).unwrap();
+        assert!((busy_score - idle_score * 0.6).abs() < 1e-9);
+        assert!(score_provider(&job, &full, ScoreWeights::default()).is_none());
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +162,7 @@ mod proptests {
                 available: true,
                 active_jobs: active.min(max_concurrent),
                 max_concurrent_jobs: max_concurrent,
+                zone: String::new(),
             };
 
             if let Some(score) = score_provider(&job, &provider, ScoreWeights::default()) {
@@ -0,0 +1,271 @@
+use crate::load::{route_job_balanced, LoadTracker};
+use crate::monitoring::RouterMetrics;
+use crate::pricing::effective_cost;
+use crate::routing::{JobRequest, ProviderCandidate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Synthetic provider behaviour layered on top of its advertised candidate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimProvider {
+    pub candidate: ProviderCandidate,
+    /// Probability in `[0, 1]` that an assigned job is never delivered.
+    pub failure_rate: f64,
+    /// Actual latency is `avg_latency_ms ± jitter_ms`, uniformly sampled.
+    pub latency_jitter_ms: u64,
+    /// Signed change applied to `price_per_unit` every step (floored at 1).
+    pub price_drift_per_step: i64,
+}
+
+impl SimProvider {
+    pub fn new(candidate: ProviderCandidate) -> Self {
+        Self {
+            candidate,
+            failure_rate: 0.0,
+            latency_jitter_ms: 0,
+            price_drift_per_step: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimConfig {
+    pub seed: u64,
+    pub steps: u64,
+    pub jobs_per_step: u32,
+    /// Steps a job occupies its provider before capacity is released.
+    pub job_duration_steps: u64,
+    /// Template for generated jobs; `job_id` is replaced per job.
+    pub job_template: JobRequest,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            steps: 100,
+            jobs_per_step: 4,
+            job_duration_steps: 2,
+            job_template: JobRequest::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SimReport {
+    pub jobs_submitted: u64,
+    pub jobs_routed: u64,
+    pub jobs_unroutable: u64,
+    pub jobs_completed: u64,
+    /// Routed jobs that failed or exceeded the job's latency bound.
+    pub sla_violations: u64,
+    /// AIC paid for successfully completed jobs.
+    pub total_cost: u128,
+    pub jobs_per_provider: BTreeMap<String, u64>,
+    /// Jain's fairness index over jobs per provider, in `(0, 1]`.
+    pub fairness_index: f64,
+}
+
+impl SimReport {
+    pub fn sla_violation_rate(&self) -> f64 {
+        if self.jobs_routed == 0 {
+            return 0.0;
+        }
+        self.sla_violations as f64 / self.jobs_routed as f64
+    }
+
+    pub fn avg_cost_per_completed_job(&self) -> f64 {
+        if self.jobs_completed == 0 {
+            return 0.0;
+        }
+        self.total_cost as f64 / self.jobs_completed as f64
+    }
+}
+
+/// Replay a synthetic job stream through the load-balanced router.
+///
+/// The run is fully determined by `config.seed`, so two routing policies can be
+/// compared on exactly the same sequence of failures and latency draws.
+pub fn simulate(config: &SimConfig, providers: &[SimProvider]) -> SimReport {
+    let mut rng = SplitMix64::new(config.seed);
+    let mut sims: Vec<SimProvider> = providers.to_vec();
+    let mut tracker = LoadTracker::new();
+    let mut metrics = RouterMetrics::new(0);
+    let mut in_flight: Vec<(u64, String)> = Vec::new();
+    let mut report = SimReport {
+        jobs_per_provider: sims
+            .iter()
+            .map(|p| (p.candidate.provider_id.clone(), 0))
+            .collect(),
+        ..SimReport::default()
+    };
+
+    for step in 0..config.steps {
+        in_flight.retain(|(release_step, provider_id)| {
+            if *release_step <= step {
+                tracker.release(provider_id);
+                false
+            } else {
+                true
+            }
+        });
+
+        for sim in &mut sims {
+            let price = sim.candidate.price_per_unit as i64 + sim.price_drift_per_step;
+            sim.candidate.price_per_unit = price.max(1) as u64;
+        }
+        let candidates: Vec<ProviderCandidate> = sims.iter().map(|s| s.candidate.clone()).collect();
+
+        for n in 0..config.jobs_per_step {
+            report.jobs_submitted += 1;
+            let job = JobRequest {
+                job_id: format!("sim-{step}-{n}"),
+                ..config.job_template.clone()
+            };
+            let Some(decision) = route_job_balanced(&job, &candidates, &mut tracker, &mut metrics)
+            else {
+                report.jobs_unroutable += 1;
+                continue;
+            };
+            report.jobs_routed += 1;
+            *report
+                .jobs_per_provider
+                .entry(decision.provider_id.clone())
+                .or_insert(0) += 1;
+            in_flight.push((
+                step + config.job_duration_steps.max(1),
+                decision.provider_id.clone(),
+            ));
+
+            let sim = sims
+                .iter()
+                .find(|s| s.candidate.provider_id == decision.provider_id)
+                .expect("routed provider comes from the simulated set");
+            let failed = rng.next_f64() < sim.failure_rate;
+            let jitter = sim.latency_jitter_ms;
+            let latency = sim.candidate.avg_latency_ms + rng.next_below(2 * jitter + 1);
+            let latency = latency.saturating_sub(jitter);
+            if failed || latency > job.max_latency_ms {
+                report.sla_violations += 1;
+            } else {
+                report.jobs_completed += 1;
+                report.total_cost += effective_cost(&job, &sim.candidate) as u128;
+            }
+        }
+    }
+
+    report.fairness_index = jain_index(report.jobs_per_provider.values().copied());
+    report
+}
+
+/// Jain's fairness index: `(Σx)² / (n·Σx²)`; 1.0 when load is perfectly even.
+pub fn jain_index(values: impl IntoIterator<Item = u64>) -> f64 {
+    let (mut n, mut sum, mut sum_sq) = (0u64, 0f64, 0f64);
+    for v in values {
+        n += 1;
+        sum += v as f64;
+        sum_sq += (v as f64) * (v as f64);
+    }
+    if n == 0 || sum_sq == 0.0 {
+        return 1.0;
+    }
+    (sum * sum) / (n as f64 * sum_sq)
+}
+
+/// Small deterministic PRNG so simulation results are stable across platforms
+/// and dependency upgrades.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    fn next_below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        self.next_u64() % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sim(id: &str, failure_rate: f64) -> SimProvider {
+        SimProvider {
+            failure_rate,
+            latency_jitter_ms: 50,
+            ..SimProvider::new(ProviderCandidate {
+                provider_id: id.to_string(),
+                max_concurrent_jobs: 4,
+                ..ProviderCandidate::default()
+            })
+        }
+    }
+
+    #[test]
+    fn same_seed_same_report() {
+        let config = SimConfig {
+            seed: 42,
+            ..SimConfig::default()
+        };
+        let providers = vec![sim("a", 0.1), sim("b", 0.3)];
+        assert_eq!(simulate(&config, &providers), simulate(&config, &providers));
+    }
+
+    #[test]
+    fn reports_violations_and_fairness() {
+        let config = SimConfig {
+            seed: 7,
+            steps: 50,
+            ..SimConfig::default()
+        };
+        let report = simulate(&config, &[sim("good", 0.0), sim("flaky", 1.0)]);
+
+        assert_eq!(report.jobs_submitted, 200);
+        assert_eq!(
+            report.jobs_routed + report.jobs_unroutable,
+            report.jobs_submitted
+        );
+        assert_eq!(report.sla_violations, report.jobs_per_provider["flaky"]);
+        assert_eq!(report.jobs_completed, report.jobs_per_provider["good"]);
+        assert!(report.fairness_index > 0.5 && report.fairness_index <= 1.0);
+        assert_eq!(
+            report.total_cost,
+            report.jobs_completed as u128 * ProviderCandidate::default().price_per_unit as u128
+        );
+    }
+
+    #[test]
+    fn price_drift_eventually_excludes_provider() {
+        let mut rising = sim("rising", 0.0);
+        rising.price_drift_per_step = 20_000;
+        let config = SimConfig {
+            steps: 10,
+            jobs_per_step: 1,
+            ..SimConfig::default()
+        };
+        let report = simulate(&config, &[rising]);
+        assert!(report.jobs_unroutable > 0);
+    }
+
+    #[test]
+    fn jain_index_bounds() {
+        assert_eq!(jain_index([5, 5, 5]), 1.0);
+        assert!((jain_index([9, 0, 0]) - 1.0 / 3.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::routing::{JobRequest, ProviderCandidate};
+use std::collections::HashMap;
+
+/// Weight given to each new RTT sample in the moving average.
+const RTT_EWMA_ALPHA: f64 = 0.2;
+
+/// True when the job is interactive and the provider shares the requester's zone.
+pub fn in_requester_zone(job: &JobRequest, provider: &ProviderCandidate) -> bool {
+    if !job.interactive || provider.zone.is_empty() {
+        return false;
+    }
+    job.requester_zone.as_deref() == Some(provider.zone.as_str())
+}
+
+/// Router-measured round-trip times to providers.
+///
+/// Self-reported `avg_latency_ms` is only a hint; once the router has probed
+/// a provider, the smoothed measurement replaces it for scoring.
+#[derive(Debug, Default, Clone)]
+pub struct RttProbes {
+    rtt_ms: HashMap<String, f64>,
+}
+
+impl RttProbes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fold a probe result into the provider's moving average.
+    pub fn record(&mut self, provider_id: &str, sample_ms: u64) {
+        let sample = sample_ms as f64;
+        self.rtt_ms
+            .entry(provider_id.to_string())
+            .and_modify(|avg| *avg = RTT_EWMA_ALPHA * sample + (1.0 - RTT_EWMA_ALPHA) * *avg)
+            .or_insert(sample);
+    }
+
+    pub fn rtt_ms(&self, provider_id: &str) -> Option<u64> {
+        self.rtt_ms.get(provider_id).map(|avg| avg.round() as u64)
+    }
+
+    /// Copy of `providers` with `avg_latency_ms` replaced by measured RTT where known.
+    pub fn apply(&self, providers: &[ProviderCandidate]) -> Vec<ProviderCandidate> {
+        providers
+            .iter()
+            .map(|p| ProviderCandidate {
+                avg_latency_ms: self.rtt_ms(&p.provider_id).unwrap_or(p.avg_latency_ms),
+                ..p.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::routing::{rank_providers, RoutingPolicy};
+
+    fn provider(id: &str, zone: &str, latency: u64, price: u64) -> ProviderCandidate {
+        ProviderCandidate {
+            provider_id: id.to_string(),
+            zone: zone.to_string(),
+            avg_latency_ms: latency,
+            price_per_unit: price,
+            ..ProviderCandidate::default()
+        }
+    }
+
+    #[test]
+    fn interactive_jobs_prefer_requester_zone() {
+        let providers = vec![
+            provider("far", "eu-west", 50, 500),
+            provider("near", "us-east", 400, 1_500),
+        ];
+        let mut job = JobRequest {
+            requester_zone: Some("us-east".to_string()),
+            interactive: true,
+            ..JobRequest::default()
+        };
+        assert_eq!(rank_providers(&job, &providers)[0].0.provider_id, "near");
+
+        job.interactive = false;
+        assert_eq!(rank_providers(&job, &providers)[0].0.provider_id, "far");
+    }
+
+    #[test]
+    fn policy_changes_winner() {
+        let providers = vec![
+            provider("fast", "", 100, 60_000),
+            provider("cheap", "", 1_500, 1_000),
+        ];
+        let latency_first = JobRequest {
+            policy: RoutingPolicy::LatencyFirst,
+            ..JobRequest::default()
+        };
+        let cost_first = JobRequest {
+            policy: RoutingPolicy::CostFirst,
+            ..JobRequest::default()
+        };
+        assert_eq!(
+            rank_providers(&latency_first, &providers)[0].0.provider_id,
+            "fast"
+        );
+        assert_eq!(
+            rank_providers(&cost_first, &providers)[0].0.provider_id,
+            "cheap"
+        );
+    }
+
+    #[test]
+    fn probes_override_reported_latency() {
+        let mut probes = RttProbes::new();
+        probes.record("p", 100);
+        probes.record("p", 200);
+        assert_eq!(probes.rtt_ms("p"), Some(120));
+
+        let applied = probes.apply(&[provider("p", "", 5, 1_000), provider("q", "", 7, 1_000)]);
+        assert_eq!(applied[0].avg_latency_ms, 120);
+        assert_eq!(applied[1].avg_latency_ms, 7);
+    }
+}
@@ -0,0 +1,184 @@
+use aether_ai_worker::sandbox::SandboxConfig;
+use aether_ai_worker::WorkerConfig;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Settings the command line can override.
+#[derive(Debug, Default, Clone)]
+pub struct Overrides {
+    pub worker_id: Option<String>,
+    pub tee_mode: Option<String>,
+    pub router_endpoint: Option<String>,
+    pub listen: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RuntimeConfig {
+    pub worker: WorkerConfig,
+    /// Base URL of the job router, e.g. `http://router:7070`.
+    pub router_endpoint: String,
+    /// Address serving `/health`, `/metrics` and `/results/:job_id`.
+    pub listen: SocketAddr,
+    /// `{hash}` URL templates models are fetched from on a cache miss.
+    pub model_sources: Vec<String>,
+    pub journal_path: PathBuf,
+    /// How long SIGTERM waits for in-flight jobs before exiting anyway.
+    pub drain_timeout: Duration,
+    /// Limits for the per-inference child process; `None` runs in-process.
+    pub sandbox: Option<SandboxConfig>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    /// Hex worker id as registered with the coordinator.
+    worker_id: Option<String>,
+    tee_mode: Option<String>,
+    router_endpoint: Option<String>,
+    listen: Option<String>,
+    data_dir: Option<String>,
+    #[serde(default)]
+    model_sources: Vec<String>,
+    max_concurrent_jobs: Option<usize>,
+    drain_timeout_secs: Option<u64>,
+    sandbox: Option<bool>,
+    sandbox_memory_mb: Option<u64>,
+    sandbox_cpu_secs: Option<u64>,
+    inference_timeout_secs: Option<u64>,
+}
+
+pub fn load_config(path: Option<&Path>, overrides: Overrides) -> Result<RuntimeConfig> {
+    let raw = match path {
+        Some(path) => {
+            let contents = fs::read_to_string(path)
+                .with_context(|| format!("failed to read config file {}", path.display()))?;
+            toml::from_str::<RawConfig>(&contents)
+                .with_context(|| format!("failed to parse config file {}", path.display()))?
+        }
+        None => RawConfig::default(),
+    };
+    resolve(raw, overrides)
+}
+
+fn resolve(raw: RawConfig, overrides: Overrides) -> Result<RuntimeConfig> {
+    let Some(worker_id) = overrides.worker_id.or(raw.worker_id) else {
+        bail!("worker_id (hex) must be set in the config file or with --worker-id");
+    };
+    let worker_id =
+        hex::decode(worker_id.trim_start_matches("0x")).context("worker_id must be hex encoded")?;
+    if worker_id.is_empty() {
+        bail!("worker_id must not be empty");
+    }
+
+    let router_endpoint = overrides
+        .router_endpoint
+        .or(raw.router_endpoint)
+        .unwrap_or_else(|| "http://127.0.0.1:7070".to_string());
+    if !router_endpoint.starts_with("http://") {
+        bail!("router endpoint must be an http:// URL: {router_endpoint}");
+    }
+    let listen = overrides
+        .listen
+        .or(raw.listen)
+        .unwrap_or_else(|| "0.0.0.0:9400".to_string());
+    let listen = listen
+        .parse()
+        .with_context(|| format!("invalid listen address {listen}"))?;
+
+    let sandbox = raw.sandbox.unwrap_or(true).then(|| {
+        let defaults = SandboxConfig::default();
+        SandboxConfig {
+            memory_limit_bytes: raw
+                .sandbox_memory_mb
+                .map_or(defaults.memory_limit_bytes, |mb| mb * 1024 * 1024),
+            cpu_time_secs: raw.sandbox_cpu_secs.unwrap_or(defaults.cpu_time_secs),
+            wall_timeout: raw
+                .inference_timeout_secs
+                .map_or(defaults.wall_timeout, Duration::from_secs),
+        }
+    });
+
+    let data_dir = PathBuf::from(raw.data_dir.unwrap_or_else(|| "./data/ai-worker".into()));
+    Ok(RuntimeConfig {
+        worker: WorkerConfig {
+            worker_id,
+            tee_type: overrides
+                .tee_mode
+                .or(raw.tee_mode)
+                .unwrap_or_else(|| "sev-snp".to_string()),
+            model_cache_dir: data_dir.join("models").display().to_string(),
+            max_concurrent_jobs: raw.max_concurrent_jobs.unwrap_or(4),
+        },
+        router_endpoint: router_endpoint.trim_end_matches('/').to_string(),
+        listen,
+        model_sources: raw.model_sources,
+        journal_path: data_dir.join("jobs.journal"),
+        drain_timeout: Duration::from_secs(raw.drain_timeout_secs.unwrap_or(60)),
+        sandbox,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_values_and_overrides() {
+        let raw: RawConfig = toml::from_str(
+            r#"
+            worker_id = "0xabcd"
+            tee_mode = "tdx"
+            router_endpoint = "http://router:7070/"
+            data_dir = "/var/lib/aether"
+            model_sources = ["http://127.0.0.1:8080/ipfs/{hash}"]
+            sandbox_memory_mb = 512
+            inference_timeout_secs = 5
+            "#,
+        )
+        .unwrap();
+        let config = resolve(
+            raw,
+            Overrides {
+                tee_mode: Some("simulation".into()),
+                ..Overrides::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(config.worker.worker_id, vec![0xab, 0xcd]);
+        assert_eq!(config.worker.tee_type, "simulation");
+        assert_eq!(config.router_endpoint, "http://router:7070");
+        assert_eq!(config.worker.model_cache_dir, "/var/lib/aether/models");
+        assert_eq!(
+            config.journal_path,
+            PathBuf::from("/var/lib/aether/jobs.journal")
+        );
+        assert_eq!(config.listen.port(), 9400);
+        let sandbox = config.sandbox.unwrap();
+        assert_eq!(sandbox.memory_limit_bytes, 512 * 1024 * 1024);
+        assert_eq!(sandbox.wall_timeout, Duration::from_secs(5));
+
+        let raw: RawConfig = toml::from_str("worker_id = \"01\"\nsandbox = false").unwrap();
+        assert!(resolve(raw, Overrides::default())
+            .unwrap()
+            .sandbox
+            .is_none());
+    }
+
+    #[test]
+    fn rejects_incomplete_config() {
+        assert!(resolve(RawConfig::default(), Overrides::default()).is_err());
+        assert!(toml::from_str::<RawConfig>("worker_id = \"01\"\nbogus = 1").is_err());
+
+        let raw = RawConfig {
+            worker_id: Some("01".into()),
+            router_endpoint: Some("https://router".into()),
+            ..RawConfig::default()
+        };
+        assert!(resolve(raw, Overrides::default()).is_err());
+    }
+}
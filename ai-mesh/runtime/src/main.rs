@@ -166,6 +166,7 @@
 //
 // SECURITY:
 // - Provider key stored in TEE-protected memory
+// - Each inference runs in a seccomp-filtered, rlimited child process
 // - Trace data encrypted at rest
 // - Network communication over TLS
 // - Attestation quotes bind to specific execution
@@ -177,11 +178,184 @@
 // - Traces → Stored for challenge period
 // ============================================================================
 
-fn main() {
-    println!("Aether AI Runtime - Attested Worker");
-    println!("Version: 0.1.0");
-    println!("TEE: Initializing...");
+mod config;
+mod metrics;
+mod router;
+mod server;
 
-    // Actual implementation would initialize TEE, connect to router,
-    // and process jobs in an event loop
+use aether_ai_worker::benchmark::BenchmarkConfig;
+use aether_ai_worker::cache::HttpSource;
+use aether_ai_worker::journal::{JobJournal, JournalConfig};
+use aether_ai_worker::sandbox::{self, SandboxedEngine};
+use aether_ai_worker::tee::backend_from_config;
+use aether_ai_worker::AiWorker;
+use anyhow::{Context, Result};
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::{load_config, Overrides};
+use crate::metrics::RuntimeMetrics;
+use crate::router::RouterClient;
+use crate::server::Health;
+
+#[derive(Parser, Debug)]
+#[command(name = "aether-ai-runtime")]
+#[command(version)]
+#[command(about = "Attested AI worker daemon")]
+struct Cli {
+    /// Worker configuration file (TOML)
+    #[arg(long, short)]
+    config: Option<PathBuf>,
+
+    /// Hex worker id, overriding the config file
+    #[arg(long)]
+    worker_id: Option<String>,
+
+    /// TEE backend: sev-snp, tdx, nitro or simulation
+    #[arg(long)]
+    tee_mode: Option<String>,
+
+    /// Job router base URL, e.g. http://router:7070
+    #[arg(long)]
+    router: Option<String>,
+
+    /// Listen address for /health, /metrics and /results
+    #[arg(long)]
+    listen: Option<String>,
+}
+
+/// Wait for a SIGTERM signal (used for graceful shutdown in containers).
+#[cfg(unix)]
+async fn sigterm_recv() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sig = signal(SignalKind::terminate()).expect("failed to register SIGTERM handler");
+    sig.recv().await;
+}
+
+#[cfg(not(unix))]
+async fn sigterm_recv() {
+    std::future::pending::<()>().await;
+}
+
+fn main() -> Result<()> {
+    // Inference children re-execute this binary; divert them before any
+    // threads or the async runtime exist.
+    sandbox::run_child_if_requested();
+    tokio::runtime::Runtime::new()?.block_on(run())
+}
+
+async fn run() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+    let config = load_config(
+        cli.config.as_deref(),
+        Overrides {
+            worker_id: cli.worker_id,
+            tee_mode: cli.tee_mode,
+            router_endpoint: cli.router,
+            listen: cli.listen,
+        },
+    )?;
+    tracing::info!(
+        "Aether AI Runtime v{} - worker {}",
+        env!("CARGO_PKG_VERSION"),
+        hex::encode(&config.worker.worker_id)
+    );
+
+    // Refuse to start without a working TEE rather than fail every job.
+    let backend = backend_from_config(&config.worker.tee_type)?;
+    let measurement = backend
+        .measurement()
+        .with_context(|| format!("initializing {} TEE", config.worker.tee_type))?;
+    tracing::info!(
+        "TEE: {:?}, measurement {}",
+        backend.tee_type(),
+        hex::encode(measurement)
+    );
+
+    let metrics = Arc::new(RuntimeMetrics::new()?);
+    let health = Arc::new(Health::new(
+        &config.worker.worker_id,
+        &config.worker.tee_type,
+    ));
+    let router = || {
+        RouterClient::new(
+            &config.router_endpoint,
+            &config.worker.worker_id,
+            metrics.clone(),
+        )
+    };
+
+    let mut worker = match &config.sandbox {
+        Some(limits) => AiWorker::with_engine(
+            config.worker.clone(),
+            Box::new(SandboxedEngine::current_exe(limits.clone())?),
+        ),
+        None => {
+            tracing::warn!("inference sandbox disabled; models run inside the worker process");
+            AiWorker::new(config.worker.clone())
+        }
+    }
+    .with_tee_backend(backend)
+    .with_journal(JobJournal::open(JournalConfig::new(&config.journal_path))?)
+    .with_capability_reporter(Box::new(router()?), BenchmarkConfig::default());
+    for source in &config.model_sources {
+        worker.add_model_source(Box::new(HttpSource::new(source.as_str())?));
+    }
+    let worker = Arc::new(worker);
+
+    let listener = tokio::net::TcpListener::bind(config.listen)
+        .await
+        .with_context(|| format!("binding {}", config.listen))?;
+    let app = server::app(health.clone(), metrics.clone(), worker.result_store());
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            tracing::error!("HTTP server failed: {e}");
+        }
+    });
+    tracing::info!("Health, metrics and results on http://{}", config.listen);
+    tracing::info!("Polling jobs from {}", config.router_endpoint);
+
+    let mut job_loop = {
+        let worker = worker.clone();
+        let (mut source, mut sink) = (router()?, router()?);
+        tokio::spawn(async move { worker.start(&mut source, &mut sink).await })
+    };
+
+    tokio::select! {
+        res = &mut job_loop => {
+            // The router source never closes, so the loop only ends on error.
+            let stats = res.context("job loop panicked")??;
+            tracing::warn!("Job loop exited: {stats:?}");
+            return Ok(());
+        }
+        _ = tokio::signal::ctrl_c() => {
+            tracing::info!("Received SIGINT, draining in-flight jobs...");
+        }
+        _ = sigterm_recv() => {
+            tracing::info!("Received SIGTERM, draining in-flight jobs...");
+        }
+    }
+
+    health.set_draining();
+    metrics.draining.set(1);
+    worker.stop();
+    match tokio::time::timeout(config.drain_timeout, job_loop).await {
+        Ok(res) => {
+            let stats = res.context("job loop panicked")??;
+            tracing::info!("Drained cleanly: {stats:?}");
+        }
+        Err(_) => {
+            // Unfinished jobs stay journaled and are resumed on restart.
+            tracing::warn!(
+                "Drain timed out after {:?}; in-flight jobs remain journaled",
+                config.drain_timeout
+            );
+        }
+    }
+    Ok(())
 }
@@ -0,0 +1,65 @@
+use anyhow::Result;
+use prometheus::{Encoder, IntCounter, IntGauge, Registry, TextEncoder};
+
+/// Worker daemon metrics, kept in a private registry so the runtime does not
+/// export unrelated process-global collectors.
+pub struct RuntimeMetrics {
+    registry: Registry,
+    pub jobs_accepted: IntCounter,
+    pub jobs_succeeded: IntCounter,
+    pub jobs_failed: IntCounter,
+    pub router_errors: IntCounter,
+    pub gas_per_sec: IntGauge,
+    pub draining: IntGauge,
+}
+
+impl RuntimeMetrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str| -> Result<IntCounter> {
+            let counter = IntCounter::new(name, help)?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok(counter)
+        };
+        let gauge = |name: &str, help: &str| -> Result<IntGauge> {
+            let gauge = IntGauge::new(name, help)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok(gauge)
+        };
+        Ok(Self {
+            jobs_accepted: counter(
+                "aether_ai_worker_jobs_accepted",
+                "Jobs pulled from the router",
+            )?,
+            jobs_succeeded: counter(
+                "aether_ai_worker_jobs_succeeded",
+                "Jobs completed and submitted",
+            )?,
+            jobs_failed: counter(
+                "aether_ai_worker_jobs_failed",
+                "Jobs that failed or were abandoned",
+            )?,
+            router_errors: counter(
+                "aether_ai_worker_router_errors",
+                "Failed requests to the job router",
+            )?,
+            gas_per_sec: gauge(
+                "aether_ai_worker_gas_per_sec",
+                "Throughput measured by the last benchmark",
+            )?,
+            draining: gauge(
+                "aether_ai_worker_draining",
+                "1 while the worker drains after SIGTERM",
+            )?,
+            registry,
+        })
+    }
+
+    /// Prometheus text exposition of every metric.
+    pub fn render(&self) -> Result<(String, Vec<u8>)> {
+        let encoder = TextEncoder::new();
+        let mut buffer = Vec::new();
+        encoder.encode(&self.registry.gather(), &mut buffer)?;
+        Ok((encoder.format_type().to_string(), buffer))
+    }
+}
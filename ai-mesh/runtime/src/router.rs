@@ -0,0 +1,328 @@
+use crate::metrics::RuntimeMetrics;
+use aether_ai_worker::benchmark::CapabilityReport;
+use aether_ai_worker::engine::OpGas;
+use aether_ai_worker::trace::LayerCommitment;
+use aether_ai_worker::{CapabilityReporter, InferenceJob, JobOutcome, JobSource, ResultSink};
+use aether_crypto_kzg::ChunkedCommitment;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const SUBMIT_ATTEMPTS: u32 = 3;
+
+/// Worker-side client for the job router's HTTP API:
+///
+///   GET  /workers/<id>/jobs?max=N     -> 200 [InferenceJob] | 204
+///   GET  /workers/<id>/jobs/<job_id>  -> 200 InferenceJob | 404
+///   POST /workers/<id>/results        <- ResultMessage
+///   POST /workers/<id>/capabilities   <- CapabilityReport
+pub struct RouterClient {
+    http: reqwest::Client,
+    endpoint: String,
+    worker_id: String,
+    metrics: Arc<RuntimeMetrics>,
+}
+
+/// Wire form of a `JobOutcome`; bytes are hex encoded.
+#[derive(Debug, Serialize)]
+pub struct ResultMessage {
+    pub job_id: String,
+    pub error: Option<String>,
+    pub output: String,
+    pub output_hash: String,
+    pub gas_used: u64,
+    pub gas_breakdown: Vec<OpGas>,
+    pub tee_attestation: String,
+    pub trace_commitments: Vec<LayerCommitment>,
+    pub trace_root: Option<ChunkedCommitment>,
+}
+
+impl From<&JobOutcome> for ResultMessage {
+    fn from(outcome: &JobOutcome) -> Self {
+        let job_id = hex::encode(&outcome.job_id);
+        match &outcome.result {
+            Ok(result) => ResultMessage {
+                job_id,
+                error: None,
+                output: hex::encode(&result.output_data),
+                output_hash: hex::encode(result.output_hash),
+                gas_used: result.gas_used,
+                gas_breakdown: result.gas_breakdown.clone(),
+                tee_attestation: hex::encode(&result.tee_attestation),
+                trace_commitments: result.trace_commitments.clone(),
+                trace_root: result.trace_root.clone(),
+            },
+            Err(error) => ResultMessage {
+                job_id,
+                error: Some(error.clone()),
+                output: String::new(),
+                output_hash: String::new(),
+                gas_used: 0,
+                gas_breakdown: Vec::new(),
+                tee_attestation: String::new(),
+                trace_commitments: Vec::new(),
+                trace_root: None,
+            },
+        }
+    }
+}
+
+impl RouterClient {
+    pub fn new(endpoint: &str, worker_id: &[u8], metrics: Arc<RuntimeMetrics>) -> Result<Self> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .context("building router HTTP client")?;
+        Ok(Self {
+            http,
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            worker_id: hex::encode(worker_id),
+            metrics,
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/workers/{}/{path}", self.endpoint, self.worker_id)
+    }
+
+    async fn post_json<T: Serialize>(&self, path: &str, body: &T) -> Result<()> {
+        let url = self.url(path);
+        let response = self
+            .http
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            .with_context(|| format!("POST {url}"))?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            bail!("POST {url} failed with {status}: {body}");
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobSource for RouterClient {
+    async fn next_jobs(&mut self, max: usize) -> Result<Option<Vec<InferenceJob>>> {
+        let url = self.url(&format!("jobs?max={max}"));
+        // An unreachable router is not fatal: report no work and poll again.
+        let jobs = match self.http.get(&url).send().await {
+            Ok(response) if response.status() == StatusCode::OK => {
+                response.json().await.unwrap_or_else(|e| {
+                    self.metrics.router_errors.inc();
+                    tracing::warn!("malformed job batch from router: {e}");
+                    Vec::new()
+                })
+            }
+            Ok(response) if response.status() == StatusCode::NO_CONTENT => Vec::new(),
+            Ok(response) => {
+                let status = response.status();
+                self.metrics.router_errors.inc();
+                tracing::warn!("GET {url} returned {status}");
+                Vec::new()
+            }
+            Err(e) => {
+                self.metrics.router_errors.inc();
+                tracing::warn!("router poll failed: {e:#}");
+                Vec::new()
+            }
+        };
+        self.metrics.jobs_accepted.inc_by(jobs.len() as u64);
+        Ok(Some(jobs))
+    }
+
+    async fn refetch(&mut self, job_id: &[u8]) -> Result<Option<InferenceJob>> {
+        let url = self.url(&format!("jobs/{}", hex::encode(job_id)));
+        let response = self.http.get(&url).send().await.map_err(|e| {
+            self.metrics.router_errors.inc();
+            anyhow::Error::new(e).context(format!("GET {url}"))
+        })?;
+        match response.status() {
+            StatusCode::OK => {
+                let job = response
+                    .json()
+                    .await
+                    .with_context(|| format!("malformed job from GET {url}"))?;
+                Ok(Some(job))
+            }
+            // No longer assigned to us: the worker abandons it.
+            StatusCode::NOT_FOUND | StatusCode::GONE => Ok(None),
+            status => {
+                self.metrics.router_errors.inc();
+                bail!("GET {url} returned {status}");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ResultSink for RouterClient {
+    async fn submit(&mut self, outcome: JobOutcome) -> Result<()> {
+        let message = ResultMessage::from(&outcome);
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match self.post_json("results", &message).await {
+                Ok(()) => {
+                    // Counted once the router has it: the worker resubmits
+                    // outcomes this returns an error for.
+                    if outcome.result.is_ok() {
+                        self.metrics.jobs_succeeded.inc();
+                    } else {
+                        self.metrics.jobs_failed.inc();
+                    }
+                    return Ok(());
+                }
+                Err(e) if attempt < SUBMIT_ATTEMPTS => {
+                    self.metrics.router_errors.inc();
+                    tracing::warn!("result submission failed (attempt {attempt}): {e:#}");
+                    tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl CapabilityReporter for RouterClient {
+    async fn report(&mut self, report: CapabilityReport) -> Result<()> {
+        self.metrics.gas_per_sec.set(report.gas_per_sec as i64);
+        self.post_json("capabilities", &report).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve one canned response and hand back the request it received.
+    async fn serve_once(response: &'static str) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let handle = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = vec![0u8; 64 * 1024];
+            while !is_complete(&request) {
+                let n = socket.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_string()
+        });
+        (endpoint, handle)
+    }
+
+    /// Whether `request` holds its whole head and `Content-Length` body.
+    fn is_complete(request: &[u8]) -> bool {
+        let Some(head_end) = request.windows(4).position(|w| w == b"\r\n\r\n") else {
+            return false;
+        };
+        let head = String::from_utf8_lossy(&request[..head_end]).to_ascii_lowercase();
+        let body_len = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|len| len.trim().parse().ok())
+            .unwrap_or(0);
+        request.len() >= head_end + 4 + body_len
+    }
+
+    fn client(endpoint: &str) -> RouterClient {
+        RouterClient::new(endpoint, &[0xab], Arc::new(RuntimeMetrics::new().unwrap())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn polls_jobs_from_router() {
+        let (endpoint, request) = serve_once(
+            "HTTP/1.0 200 OK\r\n\r\n[{\"job_id\":[1],\"model_hash\":[2],\"input_data\":[3],\
+             \"gas_limit\":10,\"seed\":4,\"requester_key\":null}]",
+        )
+        .await;
+        let jobs = client(&endpoint).next_jobs(2).await.unwrap().unwrap();
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].seed, 4);
+        assert!(request
+            .await
+            .unwrap()
+            .starts_with("GET /workers/ab/jobs?max=2 HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn unreachable_router_yields_no_jobs() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let mut client = client(&endpoint);
+        assert!(client.next_jobs(1).await.unwrap().unwrap().is_empty());
+        assert_eq!(client.metrics.router_errors.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn refetches_a_job_by_id() {
+        let (endpoint, request) = serve_once(
+            "HTTP/1.0 200 OK\r\n\r\n{\"job_id\":[1],\"model_hash\":[2],\"input_data\":[3],\
+             \"gas_limit\":10,\"seed\":4,\"requester_key\":null}",
+        )
+        .await;
+        let job = client(&endpoint).refetch(&[1]).await.unwrap().unwrap();
+        assert_eq!(job.job_id, vec![1]);
+        assert!(request
+            .await
+            .unwrap()
+            .starts_with("GET /workers/ab/jobs/01 HTTP/1.1"));
+
+        let (endpoint, _) = serve_once("HTTP/1.0 404 Not Found\r\n\r\n").await;
+        assert!(client(&endpoint).refetch(&[1]).await.unwrap().is_none());
+        let (endpoint, _) = serve_once("HTTP/1.0 500 Internal Server Error\r\n\r\n").await;
+        assert!(client(&endpoint).refetch(&[1]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn counts_outcomes_only_once_delivered() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        let mut client = client(&endpoint);
+        let outcome = JobOutcome {
+            job_id: vec![7],
+            result: Err("out of gas".to_string()),
+        };
+        tokio::time::pause();
+        assert!(client.submit(outcome.clone()).await.is_err());
+        assert_eq!(client.metrics.jobs_failed.get(), 0);
+        tokio::time::resume();
+
+        let (endpoint, _) = serve_once("HTTP/1.0 204 No Content\r\n\r\n").await;
+        client.endpoint = endpoint;
+        client.submit(outcome).await.unwrap();
+        assert_eq!(client.metrics.jobs_failed.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn submits_failures_as_errors() {
+        let (endpoint, request) = serve_once("HTTP/1.0 204 No Content\r\n\r\n").await;
+        client(&endpoint)
+            .submit(JobOutcome {
+                job_id: vec![7],
+                result: Err("out of gas".to_string()),
+            })
+            .await
+            .unwrap();
+        let request = request.await.unwrap();
+        assert!(request.starts_with("POST /workers/ab/results HTTP/1.1"));
+        assert!(request.contains("\"job_id\":\"07\""));
+        assert!(request.contains("\"error\":\"out of gas\""));
+    }
+}
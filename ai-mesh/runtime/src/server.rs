@@ -0,0 +1,114 @@
+use crate::metrics::RuntimeMetrics;
+use aether_ai_worker::output::{results_app, ResultStore};
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Liveness state shared between the job loop and the HTTP server.
+pub struct Health {
+    pub worker_id: String,
+    pub tee_type: String,
+    draining: AtomicBool,
+}
+
+impl Health {
+    pub fn new(worker_id: &[u8], tee_type: &str) -> Self {
+        Self {
+            worker_id: hex::encode(worker_id),
+            tee_type: tee_type.to_string(),
+            draining: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_draining(&self) {
+        self.draining.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    health: Arc<Health>,
+    metrics: Arc<RuntimeMetrics>,
+}
+
+#[derive(Debug, Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    worker_id: String,
+    tee_type: String,
+}
+
+/// `GET /health` is 200 while accepting work and 503 while draining, so load
+/// balancers stop routing to a worker that is shutting down.
+async fn handle_health(State(state): State<AppState>) -> impl IntoResponse {
+    let draining = state.health.is_draining();
+    let status = if draining {
+        StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
+    };
+    let body = HealthResponse {
+        status: if draining { "draining" } else { "ok" },
+        worker_id: state.health.worker_id.clone(),
+        tee_type: state.health.tee_type.clone(),
+    };
+    (status, Json(body))
+}
+
+async fn handle_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    match state.metrics.render() {
+        Ok((content_type, body)) => {
+            (StatusCode::OK, [(header::CONTENT_TYPE, content_type)], body).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+    }
+}
+
+/// `/health`, `/metrics` and the worker's `/results/:job_id` on one listener.
+pub fn app(health: Arc<Health>, metrics: Arc<RuntimeMetrics>, results: ResultStore) -> Router {
+    Router::new()
+        .route("/health", get(handle_health))
+        .route("/metrics", get(handle_metrics))
+        .with_state(AppState { health, metrics })
+        .merge(results_app(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn health_reports_draining() {
+        let health = Arc::new(Health::new(&[0xab], "simulation"));
+        let metrics = Arc::new(RuntimeMetrics::new().unwrap());
+        metrics.jobs_accepted.inc();
+        let app = app(health.clone(), metrics, ResultStore::default());
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app.clone().oneshot(get("/metrics")).await.unwrap();
+        let body = to_bytes(response.into_body(), 64 * 1024).await.unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("aether_ai_worker_jobs_accepted 1"));
+
+        health.set_draining();
+        let response = app.clone().oneshot(get("/health")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let response = app.oneshot(get("/results/abcd")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
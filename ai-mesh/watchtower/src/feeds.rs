@@ -0,0 +1,125 @@
+use aether_codecs::decode_bincode;
+use aether_rpc_grpc::streaming::FirehoseStream;
+use aether_sdk::AetherClient;
+use aether_verifiers_kzg::{WatchtowerEvent, WatchtowerMessage};
+use anyhow::Result;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+/// Decode a `TOPIC_VCR` gossip payload into a watchtower event. Malformed
+/// payloads and other towers' challenges are `None`.
+pub fn gossip_event(data: &[u8]) -> Option<WatchtowerEvent> {
+    decode_bincode::<WatchtowerMessage>(data).ok()?.into_event()
+}
+
+/// Tick the tower with each block's slot, for towers running inside a node.
+/// Returns when the stream closes or the tower stops listening.
+pub async fn forward_firehose(
+    stream: &mut FirehoseStream,
+    events: &Sender<WatchtowerEvent>,
+) -> Result<()> {
+    while let Some(event) = stream.next().await {
+        if events
+            .send(WatchtowerEvent::Slot(event.block.header.slot))
+            .is_err()
+        {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Tick the tower with a node's slot number, polled every `interval`, for
+/// towers running on their own. Returns once the tower stops listening.
+pub async fn forward_slots(
+    client: &AetherClient,
+    interval: Duration,
+    events: &Sender<WatchtowerEvent>,
+) -> Result<()> {
+    let mut last = None;
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        ticker.tick().await;
+        let slot = match client.get_block_number().await {
+            Ok(slot) => slot,
+            Err(e) => {
+                tracing::warn!("polling slot from {}: {e}", client.endpoint());
+                continue;
+            }
+        };
+        if last.is_some_and(|last| slot <= last) {
+            continue;
+        }
+        last = Some(slot);
+        if events.send(WatchtowerEvent::Slot(slot)).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_codecs::encode_bincode;
+    use aether_rpc_grpc::FirehoseServer;
+    use aether_types::{Address, Block, VrfProof, H256};
+    use aether_verifiers_kzg::{IssuedChallenge, KzgChallenge, KzgOpeningResponse};
+    use std::sync::mpsc::channel;
+
+    fn challenge() -> IssuedChallenge {
+        IssuedChallenge {
+            challenge: KzgChallenge {
+                vcr_id: H256::from([1u8; 32]),
+                layer_indices: vec![0],
+                point_indices: vec![vec![2]],
+                deadline_slot: 5,
+            },
+            watchtower: [4u8; 32],
+        }
+    }
+
+    #[test]
+    fn decodes_gossip_for_the_tower() {
+        let response = KzgOpeningResponse::new(H256::from([1u8; 32]), Vec::new());
+        let payload = encode_bincode(&WatchtowerMessage::Response(response)).unwrap();
+        assert!(matches!(
+            gossip_event(&payload),
+            Some(WatchtowerEvent::Response(r)) if r.vcr_id == H256::from([1u8; 32])
+        ));
+
+        let payload = encode_bincode(&WatchtowerMessage::Challenge(challenge())).unwrap();
+        assert!(gossip_event(&payload).is_none());
+        assert!(gossip_event(&[0xff; 7]).is_none());
+    }
+
+    #[tokio::test]
+    async fn ticks_slots_from_the_firehose() {
+        let firehose = FirehoseServer::new(8);
+        let mut stream = firehose.subscribe();
+        let (tx, rx) = channel();
+        for slot in [3, 4] {
+            let block = Block::new(
+                slot,
+                H256::zero(),
+                Address::from([0u8; 20]),
+                VrfProof {
+                    output: [0u8; 32],
+                    proof: Vec::new(),
+                },
+                Vec::new(),
+            );
+            firehose.publish(block).unwrap();
+        }
+        drop(firehose);
+
+        forward_firehose(&mut stream, &tx).await.unwrap();
+        let slots: Vec<u64> = rx
+            .try_iter()
+            .map(|event| match event {
+                WatchtowerEvent::Slot(slot) => slot,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        assert_eq!(slots, vec![3, 4]);
+    }
+}
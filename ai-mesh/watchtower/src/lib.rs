@@ -0,0 +1,19 @@
+// ============================================================================
+// AETHER AI WATCHTOWER - Spot-checking service for committed traces
+// ============================================================================
+// Runs `aether_verifiers_kzg::Watchtower` against the live network:
+//
+//   gossip (TOPIC_VCR)  -> trace announcements and worker openings
+//   firehose / RPC      -> slot ticks that expire unanswered challenges
+//   gossip (TOPIC_VCR)  <- challenges, for the workers to answer
+//   job escrow          <- fraud reports, as signed REPORT_FRAUD transactions
+//
+// The tower itself is synchronous; `feeds` pump network input into its
+// event channel and `sink::ChainSink` carries its output back out.
+// ============================================================================
+
+pub mod feeds;
+pub mod sink;
+
+pub use feeds::{forward_firehose, forward_slots, gossip_event};
+pub use sink::ChainSink;
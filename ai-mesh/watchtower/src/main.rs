@@ -0,0 +1,175 @@
+// ============================================================================
+// AETHER WATCHTOWER - Standalone trace spot-checker
+// ============================================================================
+// Joins the VCR gossip topic, follows the chain's slot over RPC and files
+// fraud reports with the job escrow program. See the library docs for the
+// data flow.
+// ============================================================================
+
+use aether_ai_watchtower::{forward_slots, gossip_event, ChainSink};
+use aether_crypto_kzg::KzgVerifier;
+use aether_crypto_primitives::Keypair;
+use aether_crypto_vrf::Tau;
+use aether_p2p::network::{NetworkEvent, TOPIC_VCR};
+use aether_p2p::P2PNetwork;
+use aether_sdk::AetherClient;
+use aether_types::Address;
+use aether_verifiers_kzg::{Watchtower, WatchtowerConfig, WatchtowerDuty, WatchtowerEvent};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[derive(Parser, Debug)]
+#[command(name = "aether-watchtower", about = "Spot-check committed AI traces")]
+struct Cli {
+    /// JSON-RPC endpoint of a node
+    #[arg(long, default_value = "http://127.0.0.1:8545")]
+    rpc: String,
+
+    /// Ed25519 key file (keytool format) that signs fraud reports
+    #[arg(long)]
+    key_file: PathBuf,
+
+    #[arg(long, default_value_t = 1)]
+    chain_id: u64,
+
+    /// P2P listen address
+    #[arg(long, default_value = "/ip4/0.0.0.0/tcp/9100")]
+    listen: String,
+
+    /// Peers to dial at startup
+    #[arg(long = "bootstrap")]
+    bootstrap: Vec<String>,
+
+    /// How often to poll the node for the current slot
+    #[arg(long, default_value_t = 500)]
+    poll_ms: u64,
+
+    #[arg(long, default_value_t = WatchtowerConfig::default().sample_layers)]
+    sample_layers: usize,
+
+    #[arg(long, default_value_t = WatchtowerConfig::default().sample_points)]
+    sample_points: usize,
+
+    /// Slots a worker has to answer a challenge
+    #[arg(long, default_value_t = WatchtowerConfig::default().response_slots)]
+    response_slots: u64,
+
+    /// This tower's stake; with --total-stake, only challenge traces the
+    /// duty draw assigns to it
+    #[arg(long, requires = "total_stake")]
+    stake: Option<u128>,
+
+    #[arg(long, requires = "stake")]
+    total_stake: Option<u128>,
+}
+
+fn load_signer(path: &std::path::Path) -> Result<Keypair> {
+    let key_file = aether_keytool::load_key(path)?;
+    if key_file.key_type != aether_keytool::KeyType::Ed25519 {
+        bail!("watchtower key must be ed25519, got {}", key_file.key_type);
+    }
+    let secret = hex::decode(&key_file.secret_key_hex).context("invalid secret key hex")?;
+    Keypair::from_bytes(&secret).map_err(|e| anyhow!("invalid secret key: {e}"))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let cli = Cli::parse();
+    let signer = load_signer(&cli.key_file)?;
+    let id: [u8; 32] = signer
+        .public_key()
+        .try_into()
+        .map_err(|_| anyhow!("ed25519 public key is not 32 bytes"))?;
+    let client = AetherClient::new(cli.rpc.clone());
+    let address = Address::from(signer.to_address());
+    let nonce = client
+        .get_account(address)
+        .await
+        .with_context(|| format!("fetching account from {}", cli.rpc))?
+        .map_or(0, |account| account.nonce);
+    tracing::info!(
+        "Aether Watchtower v{} - tower {}, reporting as {:?} from nonce {nonce}",
+        env!("CARGO_PKG_VERSION"),
+        hex::encode(id),
+        address
+    );
+
+    let config = WatchtowerConfig {
+        sample_layers: cli.sample_layers,
+        sample_points: cli.sample_points,
+        response_slots: cli.response_slots,
+        duty: cli
+            .stake
+            .zip(cli.total_stake)
+            .map(|(stake, total_stake)| WatchtowerDuty {
+                stake,
+                total_stake,
+                tau: Tau::seats(1),
+            }),
+    };
+    // Openings are checked against [τ]_2 only, so any degree will do; the
+    // setup must be the one the workers commit with.
+    let verifier = KzgVerifier::new_insecure_test(1);
+    let mut tower = Watchtower::new(config, id, verifier);
+
+    let (events_tx, events) = mpsc::channel::<WatchtowerEvent>();
+    let (gossip_tx, mut gossip_rx) = tokio::sync::mpsc::unbounded_channel::<Vec<u8>>();
+    let mut sink = ChainSink::new(client.clone(), signer, gossip_tx)
+        .with_chain_id(cli.chain_id)
+        .with_nonce(nonce);
+
+    let mut network = P2PNetwork::new_random()?;
+    network.start(&cli.listen).await?;
+    network.subscribe(TOPIC_VCR)?;
+    for peer in &cli.bootstrap {
+        if let Err(e) = network.connect_peer(peer) {
+            tracing::warn!("dialing {peer}: {e}");
+        }
+    }
+    tracing::info!("p2p peer id {}", network.peer_id_str());
+
+    let gossip_events = events_tx.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                event = network.poll() => match event {
+                    Some(NetworkEvent::VcrReceived(data)) => {
+                        let Some(event) = gossip_event(&data) else { continue };
+                        if gossip_events.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Some(_) => {}
+                    None => break,
+                },
+                Some(challenge) = gossip_rx.recv() => {
+                    if let Err(e) = network.publish(TOPIC_VCR, challenge) {
+                        tracing::warn!("publishing challenge: {e}");
+                    }
+                }
+            }
+        }
+    });
+
+    let poll = Duration::from_millis(cli.poll_ms);
+    tokio::spawn(async move { forward_slots(&client, poll, &events_tx).await });
+
+    // A failed submission is logged and the tower keeps watching; it must
+    // not stop checking other traces because one report did not land.
+    tokio::task::spawn_blocking(move || {
+        while let Ok(event) = events.recv() {
+            if let Err(e) = tower.handle(event, &mut sink) {
+                tracing::error!("watchtower: {e:#}");
+            }
+        }
+    })
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use aether_codecs::abi::{self, job_escrow};
+use aether_codecs::encode_bincode;
+use aether_crypto_primitives::Keypair;
+use aether_sdk::AetherClient;
+use aether_types::{Address, PublicKey, Signature, Transaction, JOB_ESCROW_PROGRAM_ID};
+use aether_verifiers_kzg::{FraudReport, IssuedChallenge, WatchtowerMessage, WatchtowerSink};
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashSet;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// Publishes challenges over gossip and files fraud reports with the job
+/// escrow program.
+///
+/// The tower calls the sink from a blocking thread; submissions run on the
+/// runtime the sink was created in.
+pub struct ChainSink {
+    client: AetherClient,
+    signer: Keypair,
+    chain_id: u64,
+    fee: u128,
+    gas_limit: u64,
+    nonce: u64,
+    /// Encoded `WatchtowerMessage`s for whatever publishes on `TOPIC_VCR`.
+    gossip: UnboundedSender<Vec<u8>>,
+    runtime: Handle,
+}
+
+impl ChainSink {
+    /// Must be called from within a Tokio runtime.
+    pub fn new(client: AetherClient, signer: Keypair, gossip: UnboundedSender<Vec<u8>>) -> Self {
+        let config = client.config();
+        Self {
+            fee: config.default_fee,
+            gas_limit: config.default_gas_limit,
+            client,
+            signer,
+            chain_id: 1,
+            nonce: 0,
+            gossip,
+            runtime: Handle::current(),
+        }
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Nonce of the first report; the signer's current account nonce.
+    pub fn with_nonce(mut self, nonce: u64) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        Address::from(self.signer.to_address())
+    }
+
+    /// A signed job escrow `REPORT_FRAUD` call carrying `report`.
+    pub fn fraud_report_tx(&self, report: &FraudReport) -> Result<Transaction> {
+        let report = encode_bincode(report)?;
+        let data = abi::encode_call(job_escrow::REPORT_FRAUD, |w| {
+            w.put_bytes(&report);
+        });
+        let escrow = Address::from_slice(&JOB_ESCROW_PROGRAM_ID.as_bytes()[12..])
+            .map_err(|e| anyhow!("job escrow address: {e}"))?;
+        let mut tx = Transaction {
+            nonce: self.nonce,
+            chain_id: self.chain_id,
+            sender: self.address(),
+            sender_pubkey: PublicKey::from_bytes(self.signer.public_key()),
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            reads: HashSet::new(),
+            writes: HashSet::from([escrow]),
+            program_id: Some(JOB_ESCROW_PROGRAM_ID),
+            data,
+            gas_limit: self.gas_limit,
+            fee: self.fee,
+            signature: Signature::from_bytes(vec![0; 64]),
+        };
+        tx.signature = Signature::from_bytes(self.signer.sign(tx.hash().as_bytes()));
+        Ok(tx)
+    }
+}
+
+impl WatchtowerSink for ChainSink {
+    fn submit_challenge(&mut self, challenge: &IssuedChallenge) -> Result<()> {
+        let message = encode_bincode(&WatchtowerMessage::Challenge(challenge.clone()))?;
+        self.gossip
+            .send(message)
+            .map_err(|_| anyhow!("gossip publisher has stopped"))
+    }
+
+    fn submit_fraud_report(&mut self, report: &FraudReport) -> Result<()> {
+        let tx = self.fraud_report_tx(report)?;
+        let submitted = self
+            .runtime
+            .block_on(self.client.submit(tx))
+            .with_context(|| format!("reporting fraud on {}", report.vcr_id))?;
+        self.nonce += 1;
+        tracing::info!(
+            vcr = %report.vcr_id,
+            reason = ?report.reason,
+            tx = %submitted.tx_hash,
+            "fraud report submitted"
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_codecs::decode_bincode;
+    use aether_types::H256;
+    use aether_verifiers_kzg::{FraudReason, KzgChallenge};
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn report() -> FraudReport {
+        let vcr_id = H256::from([1u8; 32]);
+        FraudReport {
+            vcr_id,
+            worker_id: vec![9u8; 32],
+            challenge: IssuedChallenge {
+                challenge: KzgChallenge {
+                    vcr_id,
+                    layer_indices: vec![0],
+                    point_indices: vec![vec![2]],
+                    deadline_slot: 5,
+                },
+                watchtower: [4u8; 32],
+            },
+            response: None,
+            reason: FraudReason::NoResponse,
+            slot: 6,
+        }
+    }
+
+    #[tokio::test]
+    async fn fraud_reports_are_signed_escrow_calls() {
+        let (gossip, _) = unbounded_channel();
+        let sink = ChainSink::new(
+            AetherClient::new("http://127.0.0.1:1"),
+            Keypair::generate(),
+            gossip,
+        )
+        .with_chain_id(100)
+        .with_nonce(7);
+
+        let tx = sink.fraud_report_tx(&report()).unwrap();
+        tx.verify_signature().unwrap();
+        assert_eq!(tx.sender, sink.address());
+        assert_eq!((tx.nonce, tx.chain_id), (7, 100));
+        assert_eq!(tx.program_id, Some(JOB_ESCROW_PROGRAM_ID));
+
+        let (selector, mut args) = abi::decode_call(&tx.data).unwrap();
+        assert_eq!(selector, job_escrow::REPORT_FRAUD);
+        let decoded: FraudReport = decode_bincode(args.take_bytes().unwrap()).unwrap();
+        args.finish().unwrap();
+        assert_eq!(decoded.vcr_id, report().vcr_id);
+        assert_eq!(decoded.reason, FraudReason::NoResponse);
+    }
+
+    #[tokio::test]
+    async fn challenges_go_out_over_gossip() {
+        let (gossip, mut published) = unbounded_channel();
+        let mut sink = ChainSink::new(
+            AetherClient::new("http://127.0.0.1:1"),
+            Keypair::generate(),
+            gossip,
+        );
+        let issued = report().challenge;
+        sink.submit_challenge(&issued).unwrap();
+
+        let payload = published.recv().await.unwrap();
+        match decode_bincode(&payload).unwrap() {
+            WatchtowerMessage::Challenge(sent) => assert_eq!(sent.challenge, issued.challenge),
+            other => panic!("unexpected {other:?}"),
+        }
+        drop(published);
+        assert!(sink.submit_challenge(&issued).is_err());
+    }
+}
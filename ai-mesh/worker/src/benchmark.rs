@@ -0,0 +1,330 @@
+// ============================================================================
+// BENCHMARK - Measured throughput behind the capabilities a worker advertises
+// ============================================================================
+// At startup, and again every refresh interval, the worker runs a fixed set
+// of synthetic MLP shapes through its own inference engine and reports what
+// it actually sustained. The coordinator routes on these numbers rather than
+// on operator-declared hardware, so a throttled or oversubscribed host drops
+// a tier on its next refresh.
+//
+// Throughput is measured in gas per second, the same unit jobs are priced
+// and metered in, so tiers translate directly into expected job latency.
+// ============================================================================
+
+use crate::engine::{Graph, GraphNode, InferenceEngine, PINNED_OPSET};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A synthetic MLP: `layers` x (MatMul -> Relu -> BitShift).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BenchmarkShape {
+    pub name: String,
+    pub input_dim: usize,
+    pub hidden_dim: usize,
+    pub layers: usize,
+}
+
+impl BenchmarkShape {
+    pub fn new(name: &str, input_dim: usize, hidden_dim: usize, layers: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            input_dim,
+            hidden_dim,
+            layers,
+        }
+    }
+
+    /// Deterministic weights, so every worker benchmarks the same model.
+    pub fn graph(&self) -> Graph {
+        let mut nodes = Vec::with_capacity(self.layers * 3);
+        let mut dim = self.input_dim;
+        for layer in 0..self.layers {
+            let weights = (0..dim)
+                .map(|i| {
+                    (0..self.hidden_dim)
+                        .map(|j| ((i * 31 + j * 17 + layer * 7) % 7) as i32 - 3)
+                        .collect()
+                })
+                .collect();
+            nodes.push(node(format!("matmul{layer}"), "MatMul", weights, 0));
+            nodes.push(node(format!("relu{layer}"), "Relu", vec![], 0));
+            nodes.push(node(format!("shift{layer}"), "BitShift", vec![], 4));
+            dim = self.hidden_dim;
+        }
+        Graph {
+            opset: PINNED_OPSET,
+            input_dim: self.input_dim,
+            nodes,
+        }
+    }
+
+    /// Engine key for the shape; cannot collide with a real model's SHA-256.
+    fn model_hash(&self) -> Vec<u8> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"AETHER-BENCHMARK");
+        hasher.update(self.name.as_bytes());
+        for dim in [self.input_dim, self.hidden_dim, self.layers] {
+            hasher.update((dim as u64).to_le_bytes());
+        }
+        let mut hash = b"bench:".to_vec();
+        hash.extend_from_slice(&hasher.finalize());
+        hash
+    }
+}
+
+fn node(name: String, op_type: &str, weights: Vec<Vec<i32>>, shift: u32) -> GraphNode {
+    GraphNode {
+        name,
+        op_type: op_type.to_string(),
+        weights,
+        bias: vec![],
+        shift,
+    }
+}
+
+/// The shapes every worker reports on, smallest first.
+pub fn standard_shapes() -> Vec<BenchmarkShape> {
+    vec![
+        BenchmarkShape::new("mlp-64x2", 64, 64, 2),
+        BenchmarkShape::new("mlp-256x4", 256, 256, 4),
+        BenchmarkShape::new("mlp-1024x2", 1024, 1024, 2),
+    ]
+}
+
+#[derive(Debug, Clone)]
+pub struct BenchmarkConfig {
+    pub shapes: Vec<BenchmarkShape>,
+    /// Timed runs per shape, after one warm-up run.
+    pub iterations: u32,
+    /// How often the benchmark is re-run and re-reported.
+    pub refresh_interval: Duration,
+    /// A shape is advertised only if one run finishes within this budget.
+    pub max_latency: Duration,
+}
+
+impl Default for BenchmarkConfig {
+    fn default() -> Self {
+        Self {
+            shapes: standard_shapes(),
+            iterations: 5,
+            refresh_interval: Duration::from_secs(600),
+            max_latency: Duration::from_secs(2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShapeResult {
+    pub shape: String,
+    pub gas_per_run: u64,
+    pub mean_latency_us: u64,
+    pub gas_per_sec: u64,
+}
+
+/// Coarse performance class the router matches `min_hardware_tier` against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum HardwareTier {
+    Basic,
+    Standard,
+    Performance,
+    Accelerated,
+}
+
+impl HardwareTier {
+    /// Lower bound of each tier above `Basic`, in gas per second.
+    pub const STANDARD_GAS_PER_SEC: u64 = 50_000_000;
+    pub const PERFORMANCE_GAS_PER_SEC: u64 = 500_000_000;
+    pub const ACCELERATED_GAS_PER_SEC: u64 = 5_000_000_000;
+
+    pub fn from_gas_per_sec(gas_per_sec: u64) -> Self {
+        match gas_per_sec {
+            g if g >= Self::ACCELERATED_GAS_PER_SEC => HardwareTier::Accelerated,
+            g if g >= Self::PERFORMANCE_GAS_PER_SEC => HardwareTier::Performance,
+            g if g >= Self::STANDARD_GAS_PER_SEC => HardwareTier::Standard,
+            _ => HardwareTier::Basic,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HardwareTier::Basic => "basic",
+            HardwareTier::Standard => "standard",
+            HardwareTier::Performance => "performance",
+            HardwareTier::Accelerated => "accelerated",
+        }
+    }
+}
+
+/// What a worker advertises to the coordinator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CapabilityReport {
+    pub worker_id: Vec<u8>,
+    pub engine: String,
+    pub tee_type: String,
+    pub max_concurrent_jobs: usize,
+    pub hardware_tier: HardwareTier,
+    /// Slowest sustained throughput across the shapes that completed.
+    pub gas_per_sec: u64,
+    /// Capability strings in the form the router's `required_capabilities`
+    /// matches: engine, TEE, `tier:<tier>`, and `shape:<name>` per shape
+    /// that ran within the latency budget.
+    pub capabilities: Vec<String>,
+    pub shapes: Vec<ShapeResult>,
+    pub measured_at: u64,
+}
+
+/// Load any benchmark shapes the engine does not hold yet.
+pub fn load_shapes(engine: &mut dyn InferenceEngine, shapes: &[BenchmarkShape]) -> Result<()> {
+    for shape in shapes {
+        let hash = shape.model_hash();
+        if !engine.is_loaded(&hash) {
+            engine.load(&hash, &shape.graph().to_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+/// Time each shape; they must already be loaded with `load_shapes`.
+pub fn measure(engine: &dyn InferenceEngine, config: &BenchmarkConfig) -> Result<Vec<ShapeResult>> {
+    if config.iterations == 0 {
+        bail!("benchmark needs at least one iteration");
+    }
+    let mut results = Vec::with_capacity(config.shapes.len());
+    for shape in &config.shapes {
+        let hash = shape.model_hash();
+        let input: Vec<u8> = (0..shape.input_dim).map(|i| (i % 251) as u8).collect();
+
+        let warmup = engine.run(&hash, &input, u64::MAX)?;
+        let started = Instant::now();
+        for _ in 0..config.iterations {
+            engine.run(&hash, &input, u64::MAX)?;
+        }
+        let elapsed = started.elapsed();
+
+        let mean = elapsed / config.iterations;
+        let total_gas = warmup.gas_used as u128 * config.iterations as u128;
+        let gas_per_sec = total_gas * 1_000_000 / elapsed.as_micros().max(1);
+        results.push(ShapeResult {
+            shape: shape.name.clone(),
+            gas_per_run: warmup.gas_used,
+            mean_latency_us: mean.as_micros() as u64,
+            gas_per_sec: gas_per_sec.min(u64::MAX as u128) as u64,
+        });
+    }
+    Ok(results)
+}
+
+/// Turn measurements into the report sent to the coordinator.
+pub fn build_report(
+    worker_id: &[u8],
+    engine: &str,
+    tee_type: &str,
+    max_concurrent_jobs: usize,
+    config: &BenchmarkConfig,
+    shapes: Vec<ShapeResult>,
+) -> CapabilityReport {
+    let gas_per_sec = shapes.iter().map(|s| s.gas_per_sec).min().unwrap_or(0);
+    let hardware_tier = HardwareTier::from_gas_per_sec(gas_per_sec);
+
+    let mut capabilities = vec![
+        engine.to_string(),
+        format!("tee:{}", tee_type.to_ascii_lowercase()),
+        format!("tier:{}", hardware_tier.as_str()),
+    ];
+    let budget_us = config.max_latency.as_micros() as u64;
+    capabilities.extend(
+        shapes
+            .iter()
+            .filter(|s| s.mean_latency_us <= budget_us)
+            .map(|s| format!("shape:{}", s.shape)),
+    );
+
+    CapabilityReport {
+        worker_id: worker_id.to_vec(),
+        engine: engine.to_string(),
+        tee_type: tee_type.to_string(),
+        max_concurrent_jobs,
+        hardware_tier,
+        gas_per_sec,
+        capabilities,
+        shapes,
+        measured_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::ReferenceEngine;
+
+    fn config() -> BenchmarkConfig {
+        BenchmarkConfig {
+            shapes: vec![
+                BenchmarkShape::new("tiny", 8, 8, 1),
+                BenchmarkShape::new("small", 16, 32, 2),
+            ],
+            iterations: 2,
+            refresh_interval: Duration::from_secs(60),
+            max_latency: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn measures_every_shape() {
+        let mut engine = ReferenceEngine::default();
+        assert!(measure(&engine, &config()).is_err());
+
+        load_shapes(&mut engine, &config().shapes).unwrap();
+        let results = measure(&engine, &config()).unwrap();
+        assert_eq!(
+            results.iter().map(|r| r.shape.as_str()).collect::<Vec<_>>(),
+            vec!["tiny", "small"]
+        );
+        assert!(results.iter().all(|r| r.gas_per_sec > 0));
+        assert!(results[1].gas_per_run > results[0].gas_per_run);
+    }
+
+    #[test]
+    fn report_advertises_tier_and_fast_shapes() {
+        let shapes = vec![
+            ShapeResult {
+                shape: "fast".to_string(),
+                gas_per_run: 10,
+                mean_latency_us: 100,
+                gas_per_sec: 600_000_000,
+            },
+            ShapeResult {
+                shape: "slow".to_string(),
+                gas_per_run: 10,
+                mean_latency_us: 5_000_000,
+                gas_per_sec: 60_000_000,
+            },
+        ];
+        let config = BenchmarkConfig {
+            max_latency: Duration::from_secs(1),
+            ..config()
+        };
+        let report = build_report(&[1], "reference", "SEV-SNP", 4, &config, shapes);
+        assert_eq!(report.hardware_tier, HardwareTier::Standard);
+        assert_eq!(report.gas_per_sec, 60_000_000);
+        assert_eq!(
+            report.capabilities,
+            vec!["reference", "tee:sev-snp", "tier:standard", "shape:fast"]
+        );
+    }
+
+    #[test]
+    fn tiers_follow_thresholds() {
+        assert_eq!(HardwareTier::from_gas_per_sec(0), HardwareTier::Basic);
+        assert_eq!(
+            HardwareTier::from_gas_per_sec(HardwareTier::PERFORMANCE_GAS_PER_SEC),
+            HardwareTier::Performance
+        );
+        assert!(HardwareTier::Accelerated > HardwareTier::Standard);
+    }
+}
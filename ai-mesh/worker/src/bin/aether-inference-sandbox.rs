@@ -0,0 +1,11 @@
+//! Standalone sandbox child for `SandboxedEngine`, for deployments that do
+//! not re-execute the worker binary itself.
+
+fn main() {
+    aether_ai_worker::sandbox::run_child_if_requested();
+    eprintln!(
+        "usage: aether-inference-sandbox {}  (request on stdin)",
+        aether_ai_worker::sandbox::SANDBOX_ARG
+    );
+    std::process::exit(2);
+}
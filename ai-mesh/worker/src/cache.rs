@@ -0,0 +1,648 @@
+// ============================================================================
+// MODEL CACHE - Content-addressed model storage
+// ============================================================================
+// Models are stored as `<cache_dir>/<hex(sha256)>.model`. A model is only ever
+// written to the cache after its bytes hash to the requested model hash. Files
+// left by a previous run are hashed again when the directory is scanned, and
+// once more the first time each is loaded, since the disk may have changed
+// underneath either; a mismatch evicts the file and the model is refetched.
+// After that first load, hits only get a cheap size check.
+//
+// Eviction is least-recently-used by total byte size. Sources are tried in
+// order until one returns bytes that verify.
+//
+// The cache is shared by concurrent jobs. Its index is locked only while it
+// is consulted or updated; downloads run unlocked, one per model, and jobs
+// wanting a model that is being fetched wait for that fetch instead of
+// starting their own.
+// ============================================================================
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard, RwLock};
+use std::time::Duration;
+
+/// Default on-disk budget for cached models (8 GiB).
+pub const DEFAULT_CACHE_BUDGET_BYTES: u64 = 8 * 1024 * 1024 * 1024;
+
+pub trait ModelSource: Send + Sync {
+    fn name(&self) -> String;
+
+    /// Fetch the raw model bytes for `model_hash` (verification is the cache's job).
+    fn fetch(&self, model_hash: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Read models from a local mirror laid out like the cache itself.
+pub struct LocalDirSource {
+    dir: PathBuf,
+}
+
+impl LocalDirSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl ModelSource for LocalDirSource {
+    fn name(&self) -> String {
+        format!("dir:{}", self.dir.display())
+    }
+
+    fn fetch(&self, model_hash: &[u8]) -> Result<Vec<u8>> {
+        let path = self.dir.join(format!("{}.model", hex::encode(model_hash)));
+        fs::read(&path).with_context(|| format!("reading {}", path.display()))
+    }
+}
+
+/// HTTP(S) GET source, e.g. an IPFS gateway or model mirror.
+///
+/// `url_template` must contain `{hash}`, which is replaced with the hex model
+/// hash (for IPFS: `http://127.0.0.1:8080/ipfs/{hash}` behind a hash→CID
+/// resolver). Content is verified by hash, so the endpoint need not be
+/// trusted.
+pub struct HttpSource {
+    url_template: String,
+    http: reqwest::Client,
+}
+
+impl HttpSource {
+    pub fn new(url_template: impl Into<String>) -> Result<Self> {
+        let url_template = url_template.into();
+        if !url_template.starts_with("http://") && !url_template.starts_with("https://") {
+            bail!("model source URL must be http:// or https://: {url_template}");
+        }
+        if !url_template.contains("{hash}") {
+            bail!("model source URL must contain {{hash}}: {url_template}");
+        }
+        let http = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(30))
+            .read_timeout(Duration::from_secs(30))
+            // Fetches may each run on their own runtime (see `get`), which
+            // a pooled connection would not outlive.
+            .pool_max_idle_per_host(0)
+            .build()
+            .context("building model source HTTP client")?;
+        Ok(Self { url_template, http })
+    }
+
+    /// Called from blocking job threads: runs on the worker's runtime when
+    /// there is one, else on a runtime of its own.
+    fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let request = async {
+            let response = self
+                .http
+                .get(url)
+                .send()
+                .await
+                .with_context(|| format!("GET {url}"))?;
+            let status = response.status();
+            if !status.is_success() {
+                bail!("GET {url} failed: {status}");
+            }
+            let body = response
+                .bytes()
+                .await
+                .with_context(|| format!("reading {url}"))?;
+            Ok(body.to_vec())
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => handle.block_on(request),
+            Err(_) => tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .context("starting model download runtime")?
+                .block_on(request),
+        }
+    }
+}
+
+impl ModelSource for HttpSource {
+    fn name(&self) -> String {
+        self.url_template.clone()
+    }
+
+    fn fetch(&self, model_hash: &[u8]) -> Result<Vec<u8>> {
+        let url = self
+            .url_template
+            .replace("{hash}", &hex::encode(model_hash));
+        self.get(&url)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub verification_failures: u64,
+    pub bytes_cached: u64,
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    size: u64,
+    last_used: u64,
+    /// Whether this process has hashed the bytes it served from disk.
+    loaded: bool,
+}
+
+pub struct ModelCache {
+    dir: PathBuf,
+    budget_bytes: u64,
+    sources: RwLock<Vec<Box<dyn ModelSource>>>,
+    state: Mutex<CacheState>,
+    /// Signalled whenever a fetch finishes.
+    fetched: Condvar,
+}
+
+#[derive(Default)]
+struct CacheState {
+    entries: HashMap<Vec<u8>, CacheEntry>,
+    clock: u64,
+    stats: CacheStats,
+    scanned: bool,
+    /// Models being fetched right now.
+    fetching: HashSet<Vec<u8>>,
+}
+
+impl ModelCache {
+    /// Create a cache rooted at `dir`. The directory is created and scanned
+    /// lazily on first use.
+    pub fn new(dir: impl Into<PathBuf>, budget_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            budget_bytes,
+            sources: RwLock::new(Vec::new()),
+            state: Mutex::new(CacheState::default()),
+            fetched: Condvar::new(),
+        }
+    }
+
+    pub fn add_source(&self, source: Box<dyn ModelSource>) {
+        self.sources
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(source);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    pub fn contains(&self, model_hash: &[u8]) -> bool {
+        self.lock().entries.contains_key(model_hash)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Return verified model bytes, fetching from sources on a miss.
+    pub fn get(&self, model_hash: &[u8]) -> Result<Vec<u8>> {
+        if model_hash.len() != 32 {
+            bail!("model hash must be a 32-byte SHA-256 digest");
+        }
+        let mut state = self.lock();
+        self.scan_locked(&mut state)?;
+        while state.fetching.contains(model_hash) {
+            state = self.fetched.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        state.clock += 1;
+        let clock = state.clock;
+
+        if let Some(entry) = state.entries.get_mut(model_hash) {
+            let path = model_path(&self.dir, model_hash);
+            match fs::read(&path) {
+                Ok(bytes)
+                    if bytes.len() as u64 == entry.size
+                        && (entry.loaded || Sha256::digest(&bytes).as_slice() == model_hash) =>
+                {
+                    entry.loaded = true;
+                    entry.last_used = clock;
+                    state.stats.hits += 1;
+                    return Ok(bytes);
+                }
+                Ok(_) => {
+                    // Corrupted on disk; evict and refetch.
+                    state.stats.verification_failures += 1;
+                    self.remove(&mut state, model_hash);
+                }
+                // File vanished underneath us; refetch.
+                Err(_) => self.remove(&mut state, model_hash),
+            }
+        }
+
+        state.stats.misses += 1;
+        state.fetching.insert(model_hash.to_vec());
+        drop(state);
+
+        // Download unlocked; the guard wakes waiters however this ends.
+        let fetch = Fetching {
+            cache: self,
+            model_hash,
+        };
+        let (fetched, failures) = self.fetch_verified(model_hash);
+        let stored = fetched
+            .as_ref()
+            .ok()
+            .map(|bytes| self.store(model_hash, bytes));
+        let mut state = self.lock();
+        state.stats.verification_failures += failures;
+        let bytes = fetched?;
+        if let Some(size) = stored.transpose()?.flatten() {
+            self.index(&mut state, model_hash, size, clock);
+        }
+        drop(state);
+        drop(fetch);
+        Ok(bytes)
+    }
+
+    /// Bytes from the first source whose bytes verify, and how many sources
+    /// returned bytes that did not.
+    fn fetch_verified(&self, model_hash: &[u8]) -> (Result<Vec<u8>>, u64) {
+        let sources = self.sources.read().unwrap_or_else(|e| e.into_inner());
+        let mut errors = Vec::new();
+        let mut failures = 0;
+        for source in sources.iter() {
+            match source.fetch(model_hash) {
+                Ok(bytes) if Sha256::digest(&bytes).as_slice() == model_hash => {
+                    return (Ok(bytes), failures)
+                }
+                Ok(_) => {
+                    failures += 1;
+                    errors.push(format!("{}: hash mismatch", source.name()));
+                }
+                Err(e) => errors.push(format!("{}: {e}", source.name())),
+            }
+        }
+        if errors.is_empty() {
+            return (
+                Err(anyhow::anyhow!("no model sources configured")),
+                failures,
+            );
+        }
+        let error = anyhow::anyhow!(
+            "model {} unavailable: {}",
+            hex::encode(model_hash),
+            errors.join("; ")
+        );
+        (Err(error), failures)
+    }
+
+    /// Write fetched bytes into the cache directory, returning their size,
+    /// or `None` for a model too big to ever fit.
+    fn store(&self, model_hash: &[u8], bytes: &[u8]) -> Result<Option<u64>> {
+        let size = bytes.len() as u64;
+        if size > self.budget_bytes {
+            // Serve it, but don't thrash the whole cache for one oversized model.
+            return Ok(None);
+        }
+        let path = model_path(&self.dir, model_hash);
+        let tmp = path.with_extension("partial");
+        fs::write(&tmp, bytes).with_context(|| format!("writing {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        Ok(Some(size))
+    }
+
+    /// Index a stored model, evicting others to stay within budget.
+    fn index(&self, state: &mut CacheState, model_hash: &[u8], size: u64, clock: u64) {
+        while state.stats.bytes_cached + size > self.budget_bytes {
+            let victim = state
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| k.clone());
+            let Some(victim) = victim else { break };
+            self.remove(state, &victim);
+            state.stats.evictions += 1;
+        }
+        state.entries.insert(
+            model_hash.to_vec(),
+            CacheEntry {
+                size,
+                last_used: clock,
+                loaded: true,
+            },
+        );
+        state.stats.bytes_cached += size;
+    }
+
+    fn remove(&self, state: &mut CacheState, model_hash: &[u8]) {
+        if let Some(entry) = state.entries.remove(model_hash) {
+            state.stats.bytes_cached -= entry.size;
+            let _ = fs::remove_file(model_path(&self.dir, model_hash));
+        }
+    }
+
+    #[cfg(test)]
+    fn scan(&self) -> Result<()> {
+        self.scan_locked(&mut self.lock())
+    }
+
+    /// Index models already on disk from a previous run, deleting any whose
+    /// contents no longer hash to their name.
+    fn scan_locked(&self, state: &mut CacheState) -> Result<()> {
+        if state.scanned {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("creating model cache {}", self.dir.display()))?;
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("model") {
+                continue;
+            }
+            let Some(hash) = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .and_then(|s| hex::decode(s).ok())
+            else {
+                continue;
+            };
+            if file_digest(&path)?.as_slice() != hash.as_slice() {
+                state.stats.verification_failures += 1;
+                fs::remove_file(&path)
+                    .with_context(|| format!("evicting corrupted model {}", path.display()))?;
+                continue;
+            }
+            let size = entry.metadata()?.len();
+            state.stats.bytes_cached += size;
+            state.entries.insert(
+                hash,
+                CacheEntry {
+                    size,
+                    last_used: 0,
+                    loaded: false,
+                },
+            );
+        }
+        state.scanned = true;
+        Ok(())
+    }
+}
+
+/// Marks a model as being fetched; dropping it lets waiting jobs look again.
+struct Fetching<'a> {
+    cache: &'a ModelCache,
+    model_hash: &'a [u8],
+}
+
+impl Drop for Fetching<'_> {
+    fn drop(&mut self) {
+        self.cache.lock().fetching.remove(self.model_hash);
+        self.cache.fetched.notify_all();
+    }
+}
+
+/// SHA-256 of a file, streamed rather than read into memory.
+fn file_digest(path: &Path) -> Result<[u8; 32]> {
+    let mut file = fs::File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher).with_context(|| format!("hashing {}", path.display()))?;
+    Ok(hasher.finalize().into())
+}
+
+fn model_path(dir: &Path, model_hash: &[u8]) -> PathBuf {
+    dir.join(format!("{}.model", hex::encode(model_hash)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{mpsc, Arc};
+
+    struct MemorySource {
+        models: HashMap<Vec<u8>, Vec<u8>>,
+        fetches: Arc<AtomicUsize>,
+    }
+
+    impl ModelSource for MemorySource {
+        fn name(&self) -> String {
+            "memory".to_string()
+        }
+
+        fn fetch(&self, model_hash: &[u8]) -> Result<Vec<u8>> {
+            self.fetches.fetch_add(1, Ordering::SeqCst);
+            self.models
+                .get(model_hash)
+                .cloned()
+                .ok_or_else(|| anyhow::anyhow!("not found"))
+        }
+    }
+
+    fn model(byte: u8, len: usize) -> (Vec<u8>, Vec<u8>) {
+        let bytes = vec![byte; len];
+        (Sha256::digest(&bytes).to_vec(), bytes)
+    }
+
+    #[test]
+    fn caches_verified_models_and_counts_hits() {
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, bytes) = model(1, 64);
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let cache = ModelCache::new(dir.path(), 1_000);
+        cache.add_source(Box::new(MemorySource {
+            models: HashMap::from([(hash.clone(), bytes.clone())]),
+            fetches: fetches.clone(),
+        }));
+
+        assert_eq!(cache.get(&hash).unwrap(), bytes);
+        assert_eq!(cache.get(&hash).unwrap(), bytes);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 1);
+
+        // A fresh cache over the same directory finds the model on disk.
+        let reopened = ModelCache::new(dir.path(), 1_000);
+        assert_eq!(reopened.get(&hash).unwrap(), bytes);
+        assert_eq!(reopened.stats().hits, 1);
+    }
+
+    #[test]
+    fn rejects_tampered_bytes() {
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, _) = model(1, 64);
+        let cache = ModelCache::new(dir.path(), 1_000);
+        cache.add_source(Box::new(MemorySource {
+            models: HashMap::from([(hash.clone(), vec![2u8; 64])]),
+            fetches: Arc::new(AtomicUsize::new(0)),
+        }));
+
+        assert!(cache.get(&hash).is_err());
+        assert_eq!(cache.stats().verification_failures, 1);
+        assert!(!cache.contains(&hash));
+    }
+
+    #[test]
+    fn evicts_corrupted_files_and_refetches() {
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, bytes) = model(1, 64);
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let source = || {
+            Box::new(MemorySource {
+                models: HashMap::from([(hash.clone(), bytes.clone())]),
+                fetches: fetches.clone(),
+            })
+        };
+        let path = model_path(dir.path(), &hash);
+        let corrupt = || fs::write(&path, vec![9u8; 64]).unwrap();
+
+        let cache = ModelCache::new(dir.path(), 1_000);
+        cache.add_source(source());
+        cache.get(&hash).unwrap();
+
+        // Same size, different bytes: caught when a new run scans the cache.
+        corrupt();
+        let reopened = ModelCache::new(dir.path(), 1_000);
+        reopened.add_source(source());
+        reopened.scan().unwrap();
+        assert!(!reopened.contains(&hash));
+        assert_eq!(reopened.stats().verification_failures, 1);
+        assert_eq!(reopened.get(&hash).unwrap(), bytes);
+        assert_eq!(fetches.load(Ordering::SeqCst), 2);
+
+        // Corrupted after the scan: caught on the first load.
+        let reopened = ModelCache::new(dir.path(), 1_000);
+        reopened.add_source(source());
+        reopened.scan().unwrap();
+        corrupt();
+        assert_eq!(reopened.get(&hash).unwrap(), bytes);
+        assert_eq!(reopened.stats().verification_failures, 1);
+        assert_eq!(reopened.stats().misses, 1);
+        assert_eq!(fetches.load(Ordering::SeqCst), 3);
+        assert_eq!(fs::read(&path).unwrap(), bytes);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, a_bytes) = model(1, 40);
+        let (b, b_bytes) = model(2, 40);
+        let (c, c_bytes) = model(3, 40);
+        let cache = ModelCache::new(dir.path(), 100);
+        cache.add_source(Box::new(MemorySource {
+            models: HashMap::from([
+                (a.clone(), a_bytes),
+                (b.clone(), b_bytes),
+                (c.clone(), c_bytes),
+            ]),
+            fetches: Arc::new(AtomicUsize::new(0)),
+        }));
+
+        cache.get(&a).unwrap();
+        cache.get(&b).unwrap();
+        cache.get(&a).unwrap();
+        cache.get(&c).unwrap();
+
+        assert!(cache.contains(&a));
+        assert!(!cache.contains(&b));
+        assert!(cache.contains(&c));
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.stats().bytes_cached, 80);
+    }
+
+    #[test]
+    fn http_source_fetches_from_gateway() {
+        let (hash, bytes) = model(7, 16);
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let body = bytes.clone();
+        let expected_path = format!("/ipfs/{}", hex::encode(&hash));
+        let server = std::thread::spawn(move || {
+            let (mut conn, _) = listener.accept().unwrap();
+            // The request head may arrive over several reads.
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = conn.read(&mut buf).unwrap();
+                assert!(n > 0, "connection closed mid-request");
+                request.extend_from_slice(&buf[..n]);
+            }
+            let request = String::from_utf8_lossy(&request).to_string();
+            assert!(request.starts_with(&format!("GET {expected_path} HTTP/1.1")));
+            write!(
+                conn,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n",
+                body.len()
+            )
+            .unwrap();
+            conn.write_all(&body).unwrap();
+        });
+
+        let source = HttpSource::new(format!("http://127.0.0.1:{port}/ipfs/{{hash}}")).unwrap();
+        assert_eq!(source.fetch(&hash).unwrap(), bytes);
+        server.join().unwrap();
+
+        assert!(HttpSource::new("https://example.com/{hash}").is_ok());
+        assert!(HttpSource::new("ftp://example.com/{hash}").is_err());
+    }
+
+    /// Blocks every fetch until the test releases it.
+    struct GatedSource {
+        inner: MemorySource,
+        started: mpsc::Sender<()>,
+        release: Mutex<mpsc::Receiver<()>>,
+    }
+
+    impl ModelSource for GatedSource {
+        fn name(&self) -> String {
+            "gated".to_string()
+        }
+
+        fn fetch(&self, model_hash: &[u8]) -> Result<Vec<u8>> {
+            self.started.send(()).unwrap();
+            self.release.lock().unwrap().recv().unwrap();
+            self.inner.fetch(model_hash)
+        }
+    }
+
+    #[test]
+    fn downloads_do_not_block_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let (slow, slow_bytes) = model(1, 64);
+        let (cached, cached_bytes) = model(2, 64);
+        fs::create_dir_all(dir.path()).unwrap();
+        fs::write(model_path(dir.path(), &cached), &cached_bytes).unwrap();
+
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let (started_tx, started) = mpsc::channel();
+        let (release, release_rx) = mpsc::channel();
+        let cache = Arc::new(ModelCache::new(dir.path(), 1_000));
+        cache.add_source(Box::new(GatedSource {
+            inner: MemorySource {
+                models: HashMap::from([(slow.clone(), slow_bytes.clone())]),
+                fetches: fetches.clone(),
+            },
+            started: started_tx,
+            release: Mutex::new(release_rx),
+        }));
+
+        let getter = |hash: Vec<u8>| {
+            let cache = cache.clone();
+            std::thread::spawn(move || cache.get(&hash).unwrap())
+        };
+        let first = getter(slow.clone());
+        started.recv().unwrap();
+        let second = getter(slow.clone());
+
+        // While the download is stuck, other models are still served.
+        assert_eq!(cache.get(&cached).unwrap(), cached_bytes);
+        assert!(!cache.contains(&slow));
+
+        release.send(()).unwrap();
+        assert_eq!(first.join().unwrap(), slow_bytes);
+        // The second job waited for the first download instead of starting
+        // its own.
+        assert_eq!(second.join().unwrap(), slow_bytes);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().misses, 1);
+        assert_eq!(cache.stats().hits, 2);
+    }
+}
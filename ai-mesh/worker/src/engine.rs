@@ -0,0 +1,467 @@
+// ============================================================================
+// INFERENCE ENGINE - Deterministic model execution
+// ============================================================================
+// Every backend runs behind `InferenceEngine` so the worker can swap the
+// execution runtime without touching job handling. Backends must produce
+// bit-identical outputs for identical (model, input) pairs on any host:
+//
+// - single-threaded execution (no reduction-order races)
+// - pinned opset; graphs declaring a different opset are rejected
+// - nondeterministic ops (random sampling, dropout) refused at load time
+// - integer arithmetic only in the reference backend (no float drift)
+//
+// The built-in `ReferenceEngine` is not an ONNX runtime and does not load
+// `.onnx` files. It interprets the mesh's own JSON graph format: a handful of
+// integer operators (MatMul, Add, Relu, BitShift) for verification workloads.
+// Operator names and the opset number follow ONNX so integer-quantized ONNX
+// graphs can be lowered onto it by a converter. Standard `.onnx` models run
+// on `onnx::OnnxEngine`, the ONNX Runtime backend behind the `onnx` feature.
+// ============================================================================
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Opset every graph must declare, numbered after the ONNX opset its
+/// operator semantics follow.
+pub const PINNED_OPSET: u32 = 17;
+
+/// Operators whose results depend on RNG state or scheduling.
+pub const NONDETERMINISTIC_OPS: &[&str] = &[
+    "RandomNormal",
+    "RandomNormalLike",
+    "RandomUniform",
+    "RandomUniformLike",
+    "Multinomial",
+    "Bernoulli",
+    "Dropout",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeterminismConfig {
+    pub intra_op_threads: usize,
+    pub inter_op_threads: usize,
+    pub opset: u32,
+    pub allow_nondeterministic_ops: bool,
+}
+
+impl Default for DeterminismConfig {
+    fn default() -> Self {
+        Self {
+            intra_op_threads: 1,
+            inter_op_threads: 1,
+            opset: PINNED_OPSET,
+            allow_nondeterministic_ops: false,
+        }
+    }
+}
+
+impl DeterminismConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.intra_op_threads != 1 || self.inter_op_threads != 1 {
+            bail!("deterministic mode requires single-threaded execution");
+        }
+        if self.allow_nondeterministic_ops {
+            bail!("deterministic mode cannot allow nondeterministic ops");
+        }
+        Ok(())
+    }
+}
+
+/// Flat charge for loading a model and setting up a run.
+pub const BASE_GAS: u64 = 1000;
+/// Per multiply-accumulate in MatMul.
+pub const GAS_PER_MAC: u64 = 2;
+/// Per output element of an elementwise op.
+pub const GAS_PER_ELEMENT: u64 = 1;
+/// Per 8-byte word read or written (inputs, weights, outputs).
+pub const GAS_PER_WORD: u64 = 1;
+
+/// Gas charged for one graph node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpGas {
+    pub node: String,
+    pub op_type: String,
+    pub gas: u64,
+}
+
+/// Output of one graph node, captured for trace commitments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayerActivation {
+    pub node: String,
+    pub op_type: String,
+    pub values: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InferenceOutput {
+    pub output: Vec<u8>,
+    pub activations: Vec<LayerActivation>,
+    /// Per-node charges, in execution order.
+    pub op_gas: Vec<OpGas>,
+    /// `BASE_GAS` plus every node's charge.
+    pub gas_used: u64,
+}
+
+pub trait InferenceEngine: Send + Sync {
+    fn name(&self) -> &str;
+
+    /// Parse and validate a model, keyed by its content hash.
+    fn load(&mut self, model_hash: &[u8], model_bytes: &[u8]) -> Result<()>;
+
+    fn is_loaded(&self, model_hash: &[u8]) -> bool;
+
+    /// Run the model, aborting before any node that would exceed `gas_limit`.
+    fn run(&self, model_hash: &[u8], input: &[u8], gas_limit: u64) -> Result<InferenceOutput>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub name: String,
+    pub op_type: String,
+    /// MatMul weights, row-major `[input_dim][output_dim]`.
+    #[serde(default)]
+    pub weights: Vec<Vec<i32>>,
+    /// Add operand, one entry per element.
+    #[serde(default)]
+    pub bias: Vec<i32>,
+    /// BitShift amount (direction is always RIGHT for requantization).
+    #[serde(default)]
+    pub shift: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Graph {
+    pub opset: u32,
+    pub input_dim: usize,
+    pub nodes: Vec<GraphNode>,
+}
+
+impl Graph {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        serde_json::from_slice(bytes).context("invalid model graph encoding")
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).expect("graph serialization is infallible")
+    }
+
+    /// Static gas cost of each node, from FLOPs and memory traffic.
+    pub fn op_costs(&self) -> Vec<OpGas> {
+        let mut dim = self.input_dim as u64;
+        self.nodes
+            .iter()
+            .map(|node| {
+                let gas = match node.op_type.as_str() {
+                    "MatMul" => {
+                        let cols = node.weights.first().map(Vec::len).unwrap_or(0) as u64;
+                        let macs = dim.saturating_mul(cols);
+                        let words = macs.saturating_add(dim).saturating_add(cols);
+                        dim = cols;
+                        macs.saturating_mul(GAS_PER_MAC)
+                            .saturating_add(words.saturating_mul(GAS_PER_WORD))
+                    }
+                    // Reads the activations and the bias, writes the result.
+                    "Add" => dim * GAS_PER_ELEMENT + 3 * dim * GAS_PER_WORD,
+                    _ => dim * GAS_PER_ELEMENT + 2 * dim * GAS_PER_WORD,
+                };
+                OpGas {
+                    node: node.name.clone(),
+                    op_type: node.op_type.clone(),
+                    gas,
+                }
+            })
+            .collect()
+    }
+
+    fn validate(&self, config: &DeterminismConfig) -> Result<()> {
+        if self.opset != config.opset {
+            bail!(
+                "model targets opset {}, worker is pinned to {}",
+                self.opset,
+                config.opset
+            );
+        }
+        let mut dim = self.input_dim;
+        for node in &self.nodes {
+            if NONDETERMINISTIC_OPS.contains(&node.op_type.as_str())
+                && !config.allow_nondeterministic_ops
+            {
+                bail!(
+                    "node {} uses nondeterministic op {}",
+                    node.name,
+                    node.op_type
+                );
+            }
+            match node.op_type.as_str() {
+                "MatMul" => {
+                    if node.weights.len() != dim {
+                        bail!(
+                            "node {} expects {} rows, got {}",
+                            node.name,
+                            dim,
+                            node.weights.len()
+                        );
+                    }
+                    let cols = node.weights.first().map(Vec::len).unwrap_or(0);
+                    if cols == 0 || node.weights.iter().any(|row| row.len() != cols) {
+                        bail!("node {} has ragged or empty weights", node.name);
+                    }
+                    dim = cols;
+                }
+                "Add" => {
+                    if node.bias.len() != dim {
+                        bail!(
+                            "node {} bias length {} != {}",
+                            node.name,
+                            node.bias.len(),
+                            dim
+                        );
+                    }
+                }
+                "Relu" => {}
+                "BitShift" => {
+                    if node.shift >= 63 {
+                        bail!("node {} shift {} out of range", node.name, node.shift);
+                    }
+                }
+                other => bail!("unsupported op {other} in node {}", node.name),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Pure-Rust integer interpreter for the JSON graph format; see the module
+/// notes for how it relates to ONNX.
+pub struct ReferenceEngine {
+    config: DeterminismConfig,
+    models: HashMap<Vec<u8>, Graph>,
+}
+
+impl ReferenceEngine {
+    pub fn new(config: DeterminismConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            models: HashMap::new(),
+        })
+    }
+}
+
+impl Default for ReferenceEngine {
+    fn default() -> Self {
+        Self::new(DeterminismConfig::default()).expect("default config is deterministic")
+    }
+}
+
+impl InferenceEngine for ReferenceEngine {
+    fn name(&self) -> &str {
+        "reference"
+    }
+
+    fn load(&mut self, model_hash: &[u8], model_bytes: &[u8]) -> Result<()> {
+        let graph = Graph::from_bytes(model_bytes)?;
+        graph.validate(&self.config)?;
+        self.models.insert(model_hash.to_vec(), graph);
+        Ok(())
+    }
+
+    fn is_loaded(&self, model_hash: &[u8]) -> bool {
+        self.models.contains_key(model_hash)
+    }
+
+    fn run(&self, model_hash: &[u8], input: &[u8], gas_limit: u64) -> Result<InferenceOutput> {
+        let graph = self
+            .models
+            .get(model_hash)
+            .ok_or_else(|| anyhow::anyhow!("model {} not loaded", hex::encode(model_hash)))?;
+        if input.len() != graph.input_dim {
+            bail!(
+                "input has {} elements, model expects {}",
+                input.len(),
+                graph.input_dim
+            );
+        }
+
+        let op_gas = graph.op_costs();
+        let mut gas_used = BASE_GAS;
+        if gas_used > gas_limit {
+            bail!("out of gas: base cost {BASE_GAS} exceeds limit {gas_limit}");
+        }
+
+        let mut values: Vec<i64> = input.iter().map(|&b| b as i64).collect();
+        let mut activations = Vec::with_capacity(graph.nodes.len());
+        for (node, cost) in graph.nodes.iter().zip(&op_gas) {
+            gas_used = gas_used.saturating_add(cost.gas);
+            if gas_used > gas_limit {
+                bail!(
+                    "out of gas at node {}: {} needed, limit {}",
+                    node.name,
+                    gas_used,
+                    gas_limit
+                );
+            }
+            values = match node.op_type.as_str() {
+                "MatMul" => {
+                    let cols = node.weights[0].len();
+                    (0..cols)
+                        .map(|j| {
+                            values
+                                .iter()
+                                .zip(&node.weights)
+                                .fold(0i64, |acc, (v, row)| {
+                                    acc.saturating_add(v.saturating_mul(row[j] as i64))
+                                })
+                        })
+                        .collect()
+                }
+                "Add" => values
+                    .iter()
+                    .zip(&node.bias)
+                    .map(|(v, b)| v.saturating_add(*b as i64))
+                    .collect(),
+                "Relu" => values.iter().map(|v| (*v).max(0)).collect(),
+                "BitShift" => values.iter().map(|v| v >> node.shift).collect(),
+                other => bail!("unsupported op {other}"),
+            };
+            activations.push(LayerActivation {
+                node: node.name.clone(),
+                op_type: node.op_type.clone(),
+                values: values.clone(),
+            });
+        }
+
+        let output = values
+            .iter()
+            .map(|v| (*v).clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+            .flat_map(i32::to_le_bytes)
+            .collect();
+        Ok(InferenceOutput {
+            output,
+            activations,
+            op_gas,
+            gas_used,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn identity_graph(input_dim: usize) -> Graph {
+    Graph {
+        opset: PINNED_OPSET,
+        input_dim,
+        nodes: vec![GraphNode {
+            name: "relu".to_string(),
+            op_type: "Relu".to_string(),
+            weights: vec![],
+            bias: vec![],
+            shift: 0,
+        }],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(name: &str, op_type: &str) -> GraphNode {
+        GraphNode {
+            name: name.to_string(),
+            op_type: op_type.to_string(),
+            weights: vec![],
+            bias: vec![],
+            shift: 0,
+        }
+    }
+
+    fn mlp() -> Graph {
+        Graph {
+            opset: PINNED_OPSET,
+            input_dim: 2,
+            nodes: vec![
+                GraphNode {
+                    weights: vec![vec![1, -2, 3], vec![4, 5, -6]],
+                    ..node("fc1", "MatMul")
+                },
+                GraphNode {
+                    bias: vec![10, 0, -100],
+                    ..node("bias1", "Add")
+                },
+                node("act1", "Relu"),
+                GraphNode {
+                    shift: 1,
+                    ..node("requant", "BitShift")
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn runs_graph_and_captures_activations() {
+        let mut engine = ReferenceEngine::default();
+        engine.load(b"mlp", &mlp().to_bytes()).unwrap();
+
+        let out = engine.run(b"mlp", &[1, 2], u64::MAX).unwrap();
+        // fc1: [1+8, -2+10, 3-12] = [9, 8, -9]; bias: [19, 8, -109]; relu: [19, 8, 0]; >>1
+        assert_eq!(out.activations[0].values, vec![9, 8, -9]);
+        assert_eq!(out.activations[3].values, vec![9, 4, 0]);
+        let mut expected = Vec::new();
+        for v in [9i32, 4, 0] {
+            expected.extend_from_slice(&v.to_le_bytes());
+        }
+        assert_eq!(out.output, expected);
+        assert_eq!(engine.run(b"mlp", &[1, 2], u64::MAX).unwrap(), out);
+    }
+
+    #[test]
+    fn meters_gas_per_operator() {
+        let mut engine = ReferenceEngine::default();
+        engine.load(b"mlp", &mlp().to_bytes()).unwrap();
+        let out = engine.run(b"mlp", &[1, 2], u64::MAX).unwrap();
+
+        // MatMul 2x3: 6 MACs, 6 weights + 2 in + 3 out words.
+        assert_eq!(out.op_gas[0].gas, 6 * GAS_PER_MAC + 11 * GAS_PER_WORD);
+        // Add over 3 elements: reads input + bias, writes output.
+        assert_eq!(out.op_gas[1].gas, 3 * GAS_PER_ELEMENT + 9 * GAS_PER_WORD);
+        let total: u64 = out.op_gas.iter().map(|g| g.gas).sum();
+        assert_eq!(out.gas_used, BASE_GAS + total);
+
+        // One unit short aborts before the last node runs.
+        let err = engine
+            .run(b"mlp", &[1, 2], out.gas_used - 1)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("out of gas at node requant"), "{err}");
+        assert!(engine.run(b"mlp", &[1, 2], out.gas_used).is_ok());
+    }
+
+    #[test]
+    fn rejects_nondeterministic_and_wrong_opset() {
+        let mut engine = ReferenceEngine::default();
+        let mut graph = identity_graph(1);
+        graph.nodes.push(node("drop", "Dropout"));
+        assert!(engine.load(b"m", &graph.to_bytes()).is_err());
+
+        let mut graph = identity_graph(1);
+        graph.opset = 13;
+        assert!(engine.load(b"m", &graph.to_bytes()).is_err());
+    }
+
+    #[test]
+    fn rejects_multithreaded_config() {
+        let config = DeterminismConfig {
+            intra_op_threads: 4,
+            ..DeterminismConfig::default()
+        };
+        assert!(ReferenceEngine::new(config).is_err());
+    }
+
+    #[test]
+    fn unknown_model_and_bad_input_fail() {
+        let mut engine = ReferenceEngine::default();
+        assert!(engine.run(b"missing", &[1], u64::MAX).is_err());
+        engine.load(b"m", &identity_graph(3).to_bytes()).unwrap();
+        assert!(engine.run(b"m", &[1], u64::MAX).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+// ============================================================================
+// JOB JOURNAL - Crash-safe record of accepted jobs
+// ============================================================================
+// Every job is journaled before it runs and cleared only once the result
+// sink has acknowledged its outcome. The journal is an append-only JSON-lines
+// file, fsynced per record:
+//
+//   {"Accepted":{"job_id":..,"input_hash":..,"at":..}}   pulled from the source
+//   {"Started":{"job_id":..}}                            an attempt began
+//   {"Finished":{"job_id":..}}                           outcome acknowledged
+//
+// Inputs may be confidential, so only their SHA-256 is written to disk. On
+// restart, anything accepted but not finished is recovered: the worker fetches
+// the job again from its source and reruns it if the input still matches.
+// Inference is deterministic, so the rerun yields the same result. Jobs that
+// have already crashed the worker `max_attempts` times, or that are older
+// than `max_age`, are abandoned instead and reported as failed so the
+// coordinator can reassign them rather than waiting out the timeout.
+// ============================================================================
+
+use crate::InferenceJob;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct JournalConfig {
+    pub path: PathBuf,
+    /// Execution attempts before a recovered job is abandoned.
+    pub max_attempts: u32,
+    /// Recovered jobs accepted at least this long ago are abandoned.
+    pub max_age: Duration,
+}
+
+impl JournalConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            max_attempts: 2,
+            max_age: Duration::from_secs(600),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Record {
+    Accepted {
+        job_id: Vec<u8>,
+        input_hash: [u8; 32],
+        at: u64,
+    },
+    Started {
+        job_id: Vec<u8>,
+    },
+    Finished {
+        job_id: Vec<u8>,
+    },
+}
+
+/// What to do with a job found unfinished at startup.
+#[derive(Debug, Clone)]
+pub enum Recovery {
+    /// Fetch the job again and rerun it if its input still hashes to
+    /// `input_hash`.
+    Resume {
+        job_id: Vec<u8>,
+        input_hash: [u8; 32],
+    },
+    /// Report the job as failed; it stays pending until that is
+    /// acknowledged and [`JobJournal::finished`] is called.
+    Abandon { job_id: Vec<u8>, reason: String },
+}
+
+/// SHA-256 of a job's input, as journaled.
+pub fn input_hash(input: &[u8]) -> [u8; 32] {
+    Sha256::digest(input).into()
+}
+
+#[derive(Debug, Clone)]
+struct PendingJob {
+    job_id: Vec<u8>,
+    input_hash: [u8; 32],
+    accepted_at: u64,
+    attempts: u32,
+}
+
+pub struct JobJournal {
+    config: JournalConfig,
+    file: File,
+    /// Unfinished jobs in acceptance order.
+    pending: Vec<PendingJob>,
+}
+
+impl JobJournal {
+    /// Open (or create) the journal, replaying any previous run's records.
+    ///
+    /// The file is compacted to just the unfinished jobs, so it stays
+    /// proportional to the work in flight rather than the worker's uptime.
+    pub fn open(config: JournalConfig) -> Result<Self> {
+        if let Some(dir) = config.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let pending = replay(&config.path)?;
+        let file = compact(&config.path, &pending)?;
+        Ok(Self {
+            config,
+            file,
+            pending,
+        })
+    }
+
+    /// Decide, for each job left unfinished by the previous run, whether to
+    /// resume or abandon it. Nothing is cleared here: the caller marks each
+    /// job finished once its outcome has been acknowledged.
+    pub fn recover(&self) -> Vec<Recovery> {
+        let now = unix_now();
+        let mut recoveries = Vec::with_capacity(self.pending.len());
+        for pending in &self.pending {
+            let age = now.saturating_sub(pending.accepted_at);
+            let reason = if pending.attempts >= self.config.max_attempts {
+                Some(format!(
+                    "abandoned after {} interrupted attempts",
+                    pending.attempts
+                ))
+            } else if age >= self.config.max_age.as_secs() {
+                Some(format!("abandoned after restart: accepted {age}s ago"))
+            } else {
+                None
+            };
+            let job_id = pending.job_id.clone();
+            recoveries.push(match reason {
+                Some(reason) => Recovery::Abandon { job_id, reason },
+                None => Recovery::Resume {
+                    job_id,
+                    input_hash: pending.input_hash,
+                },
+            });
+        }
+        recoveries
+    }
+
+    pub fn accepted(&mut self, job: &InferenceJob) -> Result<()> {
+        let at = unix_now();
+        let input_hash = input_hash(&job.input_data);
+        self.append(&Record::Accepted {
+            job_id: job.job_id.clone(),
+            input_hash,
+            at,
+        })?;
+        self.pending.push(PendingJob {
+            job_id: job.job_id.clone(),
+            input_hash,
+            accepted_at: at,
+            attempts: 0,
+        });
+        Ok(())
+    }
+
+    pub fn started(&mut self, job_id: &[u8]) -> Result<()> {
+        self.append(&Record::Started {
+            job_id: job_id.to_vec(),
+        })?;
+        if let Some(pending) = self.pending.iter_mut().find(|p| p.job_id == job_id) {
+            pending.attempts += 1;
+        }
+        Ok(())
+    }
+
+    pub fn finished(&mut self, job_id: &[u8]) -> Result<()> {
+        self.append(&Record::Finished {
+            job_id: job_id.to_vec(),
+        })?;
+        self.pending.retain(|p| p.job_id != job_id);
+        Ok(())
+    }
+
+    /// Number of jobs accepted but not yet finished.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn append(&mut self, record: &Record) -> Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data().context("syncing job journal")
+    }
+}
+
+fn replay(path: &Path) -> Result<Vec<PendingJob>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e).with_context(|| format!("opening journal {}", path.display())),
+    };
+    let mut pending: Vec<PendingJob> = Vec::new();
+    let mut attempts: HashMap<Vec<u8>, u32> = HashMap::new();
+    for line in BufReader::new(file).lines() {
+        let line = line?;
+        // A crash mid-append leaves a torn final line; everything before it
+        // was fsynced and is intact.
+        let Ok(record) = serde_json::from_str::<Record>(&line) else {
+            break;
+        };
+        match record {
+            Record::Accepted {
+                job_id,
+                input_hash,
+                at,
+            } => {
+                attempts.remove(&job_id);
+                pending.retain(|p| p.job_id != job_id);
+                pending.push(PendingJob {
+                    job_id,
+                    input_hash,
+                    accepted_at: at,
+                    attempts: 0,
+                });
+            }
+            Record::Started { job_id } => *attempts.entry(job_id).or_default() += 1,
+            Record::Finished { job_id } => pending.retain(|p| p.job_id != job_id),
+        }
+    }
+    for pending in &mut pending {
+        pending.attempts = attempts.get(&pending.job_id).copied().unwrap_or(0);
+    }
+    Ok(pending)
+}
+
+/// Rewrite the journal with only `pending`, atomically, and reopen for append.
+fn compact(path: &Path, pending: &[PendingJob]) -> Result<File> {
+    let tmp = path.with_extension("compact");
+    {
+        let mut out = File::create(&tmp)?;
+        for job in pending {
+            let mut records = vec![Record::Accepted {
+                job_id: job.job_id.clone(),
+                input_hash: job.input_hash,
+                at: job.accepted_at,
+            }];
+            records.extend((0..job.attempts).map(|_| Record::Started {
+                job_id: job.job_id.clone(),
+            }));
+            for record in records {
+                let mut line = serde_json::to_vec(&record)?;
+                line.push(b'\n');
+                out.write_all(&line)?;
+            }
+        }
+        out.sync_all()?;
+    }
+    fs::rename(&tmp, path).with_context(|| format!("compacting journal {}", path.display()))?;
+    Ok(OpenOptions::new().append(true).open(path)?)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: u8) -> InferenceJob {
+        InferenceJob {
+            job_id: vec![id],
+            model_hash: b"model".to_vec(),
+            input_data: vec![id; 2],
+            gas_limit: 1_000_000,
+            seed: 7,
+            requester_key: Some([id; 32]),
+            slot_hash: Vec::new(),
+        }
+    }
+
+    fn resumed(recoveries: &[Recovery]) -> Vec<Vec<u8>> {
+        recoveries
+            .iter()
+            .filter_map(|r| match r {
+                Recovery::Resume { job_id, .. } => Some(job_id.clone()),
+                Recovery::Abandon { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unfinished_jobs_survive_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = JournalConfig::new(dir.path().join("jobs.journal"));
+        {
+            let mut journal = JobJournal::open(config.clone()).unwrap();
+            for id in 1..=3 {
+                journal.accepted(&job(id)).unwrap();
+                journal.started(&[id]).unwrap();
+            }
+            journal.finished(&[2]).unwrap();
+        }
+
+        let journal = JobJournal::open(config.clone()).unwrap();
+        assert_eq!(journal.pending(), 2);
+        let recoveries = journal.recover();
+        assert_eq!(resumed(&recoveries), vec![vec![1], vec![3]]);
+        let Recovery::Resume {
+            input_hash: hash, ..
+        } = &recoveries[0]
+        else {
+            unreachable!()
+        };
+        assert_eq!(*hash, input_hash(&job(1).input_data));
+
+        // Only ids and hashes reach the disk, never the input itself.
+        let raw = fs::read_to_string(&config.path).unwrap();
+        assert!(!raw.contains("input_data") && !raw.contains("requester_key"));
+    }
+
+    #[test]
+    fn crash_looping_and_stale_jobs_are_abandoned() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = JournalConfig::new(dir.path().join("jobs.journal"));
+        {
+            let mut journal = JobJournal::open(config.clone()).unwrap();
+            journal.accepted(&job(1)).unwrap();
+            journal.started(&[1]).unwrap();
+            journal.started(&[1]).unwrap();
+            journal.accepted(&job(2)).unwrap();
+        }
+
+        let mut journal = JobJournal::open(config.clone()).unwrap();
+        let recoveries = journal.recover();
+        assert!(matches!(&recoveries[0], Recovery::Abandon { job_id, .. } if job_id == &[1]));
+        assert_eq!(resumed(&recoveries), vec![vec![2]]);
+        // Abandoned jobs stay pending until their failure is acknowledged.
+        assert_eq!(journal.pending(), 2);
+        journal.finished(&[1]).unwrap();
+        drop(journal);
+
+        config.max_age = Duration::ZERO;
+        let journal = JobJournal::open(config.clone()).unwrap();
+        assert!(resumed(&journal.recover()).is_empty());
+        assert_eq!(JobJournal::open(config).unwrap().pending(), 1);
+    }
+
+    #[test]
+    fn torn_tail_is_ignored() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = JournalConfig::new(dir.path().join("jobs.journal"));
+        {
+            let mut journal = JobJournal::open(config.clone()).unwrap();
+            journal.accepted(&job(1)).unwrap();
+        }
+        let mut file = OpenOptions::new().append(true).open(&config.path).unwrap();
+        file.write_all(b"{\"Finished\":{\"job_id\":[1").unwrap();
+
+        let journal = JobJournal::open(config.clone()).unwrap();
+        assert_eq!(journal.pending(), 1);
+        // Compaction dropped the torn record, so new appends parse cleanly.
+        let raw = fs::read_to_string(&config.path).unwrap();
+        assert!(raw
+            .lines()
+            .all(|l| serde_json::from_str::<Record>(l).is_ok()));
+    }
+}
@@ -5,7 +5,7 @@
 //
 // ARCHITECTURE:
 // - Runs in TEE (SEV-SNP/TDX/Nitro)
-// - Deterministic ONNX runtime
+// - Deterministic integer graph interpreter (see `engine`)
 // - Generates VCR for each inference
 // - Submits results on-chain
 //
@@ -20,7 +20,7 @@
 // 8. Receive AIC payment
 //
 // DETERMINISM:
-// - Fixed ONNX runtime version
+// - Pinned graph opset
 // - Disable non-deterministic ops
 // - Seed all RNGs
 // - No system calls during inference
@@ -33,8 +33,42 @@
 // - Attestation proves code integrity
 // ============================================================================
 
+pub mod benchmark;
+pub mod cache;
+pub mod engine;
+pub mod journal;
+#[cfg(feature = "onnx")]
+pub mod onnx;
+pub mod output;
+pub mod runner;
+pub mod sandbox;
+pub mod tee;
+pub mod trace;
+
+use aether_crypto_kzg::{ChunkOpening, ChunkedCommitment};
+use aether_verifiers_kzg::{KzgChallenge, KzgOpeningResponse};
 use anyhow::{bail, Result};
+use benchmark::{BenchmarkConfig, CapabilityReport};
+use cache::{CacheStats, ModelCache, ModelSource, DEFAULT_CACHE_BUDGET_BYTES};
+use engine::{InferenceEngine, InferenceOutput, LayerActivation, OpGas, ReferenceEngine};
+use journal::{JobJournal, Recovery};
+use output::ResultStore;
+pub use runner::{CapabilityReporter, JobOutcome, JobSource, ResultSink, WorkerStats};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+use tee::{backend_from_config, AttestationContext, Attester, TeeBackend};
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+use trace::{LayerCommitment, TraceStore};
+
+/// How long the loop waits before re-polling an idle job source.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// How long outcomes the sink rejected wait before being submitted again.
+const RESUBMIT_INTERVAL: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkerConfig {
@@ -44,132 +78,555 @@ pub struct WorkerConfig {
     pub max_concurrent_jobs: usize,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InferenceJob {
     pub job_id: Vec<u8>,
     pub model_hash: Vec<u8>,
     pub input_data: Vec<u8>,
     pub gas_limit: u64,
+    /// Seed fixed by the requester, bound into the attestation.
+    pub seed: u64,
+    /// Requester's X25519 key; when set the output is sealed to it and only
+    /// its hash leaves the worker in the result.
+    pub requester_key: Option<[u8; 32]>,
+    /// Recent slot hash the job was assigned under, bound into the
+    /// attestation so validators can bound the quote's age.
+    #[serde(default)]
+    pub slot_hash: Vec<u8>,
 }
 
 #[derive(Debug, Clone)]
 pub struct InferenceResult {
     pub job_id: Vec<u8>,
+    /// Plaintext output; empty when it was sealed to the requester.
     pub output_data: Vec<u8>,
+    /// SHA-256 of the plaintext output, published on chain.
+    pub output_hash: [u8; 32],
+    /// Flattened activations; empty when the output was sealed, since they
+    /// reveal it. The trace store still holds them for challenges.
     pub execution_trace: Vec<u8>,
+    /// Per-layer activations; empty when the output was sealed.
+    pub activations: Vec<LayerActivation>,
     pub gas_used: u64,
+    /// Per-operator charges making up `gas_used`, reported with the VCR.
+    pub gas_breakdown: Vec<OpGas>,
+    /// JSON `AttestationReport` bound to this job, as carried in the VCR.
+    pub tee_attestation: Vec<u8>,
+    /// KZG commitments to the selected layers (empty without a trace store).
+    pub trace_commitments: Vec<LayerCommitment>,
+    /// Chunked commitment to the flattened selected layers, whose root is
+    /// the VCR's trace root (`None` without a trace store).
+    pub trace_root: Option<ChunkedCommitment>,
 }
 
 pub struct AiWorker {
     config: WorkerConfig,
-    running: bool,
+    running: Arc<AtomicBool>,
+    shutdown: Arc<Notify>,
+    executor: JobExecutor,
+    benchmark: BenchmarkConfig,
+    /// Receives a fresh `CapabilityReport` at startup and every refresh.
+    reporter: Option<tokio::sync::Mutex<Box<dyn CapabilityReporter>>>,
+    capabilities: Arc<RwLock<Option<CapabilityReport>>>,
+    /// Jobs accepted but not yet submitted, persisted across restarts.
+    journal: Option<Mutex<JobJournal>>,
 }
 
 impl AiWorker {
     pub fn new(config: WorkerConfig) -> Self {
+        Self::with_engine(config, Box::new(ReferenceEngine::default()))
+    }
+
+    pub fn with_engine(config: WorkerConfig, engine: Box<dyn InferenceEngine>) -> Self {
+        let cache = ModelCache::new(&config.model_cache_dir, DEFAULT_CACHE_BUDGET_BYTES);
+        let attester = backend_from_config(&config.tee_type)
+            .ok()
+            .map(|backend| Arc::new(Attester::new(backend)));
         AiWorker {
             config,
-            running: false,
+            running: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(Notify::new()),
+            executor: JobExecutor {
+                engine: Arc::new(RwLock::new(engine)),
+                cache: Arc::new(cache),
+                attester,
+                traces: None,
+                results: ResultStore::default(),
+            },
+            benchmark: BenchmarkConfig::default(),
+            reporter: None,
+            capabilities: Arc::new(RwLock::new(None)),
+            journal: None,
+        }
+    }
+
+    /// Replace the model cache (e.g. to change its size budget).
+    pub fn with_model_cache(mut self, cache: ModelCache) -> Self {
+        self.executor.cache = Arc::new(cache);
+        self
+    }
+
+    /// Attest with `backend` instead of the one named by `tee_type`.
+    pub fn with_tee_backend(mut self, backend: Box<dyn TeeBackend>) -> Self {
+        self.executor.attester = Some(Arc::new(Attester::new(backend)));
+        self
+    }
+
+    /// Benchmark at startup and every `config.refresh_interval` while the
+    /// job loop runs, sending each result to `reporter`.
+    pub fn with_capability_reporter(
+        mut self,
+        reporter: Box<dyn CapabilityReporter>,
+        config: BenchmarkConfig,
+    ) -> Self {
+        self.reporter = Some(tokio::sync::Mutex::new(reporter));
+        self.benchmark = config;
+        self
+    }
+
+    /// Journal jobs so a restart resumes or abandons the ones left in flight.
+    pub fn with_journal(mut self, journal: JobJournal) -> Self {
+        self.journal = Some(Mutex::new(journal));
+        self
+    }
+
+    /// Commit to execution traces and keep them for challenges.
+    pub fn with_trace_store(mut self, store: TraceStore) -> Self {
+        self.executor.traces = Some(Arc::new(store));
+        self
+    }
+
+    /// Answer a KZG challenge against a trace this worker committed to.
+    pub fn respond_to_challenge(&self, challenge: &KzgChallenge) -> Result<KzgOpeningResponse> {
+        let Some(traces) = &self.executor.traces else {
+            bail!("worker has no trace store configured");
+        };
+        traces.respond_to_challenge(challenge)
+    }
+
+    /// Open a value of a committed trace against the VCR's trace root.
+    pub fn open_trace_value(&self, job_id: &[u8], index: u64) -> Result<ChunkOpening> {
+        let Some(traces) = &self.executor.traces else {
+            bail!("worker has no trace store configured");
+        };
+        traces.open_trace_value(job_id, index)
+    }
+
+    /// Sealed outputs awaiting retrieval; serve with `output::results_app`.
+    pub fn result_store(&self) -> ResultStore {
+        self.executor.results.clone()
+    }
+
+    /// Add a source that models missing from the cache are fetched from.
+    pub fn add_model_source(&mut self, source: Box<dyn ModelSource>) {
+        self.executor.cache.add_source(source);
+    }
+
+    pub fn cache_stats(&self) -> CacheStats {
+        self.executor.cache.stats()
+    }
+
+    /// Run the benchmark now and remember the result.
+    pub fn benchmark(&self) -> Result<CapabilityReport> {
+        let report = self.executor.benchmark(&self.config, &self.benchmark)?;
+        self.remember_capabilities(&report);
+        Ok(report)
+    }
+
+    /// The most recent capability report, if a benchmark has run.
+    pub fn capabilities(&self) -> Option<CapabilityReport> {
+        self.capabilities
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn remember_capabilities(&self, report: &CapabilityReport) {
+        *self.capabilities.write().unwrap_or_else(|e| e.into_inner()) = Some(report.clone());
+    }
+
+    /// Load model bytes into the inference engine under `model_hash`.
+    pub fn install_model(&mut self, model_hash: &[u8], model_bytes: &[u8]) -> Result<()> {
+        if model_hash.is_empty() {
+            bail!("empty model hash");
         }
+        self.executor.engine_mut().load(model_hash, model_bytes)
     }
 
-    /// Start worker loop
-    pub async fn start(&mut self) -> Result<()> {
-        println!(
-            "Starting AI worker: {:?}",
-            hex::encode(&self.config.worker_id)
+    /// Run the job loop until `stop()` is called or the source is exhausted.
+    ///
+    /// Pulls assignments from `source` whenever a slot is free, runs up to
+    /// `max_concurrent_jobs` inferences on the blocking pool, and hands every
+    /// outcome to `sink`. After `stop()` no new jobs are pulled, but jobs
+    /// already in flight are finished and submitted before returning.
+    ///
+    /// With a journal, jobs a previous run left unfinished are fetched again
+    /// from `source` and resumed before new ones are pulled, or reported to
+    /// `sink` as failed if they are abandoned.
+    pub async fn start<S, R>(&self, source: &mut S, sink: &mut R) -> Result<WorkerStats>
+    where
+        S: JobSource,
+        R: ResultSink,
+    {
+        tracing::info!(
+            worker_id = %hex::encode(&self.config.worker_id),
+            "starting AI worker"
         );
-        self.running = true;
+        self.running.store(true, Ordering::SeqCst);
+        let result = self.run_loop(source, sink).await;
+        self.running.store(false, Ordering::SeqCst);
+        result
+    }
+
+    async fn run_loop<S, R>(&self, source: &mut S, sink: &mut R) -> Result<WorkerStats>
+    where
+        S: JobSource,
+        R: ResultSink,
+    {
+        let max_in_flight = self.config.max_concurrent_jobs.max(1);
+        let mut in_flight: JoinSet<JobOutcome> = JoinSet::new();
+        let mut stats = WorkerStats::default();
+        let mut draining = false;
+        let mut next_report = self.reporter.as_ref().map(|_| Instant::now());
+        let mut unsubmitted = VecDeque::new();
+        let mut next_resubmit = Instant::now() + RESUBMIT_INTERVAL;
+
+        let mut resumed = VecDeque::new();
+        let recoveries = match &self.journal {
+            Some(journal) => lock(journal).recover(),
+            None => Vec::new(),
+        };
+        for recovery in recoveries {
+            let (job_id, reason) = match recovery {
+                Recovery::Resume { job_id, input_hash } => match source.refetch(&job_id).await? {
+                    Some(job)
+                        if job.job_id == job_id
+                            && journal::input_hash(&job.input_data) == input_hash =>
+                    {
+                        resumed.push_back(job);
+                        continue;
+                    }
+                    Some(_) => (
+                        job_id,
+                        "input changed since the job was accepted".to_string(),
+                    ),
+                    None => (job_id, "job no longer available to resume".to_string()),
+                },
+                Recovery::Abandon { job_id, reason } => (job_id, reason),
+            };
+            stats.abandoned += 1;
+            let outcome = JobOutcome {
+                job_id,
+                result: Err(reason),
+            };
+            self.deliver(sink, outcome, &mut unsubmitted).await?;
+        }
+        stats.resumed = resumed.len() as u64;
 
-        // In production:
-        // 1. Verify we're in TEE
-        // 2. Generate attestation
-        // 3. Register on-chain
-        // 4. Start job polling loop
+        loop {
+            if !self.running.load(Ordering::SeqCst) {
+                draining = true;
+            }
+            if draining && in_flight.is_empty() {
+                break;
+            }
 
+            if !unsubmitted.is_empty() && Instant::now() >= next_resubmit {
+                for outcome in std::mem::take(&mut unsubmitted) {
+                    self.deliver(sink, outcome, &mut unsubmitted).await?;
+                }
+                next_resubmit = Instant::now() + RESUBMIT_INTERVAL;
+            }
+
+            if !draining && next_report.is_some_and(|due| Instant::now() >= due) {
+                self.refresh_capabilities().await;
+                next_report = Some(Instant::now() + self.benchmark.refresh_interval);
+            }
+
+            if !draining && in_flight.len() < max_in_flight {
+                let free = max_in_flight - in_flight.len();
+                let jobs: Vec<InferenceJob> = if !resumed.is_empty() {
+                    resumed.drain(..free.min(resumed.len())).collect()
+                } else {
+                    match source.next_jobs(free).await? {
+                        Some(jobs) => {
+                            if let Some(journal) = &self.journal {
+                                let mut journal = lock(journal);
+                                for job in &jobs {
+                                    journal.accepted(job)?;
+                                }
+                            }
+                            stats.accepted += jobs.len() as u64;
+                            jobs
+                        }
+                        None => {
+                            // Re-check for exit: with nothing in flight the
+                            // select below would only wait on `stop()`.
+                            draining = true;
+                            continue;
+                        }
+                    }
+                };
+                for job in jobs {
+                    if let Some(journal) = &self.journal {
+                        lock(journal).started(&job.job_id)?;
+                    }
+                    let executor = self.executor.clone();
+                    in_flight.spawn_blocking(move || JobOutcome {
+                        job_id: job.job_id.clone(),
+                        result: executor.execute(&job).map_err(|e| e.to_string()),
+                    });
+                }
+            }
+
+            tokio::select! {
+                Some(joined) = in_flight.join_next(), if !in_flight.is_empty() => {
+                    let outcome = joined.map_err(|e| anyhow::anyhow!("job task panicked: {e}"))?;
+                    if outcome.result.is_ok() {
+                        stats.succeeded += 1;
+                    } else {
+                        stats.failed += 1;
+                    }
+                    self.deliver(sink, outcome, &mut unsubmitted).await?;
+                }
+                _ = self.shutdown.notified() => draining = true,
+                _ = tokio::time::sleep(POLL_INTERVAL),
+                    if !draining && in_flight.len() < max_in_flight => {}
+            }
+        }
+
+        // One last try; anything still rejected stays journaled and is rerun
+        // by the next start.
+        for outcome in std::mem::take(&mut unsubmitted) {
+            self.deliver(sink, outcome, &mut unsubmitted).await?;
+        }
+        if !unsubmitted.is_empty() {
+            tracing::warn!(
+                count = unsubmitted.len(),
+                "exiting with outcomes the result sink never accepted"
+            );
+        }
+
+        Ok(stats)
+    }
+
+    /// Hand `outcome` to `sink` and clear its job from the journal once the
+    /// sink accepts it. A rejected outcome is logged and queued on
+    /// `unsubmitted` for another try instead of stopping the worker; its job
+    /// stays journaled until then.
+    async fn deliver<R: ResultSink>(
+        &self,
+        sink: &mut R,
+        outcome: JobOutcome,
+        unsubmitted: &mut VecDeque<JobOutcome>,
+    ) -> Result<()> {
+        let job_id = outcome.job_id.clone();
+        match sink.submit(outcome.clone()).await {
+            Ok(()) => {
+                if let Some(journal) = &self.journal {
+                    lock(journal).finished(&job_id)?;
+                }
+            }
+            Err(e) => {
+                tracing::warn!(
+                    job_id = %hex::encode(&job_id),
+                    "result submission failed, will retry: {e:#}"
+                );
+                unsubmitted.push_back(outcome);
+            }
+        }
         Ok(())
     }
 
-    /// Stop worker
-    pub fn stop(&mut self) {
-        self.running = false;
+    /// Benchmark on the blocking pool and publish the report. Failures are
+    /// logged rather than fatal: the previous report stays in effect.
+    async fn refresh_capabilities(&self) {
+        let Some(reporter) = &self.reporter else {
+            return;
+        };
+        let executor = self.executor.clone();
+        let (config, benchmark) = (self.config.clone(), self.benchmark.clone());
+        let report = tokio::task::spawn_blocking(move || executor.benchmark(&config, &benchmark))
+            .await
+            .map_err(|e| anyhow::anyhow!("benchmark task panicked: {e}"))
+            .and_then(|report| report);
+        let result = match report {
+            Ok(report) => {
+                self.remember_capabilities(&report);
+                reporter.lock().await.report(report).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("capability refresh failed: {e:#}");
+        }
+    }
+
+    /// Stop worker: stop pulling new jobs and drain the ones in flight.
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        self.shutdown.notify_one();
     }
 
     /// Execute inference job
     pub fn execute_job(&self, job: &InferenceJob) -> Result<InferenceResult> {
+        self.executor.execute(job)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Shared handle to the inference engine, cheap to clone into job tasks.
+#[derive(Clone)]
+struct JobExecutor {
+    engine: Arc<RwLock<Box<dyn InferenceEngine>>>,
+    /// Locks itself; downloads don't block other jobs' lookups.
+    cache: Arc<ModelCache>,
+    /// `None` when `tee_type` names no supported TEE; every job then fails.
+    attester: Option<Arc<Attester>>,
+    traces: Option<Arc<TraceStore>>,
+    results: ResultStore,
+}
+
+impl JobExecutor {
+    fn engine(&self) -> RwLockReadGuard<'_, Box<dyn InferenceEngine>> {
+        self.engine.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn engine_mut(&self) -> RwLockWriteGuard<'_, Box<dyn InferenceEngine>> {
+        self.engine.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn benchmark(
+        &self,
+        worker: &WorkerConfig,
+        config: &BenchmarkConfig,
+    ) -> Result<CapabilityReport> {
+        benchmark::load_shapes(self.engine_mut().as_mut(), &config.shapes)?;
+        let engine = self.engine();
+        let shapes = benchmark::measure(engine.as_ref(), config)?;
+        Ok(benchmark::build_report(
+            &worker.worker_id,
+            engine.name(),
+            &worker.tee_type,
+            worker.max_concurrent_jobs,
+            config,
+            shapes,
+        ))
+    }
+
+    fn execute(&self, job: &InferenceJob) -> Result<InferenceResult> {
         // 1. Load model (verify hash)
         self.load_model(&job.model_hash)?;
 
-        // 2. Run deterministic inference
-        let output = self.run_inference(&job.input_data)?;
+        // 2. Run deterministic inference, metered against the job's gas limit
+        let output = self.run_inference(&job.model_hash, &job.input_data, job.gas_limit)?;
 
         // 3. Generate execution trace
-        let trace = self.generate_trace()?;
+        let trace = self.generate_trace(&output.activations)?;
+
+        // 4. Commit to the trace and keep it for the challenge window
+        let (trace_commitments, trace_root) = match &self.traces {
+            Some(traces) => {
+                let committed = traces.commit(&job.job_id, &output.activations)?;
+                (committed.layers, Some(committed.root))
+            }
+            None => (Vec::new(), None),
+        };
+
+        // 5. Attest, binding the quote to this job
+        let tee_attestation = self.attest(job)?;
 
-        // 4. Calculate gas used
-        let gas_used = self.calculate_gas(&trace);
+        // 6. Seal the output to the requester, keeping only its hash public.
+        // Activations would leak the output, so they stay in the worker too.
+        let output_hash: [u8; 32] = Sha256::digest(&output.output).into();
+        let (output_data, execution_trace, activations) = match &job.requester_key {
+            Some(key) => {
+                let sealed = output::seal(key, &job.job_id, &output.output)?;
+                self.results.insert(job.job_id.clone(), sealed);
+                (Vec::new(), Vec::new(), Vec::new())
+            }
+            None => (output.output, trace, output.activations),
+        };
 
         Ok(InferenceResult {
             job_id: job.job_id.clone(),
-            output_data: output,
-            execution_trace: trace,
-            gas_used,
+            output_data,
+            output_hash,
+            execution_trace,
+            activations,
+            gas_used: output.gas_used,
+            gas_breakdown: output.op_gas,
+            tee_attestation,
+            trace_commitments,
+            trace_root,
         })
     }
 
     fn load_model(&self, model_hash: &[u8]) -> Result<()> {
-        // In production:
-        // 1. Check cache
-        // 2. Download if missing
-        // 3. Verify hash
-        // 4. Load into ONNX runtime
-
         if model_hash.is_empty() {
             bail!("empty model hash");
         }
+        if self.engine().is_loaded(model_hash) {
+            return Ok(());
+        }
 
-        Ok(())
+        // Cached files are checked against the SHA-256 model hash when
+        // scanned and on first load, and misses when fetched, before any
+        // bytes reach the engine.
+        let bytes = self
+            .cache
+            .get(model_hash)
+            .map_err(|e| anyhow::anyhow!("model {} not loaded: {e}", hex::encode(model_hash)))?;
+        self.engine_mut().load(model_hash, &bytes)
     }
 
-    fn run_inference(&self, input: &[u8]) -> Result<Vec<u8>> {
-        // In production: Use ONNX Runtime
-        // - Set deterministic mode
-        // - Disable GPU (non-deterministic)
-        // - Run inference
-        // - Return output tensor
-
+    fn run_inference(
+        &self,
+        model_hash: &[u8],
+        input: &[u8],
+        gas_limit: u64,
+    ) -> Result<InferenceOutput> {
         if input.is_empty() {
             bail!("empty input");
         }
 
-        // Simulate inference
-        let output = vec![42u8; 128]; // Placeholder
-
-        Ok(output)
+        self.engine().run(model_hash, input, gas_limit)
     }
 
-    fn generate_trace(&self) -> Result<Vec<u8>> {
-        // In production:
-        // 1. Capture ops executed
-        // 2. Record intermediate values
-        // 3. Compress trace
-        // 4. Return as polynomial coefficients
-
-        let trace = vec![1u8; 256]; // Placeholder
+    fn generate_trace(&self, activations: &[LayerActivation]) -> Result<Vec<u8>> {
+        // Flatten per-layer activations in graph order; each value is an
+        // 8-byte little-endian word so it maps directly onto field elements.
+        let trace: Vec<u8> = activations
+            .iter()
+            .flat_map(|layer| layer.values.iter())
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        if trace.is_empty() {
+            bail!("model produced no activations");
+        }
 
         Ok(trace)
     }
 
-    fn calculate_gas(&self, trace: &[u8]) -> u64 {
-        // Gas = base + per_op * num_ops + per_byte * trace_size
-        const BASE_GAS: u64 = 1000;
-        const GAS_PER_BYTE: u64 = 10;
-
-        BASE_GAS + (trace.len() as u64 * GAS_PER_BYTE)
-    }
-
-    pub fn is_running(&self) -> bool {
-        self.running
+    fn attest(&self, job: &InferenceJob) -> Result<Vec<u8>> {
+        let Some(attester) = &self.attester else {
+            bail!("unsupported TEE type; refusing to produce unattested results");
+        };
+        let report = attester.attest(&AttestationContext {
+            job_id: job.job_id.clone(),
+            input_hash: Sha256::digest(&job.input_data).into(),
+            model_hash: job.model_hash.clone(),
+            slot_hash: job.slot_hash.clone(),
+            seed: job.seed,
+        })?;
+        Ok(serde_json::to_vec(&report)?)
     }
 }
 
@@ -197,14 +654,20 @@ mod tests {
     #[test]
     fn test_execute_job() {
         let config = test_config();
-        let worker = AiWorker::new(config);
+        let mut worker = AiWorker::new(config);
 
         let job = InferenceJob {
             job_id: vec![1, 2, 3],
             model_hash: vec![4, 5, 6],
             input_data: vec![7, 8, 9],
             gas_limit: 100_000,
+            seed: 0,
+            requester_key: None,
+            slot_hash: Vec::new(),
         };
+        worker
+            .install_model(&job.model_hash, &engine::identity_graph(3).to_bytes())
+            .unwrap();
 
         let result = worker.execute_job(&job).unwrap();
 
@@ -212,6 +675,183 @@ mod tests {
         assert!(!result.output_data.is_empty());
         assert!(!result.execution_trace.is_empty());
         assert!(result.gas_used > 0);
+        assert_eq!(
+            result.output_data,
+            [7i32, 8, 9].map(i32::to_le_bytes).concat()
+        );
+
+        let report: aether_verifiers_tee::AttestationReport =
+            serde_json::from_slice(&result.tee_attestation).unwrap();
+        let expected = aether_verifiers_tee::ReportDataBinding {
+            job_id: &job.job_id,
+            input_hash: &Sha256::digest(&job.input_data),
+            model_hash: &job.model_hash,
+            code_hash: &Attester::new(Box::new(tee::SimulatedBackend::new())).code_hash(),
+            slot_hash: &job.slot_hash,
+            seed: job.seed,
+        }
+        .report_data();
+        assert_eq!(report.nonce, expected.to_vec());
+    }
+
+    #[test]
+    fn test_commits_trace_and_answers_challenge() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(
+            trace::TraceConfig {
+                store_dir: dir.path().to_path_buf(),
+                layers: vec![],
+                retention_secs: 600,
+            },
+            Arc::new(aether_crypto_kzg::KzgVerifier::new_insecure_test(8)),
+        )
+        .unwrap();
+        let mut worker = AiWorker::new(test_config()).with_trace_store(store);
+        let job = InferenceJob {
+            job_id: vec![5u8; 32],
+            model_hash: vec![1],
+            input_data: vec![3, 4],
+            gas_limit: 100_000,
+            seed: 0,
+            requester_key: None,
+            slot_hash: Vec::new(),
+        };
+        worker
+            .install_model(&job.model_hash, &engine::identity_graph(2).to_bytes())
+            .unwrap();
+
+        let result = worker.execute_job(&job).unwrap();
+        assert_eq!(result.trace_commitments.len(), 1);
+        let root = result.trace_root.as_ref().unwrap();
+        let opening = worker.open_trace_value(&job.job_id, 1).unwrap();
+        let kzg = aether_crypto_kzg::KzgVerifier::new_insecure_test(8);
+        aether_crypto_kzg::verify_chunk_opening(
+            &kzg,
+            &root.root,
+            root.len,
+            root.chunk_size,
+            &opening,
+        )
+        .unwrap();
+
+        let challenge = KzgChallenge {
+            vcr_id: aether_types::H256::from_slice(&job.job_id).unwrap(),
+            layer_indices: vec![0],
+            point_indices: vec![vec![1]],
+            deadline_slot: 1,
+        };
+        let response = worker.respond_to_challenge(&challenge).unwrap();
+        assert_eq!(
+            response.openings[0].commitment.commitment,
+            result.trace_commitments[0].commitment.commitment
+        );
+    }
+
+    #[test]
+    fn test_output_sealed_to_requester() {
+        let secret = x25519_dalek::StaticSecret::random_from_rng(rand::rngs::OsRng);
+        let public = x25519_dalek::PublicKey::from(&secret);
+        let mut worker = AiWorker::new(test_config());
+        let job = InferenceJob {
+            job_id: vec![8],
+            model_hash: vec![1],
+            input_data: vec![3, 4],
+            gas_limit: 100_000,
+            seed: 0,
+            requester_key: Some(*public.as_bytes()),
+            slot_hash: Vec::new(),
+        };
+        worker
+            .install_model(&job.model_hash, &engine::identity_graph(2).to_bytes())
+            .unwrap();
+
+        let result = worker.execute_job(&job).unwrap();
+        assert!(result.output_data.is_empty());
+        assert!(result.execution_trace.is_empty());
+        assert!(result.activations.is_empty());
+
+        let sealed = worker.result_store().get(&job.job_id).unwrap();
+        let plaintext = output::open(&secret.to_bytes(), &job.job_id, &sealed).unwrap();
+        assert_eq!(plaintext, [3i32, 4].map(i32::to_le_bytes).concat());
+        assert_eq!(
+            result.output_hash,
+            <[u8; 32]>::from(Sha256::digest(&plaintext))
+        );
+    }
+
+    #[test]
+    fn test_unknown_tee_type_refuses_jobs() {
+        let mut worker = AiWorker::new(WorkerConfig {
+            tee_type: "sgx-v0".to_string(),
+            ..test_config()
+        });
+        worker
+            .install_model(&[1], &engine::identity_graph(1).to_bytes())
+            .unwrap();
+        let job = InferenceJob {
+            job_id: vec![1],
+            model_hash: vec![1],
+            input_data: vec![1],
+            gas_limit: 100_000,
+            seed: 0,
+            requester_key: None,
+            slot_hash: Vec::new(),
+        };
+        assert!(worker.execute_job(&job).is_err());
+    }
+
+    #[test]
+    fn test_unloaded_model_fails() {
+        let worker = AiWorker::new(test_config());
+        let job = InferenceJob {
+            job_id: vec![1],
+            model_hash: vec![9],
+            input_data: vec![1],
+            gas_limit: 100_000,
+            seed: 0,
+            requester_key: None,
+            slot_hash: Vec::new(),
+        };
+        assert!(worker.execute_job(&job).is_err());
+    }
+
+    #[test]
+    fn test_fetches_model_through_cache() {
+        use sha2::{Digest, Sha256};
+
+        let mirror = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let model = engine::identity_graph(2).to_bytes();
+        let model_hash = Sha256::digest(&model).to_vec();
+        std::fs::write(
+            mirror
+                .path()
+                .join(format!("{}.model", hex::encode(&model_hash))),
+            &model,
+        )
+        .unwrap();
+
+        let mut worker = AiWorker::new(WorkerConfig {
+            model_cache_dir: cache_dir.path().display().to_string(),
+            ..test_config()
+        });
+        worker.add_model_source(Box::new(cache::LocalDirSource::new(mirror.path())));
+
+        let job = InferenceJob {
+            job_id: vec![1],
+            model_hash,
+            input_data: vec![3, 4],
+            gas_limit: 100_000,
+            seed: 0,
+            requester_key: None,
+            slot_hash: Vec::new(),
+        };
+        worker.execute_job(&job).unwrap();
+        worker.execute_job(&job).unwrap();
+
+        let stats = worker.cache_stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.bytes_cached, model.len() as u64);
     }
 }
 
@@ -242,16 +882,38 @@ mod proptests {
             prop::collection::vec(any::<u8>(), 1..128),
             prop::collection::vec(any::<u8>(), 1..128),
             prop::collection::vec(any::<u8>(), 1..1024),
-            1u64..=1_000_000,
+            // Enough for the identity model on any generated input.
+            5_000u64..=1_000_000,
         )
             .prop_map(|(job_id, model_hash, input_data, gas_limit)| InferenceJob {
                 job_id,
                 model_hash,
                 input_data,
                 gas_limit,
+                seed: 0,
+                requester_key: None,
+                slot_hash: Vec::new(),
             })
     }
 
+    /// Worker with an identity model installed for `job`'s hash and input size.
+    fn worker_for(job: &InferenceJob) -> AiWorker {
+        let config = WorkerConfig {
+            worker_id: vec![1],
+            tee_type: "sim".to_string(),
+            model_cache_dir: "/tmp".to_string(),
+            max_concurrent_jobs: 1,
+        };
+        let mut worker = AiWorker::new(config);
+        worker
+            .install_model(
+                &job.model_hash,
+                &crate::engine::identity_graph(job.input_data.len()).to_bytes(),
+            )
+            .unwrap();
+        worker
+    }
+
     proptest! {
         /// Worker starts in non-running state.
         #[test]
@@ -263,7 +925,7 @@ mod proptests {
         /// Stop always puts the worker in non-running state.
         #[test]
         fn stop_sets_not_running(config in arb_config()) {
-            let mut worker = AiWorker::new(config);
+            let worker = AiWorker::new(config);
             worker.stop();
             prop_assert!(!worker.is_running());
         }
@@ -271,13 +933,7 @@ mod proptests {
         /// execute_job preserves job_id in result.
         #[test]
         fn execute_preserves_job_id(job in arb_job()) {
-            let config = WorkerConfig {
-                worker_id: vec![1],
-                tee_type: "sim".to_string(),
-                model_cache_dir: "/tmp".to_string(),
-                max_concurrent_jobs: 1,
-            };
-            let worker = AiWorker::new(config);
+            let worker = worker_for(&job);
             let result = worker.execute_job(&job).unwrap();
             prop_assert_eq!(&result.job_id, &job.job_id);
         }
@@ -285,13 +941,7 @@ mod proptests {
         /// execute_job always produces non-empty output and trace.
         #[test]
         fn execute_produces_nonempty_output(job in arb_job()) {
-            let config = WorkerConfig {
-                worker_id: vec![1],
-                tee_type: "sim".to_string(),
-                model_cache_dir: "/tmp".to_string(),
-                max_concurrent_jobs: 1,
-            };
-            let worker = AiWorker::new(config);
+            let worker = worker_for(&job);
             let result = worker.execute_job(&job).unwrap();
             prop_assert!(!result.output_data.is_empty());
             prop_assert!(!result.execution_trace.is_empty());
@@ -300,13 +950,7 @@ mod proptests {
         /// Gas used is always positive (base gas > 0) and deterministic for same trace.
         #[test]
         fn gas_always_positive(job in arb_job()) {
-            let config = WorkerConfig {
-                worker_id: vec![1],
-                tee_type: "sim".to_string(),
-                model_cache_dir: "/tmp".to_string(),
-                max_concurrent_jobs: 1,
-            };
-            let worker = AiWorker::new(config);
+            let worker = worker_for(&job);
             let result = worker.execute_job(&job).unwrap();
             prop_assert!(result.gas_used > 0);
         }
@@ -314,13 +958,7 @@ mod proptests {
         /// Gas is deterministic — same job always yields same gas.
         #[test]
         fn gas_deterministic(job in arb_job()) {
-            let config = WorkerConfig {
-                worker_id: vec![1],
-                tee_type: "sim".to_string(),
-                model_cache_dir: "/tmp".to_string(),
-                max_concurrent_jobs: 1,
-            };
-            let worker = AiWorker::new(config);
+            let worker = worker_for(&job);
             let r1 = worker.execute_job(&job).unwrap();
             let r2 = worker.execute_job(&job).unwrap();
             prop_assert_eq!(r1.gas_used, r2.gas_used);
@@ -346,6 +984,9 @@ mod proptests {
                 model_hash: vec![],
                 input_data,
                 gas_limit,
+                seed: 0,
+                requester_key: None,
+                slot_hash: Vec::new(),
             };
             prop_assert!(worker.execute_job(&job).is_err());
         }
@@ -369,23 +1010,36 @@ mod proptests {
                 model_hash,
                 input_data: vec![],
                 gas_limit,
+                seed: 0,
+                requester_key: None,
+                slot_hash: Vec::new(),
             };
             prop_assert!(worker.execute_job(&job).is_err());
         }
 
-        /// Gas calculation: gas = BASE_GAS + trace_len * GAS_PER_BYTE.
+        /// Gas calculation: BASE_GAS plus a Relu over n inputs (n elements, 2n words).
         #[test]
         fn gas_formula_correct(job in arb_job()) {
-            let config = WorkerConfig {
-                worker_id: vec![1],
-                tee_type: "sim".to_string(),
-                model_cache_dir: "/tmp".to_string(),
-                max_concurrent_jobs: 1,
-            };
-            let worker = AiWorker::new(config);
+            let worker = worker_for(&job);
             let result = worker.execute_job(&job).unwrap();
-            let expected = 1000u64 + (result.execution_trace.len() as u64 * 10);
+            let n = job.input_data.len() as u64;
+            let expected = engine::BASE_GAS
+                + n * engine::GAS_PER_ELEMENT
+                + 2 * n * engine::GAS_PER_WORD;
             prop_assert_eq!(result.gas_used, expected);
+            prop_assert_eq!(result.gas_breakdown.len(), 1);
+        }
+
+        /// Jobs whose limit is below the metered cost are aborted.
+        #[test]
+        fn over_budget_jobs_abort(job in arb_job(), shortfall in 1u64..=100) {
+            let worker = worker_for(&job);
+            let gas_used = worker.execute_job(&job).unwrap().gas_used;
+            let starved = InferenceJob {
+                gas_limit: gas_used.saturating_sub(shortfall),
+                ..job
+            };
+            prop_assert!(worker.execute_job(&starved).is_err());
         }
 
         /// WorkerConfig serialization roundtrip.
@@ -0,0 +1,568 @@
+// ============================================================================
+// ONNX ENGINE - ONNX Runtime behind `InferenceEngine`
+// ============================================================================
+// Runs standard `.onnx` models through ONNX Runtime, loaded at run time from
+// the shared library named by `ORT_DYLIB_PATH` (the `onnx` feature links
+// nothing at build time). Sessions are pinned to what the determinism rules
+// in `engine` ask of every backend:
+//
+// - one intra-op and one inter-op thread, sequential execution
+// - ONNX Runtime's deterministic compute mode
+// - the model's default-domain opset must equal the pinned one
+// - nondeterministic ops are refused at load time
+//
+// The opset and op checks read the model's protobuf directly, so a model is
+// vetted before ONNX Runtime ever parses it.
+//
+// I/O CONVENTION (same as the reference engine):
+// - each input byte is one element of the model's first input, converted
+//   to its element type; dynamic dimensions are sized to fit
+// - the first output is returned as little-endian element bytes
+// - every output is captured as an activation, floats by their bit pattern
+//
+// GAS: ONNX Runtime exposes no per-node hook, so a run is charged up front
+// for `BASE_GAS` plus the words of model and input it reads, and the output
+// words once it finishes. Runs whose charge exceeds the limit fail.
+// ============================================================================
+
+use anyhow::{bail, Context, Result};
+use ort::session::builder::GraphOptimizationLevel;
+use ort::session::Session;
+use ort::tensor::TensorElementType;
+use ort::value::{DynValue, Tensor, ValueType};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::engine::{
+    DeterminismConfig, InferenceEngine, InferenceOutput, LayerActivation, OpGas, BASE_GAS,
+    GAS_PER_WORD, NONDETERMINISTIC_OPS,
+};
+
+struct OnnxModel {
+    /// `Session::run` needs exclusive access.
+    session: Mutex<Session>,
+    input_name: String,
+    input_type: TensorElementType,
+    input_shape: Vec<i64>,
+    output_names: Vec<String>,
+    /// Words of model data every run reads.
+    model_words: u64,
+}
+
+/// ONNX Runtime backend; see the module notes.
+pub struct OnnxEngine {
+    config: DeterminismConfig,
+    models: HashMap<Vec<u8>, OnnxModel>,
+}
+
+impl OnnxEngine {
+    pub fn new(config: DeterminismConfig) -> Result<Self> {
+        config.validate()?;
+        Ok(Self {
+            config,
+            models: HashMap::new(),
+        })
+    }
+}
+
+impl InferenceEngine for OnnxEngine {
+    fn name(&self) -> &str {
+        "onnx"
+    }
+
+    fn load(&mut self, model_hash: &[u8], model_bytes: &[u8]) -> Result<()> {
+        let info = ModelInfo::parse(model_bytes)?;
+        info.validate(&self.config)?;
+
+        let session = Session::builder()
+            .and_then(|b| b.with_intra_threads(self.config.intra_op_threads))
+            .and_then(|b| b.with_inter_threads(self.config.inter_op_threads))
+            .and_then(|b| b.with_parallel_execution(false))
+            .and_then(|b| b.with_deterministic_compute(true))
+            .and_then(|b| b.with_optimization_level(GraphOptimizationLevel::Level1))
+            .and_then(|b| b.commit_from_memory(model_bytes))
+            .map_err(|e| anyhow::anyhow!("ONNX Runtime rejected the model: {e}"))?;
+
+        let input = session.inputs.first().context("model has no inputs")?;
+        let ValueType::Tensor { ty, shape, .. } = &input.input_type else {
+            bail!("model input {} is not a tensor", input.name);
+        };
+        ensure_supported(*ty)?;
+        let model = OnnxModel {
+            input_name: input.name.clone(),
+            input_type: *ty,
+            input_shape: shape.to_vec(),
+            output_names: session.outputs.iter().map(|o| o.name.clone()).collect(),
+            model_words: words(model_bytes.len()),
+            session: Mutex::new(session),
+        };
+        if model.output_names.is_empty() {
+            bail!("model has no outputs");
+        }
+        self.models.insert(model_hash.to_vec(), model);
+        Ok(())
+    }
+
+    fn is_loaded(&self, model_hash: &[u8]) -> bool {
+        self.models.contains_key(model_hash)
+    }
+
+    fn run(&self, model_hash: &[u8], input: &[u8], gas_limit: u64) -> Result<InferenceOutput> {
+        let model = self
+            .models
+            .get(model_hash)
+            .ok_or_else(|| anyhow::anyhow!("model {} not loaded", hex::encode(model_hash)))?;
+
+        let read_gas = model
+            .model_words
+            .saturating_add(words(input.len()))
+            .saturating_mul(GAS_PER_WORD);
+        let mut gas_used = BASE_GAS.saturating_add(read_gas);
+        if gas_used > gas_limit {
+            bail!("out of gas: {gas_used} needed before running, limit {gas_limit}");
+        }
+
+        let shape = fit_shape(&model.input_shape, input.len())?;
+        let value = input_tensor(model.input_type, shape, input)?;
+        let mut session = model
+            .session
+            .lock()
+            .map_err(|_| anyhow::anyhow!("ONNX session lock poisoned"))?;
+        let outputs = session
+            .run(vec![(model.input_name.as_str(), value)])
+            .map_err(|e| anyhow::anyhow!("ONNX Runtime run failed: {e}"))?;
+
+        let mut activations = Vec::with_capacity(model.output_names.len());
+        let mut output = Vec::new();
+        let mut output_bytes = 0usize;
+        for (i, name) in model.output_names.iter().enumerate() {
+            let (values, bytes) = extract(&outputs[name.as_str()])
+                .with_context(|| format!("reading output {name}"))?;
+            output_bytes = output_bytes.saturating_add(bytes.len());
+            if i == 0 {
+                output = bytes;
+            }
+            activations.push(LayerActivation {
+                node: name.clone(),
+                op_type: "Output".to_string(),
+                values,
+            });
+        }
+
+        let run_gas = read_gas.saturating_add(words(output_bytes).saturating_mul(GAS_PER_WORD));
+        gas_used = BASE_GAS.saturating_add(run_gas);
+        if gas_used > gas_limit {
+            bail!("out of gas: run used {gas_used}, limit {gas_limit}");
+        }
+        Ok(InferenceOutput {
+            output,
+            activations,
+            op_gas: vec![OpGas {
+                node: "session".to_string(),
+                op_type: "OnnxRuntime".to_string(),
+                gas: run_gas,
+            }],
+            gas_used,
+        })
+    }
+}
+
+fn words(bytes: usize) -> u64 {
+    (bytes as u64).div_ceil(8)
+}
+
+fn ensure_supported(ty: TensorElementType) -> Result<()> {
+    match ty {
+        TensorElementType::Uint8
+        | TensorElementType::Int32
+        | TensorElementType::Int64
+        | TensorElementType::Float32 => Ok(()),
+        other => bail!("unsupported tensor element type {other:?}"),
+    }
+}
+
+/// The model's input shape with its dynamic (`-1`) dimensions sized so the
+/// tensor holds exactly `len` elements: the last one takes what is left,
+/// any others are 1.
+fn fit_shape(declared: &[i64], len: usize) -> Result<Vec<i64>> {
+    let fixed: i64 = declared.iter().filter(|d| **d >= 0).product();
+    let last_dynamic = declared.iter().rposition(|d| *d < 0);
+    let len = i64::try_from(len).context("input too large")?;
+    let shape: Vec<i64> = match last_dynamic {
+        None => declared.to_vec(),
+        Some(last) if fixed > 0 && len % fixed == 0 => declared
+            .iter()
+            .enumerate()
+            .map(|(i, d)| match (*d < 0, i == last) {
+                (true, true) => len / fixed,
+                (true, false) => 1,
+                (false, _) => *d,
+            })
+            .collect(),
+        Some(_) => bail!("input of {len} elements does not fit shape {declared:?}"),
+    };
+    if shape.iter().product::<i64>() != len {
+        bail!("input has {len} elements, model expects shape {declared:?}");
+    }
+    Ok(shape)
+}
+
+fn input_tensor(ty: TensorElementType, shape: Vec<i64>, input: &[u8]) -> Result<DynValue> {
+    let value = match ty {
+        TensorElementType::Uint8 => Tensor::from_array((shape, input.to_vec()))?.into_dyn(),
+        TensorElementType::Int32 => {
+            let data: Vec<i32> = input.iter().map(|&b| i32::from(b)).collect();
+            Tensor::from_array((shape, data))?.into_dyn()
+        }
+        TensorElementType::Int64 => {
+            let data: Vec<i64> = input.iter().map(|&b| i64::from(b)).collect();
+            Tensor::from_array((shape, data))?.into_dyn()
+        }
+        TensorElementType::Float32 => {
+            let data: Vec<f32> = input.iter().map(|&b| f32::from(b)).collect();
+            Tensor::from_array((shape, data))?.into_dyn()
+        }
+        other => bail!("unsupported tensor element type {other:?}"),
+    };
+    Ok(value)
+}
+
+/// An output's elements as activation values and as little-endian bytes.
+fn extract(value: &DynValue) -> Result<(Vec<i64>, Vec<u8>)> {
+    let ValueType::Tensor { ty, .. } = value.dtype() else {
+        bail!("output is not a tensor");
+    };
+    Ok(match ty {
+        TensorElementType::Uint8 => {
+            let (_, data) = value.try_extract_tensor::<u8>()?;
+            (data.iter().map(|v| i64::from(*v)).collect(), data.to_vec())
+        }
+        TensorElementType::Int32 => {
+            let (_, data) = value.try_extract_tensor::<i32>()?;
+            let bytes = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+            (data.iter().map(|v| i64::from(*v)).collect(), bytes)
+        }
+        TensorElementType::Int64 => {
+            let (_, data) = value.try_extract_tensor::<i64>()?;
+            let bytes = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+            (data.to_vec(), bytes)
+        }
+        TensorElementType::Float32 => {
+            let (_, data) = value.try_extract_tensor::<f32>()?;
+            let bytes = data.iter().flat_map(|v| v.to_le_bytes()).collect();
+            (data.iter().map(|v| i64::from(v.to_bits())).collect(), bytes)
+        }
+        other => bail!("unsupported tensor element type {other:?}"),
+    })
+}
+
+/// What the determinism checks need from an ONNX `ModelProto`.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ModelInfo {
+    /// Default-domain (`""` or `ai.onnx`) opset, if imported.
+    opset: Option<u32>,
+    /// Every node's op type, including nodes in subgraphs.
+    op_types: Vec<String>,
+}
+
+impl ModelInfo {
+    fn parse(bytes: &[u8]) -> Result<Self> {
+        let mut info = ModelInfo::default();
+        for field in Fields::new(bytes) {
+            match field? {
+                // ModelProto.graph
+                (7, Wire::Bytes(graph)) => collect_ops(graph, &mut info.op_types, 0)?,
+                // ModelProto.opset_import
+                (8, Wire::Bytes(opset)) => {
+                    let (mut domain, mut version) = (&b""[..], 0u64);
+                    for field in Fields::new(opset) {
+                        match field? {
+                            (1, Wire::Bytes(d)) => domain = d,
+                            (2, Wire::Varint(v)) => version = v,
+                            _ => {}
+                        }
+                    }
+                    if domain.is_empty() || domain == b"ai.onnx" {
+                        info.opset = Some(u32::try_from(version).context("opset out of range")?);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if info.op_types.is_empty() {
+            bail!("not an ONNX model: no graph nodes");
+        }
+        Ok(info)
+    }
+
+    fn validate(&self, config: &DeterminismConfig) -> Result<()> {
+        match self.opset {
+            Some(opset) if opset == config.opset => {}
+            Some(opset) => bail!(
+                "model targets opset {opset}, worker is pinned to {}",
+                config.opset
+            ),
+            None => bail!("model does not import the default ONNX opset"),
+        }
+        if !config.allow_nondeterministic_ops {
+            if let Some(op) = self
+                .op_types
+                .iter()
+                .find(|op| NONDETERMINISTIC_OPS.contains(&op.as_str()))
+            {
+                bail!("model uses nondeterministic op {op}");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Subgraphs (If/Loop/Scan bodies) nest no deeper than this.
+const MAX_GRAPH_DEPTH: usize = 16;
+
+/// Op types of the nodes in a `GraphProto`, recursing into graph attributes.
+fn collect_ops(graph: &[u8], ops: &mut Vec<String>, depth: usize) -> Result<()> {
+    if depth > MAX_GRAPH_DEPTH {
+        bail!("ONNX subgraphs nested too deeply");
+    }
+    for field in Fields::new(graph) {
+        // GraphProto.node
+        let (1, Wire::Bytes(node)) = field? else {
+            continue;
+        };
+        for field in Fields::new(node) {
+            match field? {
+                // NodeProto.op_type
+                (4, Wire::Bytes(op)) => ops.push(
+                    std::str::from_utf8(op)
+                        .context("op type is not UTF-8")?
+                        .to_string(),
+                ),
+                // NodeProto.attribute: AttributeProto.g (6) and .graphs (11)
+                (5, Wire::Bytes(attribute)) => {
+                    for field in Fields::new(attribute) {
+                        if let (6 | 11, Wire::Bytes(subgraph)) = field? {
+                            collect_ops(subgraph, ops, depth + 1)?;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+enum Wire<'a> {
+    Varint(u64),
+    Bytes(&'a [u8]),
+    Fixed,
+}
+
+/// Top-level fields of one protobuf message.
+struct Fields<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Fields { rest: bytes }
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self.rest.split_first().context("truncated varint")?;
+            self.rest = rest;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("varint too long")
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.rest.len() {
+            bail!("truncated protobuf field");
+        }
+        let (taken, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(taken)
+    }
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = Result<(u64, Wire<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let field = (|| {
+            let key = self.varint()?;
+            let wire = match key & 7 {
+                0 => Wire::Varint(self.varint()?),
+                1 => {
+                    self.take(8)?;
+                    Wire::Fixed
+                }
+                2 => {
+                    let len = usize::try_from(self.varint()?).context("field too long")?;
+                    Wire::Bytes(self.take(len)?)
+                }
+                5 => {
+                    self.take(4)?;
+                    Wire::Fixed
+                }
+                other => bail!("unsupported protobuf wire type {other}"),
+            };
+            Ok((key >> 3, wire))
+        })();
+        if field.is_err() {
+            // Stop after the first malformed field.
+            self.rest = &[];
+        }
+        Some(field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(mut v: u64, out: &mut Vec<u8>) {
+        while v >= 0x80 {
+            out.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        out.push(v as u8);
+    }
+
+    fn bytes_field(field: u64, data: &[u8], out: &mut Vec<u8>) {
+        varint(field << 3 | 2, out);
+        varint(data.len() as u64, out);
+        out.extend_from_slice(data);
+    }
+
+    fn int_field(field: u64, value: u64, out: &mut Vec<u8>) {
+        varint(field << 3, out);
+        varint(value, out);
+    }
+
+    fn node(op_type: &str, input: &str, output: &str) -> Vec<u8> {
+        let mut node = Vec::new();
+        bytes_field(1, input.as_bytes(), &mut node);
+        bytes_field(2, output.as_bytes(), &mut node);
+        bytes_field(4, op_type.as_bytes(), &mut node);
+        node
+    }
+
+    fn value_info(name: &str, elem_type: u64) -> Vec<u8> {
+        // TypeProto { tensor_type: { elem_type, shape: { dim: { dim_param: "n" } } } }
+        let mut dim = Vec::new();
+        bytes_field(2, b"n", &mut dim);
+        let mut shape = Vec::new();
+        bytes_field(1, &dim, &mut shape);
+        let mut tensor = Vec::new();
+        int_field(1, elem_type, &mut tensor);
+        bytes_field(2, &shape, &mut tensor);
+        let mut ty = Vec::new();
+        bytes_field(1, &tensor, &mut ty);
+        let mut info = Vec::new();
+        bytes_field(1, name.as_bytes(), &mut info);
+        bytes_field(2, &ty, &mut info);
+        info
+    }
+
+    /// `y = Relu(x)` over a 1-D int32 tensor of any length.
+    fn relu_model(op_type: &str, opset: u64) -> Vec<u8> {
+        let mut graph = Vec::new();
+        bytes_field(1, &node(op_type, "x", "y"), &mut graph);
+        bytes_field(2, b"relu", &mut graph);
+        bytes_field(11, &value_info("x", 6), &mut graph);
+        bytes_field(12, &value_info("y", 6), &mut graph);
+        let mut opset_import = Vec::new();
+        bytes_field(1, b"", &mut opset_import);
+        int_field(2, opset, &mut opset_import);
+
+        let mut model = Vec::new();
+        int_field(1, 8, &mut model);
+        bytes_field(7, &graph, &mut model);
+        bytes_field(8, &opset_import, &mut model);
+        model
+    }
+
+    #[test]
+    fn reads_opset_and_ops_from_the_model() {
+        let info = ModelInfo::parse(&relu_model("Relu", 17)).unwrap();
+        assert_eq!(info.opset, Some(17));
+        assert_eq!(info.op_types, vec!["Relu".to_string()]);
+        info.validate(&DeterminismConfig::default()).unwrap();
+
+        let err = ModelInfo::parse(&relu_model("Relu", 13))
+            .unwrap()
+            .validate(&DeterminismConfig::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("opset 13"), "{err}");
+        let err = ModelInfo::parse(&relu_model("Dropout", 17))
+            .unwrap()
+            .validate(&DeterminismConfig::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("Dropout"), "{err}");
+    }
+
+    #[test]
+    fn finds_ops_in_subgraphs() {
+        let mut branch = Vec::new();
+        bytes_field(1, &node("RandomUniform", "", "r"), &mut branch);
+        let mut attribute = Vec::new();
+        bytes_field(1, b"then_branch", &mut attribute);
+        bytes_field(6, &branch, &mut attribute);
+        let mut if_node = node("If", "c", "y");
+        bytes_field(5, &attribute, &mut if_node);
+        let mut graph = Vec::new();
+        bytes_field(1, &if_node, &mut graph);
+        let mut model = Vec::new();
+        bytes_field(7, &graph, &mut model);
+
+        let info = ModelInfo::parse(&model).unwrap();
+        assert_eq!(info.op_types, vec!["If", "RandomUniform"]);
+    }
+
+    #[test]
+    fn rejects_malformed_models() {
+        assert!(ModelInfo::parse(b"").is_err());
+        assert!(ModelInfo::parse(&[0x3A, 0x10, 0x01]).is_err());
+        let mut engine = OnnxEngine::new(DeterminismConfig::default()).unwrap();
+        assert!(engine.load(b"m", &relu_model("Dropout", 17)).is_err());
+        assert!(!engine.is_loaded(b"m"));
+    }
+
+    #[test]
+    fn fits_dynamic_dimensions_to_the_input() {
+        assert_eq!(fit_shape(&[-1], 5).unwrap(), vec![5]);
+        assert_eq!(fit_shape(&[-1, 3], 6).unwrap(), vec![2, 3]);
+        assert_eq!(fit_shape(&[-1, -1, 2], 6).unwrap(), vec![1, 3, 2]);
+        assert_eq!(fit_shape(&[1, 4], 4).unwrap(), vec![1, 4]);
+        assert!(fit_shape(&[1, 4], 3).is_err());
+        assert!(fit_shape(&[-1, 4], 6).is_err());
+    }
+
+    /// Needs ONNX Runtime: set `ORT_DYLIB_PATH` to `libonnxruntime.so`.
+    #[test]
+    #[ignore = "requires the ONNX Runtime shared library"]
+    fn runs_an_onnx_model() {
+        let mut engine = OnnxEngine::new(DeterminismConfig::default()).unwrap();
+        engine.load(b"relu", &relu_model("Relu", 17)).unwrap();
+        let out = engine.run(b"relu", &[0, 3, 200], u64::MAX).unwrap();
+        let expected: Vec<u8> = [0i32, 3, 200]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect();
+        assert_eq!(out.output, expected);
+        assert_eq!(out.activations[0].values, vec![0, 3, 200]);
+        assert_eq!(engine.run(b"relu", &[0, 3, 200], u64::MAX).unwrap(), out);
+        assert!(engine.run(b"relu", &[1], BASE_GAS).is_err());
+    }
+}
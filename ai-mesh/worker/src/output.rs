@@ -0,0 +1,289 @@
+// ============================================================================
+// OUTPUT CONFIDENTIALITY - Results sealed to the requester
+// ============================================================================
+// Only `SHA-256(output)` goes on chain. The output itself is encrypted to the
+// requester's X25519 key and served from the worker's retrieval endpoint:
+//
+//   ephemeral X25519 key  --ECDH-->  shared secret
+//   HKDF-SHA256(salt = eph_pk || recipient_pk, info = domain) -> AEAD key
+//   ChaCha20-Poly1305(key, random nonce, aad = job_id)
+//
+// Binding the job id as associated data stops a ciphertext from being served
+// as the answer to a different job.
+// ============================================================================
+
+use anyhow::{bail, Result};
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, RwLock};
+use x25519_dalek::{EphemeralSecret, PublicKey, StaticSecret};
+
+const KDF_INFO: &[u8] = b"aether-ai-output-v1";
+
+/// Sealed outputs a [`ResultStore`] holds by default before evicting the
+/// oldest.
+pub const DEFAULT_MAX_RESULTS: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedOutput {
+    pub ephemeral_public: [u8; 32],
+    pub nonce: [u8; 12],
+    pub ciphertext: Vec<u8>,
+}
+
+/// Encrypt `plaintext` to `recipient_public`, bound to `job_id`.
+pub fn seal(recipient_public: &[u8; 32], job_id: &[u8], plaintext: &[u8]) -> Result<SealedOutput> {
+    let recipient = PublicKey::from(*recipient_public);
+    let ephemeral = EphemeralSecret::random_from_rng(OsRng);
+    let ephemeral_public = PublicKey::from(&ephemeral);
+    let shared = ephemeral.diffie_hellman(&recipient);
+    if !shared.was_contributory() {
+        bail!("requester key is a low-order point");
+    }
+
+    let cipher = derive_cipher(
+        shared.as_bytes(),
+        ephemeral_public.as_bytes(),
+        recipient_public,
+    )?;
+    let mut nonce = [0u8; 12];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: job_id,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("output encryption failed"))?;
+
+    Ok(SealedOutput {
+        ephemeral_public: *ephemeral_public.as_bytes(),
+        nonce,
+        ciphertext,
+    })
+}
+
+/// Decrypt a sealed output with the requester's secret key.
+pub fn open(recipient_secret: &[u8; 32], job_id: &[u8], sealed: &SealedOutput) -> Result<Vec<u8>> {
+    let secret = StaticSecret::from(*recipient_secret);
+    let recipient_public = PublicKey::from(&secret);
+    let shared = secret.diffie_hellman(&PublicKey::from(sealed.ephemeral_public));
+    if !shared.was_contributory() {
+        bail!("sealed output uses a low-order ephemeral key");
+    }
+
+    let cipher = derive_cipher(
+        shared.as_bytes(),
+        &sealed.ephemeral_public,
+        recipient_public.as_bytes(),
+    )?;
+    cipher
+        .decrypt(
+            Nonce::from_slice(&sealed.nonce),
+            Payload {
+                msg: &sealed.ciphertext,
+                aad: job_id,
+            },
+        )
+        .map_err(|_| anyhow::anyhow!("sealed output failed authentication"))
+}
+
+fn derive_cipher(
+    shared: &[u8; 32],
+    ephemeral_public: &[u8; 32],
+    recipient_public: &[u8; 32],
+) -> Result<ChaCha20Poly1305> {
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public);
+    salt[32..].copy_from_slice(recipient_public);
+    let mut key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared)
+        .expand(KDF_INFO, &mut key)
+        .map_err(|_| anyhow::anyhow!("HKDF expand failed"))?;
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+/// Sealed outputs awaiting retrieval, keyed by job id.
+///
+/// Holds at most `capacity` outputs; once full, each insert evicts the
+/// oldest, so requesters that never collect cannot grow it without bound.
+#[derive(Debug, Clone)]
+pub struct ResultStore {
+    outputs: Arc<RwLock<Outputs>>,
+}
+
+#[derive(Debug)]
+struct Outputs {
+    by_job: HashMap<Vec<u8>, SealedOutput>,
+    /// Job ids, oldest first.
+    order: VecDeque<Vec<u8>>,
+    capacity: usize,
+}
+
+impl Default for ResultStore {
+    fn default() -> Self {
+        Self::with_capacity(DEFAULT_MAX_RESULTS)
+    }
+}
+
+impl ResultStore {
+    pub fn with_capacity(capacity: usize) -> Self {
+        ResultStore {
+            outputs: Arc::new(RwLock::new(Outputs {
+                by_job: HashMap::new(),
+                order: VecDeque::new(),
+                capacity: capacity.max(1),
+            })),
+        }
+    }
+
+    pub fn insert(&self, job_id: Vec<u8>, sealed: SealedOutput) {
+        let mut outputs = self.outputs.write().unwrap_or_else(|e| e.into_inner());
+        if outputs.by_job.insert(job_id.clone(), sealed).is_some() {
+            return;
+        }
+        outputs.order.push_back(job_id);
+        while outputs.order.len() > outputs.capacity {
+            if let Some(oldest) = outputs.order.pop_front() {
+                outputs.by_job.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn get(&self, job_id: &[u8]) -> Option<SealedOutput> {
+        self.outputs
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .by_job
+            .get(job_id)
+            .cloned()
+    }
+
+    /// Drop a result once the requester has confirmed receipt.
+    pub fn remove(&self, job_id: &[u8]) -> Option<SealedOutput> {
+        let mut outputs = self.outputs.write().unwrap_or_else(|e| e.into_inner());
+        let removed = outputs.by_job.remove(job_id)?;
+        outputs.order.retain(|id| id != job_id);
+        Some(removed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.outputs
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .by_job
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+async fn handle_get(
+    State(store): State<ResultStore>,
+    Path(job_id): Path<String>,
+) -> Result<Json<SealedOutput>, StatusCode> {
+    let job_id =
+        hex::decode(job_id.trim_start_matches("0x")).map_err(|_| StatusCode::BAD_REQUEST)?;
+    store.get(&job_id).map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// `GET /results/:job_id` (hex) returns the job's `SealedOutput` as JSON.
+///
+/// Ciphertexts are safe to serve unauthenticated: only the requester's key
+/// opens them, and the on-chain output hash authenticates the plaintext.
+pub fn results_app(store: ResultStore) -> Router {
+    Router::new()
+        .route("/results/:job_id", get(handle_get))
+        .with_state(store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn requester() -> ([u8; 32], [u8; 32]) {
+        let secret = StaticSecret::random_from_rng(OsRng);
+        (secret.to_bytes(), *PublicKey::from(&secret).as_bytes())
+    }
+
+    #[test]
+    fn seal_and_open_roundtrip() {
+        let (secret, public) = requester();
+        let sealed = seal(&public, b"job-1", b"logits").unwrap();
+        assert_ne!(sealed.ciphertext, b"logits".to_vec());
+        assert_eq!(open(&secret, b"job-1", &sealed).unwrap(), b"logits");
+    }
+
+    #[test]
+    fn rejects_wrong_key_job_or_tampering() {
+        let (secret, public) = requester();
+        let (other_secret, _) = requester();
+        let sealed = seal(&public, b"job-1", b"logits").unwrap();
+
+        assert!(open(&other_secret, b"job-1", &sealed).is_err());
+        assert!(open(&secret, b"job-2", &sealed).is_err());
+
+        let mut tampered = sealed.clone();
+        tampered.ciphertext[0] ^= 1;
+        assert!(open(&secret, b"job-1", &tampered).is_err());
+
+        assert!(seal(&[0u8; 32], b"job-1", b"logits").is_err());
+    }
+
+    #[test]
+    fn result_store_evicts_oldest_when_full() {
+        let (_, public) = requester();
+        let store = ResultStore::with_capacity(2);
+        for job in [1u8, 2, 3] {
+            store.insert(vec![job], seal(&public, &[job], b"out").unwrap());
+        }
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&[1]).is_none());
+        assert!(store.get(&[3]).is_some());
+
+        assert!(store.remove(&[2]).is_some());
+        store.insert(vec![4], seal(&public, &[4], b"out").unwrap());
+        assert_eq!(store.len(), 2);
+        assert!(store.get(&[3]).is_some());
+    }
+
+    #[tokio::test]
+    async fn serves_sealed_outputs() {
+        let (_, public) = requester();
+        let store = ResultStore::default();
+        let sealed = seal(&public, &[0xab, 0xcd], b"out").unwrap();
+        store.insert(vec![0xab, 0xcd], sealed.clone());
+        let app = results_app(store);
+
+        let get = |uri: &str| Request::get(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(get("/results/abcd")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), 64 * 1024).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<SealedOutput>(&body).unwrap(),
+            sealed
+        );
+
+        let response = app.clone().oneshot(get("/results/ffff")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let response = app.oneshot(get("/results/zz")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
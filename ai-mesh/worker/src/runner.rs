@@ -0,0 +1,339 @@
+use crate::benchmark::CapabilityReport;
+use crate::{InferenceJob, InferenceResult};
+use anyhow::Result;
+use async_trait::async_trait;
+use tokio::sync::mpsc::{self, error::TryRecvError};
+
+/// Where a worker gets its job assignments (coordinator RPC, chain events, ...).
+#[async_trait]
+pub trait JobSource: Send {
+    /// Fetch up to `max` newly assigned jobs.
+    ///
+    /// Implementations should return promptly: an empty batch means nothing
+    /// is available yet and the worker will poll again. `None` means the
+    /// source is closed and the worker should drain and exit.
+    async fn next_jobs(&mut self, max: usize) -> Result<Option<Vec<InferenceJob>>>;
+
+    /// Fetch a job assigned earlier, to resume it after a restart. The
+    /// journal keeps no inputs, so a job the source cannot return again is
+    /// abandoned.
+    async fn refetch(&mut self, _job_id: &[u8]) -> Result<Option<InferenceJob>> {
+        Ok(None)
+    }
+}
+
+/// Where a worker reports finished jobs.
+#[async_trait]
+pub trait ResultSink: Send {
+    async fn submit(&mut self, outcome: JobOutcome) -> Result<()>;
+}
+
+/// Where a worker advertises its measured capabilities (coordinator RPC, ...).
+#[async_trait]
+pub trait CapabilityReporter: Send {
+    async fn report(&mut self, report: CapabilityReport) -> Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct JobOutcome {
+    pub job_id: Vec<u8>,
+    pub result: Result<InferenceResult, String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    pub accepted: u64,
+    pub succeeded: u64,
+    pub failed: u64,
+    /// Jobs recovered from the journal and run again.
+    pub resumed: u64,
+    /// Jobs recovered from the journal and reported as failed instead.
+    pub abandoned: u64,
+}
+
+#[async_trait]
+impl JobSource for mpsc::Receiver<InferenceJob> {
+    async fn next_jobs(&mut self, max: usize) -> Result<Option<Vec<InferenceJob>>> {
+        let mut jobs = Vec::new();
+        while jobs.len() < max {
+            match self.try_recv() {
+                Ok(job) => jobs.push(job),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) if jobs.is_empty() => return Ok(None),
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        Ok(Some(jobs))
+    }
+}
+
+#[async_trait]
+impl ResultSink for mpsc::Sender<JobOutcome> {
+    async fn submit(&mut self, outcome: JobOutcome) -> Result<()> {
+        self.send(outcome)
+            .await
+            .map_err(|_| anyhow::anyhow!("result channel closed"))
+    }
+}
+
+#[async_trait]
+impl ResultSink for Vec<JobOutcome> {
+    async fn submit(&mut self, outcome: JobOutcome) -> Result<()> {
+        self.push(outcome);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CapabilityReporter for mpsc::Sender<CapabilityReport> {
+    async fn report(&mut self, report: CapabilityReport) -> Result<()> {
+        self.send(report)
+            .await
+            .map_err(|_| anyhow::anyhow!("capability channel closed"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark::{BenchmarkConfig, BenchmarkShape};
+    use crate::engine::identity_graph;
+    use crate::journal::{JobJournal, JournalConfig};
+    use crate::{AiWorker, WorkerConfig};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn worker(max_concurrent_jobs: usize) -> AiWorker {
+        let mut worker = AiWorker::new(WorkerConfig {
+            worker_id: vec![1],
+            tee_type: "simulation".to_string(),
+            model_cache_dir: "/tmp/models".to_string(),
+            max_concurrent_jobs,
+        });
+        worker
+            .install_model(b"model", &identity_graph(2).to_bytes())
+            .unwrap();
+        worker
+    }
+
+    /// A coordinator that can hand out the jobs it assigned again.
+    struct Coordinator {
+        rx: mpsc::Receiver<InferenceJob>,
+        assigned: Vec<InferenceJob>,
+    }
+
+    #[async_trait]
+    impl JobSource for Coordinator {
+        async fn next_jobs(&mut self, max: usize) -> Result<Option<Vec<InferenceJob>>> {
+            self.rx.next_jobs(max).await
+        }
+
+        async fn refetch(&mut self, job_id: &[u8]) -> Result<Option<InferenceJob>> {
+            Ok(self.assigned.iter().find(|j| j.job_id == job_id).cloned())
+        }
+    }
+
+    fn job(id: u8, input_len: usize) -> InferenceJob {
+        InferenceJob {
+            job_id: vec![id],
+            model_hash: b"model".to_vec(),
+            input_data: vec![id; input_len],
+            gas_limit: 1_000_000,
+            seed: 0,
+            requester_key: None,
+            slot_hash: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn processes_until_source_closes() {
+        let worker = worker(2);
+        let (tx, mut rx) = mpsc::channel(16);
+        for i in 0..5 {
+            tx.send(job(i, 2)).await.unwrap();
+        }
+        tx.send(job(9, 7)).await.unwrap();
+        drop(tx);
+
+        let mut results = Vec::new();
+        let stats = worker.start(&mut rx, &mut results).await.unwrap();
+
+        assert_eq!(
+            stats,
+            WorkerStats {
+                accepted: 6,
+                succeeded: 5,
+                failed: 1,
+                resumed: 0,
+                abandoned: 0,
+            }
+        );
+        assert_eq!(results.len(), 6);
+        assert!(!worker.is_running());
+    }
+
+    #[tokio::test]
+    async fn stop_drains_in_flight_jobs() {
+        let worker = Arc::new(worker(4));
+        let (tx, mut rx) = mpsc::channel(16);
+        let (result_tx, mut result_rx) = mpsc::channel(16);
+
+        let handle = {
+            let worker = worker.clone();
+            tokio::spawn(async move {
+                let mut sink = result_tx;
+                worker.start(&mut rx, &mut sink).await
+            })
+        };
+
+        tx.send(job(1, 2)).await.unwrap();
+        let first = tokio::time::timeout(Duration::from_secs(5), result_rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.job_id, vec![1]);
+        assert!(worker.is_running());
+
+        worker.stop();
+        let stats = tokio::time::timeout(Duration::from_secs(5), handle)
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(stats.succeeded, 1);
+        assert!(!worker.is_running());
+    }
+
+    #[tokio::test]
+    async fn resumes_journaled_jobs_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = JournalConfig::new(dir.path().join("jobs.journal"));
+        {
+            // A previous run accepted four jobs and died; one of them had
+            // already crashed the worker twice.
+            let mut journal = JobJournal::open(config.clone()).unwrap();
+            for id in [1, 2, 4, 5] {
+                journal.accepted(&job(id, 2)).unwrap();
+            }
+            journal.started(&[2]).unwrap();
+            journal.started(&[2]).unwrap();
+        }
+
+        // The coordinator no longer has job 5 and now returns a different
+        // input for job 4.
+        let worker = worker(2).with_journal(JobJournal::open(config.clone()).unwrap());
+        let (tx, rx) = mpsc::channel(4);
+        tx.send(job(3, 2)).await.unwrap();
+        drop(tx);
+        let mut source = Coordinator {
+            rx,
+            assigned: vec![job(1, 2), job(2, 2), job(4, 3)],
+        };
+        let mut results = Vec::new();
+        let stats = worker.start(&mut source, &mut results).await.unwrap();
+
+        assert_eq!(
+            stats,
+            WorkerStats {
+                accepted: 1,
+                succeeded: 2,
+                failed: 0,
+                resumed: 1,
+                abandoned: 3,
+            }
+        );
+        // The coordinator hears about the abandoned jobs first.
+        let abandoned: Vec<_> = results[..3].iter().map(|r| r.job_id[0]).collect();
+        assert_eq!(abandoned, vec![2, 4, 5]);
+        assert!(results[..3].iter().all(|r| r.result.is_err()));
+        let resumed = results.iter().find(|r| r.job_id == [1]).unwrap();
+        assert!(resumed.result.is_ok());
+        assert_eq!(JobJournal::open(config).unwrap().pending(), 0);
+    }
+
+    /// Rejects the first `failures` submissions, then accepts.
+    struct FlakySink {
+        failures: usize,
+        accepted: Vec<JobOutcome>,
+    }
+
+    #[async_trait]
+    impl ResultSink for FlakySink {
+        async fn submit(&mut self, outcome: JobOutcome) -> Result<()> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                anyhow::bail!("coordinator unreachable");
+            }
+            self.accepted.push(outcome);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn rejected_outcomes_stay_journaled_until_acknowledged() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = JournalConfig::new(dir.path().join("jobs.journal"));
+        JobJournal::open(config.clone())
+            .unwrap()
+            .accepted(&job(1, 2))
+            .unwrap();
+
+        // The sink never accepts: the worker keeps running, drains and
+        // leaves both jobs for the next start.
+        let first = worker(1).with_journal(JobJournal::open(config.clone()).unwrap());
+        let (tx, mut rx) = mpsc::channel(1);
+        tx.send(job(2, 2)).await.unwrap();
+        drop(tx);
+        let mut sink = FlakySink {
+            failures: usize::MAX,
+            accepted: Vec::new(),
+        };
+        let stats = first.start(&mut rx, &mut sink).await.unwrap();
+        assert_eq!((stats.abandoned, stats.succeeded), (1, 1));
+        assert_eq!(JobJournal::open(config.clone()).unwrap().pending(), 2);
+
+        // Once the sink recovers, the retried outcomes clear the journal.
+        let second = worker(1).with_journal(JobJournal::open(config.clone()).unwrap());
+        let (tx, mut rx) = mpsc::channel(1);
+        tx.send(job(3, 2)).await.unwrap();
+        drop(tx);
+        let mut sink = FlakySink {
+            failures: 1,
+            accepted: Vec::new(),
+        };
+        second.start(&mut rx, &mut sink).await.unwrap();
+        let mut accepted: Vec<u8> = sink.accepted.iter().map(|o| o.job_id[0]).collect();
+        accepted.sort();
+        assert_eq!(accepted, vec![1, 2, 3]);
+        assert_eq!(JobJournal::open(config).unwrap().pending(), 0);
+    }
+
+    #[tokio::test]
+    async fn reports_capabilities_at_startup() {
+        let (report_tx, mut report_rx) = mpsc::channel(4);
+        let config = BenchmarkConfig {
+            shapes: vec![BenchmarkShape::new("tiny", 8, 8, 1)],
+            iterations: 1,
+            ..BenchmarkConfig::default()
+        };
+        let worker = worker(1).with_capability_reporter(Box::new(report_tx), config);
+        assert!(worker.capabilities().is_none());
+
+        let (tx, mut rx) = mpsc::channel(4);
+        tx.send(job(1, 2)).await.unwrap();
+        drop(tx);
+        let mut results = Vec::new();
+        worker.start(&mut rx, &mut results).await.unwrap();
+
+        let report = report_rx.try_recv().unwrap();
+        assert_eq!(report.worker_id, vec![1]);
+        assert_eq!(report.shapes.len(), 1);
+        assert!(report.capabilities.contains(&"shape:tiny".to_string()));
+        assert!(report
+            .capabilities
+            .contains(&format!("tier:{}", report.hardware_tier.as_str())));
+        assert_eq!(worker.capabilities(), Some(report));
+        // The benchmark model does not stop real jobs from running.
+        assert!(results[0].result.is_ok());
+    }
+}
@@ -0,0 +1,539 @@
+// ============================================================================
+// INFERENCE SANDBOX - Each run in a locked-down child process
+// ============================================================================
+// A model is untrusted input. `SandboxedEngine` runs every inference in a
+// fresh child so a hostile graph cannot reach the worker's memory (provider
+// key, sealed outputs) or stall the job loop:
+//
+//   parent: spawn child with an empty environment and only stdio, under
+//           RLIMIT_AS / RLIMIT_CPU / RLIMIT_NOFILE / RLIMIT_FSIZE, dying
+//           with the parent (PDEATHSIG)
+//   child:  read the request from stdin, then install a seccomp allow-list
+//           of the syscalls pure computation needs (memory, stdio, exit);
+//           anything else kills it with SIGSYS. Then run the reference
+//           engine and write the response to stdout
+//   parent: enforce a wall-clock deadline and map the exit status and CPU
+//           time used to a `SandboxError`
+//
+// The child is any binary that calls `run_child_if_requested()` first thing
+// in `main` (the runtime re-executes itself), or `aether-inference-sandbox`.
+// ============================================================================
+
+use crate::engine::{InferenceEngine, InferenceOutput, ReferenceEngine};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// First argument that switches a binary into sandbox-child mode.
+pub const SANDBOX_ARG: &str = "--aether-inference-sandbox";
+
+/// Largest stderr tail kept for diagnostics.
+const STDERR_LIMIT: usize = 4096;
+
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Address-space limit for the child.
+    pub memory_limit_bytes: u64,
+    /// CPU seconds before the kernel kills the child.
+    pub cpu_time_secs: u64,
+    /// Wall-clock limit, covering time spent blocked as well as on CPU.
+    pub wall_timeout: Duration,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit_bytes: 2 * 1024 * 1024 * 1024,
+            cpu_time_secs: 30,
+            wall_timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Why a sandboxed run failed, as reported back to the job loop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SandboxError {
+    /// The model ran but the engine rejected it (bad input, out of gas, ...).
+    Engine(String),
+    WallTimeout(Duration),
+    CpuTimeExceeded,
+    MemoryExceeded,
+    /// The child made a syscall the filter forbids.
+    SyscallDenied,
+    Crashed(String),
+    Protocol(String),
+}
+
+impl fmt::Display for SandboxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandboxError::Engine(msg) => write!(f, "{msg}"),
+            SandboxError::WallTimeout(limit) => write!(f, "inference exceeded {limit:?} wall time"),
+            SandboxError::CpuTimeExceeded => write!(f, "inference exceeded its CPU time limit"),
+            SandboxError::MemoryExceeded => write!(f, "inference exceeded its memory limit"),
+            SandboxError::SyscallDenied => write!(f, "inference attempted a forbidden syscall"),
+            SandboxError::Crashed(detail) => write!(f, "inference process crashed: {detail}"),
+            SandboxError::Protocol(detail) => write!(f, "sandbox protocol error: {detail}"),
+        }
+    }
+}
+
+impl std::error::Error for SandboxError {}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum ChildResponse {
+    Ok(InferenceOutput),
+    Err(String),
+}
+
+/// Runs each inference in a child process; models are kept in the parent and
+/// shipped to the child with every request.
+pub struct SandboxedEngine {
+    program: PathBuf,
+    args: Vec<String>,
+    config: SandboxConfig,
+    models: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl SandboxedEngine {
+    /// `program args...` must end up in `run_child_if_requested`; `SANDBOX_ARG`
+    /// is appended by the caller's choice of `args`.
+    pub fn new(program: impl Into<PathBuf>, args: Vec<String>, config: SandboxConfig) -> Self {
+        Self {
+            program: program.into(),
+            args,
+            config,
+            models: HashMap::new(),
+        }
+    }
+
+    /// Re-execute the current binary as the sandbox child.
+    pub fn current_exe(config: SandboxConfig) -> Result<Self> {
+        let program = std::env::current_exe().context("locating current executable")?;
+        Ok(Self::new(program, vec![SANDBOX_ARG.to_string()], config))
+    }
+
+    fn spawn_run(&self, model: &[u8], input: &[u8], gas_limit: u64) -> Result<InferenceOutput> {
+        let mut command = Command::new(&self.program);
+        command
+            .args(&self.args)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let limits = self.config.clone();
+        // SAFETY: the closure runs between fork and exec and only makes
+        // async-signal-safe syscalls (setrlimit, prctl) on stack values.
+        unsafe {
+            command.pre_exec(move || apply_limits(&limits));
+        }
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("spawning sandbox {}", self.program.display()))?;
+
+        let mut stdout = child.stdout.take().expect("piped stdout");
+        let mut stderr = child.stderr.take().expect("piped stderr");
+        let out_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.read_to_end(&mut buf).map(|_| buf)
+        });
+        let err_reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = stderr.read_to_end(&mut buf);
+            let start = buf.len().saturating_sub(STDERR_LIMIT);
+            String::from_utf8_lossy(&buf[start..]).into_owned()
+        });
+
+        // A child that dies early closes its stdin; its exit status explains
+        // why, so a failed write is not itself the error.
+        if let Some(mut stdin) = child.stdin.take() {
+            let _ = stdin.write_all(&encode_request(model, input, gas_limit));
+        }
+
+        let deadline = Instant::now() + self.config.wall_timeout;
+        let (status, cpu_time) = loop {
+            if let Some(reaped) = try_reap(child.id())? {
+                break reaped;
+            }
+            if Instant::now() >= deadline {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(SandboxError::WallTimeout(self.config.wall_timeout).into());
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        };
+
+        let stdout = out_reader
+            .join()
+            .map_err(|_| anyhow::anyhow!("sandbox stdout reader panicked"))??;
+        let stderr = err_reader.join().unwrap_or_default();
+        if !status.success() {
+            let cpu_limit = Duration::from_secs(self.config.cpu_time_secs);
+            return Err(classify_exit(
+                status.code(),
+                status.signal(),
+                cpu_time >= cpu_limit,
+                &stderr,
+            )
+            .into());
+        }
+        match serde_json::from_slice(&stdout) {
+            Ok(ChildResponse::Ok(output)) => Ok(output),
+            Ok(ChildResponse::Err(msg)) => Err(SandboxError::Engine(msg).into()),
+            Err(e) => Err(SandboxError::Protocol(format!("bad response: {e}")).into()),
+        }
+    }
+}
+
+impl InferenceEngine for SandboxedEngine {
+    fn name(&self) -> &str {
+        "reference-sandboxed"
+    }
+
+    fn load(&mut self, model_hash: &[u8], model_bytes: &[u8]) -> Result<()> {
+        // Structural validation only; nothing from the model executes here.
+        ReferenceEngine::default().load(model_hash, model_bytes)?;
+        self.models
+            .insert(model_hash.to_vec(), model_bytes.to_vec());
+        Ok(())
+    }
+
+    fn is_loaded(&self, model_hash: &[u8]) -> bool {
+        self.models.contains_key(model_hash)
+    }
+
+    fn run(&self, model_hash: &[u8], input: &[u8], gas_limit: u64) -> Result<InferenceOutput> {
+        let model = self
+            .models
+            .get(model_hash)
+            .ok_or_else(|| anyhow::anyhow!("model {} not loaded", hex::encode(model_hash)))?;
+        self.spawn_run(model, input, gas_limit)
+    }
+}
+
+fn apply_limits(config: &SandboxConfig) -> std::io::Result<()> {
+    let set = |resource, soft: u64, hard: u64| {
+        let limit = libc::rlimit {
+            rlim_cur: soft as libc::rlim_t,
+            rlim_max: hard as libc::rlim_t,
+        };
+        // SAFETY: `limit` is a valid rlimit for the duration of the call.
+        if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    };
+    set(
+        libc::RLIMIT_AS,
+        config.memory_limit_bytes,
+        config.memory_limit_bytes,
+    )?;
+    // SIGXCPU at the soft limit, SIGKILL one second later.
+    set(
+        libc::RLIMIT_CPU,
+        config.cpu_time_secs,
+        config.cpu_time_secs + 1,
+    )?;
+    set(libc::RLIMIT_NOFILE, 8, 8)?;
+    set(libc::RLIMIT_FSIZE, 0, 0)?;
+    set(libc::RLIMIT_CORE, 0, 0)?;
+
+    #[cfg(target_os = "linux")]
+    // SAFETY: plain prctl with integer arguments.
+    unsafe {
+        if libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Reap `pid` if it has exited, returning its status and the CPU time it
+/// used. `Child::try_wait` does not report resource usage.
+fn try_reap(pid: u32) -> std::io::Result<Option<(std::process::ExitStatus, Duration)>> {
+    let mut status = 0;
+    // SAFETY: rusage is plain old data; wait4 fills it in.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: both out-pointers are valid for the duration of the call.
+    let reaped = unsafe { libc::wait4(pid as libc::pid_t, &mut status, libc::WNOHANG, &mut usage) };
+    if reaped < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    if reaped == 0 {
+        return Ok(None);
+    }
+    let timeval = |tv: libc::timeval| {
+        Duration::from_secs(tv.tv_sec as u64) + Duration::from_micros(tv.tv_usec as u64)
+    };
+    let cpu_time = timeval(usage.ru_utime) + timeval(usage.ru_stime);
+    Ok(Some((std::process::ExitStatus::from_raw(status), cpu_time)))
+}
+
+/// `cpu_exhausted` says whether the child used up its CPU limit, which is
+/// what tells the rlimit's SIGKILL apart from any other.
+fn classify_exit(
+    code: Option<i32>,
+    signal: Option<i32>,
+    cpu_exhausted: bool,
+    stderr: &str,
+) -> SandboxError {
+    if stderr.contains("memory allocation of") {
+        return SandboxError::MemoryExceeded;
+    }
+    match signal {
+        Some(libc::SIGXCPU) => SandboxError::CpuTimeExceeded,
+        // The hard CPU limit is enforced with SIGKILL.
+        Some(libc::SIGKILL) if cpu_exhausted => SandboxError::CpuTimeExceeded,
+        // The seccomp filter kills with SIGSYS.
+        Some(libc::SIGSYS) => SandboxError::SyscallDenied,
+        Some(sig) => SandboxError::Crashed(format!("signal {sig}: {}", stderr.trim())),
+        None => SandboxError::Crashed(format!(
+            "exit code {}: {}",
+            code.unwrap_or(-1),
+            stderr.trim()
+        )),
+    }
+}
+
+/// `[u32 model_len][model][u32 input_len][input][u64 gas_limit]`, little-endian.
+fn encode_request(model: &[u8], input: &[u8], gas_limit: u64) -> Vec<u8> {
+    let mut out = Vec::with_capacity(16 + model.len() + input.len());
+    out.extend_from_slice(&(model.len() as u32).to_le_bytes());
+    out.extend_from_slice(model);
+    out.extend_from_slice(&(input.len() as u32).to_le_bytes());
+    out.extend_from_slice(input);
+    out.extend_from_slice(&gas_limit.to_le_bytes());
+    out
+}
+
+fn decode_request(bytes: &[u8]) -> Result<(&[u8], &[u8], u64)> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+        if bytes.len() < len {
+            bail!("truncated sandbox request");
+        }
+        let (head, rest) = bytes.split_at(len);
+        *bytes = rest;
+        Ok(head)
+    }
+    let mut rest = bytes;
+    let model_len = u32::from_le_bytes(take(&mut rest, 4)?.try_into()?) as usize;
+    let model = take(&mut rest, model_len)?;
+    let input_len = u32::from_le_bytes(take(&mut rest, 4)?.try_into()?) as usize;
+    let input = take(&mut rest, input_len)?;
+    let gas_limit = u64::from_le_bytes(take(&mut rest, 8)?.try_into()?);
+    if !rest.is_empty() {
+        bail!("trailing bytes in sandbox request");
+    }
+    Ok((model, input, gas_limit))
+}
+
+/// If this process was started as a sandbox child, serve one request and
+/// exit. Call before anything else in `main` (in particular before starting
+/// threads or an async runtime).
+pub fn run_child_if_requested() {
+    if std::env::args().nth(1).as_deref() == Some(SANDBOX_ARG) {
+        std::process::exit(match child_main() {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("sandbox child failed: {e:#}");
+                1
+            }
+        });
+    }
+}
+
+fn child_main() -> Result<()> {
+    let mut request = Vec::new();
+    std::io::stdin().read_to_end(&mut request)?;
+    let (model, input, gas_limit) = decode_request(&request)?;
+    // Parse before the filter goes on: everything after is pure computation
+    // plus the final write to stdout.
+    let mut engine = ReferenceEngine::default();
+    let loaded = engine.load(b"sandbox", model);
+
+    #[cfg(target_os = "linux")]
+    install_syscall_filter()?;
+
+    let response = match loaded.and_then(|()| engine.run(b"sandbox", input, gas_limit)) {
+        Ok(output) => ChildResponse::Ok(output),
+        Err(e) => ChildResponse::Err(e.to_string()),
+    };
+    let mut stdout = std::io::stdout().lock();
+    serde_json::to_writer(&mut stdout, &response)?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Restrict the calling thread (and anything it later spawns) to the
+/// syscalls a sandboxed inference needs; any other syscall kills the whole
+/// process with SIGSYS. Cannot be undone.
+#[cfg(target_os = "linux")]
+pub fn install_syscall_filter() -> Result<()> {
+    seccomp::install()
+}
+
+#[cfg(target_os = "linux")]
+mod seccomp {
+    use anyhow::{bail, Result};
+
+    #[cfg(target_arch = "x86_64")]
+    const AUDIT_ARCH: u32 = 0xC000_003E;
+    #[cfg(target_arch = "aarch64")]
+    const AUDIT_ARCH: u32 = 0xC000_00B7;
+
+    /// Offsets into `struct seccomp_data`.
+    const NR_OFFSET: u32 = 0;
+    const ARCH_OFFSET: u32 = 4;
+
+    /// Syscalls the child may make once the engine is loaded: memory
+    /// management, stdio on already open descriptors, signal plumbing for
+    /// panics and aborts, and exit. No open, socket, exec, clone or ptrace.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    fn allowed() -> Vec<libc::c_long> {
+        vec![
+            libc::SYS_read,
+            libc::SYS_write,
+            libc::SYS_close,
+            libc::SYS_brk,
+            libc::SYS_mmap,
+            libc::SYS_munmap,
+            libc::SYS_mremap,
+            libc::SYS_mprotect,
+            libc::SYS_madvise,
+            libc::SYS_futex,
+            libc::SYS_getrandom,
+            libc::SYS_clock_gettime,
+            libc::SYS_sched_yield,
+            libc::SYS_rt_sigaction,
+            libc::SYS_rt_sigprocmask,
+            libc::SYS_rt_sigreturn,
+            libc::SYS_sigaltstack,
+            libc::SYS_getpid,
+            libc::SYS_gettid,
+            libc::SYS_tgkill,
+            libc::SYS_exit,
+            libc::SYS_exit_group,
+        ]
+    }
+
+    fn stmt(code: u32, k: u32) -> libc::sock_filter {
+        libc::sock_filter {
+            code: code as u16,
+            jt: 0,
+            jf: 0,
+            k,
+        }
+    }
+
+    fn jeq(k: u32, jt: u8, jf: u8) -> libc::sock_filter {
+        libc::sock_filter {
+            code: (libc::BPF_JMP | libc::BPF_JEQ | libc::BPF_K) as u16,
+            jt,
+            jf,
+            k,
+        }
+    }
+
+    /// Allow-list filter: a foreign syscall ABI or any syscall not in
+    /// `allowed()` kills the process. x32 calls on x86_64 carry the
+    /// `__X32_SYSCALL_BIT` and so never match an allowed number.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    pub fn install() -> Result<()> {
+        let load = libc::BPF_LD | libc::BPF_W | libc::BPF_ABS;
+        let ret = libc::BPF_RET | libc::BPF_K;
+        let mut program = vec![
+            stmt(load, ARCH_OFFSET),
+            jeq(AUDIT_ARCH, 1, 0),
+            stmt(ret, libc::SECCOMP_RET_KILL_PROCESS),
+            stmt(load, NR_OFFSET),
+        ];
+        for nr in allowed() {
+            program.push(jeq(nr as u32, 0, 1));
+            program.push(stmt(ret, libc::SECCOMP_RET_ALLOW));
+        }
+        program.push(stmt(ret, libc::SECCOMP_RET_KILL_PROCESS));
+
+        let fprog = libc::sock_fprog {
+            len: program.len() as u16,
+            filter: program.as_mut_ptr(),
+        };
+        // SAFETY: `fprog` points at `program`, which outlives both calls; the
+        // kernel copies the filter during PR_SET_SECCOMP.
+        unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                bail!("PR_SET_NO_NEW_PRIVS: {}", std::io::Error::last_os_error());
+            }
+            if libc::prctl(
+                libc::PR_SET_SECCOMP,
+                libc::SECCOMP_MODE_FILTER,
+                &fprog as *const libc::sock_fprog,
+            ) != 0
+            {
+                bail!("PR_SET_SECCOMP: {}", std::io::Error::last_os_error());
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    pub fn install() -> Result<()> {
+        bail!("seccomp sandbox is not supported on this architecture")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_roundtrip() {
+        let encoded = encode_request(b"model", &[1, 2, 3], 42);
+        assert_eq!(
+            decode_request(&encoded).unwrap(),
+            (&b"model"[..], &[1u8, 2, 3][..], 42)
+        );
+        assert!(decode_request(&encoded[..encoded.len() - 1]).is_err());
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(decode_request(&trailing).is_err());
+    }
+
+    #[test]
+    fn classifies_child_exits() {
+        assert_eq!(
+            classify_exit(None, Some(libc::SIGXCPU), true, ""),
+            SandboxError::CpuTimeExceeded
+        );
+        assert_eq!(
+            classify_exit(None, Some(libc::SIGKILL), true, ""),
+            SandboxError::CpuTimeExceeded
+        );
+        assert!(matches!(
+            classify_exit(None, Some(libc::SIGKILL), false, ""),
+            SandboxError::Crashed(_)
+        ));
+        assert_eq!(
+            classify_exit(None, Some(libc::SIGSYS), false, ""),
+            SandboxError::SyscallDenied
+        );
+        assert_eq!(
+            classify_exit(
+                None,
+                Some(libc::SIGABRT),
+                false,
+                "memory allocation of 1048576 bytes failed"
+            ),
+            SandboxError::MemoryExceeded
+        );
+        assert!(matches!(
+            classify_exit(Some(1), None, false, "sandbox child failed: truncated"),
+            SandboxError::Crashed(msg) if msg.contains("truncated")
+        ));
+    }
+}
@@ -0,0 +1,744 @@
+// ============================================================================
+// TEE ATTESTATION - Quotes bound to the job being executed
+// ============================================================================
+// Every result carries a quote whose `report_data` commits to
+// H(job_id || input_hash || model_hash || code_hash || seed). The hardware
+// specifics live behind `TeeBackend`, so one worker binary runs on any cloud
+// TEE; each backend is a cargo feature:
+//
+// - `sev-snp`: quotes via configfs-tsm (`sev_guest`), sealing keys from the
+//   firmware's MSG_KEY_REQ (`/dev/sev-guest`), bound to the launch measurement
+// - `tdx`: quotes via configfs-tsm (`tdx_guest`); TDX has no hardware sealing
+//   key, so sealing must go through a key broker
+// - `nitro`: attestation documents from the Nitro Secure Module (`/dev/nsm`)
+// - simulation (always built): deterministic quotes for dev mode, which
+//   validators only accept when configured for it
+// ============================================================================
+
+use aether_verifiers_tee::{
+    AttestationReport, ReportDataBinding, SimulatedSigner, TeeType, REPORT_DATA_LEN,
+};
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256, Sha384};
+use std::fs;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(any(feature = "sev-snp", feature = "tdx", feature = "nitro"))]
+const MEASUREMENT_LEN: usize = 48;
+
+/// A trusted execution environment the worker can attest from.
+pub trait TeeBackend: Send + Sync {
+    fn tee_type(&self) -> TeeType;
+
+    /// Launch measurement of the running TEE (48 bytes).
+    fn measurement(&self) -> Result<Vec<u8>>;
+
+    /// Produce a quote carrying `report_data`.
+    fn quote(&self, report_data: &[u8; REPORT_DATA_LEN]) -> Result<AttestationReport>;
+
+    /// A key only this TEE (at this measurement) can derive, for sealing
+    /// local state such as provider keys. `label` separates key purposes.
+    fn sealing_key(&self, label: &[u8]) -> Result<[u8; 32]>;
+}
+
+/// Build the backend named by `WorkerConfig::tee_type`.
+pub fn backend_from_config(tee_type: &str) -> Result<Box<dyn TeeBackend>> {
+    match tee_type.to_ascii_lowercase().as_str() {
+        #[cfg(feature = "sev-snp")]
+        "sev-snp" | "sev_snp" | "snp" => Ok(Box::new(snp::SevSnpBackend::new())),
+        #[cfg(feature = "tdx")]
+        "tdx" | "intel-tdx" | "intel_tdx" => Ok(Box::new(tdx::TdxBackend::new())),
+        #[cfg(feature = "nitro")]
+        "nitro" | "aws-nitro" | "aws_nitro" => Ok(Box::new(nitro::NitroBackend::new())),
+        "simulation" | "sim" | "dev" => Ok(Box::new(SimulatedBackend::new())),
+        other => bail!("TEE type {other} is unknown or not compiled into this worker"),
+    }
+}
+
+/// Per-job values bound into the quote.
+#[derive(Debug, Clone)]
+pub struct AttestationContext {
+    pub job_id: Vec<u8>,
+    pub input_hash: [u8; 32],
+    pub model_hash: Vec<u8>,
+    pub slot_hash: Vec<u8>,
+    pub seed: u64,
+}
+
+pub struct Attester {
+    backend: Box<dyn TeeBackend>,
+    code_hash: [u8; 32],
+}
+
+impl Attester {
+    pub fn new(backend: Box<dyn TeeBackend>) -> Self {
+        Self {
+            backend,
+            code_hash: *code_hash(),
+        }
+    }
+
+    pub fn backend(&self) -> &dyn TeeBackend {
+        self.backend.as_ref()
+    }
+
+    /// Hash of the running worker binary, bound into every quote.
+    pub fn code_hash(&self) -> [u8; 32] {
+        self.code_hash
+    }
+
+    pub fn report_data(&self, ctx: &AttestationContext) -> [u8; REPORT_DATA_LEN] {
+        ReportDataBinding {
+            job_id: &ctx.job_id,
+            input_hash: &ctx.input_hash,
+            model_hash: &ctx.model_hash,
+            code_hash: &self.code_hash,
+            slot_hash: &ctx.slot_hash,
+            seed: ctx.seed,
+        }
+        .report_data()
+    }
+
+    /// Produce a quote binding `ctx`.
+    pub fn attest(&self, ctx: &AttestationContext) -> Result<AttestationReport> {
+        let report_data = self.report_data(ctx);
+        let report = self.backend.quote(&report_data)?;
+        if report.nonce != report_data {
+            bail!("TEE backend returned a quote for different report data");
+        }
+        Ok(report)
+    }
+}
+
+/// Dev-mode backend: measurement is derived from the worker binary and
+/// quotes are signed with the published devnet key, which only verifiers
+/// in dev mode accept.
+pub struct SimulatedBackend {
+    measurement: Vec<u8>,
+    signer: SimulatedSigner,
+}
+
+impl SimulatedBackend {
+    pub fn new() -> Self {
+        Self {
+            measurement: Sha384::digest(code_hash()).to_vec(),
+            signer: SimulatedSigner::devnet(),
+        }
+    }
+}
+
+impl Default for SimulatedBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TeeBackend for SimulatedBackend {
+    fn tee_type(&self) -> TeeType {
+        TeeType::Simulation
+    }
+
+    fn measurement(&self) -> Result<Vec<u8>> {
+        Ok(self.measurement.clone())
+    }
+
+    fn quote(&self, report_data: &[u8; REPORT_DATA_LEN]) -> Result<AttestationReport> {
+        self.signer
+            .quote(&self.measurement, report_data, unix_now())
+    }
+
+    fn sealing_key(&self, label: &[u8]) -> Result<[u8; 32]> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"AETHER-SIMULATED-SEAL");
+        hasher.update(&self.measurement);
+        hasher.update(label);
+        Ok(hasher.finalize().into())
+    }
+}
+
+/// Quote generation through the kernel's configfs-tsm interface, shared by
+/// the SEV-SNP and TDX guest drivers:
+///
+///   mkdir /sys/kernel/config/tsm/report/<name>
+///   write report_data -> inblob, read quote <- outblob
+#[cfg(any(feature = "sev-snp", feature = "tdx"))]
+mod tsm {
+    use super::*;
+    use anyhow::Context;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    pub const TSM_REPORT_DIR: &str = "/sys/kernel/config/tsm/report";
+
+    // SEV-SNP ATTESTATION_REPORT layout (AMD SEV-SNP ABI, table 22).
+    pub const SNP_REPORT_DATA_OFFSET: usize = 0x50;
+    pub const SNP_MEASUREMENT_OFFSET: usize = 0x90;
+    pub const SNP_REPORT_LEN: usize = 0x4A0;
+
+    // TDX quote v4: 48-byte header followed by the TD report body.
+    pub const TDX_MRTD_OFFSET: usize = 48 + 16 + 48 + 48 + 8 + 8 + 8;
+    pub const TDX_REPORT_DATA_OFFSET: usize = TDX_MRTD_OFFSET + 48 * 8;
+
+    pub struct TsmQuoter {
+        dir: PathBuf,
+        sequence: AtomicU64,
+    }
+
+    impl TsmQuoter {
+        pub fn new(dir: impl Into<PathBuf>) -> Self {
+            Self {
+                dir: dir.into(),
+                sequence: AtomicU64::new(0),
+            }
+        }
+
+        pub fn quote(
+            &self,
+            tee_type: TeeType,
+            report_data: &[u8; REPORT_DATA_LEN],
+        ) -> Result<AttestationReport> {
+            let entry = self.dir.join(format!(
+                "aether-{}-{}",
+                std::process::id(),
+                self.sequence.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir(&entry).with_context(|| {
+                format!("TEE quote interface unavailable at {}", entry.display())
+            })?;
+            let quote = request_quote(&entry, report_data);
+            let _ = fs::remove_dir(&entry);
+            let (provider, quote) = quote?;
+            parse_quote(tee_type, &provider, quote, report_data)
+        }
+    }
+
+    fn request_quote(entry: &Path, report_data: &[u8]) -> Result<(String, Vec<u8>)> {
+        fs::write(entry.join("inblob"), report_data).context("writing TSM inblob")?;
+        let quote = fs::read(entry.join("outblob")).context("reading TSM outblob")?;
+        let provider =
+            fs::read_to_string(entry.join("provider")).context("reading TSM provider")?;
+        Ok((provider.trim().to_string(), quote))
+    }
+
+    /// Extract the measurement from a raw hardware quote and check its binding.
+    pub fn parse_quote(
+        tee_type: TeeType,
+        provider: &str,
+        quote: Vec<u8>,
+        report_data: &[u8; REPORT_DATA_LEN],
+    ) -> Result<AttestationReport> {
+        let (measurement, bound) = match (&tee_type, provider) {
+            (TeeType::SevSnp, "sev_guest") => {
+                if quote.len() < SNP_REPORT_LEN {
+                    bail!("SEV-SNP report too short: {} bytes", quote.len());
+                }
+                (
+                    &quote[SNP_MEASUREMENT_OFFSET..SNP_MEASUREMENT_OFFSET + MEASUREMENT_LEN],
+                    &quote[SNP_REPORT_DATA_OFFSET..SNP_REPORT_DATA_OFFSET + REPORT_DATA_LEN],
+                )
+            }
+            (TeeType::IntelTdx, "tdx_guest") => {
+                if quote.len() < TDX_REPORT_DATA_OFFSET + REPORT_DATA_LEN {
+                    bail!("TDX quote too short: {} bytes", quote.len());
+                }
+                (
+                    &quote[TDX_MRTD_OFFSET..TDX_MRTD_OFFSET + MEASUREMENT_LEN],
+                    &quote[TDX_REPORT_DATA_OFFSET..TDX_REPORT_DATA_OFFSET + REPORT_DATA_LEN],
+                )
+            }
+            (_, other) => bail!("configured for {tee_type:?} but TSM provider is {other}"),
+        };
+        if bound != report_data {
+            bail!("hardware quote does not carry the requested report data");
+        }
+        let measurement = measurement.to_vec();
+
+        Ok(AttestationReport {
+            tee_type,
+            measurement,
+            nonce: report_data.to_vec(),
+            timestamp: unix_now(),
+            signature: Vec::new(),
+            cert_chain: Vec::new(),
+            quote,
+        })
+    }
+}
+
+#[cfg(feature = "sev-snp")]
+pub mod snp {
+    use super::tsm::{TsmQuoter, TSM_REPORT_DIR};
+    use super::*;
+    use anyhow::Context;
+    use std::fs::OpenOptions;
+    use std::os::unix::io::AsRawFd;
+    use std::path::PathBuf;
+
+    const SEV_GUEST_DEVICE: &str = "/dev/sev-guest";
+    /// `_IOWR('S', 0x1, struct snp_guest_request_ioctl)`
+    const SNP_GET_DERIVED_KEY: libc::c_ulong = 0xC020_5301;
+    /// GUEST_FIELD_SELECT bit mixing the launch measurement into the key.
+    const FIELD_MEASUREMENT: u64 = 1 << 3;
+    /// Offset of the key within MSG_KEY_RSP.
+    const DERIVED_KEY_OFFSET: usize = 0x20;
+
+    #[repr(C)]
+    struct DerivedKeyReq {
+        root_key_select: u32,
+        rsvd: u32,
+        guest_field_select: u64,
+        vmpl: u32,
+        guest_svn: u32,
+        tcb_version: u64,
+    }
+
+    #[repr(C)]
+    struct GuestRequest {
+        msg_version: u8,
+        req_data: u64,
+        resp_data: u64,
+        exitinfo2: u64,
+    }
+
+    pub struct SevSnpBackend {
+        quoter: TsmQuoter,
+        device: PathBuf,
+    }
+
+    impl SevSnpBackend {
+        pub fn new() -> Self {
+            Self::with_paths(TSM_REPORT_DIR, SEV_GUEST_DEVICE)
+        }
+
+        pub fn with_paths(tsm_dir: impl Into<PathBuf>, device: impl Into<PathBuf>) -> Self {
+            Self {
+                quoter: TsmQuoter::new(tsm_dir),
+                device: device.into(),
+            }
+        }
+    }
+
+    impl Default for SevSnpBackend {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl TeeBackend for SevSnpBackend {
+        fn tee_type(&self) -> TeeType {
+            TeeType::SevSnp
+        }
+
+        fn measurement(&self) -> Result<Vec<u8>> {
+            Ok(self.quote(&[0u8; REPORT_DATA_LEN])?.measurement)
+        }
+
+        fn quote(&self, report_data: &[u8; REPORT_DATA_LEN]) -> Result<AttestationReport> {
+            self.quoter.quote(TeeType::SevSnp, report_data)
+        }
+
+        fn sealing_key(&self, label: &[u8]) -> Result<[u8; 32]> {
+            let device = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&self.device)
+                .with_context(|| format!("opening {}", self.device.display()))?;
+            let request = DerivedKeyReq {
+                root_key_select: 0, // VCEK
+                rsvd: 0,
+                guest_field_select: FIELD_MEASUREMENT,
+                vmpl: 0,
+                guest_svn: 0,
+                tcb_version: 0,
+            };
+            let mut response = [0u8; 64];
+            let mut ioctl_arg = GuestRequest {
+                msg_version: 1,
+                req_data: &request as *const DerivedKeyReq as u64,
+                resp_data: response.as_mut_ptr() as u64,
+                exitinfo2: 0,
+            };
+            // SAFETY: `ioctl_arg` points at a request and a 64-byte response
+            // buffer that both outlive the call, matching the layout the
+            // sev-guest driver expects for SNP_GET_DERIVED_KEY.
+            let rc = unsafe {
+                libc::ioctl(
+                    device.as_raw_fd(),
+                    SNP_GET_DERIVED_KEY,
+                    &mut ioctl_arg as *mut GuestRequest,
+                )
+            };
+            if rc != 0 {
+                bail!(
+                    "SNP_GET_DERIVED_KEY failed: {} (exitinfo2 {:#x})",
+                    std::io::Error::last_os_error(),
+                    ioctl_arg.exitinfo2
+                );
+            }
+            let status = u32::from_le_bytes(response[..4].try_into().expect("4 bytes"));
+            if status != 0 {
+                bail!("SNP firmware rejected key request: status {status:#x}");
+            }
+
+            let mut hasher = Sha256::new();
+            hasher.update(&response[DERIVED_KEY_OFFSET..DERIVED_KEY_OFFSET + 32]);
+            hasher.update(label);
+            Ok(hasher.finalize().into())
+        }
+    }
+}
+
+#[cfg(feature = "tdx")]
+pub mod tdx {
+    use super::tsm::{TsmQuoter, TSM_REPORT_DIR};
+    use super::*;
+    use std::path::PathBuf;
+
+    pub struct TdxBackend {
+        quoter: TsmQuoter,
+    }
+
+    impl TdxBackend {
+        pub fn new() -> Self {
+            Self::with_tsm_dir(TSM_REPORT_DIR)
+        }
+
+        pub fn with_tsm_dir(dir: impl Into<PathBuf>) -> Self {
+            Self {
+                quoter: TsmQuoter::new(dir),
+            }
+        }
+    }
+
+    impl Default for TdxBackend {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl TeeBackend for TdxBackend {
+        fn tee_type(&self) -> TeeType {
+            TeeType::IntelTdx
+        }
+
+        fn measurement(&self) -> Result<Vec<u8>> {
+            Ok(self.quote(&[0u8; REPORT_DATA_LEN])?.measurement)
+        }
+
+        fn quote(&self, report_data: &[u8; REPORT_DATA_LEN]) -> Result<AttestationReport> {
+            self.quoter.quote(TeeType::IntelTdx, report_data)
+        }
+
+        fn sealing_key(&self, _label: &[u8]) -> Result<[u8; 32]> {
+            bail!("TDX has no hardware sealing key; release one from a key broker against a quote")
+        }
+    }
+}
+
+#[cfg(feature = "nitro")]
+pub mod nitro {
+    use super::*;
+    use anyhow::Context;
+    use ciborium::value::Value;
+    use std::fs::OpenOptions;
+    use std::os::unix::io::AsRawFd;
+    use std::path::PathBuf;
+
+    const NSM_DEVICE: &str = "/dev/nsm";
+    /// `_IOWR(0x0A, 0, struct nsm_message)`
+    const NSM_IOCTL_REQUEST: libc::c_ulong = 0xC020_0A00;
+    const NSM_RESPONSE_MAX: usize = 0x3000;
+
+    #[repr(C)]
+    struct NsmMessage {
+        request: libc::iovec,
+        response: libc::iovec,
+    }
+
+    pub struct NitroBackend {
+        device: PathBuf,
+    }
+
+    impl NitroBackend {
+        pub fn new() -> Self {
+            Self::with_device(NSM_DEVICE)
+        }
+
+        pub fn with_device(device: impl Into<PathBuf>) -> Self {
+            Self {
+                device: device.into(),
+            }
+        }
+
+        fn call(&self, request: &Value) -> Result<Value> {
+            let mut encoded = Vec::new();
+            ciborium::ser::into_writer(request, &mut encoded)?;
+            let device = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(&self.device)
+                .with_context(|| format!("opening {}", self.device.display()))?;
+
+            let mut response = vec![0u8; NSM_RESPONSE_MAX];
+            let mut message = NsmMessage {
+                request: libc::iovec {
+                    iov_base: encoded.as_mut_ptr().cast(),
+                    iov_len: encoded.len(),
+                },
+                response: libc::iovec {
+                    iov_base: response.as_mut_ptr().cast(),
+                    iov_len: response.len(),
+                },
+            };
+            // SAFETY: both iovecs point at live buffers of the stated length;
+            // the driver writes at most `response.len()` bytes and updates
+            // `response.iov_len` with the actual size.
+            let rc = unsafe {
+                libc::ioctl(
+                    device.as_raw_fd(),
+                    NSM_IOCTL_REQUEST,
+                    &mut message as *mut NsmMessage,
+                )
+            };
+            if rc != 0 {
+                bail!("NSM request failed: {}", std::io::Error::last_os_error());
+            }
+            response.truncate(message.response.iov_len);
+            Ok(ciborium::de::from_reader(response.as_slice())?)
+        }
+    }
+
+    impl Default for NitroBackend {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl TeeBackend for NitroBackend {
+        fn tee_type(&self) -> TeeType {
+            TeeType::AwsNitro
+        }
+
+        fn measurement(&self) -> Result<Vec<u8>> {
+            // PCR0 measures the enclave image.
+            let response = self.call(&describe_pcr_request(0))?;
+            let pcr = response_field(&response, "DescribePCR", "data")?;
+            if pcr.len() != MEASUREMENT_LEN {
+                bail!("PCR0 has {} bytes, expected {MEASUREMENT_LEN}", pcr.len());
+            }
+            Ok(pcr)
+        }
+
+        fn quote(&self, report_data: &[u8; REPORT_DATA_LEN]) -> Result<AttestationReport> {
+            let response = self.call(&attestation_request(report_data))?;
+            let document = response_field(&response, "Attestation", "document")?;
+            Ok(AttestationReport {
+                tee_type: TeeType::AwsNitro,
+                measurement: self.measurement()?,
+                nonce: report_data.to_vec(),
+                timestamp: unix_now(),
+                signature: document,
+                cert_chain: Vec::new(),
+                quote: Vec::new(),
+            })
+        }
+
+        fn sealing_key(&self, _label: &[u8]) -> Result<[u8; 32]> {
+            bail!("Nitro enclaves have no local sealing key; decrypt one through KMS with an attestation document")
+        }
+    }
+
+    pub(super) fn attestation_request(report_data: &[u8]) -> Value {
+        Value::Map(vec![(
+            Value::Text("Attestation".into()),
+            Value::Map(vec![
+                (
+                    Value::Text("user_data".into()),
+                    Value::Bytes(report_data.to_vec()),
+                ),
+                (Value::Text("nonce".into()), Value::Null),
+                (Value::Text("public_key".into()), Value::Null),
+            ]),
+        )])
+    }
+
+    fn describe_pcr_request(index: u16) -> Value {
+        Value::Map(vec![(
+            Value::Text("DescribePCR".into()),
+            Value::Map(vec![(
+                Value::Text("index".into()),
+                Value::Integer(index.into()),
+            )]),
+        )])
+    }
+
+    /// Pull `{ variant: { field: bytes } }` out of an NSM response.
+    pub(super) fn response_field(response: &Value, variant: &str, field: &str) -> Result<Vec<u8>> {
+        let lookup = |map: &Value, key: &str| -> Option<Value> {
+            map.as_map()?
+                .iter()
+                .find(|(k, _)| k.as_text() == Some(key))
+                .map(|(_, v)| v.clone())
+        };
+        if let Some(error) = lookup(response, "Error") {
+            bail!("NSM returned error: {error:?}");
+        }
+        lookup(response, variant)
+            .and_then(|inner| lookup(&inner, field))
+            .and_then(|value| value.as_bytes().cloned())
+            .ok_or_else(|| anyhow::anyhow!("malformed NSM {variant} response"))
+    }
+}
+
+/// SHA-256 of the worker executable, computed once per process.
+fn code_hash() -> &'static [u8; 32] {
+    static CODE_HASH: OnceLock<[u8; 32]> = OnceLock::new();
+    CODE_HASH.get_or_init(|| {
+        std::env::current_exe()
+            .and_then(fs::read)
+            .map(|bytes| Sha256::digest(bytes).into())
+            .unwrap_or([0u8; 32])
+    })
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_verifiers_tee::TeeVerifier;
+
+    fn ctx() -> AttestationContext {
+        AttestationContext {
+            job_id: b"job-1".to_vec(),
+            input_hash: [1u8; 32],
+            model_hash: vec![2u8; 32],
+            slot_hash: vec![4u8; 32],
+            seed: 42,
+        }
+    }
+
+    #[test]
+    fn selects_backends_by_name() {
+        let sim = backend_from_config("simulation").unwrap();
+        assert_eq!(sim.tee_type(), TeeType::Simulation);
+        #[cfg(feature = "sev-snp")]
+        assert_eq!(
+            backend_from_config("SEV-SNP").unwrap().tee_type(),
+            TeeType::SevSnp
+        );
+        #[cfg(feature = "tdx")]
+        assert_eq!(
+            backend_from_config("tdx").unwrap().tee_type(),
+            TeeType::IntelTdx
+        );
+        assert!(backend_from_config("sgx1").is_err());
+    }
+
+    #[test]
+    fn simulated_quote_binds_job() {
+        let attester = Attester::new(Box::new(SimulatedBackend::new()));
+        let report = attester.attest(&ctx()).unwrap();
+        assert_eq!(report.nonce, attester.report_data(&ctx()).to_vec());
+
+        let other = AttestationContext { seed: 43, ..ctx() };
+        assert_ne!(attester.report_data(&other), attester.report_data(&ctx()));
+        let later = AttestationContext {
+            slot_hash: vec![5u8; 32],
+            ..ctx()
+        };
+        assert_ne!(attester.report_data(&later), attester.report_data(&ctx()));
+
+        let mut verifier = TeeVerifier::new();
+        verifier.add_approved_measurement(attester.backend().measurement().unwrap());
+        assert!(verifier.verify(&report, report.timestamp).is_err());
+        verifier.enable_dev_mode();
+        verifier.verify(&report, report.timestamp).unwrap();
+    }
+
+    #[test]
+    fn simulated_sealing_keys_are_labelled() {
+        let backend = SimulatedBackend::new();
+        let a = backend.sealing_key(b"provider-key").unwrap();
+        assert_eq!(
+            a,
+            SimulatedBackend::new()
+                .sealing_key(b"provider-key")
+                .unwrap()
+        );
+        assert_ne!(a, backend.sealing_key(b"journal").unwrap());
+    }
+
+    #[cfg(feature = "sev-snp")]
+    #[test]
+    fn parses_hardware_quotes() {
+        use tsm::*;
+
+        let attester = Attester::new(Box::new(SimulatedBackend::new()));
+        let report_data = attester.report_data(&ctx());
+
+        let mut snp = vec![0u8; SNP_REPORT_LEN];
+        snp[SNP_REPORT_DATA_OFFSET..SNP_REPORT_DATA_OFFSET + REPORT_DATA_LEN]
+            .copy_from_slice(&report_data);
+        snp[SNP_MEASUREMENT_OFFSET..SNP_MEASUREMENT_OFFSET + MEASUREMENT_LEN].fill(0xAB);
+        let report = parse_quote(TeeType::SevSnp, "sev_guest", snp.clone(), &report_data).unwrap();
+        assert_eq!(report.measurement, vec![0xAB; MEASUREMENT_LEN]);
+        assert_eq!(report.nonce, report_data.to_vec());
+
+        let mut tdx = vec![0u8; TDX_REPORT_DATA_OFFSET + REPORT_DATA_LEN];
+        tdx[TDX_REPORT_DATA_OFFSET..].copy_from_slice(&report_data);
+        tdx[TDX_MRTD_OFFSET..TDX_MRTD_OFFSET + MEASUREMENT_LEN].fill(0xCD);
+        let report = parse_quote(TeeType::IntelTdx, "tdx_guest", tdx, &report_data).unwrap();
+        assert_eq!(report.measurement, vec![0xCD; MEASUREMENT_LEN]);
+
+        // Wrong provider, or a quote bound to different data, is refused.
+        assert!(parse_quote(TeeType::IntelTdx, "sev_guest", snp.clone(), &report_data).is_err());
+        assert!(parse_quote(TeeType::SevSnp, "sev_guest", snp, &[0u8; REPORT_DATA_LEN]).is_err());
+    }
+
+    #[cfg(feature = "sev-snp")]
+    #[test]
+    fn missing_hardware_fails_closed() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend =
+            snp::SevSnpBackend::with_paths(dir.path().join("absent"), dir.path().join("sev"));
+        let attester = Attester::new(Box::new(backend));
+        assert!(attester.attest(&ctx()).is_err());
+        assert!(attester.backend().sealing_key(b"k").is_err());
+    }
+
+    #[cfg(feature = "nitro")]
+    #[test]
+    fn nitro_messages_roundtrip() {
+        use ciborium::value::Value;
+
+        let request = nitro::attestation_request(&[7u8; REPORT_DATA_LEN]);
+        let mut encoded = Vec::new();
+        ciborium::ser::into_writer(&request, &mut encoded).unwrap();
+        let decoded: Value = ciborium::de::from_reader(encoded.as_slice()).unwrap();
+        assert_eq!(decoded, request);
+
+        let response = Value::Map(vec![(
+            Value::Text("Attestation".into()),
+            Value::Map(vec![(
+                Value::Text("document".into()),
+                Value::Bytes(vec![1, 2, 3]),
+            )]),
+        )]);
+        assert_eq!(
+            nitro::response_field(&response, "Attestation", "document").unwrap(),
+            vec![1, 2, 3]
+        );
+        let error = Value::Map(vec![(
+            Value::Text("Error".into()),
+            Value::Text("InvalidArgument".into()),
+        )]);
+        assert!(nitro::response_field(&error, "Attestation", "document").is_err());
+    }
+}
@@ -0,0 +1,358 @@
+// ============================================================================
+// TRACE COMMITMENTS - KZG commitments over layer activations
+// ============================================================================
+// Each selected layer's activations are read as evaluations at x = 0..n and
+// interpolated into a polynomial, which is committed with KZG. An opening at
+// x = i therefore proves the i-th activation of that layer.
+//
+// The selected layers are also flattened, in layer order, and committed in
+// chunks under a Merkle root (`kzg_commit`), which goes into the VCR as its
+// trace root. That covers layers too long for a single commitment; any
+// flattened value can be opened with `open_trace_value`.
+//
+// Raw traces are kept on disk for the challenge window so the worker can
+// answer `KzgChallenge`s for any job it committed to, including after a
+// restart. Challenges name the VCR by its job id.
+// ============================================================================
+
+use crate::engine::LayerActivation;
+use aether_crypto_kzg::{
+    interpolate, kzg_commit, scalar_from_i64, ChunkOpening, ChunkedCommitment, KzgCommitment,
+    KzgVerifier, ScalarBytes,
+};
+use aether_verifiers_kzg::{KzgChallenge, KzgOpeningResponse, Opening};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct TraceConfig {
+    pub store_dir: PathBuf,
+    /// Layer indices to commit to; empty commits every layer.
+    pub layers: Vec<u32>,
+    /// How long traces are kept for challenges.
+    pub retention_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayerCommitment {
+    pub layer_idx: u32,
+    /// Number of activations (evaluation points) in the layer.
+    pub len: u32,
+    pub commitment: KzgCommitment,
+}
+
+/// Everything a worker commits to for one job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceCommitment {
+    pub layers: Vec<LayerCommitment>,
+    /// Chunked commitment to the flattened selected layers.
+    pub root: ChunkedCommitment,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredLayer {
+    layer_idx: u32,
+    values: Vec<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredTrace {
+    stored_at: u64,
+    layers: Vec<StoredLayer>,
+}
+
+pub struct TraceStore {
+    config: TraceConfig,
+    verifier: Arc<KzgVerifier>,
+}
+
+impl TraceStore {
+    pub fn new(config: TraceConfig, verifier: Arc<KzgVerifier>) -> Result<Self> {
+        fs::create_dir_all(&config.store_dir)
+            .with_context(|| format!("creating trace store {}", config.store_dir.display()))?;
+        Ok(Self { config, verifier })
+    }
+
+    /// Commit to the selected layers and persist them for the challenge window.
+    pub fn commit(
+        &self,
+        job_id: &[u8],
+        activations: &[LayerActivation],
+    ) -> Result<TraceCommitment> {
+        let mut layers = Vec::new();
+        let mut commitments = Vec::new();
+        for (idx, activation) in activations.iter().enumerate() {
+            let layer_idx = idx as u32;
+            if !self.config.layers.is_empty() && !self.config.layers.contains(&layer_idx) {
+                continue;
+            }
+            let commitment = self
+                .verifier
+                .commit(&layer_coefficients(&activation.values)?)?;
+            commitments.push(LayerCommitment {
+                layer_idx,
+                len: activation.values.len() as u32,
+                commitment,
+            });
+            layers.push(StoredLayer {
+                layer_idx,
+                values: activation.values.clone(),
+            });
+        }
+        if layers.is_empty() {
+            bail!("no selected layers in trace");
+        }
+        let root = self.commit_flattened(&layers)?;
+
+        let stored = StoredTrace {
+            stored_at: unix_now(),
+            layers,
+        };
+        let path = self.trace_path(job_id);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(&stored)?)
+            .with_context(|| format!("writing trace {}", tmp.display()))?;
+        fs::rename(&tmp, &path)?;
+        Ok(TraceCommitment {
+            layers: commitments,
+            root,
+        })
+    }
+
+    /// Open value `index` of the flattened trace committed for `job_id`.
+    pub fn open_trace_value(&self, job_id: &[u8], index: u64) -> Result<ChunkOpening> {
+        let stored = self.load(job_id)?;
+        let values = flatten(&stored.layers);
+        self.commit_flattened(&stored.layers)?
+            .open(&self.verifier, &values, index)
+    }
+
+    /// Open every point the challenge asks for.
+    pub fn respond_to_challenge(&self, challenge: &KzgChallenge) -> Result<KzgOpeningResponse> {
+        challenge.validate()?;
+        let stored = self.load(challenge.vcr_id.as_bytes())?;
+
+        let mut polys: HashMap<u32, (Vec<ScalarBytes>, KzgCommitment)> = HashMap::new();
+        let mut openings = Vec::with_capacity(challenge.expected_openings());
+        for (layer_idx, point_idx) in challenge.iter_points() {
+            let layer = stored
+                .layers
+                .iter()
+                .find(|l| l.layer_idx == layer_idx)
+                .ok_or_else(|| anyhow::anyhow!("layer {layer_idx} was not committed"))?;
+            if point_idx as usize >= layer.values.len() {
+                bail!(
+                    "point {point_idx} out of range for layer {layer_idx} ({} values)",
+                    layer.values.len()
+                );
+            }
+            let (coeffs, commitment) = match polys.entry(layer_idx) {
+                Entry::Occupied(entry) => entry.into_mut(),
+                Entry::Vacant(entry) => {
+                    let coeffs = layer_coefficients(&layer.values)?;
+                    let commitment = self.verifier.commit(&coeffs)?;
+                    entry.insert((coeffs, commitment))
+                }
+            };
+
+            let point = scalar_from_i64(point_idx as i64);
+            openings.push(Opening {
+                layer_idx,
+                point_idx,
+                point: point.to_vec(),
+                commitment: commitment.clone(),
+                proof: self.verifier.create_proof(coeffs, &point)?,
+            });
+        }
+        Ok(KzgOpeningResponse::new(challenge.vcr_id, openings))
+    }
+
+    /// Delete traces whose challenge window has passed. Returns how many.
+    pub fn prune_expired(&self, now_secs: u64) -> Result<usize> {
+        let mut pruned = 0;
+        for entry in fs::read_dir(&self.config.store_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("trace") {
+                continue;
+            }
+            let Ok(stored) = fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|raw| Ok(serde_json::from_slice::<StoredTrace>(&raw)?))
+            else {
+                continue;
+            };
+            if now_secs >= stored.stored_at.saturating_add(self.config.retention_secs) {
+                fs::remove_file(&path)?;
+                pruned += 1;
+            }
+        }
+        Ok(pruned)
+    }
+
+    fn load(&self, job_id: &[u8]) -> Result<StoredTrace> {
+        let raw = fs::read(self.trace_path(job_id))
+            .with_context(|| format!("no stored trace for VCR {}", hex::encode(job_id)))?;
+        serde_json::from_slice(&raw).context("corrupt stored trace")
+    }
+
+    /// Chunks are as large as the setup allows.
+    fn commit_flattened(&self, layers: &[StoredLayer]) -> Result<ChunkedCommitment> {
+        kzg_commit(
+            &self.verifier,
+            &flatten(layers),
+            self.verifier.max_degree() + 1,
+        )
+    }
+
+    fn trace_path(&self, job_id: &[u8]) -> PathBuf {
+        self.config
+            .store_dir
+            .join(format!("{}.trace", hex::encode(job_id)))
+    }
+}
+
+fn layer_coefficients(values: &[i64]) -> Result<Vec<ScalarBytes>> {
+    let evaluations: Vec<ScalarBytes> = values.iter().map(|&v| scalar_from_i64(v)).collect();
+    interpolate(&evaluations)
+}
+
+fn flatten(layers: &[StoredLayer]) -> Vec<ScalarBytes> {
+    layers
+        .iter()
+        .flat_map(|layer| layer.values.iter().map(|&v| scalar_from_i64(v)))
+        .collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_kzg::verify_chunk_opening;
+    use aether_types::H256;
+    use aether_verifiers_kzg::verify_kzg_openings;
+
+    fn activations() -> Vec<LayerActivation> {
+        [vec![9, 8, -9], vec![19, 8, -109], vec![19, 8, 0]]
+            .into_iter()
+            .enumerate()
+            .map(|(i, values)| LayerActivation {
+                node: format!("n{i}"),
+                op_type: "Add".to_string(),
+                values,
+            })
+            .collect()
+    }
+
+    fn store(dir: &std::path::Path, layers: Vec<u32>) -> TraceStore {
+        TraceStore::new(
+            TraceConfig {
+                store_dir: dir.to_path_buf(),
+                layers,
+                retention_secs: 60,
+            },
+            Arc::new(KzgVerifier::new_insecure_test(16)),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn challenge_openings_verify() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path(), vec![0, 2]);
+        let job_id = [7u8; 32];
+        let commitments = store.commit(&job_id, &activations()).unwrap().layers;
+        assert_eq!(
+            commitments.iter().map(|c| c.layer_idx).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+
+        let challenge = KzgChallenge {
+            vcr_id: H256::from_slice(&job_id).unwrap(),
+            layer_indices: vec![0, 2],
+            point_indices: vec![vec![0, 2], vec![1]],
+            deadline_slot: 10,
+        };
+        let response = store.respond_to_challenge(&challenge).unwrap();
+        verify_kzg_openings(&store.verifier, &challenge, &response).unwrap();
+
+        // Openings match the committed values and the published commitments.
+        assert_eq!(response.openings[1].proof.evaluation, scalar_from_i64(-9));
+        assert_eq!(
+            response.openings[2].commitment.commitment,
+            commitments[1].commitment.commitment
+        );
+    }
+
+    #[test]
+    fn opens_the_flattened_trace_under_its_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = TraceStore::new(
+            TraceConfig {
+                store_dir: dir.path().to_path_buf(),
+                layers: vec![0, 2],
+                retention_secs: 60,
+            },
+            Arc::new(KzgVerifier::new_insecure_test(3)),
+        )
+        .unwrap();
+        let job_id = [3u8; 32];
+        let committed = store.commit(&job_id, &activations()).unwrap();
+        assert_eq!((committed.root.len, committed.root.chunk_size), (6, 4));
+        assert_eq!(committed.root.chunks.len(), 2);
+
+        // Layer 2 follows layer 0: flattened value 5 is layer 2's last.
+        let opening = store.open_trace_value(&job_id, 5).unwrap();
+        let value =
+            verify_chunk_opening(&store.verifier, &committed.root.root, 6, 4, &opening).unwrap();
+        assert_eq!(value, scalar_from_i64(0));
+        assert!(store.open_trace_value(&job_id, 6).is_err());
+        assert!(store.open_trace_value(&[4u8; 32], 0).is_err());
+    }
+
+    #[test]
+    fn rejects_uncommitted_layers_and_points() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path(), vec![0]);
+        let job_id = [1u8; 32];
+        store.commit(&job_id, &activations()).unwrap();
+
+        let mut challenge = KzgChallenge {
+            vcr_id: H256::from_slice(&job_id).unwrap(),
+            layer_indices: vec![1],
+            point_indices: vec![vec![0]],
+            deadline_slot: 10,
+        };
+        assert!(store.respond_to_challenge(&challenge).is_err());
+
+        challenge.layer_indices = vec![0];
+        challenge.point_indices = vec![vec![3]];
+        assert!(store.respond_to_challenge(&challenge).is_err());
+
+        challenge.vcr_id = H256::zero();
+        challenge.point_indices = vec![vec![0]];
+        assert!(store.respond_to_challenge(&challenge).is_err());
+    }
+
+    #[test]
+    fn prunes_after_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path(), vec![]);
+        store.commit(&[1u8; 32], &activations()).unwrap();
+
+        let now = unix_now();
+        assert_eq!(store.prune_expired(now).unwrap(), 0);
+        assert_eq!(store.prune_expired(now + 61).unwrap(), 1);
+    }
+}
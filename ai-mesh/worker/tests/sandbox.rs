@@ -0,0 +1,103 @@
+use aether_ai_worker::benchmark::BenchmarkShape;
+use aether_ai_worker::engine::{InferenceEngine, ReferenceEngine};
+use aether_ai_worker::sandbox::{SandboxConfig, SandboxError, SandboxedEngine, SANDBOX_ARG};
+use std::time::Duration;
+
+/// Set when the test binary re-executes itself to probe the syscall filter.
+const PROBE_ENV: &str = "AETHER_SANDBOX_PROBE";
+
+fn engine(config: SandboxConfig) -> SandboxedEngine {
+    SandboxedEngine::new(
+        env!("CARGO_BIN_EXE_aether-inference-sandbox"),
+        vec![SANDBOX_ARG.to_string()],
+        config,
+    )
+}
+
+fn sandbox_error(err: anyhow::Error) -> SandboxError {
+    err.downcast::<SandboxError>()
+        .expect("structured sandbox error")
+}
+
+#[test]
+fn matches_in_process_engine() {
+    let model = BenchmarkShape::new("small", 8, 16, 2).graph().to_bytes();
+    let input = [3u8; 8];
+
+    let mut reference = ReferenceEngine::default();
+    reference.load(b"m", &model).unwrap();
+    let mut sandboxed = engine(SandboxConfig::default());
+    sandboxed.load(b"m", &model).unwrap();
+    assert!(sandboxed.is_loaded(b"m"));
+
+    assert_eq!(
+        sandboxed.run(b"m", &input, u64::MAX).unwrap(),
+        reference.run(b"m", &input, u64::MAX).unwrap()
+    );
+}
+
+#[test]
+fn reports_engine_errors() {
+    let model = BenchmarkShape::new("small", 8, 16, 2).graph().to_bytes();
+    let mut sandboxed = engine(SandboxConfig::default());
+    sandboxed.load(b"m", &model).unwrap();
+
+    let err = sandbox_error(sandboxed.run(b"m", &[1u8; 8], 1).unwrap_err());
+    assert!(matches!(err, SandboxError::Engine(ref msg) if msg.contains("gas")));
+}
+
+#[test]
+fn kills_runs_past_the_wall_timeout() {
+    let model = BenchmarkShape::new("large", 1024, 1024, 8)
+        .graph()
+        .to_bytes();
+    let mut sandboxed = engine(SandboxConfig {
+        wall_timeout: Duration::from_millis(1),
+        ..SandboxConfig::default()
+    });
+    sandboxed.load(b"m", &model).unwrap();
+
+    let err = sandbox_error(sandboxed.run(b"m", &[1u8; 1024], u64::MAX).unwrap_err());
+    assert_eq!(err, SandboxError::WallTimeout(Duration::from_millis(1)));
+}
+
+/// Runs only when re-executed by `syscall_filter_kills_forbidden_calls`:
+/// installs the filter, then makes the syscall named in `PROBE_ENV`.
+#[cfg(target_os = "linux")]
+#[test]
+fn syscall_filter_probe() {
+    let Ok(probe) = std::env::var(PROBE_ENV) else {
+        return;
+    };
+    aether_ai_worker::sandbox::install_syscall_filter().unwrap();
+    match probe.as_str() {
+        // SAFETY: plain syscall with integer arguments.
+        "socket" => unsafe {
+            libc::socket(libc::AF_INET, libc::SOCK_STREAM, 0);
+        },
+        "open" => {
+            let _ = std::fs::File::open("/etc/hostname");
+        }
+        _ => {}
+    }
+    std::process::exit(0);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn syscall_filter_kills_forbidden_calls() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let probe = |name: &str| {
+        std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["syscall_filter_probe", "--exact", "--test-threads=1"])
+            .env(PROBE_ENV, name)
+            .output()
+            .unwrap()
+            .status
+    };
+    assert_eq!(probe("none").code(), Some(0));
+    for name in ["socket", "open"] {
+        assert_eq!(probe(name).signal(), Some(libc::SIGSYS), "{name}");
+    }
+}
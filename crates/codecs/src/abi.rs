@@ -0,0 +1,140 @@
+use crate::canonical::{CanonicalReader, CanonicalWriter};
+use crate::error::Result;
+
+/// Call ABI for native programs reached through `call_program`.
+///
+/// Call data is a little-endian u16 entry-point selector followed by the
+/// arguments in canonical encoding (see [`CanonicalWriter`]): addresses are
+/// 20 raw bytes, hashes 32 raw bytes, amounts u128 and slots u64. Return
+/// data uses the same encoding. Selectors below 0x10 mutate program state;
+/// 0x10 and above are read-only views. Published selectors never change
+/// meaning; new entry points take new numbers.
+pub fn encode_call(selector: u16, args: impl FnOnce(&mut CanonicalWriter)) -> Vec<u8> {
+    let mut writer = CanonicalWriter::new();
+    writer.put_u16(selector);
+    args(&mut writer);
+    writer.finish()
+}
+
+/// Split call data into its selector and a reader over the arguments.
+pub fn decode_call(input: &[u8]) -> Result<(u16, CanonicalReader<'_>)> {
+    let mut reader = CanonicalReader::new(input);
+    let selector = reader.take_u16()?;
+    Ok((selector, reader))
+}
+
+/// Whether `selector` names a read-only view.
+pub fn is_view(selector: u16) -> bool {
+    selector >= 0x10
+}
+
+/// AIC token. The caller is the token owner for every entry point.
+pub mod aic_token {
+    /// `(to: address, amount: u128)`
+    pub const TRANSFER: u16 = 0x01;
+    /// `(spender: address, amount: u128)`
+    pub const APPROVE: u16 = 0x02;
+    /// `(from: address, to: address, amount: u128)`; spends the caller's allowance
+    pub const TRANSFER_FROM: u16 = 0x03;
+    /// `(to: address, amount: u128)`; caller must be the mint authority
+    pub const MINT: u16 = 0x04;
+    /// `(amount: u128)`
+    pub const BURN: u16 = 0x05;
+    /// `(account: address) -> u128`
+    pub const BALANCE_OF: u16 = 0x10;
+    /// `(owner: address, spender: address) -> u128`
+    pub const ALLOWANCE: u16 = 0x11;
+}
+
+/// AI job escrow. The caller is the requester or provider.
+pub mod job_escrow {
+    /// `(job_id: hash, model_hash: hash, input_hash: hash, payment: u128, deadline_slots: u64)`
+    pub const POST_JOB: u16 = 0x01;
+    /// `(job_id: hash)`
+    pub const ACCEPT_JOB: u16 = 0x02;
+    /// `(job_id: hash)`
+    pub const CHALLENGE_JOB: u16 = 0x03;
+    /// `(job_id: hash)`
+    pub const CANCEL_JOB: u16 = 0x04;
+    /// `(amount: u128)`
+    pub const DEPOSIT_BOND: u16 = 0x05;
+    /// `(amount: u128)`
+    pub const WITHDRAW_BOND: u16 = 0x06;
+    /// `(report: bytes)`; a bincode watchtower `FraudReport`
+    pub const REPORT_FRAUD: u16 = 0x07;
+    /// `(job_id: hash, provider: address, amount: u128)`; caller is the requester
+    pub const RELEASE_MILESTONE: u16 = 0x08;
+    /// `(requester: address) -> u128`
+    pub const ESCROWED_BALANCE_OF: u16 = 0x10;
+    /// `(provider: address) -> u128`
+    pub const BOND_OF: u16 = 0x11;
+}
+
+/// Governance. The caller is the voter or delegator.
+pub mod governance {
+    /// `(proposal_id: hash, vote_for: u8)`
+    pub const VOTE: u16 = 0x01;
+    /// `(proposal_id: hash)`
+    pub const FINALIZE: u16 = 0x02;
+    /// `(proposal_id: hash)`
+    pub const CANCEL: u16 = 0x03;
+    /// `(delegate: address)`
+    pub const DELEGATE: u16 = 0x04;
+    /// `()`
+    pub const UNDELEGATE: u16 = 0x05;
+    /// `(account: address) -> u128`
+    pub const VOTING_POWER: u16 = 0x10;
+}
+
+/// Staking. The caller is the validator or delegator.
+pub mod staking {
+    /// `(initial_stake: u128, commission_bps: u16, reward_address: address)`
+    pub const REGISTER_VALIDATOR: u16 = 0x01;
+    /// `(validator: address, amount: u128)`
+    pub const DELEGATE: u16 = 0x02;
+    /// `(validator: address, amount: u128)`
+    pub const UNBOND: u16 = 0x03;
+    /// `()`; unjails the calling validator
+    pub const UNJAIL: u16 = 0x04;
+    /// `() -> u128`
+    pub const TOTAL_STAKED: u16 = 0x10;
+}
+
+/// Constant-product AMM. Pools are keyed by a 32-byte pool id.
+pub mod amm {
+    /// `(pool_id: hash, token_a: address, token_b: address, fee_bps: u32)`
+    pub const CREATE_POOL: u16 = 0x01;
+    /// `(pool_id: hash, amount_a: u128, amount_b: u128, min_lp: u128) -> u128`
+    pub const ADD_LIQUIDITY: u16 = 0x02;
+    /// `(pool_id: hash, lp_tokens: u128, min_a: u128, min_b: u128) -> (u128, u128)`
+    pub const REMOVE_LIQUIDITY: u16 = 0x03;
+    /// `(pool_id: hash, amount_in: u128, min_out: u128) -> u128`
+    pub const SWAP_A_TO_B: u16 = 0x04;
+    /// `(pool_id: hash, amount_in: u128, min_out: u128) -> u128`
+    pub const SWAP_B_TO_A: u16 = 0x05;
+    /// `(pool_id: hash) -> u128`
+    pub const PRICE: u16 = 0x10;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn call_roundtrip() {
+        let data = encode_call(aic_token::TRANSFER, |w| {
+            w.put_fixed(&[7u8; 20]).put_u128(500);
+        });
+        assert_eq!(&data[..2], &[0x01, 0x00]);
+
+        let (selector, mut args) = decode_call(&data).unwrap();
+        assert_eq!(selector, aic_token::TRANSFER);
+        assert_eq!(args.take_fixed::<20>().unwrap(), [7u8; 20]);
+        assert_eq!(args.take_u128().unwrap(), 500);
+        args.finish().unwrap();
+
+        assert!(decode_call(&[0x01]).is_err());
+        assert!(!is_view(aic_token::MINT));
+        assert!(is_view(aic_token::BALANCE_OF));
+    }
+}
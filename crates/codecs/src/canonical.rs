@@ -0,0 +1,203 @@
+use crate::error::{CodecError, Result};
+
+/// Builder for canonical binary encodings: integers are little-endian,
+/// fixed-width fields are written raw and variable-length fields carry a u32
+/// length prefix, so distinct values never share an encoding.
+#[derive(Debug, Default, Clone)]
+pub struct CanonicalWriter {
+    buf: Vec<u8>,
+}
+
+impl CanonicalWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        CanonicalWriter {
+            buf: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn put_u8(&mut self, value: u8) -> &mut Self {
+        self.buf.push(value);
+        self
+    }
+
+    pub fn put_u16(&mut self, value: u16) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn put_u32(&mut self, value: u32) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn put_u64(&mut self, value: u64) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    pub fn put_u128(&mut self, value: u128) -> &mut Self {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+        self
+    }
+
+    /// Write a field whose width is fixed by the format (hashes, tags).
+    pub fn put_fixed(&mut self, bytes: &[u8]) -> &mut Self {
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    /// Write a u32 length prefix followed by `bytes`.
+    ///
+    /// # Panics
+    /// If `bytes` is longer than `u32::MAX`.
+    pub fn put_bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        let len = u32::try_from(bytes.len()).expect("field exceeds the u32 length prefix");
+        self.put_u32(len);
+        self.buf.extend_from_slice(bytes);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn finish(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+/// Reader for encodings produced by `CanonicalWriter`. Every read is bounds
+/// checked, so untrusted input can never cause an oversized allocation.
+#[derive(Debug, Clone)]
+pub struct CanonicalReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> CanonicalReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        CanonicalReader { bytes }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(CodecError::Truncated {
+                needed: len,
+                remaining: self.bytes.len(),
+            });
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    pub fn take_u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub fn take_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take_fixed()?))
+    }
+
+    pub fn take_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take_fixed()?))
+    }
+
+    pub fn take_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take_fixed()?))
+    }
+
+    pub fn take_u128(&mut self) -> Result<u128> {
+        Ok(u128::from_le_bytes(self.take_fixed()?))
+    }
+
+    pub fn take_fixed<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    /// Read a u32 length prefix and that many bytes.
+    pub fn take_bytes(&mut self) -> Result<&'a [u8]> {
+        let len = self.take_u32()? as usize;
+        self.take(len)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Fail unless the whole input has been consumed.
+    pub fn finish(self) -> Result<()> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(CodecError::TrailingBytes(self.bytes.len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut writer = CanonicalWriter::new();
+        writer
+            .put_u8(1)
+            .put_u16(2)
+            .put_u32(3)
+            .put_u64(4)
+            .put_u128(u128::MAX - 1)
+            .put_fixed(&[5u8; 4])
+            .put_bytes(b"payload");
+        let encoded = writer.finish();
+
+        let mut reader = CanonicalReader::new(&encoded);
+        assert_eq!(reader.take_u8().unwrap(), 1);
+        assert_eq!(reader.take_u16().unwrap(), 2);
+        assert_eq!(reader.take_u32().unwrap(), 3);
+        assert_eq!(reader.take_u64().unwrap(), 4);
+        assert_eq!(reader.take_u128().unwrap(), u128::MAX - 1);
+        assert_eq!(reader.take_fixed::<4>().unwrap(), [5u8; 4]);
+        assert_eq!(reader.take_bytes().unwrap(), b"payload");
+        reader.finish().unwrap();
+    }
+
+    #[test]
+    fn rejects_truncated_and_trailing_input() {
+        let mut writer = CanonicalWriter::new();
+        writer.put_bytes(b"abc");
+        let mut encoded = writer.finish();
+
+        let mut reader = CanonicalReader::new(&encoded[..encoded.len() - 1]);
+        assert!(matches!(
+            reader.take_bytes(),
+            Err(CodecError::Truncated {
+                needed: 3,
+                remaining: 2
+            })
+        ));
+
+        encoded.push(0);
+        let mut reader = CanonicalReader::new(&encoded);
+        reader.take_bytes().unwrap();
+        assert!(matches!(reader.finish(), Err(CodecError::TrailingBytes(1))));
+
+        // A huge length prefix is rejected without allocating.
+        let mut reader = CanonicalReader::new(&[0xff, 0xff, 0xff, 0xff]);
+        assert!(reader.take_bytes().is_err());
+    }
+}
@@ -11,6 +11,12 @@ pub enum CodecError {
 
     #[error("bincode serialization failed: {0}")]
     Bincode(#[from] BincodeError),
+
+    #[error("truncated input: needed {needed} bytes, {remaining} remaining")]
+    Truncated { needed: usize, remaining: usize },
+
+    #[error("{0} trailing bytes after value")]
+    TrailingBytes(usize),
 }
 
 pub type Result<T> = std::result::Result<T, CodecError>;
@@ -1,7 +1,11 @@
+pub mod abi;
 pub mod bincode_codec;
 pub mod borsh_codec;
+pub mod canonical;
 pub mod error;
 
+pub use abi::{decode_call, encode_call};
 pub use bincode_codec::{decode_bincode, encode_bincode};
 pub use borsh_codec::{decode_borsh, encode_borsh};
+pub use canonical::{CanonicalReader, CanonicalWriter};
 pub use error::{CodecError, Result};
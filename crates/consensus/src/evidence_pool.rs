@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+
+use aether_crypto_kes::{KesSignature, KesVerificationKey};
+use aether_types::{Address, SlashEvidence, SlashEvidenceType, Slot};
+use serde::{Deserialize, Serialize};
+
+use crate::slashing::{
+    slash_rate_bps, verify_slash_proof, KesSlashProof, SlashProof, SlashType, SlashingDetector,
+};
+
+/// Evidence kept before the oldest is evicted.
+pub const MAX_POOL_EVIDENCE: usize = 1024;
+
+/// Slash evidence a proposer puts in one block.
+pub const MAX_EVIDENCE_PER_BLOCK: usize = 16;
+
+/// Proof that a validator signed two conflicting messages for one slot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Evidence {
+    /// Two BLS votes for different blocks.
+    DoubleVote(SlashProof),
+    /// Two KES-signed headers for different blocks.
+    DoubleProposal(KesSlashProof),
+}
+
+impl Evidence {
+    pub fn validator(&self) -> Address {
+        match self {
+            Evidence::DoubleVote(proof) => proof.validator,
+            Evidence::DoubleProposal(proof) => proof.validator,
+        }
+    }
+
+    pub fn slot(&self) -> Slot {
+        match self {
+            Evidence::DoubleVote(proof) => proof.vote1.slot,
+            Evidence::DoubleProposal(proof) => proof.evidence.slot,
+        }
+    }
+
+    /// The entry a proposer includes in a block. Header evidence has no
+    /// block form yet, so it only reaches watchtowers over RPC.
+    pub fn to_slash_evidence(&self) -> Option<SlashEvidence> {
+        let Evidence::DoubleVote(proof) = self else {
+            return None;
+        };
+        let (reason, evidence_type) = match proof.proof_type {
+            SlashType::DoubleSign => ("double_sign", SlashEvidenceType::DoubleSign),
+            SlashType::SurroundVote => ("surround_vote", SlashEvidenceType::SurroundVote),
+            SlashType::Downtime { .. } => return None,
+        };
+        let vote = |v: &crate::slashing::Vote| aether_types::SlashVote {
+            slot: v.slot,
+            block_hash: v.block_hash,
+            validator: v.validator,
+            validator_pubkey: v.validator_pubkey.clone(),
+            signature: v.signature.clone(),
+        };
+        Some(SlashEvidence {
+            validator: proof.validator,
+            slash_rate_bps: slash_rate_bps(&proof.proof_type),
+            reason: reason.to_string(),
+            vote1: Some(vote(&proof.vote1)),
+            vote2: Some(vote(&proof.vote2)),
+            evidence_type: Some(evidence_type),
+        })
+    }
+}
+
+/// Pooled evidence, whether its signatures check out, and whether a block
+/// has carried it yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceEntry {
+    pub evidence: Evidence,
+    pub verified: bool,
+    pub included: bool,
+}
+
+/// Signed votes and headers per (validator, slot), and the evidence of
+/// every conflict found among them.
+///
+/// Conflicts are reported as soon as they are seen, like
+/// [`SlashingDetector`]'s, but only evidence whose signatures verify is
+/// offered to blocks, so a proposer never includes a proof the chain would
+/// reject. One offense per validator and slot is kept, at most `capacity`
+/// in all: included evidence goes first, then the oldest.
+pub struct EvidencePool {
+    detector: SlashingDetector,
+    evidence: BTreeMap<(Slot, [u8; 20]), EvidenceEntry>,
+    capacity: usize,
+}
+
+impl Default for EvidencePool {
+    fn default() -> Self {
+        Self::new(MAX_POOL_EVIDENCE)
+    }
+}
+
+impl EvidencePool {
+    pub fn new(capacity: usize) -> Self {
+        EvidencePool {
+            detector: SlashingDetector::new(),
+            evidence: BTreeMap::new(),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Record a vote. Returns the proof the first time its validator is
+    /// caught voting for two blocks in its slot.
+    pub fn add_vote(&mut self, vote: &aether_types::Vote) -> Option<SlashProof> {
+        let proof = self.detector.record_vote(
+            vote.validator.to_address(),
+            vote.validator.clone(),
+            vote.slot,
+            vote.block_hash,
+            vote.signature.clone(),
+        )?;
+        self.detector.drain_pending();
+        let verified = match verify_slash_proof(&proof) {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::debug!(validator = ?proof.validator, err = %e, "double vote does not verify");
+                false
+            }
+        };
+        self.insert(Evidence::DoubleVote(proof.clone()), verified)
+            .then_some(proof)
+    }
+
+    /// Record a header's KES signature by `proposer`, whose registered key
+    /// is `kes_key`. Returns the proof the first time `proposer` is caught
+    /// signing two headers for `slot`.
+    pub fn add_header(
+        &mut self,
+        proposer: Address,
+        kes_key: &KesVerificationKey,
+        slot: Slot,
+        block_hash: [u8; 32],
+        signature: KesSignature,
+    ) -> Option<KesSlashProof> {
+        let proof = self
+            .detector
+            .record_block_signature(proposer, kes_key, slot, block_hash, signature)?;
+        self.detector.drain_pending_kes();
+        // The detector only pairs signatures that verify against `kes_key`.
+        self.insert(Evidence::DoubleProposal(proof.clone()), true)
+            .then_some(proof)
+    }
+
+    fn insert(&mut self, evidence: Evidence, verified: bool) -> bool {
+        let key = (evidence.slot(), evidence.validator().0);
+        if self.evidence.contains_key(&key) {
+            return false;
+        }
+        tracing::warn!(
+            validator = ?evidence.validator(),
+            slot = evidence.slot(),
+            "equivocation evidence pooled"
+        );
+        self.evidence.insert(
+            key,
+            EvidenceEntry {
+                evidence,
+                verified,
+                included: false,
+            },
+        );
+        while self.evidence.len() > self.capacity {
+            let evict = self
+                .evidence
+                .iter()
+                .find(|(_, entry)| entry.included)
+                .or_else(|| self.evidence.iter().next())
+                .map(|(key, _)| *key);
+            if let Some(key) = evict {
+                self.evidence.remove(&key);
+            }
+        }
+        true
+    }
+
+    /// Up to `max` entries for the next block, oldest first: verified
+    /// evidence no block has carried yet, for slots from `min_slot` on.
+    pub fn for_block(&self, min_slot: Slot, max: usize) -> Vec<SlashEvidence> {
+        self.evidence
+            .range((min_slot, [0u8; 20])..)
+            .filter(|(_, entry)| entry.verified && !entry.included)
+            .filter_map(|(_, entry)| entry.evidence.to_slash_evidence())
+            .take(max)
+            .collect()
+    }
+
+    /// Note that a block carried evidence against `validator` at `slot`.
+    pub fn mark_included(&mut self, validator: &Address, slot: Slot) {
+        if let Some(entry) = self.evidence.get_mut(&(slot, validator.0)) {
+            entry.included = true;
+        }
+    }
+
+    /// Pooled evidence for slots from `from_slot` on, oldest first.
+    pub fn entries(&self, from_slot: Slot) -> Vec<EvidenceEntry> {
+        self.evidence
+            .range((from_slot, [0u8; 20])..)
+            .map(|(_, entry)| entry.clone())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.evidence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.evidence.is_empty()
+    }
+
+    /// Forget votes and headers below `min_slot`. Evidence stays until it
+    /// is evicted.
+    pub fn prune_before(&mut self, min_slot: Slot) {
+        self.detector.prune_before(min_slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_bls::BlsKeypair;
+    use aether_types::{PublicKey, Signature, Vote, H256};
+
+    fn vote(key: &BlsKeypair, slot: Slot, block: u8) -> Vote {
+        let block_hash = H256::from([block; 32]);
+        let mut msg = block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&slot.to_le_bytes());
+        Vote {
+            slot,
+            block_hash,
+            validator: PublicKey::from_bytes(key.public_key()),
+            signature: Signature::from_bytes(key.sign(&msg)),
+            stake: 1000,
+        }
+    }
+
+    #[test]
+    fn double_vote_becomes_block_evidence_once() {
+        let key = BlsKeypair::generate();
+        let addr = PublicKey::from_bytes(key.public_key()).to_address();
+        let mut pool = EvidencePool::default();
+
+        assert!(pool.add_vote(&vote(&key, 7, 1)).is_none());
+        assert!(pool.add_vote(&vote(&key, 7, 1)).is_none());
+        assert!(pool.add_vote(&vote(&key, 8, 2)).is_none());
+        let proof = pool.add_vote(&vote(&key, 7, 2)).expect("double vote");
+        assert_eq!(proof.validator, addr);
+        // A third block at the same slot is the same offense.
+        assert!(pool.add_vote(&vote(&key, 7, 3)).is_none());
+        assert_eq!(pool.len(), 1);
+
+        let evidence = pool.for_block(0, MAX_EVIDENCE_PER_BLOCK);
+        assert_eq!(evidence.len(), 1);
+        assert_eq!(evidence[0].validator, addr);
+        assert_eq!(
+            evidence[0].evidence_type,
+            Some(SlashEvidenceType::DoubleSign)
+        );
+        assert!(pool.for_block(8, MAX_EVIDENCE_PER_BLOCK).is_empty());
+
+        pool.mark_included(&addr, 7);
+        assert!(pool.for_block(0, MAX_EVIDENCE_PER_BLOCK).is_empty());
+        let entries = pool.entries(0);
+        assert!(entries[0].verified && entries[0].included);
+        assert_eq!(entries[0].evidence.slot(), 7);
+    }
+
+    #[test]
+    fn forged_conflicting_vote_never_reaches_a_block() {
+        let key = BlsKeypair::generate();
+        let mut pool = EvidencePool::default();
+        pool.add_vote(&vote(&key, 3, 1));
+        let forged = Vote {
+            signature: vote(&key, 3, 1).signature,
+            ..vote(&key, 3, 2)
+        };
+        assert!(pool.add_vote(&forged).is_some());
+        assert!(!pool.entries(0)[0].verified);
+        assert!(pool.for_block(0, MAX_EVIDENCE_PER_BLOCK).is_empty());
+    }
+
+    #[test]
+    fn full_pool_evicts_included_evidence_first() {
+        let keys: Vec<BlsKeypair> = (0..3).map(|_| BlsKeypair::generate()).collect();
+        let mut pool = EvidencePool::new(2);
+        for (slot, key) in keys.iter().enumerate() {
+            let slot = slot as Slot + 1;
+            pool.add_vote(&vote(key, slot, 1));
+            if slot == 2 {
+                let addr = PublicKey::from_bytes(key.public_key()).to_address();
+                pool.add_vote(&vote(key, slot, 2)).unwrap();
+                pool.mark_included(&addr, slot);
+            } else if slot == 1 {
+                pool.add_vote(&vote(key, slot, 2)).unwrap();
+            }
+        }
+        pool.add_vote(&vote(&keys[2], 3, 2)).unwrap();
+        let slots: Vec<Slot> = pool.entries(0).iter().map(|e| e.evidence.slot()).collect();
+        assert_eq!(slots, vec![1, 3]);
+
+        // Records are pruned; evidence is not.
+        pool.prune_before(10);
+        assert_eq!(pool.len(), 2);
+        assert!(pool.add_vote(&vote(&keys[0], 1, 3)).is_none());
+    }
+}
@@ -0,0 +1,403 @@
+use aether_metrics::CONSENSUS_METRICS;
+use aether_types::{Address, Slot, H256};
+use anyhow::{bail, Result};
+
+use std::collections::{HashMap, HashSet};
+
+/// Maximum unfinalized blocks tracked.
+/// Prevents OOM from a peer feeding an endless side chain while finality stalls.
+const MAX_BLOCKS: usize = 8192;
+
+/// The canonical head moved to a block that does not descend from the old
+/// one, so blocks past `common_ancestor` on the old chain were abandoned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reorg {
+    pub old_head: H256,
+    pub new_head: H256,
+    pub common_ancestor: H256,
+    /// Blocks of the old chain after the common ancestor.
+    pub depth: u64,
+}
+
+struct BlockNode {
+    parent: H256,
+    slot: Slot,
+    children: Vec<H256>,
+    /// Stake of the latest votes for this block and its descendants.
+    weight: u128,
+}
+
+/// Fork choice shared by the consensus engines: never leave the latest
+/// finalized block, and below it follow the heaviest-attested subtree.
+///
+/// Each validator's stake counts once, behind its latest vote, for the
+/// voted block and all its ancestors. Equal subtrees are broken toward the
+/// lower hash, as in the node's per-slot tiebreak.
+pub struct ForkChoice {
+    root: H256,
+    blocks: HashMap<H256, BlockNode>,
+    /// Latest vote per validator: (slot, block_hash, stake). Votes for
+    /// blocks not yet seen are kept and counted when the block arrives.
+    latest_votes: HashMap<Address, (Slot, H256, u128)>,
+    head: H256,
+}
+
+impl ForkChoice {
+    /// Start from a finalized (or otherwise irreversible) block.
+    pub fn new(root: H256, root_slot: Slot) -> Self {
+        let mut blocks = HashMap::new();
+        blocks.insert(
+            root,
+            BlockNode {
+                parent: H256::zero(),
+                slot: root_slot,
+                children: Vec::new(),
+                weight: 0,
+            },
+        );
+        ForkChoice {
+            root,
+            blocks,
+            latest_votes: HashMap::new(),
+            head: root,
+        }
+    }
+
+    pub fn head(&self) -> H256 {
+        self.head
+    }
+
+    pub fn head_slot(&self) -> Slot {
+        self.blocks[&self.head].slot
+    }
+
+    /// The finalized block everything tracked descends from.
+    pub fn finalized(&self) -> H256 {
+        self.root
+    }
+
+    pub fn contains(&self, block_hash: &H256) -> bool {
+        self.blocks.contains_key(block_hash)
+    }
+
+    /// Attested stake behind `block_hash` and its descendants.
+    pub fn weight(&self, block_hash: &H256) -> Option<u128> {
+        self.blocks.get(block_hash).map(|b| b.weight)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    /// Whether `ancestor` is `block` or one of its tracked ancestors.
+    pub fn is_ancestor(&self, ancestor: &H256, block: &H256) -> bool {
+        let Some(floor) = self.blocks.get(ancestor).map(|b| b.slot) else {
+            return false;
+        };
+        let mut at = *block;
+        while let Some(node) = self.blocks.get(&at) {
+            if at == *ancestor {
+                return true;
+            }
+            if node.slot <= floor {
+                return false;
+            }
+            at = node.parent;
+        }
+        false
+    }
+
+    /// Add a block whose parent is already tracked.
+    pub fn on_block(
+        &mut self,
+        block_hash: H256,
+        parent: H256,
+        slot: Slot,
+    ) -> Result<Option<Reorg>> {
+        if self.blocks.contains_key(&block_hash) {
+            return Ok(None);
+        }
+        let parent_slot = match self.blocks.get(&parent) {
+            Some(p) => p.slot,
+            None => bail!("unknown or pruned parent {:?} for {:?}", parent, block_hash),
+        };
+        if slot <= parent_slot {
+            bail!(
+                "block at slot {} does not follow its parent at slot {}",
+                slot,
+                parent_slot
+            );
+        }
+        if self.blocks.len() >= MAX_BLOCKS {
+            bail!("fork choice is tracking {} unfinalized blocks", MAX_BLOCKS);
+        }
+        self.blocks.insert(
+            block_hash,
+            BlockNode {
+                parent,
+                slot,
+                children: Vec::new(),
+                weight: 0,
+            },
+        );
+        if let Some(p) = self.blocks.get_mut(&parent) {
+            p.children.push(block_hash);
+        }
+        let early: Vec<u128> = self
+            .latest_votes
+            .values()
+            .filter(|(_, hash, _)| *hash == block_hash)
+            .map(|(_, _, stake)| *stake)
+            .collect();
+        for stake in early {
+            self.shift_weight(block_hash, stake, true);
+        }
+        Ok(self.update_head())
+    }
+
+    /// Count `validator`'s vote, replacing any vote from an earlier slot.
+    pub fn on_vote(
+        &mut self,
+        validator: Address,
+        block_hash: H256,
+        slot: Slot,
+        stake: u128,
+    ) -> Option<Reorg> {
+        if let Some((prev_slot, prev_hash, prev_stake)) = self.latest_votes.get(&validator).copied()
+        {
+            if prev_slot >= slot {
+                return None;
+            }
+            self.shift_weight(prev_hash, prev_stake, false);
+        }
+        self.latest_votes
+            .insert(validator, (slot, block_hash, stake));
+        self.shift_weight(block_hash, stake, true);
+        self.update_head()
+    }
+
+    /// Move the root to a newly finalized block and drop every block that
+    /// does not descend from it.
+    pub fn on_finalized(&mut self, block_hash: H256) -> Result<Option<Reorg>> {
+        if block_hash == self.root {
+            return Ok(None);
+        }
+        if !self.is_ancestor(&self.root, &block_hash) {
+            bail!("finalized block {:?} is not tracked", block_hash);
+        }
+        let old_head = self.head;
+        let abandoned = if self.is_ancestor(&block_hash, &old_head) {
+            None
+        } else {
+            Some(self.common_ancestor(&old_head, &block_hash))
+        };
+
+        let mut keep = HashSet::new();
+        let mut stack = vec![block_hash];
+        while let Some(hash) = stack.pop() {
+            if let Some(node) = self.blocks.get(&hash) {
+                stack.extend(node.children.iter().copied());
+            }
+            keep.insert(hash);
+        }
+        let pruned: HashSet<H256> = self
+            .blocks
+            .keys()
+            .filter(|hash| !keep.contains(*hash))
+            .copied()
+            .collect();
+        self.blocks.retain(|hash, _| keep.contains(hash));
+        self.latest_votes
+            .retain(|_, (_, hash, _)| !pruned.contains(hash));
+        self.root = block_hash;
+
+        match abandoned {
+            None => Ok(self.update_head()),
+            Some((common_ancestor, depth)) => {
+                self.head = self.select_head();
+                let reorg = Reorg {
+                    old_head,
+                    new_head: self.head,
+                    common_ancestor,
+                    depth,
+                };
+                Self::record(&reorg);
+                Ok(Some(reorg))
+            }
+        }
+    }
+
+    fn shift_weight(&mut self, block_hash: H256, stake: u128, add: bool) {
+        let mut at = block_hash;
+        while let Some(node) = self.blocks.get_mut(&at) {
+            node.weight = if add {
+                node.weight.saturating_add(stake)
+            } else {
+                node.weight.saturating_sub(stake)
+            };
+            if at == self.root {
+                break;
+            }
+            at = node.parent;
+        }
+    }
+
+    /// Walk down from the root, taking the heaviest child at each step.
+    fn select_head(&self) -> H256 {
+        let mut at = self.root;
+        loop {
+            let best = self.blocks[&at].children.iter().max_by(|a, b| {
+                let (wa, wb) = (self.blocks[*a].weight, self.blocks[*b].weight);
+                wa.cmp(&wb).then_with(|| b.as_bytes().cmp(a.as_bytes()))
+            });
+            match best {
+                Some(child) => at = *child,
+                None => return at,
+            }
+        }
+    }
+
+    fn update_head(&mut self) -> Option<Reorg> {
+        let old_head = self.head;
+        let new_head = self.select_head();
+        self.head = new_head;
+        if new_head == old_head || self.is_ancestor(&old_head, &new_head) {
+            return None;
+        }
+        let (common_ancestor, depth) = self.common_ancestor(&old_head, &new_head);
+        let reorg = Reorg {
+            old_head,
+            new_head,
+            common_ancestor,
+            depth,
+        };
+        Self::record(&reorg);
+        Some(reorg)
+    }
+
+    /// The deepest block both `old` and `new` descend from, and how many
+    /// blocks `old` is past it.
+    fn common_ancestor(&self, old: &H256, new: &H256) -> (H256, u64) {
+        let mut depth = 0;
+        let mut at = *old;
+        while at != self.root && !self.is_ancestor(&at, new) {
+            match self.blocks.get(&at) {
+                Some(node) => at = node.parent,
+                None => break,
+            }
+            depth += 1;
+        }
+        (at, depth)
+    }
+
+    fn record(reorg: &Reorg) {
+        tracing::warn!(
+            old_head = ?reorg.old_head,
+            new_head = ?reorg.new_head,
+            common_ancestor = ?reorg.common_ancestor,
+            depth = reorg.depth,
+            "fork choice reorg"
+        );
+        CONSENSUS_METRICS.reorgs.inc();
+        CONSENSUS_METRICS.reorg_depth.observe(reorg.depth as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hash(n: u8) -> H256 {
+        H256::from([n; 32])
+    }
+
+    fn validator(n: u8) -> Address {
+        Address::from_slice(&[n; 20]).unwrap()
+    }
+
+    /// Genesis 0 with two forks: 1 ← 2 ← 3 and 1 ← 4.
+    fn forked() -> ForkChoice {
+        let mut fc = ForkChoice::new(hash(0), 0);
+        fc.on_block(hash(1), hash(0), 1).unwrap();
+        fc.on_block(hash(2), hash(1), 2).unwrap();
+        fc.on_block(hash(3), hash(2), 3).unwrap();
+        fc.on_block(hash(4), hash(1), 3).unwrap();
+        fc
+    }
+
+    #[test]
+    fn heaviest_subtree_beats_longest_chain() {
+        let mut fc = forked();
+        // Unattested: lower hash at the fork wins, then down to the tip.
+        assert_eq!(fc.head(), hash(3));
+
+        assert!(fc.on_vote(validator(1), hash(3), 3, 100).is_none());
+        let reorg = fc.on_vote(validator(2), hash(4), 3, 150).expect("reorg");
+        assert_eq!(
+            reorg,
+            Reorg {
+                old_head: hash(3),
+                new_head: hash(4),
+                common_ancestor: hash(1),
+                depth: 2,
+            }
+        );
+        assert_eq!(fc.weight(&hash(1)), Some(250));
+        assert_eq!(fc.weight(&hash(2)), Some(100));
+    }
+
+    #[test]
+    fn extending_the_head_is_not_a_reorg() {
+        let mut fc = forked();
+        assert!(fc.on_block(hash(5), hash(3), 4).unwrap().is_none());
+        assert_eq!(fc.head(), hash(5));
+        assert_eq!(fc.head_slot(), 4);
+        assert!(fc.on_vote(validator(1), hash(5), 4, 10).is_none());
+    }
+
+    #[test]
+    fn only_the_latest_vote_counts() {
+        let mut fc = forked();
+        fc.on_vote(validator(1), hash(4), 3, 100);
+        assert_eq!(fc.head(), hash(4));
+        // A stale vote is ignored; a newer one moves the stake.
+        assert!(fc.on_vote(validator(1), hash(3), 2, 100).is_none());
+        assert_eq!(fc.weight(&hash(4)), Some(100));
+        assert!(fc.on_vote(validator(1), hash(3), 4, 100).is_some());
+        assert_eq!(fc.weight(&hash(4)), Some(0));
+        assert_eq!(fc.weight(&hash(3)), Some(100));
+    }
+
+    #[test]
+    fn votes_for_unseen_blocks_count_on_arrival() {
+        let mut fc = forked();
+        fc.on_vote(validator(1), hash(9), 4, 100);
+        assert_eq!(fc.head(), hash(3));
+        let reorg = fc.on_block(hash(9), hash(4), 4).unwrap().expect("reorg");
+        assert_eq!(reorg.new_head, hash(9));
+        assert_eq!(fc.weight(&hash(1)), Some(100));
+    }
+
+    #[test]
+    fn finality_prunes_forks_and_overrides_weight() {
+        let mut fc = forked();
+        fc.on_vote(validator(1), hash(4), 3, 100);
+        assert_eq!(fc.head(), hash(4));
+
+        let reorg = fc.on_finalized(hash(2)).unwrap().expect("reorg");
+        assert_eq!(reorg.new_head, hash(3));
+        assert_eq!(reorg.common_ancestor, hash(1));
+        assert_eq!(fc.finalized(), hash(2));
+        assert!(!fc.contains(&hash(4)) && !fc.contains(&hash(1)));
+        assert_eq!(fc.len(), 2);
+
+        // Blocks off the finalized chain are rejected.
+        assert!(fc.on_block(hash(6), hash(4), 5).is_err());
+        assert!(fc.on_block(hash(6), hash(3), 3).is_err());
+        assert!(fc.on_finalized(hash(4)).is_err());
+    }
+}
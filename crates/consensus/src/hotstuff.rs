@@ -43,6 +43,13 @@ pub enum ConsensusAction {
     Finalized { slot: Slot, block_hash: H256 },
     /// Broadcast a timeout vote (view-change).
     BroadcastTimeout(TimeoutVote),
+    /// Pipelined mode: we lead `view`; build a block on `parent_hash` and
+    /// pass its hash to `propose`.
+    Propose { view: u64, parent_hash: H256 },
+    /// Pipelined mode: broadcast our proposal to all validators.
+    BroadcastProposal(Proposal),
+    /// Pipelined mode: send a new-view message to the leader of its view.
+    SendNewView(NewView),
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +99,66 @@ pub struct AggregatedVote {
     pub aggregated_pubkey: Vec<u8>,
 }
 
+/// A pipelined QC together with the parent of the block it certifies,
+/// which is part of every signed vote message, so the QC can be checked
+/// without having seen the block.
+#[derive(Debug, Clone)]
+pub struct PipelineQc {
+    pub qc: AggregatedVote,
+    pub parent_hash: H256,
+}
+
+impl PipelineQc {
+    pub fn slot(&self) -> Slot {
+        self.qc.slot
+    }
+
+    pub fn block_hash(&self) -> H256 {
+        self.qc.block_hash
+    }
+}
+
+/// Pipelined mode: a leader's block for a view, justified by the highest
+/// QC it knows. `justify` is `None` only for children of genesis.
+#[derive(Debug, Clone)]
+pub struct Proposal {
+    pub view: u64,
+    pub block_hash: H256,
+    pub parent_hash: H256,
+    pub justify: Option<PipelineQc>,
+    pub proposer: Address,
+    pub signature: Vec<u8>,
+}
+
+/// Pipelined mode: a validator leaving a view, carrying its highest QC to
+/// the next leader.
+#[derive(Debug, Clone)]
+pub struct NewView {
+    pub view: u64,
+    pub validator: Address,
+    pub high_qc: Option<PipelineQc>,
+    pub signature: Vec<u8>,
+}
+
+fn proposal_message(view: u64, block_hash: &H256, parent_hash: &H256) -> Vec<u8> {
+    let mut msg = Vec::new();
+    msg.extend_from_slice(b"proposal");
+    msg.extend_from_slice(&view.to_le_bytes());
+    msg.extend_from_slice(block_hash.as_bytes());
+    msg.extend_from_slice(parent_hash.as_bytes());
+    msg
+}
+
+fn new_view_message(view: u64, high_qc: Option<&PipelineQc>) -> Vec<u8> {
+    let (qc_slot, qc_hash) = high_qc.map_or((0, H256::zero()), |q| (q.slot(), q.block_hash()));
+    let mut msg = Vec::new();
+    msg.extend_from_slice(b"new-view");
+    msg.extend_from_slice(&view.to_le_bytes());
+    msg.extend_from_slice(&qc_slot.to_le_bytes());
+    msg.extend_from_slice(qc_hash.as_bytes());
+    msg
+}
+
 /// Deterministic canonical phase encoding for vote messages.
 /// Using a single byte prevents non-determinism from Debug format strings.
 fn phase_to_byte(phase: &Phase) -> u8 {
@@ -138,6 +205,19 @@ pub struct HotStuffConsensus {
     /// Registered BLS public keys (48 bytes each) for vote verification.
     /// Validators must have a registered BLS key to have their votes accepted.
     bls_pubkeys: HashMap<Address, Vec<u8>>,
+
+    /// Pipelined mode: leader schedule, round-robin over sorted addresses.
+    leaders: Vec<Address>,
+    /// Pipelined mode: highest QC seen, extended by the next proposal.
+    high_qc: Option<PipelineQc>,
+    last_voted_view: u64,
+    last_proposed_view: u64,
+    /// Highest view we have told the node to build a block for.
+    announced_view: u64,
+    /// Pipelined mode: generic votes per (view, block_hash).
+    pipeline_votes: HashMap<(Slot, H256), Vec<HotStuffVote>>,
+    /// Pipelined mode: new-view messages per view.
+    new_views: HashMap<u64, Vec<NewView>>,
 }
 
 impl HotStuffConsensus {
@@ -154,6 +234,8 @@ impl HotStuffConsensus {
             .into_iter()
             .map(|v| (v.pubkey.to_address(), v))
             .collect();
+        let mut leaders: Vec<Address> = validators_map.keys().copied().collect();
+        leaders.sort_by_key(|a| *a.as_bytes());
 
         HotStuffConsensus {
             current_phase: Phase::Propose,
@@ -173,6 +255,13 @@ impl HotStuffConsensus {
             my_keypair,
             my_address,
             bls_pubkeys: HashMap::new(),
+            leaders,
+            high_qc: None,
+            last_voted_view: 0,
+            last_proposed_view: 0,
+            announced_view: 0,
+            pipeline_votes: HashMap::new(),
+            new_views: HashMap::new(),
         }
     }
 
@@ -800,6 +889,9 @@ impl HotStuffConsensus {
         let prune_below = self.finalized_slot - 2;
 
         self.qcs.retain(|(slot, _, _), _| *slot >= prune_below);
+        self.pipeline_votes
+            .retain(|(slot, _), _| *slot >= prune_below);
+        self.new_views.retain(|view, _| *view >= prune_below);
         self.block_slots.retain(|_, slot| *slot >= prune_below);
         let known_hashes: HashSet<H256> = self.block_slots.keys().copied().collect();
         self.block_parents
@@ -807,6 +899,408 @@ impl HotStuffConsensus {
     }
 }
 
+/// Pipelined (chained) HotStuff. These methods share the lock, finality
+/// and BLS key registry with the phase-by-phase API above, but a node
+/// should drive a given engine through one of the two.
+impl HotStuffConsensus {
+    /// Leader of `view` in pipelined mode.
+    pub fn leader(&self, view: u64) -> Option<Address> {
+        if self.leaders.is_empty() {
+            return None;
+        }
+        Some(self.leaders[(view % self.leaders.len() as u64) as usize])
+    }
+
+    pub fn high_qc(&self) -> Option<&PipelineQc> {
+        self.high_qc.as_ref()
+    }
+
+    pub fn locked_block(&self) -> Option<H256> {
+        self.locked_block
+    }
+
+    fn is_leader(&self, view: u64) -> bool {
+        self.my_address.is_some() && self.leader(view) == self.my_address
+    }
+
+    /// Leave the current view — on a pacemaker timeout, or at start-up to
+    /// enter view 1 — and send our highest QC to the next leader.
+    pub fn new_view(&mut self) -> Result<Vec<ConsensusAction>> {
+        let view = self.current_slot.saturating_add(1);
+        let _span = tracing::warn_span!("consensus_new_view", view).entered();
+        self.current_slot = view;
+        self.new_views.retain(|v, _| *v >= view);
+
+        let (keypair, address) = match (&self.my_keypair, &self.my_address) {
+            (Some(kp), Some(addr)) => (kp, *addr),
+            _ => return Ok(vec![]),
+        };
+        let signature = keypair.sign(&new_view_message(view, self.high_qc.as_ref()));
+        Ok(vec![ConsensusAction::SendNewView(NewView {
+            view,
+            validator: address,
+            high_qc: self.high_qc.clone(),
+            signature,
+        })])
+    }
+
+    /// Process a new-view message. The leader of its view proposes once it
+    /// holds a quorum of them.
+    pub fn on_new_view(&mut self, nv: NewView) -> Result<Vec<ConsensusAction>> {
+        if !self.validators.contains_key(&nv.validator) {
+            bail!("unknown validator {:?}", nv.validator);
+        }
+        let bls_pk = self
+            .bls_pubkeys
+            .get(&nv.validator)
+            .ok_or_else(|| anyhow::anyhow!("no BLS pubkey registered for {:?}", nv.validator))?;
+        let msg = new_view_message(nv.view, nv.high_qc.as_ref());
+        if !aether_crypto_bls::keypair::verify(bls_pk, &msg, &nv.signature)? {
+            bail!("invalid BLS signature on new-view from {:?}", nv.validator);
+        }
+        if !self.is_leader(nv.view) || nv.view <= self.last_proposed_view {
+            return Ok(vec![]);
+        }
+
+        let mut actions = Vec::new();
+        if let Some(qc) = &nv.high_qc {
+            self.verify_pipeline_qc(qc)?;
+            actions.extend(self.update_high_qc(qc.clone()));
+        }
+
+        let view_msgs = self.new_views.entry(nv.view).or_default();
+        if view_msgs.iter().any(|m| m.validator == nv.validator) {
+            bail!(
+                "duplicate new-view from {:?} for view {}",
+                nv.validator,
+                nv.view
+            );
+        }
+        view_msgs.push(nv.clone());
+        let voted = view_msgs
+            .iter()
+            .filter_map(|m| self.validators.get(&m.validator))
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add);
+        if crate::has_quorum(voted, self.total_stake) && nv.view >= self.current_slot {
+            self.current_slot = nv.view;
+            actions.extend(self.ready_to_propose(nv.view));
+        }
+        Ok(actions)
+    }
+
+    /// Propose `block_hash` for `view`, extending the highest QC. Call
+    /// after a `ConsensusAction::Propose` for that view.
+    pub fn propose(&mut self, view: u64, block_hash: H256) -> Result<Vec<ConsensusAction>> {
+        let (keypair, address) = match (&self.my_keypair, &self.my_address) {
+            (Some(kp), Some(addr)) => (kp, *addr),
+            _ => bail!("cannot propose without a keypair"),
+        };
+        if self.leader(view) != Some(address) {
+            bail!("not the leader of view {}", view);
+        }
+        if view != self.current_slot || view <= self.last_proposed_view {
+            bail!(
+                "cannot propose for view {} in view {} (last proposed {})",
+                view,
+                self.current_slot,
+                self.last_proposed_view
+            );
+        }
+        let justify = self.high_qc.clone();
+        let parent_hash = justify
+            .as_ref()
+            .map_or(H256::zero(), PipelineQc::block_hash);
+        let signature = keypair.sign(&proposal_message(view, &block_hash, &parent_hash));
+        self.last_proposed_view = view;
+        Ok(vec![ConsensusAction::BroadcastProposal(Proposal {
+            view,
+            block_hash,
+            parent_hash,
+            justify,
+            proposer: address,
+            signature,
+        })])
+    }
+
+    /// Process a proposal: apply its QC (locking and finalizing along the
+    /// 3-chain) and vote for it if it is safe.
+    pub fn on_proposal(&mut self, proposal: &Proposal) -> Result<Vec<ConsensusAction>> {
+        let _span = tracing::info_span!(
+            "consensus_proposal",
+            view = proposal.view,
+            block_hash = ?proposal.block_hash,
+        )
+        .entered();
+
+        if self.leader(proposal.view) != Some(proposal.proposer) {
+            bail!(
+                "{:?} is not the leader of view {}",
+                proposal.proposer,
+                proposal.view
+            );
+        }
+        let bls_pk = self.bls_pubkeys.get(&proposal.proposer).ok_or_else(|| {
+            anyhow::anyhow!("no BLS pubkey registered for {:?}", proposal.proposer)
+        })?;
+        let msg = proposal_message(proposal.view, &proposal.block_hash, &proposal.parent_hash);
+        if !aether_crypto_bls::keypair::verify(bls_pk, &msg, &proposal.signature)? {
+            bail!(
+                "invalid BLS signature on proposal for view {}",
+                proposal.view
+            );
+        }
+        let justify_slot = match &proposal.justify {
+            Some(qc) => {
+                if qc.block_hash() != proposal.parent_hash || qc.slot() >= proposal.view {
+                    bail!("proposal for view {} does not extend its QC", proposal.view);
+                }
+                self.verify_pipeline_qc(qc)?;
+                qc.slot()
+            }
+            None if proposal.parent_hash == H256::zero() => 0,
+            None => bail!("proposal for view {} has no QC", proposal.view),
+        };
+
+        self.block_parents
+            .entry(proposal.block_hash)
+            .or_insert(proposal.parent_hash);
+        self.block_slots
+            .entry(proposal.block_hash)
+            .or_insert(proposal.view);
+
+        let mut actions = Vec::new();
+        if let Some(qc) = &proposal.justify {
+            actions.extend(self.update_high_qc(qc.clone()));
+        }
+        // A proposal from a later view is evidence the others moved on.
+        if proposal.view < self.current_slot {
+            return Ok(actions);
+        }
+        self.current_slot = proposal.view;
+
+        // Safe-node predicate: extend the lock, or carry a QC newer than it.
+        let safe = match self.locked_block {
+            Some(locked) => {
+                justify_slot > self.locked_slot || self.extends(proposal.block_hash, locked)
+            }
+            None => true,
+        };
+        if proposal.view > self.last_voted_view && safe {
+            if let Some(vote) =
+                self.create_vote(proposal.block_hash, proposal.parent_hash, Phase::Prevote)?
+            {
+                self.last_voted_view = proposal.view;
+                actions.push(ConsensusAction::BroadcastVote(vote));
+            }
+        }
+        Ok(actions)
+    }
+
+    /// Process a generic vote. On quorum the QC is applied and, if we lead
+    /// the next view, we are ready to propose.
+    pub fn on_pipeline_vote(&mut self, vote: HotStuffVote) -> Result<Vec<ConsensusAction>> {
+        if vote.phase != Phase::Prevote {
+            bail!("pipelined votes are generic prevotes, got {:?}", vote.phase);
+        }
+        self.verify_vote(&vote)?;
+        let registered_stake = self
+            .validators
+            .get(&vote.validator)
+            .map(|v| v.stake)
+            .unwrap_or(0);
+        if vote.stake != registered_stake {
+            bail!(
+                "vote stake mismatch: claimed {} but registered {}",
+                vote.stake,
+                registered_stake
+            );
+        }
+        let parent = *self
+            .block_parents
+            .entry(vote.block_hash)
+            .or_insert(vote.parent_hash);
+        if parent != vote.parent_hash {
+            bail!("vote for {:?} names the wrong parent", vote.block_hash);
+        }
+        self.block_slots.entry(vote.block_hash).or_insert(vote.slot);
+
+        let key = (vote.slot, vote.block_hash);
+        if self
+            .qcs
+            .contains_key(&(vote.slot, Phase::Prevote, vote.block_hash))
+        {
+            return Ok(vec![]);
+        }
+        let block_votes = self.pipeline_votes.entry(key).or_default();
+        if block_votes.iter().any(|v| v.validator == vote.validator) {
+            bail!(
+                "duplicate vote from {:?} in view {} for block {:?}",
+                vote.validator,
+                vote.slot,
+                vote.block_hash
+            );
+        }
+        block_votes.push(vote.clone());
+        let stake = block_votes
+            .iter()
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add);
+        if !crate::has_quorum(stake, self.total_stake) {
+            return Ok(vec![]);
+        }
+
+        let votes = self.pipeline_votes.remove(&key).unwrap_or_default();
+        let qc = PipelineQc {
+            qc: self.aggregate_votes(&votes)?,
+            parent_hash: vote.parent_hash,
+        };
+        let mut actions = self.update_high_qc(qc);
+        let next = vote.slot.saturating_add(1);
+        if next >= self.current_slot {
+            self.current_slot = next;
+            if self.is_leader(next) {
+                actions.extend(self.ready_to_propose(next));
+            }
+        }
+        Ok(actions)
+    }
+
+    /// Ask the node for a block for `view`, once per view.
+    fn ready_to_propose(&mut self, view: u64) -> Option<ConsensusAction> {
+        if view <= self.announced_view {
+            return None;
+        }
+        self.announced_view = view;
+        Some(ConsensusAction::Propose {
+            view,
+            parent_hash: self
+                .high_qc
+                .as_ref()
+                .map_or(H256::zero(), PipelineQc::block_hash),
+        })
+    }
+
+    /// Apply a verified QC for B2: raise the high QC, lock on B1 and
+    /// finalize B0 if the three come from consecutive views.
+    fn update_high_qc(&mut self, qc: PipelineQc) -> Vec<ConsensusAction> {
+        let b2 = qc.block_hash();
+        let b2_slot = qc.slot();
+        let b1 = qc.parent_hash;
+        self.block_parents.entry(b2).or_insert(b1);
+        self.block_slots.entry(b2).or_insert(b2_slot);
+        self.qcs
+            .entry((b2_slot, Phase::Prevote, b2))
+            .or_insert_with(|| qc.qc.clone());
+        if self.high_qc.as_ref().map_or(true, |h| b2_slot > h.slot()) {
+            self.high_qc = Some(qc);
+        }
+
+        let Some(b1_slot) = self.block_slots.get(&b1).copied() else {
+            return vec![];
+        };
+        if b1_slot > self.locked_slot {
+            self.locked_block = Some(b1);
+            self.locked_slot = b1_slot;
+        }
+        let Some(b0) = self.block_parents.get(&b1).copied() else {
+            return vec![];
+        };
+        let Some(b0_slot) = self.block_slots.get(&b0).copied() else {
+            return vec![];
+        };
+        let consecutive =
+            b0_slot.saturating_add(1) == b1_slot && b1_slot.saturating_add(1) == b2_slot;
+        if !consecutive || b0 == H256::zero() || b0_slot <= self.finalized_slot {
+            return vec![];
+        }
+        self.finalized_slot = b0_slot;
+        if b1_slot > self.committed_slot {
+            self.committed_slot = b1_slot;
+        }
+        tracing::info!(
+            finalized_slot = b0_slot,
+            block_hash = ?b0,
+            "Block finalized via 3-chain rule"
+        );
+        self.prune_finalized_state();
+        vec![ConsensusAction::Finalized {
+            slot: b0_slot,
+            block_hash: b0,
+        }]
+    }
+
+    /// Whether `ancestor` is on the chain of parents leading to `block`.
+    fn extends(&self, block: H256, ancestor: H256) -> bool {
+        let floor = self.block_slots.get(&ancestor).copied().unwrap_or(0);
+        let mut at = block;
+        for _ in 0..=self.block_parents.len() {
+            if at == ancestor {
+                return true;
+            }
+            match (self.block_parents.get(&at), self.block_slots.get(&at)) {
+                (Some(parent), Some(slot)) if *slot > floor => at = *parent,
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Verify a QC received from a peer against our validator set.
+    fn verify_pipeline_qc(&self, qc: &PipelineQc) -> Result<()> {
+        let agg = &qc.qc;
+        if agg.phase != Phase::Prevote {
+            bail!("pipelined QC must certify generic prevotes");
+        }
+        if self.block_parents.get(&agg.block_hash) == Some(&qc.parent_hash)
+            && self
+                .qcs
+                .contains_key(&(agg.slot, Phase::Prevote, agg.block_hash))
+        {
+            return Ok(());
+        }
+        let mut seen = HashSet::new();
+        let mut stake: u128 = 0;
+        let mut pubkeys = Vec::with_capacity(agg.signers.len());
+        for signer in &agg.signers {
+            if !seen.insert(signer) {
+                bail!("duplicate signer in QC: {:?}", signer);
+            }
+            let info = self
+                .validators
+                .get(signer)
+                .ok_or_else(|| anyhow::anyhow!("unknown signer in QC: {:?}", signer))?;
+            stake = stake.saturating_add(info.stake);
+            pubkeys.push(
+                self.bls_pubkeys
+                    .get(signer)
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("no BLS pubkey for QC signer {:?}", signer))?,
+            );
+        }
+        if !crate::has_quorum(stake, self.total_stake) {
+            bail!(
+                "QC has insufficient stake: {} / {} total",
+                stake,
+                self.total_stake
+            );
+        }
+        let agg_pk = aggregate_public_keys(&pubkeys)?;
+        let mut msg = Vec::new();
+        msg.extend_from_slice(agg.block_hash.as_bytes());
+        msg.extend_from_slice(qc.parent_hash.as_bytes());
+        msg.extend_from_slice(&agg.slot.to_le_bytes());
+        msg.push(phase_to_byte(&Phase::Prevote));
+        if !aether_crypto_bls::keypair::verify(&agg_pk, &msg, &agg.aggregated_signature)? {
+            bail!(
+                "invalid aggregated BLS signature on QC for view {}",
+                agg.slot
+            );
+        }
+        Ok(())
+    }
+}
+
 impl crate::Finality for HotStuffConsensus {
     fn check_finality(&mut self, slot: Slot) -> bool {
         if slot <= self.finalized_slot && slot > self.last_reported_finalized {
@@ -1540,6 +2034,7 @@ mod tests {
                     proof: vec![],
                 },
                 timestamp: 0,
+                erasure: aether_types::ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: None,
@@ -1583,6 +2078,7 @@ mod tests {
                     proof: vec![],
                 },
                 timestamp: 0,
+                erasure: aether_types::ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: None,
@@ -1851,4 +2347,211 @@ mod tests {
             "slot 4 already reported after second batch"
         );
     }
+
+    /// Pipelined engines for `count` validators, one per validator, with
+    /// every BLS key registered everywhere.
+    fn pipeline_nodes(count: usize) -> (Vec<HotStuffConsensus>, Vec<BlsKeypair>) {
+        let bls_keys: Vec<BlsKeypair> = (0..count)
+            .map(|i| BlsKeypair::from_secret(vec![i as u8 + 1; 32]).unwrap())
+            .collect();
+        let validators: Vec<ValidatorInfo> = bls_keys
+            .iter()
+            .map(|bk| ValidatorInfo {
+                pubkey: PublicKey::from_bytes(bk.public_key()[..32].to_vec()),
+                stake: 1000,
+                commission: 0,
+                active: true,
+            })
+            .collect();
+        let nodes = (0..count)
+            .map(|me| {
+                let addr = validators[me].pubkey.to_address();
+                let mut node = HotStuffConsensus::new(
+                    validators.clone(),
+                    Some(bls_keys[me].clone()),
+                    Some(addr),
+                );
+                for (v, key) in validators.iter().zip(&bls_keys) {
+                    node.register_bls_pubkey(
+                        v.pubkey.to_address(),
+                        key.public_key(),
+                        &key.proof_of_possession(),
+                    )
+                    .unwrap();
+                }
+                node
+            })
+            .collect();
+        (nodes, bls_keys)
+    }
+
+    fn block_for(view: u64, parent: H256) -> H256 {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&view.to_le_bytes());
+        bytes[8..].copy_from_slice(&parent.as_bytes()[..24]);
+        H256::from(bytes)
+    }
+
+    /// Deliver every action instantly until nothing is left to do, leaving
+    /// out `crashed` nodes and proposals past `max_view`. Returns the
+    /// proposals made and each node's finalized blocks.
+    fn drive(
+        nodes: &mut [HotStuffConsensus],
+        crashed: &[usize],
+        start: Vec<(usize, ConsensusAction)>,
+        max_view: u64,
+    ) -> (Vec<Proposal>, Vec<Vec<(Slot, H256)>>) {
+        let index_of = |nodes: &[HotStuffConsensus], addr: Address| {
+            nodes
+                .iter()
+                .position(|n| n.my_address == Some(addr))
+                .unwrap()
+        };
+        let mut queue: std::collections::VecDeque<_> = start.into();
+        let mut proposals = Vec::new();
+        let mut finalized = vec![Vec::new(); nodes.len()];
+        while let Some((from, action)) = queue.pop_front() {
+            if crashed.contains(&from) {
+                continue;
+            }
+            let mut out = Vec::new();
+            match action {
+                ConsensusAction::Propose { view, parent_hash } if view <= max_view => {
+                    out.push((
+                        from,
+                        nodes[from].propose(view, block_for(view, parent_hash)),
+                    ));
+                }
+                ConsensusAction::BroadcastProposal(p) => {
+                    for (to, node) in nodes.iter_mut().enumerate() {
+                        out.push((to, node.on_proposal(&p)));
+                    }
+                    proposals.push(p);
+                }
+                ConsensusAction::BroadcastVote(v) => {
+                    for (to, node) in nodes.iter_mut().enumerate() {
+                        out.push((to, node.on_pipeline_vote(v.clone())));
+                    }
+                }
+                ConsensusAction::SendNewView(nv) => {
+                    let to = index_of(nodes, nodes[from].leader(nv.view).unwrap());
+                    out.push((to, nodes[to].on_new_view(nv)));
+                }
+                ConsensusAction::Finalized { slot, block_hash } => {
+                    finalized[from].push((slot, block_hash));
+                }
+                _ => {}
+            }
+            for (to, actions) in out {
+                if !crashed.contains(&to) {
+                    queue.extend(actions.unwrap().into_iter().map(|a| (to, a)));
+                }
+            }
+        }
+        (proposals, finalized)
+    }
+
+    fn enter_next_view(nodes: &mut [HotStuffConsensus]) -> Vec<(usize, ConsensusAction)> {
+        let mut start = Vec::new();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            start.extend(node.new_view().unwrap().into_iter().map(|a| (i, a)));
+        }
+        start
+    }
+
+    #[test]
+    fn test_pipeline_finalizes_a_block_per_view() {
+        let (mut nodes, _) = pipeline_nodes(4);
+        let start = enter_next_view(&mut nodes);
+        let (proposals, finalized) = drive(&mut nodes, &[], start, 10);
+
+        assert_eq!(proposals.len(), 10);
+        // The QC for view 10 commits view 8; views 9 and 10 are in flight.
+        for node in &nodes {
+            assert_eq!(crate::Finality::finalized_slot(node), 8);
+            assert_eq!(node.current_slot(), 11);
+            assert_eq!(node.locked_slot, 9);
+        }
+        let expected: Vec<(Slot, H256)> = proposals[..8]
+            .iter()
+            .map(|p| (p.view, p.block_hash))
+            .collect();
+        assert!(finalized.iter().all(|f| *f == expected));
+    }
+
+    #[test]
+    fn test_new_view_routes_around_a_crashed_leader() {
+        let (mut nodes, _) = pipeline_nodes(4);
+        let crashed_addr = nodes[0].leader(2).unwrap();
+        let crashed = nodes
+            .iter()
+            .position(|n| n.my_address == Some(crashed_addr))
+            .unwrap();
+
+        let start = enter_next_view(&mut nodes);
+        let (proposals, _) = drive(&mut nodes, &[crashed], start, 20);
+        assert_eq!(proposals.len(), 1, "view 2 has no leader");
+
+        // Everyone left alive times out of view 2; the leader of view 3
+        // extends the QC for view 1 and the chain runs until the crashed
+        // leader's next turn.
+        let mut start = Vec::new();
+        for (i, node) in nodes.iter_mut().enumerate() {
+            if i != crashed {
+                start.extend(node.new_view().unwrap().into_iter().map(|a| (i, a)));
+            }
+        }
+        let (proposals, finalized) = drive(&mut nodes, &[crashed], start, 20);
+        let views: Vec<u64> = proposals.iter().map(|p| p.view).collect();
+        assert_eq!(views, vec![3, 4, 5]);
+        assert_eq!(proposals[0].justify.as_ref().unwrap().slot(), 1);
+        for (i, node) in nodes.iter().enumerate() {
+            if i != crashed {
+                assert_eq!(crate::Finality::finalized_slot(node), 3);
+                assert_eq!(finalized[i], vec![(3, proposals[0].block_hash)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lock_refuses_a_fork_from_an_older_qc() {
+        let (mut nodes, keys) = pipeline_nodes(4);
+        let start = enter_next_view(&mut nodes);
+        let (proposals, _) = drive(&mut nodes, &[], start, 4);
+        assert_eq!(nodes[0].locked_slot, 3);
+
+        // The leader of view 5 forks off block 1 with the QC it carried.
+        let old_qc = proposals[1].justify.clone().unwrap();
+        assert_eq!(old_qc.slot(), 1);
+        let leader = nodes[0].leader(5).unwrap();
+        let leader_idx = nodes
+            .iter()
+            .position(|n| n.my_address == Some(leader))
+            .unwrap();
+        let block_hash = block_for(5, old_qc.block_hash());
+        let fork = Proposal {
+            view: 5,
+            block_hash,
+            parent_hash: old_qc.block_hash(),
+            justify: Some(old_qc.clone()),
+            proposer: leader,
+            signature: keys[leader_idx].sign(&proposal_message(
+                5,
+                &block_hash,
+                &old_qc.block_hash(),
+            )),
+        };
+        let actions = nodes[0].on_proposal(&fork).unwrap();
+        assert!(actions.is_empty(), "locked node must not vote for the fork");
+
+        // A QC short of quorum is rejected outright.
+        let mut weak = old_qc;
+        weak.qc.signers.truncate(2);
+        let forged = Proposal {
+            justify: Some(weak),
+            view: 6,
+            ..fork
+        };
+        assert!(nodes[1].on_proposal(&forged).is_err());
+    }
 }
@@ -4,17 +4,18 @@
 // Combines VRF-PoS leader election + HotStuff BFT + BLS signature aggregation
 // ============================================================================
 
-use crate::{ConsensusEngine, Pacemaker};
+use crate::{ConsensusEngine, EpochRandomness, Pacemaker, PacemakerConfig};
 use aether_crypto_bls::{aggregate_public_keys, aggregate_signatures, BlsKeypair};
 use aether_crypto_vrf::{
     check_leader_eligibility_integer, EcVrfVerifier, VrfKeypair, VrfProof, VrfSigner, VrfVerifier,
 };
-use aether_types::{Address, Block, PublicKey, Slot, ValidatorInfo, Vote, H256};
+use aether_types::{
+    Address, AggregatedVote, Block, Epoch, EpochInfo, FinalityCertificate, FinalityPath, PublicKey,
+    RoundSync, Slot, ValidatorInfo, ValidatorSetLeaf, ValidatorSetTree, Vote, H256,
+};
 use anyhow::{bail, Result};
-use sha2::{Digest, Sha256};
 use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::time::Duration;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 
 /// Overflow-safe (a * b) / c using 256-bit intermediate product.
 fn mul_div(a: u128, b: u128, c: u128) -> u128 {
@@ -81,6 +82,27 @@ pub struct QuorumCertificate {
     pub aggregated_pubkey: Vec<u8>,
 }
 
+/// Slots after an epoch boundary during which late votes for the outgoing
+/// epoch's last slots are still counted, against the outgoing set.
+pub const TRANSITION_WINDOW_SLOTS: Slot = 4;
+
+/// Slots between finality certificates for light clients.
+pub const DEFAULT_CHECKPOINT_INTERVAL: Slot = 32;
+
+/// Finality certificates kept for serving.
+pub const MAX_CHECKPOINTS: usize = 64;
+
+/// Share of stake, in basis points, that must be exceeded by the votes for
+/// a block within its slot to finalize it in one round.
+pub const FAST_FINALITY_THRESHOLD_BPS: u128 = 9_000;
+
+/// Finalization events whose path is kept for serving.
+pub const MAX_FINALITY_PATHS: usize = 4096;
+
+/// Slots ahead of the current one this node checks its own VRF
+/// eligibility for.
+pub const LEADER_LOOKAHEAD_SLOTS: Slot = 64;
+
 /// Full Phase 1 consensus combining:
 /// - VRF-PoS for leader election
 /// - HotStuff 2-chain for BFT finality
@@ -95,14 +117,20 @@ pub struct HybridConsensus {
     /// Prevents mid-epoch stake changes from altering leader schedules.
     epoch_validators: HashMap<Address, ValidatorInfo>,
     epoch_total_stake: u128,
+    /// Previous epoch's set, so certificates from just before a boundary
+    /// can still be expanded.
+    previous_epoch_set: Option<EpochInfo>,
+    /// Next epoch's set from staking, swapped in at its first slot.
+    staged_validator_set: Option<EpochInfo>,
+    /// Late votes for the outgoing epoch, one per validator per block.
+    transition_votes: HashMap<(Slot, H256), HashMap<Address, Vote>>,
 
     // === Slot/Epoch Management ===
     current_slot: Slot,
     current_epoch: u64,
-    epoch_randomness: H256,
+    /// Per-epoch election seeds, accumulated from finalized VRF outputs.
+    epoch_randomness: EpochRandomness,
     epoch_length: u64,
-    /// Whether epoch randomness has been updated from a real VRF output this epoch.
-    epoch_randomness_updated: bool,
 
     // === VRF-PoS Parameters ===
     #[allow(dead_code)]
@@ -121,6 +149,10 @@ pub struct HybridConsensus {
     qcs: HashMap<(Slot, Phase, H256), QuorumCertificate>,
     locked_block: Option<H256>,
     locked_slot: Slot,
+    /// Finalize a block in one round once more than 90% of stake votes for it.
+    fast_finality: bool,
+    /// Slot and block of this validator's latest vote.
+    my_last_vote: Option<(Slot, H256)>,
 
     // === Block Parent Tracking (for 2-chain finality) ===
     block_parents: HashMap<H256, H256>,
@@ -137,11 +169,30 @@ pub struct HybridConsensus {
 
     // === Pacemaker (timeout-based phase advancement) ===
     pacemaker: Pacemaker,
+    /// Highest slot each validator has reported in a round sync.
+    round_reports: HashMap<Address, Slot>,
 
     // === Finality ===
     committed_slot: Slot,
     finalized_slot: Slot,
     last_reported_finalized: Slot,
+    /// Path that moved finality up to each key slot, covering the slots
+    /// after the previous key.
+    finality_paths: BTreeMap<Slot, FinalityPath>,
+    /// Highest key pruned from `finality_paths`; slots at or below it are
+    /// no longer answered.
+    finality_paths_floor: Slot,
+
+    // === Checkpoints ===
+    checkpoint_interval: Slot,
+    /// Finality certificates, oldest first.
+    checkpoints: VecDeque<FinalityCertificate>,
+
+    // === Leader Lookahead ===
+    /// Upcoming slots this node is eligible to lead, in order.
+    my_leader_slots: VecDeque<Slot>,
+    /// Last slot whose eligibility has been checked.
+    leader_lookahead_until: Slot,
 }
 
 impl HybridConsensus {
@@ -193,16 +244,18 @@ impl HybridConsensus {
         let tau_numerator = (tau_clamped * 10000.0).round() as u128;
         let tau_denominator = 10000u128;
 
-        HybridConsensus {
+        let mut consensus = HybridConsensus {
             epoch_validators: validators_map.clone(),
             epoch_total_stake: total_stake,
+            previous_epoch_set: None,
+            staged_validator_set: None,
+            transition_votes: HashMap::new(),
             validators: validators_map,
             total_stake,
             current_slot: 0,
             current_epoch: 0,
-            epoch_randomness: H256::zero(),
+            epoch_randomness: EpochRandomness::new(H256::zero(), epoch_length),
             epoch_length,
-            epoch_randomness_updated: false,
             tau,
             tau_numerator,
             tau_denominator,
@@ -215,18 +268,296 @@ impl HybridConsensus {
             qcs: HashMap::new(),
             locked_block: None,
             locked_slot: 0,
+            fast_finality: false,
+            my_last_vote: None,
             block_parents: HashMap::new(),
             block_slots: HashMap::new(),
             vote_record: HashMap::new(),
             vrf_pubkeys: HashMap::new(),
             bls_pubkeys: HashMap::new(),
-            pacemaker: Pacemaker::new(Duration::from_millis(500)),
+            pacemaker: Pacemaker::with_config(PacemakerConfig::default()),
+            round_reports: HashMap::new(),
             committed_slot: 0,
             finalized_slot: 0,
             last_reported_finalized: 0,
+            finality_paths: BTreeMap::new(),
+            finality_paths_floor: 0,
+            checkpoint_interval: DEFAULT_CHECKPOINT_INTERVAL,
+            checkpoints: VecDeque::new(),
+            my_leader_slots: VecDeque::new(),
+            leader_lookahead_until: 0,
+        };
+        consensus.extend_leader_lookahead();
+        consensus
+    }
+
+    /// The current epoch's frozen validator set, ordered by address so every
+    /// node indexes signer bitfields the same way.
+    pub fn epoch_info(&self) -> EpochInfo {
+        let mut validators: Vec<(Address, ValidatorInfo)> = self
+            .epoch_validators
+            .iter()
+            .map(|(addr, v)| (*addr, v.clone()))
+            .collect();
+        validators.sort_by_key(|(addr, _)| addr.0);
+        let start_slot = self.current_epoch.saturating_mul(self.epoch_length);
+        EpochInfo {
+            epoch: self.current_epoch,
+            start_slot,
+            end_slot: start_slot.saturating_add(self.epoch_length - 1),
+            randomness: self.epoch_randomness.current(),
+            validators: validators.into_iter().map(|(_, v)| v).collect(),
+            total_stake: self.epoch_total_stake,
+        }
+    }
+
+    /// Compact form of `qc` for inclusion in a block: signers become a
+    /// bitfield over the current epoch's validator set.
+    pub fn finality_proof(&self, qc: &QuorumCertificate) -> Result<AggregatedVote> {
+        let epoch = self.epoch_info();
+        let signers = epoch.signer_bitfield(&qc.signers)?;
+        Ok(AggregatedVote {
+            slot: qc.slot,
+            block_hash: qc.block_hash,
+            aggregated_signature: qc.aggregated_signature.clone(),
+            epoch: epoch.epoch,
+            total_stake: epoch.signed_stake(&signers)?,
+            signers,
+        })
+    }
+
+    /// Emit a finality certificate for the first finalized block in each
+    /// window of `interval` slots.
+    pub fn set_checkpoint_interval(&mut self, interval: Slot) {
+        self.checkpoint_interval = interval.max(1);
+    }
+
+    /// Round timeouts and their backoff. Set before the engine starts: the
+    /// pacemaker restarts from round 0.
+    pub fn set_pacemaker_config(&mut self, config: PacemakerConfig) {
+        self.pacemaker = Pacemaker::with_config(config);
+    }
+
+    pub fn pacemaker_config(&self) -> PacemakerConfig {
+        self.pacemaker.config()
+    }
+
+    /// Finalize blocks that more than 90% of stake votes for within their
+    /// slot in one round, instead of waiting for a certified child. While
+    /// enabled, this validator only votes on branches that keep its last
+    /// vote until that vote's slot is certified or provably outvoted, so a
+    /// split vote can stall a network whose faulty stake nears a third.
+    pub fn set_fast_finality(&mut self, enabled: bool) {
+        self.fast_finality = enabled;
+    }
+
+    pub fn fast_finality(&self) -> bool {
+        self.fast_finality
+    }
+
+    /// How `slot` became final, or `None` if it is not final yet or its
+    /// record has been pruned.
+    pub fn finality_path(&self, slot: Slot) -> Option<FinalityPath> {
+        if slot > self.finalized_slot || slot <= self.finality_paths_floor {
+            return None;
+        }
+        self.finality_paths
+            .range(slot..)
+            .next()
+            .map(|(_, path)| *path)
+    }
+
+    /// Move finality up to `slot`, remembering which path got it there.
+    fn finalize(&mut self, slot: Slot, path: FinalityPath) {
+        if slot <= self.finalized_slot {
+            return;
+        }
+        self.finalized_slot = slot;
+        self.finality_paths.insert(slot, path);
+        while self.finality_paths.len() > MAX_FINALITY_PATHS {
+            if let Some((pruned, _)) = self.finality_paths.pop_first() {
+                self.finality_paths_floor = pruned;
+            }
+        }
+    }
+
+    /// Finalize `block_hash` in one round if more than 90% of stake has
+    /// voted for it this slot. Votes arriving after its QC land in later
+    /// phases' buckets, so every bucket for the block counts.
+    fn try_fast_finality(&mut self, slot: Slot, block_hash: H256) {
+        if !self.fast_finality
+            || slot <= self.finalized_slot
+            || !self.qcs.contains_key(&(slot, Phase::Propose, block_hash))
+        {
+            return;
+        }
+        let voters: HashSet<&Address> = self
+            .votes
+            .iter()
+            .filter(|((s, _, h), _)| *s == slot && *h == block_hash)
+            .flat_map(|(_, votes)| votes.keys())
+            .collect();
+        let voted_stake = voters
+            .iter()
+            .filter_map(|addr| self.epoch_validators.get(*addr))
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add);
+        if voted_stake > mul_div(self.epoch_total_stake, FAST_FINALITY_THRESHOLD_BPS, 10_000) {
+            self.finalize(slot, FinalityPath::Fast);
+            tracing::info!(
+                finalized_slot = slot,
+                block_hash = ?block_hash,
+                "fast finality: block finalized in one round"
+            );
+        }
+    }
+
+    /// This validator's last vote while it may still have helped finalize
+    /// a block in one round without this node seeing the other votes.
+    /// Released once a QC at or above its slot is known, which hands safety
+    /// back to the regular lock, or once enough stake voted elsewhere that
+    /// the block cannot have passed 90%.
+    fn fast_vote_lock(&self) -> Option<(Slot, H256)> {
+        if !self.fast_finality {
+            return None;
+        }
+        let (slot, block_hash) = self.my_last_vote?;
+        if self.locked_block.is_some() && self.locked_slot >= slot {
+            return None;
+        }
+        let set = self.validator_set(slot / self.epoch_length)?;
+        let mut elsewhere: Vec<u128> = set
+            .validators
+            .iter()
+            .filter(|v| {
+                self.vote_record
+                    .get(&(slot, v.pubkey.to_address()))
+                    .is_some_and(|voted| *voted != block_hash)
+            })
+            .map(|v| v.stake)
+            .collect();
+        // Byzantine validators may have voted both ways. Discount the most
+        // stake a set holding under a third could account for.
+        elsewhere.sort_unstable();
+        let mut smallest = 0u128;
+        let mut count = 0;
+        for stake in &elsewhere {
+            smallest = smallest.saturating_add(*stake);
+            if smallest.saturating_mul(3) >= set.total_stake {
+                break;
+            }
+            count += 1;
+        }
+        let byzantine = elsewhere
+            .iter()
+            .rev()
+            .take(count)
+            .fold(0u128, |acc, s| acc.saturating_add(*s))
+            .min(set.total_stake / 3);
+        let honest = elsewhere
+            .iter()
+            .fold(0u128, |acc, s| acc.saturating_add(*s))
+            .saturating_sub(byzantine);
+        if honest.saturating_mul(10) >= set.total_stake {
+            return None;
+        }
+        Some((slot, block_hash))
+    }
+
+    /// Whether `hash`'s branch passes through `ancestor` at `ancestor_slot`.
+    fn descends_from(&self, mut hash: H256, ancestor: H256, ancestor_slot: Slot) -> bool {
+        while hash != ancestor {
+            match (self.block_slots.get(&hash), self.block_parents.get(&hash)) {
+                (Some(&slot), Some(&parent)) if slot > ancestor_slot => hash = parent,
+                _ => return false,
+            }
+        }
+        true
+    }
+
+    /// Merkle tree over `set` in its own order. Validators that never
+    /// registered a BLS key commit to an empty key; they cannot sign.
+    fn validator_set_tree(&self, set: &EpochInfo) -> ValidatorSetTree {
+        let leaves = set
+            .validators
+            .iter()
+            .map(|v| ValidatorSetLeaf {
+                bls_pubkey: self
+                    .bls_pubkeys
+                    .get(&v.pubkey.to_address())
+                    .cloned()
+                    .unwrap_or_default(),
+                stake: v.stake,
+            })
+            .collect();
+        ValidatorSetTree::new(set.epoch, leaves)
+    }
+
+    /// The validator-set root a light client trusts to check `epoch`'s
+    /// finality certificates.
+    pub fn validator_set_commitment(&self, epoch: Epoch) -> Option<H256> {
+        let set = ConsensusEngine::validator_set(self, epoch)?;
+        Some(self.validator_set_tree(&set).commitment())
+    }
+
+    /// Self-contained form of `qc`: signers are proven against the
+    /// validator set of the epoch the QC's slot belongs to.
+    pub fn finality_certificate(&self, qc: &QuorumCertificate) -> Result<FinalityCertificate> {
+        let epoch = qc.slot / self.epoch_length;
+        let set = ConsensusEngine::validator_set(self, epoch)
+            .ok_or_else(|| anyhow::anyhow!("validator set of epoch {epoch} is gone"))?;
+        let signers = set.signer_bitfield(&qc.signers)?;
+        FinalityCertificate::new(
+            &self.validator_set_tree(&set),
+            qc.slot,
+            qc.block_hash,
+            qc.aggregated_signature.clone(),
+            signers,
+        )
+    }
+
+    /// Certify the finalized block once finality enters a new checkpoint
+    /// window.
+    fn emit_checkpoint(&mut self) {
+        let slot = self.finalized_slot;
+        let last = self.checkpoints.back().map_or(0, |cert| cert.slot);
+        if slot / self.checkpoint_interval <= last / self.checkpoint_interval {
+            return;
+        }
+        let Some(qc) = self
+            .qcs
+            .iter()
+            .find(|((s, phase, _), _)| *s == slot && *phase == Phase::Propose)
+            .map(|(_, qc)| qc)
+        else {
+            return;
+        };
+        match self.finality_certificate(qc) {
+            Ok(cert) => {
+                tracing::info!(
+                    slot,
+                    epoch = cert.epoch,
+                    signers = cert.signer_proofs.len(),
+                    "finality checkpoint"
+                );
+                self.checkpoints.push_back(cert);
+                while self.checkpoints.len() > MAX_CHECKPOINTS {
+                    self.checkpoints.pop_front();
+                }
+            }
+            Err(e) => tracing::warn!(slot, err = %e, "cannot certify finalized block"),
         }
     }
 
+    /// Finality certificates for slots from `from_slot` on, oldest first.
+    pub fn checkpoints(&self, from_slot: Slot) -> Vec<FinalityCertificate> {
+        self.checkpoints
+            .iter()
+            .filter(|cert| cert.slot >= from_slot)
+            .cloned()
+            .collect()
+    }
+
     /// Check if I am eligible to be leader for this slot
     pub fn check_my_eligibility(&self, slot: Slot) -> Option<VrfProof> {
         let vrf_keypair = self.my_vrf_keypair.as_ref()?;
@@ -236,7 +567,7 @@ impl HybridConsensus {
 
         // Compute VRF input: epoch_randomness || slot
         let mut input = Vec::new();
-        input.extend_from_slice(self.epoch_randomness.as_bytes());
+        input.extend_from_slice(self.epoch_randomness.current().as_bytes());
         input.extend_from_slice(&slot.to_le_bytes());
 
         let proof = VrfSigner::prove(vrf_keypair.as_ref(), &input);
@@ -255,6 +586,39 @@ impl HybridConsensus {
         }
     }
 
+    /// Check eligibility for slots up to `LEADER_LOOKAHEAD_SLOTS` ahead,
+    /// one new slot per call in steady state. The lottery is private: only
+    /// this node's own slots can be known before their blocks arrive, and
+    /// only within the epoch whose seed is revealed.
+    fn extend_leader_lookahead(&mut self) {
+        while self
+            .my_leader_slots
+            .front()
+            .is_some_and(|&slot| slot <= self.current_slot)
+        {
+            self.my_leader_slots.pop_front();
+        }
+        let epoch_end = self
+            .current_epoch
+            .saturating_add(1)
+            .saturating_mul(self.epoch_length)
+            .saturating_sub(1);
+        let until = self
+            .current_slot
+            .saturating_add(LEADER_LOOKAHEAD_SLOTS)
+            .min(epoch_end);
+        self.leader_lookahead_until = self.leader_lookahead_until.max(self.current_slot);
+        while self.leader_lookahead_until < until {
+            self.leader_lookahead_until += 1;
+            if self
+                .check_my_eligibility(self.leader_lookahead_until)
+                .is_some()
+            {
+                self.my_leader_slots.push_back(self.leader_lookahead_until);
+            }
+        }
+    }
+
     /// Register a validator's VRF public key for cross-validation.
     pub fn register_vrf_pubkey(&mut self, address: Address, vrf_pubkey: [u8; 32]) {
         self.vrf_pubkeys.insert(address, vrf_pubkey);
@@ -271,7 +635,7 @@ impl HybridConsensus {
 
         // Reconstruct VRF input
         let mut input = Vec::new();
-        input.extend_from_slice(self.epoch_randomness.as_bytes());
+        input.extend_from_slice(self.epoch_randomness.current().as_bytes());
         input.extend_from_slice(&block.header.slot.to_le_bytes());
 
         // Convert VRF proof
@@ -359,24 +723,20 @@ impl HybridConsensus {
         Ok(())
     }
 
-    /// Update epoch randomness using a real VRF output from the first finalized block.
-    /// Returns true if this was the first update this epoch (idempotent guard).
-    pub fn update_epoch_randomness(&mut self, block_vrf_output: &[u8; 32]) -> bool {
-        if self.epoch_randomness_updated {
-            return false;
-        }
-        let mut hasher = Sha256::new();
-        hasher.update(self.epoch_randomness.as_bytes());
-        hasher.update(block_vrf_output);
-        hasher.update(self.current_epoch.to_le_bytes());
-        self.epoch_randomness = H256::from(<[u8; 32]>::from(hasher.finalize()));
-        self.epoch_randomness_updated = true;
-        true
+    /// Mix the VRF output of the finalized block at `slot` into the next
+    /// epoch's seed. Returns false if it falls outside the contribution
+    /// window (see `EpochRandomness`).
+    pub fn update_epoch_randomness(&mut self, slot: Slot, block_vrf_output: &[u8; 32]) -> bool {
+        self.epoch_randomness.contribute(slot, block_vrf_output)
+    }
+
+    /// Election seed of `epoch`, for recent epochs.
+    pub fn randomness_for_epoch(&self, epoch: Epoch) -> Option<H256> {
+        self.epoch_randomness.randomness_for_epoch(epoch)
     }
 
-    /// Whether epoch randomness has already been updated from a real VRF output.
-    pub fn epoch_randomness_updated(&self) -> bool {
-        self.epoch_randomness_updated
+    pub fn epoch_randomness(&self) -> &EpochRandomness {
+        &self.epoch_randomness
     }
 
     /// Process a vote and check for quorum.
@@ -388,6 +748,9 @@ impl HybridConsensus {
     pub fn process_vote(&mut self, vote: Vote) -> Result<Option<QuorumCertificate>> {
         // Verify vote is for current slot
         if vote.slot != self.current_slot {
+            if self.in_transition_window(vote.slot) {
+                return self.process_transition_vote(vote);
+            }
             bail!(
                 "vote for wrong slot: got {}, expected {}",
                 vote.slot,
@@ -417,66 +780,10 @@ impl HybridConsensus {
 
         // Verify BLS signature FIRST (before equivocation check, to prevent
         // an attacker from poisoning the equivocation record with invalid-sig votes).
-        // Mandatory: every validator MUST have a registered BLS key, and every vote
-        // MUST carry a valid 96-byte BLS signature.
-        let bls_pk = self.bls_pubkeys.get(&voter_addr).ok_or_else(|| {
-            anyhow::anyhow!(
-                "no BLS public key registered for validator {:?}",
-                voter_addr
-            )
-        })?;
-        if bls_pk.len() != 48 {
-            bail!(
-                "registered BLS pubkey has invalid length {} for {:?}",
-                bls_pk.len(),
-                voter_addr
-            );
-        }
-        let vote_msg = {
-            let mut msg = Vec::new();
-            msg.extend_from_slice(vote.block_hash.as_bytes());
-            msg.extend_from_slice(&vote.slot.to_le_bytes());
-            msg
-        };
-        let sig_bytes = vote.signature.as_bytes();
-        if sig_bytes.len() != 96 {
-            bail!(
-                "vote signature has invalid length {} from {:?}",
-                sig_bytes.len(),
-                voter_addr
-            );
-        }
-        match aether_crypto_bls::keypair::verify(bls_pk, &vote_msg, sig_bytes) {
-            Ok(true) => {} // Valid signature
-            Ok(false) => bail!("invalid BLS signature on vote from {:?}", voter_addr),
-            Err(e) => bail!("BLS verification error for {:?}: {e}", voter_addr),
-        }
-
-        // Equivocation detection: check if this validator already voted for a DIFFERENT block
-        // at this slot. Only checked AFTER signature verification so invalid-sig votes
-        // can't poison the record.
-        // Uses entry() API for atomic check-then-insert (no TOCTOU race).
-        let vote_key = (vote.slot, voter_addr);
-        match self.vote_record.entry(vote_key) {
-            Entry::Occupied(e) => {
-                if *e.get() != vote.block_hash {
-                    tracing::warn!(
-                        validator = ?voter_addr,
-                        first_block = ?e.get(),
-                        second_block = ?vote.block_hash,
-                        slot = vote.slot,
-                        "EQUIVOCATION: validator double-voted in same slot"
-                    );
-                    bail!(
-                        "equivocation detected: validator {:?} double-voted at slot {}",
-                        voter_addr,
-                        vote.slot
-                    );
-                }
-            }
-            Entry::Vacant(e) => {
-                e.insert(vote.block_hash);
-            }
+        self.verify_vote_signature(&voter_addr, &vote)?;
+        self.record_vote_for_equivocation(&voter_addr, &vote)?;
+        if self.my_address == Some(voter_addr) {
+            self.my_last_vote = Some((vote.slot, vote.block_hash));
         }
 
         // Bound vote storage: limit unique block hashes per (slot, phase) to prevent
@@ -529,9 +836,14 @@ impl HybridConsensus {
                 if vote.slot > self.committed_slot {
                     self.committed_slot = vote.slot;
                 }
-                if vote.slot > self.finalized_slot {
-                    self.finalized_slot = vote.slot;
-                }
+                // A lone validator always finalizes on its own vote; report
+                // it under whichever path this chain is configured for.
+                let path = if self.fast_finality {
+                    FinalityPath::Fast
+                } else {
+                    FinalityPath::TwoChain
+                };
+                self.finalize(vote.slot, path);
                 self.pacemaker.on_commit();
                 return Ok(Some(qc));
             }
@@ -554,6 +866,11 @@ impl HybridConsensus {
             //     1. B has a QC (from its own slot's Propose phase)
             //     2. B's child C also has a QC (current slot's Propose phase)
             //     3. C.parent_hash == B.hash
+            //     4. C is in the slot right after B
+            //
+            // Without (4) a block certified in a slot between B and C can
+            // still be extended by validators locked below B, and finalize
+            // a fork of B.
             //
             match self.current_phase {
                 Phase::Propose => {
@@ -564,9 +881,10 @@ impl HybridConsensus {
                             // Parent's QC was also formed in Propose phase
                             let parent_key = (parent_slot, Phase::Propose, parent_hash);
                             if self.qcs.contains_key(&parent_key)
+                                && parent_slot + 1 == vote.slot
                                 && parent_slot > self.finalized_slot
                             {
-                                self.finalized_slot = parent_slot;
+                                self.finalize(parent_slot, FinalityPath::TwoChain);
                                 tracing::info!(
                                     finalized_slot = parent_slot,
                                     child_slot = vote.slot,
@@ -597,7 +915,7 @@ impl HybridConsensus {
                             if self.qcs.contains_key(&prevote_key)
                                 && parent_slot > self.finalized_slot
                             {
-                                self.finalized_slot = parent_slot;
+                                self.finalize(parent_slot, FinalityPath::TwoChain);
                                 tracing::info!(
                                     finalized_slot = parent_slot,
                                     child_slot = vote.slot,
@@ -613,6 +931,8 @@ impl HybridConsensus {
                 Phase::Commit => {}
             }
 
+            self.try_fast_finality(vote.slot, vote.block_hash);
+
             // CRITICAL: Advance phase after QC formation.
             // This drives the HotStuff state machine:
             // Propose → Prevote → Precommit → Commit → Propose
@@ -621,9 +941,413 @@ impl HybridConsensus {
             return Ok(Some(qc));
         }
 
+        // A block past its QC can still cross the fast-finality threshold.
+        self.try_fast_finality(vote.slot, vote.block_hash);
         Ok(None)
     }
 
+    /// Slot of `block`'s parent if it is certified: we formed its QC
+    /// ourselves, or the block carries it as its `aggregated_vote`.
+    fn certified_parent_slot(&self, block: &Block) -> Result<Option<Slot>> {
+        let parent = block.header.parent_hash;
+        if let Some(&slot) = self.block_slots.get(&parent) {
+            if self.qcs.contains_key(&(slot, Phase::Propose, parent)) {
+                return Ok(Some(slot));
+            }
+        }
+        match &block.aggregated_vote {
+            Some(qc) if qc.block_hash == parent => {
+                self.verify_aggregated_vote(qc)?;
+                Ok(Some(qc.slot))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Check a QC carried in a block: a 2/3 stake quorum of its epoch's
+    /// validators signed `block_hash || slot`. Returns the signers and their
+    /// stake.
+    fn verify_aggregated_vote(&self, qc: &AggregatedVote) -> Result<(Vec<Address>, u128)> {
+        let set = self
+            .validator_set(qc.epoch)
+            .ok_or_else(|| anyhow::anyhow!("QC references unknown epoch {}", qc.epoch))?;
+        let signers = set.signers(&qc.signers)?;
+        let signed_stake = signers
+            .iter()
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add);
+        if !crate::has_quorum(signed_stake, set.total_stake) {
+            bail!(
+                "QC for slot {} has stake {} < 2/3 of {}",
+                qc.slot,
+                signed_stake,
+                set.total_stake
+            );
+        }
+        let pubkeys = signers
+            .iter()
+            .map(|v| {
+                self.bls_pubkeys
+                    .get(&v.pubkey.to_address())
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("no BLS key for QC signer"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let agg_pk = aggregate_public_keys(&pubkeys)
+            .map_err(|e| anyhow::anyhow!("failed to aggregate QC signer keys: {e}"))?;
+        let mut msg = qc.block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&qc.slot.to_le_bytes());
+        let valid = aether_crypto_bls::verify_aggregated(&agg_pk, &msg, &qc.aggregated_signature)
+            .map_err(|e| anyhow::anyhow!("QC signature verification error: {e}"))?;
+        if !valid {
+            bail!("invalid QC signature for slot {}", qc.slot);
+        }
+        let addresses = signers.iter().map(|v| v.pubkey.to_address()).collect();
+        Ok((addresses, signed_stake))
+    }
+
+    /// Learn a QC a block carries for its parent and lock on it if it is
+    /// the highest seen. A validator that missed the votes would otherwise
+    /// stay locked below it and could vote for a branch that skips a block
+    /// the rest of the network is about to finalize.
+    pub fn record_qc(&mut self, qc: &AggregatedVote) -> Result<()> {
+        let key = (qc.slot, Phase::Propose, qc.block_hash);
+        if self.qcs.contains_key(&key) {
+            return Ok(());
+        }
+        let (signers, total_stake) = self.verify_aggregated_vote(qc)?;
+        self.block_slots.entry(qc.block_hash).or_insert(qc.slot);
+        self.qcs.insert(
+            key,
+            QuorumCertificate {
+                slot: qc.slot,
+                block_hash: qc.block_hash,
+                phase: Phase::Propose,
+                total_stake,
+                signers,
+                aggregated_signature: qc.aggregated_signature.clone(),
+                aggregated_pubkey: vec![],
+            },
+        );
+        if self.locked_block.is_none() || qc.slot > self.locked_slot {
+            self.locked_block = Some(qc.block_hash);
+            self.locked_slot = qc.slot;
+        }
+        Ok(())
+    }
+
+    /// This validator's slot and highest QC, signed, for broadcast when the
+    /// pacemaker times out. `None` if this node is not a validator.
+    pub fn round_sync(&self) -> Result<Option<RoundSync>> {
+        let (Some(bls_keypair), Some(my_addr)) = (&self.my_bls_keypair, &self.my_address) else {
+            return Ok(None);
+        };
+        let validator = self
+            .epoch_validators
+            .get(my_addr)
+            .ok_or_else(|| anyhow::anyhow!("not in validator set"))?;
+        let high_qc = self
+            .qcs
+            .values()
+            .filter(|qc| qc.phase == Phase::Propose)
+            .max_by_key(|qc| qc.slot)
+            .and_then(|qc| self.finality_proof(qc).ok());
+        let signature = bls_keypair.sign(&RoundSync::signing_message(self.current_slot));
+        Ok(Some(RoundSync {
+            slot: self.current_slot,
+            high_qc,
+            validator: validator.pubkey.clone(),
+            signature: aether_types::Signature::from_bytes(signature),
+        }))
+    }
+
+    /// Take in another validator's round sync. Returns the slot this node
+    /// skipped ahead to, if it did.
+    ///
+    /// Validators holding more than a third of the stake include an honest
+    /// one, so the highest slot that much stake reports reaching is one an
+    /// honest clock got to: jumping there ends a partition or clock skew
+    /// without letting a lone byzantine validator drag everyone ahead.
+    pub fn on_round_sync(&mut self, sync: &RoundSync) -> Result<Option<Slot>> {
+        let sender = sync.validator.to_address();
+        if !self.epoch_validators.contains_key(&sender) {
+            bail!("round sync from unknown validator: {:?}", sender);
+        }
+        let bls_pk = self.bls_pubkeys.get(&sender).ok_or_else(|| {
+            anyhow::anyhow!("no BLS public key registered for validator {:?}", sender)
+        })?;
+        let msg = RoundSync::signing_message(sync.slot);
+        match aether_crypto_bls::keypair::verify(bls_pk, &msg, sync.signature.as_bytes()) {
+            Ok(true) => {}
+            Ok(false) => bail!("invalid BLS signature on round sync from {:?}", sender),
+            Err(e) => bail!("BLS verification error for {:?}: {e}", sender),
+        }
+
+        // A QC from an epoch this node no longer knows is no reason to
+        // ignore the slot report.
+        if let Some(qc) = &sync.high_qc {
+            if let Err(e) = self.record_qc(qc) {
+                tracing::debug!(slot = qc.slot, err = %e, "ignoring round sync QC");
+            }
+        }
+        let reported = self.round_reports.entry(sender).or_insert(0);
+        *reported = (*reported).max(sync.slot);
+
+        let target = self.synced_slot();
+        if target <= self.current_slot {
+            return Ok(None);
+        }
+        let skipped = target - self.current_slot;
+        ConsensusEngine::skip_to_slot(self, target);
+        // One round per skipped slot: a jump of more than one also clears
+        // the backoff the partitioned node built up.
+        let round = self.pacemaker.current_round().saturating_add(skipped);
+        self.pacemaker.advance_to_round(round);
+        tracing::info!(
+            slot = target,
+            skipped,
+            "caught up to the network via round sync"
+        );
+        Ok(Some(target))
+    }
+
+    /// Highest slot reported by validators holding more than a third of
+    /// the current epoch's stake.
+    fn synced_slot(&mut self) -> Slot {
+        let validators = &self.epoch_validators;
+        self.round_reports
+            .retain(|address, _| validators.contains_key(address));
+        let mut reports: Vec<(Slot, u128)> = self
+            .round_reports
+            .iter()
+            .map(|(address, slot)| (*slot, validators[address].stake))
+            .collect();
+        reports.sort_unstable_by_key(|&(slot, _)| std::cmp::Reverse(slot));
+        let mut stake = 0u128;
+        for (slot, validator_stake) in reports {
+            stake = stake.saturating_add(validator_stake);
+            if stake.saturating_mul(3) > self.epoch_total_stake {
+                return slot;
+            }
+        }
+        0
+    }
+
+    /// Check a vote's BLS signature against the voter's registered key.
+    /// Mandatory: every validator MUST have a registered BLS key, and every vote
+    /// MUST carry a valid 96-byte BLS signature.
+    fn verify_vote_signature(&self, voter_addr: &Address, vote: &Vote) -> Result<()> {
+        let bls_pk = self.bls_pubkeys.get(voter_addr).ok_or_else(|| {
+            anyhow::anyhow!(
+                "no BLS public key registered for validator {:?}",
+                voter_addr
+            )
+        })?;
+        if bls_pk.len() != 48 {
+            bail!(
+                "registered BLS pubkey has invalid length {} for {:?}",
+                bls_pk.len(),
+                voter_addr
+            );
+        }
+        let vote_msg = {
+            let mut msg = Vec::new();
+            msg.extend_from_slice(vote.block_hash.as_bytes());
+            msg.extend_from_slice(&vote.slot.to_le_bytes());
+            msg
+        };
+        let sig_bytes = vote.signature.as_bytes();
+        if sig_bytes.len() != 96 {
+            bail!(
+                "vote signature has invalid length {} from {:?}",
+                sig_bytes.len(),
+                voter_addr
+            );
+        }
+        match aether_crypto_bls::keypair::verify(bls_pk, &vote_msg, sig_bytes) {
+            Ok(true) => {} // Valid signature
+            Ok(false) => bail!("invalid BLS signature on vote from {:?}", voter_addr),
+            Err(e) => bail!("BLS verification error for {:?}: {e}", voter_addr),
+        }
+        Ok(())
+    }
+
+    /// Equivocation detection: reject a second vote from the same validator
+    /// for a different block at the same slot.
+    fn record_vote_for_equivocation(&mut self, voter_addr: &Address, vote: &Vote) -> Result<()> {
+        // Only checked AFTER signature verification so invalid-sig votes
+        // can't poison the record.
+        // Uses entry() API for atomic check-then-insert (no TOCTOU race).
+        let vote_key = (vote.slot, *voter_addr);
+        match self.vote_record.entry(vote_key) {
+            Entry::Occupied(e) => {
+                if *e.get() != vote.block_hash {
+                    tracing::warn!(
+                        validator = ?voter_addr,
+                        first_block = ?e.get(),
+                        second_block = ?vote.block_hash,
+                        slot = vote.slot,
+                        "EQUIVOCATION: validator double-voted in same slot"
+                    );
+                    bail!(
+                        "equivocation detected: validator {:?} double-voted at slot {}",
+                        voter_addr,
+                        vote.slot
+                    );
+                }
+            }
+            Entry::Vacant(e) => {
+                e.insert(vote.block_hash);
+            }
+        }
+        Ok(())
+    }
+
+    /// Queue the validator set staking elected for the next epoch. It replaces
+    /// the live set at the epoch's first slot, without a restart.
+    pub fn stage_validator_set(&mut self, next: EpochInfo) -> Result<()> {
+        let epoch = self.current_epoch.saturating_add(1);
+        if next.epoch != epoch {
+            bail!(
+                "can only stage the next epoch's set ({}), got epoch {}",
+                epoch,
+                next.epoch
+            );
+        }
+        if next.start_slot != epoch.saturating_mul(self.epoch_length) {
+            bail!(
+                "epoch {} starts at slot {}, not {}",
+                epoch,
+                epoch.saturating_mul(self.epoch_length),
+                next.start_slot
+            );
+        }
+        // Recompute rather than trust next.total_stake: it sets the quorum.
+        let total_stake = next
+            .validators
+            .iter()
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add);
+        if total_stake == 0 {
+            bail!("validator set for epoch {} has no stake", epoch);
+        }
+        self.staged_validator_set = Some(EpochInfo {
+            total_stake,
+            ..next
+        });
+        Ok(())
+    }
+
+    /// Whether a vote for `slot` is a late vote for the outgoing epoch that
+    /// still counts: one of its last slots, arriving early in the new epoch.
+    fn in_transition_window(&self, slot: Slot) -> bool {
+        let Some(outgoing) = &self.previous_epoch_set else {
+            return false;
+        };
+        let start = self.current_epoch.saturating_mul(self.epoch_length);
+        slot >= outgoing.start_slot
+            && slot < start
+            && slot.saturating_add(TRANSITION_WINDOW_SLOTS) >= start
+            && self.current_slot < start.saturating_add(TRANSITION_WINDOW_SLOTS)
+    }
+
+    /// Count a late vote for the outgoing epoch against the outgoing set and
+    /// its stake, so the epoch's last blocks can still gather a QC after
+    /// validators that left it stop voting.
+    fn process_transition_vote(&mut self, vote: Vote) -> Result<Option<QuorumCertificate>> {
+        let voter_addr = vote.validator.to_address();
+        let outgoing = self
+            .previous_epoch_set
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("no outgoing validator set"))?;
+        let registered = outgoing
+            .validators
+            .iter()
+            .find(|v| v.pubkey.to_address() == voter_addr)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "{:?} is not in epoch {}'s validator set",
+                    voter_addr,
+                    outgoing.epoch
+                )
+            })?;
+        if vote.stake != registered.stake {
+            bail!(
+                "claimed stake {} != registered stake {} for {:?}",
+                vote.stake,
+                registered.stake,
+                voter_addr
+            );
+        }
+        let outgoing_stake = outgoing.total_stake;
+        self.verify_vote_signature(&voter_addr, &vote)?;
+        self.record_vote_for_equivocation(&voter_addr, &vote)?;
+
+        let qc_key = (vote.slot, Phase::Propose, vote.block_hash);
+        if self.qcs.contains_key(&qc_key) {
+            return Ok(None);
+        }
+        let votes_map = self
+            .transition_votes
+            .entry((vote.slot, vote.block_hash))
+            .or_default();
+        if votes_map.insert(voter_addr, vote.clone()).is_some() {
+            return Ok(None);
+        }
+        let voted_stake = votes_map
+            .values()
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add);
+        if !crate::has_quorum(voted_stake, outgoing_stake) {
+            return Ok(None);
+        }
+
+        let votes_vec: Vec<Vote> = votes_map.values().cloned().collect();
+        let qc = QuorumCertificate {
+            phase: Phase::Propose,
+            ..self.aggregate_votes(&votes_vec)?
+        };
+        self.qcs.insert(qc_key, qc.clone());
+        self.transition_votes.remove(&(vote.slot, vote.block_hash));
+        tracing::info!(
+            slot = vote.slot,
+            block_hash = ?vote.block_hash,
+            "QC formed from late votes of the outgoing validator set"
+        );
+
+        // 2-chain rule, both ways: this block may finalize its parent, and a
+        // child certified before the late QC arrived now finalizes it. Both
+        // need the two blocks in consecutive slots.
+        let parent = self
+            .block_parents
+            .get(&vote.block_hash)
+            .and_then(|p| self.block_slots.get(p).map(|slot| (*p, *slot)));
+        if let Some((parent_hash, parent_slot)) = parent {
+            if self
+                .qcs
+                .contains_key(&(parent_slot, Phase::Propose, parent_hash))
+                && parent_slot + 1 == vote.slot
+                && parent_slot > self.finalized_slot
+            {
+                self.finalize(parent_slot, FinalityPath::TwoChain);
+            }
+        }
+        let certified_child = self.block_parents.iter().any(|(child, parent)| {
+            *parent == vote.block_hash
+                && self.block_slots.get(child).is_some_and(|slot| {
+                    *slot == vote.slot + 1
+                        && self.qcs.contains_key(&(*slot, Phase::Propose, *child))
+                })
+        });
+        if certified_child {
+            self.finalize(vote.slot, FinalityPath::TwoChain);
+        }
+        if vote.slot > self.committed_slot {
+            self.committed_slot = vote.slot;
+        }
+        Ok(Some(qc))
+    }
+
     /// Process a batch of votes with a single multi-pairing BLS verification.
     ///
     /// Batch verification via `verify_batch` collapses N pairing checks into
@@ -838,6 +1562,9 @@ impl ConsensusEngine for HybridConsensus {
     }
 
     fn advance_slot(&mut self) {
+        // Certify before the QCs are pruned or the epoch's set rotates.
+        self.emit_checkpoint();
+
         self.current_slot = self.current_slot.saturating_add(1);
         self.current_phase = Phase::Propose;
         self.votes.clear();
@@ -853,26 +1580,47 @@ impl ConsensusEngine for HybridConsensus {
         let slots_to_keep: std::collections::HashSet<&H256> = self.block_slots.keys().collect();
         self.block_parents.retain(|k, _| slots_to_keep.contains(k));
 
+        // Late votes for the outgoing epoch are no longer counted.
+        if self.current_slot % self.epoch_length == TRANSITION_WINDOW_SLOTS {
+            self.transition_votes.clear();
+        }
+
         // Check for epoch transition
         if self.epoch_length > 0 && self.current_slot % self.epoch_length == 0 {
-            // If no real VRF output arrived this epoch, apply deterministic fallback.
-            if !self.epoch_randomness_updated {
-                let mut hasher = Sha256::new();
-                hasher.update(self.epoch_randomness.as_bytes());
-                hasher.update(self.current_slot.to_le_bytes());
-                hasher.update(self.current_epoch.to_le_bytes());
-                let new_randomness = hasher.finalize();
-                self.epoch_randomness = H256::from(<[u8; 32]>::from(new_randomness));
-            }
-            self.epoch_randomness_updated = false;
+            self.previous_epoch_set = Some(self.epoch_info());
+            // Reveal the seed committed during the epoch that just ended.
+            self.epoch_randomness.advance_to(self.current_slot);
             self.current_epoch = self.current_epoch.saturating_add(1);
+            self.transition_votes.clear();
+
+            // Swap in the set staking elected for this epoch, if any.
+            if let Some(next) = self.staged_validator_set.take() {
+                if next.epoch == self.current_epoch {
+                    tracing::info!(
+                        epoch = next.epoch,
+                        validators = next.validators.len(),
+                        total_stake = next.total_stake,
+                        "activating staked validator set"
+                    );
+                    self.total_stake = next.total_stake;
+                    self.validators = next
+                        .validators
+                        .into_iter()
+                        .map(|v| (v.pubkey.to_address(), v))
+                        .collect();
+                }
+            }
 
             // Snapshot the current validator set for the new epoch.
             // Leader election uses this frozen snapshot so mid-epoch slashing
             // doesn't retroactively alter the leader schedule.
             self.epoch_validators = self.validators.clone();
             self.epoch_total_stake = self.total_stake;
+
+            // New seed and stake: every looked-ahead slot is stale.
+            self.my_leader_slots.clear();
         }
+        self.extend_leader_lookahead();
     }
 
     fn is_leader(&self, slot: Slot, validator_pubkey: &PublicKey) -> bool {
@@ -916,13 +1664,18 @@ impl ConsensusEngine for HybridConsensus {
         // Without this, validators can deadlock after a timeout if the locked block's
         // chain stalls — the new leader's block extending a different branch would be
         // permanently rejected.
+        //
+        // The parent must itself be certified. A byzantine leader can otherwise
+        // build on an uncertified sibling of our lock, and the quorum it gathers
+        // finalizes a fork of a block others already finalized.
         if let Some(locked) = &self.locked_block {
             if block.header.parent_hash != *locked {
-                let parent_slot = self
-                    .block_slots
-                    .get(&block.header.parent_hash)
-                    .copied()
-                    .unwrap_or(0);
+                let Some(parent_slot) = self.certified_parent_slot(block)? else {
+                    bail!(
+                        "block does not extend locked block and its parent has no QC \
+                         (safe node predicate failed)"
+                    );
+                };
                 if parent_slot < self.locked_slot {
                     bail!(
                         "block does not extend locked block and parent slot {} < locked slot {} \
@@ -934,6 +1687,27 @@ impl ConsensusEngine for HybridConsensus {
             }
         }
 
+        // Fast-finality lock: the block this validator last voted for may
+        // have been finalized in one round by votes it never saw. A branch
+        // that leaves it is only safe from a parent certified at or above
+        // its slot.
+        if let Some((voted_slot, voted_block)) = self.fast_vote_lock() {
+            if block.header.slot > voted_slot
+                && !self.descends_from(block.header.parent_hash, voted_block, voted_slot)
+                && !matches!(
+                    self.certified_parent_slot(block)?,
+                    Some(parent_slot) if parent_slot >= voted_slot
+                )
+            {
+                bail!(
+                    "block does not extend block {:?} voted for at slot {} \
+                     (fast finality lock)",
+                    voted_block,
+                    voted_slot
+                );
+            }
+        }
+
         Ok(())
     }
 
@@ -950,8 +1724,8 @@ impl ConsensusEngine for HybridConsensus {
         self.check_my_eligibility(slot)
     }
 
-    fn update_epoch_randomness(&mut self, vrf_output: &[u8; 32]) -> bool {
-        HybridConsensus::update_epoch_randomness(self, vrf_output)
+    fn update_epoch_randomness(&mut self, slot: Slot, vrf_output: &[u8; 32]) -> bool {
+        HybridConsensus::update_epoch_randomness(self, slot, vrf_output)
     }
 
     fn validator_stake(&self, address: &Address) -> u128 {
@@ -1006,6 +1780,60 @@ impl ConsensusEngine for HybridConsensus {
             .map(|(addr, v)| (*addr, v.stake))
             .collect()
     }
+
+    fn stage_validator_set(&mut self, next: EpochInfo) -> Result<()> {
+        HybridConsensus::stage_validator_set(self, next)
+    }
+
+    fn finality_certificates(&self, from_slot: Slot) -> Vec<FinalityCertificate> {
+        self.checkpoints(from_slot)
+    }
+
+    fn finality_path(&self, slot: Slot) -> Option<FinalityPath> {
+        HybridConsensus::finality_path(self, slot)
+    }
+
+    /// Only this node's own upcoming slots: other validators' eligibility
+    /// stays secret until they propose.
+    fn upcoming_leaders(&self, n: usize) -> Vec<(Slot, PublicKey)> {
+        let Some(me) = self
+            .my_address
+            .and_then(|addr| self.epoch_validators.get(&addr))
+        else {
+            return Vec::new();
+        };
+        self.my_leader_slots
+            .iter()
+            .take(n)
+            .map(|&slot| (slot, me.pubkey.clone()))
+            .collect()
+    }
+
+    fn validator_set_commitment(&self, epoch: Epoch) -> Option<H256> {
+        HybridConsensus::validator_set_commitment(self, epoch)
+    }
+
+    fn record_qc(&mut self, qc: &AggregatedVote) -> Result<()> {
+        HybridConsensus::record_qc(self, qc)
+    }
+
+    fn round_sync(&self) -> Result<Option<RoundSync>> {
+        HybridConsensus::round_sync(self)
+    }
+
+    fn on_round_sync(&mut self, sync: &RoundSync) -> Result<Option<Slot>> {
+        HybridConsensus::on_round_sync(self, sync)
+    }
+
+    fn validator_set(&self, epoch: Epoch) -> Option<EpochInfo> {
+        if epoch == self.current_epoch {
+            return Some(self.epoch_info());
+        }
+        self.previous_epoch_set
+            .as_ref()
+            .filter(|set| set.epoch == epoch)
+            .cloned()
+    }
 }
 
 #[cfg(test)]
@@ -1013,7 +1841,7 @@ mod tests {
     use super::*;
     use crate::Finality;
     use aether_crypto_primitives::Keypair;
-    use aether_types::BlockHeader;
+    use aether_types::{BlockHeader, ErasureParams};
 
     fn create_test_validator(stake: u128) -> ValidatorInfo {
         let keypair = Keypair::generate();
@@ -1243,42 +2071,38 @@ mod tests {
     #[test]
     fn test_epoch_randomness_update() {
         let v1 = create_test_validator(1000);
-        let mut consensus = HybridConsensus::new(vec![v1], 0.8, 100, None, None, None);
-
-        let initial_randomness = consensus.epoch_randomness;
+        let mut consensus = HybridConsensus::new(vec![v1], 0.8, 99, None, None, None);
 
-        // First update should succeed
-        let vrf_output = [42u8; 32];
-        assert!(consensus.update_epoch_randomness(&vrf_output));
+        let initial_randomness = consensus.epoch_randomness.current();
 
-        assert_ne!(
-            consensus.epoch_randomness, initial_randomness,
-            "Randomness should change after VRF update"
-        );
+        // A finalized block's VRF output feeds the next epoch's seed, not
+        // the one leaders of this epoch are already elected with.
+        assert!(consensus.update_epoch_randomness(3, &[42u8; 32]));
+        assert_eq!(consensus.epoch_randomness.current(), initial_randomness);
 
-        // Second update in same epoch should be rejected (idempotent guard)
-        let randomness_after_first = consensus.epoch_randomness;
-        assert!(!consensus.update_epoch_randomness(&[99u8; 32]));
-        assert_eq!(
-            consensus.epoch_randomness, randomness_after_first,
-            "Second update in same epoch should be rejected"
-        );
+        // The same slot cannot contribute twice.
+        assert!(!consensus.update_epoch_randomness(3, &[99u8; 32]));
 
-        // Different VRF output → different randomness (fresh consensus)
-        let mut consensus2 = HybridConsensus::new(
-            vec![create_test_validator(1000)],
-            0.8,
-            100,
-            None,
-            None,
-            None,
-        );
-        assert!(consensus2.update_epoch_randomness(&[99u8; 32]));
+        // Different VRF output → different next seed (fresh consensus)
+        let mut consensus2 =
+            HybridConsensus::new(vec![create_test_validator(1000)], 0.8, 99, None, None, None);
+        assert!(consensus2.update_epoch_randomness(3, &[99u8; 32]));
+        for _ in 0..99 {
+            consensus.advance_slot();
+            consensus2.advance_slot();
+        }
 
+        assert_ne!(consensus.epoch_randomness.current(), initial_randomness);
         assert_ne!(
-            consensus.epoch_randomness, consensus2.epoch_randomness,
+            consensus.epoch_randomness.current(),
+            consensus2.epoch_randomness.current(),
             "Different VRF outputs should produce different randomness"
         );
+        assert_eq!(
+            consensus.randomness_for_epoch(0),
+            Some(initial_randomness),
+            "past seeds stay available for verifying older blocks"
+        );
     }
 
     #[test]
@@ -1314,58 +2138,99 @@ mod tests {
         assert_eq!(consensus.epoch_validators.get(&v1_addr).unwrap().stake, 500);
     }
 
+    #[test]
+    fn test_validator_set_spans_previous_epoch() {
+        let mut validators: Vec<ValidatorInfo> =
+            (0..3).map(|_| create_test_validator(1000)).collect();
+        validators[1].stake = 2000;
+        let mut consensus = HybridConsensus::new(validators, 0.8, 10, None, None, None);
+
+        let set = consensus.validator_set(0).unwrap();
+        assert_eq!(set.total_stake, 4000);
+        let addrs: Vec<Address> = set
+            .validators
+            .iter()
+            .map(|v| v.pubkey.to_address())
+            .collect();
+        assert!(addrs.windows(2).all(|w| w[0].0 < w[1].0));
+
+        // A QC over two of the three becomes a one-byte bitfield.
+        let qc = QuorumCertificate {
+            slot: 3,
+            block_hash: H256::zero(),
+            phase: Phase::Precommit,
+            total_stake: 0,
+            signers: vec![addrs[2], addrs[0]],
+            aggregated_signature: vec![0u8; 96],
+            aggregated_pubkey: vec![0u8; 48],
+        };
+        let proof = consensus.finality_proof(&qc).unwrap();
+        assert_eq!(proof.epoch, 0);
+        assert_eq!(proof.signers.as_bytes(), &[0b101]);
+        assert_eq!(proof.total_stake, set.signed_stake(&proof.signers).unwrap());
+
+        for _ in 0..10 {
+            consensus.advance_slot();
+        }
+        assert_eq!(consensus.validator_set(1).unwrap().epoch, 1);
+        let previous = consensus.validator_set(0).unwrap();
+        assert_eq!(
+            previous.signed_stake(&proof.signers).unwrap(),
+            proof.total_stake
+        );
+        for _ in 0..10 {
+            consensus.advance_slot();
+        }
+        assert!(consensus.validator_set(0).is_none());
+    }
+
     #[test]
     fn test_epoch_randomness_resets_across_epochs() {
         let v1 = create_test_validator(1000);
-        let mut consensus = HybridConsensus::new(vec![v1], 0.8, 5, None, None, None);
+        let mut consensus = HybridConsensus::new(vec![v1], 0.8, 6, None, None, None);
 
-        // Update randomness in epoch 0
-        assert!(consensus.update_epoch_randomness(&[42u8; 32]));
-        assert!(consensus.epoch_randomness_updated());
+        // Contribute in epoch 0; slot 4 is past the window and commits.
+        assert!(consensus.update_epoch_randomness(1, &[42u8; 32]));
+        assert!(!consensus.update_epoch_randomness(4, &[43u8; 32]));
+        let committed = consensus.epoch_randomness().committed().unwrap();
 
         // Advance to epoch boundary
-        for _ in 0..5 {
+        for _ in 0..6 {
             consensus.advance_slot();
         }
 
-        // Flag should be reset for new epoch
-        assert!(!consensus.epoch_randomness_updated());
-        // Should be able to update again
-        assert!(consensus.update_epoch_randomness(&[77u8; 32]));
-        assert!(consensus.epoch_randomness_updated());
+        // The committed seed is revealed and contributions start afresh.
+        assert_eq!(consensus.randomness_for_epoch(1), Some(committed));
+        assert!(consensus.epoch_randomness().committed().is_none());
+        assert!(!consensus.update_epoch_randomness(1, &[77u8; 32]));
+        assert!(consensus.update_epoch_randomness(7, &[77u8; 32]));
     }
 
     #[test]
     fn test_epoch_fallback_randomness_only_when_no_vrf() {
         let v1 = create_test_validator(1000);
 
-        // Case 1: No VRF update — deterministic fallback applies at epoch boundary
+        // Case 1: No VRF update — the seed still moves at the epoch boundary
         let mut c1 = HybridConsensus::new(vec![v1.clone()], 0.8, 5, None, None, None);
-        let r_before = c1.epoch_randomness;
+        let r_before = c1.epoch_randomness.current();
         for _ in 0..5 {
             c1.advance_slot();
         }
         assert_ne!(
-            c1.epoch_randomness, r_before,
+            c1.epoch_randomness.current(),
+            r_before,
             "fallback should change randomness"
         );
 
-        // Case 2: VRF update applied — fallback should NOT override at boundary
+        // Case 2: VRF update applied — the boundary reveals the VRF-derived seed
         let mut c2 = HybridConsensus::new(vec![v1], 0.8, 5, None, None, None);
-        c2.update_epoch_randomness(&[42u8; 32]);
-        let r_after_vrf = c2.epoch_randomness;
+        c2.update_epoch_randomness(1, &[42u8; 32]);
         for _ in 0..5 {
             c2.advance_slot();
         }
-        // The VRF-derived randomness should have been preserved through the boundary
-        // (fallback skipped because epoch_randomness_updated was true).
-        assert_eq!(
-            c2.epoch_randomness, r_after_vrf,
-            "VRF randomness should be preserved — fallback must not override"
-        );
-        // And it should differ from the fallback-only path
         assert_ne!(
-            c1.epoch_randomness, c2.epoch_randomness,
+            c1.epoch_randomness.current(),
+            c2.epoch_randomness.current(),
             "VRF-seeded and fallback-seeded epochs should diverge"
         );
     }
@@ -1779,7 +2644,7 @@ mod tests {
         parent_hash: H256,
     ) -> Block {
         let mut input = Vec::new();
-        input.extend_from_slice(consensus.epoch_randomness.as_bytes());
+        input.extend_from_slice(consensus.epoch_randomness.current().as_bytes());
         input.extend_from_slice(&slot.to_le_bytes());
         let proof = vrf_kp.prove(&input);
         Block {
@@ -1799,6 +2664,7 @@ mod tests {
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_secs(),
+                erasure: ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: None,
@@ -1806,6 +2672,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_upcoming_leaders_track_own_eligibility_within_epoch() {
+        let vrf_kp = VrfKeypair::generate();
+        let me = create_test_validator(1000);
+        let addr = me.pubkey.to_address();
+        let mut consensus = HybridConsensus::new(
+            vec![me.clone(), create_test_validator(1000)],
+            0.5,
+            20,
+            Some(vrf_kp),
+            None,
+            Some(addr),
+        );
+        let expected = |c: &HybridConsensus, slots: std::ops::RangeInclusive<Slot>| {
+            slots
+                .filter(|&slot| c.check_my_eligibility(slot).is_some())
+                .collect::<Vec<_>>()
+        };
+        let upcoming = |c: &HybridConsensus| {
+            c.upcoming_leaders(100)
+                .into_iter()
+                .map(|(slot, leader)| {
+                    assert_eq!(leader, me.pubkey);
+                    slot
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // Only the rest of the epoch whose seed is known.
+        assert_eq!(upcoming(&consensus), expected(&consensus, 1..=19));
+        for _ in 0..7 {
+            consensus.advance_slot();
+        }
+        assert_eq!(upcoming(&consensus), expected(&consensus, 8..=19));
+        assert!(consensus.upcoming_leaders(1).len() <= 1);
+
+        // A new seed replaces the lookahead wholesale.
+        for _ in 0..13 {
+            consensus.advance_slot();
+        }
+        assert_eq!(consensus.current_epoch, 1);
+        assert_eq!(upcoming(&consensus), expected(&consensus, 21..=39));
+        assert!(consensus.leader_schedule(1).is_empty());
+    }
+
     #[test]
     fn test_validate_block_no_lock_accepts_any_parent() {
         let (mut consensus, vrf_kp, proposer) = create_single_validator_consensus();
@@ -1861,6 +2772,238 @@ mod tests {
         );
     }
 
+    fn certify(consensus: &mut HybridConsensus, block_hash: H256, slot: Slot) {
+        consensus.qcs.insert(
+            (slot, Phase::Propose, block_hash),
+            QuorumCertificate {
+                slot,
+                block_hash,
+                phase: Phase::Propose,
+                total_stake: 0,
+                signers: vec![],
+                aggregated_signature: vec![],
+                aggregated_pubkey: vec![],
+            },
+        );
+    }
+
+    #[test]
+    fn test_validate_block_safe_unlock_rejects_uncertified_parent() {
+        let (mut consensus, vrf_kp, proposer) = create_single_validator_consensus();
+        let locked_hash = H256::from_slice(&[0xBB; 32]).unwrap();
+        consensus.locked_block = Some(locked_hash);
+        consensus.locked_slot = 3;
+        consensus.current_slot = 10;
+
+        // A sibling of some certified block: high enough slot, but no QC.
+        // Voting for its child would let a byzantine leader route around
+        // the lock.
+        let sibling = H256::from_slice(&[0xDD; 32]).unwrap();
+        consensus.block_slots.insert(sibling, 5);
+
+        let block = make_valid_block(&consensus, &vrf_kp, proposer, 7, sibling);
+        let err = consensus.validate_block(&block).unwrap_err().to_string();
+        assert!(err.contains("has no QC"), "{err}");
+    }
+
+    #[test]
+    fn test_record_qc_locks_on_carried_qc() {
+        let keyed: Vec<(ValidatorInfo, BlsKeypair)> = (0..3)
+            .map(|_| create_test_validator_with_bls(1_000))
+            .collect();
+        let mut consensus = HybridConsensus::new(
+            keyed.iter().map(|(v, _)| v.clone()).collect(),
+            0.8,
+            100,
+            None,
+            None,
+            None,
+        );
+        for (v, kp) in &keyed {
+            consensus
+                .register_bls_pubkey(
+                    v.pubkey.to_address(),
+                    kp.public_key(),
+                    &kp.proof_of_possession(),
+                )
+                .unwrap();
+        }
+        let block_hash = H256::from_slice(&[0x22; 32]).unwrap();
+        let mut msg = block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&3u64.to_le_bytes());
+        let signers: Vec<Address> = keyed.iter().map(|(v, _)| v.pubkey.to_address()).collect();
+        let signatures: Vec<Vec<u8>> = keyed.iter().map(|(_, kp)| kp.sign(&msg)).collect();
+        let epoch = consensus.epoch_info();
+        let mut qc = AggregatedVote {
+            slot: 3,
+            block_hash,
+            aggregated_signature: aggregate_signatures(&signatures).unwrap(),
+            epoch: epoch.epoch,
+            signers: epoch.signer_bitfield(&signers).unwrap(),
+            total_stake: 3_000,
+        };
+
+        // A QC for another slot does not verify.
+        qc.slot = 4;
+        assert!(consensus.record_qc(&qc).is_err());
+        assert_eq!(consensus.locked_block, None);
+
+        qc.slot = 3;
+        consensus.record_qc(&qc).unwrap();
+        assert_eq!(consensus.locked_block, Some(block_hash));
+        assert_eq!(consensus.locked_slot, 3);
+        assert!(consensus.qcs.contains_key(&(3, Phase::Propose, block_hash)));
+    }
+
+    /// A QC for `block_hash` at `slot` signed by `signers`, as a block
+    /// carries it for its parent.
+    fn carried_qc(
+        consensus: &HybridConsensus,
+        signers: &[(Address, &BlsKeypair)],
+        block_hash: H256,
+        slot: Slot,
+    ) -> AggregatedVote {
+        let mut msg = block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&slot.to_le_bytes());
+        let signatures: Vec<Vec<u8>> = signers.iter().map(|(_, kp)| kp.sign(&msg)).collect();
+        let addresses: Vec<Address> = signers.iter().map(|(addr, _)| *addr).collect();
+        let epoch = consensus.epoch_info();
+        AggregatedVote {
+            slot,
+            block_hash,
+            aggregated_signature: aggregate_signatures(&signatures).unwrap(),
+            epoch: epoch.epoch,
+            signers: epoch.signer_bitfield(&addresses).unwrap(),
+            total_stake: epoch.total_stake,
+        }
+    }
+
+    /// The single-validator fixture with its BLS key registered.
+    fn create_single_validator_with_bls() -> (HybridConsensus, VrfKeypair, Address, BlsKeypair) {
+        let (mut consensus, vrf_kp, addr) = create_single_validator_consensus();
+        let bls = BlsKeypair::generate();
+        consensus
+            .register_bls_pubkey(addr, bls.public_key(), &bls.proof_of_possession())
+            .unwrap();
+        (consensus, vrf_kp, addr, bls)
+    }
+
+    #[test]
+    fn test_safe_node_accepts_parent_certified_by_carried_qc() {
+        let (mut consensus, vrf_kp, proposer, bls) = create_single_validator_with_bls();
+        consensus.locked_block = Some(H256::from_slice(&[0xBB; 32]).unwrap());
+        consensus.locked_slot = 3;
+        consensus.current_slot = 10;
+
+        // We never saw the parent's votes; the block carries its QC.
+        let parent = H256::from_slice(&[0xDD; 32]).unwrap();
+        let mut block = make_valid_block(&consensus, &vrf_kp, proposer, 7, parent);
+        block.aggregated_vote = Some(carried_qc(&consensus, &[(proposer, &bls)], parent, 5));
+        consensus.validate_block(&block).unwrap();
+
+        // A QC for some other block does not certify the parent.
+        let other = H256::from_slice(&[0xEE; 32]).unwrap();
+        block.aggregated_vote = Some(carried_qc(&consensus, &[(proposer, &bls)], other, 5));
+        let err = consensus.validate_block(&block).unwrap_err().to_string();
+        assert!(err.contains("has no QC"), "{err}");
+
+        // Nor does a QC the validator set did not sign.
+        let forger = BlsKeypair::generate();
+        block.aggregated_vote = Some(carried_qc(&consensus, &[(proposer, &forger)], parent, 5));
+        assert!(consensus.validate_block(&block).is_err());
+
+        // A genuine QC below the lock still does not unlock.
+        block.aggregated_vote = Some(carried_qc(&consensus, &[(proposer, &bls)], parent, 2));
+        let err = consensus.validate_block(&block).unwrap_err().to_string();
+        assert!(err.contains("parent slot 2 < locked slot 3"), "{err}");
+    }
+
+    #[test]
+    fn test_verify_aggregated_vote_needs_quorum_of_known_epoch() {
+        let keyed: Vec<(ValidatorInfo, BlsKeypair)> = (0..3)
+            .map(|_| create_test_validator_with_bls(1_000))
+            .collect();
+        let mut consensus = HybridConsensus::new(
+            keyed.iter().map(|(v, _)| v.clone()).collect(),
+            0.8,
+            100,
+            None,
+            None,
+            None,
+        );
+        let signers: Vec<(Address, &BlsKeypair)> = keyed
+            .iter()
+            .map(|(v, kp)| {
+                let addr = v.pubkey.to_address();
+                consensus
+                    .register_bls_pubkey(addr, kp.public_key(), &kp.proof_of_possession())
+                    .unwrap();
+                (addr, kp)
+            })
+            .collect();
+        let block_hash = H256::from_slice(&[0x33; 32]).unwrap();
+
+        // One of three equal stakes is not a quorum.
+        let qc = carried_qc(&consensus, &signers[..1], block_hash, 2);
+        let err = consensus
+            .verify_aggregated_vote(&qc)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("< 2/3"), "{err}");
+
+        let mut qc = carried_qc(&consensus, &signers[..2], block_hash, 2);
+        let (addresses, stake) = consensus.verify_aggregated_vote(&qc).unwrap();
+        assert_eq!(
+            addresses.into_iter().collect::<HashSet<_>>(),
+            HashSet::from([signers[0].0, signers[1].0])
+        );
+        assert_eq!(stake, 2_000);
+
+        qc.epoch = 7;
+        let err = consensus
+            .verify_aggregated_vote(&qc)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown epoch"), "{err}");
+    }
+
+    #[test]
+    fn test_carried_qc_lock_is_enforced_and_never_lowered() {
+        let (mut consensus, vrf_kp, proposer, bls) = create_single_validator_with_bls();
+        consensus.current_slot = 10;
+        let locked = H256::from_slice(&[0x44; 32]).unwrap();
+        consensus
+            .record_qc(&carried_qc(&consensus, &[(proposer, &bls)], locked, 4))
+            .unwrap();
+        assert_eq!(
+            (consensus.locked_block, consensus.locked_slot),
+            (Some(locked), 4)
+        );
+
+        // Having learned the QC from a block, we vote as if we had seen its
+        // votes: a branch from a parent certified below it is refused...
+        let stale = H256::from_slice(&[0x55; 32]).unwrap();
+        consensus.block_slots.insert(stale, 2);
+        certify(&mut consensus, stale, 2);
+        let block = make_valid_block(&consensus, &vrf_kp, proposer, 6, stale);
+        let err = consensus.validate_block(&block).unwrap_err().to_string();
+        assert!(err.contains("safe node predicate"), "{err}");
+        // ...while extending the locked block is fine.
+        let block = make_valid_block(&consensus, &vrf_kp, proposer, 6, locked);
+        consensus.validate_block(&block).unwrap();
+
+        // An older carried QC is recorded but does not move the lock back.
+        let older = H256::from_slice(&[0x66; 32]).unwrap();
+        consensus
+            .record_qc(&carried_qc(&consensus, &[(proposer, &bls)], older, 3))
+            .unwrap();
+        assert!(consensus.qcs.contains_key(&(3, Phase::Propose, older)));
+        assert_eq!(
+            (consensus.locked_block, consensus.locked_slot),
+            (Some(locked), 4)
+        );
+    }
+
     #[test]
     fn test_validate_block_safe_unlock_accepts_higher_parent_slot() {
         let (mut consensus, vrf_kp, proposer) = create_single_validator_consensus();
@@ -1873,6 +3016,7 @@ mod tests {
         // This justifies unlocking — the network has moved past our lock.
         let other_parent = H256::from_slice(&[0xDD; 32]).unwrap();
         consensus.block_slots.insert(other_parent, 5);
+        certify(&mut consensus, other_parent, 5);
 
         let block = make_valid_block(&consensus, &vrf_kp, proposer, 7, other_parent);
         assert!(
@@ -1892,6 +3036,7 @@ mod tests {
         // Parent is at slot 5, EQUAL to locked_slot — should accept (>= threshold)
         let other_parent = H256::from_slice(&[0xEE; 32]).unwrap();
         consensus.block_slots.insert(other_parent, 5);
+        certify(&mut consensus, other_parent, 5);
 
         let block = make_valid_block(&consensus, &vrf_kp, proposer, 7, other_parent);
         assert!(
@@ -2104,6 +3249,209 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_two_chain_finality_needs_consecutive_slots() {
+        let keyed: Vec<(ValidatorInfo, BlsKeypair)> = (0..3)
+            .map(|_| create_test_validator_with_bls(1000))
+            .collect();
+        let mut consensus = HybridConsensus::new(
+            keyed.iter().map(|(v, _)| v.clone()).collect(),
+            0.8,
+            100,
+            None,
+            None,
+            None,
+        );
+        let certify_at = |consensus: &mut HybridConsensus, block: H256, parent: H256| {
+            let slot = consensus.current_slot;
+            consensus.block_parents.insert(block, parent);
+            consensus.block_slots.insert(block, slot);
+            let mut qc = None;
+            for (v, kp) in &keyed[..2] {
+                let vote = make_signed_vote(consensus, v, kp, block, slot);
+                qc = consensus.process_vote(vote).unwrap();
+            }
+            assert!(qc.is_some(), "QC should form for slot {slot}");
+        };
+
+        // block_a certified at slot 1; slot 2 is skipped.
+        let block_a = H256::from_slice(&[0xA1; 32]).unwrap();
+        consensus.current_slot = 1;
+        certify_at(&mut consensus, block_a, H256::zero());
+        consensus.advance_slot();
+        consensus.advance_slot();
+
+        // Its child, certified at slot 3, is two slots on: a block certified
+        // at slot 2 on another branch could still be extended by validators
+        // locked below block_a, so block_a is not final.
+        let block_b = H256::from_slice(&[0xB3; 32]).unwrap();
+        certify_at(&mut consensus, block_b, block_a);
+        assert_eq!(consensus.finalized_slot, 0);
+        assert_eq!(consensus.locked_block, Some(block_b));
+
+        // A certified child in the very next slot finalizes block_b.
+        consensus.advance_slot();
+        let block_c = H256::from_slice(&[0xC4; 32]).unwrap();
+        certify_at(&mut consensus, block_c, block_b);
+        assert_eq!(consensus.finalized_slot, 3);
+    }
+
+    /// Three validators in epoch 0; staking replaces v3 with v4 for epoch 1.
+    fn hot_swap_fixture() -> (HybridConsensus, Vec<(ValidatorInfo, BlsKeypair)>) {
+        let keys: Vec<(ValidatorInfo, BlsKeypair)> = (0..4)
+            .map(|_| create_test_validator_with_bls(1000))
+            .collect();
+        let genesis = keys[..3].iter().map(|(v, _)| v.clone()).collect();
+        let mut consensus = HybridConsensus::new(genesis, 0.8, 10, None, None, None);
+        let next = EpochInfo {
+            epoch: 1,
+            start_slot: 10,
+            end_slot: 19,
+            randomness: H256::zero(),
+            validators: vec![keys[0].0.clone(), keys[1].0.clone(), keys[3].0.clone()],
+            total_stake: 0,
+        };
+        assert!(consensus
+            .stage_validator_set(EpochInfo {
+                epoch: 2,
+                ..next.clone()
+            })
+            .is_err());
+        assert!(consensus
+            .stage_validator_set(EpochInfo {
+                start_slot: 12,
+                ..next.clone()
+            })
+            .is_err());
+        consensus.stage_validator_set(next).unwrap();
+        (consensus, keys)
+    }
+
+    #[test]
+    fn test_staged_validator_set_activates_at_epoch_boundary() {
+        let (mut consensus, keys) = hot_swap_fixture();
+        let outgoing = keys[2].0.pubkey.to_address();
+        let incoming = keys[3].0.pubkey.to_address();
+
+        for _ in 0..9 {
+            consensus.advance_slot();
+        }
+        assert!(consensus.validators.contains_key(&outgoing));
+        assert!(!consensus.validators.contains_key(&incoming));
+
+        consensus.advance_slot();
+        assert_eq!(consensus.current_epoch, 1);
+        let set = consensus.validator_set(1).unwrap();
+        assert_eq!(set.total_stake, 3000);
+        let members: Vec<Address> = set
+            .validators
+            .iter()
+            .map(|v| v.pubkey.to_address())
+            .collect();
+        assert!(members.contains(&incoming) && !members.contains(&outgoing));
+        // The outgoing set stays queryable for proofs over its last slots.
+        assert!(consensus
+            .validator_set(0)
+            .unwrap()
+            .validators
+            .iter()
+            .any(|v| v.pubkey.to_address() == outgoing));
+
+        // The new member votes in the new epoch; the departed one cannot.
+        let block = H256::from([0x10; 32]);
+        let (v3, bls3) = &keys[2];
+        let (v4, bls4) = &keys[3];
+        let vote = make_signed_vote(&mut consensus, v4, bls4, block, 10);
+        assert!(consensus.process_vote(vote).unwrap().is_none());
+        let vote = make_signed_vote(&mut consensus, v3, bls3, block, 10);
+        assert!(consensus.process_vote(vote).is_err());
+    }
+
+    #[test]
+    fn test_outgoing_set_votes_count_during_transition_window() {
+        let (mut consensus, keys) = hot_swap_fixture();
+        let block_8 = H256::from([0x08; 32]);
+        let block_9 = H256::from([0x09; 32]);
+        consensus.block_parents.insert(block_9, block_8);
+        consensus.block_slots.insert(block_8, 8);
+        consensus.block_slots.insert(block_9, 9);
+        for _ in 0..8 {
+            consensus.advance_slot();
+        }
+        for (v, bls) in &keys[..2] {
+            let vote = make_signed_vote(&mut consensus, v, bls, block_8, 8);
+            consensus.process_vote(vote).unwrap();
+        }
+        consensus.advance_slot();
+        consensus.advance_slot();
+        assert_eq!(consensus.current_epoch, 1);
+
+        // Slot 9's votes arrive after the swap, and v3 has left the set:
+        // they are still weighed against epoch 0's stake.
+        let (v3, bls3) = &keys[2];
+        let (v4, bls4) = &keys[3];
+        let late = make_signed_vote(&mut consensus, v4, bls4, block_9, 9);
+        assert!(consensus.process_vote(late).is_err());
+        let late = make_signed_vote(&mut consensus, v3, bls3, block_9, 9);
+        assert!(consensus.process_vote(late.clone()).unwrap().is_none());
+        assert!(consensus.process_vote(late).unwrap().is_none());
+        let (v1, bls1) = &keys[0];
+        let late = make_signed_vote(&mut consensus, v1, bls1, block_9, 9);
+        let qc = consensus.process_vote(late).unwrap().expect("late QC");
+        assert_eq!((qc.slot, qc.phase), (9, Phase::Propose));
+        assert_eq!(qc.signers.len(), 2);
+        // Slot 8 had a QC, so its child's late QC finalizes it.
+        assert_eq!(consensus.finalized_slot, 8);
+
+        // Once the window closes, late votes are refused.
+        for _ in 0..TRANSITION_WINDOW_SLOTS {
+            consensus.advance_slot();
+        }
+        let (v2, bls2) = &keys[1];
+        let late = make_signed_vote(&mut consensus, v2, bls2, block_9, 9);
+        assert!(consensus.process_vote(late).is_err());
+        assert!(consensus.transition_votes.is_empty());
+    }
+
+    #[test]
+    fn test_finality_certificates_verify_against_set_commitment() {
+        let keys: Vec<(ValidatorInfo, BlsKeypair)> = (0..3)
+            .map(|_| create_test_validator_with_bls(1000))
+            .collect();
+        let set = keys.iter().map(|(v, _)| v.clone()).collect();
+        let mut consensus = HybridConsensus::new(set, 0.8, 100, None, None, None);
+        consensus.set_checkpoint_interval(4);
+
+        // A chain of blocks, each certified by two of three validators.
+        for slot in 1..=10u64 {
+            consensus.advance_slot();
+            let block = H256::from([slot as u8; 32]);
+            let parent = H256::from([slot as u8 - 1; 32]);
+            consensus.record_block(block, parent, slot);
+            for (vi, bls) in &keys[..2] {
+                let vote = make_signed_vote(&mut consensus, vi, bls, block, slot);
+                consensus.process_vote(vote).unwrap();
+            }
+        }
+        consensus.advance_slot();
+        assert_eq!(consensus.finalized_slot, 9);
+
+        // One certificate per four-slot window finality entered.
+        let certs = consensus.finality_certificates(0);
+        let slots: Vec<Slot> = certs.iter().map(|cert| cert.slot).collect();
+        assert_eq!(slots, vec![4, 8]);
+        assert_eq!(consensus.finality_certificates(5).len(), 1);
+
+        let root = consensus.validator_set_commitment(0).unwrap();
+        let mut client = aether_light_client::CheckpointVerifier::new(0, root);
+        for cert in &certs {
+            assert_eq!(cert.block_hash, H256::from([cert.slot as u8; 32]));
+            assert_eq!(cert.signers.count(), 2);
+            client.verify(cert).unwrap();
+        }
+        assert_eq!(client.finalized_slot(), 8);
+    }
+
     #[test]
     fn test_two_chain_finality_no_parent_qc() {
         // If the parent block does NOT have a QC, finality must NOT advance.
@@ -2418,4 +3766,314 @@ mod tests {
         let results = consensus.batch_process_votes(vec![]).unwrap();
         assert!(results.is_empty());
     }
+
+    /// Equal-stake validators with every BLS key registered, run by the
+    /// first of them.
+    fn create_round_sync_network(n: usize) -> (HybridConsensus, Vec<(ValidatorInfo, BlsKeypair)>) {
+        let keyed: Vec<(ValidatorInfo, BlsKeypair)> = (0..n)
+            .map(|_| create_test_validator_with_bls(1_000))
+            .collect();
+        let mut consensus = HybridConsensus::new(
+            keyed.iter().map(|(v, _)| v.clone()).collect(),
+            0.8,
+            100,
+            None,
+            Some(keyed[0].1.clone()),
+            Some(keyed[0].0.pubkey.to_address()),
+        );
+        for (v, kp) in &keyed {
+            consensus
+                .register_bls_pubkey(
+                    v.pubkey.to_address(),
+                    kp.public_key(),
+                    &kp.proof_of_possession(),
+                )
+                .unwrap();
+        }
+        (consensus, keyed)
+    }
+
+    fn make_round_sync(vi: &ValidatorInfo, bls_kp: &BlsKeypair, slot: Slot) -> RoundSync {
+        RoundSync {
+            slot,
+            high_qc: None,
+            validator: vi.pubkey.clone(),
+            signature: aether_types::Signature::from_bytes(
+                bls_kp.sign(&RoundSync::signing_message(slot)),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_round_sync_waits_for_a_third_of_stake() {
+        let (mut consensus, keyed) = create_round_sync_network(4);
+        consensus.skip_to_slot(2);
+
+        // One validator of four could be byzantine: no jump.
+        let (v1, kp1) = &keyed[1];
+        assert_eq!(
+            consensus
+                .on_round_sync(&make_round_sync(v1, kp1, 20))
+                .unwrap(),
+            None
+        );
+        assert_eq!(consensus.current_slot(), 2);
+
+        // Two of four hold more than a third; the lower of their slots is
+        // one an honest validator reached.
+        let (v2, kp2) = &keyed[2];
+        assert_eq!(
+            consensus
+                .on_round_sync(&make_round_sync(v2, kp2, 15))
+                .unwrap(),
+            Some(15)
+        );
+        assert_eq!(consensus.current_slot(), 15);
+
+        // Reports behind the current slot change nothing.
+        let (v3, kp3) = &keyed[3];
+        assert_eq!(
+            consensus
+                .on_round_sync(&make_round_sync(v3, kp3, 4))
+                .unwrap(),
+            None
+        );
+        assert_eq!(consensus.current_slot(), 15);
+    }
+
+    #[test]
+    fn test_round_sync_catch_up_clears_backoff() {
+        let (mut consensus, keyed) = create_round_sync_network(4);
+        consensus.set_pacemaker_config(PacemakerConfig {
+            base_timeout: std::time::Duration::from_millis(100),
+            ..PacemakerConfig::default()
+        });
+        for _ in 0..3 {
+            consensus.on_timeout();
+        }
+        assert_eq!(
+            consensus.pacemaker.current_timeout(),
+            std::time::Duration::from_millis(800)
+        );
+
+        for (v, kp) in &keyed[1..3] {
+            consensus
+                .on_round_sync(&make_round_sync(v, kp, 10))
+                .unwrap();
+        }
+        assert_eq!(consensus.current_slot(), 10);
+        assert_eq!(
+            consensus.pacemaker.current_timeout(),
+            std::time::Duration::from_millis(100)
+        );
+    }
+
+    #[test]
+    fn test_round_sync_rejects_forged_or_unknown_sender() {
+        let (mut consensus, keyed) = create_round_sync_network(4);
+        let (v1, kp1) = &keyed[1];
+
+        let mut forged = make_round_sync(v1, kp1, 5);
+        forged.slot = 50;
+        let err = consensus.on_round_sync(&forged).unwrap_err();
+        assert!(err.to_string().contains("invalid BLS signature"), "{err}");
+
+        let (outsider, outsider_kp) = create_test_validator_with_bls(1_000);
+        let err = consensus
+            .on_round_sync(&make_round_sync(&outsider, &outsider_kp, 5))
+            .unwrap_err();
+        assert!(err.to_string().contains("unknown validator"), "{err}");
+        assert!(consensus.round_reports.is_empty());
+    }
+
+    #[test]
+    fn test_round_sync_carries_highest_qc() {
+        let (mut leader, keyed) = create_round_sync_network(3);
+        leader.advance_slot();
+        let block_hash = H256::from_slice(&[0x44; 32]).unwrap();
+        let mut formed = None;
+        for (v, kp) in &keyed {
+            let vote = make_signed_vote(&mut leader, v, kp, block_hash, 1);
+            formed = formed.or(leader.process_vote(vote).unwrap());
+        }
+        assert!(formed.is_some());
+
+        let sync = leader.round_sync().unwrap().unwrap();
+        assert_eq!(sync.slot, 1);
+        assert_eq!(
+            sync.high_qc.as_ref().map(|qc| qc.block_hash),
+            Some(block_hash)
+        );
+
+        // A validator that missed the votes learns the QC and locks on it.
+        let mut laggard = HybridConsensus::new(
+            keyed.iter().map(|(v, _)| v.clone()).collect(),
+            0.8,
+            100,
+            None,
+            None,
+            None,
+        );
+        for (v, kp) in &keyed {
+            laggard
+                .register_bls_pubkey(
+                    v.pubkey.to_address(),
+                    kp.public_key(),
+                    &kp.proof_of_possession(),
+                )
+                .unwrap();
+        }
+        laggard.on_round_sync(&sync).unwrap();
+        assert_eq!(laggard.locked_block, Some(block_hash));
+        assert!(laggard.round_sync().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fast_finality_needs_over_ninety_percent() {
+        let (mut consensus, keyed) = create_round_sync_network(10);
+        consensus.set_fast_finality(true);
+        consensus.current_slot = 1;
+        let block_hash = H256::from_slice(&[0xF1; 32]).unwrap();
+        consensus.record_block(block_hash, H256::zero(), 1);
+
+        for (i, (vi, kp)) in keyed.iter().enumerate() {
+            let vote = make_signed_vote(&mut consensus, vi, kp, block_hash, 1);
+            let qc = consensus.process_vote(vote).unwrap();
+            // The QC forms at seven of ten; finality waits for all ten,
+            // including the votes that land after the QC.
+            assert_eq!(qc.is_some(), i == 6, "vote {i}");
+            assert_eq!(consensus.finalized_slot, if i == 9 { 1 } else { 0 });
+        }
+        assert_eq!(consensus.finality_path(1), Some(FinalityPath::Fast));
+        assert_eq!(consensus.my_last_vote, Some((1, block_hash)));
+    }
+
+    #[test]
+    fn test_fast_finality_off_falls_back_to_two_chain() {
+        let (mut consensus, keyed) = create_round_sync_network(4);
+        let parent = H256::from_slice(&[0xF2; 32]).unwrap();
+        let child = H256::from_slice(&[0xF3; 32]).unwrap();
+        consensus.record_block(parent, H256::zero(), 1);
+        consensus.record_block(child, parent, 2);
+
+        for (slot, block_hash) in [(1, parent), (2, child)] {
+            consensus.skip_to_slot(slot);
+            for (vi, kp) in &keyed {
+                let vote = make_signed_vote(&mut consensus, vi, kp, block_hash, slot);
+                consensus.process_vote(vote).unwrap();
+            }
+            if slot == 1 {
+                assert_eq!(consensus.finalized_slot, 0, "unanimous, but fast path off");
+                assert_eq!(consensus.finality_path(1), None);
+            }
+        }
+        assert_eq!(consensus.finalized_slot, 1);
+        assert_eq!(consensus.finality_path(1), Some(FinalityPath::TwoChain));
+    }
+
+    #[test]
+    fn test_single_validator_reports_configured_finality_path() {
+        for fast in [false, true] {
+            let (mut consensus, keyed) = create_round_sync_network(1);
+            consensus.set_fast_finality(fast);
+            consensus.current_slot = 1;
+            let block_hash = H256::from_slice(&[0xF4; 32]).unwrap();
+            consensus.record_block(block_hash, H256::zero(), 1);
+
+            let (vi, kp) = &keyed[0];
+            let vote = make_signed_vote(&mut consensus, vi, kp, block_hash, 1);
+            consensus.process_vote(vote).unwrap();
+            assert_eq!(consensus.finalized_slot, 1);
+            let expected = if fast {
+                FinalityPath::Fast
+            } else {
+                FinalityPath::TwoChain
+            };
+            assert_eq!(consensus.finality_path(1), Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_finality_path_covers_slots_up_to_each_event() {
+        let (mut consensus, _) = create_round_sync_network(4);
+        consensus.finalize(3, FinalityPath::TwoChain);
+        consensus.finalize(5, FinalityPath::Fast);
+        consensus.finalize(4, FinalityPath::Fast);
+
+        assert_eq!(consensus.finality_path(0), None);
+        for slot in 1..=3 {
+            assert_eq!(consensus.finality_path(slot), Some(FinalityPath::TwoChain));
+        }
+        for slot in 4..=5 {
+            assert_eq!(consensus.finality_path(slot), Some(FinalityPath::Fast));
+        }
+        assert_eq!(consensus.finality_path(6), None);
+
+        for slot in 6..(6 + MAX_FINALITY_PATHS as Slot) {
+            consensus.finalize(slot, FinalityPath::Fast);
+        }
+        assert_eq!(consensus.finality_path(3), None, "pruned");
+        assert_eq!(consensus.finality_path(6), Some(FinalityPath::Fast));
+    }
+
+    #[test]
+    fn test_fast_vote_lock_holds_off_forks_of_last_vote() {
+        let (mut consensus, vrf_kp, proposer) = create_single_validator_consensus();
+        consensus.set_fast_finality(true);
+        let locked = H256::from_slice(&[0xA1; 32]).unwrap();
+        let voted = H256::from_slice(&[0xA2; 32]).unwrap();
+        consensus.locked_block = Some(locked);
+        consensus.locked_slot = 3;
+        consensus.record_block(voted, locked, 5);
+        consensus.my_last_vote = Some((5, voted));
+        consensus.current_slot = 10;
+
+        // A fork off the locked block passes the regular lock but may undo
+        // a block finalized in one round.
+        let fork_parent = H256::from_slice(&[0xA3; 32]).unwrap();
+        consensus.record_block(fork_parent, locked, 4);
+        certify(&mut consensus, fork_parent, 4);
+        let fork = make_valid_block(&consensus, &vrf_kp, proposer, 7, fork_parent);
+        let err = consensus.validate_block(&fork).unwrap_err().to_string();
+        assert!(err.contains("fast finality lock"), "{err}");
+
+        // Building on the voted block is fine.
+        let child = H256::from_slice(&[0xA4; 32]).unwrap();
+        consensus.record_block(child, voted, 6);
+        certify(&mut consensus, child, 6);
+        let extension = make_valid_block(&consensus, &vrf_kp, proposer, 7, child);
+        consensus.validate_block(&extension).unwrap();
+
+        // A QC at the voted slot hands safety back to the regular lock.
+        consensus.locked_block = Some(voted);
+        consensus.locked_slot = 5;
+        assert_eq!(consensus.fast_vote_lock(), None);
+
+        // With the fast path off there is no extra lock.
+        consensus.locked_block = Some(locked);
+        consensus.locked_slot = 3;
+        consensus.set_fast_finality(false);
+        consensus.validate_block(&fork).unwrap();
+    }
+
+    #[test]
+    fn test_fast_vote_lock_released_when_outvoted() {
+        let (mut consensus, keyed) = create_round_sync_network(4);
+        consensus.set_fast_finality(true);
+        let voted = H256::from_slice(&[0xB1; 32]).unwrap();
+        let other = H256::from_slice(&[0xB2; 32]).unwrap();
+        consensus.my_last_vote = Some((5, voted));
+        let addr = |i: usize| keyed[i].0.pubkey.to_address();
+        consensus.vote_record.insert((5, addr(0)), voted);
+
+        // One of four voting elsewhere could be a byzantine validator that
+        // also voted for our block: it may still have reached 100%.
+        consensus.vote_record.insert((5, addr(1)), other);
+        assert_eq!(consensus.fast_vote_lock(), Some((5, voted)));
+
+        // Two of four: at least one is honest, so our block stayed at or
+        // below 75%.
+        consensus.vote_record.insert((5, addr(2)), other);
+        assert_eq!(consensus.fast_vote_lock(), None);
+    }
 }
@@ -0,0 +1,271 @@
+// ============================================================================
+// KES SCHEDULE - Slot to period mapping and automatic key evolution
+// ============================================================================
+// KES periods are fixed by genesis: period p covers slots
+// [p * kes_slots_per_period, (p + 1) * kes_slots_per_period), counted from
+// the genesis slot, so every node and every verifier agrees on the period of
+// any slot without asking the signer.
+//
+// `KesScheduler` owns the local hot key and is driven by the slot clock:
+//
+//   - `on_slot` evolves the key as soon as a boundary is crossed, whether or
+//     not the validator leads anything in the new period, so the previous
+//     period's key is gone from memory (and from disk when a `KesKeyStore`
+//     is attached) at the boundary.
+//   - Once the key is within `warning_periods` of its last period the
+//     `aether_kes_expiry_alert` gauge goes to 1 and a warning is logged on
+//     each evolution; operators must register a new key before then.
+//   - A key past its last period is exhausted: it signs nothing and every
+//     refusal is counted, instead of signing blocks no verifier will accept.
+// ============================================================================
+
+use aether_crypto_kes::{KesKey, KesKeyStore, KesSignature, KesVerificationKey};
+use aether_metrics::KES_METRICS;
+use aether_types::{ChainConfig, Slot};
+use anyhow::{bail, Result};
+
+/// Periods of warning before exhaustion when none is configured.
+pub const DEFAULT_WARNING_PERIODS: u32 = 7;
+
+/// The genesis KES period layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KesSchedule {
+    slots_per_period: u64,
+    epoch_slots: u64,
+}
+
+impl KesSchedule {
+    pub fn new(slots_per_period: u64, epoch_slots: u64) -> Self {
+        KesSchedule {
+            slots_per_period: slots_per_period.max(1),
+            epoch_slots: epoch_slots.max(1),
+        }
+    }
+
+    pub fn from_config(config: &ChainConfig) -> Self {
+        Self::new(
+            config.consensus.kes_slots_per_period,
+            config.chain.epoch_slots,
+        )
+    }
+
+    pub fn slots_per_period(&self) -> u64 {
+        self.slots_per_period
+    }
+
+    pub fn period_for_slot(&self, slot: Slot) -> u32 {
+        aether_crypto_kes::period_for_slot(slot, self.slots_per_period)
+    }
+
+    /// Period of an epoch's first slot.
+    pub fn period_for_epoch(&self, epoch: u64) -> u32 {
+        self.period_for_slot(epoch.saturating_mul(self.epoch_slots))
+    }
+
+    pub fn first_slot_of_period(&self, period: u32) -> Slot {
+        u64::from(period).saturating_mul(self.slots_per_period)
+    }
+}
+
+/// The local KES key, evolved on the genesis schedule.
+pub struct KesScheduler {
+    schedule: KesSchedule,
+    key: KesKey,
+    store: Option<KesKeyStore>,
+    warning_periods: u32,
+    exhausted: bool,
+}
+
+impl KesScheduler {
+    pub fn new(schedule: KesSchedule, key: KesKey) -> Self {
+        let scheduler = KesScheduler {
+            schedule,
+            key,
+            store: None,
+            warning_periods: DEFAULT_WARNING_PERIODS,
+            exhausted: false,
+        };
+        scheduler.record_metrics();
+        scheduler
+    }
+
+    /// Persist every evolution to `store` before the evolved key is used.
+    pub fn with_store(mut self, store: KesKeyStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Raise the expiry alert once `periods` or fewer remain.
+    pub fn with_warning_periods(mut self, periods: u32) -> Self {
+        self.warning_periods = periods;
+        self.record_metrics();
+        self
+    }
+
+    pub fn schedule(&self) -> &KesSchedule {
+        &self.schedule
+    }
+
+    pub fn verification_key(&self) -> KesVerificationKey {
+        self.key.verification_key()
+    }
+
+    pub fn current_period(&self) -> u32 {
+        self.key.current_period()
+    }
+
+    /// Evolutions left before the key is exhausted.
+    pub fn periods_remaining(&self) -> u32 {
+        if self.exhausted {
+            return 0;
+        }
+        self.key.max_periods() - 1 - self.key.current_period()
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    pub fn expiry_alert(&self) -> bool {
+        self.exhausted || self.periods_remaining() <= self.warning_periods
+    }
+
+    /// Advance to the period of `slot`, evolving across any boundary.
+    /// Call on every slot tick.
+    pub fn on_slot(&mut self, slot: Slot) -> Result<()> {
+        let period = self.schedule.period_for_slot(slot);
+        if period <= self.key.current_period() || self.exhausted {
+            return Ok(());
+        }
+        if period >= self.key.max_periods() {
+            tracing::error!(
+                slot,
+                max_periods = self.key.max_periods(),
+                "KES key exhausted; register a new key to keep producing blocks"
+            );
+            self.exhausted = true;
+            self.record_metrics();
+            return Ok(());
+        }
+
+        match &self.store {
+            Some(store) => store.evolve_to(&mut self.key, period)?,
+            None => self.key.evolve_to(period)?,
+        }
+        KES_METRICS.evolutions.inc();
+        if self.expiry_alert() {
+            tracing::warn!(
+                period,
+                periods_remaining = self.periods_remaining(),
+                "KES key close to exhaustion; register a new key"
+            );
+        }
+        self.record_metrics();
+        Ok(())
+    }
+
+    /// Sign `message` for `slot`, evolving first if a boundary was crossed.
+    pub fn sign(&mut self, slot: Slot, message: &[u8]) -> Result<KesSignature> {
+        self.on_slot(slot)?;
+        if self.exhausted {
+            KES_METRICS.exhausted_refusals.inc();
+            bail!("KES key is exhausted; refusing to sign for slot {slot}");
+        }
+        let period = self.schedule.period_for_slot(slot);
+        Ok(self.key.sign(period, message)?)
+    }
+
+    fn record_metrics(&self) {
+        KES_METRICS
+            .current_period
+            .set(i64::from(self.key.current_period()));
+        KES_METRICS
+            .periods_remaining
+            .set(i64::from(self.periods_remaining()));
+        KES_METRICS.expiry_alert.set(i64::from(self.expiry_alert()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_kes::PassphraseSealer;
+
+    fn scheduler(max_periods: u32) -> KesScheduler {
+        KesScheduler::new(
+            KesSchedule::new(10, 40),
+            KesKey::from_seed([3u8; 32], max_periods),
+        )
+        .with_warning_periods(2)
+    }
+
+    #[test]
+    fn schedule_follows_genesis_config() {
+        let config = ChainConfig::devnet();
+        let schedule = KesSchedule::from_config(&config);
+        assert_eq!(schedule.slots_per_period(), 172_800);
+        assert_eq!(schedule.period_for_slot(172_799), 0);
+        assert_eq!(schedule.period_for_slot(172_800), 1);
+        // Four 43_200-slot epochs per period.
+        assert_eq!(schedule.period_for_epoch(3), 0);
+        assert_eq!(schedule.period_for_epoch(4), 1);
+        assert_eq!(schedule.first_slot_of_period(2), 345_600);
+    }
+
+    #[test]
+    fn evolves_at_boundaries() {
+        let mut scheduler = scheduler(8);
+        let vk = scheduler.verification_key();
+
+        scheduler.on_slot(9).unwrap();
+        assert_eq!(scheduler.current_period(), 0);
+        scheduler.on_slot(10).unwrap();
+        assert_eq!(scheduler.current_period(), 1);
+        // Skipped periods are crossed in one step.
+        scheduler.on_slot(35).unwrap();
+        assert_eq!(scheduler.current_period(), 3);
+        assert_eq!(scheduler.periods_remaining(), 4);
+        assert!(!scheduler.expiry_alert());
+
+        let sig = scheduler.sign(47, b"block").unwrap();
+        assert_eq!(sig.period, 4);
+        assert!(sig.verify(&vk, b"block"));
+        // The past is gone.
+        assert!(scheduler.sign(35, b"old block").is_err());
+    }
+
+    #[test]
+    fn alerts_then_refuses_when_exhausted() {
+        let mut scheduler = scheduler(4);
+        scheduler.on_slot(10).unwrap();
+        assert_eq!(scheduler.periods_remaining(), 2);
+        assert!(scheduler.expiry_alert());
+
+        assert!(scheduler.sign(39, b"last").is_ok());
+        assert_eq!(scheduler.periods_remaining(), 0);
+        assert!(!scheduler.is_exhausted());
+
+        scheduler.on_slot(40).unwrap();
+        assert!(scheduler.is_exhausted());
+        assert_eq!(scheduler.current_period(), 3);
+        let err = scheduler.sign(40, b"too late").unwrap_err();
+        assert!(err.to_string().contains("exhausted"), "{err}");
+    }
+
+    #[test]
+    fn evolutions_are_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = || {
+            KesKeyStore::new(
+                dir.path().join("kes.key"),
+                Box::new(PassphraseSealer::with_iterations(b"pass", 1_000)),
+            )
+        };
+        let key = KesKey::from_seed([3u8; 32], 8);
+        store().save(&key).unwrap();
+
+        let mut scheduler = KesScheduler::new(KesSchedule::new(10, 40), key).with_store(store());
+        scheduler.on_slot(52).unwrap();
+        assert_eq!(store().stored_period().unwrap(), Some(5));
+    }
+}
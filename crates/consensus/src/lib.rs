@@ -2,10 +2,22 @@
 // AETHER CONSENSUS - Full consensus implementation
 // ============================================================================
 // PURPOSE: Provides multiple consensus engines:
-// - SimpleConsensus: Round-robin for testing
+// - SimpleConsensus: Round-robin for testing, with a public leader schedule
 // - VRF-PoS: VRF-based leader election
-// - HotStuff: BFT consensus with BLS aggregation
-// - HybridConsensus: VRF + HotStuff + BLS (full Phase 1 integration)
+// - HotStuff: BFT consensus with BLS aggregation, 2-chain or pipelined
+//   3-chain with new-view view changes
+// - ForkChoice: engine-agnostic head selection (latest finalized block, then
+//   heaviest-attested subtree) that reports reorgs
+// - EvidencePool: bounded record of signed votes/headers that turns
+//   equivocations into slash evidence for blocks and watchtowers
+// - RecordingEngine / replay: trace every consensus input to JSON lines and
+//   replay it into any engine to debug finality stalls
+// - HybridConsensus: VRF + HotStuff + BLS (full Phase 1 integration), with
+//   staked validator sets hot-swapped at epoch boundaries and a finality
+//   certificate every N slots for light clients and bridges; optional
+//   one-round finality when more than 90% of stake votes for a block
+// - Pacemaker: round timeouts with configurable exponential backoff; hybrid
+//   validators exchange round syncs to recover from partitions and skew
 // ============================================================================
 
 use aether_crypto_vrf::VrfProof;
@@ -51,7 +63,7 @@ pub trait ConsensusEngine: Finality + Send + Sync {
         None
     }
 
-    fn update_epoch_randomness(&mut self, _vrf_output: &[u8; 32]) -> bool {
+    fn update_epoch_randomness(&mut self, _slot: Slot, _vrf_output: &[u8; 32]) -> bool {
         false
     }
 
@@ -87,6 +99,74 @@ pub trait ConsensusEngine: Finality + Send + Sync {
     fn validator_addresses_and_stakes(&self) -> Vec<(aether_types::Address, u128)> {
         Vec::new()
     }
+
+    /// Queue the validator set staking elected for `next.epoch`. It takes
+    /// effect at `next.start_slot` without a restart; late votes for the
+    /// outgoing epoch are still weighed against the outgoing set.
+    fn stage_validator_set(&mut self, next: aether_types::EpochInfo) -> Result<()> {
+        anyhow::bail!(
+            "engine cannot change its validator set (epoch {})",
+            next.epoch
+        )
+    }
+
+    /// Validator set of `epoch`, in the order aggregate signer bitfields
+    /// index it. `None` if the engine no longer (or never) knew that epoch.
+    fn validator_set(&self, _epoch: aether_types::Epoch) -> Option<aether_types::EpochInfo> {
+        None
+    }
+
+    /// Learn the QC a block carries for its parent. Call it before voting on
+    /// the block, so a validator that missed the parent's votes still locks
+    /// on it.
+    fn record_qc(&mut self, _qc: &aether_types::AggregatedVote) -> Result<()> {
+        Ok(())
+    }
+
+    /// This validator's slot and highest QC, signed, to broadcast when the
+    /// pacemaker times out. `None` if the node is not a validator or the
+    /// engine does not synchronize rounds.
+    fn round_sync(&self) -> Result<Option<aether_types::RoundSync>> {
+        Ok(None)
+    }
+
+    /// Take in a peer's round sync. Returns the slot the engine skipped
+    /// ahead to, if enough stake had moved past it.
+    fn on_round_sync(&mut self, _sync: &aether_types::RoundSync) -> Result<Option<Slot>> {
+        Ok(None)
+    }
+
+    /// Whether `slot` was finalized in one round or by the two-chain rule.
+    /// `None` if it is not final or the engine does not track it.
+    fn finality_path(&self, _slot: Slot) -> Option<aether_types::FinalityPath> {
+        None
+    }
+
+    /// Finality certificates emitted for slots from `from_slot` on, oldest
+    /// first. Light clients check them against a validator-set commitment.
+    fn finality_certificates(&self, _from_slot: Slot) -> Vec<aether_types::FinalityCertificate> {
+        Vec::new()
+    }
+
+    /// Commitment to `epoch`'s validator set that its finality certificates
+    /// prove their signers against.
+    fn validator_set_commitment(&self, _epoch: aether_types::Epoch) -> Option<H256> {
+        None
+    }
+
+    /// Leaders of `epoch` in the order they take its slots: slot `s` is led
+    /// by `schedule[(s - start_slot) % schedule.len()]`. Empty when leaders
+    /// are not public ahead of time, as with a private VRF lottery, or the
+    /// epoch is unknown.
+    fn leader_schedule(&self, _epoch: aether_types::Epoch) -> Vec<PublicKey> {
+        Vec::new()
+    }
+
+    /// Known leaders of up to `n` slots after the current one, soonest
+    /// first, so the node can connect to them before they propose.
+    fn upcoming_leaders(&self, _n: usize) -> Vec<(Slot, PublicKey)> {
+        Vec::new()
+    }
 }
 
 /// Trivial finality for testing: every slot is immediately final.
@@ -144,16 +224,29 @@ pub fn has_quorum(voted_stake: u128, total_stake: u128) -> bool {
     }
 }
 
+pub mod evidence_pool;
+pub mod fork_choice;
 pub mod hotstuff;
 pub mod hybrid;
+pub mod kes_schedule;
 pub mod pacemaker;
+pub mod randomness;
+pub mod replay;
 pub mod simple;
 pub mod slashing;
 pub mod vrf_pos;
 
-pub use hotstuff::{ConsensusAction, HotStuffConsensus, TimeoutCertificate, TimeoutVote};
+pub use evidence_pool::{Evidence, EvidenceEntry, EvidencePool};
+pub use fork_choice::{ForkChoice, Reorg};
+pub use hotstuff::{
+    ConsensusAction, HotStuffConsensus, NewView, PipelineQc, Proposal, TimeoutCertificate,
+    TimeoutVote,
+};
 pub use hybrid::HybridConsensus;
-pub use pacemaker::Pacemaker;
+pub use kes_schedule::{KesSchedule, KesScheduler};
+pub use pacemaker::{Pacemaker, PacemakerConfig};
+pub use randomness::EpochRandomness;
+pub use replay::{replay, ConsensusInput, RecordingEngine, ReplayReport};
 pub use simple::SimpleConsensus;
 pub use slashing::SlashingDetector;
 pub use vrf_pos::VrfPosConsensus;
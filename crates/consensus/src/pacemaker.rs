@@ -1,17 +1,49 @@
 use std::time::{Duration, Instant};
 
+use aether_types::ConsensusParams;
+
+/// Timeouts a [`Pacemaker`] backs off between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PacemakerConfig {
+    /// Timeout of a round after a commit.
+    pub base_timeout: Duration,
+    /// Ceiling the timeout backs off to.
+    pub max_timeout: Duration,
+    /// Most times the timeout doubles before it stops growing, whatever
+    /// `max_timeout` allows.
+    pub max_backoff_exponent: u32,
+}
+
+impl Default for PacemakerConfig {
+    fn default() -> Self {
+        PacemakerConfig {
+            base_timeout: Duration::from_millis(500),
+            max_timeout: Duration::from_secs(30),
+            max_backoff_exponent: 5,
+        }
+    }
+}
+
+impl From<&ConsensusParams> for PacemakerConfig {
+    fn from(params: &ConsensusParams) -> Self {
+        let base_timeout = Duration::from_millis(params.round_timeout_ms.max(1));
+        PacemakerConfig {
+            base_timeout,
+            max_timeout: Duration::from_millis(params.max_round_timeout_ms).max(base_timeout),
+            ..PacemakerConfig::default()
+        }
+    }
+}
+
 /// Pacemaker drives round progression with exponential backoff timeouts.
 ///
 /// When the current round's leader doesn't produce a block or enough votes
 /// aren't collected, the pacemaker fires a timeout. Validators then
 /// participate in a view-change protocol to elect a new leader.
 pub struct Pacemaker {
-    /// Base timeout for a round.
-    base_timeout: Duration,
+    config: PacemakerConfig,
     /// Current timeout (increases on consecutive failures).
     current_timeout: Duration,
-    /// Maximum timeout cap.
-    max_timeout: Duration,
     /// When the current round started.
     round_start: Instant,
     /// Current round number (advances on timeout or successful commit).
@@ -22,16 +54,26 @@ pub struct Pacemaker {
 
 impl Pacemaker {
     pub fn new(base_timeout: Duration) -> Self {
-        Pacemaker {
+        Self::with_config(PacemakerConfig {
             base_timeout,
-            current_timeout: base_timeout,
-            max_timeout: Duration::from_secs(30),
+            ..PacemakerConfig::default()
+        })
+    }
+
+    pub fn with_config(config: PacemakerConfig) -> Self {
+        Pacemaker {
+            config,
+            current_timeout: config.base_timeout,
             round_start: Instant::now(),
             current_round: 0,
             consecutive_timeouts: 0,
         }
     }
 
+    pub fn config(&self) -> PacemakerConfig {
+        self.config
+    }
+
     /// Check if the current round has timed out.
     pub fn is_timed_out(&self) -> bool {
         self.round_start.elapsed() >= self.current_timeout
@@ -49,10 +91,15 @@ impl Pacemaker {
     pub fn on_timeout(&mut self) {
         self.consecutive_timeouts = self.consecutive_timeouts.saturating_add(1);
         self.current_round = self.current_round.saturating_add(1);
-        self.current_timeout = std::cmp::min(
-            self.base_timeout * 2u32.pow(self.consecutive_timeouts.min(5)),
-            self.max_timeout,
-        );
+        let exponent = self
+            .consecutive_timeouts
+            .min(self.config.max_backoff_exponent)
+            .min(31);
+        self.current_timeout = self
+            .config
+            .base_timeout
+            .saturating_mul(1u32 << exponent)
+            .min(self.config.max_timeout);
         self.round_start = Instant::now();
     }
 
@@ -61,7 +108,7 @@ impl Pacemaker {
     pub fn on_commit(&mut self) {
         self.consecutive_timeouts = 0;
         self.current_round = self.current_round.saturating_add(1);
-        self.current_timeout = self.base_timeout;
+        self.current_timeout = self.config.base_timeout;
         self.round_start = Instant::now();
     }
 
@@ -105,7 +152,7 @@ impl Pacemaker {
         if steps > 1 {
             // We caught up via a TC/sync — not a local stall, so reset backoff.
             self.consecutive_timeouts = 0;
-            self.current_timeout = self.base_timeout;
+            self.current_timeout = self.config.base_timeout;
         }
         self.round_start = Instant::now();
     }
@@ -159,6 +206,42 @@ mod tests {
         assert_eq!(pm.consecutive_timeouts, 0);
     }
 
+    #[test]
+    fn test_config_caps_backoff() {
+        let mut pm = Pacemaker::with_config(PacemakerConfig {
+            base_timeout: Duration::from_millis(100),
+            max_timeout: Duration::from_millis(700),
+            max_backoff_exponent: 2,
+        });
+        pm.on_timeout();
+        assert_eq!(pm.current_timeout(), Duration::from_millis(200));
+        pm.on_timeout();
+        assert_eq!(pm.current_timeout(), Duration::from_millis(400));
+        // The exponent stops at 2 even though 800ms would be capped anyway.
+        pm.on_timeout();
+        assert_eq!(pm.current_timeout(), Duration::from_millis(400));
+
+        let mut pm = Pacemaker::with_config(PacemakerConfig {
+            max_backoff_exponent: 10,
+            ..pm.config()
+        });
+        for _ in 0..10 {
+            pm.on_timeout();
+        }
+        assert_eq!(pm.current_timeout(), Duration::from_millis(700));
+    }
+
+    #[test]
+    fn test_config_from_chain_params() {
+        let mut params = aether_types::ChainConfig::devnet().consensus;
+        params.round_timeout_ms = 2_000;
+        params.max_round_timeout_ms = 1_000;
+        let config = PacemakerConfig::from(&params);
+        assert_eq!(config.base_timeout, Duration::from_secs(2));
+        // A ceiling below the base timeout is raised to it.
+        assert_eq!(config.max_timeout, Duration::from_secs(2));
+    }
+
     #[test]
     fn test_leader_rotation() {
         let pm = Pacemaker::new(Duration::from_millis(500));
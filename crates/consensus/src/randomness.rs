@@ -0,0 +1,265 @@
+// ============================================================================
+// EPOCH RANDOMNESS - VRF output accumulation with a commit-reveal guard
+// ============================================================================
+// Leader election for epoch e+1 keys off seed(e+1), which is built from the
+// VRF outputs of epoch e's finalized blocks:
+//
+//   acc_0     = H("aether-epoch-acc"  || seed(e) || e)
+//   acc_i     = H("aether-epoch-acc"  || acc_{i-1} || slot_i || vrf_output_i)
+//   seed(e+1) = H("aether-epoch-seed" || seed(e) || acc_n || n || e+1)
+//
+// Every VRF output is unique per (key, input), so a leader cannot choose its
+// contribution; the only lever is withholding its block. The guard limits
+// what that buys:
+//
+//   - Contribution window: only blocks in the first 2/3 of the epoch count.
+//     The leaders at the end of an epoch, who know the most about how the
+//     seed will turn out, have no say in it.
+//   - Commit: the first finalized block at or past the window closes the
+//     accumulator and fixes seed(e+1). A withheld block released later is
+//     ignored, as is any block that arrives after a later slot was mixed in.
+//   - Reveal: seed(e+1) takes effect only at the epoch boundary, a third of
+//     an epoch after it was fixed.
+//
+// What remains is one bit per withheld in-window block, paid for with that
+// block's reward. An epoch with no contributions still gets a fresh seed
+// from the chain. The window leaves a third of an epoch for finality to
+// catch up; if finality stalls past the boundary, the seed is committed
+// from whatever was finalized by then.
+// ============================================================================
+
+use aether_types::{Slot, H256};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+const ACC_DOMAIN: &[u8] = b"aether-epoch-acc";
+const SEED_DOMAIN: &[u8] = b"aether-epoch-seed";
+
+/// Fraction of each epoch, from its start, whose blocks contribute.
+const WINDOW_NUMERATOR: u64 = 2;
+const WINDOW_DENOMINATOR: u64 = 3;
+
+/// Revealed seeds kept for verifying blocks from recent epochs.
+pub const SEED_HISTORY: usize = 16;
+
+#[derive(Debug, Clone)]
+pub struct EpochRandomness {
+    epoch_length: u64,
+    epoch: u64,
+    seeds: BTreeMap<u64, H256>,
+    accumulator: H256,
+    contributions: u64,
+    last_slot: Option<Slot>,
+    /// seed(epoch + 1), once the contribution window has closed.
+    committed: Option<H256>,
+}
+
+impl EpochRandomness {
+    pub fn new(genesis_seed: H256, epoch_length: u64) -> Self {
+        let mut seeds = BTreeMap::new();
+        seeds.insert(0, genesis_seed);
+        EpochRandomness {
+            epoch_length: epoch_length.max(1),
+            epoch: 0,
+            seeds,
+            accumulator: initial_accumulator(&genesis_seed, 0),
+            contributions: 0,
+            last_slot: None,
+            committed: None,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Seed of the current epoch.
+    pub fn current(&self) -> H256 {
+        self.seeds[&self.epoch]
+    }
+
+    /// Seed of epoch `epoch`, if it has been revealed and is still within
+    /// `SEED_HISTORY`.
+    pub fn randomness_for_epoch(&self, epoch: u64) -> Option<H256> {
+        self.seeds.get(&epoch).copied()
+    }
+
+    /// The next epoch's seed, once committed. Not used for election until
+    /// the boundary reveals it.
+    pub fn committed(&self) -> Option<H256> {
+        self.committed
+    }
+
+    /// First slot of the current epoch whose block no longer contributes.
+    pub fn window_end(&self) -> Slot {
+        let window = (self.epoch_length * WINDOW_NUMERATOR / WINDOW_DENOMINATOR).max(1);
+        self.epoch_start().saturating_add(window)
+    }
+
+    /// VRF outputs mixed in this epoch so far.
+    pub fn contributions(&self) -> u64 {
+        self.contributions
+    }
+
+    /// Mix in the VRF output of the finalized block at `slot`. Returns false
+    /// if it was ignored: outside this epoch, past the contribution window,
+    /// or not after the last contributed slot. A block past the window
+    /// commits the next seed.
+    pub fn contribute(&mut self, slot: Slot, vrf_output: &[u8; 32]) -> bool {
+        if slot < self.epoch_start() || slot >= self.next_epoch_start() {
+            return false;
+        }
+        if slot >= self.window_end() {
+            self.commit();
+            return false;
+        }
+        if self.committed.is_some() || self.last_slot.is_some_and(|last| slot <= last) {
+            return false;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(ACC_DOMAIN);
+        hasher.update(self.accumulator.as_bytes());
+        hasher.update(slot.to_le_bytes());
+        hasher.update(vrf_output);
+        self.accumulator = H256::from(<[u8; 32]>::from(hasher.finalize()));
+        self.contributions += 1;
+        self.last_slot = Some(slot);
+        true
+    }
+
+    /// Advance the slot clock to `slot`, revealing the seed of every epoch
+    /// boundary crossed.
+    pub fn advance_to(&mut self, slot: Slot) {
+        while slot >= self.next_epoch_start() {
+            let seed = self.commit();
+            self.epoch += 1;
+            self.seeds.insert(self.epoch, seed);
+            while self.seeds.len() > SEED_HISTORY {
+                self.seeds.pop_first();
+            }
+            self.accumulator = initial_accumulator(&seed, self.epoch);
+            self.contributions = 0;
+            self.last_slot = None;
+            self.committed = None;
+        }
+    }
+
+    /// Close the contribution window and fix the next seed (idempotent).
+    fn commit(&mut self) -> H256 {
+        if let Some(seed) = self.committed {
+            return seed;
+        }
+        let mut hasher = Sha256::new();
+        hasher.update(SEED_DOMAIN);
+        hasher.update(self.current().as_bytes());
+        hasher.update(self.accumulator.as_bytes());
+        hasher.update(self.contributions.to_le_bytes());
+        hasher.update((self.epoch + 1).to_le_bytes());
+        let seed = H256::from(<[u8; 32]>::from(hasher.finalize()));
+        self.committed = Some(seed);
+        seed
+    }
+
+    fn epoch_start(&self) -> Slot {
+        self.epoch.saturating_mul(self.epoch_length)
+    }
+
+    fn next_epoch_start(&self) -> Slot {
+        (self.epoch + 1).saturating_mul(self.epoch_length)
+    }
+}
+
+fn initial_accumulator(seed: &H256, epoch: u64) -> H256 {
+    let mut hasher = Sha256::new();
+    hasher.update(ACC_DOMAIN);
+    hasher.update(seed.as_bytes());
+    hasher.update(epoch.to_le_bytes());
+    H256::from(<[u8; 32]>::from(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Finalize blocks at `slots` with distinct outputs, then cross into the
+    /// next epoch.
+    fn run_epoch(randomness: &mut EpochRandomness, slots: &[Slot]) -> H256 {
+        for &slot in slots {
+            randomness.advance_to(slot);
+            randomness.contribute(slot, &[slot as u8; 32]);
+        }
+        randomness.advance_to(randomness.next_epoch_start());
+        randomness.current()
+    }
+
+    #[test]
+    fn contributions_shape_the_next_seed_only() {
+        let mut randomness = EpochRandomness::new(H256::zero(), 9);
+        assert_eq!(randomness.window_end(), 6);
+
+        assert!(randomness.contribute(1, &[1u8; 32]));
+        assert_eq!(randomness.current(), H256::zero());
+        // Replays and out-of-order blocks are not mixed in.
+        assert!(!randomness.contribute(1, &[2u8; 32]));
+        assert!(!randomness.contribute(0, &[2u8; 32]));
+        assert!(randomness.contribute(4, &[4u8; 32]));
+        assert_eq!(randomness.contributions(), 2);
+
+        randomness.advance_to(9);
+        assert_eq!(randomness.epoch(), 1);
+        assert_ne!(randomness.current(), H256::zero());
+        assert_eq!(randomness.randomness_for_epoch(0), Some(H256::zero()));
+        assert_eq!(
+            randomness.randomness_for_epoch(1),
+            Some(randomness.current())
+        );
+        assert_eq!(randomness.randomness_for_epoch(2), None);
+
+        // Same blocks, same seed; different blocks, different seed.
+        let a = run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[1, 4]);
+        let b = run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[1, 4]);
+        let c = run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[1]);
+        let empty = run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[]);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(empty, H256::zero());
+        assert_ne!(empty, c);
+    }
+
+    #[test]
+    fn tail_and_withheld_blocks_cannot_move_the_seed() {
+        let honest = run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[1, 3, 7]);
+
+        // Blocks after the window (here slot 8) do not count.
+        let tail = run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[1, 3, 8]);
+        assert_eq!(honest, tail);
+
+        let mut randomness = EpochRandomness::new(H256::zero(), 9);
+        randomness.contribute(1, &[1u8; 32]);
+        // The first block past the window commits the seed...
+        assert!(!randomness.contribute(6, &[6u8; 32]));
+        let committed = randomness.committed().unwrap();
+        // ...so a withheld in-window block released afterwards is ignored.
+        assert!(!randomness.contribute(3, &[3u8; 32]));
+        randomness.advance_to(9);
+        assert_eq!(randomness.current(), committed);
+        assert_eq!(
+            committed,
+            run_epoch(&mut EpochRandomness::new(H256::zero(), 9), &[1])
+        );
+    }
+
+    #[test]
+    fn history_is_bounded_and_skips_reveal_every_epoch() {
+        let mut randomness = EpochRandomness::new(H256::zero(), 4);
+        randomness.advance_to(4 * (SEED_HISTORY as u64 + 3));
+        assert_eq!(randomness.epoch(), SEED_HISTORY as u64 + 3);
+        assert!(randomness.randomness_for_epoch(0).is_none());
+        let seeds: Vec<H256> = (4..=SEED_HISTORY as u64 + 3)
+            .map(|e| randomness.randomness_for_epoch(e).unwrap())
+            .collect();
+        assert_eq!(seeds.len(), SEED_HISTORY);
+        let distinct: std::collections::HashSet<_> = seeds.iter().collect();
+        assert_eq!(distinct.len(), SEED_HISTORY);
+    }
+}
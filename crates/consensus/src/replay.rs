@@ -0,0 +1,564 @@
+use std::io::{BufRead, Write};
+use std::sync::Mutex;
+
+use aether_types::{
+    Address, AggregatedVote, Block, EpochInfo, PublicKey, RoundSync, Slot, Vote, H256,
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::{ConsensusEngine, Finality, VrfProof};
+
+/// One call that changed (or tested) a consensus engine's state.
+///
+/// Queries that only read state are not inputs, except block validation,
+/// whose verdict is worth comparing on replay.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsensusInput {
+    AdvanceSlot,
+    SkipToSlot(Slot),
+    ValidateBlock(Box<Block>),
+    RecordBlock {
+        block_hash: H256,
+        parent_hash: H256,
+        slot: Slot,
+    },
+    Vote(Vote),
+    CheckFinality(Slot),
+    /// The pacemaker's view timer fired.
+    Timeout,
+    AdvanceRound(u64),
+    EpochRandomness {
+        slot: Slot,
+        vrf_output: [u8; 32],
+    },
+    RegisterBlsKey {
+        address: Address,
+        bls_pubkey: Vec<u8>,
+        pop_signature: Vec<u8>,
+    },
+    Slash {
+        address: Address,
+        slash_bps: u128,
+    },
+    StageValidatorSet(EpochInfo),
+    RecordQc(AggregatedVote),
+    RoundSync(RoundSync),
+}
+
+/// What the engine made of an input: the call's verdict and where it left
+/// the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outcome {
+    /// `Ok` for fallible calls, the returned flag for `check_finality` and
+    /// epoch randomness, a non-zero amount for slashes.
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub current_slot: Slot,
+    pub finalized_slot: Slot,
+}
+
+/// One line of a trace file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TraceEntry {
+    pub input: ConsensusInput,
+    pub outcome: Outcome,
+}
+
+/// Feed `input` to `engine` and report the outcome.
+pub fn apply(engine: &mut dyn ConsensusEngine, input: &ConsensusInput) -> Outcome {
+    let (ok, error) = match input {
+        ConsensusInput::AdvanceSlot => {
+            engine.advance_slot();
+            (true, None)
+        }
+        ConsensusInput::SkipToSlot(slot) => {
+            engine.skip_to_slot(*slot);
+            (true, None)
+        }
+        ConsensusInput::ValidateBlock(block) => verdict(engine.validate_block(block)),
+        ConsensusInput::RecordBlock {
+            block_hash,
+            parent_hash,
+            slot,
+        } => {
+            engine.record_block(*block_hash, *parent_hash, *slot);
+            (true, None)
+        }
+        ConsensusInput::Vote(vote) => verdict(engine.add_vote(vote.clone())),
+        ConsensusInput::CheckFinality(slot) => (engine.check_finality(*slot), None),
+        ConsensusInput::Timeout => {
+            engine.on_timeout();
+            (true, None)
+        }
+        ConsensusInput::AdvanceRound(round) => {
+            engine.advance_pacemaker_to_round(*round);
+            (true, None)
+        }
+        ConsensusInput::EpochRandomness { slot, vrf_output } => {
+            (engine.update_epoch_randomness(*slot, vrf_output), None)
+        }
+        ConsensusInput::RegisterBlsKey {
+            address,
+            bls_pubkey,
+            pop_signature,
+        } => verdict(engine.register_bls_pubkey(*address, bls_pubkey.clone(), pop_signature)),
+        ConsensusInput::Slash { address, slash_bps } => {
+            (engine.slash_validator(address, *slash_bps) > 0, None)
+        }
+        ConsensusInput::StageValidatorSet(next) => {
+            verdict(engine.stage_validator_set(next.clone()))
+        }
+        ConsensusInput::RecordQc(qc) => verdict(engine.record_qc(qc)),
+        ConsensusInput::RoundSync(sync) => verdict(engine.on_round_sync(sync).map(|_| ())),
+    };
+    Outcome {
+        ok,
+        error,
+        current_slot: engine.current_slot(),
+        finalized_slot: engine.finalized_slot(),
+    }
+}
+
+fn verdict(result: Result<()>) -> (bool, Option<String>) {
+    match result {
+        Ok(()) => (true, None),
+        Err(e) => (false, Some(e.to_string())),
+    }
+}
+
+/// A consensus engine that writes every input it receives, with its
+/// outcome, to a trace of JSON lines.
+///
+/// Wraps the engine a node runs, so a devnet validator whose finality
+/// stalls leaves a trace that [`replay`] feeds into a fresh engine. If the
+/// trace cannot be written, recording stops with a warning and the engine
+/// carries on.
+pub struct RecordingEngine {
+    inner: Box<dyn ConsensusEngine>,
+    sink: Mutex<Option<Box<dyn Write + Send>>>,
+}
+
+impl RecordingEngine {
+    pub fn new(inner: Box<dyn ConsensusEngine>, sink: Box<dyn Write + Send>) -> Self {
+        RecordingEngine {
+            inner,
+            sink: Mutex::new(Some(sink)),
+        }
+    }
+
+    /// Record to a new file at `path`.
+    pub fn to_file(inner: Box<dyn ConsensusEngine>, path: &std::path::Path) -> Result<Self> {
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("failed to create trace file {}", path.display()))?;
+        Ok(Self::new(inner, Box::new(std::io::BufWriter::new(file))))
+    }
+
+    pub fn into_inner(self) -> Box<dyn ConsensusEngine> {
+        self.inner
+    }
+
+    fn write(&self, input: ConsensusInput, outcome: &Outcome) {
+        let Ok(mut sink) = self.sink.lock() else {
+            return;
+        };
+        let Some(writer) = sink.as_mut() else {
+            return;
+        };
+        let entry = TraceEntry {
+            input,
+            outcome: outcome.clone(),
+        };
+        let written = serde_json::to_writer(&mut *writer, &entry)
+            .map_err(anyhow::Error::from)
+            .and_then(|()| writer.write_all(b"\n").map_err(anyhow::Error::from))
+            .and_then(|()| writer.flush().map_err(anyhow::Error::from));
+        if let Err(e) = written {
+            tracing::warn!(err = %e, "consensus trace write failed, recording stopped");
+            *sink = None;
+        }
+    }
+
+    fn record(&mut self, input: ConsensusInput) -> Outcome {
+        let outcome = apply(self.inner.as_mut(), &input);
+        self.write(input, &outcome);
+        outcome
+    }
+
+    fn outcome(&self, ok: bool, error: Option<String>) -> Outcome {
+        Outcome {
+            ok,
+            error,
+            current_slot: self.inner.current_slot(),
+            finalized_slot: self.inner.finalized_slot(),
+        }
+    }
+}
+
+impl Finality for RecordingEngine {
+    fn check_finality(&mut self, slot: Slot) -> bool {
+        self.record(ConsensusInput::CheckFinality(slot)).ok
+    }
+
+    fn finalized_slot(&self) -> Slot {
+        self.inner.finalized_slot()
+    }
+
+    fn record_block(&mut self, block_hash: H256, parent_hash: H256, slot: Slot) {
+        self.record(ConsensusInput::RecordBlock {
+            block_hash,
+            parent_hash,
+            slot,
+        });
+    }
+}
+
+impl ConsensusEngine for RecordingEngine {
+    fn current_slot(&self) -> Slot {
+        self.inner.current_slot()
+    }
+
+    fn advance_slot(&mut self) {
+        self.record(ConsensusInput::AdvanceSlot);
+    }
+
+    fn skip_to_slot(&mut self, slot: Slot) {
+        self.record(ConsensusInput::SkipToSlot(slot));
+    }
+
+    fn is_leader(&self, slot: Slot, validator_pubkey: &PublicKey) -> bool {
+        self.inner.is_leader(slot, validator_pubkey)
+    }
+
+    fn validate_block(&self, block: &Block) -> Result<()> {
+        let result = self.inner.validate_block(block);
+        let outcome = match &result {
+            Ok(()) => self.outcome(true, None),
+            Err(e) => self.outcome(false, Some(e.to_string())),
+        };
+        self.write(
+            ConsensusInput::ValidateBlock(Box::new(block.clone())),
+            &outcome,
+        );
+        result
+    }
+
+    fn add_vote(&mut self, vote: Vote) -> Result<()> {
+        match self.record(ConsensusInput::Vote(vote)) {
+            Outcome { ok: true, .. } => Ok(()),
+            Outcome { error, .. } => Err(anyhow::anyhow!(error.unwrap_or_default())),
+        }
+    }
+
+    fn total_stake(&self) -> u128 {
+        self.inner.total_stake()
+    }
+
+    fn get_leader_proof(&self, slot: Slot) -> Option<VrfProof> {
+        self.inner.get_leader_proof(slot)
+    }
+
+    fn update_epoch_randomness(&mut self, slot: Slot, vrf_output: &[u8; 32]) -> bool {
+        self.record(ConsensusInput::EpochRandomness {
+            slot,
+            vrf_output: *vrf_output,
+        })
+        .ok
+    }
+
+    fn validator_stake(&self, address: &Address) -> u128 {
+        self.inner.validator_stake(address)
+    }
+
+    /// Read from the wall clock, so not an input: the [`ConsensusInput::Timeout`]
+    /// it leads to is.
+    fn is_timed_out(&self) -> bool {
+        self.inner.is_timed_out()
+    }
+
+    fn on_timeout(&mut self) {
+        self.record(ConsensusInput::Timeout);
+    }
+
+    fn advance_pacemaker_to_round(&mut self, round: u64) {
+        self.record(ConsensusInput::AdvanceRound(round));
+    }
+
+    fn get_bls_pubkey(&self, address: &Address) -> Option<Vec<u8>> {
+        self.inner.get_bls_pubkey(address)
+    }
+
+    fn register_bls_pubkey(
+        &mut self,
+        address: Address,
+        bls_pubkey: Vec<u8>,
+        pop_signature: &[u8],
+    ) -> Result<()> {
+        let outcome = self.record(ConsensusInput::RegisterBlsKey {
+            address,
+            bls_pubkey,
+            pop_signature: pop_signature.to_vec(),
+        });
+        match outcome.error {
+            None => Ok(()),
+            Some(error) => Err(anyhow::anyhow!(error)),
+        }
+    }
+
+    fn slash_validator(&mut self, address: &Address, slash_bps: u128) -> u128 {
+        // The amount is not part of the outcome, so slash the inner engine
+        // directly and record the call.
+        let amount = self.inner.slash_validator(address, slash_bps);
+        let outcome = self.outcome(amount > 0, None);
+        self.write(
+            ConsensusInput::Slash {
+                address: *address,
+                slash_bps,
+            },
+            &outcome,
+        );
+        amount
+    }
+
+    fn validator_addresses_and_stakes(&self) -> Vec<(Address, u128)> {
+        self.inner.validator_addresses_and_stakes()
+    }
+
+    fn stage_validator_set(&mut self, next: EpochInfo) -> Result<()> {
+        let outcome = self.record(ConsensusInput::StageValidatorSet(next));
+        match outcome.error {
+            None => Ok(()),
+            Some(error) => Err(anyhow::anyhow!(error)),
+        }
+    }
+
+    fn validator_set(&self, epoch: aether_types::Epoch) -> Option<EpochInfo> {
+        self.inner.validator_set(epoch)
+    }
+
+    fn record_qc(&mut self, qc: &AggregatedVote) -> Result<()> {
+        let outcome = self.record(ConsensusInput::RecordQc(qc.clone()));
+        match outcome.error {
+            None => Ok(()),
+            Some(error) => Err(anyhow::anyhow!(error)),
+        }
+    }
+
+    fn round_sync(&self) -> Result<Option<RoundSync>> {
+        self.inner.round_sync()
+    }
+
+    fn on_round_sync(&mut self, sync: &RoundSync) -> Result<Option<Slot>> {
+        let before = self.inner.current_slot();
+        let outcome = self.record(ConsensusInput::RoundSync(sync.clone()));
+        match outcome.error {
+            None => Ok((outcome.current_slot > before).then_some(outcome.current_slot)),
+            Some(error) => Err(anyhow::anyhow!(error)),
+        }
+    }
+
+    fn finality_certificates(&self, from_slot: Slot) -> Vec<aether_types::FinalityCertificate> {
+        self.inner.finality_certificates(from_slot)
+    }
+
+    fn finality_path(&self, slot: Slot) -> Option<aether_types::FinalityPath> {
+        self.inner.finality_path(slot)
+    }
+
+    fn validator_set_commitment(&self, epoch: aether_types::Epoch) -> Option<H256> {
+        self.inner.validator_set_commitment(epoch)
+    }
+
+    fn leader_schedule(&self, epoch: aether_types::Epoch) -> Vec<PublicKey> {
+        self.inner.leader_schedule(epoch)
+    }
+
+    fn upcoming_leaders(&self, n: usize) -> Vec<(Slot, PublicKey)> {
+        self.inner.upcoming_leaders(n)
+    }
+}
+
+/// An input whose outcome on replay differs from the recorded one.
+#[derive(Clone, Debug)]
+pub struct Divergence {
+    /// Line of the trace, from 0.
+    pub index: usize,
+    pub input: ConsensusInput,
+    pub recorded: Outcome,
+    pub replayed: Outcome,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReplayReport {
+    pub inputs: usize,
+    pub current_slot: Slot,
+    pub finalized_slot: Slot,
+    /// Input indices at which the finalized slot advanced, with the slot.
+    pub finalized_at: Vec<(usize, Slot)>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl ReplayReport {
+    /// Inputs fed since the finalized slot last advanced: how long a stall
+    /// at the end of the trace has lasted.
+    pub fn inputs_since_finality(&self) -> usize {
+        let last = self.finalized_at.last().map_or(0, |(i, _)| i + 1);
+        self.inputs - last
+    }
+}
+
+/// Feed a trace into `engine`, which must start where the recorded one did
+/// (same validators, keys and slot), and compare each outcome with the
+/// recorded one. Replay does not stop at a divergence, so the report shows
+/// everything that differed.
+///
+/// Checks against the wall clock, such as block timestamp drift, may give
+/// a different verdict than they did when the trace was recorded.
+pub fn replay(engine: &mut dyn ConsensusEngine, trace: impl BufRead) -> Result<ReplayReport> {
+    let mut report = ReplayReport {
+        current_slot: engine.current_slot(),
+        finalized_slot: engine.finalized_slot(),
+        ..ReplayReport::default()
+    };
+    for (index, line) in trace.lines().enumerate() {
+        let line = line.context("failed to read trace")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: TraceEntry = serde_json::from_str(&line)
+            .with_context(|| format!("malformed trace entry on line {}", index + 1))?;
+        let replayed = apply(engine, &entry.input);
+        if replayed.finalized_slot > report.finalized_slot {
+            report.finalized_at.push((index, replayed.finalized_slot));
+        }
+        report.inputs = index + 1;
+        report.current_slot = replayed.current_slot;
+        report.finalized_slot = replayed.finalized_slot;
+        if replayed != entry.outcome {
+            report.divergences.push(Divergence {
+                index,
+                input: entry.input,
+                recorded: entry.outcome,
+                replayed,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleConsensus;
+    use aether_crypto_primitives::Keypair;
+    use aether_types::{Signature, ValidatorInfo};
+    use std::io::Cursor;
+    use std::sync::Arc;
+
+    /// A sink the test can read back after the engine took ownership.
+    #[derive(Clone, Default)]
+    struct SharedBuf(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuf {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn make_validators() -> Vec<ValidatorInfo> {
+        (1..=3)
+            .map(|i| ValidatorInfo {
+                pubkey: PublicKey::from_bytes(Keypair::generate().public_key()),
+                stake: 1000 * i,
+                commission: 0,
+                active: true,
+            })
+            .collect()
+    }
+
+    fn vote(slot: Slot, v: &ValidatorInfo) -> Vote {
+        Vote {
+            slot,
+            block_hash: H256::from([slot as u8; 32]),
+            validator: v.pubkey.clone(),
+            signature: Signature::from_bytes(vec![]),
+            stake: v.stake,
+        }
+    }
+
+    fn run(engine: &mut dyn ConsensusEngine, validators: &[ValidatorInfo]) {
+        for slot in 1..=4 {
+            engine.advance_slot();
+            engine.record_block(H256::from([slot as u8; 32]), H256::zero(), slot);
+            // Slot 3 only hears from the smallest validator: it stalls.
+            let voters = if slot == 3 {
+                &validators[..1]
+            } else {
+                &validators[1..]
+            };
+            for v in voters {
+                engine.add_vote(vote(slot, v)).unwrap();
+            }
+            engine.check_finality(slot);
+        }
+        assert!(engine.add_vote(vote(9, &validators[0])).is_err());
+    }
+
+    #[test]
+    fn replay_reproduces_a_recorded_run() {
+        let validators = make_validators();
+        let buf = SharedBuf::default();
+        let mut recording = RecordingEngine::new(
+            Box::new(SimpleConsensus::new(validators.clone())),
+            Box::new(buf.clone()),
+        );
+        run(&mut recording, &validators);
+        assert_eq!(recording.finalized_slot(), 4);
+
+        let trace = buf.0.lock().unwrap().clone();
+        let mut fresh = SimpleConsensus::new(validators.clone());
+        let report = replay(&mut fresh, Cursor::new(&trace)).unwrap();
+        assert!(report.divergences.is_empty(), "{:?}", report.divergences);
+        // Per slot an advance, a block, its votes and a finality check; then
+        // the rejected vote.
+        assert_eq!(report.inputs, 5 + 5 + 4 + 5 + 1);
+        assert_eq!((report.current_slot, report.finalized_slot), (4, 4));
+        let slots: Vec<Slot> = report.finalized_at.iter().map(|(_, s)| *s).collect();
+        assert_eq!(slots, vec![1, 2, 4]);
+        assert_eq!(report.inputs_since_finality(), 1);
+    }
+
+    #[test]
+    fn replay_into_a_different_set_reports_divergences() {
+        let validators = make_validators();
+        let buf = SharedBuf::default();
+        let mut recording = RecordingEngine::new(
+            Box::new(SimpleConsensus::new(validators.clone())),
+            Box::new(buf.clone()),
+        );
+        run(&mut recording, &validators);
+        let trace = buf.0.lock().unwrap().clone();
+
+        // Twice the stake elsewhere: no recorded vote reaches a quorum.
+        let mut heavier = validators.clone();
+        heavier.push(ValidatorInfo {
+            stake: 12_000,
+            ..make_validators()[0].clone()
+        });
+        let mut engine = SimpleConsensus::new(heavier);
+        let report = replay(&mut engine, Cursor::new(&trace)).unwrap();
+        assert_eq!(report.finalized_slot, 0);
+        let first = &report.divergences[0];
+        assert!(matches!(first.input, ConsensusInput::CheckFinality(1)));
+        assert!(first.recorded.ok && !first.replayed.ok);
+
+        assert!(replay(&mut engine, Cursor::new("{not json")).is_err());
+    }
+}
@@ -2,7 +2,7 @@
 // Full VRF-PoS + HotStuff will be added progressively
 
 use crate::{ConsensusEngine, Finality};
-use aether_types::{Block, PublicKey, Slot, ValidatorInfo, Vote};
+use aether_types::{Block, Epoch, EpochInfo, PublicKey, Slot, ValidatorInfo, Vote, H256};
 use anyhow::{bail, Result};
 use std::collections::HashMap;
 
@@ -11,15 +11,37 @@ pub struct SimpleConsensus {
     current_slot: Slot,
     finalized_slot: Slot,
     votes: HashMap<Slot, Vec<Vote>>,
+    /// Sets that replaced the genesis one, by start slot. Votes and blocks
+    /// for a slot are judged against the set active at that slot.
+    epochs: Vec<EpochInfo>,
+    staged: Option<EpochInfo>,
+    /// Leader rotation of the active set from its first slot, rebuilt when
+    /// a staged set takes over.
+    schedule: Vec<PublicKey>,
+}
+
+/// Round-robin order of `validators` starting at `start_slot`.
+fn rotation(validators: &[ValidatorInfo], start_slot: Slot) -> Vec<PublicKey> {
+    let len = validators.len() as u64;
+    (0..len)
+        .map(|i| {
+            validators[(start_slot.wrapping_add(i) % len) as usize]
+                .pubkey
+                .clone()
+        })
+        .collect()
 }
 
 impl SimpleConsensus {
     pub fn new(validators: Vec<ValidatorInfo>) -> Self {
         SimpleConsensus {
+            schedule: rotation(&validators, 0),
             validators,
             current_slot: 0,
             finalized_slot: 0,
             votes: HashMap::new(),
+            epochs: Vec::new(),
+            staged: None,
         }
     }
 
@@ -29,16 +51,93 @@ impl SimpleConsensus {
 
     pub fn advance_slot(&mut self) {
         self.current_slot = self.current_slot.saturating_add(1);
+        if self
+            .staged
+            .as_ref()
+            .is_some_and(|next| next.start_slot <= self.current_slot)
+        {
+            if let Some(next) = self.staged.take() {
+                self.schedule = rotation(&next.validators, next.start_slot);
+                self.epochs.push(next);
+            }
+        }
+    }
+
+    /// Queue the next epoch's set; it becomes active at `next.start_slot`.
+    pub fn stage_validator_set(&mut self, next: EpochInfo) -> Result<()> {
+        let epoch = self.epochs.last().map_or(0, |e| e.epoch).saturating_add(1);
+        if next.epoch != epoch {
+            bail!("expected a set for epoch {}, got {}", epoch, next.epoch);
+        }
+        if next.start_slot <= self.current_slot {
+            bail!(
+                "epoch {} must start after the current slot {}",
+                next.epoch,
+                self.current_slot
+            );
+        }
+        if next.validators.is_empty() {
+            bail!("validator set for epoch {} is empty", next.epoch);
+        }
+        self.staged = Some(next);
+        Ok(())
+    }
+
+    /// Validators active at `slot`.
+    fn validators_at(&self, slot: Slot) -> &[ValidatorInfo] {
+        self.epochs
+            .iter()
+            .rev()
+            .find(|e| e.start_slot <= slot)
+            .map_or(&self.validators, |e| &e.validators)
+    }
+
+    fn stake_at(&self, slot: Slot) -> u128 {
+        self.validators_at(slot)
+            .iter()
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add)
     }
 
     // Simplified leader election - round-robin by stake
     pub fn get_leader(&self, slot: Slot) -> Option<&ValidatorInfo> {
-        if self.validators.is_empty() {
+        let validators = self.validators_at(slot);
+        if validators.is_empty() {
             return None;
         }
 
-        let index = (slot as usize) % self.validators.len();
-        Some(&self.validators[index])
+        let index = (slot as usize) % validators.len();
+        Some(&validators[index])
+    }
+
+    /// Leader rotation of `epoch`, including a staged one.
+    pub fn leader_schedule(&self, epoch: Epoch) -> Vec<PublicKey> {
+        if self.epochs.last().map_or(0, |e| e.epoch) == epoch {
+            return self.schedule.clone();
+        }
+        if let Some(next) = self.staged.as_ref().filter(|next| next.epoch == epoch) {
+            return rotation(&next.validators, next.start_slot);
+        }
+        match ConsensusEngine::validator_set(self, epoch) {
+            Some(info) => rotation(&info.validators, info.start_slot),
+            None => Vec::new(),
+        }
+    }
+
+    /// Leaders of the `n` slots after the current one, counting a staged
+    /// set from its start slot.
+    pub fn upcoming_leaders(&self, n: usize) -> Vec<(Slot, PublicKey)> {
+        (1..=n as u64)
+            .filter_map(|offset| {
+                let slot = self.current_slot.checked_add(offset)?;
+                let validators = match &self.staged {
+                    Some(next) if next.start_slot <= slot => &next.validators[..],
+                    _ => self.validators_at(slot),
+                };
+                let leader = validators.get((slot % validators.len().max(1) as u64) as usize)?;
+                Some((slot, leader.pubkey.clone()))
+            })
+            .collect()
     }
 
     pub fn is_leader(&self, slot: Slot, validator_pubkey: &PublicKey) -> bool {
@@ -85,16 +184,16 @@ impl SimpleConsensus {
             None => return false,
         };
 
-        // Calculate total voting stake
-        let total_stake: u128 = self
-            .validators
-            .iter()
-            .map(|v| v.stake)
-            .fold(0u128, u128::saturating_add);
+        // Weigh votes against the set that was active at `slot`, so late
+        // votes for an outgoing epoch still count after a set change.
+        let validators = self.validators_at(slot);
+        let total_stake = self.stake_at(slot);
         let mut voted_stake = 0u128;
 
         for vote in votes {
-            voted_stake = voted_stake.saturating_add(vote.stake);
+            if validators.iter().any(|v| v.pubkey == vote.validator) {
+                voted_stake = voted_stake.saturating_add(vote.stake);
+            }
         }
 
         // Check if ≥2/3 stake voted
@@ -113,10 +212,7 @@ impl SimpleConsensus {
     }
 
     pub fn total_stake(&self) -> u128 {
-        self.validators
-            .iter()
-            .map(|v| v.stake)
-            .fold(0u128, u128::saturating_add)
+        self.stake_at(self.current_slot)
     }
 }
 
@@ -155,13 +251,52 @@ impl ConsensusEngine for SimpleConsensus {
         SimpleConsensus::total_stake(self)
     }
 
+    fn stage_validator_set(&mut self, next: EpochInfo) -> Result<()> {
+        SimpleConsensus::stage_validator_set(self, next)
+    }
+
+    fn leader_schedule(&self, epoch: Epoch) -> Vec<PublicKey> {
+        SimpleConsensus::leader_schedule(self, epoch)
+    }
+
+    fn upcoming_leaders(&self, n: usize) -> Vec<(Slot, PublicKey)> {
+        SimpleConsensus::upcoming_leaders(self, n)
+    }
+
     fn validator_addresses_and_stakes(&self) -> Vec<(aether_types::Address, u128)> {
-        self.validators
+        self.validators_at(self.current_slot)
             .iter()
             .filter(|v| v.stake > 0)
             .map(|v| (v.pubkey.to_address(), v.stake))
             .collect()
     }
+
+    /// The configured set as epoch 0, then each set activated since, in
+    /// configuration order. An epoch runs until the next one starts.
+    fn validator_set(&self, epoch: Epoch) -> Option<EpochInfo> {
+        let end_of = |i: usize| {
+            self.epochs
+                .get(i)
+                .map_or(Slot::MAX, |next| next.start_slot.saturating_sub(1))
+        };
+        if epoch == 0 {
+            return Some(EpochInfo {
+                epoch: 0,
+                start_slot: 0,
+                end_slot: end_of(0),
+                randomness: H256::zero(),
+                validators: self.validators.clone(),
+                total_stake: self.stake_at(0),
+            });
+        }
+        let i = self.epochs.iter().position(|e| e.epoch == epoch)?;
+        let info = &self.epochs[i];
+        Some(EpochInfo {
+            end_slot: end_of(i + 1),
+            total_stake: self.stake_at(info.start_slot),
+            ..info.clone()
+        })
+    }
 }
 
 #[cfg(test)]
@@ -221,4 +356,102 @@ mod tests {
         assert!(consensus.check_finality(slot));
         assert_eq!(consensus.finalized_slot(), slot);
     }
+
+    #[test]
+    fn test_staged_set_takes_over_at_its_start_slot() {
+        let validators = create_test_validators(3);
+        let incoming = create_test_validators(2);
+        let mut consensus = SimpleConsensus::new(validators.clone());
+        let next = EpochInfo {
+            epoch: 1,
+            start_slot: 3,
+            end_slot: Slot::MAX,
+            randomness: H256::zero(),
+            validators: incoming.clone(),
+            total_stake: 3000,
+        };
+        assert!(consensus
+            .stage_validator_set(EpochInfo {
+                start_slot: 0,
+                ..next.clone()
+            })
+            .is_err());
+        consensus.stage_validator_set(next).unwrap();
+
+        let vote = |slot: Slot, v: &ValidatorInfo| Vote {
+            slot,
+            block_hash: H256::zero(),
+            validator: v.pubkey.clone(),
+            signature: Signature::from_bytes(vec![]),
+            stake: v.stake,
+        };
+        for _ in 0..3 {
+            consensus.advance_slot();
+        }
+        assert_eq!(consensus.total_stake(), 3000);
+        assert_eq!(consensus.get_leader(3).unwrap().pubkey, incoming[1].pubkey);
+        assert_eq!(
+            consensus.get_leader(2).unwrap().pubkey,
+            validators[2].pubkey
+        );
+        let old = consensus.validator_set(0).unwrap();
+        assert_eq!((old.end_slot, old.total_stake), (2, 6000));
+        assert_eq!(consensus.validator_set(1).unwrap().start_slot, 3);
+
+        // A late slot-2 vote from the outgoing set still counts against it,
+        // while a departed validator's vote in the new epoch does not.
+        consensus.add_vote(vote(2, &validators[2])).unwrap();
+        consensus.add_vote(vote(2, &incoming[1])).unwrap();
+        assert!(!consensus.check_finality(2));
+        consensus.add_vote(vote(2, &validators[1])).unwrap();
+        assert!(consensus.check_finality(2));
+
+        consensus.add_vote(vote(3, &validators[2])).unwrap();
+        assert!(!consensus.check_finality(3));
+        consensus.add_vote(vote(3, &incoming[1])).unwrap();
+        assert!(consensus.check_finality(3));
+    }
+    #[test]
+    fn test_leader_schedule_and_lookahead_follow_staged_set() {
+        let validators = create_test_validators(3);
+        let incoming = create_test_validators(2);
+        let mut consensus = SimpleConsensus::new(validators.clone());
+        let keys = |vs: &[ValidatorInfo]| -> Vec<PublicKey> {
+            vs.iter().map(|v| v.pubkey.clone()).collect()
+        };
+        assert_eq!(consensus.leader_schedule(0), keys(&validators));
+
+        consensus
+            .stage_validator_set(EpochInfo {
+                epoch: 1,
+                start_slot: 3,
+                end_slot: Slot::MAX,
+                randomness: H256::zero(),
+                validators: incoming.clone(),
+                total_stake: 3000,
+            })
+            .unwrap();
+        // Slot 3 is the new set's first; round-robin puts incoming[1] there.
+        assert_eq!(
+            consensus.leader_schedule(1),
+            vec![incoming[1].pubkey.clone(), incoming[0].pubkey.clone()]
+        );
+        let upcoming = consensus.upcoming_leaders(4);
+        let slots: Vec<Slot> = upcoming.iter().map(|(slot, _)| *slot).collect();
+        assert_eq!(slots, vec![1, 2, 3, 4]);
+        assert_eq!(upcoming[1].1, validators[2].pubkey);
+        assert_eq!(upcoming[2].1, incoming[1].pubkey);
+
+        for _ in 0..3 {
+            consensus.advance_slot();
+        }
+        // Active now, and served from the cached rotation.
+        assert_eq!(consensus.schedule, consensus.leader_schedule(1));
+        assert_eq!(consensus.schedule[0], incoming[1].pubkey);
+        for (slot, leader) in consensus.upcoming_leaders(5) {
+            assert!(consensus.is_leader(slot, &leader));
+        }
+        assert_eq!(consensus.leader_schedule(0), keys(&validators));
+        assert!(consensus.leader_schedule(2).is_empty());
+    }
 }
@@ -1,6 +1,8 @@
+use aether_crypto_kes::{DoubleSignEvidence, KesSignature, KesVerificationKey};
+use aether_da_shreds::ShredEquivocation;
 use aether_types::{Address, PublicKey, Signature, H256};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// Overflow-safe (a * b) / c using 256-bit intermediate product.
 fn mul_div(a: u128, b: u128, c: u128) -> u128 {
@@ -219,6 +221,62 @@ fn verify_vote_signature(vote: &Vote) -> anyhow::Result<()> {
     }
 }
 
+/// A block double-sign proven by two KES signatures for the same slot.
+///
+/// The evidence names no key of its own: it is checked against the KES key
+/// the accused validator registered, so it cannot be pinned on anyone else.
+/// Slashed at the `SlashType::DoubleSign` rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KesSlashProof {
+    pub validator: Address,
+    pub evidence: DoubleSignEvidence,
+}
+
+impl KesSlashProof {
+    pub fn proof_type(&self) -> SlashType {
+        SlashType::DoubleSign
+    }
+}
+
+/// Verify a KES slash proof against `registered_key`, the KES verification
+/// key on record for `proof.validator`.
+pub fn verify_kes_slash_proof(
+    proof: &KesSlashProof,
+    registered_key: &KesVerificationKey,
+) -> anyhow::Result<()> {
+    if !proof.evidence.verify(registered_key) {
+        anyhow::bail!("KES double-sign evidence does not verify against the validator's key");
+    }
+    Ok(())
+}
+
+/// A leader equivocating in Turbine: two different shreds it signed for
+/// the same position in a slot. Checked against the leader's registered
+/// key and slashed at the `SlashType::DoubleSign` rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShredSlashProof {
+    pub validator: Address,
+    pub evidence: ShredEquivocation,
+}
+
+impl ShredSlashProof {
+    pub fn proof_type(&self) -> SlashType {
+        SlashType::DoubleSign
+    }
+}
+
+/// Verify a shred slash proof against `registered_key`, the Ed25519 key on
+/// record for `proof.validator`.
+pub fn verify_shred_slash_proof(
+    proof: &ShredSlashProof,
+    registered_key: &PublicKey,
+) -> anyhow::Result<()> {
+    proof
+        .evidence
+        .verify(registered_key.as_bytes())
+        .map_err(|e| anyhow::anyhow!("shred equivocation evidence rejected: {}", e))
+}
+
 /// Return the slash rate in basis points for a given offense type.
 ///
 /// This avoids the lossy roundtrip of computing an absolute slash amount and
@@ -276,15 +334,27 @@ struct RecordedVote {
 pub struct SlashingDetector {
     /// Maps (validator_address, slot) -> first vote (with full signature).
     seen_votes: HashMap<(Address, u64), RecordedVote>,
+    /// Maps (proposer, slot) -> first KES-signed block header seen.
+    seen_blocks: HashMap<(Address, u64), ([u8; 32], KesSignature)>,
     /// Pending slash proofs awaiting enforcement.
     pending_slashes: Vec<SlashProof>,
+    /// Pending KES double-sign proofs awaiting enforcement.
+    pending_kes_slashes: Vec<KesSlashProof>,
+    /// Slots each proposer has already been caught equivocating shreds in.
+    shred_equivocations: HashSet<(Address, u64)>,
+    /// Pending shred equivocation proofs awaiting enforcement.
+    pending_shred_slashes: Vec<ShredSlashProof>,
 }
 
 impl SlashingDetector {
     pub fn new() -> Self {
         SlashingDetector {
             seen_votes: HashMap::new(),
+            seen_blocks: HashMap::new(),
             pending_slashes: Vec::new(),
+            pending_kes_slashes: Vec::new(),
+            shred_equivocations: HashSet::new(),
+            pending_shred_slashes: Vec::new(),
         }
     }
 
@@ -343,14 +413,92 @@ impl SlashingDetector {
         }
     }
 
+    /// Record a block header's KES signature by `proposer`, whose registered
+    /// KES key is `kes_key`. A second valid signature for a different block
+    /// in the same slot yields a `KesSlashProof`. Signatures that do not
+    /// verify are ignored, so a forged header cannot frame the proposer.
+    pub fn record_block_signature(
+        &mut self,
+        proposer: Address,
+        kes_key: &KesVerificationKey,
+        slot: u64,
+        block_hash: [u8; 32],
+        signature: KesSignature,
+    ) -> Option<KesSlashProof> {
+        match self.seen_blocks.entry((proposer, slot)) {
+            std::collections::hash_map::Entry::Vacant(e) => {
+                let message = aether_crypto_kes::block_signing_message(slot, &block_hash);
+                if signature.verify(kes_key, &message) {
+                    e.insert((block_hash, signature));
+                }
+                None
+            }
+            std::collections::hash_map::Entry::Occupied(e) => {
+                let (first_hash, first_sig) = e.get();
+                if *first_hash == block_hash {
+                    return None;
+                }
+                let evidence = DoubleSignEvidence::from_signatures(
+                    kes_key,
+                    slot,
+                    (first_hash, first_sig),
+                    (&block_hash, &signature),
+                )
+                .ok()?;
+                let proof = KesSlashProof {
+                    validator: proposer,
+                    evidence,
+                };
+                self.pending_kes_slashes.push(proof.clone());
+                Some(proof)
+            }
+        }
+    }
+
+    /// Record shred equivocation evidence from Turbine against `proposer`,
+    /// whose registered key is `proposer_key`. Evidence that does not
+    /// verify is ignored, and a slot yields at most one proof.
+    pub fn record_shred_equivocation(
+        &mut self,
+        proposer: Address,
+        proposer_key: &PublicKey,
+        evidence: ShredEquivocation,
+    ) -> Option<ShredSlashProof> {
+        let key = (proposer, evidence.slot());
+        if self.shred_equivocations.contains(&key) {
+            return None;
+        }
+        let proof = ShredSlashProof {
+            validator: proposer,
+            evidence,
+        };
+        verify_shred_slash_proof(&proof, proposer_key).ok()?;
+        self.shred_equivocations.insert(key);
+        self.pending_shred_slashes.push(proof.clone());
+        Some(proof)
+    }
+
     /// Drain all pending slash proofs for processing.
     pub fn drain_pending(&mut self) -> Vec<SlashProof> {
         std::mem::take(&mut self.pending_slashes)
     }
 
-    /// Prune vote records for slots below `min_slot` to bound memory.
+    /// Drain all pending KES double-sign proofs for processing.
+    pub fn drain_pending_kes(&mut self) -> Vec<KesSlashProof> {
+        std::mem::take(&mut self.pending_kes_slashes)
+    }
+
+    /// Drain all pending shred equivocation proofs for processing.
+    pub fn drain_pending_shred(&mut self) -> Vec<ShredSlashProof> {
+        std::mem::take(&mut self.pending_shred_slashes)
+    }
+
+    /// Prune vote and block records for slots below `min_slot` to bound memory.
     pub fn prune_before(&mut self, min_slot: u64) {
         self.seen_votes.retain(|&(_, slot), _| slot >= min_slot);
+        self.seen_blocks.retain(|&(_, slot), _| slot >= min_slot);
+        self.shred_equivocations
+            .retain(|&(_, slot)| slot >= min_slot);
     }
 }
 
@@ -743,6 +891,85 @@ mod tests {
         assert_eq!(pending.len(), 1);
         assert!(detector.drain_pending().is_empty());
     }
+
+    #[test]
+    fn test_slashing_detector_extracts_kes_double_sign() {
+        use aether_crypto_kes::{block_signing_message, KesKey};
+
+        let mut detector = SlashingDetector::new();
+        let mut key = KesKey::from_seed([9u8; 32], 8);
+        let vk = key.verification_key();
+        let proposer = Address::from_slice(&[7u8; 20]).unwrap();
+        let mut sign = |hash: [u8; 32]| key.sign(1, &block_signing_message(15, &hash)).unwrap();
+        let (sig_a, sig_b) = (sign([1u8; 32]), sign([2u8; 32]));
+
+        // A header with a bad signature is not remembered against the proposer.
+        let mut forged = sig_b.clone();
+        forged.signature[0] ^= 1;
+        assert!(detector
+            .record_block_signature(proposer, &vk, 15, [3u8; 32], forged)
+            .is_none());
+
+        assert!(detector
+            .record_block_signature(proposer, &vk, 15, [1u8; 32], sig_a.clone())
+            .is_none());
+        assert!(detector
+            .record_block_signature(proposer, &vk, 15, [1u8; 32], sig_a)
+            .is_none());
+        let proof = detector
+            .record_block_signature(proposer, &vk, 15, [2u8; 32], sig_b)
+            .expect("should detect KES double-sign");
+        assert_eq!(proof.proof_type(), SlashType::DoubleSign);
+        verify_kes_slash_proof(&proof, &vk).expect("detector-produced proof must verify");
+
+        let other = KesKey::from_seed([8u8; 32], 8).verification_key();
+        assert!(verify_kes_slash_proof(&proof, &other).is_err());
+        assert_eq!(detector.drain_pending_kes().len(), 1);
+        assert!(detector.drain_pending().is_empty());
+    }
+
+    #[test]
+    fn test_slashing_detector_accepts_shred_equivocation() {
+        use aether_crypto_primitives::Keypair;
+        use aether_da_shreds::shred::{Shred, ShredVariant};
+
+        let leader = Keypair::generate();
+        let leader_key = PublicKey::from_bytes(leader.public_key());
+        let proposer = leader_key.to_address();
+        let shred = |payload: &[u8]| {
+            let hash = Shred::hash_payload(payload);
+            let sig = leader.sign(&Shred::build_signing_message(20, 1, &hash));
+            Shred::new(
+                ShredVariant::Data,
+                20,
+                1,
+                1,
+                0,
+                H256::zero(),
+                payload.to_vec(),
+                Signature::from_bytes(sig),
+            )
+        };
+        let evidence = ShredEquivocation::new(&shred(b"a"), &shred(b"b")).unwrap();
+
+        let mut detector = SlashingDetector::new();
+        let stranger = PublicKey::from_bytes(Keypair::generate().public_key());
+        assert!(detector
+            .record_shred_equivocation(proposer, &stranger, evidence.clone())
+            .is_none());
+        let proof = detector
+            .record_shred_equivocation(proposer, &leader_key, evidence.clone())
+            .expect("valid evidence is accepted");
+        assert_eq!(proof.proof_type(), SlashType::DoubleSign);
+        verify_shred_slash_proof(&proof, &leader_key).unwrap();
+        assert!(detector
+            .record_shred_equivocation(proposer, &leader_key, evidence)
+            .is_none());
+        assert_eq!(detector.drain_pending_shred().len(), 1);
+
+        detector.prune_before(21);
+        assert!(detector.drain_pending_shred().is_empty());
+    }
 }
 
 #[cfg(test)]
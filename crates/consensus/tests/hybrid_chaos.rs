@@ -0,0 +1,510 @@
+//! Failure injection for `HybridConsensus`.
+//!
+//! Several in-process validators run slot by slot over a seeded network
+//! that drops and delays messages, while up to f of them are byzantine:
+//! they either go silent or equivocate, proposing two blocks when elected
+//! and voting for every block they see. Each run checks safety (no two
+//! validators finalize conflicting blocks, no validator finalizes a fork)
+//! and, when faults stay within bounds, liveness (honest validators keep
+//! certifying blocks). Half the runs enable one-round fast finality; its
+//! vote lock can stall a faulty network, so those runs only check liveness
+//! when every validator is honest.
+//!
+//! Runs are reproducible from their seed. `AETHER_CHAOS_RUNS` sets how many
+//! seeds the quick test covers; the ignored soak test covers thousands.
+
+use std::collections::{BTreeMap, HashMap};
+
+use aether_consensus::hybrid::{HybridConsensus, Phase};
+use aether_consensus::{ConsensusEngine, Finality};
+use aether_crypto_bls::BlsKeypair;
+use aether_crypto_vrf::VrfKeypair;
+use aether_types::{
+    Address, AggregatedVote, Block, FinalityPath, PublicKey, Slot, ValidatorInfo, Vote, H256,
+};
+
+/// Leader election rate of the devnet chain config.
+const TAU: f64 = 0.8;
+
+/// Network steps per slot: proposals go out at step 0, and anything
+/// delivered after the last step arrives in a later slot.
+const STEPS_PER_SLOT: u64 = 8;
+
+/// A proposal and the votes on it take at most `2 * (1 + MAX_DELAY)` steps,
+/// which fits in a slot, so without loss every slot with an honest leader
+/// can gather a QC.
+const MAX_DELAY: u64 = 2;
+
+/// Without message loss, every honest validator must know a QC from the last
+/// this many slots by the end of a run. Slots where no honest validator is
+/// elected, or where two are and split the vote, get no QC, so gaps of a
+/// dozen slots do occur. Finality needs QCs in two consecutive slots, which
+/// VRF election can withhold for far longer, so it is only checked in the
+/// fault-free run.
+const LIVENESS_WINDOW: Slot = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Fault {
+    Honest,
+    /// Crashed from the start.
+    Silent,
+    /// Double-proposes and votes for every block it sees.
+    Equivocating,
+}
+
+#[derive(Clone, Debug)]
+struct ChaosConfig {
+    seed: u64,
+    faults: Vec<Fault>,
+    slots: u64,
+    /// Chance in percent that a message is lost.
+    drop_percent: u64,
+    /// Extra network steps a message may take.
+    max_delay: u64,
+    /// Whether validators finalize in one round on more than 90% of stake.
+    fast_finality: bool,
+}
+
+impl ChaosConfig {
+    /// A random configuration with at most f byzantine validators.
+    fn random(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let validators = 4 + rng.below(4) as usize;
+        let f = (validators - 1) / 3;
+        let mut faults = vec![Fault::Honest; validators];
+        for fault in faults.iter_mut().take(rng.below(f as u64 + 1) as usize) {
+            *fault = if rng.below(2) == 0 {
+                Fault::Silent
+            } else {
+                Fault::Equivocating
+            };
+        }
+        ChaosConfig {
+            seed,
+            faults,
+            slots: 48,
+            // Half the runs lose messages; only those without loss must stay live.
+            drop_percent: rng.below(2) * (1 + rng.below(10)),
+            max_delay: rng.below(MAX_DELAY + 1),
+            fast_finality: rng.below(2) == 0,
+        }
+    }
+}
+
+/// Small deterministic PRNG so a seed replays the same run.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Rng(seed ^ 0x9e37_79b9_7f4a_7c15)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % bound.max(1)
+    }
+}
+
+#[derive(Clone)]
+enum Msg {
+    Proposal(Box<Block>),
+    Vote(Vote),
+}
+
+struct Chaos {
+    config: ChaosConfig,
+    rng: Rng,
+    nodes: Vec<HybridConsensus>,
+    bls: Vec<BlsKeypair>,
+    addresses: Vec<Address>,
+    /// Pending deliveries by (step, sequence).
+    queue: BTreeMap<(u64, u64), (usize, Msg)>,
+    seq: u64,
+    /// Every block proposed, for walking chains.
+    blocks: HashMap<H256, Block>,
+    /// Slot each node last voted in.
+    voted: Vec<Slot>,
+    /// QCs each node formed or learned from a block, by slot.
+    certified: Vec<BTreeMap<Slot, AggregatedVote>>,
+    /// Blocks each node finalized, by slot.
+    finalized: Vec<BTreeMap<Slot, H256>>,
+}
+
+impl Chaos {
+    fn new(config: ChaosConfig) -> Self {
+        let n = config.faults.len();
+        // A small leading byte keeps the BLS scalar below the group order.
+        let secret = |tag: u8, i: usize| {
+            let mut bytes = [0u8; 32];
+            bytes[0] = tag;
+            bytes[1] = i as u8;
+            bytes[24..].copy_from_slice(&config.seed.to_le_bytes());
+            bytes
+        };
+        let bls: Vec<BlsKeypair> = (0..n)
+            .map(|i| BlsKeypair::from_secret(secret(1, i).to_vec()).unwrap())
+            .collect();
+        let vrf: Vec<VrfKeypair> = (0..n)
+            .map(|i| VrfKeypair::from_secret(&secret(2, i)).unwrap())
+            .collect();
+        let validators: Vec<ValidatorInfo> = bls
+            .iter()
+            .map(|key| ValidatorInfo {
+                pubkey: PublicKey::from_bytes(key.public_key()[..32].to_vec()),
+                stake: 1000,
+                commission: 0,
+                active: true,
+            })
+            .collect();
+        let addresses: Vec<Address> = validators.iter().map(|v| v.pubkey.to_address()).collect();
+
+        let nodes = (0..n)
+            .map(|me| {
+                let mut node = HybridConsensus::new(
+                    validators.clone(),
+                    TAU,
+                    1_000,
+                    Some(vrf[me].clone()),
+                    Some(bls[me].clone()),
+                    Some(addresses[me]),
+                );
+                for i in 0..n {
+                    node.register_vrf_pubkey(addresses[i], *vrf[i].public_key());
+                    node.register_bls_pubkey(
+                        addresses[i],
+                        bls[i].public_key(),
+                        &bls[i].proof_of_possession(),
+                    )
+                    .unwrap();
+                }
+                node.set_fast_finality(config.fast_finality);
+                node
+            })
+            .collect();
+
+        Chaos {
+            rng: Rng::new(config.seed),
+            config,
+            nodes,
+            bls,
+            addresses,
+            queue: BTreeMap::new(),
+            seq: 0,
+            blocks: HashMap::new(),
+            voted: vec![0; n],
+            certified: vec![BTreeMap::new(); n],
+            finalized: vec![BTreeMap::new(); n],
+        }
+    }
+
+    fn fault(&self, node: usize) -> Fault {
+        self.config.faults[node]
+    }
+
+    fn send(&mut self, now: u64, from: usize, to: usize, msg: Msg) {
+        if from != to && self.rng.below(100) < self.config.drop_percent {
+            return;
+        }
+        let delay = if from == to {
+            0
+        } else {
+            1 + self.rng.below(self.config.max_delay + 1)
+        };
+        self.seq += 1;
+        self.queue.insert((now + delay, self.seq), (to, msg));
+    }
+
+    fn broadcast(&mut self, now: u64, from: usize, msg: Msg) {
+        for to in 0..self.nodes.len() {
+            self.send(now, from, to, msg.clone());
+        }
+    }
+
+    /// Build `block` on the block `qc` certifies, carrying the QC so voters
+    /// that missed it can still check the parent is certified.
+    fn extend(block: &mut Block, qc: Option<&AggregatedVote>) {
+        block.header.parent_hash = qc.map_or_else(H256::zero, |qc| qc.block_hash);
+        block.aggregated_vote = qc.cloned();
+    }
+
+    /// Note that `node` knows a QC for `qc.block_hash`. Honest validators
+    /// never vote twice in a slot, so there is at most one per slot.
+    fn certify(&mut self, node: usize, qc: AggregatedVote) {
+        let (slot, hash) = (qc.slot, qc.block_hash);
+        let previous = self.certified[node].insert(slot, qc);
+        assert!(
+            previous.map_or(hash, |qc| qc.block_hash) == hash,
+            "{:?}: node {node} saw QCs for two blocks at slot {slot}",
+            self.config
+        );
+    }
+
+    fn propose(&mut self, now: u64, node: usize, slot: Slot) {
+        let Some(proof) = self.nodes[node].get_leader_proof(slot) else {
+            return;
+        };
+        let vrf_proof = aether_types::VrfProof {
+            output: proof.output,
+            proof: proof.proof,
+        };
+        // Honest leaders extend the highest block they have a QC for.
+        let mut block = Block::new(slot, H256::zero(), self.addresses[node], vrf_proof, vec![]);
+        // Wall-clock timestamps would make runs unrepeatable.
+        block.header.timestamp = slot;
+        Self::extend(&mut block, self.certified[node].values().next_back());
+        self.blocks.insert(block.hash(), block.clone());
+
+        if self.fault(node) != Fault::Equivocating {
+            self.broadcast(now, node, Msg::Proposal(Box::new(block)));
+            return;
+        }
+        // A conflicting twin for half the network, built on a random block
+        // this node knows so forks reach back past the head.
+        let mut twin = block.clone();
+        twin.header.state_root = H256::from([0xEE; 32]);
+        let known = self.certified[node].len() as u64;
+        if known > 0 {
+            let pick = self.rng.below(known) as usize;
+            Self::extend(&mut twin, self.certified[node].values().nth(pick));
+        }
+        self.blocks.insert(twin.hash(), twin.clone());
+        for to in 0..self.nodes.len() {
+            let copy = if to % 2 == 0 { &block } else { &twin };
+            self.send(now, node, to, Msg::Proposal(Box::new(copy.clone())));
+        }
+    }
+
+    fn deliver(&mut self, now: u64, to: usize, msg: Msg) {
+        match msg {
+            Msg::Proposal(block) => {
+                let hash = block.hash();
+                self.nodes[to].record_block(hash, block.header.parent_hash, block.header.slot);
+                if let Some(qc) = &block.aggregated_vote {
+                    if self.nodes[to].record_qc(qc).is_ok() {
+                        self.certify(to, qc.clone());
+                    }
+                }
+                let slot = self.nodes[to].current_slot();
+                if block.header.slot != slot {
+                    return;
+                }
+                let vote = match self.fault(to) {
+                    Fault::Silent => return,
+                    // Votes for everything, valid or not.
+                    Fault::Equivocating => self.sign_vote(to, hash, slot),
+                    Fault::Honest => {
+                        if self.voted[to] == slot {
+                            return;
+                        }
+                        if self.nodes[to].validate_block(&block).is_err() {
+                            return;
+                        }
+                        self.voted[to] = slot;
+                        self.nodes[to]
+                            .create_vote(hash, Phase::Propose)
+                            .unwrap()
+                            .unwrap()
+                    }
+                };
+                self.broadcast(now, to, Msg::Vote(vote));
+            }
+            Msg::Vote(vote) => {
+                let before = self.nodes[to].finalized_slot();
+                // With fast finality a vote after the QC can finalize too.
+                if let Ok(Some(qc)) = self.nodes[to].process_vote(vote) {
+                    let proof = self.nodes[to].finality_proof(&qc).unwrap();
+                    self.certify(to, proof);
+                }
+                let after = self.nodes[to].finalized_slot();
+                if after > before {
+                    let qc = self.certified[to].get(&after).unwrap_or_else(|| {
+                        panic!(
+                            "{:?}: node {to} finalized slot {after} without a QC",
+                            self.config
+                        )
+                    });
+                    self.finalized[to].insert(after, qc.block_hash);
+                }
+            }
+        }
+    }
+
+    fn sign_vote(&self, node: usize, block_hash: H256, slot: Slot) -> Vote {
+        let mut msg = block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&slot.to_le_bytes());
+        Vote {
+            slot,
+            block_hash,
+            validator: PublicKey::from_bytes(self.bls[node].public_key()[..32].to_vec()),
+            signature: aether_types::Signature::from_bytes(self.bls[node].sign(&msg)),
+            stake: 1000,
+        }
+    }
+
+    fn run(&mut self) {
+        for slot in 1..=self.config.slots {
+            for node in &mut self.nodes {
+                node.advance_slot();
+            }
+            let start = slot * STEPS_PER_SLOT;
+            for node in 0..self.nodes.len() {
+                if self.fault(node) != Fault::Silent {
+                    self.propose(start, node, slot);
+                }
+            }
+            for step in start..start + STEPS_PER_SLOT {
+                while let Some(entry) = self.queue.first_entry() {
+                    if entry.key().0 > step {
+                        break;
+                    }
+                    let (to, msg) = entry.remove();
+                    self.deliver(step, to, msg);
+                }
+            }
+        }
+    }
+
+    /// Whether `ancestor` is on `block`'s chain.
+    fn descends(&self, mut block: H256, ancestor: H256) -> bool {
+        while block != ancestor {
+            match self.blocks.get(&block) {
+                Some(b) => block = b.header.parent_hash,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn honest(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&n| self.fault(n) == Fault::Honest)
+            .collect()
+    }
+
+    /// Honest validators' finalized blocks all lie on one chain.
+    fn assert_safe(&self) {
+        let mut by_slot: BTreeMap<Slot, H256> = BTreeMap::new();
+        for node in self.honest() {
+            for (slot, hash) in &self.finalized[node] {
+                let agreed = *by_slot.entry(*slot).or_insert(*hash);
+                assert_eq!(
+                    agreed, *hash,
+                    "{:?}: conflicting finalization at slot {slot}",
+                    self.config
+                );
+            }
+        }
+        let chain: Vec<H256> = by_slot.values().copied().collect();
+        for pair in chain.windows(2) {
+            assert!(
+                self.descends(pair[1], pair[0]),
+                "{:?}: finalized blocks fork",
+                self.config
+            );
+        }
+    }
+
+    /// Slots of the last finalized block per honest validator.
+    fn finalized_slots(&self) -> Vec<Slot> {
+        self.honest()
+            .into_iter()
+            .map(|n| self.finalized[n].keys().next_back().copied().unwrap_or(0))
+            .collect()
+    }
+
+    /// Without message loss, every honest validator certified recently.
+    fn assert_live(&self) {
+        let recent = self.config.slots.saturating_sub(LIVENESS_WINDOW);
+        let slots: Vec<Slot> = self
+            .honest()
+            .into_iter()
+            .map(|n| self.certified[n].keys().next_back().copied().unwrap_or(0))
+            .collect();
+        assert!(
+            slots.iter().all(|&slot| slot >= recent),
+            "{:?}: QCs stalled, last certified slots {slots:?}",
+            self.config
+        );
+    }
+}
+
+fn chaos_runs(runs: u64) {
+    for seed in 0..runs {
+        let mut chaos = Chaos::new(ChaosConfig::random(seed));
+        chaos.run();
+        chaos.assert_safe();
+        let faulty = chaos.honest().len() < chaos.nodes.len();
+        if chaos.config.drop_percent == 0 && !(chaos.config.fast_finality && faulty) {
+            chaos.assert_live();
+        }
+    }
+}
+
+#[test]
+fn fault_free_run_finalizes_the_same_chain_everywhere() {
+    let mut chaos = Chaos::new(ChaosConfig {
+        seed: 7,
+        faults: vec![Fault::Honest; 4],
+        slots: 30,
+        drop_percent: 0,
+        max_delay: 0,
+        fast_finality: false,
+    });
+    chaos.run();
+    chaos.assert_safe();
+    let slots = chaos.finalized_slots();
+    assert!(slots.iter().all(|&slot| slot >= 10), "{slots:?}");
+}
+
+#[test]
+fn fault_free_run_finalizes_in_one_round() {
+    let mut chaos = Chaos::new(ChaosConfig {
+        seed: 7,
+        faults: vec![Fault::Honest; 4],
+        slots: 30,
+        drop_percent: 0,
+        max_delay: 0,
+        fast_finality: true,
+    });
+    chaos.run();
+    chaos.assert_safe();
+    let slots = chaos.finalized_slots();
+    assert!(slots.iter().all(|&slot| slot >= 20), "{slots:?}");
+    // Slots where two leaders split the vote still fall back to two-chain.
+    for node in 0..chaos.nodes.len() {
+        let paths: Vec<FinalityPath> = chaos.finalized[node]
+            .keys()
+            .map(|slot| chaos.nodes[node].finality_path(*slot).unwrap())
+            .collect();
+        let fast = paths.iter().filter(|p| **p == FinalityPath::Fast).count();
+        assert!(fast * 2 > paths.len(), "node {node}: {paths:?}");
+    }
+}
+
+#[test]
+fn randomized_faults_never_finalize_conflicting_blocks() {
+    let runs = std::env::var("AETHER_CHAOS_RUNS")
+        .ok()
+        .and_then(|runs| runs.parse().ok())
+        .unwrap_or(16);
+    chaos_runs(runs);
+}
+
+#[test]
+#[ignore = "soak test: thousands of runs, about an hour"]
+fn randomized_faults_soak() {
+    chaos_runs(2_000);
+}
+
+#[test]
+fn same_seed_replays_the_same_run() {
+    let run = |seed| {
+        let mut chaos = Chaos::new(ChaosConfig::random(seed));
+        chaos.run();
+        chaos.finalized
+    };
+    assert_eq!(run(5), run(5));
+}
@@ -0,0 +1,357 @@
+//! Liveness of pipelined HotStuff under partial synchrony.
+//!
+//! Validators run in virtual time over a seeded network: before the global
+//! stabilization time (GST) messages take arbitrarily long, but no longer
+//! than GST + Δ; after it they arrive within Δ. Leaders crash along the way.
+//! The chain must keep finalizing after GST, and every validator must
+//! finalize the same chain.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use aether_consensus::hotstuff::*;
+use aether_consensus::Finality;
+use aether_crypto_bls::BlsKeypair;
+use aether_types::{Address, PublicKey, Slot, ValidatorInfo, H256};
+
+#[derive(Clone)]
+enum Msg {
+    Proposal(Proposal),
+    Vote(HotStuffVote),
+    NewView(NewView),
+}
+
+enum Event {
+    Deliver { to: usize, msg: Box<Msg> },
+    Timeout { node: usize, view: u64 },
+}
+
+struct SimConfig {
+    validators: usize,
+    seed: u64,
+    /// Global stabilization time, in milliseconds.
+    gst: u64,
+    /// Delivery bound after GST.
+    delta: u64,
+    /// View timeout before backoff.
+    base_timeout: u64,
+    /// Validators that stop, and when.
+    crashes: Vec<(usize, u64)>,
+    horizon: u64,
+}
+
+struct Sim {
+    config: SimConfig,
+    nodes: Vec<HotStuffConsensus>,
+    addresses: Vec<Address>,
+    now: u64,
+    seq: u64,
+    rng: u64,
+    queue: BinaryHeap<Reverse<(u64, u64)>>,
+    events: HashMap<u64, Event>,
+    /// Timeouts each node has hit in a row, for backoff.
+    timeouts: Vec<u32>,
+    /// Every proposed block's parent, to check finalized chains.
+    parents: HashMap<H256, H256>,
+    proposers: HashMap<H256, usize>,
+    /// Each node's finalized blocks with the time it finalized them.
+    finalized: Vec<Vec<(Slot, H256, u64)>>,
+}
+
+impl Sim {
+    fn new(config: SimConfig) -> Self {
+        let keys: Vec<BlsKeypair> = (0..config.validators)
+            .map(|i| BlsKeypair::from_secret(vec![i as u8 + 1; 32]).unwrap())
+            .collect();
+        let validators: Vec<ValidatorInfo> = keys
+            .iter()
+            .map(|k| ValidatorInfo {
+                pubkey: PublicKey::from_bytes(k.public_key()[..32].to_vec()),
+                stake: 1000,
+                commission: 0,
+                active: true,
+            })
+            .collect();
+        let addresses: Vec<Address> = validators.iter().map(|v| v.pubkey.to_address()).collect();
+        let nodes = (0..config.validators)
+            .map(|me| {
+                let mut node = HotStuffConsensus::new(
+                    validators.clone(),
+                    Some(keys[me].clone()),
+                    Some(addresses[me]),
+                );
+                for (addr, key) in addresses.iter().zip(&keys) {
+                    node.register_bls_pubkey(*addr, key.public_key(), &key.proof_of_possession())
+                        .unwrap();
+                }
+                node
+            })
+            .collect();
+        let n = config.validators;
+        let rng = config.seed ^ 0x9e37_79b9_7f4a_7c15;
+        Sim {
+            config,
+            nodes,
+            addresses,
+            now: 0,
+            seq: 0,
+            rng,
+            queue: BinaryHeap::new(),
+            events: HashMap::new(),
+            timeouts: vec![0; n],
+            parents: HashMap::new(),
+            proposers: HashMap::new(),
+            finalized: vec![Vec::new(); n],
+        }
+    }
+
+    fn random(&mut self, bound: u64) -> u64 {
+        self.rng = self
+            .rng
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.rng >> 33) % bound.max(1)
+    }
+
+    fn crashed(&self, node: usize) -> bool {
+        self.config
+            .crashes
+            .iter()
+            .any(|(n, at)| *n == node && self.now >= *at)
+    }
+
+    fn schedule(&mut self, at: u64, event: Event) {
+        self.seq += 1;
+        self.queue.push(Reverse((at, self.seq)));
+        self.events.insert(self.seq, event);
+    }
+
+    fn send(&mut self, from: usize, to: usize, msg: Msg) {
+        let delay = if from == to {
+            0
+        } else if self.now < self.config.gst {
+            self.random(self.config.gst - self.now + self.config.delta + 1)
+        } else {
+            1 + self.random(self.config.delta)
+        };
+        let msg = Box::new(msg);
+        self.schedule(self.now + delay, Event::Deliver { to, msg });
+    }
+
+    fn broadcast(&mut self, from: usize, msg: Msg) {
+        for to in 0..self.nodes.len() {
+            self.send(from, to, msg.clone());
+        }
+    }
+
+    fn leader_index(&self, view: u64) -> usize {
+        let leader = self.nodes[0].leader(view).unwrap();
+        self.addresses.iter().position(|a| *a == leader).unwrap()
+    }
+
+    fn arm_timer(&mut self, node: usize) {
+        let backoff = 1u64 << self.timeouts[node].min(6);
+        let view = self.nodes[node].current_slot();
+        let at = self.now + self.config.base_timeout * backoff;
+        self.schedule(at, Event::Timeout { node, view });
+    }
+
+    fn execute(&mut self, node: usize, actions: Vec<ConsensusAction>) {
+        for action in actions {
+            match action {
+                ConsensusAction::Propose { view, parent_hash } => {
+                    let mut bytes = [0u8; 32];
+                    bytes[..8].copy_from_slice(&view.to_le_bytes());
+                    bytes[8..].copy_from_slice(&parent_hash.as_bytes()[..24]);
+                    let block_hash = H256::from(bytes);
+                    self.parents.insert(block_hash, parent_hash);
+                    self.proposers.insert(block_hash, node);
+                    let more = self.nodes[node].propose(view, block_hash).unwrap();
+                    self.execute(node, more);
+                }
+                ConsensusAction::BroadcastProposal(p) => self.broadcast(node, Msg::Proposal(p)),
+                ConsensusAction::BroadcastVote(v) => self.broadcast(node, Msg::Vote(v)),
+                ConsensusAction::SendNewView(nv) => {
+                    let leader = self.leader_index(nv.view);
+                    self.send(node, leader, Msg::NewView(nv));
+                }
+                ConsensusAction::Finalized { slot, block_hash } => {
+                    self.finalized[node].push((slot, block_hash, self.now));
+                    self.timeouts[node] = 0;
+                }
+                ConsensusAction::BroadcastTimeout(_) => {}
+            }
+        }
+    }
+
+    fn run(&mut self) {
+        for node in 0..self.nodes.len() {
+            let actions = self.nodes[node].new_view().unwrap();
+            self.execute(node, actions);
+            self.arm_timer(node);
+        }
+        while let Some(Reverse((at, seq))) = self.queue.pop() {
+            if at > self.config.horizon {
+                break;
+            }
+            self.now = at;
+            let event = self.events.remove(&seq).unwrap();
+            let node = match &event {
+                Event::Deliver { to, .. } => *to,
+                Event::Timeout { node, .. } => *node,
+            };
+            if self.crashed(node) {
+                continue;
+            }
+            let view_before = self.nodes[node].current_slot();
+            let actions = match event {
+                Event::Deliver { to, msg } => match *msg {
+                    Msg::Proposal(p) => self.nodes[to].on_proposal(&p),
+                    Msg::Vote(v) => self.nodes[to].on_pipeline_vote(v),
+                    Msg::NewView(nv) => self.nodes[to].on_new_view(nv),
+                }
+                .unwrap(),
+                Event::Timeout { node, view } => {
+                    if self.nodes[node].current_slot() != view {
+                        continue;
+                    }
+                    self.timeouts[node] += 1;
+                    self.nodes[node].new_view().unwrap()
+                }
+            };
+            self.execute(node, actions);
+            if self.nodes[node].current_slot() != view_before {
+                self.arm_timer(node);
+            }
+        }
+    }
+
+    fn honest(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|n| !self.config.crashes.iter().any(|(c, _)| c == n))
+            .collect()
+    }
+
+    /// Whether `ancestor` is on `block`'s chain of proposed parents.
+    fn descends(&self, mut block: H256, ancestor: H256) -> bool {
+        while block != ancestor {
+            match self.parents.get(&block) {
+                Some(parent) => block = *parent,
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Every node's finalized blocks form one chain, and nodes agree on
+    /// every slot both finalized.
+    fn assert_safe(&self) {
+        let mut by_slot: HashMap<Slot, H256> = HashMap::new();
+        for log in &self.finalized {
+            for pair in log.windows(2) {
+                assert!(pair[0].0 < pair[1].0);
+                assert!(self.descends(pair[1].1, pair[0].1), "finalized a fork");
+            }
+            for (slot, hash, _) in log {
+                assert_eq!(*by_slot.entry(*slot).or_insert(*hash), *hash);
+            }
+        }
+        let mut all: Vec<(Slot, H256)> = by_slot.into_iter().collect();
+        all.sort_by_key(|(slot, _)| *slot);
+        for pair in all.windows(2) {
+            assert!(self.descends(pair[1].1, pair[0].1), "nodes finalized forks");
+        }
+    }
+
+    /// Blocks `node` finalized at or after `since`.
+    fn finalized_since(&self, node: usize, since: u64) -> usize {
+        self.finalized[node]
+            .iter()
+            .filter(|(_, _, at)| *at >= since)
+            .count()
+    }
+}
+
+#[test]
+fn keeps_finalizing_through_crashed_leaders() {
+    let probe = Sim::new(SimConfig {
+        validators: 7,
+        seed: 0,
+        gst: 0,
+        delta: 0,
+        base_timeout: 0,
+        crashes: vec![],
+        horizon: 0,
+    });
+    // Two of seven crash: the leaders of views ≡ 0 and 3 (mod 7), one at
+    // the start and one mid-run, leaving a run of three honest leaders.
+    let first = probe.leader_index(7);
+    let second = probe.leader_index(3);
+
+    let mut sim = Sim::new(SimConfig {
+        validators: 7,
+        seed: 11,
+        gst: 2_000,
+        delta: 50,
+        base_timeout: 400,
+        crashes: vec![(first, 0), (second, 4_000)],
+        horizon: 30_000,
+    });
+    sim.run();
+    sim.assert_safe();
+
+    for node in sim.honest() {
+        let after_crash = sim.finalized_since(node, 4_000);
+        assert!(after_crash >= 10, "node {node}: {after_crash} blocks");
+        let head = sim.nodes[node].finalized_slot();
+        assert!(head > 50, "node {node} finalized only to view {head}");
+    }
+    // Crashed leaders never get a block finalized after they stop.
+    for log in &sim.finalized {
+        for (_, hash, _) in log {
+            let proposer = sim.proposers[hash];
+            assert_ne!(proposer, first);
+        }
+    }
+}
+
+#[test]
+fn recovers_after_gst() {
+    let mut sim = Sim::new(SimConfig {
+        validators: 4,
+        seed: 3,
+        gst: 6_000,
+        delta: 40,
+        base_timeout: 300,
+        crashes: vec![],
+        horizon: 10_000,
+    });
+    sim.run();
+    sim.assert_safe();
+
+    // Timeouts back off until views last long enough for Δ-bounded rounds;
+    // from then on every view finalizes a block.
+    for node in 0..4 {
+        let after_gst = sim.finalized_since(node, 6_000);
+        assert!(after_gst >= 30, "node {node}: {after_gst} blocks after GST");
+    }
+}
+
+#[test]
+fn same_seed_same_run() {
+    let config = || SimConfig {
+        validators: 4,
+        seed: 5,
+        gst: 1_000,
+        delta: 30,
+        base_timeout: 200,
+        crashes: vec![(1, 2_000)],
+        horizon: 5_000,
+    };
+    let mut a = Sim::new(config());
+    let mut b = Sim::new(config());
+    a.run();
+    b.run();
+    assert!(a.finalized.iter().any(|log| !log.is_empty()));
+    assert_eq!(a.finalized, b.finalized);
+}
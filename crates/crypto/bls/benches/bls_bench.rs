@@ -0,0 +1,75 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use aether_crypto_bls::{
+    aggregate_public_keys, aggregate_signatures, verify_aggregated, verify_batch,
+    verify_votes_batched, BlsKeypair, PreparedMessage, ValidatorKeys,
+};
+
+// A slot is 500ms and the votes for it must be verified well inside that,
+// alongside block execution. These benches track the cost of checking every
+// validator's individual vote for one block.
+const VALIDATOR_COUNTS: [usize; 3] = [100, 500, 1000];
+const MESSAGE: &[u8] = b"slot-block-hash";
+
+fn make_votes(n: usize) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+    let keypairs: Vec<BlsKeypair> = (0..n).map(|_| BlsKeypair::generate()).collect();
+    (
+        keypairs.iter().map(|k| k.public_key()).collect(),
+        keypairs.iter().map(|k| k.sign(MESSAGE)).collect(),
+    )
+}
+
+fn bench_slot_votes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bls_slot_votes");
+    group.sample_size(10);
+
+    for &n in &VALIDATOR_COUNTS {
+        let (pks, sigs) = make_votes(n);
+        let pairs: Vec<(&[u8], &[u8])> = pks
+            .iter()
+            .zip(&sigs)
+            .map(|(pk, sig)| (pk.as_slice(), sig.as_slice()))
+            .collect();
+        let triples: Vec<(&[u8], &[u8], &[u8])> =
+            pairs.iter().map(|(pk, sig)| (*pk, MESSAGE, *sig)).collect();
+        let prepared = PreparedMessage::new(MESSAGE);
+        let keys = ValidatorKeys::new(&pks).unwrap();
+        let signers: Vec<(usize, &[u8])> = sigs.iter().map(Vec::as_slice).enumerate().collect();
+
+        // The slot hot path: keys validated once per epoch, message prepared
+        // once per block.
+        group.bench_with_input(BenchmarkId::new("signers", n), &n, |b, _| {
+            b.iter(|| assert!(prepared.verify_signers(&keys, black_box(&signers)).unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("prepared", n), &n, |b, _| {
+            b.iter(|| assert!(prepared.verify_votes(black_box(&pairs)).unwrap()))
+        });
+        group.bench_with_input(BenchmarkId::new("batched", n), &n, |b, _| {
+            b.iter(|| assert!(verify_votes_batched(black_box(&triples)).unwrap()))
+        });
+
+        group.bench_with_input(BenchmarkId::new("verify_batch", n), &n, |b, _| {
+            b.iter(|| assert!(verify_batch(black_box(&triples)).unwrap()))
+        });
+    }
+    group.finish();
+}
+
+fn bench_aggregated(c: &mut Criterion) {
+    let (pks, sigs) = make_votes(1000);
+    let agg_pk = aggregate_public_keys(&pks).unwrap();
+    let agg_sig = aggregate_signatures(&sigs).unwrap();
+    let prepared = PreparedMessage::new(MESSAGE);
+
+    let mut group = c.benchmark_group("bls_aggregated_vote");
+    group.bench_function("verify_aggregated", |b| {
+        b.iter(|| assert!(verify_aggregated(black_box(&agg_pk), MESSAGE, &agg_sig).unwrap()))
+    });
+    group.bench_function("prepared", |b| {
+        b.iter(|| assert!(prepared.verify(black_box(&agg_pk), &agg_sig).unwrap()))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_slot_votes, bench_aggregated);
+criterion_main!(benches);
@@ -0,0 +1,272 @@
+use anyhow::{anyhow, bail, Result};
+use blst::min_pk::{
+    AggregatePublicKey, AggregateSignature, PublicKey as BlstPublicKey, Signature as BlstSignature,
+};
+use std::collections::HashMap;
+
+use crate::keypair::verify;
+
+/// A vote as it arrives from gossip.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccumulatedVote {
+    pub public_key: Vec<u8>,
+    pub message: Vec<u8>,
+    pub signature: Vec<u8>,
+    pub stake: u128,
+}
+
+/// Two votes from one validator for different messages in the same round.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConflictingVotes {
+    pub first: AccumulatedVote,
+    pub second: AccumulatedVote,
+}
+
+/// What `AggregationAccumulator::add` did with a vote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VoteOutcome {
+    /// Folded into the aggregate for its message, which now carries `stake`.
+    Added { stake: u128 },
+    /// The validator already voted for this message; nothing changed.
+    Duplicate,
+    /// The validator already voted for another message. The new vote is not
+    /// counted; the pair is evidence of equivocation.
+    Conflict(Box<ConflictingVotes>),
+}
+
+/// Snapshot of the votes accumulated for one message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aggregate {
+    pub message: Vec<u8>,
+    /// 96-byte aggregate signature.
+    pub signature: Vec<u8>,
+    /// 48-byte aggregate public key.
+    pub public_key: Vec<u8>,
+    pub stake: u128,
+    /// Signer public keys in arrival order.
+    pub signers: Vec<Vec<u8>>,
+}
+
+struct RunningAggregate {
+    signature: AggregateSignature,
+    public_key: AggregatePublicKey,
+    stake: u128,
+    signers: Vec<Vec<u8>>,
+}
+
+/// Aggregates the votes of one round (e.g. a slot and phase) one at a time
+/// as they arrive, instead of collecting them all and aggregating at the end.
+///
+/// Every vote is verified on arrival, so a bad signature is rejected on its
+/// own and cannot spoil the running aggregate. Each validator counts once:
+/// a repeat of its vote is a duplicate, a vote for a different message is a
+/// conflict. Public keys must have passed proof-of-possession checks before
+/// their votes are accumulated (see `verify_pop`); the accumulator does not
+/// defend against rogue keys by itself.
+#[derive(Default)]
+pub struct AggregationAccumulator {
+    aggregates: HashMap<Vec<u8>, RunningAggregate>,
+    votes: HashMap<Vec<u8>, AccumulatedVote>,
+}
+
+impl AggregationAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Verify `vote` and fold it into the aggregate for its message.
+    pub fn add(&mut self, vote: AccumulatedVote) -> Result<VoteOutcome> {
+        if let Some(previous) = self.votes.get(&vote.public_key) {
+            if previous.message == vote.message {
+                return Ok(VoteOutcome::Duplicate);
+            }
+            if !verify(&vote.public_key, &vote.message, &vote.signature)? {
+                bail!("invalid vote signature");
+            }
+            return Ok(VoteOutcome::Conflict(Box::new(ConflictingVotes {
+                first: previous.clone(),
+                second: vote,
+            })));
+        }
+        if !verify(&vote.public_key, &vote.message, &vote.signature)? {
+            bail!("invalid vote signature");
+        }
+
+        let signature = BlstSignature::from_bytes(&vote.signature)
+            .map_err(|e| anyhow!("signature deserialization failed: {:?}", e))?;
+        let public_key = BlstPublicKey::from_bytes(&vote.public_key)
+            .map_err(|e| anyhow!("public key deserialization failed: {:?}", e))?;
+        let stake = match self.aggregates.get_mut(&vote.message) {
+            Some(running) => {
+                running
+                    .signature
+                    .add_signature(&signature, false)
+                    .map_err(|e| anyhow!("failed to add signature: {:?}", e))?;
+                running
+                    .public_key
+                    .add_public_key(&public_key, false)
+                    .map_err(|e| anyhow!("failed to add public key: {:?}", e))?;
+                running.stake = running.stake.saturating_add(vote.stake);
+                running.signers.push(vote.public_key.clone());
+                running.stake
+            }
+            None => {
+                self.aggregates.insert(
+                    vote.message.clone(),
+                    RunningAggregate {
+                        signature: AggregateSignature::from_signature(&signature),
+                        public_key: AggregatePublicKey::from_public_key(&public_key),
+                        stake: vote.stake,
+                        signers: vec![vote.public_key.clone()],
+                    },
+                );
+                vote.stake
+            }
+        };
+        self.votes.insert(vote.public_key.clone(), vote);
+        Ok(VoteOutcome::Added { stake })
+    }
+
+    /// The aggregate for `message` so far, if anyone voted for it.
+    pub fn aggregate(&self, message: &[u8]) -> Option<Aggregate> {
+        self.aggregates
+            .get_key_value(message)
+            .map(|(message, running)| Aggregate {
+                message: message.clone(),
+                signature: running.signature.to_signature().to_bytes().to_vec(),
+                public_key: running.public_key.to_public_key().to_bytes().to_vec(),
+                stake: running.stake,
+                signers: running.signers.clone(),
+            })
+    }
+
+    /// The aggregate with the most stake behind it.
+    pub fn leading(&self) -> Option<Aggregate> {
+        self.aggregates
+            .iter()
+            .max_by(|(a_msg, a), (b_msg, b)| a.stake.cmp(&b.stake).then(b_msg.cmp(a_msg)))
+            .and_then(|(message, _)| self.aggregate(message))
+    }
+
+    /// Stake accumulated for `message`.
+    pub fn stake_for(&self, message: &[u8]) -> u128 {
+        self.aggregates
+            .get(message)
+            .map(|running| running.stake)
+            .unwrap_or(0)
+    }
+
+    /// Whether `public_key` has a counted vote.
+    pub fn has_voted(&self, public_key: &[u8]) -> bool {
+        self.votes.contains_key(public_key)
+    }
+
+    /// Number of counted votes across all messages.
+    pub fn len(&self) -> usize {
+        self.votes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.votes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::BlsKeypair;
+    use crate::{aggregate_public_keys, aggregate_signatures, verify_aggregated};
+
+    fn vote(keypair: &BlsKeypair, message: &[u8], stake: u128) -> AccumulatedVote {
+        AccumulatedVote {
+            public_key: keypair.public_key(),
+            message: message.to_vec(),
+            signature: keypair.sign(message),
+            stake,
+        }
+    }
+
+    #[test]
+    fn running_aggregate_matches_batch_aggregation() {
+        let keypairs: Vec<BlsKeypair> = (0..4).map(|_| BlsKeypair::generate()).collect();
+        let mut accumulator = AggregationAccumulator::new();
+        for (i, keypair) in keypairs.iter().enumerate() {
+            let outcome = accumulator.add(vote(keypair, b"block-a", 10)).unwrap();
+            assert_eq!(
+                outcome,
+                VoteOutcome::Added {
+                    stake: 10 * (i as u128 + 1)
+                }
+            );
+
+            // The aggregate can be taken at any point and verifies.
+            let aggregate = accumulator.aggregate(b"block-a").unwrap();
+            assert!(
+                verify_aggregated(&aggregate.public_key, b"block-a", &aggregate.signature).unwrap()
+            );
+            assert_eq!(aggregate.signers.len(), i + 1);
+        }
+
+        let aggregate = accumulator.aggregate(b"block-a").unwrap();
+        let signatures: Vec<Vec<u8>> = keypairs.iter().map(|k| k.sign(b"block-a")).collect();
+        let public_keys: Vec<Vec<u8>> = keypairs.iter().map(|k| k.public_key()).collect();
+        assert_eq!(
+            aggregate.signature,
+            aggregate_signatures(&signatures).unwrap()
+        );
+        assert_eq!(
+            aggregate.public_key,
+            aggregate_public_keys(&public_keys).unwrap()
+        );
+        assert_eq!(aggregate.stake, 40);
+        assert_eq!(aggregate.signers, public_keys);
+    }
+
+    #[test]
+    fn detects_duplicates_and_conflicts() {
+        let alice = BlsKeypair::generate();
+        let bob = BlsKeypair::generate();
+        let mut accumulator = AggregationAccumulator::new();
+        accumulator.add(vote(&alice, b"block-a", 10)).unwrap();
+
+        assert_eq!(
+            accumulator.add(vote(&alice, b"block-a", 10)).unwrap(),
+            VoteOutcome::Duplicate
+        );
+        let VoteOutcome::Conflict(conflict) =
+            accumulator.add(vote(&alice, b"block-b", 10)).unwrap()
+        else {
+            panic!("expected a conflict");
+        };
+        assert_eq!(conflict.first.message, b"block-a");
+        assert_eq!(conflict.second.message, b"block-b");
+        // Neither the duplicate nor the conflicting vote counted.
+        assert_eq!(accumulator.stake_for(b"block-a"), 10);
+        assert_eq!(accumulator.stake_for(b"block-b"), 0);
+        assert_eq!(accumulator.len(), 1);
+
+        accumulator.add(vote(&bob, b"block-b", 25)).unwrap();
+        let leading = accumulator.leading().unwrap();
+        assert_eq!(leading.message, b"block-b");
+        assert_eq!(leading.stake, 25);
+    }
+
+    #[test]
+    fn rejects_invalid_signatures_without_counting_them() {
+        let alice = BlsKeypair::generate();
+        let bob = BlsKeypair::generate();
+        let mut accumulator = AggregationAccumulator::new();
+
+        let mut forged = vote(&alice, b"block-a", 10);
+        forged.signature = bob.sign(b"block-a");
+        assert!(accumulator.add(forged).is_err());
+        assert!(!accumulator.has_voted(&alice.public_key()));
+
+        // A forged "conflict" is not evidence against a validator either.
+        accumulator.add(vote(&alice, b"block-a", 10)).unwrap();
+        let mut forged = vote(&alice, b"block-b", 10);
+        forged.signature = bob.sign(b"block-b");
+        assert!(accumulator.add(forged).is_err());
+        assert!(accumulator.aggregate(b"block-b").is_none());
+    }
+}
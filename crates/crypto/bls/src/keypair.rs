@@ -1,8 +1,12 @@
+use std::fmt;
+
+use aether_crypto_primitives::SecretBytes;
 use anyhow::{anyhow, Result};
 use blst::min_pk::{
     PublicKey as BlstPublicKey, SecretKey as BlstSecretKey, Signature as BlstSignature,
 };
 use blst::BLST_ERROR;
+use zeroize::Zeroize;
 
 const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
 /// Domain separation tag for proof-of-possession (prevents rogue key attacks).
@@ -34,25 +38,27 @@ impl BlsKeypair {
         let mut rng = rand::thread_rng();
 
         // Generate 32-byte secret key
-        let mut ikm = [0u8; 32];
-        rng.fill_bytes(&mut ikm);
+        let mut ikm = SecretBytes::<32>::zero();
+        rng.fill_bytes(ikm.expose_secret_mut());
 
-        let secret =
-            BlstSecretKey::key_gen(&ikm, &[]).expect("random IKM always valid for key generation");
+        let secret = BlstSecretKey::key_gen(ikm.expose_secret(), &[])
+            .expect("random IKM always valid for key generation");
         let public = secret.sk_to_pk();
 
         BlsKeypair { secret, public }
     }
 
-    /// Create keypair from secret key
+    /// Create keypair from secret key. The passed bytes are wiped.
     #[must_use = "constructing a keypair without binding it is a no-op"]
-    pub fn from_secret(secret: Vec<u8>) -> Result<Self> {
-        if secret.len() != 32 {
-            anyhow::bail!("BLS secret key must be 32 bytes");
-        }
-
-        let secret = BlstSecretKey::from_bytes(&secret)
-            .map_err(|e| anyhow!("invalid secret key bytes: {:?}", e))?;
+    pub fn from_secret(mut secret: Vec<u8>) -> Result<Self> {
+        let parsed = if secret.len() != 32 {
+            Err(anyhow!("BLS secret key must be 32 bytes"))
+        } else {
+            BlstSecretKey::from_bytes(&secret)
+                .map_err(|e| anyhow!("invalid secret key bytes: {:?}", e))
+        };
+        secret.zeroize();
+        let secret = parsed?;
         let public = secret.sk_to_pk();
 
         Ok(BlsKeypair { secret, public })
@@ -75,8 +81,8 @@ impl BlsKeypair {
     /// Get secret key bytes (32-byte scalar)
     #[inline]
     #[must_use]
-    pub fn secret_key(&self) -> Vec<u8> {
-        self.secret.to_bytes().to_vec()
+    pub fn secret_key(&self) -> SecretBytes {
+        SecretBytes::new(self.secret.to_bytes())
     }
 
     /// Generate a proof-of-possession: sign the public key bytes with a distinct DST.
@@ -92,6 +98,15 @@ impl BlsKeypair {
     }
 }
 
+/// Shows the public key only; blst's own `Debug` would print the scalar.
+impl fmt::Debug for BlsKeypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BlsKeypair")
+            .field("public", &self.public.to_bytes())
+            .finish_non_exhaustive()
+    }
+}
+
 /// Verify a proof-of-possession for a BLS public key.
 /// Returns true if the PoP is valid (the key holder proved knowledge of the secret key).
 #[must_use = "discarding a PoP verification result is a security bug"]
@@ -134,15 +149,26 @@ pub fn verify(public_key: &[u8], _message: &[u8], signature: &[u8]) -> Result<bo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aether_crypto_primitives::secret::assert_not_leaked;
 
     #[test]
     fn test_keypair_generation() {
         let keypair = BlsKeypair::generate();
 
-        assert_eq!(keypair.secret_key().len(), 32);
+        assert_eq!(keypair.secret_key().expose_secret().len(), 32);
         assert_eq!(keypair.public_key().len(), 48);
     }
 
+    #[test]
+    fn test_secret_stays_out_of_debug_output() {
+        let keypair = BlsKeypair::from_secret(vec![0x11; 32]).unwrap();
+        let secret = keypair.secret_key();
+        assert_eq!(secret.expose_secret(), &[0x11; 32]);
+        assert_not_leaked(format!("{keypair:?}"), secret.expose_secret());
+        assert_not_leaked(format!("{secret:?}"), secret.expose_secret());
+        assert!(BlsKeypair::from_secret(vec![0x11; 31]).is_err());
+    }
+
     #[test]
     fn test_signing() {
         let keypair = BlsKeypair::generate();
@@ -53,7 +53,9 @@
 //         aggregated_signature: agg_sig,
 //         aggregated_pubkey: agg_pubkey,
 //         total_stake: total_stake,
-//         signers: votes.map(|v| v.validator_pubkey)
+//         epoch: current_epoch,
+//         // bit i set = validator i of the epoch's set signed
+//         signers: bitfield(votes.map(|v| index_in_epoch(v.validator_pubkey)))
 //     }
 // ```
 //
@@ -68,9 +70,12 @@
 // ```
 //
 // OPTIMIZATIONS:
+// - Aggregate incrementally as votes arrive (`AggregationAccumulator`)
 // - Batch verify multiple aggregated votes
-// - Precompute pairing elements
-// - Parallel signature aggregation
+// - Precompute Miller-loop lines for the slot's message (`PreparedMessage`)
+// - Randomly weighted vote sums in parallel (rayon), one final exponentiation
+//   per batch (`verify_votes_batched`); see benches/bls_bench.rs for the
+//   1000-validator / 500ms slot budget
 //
 // SECURITY:
 // - Rogue key attack prevention (proof-of-possession)
@@ -79,13 +84,19 @@
 // OUTPUTS:
 // - Aggregated signature → Block finality proof
 // - Verification result → Consensus state transition
-// - Signer list → Reward distribution
+// - Signer bitfield → Reward distribution
 // ============================================================================
 
+pub mod accumulator;
 pub mod aggregate;
 pub mod keypair;
+pub mod prepared;
 pub mod verify;
 
+pub use accumulator::{
+    AccumulatedVote, Aggregate, AggregationAccumulator, ConflictingVotes, VoteOutcome,
+};
 pub use aggregate::{aggregate_public_keys, aggregate_signatures};
 pub use keypair::{verify_pop, BlsKeypair};
+pub use prepared::{verify_votes_batched, PreparedMessage, ValidatorKeys};
 pub use verify::{verify_aggregated, verify_aggregated_with_pop, verify_batch};
@@ -0,0 +1,366 @@
+use anyhow::{anyhow, bail, Result};
+use blst::min_pk::{PublicKey as BlstPublicKey, Signature as BlstSignature};
+use blst::{blst_fp12, blst_fp6, blst_p1, blst_p1_affine, blst_p2, blst_p2_affine, MultiPoint};
+use rand::RngCore;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// Number of line evaluations blst precomputes for one G2 point.
+const LINES: usize = 68;
+
+/// Bits of each random batching scalar.
+const SCALAR_BITS: usize = 64;
+
+/// A message hashed to G2 with its Miller-loop lines precomputed.
+///
+/// Verification checks e(pk, H(m)) == e(G1, sig). Public keys live in G1, so
+/// the G2 argument that repeats across a slot's votes is H(m): every
+/// validator signs the same block hash. Preparing it once takes the hash and
+/// the line computation (about half a Miller loop) out of every check made
+/// against it; the G1 generator side needs no lines.
+pub struct PreparedMessage {
+    message: Vec<u8>,
+    lines: Box<[blst_fp6]>,
+}
+
+impl PreparedMessage {
+    pub fn new(message: &[u8]) -> Self {
+        let hashed = hash_to_g2(message);
+        let mut lines = vec![blst_fp6::default(); LINES].into_boxed_slice();
+        // SAFETY: `lines` holds the 68 blst_fp6 blst_precompute_lines writes;
+        // `hashed` is a valid affine point.
+        unsafe { blst::blst_precompute_lines(lines.as_mut_ptr(), &hashed) };
+        PreparedMessage {
+            message: message.to_vec(),
+            lines,
+        }
+    }
+
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Verify one signature (or an aggregate against its aggregate key).
+    #[must_use = "discarding a verification result is a security bug"]
+    pub fn verify(&self, public_key: &[u8], signature: &[u8]) -> Result<bool> {
+        let pk = decode_public_key(public_key)?;
+        let sig = decode_signature(signature)?;
+        Ok(self.check(&pk, &sig))
+    }
+
+    /// Verify many signatures on this message with one pairing check.
+    ///
+    /// Returns `Ok(true)` iff every vote is valid. Keys are decoded and
+    /// subgroup-checked on every call; for a known validator set use
+    /// `verify_signers`.
+    #[must_use = "discarding a batch verification result is a security bug"]
+    pub fn verify_votes(&self, votes: &[(&[u8], &[u8])]) -> Result<bool> {
+        if votes.is_empty() {
+            return Ok(true);
+        }
+        let decoded = votes
+            .par_iter()
+            .map(|(pk, sig)| Ok((decode_public_key(pk)?, decode_signature(sig)?)))
+            .collect::<Result<Vec<_>>>()?;
+        let (pks, sigs): (Vec<_>, Vec<_>) = decoded.into_iter().unzip();
+        let (pk, sig) = weighted_sums(&pks, &sigs);
+        Ok(self.check(&pk, &sig))
+    }
+
+    /// Verify `(validator index, signature)` votes against an epoch's
+    /// validated keys with one pairing check. This is the slot hot path:
+    /// only the signatures are decoded.
+    #[must_use = "discarding a batch verification result is a security bug"]
+    pub fn verify_signers(&self, keys: &ValidatorKeys, votes: &[(usize, &[u8])]) -> Result<bool> {
+        if votes.is_empty() {
+            return Ok(true);
+        }
+        let pks = votes
+            .iter()
+            .map(|(index, _)| {
+                keys.get(*index)
+                    .copied()
+                    .ok_or_else(|| anyhow!("validator index {index} out of range"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let sigs = votes
+            .par_iter()
+            .map(|(_, sig)| decode_signature(sig))
+            .collect::<Result<Vec<_>>>()?;
+        let (pk, sig) = weighted_sums(&pks, &sigs);
+        Ok(self.check(&pk, &sig))
+    }
+
+    fn check(&self, pk: &blst_p1_affine, sig: &blst_p2_affine) -> bool {
+        let mut lhs = blst_fp12::default();
+        // SAFETY: `lines` was filled by blst_precompute_lines; `pk` is valid.
+        unsafe { blst::blst_miller_loop_lines(&mut lhs, self.lines.as_ptr(), pk) };
+        final_verify(&lhs, &generator_miller_loop(sig))
+    }
+}
+
+/// An epoch's validator public keys, decoded and subgroup-checked once.
+///
+/// Indexed like the epoch's validator set, so a `SignerBitfield` index maps
+/// straight to its key.
+#[derive(Clone, Default)]
+pub struct ValidatorKeys {
+    keys: Vec<blst_p1_affine>,
+}
+
+impl ValidatorKeys {
+    pub fn new(public_keys: &[Vec<u8>]) -> Result<Self> {
+        let keys = public_keys
+            .par_iter()
+            .map(|pk| decode_public_key(pk))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ValidatorKeys { keys })
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    fn get(&self, index: usize) -> Option<&blst_p1_affine> {
+        self.keys.get(index)
+    }
+}
+
+/// Verify votes over any number of distinct messages with one final
+/// exponentiation.
+///
+/// Votes are grouped by message and randomly weighted; each group collapses
+/// to one Miller loop against its summed keys, the groups' loops run in
+/// parallel and are multiplied together, and a single loop covers the summed
+/// signatures. A slot's votes mostly share one block hash, so this costs a
+/// handful of Miller loops however many validators voted. Returns `Ok(true)`
+/// iff every vote is valid; use `PreparedMessage::verify` per vote to find a
+/// failing one.
+#[must_use = "discarding a batch verification result is a security bug"]
+pub fn verify_votes_batched(votes: &[(&[u8], &[u8], &[u8])]) -> Result<bool> {
+    if votes.is_empty() {
+        return Ok(true);
+    }
+    let decoded = votes
+        .par_iter()
+        .map(|(pk, _, sig)| Ok((decode_public_key(pk)?, decode_signature(sig)?)))
+        .collect::<Result<Vec<_>>>()?;
+    let mut groups: HashMap<&[u8], (Vec<blst_p1_affine>, Vec<blst_p2_affine>)> = HashMap::new();
+    for ((_, message, _), (pk, sig)) in votes.iter().zip(decoded) {
+        let group = groups.entry(message).or_default();
+        group.0.push(pk);
+        group.1.push(sig);
+    }
+
+    let partials: Vec<(blst_fp12, blst_p2_affine)> = groups
+        .into_par_iter()
+        .map(|(message, (pks, sigs))| {
+            let (pk, sig) = weighted_sums(&pks, &sigs);
+            let mut loop_out = blst_fp12::default();
+            // SAFETY: both arguments are valid affine points.
+            unsafe { blst::blst_miller_loop(&mut loop_out, &hash_to_g2(message), &pk) };
+            (loop_out, sig)
+        })
+        .collect();
+
+    let mut product = partials[0].0;
+    let mut sig_sum = blst_p2::default();
+    // SAFETY: all operands are valid field elements / points, and every
+    // output is a fresh copy distinct from its inputs.
+    unsafe {
+        blst::blst_p2_from_affine(&mut sig_sum, &partials[0].1);
+        for (loop_out, sig) in &partials[1..] {
+            let acc = product;
+            blst::blst_fp12_mul(&mut product, &acc, loop_out);
+            let acc = sig_sum;
+            blst::blst_p2_add_or_double_affine(&mut sig_sum, &acc, sig);
+        }
+    }
+    Ok(final_verify(
+        &product,
+        &generator_miller_loop(&to_affine_p2(&sig_sum)),
+    ))
+}
+
+fn decode_public_key(public_key: &[u8]) -> Result<blst_p1_affine> {
+    if public_key.len() != 48 {
+        bail!("BLS public key must be 48 bytes");
+    }
+    let pk = BlstPublicKey::key_validate(public_key)
+        .map_err(|e| anyhow!("invalid public key: {:?}", e))?;
+    let affine: &blst_p1_affine = (&pk).into();
+    Ok(*affine)
+}
+
+fn decode_signature(signature: &[u8]) -> Result<blst_p2_affine> {
+    if signature.len() != 96 {
+        bail!("BLS signature must be 96 bytes");
+    }
+    let sig = BlstSignature::sig_validate(signature, true)
+        .map_err(|e| anyhow!("invalid signature: {:?}", e))?;
+    let affine: &blst_p2_affine = (&sig).into();
+    Ok(*affine)
+}
+
+/// Σ rᵢ·pkᵢ and Σ rᵢ·sigᵢ for fresh random 64-bit rᵢ, so invalid votes
+/// cannot cancel each other out. blst's Pippenger multi-scalar
+/// multiplication spreads each sum across its own thread pool.
+fn weighted_sums(
+    pks: &[blst_p1_affine],
+    sigs: &[blst_p2_affine],
+) -> (blst_p1_affine, blst_p2_affine) {
+    let mut scalars = vec![0u8; pks.len() * SCALAR_BITS / 8];
+    rand::thread_rng().fill_bytes(&mut scalars);
+    for scalar in scalars.chunks_mut(SCALAR_BITS / 8) {
+        scalar[0] |= 1; // ensure nonzero
+    }
+    let pk: blst_p1 = pks.mult(&scalars, SCALAR_BITS);
+    let sig: blst_p2 = sigs.mult(&scalars, SCALAR_BITS);
+    let mut pk_affine = blst_p1_affine::default();
+    // SAFETY: `pk` is a valid projective point.
+    unsafe { blst::blst_p1_to_affine(&mut pk_affine, &pk) };
+    (pk_affine, to_affine_p2(&sig))
+}
+
+fn hash_to_g2(message: &[u8]) -> blst_p2_affine {
+    let mut point = blst_p2::default();
+    // SAFETY: pointers and lengths come from live slices; no augmentation.
+    unsafe {
+        blst::blst_hash_to_g2(
+            &mut point,
+            message.as_ptr(),
+            message.len(),
+            DST.as_ptr(),
+            DST.len(),
+            std::ptr::null(),
+            0,
+        )
+    };
+    to_affine_p2(&point)
+}
+
+fn to_affine_p2(point: &blst_p2) -> blst_p2_affine {
+    let mut out = blst_p2_affine::default();
+    // SAFETY: `point` is a valid projective point.
+    unsafe { blst::blst_p2_to_affine(&mut out, point) };
+    out
+}
+
+/// Miller loop of e(G1, sig).
+fn generator_miller_loop(sig: &blst_p2_affine) -> blst_fp12 {
+    let mut out = blst_fp12::default();
+    // SAFETY: blst_p1_affine_generator points to a static constant; `sig` is
+    // a valid affine point.
+    unsafe { blst::blst_miller_loop(&mut out, sig, blst::blst_p1_affine_generator()) };
+    out
+}
+
+/// Whether two Miller loop outputs agree after the final exponentiation.
+fn final_verify(lhs: &blst_fp12, rhs: &blst_fp12) -> bool {
+    // SAFETY: both are valid Miller loop outputs.
+    unsafe { blst::blst_fp12_finalverify(lhs, rhs) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aggregate::{aggregate_public_keys, aggregate_signatures};
+    use crate::keypair::BlsKeypair;
+    use std::time::{Duration, Instant};
+
+    fn votes(n: usize, message: &[u8]) -> (Vec<Vec<u8>>, Vec<Vec<u8>>) {
+        let keypairs: Vec<BlsKeypair> = (0..n).map(|_| BlsKeypair::generate()).collect();
+        (
+            keypairs.iter().map(|k| k.public_key()).collect(),
+            keypairs.iter().map(|k| k.sign(message)).collect(),
+        )
+    }
+
+    fn pairs<'a>(pks: &'a [Vec<u8>], sigs: &'a [Vec<u8>]) -> Vec<(&'a [u8], &'a [u8])> {
+        pks.iter()
+            .zip(sigs)
+            .map(|(pk, sig)| (pk.as_slice(), sig.as_slice()))
+            .collect()
+    }
+
+    #[test]
+    fn prepared_message_verifies_single_and_aggregate() {
+        let (pks, sigs) = votes(3, b"block");
+        let prepared = PreparedMessage::new(b"block");
+        assert!(prepared.verify(&pks[0], &sigs[0]).unwrap());
+        assert!(!prepared.verify(&pks[1], &sigs[0]).unwrap());
+        assert!(!PreparedMessage::new(b"other")
+            .verify(&pks[0], &sigs[0])
+            .unwrap());
+
+        let agg_pk = aggregate_public_keys(&pks).unwrap();
+        let agg_sig = aggregate_signatures(&sigs).unwrap();
+        assert!(prepared.verify(&agg_pk, &agg_sig).unwrap());
+        assert!(prepared.verify(&[0u8; 48], &sigs[0]).is_err());
+    }
+
+    #[test]
+    fn batch_catches_one_bad_vote() {
+        let (pks, mut sigs) = votes(20, b"block");
+        let prepared = PreparedMessage::new(b"block");
+        let keys = ValidatorKeys::new(&pks).unwrap();
+        assert!(prepared.verify_votes(&pairs(&pks, &sigs)).unwrap());
+        assert!(prepared.verify_votes(&[]).unwrap());
+
+        // Swapping two valid signatures keeps the plain sums equal; only the
+        // random weights expose it.
+        sigs.swap(3, 4);
+        assert!(!prepared.verify_votes(&pairs(&pks, &sigs)).unwrap());
+
+        let signers: Vec<(usize, &[u8])> = sigs.iter().map(Vec::as_slice).enumerate().collect();
+        assert!(!prepared.verify_signers(&keys, &signers).unwrap());
+        assert!(prepared.verify_signers(&keys, &signers[5..]).unwrap());
+        assert!(prepared.verify_signers(&keys, &[(20, &sigs[0])]).is_err());
+    }
+
+    #[test]
+    fn batched_votes_across_messages() {
+        let (pks_a, sigs_a) = votes(5, b"block-a");
+        let (pks_b, sigs_b) = votes(3, b"block-b");
+        let mut batch: Vec<(&[u8], &[u8], &[u8])> = Vec::new();
+        for (pk, sig) in pairs(&pks_a, &sigs_a) {
+            batch.push((pk, b"block-a", sig));
+        }
+        for (pk, sig) in pairs(&pks_b, &sigs_b) {
+            batch.push((pk, b"block-b", sig));
+        }
+        assert!(verify_votes_batched(&batch).unwrap());
+
+        // A signature presented for the wrong message fails the batch.
+        batch[0].1 = b"block-b";
+        assert!(!verify_votes_batched(&batch).unwrap());
+        assert!(verify_votes_batched(&[]).unwrap());
+    }
+
+    /// Every one of 1000 validators' individual votes on a block must verify
+    /// inside one 500ms slot, even on a single core. Run with
+    /// `--release --ignored`; see benches/bls_bench.rs for the breakdown.
+    #[test]
+    #[ignore]
+    fn test_slot_vote_budget() {
+        const VALIDATORS: usize = 1000;
+        const SLOT: Duration = Duration::from_millis(500);
+
+        let (pks, sigs) = votes(VALIDATORS, b"slot-block");
+        let keys = ValidatorKeys::new(&pks).unwrap();
+        let signers: Vec<(usize, &[u8])> = sigs.iter().map(Vec::as_slice).enumerate().collect();
+
+        let start = Instant::now();
+        let prepared = PreparedMessage::new(b"slot-block");
+        assert!(prepared.verify_signers(&keys, &signers).unwrap());
+        let elapsed = start.elapsed();
+        println!("{VALIDATORS} votes verified in {elapsed:?}");
+        assert!(elapsed < SLOT, "{elapsed:?} exceeds the {SLOT:?} slot");
+    }
+}
@@ -14,6 +14,9 @@ pub enum KesError {
     #[error("invalid signature")]
     InvalidSignature,
 
+    #[error("invalid double-sign evidence: {0}")]
+    InvalidEvidence(&'static str),
+
     #[error("key generation failed: {0}")]
     KeyGeneration(String),
 }
@@ -0,0 +1,253 @@
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{KesError, Result};
+use crate::signature::{KesSignature, KesVerificationKey};
+
+const BLOCK_DOMAIN: &[u8] = b"aether-block-kes";
+
+/// Upper bound on tree depth accepted when decoding evidence.
+const MAX_DEPTH: u32 = 31;
+
+/// Message a block header's KES signature covers.
+#[must_use]
+pub fn block_signing_message(slot: u64, block_hash: &[u8; 32]) -> Vec<u8> {
+    let mut message = Vec::with_capacity(BLOCK_DOMAIN.len() + 8 + 32);
+    message.extend_from_slice(BLOCK_DOMAIN);
+    message.extend_from_slice(&slot.to_le_bytes());
+    message.extend_from_slice(block_hash);
+    message
+}
+
+/// One side of a double-sign: a block hash and the leaf's Ed25519
+/// signature over `block_signing_message(slot, block_hash)`.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignedHeader {
+    pub block_hash: [u8; 32],
+    /// Ed25519 signature bytes (64 bytes as Vec for serde compatibility).
+    pub signature: Vec<u8>,
+}
+
+/// Proof that a KES key signed two different blocks for the same slot.
+///
+/// Both signatures come from the same period's leaf, so the leaf key and
+/// authentication path are carried once. The evidence verifies against the
+/// offender's registered `KesVerificationKey` alone: no chain state, block
+/// bodies or signer cooperation are needed. Headers are kept in block hash
+/// order, so the same offense always yields the same evidence.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DoubleSignEvidence {
+    pub slot: u64,
+    pub period: u32,
+    pub leaf_pubkey: [u8; 32],
+    pub auth_path: Vec<[u8; 32]>,
+    pub headers: [SignedHeader; 2],
+}
+
+impl DoubleSignEvidence {
+    /// Build evidence from two signatures by `vk` over conflicting headers
+    /// for `slot`. Fails unless both signatures verify, share a period, and
+    /// sign different blocks.
+    pub fn from_signatures(
+        vk: &KesVerificationKey,
+        slot: u64,
+        (hash_a, sig_a): (&[u8; 32], &KesSignature),
+        (hash_b, sig_b): (&[u8; 32], &KesSignature),
+    ) -> Result<Self> {
+        if hash_a == hash_b {
+            return Err(KesError::InvalidEvidence("headers do not conflict"));
+        }
+        if sig_a.period != sig_b.period
+            || sig_a.leaf_pubkey != sig_b.leaf_pubkey
+            || sig_a.auth_path != sig_b.auth_path
+        {
+            return Err(KesError::InvalidEvidence(
+                "signatures are not from the same period key",
+            ));
+        }
+        if !sig_a.verify(vk, &block_signing_message(slot, hash_a))
+            || !sig_b.verify(vk, &block_signing_message(slot, hash_b))
+        {
+            return Err(KesError::InvalidSignature);
+        }
+
+        let mut headers = [
+            SignedHeader {
+                block_hash: *hash_a,
+                signature: sig_a.signature.clone(),
+            },
+            SignedHeader {
+                block_hash: *hash_b,
+                signature: sig_b.signature.clone(),
+            },
+        ];
+        headers.sort_by_key(|header| header.block_hash);
+        Ok(DoubleSignEvidence {
+            slot,
+            period: sig_a.period,
+            leaf_pubkey: sig_a.leaf_pubkey,
+            auth_path: sig_a.auth_path.clone(),
+            headers,
+        })
+    }
+
+    /// Check the evidence against the accused key: two different blocks for
+    /// `slot`, both signed by the leaf `vk` commits to for `period`.
+    #[must_use = "discarding an evidence verification result is a security bug"]
+    pub fn verify(&self, vk: &KesVerificationKey) -> bool {
+        if self.headers[0].block_hash >= self.headers[1].block_hash {
+            return false;
+        }
+        self.headers.iter().all(|header| {
+            self.signature(header)
+                .verify(vk, &block_signing_message(self.slot, &header.block_hash))
+        })
+    }
+
+    /// The KES signature for one of the two headers.
+    #[must_use]
+    pub fn signature(&self, header: &SignedHeader) -> KesSignature {
+        KesSignature {
+            period: self.period,
+            signature: header.signature.clone(),
+            leaf_pubkey: self.leaf_pubkey,
+            auth_path: self.auth_path.clone(),
+        }
+    }
+
+    /// Canonical encoding for inclusion in blocks and transactions.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer =
+            CanonicalWriter::with_capacity(80 + 32 * self.auth_path.len() + 2 * (32 + 64));
+        writer
+            .put_u64(self.slot)
+            .put_u32(self.period)
+            .put_fixed(&self.leaf_pubkey)
+            .put_u32(self.auth_path.len() as u32);
+        for node in &self.auth_path {
+            writer.put_fixed(node);
+        }
+        for header in &self.headers {
+            writer
+                .put_fixed(&header.block_hash)
+                .put_bytes(&header.signature);
+        }
+        writer.finish()
+    }
+
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = CanonicalReader::new(bytes);
+        let slot = reader.take_u64()?;
+        let period = reader.take_u32()?;
+        let leaf_pubkey = reader.take_fixed()?;
+        let depth = reader.take_u32()?;
+        if depth > MAX_DEPTH {
+            anyhow::bail!("evidence auth path too long: {depth}");
+        }
+        let auth_path = (0..depth)
+            .map(|_| reader.take_fixed())
+            .collect::<std::result::Result<_, _>>()?;
+        let mut header = || -> anyhow::Result<SignedHeader> {
+            Ok(SignedHeader {
+                block_hash: reader.take_fixed()?,
+                signature: reader.take_bytes()?.to_vec(),
+            })
+        };
+        let headers = [header()?, header()?];
+        reader.finish()?;
+        Ok(DoubleSignEvidence {
+            slot,
+            period,
+            leaf_pubkey,
+            auth_path,
+            headers,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::evolution::KesKey;
+
+    fn sign(key: &mut KesKey, period: u32, slot: u64, hash: &[u8; 32]) -> KesSignature {
+        key.sign(period, &block_signing_message(slot, hash))
+            .unwrap()
+    }
+
+    #[test]
+    fn extracts_self_verifying_evidence() {
+        let mut key = KesKey::from_seed([2u8; 32], 16);
+        let vk = key.verification_key();
+        let sig_a = sign(&mut key, 3, 31, &[0xbb; 32]);
+        let sig_b = sign(&mut key, 3, 31, &[0xaa; 32]);
+
+        let evidence = DoubleSignEvidence::from_signatures(
+            &vk,
+            31,
+            (&[0xbb; 32], &sig_a),
+            (&[0xaa; 32], &sig_b),
+        )
+        .unwrap();
+        assert!(evidence.verify(&vk));
+        assert_eq!(evidence.headers[0].block_hash, [0xaa; 32]);
+        // Argument order does not change the evidence.
+        let swapped = DoubleSignEvidence::from_signatures(
+            &vk,
+            31,
+            (&[0xaa; 32], &sig_b),
+            (&[0xbb; 32], &sig_a),
+        )
+        .unwrap();
+        assert_eq!(swapped, evidence);
+
+        let bytes = evidence.encode();
+        assert_eq!(DoubleSignEvidence::decode(&bytes).unwrap(), evidence);
+        assert!(DoubleSignEvidence::decode(&bytes[..bytes.len() - 1]).is_err());
+
+        // Only the offender's key convicts.
+        let other = KesKey::from_seed([3u8; 32], 16).verification_key();
+        assert!(!evidence.verify(&other));
+        // Nor does it survive moving to another slot or duplicating a header.
+        let mut moved = evidence.clone();
+        moved.slot = 32;
+        assert!(!moved.verify(&vk));
+        let mut duplicated = evidence;
+        duplicated.headers[1] = duplicated.headers[0].clone();
+        assert!(!duplicated.verify(&vk));
+    }
+
+    #[test]
+    fn rejects_non_equivocations() {
+        let mut key = KesKey::from_seed([2u8; 32], 16);
+        let vk = key.verification_key();
+        let sig = sign(&mut key, 3, 31, &[0xaa; 32]);
+        let later = sign(&mut key, 4, 41, &[0xbb; 32]);
+
+        // Same block twice.
+        assert_eq!(
+            DoubleSignEvidence::from_signatures(&vk, 31, (&[0xaa; 32], &sig), (&[0xaa; 32], &sig)),
+            Err(KesError::InvalidEvidence("headers do not conflict"))
+        );
+        // Different periods.
+        assert!(DoubleSignEvidence::from_signatures(
+            &vk,
+            31,
+            (&[0xaa; 32], &sig),
+            (&[0xbb; 32], &later)
+        )
+        .is_err());
+        // A signature for a different slot.
+        let mut forged = sig.clone();
+        forged.signature = later.signature.clone();
+        assert_eq!(
+            DoubleSignEvidence::from_signatures(
+                &vk,
+                31,
+                (&[0xaa; 32], &sig),
+                (&[0xbb; 32], &forged)
+            ),
+            Err(KesError::InvalidSignature)
+        );
+    }
+}
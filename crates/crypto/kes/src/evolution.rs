@@ -1,40 +1,55 @@
+use std::fmt;
+
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use aether_crypto_primitives::SecretBytes;
 use ed25519_dalek::{Signer, SigningKey};
-use rand::rngs::OsRng;
-use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use subtle::ConstantTimeEq;
-use zeroize::Zeroize;
+use zeroize::Zeroizing;
 
 use crate::error::{KesError, Result};
 use crate::signature::{KesSignature, KesVerificationKey};
 
-/// Key-Evolving Signature (KES) scheme using a binary tree of Ed25519 keypairs.
+/// Key-Evolving Signature (KES) scheme: the binary sum composition of
+/// Ed25519 (MMM, as in Cardano's Praos protocol).
+///
+/// A tree of `depth` levels covers `2^depth` periods, one Ed25519 leaf key
+/// per period. A subtree's verification key is the hash of its children's
+/// and the root is the public key. Seeds split the same way: a subtree's
+/// seed derives the seeds of its two children, so the whole tree follows
+/// from the root seed.
 ///
-/// Based on the MMM (Malkin-Micciancio-Miner) sum composition construction
-/// used in Cardano's Praos protocol.
+/// The key holds only what the current period needs, O(depth) in total:
+/// - the current leaf's secret
+/// - for every level where the current path goes left, the seed of the
+///   right subtree still to come
+/// - the verification keys of the siblings along the current path, which
+///   are the signature's authentication path
 ///
-/// The key tree has `depth` levels, supporting `2^depth` time periods.
-/// At each period, only one leaf keypair is active. When the key evolves
-/// to the next period, the old leaf's secret key is securely erased,
-/// providing forward secrecy: compromise of the current key cannot forge
-/// signatures for past periods.
+/// `evolve` moves to the next period and zeroizes the old leaf secret and
+/// the seed it was derived from, so compromise of the current key cannot
+/// forge signatures for past periods.
 ///
 /// Signature structure:
 /// - Ed25519 signature from the active leaf keypair (64 bytes)
 /// - Authentication path: sibling hashes from leaf to root (depth * 32 bytes)
 /// - Active leaf's public key (32 bytes)
 ///
-/// Verification:
-/// 1. Verify the Ed25519 signature against the leaf public key
-/// 2. Reconstruct the Merkle root from the leaf public key hash + auth path
-/// 3. Compare against the stored root (verification key)
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Verification derives the period's public key from the leaf key and the
+/// path, and compares it to the root.
+///
+/// Secrets are held as `SecretBytes`, so they are wiped as soon as they are
+/// replaced or the key is dropped. The only encoding of the key is the
+/// sealed one in `storage`; `Debug` shows the public state only.
 pub struct KesKey {
-    /// All leaf signing keys. Evolved (erased) keys are set to None.
-    leaves: Vec<Option<[u8; 32]>>,
-    /// All leaf public keys (always available for auth path computation).
-    leaf_pubkeys: Vec<[u8; 32]>,
-    /// Merkle root of the leaf public key hashes.
+    /// Ed25519 seed of the current period's leaf.
+    leaf_secret: SecretBytes,
+    /// Per level, bottom-up: seed of the right subtree while the current
+    /// path goes left at that level.
+    right_seeds: Vec<Option<SecretBytes>>,
+    /// Per level, bottom-up: verification key of the current path's sibling.
+    auth_path: Vec<[u8; 32]>,
+    /// Root of the tree (verification key).
     root: [u8; 32],
     /// Current period (monotonically increasing).
     current_period: u32,
@@ -44,67 +59,37 @@ pub struct KesKey {
 
 impl KesKey {
     /// Generate a new KES key supporting `max_periods` time periods.
-    /// `max_periods` is rounded up to the next power of 2.
+    /// `max_periods` is rounded up to the next power of 2, at most 2^31.
     #[must_use]
     pub fn generate(max_periods: u32) -> Self {
-        let max_periods = max_periods.max(2);
-        let depth = (max_periods as f64).log2().ceil() as u32;
-        let num_leaves = 1u32 << depth;
-
-        let mut leaves = Vec::with_capacity(num_leaves as usize);
-        let mut leaf_pubkeys = Vec::with_capacity(num_leaves as usize);
-
-        for _ in 0..num_leaves {
-            let signing_key = SigningKey::generate(&mut OsRng);
-            let verifying_key = signing_key.verifying_key();
-            leaves.push(Some(signing_key.to_bytes()));
-            leaf_pubkeys.push(verifying_key.to_bytes());
-        }
-
-        let root = compute_merkle_root(&leaf_pubkeys);
-
-        KesKey {
-            leaves,
-            leaf_pubkeys,
-            root,
-            current_period: 0,
-            depth,
-        }
+        Self::from_secret_seed(SecretBytes::random(), max_periods)
     }
 
     /// Create a KES key from an explicit seed (deterministic).
     #[must_use]
     pub fn from_seed(seed: [u8; 32], max_periods: u32) -> Self {
-        let max_periods = max_periods.max(2);
-        let depth = (max_periods as f64).log2().ceil() as u32;
-        let num_leaves = 1u32 << depth;
-
-        let mut leaves = Vec::with_capacity(num_leaves as usize);
-        let mut leaf_pubkeys = Vec::with_capacity(num_leaves as usize);
-
-        for i in 0..num_leaves {
-            // Derive each leaf's secret deterministically from seed
-            let mut hasher = Sha256::new();
-            hasher.update(seed);
-            hasher.update(b"kes-leaf");
-            hasher.update(i.to_le_bytes());
-            let leaf_seed: [u8; 32] = hasher.finalize().into();
-
-            let signing_key = SigningKey::from_bytes(&leaf_seed);
-            let verifying_key = signing_key.verifying_key();
-            leaves.push(Some(leaf_seed));
-            leaf_pubkeys.push(verifying_key.to_bytes());
-        }
+        Self::from_secret_seed(SecretBytes::new(seed), max_periods)
+    }
 
-        let root = compute_merkle_root(&leaf_pubkeys);
+    fn from_secret_seed(seed: SecretBytes, max_periods: u32) -> Self {
+        let depth = tree_depth(max_periods);
 
-        KesKey {
-            leaves,
-            leaf_pubkeys,
-            root,
+        let mut key = KesKey {
+            leaf_secret: SecretBytes::zero(),
+            right_seeds: vec![None; depth as usize],
+            auth_path: vec![[0u8; 32]; depth as usize],
+            root: [0u8; 32],
             current_period: 0,
             depth,
-        }
+        };
+        key.descend(seed, depth);
+
+        // Period 0 is the leftmost leaf: every sibling is on the right.
+        key.root = key.auth_path.iter().fold(
+            hash_leaf(&leaf_pubkey(&key.leaf_secret)),
+            |node, sibling| hash_node(&node, sibling),
+        );
+        key
     }
 
     /// Maximum number of supported periods.
@@ -131,8 +116,23 @@ impl KesKey {
     ///
     /// The period must be >= current_period (forward only).
     /// Signing at a period automatically evolves the key to that period,
-    /// erasing all leaf keys for periods < `period`.
+    /// erasing the secrets of all periods before `period`.
     pub fn sign(&mut self, period: u32, message: &[u8]) -> Result<KesSignature> {
+        self.evolve_to(period)?;
+
+        let signing_key = SigningKey::from_bytes(self.leaf_secret.expose_secret());
+        let ed_signature = signing_key.sign(message);
+
+        Ok(KesSignature {
+            period,
+            signature: ed_signature.to_bytes().to_vec(),
+            leaf_pubkey: signing_key.verifying_key().to_bytes(),
+            auth_path: self.auth_path.clone(),
+        })
+    }
+
+    /// Evolve forward to `period` (a no-op at the current period).
+    pub fn evolve_to(&mut self, period: u32) -> Result<()> {
         if period >= self.max_periods() {
             return Err(KesError::PeriodOutOfRange {
                 requested: period,
@@ -147,94 +147,207 @@ impl KesKey {
             });
         }
 
-        // Evolve: erase all keys for periods before `period`
-        self.evolve_to(period);
+        while self.current_period < period {
+            self.evolve()?;
+        }
+        Ok(())
+    }
 
-        // Get the active leaf's signing key
-        let leaf_secret = self.leaves[period as usize]
-            .as_ref()
-            .ok_or(KesError::KeyErased { period })?;
+    /// Move to the next period, erasing the current leaf secret.
+    ///
+    /// The path to the next leaf turns right at the lowest level where the
+    /// current one went left. The subtree being left becomes the sibling
+    /// there, and the right subtree's stored seed is expanded down to its
+    /// leftmost leaf and then erased.
+    pub fn evolve(&mut self) -> Result<()> {
+        let next = self.current_period + 1;
+        if next >= self.max_periods() {
+            return Err(KesError::PeriodOutOfRange {
+                requested: next,
+                max_periods: self.max_periods(),
+            });
+        }
 
-        let signing_key = SigningKey::from_bytes(leaf_secret);
-        let ed_signature = signing_key.sign(message);
+        let level = self.current_period.trailing_ones() as usize;
+        let right = self.right_seeds[level].take().ok_or(KesError::KeyErased {
+            period: self.current_period,
+        })?;
 
-        // Build authentication path (sibling hashes from leaf to root)
-        let auth_path = compute_auth_path(&self.leaf_pubkeys, period as usize);
+        // Below `level` the current path only went right, so the subtree
+        // being left hashes up with its siblings on the left.
+        let left = self.auth_path[..level].iter().fold(
+            hash_leaf(&leaf_pubkey(&self.leaf_secret)),
+            |node, sibling| hash_node(sibling, &node),
+        );
+        self.auth_path[level] = left;
+        self.descend(right, level as u32);
+        self.current_period = next;
+        Ok(())
+    }
 
-        Ok(KesSignature {
-            period,
-            signature: ed_signature.to_bytes().to_vec(),
-            leaf_pubkey: self.leaf_pubkeys[period as usize],
-            auth_path,
-        })
+    /// Canonical encoding of the full secret state, for sealed storage.
+    pub(crate) fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut writer = CanonicalWriter::with_capacity(72 + 65 * self.depth as usize);
+        writer
+            .put_u32(self.depth)
+            .put_u32(self.current_period)
+            .put_fixed(&self.root)
+            .put_fixed(self.leaf_secret.expose_secret());
+        for (seed, sibling) in self.right_seeds.iter().zip(&self.auth_path) {
+            match seed {
+                Some(seed) => writer.put_u8(1).put_fixed(seed.expose_secret()),
+                None => writer.put_u8(0),
+            };
+            writer.put_fixed(sibling);
+        }
+        Zeroizing::new(writer.finish())
     }
 
-    /// Evolve the key to a target period, securely erasing past keys.
-    fn evolve_to(&mut self, target_period: u32) {
-        for i in self.current_period..target_period {
-            if let Some(ref mut key_bytes) = self.leaves[i as usize] {
-                key_bytes.zeroize();
+    /// Decode `to_bytes` output, checking that the state is one `evolve`
+    /// can reach: seeds exactly where the path goes left, and a path that
+    /// leads from the current leaf to the root.
+    pub(crate) fn from_bytes(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut reader = CanonicalReader::new(bytes);
+        let depth = reader.take_u32()?;
+        if depth == 0 || depth >= u32::BITS {
+            anyhow::bail!("KES key depth {depth} out of range");
+        }
+        let mut key = KesKey {
+            current_period: reader.take_u32()?,
+            root: reader.take_fixed()?,
+            leaf_secret: SecretBytes::new(reader.take_fixed()?),
+            right_seeds: Vec::with_capacity(depth as usize),
+            auth_path: Vec::with_capacity(depth as usize),
+            depth,
+        };
+        if key.current_period >= key.max_periods() {
+            anyhow::bail!("KES key period {} out of range", key.current_period);
+        }
+        for level in 0..depth {
+            let seed = match reader.take_u8()? {
+                0 => None,
+                1 => Some(SecretBytes::new(reader.take_fixed()?)),
+                flag => anyhow::bail!("invalid KES seed flag {flag}"),
+            };
+            let goes_left = (key.current_period >> level) & 1 == 0;
+            if seed.is_some() != goes_left {
+                anyhow::bail!("KES key seeds do not match period {}", key.current_period);
             }
-            self.leaves[i as usize] = None;
+            key.right_seeds.push(seed);
+            key.auth_path.push(reader.take_fixed()?);
         }
-        self.current_period = target_period;
+        reader.finish()?;
+        if !verify_auth_path(
+            &leaf_pubkey(&key.leaf_secret),
+            key.current_period as usize,
+            &key.auth_path,
+            &key.root,
+        ) {
+            anyhow::bail!("KES key state does not match its root");
+        }
+        Ok(key)
     }
-}
 
-impl Drop for KesKey {
-    fn drop(&mut self) {
-        // Zeroize all remaining secret key material
-        for key_bytes in self.leaves.iter_mut().flatten() {
-            key_bytes.zeroize();
+    /// Walk from the root of a subtree of height `height` down to its
+    /// leftmost leaf, recording the right siblings and their seeds on the
+    /// way, and make that leaf current.
+    fn descend(&mut self, mut seed: SecretBytes, height: u32) {
+        for level in (0..height as usize).rev() {
+            let (left, right) = split_seed(&seed);
+            self.auth_path[level] = subtree_root(&right, level as u32);
+            self.right_seeds[level] = Some(right);
+            seed = left;
         }
+        self.leaf_secret = seed;
+    }
+}
+
+impl fmt::Debug for KesKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KesKey")
+            .field("root", &self.root)
+            .field("current_period", &self.current_period)
+            .field("depth", &self.depth)
+            .finish_non_exhaustive()
+    }
+}
+
+/// KES period containing `slot` when each period lasts `slots_per_period`
+/// slots. Saturates at `u32::MAX`, which no key supports.
+#[must_use]
+pub fn period_for_slot(slot: u64, slots_per_period: u64) -> u32 {
+    u32::try_from(slot / slots_per_period.max(1)).unwrap_or(u32::MAX)
+}
+
+/// Depth of the smallest tree covering `max_periods`, between 1 and 31.
+/// Depth 32 would overflow `KesKey::max_periods`; `from_bytes` rejects it too.
+fn tree_depth(max_periods: u32) -> u32 {
+    max_periods
+        .clamp(2, 1 << (u32::BITS - 1))
+        .next_power_of_two()
+        .trailing_zeros()
+}
+
+/// Seeds of a subtree's left and right children.
+fn split_seed(seed: &SecretBytes) -> (SecretBytes, SecretBytes) {
+    let child = |side: u8| {
+        let mut h = Sha256::new();
+        h.update(b"kes-split");
+        h.update([side]);
+        h.update(seed.expose_secret());
+        SecretBytes::new(h.finalize().into())
+    };
+    (child(0), child(1))
+}
+
+fn leaf_pubkey(seed: &SecretBytes) -> [u8; 32] {
+    SigningKey::from_bytes(seed.expose_secret())
+        .verifying_key()
+        .to_bytes()
+}
+
+/// Verification key of the subtree of height `height` grown from `seed`.
+fn subtree_root(seed: &SecretBytes, height: u32) -> [u8; 32] {
+    if height == 0 {
+        return hash_leaf(&leaf_pubkey(seed));
     }
+    let (left, right) = split_seed(seed);
+    hash_node(
+        &subtree_root(&left, height - 1),
+        &subtree_root(&right, height - 1),
+    )
+}
+
+fn hash_leaf(pubkey: &[u8; 32]) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update([0x00]);
+    h.update(pubkey);
+    h.finalize().into()
+}
+
+fn hash_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut h = Sha256::new();
+    h.update([0x01]);
+    h.update(left);
+    h.update(right);
+    h.finalize().into()
 }
 
 /// Compute the Merkle root from leaf public keys.
-fn compute_merkle_root(leaf_pubkeys: &[[u8; 32]]) -> [u8; 32] {
-    // Hash each leaf public key
-    let mut current_level: Vec<[u8; 32]> = leaf_pubkeys
-        .iter()
-        .map(|pk| {
-            let mut h = Sha256::new();
-            h.update([0x00]);
-            h.update(pk);
-            h.finalize().into()
-        })
-        .collect();
+pub fn compute_merkle_root(leaf_pubkeys: &[[u8; 32]]) -> [u8; 32] {
+    let mut current_level: Vec<[u8; 32]> = leaf_pubkeys.iter().map(hash_leaf).collect();
 
     // Build tree bottom-up
     while current_level.len() > 1 {
-        let mut next_level = Vec::with_capacity(current_level.len() / 2);
-        for pair in current_level.chunks(2) {
-            let mut h = Sha256::new();
-            h.update([0x01]);
-            h.update(pair[0]);
-            if pair.len() > 1 {
-                h.update(pair[1]);
-            } else {
-                h.update(pair[0]); // duplicate if odd
-            }
-            next_level.push(h.finalize().into());
-        }
-        current_level = next_level;
+        current_level = next_level(&current_level);
     }
 
     current_level[0]
 }
 
 /// Compute the authentication path (sibling hashes) for a given leaf index.
-fn compute_auth_path(leaf_pubkeys: &[[u8; 32]], leaf_index: usize) -> Vec<[u8; 32]> {
-    // Hash each leaf public key
-    let mut current_level: Vec<[u8; 32]> = leaf_pubkeys
-        .iter()
-        .map(|pk| {
-            let mut h = Sha256::new();
-            h.update([0x00]);
-            h.update(pk);
-            h.finalize().into()
-        })
-        .collect();
+pub fn compute_auth_path(leaf_pubkeys: &[[u8; 32]], leaf_index: usize) -> Vec<[u8; 32]> {
+    let mut current_level: Vec<[u8; 32]> = leaf_pubkeys.iter().map(hash_leaf).collect();
 
     let mut path = Vec::new();
     let mut idx = leaf_index;
@@ -248,29 +361,23 @@ fn compute_auth_path(leaf_pubkeys: &[[u8; 32]], leaf_index: usize) -> Vec<[u8; 3
             path.push(current_level[idx]); // duplicate if no sibling
         }
 
-        // Build next level
-        let mut next_level = Vec::with_capacity(current_level.len() / 2);
-        for pair in current_level.chunks(2) {
-            let mut h = Sha256::new();
-            h.update([0x01]);
-            h.update(pair[0]);
-            if pair.len() > 1 {
-                h.update(pair[1]);
-            } else {
-                h.update(pair[0]);
-            }
-            next_level.push(h.finalize().into());
-        }
-
-        current_level = next_level;
+        current_level = next_level(&current_level);
         idx /= 2;
     }
 
     path
 }
 
+/// Hash pairs of nodes into the level above, duplicating an odd last node.
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| hash_node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
 /// Verify a Merkle authentication path from a leaf to the expected root.
-pub(crate) fn verify_auth_path(
+pub fn verify_auth_path(
     leaf_pubkey: &[u8; 32],
     leaf_index: usize,
     auth_path: &[[u8; 32]],
@@ -284,26 +391,14 @@ pub(crate) fn verify_auth_path(
         return false;
     }
 
-    // Hash the leaf public key
-    let mut current_hash: [u8; 32] = {
-        let mut h = Sha256::new();
-        h.update([0x00]);
-        h.update(leaf_pubkey);
-        h.finalize().into()
-    };
-
+    let mut current_hash = hash_leaf(leaf_pubkey);
     let mut idx = leaf_index;
     for sibling in auth_path {
-        let mut h = Sha256::new();
-        h.update([0x01]);
-        if idx % 2 == 0 {
-            h.update(current_hash);
-            h.update(sibling);
+        current_hash = if idx % 2 == 0 {
+            hash_node(&current_hash, sibling)
         } else {
-            h.update(sibling);
-            h.update(current_hash);
-        }
-        current_hash = h.finalize().into();
+            hash_node(sibling, &current_hash)
+        };
         idx /= 2;
     }
 
@@ -313,6 +408,40 @@ pub(crate) fn verify_auth_path(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aether_crypto_primitives::secret::assert_not_leaked;
+
+    /// Seeds on the path from the root to leaf `period`, root first.
+    pub(super) fn path_seeds(seed: [u8; 32], depth: u32, period: u32) -> Vec<SecretBytes> {
+        let mut seeds = vec![SecretBytes::new(seed)];
+        for level in (0..depth).rev() {
+            let (left, right) = split_seed(seeds.last().unwrap());
+            seeds.push(if (period >> level) & 1 == 0 {
+                left
+            } else {
+                right
+            });
+        }
+        seeds
+    }
+
+    /// Every secret the key still holds.
+    pub(super) fn held_secrets(key: &KesKey) -> Vec<SecretBytes> {
+        std::iter::once(key.leaf_secret.clone())
+            .chain(key.right_seeds.iter().flatten().cloned())
+            .collect()
+    }
+
+    /// No secret from which a signing key for a period before the current
+    /// one could be derived is still held.
+    pub(super) fn past_periods_erased(key: &KesKey, seed: [u8; 32]) -> bool {
+        let held = held_secrets(key);
+        (0..key.current_period()).all(|past| {
+            path_seeds(seed, key.depth, past)
+                .iter()
+                .skip(1)
+                .all(|node| !held.contains(node))
+        })
+    }
 
     #[test]
     fn test_kes_generates_and_signs() {
@@ -325,6 +454,15 @@ mod tests {
         assert_eq!(key.current_period(), 0);
     }
 
+    #[test]
+    fn test_period_for_slot() {
+        assert_eq!(period_for_slot(0, 100), 0);
+        assert_eq!(period_for_slot(99, 100), 0);
+        assert_eq!(period_for_slot(100, 100), 1);
+        assert_eq!(period_for_slot(5, 0), 5);
+        assert_eq!(period_for_slot(u64::MAX, 1), u32::MAX);
+    }
+
     #[test]
     fn test_kes_monotonic_period() {
         let mut key = KesKey::generate(4);
@@ -354,9 +492,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_kes_depth_is_capped_below_32() {
+        assert_eq!(tree_depth(0), 1);
+        assert_eq!(tree_depth(5), 3);
+        assert_eq!(tree_depth(1 << 31), 31);
+        assert_eq!(tree_depth((1 << 31) + 1), 31);
+        assert_eq!(tree_depth(u32::MAX), 31);
+    }
+
     #[test]
     fn test_kes_forward_secrecy_erases_keys() {
-        let mut key = KesKey::generate(4);
+        let seed = [7u8; 32];
+        let mut key = KesKey::from_seed(seed, 4);
         let vk = key.verification_key();
 
         // Sign at period 0
@@ -367,11 +515,52 @@ mod tests {
         let sig2 = key.sign(2, b"period 2").unwrap();
         assert!(sig2.verify(&vk, b"period 2"));
 
-        // Periods 0 and 1 keys should be None
-        assert!(key.leaves[0].is_none(), "period 0 key should be erased");
-        assert!(key.leaves[1].is_none(), "period 1 key should be erased");
+        assert!(past_periods_erased(&key, seed));
         // Period 2 key should still exist
-        assert!(key.leaves[2].is_some(), "period 2 key should exist");
+        assert_eq!(
+            key.leaf_secret,
+            *path_seeds(seed, 2, 2).last().unwrap(),
+            "period 2 key should exist"
+        );
+    }
+
+    #[test]
+    fn test_kes_secrets_stay_out_of_debug_output() {
+        let mut key = KesKey::from_seed([7u8; 32], 16);
+        key.evolve_to(5).unwrap();
+        let debug = format!("{key:?}");
+        assert!(debug.contains("current_period: 5"), "{debug}");
+        for secret in held_secrets(&key) {
+            assert_not_leaked(&debug, secret.expose_secret());
+        }
+    }
+
+    #[test]
+    fn test_kes_evolve_steps_through_every_period() {
+        let seed = [9u8; 32];
+        let mut key = KesKey::from_seed(seed, 16);
+        let vk = key.verification_key();
+
+        for period in 0..16 {
+            assert_eq!(key.current_period(), period);
+            // The state stays logarithmic in the number of periods.
+            assert!(held_secrets(&key).len() <= 1 + key.depth as usize);
+            assert!(past_periods_erased(&key, seed));
+            let sig = key.sign(period, b"msg").unwrap();
+            assert!(sig.verify(&vk, b"msg"));
+            if period < 15 {
+                key.evolve().unwrap();
+            }
+        }
+        assert_eq!(
+            key.evolve().unwrap_err(),
+            KesError::PeriodOutOfRange {
+                requested: 16,
+                max_periods: 16,
+            }
+        );
+        // The last leaf holds no right seeds.
+        assert_eq!(held_secrets(&key).len(), 1);
     }
 
     #[test]
@@ -422,12 +611,21 @@ mod tests {
 
     #[test]
     fn test_kes_merkle_root_consistency() {
-        let key = KesKey::generate(8);
+        let seed = [5u8; 32];
+        let mut key = KesKey::from_seed(seed, 8);
         let vk = key.verification_key();
 
-        // Recompute root manually
-        let root = compute_merkle_root(&key.leaf_pubkeys);
-        assert_eq!(root, vk.root());
+        // The sum composition is the Merkle tree over every period's leaf.
+        let leaves: Vec<[u8; 32]> = (0..8)
+            .map(|period| leaf_pubkey(path_seeds(seed, 3, period).last().unwrap()))
+            .collect();
+        assert_eq!(compute_merkle_root(&leaves), vk.root());
+
+        for period in 0..8 {
+            let sig = key.sign(period, b"msg").unwrap();
+            assert_eq!(sig.leaf_pubkey, leaves[period as usize]);
+            assert_eq!(sig.auth_path, compute_auth_path(&leaves, period as usize));
+        }
     }
 }
 
@@ -514,7 +712,7 @@ mod proptests {
             prop_assert!(result.is_err(), "sign at max_periods={} must fail (out-of-range)", actual_max);
         }
 
-        /// Forward secrecy: past leaf keys are None after evolving forward.
+        /// Forward secrecy: past leaf keys are erased after evolving forward.
         #[test]
         fn forward_secrecy_erases_past_keys(
             seed in prop::array::uniform32(any::<u8>()),
@@ -522,12 +720,11 @@ mod proptests {
         ) {
             let mut key = KesKey::from_seed(seed, 16);
             key.sign(target, b"evolve").unwrap();
-            for i in 0..target {
-                prop_assert!(key.leaves[i as usize].is_none(),
-                    "leaf key at period {} must be erased after evolving to {}", i, target);
-            }
+            prop_assert!(tests::past_periods_erased(&key, seed),
+                "keys before period {} must be erased", target);
             // Current period's key must still be present
-            prop_assert!(key.leaves[target as usize].is_some(),
+            let path = tests::path_seeds(seed, 4, target);
+            prop_assert_eq!(&key.leaf_secret, path.last().unwrap(),
                 "leaf key at current period {} must still exist", target);
         }
 
@@ -1,7 +1,13 @@
 pub mod error;
+pub mod evidence;
 pub mod evolution;
 pub mod signature;
+pub mod storage;
 
 pub use error::{KesError, Result};
-pub use evolution::KesKey;
+pub use evidence::{block_signing_message, DoubleSignEvidence, SignedHeader};
+pub use evolution::{
+    compute_auth_path, compute_merkle_root, period_for_slot, verify_auth_path, KesKey,
+};
 pub use signature::{KesSignature, KesVerificationKey};
+pub use storage::{KesKeyStore, KeySealer, PassphraseSealer};
@@ -0,0 +1,369 @@
+// ============================================================================
+// SEALED KES KEY STORAGE
+// ============================================================================
+// A KES key is only forward secure if the erased periods stay erased on disk
+// too. `KesKeyStore` keeps one sealed file per key:
+//
+//   "AKES" || version u8 || sealer kind u8 || period u32 || sealed state
+//
+// The header is authenticated as associated data, so a file cannot be
+// relabelled to another period or sealer. The state is sealed by a
+// `KeySealer`: `PassphraseSealer` (PBKDF2-HMAC-SHA256 + ChaCha20-Poly1305)
+// for operators, or a TEE / OS-keyring backend implementing the same trait.
+//
+// Persistence is atomic: the new state goes to a temporary file that is
+// synced and then renamed over the old one, and the directory is synced.
+// `KesKeyStore::sign` persists an evolved key before signing with it, so a
+// crash can never leave an older period's key on disk after a signature for
+// a newer period has left the process. Saving a key older than the one on
+// disk is refused.
+// ============================================================================
+
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::pbkdf2;
+use ring::rand::{SecureRandom, SystemRandom};
+use zeroize::Zeroizing;
+
+use crate::evolution::KesKey;
+use crate::signature::KesSignature;
+
+const MAGIC: &[u8; 4] = b"AKES";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 10;
+
+const SALT_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+/// PBKDF2 rounds for `PassphraseSealer::new` (OWASP 2023 guidance for
+/// PBKDF2-HMAC-SHA256).
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Encrypts KES key state at rest. `aad` must be authenticated alongside
+/// the plaintext.
+pub trait KeySealer: Send + Sync {
+    /// Identifies the backend in the file header; a file only opens with
+    /// the kind of sealer that wrote it.
+    fn kind(&self) -> u8;
+    fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>>;
+    fn unseal(&self, aad: &[u8], sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>>;
+}
+
+/// Operator-passphrase sealing: a fresh salt and nonce per seal, a key
+/// stretched with PBKDF2-HMAC-SHA256, ChaCha20-Poly1305.
+///
+/// Sealed layout: iterations u32 || salt (16) || nonce (12) || ciphertext+tag
+pub struct PassphraseSealer {
+    passphrase: Zeroizing<Vec<u8>>,
+    iterations: NonZeroU32,
+    rng: SystemRandom,
+}
+
+impl PassphraseSealer {
+    pub const KIND: u8 = 1;
+
+    pub fn new(passphrase: &[u8]) -> Self {
+        Self::with_iterations(passphrase, DEFAULT_PBKDF2_ITERATIONS)
+    }
+
+    /// Custom PBKDF2 rounds (at least 1). Files record the rounds they were
+    /// sealed with, so this only affects new seals.
+    pub fn with_iterations(passphrase: &[u8], iterations: u32) -> Self {
+        PassphraseSealer {
+            passphrase: Zeroizing::new(passphrase.to_vec()),
+            iterations: NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN),
+            rng: SystemRandom::new(),
+        }
+    }
+
+    fn cipher(&self, iterations: NonZeroU32, salt: &[u8]) -> Result<LessSafeKey> {
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        pbkdf2::derive(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            iterations,
+            salt,
+            &self.passphrase,
+            key.as_mut(),
+        );
+        let key = UnboundKey::new(&CHACHA20_POLY1305, key.as_ref())
+            .map_err(|_| anyhow!("invalid sealing key"))?;
+        Ok(LessSafeKey::new(key))
+    }
+}
+
+impl KeySealer for PassphraseSealer {
+    fn kind(&self) -> u8 {
+        Self::KIND
+    }
+
+    fn seal(&self, aad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut nonce = [0u8; NONCE_LEN];
+        self.rng
+            .fill(&mut salt)
+            .and_then(|_| self.rng.fill(&mut nonce))
+            .map_err(|_| anyhow!("system randomness unavailable"))?;
+
+        let mut sealed = Vec::with_capacity(4 + SALT_LEN + NONCE_LEN + plaintext.len() + 16);
+        sealed.extend_from_slice(&self.iterations.get().to_le_bytes());
+        sealed.extend_from_slice(&salt);
+        sealed.extend_from_slice(&nonce);
+        let body = sealed.len();
+        sealed.extend_from_slice(plaintext);
+
+        let mut in_out = sealed.split_off(body);
+        self.cipher(self.iterations, &salt)?
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad),
+                &mut in_out,
+            )
+            .map_err(|_| anyhow!("sealing failed"))?;
+        sealed.extend_from_slice(&in_out);
+        Ok(sealed)
+    }
+
+    fn unseal(&self, aad: &[u8], sealed: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        if sealed.len() < 4 + SALT_LEN + NONCE_LEN {
+            bail!("sealed KES key is truncated");
+        }
+        let (iterations, rest) = sealed.split_at(4);
+        let (salt, rest) = rest.split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let iterations = u32::from_le_bytes(iterations.try_into().expect("4 bytes"));
+        let iterations =
+            NonZeroU32::new(iterations).ok_or_else(|| anyhow!("zero PBKDF2 iterations"))?;
+        let nonce = Nonce::try_assume_unique_for_key(nonce).expect("nonce length checked");
+
+        let mut in_out = Zeroizing::new(ciphertext.to_vec());
+        let len = self
+            .cipher(iterations, salt)?
+            .open_in_place(nonce, Aad::from(aad), &mut in_out)
+            .map_err(|_| anyhow!("wrong passphrase or corrupted KES key file"))?
+            .len();
+        in_out.truncate(len);
+        Ok(in_out)
+    }
+}
+
+/// A KES key sealed in a single file.
+pub struct KesKeyStore {
+    path: PathBuf,
+    sealer: Box<dyn KeySealer>,
+}
+
+impl KesKeyStore {
+    pub fn new(path: impl Into<PathBuf>, sealer: Box<dyn KeySealer>) -> Self {
+        KesKeyStore {
+            path: path.into(),
+            sealer,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Period of the stored key, read from the header without unsealing.
+    /// `None` if nothing is stored yet.
+    pub fn stored_period(&self) -> Result<Option<u32>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(self.parse_header(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("reading {}", self.path.display())),
+        }
+    }
+
+    pub fn load(&self) -> Result<KesKey> {
+        let bytes =
+            fs::read(&self.path).with_context(|| format!("reading {}", self.path.display()))?;
+        let period = self.parse_header(&bytes)?;
+        let (header, sealed) = bytes.split_at(HEADER_LEN);
+        let state = self.sealer.unseal(header, sealed)?;
+        let key = KesKey::from_bytes(&state)?;
+        if key.current_period() != period {
+            bail!("KES key file header does not match its contents");
+        }
+        Ok(key)
+    }
+
+    /// Persist `key` atomically. Refuses to replace a stored key of a later
+    /// period, which would bring erased periods back.
+    pub fn save(&self, key: &KesKey) -> Result<()> {
+        if let Some(stored) = self.stored_period()? {
+            if key.current_period() < stored {
+                bail!(
+                    "refusing to overwrite KES key at period {stored} with period {}",
+                    key.current_period()
+                );
+            }
+        }
+
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(MAGIC);
+        header.push(VERSION);
+        header.push(self.sealer.kind());
+        header.extend_from_slice(&key.current_period().to_le_bytes());
+        let sealed = self.sealer.seal(&header, &key.to_bytes())?;
+
+        let mut contents = header;
+        contents.extend_from_slice(&sealed);
+        write_atomic(&self.path, &contents)
+    }
+
+    /// Evolve `key` to `period` and persist it before it is used.
+    pub fn evolve_to(&self, key: &mut KesKey, period: u32) -> Result<()> {
+        if period == key.current_period() {
+            return Ok(());
+        }
+        key.evolve_to(period)?;
+        self.save(key)
+    }
+
+    /// Sign at `period`, persisting the evolved key first so no signature
+    /// for `period` exists while an older key is still on disk.
+    pub fn sign(&self, key: &mut KesKey, period: u32, message: &[u8]) -> Result<KesSignature> {
+        self.evolve_to(key, period)?;
+        Ok(key.sign(period, message)?)
+    }
+
+    fn parse_header(&self, bytes: &[u8]) -> Result<u32> {
+        if bytes.len() < HEADER_LEN || &bytes[..4] != MAGIC {
+            bail!("{} is not a KES key file", self.path.display());
+        }
+        if bytes[4] != VERSION {
+            bail!("unsupported KES key file version {}", bytes[4]);
+        }
+        if bytes[5] != self.sealer.kind() {
+            bail!(
+                "KES key file was sealed by backend {}, not {}",
+                bytes[5],
+                self.sealer.kind()
+            );
+        }
+        Ok(u32::from_le_bytes(
+            bytes[6..10].try_into().expect("4 bytes"),
+        ))
+    }
+}
+
+/// Replace `path` with `contents` so that a crash leaves either the old or
+/// the new file, never a mix. New files are created owner-only (0600).
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(dir)?;
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = dir.join(tmp_name);
+
+    let mut options = OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(&tmp)
+        .with_context(|| format!("creating {}", tmp.display()))?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    fs::rename(&tmp, path).with_context(|| format!("replacing {}", path.display()))?;
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_primitives::secret::assert_not_leaked;
+
+    fn store(dir: &Path, passphrase: &[u8]) -> KesKeyStore {
+        KesKeyStore::new(
+            dir.join("kes.key"),
+            Box::new(PassphraseSealer::with_iterations(passphrase, 1_000)),
+        )
+    }
+
+    #[test]
+    fn sealed_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path(), b"correct horse");
+        assert_eq!(store.stored_period().unwrap(), None);
+
+        let mut key = KesKey::from_seed([4u8; 32], 16);
+        let vk = key.verification_key();
+        store.evolve_to(&mut key, 3).unwrap();
+        assert_eq!(store.stored_period().unwrap(), Some(3));
+
+        let mut loaded = store.load().unwrap();
+        assert_eq!(loaded.current_period(), 3);
+        assert_eq!(loaded.verification_key(), vk);
+        assert!(loaded.sign(5, b"msg").unwrap().verify(&vk, b"msg"));
+
+        // No plaintext secret and no leftover temporary file on disk.
+        let contents = fs::read(store.path()).unwrap();
+        assert_not_leaked(&contents, &key.to_bytes()[40..72]);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(store.path()).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn wrong_passphrase_and_tampering_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = KesKey::from_seed([4u8; 32], 8);
+        store(dir.path(), b"right").save(&key).unwrap();
+
+        assert!(store(dir.path(), b"wrong").load().is_err());
+
+        // Relabelling the period in the header breaks authentication.
+        let path = dir.path().join("kes.key");
+        let mut contents = fs::read(&path).unwrap();
+        contents[6] = 5;
+        fs::write(&path, &contents).unwrap();
+        assert!(store(dir.path(), b"right").load().is_err());
+
+        contents[6] = 0;
+        let last = contents.len() - 1;
+        contents[last] ^= 1;
+        fs::write(&path, &contents).unwrap();
+        assert!(store(dir.path(), b"right").load().is_err());
+    }
+
+    #[test]
+    fn signing_persists_evolution_and_refuses_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path(), b"pass");
+        let mut key = KesKey::from_seed([6u8; 32], 8);
+        let stale = KesKey::from_seed([6u8; 32], 8);
+        store.save(&key).unwrap();
+
+        let sig = store.sign(&mut key, 4, b"block").unwrap();
+        assert!(sig.verify(&key.verification_key(), b"block"));
+        // The key on disk already moved on; restarting cannot sign period 3.
+        let mut restarted = store.load().unwrap();
+        assert_eq!(restarted.current_period(), 4);
+        assert!(restarted.sign(3, b"old").is_err());
+
+        let err = store.save(&stale).unwrap_err();
+        assert!(err.to_string().contains("refusing"), "{err}");
+        assert_eq!(store.stored_period().unwrap(), Some(4));
+    }
+}
@@ -0,0 +1,96 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use aether_crypto_kzg::msm::{msm, msm_naive, msm_serial};
+use aether_crypto_kzg::{scalar_from_i64, KzgCommitment, KzgProof, KzgVerifier, ScalarBytes};
+use blst::{blst_p1, blst_p1_affine};
+use sha2::{Digest, Sha256};
+
+/// 256 openings of one degree-15 trace polynomial, as in a full VCR challenge.
+fn openings(verifier: &KzgVerifier) -> (Vec<KzgCommitment>, Vec<KzgProof>, Vec<ScalarBytes>) {
+    let coeffs: Vec<ScalarBytes> = (1..=16).map(scalar_from_i64).collect();
+    let commitment = verifier.commit(&coeffs).unwrap();
+    let points: Vec<ScalarBytes> = (0..256).map(scalar_from_i64).collect();
+    let proofs = points
+        .iter()
+        .map(|z| verifier.create_proof(&coeffs, z).unwrap())
+        .collect();
+    (vec![commitment; points.len()], proofs, points)
+}
+
+fn bench_verify_256(c: &mut Criterion) {
+    let verifier = KzgVerifier::new_insecure_test(16);
+    let (commitments, proofs, points) = openings(&verifier);
+
+    c.bench_function("kzg_verify_256_individually", |b| {
+        b.iter(|| {
+            for ((commitment, proof), z) in commitments.iter().zip(&proofs).zip(&points) {
+                assert!(verifier
+                    .verify(black_box(commitment), black_box(proof), black_box(z))
+                    .unwrap());
+            }
+        })
+    });
+
+    c.bench_function("kzg_batch_verify_256", |b| {
+        b.iter(|| {
+            assert!(verifier
+                .batch_verify(
+                    black_box(&commitments),
+                    black_box(&proofs),
+                    black_box(&points)
+                )
+                .unwrap())
+        })
+    });
+}
+
+/// `n` distinct G1 points (multiples of the generator) and hash-derived scalars.
+fn msm_inputs(n: usize) -> (Vec<blst_p1_affine>, Vec<ScalarBytes>) {
+    // SAFETY: the generator is a static constant.
+    let generator = unsafe { *blst::blst_p1_generator() };
+    let mut projective = Vec::with_capacity(n);
+    let mut acc = generator;
+    for _ in 0..n {
+        projective.push(acc);
+        let mut next = blst_p1::default();
+        // SAFETY: both inputs are valid points.
+        unsafe { blst::blst_p1_add_or_double(&mut next, &acc, &generator) };
+        acc = next;
+    }
+    let points = blst::p1_affines::from(&projective).as_slice().to_vec();
+    let scalars = (0..n as u64)
+        .map(|i| Sha256::digest(i.to_le_bytes()).into())
+        .collect();
+    (points, scalars)
+}
+
+fn bench_msm(c: &mut Criterion) {
+    let mut group = c.benchmark_group("kzg_msm");
+    group.sample_size(10);
+
+    let n = 1 << 14;
+    let (points, scalars) = msm_inputs(n);
+    group.bench_with_input(BenchmarkId::new("naive", n), &n, |b, _| {
+        b.iter(|| msm_naive(black_box(&points), black_box(&scalars)))
+    });
+    group.bench_with_input(BenchmarkId::new("pippenger", n), &n, |b, _| {
+        b.iter(|| msm_serial(black_box(&points), black_box(&scalars)))
+    });
+    group.bench_with_input(BenchmarkId::new("pippenger_parallel", n), &n, |b, _| {
+        b.iter(|| msm(black_box(&points), black_box(&scalars)))
+    });
+
+    // The naive loop takes minutes at 2^20 and is left out.
+    let n = 1 << 20;
+    let (points, scalars) = msm_inputs(n);
+    group.bench_with_input(BenchmarkId::new("pippenger", n), &n, |b, _| {
+        b.iter(|| msm_serial(black_box(&points), black_box(&scalars)))
+    });
+    group.bench_with_input(BenchmarkId::new("pippenger_parallel", n), &n, |b, _| {
+        b.iter(|| msm(black_box(&points), black_box(&scalars)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_verify_256, bench_msm);
+criterion_main!(benches);
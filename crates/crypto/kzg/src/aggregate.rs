@@ -0,0 +1,252 @@
+// ============================================================================
+// AGGREGATED OPENINGS - One proof for every layer of a VCR
+// ============================================================================
+// A VCR commits to 8–16 layer polynomials P_j. Opening each at the challenge
+// point costs a 48-byte proof and a pairing check per layer. Openings at a
+// shared point z aggregate instead:
+//
+//   γ = H(domain || z || C_0..C_m || y_0..y_m)        (128-bit challenge)
+//   P = Σ γ^j · P_j     C = Σ γ^j · C_j     y = Σ γ^j · y_j
+//
+// and one KZG proof that P(z) = y covers every claim y_j = P_j(z): with γ
+// fixed after the claims, a wrong y_j survives with probability m / 2^128.
+// On chain this is the point, the per-layer evaluations and a single 48-byte
+// proof, checked with one pairing regardless of the layer count; receipts
+// carry it in their layer opening extension (`aether_verifiers_vcr`).
+//
+// Wire format (aether-codecs canonical encoding):
+//
+//   point (32) || count u32 || evaluations (32 each) || proof (48)
+// ============================================================================
+
+use crate::commitment::{
+    compress_g1, decode_commitment, evaluate_polynomial, g1_msm, scalar_add, scalar_from_bytes,
+    scalar_mul, scalar_one, to_scalar_bytes, KzgCommitment, KzgProof, KzgVerifier, ScalarBytes,
+};
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use anyhow::{bail, ensure, Result};
+use blst::blst_fr;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Most layers one aggregated opening may cover.
+pub const MAX_AGGREGATED_LAYERS: usize = 16;
+
+const AGGREGATE_DOMAIN: &[u8] = b"AETHER-KZG-AGGREGATE-v1";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AggregatedOpening {
+    /// Point every layer is opened at.
+    pub point: ScalarBytes,
+    /// P_j(point), in the order of the layer commitments.
+    pub evaluations: Vec<ScalarBytes>,
+    /// 48-byte proof for the γ-combination of the layers.
+    pub proof: Vec<u8>,
+}
+
+/// Open every layer at `point` with one proof. `layers[j]` are the
+/// coefficients committed to in `commitments[j]`.
+pub fn aggregate_openings(
+    verifier: &KzgVerifier,
+    commitments: &[KzgCommitment],
+    layers: &[Vec<ScalarBytes>],
+    point: &ScalarBytes,
+) -> Result<AggregatedOpening> {
+    check_layer_count(commitments.len())?;
+    ensure!(
+        layers.len() == commitments.len(),
+        "{} layers for {} commitments",
+        layers.len(),
+        commitments.len()
+    );
+    let evaluations: Vec<ScalarBytes> = layers
+        .iter()
+        .map(|coeffs| to_scalar_bytes(&evaluate_polynomial(coeffs, point)))
+        .collect();
+    let powers = gamma_powers(point, commitments, &evaluations);
+
+    let degree = layers.iter().map(Vec::len).max().unwrap_or(0);
+    let mut combined = vec![blst_fr::default(); degree];
+    for (coeffs, power) in layers.iter().zip(&powers) {
+        for (acc, coeff) in combined.iter_mut().zip(coeffs) {
+            *acc = scalar_add(acc, &scalar_mul(power, &scalar_from_bytes(coeff)));
+        }
+    }
+    let combined: Vec<ScalarBytes> = combined.iter().map(to_scalar_bytes).collect();
+    let proof = verifier.create_proof(&combined, point)?;
+
+    Ok(AggregatedOpening {
+        point: *point,
+        evaluations,
+        proof: proof.proof,
+    })
+}
+
+/// Check `opening` against the layer commitments, in order.
+#[must_use = "discarding a KZG verification result is a security bug"]
+pub fn verify_aggregated_opening(
+    verifier: &KzgVerifier,
+    commitments: &[KzgCommitment],
+    opening: &AggregatedOpening,
+) -> Result<bool> {
+    check_layer_count(commitments.len())?;
+    ensure!(
+        opening.evaluations.len() == commitments.len(),
+        "{} evaluations for {} commitments",
+        opening.evaluations.len(),
+        commitments.len()
+    );
+    let powers = gamma_powers(&opening.point, commitments, &opening.evaluations);
+
+    let points = commitments
+        .iter()
+        .map(decode_commitment)
+        .collect::<Result<Vec<_>>>()?;
+    let scalars: Vec<ScalarBytes> = powers.iter().map(to_scalar_bytes).collect();
+    let commitment = KzgCommitment {
+        commitment: compress_g1(&g1_msm(&points, &scalars, 256)),
+    };
+    let mut evaluation = blst_fr::default();
+    for (y, power) in opening.evaluations.iter().zip(&powers) {
+        evaluation = scalar_add(&evaluation, &scalar_mul(power, &scalar_from_bytes(y)));
+    }
+    let proof = KzgProof {
+        proof: opening.proof.clone(),
+        evaluation: to_scalar_bytes(&evaluation).to_vec(),
+    };
+    verifier.verify(&commitment, &proof, &opening.point)
+}
+
+impl AggregatedOpening {
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::with_capacity(84 + 32 * self.evaluations.len());
+        writer
+            .put_fixed(&self.point)
+            .put_u32(self.evaluations.len() as u32);
+        for evaluation in &self.evaluations {
+            writer.put_fixed(evaluation);
+        }
+        writer.put_fixed(&self.proof);
+        writer.finish()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = CanonicalReader::new(bytes);
+        let point = reader.take_fixed()?;
+        let count = reader.take_u32()? as usize;
+        check_layer_count(count)?;
+        let evaluations = (0..count)
+            .map(|_| reader.take_fixed())
+            .collect::<aether_codecs::Result<Vec<ScalarBytes>>>()?;
+        let proof = reader.take_fixed::<48>()?.to_vec();
+        reader.finish()?;
+        Ok(AggregatedOpening {
+            point,
+            evaluations,
+            proof,
+        })
+    }
+}
+
+fn check_layer_count(count: usize) -> Result<()> {
+    if count == 0 || count > MAX_AGGREGATED_LAYERS {
+        bail!("aggregated opening must cover 1..={MAX_AGGREGATED_LAYERS} layers, got {count}");
+    }
+    Ok(())
+}
+
+/// γ^0..γ^(m-1) for γ bound to the point, commitments and claimed values.
+fn gamma_powers(
+    point: &ScalarBytes,
+    commitments: &[KzgCommitment],
+    evaluations: &[ScalarBytes],
+) -> Vec<blst_fr> {
+    let mut hasher = Sha256::new();
+    hasher.update(AGGREGATE_DOMAIN);
+    hasher.update(point);
+    hasher.update((commitments.len() as u64).to_le_bytes());
+    for commitment in commitments {
+        hasher.update((commitment.commitment.len() as u64).to_le_bytes());
+        hasher.update(&commitment.commitment);
+    }
+    for evaluation in evaluations {
+        hasher.update(evaluation);
+    }
+    let digest = hasher.finalize();
+    let mut gamma = [0u8; 32];
+    gamma[..16].copy_from_slice(&digest[..16]);
+    let gamma = scalar_from_bytes(&gamma);
+
+    let mut powers = Vec::with_capacity(commitments.len());
+    let mut power = scalar_one();
+    for _ in 0..commitments.len() {
+        powers.push(power);
+        power = scalar_mul(&power, &gamma);
+    }
+    powers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commitment::scalar_from_i64;
+
+    /// Twelve layers of differing degree and their commitments.
+    fn layers(verifier: &KzgVerifier) -> (Vec<KzgCommitment>, Vec<Vec<ScalarBytes>>) {
+        let layers: Vec<Vec<ScalarBytes>> = (0..12i64)
+            .map(|j| {
+                (0..=j % 5 + 2)
+                    .map(|k| scalar_from_i64(j * 10 - k))
+                    .collect()
+            })
+            .collect();
+        let commitments = layers.iter().map(|l| verifier.commit(l).unwrap()).collect();
+        (commitments, layers)
+    }
+
+    #[test]
+    fn one_proof_covers_every_layer() {
+        let verifier = KzgVerifier::new_insecure_test(8);
+        let (commitments, layers) = layers(&verifier);
+        let z = scalar_from_i64(3);
+        let opening = aggregate_openings(&verifier, &commitments, &layers, &z).unwrap();
+        assert_eq!(opening.proof.len(), 48);
+        assert!(verify_aggregated_opening(&verifier, &commitments, &opening).unwrap());
+
+        // Each claim is the layer's own value at z.
+        for (coeffs, evaluation) in layers.iter().zip(&opening.evaluations) {
+            let single = verifier.create_proof(coeffs, &z).unwrap();
+            assert_eq!(single.evaluation, evaluation.to_vec());
+        }
+
+        let mut wrong_value = opening.clone();
+        wrong_value.evaluations[7] = scalar_from_i64(1);
+        assert!(!verify_aggregated_opening(&verifier, &commitments, &wrong_value).unwrap());
+
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(!verify_aggregated_opening(&verifier, &swapped, &opening).unwrap());
+
+        let mut wrong_point = opening.clone();
+        wrong_point.point = scalar_from_i64(4);
+        assert!(!verify_aggregated_opening(&verifier, &commitments, &wrong_point).unwrap());
+
+        assert!(verify_aggregated_opening(&verifier, &commitments[1..], &opening).is_err());
+    }
+
+    #[test]
+    fn codec_roundtrip_and_bounds() {
+        let verifier = KzgVerifier::new_insecure_test(8);
+        let (commitments, layers) = layers(&verifier);
+        let opening =
+            aggregate_openings(&verifier, &commitments, &layers, &scalar_from_i64(5)).unwrap();
+        let encoded = opening.encode();
+        assert_eq!(encoded.len(), 32 + 4 + 12 * 32 + 48);
+        assert_eq!(AggregatedOpening::decode(&encoded).unwrap(), opening);
+        assert!(AggregatedOpening::decode(&encoded[..encoded.len() - 1]).is_err());
+
+        let mut too_many = opening;
+        too_many.evaluations = vec![[0u8; 32]; MAX_AGGREGATED_LAYERS + 1];
+        assert!(AggregatedOpening::decode(&too_many.encode()).is_err());
+    }
+}
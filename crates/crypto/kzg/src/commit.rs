@@ -0,0 +1,280 @@
+// ============================================================================
+// CHUNKED TRACE COMMITMENTS
+// ============================================================================
+// A single KZG commitment covers at most `max_degree + 1` evaluations. Longer
+// traces are split into fixed-size chunks; each chunk is interpolated over
+// x = 0..chunk_size and committed on its own, and the chunk commitments are
+// bound together by a SHA-256 Merkle root, which is what goes into the VCR.
+//
+//   trace value i  ->  chunk i / chunk_size, opened at x = i % chunk_size
+//
+// An opening of value i is the chunk commitment, its Merkle path to the root
+// and an ordinary KZG proof against that commitment.
+//
+// Merkle tree: leaves H(0x00 || commitment), nodes H(0x01 || left || right),
+// an odd node at any level is carried up unchanged.
+// ============================================================================
+
+use crate::commitment::{
+    interpolate, scalar_from_i64, KzgCommitment, KzgProof, KzgVerifier, ScalarBytes,
+};
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const LEAF_TAG: u8 = 0x00;
+const NODE_TAG: u8 = 0x01;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkedCommitment {
+    /// Evaluations per chunk; the last chunk may be shorter.
+    pub chunk_size: u32,
+    /// Total number of trace values committed.
+    pub len: u64,
+    pub chunks: Vec<KzgCommitment>,
+    /// Merkle root over `chunks`.
+    pub root: [u8; 32],
+}
+
+/// Proof that trace value `index` is `proof.evaluation`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkOpening {
+    pub index: u64,
+    pub chunk: KzgCommitment,
+    /// Sibling hashes from the leaf up; `None` where the node had no sibling.
+    pub path: Vec<Option<[u8; 32]>>,
+    pub proof: KzgProof,
+}
+
+/// Commit to `evaluations` in chunks of `chunk_size` (at most the setup's
+/// `max_degree + 1`).
+pub fn kzg_commit(
+    verifier: &KzgVerifier,
+    evaluations: &[ScalarBytes],
+    chunk_size: usize,
+) -> Result<ChunkedCommitment> {
+    ensure!(!evaluations.is_empty(), "empty trace");
+    check_chunk_size(verifier, chunk_size)?;
+    let chunks = evaluations
+        .chunks(chunk_size)
+        .map(|chunk| verifier.commit(&interpolate(chunk)?))
+        .collect::<Result<Vec<_>>>()?;
+    let root = merkle_root(&chunks);
+    Ok(ChunkedCommitment {
+        chunk_size: chunk_size as u32,
+        len: evaluations.len() as u64,
+        chunks,
+        root,
+    })
+}
+
+impl ChunkedCommitment {
+    /// Open trace value `index`. `evaluations` must be the committed trace.
+    pub fn open(
+        &self,
+        verifier: &KzgVerifier,
+        evaluations: &[ScalarBytes],
+        index: u64,
+    ) -> Result<ChunkOpening> {
+        ensure!(
+            evaluations.len() as u64 == self.len,
+            "trace has {} values, commitment covers {}",
+            evaluations.len(),
+            self.len
+        );
+        ensure!(
+            index < self.len,
+            "index {index} out of range ({})",
+            self.len
+        );
+        let chunk_size = self.chunk_size as usize;
+        let chunk_idx = (index / self.chunk_size as u64) as usize;
+        let start = chunk_idx * chunk_size;
+        let end = (start + chunk_size).min(evaluations.len());
+        let coeffs = interpolate(&evaluations[start..end])?;
+        let z = scalar_from_i64((index % self.chunk_size as u64) as i64);
+        Ok(ChunkOpening {
+            index,
+            chunk: self.chunks[chunk_idx].clone(),
+            path: merkle_path(&self.chunks, chunk_idx),
+            proof: verifier.create_proof(&coeffs, &z)?,
+        })
+    }
+}
+
+/// Check `opening` against a trace `root` of `len` values in chunks of
+/// `chunk_size`. Returns the opened value.
+pub fn verify_chunk_opening(
+    verifier: &KzgVerifier,
+    root: &[u8; 32],
+    len: u64,
+    chunk_size: u32,
+    opening: &ChunkOpening,
+) -> Result<ScalarBytes> {
+    ensure!(chunk_size > 0, "chunk size must be positive");
+    ensure!(
+        opening.index < len,
+        "index {} out of range ({len})",
+        opening.index
+    );
+    let chunk_idx = opening.index / chunk_size as u64;
+    let chunk_count = len.div_ceil(chunk_size as u64);
+    let expected_path = merkle_path_shape(chunk_count as usize, chunk_idx as usize);
+    ensure!(
+        opening.path.len() == expected_path.len()
+            && opening
+                .path
+                .iter()
+                .zip(&expected_path)
+                .all(|(sibling, has)| sibling.is_some() == *has),
+        "Merkle path does not fit chunk {chunk_idx} of {chunk_count}"
+    );
+
+    let mut node = leaf_hash(&opening.chunk);
+    let mut position = chunk_idx;
+    for sibling in &opening.path {
+        if let Some(sibling) = sibling {
+            node = if position % 2 == 0 {
+                node_hash(&node, sibling)
+            } else {
+                node_hash(sibling, &node)
+            };
+        }
+        position /= 2;
+    }
+    ensure!(
+        &node == root,
+        "chunk commitment is not under the trace root"
+    );
+
+    let z = scalar_from_i64((opening.index % chunk_size as u64) as i64);
+    if !verifier.verify(&opening.chunk, &opening.proof, &z)? {
+        bail!("KZG opening of value {} failed", opening.index);
+    }
+    let mut value = [0u8; 32];
+    value.copy_from_slice(&opening.proof.evaluation);
+    Ok(value)
+}
+
+fn check_chunk_size(verifier: &KzgVerifier, chunk_size: usize) -> Result<()> {
+    ensure!(chunk_size > 0, "chunk size must be positive");
+    ensure!(
+        chunk_size <= verifier.max_degree() + 1,
+        "chunk size {chunk_size} exceeds setup capacity {}",
+        verifier.max_degree() + 1
+    );
+    Ok(())
+}
+
+fn leaf_hash(commitment: &KzgCommitment) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([LEAF_TAG])
+        .chain_update(&commitment.commitment)
+        .finalize()
+        .into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([NODE_TAG])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!("chunks(2) yields one or two nodes"),
+        })
+        .collect()
+}
+
+fn merkle_root(chunks: &[KzgCommitment]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = chunks.iter().map(leaf_hash).collect();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+fn merkle_path(chunks: &[KzgCommitment], mut position: usize) -> Vec<Option<[u8; 32]>> {
+    let mut level: Vec<[u8; 32]> = chunks.iter().map(leaf_hash).collect();
+    let mut path = Vec::new();
+    while level.len() > 1 {
+        path.push(level.get(position ^ 1).copied());
+        level = next_level(&level);
+        position /= 2;
+    }
+    path
+}
+
+/// Which levels of the path to leaf `position` have a sibling.
+fn merkle_path_shape(mut width: usize, mut position: usize) -> Vec<bool> {
+    let mut shape = Vec::new();
+    while width > 1 {
+        shape.push((position ^ 1) < width);
+        width = width.div_ceil(2);
+        position /= 2;
+    }
+    shape
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trace(len: i64) -> Vec<ScalarBytes> {
+        (0..len).map(|v| scalar_from_i64(v * 7 - 3)).collect()
+    }
+
+    #[test]
+    fn commits_traces_beyond_setup_degree() {
+        let verifier = KzgVerifier::new_insecure_test(7);
+        let values = trace(21);
+        assert!(verifier.commit(&interpolate(&values).unwrap()).is_err());
+        assert!(kzg_commit(&verifier, &values, 9).is_err());
+
+        let committed = kzg_commit(&verifier, &values, 8).unwrap();
+        assert_eq!(committed.chunks.len(), 3);
+        assert_eq!(kzg_commit(&verifier, &values, 8).unwrap(), committed);
+
+        for index in [0, 7, 8, 15, 16, 20] {
+            let opening = committed.open(&verifier, &values, index).unwrap();
+            let value = verify_chunk_opening(&verifier, &committed.root, 21, 8, &opening).unwrap();
+            assert_eq!(value, values[index as usize]);
+        }
+        assert!(committed.open(&verifier, &values, 21).is_err());
+    }
+
+    #[test]
+    fn rejects_openings_outside_the_root() {
+        let verifier = KzgVerifier::new_insecure_test(7);
+        let values = trace(21);
+        let committed = kzg_commit(&verifier, &values, 8).unwrap();
+        let opening = committed.open(&verifier, &values, 10).unwrap();
+        let verify = |opening: &ChunkOpening| {
+            verify_chunk_opening(&verifier, &committed.root, 21, 8, opening)
+        };
+        verify(&opening).unwrap();
+
+        // A valid opening of a chunk from another trace.
+        let mut other_values = values.clone();
+        other_values[10] = scalar_from_i64(1);
+        let other = kzg_commit(&verifier, &other_values, 8).unwrap();
+        assert!(verify(&other.open(&verifier, &other_values, 10).unwrap()).is_err());
+
+        // Claiming the opening is for a different index.
+        let mut moved = opening.clone();
+        moved.index = 2;
+        assert!(verify(&moved).is_err());
+
+        let mut truncated = opening;
+        truncated.path.pop();
+        assert!(verify(&truncated).is_err());
+    }
+}
@@ -1,3 +1,13 @@
+pub mod aggregate;
+pub mod commit;
 pub mod commitment;
+pub mod msm;
 
-pub use commitment::{KzgCommitment, KzgProof, KzgVerifier, ScalarBytes, TrustedSetup};
+pub use aggregate::{
+    aggregate_openings, verify_aggregated_opening, AggregatedOpening, MAX_AGGREGATED_LAYERS,
+};
+pub use commit::{kzg_commit, verify_chunk_opening, ChunkOpening, ChunkedCommitment};
+pub use commitment::{
+    interpolate, scalar_from_i64, KzgCommitment, KzgProof, KzgVerifier, ScalarBytes, TrustedSetup,
+    PACKED_OPENING_LEN,
+};
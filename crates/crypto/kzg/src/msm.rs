@@ -0,0 +1,402 @@
+// ============================================================================
+// MULTI-SCALAR MULTIPLICATION - Σ s_i · P_i over G1
+// ============================================================================
+// Commitments and proofs are MSMs over the setup points, so this is where a
+// worker spends its time on large traces. Pippenger bucketing:
+//
+//   split each scalar into c-bit windows (c ≈ ln n)
+//   per window: add every point into bucket[window value], then
+//               Σ k · bucket[k] with two running sums (2 · 2^c additions)
+//   combine windows from the top: acc = acc · 2^c + window_sum
+//
+// That is about (256 / c) · (n + 2^(c+1)) additions instead of n full scalar
+// multiplications. Buckets are kept affine and filled in batches: additions
+// into distinct buckets share one field inversion (Montgomery's trick), which
+// makes each one about half the cost of a mixed projective add. Windows are
+// independent and run on the rayon pool once the input is large enough to
+// pay for the threads.
+// ============================================================================
+
+use crate::commitment::ScalarBytes;
+use blst::{blst_fp, blst_p1, blst_p1_affine};
+use rayon::prelude::*;
+
+/// Scalars are read as raw 256-bit little-endian integers; points have prime
+/// order, so non-canonical encodings still give the reduced result.
+const SCALAR_BITS: usize = 256;
+
+/// Below this many points, windows are summed on the calling thread.
+const PARALLEL_THRESHOLD: usize = 1 << 10;
+
+/// Pippenger MSM, parallel over windows for large inputs. Extra points or
+/// scalars beyond the shorter slice are ignored.
+#[must_use]
+pub fn msm(points: &[blst_p1_affine], scalars: &[ScalarBytes]) -> blst_p1 {
+    msm_bits(points, scalars, SCALAR_BITS)
+}
+
+/// Pippenger MSM on the calling thread only.
+#[must_use]
+pub fn msm_serial(points: &[blst_p1_affine], scalars: &[ScalarBytes]) -> blst_p1 {
+    pippenger(points, scalars, SCALAR_BITS, false)
+}
+
+/// One scalar multiplication per point; the reference the others must match.
+#[must_use]
+pub fn msm_naive(points: &[blst_p1_affine], scalars: &[ScalarBytes]) -> blst_p1 {
+    let mut acc = blst_p1::default();
+    for (point, scalar) in points.iter().zip(scalars) {
+        let mut projective = blst_p1::default();
+        let mut term = blst_p1::default();
+        // SAFETY: `point` is a valid affine point and `scalar` is 32 bytes,
+        // of which blst_p1_mult reads exactly 256 bits.
+        unsafe {
+            blst::blst_p1_from_affine(&mut projective, point);
+            blst::blst_p1_mult(&mut term, &projective, scalar.as_ptr(), SCALAR_BITS);
+        }
+        acc = add(&acc, &term);
+    }
+    acc
+}
+
+/// MSM where only the low `nbits` of each scalar can be set.
+pub(crate) fn msm_bits(
+    points: &[blst_p1_affine],
+    scalars: &[ScalarBytes],
+    nbits: usize,
+) -> blst_p1 {
+    let parallel = points.len().min(scalars.len()) >= PARALLEL_THRESHOLD;
+    pippenger(points, scalars, nbits.min(SCALAR_BITS), parallel)
+}
+
+fn pippenger(
+    points: &[blst_p1_affine],
+    scalars: &[ScalarBytes],
+    nbits: usize,
+    parallel: bool,
+) -> blst_p1 {
+    let n = points.len().min(scalars.len());
+    if n == 0 || nbits == 0 {
+        return blst_p1::default();
+    }
+    let (points, scalars) = (&points[..n], &scalars[..n]);
+    let c = window_bits(n);
+    let windows = nbits.div_ceil(c);
+    let window_sum = |window: usize| bucket_sum(points, scalars, window * c, c);
+    let sums: Vec<blst_p1> = if parallel {
+        (0..windows).into_par_iter().map(window_sum).collect()
+    } else {
+        (0..windows).map(window_sum).collect()
+    };
+
+    let mut acc = blst_p1::default();
+    for sum in sums.iter().rev() {
+        for _ in 0..c {
+            acc = double(&acc);
+        }
+        acc = add(&acc, sum);
+    }
+    acc
+}
+
+/// Window width minimising bucket plus accumulation work, ~ln(n) + 2.
+fn window_bits(n: usize) -> usize {
+    if n < 32 {
+        3
+    } else {
+        (n.ilog2() as usize * 69 / 100) + 2
+    }
+}
+
+/// Σ window(s_i) · P_i for the `c`-bit window starting at bit `offset`.
+fn bucket_sum(
+    points: &[blst_p1_affine],
+    scalars: &[ScalarBytes],
+    offset: usize,
+    c: usize,
+) -> blst_p1 {
+    let mut buckets = AffineBuckets::new((1 << c) - 1);
+    for (point, scalar) in points.iter().zip(scalars) {
+        let value = window_value(scalar, offset, c);
+        if value != 0 && !is_inf(point) {
+            buckets.add(value - 1, point);
+            if buckets.queue.len() >= buckets.capacity {
+                buckets.drain();
+            }
+        }
+    }
+    buckets.finish();
+
+    // running = Σ_{j ≥ k} bucket[j]; Σ_k running_k = Σ_k k · bucket[k].
+    let mut running = blst_p1::default();
+    let mut total = blst_p1::default();
+    for bucket in buckets.buckets.iter().rev() {
+        running = add_affine(&running, bucket);
+        total = add(&total, &running);
+    }
+    total
+}
+
+/// Affine buckets with additions deferred into batches that share one
+/// inversion. A bucket takes part in at most one addition per batch; further
+/// points for it wait in `queue` until the batch is applied.
+struct AffineBuckets<'a> {
+    buckets: Vec<blst_p1_affine>,
+    busy: Vec<bool>,
+    pending: Vec<(usize, &'a blst_p1_affine)>,
+    queue: Vec<(usize, &'a blst_p1_affine)>,
+    capacity: usize,
+    /// Scratch for the batch: x2 - x1, then prefix products.
+    deltas: Vec<blst_fp>,
+    prefix: Vec<blst_fp>,
+}
+
+impl<'a> AffineBuckets<'a> {
+    fn new(count: usize) -> Self {
+        let capacity = (count / 4).clamp(16, 2048);
+        Self {
+            buckets: vec![blst_p1_affine::default(); count],
+            busy: vec![false; count],
+            pending: Vec::with_capacity(capacity),
+            queue: Vec::new(),
+            capacity,
+            deltas: Vec::with_capacity(capacity),
+            prefix: Vec::with_capacity(capacity),
+        }
+    }
+
+    fn add(&mut self, bucket: usize, point: &'a blst_p1_affine) {
+        if self.busy[bucket] {
+            self.queue.push((bucket, point));
+        } else if is_inf(&self.buckets[bucket]) {
+            self.buckets[bucket] = *point;
+        } else {
+            self.busy[bucket] = true;
+            self.pending.push((bucket, point));
+            if self.pending.len() == self.capacity {
+                self.flush();
+            }
+        }
+    }
+
+    /// Apply everything still pending or queued.
+    fn finish(&mut self) {
+        self.flush();
+        self.drain();
+        self.flush();
+    }
+
+    fn drain(&mut self) {
+        while !self.queue.is_empty() {
+            for (bucket, point) in std::mem::take(&mut self.queue) {
+                self.add(bucket, point);
+            }
+            self.flush();
+        }
+    }
+
+    /// Apply the pending additions with one inversion.
+    fn flush(&mut self) {
+        // Equal x means doubling or cancellation; those few go through
+        // projective coordinates instead of the batch.
+        let (buckets, busy) = (&mut self.buckets, &mut self.busy);
+        self.pending.retain(|&(bucket, point)| {
+            if buckets[bucket].x != point.x {
+                return true;
+            }
+            busy[bucket] = false;
+            let mut sum = blst_p1::default();
+            // SAFETY: all values are valid points; results go to locals.
+            unsafe {
+                blst::blst_p1_from_affine(&mut sum, &buckets[bucket]);
+                blst::blst_p1_add_or_double_affine(&mut sum, &sum, point);
+                blst::blst_p1_to_affine(&mut buckets[bucket], &sum);
+            }
+            false
+        });
+
+        self.deltas.clear();
+        self.prefix.clear();
+        let mut product = fp_one();
+        for &(bucket, point) in &self.pending {
+            let delta = fp_sub(&point.x, &self.buckets[bucket].x);
+            product = fp_mul(&product, &delta);
+            self.deltas.push(delta);
+            self.prefix.push(product);
+        }
+
+        // inverse = 1 / Π deltas, peeled off one delta at a time from the back.
+        let mut inverse = blst_fp::default();
+        // SAFETY: `product` is a valid field element; the result goes to
+        // `inverse`.
+        unsafe { blst::blst_fp_inverse(&mut inverse, &product) };
+        for i in (0..self.pending.len()).rev() {
+            let inv_delta = match i {
+                0 => inverse,
+                _ => fp_mul(&inverse, &self.prefix[i - 1]),
+            };
+            inverse = fp_mul(&inverse, &self.deltas[i]);
+
+            let (bucket, point) = self.pending[i];
+            let current = &mut self.buckets[bucket];
+            let lambda = fp_mul(&fp_sub(&point.y, &current.y), &inv_delta);
+            let x = fp_sub(&fp_sub(&fp_sqr(&lambda), &current.x), &point.x);
+            let y = fp_sub(&fp_mul(&lambda, &fp_sub(&current.x, &x)), &current.y);
+            *current = blst_p1_affine { x, y };
+        }
+
+        for &(bucket, _) in &self.pending {
+            self.busy[bucket] = false;
+        }
+        self.pending.clear();
+    }
+}
+
+fn is_inf(point: &blst_p1_affine) -> bool {
+    // SAFETY: reads a valid affine point.
+    unsafe { blst::blst_p1_affine_is_inf(point) }
+}
+
+fn fp_one() -> blst_fp {
+    let mut out = blst_fp::default();
+    // SAFETY: writes the Montgomery form of 1 to `out`.
+    unsafe { blst::blst_fp_from_uint64(&mut out, [1u64, 0, 0, 0, 0, 0].as_ptr()) };
+    out
+}
+
+fn fp_mul(a: &blst_fp, b: &blst_fp) -> blst_fp {
+    let mut out = blst_fp::default();
+    // SAFETY: field elements in, field element out.
+    unsafe { blst::blst_fp_mul(&mut out, a, b) };
+    out
+}
+
+fn fp_sqr(a: &blst_fp) -> blst_fp {
+    let mut out = blst_fp::default();
+    // SAFETY: field element in, field element out.
+    unsafe { blst::blst_fp_sqr(&mut out, a) };
+    out
+}
+
+fn fp_sub(a: &blst_fp, b: &blst_fp) -> blst_fp {
+    let mut out = blst_fp::default();
+    // SAFETY: field elements in, field element out.
+    unsafe { blst::blst_fp_sub(&mut out, a, b) };
+    out
+}
+
+/// Bits `offset..offset + c` of a little-endian scalar; bits past 256 are 0.
+fn window_value(scalar: &ScalarBytes, offset: usize, c: usize) -> usize {
+    let mut value = 0usize;
+    for bit in 0..c {
+        let idx = offset + bit;
+        if idx >= SCALAR_BITS {
+            break;
+        }
+        if scalar[idx / 8] >> (idx % 8) & 1 == 1 {
+            value |= 1 << bit;
+        }
+    }
+    value
+}
+
+fn add(a: &blst_p1, b: &blst_p1) -> blst_p1 {
+    let mut out = blst_p1::default();
+    // SAFETY: both inputs are valid projective points (the zero value is the
+    // point at infinity); the result is written to `out`.
+    unsafe { blst::blst_p1_add_or_double(&mut out, a, b) };
+    out
+}
+
+fn add_affine(a: &blst_p1, b: &blst_p1_affine) -> blst_p1 {
+    let mut out = blst_p1::default();
+    // SAFETY: `a` is a valid projective point and `b` a valid affine point.
+    unsafe { blst::blst_p1_add_or_double_affine(&mut out, a, b) };
+    out
+}
+
+fn double(a: &blst_p1) -> blst_p1 {
+    let mut out = blst_p1::default();
+    // SAFETY: `a` is a valid projective point; the result is written to `out`.
+    unsafe { blst::blst_p1_double(&mut out, a) };
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(n: usize) -> Vec<blst_p1_affine> {
+        if n == 0 {
+            return Vec::new();
+        }
+        // SAFETY: the generator is a static constant.
+        let generator = unsafe { *blst::blst_p1_generator() };
+        let mut projective = Vec::with_capacity(n);
+        let mut acc = generator;
+        for _ in 0..n {
+            projective.push(acc);
+            acc = add(&acc, &double(&generator));
+        }
+        blst::p1_affines::from(&projective).as_slice().to_vec()
+    }
+
+    fn scalars(n: usize) -> Vec<ScalarBytes> {
+        use sha2::{Digest, Sha256};
+        (0..n as u64)
+            .map(|i| Sha256::digest(i.to_le_bytes()).into())
+            .collect()
+    }
+
+    fn eq(a: &blst_p1, b: &blst_p1) -> bool {
+        // SAFETY: both are valid projective points.
+        unsafe { blst::blst_p1_is_equal(a, b) }
+    }
+
+    #[test]
+    fn pippenger_matches_naive() {
+        for n in [0, 1, 2, 31, 32, 100] {
+            let (p, s) = (points(n), scalars(n));
+            let expected = msm_naive(&p, &s);
+            assert!(eq(&msm_serial(&p, &s), &expected), "serial, n = {n}");
+            assert!(
+                eq(&pippenger(&p, &s, SCALAR_BITS, true), &expected),
+                "parallel, n = {n}"
+            );
+        }
+    }
+
+    #[test]
+    fn handles_zero_scalars_and_short_widths() {
+        let p = points(40);
+        let mut s = scalars(40);
+        for scalar in s.iter_mut().step_by(3) {
+            *scalar = [0u8; 32];
+        }
+        assert!(eq(&msm(&p, &s), &msm_naive(&p, &s)));
+
+        for scalar in s.iter_mut() {
+            scalar[16..].fill(0);
+        }
+        assert!(eq(&msm_bits(&p, &s, 128), &msm_naive(&p, &s)));
+    }
+
+    #[test]
+    fn handles_doubling_and_cancellation_in_buckets() {
+        // Repeated and negated points all land in the same few buckets.
+        let base = points(3);
+        let mut negated = base[1];
+        // SAFETY: negates the y coordinate of a valid affine point.
+        unsafe { blst::blst_fp_cneg(&mut negated.y, &base[1].y, true) };
+        let p: Vec<blst_p1_affine> = (0..200)
+            .map(|i| match i % 4 {
+                0 | 1 => base[i % 3],
+                2 => negated,
+                _ => blst_p1_affine::default(),
+            })
+            .collect();
+        let s: Vec<ScalarBytes> = (0..200u8).map(|i| [i % 5; 32]).collect();
+        assert!(eq(&msm(&p, &s), &msm_naive(&p, &s)));
+        assert!(eq(&msm_serial(&p, &s), &msm_naive(&p, &s)));
+    }
+}
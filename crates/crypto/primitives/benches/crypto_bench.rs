@@ -1,7 +1,7 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
 use aether_crypto_primitives::ed25519::{verify, verify_batch, Keypair};
-use aether_crypto_primitives::hash::{blake3_hash, hash_multiple, sha256};
+use aether_crypto_primitives::hash::{blake3_hash, hash_multiple, poseidon_hash, sha256};
 
 // ---------------------------------------------------------------------------
 // Ed25519 benchmarks
@@ -33,27 +33,66 @@ fn bench_ed25519_verify(c: &mut Criterion) {
     });
 }
 
-fn bench_ed25519_batch_verify(c: &mut Criterion) {
-    let mut group = c.benchmark_group("ed25519/batch_verify");
-
-    for n in [10, 50, 100, 500] {
-        let verifications: Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> = (0..n)
-            .map(|i| {
-                let kp = Keypair::generate();
-                let msg = format!("bench msg {i}").into_bytes();
-                let sig = kp.sign(&msg);
-                let pk = kp.public_key();
-                (pk, msg, sig)
-            })
+fn signed_messages(n: usize) -> Vec<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+    (0..n)
+        .map(|i| {
+            let kp = Keypair::generate();
+            let msg = format!("bench msg {i}").into_bytes();
+            let sig = kp.sign(&msg);
+            let pk = kp.public_key();
+            (pk, msg, sig)
+        })
+        .collect()
+}
+
+fn bench_batch(
+    c: &mut Criterion,
+    name: &str,
+    sizes: &[usize],
+    corrupt: impl Fn(&mut [(Vec<u8>, Vec<u8>, Vec<u8>)]),
+) {
+    let mut group = c.benchmark_group(name);
+
+    for &n in sizes {
+        let mut verifications = signed_messages(n);
+        corrupt(&mut verifications);
+        let msgs: Vec<&[u8]> = verifications.iter().map(|(_, m, _)| m.as_slice()).collect();
+        let sigs: Vec<&[u8]> = verifications.iter().map(|(_, _, s)| s.as_slice()).collect();
+        let pks: Vec<&[u8]> = verifications
+            .iter()
+            .map(|(pk, _, _)| pk.as_slice())
             .collect();
 
-        group.bench_with_input(BenchmarkId::from_parameter(n), &verifications, |b, v| {
-            b.iter(|| black_box(verify_batch(black_box(v)).unwrap()));
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| {
+                black_box(
+                    verify_batch(black_box(&msgs), black_box(&sigs), black_box(&pks)).unwrap(),
+                )
+            });
         });
     }
     group.finish();
 }
 
+fn bench_ed25519_batch_verify(c: &mut Criterion) {
+    bench_batch(c, "ed25519/batch_verify", &[10, 50, 100, 500, 1000], |_| {});
+}
+
+/// One bad signature per hundred: the cost of isolating failures.
+fn bench_ed25519_batch_verify_with_failures(c: &mut Criterion) {
+    bench_batch(
+        c,
+        "ed25519/batch_verify_1pct_invalid",
+        &[100, 500, 1000],
+        |verifications| {
+            for (_, _, sig) in verifications.iter_mut().step_by(100) {
+                sig[0] ^= 0x01;
+            }
+        },
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Hashing benchmarks
 // ---------------------------------------------------------------------------
@@ -80,6 +119,17 @@ fn bench_blake3(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_poseidon(c: &mut Criterion) {
+    let mut group = c.benchmark_group("poseidon");
+    for size in [32, 256, 1024] {
+        let data = vec![0xCDu8; size];
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, d| {
+            b.iter(|| black_box(poseidon_hash(black_box(d))));
+        });
+    }
+    group.finish();
+}
+
 fn bench_hash_multiple(c: &mut Criterion) {
     let chunks: Vec<Vec<u8>> = (0..16).map(|i| vec![i as u8; 64]).collect();
     let refs: Vec<&[u8]> = chunks.iter().map(|c| c.as_slice()).collect();
@@ -94,8 +144,10 @@ criterion_group!(
     bench_ed25519_sign,
     bench_ed25519_verify,
     bench_ed25519_batch_verify,
+    bench_ed25519_batch_verify_with_failures,
     bench_sha256,
     bench_blake3,
+    bench_poseidon,
     bench_hash_multiple,
 );
 criterion_main!(benches);
@@ -1,5 +1,13 @@
-use ed25519_dalek::{Signature as DalekSignature, Signer, SigningKey, Verifier, VerifyingKey};
+use curve25519_dalek::constants::ED25519_BASEPOINT_POINT;
+use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use ed25519_dalek::{Signer, SigningKey};
+use sha2::{Digest, Sha512};
 use thiserror::Error;
+use zeroize::Zeroize;
+
+use crate::secret::SecretBytes;
 
 #[derive(Error, Debug)]
 pub enum Ed25519Error {
@@ -9,6 +17,8 @@ pub enum Ed25519Error {
     PublicKey,
     #[error("invalid secret key")]
     SecretKey,
+    #[error("batch inputs differ in length")]
+    BatchLength,
 }
 
 pub struct Keypair {
@@ -31,6 +41,7 @@ impl Keypair {
         let mut key_bytes = [0u8; 32];
         key_bytes.copy_from_slice(bytes);
         let signing_key = SigningKey::from_bytes(&key_bytes);
+        key_bytes.zeroize();
         Ok(Keypair { signing_key })
     }
 
@@ -42,8 +53,8 @@ impl Keypair {
 
     #[inline]
     #[must_use]
-    pub fn secret_key(&self) -> Vec<u8> {
-        self.signing_key.to_bytes().to_vec()
+    pub fn secret_key(&self) -> SecretBytes {
+        SecretBytes::new(self.signing_key.to_bytes())
     }
 
     #[must_use]
@@ -52,100 +63,218 @@ impl Keypair {
     }
 }
 
+/// Verify an Ed25519 signature under the cofactored ZIP-215 rules: A and
+/// R may be any encoding of any curve point, s must be canonical, and the
+/// check is `[8](sB - R - kA) = 0`. [`verify_batch`] checks the same
+/// equation, so a signature is valid alone exactly when it is valid in a
+/// batch, whatever torsion its points carry.
 #[must_use = "discarding a signature verification result is a security bug"]
 pub fn verify(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), Ed25519Error> {
-    if public_key.len() != 32 {
-        return Err(Ed25519Error::PublicKey);
-    }
+    let public_key: &[u8; 32] = public_key.try_into().map_err(|_| Ed25519Error::PublicKey)?;
     if signature.len() != 64 {
         return Err(Ed25519Error::Signature);
     }
+    if CompressedEdwardsY(*public_key).decompress().is_none() {
+        return Err(Ed25519Error::PublicKey);
+    }
+    match Parsed::new(message, signature, public_key) {
+        Some(parsed) if parsed.verify_single() => Ok(()),
+        _ => Err(Ed25519Error::Signature),
+    }
+}
 
-    let mut pk_bytes = [0u8; 32];
-    pk_bytes.copy_from_slice(public_key);
-    let verifying_key = VerifyingKey::from_bytes(&pk_bytes).map_err(|_| Ed25519Error::PublicKey)?;
-
-    let mut sig_bytes = [0u8; 64];
-    sig_bytes.copy_from_slice(signature);
-    let signature = DalekSignature::from_bytes(&sig_bytes);
+/// Signatures per batch equation. Chunks are verified in parallel.
+const BATCH_CHUNK: usize = 64;
 
-    verifying_key
-        .verify(message, &signature)
-        .map_err(|_| Ed25519Error::Signature)?;
+/// A failing batch at or below this size is verified signature by signature.
+const BISECT_FLOOR: usize = 4;
 
-    Ok(())
-}
-
-/// Batch signature verification - optimized for verifying multiple signatures at once
+/// Verify many signatures at once; `result[i]` says whether
+/// `signatures[i]` is a valid signature of `messages[i]` by `public_keys[i]`.
 ///
-/// Phase 4.2: CPU-optimized batch verification with hooks for future GPU acceleration
-/// Target: ≥ 300k sig/s per node (Phase 4 acceptance criteria)
+/// Inputs are split into chunks checked with one random linear
+/// combination of the cofactored equation [`verify`] uses, roughly twice
+/// as fast per signature as one-by-one verification. A chunk that fails
+/// is bisected until the bad signatures are isolated, so one invalid
+/// signature costs O(log n) extra batches rather than rejecting its
+/// neighbours. Malformed keys or signatures are simply `false`.
 ///
-/// Currently implements parallel CPU verification. Future enhancements:
-/// - GPU batch verification via CUDA/OpenCL for 300k+/s throughput
-/// - SIMD optimizations for vectorized operations
+/// Multiplying by the cofactor clears small-order components before the
+/// weights can cancel them, so torsion in A or R cannot make a batch pass
+/// where `verify` fails, or the reverse. The weights are derived from the
+/// inputs, so every node reaches the same result for the same batch.
 #[must_use = "discarding a batch verification result is a security bug"]
 pub fn verify_batch(
-    verifications: &[(Vec<u8>, Vec<u8>, Vec<u8>)], // (public_key, message, signature) tuples
+    messages: &[&[u8]],
+    signatures: &[&[u8]],
+    public_keys: &[&[u8]],
 ) -> Result<Vec<bool>, Ed25519Error> {
     use rayon::prelude::*;
 
-    if verifications.is_empty() {
-        return Ok(Vec::new());
+    if messages.len() != signatures.len() || messages.len() != public_keys.len() {
+        return Err(Ed25519Error::BatchLength);
     }
 
-    // Prepare inputs for Dalek batch verification
-    let mut verifying_keys = Vec::with_capacity(verifications.len());
-    let mut signatures = Vec::with_capacity(verifications.len());
-    let mut message_refs = Vec::with_capacity(verifications.len());
-
-    for (pk, msg, sig) in verifications {
-        if pk.len() != 32 {
-            return Err(Ed25519Error::PublicKey);
-        }
-        if sig.len() != 64 {
-            return Err(Ed25519Error::Signature);
-        }
-
-        let mut pk_bytes = [0u8; 32];
-        pk_bytes.copy_from_slice(pk);
-        let vk = VerifyingKey::from_bytes(&pk_bytes).map_err(|_| Ed25519Error::PublicKey)?;
-
-        let mut sig_bytes = [0u8; 64];
-        sig_bytes.copy_from_slice(sig);
-        let dalek_sig = DalekSignature::from_bytes(&sig_bytes);
+    let parsed: Vec<Option<Parsed>> = (0..messages.len())
+        .into_par_iter()
+        .map(|i| Parsed::new(messages[i], signatures[i], public_keys[i]))
+        .collect();
 
-        verifying_keys.push(vk);
-        signatures.push(dalek_sig);
-        message_refs.push(msg.as_slice());
-    }
+    let mut results = vec![false; parsed.len()];
+    let batchable: Vec<usize> = (0..parsed.len()).filter(|&i| parsed[i].is_some()).collect();
 
-    let results: Vec<bool> = signatures
-        .par_iter()
-        .enumerate()
-        .map(|(idx, signature)| {
-            verifying_keys[idx]
-                .verify(message_refs[idx], signature)
-                .is_ok()
+    let verified: Vec<(usize, bool)> = batchable
+        .par_chunks(BATCH_CHUNK)
+        .flat_map_iter(|chunk| {
+            let mut out = Vec::with_capacity(chunk.len());
+            verify_isolating(&parsed, chunk, &mut out);
+            out
         })
         .collect();
-
+    for (i, valid) in verified {
+        results[i] = valid;
+    }
     Ok(results)
 }
 
 /// Batch verification returning only count of successful verifications
 /// Optimized for consensus vote aggregation where individual failures don't matter
 pub fn verify_batch_count(
-    verifications: &[(Vec<u8>, Vec<u8>, Vec<u8>)],
+    messages: &[&[u8]],
+    signatures: &[&[u8]],
+    public_keys: &[&[u8]],
 ) -> Result<usize, Ed25519Error> {
-    let results = verify_batch(verifications)?;
+    let results = verify_batch(messages, signatures, public_keys)?;
     Ok(results.into_iter().filter(|v| *v).count())
 }
 
+/// A well-formed signature, decoded for the verification equation.
+struct Parsed {
+    a: EdwardsPoint,
+    r: EdwardsPoint,
+    s: Scalar,
+    /// k = H(R || A || M)
+    k: Scalar,
+}
+
+impl Parsed {
+    fn new(message: &[u8], signature: &[u8], public_key: &[u8]) -> Option<Self> {
+        let a_bytes: [u8; 32] = public_key.try_into().ok()?;
+        let signature: &[u8; 64] = signature.try_into().ok()?;
+        let (r_bytes, s_bytes) = signature.split_at(32);
+        let a = CompressedEdwardsY(a_bytes).decompress()?;
+        let r = CompressedEdwardsY(r_bytes.try_into().ok()?).decompress()?;
+        let s = Option::from(Scalar::from_canonical_bytes(s_bytes.try_into().ok()?))?;
+        let hram = Sha512::new()
+            .chain_update(r_bytes)
+            .chain_update(a_bytes)
+            .chain_update(message)
+            .finalize();
+        let k = Scalar::from_bytes_mod_order_wide(&hram.into());
+        Some(Parsed { a, r, s, k })
+    }
+
+    /// `[8](sB - kA - R) = 0`
+    fn verify_single(&self) -> bool {
+        let sb_minus_ka =
+            EdwardsPoint::vartime_double_scalar_mul_basepoint(&self.k, &-self.a, &self.s);
+        (sb_minus_ka - self.r).mul_by_cofactor().is_identity()
+    }
+}
+
+/// One weight per item, derived from every item's s and k (which binds
+/// R, A and the message), so a forger cannot pick inputs that cancel.
+fn batch_weights(items: &[&Parsed]) -> Vec<Scalar> {
+    let mut transcript = Sha512::new_with_prefix(b"aether-ed25519-batch");
+    for item in items {
+        transcript.update(item.s.as_bytes());
+        transcript.update(item.k.as_bytes());
+    }
+    let seed = transcript.finalize();
+    (0..items.len() as u64)
+        .map(|i| {
+            let digest = Sha512::new()
+                .chain_update(seed)
+                .chain_update(i.to_le_bytes())
+                .finalize();
+            let mut weight = [0u8; 16];
+            weight.copy_from_slice(&digest[..16]);
+            Scalar::from(u128::from_le_bytes(weight))
+        })
+        .collect()
+}
+
+/// `[8](-(Σ z·s)B + Σ z·R + Σ (z·k)A) = 0`
+fn verify_combined(items: &[&Parsed]) -> bool {
+    let weights = batch_weights(items);
+    let b_coefficient: Scalar = items.iter().zip(&weights).map(|(item, z)| z * item.s).sum();
+    let scalars = std::iter::once(-b_coefficient)
+        .chain(weights.iter().copied())
+        .chain(items.iter().zip(&weights).map(|(item, z)| z * item.k));
+    let points = std::iter::once(ED25519_BASEPOINT_POINT)
+        .chain(items.iter().map(|item| item.r))
+        .chain(items.iter().map(|item| item.a));
+    EdwardsPoint::vartime_multiscalar_mul(scalars, points)
+        .mul_by_cofactor()
+        .is_identity()
+}
+
+/// Verify `indices` of `parsed` as one batch, bisecting on failure, and
+/// append each index's result to `out`.
+fn verify_isolating(parsed: &[Option<Parsed>], indices: &[usize], out: &mut Vec<(usize, bool)>) {
+    let items: Vec<&Parsed> = indices
+        .iter()
+        .map(|&i| parsed[i].as_ref().expect("only parsed entries are batched"))
+        .collect();
+
+    if indices.len() <= BISECT_FLOOR {
+        out.extend(
+            indices
+                .iter()
+                .zip(&items)
+                .map(|(&i, item)| (i, item.verify_single())),
+        );
+        return;
+    }
+
+    if verify_combined(&items) {
+        out.extend(indices.iter().map(|&i| (i, true)));
+        return;
+    }
+
+    let (left, right) = indices.split_at(indices.len() / 2);
+    verify_isolating(parsed, left, out);
+    verify_isolating(parsed, right, out);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    type Verification = (Vec<u8>, Vec<u8>, Vec<u8>);
+    type Columns<'a> = (Vec<&'a [u8]>, Vec<&'a [u8]>, Vec<&'a [u8]>);
+
+    fn signed(count: usize, label: &str) -> Vec<Verification> {
+        (0..count)
+            .map(|i| {
+                let keypair = Keypair::generate();
+                let message = format!("{label} {i}").into_bytes();
+                let signature = keypair.sign(&message);
+                (keypair.public_key(), message, signature)
+            })
+            .collect()
+    }
+
+    fn split(verifications: &[Verification]) -> Columns<'_> {
+        let messages = verifications.iter().map(|(_, m, _)| m.as_slice()).collect();
+        let signatures = verifications.iter().map(|(_, _, s)| s.as_slice()).collect();
+        let keys = verifications
+            .iter()
+            .map(|(pk, _, _)| pk.as_slice())
+            .collect();
+        (messages, signatures, keys)
+    }
+
     #[test]
     fn test_sign_verify() {
         let keypair = Keypair::generate();
@@ -170,18 +299,10 @@ mod tests {
     #[test]
     fn test_batch_verification() {
         let count = 100;
-        let mut verifications = Vec::new();
-
-        for i in 0..count {
-            let keypair = Keypair::generate();
-            let message = format!("message {}", i).into_bytes();
-            let signature = keypair.sign(&message);
-            let public_key = keypair.public_key();
-
-            verifications.push((public_key, message, signature));
-        }
+        let verifications = signed(count, "message");
 
-        let results = verify_batch(&verifications).unwrap();
+        let (messages, signatures, keys) = split(&verifications);
+        let results = verify_batch(&messages, &signatures, &keys).unwrap();
         assert_eq!(results.len(), count);
         assert_eq!(results.iter().filter(|&&v| v).count(), count);
     }
@@ -189,31 +310,128 @@ mod tests {
     #[test]
     fn test_batch_verification_with_failures() {
         let count = 50;
-        let mut verifications = Vec::new();
-
-        // Add 25 valid signatures
-        for i in 0..count / 2 {
-            let keypair = Keypair::generate();
-            let message = format!("valid {}", i).into_bytes();
-            let signature = keypair.sign(&message);
-            let public_key = keypair.public_key();
-            verifications.push((public_key, message, signature));
-        }
-
-        // Add 25 invalid signatures
-        for i in 0..count / 2 {
-            let keypair = Keypair::generate();
-            let message = format!("invalid {}", i).into_bytes();
-            let mut signature = keypair.sign(&message);
+        // 25 valid signatures, then 25 invalid ones
+        let mut verifications = signed(count / 2, "valid");
+        let mut invalid = signed(count / 2, "invalid");
+        for (_, _, signature) in invalid.iter_mut() {
             signature[0] ^= 0x01; // Corrupt
-            let public_key = keypair.public_key();
-            verifications.push((public_key, message, signature));
         }
+        verifications.extend(invalid);
 
-        let count_valid = verify_batch_count(&verifications).unwrap();
+        let (messages, signatures, keys) = split(&verifications);
+        let count_valid = verify_batch_count(&messages, &signatures, &keys).unwrap();
         assert_eq!(count_valid, count / 2);
     }
 
+    #[test]
+    fn test_batch_isolates_failures() {
+        // Spans several chunks, with failures of every kind scattered through.
+        let mut verifications = signed(3 * BATCH_CHUNK + 7, "mixed");
+        let bad = [
+            0,
+            5,
+            BATCH_CHUNK + 1,
+            2 * BATCH_CHUNK + 30,
+            3 * BATCH_CHUNK + 6,
+        ];
+        verifications[bad[0]].2[10] ^= 0x01;
+        verifications[bad[1]].1.push(0);
+        let other = Keypair::generate().public_key();
+        verifications[bad[2]].0 = other;
+        verifications[bad[3]].2.truncate(63);
+        verifications[bad[4]].0 = vec![0xff; 32];
+
+        let (messages, signatures, keys) = split(&verifications);
+        let results = verify_batch(&messages, &signatures, &keys).unwrap();
+        for (i, valid) in results.iter().enumerate() {
+            assert_eq!(*valid, !bad.contains(&i), "signature {i}");
+        }
+
+        assert!(verify_batch(&[], &[], &[]).unwrap().is_empty());
+        assert!(matches!(
+            verify_batch(&messages, &signatures[1..], &keys),
+            Err(Ed25519Error::BatchLength)
+        ));
+    }
+
+    #[test]
+    fn test_batch_matches_verify_on_non_canonical_r() {
+        // Identity key, s = 0 and R the identity encoded with its sign bit
+        // set. ZIP-215 accepts non-canonical encodings; what matters is
+        // that the batch agrees with `verify`.
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        let mut signature = vec![0u8; 64];
+        signature[..32].copy_from_slice(&identity);
+        signature[31] |= 0x80;
+        let message = b"non-canonical".to_vec();
+        let alone = verify(&identity, &message, &signature).is_ok();
+
+        let mut verifications = signed(2 * BATCH_CHUNK, "filler");
+        verifications.insert(BATCH_CHUNK / 2, (identity.to_vec(), message, signature));
+        let (messages, signatures, keys) = split(&verifications);
+        let results = verify_batch(&messages, &signatures, &keys).unwrap();
+        for (i, valid) in results.iter().enumerate() {
+            let expected = if i == BATCH_CHUNK / 2 { alone } else { true };
+            assert_eq!(*valid, expected, "signature {i}");
+        }
+    }
+
+    /// A signature by a mixed-order key `aB + T`, with `T` of order 8, for
+    /// a message whose k is not a multiple of 8: the cofactorless equation
+    /// is off by `-kT` and rejects it, the cofactored one accepts.
+    fn mixed_order_signature() -> Verification {
+        let torsion = curve25519_dalek::constants::EIGHT_TORSION[1];
+        let a = Scalar::from(0x1234_5678_u64);
+        let r = Scalar::from(0x9abc_def0_u64);
+        let key = (ED25519_BASEPOINT_POINT * a + torsion)
+            .compress()
+            .to_bytes();
+        let big_r = (ED25519_BASEPOINT_POINT * r).compress().to_bytes();
+        for attempt in 0u32.. {
+            let message = format!("torsion {attempt}").into_bytes();
+            let hram = Sha512::new()
+                .chain_update(big_r)
+                .chain_update(key)
+                .chain_update(&message)
+                .finalize();
+            let k = Scalar::from_bytes_mod_order_wide(&hram.into());
+            if (torsion * k).is_identity() {
+                continue;
+            }
+            let mut signature = big_r.to_vec();
+            signature.extend_from_slice((r + k * a).as_bytes());
+            return (key.to_vec(), message, signature);
+        }
+        unreachable!()
+    }
+
+    #[test]
+    fn test_torsion_is_accepted_alone_and_in_batches() {
+        let (key, message, signature) = mixed_order_signature();
+        let cofactorless =
+            ed25519_dalek::VerifyingKey::from_bytes(&key.clone().try_into().unwrap())
+                .unwrap()
+                .verify_strict(
+                    &message,
+                    &ed25519_dalek::Signature::from_slice(&signature).unwrap(),
+                );
+        assert!(cofactorless.is_err(), "the vector exercises torsion");
+        assert!(verify(&key, &message, &signature).is_ok());
+
+        let mut tampered = signature.clone();
+        tampered[40] ^= 1;
+        let mut verifications = signed(2 * BATCH_CHUNK, "filler");
+        verifications.insert(3, (key.clone(), message.clone(), signature));
+        verifications.insert(BATCH_CHUNK + 3, (key, message, tampered.clone()));
+        let (messages, signatures, keys) = split(&verifications);
+        let results = verify_batch(&messages, &signatures, &keys).unwrap();
+        for (i, valid) in results.iter().enumerate() {
+            assert_eq!(*valid, i != BATCH_CHUNK + 3, "signature {i}");
+        }
+        assert!(verify(keys[BATCH_CHUNK + 3], messages[BATCH_CHUNK + 3], &tampered).is_err());
+    }
+
     #[test]
     #[ignore] // Performance test - run with --ignored
     fn test_phase4_batch_performance() {
@@ -222,20 +440,12 @@ mod tests {
         use std::time::Instant;
 
         let batch_size = 10_000;
-        let mut verifications = Vec::with_capacity(batch_size);
-
-        // Generate test signatures
-        for i in 0..batch_size {
-            let keypair = Keypair::generate();
-            let message = format!("perf test {}", i).into_bytes();
-            let signature = keypair.sign(&message);
-            let public_key = keypair.public_key();
-            verifications.push((public_key, message, signature));
-        }
+        let verifications = signed(batch_size, "perf test");
+        let (messages, signatures, keys) = split(&verifications);
 
         // Measure batch verification time
         let start = Instant::now();
-        let results = verify_batch(&verifications).unwrap();
+        let results = verify_batch(&messages, &signatures, &keys).unwrap();
         let elapsed = start.elapsed();
 
         let successes = results.iter().filter(|&&v| v).count();
@@ -363,7 +573,10 @@ mod proptests {
                 let pk = kp.public_key();
                 verifications.push((pk, messages[i].clone(), sig));
             }
-            let batch_results = verify_batch(&verifications).unwrap();
+            let messages: Vec<&[u8]> = verifications.iter().map(|(_, m, _)| m.as_slice()).collect();
+            let signatures: Vec<&[u8]> = verifications.iter().map(|(_, _, s)| s.as_slice()).collect();
+            let keys: Vec<&[u8]> = verifications.iter().map(|(pk, _, _)| pk.as_slice()).collect();
+            let batch_results = verify_batch(&messages, &signatures, &keys).unwrap();
             for (i, (pk, msg, sig)) in verifications.iter().enumerate() {
                 let individual = verify(pk, msg, sig).is_ok();
                 prop_assert_eq!(
@@ -1,6 +1,58 @@
-use blake3::Hasher as Blake3Hasher;
 use sha2::{Digest, Sha256};
 
+use crate::poseidon::PoseidonHasher;
+
+/// Incremental hashing to a 32-byte digest, implemented by every suite in
+/// this module so commitments can be generic over the hash.
+pub trait Hasher: Default {
+    fn update(&mut self, data: &[u8]);
+
+    fn finalize(self) -> [u8; 32];
+
+    #[must_use]
+    fn digest(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Self::default();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Sha256Hasher(Sha256);
+
+impl Hasher for Sha256Hasher {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        Digest::finalize(self.0).into()
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct Blake3Hasher(blake3::Hasher);
+
+impl Hasher for Blake3Hasher {
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        self.0.finalize().into()
+    }
+}
+
+impl Hasher for PoseidonHasher {
+    fn update(&mut self, data: &[u8]) {
+        PoseidonHasher::update(self, data);
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        PoseidonHasher::finalize(self)
+    }
+}
+
 #[inline]
 #[must_use]
 pub fn sha256(data: &[u8]) -> [u8; 32] {
@@ -12,11 +64,19 @@ pub fn sha256(data: &[u8]) -> [u8; 32] {
 #[inline]
 #[must_use]
 pub fn blake3_hash(data: &[u8]) -> [u8; 32] {
-    let mut hasher = Blake3Hasher::new();
+    let mut hasher = blake3::Hasher::new();
     hasher.update(data);
     hasher.finalize().into()
 }
 
+/// Poseidon over BN254, for commitments a SNARK circuit has to recompute.
+/// Far slower than SHA-256 or BLAKE3 natively; use it only where a proof
+/// needs it.
+#[must_use]
+pub fn poseidon_hash(data: &[u8]) -> [u8; 32] {
+    PoseidonHasher::digest(data)
+}
+
 #[must_use]
 pub fn hash_multiple(chunks: &[&[u8]]) -> [u8; 32] {
     let mut hasher = Sha256::new();
@@ -51,6 +111,26 @@ mod tests {
         let hash2 = blake3_hash(data);
         assert_eq!(hash, hash2);
     }
+
+    fn digest_in_pieces<H: Hasher>(data: &[u8]) -> [u8; 32] {
+        let mut hasher = H::default();
+        for chunk in data.chunks(13) {
+            hasher.update(chunk);
+        }
+        hasher.finalize()
+    }
+
+    #[test]
+    fn hashers_match_one_shot_functions() {
+        let data = vec![0x5au8; 100];
+        assert_eq!(digest_in_pieces::<Sha256Hasher>(&data), sha256(&data));
+        assert_eq!(digest_in_pieces::<Blake3Hasher>(&data), blake3_hash(&data));
+        assert_eq!(
+            digest_in_pieces::<PoseidonHasher>(&data),
+            poseidon_hash(&data)
+        );
+        assert_ne!(poseidon_hash(&data), sha256(&data));
+    }
 }
 
 #[cfg(test)]
@@ -126,6 +206,16 @@ mod proptests {
             prop_assert_ne!(h_ab, h_ba, "hash_multiple must be order-sensitive");
         }
 
+        /// poseidon_hash is deterministic and order-sensitive.
+        #[test]
+        fn poseidon_deterministic(data in prop::collection::vec(any::<u8>(), 0..128)) {
+            prop_assert_eq!(poseidon_hash(&data), poseidon_hash(&data));
+            let mut reversed = data.clone();
+            reversed.reverse();
+            prop_assume!(reversed != data);
+            prop_assert_ne!(poseidon_hash(&data), poseidon_hash(&reversed));
+        }
+
         /// hash_multiple([data]) equals sha256(data) — single-chunk consistency.
         #[test]
         fn hash_multiple_single_matches_sha256(data in prop::collection::vec(any::<u8>(), 1..128)) {
@@ -1,4 +1,39 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256, Sha512};
+use thiserror::Error;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
 use crate::ed25519;
+use crate::secret::SecretBytes;
+
+/// SLIP-44 coin type of Aether keys in BIP-44 style paths.
+pub const AETHER_COIN_TYPE: u32 = 7777;
+
+/// Hardened bit of a derivation path component.
+pub const HARDENED: u32 = 0x8000_0000;
+
+const BIP39_ENGLISH: &str = include_str!("bip39_english.txt");
+const PBKDF2_ROUNDS: u32 = 2048;
+const SLIP10_CURVE: &[u8] = b"ed25519 seed";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum HdError {
+    #[error("mnemonic must have 12, 15, 18, 21 or 24 words, not {0}")]
+    WordCount(usize),
+    #[error("entropy must be 16 to 32 bytes in steps of 4, not {0}")]
+    EntropyLength(usize),
+    #[error("{0:?} is not a BIP-39 English word")]
+    UnknownWord(String),
+    #[error("mnemonic checksum mismatch")]
+    Checksum,
+    #[error("invalid derivation path: {0}")]
+    Path(String),
+}
 
 pub struct Keypair {
     inner: ed25519::Keypair,
@@ -27,7 +62,7 @@ impl Keypair {
 
     #[inline]
     #[must_use]
-    pub fn secret_key(&self) -> Vec<u8> {
+    pub fn secret_key(&self) -> SecretBytes {
         self.inner.secret_key()
     }
 
@@ -36,9 +71,21 @@ impl Keypair {
         self.inner.sign(message)
     }
 
+    /// The SLIP-10 Ed25519 key at `path` below a BIP-39 seed.
+    #[must_use]
+    pub fn from_seed(seed: &[u8], path: &DerivationPath) -> Self {
+        let node = slip10_derive(seed, path);
+        Keypair::from_bytes(&node.key).expect("SLIP-10 keys are 32 bytes")
+    }
+
+    /// The key at `path` for `mnemonic` and its optional passphrase.
+    #[must_use]
+    pub fn from_mnemonic(mnemonic: &Mnemonic, passphrase: &str, path: &DerivationPath) -> Self {
+        Self::from_seed(mnemonic.to_seed(passphrase).as_slice(), path)
+    }
+
     #[must_use]
     pub fn to_address(&self) -> [u8; 20] {
-        use sha2::{Digest, Sha256};
         let pubkey = self.public_key();
         let hash = Sha256::digest(&pubkey);
         let mut addr = [0u8; 20];
@@ -46,3 +93,411 @@ impl Keypair {
         addr
     }
 }
+
+// ============================================================================
+// BIP-39 mnemonics
+// ============================================================================
+
+/// A BIP-39 mnemonic over the English wordlist.
+///
+/// Only the entropy is kept; the phrase and the seed are derived on demand
+/// and the entropy is wiped on drop.
+#[derive(Clone, PartialEq, Eq, Zeroize, ZeroizeOnDrop)]
+pub struct Mnemonic {
+    entropy: Vec<u8>,
+}
+
+impl Mnemonic {
+    /// A fresh mnemonic of `word_count` words from the OS RNG.
+    pub fn generate(word_count: usize) -> Result<Self, HdError> {
+        use rand::RngCore;
+
+        if !matches!(word_count, 12 | 15 | 18 | 21 | 24) {
+            return Err(HdError::WordCount(word_count));
+        }
+        let mut entropy = vec![0u8; word_count / 3 * 4];
+        rand::rngs::OsRng.fill_bytes(&mut entropy);
+        Ok(Mnemonic { entropy })
+    }
+
+    pub fn from_entropy(entropy: &[u8]) -> Result<Self, HdError> {
+        if !(16..=32).contains(&entropy.len()) || entropy.len() % 4 != 0 {
+            return Err(HdError::EntropyLength(entropy.len()));
+        }
+        Ok(Mnemonic {
+            entropy: entropy.to_vec(),
+        })
+    }
+
+    /// Recover a mnemonic from its phrase, checking every word and the
+    /// checksum. Case and runs of whitespace are ignored.
+    pub fn from_phrase(phrase: &str) -> Result<Self, HdError> {
+        let normalized = Zeroizing::new(phrase.nfkd().collect::<String>().to_lowercase());
+        let words: Vec<&str> = normalized.split_whitespace().collect();
+        if !matches!(words.len(), 12 | 15 | 18 | 21 | 24) {
+            return Err(HdError::WordCount(words.len()));
+        }
+
+        let mut bits = Zeroizing::new(Vec::with_capacity(words.len() * 11));
+        for word in &words {
+            let index = wordlist()
+                .binary_search(word)
+                .map_err(|_| HdError::UnknownWord((*word).to_string()))?;
+            bits.extend((0..11).rev().map(|bit| (index >> bit) & 1 == 1));
+        }
+        let checksum_bits = words.len() / 3;
+        let (entropy_bits, checksum) = bits.split_at(bits.len() - checksum_bits);
+        let entropy: Vec<u8> = entropy_bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0u8, |acc, &bit| acc << 1 | u8::from(bit)))
+            .collect();
+        let mnemonic = Mnemonic { entropy };
+        if mnemonic.checksum_bits() != checksum {
+            return Err(HdError::Checksum);
+        }
+        Ok(mnemonic)
+    }
+
+    #[must_use]
+    pub fn entropy(&self) -> &[u8] {
+        &self.entropy
+    }
+
+    #[must_use]
+    pub fn word_count(&self) -> usize {
+        self.entropy.len() * 3 / 4
+    }
+
+    /// The space-separated phrase.
+    #[must_use]
+    pub fn phrase(&self) -> Zeroizing<String> {
+        let mut bits: Vec<bool> = self
+            .entropy
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| (byte >> bit) & 1 == 1))
+            .collect();
+        bits.extend(self.checksum_bits());
+        let words: Vec<&str> = bits
+            .chunks(11)
+            .map(|chunk| {
+                let index = chunk
+                    .iter()
+                    .fold(0usize, |acc, &bit| acc << 1 | usize::from(bit));
+                wordlist()[index]
+            })
+            .collect();
+        bits.zeroize();
+        Zeroizing::new(words.join(" "))
+    }
+
+    /// The 64-byte BIP-39 seed: PBKDF2-HMAC-SHA512 over the phrase, salted
+    /// with "mnemonic" and `passphrase`, both NFKD-normalized.
+    #[must_use]
+    pub fn to_seed(&self, passphrase: &str) -> Zeroizing<[u8; 64]> {
+        let phrase = self.phrase();
+        let salt = Zeroizing::new(format!("mnemonic{passphrase}").nfkd().collect::<String>());
+        let mut seed = Zeroizing::new([0u8; 64]);
+        pbkdf2_sha512(phrase.as_bytes(), salt.as_bytes(), PBKDF2_ROUNDS, &mut seed);
+        seed
+    }
+
+    fn checksum_bits(&self) -> Vec<bool> {
+        let hash = Sha256::digest(&self.entropy);
+        (0..self.entropy.len() / 4)
+            .map(|i| (hash[i / 8] >> (7 - i % 8)) & 1 == 1)
+            .collect()
+    }
+}
+
+impl fmt::Debug for Mnemonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Mnemonic({} words)", self.word_count())
+    }
+}
+
+impl FromStr for Mnemonic {
+    type Err = HdError;
+
+    fn from_str(phrase: &str) -> Result<Self, HdError> {
+        Self::from_phrase(phrase)
+    }
+}
+
+fn wordlist() -> &'static [&'static str] {
+    static WORDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    WORDS.get_or_init(|| BIP39_ENGLISH.lines().collect())
+}
+
+/// PBKDF2-HMAC-SHA512 for a single 64-byte block.
+fn pbkdf2_sha512(password: &[u8], salt: &[u8], rounds: u32, out: &mut [u8; 64]) {
+    let prf = Hmac::<Sha512>::new_from_slice(password).expect("HMAC takes any key length");
+    let mut mac = prf.clone();
+    mac.update(salt);
+    mac.update(&1u32.to_be_bytes());
+    let mut block: [u8; 64] = mac.finalize().into_bytes().into();
+    *out = block;
+    for _ in 1..rounds {
+        let mut mac = prf.clone();
+        mac.update(&block);
+        block = mac.finalize().into_bytes().into();
+        out.iter_mut().zip(&block).for_each(|(o, b)| *o ^= b);
+    }
+    block.zeroize();
+}
+
+// ============================================================================
+// SLIP-10 derivation
+// ============================================================================
+
+/// A path of hardened indices, `m/44'/7777'/0'/0'/0'`.
+///
+/// Ed25519 under SLIP-10 has only hardened derivation, so every component
+/// must be marked hardened (`'` or `h`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DerivationPath {
+    indices: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// The standard Aether path for `account`'s `index`th address:
+    /// `m/44'/7777'/account'/0'/index'`.
+    #[must_use]
+    pub fn aether(account: u32, index: u32) -> Self {
+        DerivationPath {
+            indices: [44, AETHER_COIN_TYPE, account, 0, index]
+                .iter()
+                .map(|i| i | HARDENED)
+                .collect(),
+        }
+    }
+
+    /// Components with the hardened bit set, as `LedgerSigner` takes them.
+    #[must_use]
+    pub fn indices(&self) -> &[u32] {
+        &self.indices
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = HdError;
+
+    fn from_str(path: &str) -> Result<Self, HdError> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(HdError::Path(format!("{path:?} does not start at m")));
+        }
+        let indices = parts
+            .map(|part| {
+                let index = part
+                    .strip_suffix(['\'', 'h', 'H'])
+                    .ok_or_else(|| HdError::Path(format!("{part:?} is not hardened")))?;
+                match index.parse::<u32>() {
+                    Ok(index) if index < HARDENED => Ok(index | HARDENED),
+                    _ => Err(HdError::Path(format!("bad index {part:?}"))),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(DerivationPath { indices })
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        for index in &self.indices {
+            write!(f, "/{}'", index & !HARDENED)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Zeroize, ZeroizeOnDrop)]
+struct Slip10Node {
+    key: [u8; 32],
+    chain_code: [u8; 32],
+}
+
+impl Slip10Node {
+    fn from_hmac(key: &[u8], data: &[&[u8]]) -> Self {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes any key length");
+        for part in data {
+            mac.update(part);
+        }
+        let mut output: [u8; 64] = mac.finalize().into_bytes().into();
+        let node = Slip10Node {
+            key: output[..32].try_into().expect("32 bytes"),
+            chain_code: output[32..].try_into().expect("32 bytes"),
+        };
+        output.zeroize();
+        node
+    }
+
+    fn child(&self, index: u32) -> Self {
+        Self::from_hmac(&self.chain_code, &[&[0], &self.key, &index.to_be_bytes()])
+    }
+}
+
+fn slip10_derive(seed: &[u8], path: &DerivationPath) -> Slip10Node {
+    let master = Slip10Node::from_hmac(SLIP10_CURVE, &[seed]);
+    path.indices
+        .iter()
+        .fold(master, |node, &index| node.child(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::assert_not_leaked;
+
+    fn unhex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn wordlist_is_canonical() {
+        assert_eq!(wordlist().len(), 2048);
+        assert!(wordlist().windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(
+            crate::hash::sha256(format!("{}\n", wordlist().join("\n")).as_bytes()),
+            unhex("2f5eed53a4727b4bf8880d8f3f199efc90e58503646d9ff8eff3a2ed3b24dbda")[..]
+        );
+    }
+
+    #[test]
+    fn bip39_vectors() {
+        // From the BIP-39 reference vectors, all with passphrase "TREZOR".
+        let vectors = [
+            (
+                "00000000000000000000000000000000",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+            ),
+            (
+                "7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f7f",
+                "legal winner thank year wave sausage worth useful legal winner thank yellow",
+                "2e8905819b8723fe2c1d161860e5ee1830318dbf49a83bd451cfb8440c28bd6fa457fe1296106559a3c80937a1c1069be3a3a5bd381ee6260e8d9739fce1f607",
+            ),
+            (
+                "80808080808080808080808080808080",
+                "letter advice cage absurd amount doctor acoustic avoid letter advice cage above",
+                "d71de856f81a8acc65e6fc851a38d4d7ec216fd0796d0a6827a3ad6ed5511a30fa280f12eb2e47ed2ac03b5c462a0358d18d69fe4f985ec81778c1b370b652a8",
+            ),
+            (
+                "ffffffffffffffffffffffffffffffff",
+                "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong",
+                "ac27495480225222079d7be181583751e86f571027b0497b5b5d11218e0a8a13332572917f0f8e5a589620c6f15b11c61dee327651a14c34e18231052e48c069",
+            ),
+            (
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art",
+                "bda85446c68413707090a52022edd26a1c9462295029f2e60cd7c4f2bbd3097170af7a4d73245cafa9c3cca8d561a7c3de6f5d4a10be8ed2a5e608d68f92fcc8",
+            ),
+        ];
+        for (entropy, phrase, seed) in vectors {
+            let mnemonic = Mnemonic::from_entropy(&unhex(entropy)).unwrap();
+            assert_eq!(mnemonic.phrase().as_str(), phrase);
+            assert_eq!(Mnemonic::from_phrase(phrase).unwrap(), mnemonic);
+            assert_eq!(mnemonic.to_seed("TREZOR").to_vec(), unhex(seed));
+        }
+    }
+
+    #[test]
+    fn rejects_bad_phrases() {
+        let phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon";
+        assert_eq!(Mnemonic::from_phrase(phrase), Err(HdError::WordCount(11)));
+        assert_eq!(
+            Mnemonic::from_phrase(&format!("{phrase} abandon")),
+            Err(HdError::Checksum)
+        );
+        assert_eq!(
+            Mnemonic::from_phrase(&format!("{phrase} aboot")),
+            Err(HdError::UnknownWord("aboot".into()))
+        );
+        // Case and spacing do not matter.
+        assert!(Mnemonic::from_phrase(&format!("  {}   ABOUT\n", phrase.to_uppercase())).is_ok());
+        assert_eq!(
+            Mnemonic::from_entropy(&[0; 15]),
+            Err(HdError::EntropyLength(15))
+        );
+        assert_eq!(Mnemonic::generate(13), Err(HdError::WordCount(13)));
+
+        let generated = Mnemonic::generate(24).unwrap();
+        assert_eq!(generated.word_count(), 24);
+        assert_eq!(
+            Mnemonic::from_phrase(&generated.phrase()).unwrap(),
+            generated
+        );
+        assert_eq!(format!("{generated:?}"), "Mnemonic(24 words)");
+    }
+
+    #[test]
+    fn slip10_vectors() {
+        // SLIP-10 test vector 1 for ed25519.
+        let seed = unhex("000102030405060708090a0b0c0d0e0f");
+        let vectors = [
+            (
+                "m",
+                "90046a93de5380a72b5e45010748567d5ea02bbf6522f979e05c0d8d8ca9fffb",
+                "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7",
+                "a4b2856bfec510abab89753fac1ac0e1112364e7d250545963f135f2a33188ed",
+            ),
+            (
+                "m/0'",
+                "8b59aa11380b624e81507a27fedda59fea6d0b779a778918a2fd3590e16e9c69",
+                "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3",
+                "8c8a13df77a28f3445213a0f432fde644acaa215fc72dcdf300d5efaa85d350c",
+            ),
+            (
+                "m/0H/1H",
+                "a320425f77d1b5c2505a6b1b27382b37368ee640e3557c315416801243552f14",
+                "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2",
+                "1932a5270f335bed617d5b935c80aedb1a35bd9fc1e31acafd5372c30f5c1187",
+            ),
+        ];
+        for (path, chain_code, private, public) in vectors {
+            let path: DerivationPath = path.parse().unwrap();
+            let node = slip10_derive(&seed, &path);
+            assert_eq!(node.chain_code.to_vec(), unhex(chain_code), "{path}");
+            assert_eq!(node.key.to_vec(), unhex(private), "{path}");
+            assert_eq!(Keypair::from_seed(&seed, &path).public_key(), unhex(public));
+        }
+    }
+
+    #[test]
+    fn secrets_stay_out_of_debug_output() {
+        let mnemonic = Mnemonic::from_entropy(&[0x5a; 32]).unwrap();
+        assert_not_leaked(format!("{mnemonic:?}"), mnemonic.entropy());
+        assert!(!format!("{mnemonic:?}").contains(mnemonic.phrase().as_str()));
+
+        let key = Keypair::from_mnemonic(&mnemonic, "", &DerivationPath::aether(0, 0));
+        let secret = key.secret_key();
+        assert_not_leaked(format!("{secret:?}"), secret.expose_secret());
+    }
+
+    #[test]
+    fn derives_many_addresses_from_one_mnemonic() {
+        let mnemonic = Mnemonic::from_entropy(&[7u8; 16]).unwrap();
+        let path = DerivationPath::aether(0, 3);
+        assert_eq!(path.to_string(), "m/44'/7777'/0'/0'/3'");
+        assert_eq!(path.to_string().parse::<DerivationPath>().unwrap(), path);
+
+        let key = Keypair::from_mnemonic(&mnemonic, "", &path);
+        let again = Keypair::from_mnemonic(&mnemonic, "", &path);
+        assert_eq!(key.secret_key(), again.secret_key());
+        let others = [
+            Keypair::from_mnemonic(&mnemonic, "", &DerivationPath::aether(0, 4)),
+            Keypair::from_mnemonic(&mnemonic, "", &DerivationPath::aether(1, 3)),
+            Keypair::from_mnemonic(&mnemonic, "passphrase", &path),
+        ];
+        for other in &others {
+            assert_ne!(other.to_address(), key.to_address());
+        }
+
+        assert!("m/44/0'".parse::<DerivationPath>().is_err());
+        assert!("44'/0'".parse::<DerivationPath>().is_err());
+        assert!("m/2147483648'".parse::<DerivationPath>().is_err());
+    }
+}
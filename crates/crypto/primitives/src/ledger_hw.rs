@@ -0,0 +1,369 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::keypair::HARDENED;
+use crate::signer::{checked, Signer, SignerError};
+
+/// Size of a Ledger HID report.
+pub const HID_PACKET_SIZE: usize = 64;
+
+const HID_CHANNEL: u16 = 0x0101;
+const HID_TAG_APDU: u8 = 0x05;
+
+const CLA: u8 = 0xe0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN: u8 = 0x03;
+const P1_FIRST: u8 = 0x00;
+const P1_NEXT: u8 = 0x01;
+const P2_LAST: u8 = 0x00;
+const P2_MORE: u8 = 0x80;
+const MAX_APDU_DATA: usize = 255;
+
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+
+/// One HID report in each direction.
+pub trait HidDevice: Send {
+    fn write_packet(&mut self, packet: &[u8; HID_PACKET_SIZE]) -> std::io::Result<()>;
+    fn read_packet(&mut self) -> std::io::Result<[u8; HID_PACKET_SIZE]>;
+}
+
+/// Exchanges one APDU for the device's raw response (data || status word).
+pub trait ApduTransport: Send + Sync {
+    fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, SignerError>;
+}
+
+/// A Linux hidraw node (`/dev/hidrawN`) of a connected Ledger.
+pub struct Hidraw {
+    file: File,
+}
+
+impl Hidraw {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, SignerError> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        Ok(Hidraw { file })
+    }
+}
+
+impl HidDevice for Hidraw {
+    fn write_packet(&mut self, packet: &[u8; HID_PACKET_SIZE]) -> std::io::Result<()> {
+        // hidraw expects the report ID first; Ledger uses none (0).
+        let mut report = [0u8; HID_PACKET_SIZE + 1];
+        report[1..].copy_from_slice(packet);
+        self.file.write_all(&report)
+    }
+
+    fn read_packet(&mut self) -> std::io::Result<[u8; HID_PACKET_SIZE]> {
+        let mut packet = [0u8; HID_PACKET_SIZE];
+        self.file.read_exact(&mut packet)?;
+        Ok(packet)
+    }
+}
+
+/// APDUs over the Ledger HID framing: each message is split into 64-byte
+/// reports carrying the channel, a tag, a sequence number and, in the
+/// first report, the total length.
+pub struct HidTransport<D> {
+    device: Mutex<D>,
+}
+
+impl<D: HidDevice> HidTransport<D> {
+    pub fn new(device: D) -> Self {
+        HidTransport {
+            device: Mutex::new(device),
+        }
+    }
+}
+
+impl<D: HidDevice> ApduTransport for HidTransport<D> {
+    fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let mut device = self
+            .device
+            .lock()
+            .map_err(|_| SignerError::Transport("device lock poisoned".into()))?;
+        for packet in frame(apdu)? {
+            device.write_packet(&packet)?;
+        }
+        unframe(|| device.read_packet())
+    }
+}
+
+/// Split `message` into HID reports.
+pub fn frame(message: &[u8]) -> Result<Vec<[u8; HID_PACKET_SIZE]>, SignerError> {
+    let len = u16::try_from(message.len())
+        .map_err(|_| SignerError::Transport("APDU too long for HID framing".into()))?;
+    let mut payload = Vec::with_capacity(message.len() + 2);
+    payload.extend_from_slice(&len.to_be_bytes());
+    payload.extend_from_slice(message);
+
+    Ok(payload
+        .chunks(HID_PACKET_SIZE - 5)
+        .enumerate()
+        .map(|(seq, chunk)| {
+            let mut packet = [0u8; HID_PACKET_SIZE];
+            packet[..2].copy_from_slice(&HID_CHANNEL.to_be_bytes());
+            packet[2] = HID_TAG_APDU;
+            packet[3..5].copy_from_slice(&(seq as u16).to_be_bytes());
+            packet[5..5 + chunk.len()].copy_from_slice(chunk);
+            packet
+        })
+        .collect())
+}
+
+/// Reassemble one message from HID reports produced by `read`.
+pub fn unframe(
+    mut read: impl FnMut() -> std::io::Result<[u8; HID_PACKET_SIZE]>,
+) -> Result<Vec<u8>, SignerError> {
+    let mut payload = Vec::new();
+    let mut expected = None;
+    let mut seq = 0u16;
+    loop {
+        let packet = read()?;
+        if packet[..2] != HID_CHANNEL.to_be_bytes() || packet[2] != HID_TAG_APDU {
+            return Err(SignerError::InvalidResponse(
+                "unexpected HID channel or tag".into(),
+            ));
+        }
+        if packet[3..5] != seq.to_be_bytes() {
+            return Err(SignerError::InvalidResponse(
+                "HID packet out of sequence".into(),
+            ));
+        }
+        payload.extend_from_slice(&packet[5..]);
+        let total = *expected
+            .get_or_insert_with(|| usize::from(u16::from_be_bytes([packet[5], packet[6]])) + 2);
+        if payload.len() >= total {
+            payload.truncate(total);
+            return Ok(payload.split_off(2));
+        }
+        seq = seq.wrapping_add(1);
+    }
+}
+
+/// An Ed25519 key held by the Aether app on a Ledger device.
+///
+/// The device derives the key at `path` (every component hardened, as
+/// SLIP-10 requires for Ed25519) and asks the user to approve each
+/// signature. The private key never leaves the device.
+pub struct LedgerSigner<T> {
+    transport: T,
+    path: Vec<u32>,
+    public_key: Vec<u8>,
+}
+
+impl<T: ApduTransport> LedgerSigner<T> {
+    /// Connect to the key at `path`, reading its public key once.
+    pub fn new(transport: T, path: &[u32]) -> Result<Self, SignerError> {
+        let path: Vec<u32> = path.iter().map(|index| index | HARDENED).collect();
+        let response = exchange(
+            &transport,
+            INS_GET_PUBLIC_KEY,
+            P1_FIRST,
+            P2_LAST,
+            &encode_path(&path)?,
+        )?;
+        if response.len() != 32 {
+            return Err(SignerError::InvalidResponse(format!(
+                "public key of {} bytes",
+                response.len()
+            )));
+        }
+        Ok(LedgerSigner {
+            transport,
+            path,
+            public_key: response,
+        })
+    }
+
+    #[must_use]
+    pub fn path(&self) -> &[u32] {
+        &self.path
+    }
+}
+
+impl<T: ApduTransport> Signer for LedgerSigner<T> {
+    fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        // The path goes first, then the message, cut into APDU-sized chunks.
+        let mut data = encode_path(&self.path)?;
+        data.extend_from_slice(message);
+        let chunks: Vec<&[u8]> = data.chunks(MAX_APDU_DATA).collect();
+        let mut response = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let p1 = if i == 0 { P1_FIRST } else { P1_NEXT };
+            let p2 = if i + 1 < chunks.len() {
+                P2_MORE
+            } else {
+                P2_LAST
+            };
+            response = exchange(&self.transport, INS_SIGN, p1, p2, chunk)?;
+        }
+        if response.len() != 64 {
+            return Err(SignerError::InvalidResponse(format!(
+                "signature of {} bytes",
+                response.len()
+            )));
+        }
+        checked(&self.public_key, message, response)
+    }
+}
+
+fn encode_path(path: &[u32]) -> Result<Vec<u8>, SignerError> {
+    let depth =
+        u8::try_from(path.len()).map_err(|_| SignerError::Transport("path too deep".into()))?;
+    let mut encoded = Vec::with_capacity(1 + 4 * path.len());
+    encoded.push(depth);
+    for index in path {
+        encoded.extend_from_slice(&index.to_be_bytes());
+    }
+    Ok(encoded)
+}
+
+/// Send one APDU and return its data once the status word is checked.
+fn exchange(
+    transport: &impl ApduTransport,
+    ins: u8,
+    p1: u8,
+    p2: u8,
+    data: &[u8],
+) -> Result<Vec<u8>, SignerError> {
+    let mut apdu = Vec::with_capacity(5 + data.len());
+    apdu.extend_from_slice(&[CLA, ins, p1, p2, data.len() as u8]);
+    apdu.extend_from_slice(data);
+
+    let mut response = transport.exchange(&apdu)?;
+    if response.len() < 2 {
+        return Err(SignerError::InvalidResponse("missing status word".into()));
+    }
+    let sw = response.split_off(response.len() - 2);
+    match u16::from_be_bytes([sw[0], sw[1]]) {
+        SW_OK => Ok(response),
+        SW_USER_REJECTED => Err(SignerError::Rejected),
+        status => Err(SignerError::Device(status)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed25519;
+    use crate::keypair::Keypair;
+    use std::collections::VecDeque;
+
+    /// Emulates the device end of the Aether app over HID.
+    struct FakeLedger {
+        keypair: Keypair,
+        approve: bool,
+        pending: Vec<[u8; HID_PACKET_SIZE]>,
+        replies: VecDeque<[u8; HID_PACKET_SIZE]>,
+        signing: Vec<u8>,
+    }
+
+    impl FakeLedger {
+        fn new(approve: bool) -> Self {
+            FakeLedger {
+                keypair: Keypair::from_bytes(&[9u8; 32]).unwrap(),
+                approve,
+                pending: Vec::new(),
+                replies: VecDeque::new(),
+                signing: Vec::new(),
+            }
+        }
+
+        fn handle(&mut self, apdu: &[u8]) -> Vec<u8> {
+            let (header, data) = apdu.split_at(5);
+            assert_eq!(header[0], CLA);
+            assert_eq!(usize::from(header[4]), data.len());
+            let mut reply = match (header[1], header[2], header[3]) {
+                (INS_GET_PUBLIC_KEY, P1_FIRST, P2_LAST) => {
+                    assert_eq!(data, encode_path(&[44 | HARDENED, 7 | HARDENED]).unwrap());
+                    self.keypair.public_key()
+                }
+                (INS_SIGN, p1, p2) => {
+                    if p1 == P1_FIRST {
+                        self.signing.clear();
+                    }
+                    self.signing.extend_from_slice(data);
+                    if p2 == P2_MORE {
+                        Vec::new()
+                    } else if !self.approve {
+                        return SW_USER_REJECTED.to_be_bytes().to_vec();
+                    } else {
+                        let depth = usize::from(self.signing[0]);
+                        self.keypair.sign(&self.signing[1 + 4 * depth..])
+                    }
+                }
+                _ => return 0x6d00u16.to_be_bytes().to_vec(),
+            };
+            reply.extend_from_slice(&SW_OK.to_be_bytes());
+            reply
+        }
+    }
+
+    impl HidDevice for FakeLedger {
+        fn write_packet(&mut self, packet: &[u8; HID_PACKET_SIZE]) -> std::io::Result<()> {
+            self.pending.push(*packet);
+            let mut packets = self.pending.clone().into_iter();
+            if let Ok(apdu) = unframe(|| {
+                packets
+                    .next()
+                    .ok_or_else(|| std::io::ErrorKind::WouldBlock.into())
+            }) {
+                self.pending.clear();
+                let reply = self.handle(&apdu);
+                self.replies.extend(frame(&reply).unwrap());
+            }
+            Ok(())
+        }
+
+        fn read_packet(&mut self) -> std::io::Result<[u8; HID_PACKET_SIZE]> {
+            self.replies
+                .pop_front()
+                .ok_or_else(|| std::io::ErrorKind::UnexpectedEof.into())
+        }
+    }
+
+    #[test]
+    fn hid_framing_roundtrip() {
+        for len in [0, 1, 59, 60, 61, 200, 600] {
+            let message: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let mut packets = frame(&message).unwrap().into_iter();
+            let decoded = unframe(|| Ok(packets.next().unwrap())).unwrap();
+            assert_eq!(decoded, message);
+            assert!(packets.next().is_none());
+        }
+
+        let mut packets = frame(&[1u8; 100]).unwrap();
+        packets.swap(0, 1);
+        let mut packets = packets.into_iter();
+        assert!(unframe(|| Ok(packets.next().unwrap())).is_err());
+    }
+
+    #[test]
+    fn signs_on_device() {
+        let signer = LedgerSigner::new(HidTransport::new(FakeLedger::new(true)), &[44, 7]).unwrap();
+        assert_eq!(signer.path(), &[44 | HARDENED, 7 | HARDENED]);
+        let public_key = Signer::public_key(&signer);
+
+        // Long enough to need several APDUs.
+        for message in [b"short".to_vec(), vec![0x42; 700]] {
+            let signature = Signer::sign(&signer, &message).unwrap();
+            assert!(ed25519::verify(&public_key, &message, &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn surfaces_user_rejection() {
+        let signer =
+            LedgerSigner::new(HidTransport::new(FakeLedger::new(false)), &[44, 7]).unwrap();
+        assert!(matches!(
+            Signer::sign(&signer, b"tx"),
+            Err(SignerError::Rejected)
+        ));
+    }
+}
@@ -5,7 +5,25 @@
 //
 // CRYPTOGRAPHIC SUITE:
 // - Signing: Ed25519 (transaction signatures)
-// - Hashing: SHA-256 (general), BLAKE3 (PoH-style sequencing)
+// - Hashing: SHA-256 (general), BLAKE3 (PoH-style sequencing),
+//   Poseidon over BN254 (SNARK-friendly commitments); all behind `Hasher`
+//
+// SECRETS:
+// - Private key bytes leave a keypair only as `SecretBytes`: zeroized on
+//   drop, redacted in Debug, constant-time eq, never Serialize
+//
+// HD KEYS:
+// - BIP-39 mnemonics and SLIP-10 derivation along m/44'/7777'/account'/...
+//   in `keypair`, so many addresses come from one backed-up seed
+//
+// MULTISIG:
+// - `MultisigPolicy` (M-of-N Ed25519 keys), its canonical signing payload
+//   and `SignatureCollector` for gathering cosigner approvals
+//
+// SIGNERS:
+// - `Signer` abstracts where a signing key lives: in process (`Keypair`),
+//   on a Ledger device (`LedgerSigner`, APDUs over HID) or in another
+//   process (`RemoteSigner`, over TCP)
 //
 // COMPONENT CONNECTIONS:
 // ┌──────────────────────────────────────────────────────────────────┐
@@ -25,7 +43,21 @@
 pub mod ed25519;
 pub mod hash;
 pub mod keypair;
+pub mod ledger_hw;
+pub mod multisig;
+pub mod poseidon;
+pub mod remote_signer;
+pub mod secret;
+pub mod signer;
 
 pub use ed25519::{verify, Keypair as Ed25519Keypair};
-pub use hash::{blake3_hash, hash_multiple, sha256};
-pub use keypair::Keypair;
+pub use hash::{
+    blake3_hash, hash_multiple, poseidon_hash, sha256, Blake3Hasher, Hasher, Sha256Hasher,
+};
+pub use keypair::{DerivationPath, HdError, Keypair, Mnemonic};
+pub use ledger_hw::{HidTransport, Hidraw, LedgerSigner};
+pub use multisig::{MultisigError, MultisigPolicy, MultisigSignature, SignatureCollector};
+pub use poseidon::PoseidonHasher;
+pub use remote_signer::RemoteSigner;
+pub use secret::SecretBytes;
+pub use signer::{Signer, SignerError};
@@ -0,0 +1,471 @@
+use std::collections::BTreeMap;
+
+use ed25519_dalek::VerifyingKey;
+use thiserror::Error;
+
+use crate::ed25519;
+use crate::hash::sha256;
+use crate::signer::{Signer, SignerError};
+
+/// Most keys a policy may name; the signer bitmap is a `u16`.
+pub const MAX_MULTISIG_KEYS: usize = 16;
+
+/// Longest encoded [`MultisigSignature`]: the bitmap and one signature per
+/// key.
+pub const MAX_MULTISIG_SIGNATURE_LEN: usize = 2 + 64 * MAX_MULTISIG_KEYS;
+
+const POLICY_MAGIC: &[u8; 4] = b"AMS\x01";
+const PAYLOAD_DOMAIN: &[u8] = b"aether-multisig-v1";
+
+#[derive(Error, Debug)]
+pub enum MultisigError {
+    #[error("threshold {threshold} cannot be met by {keys} keys")]
+    InvalidThreshold { threshold: u8, keys: usize },
+    #[error("{0} keys exceeds the limit of {MAX_MULTISIG_KEYS}")]
+    TooManyKeys(usize),
+    #[error("key {0} is not a valid Ed25519 public key")]
+    InvalidKey(usize),
+    #[error("key {0} appears more than once")]
+    DuplicateKey(usize),
+    #[error("malformed multisig encoding: {0}")]
+    Malformed(&'static str),
+    #[error("{0} is not a signer of this account")]
+    UnknownSigner(String),
+    #[error("signature from signer {0} does not verify")]
+    BadSignature(usize),
+    #[error("{have} of {need} required signatures")]
+    BelowThreshold { have: usize, need: usize },
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+}
+
+/// Who may authorize for an M-of-N account: `threshold` signatures from
+/// distinct `keys`.
+///
+/// Keys are kept sorted, so the encoding, and every address derived from
+/// it, does not depend on the order they were listed in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MultisigPolicy {
+    threshold: u8,
+    keys: Vec<[u8; 32]>,
+}
+
+impl MultisigPolicy {
+    pub fn new(threshold: u8, keys: &[&[u8]]) -> Result<Self, MultisigError> {
+        if keys.len() > MAX_MULTISIG_KEYS {
+            return Err(MultisigError::TooManyKeys(keys.len()));
+        }
+        if threshold == 0 || usize::from(threshold) > keys.len() {
+            return Err(MultisigError::InvalidThreshold {
+                threshold,
+                keys: keys.len(),
+            });
+        }
+        let mut parsed = Vec::with_capacity(keys.len());
+        for (i, key) in keys.iter().enumerate() {
+            let key: [u8; 32] = (*key)
+                .try_into()
+                .map_err(|_| MultisigError::InvalidKey(i))?;
+            VerifyingKey::from_bytes(&key).map_err(|_| MultisigError::InvalidKey(i))?;
+            if parsed.contains(&key) {
+                return Err(MultisigError::DuplicateKey(i));
+            }
+            parsed.push(key);
+        }
+        parsed.sort_unstable();
+        Ok(MultisigPolicy {
+            threshold,
+            keys: parsed,
+        })
+    }
+
+    #[must_use]
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The keys, sorted.
+    #[must_use]
+    pub fn keys(&self) -> &[[u8; 32]] {
+        &self.keys
+    }
+
+    #[must_use]
+    pub fn index_of(&self, public_key: &[u8]) -> Option<usize> {
+        self.keys
+            .iter()
+            .position(|key| key.as_slice() == public_key)
+    }
+
+    /// `magic || threshold u8 || key count u8 || keys`. Always longer than
+    /// an Ed25519 key, so the two cannot be confused where either may
+    /// appear.
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(6 + 32 * self.keys.len());
+        out.extend_from_slice(POLICY_MAGIC);
+        out.push(self.threshold);
+        out.push(self.keys.len() as u8);
+        for key in &self.keys {
+            out.extend_from_slice(key);
+        }
+        out
+    }
+
+    /// Parse an encoding, rejecting anything `new` would not have built,
+    /// including unsorted keys.
+    pub fn decode(bytes: &[u8]) -> Result<Self, MultisigError> {
+        let body = bytes
+            .strip_prefix(POLICY_MAGIC.as_slice())
+            .ok_or(MultisigError::Malformed("missing policy magic"))?;
+        let [threshold, count, keys @ ..] = body else {
+            return Err(MultisigError::Malformed("truncated policy header"));
+        };
+        if keys.len() != 32 * usize::from(*count) {
+            return Err(MultisigError::Malformed("key count does not match length"));
+        }
+        let keys: Vec<&[u8]> = keys.chunks_exact(32).collect();
+        let policy = MultisigPolicy::new(*threshold, &keys)?;
+        if policy.encode() != bytes {
+            return Err(MultisigError::Malformed("keys are not sorted"));
+        }
+        Ok(policy)
+    }
+
+    /// Whether `bytes` claims to be an encoded policy rather than a key.
+    #[must_use]
+    pub fn is_encoded(bytes: &[u8]) -> bool {
+        bytes.len() > 32 && bytes.starts_with(POLICY_MAGIC)
+    }
+
+    /// What each signer signs to authorize `message` for this account.
+    ///
+    /// Domain-separated and bound to the policy, so a cosigner's signature
+    /// can be replayed neither as a single-key signature over `message` nor
+    /// for another account the same key belongs to.
+    #[must_use]
+    pub fn signing_payload(&self, message: &[u8]) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(PAYLOAD_DOMAIN.len() + 32 + message.len());
+        payload.extend_from_slice(PAYLOAD_DOMAIN);
+        payload.extend_from_slice(&sha256(&self.encode()));
+        payload.extend_from_slice(message);
+        payload
+    }
+
+    /// Check that `signature` authorizes `message`: at least `threshold`
+    /// signers, every one of which verifies.
+    pub fn verify(
+        &self,
+        message: &[u8],
+        signature: &MultisigSignature,
+    ) -> Result<(), MultisigError> {
+        if let Some(index) = signature.signers().find(|&i| i >= self.keys.len()) {
+            return Err(MultisigError::UnknownSigner(format!("index {index}")));
+        }
+        let need = usize::from(self.threshold);
+        if signature.len() < need {
+            return Err(MultisigError::BelowThreshold {
+                have: signature.len(),
+                need,
+            });
+        }
+        let payload = self.signing_payload(message);
+        for (index, sig) in signature.iter() {
+            ed25519::verify(&self.keys[index], &payload, sig)
+                .map_err(|_| MultisigError::BadSignature(index))?;
+        }
+        Ok(())
+    }
+}
+
+/// Signatures from a subset of a policy's keys, identified by their index
+/// in the sorted key list.
+///
+/// Encoded as a `u16` LE bitmap of signer indices followed by their 64-byte
+/// signatures in index order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MultisigSignature {
+    signatures: BTreeMap<usize, [u8; 64]>,
+}
+
+impl MultisigSignature {
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// Indices of the keys that signed, ascending.
+    pub fn signers(&self) -> impl Iterator<Item = usize> + '_ {
+        self.signatures.keys().copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &[u8; 64])> + '_ {
+        self.signatures.iter().map(|(i, sig)| (*i, sig))
+    }
+
+    #[must_use]
+    pub fn encode(&self) -> Vec<u8> {
+        let bitmap = self.signers().fold(0u16, |bits, i| bits | (1 << i));
+        let mut out = Vec::with_capacity(2 + 64 * self.len());
+        out.extend_from_slice(&bitmap.to_le_bytes());
+        for sig in self.signatures.values() {
+            out.extend_from_slice(sig);
+        }
+        out
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, MultisigError> {
+        let [lo, hi, sigs @ ..] = bytes else {
+            return Err(MultisigError::Malformed("truncated signer bitmap"));
+        };
+        let bitmap = u16::from_le_bytes([*lo, *hi]);
+        if sigs.len() != 64 * bitmap.count_ones() as usize {
+            return Err(MultisigError::Malformed(
+                "signature count does not match bitmap",
+            ));
+        }
+        let indices = (0..MAX_MULTISIG_KEYS).filter(|i| bitmap & (1 << i) != 0);
+        let signatures = indices
+            .zip(sigs.chunks_exact(64))
+            .map(|(i, sig)| (i, sig.try_into().expect("64-byte chunk")))
+            .collect();
+        Ok(MultisigSignature { signatures })
+    }
+}
+
+/// Gathers partial signatures for one message until a policy's threshold
+/// is met.
+///
+/// Each signature is checked as it arrives, so a bad one is blamed on the
+/// cosigner who sent it rather than discovered when the finished signature
+/// is rejected.
+#[derive(Clone, Debug)]
+pub struct SignatureCollector {
+    policy: MultisigPolicy,
+    payload: Vec<u8>,
+    signature: MultisigSignature,
+}
+
+impl SignatureCollector {
+    #[must_use]
+    pub fn new(policy: MultisigPolicy, message: &[u8]) -> Self {
+        let payload = policy.signing_payload(message);
+        SignatureCollector {
+            policy,
+            payload,
+            signature: MultisigSignature::default(),
+        }
+    }
+
+    /// The bytes each cosigner must sign.
+    #[must_use]
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    #[must_use]
+    pub fn policy(&self) -> &MultisigPolicy {
+        &self.policy
+    }
+
+    /// Add `public_key`'s signature over [`payload`](Self::payload),
+    /// returning how many distinct signers have signed so far. Adding the
+    /// same signer again replaces its signature.
+    pub fn add(&mut self, public_key: &[u8], signature: &[u8]) -> Result<usize, MultisigError> {
+        let index = self
+            .policy
+            .index_of(public_key)
+            .ok_or_else(|| MultisigError::UnknownSigner(hex_prefix(public_key)))?;
+        ed25519::verify(public_key, &self.payload, signature)
+            .map_err(|_| MultisigError::BadSignature(index))?;
+        let signature = signature
+            .try_into()
+            .expect("verified signatures are 64 bytes");
+        self.signature.signatures.insert(index, signature);
+        Ok(self.signature.len())
+    }
+
+    /// Have `signer` sign the payload and add the result.
+    pub fn sign_with<S: Signer + ?Sized>(&mut self, signer: &S) -> Result<usize, MultisigError> {
+        let public_key = signer.public_key();
+        if self.policy.index_of(&public_key).is_none() {
+            return Err(MultisigError::UnknownSigner(hex_prefix(&public_key)));
+        }
+        let signature = signer.sign(&self.payload)?;
+        self.add(&public_key, &signature)
+    }
+
+    /// The signatures gathered so far, for handing to another cosigner's
+    /// collector.
+    #[must_use]
+    pub fn partial(&self) -> &MultisigSignature {
+        &self.signature
+    }
+
+    /// Add every signature from a partial signature gathered elsewhere,
+    /// returning how many distinct signers have signed so far.
+    pub fn merge(&mut self, partial: &MultisigSignature) -> Result<usize, MultisigError> {
+        for (index, signature) in partial.iter() {
+            let key = *self
+                .policy
+                .keys
+                .get(index)
+                .ok_or_else(|| MultisigError::UnknownSigner(format!("index {index}")))?;
+            self.add(&key, signature)?;
+        }
+        Ok(self.signature.len())
+    }
+
+    #[must_use]
+    pub fn is_complete(&self) -> bool {
+        self.signature.len() >= usize::from(self.policy.threshold)
+    }
+
+    /// Keys that have not signed yet.
+    #[must_use]
+    pub fn missing(&self) -> Vec<[u8; 32]> {
+        self.policy
+            .keys
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.signature.signatures.contains_key(i))
+            .map(|(_, key)| *key)
+            .collect()
+    }
+
+    /// The combined signature, once the threshold is met.
+    pub fn finish(&self) -> Result<MultisigSignature, MultisigError> {
+        if !self.is_complete() {
+            return Err(MultisigError::BelowThreshold {
+                have: self.signature.len(),
+                need: usize::from(self.policy.threshold),
+            });
+        }
+        Ok(self.signature.clone())
+    }
+}
+
+fn hex_prefix(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().take(8).map(|b| format!("{b:02x}")).collect();
+    format!("key {hex}..")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keypair::Keypair;
+
+    fn team(n: usize, threshold: u8) -> (Vec<Keypair>, MultisigPolicy) {
+        let keypairs: Vec<Keypair> = (0..n).map(|_| Keypair::generate()).collect();
+        let keys: Vec<Vec<u8>> = keypairs.iter().map(Keypair::public_key).collect();
+        let keys: Vec<&[u8]> = keys.iter().map(Vec::as_slice).collect();
+        let policy = MultisigPolicy::new(threshold, &keys).unwrap();
+        (keypairs, policy)
+    }
+
+    #[test]
+    fn policy_is_validated_and_canonical() {
+        let (keypairs, policy) = team(3, 2);
+        let pk: Vec<Vec<u8>> = keypairs.iter().map(Keypair::public_key).collect();
+
+        let reversed = MultisigPolicy::new(2, &[&pk[2], &pk[1], &pk[0]]).unwrap();
+        assert_eq!(reversed.encode(), policy.encode());
+        assert_eq!(MultisigPolicy::decode(&policy.encode()).unwrap(), policy);
+        assert!(MultisigPolicy::is_encoded(&policy.encode()));
+        assert!(!MultisigPolicy::is_encoded(&pk[0]));
+
+        assert!(matches!(
+            MultisigPolicy::new(0, &[&pk[0]]),
+            Err(MultisigError::InvalidThreshold { .. })
+        ));
+        assert!(matches!(
+            MultisigPolicy::new(3, &[&pk[0], &pk[1]]),
+            Err(MultisigError::InvalidThreshold { .. })
+        ));
+        assert!(matches!(
+            MultisigPolicy::new(1, &[&pk[0], &pk[0]]),
+            Err(MultisigError::DuplicateKey(1))
+        ));
+        assert!(matches!(
+            MultisigPolicy::new(1, &[&pk[0][..31]]),
+            Err(MultisigError::InvalidKey(0))
+        ));
+
+        // Swapping two keys in the encoding is caught, not silently sorted.
+        let mut unsorted = policy.encode();
+        let (a, b) = (6..38, 38..70);
+        let first = unsorted[a.clone()].to_vec();
+        unsorted.copy_within(b.clone(), a.start);
+        unsorted[b].copy_from_slice(&first);
+        assert!(matches!(
+            MultisigPolicy::decode(&unsorted),
+            Err(MultisigError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn collects_partial_signatures_to_threshold() {
+        let (keypairs, policy) = team(3, 2);
+        let mut collector = SignatureCollector::new(policy.clone(), b"tx hash");
+        assert!(matches!(
+            collector.finish(),
+            Err(MultisigError::BelowThreshold { have: 0, need: 2 })
+        ));
+
+        assert_eq!(collector.sign_with(&keypairs[2]).unwrap(), 1);
+        // A cosigner sending the same partial signature twice counts once.
+        let partial = keypairs[2].sign(collector.payload());
+        assert_eq!(
+            collector.add(&keypairs[2].public_key(), &partial).unwrap(),
+            1
+        );
+        assert_eq!(collector.missing().len(), 2);
+        assert!(!collector.is_complete());
+
+        // The other half is gathered by a second cosigner and merged in.
+        let mut elsewhere = SignatureCollector::new(policy.clone(), b"tx hash");
+        elsewhere.sign_with(&keypairs[0]).unwrap();
+        assert_eq!(collector.merge(elsewhere.partial()).unwrap(), 2);
+        let signature = collector.finish().unwrap();
+        let decoded = MultisigSignature::decode(&signature.encode()).unwrap();
+        assert_eq!(decoded, signature);
+        policy.verify(b"tx hash", &decoded).unwrap();
+        assert!(matches!(
+            policy.verify(b"other tx", &decoded),
+            Err(MultisigError::BadSignature(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_outsiders_and_single_key_replays() {
+        let (keypairs, policy) = team(2, 1);
+        let mut collector = SignatureCollector::new(policy.clone(), b"m");
+
+        let outsider = Keypair::generate();
+        assert!(matches!(
+            collector.sign_with(&outsider),
+            Err(MultisigError::UnknownSigner(_))
+        ));
+        // A plain signature over the message is not a multisig approval.
+        assert!(matches!(
+            collector.add(&keypairs[0].public_key(), &keypairs[0].sign(b"m")),
+            Err(MultisigError::BadSignature(_))
+        ));
+
+        let mut signature = MultisigSignature::default();
+        signature.signatures.insert(5, [0u8; 64]);
+        assert!(matches!(
+            policy.verify(b"m", &signature),
+            Err(MultisigError::UnknownSigner(_))
+        ));
+        assert!(matches!(
+            MultisigSignature::decode(&[0b11, 0, 1]),
+            Err(MultisigError::Malformed(_))
+        ));
+    }
+}
@@ -0,0 +1,568 @@
+//! Poseidon over the BN254 scalar field.
+//!
+//! Width 3 (rate 2, capacity 1), `x^5` S-box, 8 full and 57 partial
+//! rounds: the `x5_254_3` instance from the Poseidon paper, whose round
+//! constants and MDS matrix come from the reference Grain LFSR and match
+//! circomlib's `Poseidon(2)`. SHA-256 and BLAKE3 cost tens of thousands of
+//! constraints per block inside a SNARK; one Poseidon permutation costs a
+//! few hundred, which is what lets a proof commit to a whole trace.
+
+use std::fmt;
+use std::ops::{Add, Mul, Sub};
+use std::sync::OnceLock;
+
+/// State width.
+pub const WIDTH: usize = 3;
+/// Field elements absorbed per permutation.
+pub const RATE: usize = 2;
+pub const FULL_ROUNDS: usize = 8;
+pub const PARTIAL_ROUNDS: usize = 57;
+
+/// Bytes packed into each field element by the byte sponge; 31 bytes always
+/// fit below the 254-bit modulus.
+const BYTES_PER_ELEMENT: usize = 31;
+
+/// Capacity element for the byte sponge, so that its outputs never collide
+/// with [`hash2`], whose capacity starts at zero.
+const BYTE_SPONGE_DOMAIN: u64 = 1;
+
+/// r = 21888242871839275222246405745257275088548364400416034343698204186575808495617
+const MODULUS: [u64; 4] = [
+    0x43e1_f593_f000_0001,
+    0x2833_e848_79b9_7091,
+    0xb850_45b6_8181_585d,
+    0x3064_4e72_e131_a029,
+];
+
+/// 2^512 mod r, for entering Montgomery form.
+const R2: [u64; 4] = [
+    0x1bb8_e645_ae21_6da7,
+    0x53fe_3ab1_e35c_59e3,
+    0x8c49_833d_53bb_8085,
+    0x0216_d0b1_7f4e_44a5,
+];
+
+/// -r^-1 mod 2^64.
+const INV: u64 = 0xc2e1_f593_efff_ffff;
+
+/// Bit length of the modulus, as fed to the Grain LFSR.
+const FIELD_BITS: usize = 254;
+
+/// An element of the BN254 scalar field, stored in Montgomery form.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Fr([u64; 4]);
+
+impl Fr {
+    pub const ZERO: Fr = Fr([0; 4]);
+
+    #[must_use]
+    pub fn one() -> Fr {
+        Fr::from_u64(1)
+    }
+
+    #[must_use]
+    pub fn from_u64(value: u64) -> Fr {
+        Fr(mont_mul(&[value, 0, 0, 0], &R2))
+    }
+
+    /// Decode a little-endian element, or `None` if it is not below the
+    /// modulus.
+    #[must_use]
+    pub fn from_bytes_le(bytes: &[u8; 32]) -> Option<Fr> {
+        let limbs = limbs_from_le(bytes);
+        if !less_than_modulus(&limbs) {
+            return None;
+        }
+        Some(Fr(mont_mul(&limbs, &R2)))
+    }
+
+    /// Little-endian encoding of the canonical value.
+    #[must_use]
+    pub fn to_bytes_le(self) -> [u8; 32] {
+        let limbs = mont_mul(&self.0, &[1, 0, 0, 0]);
+        let mut bytes = [0u8; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(limbs) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[must_use]
+    pub fn is_zero(self) -> bool {
+        self.0 == [0; 4]
+    }
+
+    #[must_use]
+    pub fn square(self) -> Fr {
+        self * self
+    }
+
+    /// `self^exponent`, with the exponent as little-endian limbs.
+    #[must_use]
+    pub fn pow(self, exponent: &[u64; 4]) -> Fr {
+        let mut result = Fr::one();
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                result = result.square();
+                if (limb >> bit) & 1 == 1 {
+                    result = result * self;
+                }
+            }
+        }
+        result
+    }
+
+    /// Multiplicative inverse, or `None` for zero.
+    #[must_use]
+    pub fn invert(self) -> Option<Fr> {
+        if self.is_zero() {
+            return None;
+        }
+        let mut exponent = MODULUS;
+        exponent[0] -= 2;
+        Some(self.pow(&exponent))
+    }
+
+    #[inline]
+    fn sbox(self) -> Fr {
+        let x2 = self.square();
+        x2.square() * self
+    }
+}
+
+impl Add for Fr {
+    type Output = Fr;
+
+    fn add(self, rhs: Fr) -> Fr {
+        let mut sum = [0u64; 4];
+        let mut carry = 0;
+        for (i, limb) in sum.iter_mut().enumerate() {
+            (*limb, carry) = adc(self.0[i], rhs.0[i], carry);
+        }
+        // Both inputs are below r < 2^254, so the sum cannot overflow 256 bits.
+        Fr(reduce_once(sum))
+    }
+}
+
+impl Sub for Fr {
+    type Output = Fr;
+
+    fn sub(self, rhs: Fr) -> Fr {
+        let (diff, borrow) = sub_limbs(&self.0, &rhs.0);
+        if borrow == 0 {
+            return Fr(diff);
+        }
+        let mut wrapped = [0u64; 4];
+        let mut carry = 0;
+        for (i, limb) in wrapped.iter_mut().enumerate() {
+            (*limb, carry) = adc(diff[i], MODULUS[i], carry);
+        }
+        Fr(wrapped)
+    }
+}
+
+impl Mul for Fr {
+    type Output = Fr;
+
+    fn mul(self, rhs: Fr) -> Fr {
+        Fr(mont_mul(&self.0, &rhs.0))
+    }
+}
+
+impl fmt::Debug for Fr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fr(0x")?;
+        for byte in self.to_bytes_le().iter().rev() {
+            write!(f, "{byte:02x}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = u128::from(a) + u128::from(b) + u128::from(carry);
+    (t as u64, (t >> 64) as u64)
+}
+
+#[inline(always)]
+fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = u128::from(acc) + u128::from(a) * u128::from(b) + u128::from(carry);
+    (t as u64, (t >> 64) as u64)
+}
+
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut out = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(b[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        out[i] = d2;
+        borrow = u64::from(b1 | b2);
+    }
+    (out, borrow)
+}
+
+/// Subtract the modulus once if `limbs` is at least the modulus.
+fn reduce_once(limbs: [u64; 4]) -> [u64; 4] {
+    let (reduced, borrow) = sub_limbs(&limbs, &MODULUS);
+    if borrow == 0 {
+        reduced
+    } else {
+        limbs
+    }
+}
+
+fn less_than_modulus(limbs: &[u64; 4]) -> bool {
+    sub_limbs(limbs, &MODULUS).1 == 1
+}
+
+fn limbs_from_le(bytes: &[u8; 32]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+    for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_le_bytes(chunk.try_into().expect("8 bytes"));
+    }
+    limbs
+}
+
+/// Montgomery multiplication (CIOS): `a * b * 2^-256 mod r`.
+fn mont_mul(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut t = [0u64; 6];
+    for &b_i in b {
+        let mut carry = 0;
+        for j in 0..4 {
+            (t[j], carry) = mac(t[j], a[j], b_i, carry);
+        }
+        (t[4], t[5]) = adc(t[4], carry, 0);
+
+        let m = t[0].wrapping_mul(INV);
+        let (_, mut carry) = mac(t[0], m, MODULUS[0], 0);
+        for j in 1..4 {
+            (t[j - 1], carry) = mac(t[j], m, MODULUS[j], carry);
+        }
+        let (t3, c) = adc(t[4], carry, 0);
+        t[3] = t3;
+        t[4] = t[5] + c;
+    }
+    reduce_once([t[0], t[1], t[2], t[3]])
+}
+
+/// The Grain LFSR in self-shrinking mode, seeded with the instance
+/// parameters, exactly as in the Poseidon reference parameter script.
+struct Grain {
+    state: [bool; 80],
+}
+
+impl Grain {
+    fn new() -> Self {
+        let mut state = [false; 80];
+        let fields: [(u64, usize); 6] = [
+            (1, 2), // prime field
+            (0, 4), // x^alpha S-box
+            (FIELD_BITS as u64, 12),
+            (WIDTH as u64, 12),
+            (FULL_ROUNDS as u64, 10),
+            (PARTIAL_ROUNDS as u64, 10),
+        ];
+        let mut pos = 0;
+        for (value, bits) in fields {
+            for bit in (0..bits).rev() {
+                state[pos] = (value >> bit) & 1 == 1;
+                pos += 1;
+            }
+        }
+        for bit in &mut state[pos..] {
+            *bit = true;
+        }
+
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.copy_within(1.., 0);
+        self.state[79] = bit;
+        bit
+    }
+
+    fn next_bit(&mut self) -> bool {
+        // Emit the second bit of each pair whose first bit is set.
+        while !self.step() {
+            self.step();
+        }
+        self.step()
+    }
+
+    /// `FIELD_BITS` bits, most significant first.
+    fn next_limbs(&mut self) -> [u64; 4] {
+        let mut limbs = [0u64; 4];
+        for _ in 0..FIELD_BITS {
+            let bit = u64::from(self.next_bit());
+            for i in (1..4).rev() {
+                limbs[i] = (limbs[i] << 1) | (limbs[i - 1] >> 63);
+            }
+            limbs[0] = (limbs[0] << 1) | bit;
+        }
+        limbs
+    }
+
+    /// A uniform element, by rejection.
+    fn next_element(&mut self) -> Fr {
+        loop {
+            let limbs = self.next_limbs();
+            if less_than_modulus(&limbs) {
+                return Fr(mont_mul(&limbs, &R2));
+            }
+        }
+    }
+
+    /// An element reduced mod r, as the reference does for MDS inputs.
+    fn next_reduced(&mut self) -> Fr {
+        Fr(mont_mul(&reduce_once(self.next_limbs()), &R2))
+    }
+}
+
+struct Params {
+    round_constants: Vec<[Fr; WIDTH]>,
+    mds: [[Fr; WIDTH]; WIDTH],
+}
+
+impl Params {
+    fn generate() -> Self {
+        let mut grain = Grain::new();
+        let round_constants = (0..FULL_ROUNDS + PARTIAL_ROUNDS)
+            .map(|_| std::array::from_fn(|_| grain.next_element()))
+            .collect();
+
+        // Cauchy matrix 1 / (x_i + y_j) over 2t distinct sampled points.
+        // The reference also screens candidates for invariant subspaces;
+        // the first candidate for this instance passes, and the test
+        // vectors below pin the result.
+        let points: [Fr; 2 * WIDTH] = std::array::from_fn(|_| grain.next_reduced());
+        let mds = std::array::from_fn(|i| {
+            std::array::from_fn(|j| {
+                (points[i] + points[WIDTH + j])
+                    .invert()
+                    .expect("Grain points for x5_254_3 give a valid Cauchy matrix")
+            })
+        });
+        Params {
+            round_constants,
+            mds,
+        }
+    }
+
+    fn get() -> &'static Params {
+        static PARAMS: OnceLock<Params> = OnceLock::new();
+        PARAMS.get_or_init(Params::generate)
+    }
+}
+
+/// The Poseidon permutation, in place.
+pub fn permute(state: &mut [Fr; WIDTH]) {
+    let params = Params::get();
+    let half_full = FULL_ROUNDS / 2;
+    for (round, constants) in params.round_constants.iter().enumerate() {
+        for (cell, constant) in state.iter_mut().zip(constants) {
+            *cell = *cell + *constant;
+        }
+        if round < half_full || round >= half_full + PARTIAL_ROUNDS {
+            for cell in state.iter_mut() {
+                *cell = cell.sbox();
+            }
+        } else {
+            state[0] = state[0].sbox();
+        }
+        let mixed: [Fr; WIDTH] = std::array::from_fn(|i| {
+            params.mds[i]
+                .iter()
+                .zip(state.iter())
+                .fold(Fr::ZERO, |acc, (m, s)| acc + *m * *s)
+        });
+        *state = mixed;
+    }
+}
+
+/// Two-to-one compression, compatible with circomlib's `Poseidon(2)`; the
+/// building block for Merkle trees that a circuit has to open.
+#[must_use]
+pub fn hash2(left: Fr, right: Fr) -> Fr {
+    let mut state = [Fr::ZERO, left, right];
+    permute(&mut state);
+    state[0]
+}
+
+/// Poseidon as a sponge over byte strings.
+///
+/// Input is padded with `0x01` and zeros to a multiple of 31 bytes, each
+/// 31-byte chunk becomes one little-endian field element, and elements are
+/// absorbed two per permutation. The digest is the first rate element,
+/// little-endian.
+#[derive(Clone)]
+pub struct PoseidonHasher {
+    state: [Fr; WIDTH],
+    /// An element waiting for its partner in the rate.
+    pending: Option<Fr>,
+    buffer: [u8; BYTES_PER_ELEMENT],
+    buffered: usize,
+}
+
+impl Default for PoseidonHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PoseidonHasher {
+    #[must_use]
+    pub fn new() -> Self {
+        PoseidonHasher {
+            state: [Fr::from_u64(BYTE_SPONGE_DOMAIN), Fr::ZERO, Fr::ZERO],
+            pending: None,
+            buffer: [0; BYTES_PER_ELEMENT],
+            buffered: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = (BYTES_PER_ELEMENT - self.buffered).min(data.len());
+            self.buffer[self.buffered..self.buffered + take].copy_from_slice(&data[..take]);
+            self.buffered += take;
+            data = &data[take..];
+            if self.buffered == BYTES_PER_ELEMENT {
+                self.absorb_buffer();
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn finalize(mut self) -> [u8; 32] {
+        // The padding byte makes the last element non-zero, so an odd
+        // element count padded with a zero element stays unambiguous.
+        self.buffer[self.buffered] = 0x01;
+        self.buffer[self.buffered + 1..].fill(0);
+        self.buffered = BYTES_PER_ELEMENT;
+        self.absorb_buffer();
+        if let Some(element) = self.pending.take() {
+            self.absorb_pair(element, Fr::ZERO);
+        }
+        self.state[1].to_bytes_le()
+    }
+
+    fn absorb_buffer(&mut self) {
+        let mut bytes = [0u8; 32];
+        bytes[..BYTES_PER_ELEMENT].copy_from_slice(&self.buffer);
+        let element = Fr::from_bytes_le(&bytes).expect("31 bytes are below the modulus");
+        self.buffered = 0;
+        match self.pending.take() {
+            Some(first) => self.absorb_pair(first, element),
+            None => self.pending = Some(element),
+        }
+    }
+
+    fn absorb_pair(&mut self, first: Fr, second: Fr) {
+        self.state[1] = self.state[1] + first;
+        self.state[2] = self.state[2] + second;
+        permute(&mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse a big-endian hex constant as printed by the reference scripts.
+    fn fr(hex: &str) -> Fr {
+        let hex = hex.trim_start_matches("0x");
+        let padded = format!("{hex:0>64}");
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().rev().enumerate() {
+            *byte = u8::from_str_radix(&padded[2 * i..2 * i + 2], 16).unwrap();
+        }
+        Fr::from_bytes_le(&bytes).unwrap()
+    }
+
+    #[test]
+    fn field_arithmetic() {
+        let a = Fr::from_u64(u64::MAX);
+        let b = Fr::from_u64(12345);
+        assert_eq!(a * b.invert().unwrap() * b, a);
+        assert_eq!(a + b - b, a);
+        assert_eq!(b - a + a, b);
+        assert_eq!(Fr::ZERO - Fr::one() + Fr::one(), Fr::ZERO);
+        assert!(Fr::ZERO.invert().is_none());
+        assert_eq!(Fr::from_bytes_le(&a.to_bytes_le()), Some(a));
+
+        let mut modulus = [0u8; 32];
+        for (chunk, limb) in modulus.chunks_exact_mut(8).zip(MODULUS) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        assert!(Fr::from_bytes_le(&modulus).is_none());
+        modulus[0] -= 1;
+        assert_eq!(Fr::from_bytes_le(&modulus), Some(Fr::ZERO - Fr::one()));
+    }
+
+    #[test]
+    fn parameters_match_reference() {
+        let params = Params::get();
+        assert_eq!(params.round_constants.len(), FULL_ROUNDS + PARTIAL_ROUNDS);
+        assert_eq!(
+            params.round_constants[0][0],
+            fr("0x0ee9a592ba9a9518d05986d656f40c2114c4993c11bb29938d21d47304cd8e6e")
+        );
+        assert_eq!(
+            params.mds[0][0],
+            fr("0x109b7f411ba0e4c9b2b70caf5c36a7b194be7c11ad24378bfedb68592ba8118b")
+        );
+    }
+
+    #[test]
+    fn permutation_matches_reference_vector() {
+        let mut state = [Fr::ZERO, Fr::from_u64(1), Fr::from_u64(2)];
+        permute(&mut state);
+        assert_eq!(
+            state,
+            [
+                fr("0x115cc0f5e7d690413df64c6b9662e9cf2a3617f2743245519e19607a4417189a"),
+                fr("0x0fca49b798923ab0239de1c9e7a4a9a2210312b6a2f616d18b5a87f9b628ae29"),
+                fr("0x0e7ae82e40091e63cbd4f16a6d16310b3729d4b6e138fcf54110e2867045a30c"),
+            ]
+        );
+        assert_eq!(hash2(Fr::from_u64(1), Fr::from_u64(2)), state[0]);
+    }
+
+    #[test]
+    fn sponge_is_incremental_and_padded() {
+        let data: Vec<u8> = (0..200u8).collect();
+        let mut whole = PoseidonHasher::new();
+        whole.update(&data);
+        let whole = whole.finalize();
+
+        let mut pieces = PoseidonHasher::new();
+        for chunk in data.chunks(7) {
+            pieces.update(chunk);
+        }
+        assert_eq!(pieces.finalize(), whole);
+
+        // Lengths around the chunk and rate boundaries all differ.
+        let digests: Vec<[u8; 32]> = [0, 1, 30, 31, 32, 61, 62, 63]
+            .iter()
+            .map(|&len| {
+                let mut hasher = PoseidonHasher::new();
+                hasher.update(&vec![0u8; len]);
+                hasher.finalize()
+            })
+            .collect();
+        for (i, a) in digests.iter().enumerate() {
+            for b in &digests[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+    }
+}
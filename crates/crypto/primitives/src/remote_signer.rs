@@ -0,0 +1,232 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::signer::{checked, Signer, SignerError};
+
+const OP_PUBLIC_KEY: u8 = 1;
+const OP_SIGN: u8 = 2;
+const STATUS_OK: u8 = 0;
+const STATUS_REFUSED: u8 = 1;
+
+/// Largest request or response body accepted.
+pub const MAX_FRAME: usize = 1 << 20;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A key held by another process, reached over TCP.
+///
+/// Each request is `op u8 || len u32 LE || body` and each response
+/// `status u8 || len u32 LE || body`, where a refusal carries its reason as
+/// UTF-8. The protocol is unauthenticated: bind the server to loopback or
+/// run it behind a tunnel the host already trusts. Signatures are checked
+/// against the key before they are returned, and a dropped connection is
+/// re-established on the next request.
+pub struct RemoteSigner {
+    addr: String,
+    timeout: Duration,
+    stream: Mutex<Option<TcpStream>>,
+    public_key: Vec<u8>,
+}
+
+impl RemoteSigner {
+    /// Connect to the signer at `addr` and fetch its public key.
+    pub fn connect(addr: impl Into<String>) -> Result<Self, SignerError> {
+        Self::with_timeout(addr, DEFAULT_TIMEOUT)
+    }
+
+    pub fn with_timeout(addr: impl Into<String>, timeout: Duration) -> Result<Self, SignerError> {
+        let mut signer = RemoteSigner {
+            addr: addr.into(),
+            timeout,
+            stream: Mutex::new(None),
+            public_key: Vec::new(),
+        };
+        let public_key = signer.request(OP_PUBLIC_KEY, &[])?;
+        if public_key.len() != 32 {
+            return Err(SignerError::InvalidResponse(format!(
+                "public key of {} bytes",
+                public_key.len()
+            )));
+        }
+        signer.public_key = public_key;
+        Ok(signer)
+    }
+
+    #[must_use]
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    fn request(&self, op: u8, body: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let mut guard = self
+            .stream
+            .lock()
+            .map_err(|_| SignerError::Transport("connection lock poisoned".into()))?;
+        let stream = match guard.as_mut() {
+            Some(stream) => stream,
+            None => guard.insert(self.open()?),
+        };
+        let result = write_frame(stream, op, body).and_then(|()| read_frame(stream));
+        let (status, body) = match result {
+            Ok(frame) => frame,
+            Err(err) => {
+                *guard = None;
+                return Err(err.into());
+            }
+        };
+        match status {
+            STATUS_OK => Ok(body),
+            STATUS_REFUSED => Err(SignerError::Remote(
+                String::from_utf8_lossy(&body).into_owned(),
+            )),
+            other => Err(SignerError::InvalidResponse(format!("status {other}"))),
+        }
+    }
+
+    fn open(&self) -> Result<TcpStream, SignerError> {
+        let addr = self
+            .addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| SignerError::Transport(format!("{} did not resolve", self.addr)))?;
+        let stream = TcpStream::connect_timeout(&addr, self.timeout)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        stream.set_nodelay(true)?;
+        Ok(stream)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn public_key(&self) -> Vec<u8> {
+        self.public_key.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let signature = self.request(OP_SIGN, message)?;
+        checked(&self.public_key, message, signature)
+    }
+}
+
+/// Answer requests on `stream` with `signer` until the peer disconnects.
+pub fn serve_connection<S: Signer + ?Sized>(
+    mut stream: impl Read + Write,
+    signer: &S,
+) -> std::io::Result<()> {
+    loop {
+        let (op, body) = match read_frame(&mut stream) {
+            Ok(frame) => frame,
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let reply = match op {
+            OP_PUBLIC_KEY => Ok(signer.public_key()),
+            OP_SIGN => signer.sign(&body).map_err(|err| err.to_string()),
+            other => Err(format!("unknown operation {other}")),
+        };
+        match reply {
+            Ok(data) => write_frame(&mut stream, STATUS_OK, &data)?,
+            Err(reason) => write_frame(&mut stream, STATUS_REFUSED, reason.as_bytes())?,
+        }
+    }
+}
+
+fn write_frame(stream: &mut impl Write, tag: u8, body: &[u8]) -> std::io::Result<()> {
+    if body.len() > MAX_FRAME {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidInput,
+            "frame too large",
+        ));
+    }
+    let mut frame = Vec::with_capacity(5 + body.len());
+    frame.push(tag);
+    frame.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    frame.extend_from_slice(body);
+    stream.write_all(&frame)?;
+    stream.flush()
+}
+
+fn read_frame(stream: &mut impl Read) -> std::io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 5];
+    stream.read_exact(&mut header)?;
+    let len = u32::from_le_bytes(header[1..].try_into().expect("4 bytes")) as usize;
+    if len > MAX_FRAME {
+        return Err(std::io::Error::new(
+            ErrorKind::InvalidData,
+            "frame too large",
+        ));
+    }
+    let mut body = vec![0u8; len];
+    stream.read_exact(&mut body)?;
+    Ok((header[0], body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ed25519;
+    use crate::keypair::Keypair;
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Serve `signer` on loopback for `connections` connections.
+    fn spawn_server(signer: impl Signer + 'static, connections: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let _ = serve_connection(stream.unwrap(), &signer);
+            }
+        });
+        addr
+    }
+
+    struct Refusing(Keypair);
+
+    impl Signer for Refusing {
+        fn public_key(&self) -> Vec<u8> {
+            self.0.public_key()
+        }
+
+        fn sign(&self, _message: &[u8]) -> Result<Vec<u8>, SignerError> {
+            Err(SignerError::Rejected)
+        }
+    }
+
+    #[test]
+    fn signs_remotely() {
+        let keypair = Keypair::generate();
+        let public_key = keypair.public_key();
+        let signer = RemoteSigner::connect(spawn_server(keypair, 1)).unwrap();
+        assert_eq!(Signer::public_key(&signer), public_key);
+
+        for message in [b"tx".to_vec(), vec![7u8; 4096]] {
+            let signature = signer.sign(&message).unwrap();
+            assert!(ed25519::verify(&public_key, &message, &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn reports_refusals() {
+        let signer = RemoteSigner::connect(spawn_server(Refusing(Keypair::generate()), 1)).unwrap();
+        match signer.sign(b"tx") {
+            Err(SignerError::Remote(reason)) => assert!(reason.contains("rejected"), "{reason}"),
+            other => panic!("unexpected {other:?}"),
+        }
+        // The connection stays usable after a refusal.
+        assert!(matches!(signer.sign(b"tx"), Err(SignerError::Remote(_))));
+    }
+
+    #[test]
+    fn refuses_unreachable_signer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        drop(listener);
+        assert!(matches!(
+            RemoteSigner::with_timeout(addr, Duration::from_secs(1)),
+            Err(SignerError::Transport(_))
+        ));
+    }
+}
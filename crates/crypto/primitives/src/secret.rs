@@ -0,0 +1,171 @@
+use std::fmt;
+
+use rand::RngCore;
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+/// Fixed-size secret key material.
+///
+/// The bytes are wiped on drop, `Debug` prints only the length, equality is
+/// constant time, and there is deliberately no `Serialize`: writing a secret
+/// out takes an explicit `expose_secret`, which is easy to audit for.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct SecretBytes<const N: usize = 32>([u8; N]);
+
+impl<const N: usize> SecretBytes<N> {
+    /// Take ownership of `bytes`. The caller's copy, if any, is its own to
+    /// wipe.
+    #[must_use]
+    pub fn new(bytes: [u8; N]) -> Self {
+        SecretBytes(bytes)
+    }
+
+    #[must_use]
+    pub fn zero() -> Self {
+        SecretBytes([0u8; N])
+    }
+
+    /// Fresh bytes from the OS RNG.
+    #[must_use]
+    pub fn random() -> Self {
+        let mut secret = Self::zero();
+        rand::rngs::OsRng.fill_bytes(&mut secret.0);
+        secret
+    }
+
+    /// Copy `bytes` in, or `None` unless it is exactly `N` bytes long.
+    #[must_use]
+    pub fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != N {
+            return None;
+        }
+        let mut secret = Self::zero();
+        secret.0.copy_from_slice(bytes);
+        Some(secret)
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn expose_secret(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn expose_secret_mut(&mut self) -> &mut [u8; N] {
+        &mut self.0
+    }
+
+    /// Lowercase hex, for export formats that need text.
+    #[must_use]
+    pub fn to_hex(&self) -> Zeroizing<String> {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut hex = Zeroizing::new(String::with_capacity(2 * N));
+        for byte in &self.0 {
+            hex.push(DIGITS[usize::from(byte >> 4)] as char);
+            hex.push(DIGITS[usize::from(byte & 0x0f)] as char);
+        }
+        hex
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for SecretBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Self::new(bytes)
+    }
+}
+
+impl<const N: usize> PartialEq for SecretBytes<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.ct_eq(&other.0).into()
+    }
+}
+
+impl<const N: usize> Eq for SecretBytes<N> {}
+
+impl<const N: usize> fmt::Debug for SecretBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes<{N}>(REDACTED)")
+    }
+}
+
+/// How `secret` shows up in `output`, if it does: raw, as hex in either
+/// case, or as a list of decimal bytes (what `Debug` and serde's JSON
+/// encoding of byte arrays produce).
+#[must_use]
+pub fn find_leak(output: &[u8], secret: &[u8]) -> Option<&'static str> {
+    if secret.is_empty() {
+        return None;
+    }
+    let contains = |needle: &[u8]| output.windows(needle.len()).any(|w| w == needle);
+    let hex: String = secret.iter().map(|b| format!("{b:02x}")).collect();
+    let decimal = |sep: &str| {
+        secret
+            .iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>()
+            .join(sep)
+    };
+
+    if contains(secret) {
+        Some("raw bytes")
+    } else if contains(hex.as_bytes()) {
+        Some("lowercase hex")
+    } else if contains(hex.to_uppercase().as_bytes()) {
+        Some("uppercase hex")
+    } else if contains(decimal(",").as_bytes()) || contains(decimal(", ").as_bytes()) {
+        Some("decimal bytes")
+    } else {
+        None
+    }
+}
+
+/// Panic if `secret` appears in `output` in any encoding `find_leak`
+/// knows. For tests that serialize or format types holding secrets.
+#[track_caller]
+pub fn assert_not_leaked(output: impl AsRef<[u8]>, secret: &[u8]) {
+    if let Some(encoding) = find_leak(output.as_ref(), secret) {
+        panic!("secret leaked into output as {encoding}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_prints_or_compares_loosely() {
+        let secret = SecretBytes::new([0xabu8; 32]);
+        assert_eq!(format!("{secret:?}"), "SecretBytes<32>(REDACTED)");
+        assert_not_leaked(format!("{secret:?}"), secret.expose_secret());
+        assert_eq!(secret.to_hex().as_str(), "ab".repeat(32));
+
+        assert_eq!(secret, SecretBytes::from_slice(&[0xab; 32]).unwrap());
+        assert_ne!(secret, SecretBytes::random());
+        assert!(SecretBytes::<32>::from_slice(&[0xab; 31]).is_none());
+    }
+
+    #[test]
+    fn zeroizes() {
+        let mut secret = SecretBytes::new([7u8; 16]);
+        secret.zeroize();
+        assert_eq!(secret.expose_secret(), &[0u8; 16]);
+    }
+
+    #[test]
+    fn finds_leaks_in_common_encodings() {
+        let secret: Vec<u8> = (200..232).collect();
+        let hex: String = secret.iter().map(|b| format!("{b:02x}")).collect();
+        assert_eq!(find_leak(&secret, &secret), Some("raw bytes"));
+        assert_eq!(find_leak(hex.as_bytes(), &secret), Some("lowercase hex"));
+        assert_eq!(
+            find_leak(hex.to_uppercase().as_bytes(), &secret),
+            Some("uppercase hex")
+        );
+        assert_eq!(
+            find_leak(format!("{secret:?}").as_bytes(), &secret),
+            Some("decimal bytes")
+        );
+        assert_eq!(find_leak(b"{\"key\":\"redacted\"}", &secret), None);
+    }
+}
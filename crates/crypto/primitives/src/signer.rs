@@ -0,0 +1,110 @@
+use thiserror::Error;
+
+use crate::{ed25519, keypair};
+
+#[derive(Error, Debug)]
+pub enum SignerError {
+    #[error("signer transport: {0}")]
+    Transport(String),
+    #[error("signing rejected on the device")]
+    Rejected,
+    #[error("device returned status {0:#06x}")]
+    Device(u16),
+    #[error("malformed signer response: {0}")]
+    InvalidResponse(String),
+    #[error("remote signer refused: {0}")]
+    Remote(String),
+    #[error("signer returned a signature that does not verify")]
+    BadSignature,
+}
+
+impl From<std::io::Error> for SignerError {
+    fn from(err: std::io::Error) -> Self {
+        SignerError::Transport(err.to_string())
+    }
+}
+
+/// Something that can produce Ed25519 signatures for one key.
+///
+/// The key may live in process (`Keypair`), on a hardware wallet
+/// (`LedgerSigner`) or behind another process (`RemoteSigner`); callers
+/// only ever see the public key and signatures.
+pub trait Signer: Send + Sync {
+    /// The 32-byte Ed25519 public key.
+    fn public_key(&self) -> Vec<u8>;
+
+    /// Sign `message`, returning a 64-byte signature.
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError>;
+}
+
+impl Signer for keypair::Keypair {
+    fn public_key(&self) -> Vec<u8> {
+        keypair::Keypair::public_key(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        Ok(keypair::Keypair::sign(self, message))
+    }
+}
+
+impl Signer for ed25519::Keypair {
+    fn public_key(&self) -> Vec<u8> {
+        ed25519::Keypair::public_key(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        Ok(ed25519::Keypair::sign(self, message))
+    }
+}
+
+impl<S: Signer + ?Sized> Signer for Box<S> {
+    fn public_key(&self) -> Vec<u8> {
+        (**self).public_key()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, SignerError> {
+        (**self).sign(message)
+    }
+}
+
+/// Check a signature that came from outside the process before using it,
+/// so a faulty device or signer cannot slip an invalid one into a
+/// transaction.
+pub(crate) fn checked(
+    public_key: &[u8],
+    message: &[u8],
+    signature: Vec<u8>,
+) -> Result<Vec<u8>, SignerError> {
+    ed25519::verify(public_key, message, &signature).map_err(|_| SignerError::BadSignature)?;
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keypairs_are_signers() {
+        let keypair = keypair::Keypair::generate();
+        let signers: Vec<Box<dyn Signer>> = vec![
+            Box::new(keypair::Keypair::from_bytes(keypair.secret_key().expose_secret()).unwrap()),
+            Box::new(ed25519::Keypair::from_bytes(keypair.secret_key().expose_secret()).unwrap()),
+        ];
+        for signer in &signers {
+            assert_eq!(signer.public_key(), keypair.public_key());
+            let signature = signer.sign(b"message").unwrap();
+            assert!(ed25519::verify(&signer.public_key(), b"message", &signature).is_ok());
+        }
+    }
+
+    #[test]
+    fn checked_rejects_foreign_signatures() {
+        let keypair = keypair::Keypair::generate();
+        let other = keypair::Keypair::generate();
+        assert!(checked(&keypair.public_key(), b"m", keypair.sign(b"m")).is_ok());
+        assert!(matches!(
+            checked(&keypair.public_key(), b"m", other.sign(b"m")),
+            Err(SignerError::BadSignature)
+        ));
+    }
+}
@@ -1,6 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 
-use aether_crypto_vrf::{verify_proof, VrfKeypair};
+use aether_crypto_vrf::{verify_batch, verify_proof, BatchEntry, VrfKeypair, VrfProof};
 
 fn bench_vrf_prove(c: &mut Criterion) {
     let keypair = VrfKeypair::generate();
@@ -37,10 +37,45 @@ fn bench_vrf_prove_and_verify(c: &mut Criterion) {
     });
 }
 
+/// Catching up on `n` slots led by a rotating set of 100 validators.
+fn bench_vrf_sync_batch(c: &mut Criterion) {
+    let keypairs: Vec<VrfKeypair> = (0..100).map(|_| VrfKeypair::generate()).collect();
+    let mut group = c.benchmark_group("vrf_sync");
+    group.sample_size(10);
+
+    for n in [100u64, 1_000] {
+        let proofs: Vec<([u8; 32], Vec<u8>, VrfProof)> = (0..n)
+            .map(|slot| {
+                let keypair = &keypairs[slot as usize % keypairs.len()];
+                let input = slot.to_le_bytes().to_vec();
+                let proof = keypair.prove(&input);
+                (*keypair.public_key(), input, proof)
+            })
+            .collect();
+        let entries: Vec<BatchEntry<'_>> = proofs
+            .iter()
+            .map(|(pk, input, proof)| (pk, input.as_slice(), proof))
+            .collect();
+
+        group.bench_with_input(BenchmarkId::new("sequential", n), &n, |b, _| {
+            b.iter(|| {
+                assert!(entries
+                    .iter()
+                    .all(|(pk, input, proof)| verify_proof(pk, input, proof).unwrap()))
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("verify_batch", n), &n, |b, _| {
+            b.iter(|| assert!(verify_batch(black_box(&entries))))
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_vrf_prove,
     bench_vrf_verify,
-    bench_vrf_prove_and_verify
+    bench_vrf_prove_and_verify,
+    bench_vrf_sync_batch
 );
 criterion_main!(benches);
@@ -0,0 +1,188 @@
+use curve25519_dalek::{
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    traits::VartimeMultiscalarMul,
+};
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+use crate::ecvrf::{
+    challenge_generation_from_bytes, decode_proof, encode_to_curve_try_and_increment,
+    proof_to_hash, scalar_to_16_bytes, verify_proof, VrfProof,
+};
+
+/// One proof to check in a batch: (public key, input, proof).
+pub type BatchEntry<'a> = (&'a [u8; 32], &'a [u8], &'a VrfProof);
+
+/// A public key decompressed once for every proof made under it.
+struct PreparedKey {
+    point: EdwardsPoint,
+    compressed: [u8; 32],
+}
+
+/// Verify many VRF proofs, e.g. the leader proofs of a range of blocks during
+/// sync. Returns true iff every entry verifies.
+///
+/// ECVRF proofs carry the challenge rather than the commitments U and V, so
+/// each proof still needs its own challenge hash; the savings come from
+/// sharing work across entries instead of from one combined equation:
+/// - every distinct public key is decompressed and re-encoded once (sync
+///   sees the same few leaders over and over)
+/// - U and V are computed with variable-time double-scalar multiplication
+///   against dalek's precomputed basepoint table; every input is public, so
+///   the constant-time ladder `verify_proof` uses buys nothing here
+/// - entries are spread across the rayon pool, stopping at the first failure
+///
+/// Accepts exactly what `verify_proof` accepts. When it returns false, use
+/// `find_invalid` to pinpoint the failing entry.
+#[must_use = "discarding a VRF verification result is a security bug"]
+pub fn verify_batch(entries: &[BatchEntry<'_>]) -> bool {
+    let mut distinct: Vec<&[u8; 32]> = entries.iter().map(|(pk, _, _)| *pk).collect();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let keys: HashMap<&[u8; 32], PreparedKey> = distinct
+        .into_par_iter()
+        .filter_map(|pk| prepare_key(pk).map(|key| (pk, key)))
+        .collect();
+
+    entries.par_iter().all(|(pk, alpha, proof)| {
+        keys.get(pk)
+            .is_some_and(|key| verify_with_key(key, pk, alpha, proof))
+    })
+}
+
+/// Index of the first entry that fails `verify_proof`, checked one proof at
+/// a time. This is the slow path for when `verify_batch` rejects a batch.
+pub fn find_invalid(entries: &[BatchEntry<'_>]) -> Option<usize> {
+    entries
+        .iter()
+        .position(|(pk, alpha, proof)| !matches!(verify_proof(pk, alpha, proof), Ok(true)))
+}
+
+fn prepare_key(public_key: &[u8; 32]) -> Option<PreparedKey> {
+    let point = CompressedEdwardsY(*public_key).decompress()?;
+    if point.is_small_order() {
+        return None;
+    }
+    Some(PreparedKey {
+        point,
+        compressed: point.compress().to_bytes(),
+    })
+}
+
+/// `verify_proof` steps 1-6 against a prepared key.
+fn verify_with_key(
+    key: &PreparedKey,
+    public_key: &[u8; 32],
+    alpha: &[u8],
+    proof: &VrfProof,
+) -> bool {
+    if proof.proof.len() != 80 {
+        return false;
+    }
+    let Ok((gamma, c, s)) = decode_proof(&proof.proof) else {
+        return false;
+    };
+    let h = encode_to_curve_try_and_increment(public_key, alpha);
+
+    // U = s*B - c*Y, V = s*H - c*Gamma
+    let u = EdwardsPoint::vartime_double_scalar_mul_basepoint(&-c, &key.point, &s);
+    let v = EdwardsPoint::vartime_multiscalar_mul([s, -c], [h, gamma]);
+
+    let c_prime = challenge_generation_from_bytes(&key.compressed, &h, &gamma, &u, &v);
+    scalar_to_16_bytes(&c) == scalar_to_16_bytes(&c_prime) && proof_to_hash(&gamma) == proof.output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecvrf::VrfKeypair;
+
+    /// `slots` proofs from each of `leaders` keys, as sync would see them.
+    fn sync_proofs(leaders: usize, slots: u64) -> Vec<([u8; 32], Vec<u8>, VrfProof)> {
+        let keypairs: Vec<VrfKeypair> = (0..leaders).map(|_| VrfKeypair::generate()).collect();
+        (0..slots)
+            .map(|slot| {
+                let keypair = &keypairs[slot as usize % leaders];
+                let input = slot.to_le_bytes().to_vec();
+                let proof = keypair.prove(&input);
+                (*keypair.public_key(), input, proof)
+            })
+            .collect()
+    }
+
+    fn entries(proofs: &[([u8; 32], Vec<u8>, VrfProof)]) -> Vec<BatchEntry<'_>> {
+        proofs
+            .iter()
+            .map(|(pk, input, proof)| (pk, input.as_slice(), proof))
+            .collect()
+    }
+
+    #[test]
+    fn batch_accepts_valid_proofs() {
+        let proofs = sync_proofs(4, 40);
+        assert!(verify_batch(&entries(&proofs)));
+        assert_eq!(find_invalid(&entries(&proofs)), None);
+        assert!(verify_batch(&[]));
+    }
+
+    #[test]
+    fn batch_rejects_and_fallback_pinpoints() {
+        let mut proofs = sync_proofs(3, 30);
+        proofs[17].2.proof[50] ^= 0x01;
+        assert!(!verify_batch(&entries(&proofs)));
+        assert_eq!(find_invalid(&entries(&proofs)), Some(17));
+
+        let mut proofs = sync_proofs(3, 30);
+        proofs[5].2.output[0] ^= 0xff;
+        assert_eq!(find_invalid(&entries(&proofs)), Some(5));
+        assert!(!verify_batch(&entries(&proofs)));
+
+        // A proof replayed for another slot's input.
+        let mut proofs = sync_proofs(3, 30);
+        proofs[9].1 = b"other slot".to_vec();
+        assert!(!verify_batch(&entries(&proofs)));
+        assert_eq!(find_invalid(&entries(&proofs)), Some(9));
+    }
+
+    #[test]
+    fn batch_rejects_undecodable_keys_and_proofs() {
+        let mut proofs = sync_proofs(2, 6);
+        proofs[2].2.proof.truncate(32);
+        assert!(!verify_batch(&entries(&proofs)));
+        assert_eq!(find_invalid(&entries(&proofs)), Some(2));
+
+        // y = 2 is not the y-coordinate of any curve point.
+        let mut bad_key = [0u8; 32];
+        bad_key[0] = 2;
+        assert!(CompressedEdwardsY(bad_key).decompress().is_none());
+        let mut proofs = sync_proofs(2, 6);
+        proofs[4].0 = bad_key;
+        assert!(!verify_batch(&entries(&proofs)));
+        assert_eq!(find_invalid(&entries(&proofs)), Some(4));
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use crate::ecvrf::VrfKeypair;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// The batch path accepts exactly what `verify_proof` accepts.
+        #[test]
+        fn batch_agrees_with_verify_proof(
+            secret in prop::array::uniform32(any::<u8>()),
+            input in prop::collection::vec(any::<u8>(), 0..64),
+            flip in prop::option::of((0usize..80, 1u8..=255)),
+        ) {
+            let kp = VrfKeypair::from_secret(&secret).unwrap();
+            let mut proof = kp.prove(&input);
+            if let Some((idx, mask)) = flip {
+                proof.proof[idx] ^= mask;
+            }
+            let single = matches!(verify_proof(kp.public_key(), &input, &proof), Ok(true));
+            prop_assert_eq!(verify_batch(&[(kp.public_key(), &input, &proof)]), single);
+        }
+    }
+}
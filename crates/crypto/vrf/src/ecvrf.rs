@@ -7,18 +7,25 @@ use curve25519_dalek::{
 use sha2::{Digest, Sha512};
 use subtle::ConstantTimeEq;
 
-/// ECVRF-EDWARDS25519-SHA512-ELL2 implementation per RFC 9381.
+use crate::sortition::{sortition, Tau};
+
+/// ECVRF-EDWARDS25519-SHA512-TAI implementation per RFC 9381.
 ///
 /// Provides verifiable pseudorandom output bound to a secret key and input.
 /// Used for slot leader election in VRF-PoS consensus.
 ///
 /// Proof structure: Gamma (32 bytes) || c (16 bytes) || s (32 bytes) = 80 bytes
-/// Output: SHA-512(suite_string || 0x03 || Gamma_cofactor) truncated to 32 bytes
-const SUITE_STRING: u8 = 0x04; // ECVRF-EDWARDS25519-SHA512-ELL2
+/// Output: beta = SHA-512(suite_string || 0x03 || Gamma_cofactor || 0x00);
+/// the chain uses its first 32 bytes (`VrfProof::output`).
+const SUITE_STRING: u8 = 0x03; // ECVRF-EDWARDS25519-SHA512-TAI
 
 #[derive(Clone, Debug)]
 pub struct VrfKeypair {
+    /// RFC 9381 SK: the 32-byte seed both halves below are derived from.
+    seed: [u8; 32],
     secret: Scalar,
+    /// Second half of SHA-512(SK), keying nonce generation.
+    nonce_key: [u8; 32],
     public: EdwardsPoint,
     public_bytes: [u8; 32],
 }
@@ -26,7 +33,28 @@ pub struct VrfKeypair {
 #[derive(Clone, Debug)]
 pub struct VrfProof {
     pub proof: Vec<u8>,   // 80 bytes: Gamma(32) || c(16) || s(32)
-    pub output: [u8; 32], // Beta string (hash of Gamma)
+    pub output: [u8; 32], // First 32 bytes of the beta string (hash of Gamma)
+}
+
+/// The full 64-byte RFC 9381 beta string of a proof. `VrfProof::output`
+/// is its first 32 bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VrfOutput(pub [u8; 64]);
+
+impl VrfOutput {
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8; 64] {
+        &self.0
+    }
+
+    /// The 32 bytes carried in block headers.
+    #[must_use]
+    pub fn truncated(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        out.copy_from_slice(&self.0[..32]);
+        out
+    }
 }
 
 impl Drop for VrfKeypair {
@@ -34,6 +62,8 @@ impl Drop for VrfKeypair {
         use zeroize::Zeroize;
         // Overwrite secret scalar with zero to prevent memory recovery
         self.secret = Scalar::ZERO;
+        self.seed.zeroize();
+        self.nonce_key.zeroize();
         self.public_bytes.zeroize();
     }
 }
@@ -49,12 +79,13 @@ impl VrfKeypair {
         Self::from_secret_bytes(&secret_bytes)
     }
 
-    /// Export the secret key bytes (for key persistence).
+    /// Export the secret key bytes (for key persistence); `from_secret`
+    /// restores the same keypair from them.
     /// WARNING: Handle with care — this is the raw secret key.
     #[inline]
     #[must_use]
     pub fn secret_bytes(&self) -> [u8; 32] {
-        self.secret.to_bytes()
+        self.seed
     }
 
     /// Create keypair from raw 32-byte secret.
@@ -81,9 +112,13 @@ impl VrfKeypair {
         let secret = Scalar::from_bytes_mod_order(scalar_bytes);
         let public = secret * ED25519_BASEPOINT_POINT;
         let public_bytes = public.compress().to_bytes();
+        let mut nonce_key = [0u8; 32];
+        nonce_key.copy_from_slice(&hash[32..]);
 
         VrfKeypair {
+            seed: *secret_bytes,
             secret,
+            nonce_key,
             public,
             public_bytes,
         }
@@ -108,14 +143,14 @@ impl VrfKeypair {
     /// 7. output = proof_to_hash(Gamma)
     #[must_use]
     pub fn prove(&self, alpha: &[u8]) -> VrfProof {
-        // Step 1: Hash to curve using Elligator2
+        // Step 1: Hash to curve using try-and-increment
         let h = encode_to_curve_try_and_increment(&self.public_bytes, alpha);
 
         // Step 2: Gamma = x * H
         let gamma = self.secret * h;
 
-        // Step 3: Nonce generation (deterministic, RFC 6979-like)
-        let k = nonce_generation(&self.secret, &h);
+        // Step 3: Nonce generation (deterministic, RFC 8032 style)
+        let k = nonce_generation(&self.nonce_key, &h);
 
         // Step 4: k*B and k*H
         let k_b = k * ED25519_BASEPOINT_POINT;
@@ -168,20 +203,14 @@ pub fn verify_proof(public_key: &[u8; 32], alpha: &[u8], proof: &VrfProof) -> Re
     let y = y_compressed
         .decompress()
         .ok_or_else(|| anyhow::anyhow!("public key not on curve"))?;
+    if y.is_small_order() {
+        bail!("public key has small order");
+    }
 
-    // Decode proof components
-    let mut gamma_bytes = [0u8; 32];
-    gamma_bytes.copy_from_slice(&proof.proof[0..32]);
-    let gamma = CompressedEdwardsY(gamma_bytes)
-        .decompress()
-        .ok_or_else(|| anyhow::anyhow!("Gamma not on curve"))?;
-
-    let c = scalar_from_16_bytes(&proof.proof[32..48]);
-
-    let mut s_bytes = [0u8; 32];
-    s_bytes.copy_from_slice(&proof.proof[48..80]);
-    let s = Option::from(Scalar::from_canonical_bytes(s_bytes))
-        .unwrap_or_else(|| Scalar::from_bytes_mod_order(s_bytes));
+    // A malformed proof is a rejection, not an error.
+    let Ok((gamma, c, s)) = decode_proof(&proof.proof) else {
+        return Ok(false);
+    };
 
     // Step 2: H = encode_to_curve(Y, alpha)
     let h = encode_to_curve_try_and_increment(public_key, alpha);
@@ -207,14 +236,49 @@ pub fn verify_proof(public_key: &[u8; 32], alpha: &[u8], proof: &VrfProof) -> Re
     Ok(bool::from(challenge_ok & output_ok))
 }
 
+/// Decode an 80-byte proof into (Gamma, c, s). Gamma must be a canonical
+/// point encoding and s must be below the group order (RFC 9381 5.4.4), so
+/// every proof has exactly one encoding.
+pub(crate) fn decode_proof(proof: &[u8]) -> Result<(EdwardsPoint, Scalar, Scalar)> {
+    if proof.len() != 80 {
+        bail!("proof must be 80 bytes");
+    }
+    let mut gamma_bytes = [0u8; 32];
+    gamma_bytes.copy_from_slice(&proof[0..32]);
+    let gamma = CompressedEdwardsY(gamma_bytes)
+        .decompress()
+        .ok_or_else(|| anyhow::anyhow!("Gamma not on curve"))?;
+    if gamma.compress().to_bytes() != gamma_bytes {
+        bail!("Gamma is not canonically encoded");
+    }
+
+    let c = scalar_from_16_bytes(&proof[32..48]);
+
+    let mut s_bytes = [0u8; 32];
+    s_bytes.copy_from_slice(&proof[48..80]);
+    let s = Option::from(Scalar::from_canonical_bytes(s_bytes))
+        .ok_or_else(|| anyhow::anyhow!("s is not reduced modulo the group order"))?;
+    Ok((gamma, c, s))
+}
+
+/// RFC 9381 ECVRF_proof_to_hash: the beta string of a proof. Does not
+/// verify the proof; only call this on proofs `verify_proof` accepted.
+pub fn proof_to_output(proof: &[u8]) -> Result<VrfOutput> {
+    let (gamma, _, _) = decode_proof(proof)?;
+    Ok(proof_to_beta(&gamma))
+}
+
 /// Encode input to a curve point using try-and-increment method.
 ///
-/// Per RFC 9381 Section 5.4.1.2 (try_and_increment):
+/// Per RFC 9381 Section 5.4.1.1 (try_and_increment):
 /// For i = 0, 1, 2, ...:
-///   hash = SHA-512(suite || 0x01 || public_key || alpha || i)
+///   hash = SHA-512(suite || 0x01 || public_key || alpha || i || 0x00)
 ///   attempt to decompress hash[0..32] as Edwards point
 ///   if valid, return cofactor * point
-fn encode_to_curve_try_and_increment(public_key: &[u8; 32], alpha: &[u8]) -> EdwardsPoint {
+pub(crate) fn encode_to_curve_try_and_increment(
+    public_key: &[u8; 32],
+    alpha: &[u8],
+) -> EdwardsPoint {
     for ctr in 0u8..=255 {
         let mut hasher = Sha512::new();
         hasher.update([SUITE_STRING]);
@@ -222,6 +286,7 @@ fn encode_to_curve_try_and_increment(public_key: &[u8; 32], alpha: &[u8]) -> Edw
         hasher.update(public_key);
         hasher.update(alpha);
         hasher.update([ctr]);
+        hasher.update([0x00]);
         let hash_output = hasher.finalize();
 
         let mut attempt = [0u8; 32];
@@ -239,12 +304,12 @@ fn encode_to_curve_try_and_increment(public_key: &[u8; 32], alpha: &[u8]) -> Edw
     ED25519_BASEPOINT_POINT.mul_by_cofactor()
 }
 
-/// Deterministic nonce generation (RFC 6979-like).
+/// Deterministic nonce generation (RFC 9381 Section 5.4.2.2, as RFC 8032).
 ///
-/// k = SHA-512(secret_scalar_bytes || compressed_H) reduced mod L
-fn nonce_generation(secret: &Scalar, h: &EdwardsPoint) -> Scalar {
+/// k = SHA-512(SHA-512(SK)[32..64] || compressed_H) reduced mod L
+fn nonce_generation(nonce_key: &[u8; 32], h: &EdwardsPoint) -> Scalar {
     let mut hasher = Sha512::new();
-    hasher.update(secret.to_bytes());
+    hasher.update(nonce_key);
     hasher.update(h.compress().to_bytes());
     let hash = hasher.finalize();
     let mut wide_bytes = [0u8; 64];
@@ -254,22 +319,35 @@ fn nonce_generation(secret: &Scalar, h: &EdwardsPoint) -> Scalar {
 
 /// Generate challenge scalar c from points.
 ///
-/// c = SHA-512(suite || 0x02 || Y || H || Gamma || U || V)[0..16] as scalar
+/// c = SHA-512(suite || 0x02 || Y || H || Gamma || U || V || 0x00)[0..16] as scalar
 fn challenge_generation(
     y: &EdwardsPoint,
     h: &EdwardsPoint,
     gamma: &EdwardsPoint,
     u: &EdwardsPoint,
     v: &EdwardsPoint,
+) -> Scalar {
+    challenge_generation_from_bytes(&y.compress().to_bytes(), h, gamma, u, v)
+}
+
+/// `challenge_generation` with the public key already compressed, for
+/// callers that check many proofs under one key.
+pub(crate) fn challenge_generation_from_bytes(
+    y: &[u8; 32],
+    h: &EdwardsPoint,
+    gamma: &EdwardsPoint,
+    u: &EdwardsPoint,
+    v: &EdwardsPoint,
 ) -> Scalar {
     let mut hasher = Sha512::new();
     hasher.update([SUITE_STRING]);
     hasher.update([0x02]); // challenge domain separator
-    hasher.update(y.compress().to_bytes());
+    hasher.update(y);
     hasher.update(h.compress().to_bytes());
     hasher.update(gamma.compress().to_bytes());
     hasher.update(u.compress().to_bytes());
     hasher.update(v.compress().to_bytes());
+    hasher.update([0x00]);
     let hash = hasher.finalize();
 
     // Take first 16 bytes as the challenge (128-bit security)
@@ -277,7 +355,7 @@ fn challenge_generation(
 }
 
 /// Convert Scalar to 16 bytes (truncated representation for challenge c).
-fn scalar_to_16_bytes(s: &Scalar) -> [u8; 16] {
+pub(crate) fn scalar_to_16_bytes(s: &Scalar) -> [u8; 16] {
     let bytes = s.to_bytes();
     let mut out = [0u8; 16];
     out.copy_from_slice(&bytes[..16]);
@@ -285,25 +363,28 @@ fn scalar_to_16_bytes(s: &Scalar) -> [u8; 16] {
 }
 
 /// Reconstruct Scalar from 16 bytes (zero-extend to 32 bytes).
-fn scalar_from_16_bytes(bytes: &[u8]) -> Scalar {
+pub(crate) fn scalar_from_16_bytes(bytes: &[u8]) -> Scalar {
     let mut scalar_bytes = [0u8; 32];
     scalar_bytes[..16].copy_from_slice(&bytes[..16]);
     Scalar::from_bytes_mod_order(scalar_bytes)
 }
 
-/// Convert VRF Gamma point to output hash (Beta string).
+/// Convert VRF Gamma point to the chain's 32-byte output.
+pub(crate) fn proof_to_hash(gamma: &EdwardsPoint) -> [u8; 32] {
+    proof_to_beta(gamma).truncated()
+}
+
+/// Beta string of a Gamma point.
 ///
-/// output = SHA-512(suite || 0x03 || cofactor_Gamma)[0..32]
-fn proof_to_hash(gamma: &EdwardsPoint) -> [u8; 32] {
+/// beta = SHA-512(suite || 0x03 || cofactor_Gamma || 0x00)
+fn proof_to_beta(gamma: &EdwardsPoint) -> VrfOutput {
     let cofactor_gamma = gamma.mul_by_cofactor();
     let mut hasher = Sha512::new();
     hasher.update([SUITE_STRING]);
     hasher.update([0x03]); // proof_to_hash domain separator
     hasher.update(cofactor_gamma.compress().to_bytes());
-    let hash = hasher.finalize();
-    let mut output = [0u8; 32];
-    output.copy_from_slice(&hash[..32]);
-    output
+    hasher.update([0x00]);
+    VrfOutput(hasher.finalize().into())
 }
 
 #[deprecated(
@@ -340,54 +421,6 @@ pub fn check_leader_eligibility(
     output_value < threshold
 }
 
-/// Multiply two u128 values and return the result as (high, low) u128 pair (256-bit).
-fn mul_u128_wide(a: u128, b: u128) -> (u128, u128) {
-    let a_lo = a as u64 as u128;
-    let a_hi = a >> 64;
-    let b_lo = b as u64 as u128;
-    let b_hi = b >> 64;
-
-    let lo_lo = a_lo * b_lo;
-    let hi_lo = a_hi * b_lo;
-    let lo_hi = a_lo * b_hi;
-    let hi_hi = a_hi * b_hi;
-
-    let mid = (lo_lo >> 64) + (hi_lo as u64 as u128) + (lo_hi as u64 as u128);
-    let carry = (mid >> 64) + (hi_lo >> 64) + (lo_hi >> 64);
-
-    let low = (lo_lo as u64 as u128) | (mid << 64);
-    let high = hi_hi + carry;
-    (high, low)
-}
-
-/// Compare two 256-bit values: (a_hi, a_lo) < (b_hi, b_lo)
-fn lt_u256(a: (u128, u128), b: (u128, u128)) -> bool {
-    if a.0 != b.0 {
-        a.0 < b.0
-    } else {
-        a.1 < b.1
-    }
-}
-
-/// Multiply a 256-bit value (hi, lo) by a u128 scalar, returning a 256-bit result.
-/// Overflow beyond 256 bits saturates to (u128::MAX, u128::MAX).
-fn mul_u256_by_u128(val: (u128, u128), scalar: u128) -> (u128, u128) {
-    // low * scalar
-    let (lo_hi, lo_lo) = mul_u128_wide(val.1, scalar);
-    // high * scalar
-    let (hi_hi, hi_lo) = mul_u128_wide(val.0, scalar);
-
-    if hi_hi > 0 {
-        return (u128::MAX, u128::MAX); // overflow, saturate
-    }
-
-    let new_hi = hi_lo.checked_add(lo_hi);
-    match new_hi {
-        Some(h) => (h, lo_lo),
-        None => (u128::MAX, u128::MAX), // overflow, saturate
-    }
-}
-
 /// Check leader eligibility using integer-only arithmetic (deterministic across platforms).
 ///
 /// tau is represented as a fraction: tau_numerator / tau_denominator
@@ -396,7 +429,7 @@ fn mul_u256_by_u128(val: (u128, u128), scalar: u128) -> (u128, u128) {
 /// Eligible if: vrf_value / 2^64 < (tau_numerator / tau_denominator) * (stake / total_stake)
 /// Rearranged: vrf_value * total_stake * tau_denominator < tau_numerator * stake * 2^64
 ///
-/// Uses 256-bit arithmetic to avoid overflow at high stake values.
+/// Single-seat `sortition`: exact fixed-point, branch-free in the VRF output.
 #[must_use]
 pub fn check_leader_eligibility_integer(
     vrf_output: &[u8; 32],
@@ -405,29 +438,12 @@ pub fn check_leader_eligibility_integer(
     tau_numerator: u128,
     tau_denominator: u128,
 ) -> bool {
-    if total_stake == 0 || tau_denominator == 0 {
-        return false;
-    }
-    // Interpret first 8 bytes as u64 (sufficient entropy for threshold comparison)
-    let mut bytes = [0u8; 8];
-    bytes.copy_from_slice(&vrf_output[..8]);
-    let vrf_value = u64::from_le_bytes(bytes) as u128;
-
-    // LHS = vrf_value * total_stake * tau_denominator (256-bit)
-    let lhs_step1 = mul_u128_wide(vrf_value, total_stake);
-    let lhs = mul_u256_by_u128(lhs_step1, tau_denominator);
-
-    // RHS = tau_numerator * stake * 2^64 (256-bit)
-    // Multiplying by 2^64 is just shifting left by 64 bits
-    let rhs_step1 = mul_u128_wide(tau_numerator, stake);
-    // Shift (hi, lo) left by 64: new_hi = (old_hi << 64) | (old_lo >> 64), new_lo = old_lo << 64
-    let rhs = if rhs_step1.0 >> 64 != 0 {
-        (u128::MAX, u128::MAX) // overflow on shift
-    } else {
-        ((rhs_step1.0 << 64) | (rhs_step1.1 >> 64), rhs_step1.1 << 64)
-    };
-
-    lt_u256(lhs, rhs)
+    sortition(
+        vrf_output,
+        stake,
+        total_stake,
+        Tau::new(tau_numerator, tau_denominator),
+    ) > 0
 }
 
 #[cfg(test)]
@@ -611,6 +627,43 @@ mod tests {
         let proof1 = kp1.prove(b"test");
         let proof2 = kp2.prove(b"test");
         assert_eq!(proof1.output, proof2.output);
+
+        // Exported secrets restore the same key.
+        let restored = VrfKeypair::from_secret(&kp1.secret_bytes()).unwrap();
+        assert_eq!(restored.public_key(), kp1.public_key());
+        assert_eq!(restored.prove(b"test").proof, proof1.proof);
+    }
+
+    #[test]
+    fn test_rejects_non_canonical_proofs() {
+        let kp = VrfKeypair::from_secret(&[7u8; 32]).unwrap();
+        let proof = kp.prove(b"alpha");
+
+        // s + L encodes the same scalar but is not canonical.
+        let mut s_bytes = [0u8; 32];
+        s_bytes.copy_from_slice(&proof.proof[48..80]);
+        let s = Scalar::from_canonical_bytes(s_bytes).unwrap();
+        let l_minus_one = -Scalar::ONE;
+        let mut widened = [0u8; 32];
+        let mut carry = 1u16; // s + (L - 1) + 1 = s + L
+        let (a, b) = (s.to_bytes(), l_minus_one.to_bytes());
+        for i in 0..32 {
+            let sum = a[i] as u16 + b[i] as u16 + carry;
+            widened[i] = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(carry, 0);
+        let mut malleated = proof.clone();
+        malleated.proof[48..80].copy_from_slice(&widened);
+        assert!(!matches!(
+            verify_proof(kp.public_key(), b"alpha", &malleated),
+            Ok(true)
+        ));
+
+        // The identity is a small-order public key.
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert!(verify_proof(&identity, b"alpha", &proof).is_err());
     }
 
     #[test]
@@ -0,0 +1,114 @@
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use anyhow::Result;
+
+use crate::ecvrf::{proof_to_output, VrfOutput, VrfProof};
+
+/// Length of an RFC 9381 ECVRF-EDWARDS25519 proof (pi string).
+pub const PROOF_LEN: usize = 80;
+
+/// Length of an RFC 9381 beta string (SHA-512).
+pub const OUTPUT_LEN: usize = 64;
+
+impl VrfProof {
+    /// Canonical encoding: the 80-byte pi string of RFC 9381, nothing else.
+    /// The output is a function of pi and is recomputed on decode, so a
+    /// proof cannot travel with a mismatched output.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::with_capacity(PROOF_LEN);
+        self.write_to(&mut writer);
+        writer.finish()
+    }
+
+    /// Decode `encode()` output. Rejects anything but a canonical pi
+    /// (on-curve, canonically encoded Gamma and s below the group order).
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = CanonicalReader::new(bytes);
+        let proof = Self::read_from(&mut reader)?;
+        reader.finish()?;
+        Ok(proof)
+    }
+
+    /// Append the canonical encoding to a larger structure.
+    ///
+    /// # Panics
+    /// If `proof` is not `PROOF_LEN` bytes; every proof from `prove` is.
+    pub fn write_to(&self, writer: &mut CanonicalWriter) {
+        assert_eq!(self.proof.len(), PROOF_LEN, "VRF proof must be 80 bytes");
+        writer.put_fixed(&self.proof);
+    }
+
+    pub fn read_from(reader: &mut CanonicalReader) -> Result<Self> {
+        let pi: [u8; PROOF_LEN] = reader.take_fixed()?;
+        let output = proof_to_output(&pi)?.truncated();
+        Ok(VrfProof {
+            proof: pi.to_vec(),
+            output,
+        })
+    }
+}
+
+impl VrfOutput {
+    /// Canonical encoding: the 64-byte beta string.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::with_capacity(OUTPUT_LEN);
+        writer.put_fixed(&self.0);
+        writer.finish()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = CanonicalReader::new(bytes);
+        let output = VrfOutput(reader.take_fixed()?);
+        reader.finish()?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecvrf::{verify_proof, VrfKeypair};
+
+    #[test]
+    fn proof_roundtrip_recomputes_output() {
+        let kp = VrfKeypair::from_secret(&[3u8; 32]).unwrap();
+        let proof = kp.prove(b"slot 9");
+        let bytes = proof.encode();
+        assert_eq!(bytes.len(), PROOF_LEN);
+
+        let decoded = VrfProof::decode(&bytes).unwrap();
+        assert_eq!(decoded.proof, proof.proof);
+        assert_eq!(decoded.output, proof.output);
+        assert!(verify_proof(kp.public_key(), b"slot 9", &decoded).unwrap());
+
+        let output = proof_to_output(&proof.proof).unwrap();
+        assert_eq!(output.truncated(), proof.output);
+        assert_eq!(VrfOutput::decode(&output.encode()).unwrap(), output);
+    }
+
+    #[test]
+    fn decode_rejects_malformed_proofs() {
+        let kp = VrfKeypair::from_secret(&[3u8; 32]).unwrap();
+        let bytes = kp.prove(b"slot 9").encode();
+
+        assert!(VrfProof::decode(&bytes[..79]).is_err());
+        let mut long = bytes.clone();
+        long.push(0);
+        assert!(VrfProof::decode(&long).is_err());
+
+        // s with the top bits set is above the group order.
+        let mut high_s = bytes.clone();
+        high_s[79] = 0xff;
+        assert!(VrfProof::decode(&high_s).is_err());
+
+        // y = 2 is not on the curve.
+        let mut off_curve = bytes;
+        off_curve[..32].copy_from_slice(&{
+            let mut y = [0u8; 32];
+            y[0] = 2;
+            y
+        });
+        assert!(VrfProof::decode(&off_curve).is_err());
+
+        assert!(VrfOutput::decode(&[0u8; 63]).is_err());
+    }
+}
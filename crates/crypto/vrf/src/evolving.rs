@@ -0,0 +1,264 @@
+use aether_crypto_kes::{
+    compute_auth_path, compute_merkle_root, verify_auth_path, KesError, KesVerificationKey,
+};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroize;
+
+use crate::ecvrf::{verify_proof, VrfKeypair, VrfProof};
+
+/// A VRF key that evolves with the KES schedule: one VRF keypair per KES
+/// period, all committed to by a Merkle root registered once.
+///
+/// Moving to a period erases the secrets of every earlier period, so a hot
+/// key stolen in period p cannot produce leadership proofs for slots of
+/// periods before p: the keys for those periods are gone, and a proof made
+/// with the stolen key does not verify for any period but its own. The tree
+/// is the one KES uses, so the same `KesVerificationKey` type and
+/// `period_for_slot` schedule apply.
+#[derive(Debug)]
+pub struct EvolvingVrfKey {
+    /// Per-period VRF secrets; erased periods are `None`.
+    secrets: Vec<Option<[u8; 32]>>,
+    public_keys: Vec<[u8; 32]>,
+    root: [u8; 32],
+    current_period: u32,
+}
+
+/// A VRF proof made with the key of `period`, with the Merkle path binding
+/// that key to the registered root.
+#[derive(Clone, Debug)]
+pub struct PeriodVrfProof {
+    pub period: u32,
+    pub public_key: [u8; 32],
+    pub auth_path: Vec<[u8; 32]>,
+    pub proof: VrfProof,
+}
+
+impl EvolvingVrfKey {
+    /// Generate keys for `max_periods` periods (rounded up to a power of 2).
+    #[must_use]
+    pub fn generate(max_periods: u32) -> Self {
+        let mut rng = rand::thread_rng();
+        Self::from_secrets((0..leaf_count(max_periods)).map(|_| {
+            let mut secret = [0u8; 32];
+            rng.fill_bytes(&mut secret);
+            secret
+        }))
+    }
+
+    /// Derive every period's key from `seed` (deterministic).
+    #[must_use]
+    pub fn from_seed(seed: [u8; 32], max_periods: u32) -> Self {
+        Self::from_secrets((0..leaf_count(max_periods)).map(|period| {
+            let mut hasher = Sha256::new();
+            hasher.update(seed);
+            hasher.update(b"vrf-period");
+            hasher.update(period.to_le_bytes());
+            hasher.finalize().into()
+        }))
+    }
+
+    fn from_secrets(secrets: impl Iterator<Item = [u8; 32]>) -> Self {
+        let mut stored = Vec::new();
+        let mut public_keys = Vec::new();
+        for secret in secrets {
+            let keypair = VrfKeypair::from_secret(&secret).expect("32-byte secret");
+            public_keys.push(*keypair.public_key());
+            stored.push(Some(secret));
+        }
+        EvolvingVrfKey {
+            root: compute_merkle_root(&public_keys),
+            secrets: stored,
+            public_keys,
+            current_period: 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn max_periods(&self) -> u32 {
+        self.public_keys.len() as u32
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn current_period(&self) -> u32 {
+        self.current_period
+    }
+
+    /// Root of all period keys, registered in place of a single VRF key.
+    #[must_use]
+    pub fn verification_key(&self) -> KesVerificationKey {
+        KesVerificationKey::new(self.root, self.max_periods())
+    }
+
+    /// Prove `alpha` with the key of `period`, first erasing every earlier
+    /// period's key.
+    pub fn prove(
+        &mut self,
+        period: u32,
+        alpha: &[u8],
+    ) -> aether_crypto_kes::Result<PeriodVrfProof> {
+        self.evolve_to(period)?;
+        let secret = self.secrets[period as usize]
+            .as_ref()
+            .ok_or(KesError::KeyErased { period })?;
+        let keypair = VrfKeypair::from_secret(secret).expect("32-byte secret");
+        Ok(PeriodVrfProof {
+            period,
+            public_key: *keypair.public_key(),
+            auth_path: compute_auth_path(&self.public_keys, period as usize),
+            proof: keypair.prove(alpha),
+        })
+    }
+
+    /// Erase the keys of all periods before `period`. Nodes call this at
+    /// every period boundary, whether or not they lead a slot in it.
+    pub fn evolve_to(&mut self, period: u32) -> aether_crypto_kes::Result<()> {
+        if period >= self.max_periods() {
+            return Err(KesError::PeriodOutOfRange {
+                requested: period,
+                max_periods: self.max_periods(),
+            });
+        }
+        if period < self.current_period {
+            return Err(KesError::PeriodRegression {
+                current: self.current_period,
+                requested: period,
+            });
+        }
+        for secret in &mut self.secrets[self.current_period as usize..period as usize] {
+            if let Some(bytes) = secret.as_mut() {
+                bytes.zeroize();
+            }
+            *secret = None;
+        }
+        self.current_period = period;
+        Ok(())
+    }
+}
+
+impl Drop for EvolvingVrfKey {
+    fn drop(&mut self) {
+        for secret in self.secrets.iter_mut().flatten() {
+            secret.zeroize();
+        }
+    }
+}
+
+impl PeriodVrfProof {
+    /// Verify that the proof was made with the key `vk` commits to for
+    /// `claimed_period` (the KES period of the slot being led), and that it
+    /// proves `alpha`.
+    #[must_use = "discarding a VRF verification result is a security bug"]
+    pub fn verify(&self, vk: &KesVerificationKey, claimed_period: u32, alpha: &[u8]) -> bool {
+        if self.period != claimed_period || self.period >= vk.max_periods() {
+            return false;
+        }
+        if self.auth_path.len() != vk.max_periods().next_power_of_two().trailing_zeros() as usize {
+            return false;
+        }
+        if !verify_auth_path(
+            &self.public_key,
+            self.period as usize,
+            &self.auth_path,
+            &vk.root(),
+        ) {
+            return false;
+        }
+        matches!(verify_proof(&self.public_key, alpha, &self.proof), Ok(true))
+    }
+
+    /// The VRF output, for sortition and epoch randomness.
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> &[u8; 32] {
+        &self.proof.output
+    }
+}
+
+fn leaf_count(max_periods: u32) -> u32 {
+    max_periods.max(2).next_power_of_two()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_kes::period_for_slot;
+
+    #[test]
+    fn proofs_verify_only_for_their_period() {
+        let mut key = EvolvingVrfKey::from_seed([7u8; 32], 8);
+        let vk = key.verification_key();
+        assert_eq!(vk.max_periods(), 8);
+
+        let proof = key.prove(2, b"slot 250").unwrap();
+        assert!(proof.verify(&vk, 2, b"slot 250"));
+        assert!(!proof.verify(&vk, 2, b"slot 251"));
+        // Claimed for another period, or relabelled as one.
+        assert!(!proof.verify(&vk, 3, b"slot 250"));
+        let mut relabelled = proof.clone();
+        relabelled.period = 3;
+        assert!(!relabelled.verify(&vk, 3, b"slot 250"));
+
+        // A key outside the tree.
+        let mut foreign = proof.clone();
+        let other = VrfKeypair::from_secret(&[9u8; 32]).unwrap();
+        foreign.public_key = *other.public_key();
+        foreign.proof = other.prove(b"slot 250");
+        assert!(!foreign.verify(&vk, 2, b"slot 250"));
+
+        let other_vk = EvolvingVrfKey::from_seed([8u8; 32], 8).verification_key();
+        assert!(!proof.verify(&other_vk, 2, b"slot 250"));
+
+        let mut short = proof;
+        short.auth_path.pop();
+        assert!(!short.verify(&vk, 2, b"slot 250"));
+    }
+
+    #[test]
+    fn evolving_erases_past_periods() {
+        let mut key = EvolvingVrfKey::generate(4);
+        let vk = key.verification_key();
+        let past = key.prove(0, b"alpha").unwrap();
+
+        key.evolve_to(period_for_slot(250, 100)).unwrap();
+        assert_eq!(key.current_period(), 2);
+        assert!(key.secrets[..2].iter().all(Option::is_none));
+        assert_eq!(
+            key.prove(1, b"alpha").unwrap_err(),
+            KesError::PeriodRegression {
+                current: 2,
+                requested: 1
+            }
+        );
+        assert_eq!(
+            key.evolve_to(4).unwrap_err(),
+            KesError::PeriodOutOfRange {
+                requested: 4,
+                max_periods: 4
+            }
+        );
+
+        // Proofs made before evolving stay valid; a stolen current key can
+        // only prove for the current period and later.
+        assert!(past.verify(&vk, 0, b"alpha"));
+        let current = key.prove(2, b"alpha").unwrap();
+        assert!(current.verify(&vk, 2, b"alpha"));
+        assert!(!current.verify(&vk, 0, b"alpha"));
+    }
+
+    #[test]
+    fn deterministic_from_seed() {
+        let mut a = EvolvingVrfKey::from_seed([1u8; 32], 4);
+        let mut b = EvolvingVrfKey::from_seed([1u8; 32], 4);
+        assert_eq!(a.verification_key(), b.verification_key());
+        assert_eq!(
+            a.prove(1, b"x").unwrap().output(),
+            b.prove(1, b"x").unwrap().output()
+        );
+        // Each period has its own key.
+        assert_ne!(a.public_keys[0], a.public_keys[1]);
+    }
+}
@@ -1,6 +1,17 @@
+pub mod batch;
 pub mod ecvrf;
-
-pub use ecvrf::{check_leader_eligibility_integer, verify_proof, VrfKeypair, VrfProof};
+pub mod encoding;
+pub mod evolving;
+pub mod sortition;
+
+pub use batch::{find_invalid, verify_batch, BatchEntry};
+pub use ecvrf::{
+    check_leader_eligibility_integer, proof_to_output, verify_proof, VrfKeypair, VrfOutput,
+    VrfProof,
+};
+pub use encoding::{OUTPUT_LEN, PROOF_LEN};
+pub use evolving::{EvolvingVrfKey, PeriodVrfProof};
+pub use sortition::{sortition, Tau};
 
 #[allow(deprecated)]
 pub use ecvrf::{check_leader_eligibility, output_to_value};
@@ -19,6 +30,15 @@ pub trait VrfSigner: Send + Sync {
 /// of the secret key corresponding to the given public key.
 pub trait VrfVerifier: Send + Sync {
     fn verify(&self, public_key: &[u8; 32], alpha: &[u8], proof: &VrfProof) -> Result<bool>;
+
+    /// Verify many proofs at once; true iff every entry verifies. Block sync
+    /// checks thousands of leader proofs, so implementations may share work
+    /// across entries. The default checks them one by one.
+    fn verify_batch(&self, entries: &[BatchEntry<'_>]) -> bool {
+        entries
+            .iter()
+            .all(|(pk, alpha, proof)| matches!(self.verify(pk, alpha, proof), Ok(true)))
+    }
 }
 
 impl VrfSigner for VrfKeypair {
@@ -31,13 +51,17 @@ impl VrfSigner for VrfKeypair {
     }
 }
 
-/// ECVRF-EDWARDS25519-SHA512-ELL2 verifier (RFC 9381).
+/// ECVRF-EDWARDS25519-SHA512-TAI verifier (RFC 9381).
 pub struct EcVrfVerifier;
 
 impl VrfVerifier for EcVrfVerifier {
     fn verify(&self, public_key: &[u8; 32], alpha: &[u8], proof: &VrfProof) -> Result<bool> {
         verify_proof(public_key, alpha, proof)
     }
+
+    fn verify_batch(&self, entries: &[BatchEntry<'_>]) -> bool {
+        batch::verify_batch(entries)
+    }
 }
 
 pub mod mock {
@@ -176,6 +200,21 @@ mod trait_tests {
             .unwrap());
     }
 
+    #[test]
+    fn batch_verification_through_trait() {
+        let keypair = VrfKeypair::generate();
+        let good = keypair.prove(b"slot-1");
+        let bad = keypair.prove(b"slot-2");
+        let pk = keypair.public_key();
+        let entries: [BatchEntry<'_>; 2] = [(pk, b"slot-1", &good), (pk, b"slot-3", &bad)];
+
+        let verifier: Box<dyn VrfVerifier> = Box::new(EcVrfVerifier);
+        assert!(verifier.verify_batch(&entries[..1]));
+        assert!(!verifier.verify_batch(&entries));
+        assert!(MockVrfVerifier.verify_batch(&entries));
+        assert!(!RejectAllVrfVerifier.verify_batch(&entries));
+    }
+
     #[test]
     fn trait_objects_are_object_safe() {
         let keypair = VrfKeypair::generate();
@@ -0,0 +1,233 @@
+use subtle::{Choice, ConditionallySelectable};
+
+/// Expected seats across all stake, as `numerator / denominator`.
+///
+/// Leader election uses tau <= 1 (the chance some validator leads a slot);
+/// a committee of expected size k uses `Tau::seats(k)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tau {
+    pub numerator: u128,
+    pub denominator: u128,
+}
+
+impl Tau {
+    pub const fn new(numerator: u128, denominator: u128) -> Self {
+        Tau {
+            numerator,
+            denominator,
+        }
+    }
+
+    /// A committee of `k` expected seats.
+    pub const fn seats(k: u64) -> Self {
+        Tau::new(k as u128, 1)
+    }
+}
+
+/// Seats won by `stake` out of `total_stake` with this VRF output.
+///
+/// With lambda = tau * stake / total_stake expected seats and u the first 8
+/// bytes of the output read as a fraction in [0, 1):
+///
+///   seats = floor(lambda) + (u < frac(lambda) ? 1 : 0)
+///
+/// so the expectation is exactly lambda, splitting stake across identities
+/// gains nothing, and for lambda <= 1 this is the single-seat rule
+/// `u < lambda` used for leader election. Everything is exact fixed-point
+/// (no floats, identical on every platform), and the comparison against the
+/// VRF output is branch-free, so a leader's own eligibility does not leak
+/// through timing before it publishes a block.
+#[must_use]
+pub fn sortition(vrf_output: &[u8; 32], stake: u128, total_stake: u128, tau: Tau) -> u64 {
+    if total_stake == 0 || tau.denominator == 0 {
+        return 0;
+    }
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&vrf_output[..8]);
+    let u = u64::from_le_bytes(bytes);
+
+    // lambda = n / d; only these public quantities are divided.
+    let n = mul(&from_u128(stake), &from_u128(tau.numerator));
+    let d = mul(&from_u128(total_stake), &from_u128(tau.denominator));
+    let (whole, frac) = div_rem(&n, &d);
+
+    // u / 2^64 < frac / d  <=>  u * d < frac * 2^64
+    let lhs = mul(&from_u128(u as u128), &d);
+    let rhs = shl64(&frac);
+    let extra = u64::conditional_select(&0, &1, ct_lt(&lhs, &rhs));
+
+    let whole = if whole[1..].iter().any(|&limb| limb != 0) {
+        u64::MAX
+    } else {
+        whole[0]
+    };
+    whole.saturating_add(extra)
+}
+
+// ============================================================================
+// 384-bit unsigned arithmetic, little-endian u64 limbs. Products of two u128s
+// and their shift by 64 bits fit with room to spare.
+// ============================================================================
+
+const LIMBS: usize = 6;
+type Wide = [u64; LIMBS];
+
+fn from_u128(value: u128) -> Wide {
+    let mut out = [0u64; LIMBS];
+    out[0] = value as u64;
+    out[1] = (value >> 64) as u64;
+    out
+}
+
+fn mul(a: &Wide, b: &Wide) -> Wide {
+    let mut out = [0u64; LIMBS];
+    for i in 0..LIMBS {
+        let mut carry = 0u128;
+        for j in 0..LIMBS - i {
+            let cur = out[i + j] as u128 + (a[i] as u128) * (b[j] as u128) + carry;
+            out[i + j] = cur as u64;
+            carry = cur >> 64;
+        }
+    }
+    out
+}
+
+fn shl64(a: &Wide) -> Wide {
+    let mut out = [0u64; LIMBS];
+    out[1..].copy_from_slice(&a[..LIMBS - 1]);
+    out
+}
+
+/// Branch-free `a < b`: the borrow out of `a - b`.
+fn ct_lt(a: &Wide, b: &Wide) -> Choice {
+    let mut borrow = 0u64;
+    for i in 0..LIMBS {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (_, b2) = diff.overflowing_sub(borrow);
+        borrow = (b1 | b2) as u64;
+    }
+    Choice::from(borrow as u8)
+}
+
+fn sub(a: &Wide, b: &Wide) -> Wide {
+    let mut out = [0u64; LIMBS];
+    let mut borrow = 0u64;
+    for i in 0..LIMBS {
+        let (diff, b1) = a[i].overflowing_sub(b[i]);
+        let (diff, b2) = diff.overflowing_sub(borrow);
+        out[i] = diff;
+        borrow = (b1 | b2) as u64;
+    }
+    out
+}
+
+/// Schoolbook binary long division. Operates on public values only.
+fn div_rem(n: &Wide, d: &Wide) -> (Wide, Wide) {
+    let mut quotient = [0u64; LIMBS];
+    let mut remainder = [0u64; LIMBS];
+    for bit in (0..LIMBS * 64).rev() {
+        // remainder = remainder << 1 | bit of n
+        for i in (1..LIMBS).rev() {
+            remainder[i] = (remainder[i] << 1) | (remainder[i - 1] >> 63);
+        }
+        remainder[0] = (remainder[0] << 1) | ((n[bit / 64] >> (bit % 64)) & 1);
+        if !bool::from(ct_lt(&remainder, d)) {
+            remainder = sub(&remainder, d);
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(u: u64) -> [u8; 32] {
+        let mut out = [0xffu8; 32];
+        out[..8].copy_from_slice(&u.to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn single_seat_threshold() {
+        let half = 1u64 << 63;
+        // lambda = 0.8 * 5000 / 10000 = 0.4
+        let tau = Tau::new(4, 5);
+        let threshold = (0.4f64 * 2f64.powi(64)) as u64;
+        assert_eq!(sortition(&output(0), 5000, 10_000, tau), 1);
+        assert_eq!(sortition(&output(threshold - 1_000), 5000, 10_000, tau), 1);
+        assert_eq!(sortition(&output(threshold + 1_000), 5000, 10_000, tau), 0);
+        assert_eq!(sortition(&output(half), 5000, 10_000, tau), 0);
+
+        assert_eq!(sortition(&output(0), 0, 10_000, tau), 0);
+        assert_eq!(sortition(&output(0), 100, 0, tau), 0);
+        assert_eq!(sortition(&output(0), 100, 1000, Tau::new(4, 0)), 0);
+    }
+
+    #[test]
+    fn committee_seats() {
+        // lambda = 10 * 250 / 1000 = 2.5
+        let tau = Tau::seats(10);
+        assert_eq!(sortition(&output(0), 250, 1000, tau), 3);
+        assert_eq!(sortition(&output((1 << 63) - 1), 250, 1000, tau), 3);
+        assert_eq!(sortition(&output(1 << 63), 250, 1000, tau), 2);
+        assert_eq!(sortition(&output(u64::MAX), 250, 1000, tau), 2);
+        // Whole stake, integral lambda: exactly tau seats.
+        assert_eq!(sortition(&output(0), 1000, 1000, tau), 10);
+        assert_eq!(sortition(&output(u64::MAX), 1000, 1000, tau), 10);
+    }
+
+    #[test]
+    fn large_values_are_exact() {
+        let total = u128::MAX;
+        let tau = Tau::new(u128::MAX, u128::MAX);
+        assert_eq!(sortition(&output(u64::MAX), total, total, tau), 1);
+        assert_eq!(sortition(&output(0), total / 2, total, tau), 1);
+        assert_eq!(sortition(&output(u64::MAX), total / 2, total, tau), 0);
+        assert_eq!(
+            sortition(&output(0), u128::MAX, 1, Tau::seats(u64::MAX)),
+            u64::MAX
+        );
+    }
+}
+
+#[cfg(test)]
+mod proptests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        /// Seats are floor(lambda) or ceil(lambda), and the single-seat rule
+        /// matches u < lambda computed in plain u128 arithmetic.
+        #[test]
+        fn seats_bracket_lambda(
+            u in any::<u64>(),
+            stake in 0u128..1_000_000,
+            total in 1u128..1_000_000,
+            k in 1u64..100,
+        ) {
+            let seats = sortition(&{
+                let mut out = [0u8; 32];
+                out[..8].copy_from_slice(&u.to_le_bytes());
+                out
+            }, stake, total, Tau::seats(k));
+            let floor = (stake * k as u128 / total) as u64;
+            let exact = (stake * k as u128) % total == 0;
+            prop_assert!(seats == floor || (!exact && seats == floor + 1));
+        }
+
+        #[test]
+        fn single_seat_matches_reference(
+            u in any::<u64>(),
+            stake in 0u128..1_000_000,
+            total in 1u128..1_000_000,
+        ) {
+            prop_assume!(stake <= total);
+            let mut out = [0u8; 32];
+            out[..8].copy_from_slice(&u.to_le_bytes());
+            let expected = (u as u128) * total < stake << 64;
+            prop_assert_eq!(sortition(&out, stake, total, Tau::new(1, 1)) > 0, expected);
+        }
+    }
+}
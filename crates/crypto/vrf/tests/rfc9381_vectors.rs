@@ -0,0 +1,120 @@
+//! Conformance with the ECVRF-EDWARDS25519-SHA512-TAI test vectors of
+//! RFC 9381, Appendix B.3: examples 16 to 18, checked byte for byte.
+
+use aether_crypto_vrf::ecvrf::proof_to_output;
+use aether_crypto_vrf::{verify_proof, VrfKeypair, VrfOutput, VrfProof};
+
+struct Vector {
+    sk: &'static str,
+    pk: &'static str,
+    alpha: &'static str,
+    gamma: &'static str,
+    /// The remaining 48 bytes of pi, c || s.
+    c_s: &'static str,
+    beta: &'static str,
+}
+
+const VECTORS: &[Vector] = &[
+    Vector {
+        sk: "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        pk: "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        alpha: "",
+        gamma: "8657106690b5526245a92b003bb079ccd1a92130477671f6fc01ad16f26f723f",
+        c_s: "26f8a57ccaed74ee1b190bed1f479d97\
+              27d2d0f9b005a6e456a35d4fb0daab1268a1b0db10836d9826a528ca76567805",
+        beta: "90cf1df3b703cce59e2a35b925d411164068269d7b2d29f3301c03dd757876ff\
+               66b71dda49d2de59d03450451af026798e8f81cd2e333de5cdf4f3e140fdd8ae",
+    },
+    Vector {
+        sk: "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        pk: "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        alpha: "72",
+        gamma: "f3141cd382dc42909d19ec5110469e4feae18300e94f304590abdced48aed593",
+        c_s: "3bf0864a62558b3ed7f2fea45c92a465\
+              301b3bbf5e3e54ddf2d935be3b67926da3ef39226bbc355bdc9850112c8f4b02",
+        beta: "eb4440665d3891d668e7e0fcaf587f1b4bd7fbfe99d0eb2211ccec90496310eb\
+               5e33821bc613efb94db5e5b54c70a848a0bef4553a41befc57663b56373a5031",
+    },
+    Vector {
+        sk: "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        pk: "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        alpha: "af82",
+        gamma: "9bc0f79119cc5604bf02d23b4caede71393cedfbb191434dd016d30177ccbf80",
+        c_s: "96bb474e53895c362d8628ee9f9ea3c0\
+              e52c7a5c691b6c18c9979866568add7a2d41b00b05081ed0f58ee5e31b3a970e",
+        beta: "645427e5d00c62a23fb703732fa5d892940935942101e456ecca7bb217c61c45\
+               2118fec1219202a0edcf038bb6373241578be7217ba85a2687f7a0310b2df19f",
+    },
+];
+
+fn hex(s: &str) -> Vec<u8> {
+    let s: String = s.split_whitespace().collect();
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn pk(v: &Vector) -> [u8; 32] {
+    hex(v.pk).try_into().unwrap()
+}
+
+/// The vector's proof, decoded from its published pi.
+fn proof(v: &Vector) -> VrfProof {
+    VrfProof::decode(&[hex(v.gamma), hex(v.c_s)].concat()).unwrap()
+}
+
+#[test]
+fn prove_matches_vectors() {
+    for v in VECTORS {
+        let kp = VrfKeypair::from_secret(&hex(v.sk)).unwrap();
+        assert_eq!(kp.public_key(), &pk(v));
+
+        let proof = kp.prove(&hex(v.alpha));
+        assert_eq!(proof.proof[..32], hex(v.gamma)[..]);
+        assert_eq!(proof.proof[32..], hex(v.c_s)[..]);
+
+        let beta = proof_to_output(&proof.proof).unwrap();
+        assert_eq!(beta.as_bytes().to_vec(), hex(v.beta));
+        assert_eq!(proof.output.to_vec(), hex(v.beta)[..32]);
+    }
+}
+
+#[test]
+fn verify_accepts_vectors_from_their_encoding() {
+    for v in VECTORS {
+        let pi = proof(v).encode();
+        let decoded = VrfProof::decode(&pi).unwrap();
+        assert_eq!(decoded.output.to_vec(), hex(v.beta)[..32]);
+        assert!(verify_proof(&pk(v), &hex(v.alpha), &decoded).unwrap());
+        assert_eq!(
+            VrfOutput::decode(&hex(v.beta)).unwrap(),
+            proof_to_output(&pi).unwrap()
+        );
+
+        // Another input, or a bit of Gamma, c or s flipped, fails.
+        let mut other = hex(v.alpha);
+        other.push(0);
+        assert!(!verify_proof(&pk(v), &other, &decoded).unwrap());
+        for byte in [0, 40, 60] {
+            let mut tampered = pi.clone();
+            tampered[byte] ^= 0x01;
+            let rejected = match VrfProof::decode(&tampered) {
+                Ok(proof) => !matches!(verify_proof(&pk(v), &hex(v.alpha), &proof), Ok(true)),
+                Err(_) => true,
+            };
+            assert!(rejected, "tampered byte {byte} accepted");
+        }
+    }
+}
+
+#[test]
+fn proofs_do_not_verify_under_other_vector_keys() {
+    let proofs: Vec<VrfProof> = VECTORS.iter().map(proof).collect();
+    for (i, v) in VECTORS.iter().enumerate() {
+        for (j, proof) in proofs.iter().enumerate() {
+            let ok = verify_proof(&pk(v), &hex(VECTORS[j].alpha), proof).unwrap();
+            assert_eq!(ok, i == j);
+        }
+    }
+}
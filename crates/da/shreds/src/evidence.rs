@@ -0,0 +1,123 @@
+use aether_types::Slot;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::shred::Shred;
+
+/// Whether the leader signed two different shreds at `a`'s position:
+/// different payloads, or different FEC set roots.
+pub fn conflicting(a: &Shred, b: &Shred) -> bool {
+    if (a.slot, a.fec_set_index, a.index) != (b.slot, b.fec_set_index, b.index) {
+        return false;
+    }
+    if a.payload_hash != b.payload_hash {
+        return true;
+    }
+    match (a.merkle_root(), b.merkle_root()) {
+        (Some(Ok(root_a)), Some(Ok(root_b))) => root_a != root_b,
+        _ => false,
+    }
+}
+
+/// Two conflicting shreds from one leader: proof it equivocated.
+///
+/// Payloads are dropped, since the signatures only cover their hashes, so
+/// the evidence stays small enough to put in a block.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ShredEquivocation {
+    pub first: Shred,
+    pub second: Shred,
+}
+
+impl ShredEquivocation {
+    /// Evidence from two shreds whose signatures the caller has checked,
+    /// if they conflict.
+    pub fn new(first: &Shred, second: &Shred) -> Option<Self> {
+        if !conflicting(first, second) {
+            return None;
+        }
+        let strip = |shred: &Shred| Shred {
+            payload: Vec::new(),
+            ..shred.clone()
+        };
+        Some(ShredEquivocation {
+            first: strip(first),
+            second: strip(second),
+        })
+    }
+
+    pub fn slot(&self) -> Slot {
+        self.first.slot
+    }
+
+    pub fn index(&self) -> u32 {
+        self.first.index
+    }
+
+    /// Check the evidence against the accused leader's key.
+    pub fn verify(&self, proposer_pubkey: &[u8]) -> Result<()> {
+        if !conflicting(&self.first, &self.second) {
+            bail!("shreds do not conflict");
+        }
+        for shred in [&self.first, &self.second] {
+            if let Some(Err(e)) = shred.merkle_root() {
+                bail!("invalid merkle proof: {}", e);
+            }
+            aether_crypto_primitives::verify(
+                proposer_pubkey,
+                &shred.signing_message(),
+                shred.signature.as_bytes(),
+            )
+            .map_err(|e| anyhow::anyhow!("invalid shred signature: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shred::ShredVariant;
+    use aether_crypto_primitives::Keypair;
+    use aether_types::{Signature, H256};
+
+    fn signed(key: &Keypair, slot: Slot, index: u32, payload: &[u8]) -> Shred {
+        let payload_hash = Shred::hash_payload(payload);
+        let msg = Shred::build_signing_message(slot, index, &payload_hash);
+        Shred::new(
+            ShredVariant::Data,
+            slot,
+            index,
+            1,
+            0,
+            H256::zero(),
+            payload.to_vec(),
+            Signature::from_bytes(key.sign(&msg)),
+        )
+    }
+
+    #[test]
+    fn two_payloads_at_one_index_prove_equivocation() {
+        let key = Keypair::generate();
+        let a = signed(&key, 5, 2, b"block a");
+        let b = signed(&key, 5, 2, b"block b");
+
+        assert!(ShredEquivocation::new(&a, &a.clone()).is_none());
+        assert!(ShredEquivocation::new(&a, &signed(&key, 5, 3, b"block b")).is_none());
+        let evidence = ShredEquivocation::new(&a, &b).unwrap();
+        assert!(evidence.first.payload.is_empty());
+        assert_eq!((evidence.slot(), evidence.index()), (5, 2));
+        evidence.verify(&key.public_key()).unwrap();
+        assert!(evidence.verify(&Keypair::generate().public_key()).is_err());
+    }
+
+    #[test]
+    fn forged_second_shred_is_not_evidence() {
+        let key = Keypair::generate();
+        let a = signed(&key, 5, 2, b"block a");
+        let mut b = a.clone();
+        b.payload_hash = Shred::hash_payload(b"framed");
+        let evidence = ShredEquivocation::new(&a, &b).unwrap();
+        assert!(evidence.verify(&key.public_key()).is_err());
+    }
+}
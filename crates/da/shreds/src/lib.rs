@@ -24,8 +24,18 @@
 //     fec_set_index: u32  // Which FEC set this belongs to
 //     payload: Vec<u8>  // Actual data chunk
 //     signature: Signature  // Leader signature
+//     merkle_proof: Option<Vec<H256>>  // Path to the FEC set root
 // ```
 //
+// MERKLE SIGNING:
+// The leader builds a Merkle tree over each FEC set, one leaf per shred
+// (slot, set, index, payload hash), and signs only the root. Every shred
+// carries its path to the root and the same signature, so the leader
+// signs once per set and a receiver can check any single shred; once a
+// root has verified, later shreds of the set only cost the path hashes
+// (`validation::VerifiedRoots`). Shreds without a path are still checked
+// against a per-shred signature.
+//
 // PSEUDOCODE:
 // ```
 // enum ShredVariant:
@@ -76,6 +86,11 @@
 //     return None  // Insufficient shreds
 // ```
 //
+// EQUIVOCATION:
+// Two validly signed shreds for the same (slot, set, index) with different
+// payloads or roots prove the leader equivocated. `ShredEquivocation`
+// keeps both, payloads dropped, and verifies against the leader's key.
+//
 // WIRE PROTOCOL:
 // - Shreds gossipped on 'shred' topic
 // - ~170KB per shred for 2MB block / 12 shreds
@@ -88,8 +103,11 @@
 // - Reconstruction status → Repair requests
 // ============================================================================
 
+pub mod evidence;
+pub mod merkle;
 pub mod serialization;
 pub mod shred;
 pub mod validation;
 
+pub use evidence::ShredEquivocation;
 pub use shred::Shred;
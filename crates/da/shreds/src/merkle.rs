@@ -0,0 +1,144 @@
+use aether_types::{Slot, H256};
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// Leaf of an FEC set's Merkle tree. Binds the shred's position as well as
+/// its payload, so a proof for one index cannot vouch for another.
+pub fn leaf_hash(slot: Slot, fec_set_index: u32, index: u32, payload_hash: &H256) -> H256 {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(slot.to_le_bytes());
+    hasher.update(fec_set_index.to_le_bytes());
+    hasher.update(index.to_le_bytes());
+    hasher.update(payload_hash.as_bytes());
+    H256::from(<[u8; 32]>::from(hasher.finalize()))
+}
+
+fn node_hash(left: &H256, right: &H256) -> H256 {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    H256::from(<[u8; 32]>::from(hasher.finalize()))
+}
+
+/// Binary SHA-256 tree over the leaves of one FEC set. A level with an odd
+/// number of nodes pairs its last node with itself.
+pub struct MerkleTree {
+    levels: Vec<Vec<H256>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<H256>) -> Result<Self> {
+        if leaves.is_empty() {
+            bail!("merkle tree needs at least one leaf");
+        }
+        let mut levels = vec![leaves];
+        while levels.last().map_or(0, Vec::len) > 1 {
+            let level = levels.last().expect("levels is non-empty");
+            let next = level
+                .chunks(2)
+                .map(|pair| node_hash(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+                .collect();
+            levels.push(next);
+        }
+        Ok(MerkleTree { levels })
+    }
+
+    pub fn root(&self) -> H256 {
+        self.levels.last().expect("levels is non-empty")[0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Siblings from leaf `index` up to, not including, the root.
+    pub fn proof(&self, index: usize) -> Option<Vec<H256>> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut at = index;
+        let proof = self.levels[..self.levels.len() - 1]
+            .iter()
+            .map(|level| {
+                let sibling = level.get(at ^ 1).unwrap_or(&level[at]);
+                at /= 2;
+                *sibling
+            })
+            .collect();
+        Some(proof)
+    }
+}
+
+/// The root `proof` leads to from `leaf` at position `index`.
+pub fn root_from_proof(leaf: H256, index: u32, proof: &[H256]) -> Result<H256> {
+    if proof.len() < 32 && (index as u64) >> proof.len() != 0 {
+        bail!(
+            "leaf index {index} does not fit a proof of depth {}",
+            proof.len()
+        );
+    }
+    let mut at = index;
+    let mut node = leaf;
+    for sibling in proof {
+        node = if at & 1 == 0 {
+            node_hash(&node, sibling)
+        } else {
+            node_hash(sibling, &node)
+        };
+        at >>= 1;
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: u32) -> Vec<H256> {
+        (0..count)
+            .map(|i| leaf_hash(7, 0, i, &H256::from([i as u8; 32])))
+            .collect()
+    }
+
+    #[test]
+    fn every_proof_leads_to_the_root() {
+        for count in [1, 2, 3, 12, 14, 32, 33] {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone()).unwrap();
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert_eq!(
+                    root_from_proof(*leaf, i as u32, &proof).unwrap(),
+                    tree.root(),
+                    "leaf {i} of {count}"
+                );
+            }
+            assert!(tree.proof(count as usize).is_none());
+        }
+    }
+
+    #[test]
+    fn proofs_do_not_transfer() {
+        let leaves = leaves(12);
+        let tree = MerkleTree::new(leaves.clone()).unwrap();
+        let proof = tree.proof(3).unwrap();
+        // Wrong position, wrong leaf, and an index the proof is too short for.
+        assert_ne!(root_from_proof(leaves[3], 2, &proof).unwrap(), tree.root());
+        assert_ne!(root_from_proof(leaves[4], 3, &proof).unwrap(), tree.root());
+        assert!(root_from_proof(leaves[3], 3 + 16, &proof).is_err());
+    }
+
+    #[test]
+    fn empty_tree_is_rejected() {
+        assert!(MerkleTree::new(Vec::new()).is_err());
+    }
+}
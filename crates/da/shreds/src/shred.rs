@@ -1,6 +1,8 @@
 use aether_types::{Signature, Slot, H256};
 use serde::{Deserialize, Serialize};
 
+const MERKLE_DOMAIN: &[u8] = b"aether-shred-merkle-root";
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ShredVariant {
     Data,
@@ -18,6 +20,10 @@ pub struct Shred {
     pub payload: Vec<u8>,
     pub signature: Signature,
     pub payload_hash: H256,
+    /// Path from this shred's leaf to the FEC set's Merkle root. When set,
+    /// `signature` covers the root rather than this shred alone.
+    #[serde(default)]
+    pub merkle_proof: Option<Vec<H256>>,
 }
 
 impl Shred {
@@ -43,9 +49,38 @@ impl Shred {
             payload,
             signature,
             payload_hash,
+            merkle_proof: None,
         }
     }
 
+    /// Attach the path to the FEC set's Merkle root, making this a shred
+    /// whose signature is the leader's signature over that root.
+    pub fn with_merkle_proof(mut self, proof: Vec<H256>) -> Self {
+        self.merkle_proof = Some(proof);
+        self
+    }
+
+    /// This shred's leaf in its FEC set's Merkle tree.
+    pub fn merkle_leaf(&self) -> H256 {
+        crate::merkle::leaf_hash(
+            self.slot,
+            self.fec_set_index,
+            self.index,
+            &self.payload_hash,
+        )
+    }
+
+    /// The FEC set root this shred's proof leads to; `None` for shreds
+    /// signed one by one.
+    pub fn merkle_root(&self) -> Option<anyhow::Result<H256>> {
+        let proof = self.merkle_proof.as_ref()?;
+        Some(crate::merkle::root_from_proof(
+            self.merkle_leaf(),
+            self.index,
+            proof,
+        ))
+    }
+
     pub fn hash_payload(payload: &[u8]) -> H256 {
         use sha2::{Digest, Sha256};
         let mut hasher = Sha256::new();
@@ -56,7 +91,17 @@ impl Shred {
     /// Canonical message used for Ed25519 signing and verification.
     /// Includes slot, index, and payload hash to bind the signature
     /// to a specific shred without including the full payload.
+    ///
+    /// For Merkle shreds this is the root message instead, or empty if the
+    /// proof is malformed, which no signature covers.
     pub fn signing_message(&self) -> Vec<u8> {
+        match self.merkle_root() {
+            Some(Ok(root)) => {
+                return Self::build_merkle_signing_message(self.slot, self.fec_set_index, &root)
+            }
+            Some(Err(_)) => return Vec::new(),
+            None => {}
+        }
         let mut msg = Vec::with_capacity(8 + 4 + 32);
         msg.extend_from_slice(&self.slot.to_le_bytes());
         msg.extend_from_slice(&self.index.to_le_bytes());
@@ -72,6 +117,18 @@ impl Shred {
         msg.extend_from_slice(payload_hash.as_bytes());
         msg
     }
+
+    /// Message the leader signs once per FEC set: slot, set index and the
+    /// Merkle root over its shreds, behind a domain tag so it can never be
+    /// mistaken for a per-shred message.
+    pub fn build_merkle_signing_message(slot: Slot, fec_set_index: u32, root: &H256) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(MERKLE_DOMAIN.len() + 8 + 4 + 32);
+        msg.extend_from_slice(MERKLE_DOMAIN);
+        msg.extend_from_slice(&slot.to_le_bytes());
+        msg.extend_from_slice(&fec_set_index.to_le_bytes());
+        msg.extend_from_slice(root.as_bytes());
+        msg
+    }
 }
 
 #[cfg(test)]
@@ -92,6 +149,38 @@ mod tests {
         );
         assert_eq!(shred.payload_hash, Shred::hash_payload(b"payload"));
     }
+
+    #[test]
+    fn merkle_shreds_sign_the_root() {
+        let shred = |index| {
+            Shred::new(
+                ShredVariant::Data,
+                3,
+                index,
+                1,
+                0,
+                H256::zero(),
+                vec![index as u8; 8],
+                Signature::from_bytes(vec![1, 2, 3]),
+            )
+        };
+        let shreds: Vec<Shred> = (0..3).map(shred).collect();
+        let tree = crate::merkle::MerkleTree::new(shreds.iter().map(Shred::merkle_leaf).collect())
+            .unwrap();
+        let root_message = Shred::build_merkle_signing_message(3, 0, &tree.root());
+
+        for (i, shred) in shreds.into_iter().enumerate() {
+            assert_eq!(shred.signing_message().len(), 44);
+            let proof = tree.proof(i).unwrap();
+            let merkle = shred.with_merkle_proof(proof);
+            assert_eq!(merkle.merkle_root().unwrap().unwrap(), tree.root());
+            assert_eq!(merkle.signing_message(), root_message);
+        }
+
+        // A proof too short for the index signs nothing.
+        let orphan = shred(5).with_merkle_proof(vec![H256::zero()]);
+        assert!(orphan.signing_message().is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,6 @@
+use std::collections::{HashSet, VecDeque};
+
+use aether_types::H256;
 use anyhow::{bail, Result};
 
 use crate::shred::Shred;
@@ -8,6 +11,60 @@ pub fn validate_shred(
     current_slot: u64,
     max_slot_age: u64,
     proposer_pubkey: &[u8],
+) -> Result<()> {
+    validate_shred_cached(shred, current_slot, max_slot_age, proposer_pubkey, None)
+}
+
+/// Merkle roots whose signature has already been checked, so the other
+/// shreds of an FEC set are verified by their path alone.
+pub struct VerifiedRoots {
+    capacity: usize,
+    roots: HashSet<(Vec<u8>, H256)>,
+    order: VecDeque<(Vec<u8>, H256)>,
+}
+
+impl VerifiedRoots {
+    pub fn new(capacity: usize) -> Self {
+        VerifiedRoots {
+            capacity,
+            roots: HashSet::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.roots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.roots.is_empty()
+    }
+
+    fn contains(&self, proposer: &[u8], root: H256) -> bool {
+        self.roots.contains(&(proposer.to_vec(), root))
+    }
+
+    fn insert(&mut self, proposer: &[u8], root: H256) {
+        if self.capacity == 0 || !self.roots.insert((proposer.to_vec(), root)) {
+            return;
+        }
+        self.order.push_back((proposer.to_vec(), root));
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.roots.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// [`validate_shred`], skipping the signature check for Merkle shreds
+/// whose root `verified` has already seen signed by `proposer_pubkey`.
+pub fn validate_shred_cached(
+    shred: &Shred,
+    current_slot: u64,
+    max_slot_age: u64,
+    proposer_pubkey: &[u8],
+    verified: Option<&mut VerifiedRoots>,
 ) -> Result<()> {
     if shred.payload_hash != Shred::hash_payload(&shred.payload) {
         bail!("payload hash mismatch");
@@ -17,15 +74,31 @@ pub fn validate_shred(
         bail!("missing signature");
     }
 
-    // Verify Ed25519 signature against the proposer's public key
-    let msg = shred.signing_message();
-    aether_crypto_primitives::verify(proposer_pubkey, &msg, shred.signature.as_bytes())
-        .map_err(|e| anyhow::anyhow!("invalid shred signature: {}", e))?;
+    let root = match shred.merkle_root() {
+        Some(Ok(root)) => Some(root),
+        Some(Err(e)) => bail!("invalid merkle proof: {}", e),
+        None => None,
+    };
+    let known = match (root, verified.as_deref()) {
+        (Some(root), Some(roots)) => roots.contains(proposer_pubkey, root),
+        _ => false,
+    };
+
+    if !known {
+        // Verify Ed25519 signature against the proposer's public key
+        let msg = shred.signing_message();
+        aether_crypto_primitives::verify(proposer_pubkey, &msg, shred.signature.as_bytes())
+            .map_err(|e| anyhow::anyhow!("invalid shred signature: {}", e))?;
+    }
 
     if shred.slot.saturating_add(max_slot_age) < current_slot {
         bail!("stale shred");
     }
 
+    if let (Some(root), Some(roots)) = (root, verified) {
+        roots.insert(proposer_pubkey, root);
+    }
+
     Ok(())
 }
 
@@ -132,6 +205,92 @@ mod tests {
         );
     }
 
+    fn merkle_set(key: &Keypair, slot: u64, count: u32) -> Vec<Shred> {
+        let shreds: Vec<Shred> = (0..count)
+            .map(|index| {
+                Shred::new(
+                    ShredVariant::Data,
+                    slot,
+                    index,
+                    1,
+                    0,
+                    H256::zero(),
+                    vec![index as u8; 16],
+                    Signature::from_bytes(vec![]),
+                )
+            })
+            .collect();
+        let tree = crate::merkle::MerkleTree::new(shreds.iter().map(Shred::merkle_leaf).collect())
+            .unwrap();
+        let signature = Signature::from_bytes(key.sign(&Shred::build_merkle_signing_message(
+            slot,
+            0,
+            &tree.root(),
+        )));
+        shreds
+            .into_iter()
+            .enumerate()
+            .map(|(i, shred)| Shred {
+                signature: signature.clone(),
+                ..shred.with_merkle_proof(tree.proof(i).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn validates_merkle_shreds_against_one_signature() {
+        let key = Keypair::generate();
+        let shreds = merkle_set(&key, 10, 12);
+        let mut roots = VerifiedRoots::new(16);
+        for shred in &shreds {
+            validate_shred(shred, 12, 5, &key.public_key()).unwrap();
+            validate_shred_cached(shred, 12, 5, &key.public_key(), Some(&mut roots)).unwrap();
+        }
+        assert_eq!(roots.len(), 1);
+
+        // The root is trusted only for the key that signed it.
+        let other = Keypair::generate();
+        let mut roots = VerifiedRoots::new(16);
+        validate_shred_cached(&shreds[0], 12, 5, &key.public_key(), Some(&mut roots)).unwrap();
+        assert!(
+            validate_shred_cached(&shreds[1], 12, 5, &other.public_key(), Some(&mut roots))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn rejects_merkle_shred_with_bad_path() {
+        let key = Keypair::generate();
+        let mut roots = VerifiedRoots::new(16);
+        let mut shreds = merkle_set(&key, 10, 12);
+        validate_shred_cached(&shreds[0], 12, 5, &key.public_key(), Some(&mut roots)).unwrap();
+
+        // A payload swapped in with its hash recomputed no longer leads to
+        // the signed root, cached or not.
+        let mut forged = shreds.remove(1);
+        forged.payload = vec![0xEE; 16];
+        forged.payload_hash = Shred::hash_payload(&forged.payload);
+        let err =
+            validate_shred_cached(&forged, 12, 5, &key.public_key(), Some(&mut roots)).unwrap_err();
+        assert!(err.to_string().contains("invalid shred signature"), "{err}");
+
+        let mut moved = shreds.remove(1);
+        moved.index = 40;
+        let err = validate_shred(&moved, 12, 5, &key.public_key()).unwrap_err();
+        assert!(err.to_string().contains("invalid merkle proof"), "{err}");
+    }
+
+    #[test]
+    fn verified_roots_are_bounded() {
+        let key = Keypair::generate();
+        let mut roots = VerifiedRoots::new(2);
+        for slot in 10..15 {
+            let shred = &merkle_set(&key, slot, 4)[0];
+            validate_shred_cached(shred, slot, 5, &key.public_key(), Some(&mut roots)).unwrap();
+        }
+        assert_eq!(roots.len(), 2);
+    }
+
     #[test]
     fn rejects_tampered_payload() {
         let key = Keypair::generate();
@@ -0,0 +1,168 @@
+use aether_types::ErasureParams;
+
+/// Bounds on the Reed-Solomon rate a leader may pick.
+#[derive(Clone, Debug)]
+pub struct CodingPolicy {
+    pub data_shards: u16,
+    pub min_parity: u16,
+    pub max_parity: u16,
+    /// How many times the measured loss the parity should cover.
+    pub headroom: f64,
+    /// Shreds an epoch must have measured before its loss rate is trusted.
+    pub min_samples: u64,
+}
+
+impl Default for CodingPolicy {
+    fn default() -> Self {
+        CodingPolicy {
+            data_shards: 10,
+            min_parity: 2,
+            max_parity: 8,
+            headroom: 2.0,
+            min_samples: 1_000,
+        }
+    }
+}
+
+impl CodingPolicy {
+    /// Whether a block header's coding is one this policy could have
+    /// chosen. Receivers check this before building a decoder for it.
+    pub fn accepts(&self, params: ErasureParams) -> bool {
+        params.data_shards == self.data_shards
+            && (self.min_parity..=self.max_parity).contains(&params.parity_shards)
+    }
+
+    /// Parity that covers `loss` with the configured headroom: `r` parity
+    /// shards out of `k + r` survive a loss rate of `r / (k + r)`.
+    pub fn parity_for(&self, loss: f64) -> u16 {
+        let target = self.headroom * loss.max(0.0);
+        if target >= 1.0 {
+            return self.max_parity;
+        }
+        let parity = (target * self.data_shards as f64 / (1.0 - target)).ceil();
+        (parity.min(self.max_parity as f64) as u16).clamp(self.min_parity, self.max_parity)
+    }
+}
+
+/// Picks each epoch's erasure coding from the shred loss measured in the
+/// last one.
+///
+/// Loss is counted per block as the shreds that did not arrive down the
+/// tree: those recovered from parity plus those fetched by repair. Parity
+/// goes up as soon as an epoch's loss calls for it, but comes down one
+/// shred per epoch, so a single quiet epoch does not undo it.
+#[derive(Clone, Debug)]
+pub struct AdaptiveCoding {
+    policy: CodingPolicy,
+    current: ErasureParams,
+    expected: u64,
+    missing: u64,
+}
+
+impl AdaptiveCoding {
+    pub fn new(policy: CodingPolicy) -> Self {
+        let current = ErasureParams::new(policy.data_shards, policy.min_parity);
+        AdaptiveCoding {
+            policy,
+            current,
+            expected: 0,
+            missing: 0,
+        }
+    }
+
+    pub fn policy(&self) -> &CodingPolicy {
+        &self.policy
+    }
+
+    /// The coding to shred this epoch's blocks with.
+    pub fn current(&self) -> ErasureParams {
+        self.current
+    }
+
+    /// Record one block: `expected` shreds were sent, `missing` of them
+    /// did not arrive and were recovered or repaired.
+    pub fn record_block(&mut self, expected: usize, missing: usize) {
+        self.expected += expected as u64;
+        self.missing += missing.min(expected) as u64;
+    }
+
+    /// The loss rate measured so far this epoch, once there are enough
+    /// samples to go on.
+    pub fn loss(&self) -> Option<f64> {
+        if self.expected == 0 || self.expected < self.policy.min_samples {
+            return None;
+        }
+        Some(self.missing as f64 / self.expected as f64)
+    }
+
+    /// Close the epoch and return the coding for the next one.
+    pub fn on_epoch(&mut self) -> ErasureParams {
+        if let Some(loss) = self.loss() {
+            let target = self.policy.parity_for(loss);
+            let parity = if target >= self.current.parity_shards {
+                target
+            } else {
+                self.current.parity_shards - 1
+            };
+            self.current = ErasureParams::new(self.policy.data_shards, parity);
+        }
+        self.expected = 0;
+        self.missing = 0;
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An epoch of ten blocks, each losing `missing` of 200 shreds.
+    fn epoch(coding: &mut AdaptiveCoding, missing: usize) -> ErasureParams {
+        for _ in 0..10 {
+            coding.record_block(200, missing);
+        }
+        coding.on_epoch()
+    }
+
+    #[test]
+    fn parity_tracks_loss_within_bounds() {
+        let policy = CodingPolicy::default();
+        assert_eq!(policy.parity_for(0.0), 2);
+        assert_eq!(policy.parity_for(0.05), 2);
+        // 2 × 12.5% loss = 25%: 4 of 14 shreds.
+        assert_eq!(policy.parity_for(0.125), 4);
+        assert_eq!(policy.parity_for(0.3), 8);
+        assert_eq!(policy.parity_for(0.9), 8);
+    }
+
+    #[test]
+    fn raises_at_once_and_lowers_gradually() {
+        let mut coding = AdaptiveCoding::new(CodingPolicy::default());
+        assert_eq!(coding.current(), ErasureParams::new(10, 2));
+        assert_eq!(epoch(&mut coding, 0), ErasureParams::new(10, 2));
+
+        // One epoch at 12.5% loss.
+        assert_eq!(epoch(&mut coding, 25), ErasureParams::new(10, 4));
+        assert_eq!(epoch(&mut coding, 0).parity_shards, 3);
+        assert_eq!(epoch(&mut coding, 0).parity_shards, 2);
+        assert_eq!(epoch(&mut coding, 0).parity_shards, 2);
+    }
+
+    #[test]
+    fn too_few_samples_keep_the_current_rate() {
+        let mut coding = AdaptiveCoding::new(CodingPolicy::default());
+        coding.record_block(12, 6);
+        assert_eq!(coding.loss(), None);
+        assert_eq!(coding.on_epoch(), ErasureParams::new(10, 2));
+    }
+
+    #[test]
+    fn accepts_only_codings_within_policy() {
+        let policy = CodingPolicy::default();
+        assert!(policy.accepts(ErasureParams::new(10, 2)));
+        assert!(policy.accepts(ErasureParams::new(10, 8)));
+        assert!(!policy.accepts(ErasureParams::new(10, 9)));
+        assert!(!policy.accepts(ErasureParams::new(10, 1)));
+        assert!(!policy.accepts(ErasureParams::new(200, 4)));
+    }
+}
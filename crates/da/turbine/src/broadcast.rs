@@ -1,7 +1,8 @@
 use aether_crypto_primitives::Keypair;
 use aether_da_erasure::ReedSolomonEncoder;
+use aether_da_shreds::merkle::MerkleTree;
 use aether_da_shreds::{shred::ShredVariant, Shred};
-use aether_types::{Signature, Slot, H256};
+use aether_types::{ErasureParams, Signature, Slot, H256};
 use anyhow::Result;
 
 pub struct TurbineBroadcaster {
@@ -25,10 +26,28 @@ impl TurbineBroadcaster {
         })
     }
 
+    /// The coding blocks are shredded with, for the block header.
+    pub fn coding(&self) -> ErasureParams {
+        ErasureParams::new(
+            self.encoder.data_shards as u16,
+            self.encoder.parity_shards as u16,
+        )
+    }
+
+    /// Switch to `params`, e.g. at an epoch boundary. Blocks shredded from
+    /// here on must carry them in their header.
+    pub fn set_coding(&mut self, params: ErasureParams) -> Result<()> {
+        self.encoder =
+            ReedSolomonEncoder::new(params.data_shards as usize, params.parity_shards as usize)?;
+        Ok(())
+    }
+
     pub fn shard_count(&self) -> usize {
         self.encoder.data_shards + self.encoder.parity_shards
     }
 
+    /// Shred `payload` as one FEC set. The leader signs the set's Merkle
+    /// root once and every shred carries that signature and its path.
     pub fn make_shreds(&self, slot: Slot, block_id: H256, payload: &[u8]) -> Result<Vec<Shred>> {
         let shards = self.encoder.encode(payload)?;
         let mut result = Vec::with_capacity(shards.len());
@@ -43,10 +62,6 @@ impl TurbineBroadcaster {
                 ShredVariant::Parity
             };
 
-            let payload_hash = Shred::hash_payload(&chunk);
-            let msg = Shred::build_signing_message(slot, shard_index, &payload_hash);
-            let signature = Signature::from_bytes(self.signing_key.sign(&msg));
-
             result.push(Shred::new(
                 variant,
                 slot,
@@ -55,10 +70,18 @@ impl TurbineBroadcaster {
                 0,
                 block_id,
                 chunk,
-                signature,
+                Signature::from_bytes(Vec::new()),
             ));
         }
 
+        let tree = MerkleTree::new(result.iter().map(Shred::merkle_leaf).collect())?;
+        let msg = Shred::build_merkle_signing_message(slot, 0, &tree.root());
+        let signature = Signature::from_bytes(self.signing_key.sign(&msg));
+        for (idx, shred) in result.iter_mut().enumerate() {
+            shred.signature = signature.clone();
+            shred.merkle_proof = tree.proof(idx);
+        }
+
         Ok(result)
     }
 
@@ -87,6 +110,21 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn set_coding_changes_the_shred_count() {
+        let mut broadcaster = TurbineBroadcaster::new(10, 2, 1, Keypair::generate()).unwrap();
+        assert_eq!(broadcaster.coding(), ErasureParams::default());
+
+        broadcaster.set_coding(ErasureParams::new(10, 4)).unwrap();
+        assert_eq!(broadcaster.coding(), ErasureParams::new(10, 4));
+        let shreds = broadcaster
+            .make_shreds(1, H256::zero(), &[7; 1000])
+            .unwrap();
+        assert_eq!(shreds.len(), 14);
+        assert!(broadcaster.set_coding(ErasureParams::new(10, 0)).is_err());
+        assert_eq!(broadcaster.coding(), ErasureParams::new(10, 4));
+    }
+
     #[test]
     fn shred_signatures_are_valid_ed25519() {
         let key = Keypair::generate();
@@ -103,6 +141,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn one_signature_covers_the_fec_set() {
+        let key = Keypair::generate();
+        let pubkey = key.public_key();
+        let broadcaster = TurbineBroadcaster::new(10, 2, 1, key).unwrap();
+        let shreds = broadcaster
+            .make_shreds(9, H256::zero(), &[5; 4096])
+            .unwrap();
+
+        let root = shreds[0].merkle_root().unwrap().unwrap();
+        for shred in &shreds {
+            assert_eq!(shred.signature, shreds[0].signature);
+            assert_eq!(shred.merkle_root().unwrap().unwrap(), root);
+            aether_da_shreds::validation::validate_shred(shred, 9, 4, &pubkey)
+                .expect("every shred verifies on its own");
+        }
+    }
+
     #[test]
     fn shred_signatures_reject_wrong_key() {
         let key = Keypair::generate();
@@ -0,0 +1,104 @@
+use std::collections::{BTreeMap, HashMap};
+
+use aether_da_shreds::{Shred, ShredEquivocation};
+use aether_types::Slot;
+
+/// Watches verified shreds for a leader signing two versions of one.
+///
+/// The first shred seen at each (slot, FEC set, index) is remembered; a
+/// conflicting one makes the slot equivocating. Its shreds are no longer
+/// worth retransmitting, and the two shreds become evidence for slashing.
+#[derive(Default)]
+pub struct EquivocationDetector {
+    seen: HashMap<(Slot, u32, u32), Shred>,
+    evidence: BTreeMap<Slot, ShredEquivocation>,
+    pending: Vec<ShredEquivocation>,
+}
+
+impl EquivocationDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a shred whose signature has been checked. Returns evidence
+    /// the first time its slot is caught equivocating.
+    pub fn observe(&mut self, shred: &Shred) -> Option<ShredEquivocation> {
+        if self.evidence.contains_key(&shred.slot) {
+            return None;
+        }
+        let key = (shred.slot, shred.fec_set_index, shred.index);
+        let first = match self.seen.get(&key) {
+            Some(first) => first,
+            None => {
+                let header = Shred {
+                    payload: Vec::new(),
+                    ..shred.clone()
+                };
+                self.seen.insert(key, header);
+                return None;
+            }
+        };
+        let evidence = ShredEquivocation::new(first, shred)?;
+        self.evidence.insert(shred.slot, evidence.clone());
+        self.pending.push(evidence.clone());
+        Some(evidence)
+    }
+
+    /// Whether shreds of `slot` should still be passed down the tree.
+    pub fn should_retransmit(&self, slot: Slot) -> bool {
+        !self.evidence.contains_key(&slot)
+    }
+
+    pub fn evidence(&self, slot: Slot) -> Option<&ShredEquivocation> {
+        self.evidence.get(&slot)
+    }
+
+    /// Evidence caught since the last drain, for the slashing module.
+    pub fn drain_evidence(&mut self) -> Vec<ShredEquivocation> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Forget slots below `min_slot` to bound memory.
+    pub fn prune_before(&mut self, min_slot: Slot) {
+        self.seen.retain(|&(slot, _, _), _| slot >= min_slot);
+        self.evidence = self.evidence.split_off(&min_slot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TurbineBroadcaster;
+    use aether_crypto_primitives::Keypair;
+    use aether_types::H256;
+
+    #[test]
+    fn catches_a_leader_shredding_two_blocks_for_one_slot() {
+        let key = Keypair::generate();
+        let pubkey = key.public_key();
+        let broadcaster = TurbineBroadcaster::new(4, 2, 1, key).unwrap();
+        let block_a = broadcaster.make_shreds(8, H256::zero(), &[1; 400]).unwrap();
+        let block_b = broadcaster.make_shreds(8, H256::zero(), &[2; 400]).unwrap();
+        let next = broadcaster.make_shreds(9, H256::zero(), &[1; 400]).unwrap();
+
+        let mut detector = EquivocationDetector::new();
+        for shred in block_a.iter().chain(&block_a).chain(&next) {
+            assert!(detector.observe(shred).is_none());
+        }
+        assert!(detector.should_retransmit(8));
+
+        let evidence = detector.observe(&block_b[3]).expect("equivocation");
+        evidence.verify(&pubkey).unwrap();
+        assert_eq!((evidence.slot(), evidence.index()), (8, 3));
+        assert!(!detector.should_retransmit(8));
+        assert!(detector.should_retransmit(9));
+        // One piece of evidence per slot is enough.
+        assert!(detector.observe(&block_b[4]).is_none());
+        assert_eq!(detector.drain_evidence(), vec![evidence]);
+        assert!(detector.drain_evidence().is_empty());
+
+        detector.prune_before(9);
+        assert!(detector.evidence(8).is_none());
+        assert!(detector.observe(&next[0]).is_none());
+    }
+}
@@ -85,29 +85,59 @@
 //     return join(data_chunks)
 // ```
 //
+// ADAPTIVE CODING:
+// - The leader counts shreds that missed the tree (recovered from parity
+//   or repaired) and picks next epoch's RS(n, k) from the loss rate
+//   (`AdaptiveCoding::on_epoch`), e.g. RS(12, 10) → RS(14, 10) at 12% loss
+// - Parity rises at once and falls one shred per epoch
+// - The coding rides in `BlockHeader::erasure`; receivers decode each
+//   block with its own (`TurbineReceiver::ingest_shred_with`)
+//
 // TOPOLOGY:
-// - Stake-weighted tree construction
+// - Stake-weighted tree construction: validators are drawn in proportion
+//   to stake from a seeded SHA-256 stream, so every node derives the same
+//   tree; the leader is the root and each layer is `fanout` times wider
 // - Higher stake = higher in tree (lower latency)
-// - Periodically rebuild (per epoch)
+// - Rebuilt per epoch from the validator set and epoch randomness
+//   (`EpochTopology::on_epoch`), reshuffled per slot
 //
 // PERFORMANCE:
 // - 2MB block, 12 shreds = ~170KB per shred
 // - 500ms slot → need <200ms propagation
 // - Tree depth 3 → 3 hops × 50ms RTT = 150ms ✓
 //
+// STREAMING:
+// - Data shreds hold the block in order, so once shreds 0..i are in the
+//   receiver releases their bytes (`ingest_shred_streaming`) and
+//   transaction verification starts before the last shred lands; the
+//   chunk that completes the block carries whatever parity recovered
+//
+// EQUIVOCATION:
+// - `EquivocationDetector` remembers the first verified shred at each
+//   (slot, set, index); a second, different one signed by the leader
+//   marks the slot equivocating: its shreds stop being retransmitted and
+//   both shreds go to the slashing module as evidence
+//
 // OUTPUTS:
+// - Streamed block chunks → Execution pipeline
+// - Shred equivocation evidence → Slashing
 // - Reconstructed blocks → Consensus
 // - Missing shred requests → Repair protocol
 // - Propagation metrics → Monitoring
 // ============================================================================
 
+pub mod adaptive;
 pub mod broadcast;
+pub mod equivocation;
 pub mod receive;
 pub mod repair;
 pub mod topology;
 
+pub use adaptive::{AdaptiveCoding, CodingPolicy};
 pub use broadcast::TurbineBroadcaster;
-pub use receive::TurbineReceiver;
+pub use equivocation::EquivocationDetector;
+pub use receive::{BlockChunk, TurbineReceiver};
+pub use topology::{EpochTopology, TurbineTopology};
 
 #[cfg(test)]
 mod tests;
@@ -2,7 +2,7 @@ use std::collections::{HashMap, VecDeque};
 
 use aether_da_erasure::ReedSolomonDecoder;
 use aether_da_shreds::Shred;
-use aether_types::H256;
+use aether_types::{ErasureParams, H256};
 use anyhow::{bail, Result};
 
 /// Maximum number of in-flight blocks to prevent memory exhaustion DoS.
@@ -12,17 +12,87 @@ const MAX_PENDING_BLOCKS: usize = 64;
 /// Bounds total memory a malicious peer can force the receiver to hold.
 const MAX_PENDING_BYTES: usize = 128 * 1024 * 1024;
 
+/// Decoders kept for codings other than the default. The rate changes at
+/// most once an epoch, so only a couple are ever live.
+const MAX_CODINGS: usize = 8;
+
+struct PendingBlock {
+    coding: ErasureParams,
+    shards: Vec<Option<Vec<u8>>>,
+    stream: BodyStream,
+}
+
+/// In-order block bytes handed out by
+/// [`TurbineReceiver::ingest_shred_streaming`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockChunk {
+    pub block_id: H256,
+    /// Where `data` starts in the block.
+    pub offset: usize,
+    pub data: Vec<u8>,
+    /// Whether this chunk completes the block.
+    pub last: bool,
+}
+
+/// How far into a block's data shreds streaming has got. The encoder lays
+/// the block out as an 8-byte length prefix followed by the body, split
+/// evenly across the data shreds.
+#[derive(Default)]
+struct BodyStream {
+    /// Data shreds consumed, all of them in order.
+    next_shard: usize,
+    prefix: Vec<u8>,
+    body_len: Option<usize>,
+    /// Body bytes handed out so far.
+    released: usize,
+}
+
+impl BodyStream {
+    /// Consume the data shreds now contiguous with those already consumed
+    /// and return the body bytes they hold.
+    fn advance(&mut self, data_shards: &[Option<Vec<u8>>]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        while let Some(Some(shard)) = data_shards.get(self.next_shard) {
+            let mut bytes = &shard[..];
+            if self.body_len.is_none() {
+                let take = (8 - self.prefix.len()).min(bytes.len());
+                self.prefix.extend_from_slice(&bytes[..take]);
+                bytes = &bytes[take..];
+                if let Ok(prefix) = <[u8; 8]>::try_from(&self.prefix[..]) {
+                    let body_len = usize::try_from(u64::from_le_bytes(prefix))?;
+                    let capacity = (shard.len() * data_shards.len()).saturating_sub(8);
+                    if body_len > capacity {
+                        bail!("block length {body_len} exceeds its {capacity}-byte shreds");
+                    }
+                    self.body_len = Some(body_len);
+                }
+            }
+            if let Some(body_len) = self.body_len {
+                let take = (body_len - self.released).min(bytes.len());
+                out.extend_from_slice(&bytes[..take]);
+                self.released += take;
+            }
+            self.next_shard += 1;
+        }
+        Ok(out)
+    }
+}
+
 pub struct TurbineReceiver {
-    decoder: ReedSolomonDecoder,
-    pending: HashMap<H256, Vec<Option<Vec<u8>>>>,
+    coding: ErasureParams,
+    decoders: HashMap<ErasureParams, ReedSolomonDecoder>,
+    pending: HashMap<H256, PendingBlock>,
     pending_order: VecDeque<H256>,
     pending_bytes: usize,
 }
 
 impl TurbineReceiver {
     pub fn new(data_shards: usize, parity_shards: usize) -> Result<Self> {
+        let decoder = ReedSolomonDecoder::new(data_shards, parity_shards)?;
+        let coding = ErasureParams::new(u16::try_from(data_shards)?, u16::try_from(parity_shards)?);
         Ok(TurbineReceiver {
-            decoder: ReedSolomonDecoder::new(data_shards, parity_shards)?,
+            coding,
+            decoders: HashMap::from([(coding, decoder)]),
             pending: HashMap::new(),
             pending_order: VecDeque::new(),
             pending_bytes: 0,
@@ -38,26 +108,111 @@ impl TurbineReceiver {
 
     fn evict_oldest_pending(&mut self) {
         if let Some(block_id) = self.pending_order.pop_front() {
-            if let Some(shards) = self.pending.remove(&block_id) {
+            if let Some(block) = self.pending.remove(&block_id) {
                 self.pending_bytes = self
                     .pending_bytes
-                    .saturating_sub(Self::block_bytes(&shards));
+                    .saturating_sub(Self::block_bytes(&block.shards));
             }
         }
     }
 
     fn remove_pending(&mut self, block_id: &H256) {
-        if let Some(shards) = self.pending.remove(block_id) {
+        if let Some(block) = self.pending.remove(block_id) {
             self.pending_bytes = self
                 .pending_bytes
-                .saturating_sub(Self::block_bytes(&shards));
+                .saturating_sub(Self::block_bytes(&block.shards));
         }
         self.pending_order.retain(|queued| queued != block_id);
     }
 
+    fn decoder(&mut self, coding: ErasureParams) -> Result<&ReedSolomonDecoder> {
+        if !self.decoders.contains_key(&coding) {
+            let decoder = ReedSolomonDecoder::new(
+                coding.data_shards as usize,
+                coding.parity_shards as usize,
+            )?;
+            if self.decoders.len() > MAX_CODINGS {
+                let default = self.coding;
+                self.decoders.retain(|kept, _| *kept == default);
+            }
+            self.decoders.insert(coding, decoder);
+        }
+        Ok(&self.decoders[&coding])
+    }
+
+    /// Ingest a shred of a block coded with the receiver's default coding.
     pub fn ingest_shred(&mut self, shred: Shred) -> Result<Option<Vec<u8>>> {
-        let (data_shards, parity_shards) = self.decoder.shard_config();
-        let total_shards = data_shards + parity_shards;
+        self.ingest_shred_with(shred, self.coding)
+    }
+
+    /// Ingest a shred of a block coded with `coding`, as announced in the
+    /// block's header. Every shred of a block must be ingested with the
+    /// same coding.
+    pub fn ingest_shred_with(
+        &mut self,
+        shred: Shred,
+        coding: ErasureParams,
+    ) -> Result<Option<Vec<u8>>> {
+        let block_id = shred.block_id;
+        if !self.store(shred, coding)? {
+            return Ok(None);
+        }
+        self.decode(&block_id).map(Some)
+    }
+
+    /// Ingest a shred and release whatever block bytes it makes available
+    /// in order: data shreds stream out as soon as every data shred before
+    /// them has arrived, so execution can start on the front of the block
+    /// while the rest is in flight. The chunks of a block, concatenated,
+    /// are the block; the one that completes it is marked `last`.
+    pub fn ingest_shred_streaming(
+        &mut self,
+        shred: Shred,
+        coding: ErasureParams,
+    ) -> Result<Option<BlockChunk>> {
+        let block_id = shred.block_id;
+        let ready = self.store(shred, coding)?;
+        let block = self
+            .pending
+            .get_mut(&block_id)
+            .expect("store keeps the block pending");
+        let data_shards = block.coding.data_shards as usize;
+        let offset = block.stream.released;
+        let mut data = block.stream.advance(&block.shards[..data_shards])?;
+
+        if ready {
+            let released = block.stream.released;
+            let recovered = self.decode(&block_id)?;
+            if released > recovered.len() {
+                bail!(
+                    "streamed {released} bytes of a {}-byte block",
+                    recovered.len()
+                );
+            }
+            data.extend_from_slice(&recovered[released..]);
+            return Ok(Some(BlockChunk {
+                block_id,
+                offset,
+                data,
+                last: true,
+            }));
+        }
+        if data.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(BlockChunk {
+            block_id,
+            offset,
+            data,
+            last: false,
+        }))
+    }
+
+    /// Buffer `shred`; returns whether its block now has enough shreds to
+    /// decode.
+    fn store(&mut self, shred: Shred, coding: ErasureParams) -> Result<bool> {
+        let data_shards = coding.data_shards as usize;
+        let total_shards = coding.total_shards();
         let shred_idx = shred.index as usize;
         if shred_idx >= total_shards {
             bail!(
@@ -66,6 +221,26 @@ impl TurbineReceiver {
                 total_shards
             );
         }
+        if let Some(block) = self.pending.get(&shred.block_id) {
+            if block.coding != coding {
+                bail!(
+                    "block {:?} is coded {:?}, shred arrived as {:?}",
+                    shred.block_id,
+                    block.coding,
+                    coding
+                );
+            }
+            let width = block.shards.iter().flatten().map(Vec::len).next();
+            if width.is_some_and(|width| width != shred.payload.len()) {
+                bail!(
+                    "shred payload is {} bytes, block {:?} has {}-byte shreds",
+                    shred.payload.len(),
+                    shred.block_id,
+                    width.unwrap_or_default()
+                );
+            }
+        }
+        self.decoder(coding)?;
 
         let payload_len = shred.payload.len();
 
@@ -84,10 +259,15 @@ impl TurbineReceiver {
             self.evict_oldest_pending();
         }
 
-        let entry = self
+        let entry = &mut self
             .pending
             .entry(shred.block_id)
-            .or_insert_with(|| vec![None; total_shards]);
+            .or_insert_with(|| PendingBlock {
+                coding,
+                shards: vec![None; total_shards],
+                stream: BodyStream::default(),
+            })
+            .shards;
 
         if is_new_block {
             self.pending_order.push_back(shred.block_id);
@@ -96,16 +276,17 @@ impl TurbineReceiver {
         if let Some(old) = entry[shred_idx].take() {
             self.pending_bytes = self.pending_bytes.saturating_sub(old.len());
         }
-        entry[shred_idx] = Some(shred.payload.clone());
+        entry[shred_idx] = Some(shred.payload);
         self.pending_bytes = self.pending_bytes.saturating_add(payload_len);
 
-        if entry.iter().filter(|chunk| chunk.is_some()).count() < data_shards {
-            return Ok(None);
-        }
+        Ok(entry.iter().filter(|chunk| chunk.is_some()).count() >= data_shards)
+    }
 
-        let recovered = self.decoder.decode(entry)?;
-        self.remove_pending(&shred.block_id);
-        Ok(Some(recovered))
+    fn decode(&mut self, block_id: &H256) -> Result<Vec<u8>> {
+        let block = &self.pending[block_id];
+        let recovered = self.decoders[&block.coding].decode(&block.shards)?;
+        self.remove_pending(block_id);
+        Ok(recovered)
     }
 }
 
@@ -166,6 +347,110 @@ mod tests {
         assert_eq!(recovered, b"hello ");
     }
 
+    #[test]
+    fn decodes_each_block_with_its_header_coding() {
+        let default = aether_da_erasure::ReedSolomonEncoder::new(2, 1).unwrap();
+        let wider = aether_da_erasure::ReedSolomonEncoder::new(2, 3).unwrap();
+        let old = default.encode(b"epoch 1").unwrap();
+        let new = wider.encode(b"epoch 2").unwrap();
+        let old_id = H256::from_slice(&[1; 32]).unwrap();
+        let new_id = H256::from_slice(&[2; 32]).unwrap();
+        let coding = ErasureParams::new(2, 3);
+
+        let mut receiver = TurbineReceiver::new(2, 1).unwrap();
+        // Index 4 only exists under the wider coding.
+        assert!(receiver
+            .ingest_shred(make_shred(new_id, 4, &new[4]))
+            .is_err());
+        assert!(receiver
+            .ingest_shred_with(make_shred(new_id, 4, &new[4]), coding)
+            .unwrap()
+            .is_none());
+        assert!(receiver
+            .ingest_shred(make_shred(old_id, 2, &old[2]))
+            .unwrap()
+            .is_none());
+        // A block keeps the coding its first shred came with.
+        assert!(receiver
+            .ingest_shred(make_shred(new_id, 0, &new[0]))
+            .is_err());
+
+        let recovered = receiver
+            .ingest_shred_with(make_shred(new_id, 3, &new[3]), coding)
+            .unwrap()
+            .unwrap();
+        assert_eq!(recovered, b"epoch 2");
+        let recovered = receiver
+            .ingest_shred(make_shred(old_id, 0, &old[0]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(recovered, b"epoch 1");
+    }
+
+    #[test]
+    fn streams_data_shreds_in_order() {
+        let encoder = aether_da_erasure::ReedSolomonEncoder::new(4, 2).unwrap();
+        let block: Vec<u8> = (0..100).collect();
+        let shards = encoder.encode(&block).unwrap();
+        // 108 prefixed bytes in 27-byte shreds: the body starts 8 bytes in.
+        assert_eq!(shards[0].len(), 27);
+        let coding = ErasureParams::new(4, 2);
+        let block_id = H256::zero();
+        let mut receiver = TurbineReceiver::new(4, 2).unwrap();
+        let mut ingest = |index: usize| {
+            receiver
+                .ingest_shred_streaming(make_shred(block_id, index as u32, &shards[index]), coding)
+                .unwrap()
+        };
+
+        // Shred 1 has to wait for shred 0; then both go out together.
+        assert_eq!(ingest(1), None);
+        let first = ingest(0).unwrap();
+        assert_eq!((first.offset, first.last), (0, false));
+        assert_eq!(first.data, block[..46]);
+        // Parity adds nothing in order, and shred 2 is never seen.
+        assert_eq!(ingest(5), None);
+        let last = ingest(3).unwrap();
+        assert_eq!((last.offset, last.last), (46, true));
+        assert_eq!(last.data, block[46..]);
+    }
+
+    #[test]
+    fn streaming_a_block_shred_by_shred_reassembles_it() {
+        let encoder = aether_da_erasure::ReedSolomonEncoder::new(10, 2).unwrap();
+        // Shreds too narrow to hold the whole length prefix.
+        for len in [0usize, 3, 50, 1000] {
+            let block: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let shards = encoder.encode(&block).unwrap();
+            let mut receiver = TurbineReceiver::new(10, 2).unwrap();
+            let mut streamed = Vec::new();
+            for (i, shard) in shards.iter().enumerate().take(10) {
+                let shred = make_shred(H256::zero(), i as u32, shard);
+                if let Some(chunk) = receiver
+                    .ingest_shred_streaming(shred, ErasureParams::default())
+                    .unwrap()
+                {
+                    assert_eq!(chunk.offset, streamed.len());
+                    streamed.extend_from_slice(&chunk.data);
+                    assert_eq!(chunk.last, i == 9);
+                }
+            }
+            assert_eq!(streamed, block, "block of {len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_shreds_of_mismatched_width() {
+        let mut receiver = TurbineReceiver::new(2, 1).unwrap();
+        let block_id = H256::zero();
+        receiver
+            .ingest_shred(make_shred(block_id, 0, &[1; 8]))
+            .unwrap();
+        assert!(receiver
+            .ingest_shred(make_shred(block_id, 1, &[1; 9]))
+            .is_err());
+    }
+
     #[test]
     fn rejects_shred_when_pending_bytes_exceeded() {
         let mut receiver = TurbineReceiver::new(2, 1).unwrap();
@@ -1,9 +1,17 @@
 use std::collections::HashMap;
 
+use aether_types::{EpochInfo, PublicKey};
+use sha2::{Digest, Sha256};
+
+/// Children per node in a stake-weighted tree.
+pub const DEFAULT_FANOUT: usize = 200;
+
 #[derive(Clone, Debug, Default)]
 pub struct TurbineTopology {
     layers: Vec<Vec<String>>,
     adjacency: HashMap<String, Vec<String>>,
+    /// Layer each node sits in.
+    depth: HashMap<String, usize>,
 }
 
 impl TurbineTopology {
@@ -11,11 +19,46 @@ impl TurbineTopology {
         let mut topology = TurbineTopology {
             layers,
             adjacency: HashMap::new(),
+            depth: HashMap::new(),
         };
         topology.rebuild_adjacency();
         topology
     }
 
+    /// A tree rooted at `root` over `nodes` and their stakes, shuffled by
+    /// `seed` so that the more stake a node has, the likelier it is to sit
+    /// near the root. Below the root each layer is `fanout` times wider
+    /// than the one above, and every node has at most `fanout` children.
+    ///
+    /// The same inputs always give the same tree, on every machine. `root`
+    /// is left out of the shuffle if it is in `nodes`; nodes without stake
+    /// go at the bottom.
+    pub fn stake_weighted(
+        root: &str,
+        nodes: &[(String, u128)],
+        fanout: usize,
+        seed: &[u8],
+    ) -> Self {
+        let fanout = fanout.max(1);
+        let candidates: Vec<(String, u128)> = nodes
+            .iter()
+            .filter(|(node, _)| node != root)
+            .cloned()
+            .collect();
+        let order = weighted_shuffle(&candidates, seed);
+
+        let mut layers = vec![vec![root.to_string()]];
+        let mut width = fanout;
+        let mut rest = order.as_slice();
+        while !rest.is_empty() {
+            let (layer, tail) = rest.split_at(width.min(rest.len()));
+            layers.push(layer.to_vec());
+            rest = tail;
+            width = width.saturating_mul(fanout);
+        }
+        TurbineTopology::new(layers)
+    }
+
     pub fn layers(&self) -> &[Vec<String>] {
         &self.layers
     }
@@ -24,10 +67,33 @@ impl TurbineTopology {
         self.layers.get(depth).map(|layer| layer.as_slice())
     }
 
+    /// Nodes at `depth`; layer 0 is the root.
+    pub fn get_layer(&self, depth: usize) -> &[String] {
+        self.layer(depth).unwrap_or_default()
+    }
+
     pub fn children(&self, node: &str) -> Vec<String> {
         self.adjacency.get(node).cloned().unwrap_or_else(Vec::new)
     }
 
+    /// Nodes `node` forwards to; empty for leaves and unknown nodes.
+    pub fn get_children(&self, node: &str) -> &[String] {
+        self.adjacency.get(node).map_or(&[], Vec::as_slice)
+    }
+
+    /// The layer `node` is in, if it is in the tree.
+    pub fn depth_of(&self, node: &str) -> Option<usize> {
+        self.depth.get(node).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.depth.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.depth.is_empty()
+    }
+
     pub fn add_layer(&mut self, layer: Vec<String>) {
         self.layers.push(layer);
         self.rebuild_adjacency();
@@ -36,6 +102,12 @@ impl TurbineTopology {
     #[allow(clippy::manual_div_ceil)]
     fn rebuild_adjacency(&mut self) {
         self.adjacency.clear();
+        self.depth = self
+            .layers
+            .iter()
+            .enumerate()
+            .flat_map(|(depth, layer)| layer.iter().map(move |node| (node.clone(), depth)))
+            .collect();
         for idx in 0..self.layers.len().saturating_sub(1) {
             let parents = &self.layers[idx];
             let children = &self.layers[idx + 1];
@@ -58,9 +130,167 @@ impl TurbineTopology {
     }
 }
 
+/// The topology of one epoch: its staked validators, and the seed their
+/// trees are shuffled with, taken from the epoch's randomness. Each slot's
+/// leader roots its own tree.
+#[derive(Clone, Debug)]
+pub struct EpochTopology {
+    epoch: u64,
+    fanout: usize,
+    seed: [u8; 32],
+    stakes: Vec<(String, u128)>,
+}
+
+impl EpochTopology {
+    pub fn new(info: &EpochInfo, fanout: usize) -> Self {
+        let stakes = info
+            .validators
+            .iter()
+            .filter(|v| v.active && v.stake > 0)
+            .map(|v| (node_id(&v.pubkey), v.stake))
+            .collect();
+        EpochTopology {
+            epoch: info.epoch,
+            fanout,
+            seed: *info.randomness.as_bytes(),
+            stakes,
+        }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn validators(&self) -> usize {
+        self.stakes.len()
+    }
+
+    /// Take the validator set and seed of `info` if it is a later epoch.
+    /// Returns whether anything changed.
+    pub fn on_epoch(&mut self, info: &EpochInfo) -> bool {
+        if info.epoch <= self.epoch {
+            return false;
+        }
+        *self = EpochTopology::new(info, self.fanout);
+        true
+    }
+
+    /// The tree `leader`'s shreds for `slot` go down. Each slot is shuffled
+    /// afresh so no validator is stuck deep in the tree for a whole epoch.
+    pub fn tree(&self, leader: &PublicKey, slot: u64) -> TurbineTopology {
+        let mut seed = self.seed.to_vec();
+        seed.extend_from_slice(&slot.to_le_bytes());
+        TurbineTopology::stake_weighted(&node_id(leader), &self.stakes, self.fanout, &seed)
+    }
+}
+
+/// How a validator is named in a tree: its public key in hex.
+pub fn node_id(pubkey: &PublicKey) -> String {
+    pubkey
+        .as_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Order `nodes` by repeatedly drawing one with probability proportional
+/// to its stake, using a counter-mode SHA-256 stream keyed by `seed` and a
+/// Fenwick tree over the stakes remaining. Zero-stake nodes follow in
+/// input order.
+fn weighted_shuffle(nodes: &[(String, u128)], seed: &[u8]) -> Vec<String> {
+    let mut tree = Fenwick::new(nodes.iter().map(|(_, stake)| *stake));
+    let mut order = Vec::with_capacity(nodes.len());
+    let mut counter = 0u64;
+    while tree.total() > 0 {
+        let mut hasher = Sha256::new();
+        hasher.update(seed);
+        hasher.update(counter.to_le_bytes());
+        counter += 1;
+        let digest = hasher.finalize();
+        let mut draw = [0u8; 16];
+        draw.copy_from_slice(&digest[..16]);
+        let target = u128::from_le_bytes(draw) % tree.total();
+        let index = tree.find(target);
+        order.push(nodes[index].0.clone());
+        tree.take(index);
+    }
+    order.extend(
+        nodes
+            .iter()
+            .filter(|(_, stake)| *stake == 0)
+            .map(|(node, _)| node.clone()),
+    );
+    order
+}
+
+/// Prefix sums of stakes, for drawing by stake in O(log n).
+struct Fenwick {
+    sums: Vec<u128>,
+    stakes: Vec<u128>,
+    total: u128,
+}
+
+impl Fenwick {
+    fn new(stakes: impl Iterator<Item = u128>) -> Self {
+        let stakes: Vec<u128> = stakes.collect();
+        let mut sums = vec![0u128; stakes.len() + 1];
+        for (i, stake) in stakes.iter().enumerate() {
+            let mut pos = i + 1;
+            while pos < sums.len() {
+                sums[pos] = sums[pos].saturating_add(*stake);
+                pos += pos & pos.wrapping_neg();
+            }
+        }
+        let total = stakes.iter().fold(0u128, |a, s| a.saturating_add(*s));
+        Fenwick {
+            sums,
+            stakes,
+            total,
+        }
+    }
+
+    fn total(&self) -> u128 {
+        self.total
+    }
+
+    /// The index whose stake covers `target`, with `target < total`.
+    fn find(&self, mut target: u128) -> usize {
+        let mut pos = 0;
+        let mut step = (self.sums.len() - 1).next_power_of_two();
+        while step > 0 {
+            let next = pos + step;
+            if next < self.sums.len() && self.sums[next] <= target {
+                pos = next;
+                target -= self.sums[next];
+            }
+            step /= 2;
+        }
+        pos
+    }
+
+    /// Remove index `i`'s stake.
+    fn take(&mut self, i: usize) {
+        let stake = std::mem::take(&mut self.stakes[i]);
+        self.total -= stake;
+        let mut pos = i + 1;
+        while pos < self.sums.len() {
+            self.sums[pos] -= stake;
+            pos += pos & pos.wrapping_neg();
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aether_types::{ValidatorInfo, H256};
+    use std::collections::HashSet;
+
+    fn stakes(n: usize) -> Vec<(String, u128)> {
+        (0..n)
+            .map(|i| (format!("v{i}"), 1_000 + i as u128))
+            .collect()
+    }
 
     #[test]
     fn builds_adjacency() {
@@ -73,5 +303,113 @@ mod tests {
         let root_children = topology.children("leader");
         assert_eq!(root_children.len(), 2);
         assert!(root_children.contains(&"a".to_string()));
+        assert_eq!(topology.get_children("a"), ["c", "d"]);
+        assert!(topology.get_children("c").is_empty());
+        assert_eq!(topology.get_layer(1), ["a", "b"]);
+        assert!(topology.get_layer(3).is_empty());
+        assert_eq!(topology.depth_of("e"), Some(2));
+    }
+
+    #[test]
+    fn stake_weighted_tree_is_balanced() {
+        let nodes = stakes(1_000);
+        let tree = TurbineTopology::stake_weighted("v0", &nodes, 32, b"seed");
+
+        // v0 roots the tree, then 32, then the remaining 967.
+        assert_eq!(tree.get_layer(0), ["v0"]);
+        assert_eq!(tree.get_layer(1).len(), 32);
+        assert_eq!(tree.get_layer(2).len(), 967);
+        assert_eq!(tree.layers().len(), 3);
+        assert_eq!(tree.len(), 1_000);
+
+        let mut seen = HashSet::new();
+        for layer in tree.layers() {
+            for node in layer {
+                assert!(seen.insert(node.clone()), "{node} placed twice");
+                assert!(tree.get_children(node).len() <= 32);
+            }
+        }
+        // Children are spread evenly: 967 over 32 parents is 31 each,
+        // with the remainder on the last.
+        let layer1 = tree.get_layer(1);
+        assert!(layer1[..31]
+            .iter()
+            .all(|node| tree.get_children(node).len() == 31));
+        assert_eq!(tree.get_children(&layer1[31]).len(), 6);
+    }
+
+    #[test]
+    fn same_seed_same_tree() {
+        let nodes = stakes(300);
+        let a = TurbineTopology::stake_weighted("v7", &nodes, 8, b"epoch-1");
+        let b = TurbineTopology::stake_weighted("v7", &nodes, 8, b"epoch-1");
+        let c = TurbineTopology::stake_weighted("v7", &nodes, 8, b"epoch-2");
+        assert_eq!(a.layers(), b.layers());
+        assert_ne!(a.layers(), c.layers());
+        assert_eq!(a.depth_of("v7"), Some(0));
+    }
+
+    #[test]
+    fn stake_pulls_nodes_toward_the_root() {
+        // One validator with as much stake as the 199 others together.
+        let mut nodes: Vec<(String, u128)> = (1..200).map(|i| (format!("v{i}"), 1)).collect();
+        nodes.push(("whale".into(), 199));
+        nodes.push(("idle".into(), 0));
+        let mut near_root = 0;
+        for trial in 0..100u32 {
+            let tree = TurbineTopology::stake_weighted("leader", &nodes, 8, &trial.to_le_bytes());
+            if tree.depth_of("whale") == Some(1) {
+                near_root += 1;
+            }
+            assert_eq!(tree.layers().last().unwrap().last().unwrap(), "idle");
+        }
+        assert!(near_root > 90, "whale in layer 1 only {near_root} times");
+    }
+
+    fn epoch(number: u64, keys: &[u8]) -> EpochInfo {
+        let validators: Vec<ValidatorInfo> = keys
+            .iter()
+            .map(|k| ValidatorInfo {
+                pubkey: PublicKey::from_bytes(vec![*k; 32]),
+                stake: u128::from(*k) * 100,
+                commission: 0,
+                active: true,
+            })
+            .collect();
+        EpochInfo {
+            epoch: number,
+            start_slot: number * 100,
+            end_slot: number * 100 + 99,
+            randomness: H256::from([number as u8; 32]),
+            total_stake: validators.iter().map(|v| v.stake).sum(),
+            validators,
+        }
+    }
+
+    #[test]
+    fn epoch_topology_rebuilds_on_new_epochs_only() {
+        let keys: Vec<u8> = (1..=50).collect();
+        let mut topology = EpochTopology::new(&epoch(1, &keys), 4);
+        let leader = PublicKey::from_bytes(vec![3; 32]);
+        let tree = topology.tree(&leader, 100);
+        assert_eq!(tree.get_layer(0), [node_id(&leader)]);
+        assert_eq!(tree.len(), 50);
+        assert_eq!(tree.get_layer(1).len(), 4);
+        assert_eq!(topology.tree(&leader, 100).layers(), tree.layers());
+        assert_ne!(topology.tree(&leader, 101).layers(), tree.layers());
+
+        // A stale or repeated epoch changes nothing.
+        assert!(!topology.on_epoch(&epoch(1, &keys[..10])));
+        assert_eq!(topology.validators(), 50);
+
+        let fewer: Vec<u8> = (1..=20).collect();
+        assert!(topology.on_epoch(&epoch(2, &fewer)));
+        assert_eq!(topology.epoch(), 2);
+        let tree = topology.tree(&leader, 200);
+        assert_eq!(tree.len(), 20);
+        assert_eq!(
+            tree.depth_of(&node_id(&PublicKey::from_bytes(vec![40; 32]))),
+            None
+        );
     }
 }
@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
 /// EIP-1559 style dynamic base fee with burn mechanism.
 ///
 /// The base fee adjusts each block based on gas utilization:
@@ -62,6 +65,13 @@ impl FeeMarket {
         self.base_fee.saturating_mul(gas_limit as u128)
     }
 
+    /// Priority fee per gas a transaction offers above the current base fee,
+    /// or `None` if its fee does not cover the base fee for its gas limit.
+    pub fn priority_fee_per_gas(&self, fee: u128, gas_limit: u64) -> Option<u128> {
+        let fee_per_gas = fee / u128::from(gas_limit.max(1));
+        fee_per_gas.checked_sub(self.base_fee)
+    }
+
     /// Process a block and update the base fee.
     ///
     /// `block_gas_used`: total gas consumed by all txs in the block.
@@ -127,6 +137,112 @@ impl FeeMarket {
     }
 }
 
+/// Percentile of recent priority fees suggested to new transactions.
+pub const SUGGESTED_PRIORITY_PERCENTILE: u8 = 60;
+
+/// Fee data of one processed block, as seen by the [`GasPriceOracle`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockFeeSample {
+    pub slot: u64,
+    /// Base fee per gas the block paid.
+    pub base_fee: u128,
+    pub gas_used: u64,
+    pub max_gas: u64,
+    /// Priority fee per gas of each included transaction, ascending.
+    pub priority_fees: Vec<u128>,
+}
+
+/// Fee history over a range of recent blocks, oldest first.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    pub oldest_slot: u64,
+    /// Base fee of each block, followed by the next block's base fee.
+    pub base_fee_per_gas: Vec<u128>,
+    /// Gas used over the block gas limit.
+    pub gas_used_ratio: Vec<f64>,
+    /// Priority fee per gas at each requested percentile, per block.
+    pub reward: Vec<Vec<u128>>,
+}
+
+/// Remembers the fees paid in recent blocks to estimate the priority fee a
+/// transaction needs for prompt inclusion.
+#[derive(Debug, Clone)]
+pub struct GasPriceOracle {
+    blocks: VecDeque<BlockFeeSample>,
+    max_blocks: usize,
+}
+
+impl GasPriceOracle {
+    pub fn new(max_blocks: usize) -> Self {
+        GasPriceOracle {
+            blocks: VecDeque::new(),
+            max_blocks: max_blocks.max(1),
+        }
+    }
+
+    /// Record a block, forgetting the oldest once `max_blocks` are held.
+    pub fn record_block(&mut self, mut sample: BlockFeeSample) {
+        sample.priority_fees.sort_unstable();
+        if self.blocks.len() == self.max_blocks {
+            self.blocks.pop_front();
+        }
+        self.blocks.push_back(sample);
+    }
+
+    /// The [`SUGGESTED_PRIORITY_PERCENTILE`] of priority fees paid across
+    /// the remembered blocks; zero if none paid any.
+    pub fn suggest_priority_fee(&self) -> u128 {
+        let mut fees: Vec<u128> = self
+            .blocks
+            .iter()
+            .flat_map(|b| b.priority_fees.iter().copied())
+            .collect();
+        fees.sort_unstable();
+        percentile(&fees, SUGGESTED_PRIORITY_PERCENTILE)
+    }
+
+    /// Fees of the last `block_count` remembered blocks. Percentiles above
+    /// 100 are clamped; blocks without transactions report zero rewards.
+    pub fn fee_history(
+        &self,
+        block_count: usize,
+        percentiles: &[u8],
+        next_base_fee: u128,
+    ) -> FeeHistory {
+        let skip = self.blocks.len().saturating_sub(block_count);
+        let blocks: Vec<&BlockFeeSample> = self.blocks.iter().skip(skip).collect();
+        let mut base_fee_per_gas: Vec<u128> = blocks.iter().map(|b| b.base_fee).collect();
+        base_fee_per_gas.push(next_base_fee);
+        FeeHistory {
+            oldest_slot: blocks.first().map_or(0, |b| b.slot),
+            base_fee_per_gas,
+            gas_used_ratio: blocks
+                .iter()
+                .map(|b| b.gas_used as f64 / b.max_gas.max(1) as f64)
+                .collect(),
+            reward: blocks
+                .iter()
+                .map(|b| {
+                    percentiles
+                        .iter()
+                        .map(|p| percentile(&b.priority_fees, (*p).min(100)))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice; zero when empty.
+fn percentile(sorted: &[u128], pct: u8) -> u128 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let index = (sorted.len() - 1) * usize::from(pct) / 100;
+    sorted[index]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,4 +361,51 @@ mod tests {
         let result = fm.min_fee_for_gas(2);
         assert_eq!(result, u128::MAX, "overflow should saturate to u128::MAX");
     }
+
+    #[test]
+    fn test_priority_fee_per_gas() {
+        let fm = FeeMarket::new(10, 1_000_000, 1);
+        assert_eq!(fm.priority_fee_per_gas(21_000 * 13, 21_000), Some(3));
+        assert_eq!(fm.priority_fee_per_gas(21_000 * 10, 21_000), Some(0));
+        assert_eq!(fm.priority_fee_per_gas(21_000 * 9, 21_000), None);
+    }
+
+    fn sample(slot: u64, base_fee: u128, priority_fees: Vec<u128>) -> BlockFeeSample {
+        BlockFeeSample {
+            slot,
+            base_fee,
+            gas_used: 250_000,
+            max_gas: 1_000_000,
+            priority_fees,
+        }
+    }
+
+    #[test]
+    fn test_oracle_suggests_recent_percentile() {
+        let mut oracle = GasPriceOracle::new(2);
+        assert_eq!(oracle.suggest_priority_fee(), 0);
+
+        oracle.record_block(sample(1, 100, vec![1_000, 1_000]));
+        oracle.record_block(sample(2, 100, vec![5, 1, 3]));
+        oracle.record_block(sample(3, 100, vec![4, 2]));
+        // Slot 1 has been forgotten: fees are 1..=5.
+        assert_eq!(oracle.suggest_priority_fee(), 3);
+    }
+
+    #[test]
+    fn test_fee_history() {
+        let mut oracle = GasPriceOracle::new(8);
+        oracle.record_block(sample(4, 100, vec![]));
+        oracle.record_block(sample(5, 110, vec![30, 10, 20]));
+
+        let history = oracle.fee_history(10, &[0, 50, 250], 120);
+        assert_eq!(history.oldest_slot, 4);
+        assert_eq!(history.base_fee_per_gas, vec![100, 110, 120]);
+        assert_eq!(history.gas_used_ratio, vec![0.25, 0.25]);
+        assert_eq!(history.reward, vec![vec![0, 0, 0], vec![10, 20, 30]]);
+
+        let latest = oracle.fee_history(1, &[], 120);
+        assert_eq!(latest.oldest_slot, 5);
+        assert_eq!(latest.base_fee_per_gas, vec![110, 120]);
+    }
 }
@@ -12,5 +12,5 @@ pub mod state;
 mod proptest_tests;
 
 pub use emission::EmissionSchedule;
-pub use fee_market::FeeMarket;
+pub use fee_market::{BlockFeeSample, FeeHistory, FeeMarket, GasPriceOracle};
 pub use state::Ledger;
@@ -595,9 +595,7 @@ impl Ledger {
             }
         }
 
-        let batch_inputs: Vec<_> = transactions.iter().map(|tx| tx.ed25519_tuple()).collect();
-        let batch_results = ed25519::verify_batch(&batch_inputs)
-            .map_err(|e| anyhow!("batch signature verification failed: {e:?}"))?;
+        let batch_results = verify_signatures(transactions)?;
 
         // Clone the merkle tree for speculative root computation
         let mut spec_tree = self.merkle_tree.clone();
@@ -995,9 +993,7 @@ impl Ledger {
             }
         }
 
-        let batch_inputs: Vec<_> = transactions.iter().map(|tx| tx.ed25519_tuple()).collect();
-        let batch_results = ed25519::verify_batch(&batch_inputs)
-            .map_err(|e| anyhow!("batch signature verification failed: {e:?}"))?;
+        let batch_results = verify_signatures(transactions)?;
 
         for (tx, is_valid) in transactions.iter().zip(batch_results.iter()) {
             if !*is_valid {
@@ -1032,6 +1028,28 @@ impl Ledger {
     }
 }
 
+/// Batch-verify the signatures of `transactions`, one result each.
+///
+/// Multisig senders have no single key to batch; the batch marks them
+/// invalid and they are checked against their policy afterwards.
+fn verify_signatures(transactions: &[Transaction]) -> Result<Vec<bool>> {
+    let batch_inputs: Vec<_> = transactions.iter().map(|tx| tx.ed25519_tuple()).collect();
+    let messages: Vec<&[u8]> = batch_inputs.iter().map(|(_, m, _)| m.as_slice()).collect();
+    let signatures: Vec<&[u8]> = batch_inputs.iter().map(|(_, _, s)| s.as_slice()).collect();
+    let public_keys: Vec<&[u8]> = batch_inputs
+        .iter()
+        .map(|(pk, _, _)| pk.as_slice())
+        .collect();
+    let mut results = ed25519::verify_batch(&messages, &signatures, &public_keys)
+        .map_err(|e| anyhow!("batch signature verification failed: {e:?}"))?;
+    for (tx, valid) in transactions.iter().zip(results.iter_mut()) {
+        if tx.is_multisig() {
+            *valid = tx.verify_signature().is_ok();
+        }
+    }
+    Ok(results)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
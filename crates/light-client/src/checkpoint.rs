@@ -0,0 +1,229 @@
+use aether_types::checkpoint::verify_validator_leaf;
+use aether_types::{Epoch, FinalityCertificate, Slot, H256};
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+
+use crate::verifier::has_quorum;
+
+/// Verify `cert` against the validator-set commitment the caller trusts for
+/// its epoch. Returns the stake that signed.
+///
+/// Checks that:
+/// 1. The certificate's set size, total stake and Merkle root hash to
+///    `trusted_root`
+/// 2. Every signer's key and stake prove against that Merkle root
+/// 3. The signers hold ≥2/3 of the epoch's stake
+/// 4. The aggregated BLS signature is valid over `block_hash || slot`
+pub fn verify_finality_certificate(cert: &FinalityCertificate, trusted_root: H256) -> Result<u128> {
+    if cert.commitment() != trusted_root {
+        bail!(
+            "certificate for slot {} is not signed under the trusted validator set of epoch {}",
+            cert.slot,
+            cert.epoch
+        );
+    }
+
+    cert.signers.validate()?;
+    if cert.signer_proofs.len() != cert.signers.count() {
+        bail!(
+            "{} signer proofs for {} signers",
+            cert.signer_proofs.len(),
+            cert.signers.count()
+        );
+    }
+    if cert.signer_proofs.is_empty() {
+        bail!("certificate for slot {} has no signers", cert.slot);
+    }
+
+    let mut signed_stake: u128 = 0;
+    let mut signer_keys = Vec::with_capacity(cert.signer_proofs.len());
+    for (index, proof) in cert.signers.indices().zip(&cert.signer_proofs) {
+        if !verify_validator_leaf(&cert.validator_set_root, index, &proof.leaf, &proof.path) {
+            bail!("signer {index} does not prove against the validator set");
+        }
+        signed_stake = signed_stake.saturating_add(proof.leaf.stake);
+        signer_keys.push(proof.leaf.bls_pubkey.clone());
+    }
+
+    if !has_quorum(signed_stake, cert.total_stake) {
+        bail!(
+            "signed stake {} < 2/3 of total {}",
+            signed_stake,
+            cert.total_stake
+        );
+    }
+
+    if cert.aggregated_signature.is_empty() {
+        bail!(
+            "certificate for slot {} has empty aggregate signature",
+            cert.slot
+        );
+    }
+    let agg_pk = aether_crypto_bls::aggregate_public_keys(&signer_keys)
+        .map_err(|e| anyhow::anyhow!("failed to aggregate signer public keys: {e}"))?;
+    let valid = aether_crypto_bls::verify_aggregated(
+        &agg_pk,
+        &cert.signing_message(),
+        &cert.aggregated_signature,
+    )
+    .map_err(|e| anyhow::anyhow!("BLS verification error: {e}"))?;
+    if !valid {
+        bail!(
+            "invalid BLS aggregate signature on certificate for slot {}",
+            cert.slot
+        );
+    }
+
+    Ok(signed_stake)
+}
+
+/// Follows finality through checkpoint certificates, holding only one
+/// validator-set commitment per epoch.
+pub struct CheckpointVerifier {
+    /// Trusted validator-set commitments by epoch.
+    trusted_roots: BTreeMap<Epoch, H256>,
+    /// Highest verified checkpoint.
+    finalized_slot: Slot,
+    finalized_block: H256,
+}
+
+impl CheckpointVerifier {
+    /// Start from the commitment of `epoch`'s validator set.
+    pub fn new(epoch: Epoch, validator_set_root: H256) -> Self {
+        CheckpointVerifier {
+            trusted_roots: BTreeMap::from([(epoch, validator_set_root)]),
+            finalized_slot: 0,
+            finalized_block: H256::zero(),
+        }
+    }
+
+    /// Trust `validator_set_root` for `epoch`, e.g. after reading it from a
+    /// verified state proof or a bridge contract.
+    pub fn trust_validator_set(&mut self, epoch: Epoch, validator_set_root: H256) {
+        self.trusted_roots.insert(epoch, validator_set_root);
+    }
+
+    pub fn trusted_root(&self, epoch: Epoch) -> Option<H256> {
+        self.trusted_roots.get(&epoch).copied()
+    }
+
+    /// Verify and accept a checkpoint. Its slot must advance past the last
+    /// accepted one and its epoch's validator set must be trusted.
+    pub fn verify(&mut self, cert: &FinalityCertificate) -> Result<()> {
+        if cert.slot <= self.finalized_slot {
+            bail!(
+                "slot {} does not advance beyond finalized slot {}",
+                cert.slot,
+                self.finalized_slot
+            );
+        }
+        let Some(root) = self.trusted_root(cert.epoch) else {
+            bail!("no trusted validator set for epoch {}", cert.epoch);
+        };
+        verify_finality_certificate(cert, root)?;
+        self.finalized_slot = cert.slot;
+        self.finalized_block = cert.block_hash;
+        Ok(())
+    }
+
+    pub fn finalized_slot(&self) -> Slot {
+        self.finalized_slot
+    }
+
+    pub fn finalized_block(&self) -> H256 {
+        self.finalized_block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_bls::BlsKeypair;
+    use aether_types::{SignerBitfield, ValidatorSetLeaf, ValidatorSetTree};
+
+    fn validators(stakes: &[u128]) -> (Vec<BlsKeypair>, ValidatorSetTree) {
+        let keys: Vec<BlsKeypair> = stakes.iter().map(|_| BlsKeypair::generate()).collect();
+        let leaves = keys
+            .iter()
+            .zip(stakes)
+            .map(|(key, &stake)| ValidatorSetLeaf {
+                bls_pubkey: key.public_key(),
+                stake,
+            })
+            .collect();
+        (keys, ValidatorSetTree::new(2, leaves))
+    }
+
+    fn certificate(
+        keys: &[BlsKeypair],
+        tree: &ValidatorSetTree,
+        slot: Slot,
+        signers: &[usize],
+    ) -> FinalityCertificate {
+        let block_hash = H256::from([slot as u8; 32]);
+        let mut msg = block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&slot.to_le_bytes());
+        let mut bitfield = SignerBitfield::new(tree.len());
+        let signatures: Vec<Vec<u8>> = signers
+            .iter()
+            .map(|&i| {
+                bitfield.set(i).unwrap();
+                keys[i].sign(&msg)
+            })
+            .collect();
+        let signature = aether_crypto_bls::aggregate_signatures(&signatures).unwrap();
+        FinalityCertificate::new(tree, slot, block_hash, signature, bitfield).unwrap()
+    }
+
+    #[test]
+    fn test_quorum_certificate_verifies_against_set_root() {
+        let (keys, tree) = validators(&[100, 100, 100, 100]);
+        let mut verifier = CheckpointVerifier::new(2, tree.commitment());
+
+        let cert = certificate(&keys, &tree, 64, &[0, 1, 3]);
+        assert_eq!(
+            verify_finality_certificate(&cert, tree.commitment()).unwrap(),
+            300
+        );
+        verifier.verify(&cert).unwrap();
+        assert_eq!(verifier.finalized_slot(), 64);
+        assert_eq!(verifier.finalized_block(), cert.block_hash);
+
+        // Same certificate again does not advance.
+        assert!(verifier.verify(&cert).is_err());
+        // Unknown epoch.
+        let (other_keys, other_tree) = validators(&[1, 1, 1]);
+        let mut other = certificate(&other_keys, &other_tree, 96, &[0, 1, 2]);
+        other.epoch = 3;
+        assert!(verifier.verify(&other).is_err());
+    }
+
+    #[test]
+    fn test_reject_minority_and_tampered_certificates() {
+        let (keys, tree) = validators(&[100, 100, 100, 100]);
+        let root = tree.commitment();
+
+        let minority = certificate(&keys, &tree, 64, &[0, 1]);
+        assert!(verify_finality_certificate(&minority, root).is_err());
+
+        // Inflating a signer's stake breaks its Merkle proof.
+        let mut inflated = minority.clone();
+        inflated.signer_proofs[0].leaf.stake = 1_000;
+        assert!(verify_finality_certificate(&inflated, root).is_err());
+
+        // Shrinking the claimed total breaks the commitment.
+        let mut shrunk = minority;
+        shrunk.total_stake = 200;
+        assert!(verify_finality_certificate(&shrunk, root).is_err());
+
+        // A valid quorum for a different block.
+        let mut moved = certificate(&keys, &tree, 64, &[0, 1, 2]);
+        moved.block_hash = H256::from([9u8; 32]);
+        assert!(verify_finality_certificate(&moved, root).is_err());
+
+        // A set the client does not trust.
+        let (other_keys, other_tree) = validators(&[100, 100, 100, 100]);
+        let foreign = certificate(&other_keys, &other_tree, 64, &[0, 1, 2]);
+        assert!(verify_finality_certificate(&foreign, root).is_err());
+    }
+}
@@ -70,6 +70,7 @@ mod tests {
                 proof: vec![0u8; 80],
             },
             timestamp: 1000 + slot,
+            erasure: ErasureParams::default(),
         }
     }
 
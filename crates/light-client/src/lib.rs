@@ -6,16 +6,20 @@
 //! 1. Download block headers only (not full transactions)
 //! 2. Verify finality by checking BLS aggregate signatures on headers
 //! 3. Query account/UTXO state via Merkle proofs against the state root
+//! 4. Or follow checkpoint finality certificates, trusting only a
+//!    validator-set root per epoch
 //!
 //! # Security model
 //! - Trusts the validator set (configured at initialization)
 //! - Verifies 2/3 stake signed off on each finalized header
 //! - Merkle proofs are self-verifying against the state root in the header
 
+pub mod checkpoint;
 pub mod header_store;
 pub mod state_query;
 pub mod verifier;
 
+pub use checkpoint::{verify_finality_certificate, CheckpointVerifier};
 pub use header_store::HeaderStore;
 pub use state_query::{StateProof, StateQuery};
 pub use verifier::LightClientVerifier;
@@ -21,7 +21,7 @@ pub struct FinalizedHeader {
 
 /// Check if `voted_stake` represents a 2/3 quorum of `total_stake`.
 /// Uses checked arithmetic to avoid overflow.
-fn has_quorum(voted_stake: u128, total_stake: u128) -> bool {
+pub(crate) fn has_quorum(voted_stake: u128, total_stake: u128) -> bool {
     if total_stake == 0 {
         return false;
     }
@@ -235,6 +235,7 @@ mod tests {
                 proof: vec![0u8; 80],
             },
             timestamp: 1000 + slot,
+            erasure: ErasureParams::default(),
         };
 
         let msg = header_message(&header);
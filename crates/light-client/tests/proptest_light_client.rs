@@ -42,6 +42,7 @@ fn make_header(slot: u64) -> BlockHeader {
             proof: vec![0u8; 80],
         },
         timestamp: 1000 + slot,
+        erasure: ErasureParams::default(),
     }
 }
 
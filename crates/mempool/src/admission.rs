@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use aether_crypto_primitives::ed25519;
+use aether_metrics::MEMPOOL_METRICS;
+use aether_types::{Account, Address, Transaction, H256};
+
+use crate::pool::{TxPrecheck, MAX_NONCE_GAP};
+
+/// Account state the pipeline checks nonces and balances against,
+/// typically the last committed block's.
+pub trait AccountSnapshot: Send + Sync {
+    fn account(&self, address: &Address) -> Option<Account>;
+}
+
+impl<F> AccountSnapshot for F
+where
+    F: Fn(&Address) -> Option<Account> + Send + Sync,
+{
+    fn account(&self, address: &Address) -> Option<Account> {
+        self(address)
+    }
+}
+
+/// The stage of [`AdmissionPipeline`] that turned a transaction away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdmissionStage {
+    /// Chain ID and fee.
+    Envelope,
+    /// Nonce and fee balance against the account snapshot.
+    State,
+    Signature,
+}
+
+impl AdmissionStage {
+    pub fn label(self) -> &'static str {
+        match self {
+            AdmissionStage::Envelope => "envelope",
+            AdmissionStage::State => "state",
+            AdmissionStage::Signature => "signature",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("transaction rejected at {} stage: {reason}", .stage.label())]
+pub struct Rejection {
+    pub tx_hash: H256,
+    pub stage: AdmissionStage,
+    pub reason: String,
+}
+
+/// A transaction that passed every admission stage. Only the pipeline
+/// makes these, so the mempool can insert them without checking again.
+#[derive(Clone, Debug)]
+pub struct AdmittedTx(Transaction);
+
+impl AdmittedTx {
+    pub fn tx(&self) -> &Transaction {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Transaction {
+        self.0
+    }
+}
+
+/// Validates batches of incoming transactions before they reach the
+/// mempool, so the node's lock is only held to insert ones already known
+/// to be well formed and payable.
+///
+/// Stages run cheapest first over the whole batch: chain ID and fee, then
+/// nonce and fee balance against an account snapshot, then one batched
+/// Ed25519 verification of what is left. `admit` is CPU-bound and meant
+/// for a blocking thread pool.
+#[derive(Clone)]
+pub struct AdmissionPipeline {
+    precheck: TxPrecheck,
+    accounts: Arc<dyn AccountSnapshot>,
+}
+
+impl AdmissionPipeline {
+    pub fn new(precheck: TxPrecheck, accounts: impl AccountSnapshot + 'static) -> Self {
+        AdmissionPipeline {
+            precheck,
+            accounts: Arc::new(accounts),
+        }
+    }
+
+    /// Run `txs` through every stage. Results are in input order.
+    pub fn admit(&self, txs: Vec<Transaction>) -> Vec<Result<AdmittedTx, Rejection>> {
+        let _span = tracing::debug_span!("tx_admission", batch = txs.len()).entered();
+        let mut results: Vec<Result<Transaction, Rejection>> = txs
+            .into_iter()
+            .map(|tx| {
+                self.check_envelope(&tx)?;
+                self.check_state(&tx)?;
+                Ok(tx)
+            })
+            .collect();
+        verify_signatures(&mut results);
+
+        results
+            .into_iter()
+            .map(|result| {
+                result.map(AdmittedTx).map_err(|rejection| {
+                    MEMPOOL_METRICS
+                        .admission_rejected
+                        .with_label_values(&[rejection.stage.label()])
+                        .inc();
+                    rejection
+                })
+            })
+            .collect()
+    }
+
+    fn check_envelope(&self, tx: &Transaction) -> Result<(), Rejection> {
+        self.precheck
+            .check_envelope(tx)
+            .map_err(|e| rejection(tx, AdmissionStage::Envelope, e))
+    }
+
+    fn check_state(&self, tx: &Transaction) -> Result<(), Rejection> {
+        let account = self
+            .accounts
+            .account(&tx.sender)
+            .unwrap_or_else(|| Account::new(tx.sender));
+        if tx.nonce < account.nonce {
+            return Err(rejection(
+                tx,
+                AdmissionStage::State,
+                format!("nonce too low: account is at {}", account.nonce),
+            ));
+        }
+        if tx.nonce - account.nonce > MAX_NONCE_GAP {
+            return Err(rejection(
+                tx,
+                AdmissionStage::State,
+                format!("nonce too far ahead: account is at {}", account.nonce),
+            ));
+        }
+        // UTxO transactions pay their fee out of their inputs.
+        let pays_from_balance = tx.inputs.is_empty() && tx.outputs.is_empty();
+        if pays_from_balance && account.balance < tx.fee {
+            return Err(rejection(
+                tx,
+                AdmissionStage::State,
+                format!("balance {} cannot cover fee {}", account.balance, tx.fee),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Batch-verify the single-key signatures among the still admissible
+/// `results`, rejecting those that fail. Multisig senders have no single
+/// key to batch and are checked one by one.
+fn verify_signatures(results: &mut [Result<Transaction, Rejection>]) {
+    let mut batched = Vec::new();
+    for result in results.iter_mut() {
+        let Ok(tx) = result else {
+            continue;
+        };
+        if tx.is_multisig() {
+            if let Err(e) = tx.verify_signature() {
+                *result = Err(rejection(tx, AdmissionStage::Signature, e));
+            }
+        } else if tx.sender_pubkey.to_address() != tx.sender {
+            *result = Err(rejection(
+                tx,
+                AdmissionStage::Signature,
+                "sender address does not match public key",
+            ));
+        } else {
+            batched.push(tx.ed25519_tuple());
+        }
+    }
+    if batched.is_empty() {
+        return;
+    }
+
+    let messages: Vec<&[u8]> = batched.iter().map(|(_, m, _)| m.as_slice()).collect();
+    let signatures: Vec<&[u8]> = batched.iter().map(|(_, _, s)| s.as_slice()).collect();
+    let public_keys: Vec<&[u8]> = batched.iter().map(|(pk, _, _)| pk.as_slice()).collect();
+    // The three slices always have equal length.
+    let valid = ed25519::verify_batch(&messages, &signatures, &public_keys)
+        .unwrap_or_else(|_| vec![false; batched.len()]);
+
+    let mut valid = valid.into_iter();
+    for result in results.iter_mut() {
+        let Ok(tx) = result else {
+            continue;
+        };
+        if tx.is_multisig() {
+            continue;
+        }
+        if valid.next() != Some(true) {
+            *result = Err(rejection(
+                tx,
+                AdmissionStage::Signature,
+                "signature verification failed",
+            ));
+        }
+    }
+}
+
+fn rejection(tx: &Transaction, stage: AdmissionStage, reason: impl ToString) -> Rejection {
+    Rejection {
+        tx_hash: tx.hash(),
+        stage,
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_primitives::Keypair;
+    use aether_types::{ChainConfig, PublicKey, Signature};
+    use std::collections::{HashMap, HashSet};
+
+    fn signed_tx(kp: &Keypair, nonce: u64, fee: u128) -> Transaction {
+        let sender_pubkey = PublicKey::from_bytes(kp.public_key().to_vec());
+        let mut tx = Transaction {
+            nonce,
+            chain_id: 900,
+            sender: sender_pubkey.to_address(),
+            sender_pubkey,
+            inputs: vec![],
+            outputs: vec![],
+            reads: HashSet::new(),
+            writes: HashSet::new(),
+            program_id: None,
+            data: vec![],
+            gas_limit: 21000,
+            fee,
+            signature: Signature::from_bytes(vec![]),
+        };
+        tx.signature = Signature::from_bytes(kp.sign(tx.hash().as_bytes()));
+        tx
+    }
+
+    fn pipeline(accounts: Vec<Account>) -> AdmissionPipeline {
+        let config = ChainConfig::devnet();
+        let accounts: HashMap<Address, Account> =
+            accounts.into_iter().map(|a| (a.address, a)).collect();
+        AdmissionPipeline::new(
+            TxPrecheck::new(config.fees, config.chain.chain_id_numeric),
+            move |address: &Address| accounts.get(address).cloned(),
+        )
+    }
+
+    fn funded(kp: &Keypair, nonce: u64) -> Account {
+        let mut account = Account::with_balance(
+            PublicKey::from_bytes(kp.public_key().to_vec()).to_address(),
+            1_000_000,
+        );
+        account.nonce = nonce;
+        account
+    }
+
+    #[test]
+    fn test_admits_valid_batch() {
+        let keys: Vec<Keypair> = (0..8).map(|_| Keypair::generate()).collect();
+        let pipeline = pipeline(keys.iter().map(|kp| funded(kp, 0)).collect());
+        let txs: Vec<Transaction> = keys.iter().map(|kp| signed_tx(kp, 0, 60_000)).collect();
+
+        let results = pipeline.admit(txs.clone());
+        assert_eq!(results.len(), txs.len());
+        for (result, tx) in results.into_iter().zip(&txs) {
+            assert_eq!(result.unwrap().tx().hash(), tx.hash());
+        }
+    }
+
+    #[test]
+    fn test_rejects_at_each_stage() {
+        let kp = Keypair::generate();
+        let poor = Keypair::generate();
+        let pipeline = pipeline(vec![funded(&kp, 5)]);
+
+        let mut wrong_chain = signed_tx(&kp, 5, 60_000);
+        wrong_chain.chain_id = 1;
+        let stale = signed_tx(&kp, 4, 60_000);
+        let too_far = signed_tx(&kp, 5 + MAX_NONCE_GAP + 1, 60_000);
+        let unfunded = signed_tx(&poor, 0, 60_000);
+        let mut forged = signed_tx(&kp, 6, 60_000);
+        forged.fee += 1;
+        let good = signed_tx(&kp, 5, 60_000);
+
+        let stages: Vec<Option<AdmissionStage>> = pipeline
+            .admit(vec![wrong_chain, stale, too_far, unfunded, forged, good])
+            .into_iter()
+            .map(|result| result.err().map(|rejection| rejection.stage))
+            .collect();
+        assert_eq!(
+            stages,
+            vec![
+                Some(AdmissionStage::Envelope),
+                Some(AdmissionStage::State),
+                Some(AdmissionStage::State),
+                Some(AdmissionStage::State),
+                Some(AdmissionStage::Signature),
+                None,
+            ]
+        );
+    }
+}
@@ -4,6 +4,8 @@
 // PURPOSE: Buffer and prioritize pending transactions before block inclusion
 // ============================================================================
 
+pub mod admission;
 pub mod pool;
 
-pub use pool::Mempool;
+pub use admission::{AccountSnapshot, AdmissionPipeline, AdmissionStage, AdmittedTx, Rejection};
+pub use pool::{Mempool, TxPrecheck, TxStatusEvent};
@@ -1,27 +1,41 @@
+use crate::admission::AdmittedTx;
 use aether_metrics::MEMPOOL_METRICS;
-use aether_types::{Address, FeeParams, Transaction, H256};
+use aether_types::{Address, FeeParams, Transaction, TxLifecycle, UtxoId, H256};
 use anyhow::Result;
 use std::cmp::Ordering;
-use std::collections::{BTreeMap, BinaryHeap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
 use std::time::Instant;
+use tokio::sync::broadcast;
 
 const MAX_MEMPOOL_SIZE: usize = 50_000;
+/// Cap on the serialized size of all pooled transactions.
+const MAX_MEMPOOL_BYTES: usize = 64 * 1024 * 1024;
 const MIN_FEE: u128 = 1000;
+/// Maximum pooled transactions per sender, pending and queued together.
+const MAX_TXS_PER_SENDER: usize = 128;
+/// Admission floor as a percentage of the base fee. A cheaper transaction
+/// could only be included after the base fee halved, so it is not pooled.
+const MIN_BASE_FEE_PERCENT: u128 = 50;
 const MAX_TXS_PER_SENDER_PER_SECOND: u32 = 100;
 const RATE_LIMIT_WINDOW_SECS: u64 = 1;
 /// Maximum queued (future-nonce) transactions per sender.
 const MAX_QUEUED_PER_SENDER: usize = 64;
 /// Maximum nonce gap from the expected nonce.
-const MAX_NONCE_GAP: u64 = 256;
+pub(crate) const MAX_NONCE_GAP: u64 = 256;
 /// Txs waiting longer than this many slots with sufficient fee must be included.
 const FORCED_INCLUSION_SLOTS: u64 = 10;
 /// Maximum age (in slots) before a transaction is evicted from the mempool.
 /// At ~2s slots this is ~1 hour.
 const MAX_TX_AGE_SLOTS: u64 = 1800;
+/// Status events buffered per subscriber before the slowest one lags.
+const STATUS_CHANNEL_CAPACITY: usize = 4096;
 
 #[derive(Clone)]
 struct PrioritizedTx {
     tx: Transaction,
+    /// Fee per unit of gas limit; the priority fee is this minus the base fee.
+    fee_per_gas: u128,
+    /// Fee per serialized byte, breaking ties between equal gas prices.
     fee_rate: u128,
     timestamp: u64,
     /// Slot when the tx entered the mempool (for forced inclusion tracking).
@@ -44,13 +58,66 @@ impl PartialOrd for PrioritizedTx {
 
 impl Ord for PrioritizedTx {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.fee_rate.cmp(&other.fee_rate) {
-            Ordering::Equal => other.timestamp.cmp(&self.timestamp),
-            other => other,
+        self.fee_per_gas
+            .cmp(&other.fee_per_gas)
+            .then(self.fee_rate.cmp(&other.fee_rate))
+            .then(other.timestamp.cmp(&self.timestamp))
+    }
+}
+
+/// The checks the mempool makes on a transaction by itself, without pool
+/// or chain state. Cheap to clone, so gossip can run them off the node's
+/// thread and drop bad transactions before forwarding them.
+#[derive(Clone, Debug)]
+pub struct TxPrecheck {
+    fee_params: FeeParams,
+    /// Expected chain ID for replay protection (0 = no validation).
+    expected_chain_id: u64,
+}
+
+impl TxPrecheck {
+    pub fn new(fee_params: FeeParams, expected_chain_id: u64) -> Self {
+        TxPrecheck {
+            fee_params,
+            expected_chain_id,
+        }
+    }
+
+    /// Chain ID, signature and fee checks.
+    pub fn check(&self, tx: &Transaction) -> Result<()> {
+        self.check_envelope(tx)?;
+        tx.verify_signature()
+            .map_err(|e| anyhow::anyhow!("invalid signature: {}", e))
+    }
+
+    /// Chain ID and fee checks, leaving out the signature.
+    pub fn check_envelope(&self, tx: &Transaction) -> Result<()> {
+        // Reject cross-chain transactions (replay protection)
+        if self.expected_chain_id != 0 && tx.chain_id != self.expected_chain_id {
+            anyhow::bail!(
+                "chain_id mismatch: tx has {}, expected {}",
+                tx.chain_id,
+                self.expected_chain_id
+            );
+        }
+
+        tx.calculate_fee(&self.fee_params)
+            .map_err(|e| anyhow::anyhow!("invalid fee: {}", e))?;
+
+        if tx.fee < MIN_FEE {
+            anyhow::bail!("fee below minimum");
         }
+        Ok(())
     }
 }
 
+/// A transaction's move to a new [`TxLifecycle`] status.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TxStatusEvent {
+    pub tx_hash: H256,
+    pub status: TxLifecycle,
+}
+
 /// Rate limit tracker per sender.
 struct RateLimitEntry {
     window_start: Instant,
@@ -60,6 +127,9 @@ struct RateLimitEntry {
 pub struct Mempool {
     /// Priority queue for pending (ready-to-execute) transactions.
     pending: BinaryHeap<PrioritizedTx>,
+    /// Heap entries of txs evicted from `pending`, left in place until
+    /// the next rebuild.
+    stale_pending: usize,
     /// Quick lookup by hash.
     by_hash: HashMap<H256, Transaction>,
     /// Txs grouped by sender.
@@ -69,34 +139,68 @@ pub struct Mempool {
     /// Queued transactions: future nonces waiting for gaps to fill.
     /// sender → nonce → (Transaction, submitted_slot)
     queued: HashMap<Address, BTreeMap<u64, (Transaction, u64)>>,
+    /// Every pooled tx as (fee per gas, is pending, hash), so the first
+    /// entry is the next to evict: the lowest payer, queued before pending
+    /// at equal fee.
+    eviction_order: BTreeSet<(u128, bool, [u8; 32])>,
     /// Per-sender rate limiting.
     rate_limits: HashMap<Address, RateLimitEntry>,
     /// Monotonic counter for FIFO tiebreaking.
     current_time: u64,
     /// Current slot number (updated externally for forced inclusion tracking).
     current_slot: u64,
-    /// Fee parameters for validating transaction fees.
-    fee_params: FeeParams,
-    /// Expected chain ID for replay protection (0 = no validation).
-    expected_chain_id: u64,
+    /// Base fee per gas of the next block; cheaper txs wait in the pool.
+    base_fee: u128,
+    /// Serialized size of everything in `by_hash`.
+    total_bytes: usize,
+    /// Capacity, evicting the lowest payers beyond it.
+    max_txs: usize,
+    max_bytes: usize,
+    /// Stateless checks every transaction must pass.
+    precheck: TxPrecheck,
+    /// Lifecycle events of pooled transactions, plus the chain events the
+    /// node reports through `notify_status`.
+    status_tx: broadcast::Sender<TxStatusEvent>,
 }
 
 impl Mempool {
     pub fn new(fee_params: FeeParams, expected_chain_id: u64) -> Self {
         Mempool {
             pending: BinaryHeap::new(),
+            stale_pending: 0,
             by_hash: HashMap::new(),
             by_sender: HashMap::new(),
             next_nonce: HashMap::new(),
             queued: HashMap::new(),
+            eviction_order: BTreeSet::new(),
             rate_limits: HashMap::new(),
             current_time: 0,
             current_slot: 0,
-            fee_params,
-            expected_chain_id,
+            base_fee: 0,
+            total_bytes: 0,
+            max_txs: MAX_MEMPOOL_SIZE,
+            max_bytes: MAX_MEMPOOL_BYTES,
+            precheck: TxPrecheck::new(fee_params, expected_chain_id),
+            status_tx: broadcast::channel(STATUS_CHANNEL_CAPACITY).0,
         }
     }
 
+    /// Status changes of transactions from now on: admission, eviction
+    /// and replacement here, inclusion and finality as the node reports them.
+    pub fn subscribe_status(&self) -> broadcast::Receiver<TxStatusEvent> {
+        self.status_tx.subscribe()
+    }
+
+    /// Publish a status change; a no-op without subscribers.
+    pub fn notify_status(&self, tx_hash: H256, status: TxLifecycle) {
+        let _ = self.status_tx.send(TxStatusEvent { tx_hash, status });
+    }
+
+    /// The stateless checks this pool applies, for use outside it.
+    pub fn precheck(&self) -> TxPrecheck {
+        self.precheck.clone()
+    }
+
     /// Create with devnet fee defaults (convenience for tests).
     pub fn with_defaults() -> Self {
         let config = aether_types::ChainConfig::devnet();
@@ -110,6 +214,17 @@ impl Mempool {
         self.expire_old_transactions();
     }
 
+    /// Update the base fee per gas after each block. Transactions whose fee
+    /// does not cover it stay pooled but are not selected.
+    pub fn set_base_fee(&mut self, base_fee: u128) {
+        self.base_fee = base_fee;
+    }
+
+    /// Lowest fee per gas a new transaction may pay.
+    pub fn admission_floor(&self) -> u128 {
+        self.base_fee.saturating_mul(MIN_BASE_FEE_PERCENT) / 100
+    }
+
     /// Remove transactions that have been in the mempool longer than `MAX_TX_AGE_SLOTS`.
     /// This prevents indefinite accumulation from senders whose nonces never advance.
     fn expire_old_transactions(&mut self) {
@@ -149,9 +264,19 @@ impl Mempool {
 
         if !expired_hashes.is_empty() {
             let count = expired_hashes.len() as u64;
-            // Remove from by_hash and by_sender, rebuild pending heap
-            self.remove_transactions(&expired_hashes);
-            MEMPOOL_METRICS.evictions_total.inc_by(count);
+            for hash in &expired_hashes {
+                if self.untrack(hash).is_some() {
+                    self.notify_status(
+                        *hash,
+                        TxLifecycle::Dropped {
+                            reason: "expired".to_string(),
+                        },
+                    );
+                }
+            }
+            self.rebuild_heap();
+            MEMPOOL_METRICS.expired_total.inc_by(count);
+            self.update_gauges();
         }
     }
 
@@ -179,31 +304,30 @@ impl Mempool {
             pool_size = self.by_hash.len(),
         )
         .entered();
-        // Reject cross-chain transactions (replay protection)
-        if self.expected_chain_id != 0 && tx.chain_id != self.expected_chain_id {
+        if let Err(e) = self.precheck.check(&tx) {
             MEMPOOL_METRICS.rejected_total.inc();
-            anyhow::bail!(
-                "chain_id mismatch: tx has {}, expected {}",
-                tx.chain_id,
-                self.expected_chain_id
-            );
+            return Err(e);
         }
+        self.insert(tx)
+    }
 
-        tx.verify_signature().map_err(|e| {
-            MEMPOOL_METRICS.rejected_total.inc();
-            anyhow::anyhow!("invalid signature: {}", e)
-        })?;
-
-        tx.calculate_fee(&self.fee_params).map_err(|e| {
-            MEMPOOL_METRICS.rejected_total.inc();
-            anyhow::anyhow!("invalid fee: {}", e)
-        })?;
-
-        if tx.fee < MIN_FEE {
-            MEMPOOL_METRICS.rejected_total.inc();
-            anyhow::bail!("fee below minimum");
-        }
+    /// Add a transaction that already passed the [`AdmissionPipeline`],
+    /// skipping the checks it ran.
+    ///
+    /// [`AdmissionPipeline`]: crate::AdmissionPipeline
+    pub fn add_admitted(&mut self, tx: AdmittedTx) -> Result<()> {
+        let tx = tx.into_inner();
+        let _span = tracing::debug_span!(
+            "mempool_add_admitted_tx",
+            fee = tx.fee,
+            nonce = tx.nonce,
+            pool_size = self.by_hash.len(),
+        )
+        .entered();
+        self.insert(tx)
+    }
 
+    fn insert(&mut self, tx: Transaction) -> Result<()> {
         // Rate limiting
         if let Err(e) = self.check_rate_limit(&tx.sender) {
             MEMPOOL_METRICS.rate_limited_total.inc();
@@ -211,6 +335,20 @@ impl Mempool {
             return Err(e);
         }
 
+        let tx_fee_per_gas = fee_per_gas(&tx);
+        let floor = self.admission_floor();
+        if tx_fee_per_gas < floor {
+            MEMPOOL_METRICS.underpriced_total.inc();
+            MEMPOOL_METRICS.rejected_total.inc();
+            anyhow::bail!(
+                "fee per gas {} below admission floor {} ({}% of base fee {})",
+                tx_fee_per_gas,
+                floor,
+                MIN_BASE_FEE_PERCENT,
+                self.base_fee
+            );
+        }
+
         let tx_hash = tx.hash();
 
         // Exact duplicate check
@@ -220,7 +358,9 @@ impl Mempool {
         }
 
         // Replace-by-fee: if the same sender already has a tx with the same nonce,
-        // allow replacement only if the new fee is >10% higher.
+        // allow replacement only if the new fee is >10% higher. The old tx
+        // stays pooled until every other admission check has passed.
+        let mut replaced = None;
         if let Some(existing_hashes) = self.by_sender.get(&tx.sender) {
             let same_nonce_hash = existing_hashes
                 .iter()
@@ -237,32 +377,16 @@ impl Mempool {
                         old_fee
                     );
                 }
-                MEMPOOL_METRICS.rbf_replacements_total.inc();
-                let old_nonce = self.by_hash[&old_hash].nonce;
-                // Remove the old transaction being replaced
-                self.by_hash.remove(&old_hash);
-                if let Some(sender_txs) = self.by_sender.get_mut(&tx.sender) {
-                    sender_txs.remove(&old_hash);
-                }
-                // If the replaced tx was already pending (nonce < next_nonce),
-                // roll back next_nonce so the replacement can enter pending.
-                let expected = self.next_nonce.get(&tx.sender).copied().unwrap_or(0);
-                if old_nonce < expected {
-                    self.next_nonce.insert(tx.sender, old_nonce);
-                }
-                // Stale heap entry is harmless — skipped in get_transactions() via by_hash check
+                replaced = Some(old_hash);
             }
         }
 
-        // Capacity check
-        if self.by_hash.len() >= MAX_MEMPOOL_SIZE {
-            self.evict_lowest_fee();
-        }
-
         // Nonce-based routing
         let expected_nonce = self.next_nonce.get(&tx.sender).copied().unwrap_or(0);
+        // A replacement below the expected nonce takes over a pending slot.
+        let replaces_pending = replaced.is_some() && tx.nonce < expected_nonce;
 
-        if tx.nonce < expected_nonce {
+        if tx.nonce < expected_nonce && !replaces_pending {
             MEMPOOL_METRICS.rejected_total.inc();
             anyhow::bail!(
                 "nonce too low: tx nonce {} < expected {}",
@@ -271,26 +395,10 @@ impl Mempool {
             );
         }
 
-        // Track in by_hash and by_sender
-        self.by_hash.insert(tx_hash, tx.clone());
-        self.by_sender.entry(tx.sender).or_default().insert(tx_hash);
-
-        if tx.nonce == expected_nonce {
-            // Ready to execute — add to pending
-            self.add_to_pending(tx);
-            // Promote any queued txs that are now sequential
-            self.promote_queued(tx_hash);
-        } else {
+        if tx.nonce > expected_nonce {
             // Future nonce — enforce per-sender limits to prevent DoS
             let nonce_gap = tx.nonce.saturating_sub(expected_nonce);
             if nonce_gap > MAX_NONCE_GAP {
-                self.by_hash.remove(&tx_hash);
-                if let Some(sender_txs) = self.by_sender.get_mut(&tx.sender) {
-                    sender_txs.remove(&tx_hash);
-                    if sender_txs.is_empty() {
-                        self.by_sender.remove(&tx.sender);
-                    }
-                }
                 MEMPOOL_METRICS.rejected_total.inc();
                 anyhow::bail!(
                     "nonce gap too large: tx nonce {} is {} ahead of expected {}",
@@ -300,26 +408,69 @@ impl Mempool {
                 );
             }
 
-            let sender_queued = self.queued.entry(tx.sender).or_default();
-            if sender_queued.len() >= MAX_QUEUED_PER_SENDER {
-                self.by_hash.remove(&tx_hash);
-                if let Some(sender_txs) = self.by_sender.get_mut(&tx.sender) {
-                    sender_txs.remove(&tx_hash);
-                    if sender_txs.is_empty() {
-                        self.by_sender.remove(&tx.sender);
-                    }
-                }
+            let queued = self.queued.get(&tx.sender).map_or(0, BTreeMap::len);
+            if queued >= MAX_QUEUED_PER_SENDER && replaced.is_none() {
                 MEMPOOL_METRICS.rejected_total.inc();
                 anyhow::bail!(
                     "too many queued transactions for sender (max {})",
                     MAX_QUEUED_PER_SENDER
                 );
             }
-            sender_queued.insert(tx.nonce, (tx, self.current_slot));
+        }
+
+        let sender_txs = self.by_sender.get(&tx.sender).map_or(0, HashSet::len);
+        if sender_txs - usize::from(replaced.is_some()) >= MAX_TXS_PER_SENDER {
+            MEMPOOL_METRICS.rejected_total.inc();
+            anyhow::bail!(
+                "too many transactions for sender (max {})",
+                MAX_TXS_PER_SENDER
+            );
+        }
+
+        // Capacity check: only a better-paying tx displaces pooled ones
+        let tx_size = tx_size(&tx);
+        let victims = match self.eviction_victims(&tx, tx_fee_per_gas, tx_size, replaced) {
+            Ok(victims) => victims,
+            Err(e) => {
+                MEMPOOL_METRICS.rejected_total.inc();
+                return Err(e);
+            }
+        };
+
+        // Admitted: only now does the pool change.
+        if let Some(old_hash) = replaced {
+            MEMPOOL_METRICS.rbf_replacements_total.inc();
+            // A queued entry is overwritten below; a pending one's heap
+            // entry is skipped in get_transactions() via the by_hash check.
+            self.untrack(&old_hash);
+            self.notify_status(old_hash, TxLifecycle::Replaced { by: tx_hash });
+        }
+        for victim in victims {
+            self.evict(victim);
+        }
+
+        // Track in by_hash and by_sender
+        self.by_hash.insert(tx_hash, tx.clone());
+        self.by_sender.entry(tx.sender).or_default().insert(tx_hash);
+        self.total_bytes += tx_size;
+
+        if tx.nonce == expected_nonce || replaces_pending {
+            // Ready to execute — add to pending
+            self.add_to_pending(tx);
+            // Promote any queued txs that are now sequential
+            self.promote_queued(tx_hash);
+        } else {
+            self.eviction_order
+                .insert((tx_fee_per_gas, false, tx_hash.0));
+            self.queued
+                .entry(tx.sender)
+                .or_default()
+                .insert(tx.nonce, (tx, self.current_slot));
         }
 
         MEMPOOL_METRICS.admitted_total.inc();
         self.update_gauges();
+        self.notify_status(tx_hash, TxLifecycle::Pending);
         Ok(())
     }
 
@@ -361,14 +512,11 @@ impl Mempool {
     }
 
     fn add_to_pending(&mut self, tx: Transaction) {
-        let tx_size = bincode::serialize(&tx)
-            .map(|b| b.len() as u128)
-            .unwrap_or(1); // Fallback to 1 to avoid divide-by-zero
-        let fee_rate = if tx_size > 0 {
-            tx.fee / tx_size
-        } else {
-            tx.fee
-        };
+        let fee_rate = tx.fee.checked_div(tx_size(&tx) as u128).unwrap_or(tx.fee);
+        let fee_per_gas = fee_per_gas(&tx);
+        let hash = tx.hash();
+        self.eviction_order.remove(&(fee_per_gas, false, hash.0));
+        self.eviction_order.insert((fee_per_gas, true, hash.0));
 
         // Advance expected nonce
         let sender = tx.sender;
@@ -380,6 +528,7 @@ impl Mempool {
 
         self.pending.push(PrioritizedTx {
             tx,
+            fee_per_gas,
             fee_rate,
             timestamp: self.current_time,
             submitted_slot: self.current_slot,
@@ -445,13 +594,19 @@ impl Mempool {
         }
     }
 
+    /// Highest priority fee first, up to `max_count` transactions and
+    /// `max_gas` total gas limit, skipping any below the base fee.
     pub fn get_transactions(&mut self, max_count: usize, max_gas: u64) -> Vec<Transaction> {
         let mut selected = Vec::new();
         let mut total_gas = 0u64;
         let mut temp_heap = BinaryHeap::new();
 
         while let Some(ptx) = self.pending.pop() {
-            if selected.len() >= max_count || total_gas >= max_gas {
+            // The heap is ordered by fee per gas, so the rest are underpriced too.
+            if selected.len() >= max_count
+                || total_gas >= max_gas
+                || ptx.fee_per_gas < self.base_fee
+            {
                 temp_heap.push(ptx);
                 break;
             }
@@ -474,6 +629,70 @@ impl Mempool {
         selected
     }
 
+    /// Best-paying transactions within `max_gas` and `max_bytes`, arranged
+    /// for parallel execution.
+    ///
+    /// A first pass takes transactions in priority order, skipping any
+    /// that conflict with one already chosen under the runtime scheduler's
+    /// rule (shared write, write/read overlap or shared UTxO input), so as
+    /// much of the block as possible runs as a single parallel batch. A
+    /// second pass fills what capacity is left with the skipped ones, still
+    /// best-paying first. A sender's transactions are only taken in nonce
+    /// order, and those below the base fee not at all.
+    pub fn build_block(&self, max_gas: u64, max_bytes: usize) -> Vec<Transaction> {
+        let mut candidates: Vec<&PrioritizedTx> = self
+            .pending
+            .iter()
+            .filter(|ptx| self.by_hash.contains_key(&ptx.tx.hash()))
+            .collect();
+
+        // Next nonce each sender may include: its lowest pending one.
+        let mut next_nonce: HashMap<Address, u64> = HashMap::new();
+        for ptx in &candidates {
+            next_nonce
+                .entry(ptx.tx.sender)
+                .and_modify(|n| *n = (*n).min(ptx.tx.nonce))
+                .or_insert(ptx.tx.nonce);
+        }
+        candidates.retain(|ptx| ptx.fee_per_gas >= self.base_fee);
+        candidates.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut chosen = vec![false; candidates.len()];
+        let mut selected = Vec::new();
+        let mut accesses = AccessSet::default();
+        let (mut gas, mut bytes) = (0u64, 0usize);
+        for allow_conflicts in [false, true] {
+            // Repeat so a sender's next nonce gets its turn once the one
+            // before it is in.
+            let mut progressed = true;
+            while progressed {
+                progressed = false;
+                for (i, ptx) in candidates.iter().enumerate() {
+                    let tx = &ptx.tx;
+                    if chosen[i] || next_nonce.get(&tx.sender) != Some(&tx.nonce) {
+                        continue;
+                    }
+                    let size = tx_size(tx);
+                    if gas.saturating_add(tx.gas_limit) > max_gas
+                        || bytes.saturating_add(size) > max_bytes
+                        || (!allow_conflicts && accesses.conflicts(tx))
+                    {
+                        continue;
+                    }
+                    chosen[i] = true;
+                    progressed = true;
+                    gas += tx.gas_limit;
+                    bytes += size;
+                    accesses.add(tx);
+                    next_nonce.insert(tx.sender, tx.nonce.saturating_add(1));
+                    selected.push(tx.clone());
+                }
+            }
+        }
+
+        selected
+    }
+
     /// Return transactions that MUST be included (anti-censorship).
     /// A tx must be included if it has waited > FORCED_INCLUSION_SLOTS
     /// and pays >= 2x the base_fee (clearly willing to pay market rate).
@@ -483,6 +702,9 @@ impl Mempool {
 
         // Iterate pending heap without consuming (peek at all items)
         for ptx in self.pending.iter() {
+            if !self.by_hash.contains_key(&ptx.tx.hash()) {
+                continue;
+            }
             let age = current_slot.saturating_sub(ptx.submitted_slot);
             if age >= FORCED_INCLUSION_SLOTS && ptx.tx.fee >= min_fee {
                 forced.push(ptx.tx.clone());
@@ -495,10 +717,7 @@ impl Mempool {
     pub fn remove_transactions(&mut self, tx_hashes: &[H256]) {
         let mut removed = 0u64;
         for hash in tx_hashes {
-            if let Some(tx) = self.by_hash.remove(hash) {
-                if let Some(sender_txs) = self.by_sender.get_mut(&tx.sender) {
-                    sender_txs.remove(hash);
-                }
+            if self.untrack(hash).is_some() {
                 removed += 1;
             }
         }
@@ -515,48 +734,156 @@ impl Mempool {
             }
         }
         self.pending = new_heap;
+        self.stale_pending = 0;
     }
 
-    fn evict_lowest_fee(&mut self) {
-        MEMPOOL_METRICS.evictions_total.inc();
-        // Prefer evicting queued (future-nonce) txs over ready-to-execute pending txs.
-        let mut worst_queued: Option<(Address, u64, u128)> = None;
-        for (sender, nonces) in &self.queued {
-            for (nonce, (tx, _slot)) in nonces {
-                let dominated = worst_queued.as_ref().map_or(true, |(_, _, f)| tx.fee < *f);
-                if dominated {
-                    worst_queued = Some((*sender, *nonce, tx.fee));
-                }
+    /// Drop a transaction from the lookup maps and the byte count. The
+    /// caller removes it from `pending` or `queued`.
+    fn untrack(&mut self, hash: &H256) -> Option<Transaction> {
+        let tx = self.by_hash.remove(hash)?;
+        self.total_bytes = self.total_bytes.saturating_sub(tx_size(&tx));
+        let fee = fee_per_gas(&tx);
+        self.eviction_order.remove(&(fee, false, hash.0));
+        self.eviction_order.remove(&(fee, true, hash.0));
+        if let Some(sender_txs) = self.by_sender.get_mut(&tx.sender) {
+            sender_txs.remove(hash);
+            if sender_txs.is_empty() {
+                self.by_sender.remove(&tx.sender);
             }
         }
+        Some(tx)
+    }
 
-        if let Some((sender, nonce, _)) = worst_queued {
-            if let Some(nonces) = self.queued.get_mut(&sender) {
-                if let Some((tx, _slot)) = nonces.remove(&nonce) {
-                    let tx_hash = tx.hash();
-                    self.by_hash.remove(&tx_hash);
-                    if let Some(sender_txs) = self.by_sender.get_mut(&sender) {
-                        sender_txs.remove(&tx_hash);
-                    }
-                }
-                if nonces.is_empty() {
-                    self.queued.remove(&sender);
+    /// The lowest payers to evict so `tx`, of `size` bytes, fits, worked
+    /// out without touching the pool. Fails as soon as the next one to go
+    /// pays at least `fee_per_gas`. `replaced` is the tx `tx` replaces by
+    /// fee, whose room it reuses.
+    fn eviction_victims(
+        &self,
+        tx: &Transaction,
+        fee_per_gas: u128,
+        size: usize,
+        replaced: Option<H256>,
+    ) -> Result<Vec<H256>> {
+        if size > self.max_bytes {
+            anyhow::bail!("transaction of {} bytes exceeds the mempool", size);
+        }
+        let mut count = self.by_hash.len();
+        let mut bytes = self.total_bytes;
+        if let Some(old) = replaced.and_then(|hash| self.by_hash.get(&hash)) {
+            count -= 1;
+            bytes = bytes.saturating_sub(tx_size(old));
+        }
+        let mut victims = Vec::new();
+        while count >= self.max_txs || bytes.saturating_add(size) > self.max_bytes {
+            match self.next_victim(Some(tx), replaced, &victims) {
+                Some((hash, lowest)) if lowest < fee_per_gas => {
+                    count -= 1;
+                    bytes = bytes.saturating_sub(tx_size(&self.by_hash[&hash]));
+                    victims.push(hash);
                 }
+                Some((_, lowest)) => anyhow::bail!(
+                    "mempool full: fee per gas {} does not beat the lowest pooled {}",
+                    fee_per_gas,
+                    lowest
+                ),
+                None => anyhow::bail!("mempool full: nothing can be evicted"),
             }
-            return;
         }
+        Ok(victims)
+    }
+
+    /// The next transaction to evict and its fee per gas: the lowest
+    /// payer, queued (future-nonce) before pending at equal fee.
+    #[cfg(test)]
+    fn eviction_candidate(&self) -> Option<(H256, u128)> {
+        self.next_victim(None, None, &[])
+    }
+
+    /// The lowest payer that can go once `evicted` are gone, skipping
+    /// `replaced`. A pending tx goes only from the top of its sender's
+    /// nonce chain, and never from below an `incoming` tx of the same
+    /// sender, so eviction never strands later nonces behind a gap.
+    fn next_victim(
+        &self,
+        incoming: Option<&Transaction>,
+        replaced: Option<H256>,
+        evicted: &[H256],
+    ) -> Option<(H256, u128)> {
+        self.eviction_order
+            .iter()
+            .map(|&(fee_per_gas, pending, hash)| (H256(hash), fee_per_gas, pending))
+            .find(|(hash, _, pending)| {
+                Some(*hash) != replaced
+                    && !evicted.contains(hash)
+                    && (!pending || self.tops_nonce_chain(hash, incoming, evicted))
+            })
+            .map(|(hash, fee_per_gas, _)| (hash, fee_per_gas))
+    }
+
+    /// Whether the pending tx `hash` has no later pending nonce of its
+    /// sender behind it, counting `incoming` and ignoring `evicted`.
+    fn tops_nonce_chain(
+        &self,
+        hash: &H256,
+        incoming: Option<&Transaction>,
+        evicted: &[H256],
+    ) -> bool {
+        let Some(tx) = self.by_hash.get(hash) else {
+            return false;
+        };
+        if incoming.is_some_and(|new| new.sender == tx.sender && new.nonce > tx.nonce) {
+            return false;
+        }
+        self.by_sender.get(&tx.sender).map_or(true, |hashes| {
+            hashes.iter().all(|other| {
+                other == hash
+                    || evicted.contains(other)
+                    || self.by_hash.get(other).map_or(true, |later| {
+                        later.nonce < tx.nonce
+                            || self
+                                .eviction_order
+                                .contains(&(fee_per_gas(later), false, other.0))
+                    })
+            })
+        })
+    }
 
-        // Fall back to evicting lowest-fee pending tx
-        let mut txs: Vec<_> = std::mem::take(&mut self.pending).into_vec();
-        txs.sort_by(|a, b| b.cmp(a));
-        if let Some(lowest) = txs.pop() {
-            let tx_hash = lowest.tx.hash();
-            self.by_hash.remove(&tx_hash);
-            if let Some(sender_txs) = self.by_sender.get_mut(&lowest.tx.sender) {
-                sender_txs.remove(&tx_hash);
+    fn evict(&mut self, hash: H256) {
+        let Some(tx) = self.untrack(&hash) else {
+            return;
+        };
+        MEMPOOL_METRICS.evictions_total.inc();
+        self.notify_status(
+            hash,
+            TxLifecycle::Dropped {
+                reason: "evicted".to_string(),
+            },
+        );
+        if let Some(nonces) = self.queued.get_mut(&tx.sender) {
+            if nonces.get(&tx.nonce).is_some_and(|(q, _)| q.hash() == hash) {
+                nonces.remove(&tx.nonce);
+                if nonces.is_empty() {
+                    self.queued.remove(&tx.sender);
+                }
+                return;
             }
         }
-        self.pending = txs.into();
+        // It topped its sender's pending chain, so its nonce is the next
+        // one expected again and the sender can resubmit it.
+        if self
+            .next_nonce
+            .get(&tx.sender)
+            .is_some_and(|&next| next > tx.nonce)
+        {
+            self.next_nonce.insert(tx.sender, tx.nonce);
+        }
+        // Its heap entry is skipped like a replaced tx's until there are
+        // enough of them to be worth a rebuild.
+        self.stale_pending += 1;
+        if self.stale_pending * 2 > self.pending.len() {
+            self.rebuild_heap();
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -570,8 +897,17 @@ impl Mempool {
     /// Update Prometheus gauge metrics to reflect current pool state.
     fn update_gauges(&self) {
         MEMPOOL_METRICS.pool_size.set(self.by_hash.len() as i64);
-        MEMPOOL_METRICS.pending_size.set(self.pending.len() as i64);
-        MEMPOOL_METRICS.queued_size.set(self.queued_len() as i64);
+        let queued = self.queued_len();
+        MEMPOOL_METRICS
+            .pending_size
+            .set(self.by_hash.len().saturating_sub(queued) as i64);
+        MEMPOOL_METRICS.queued_size.set(queued as i64);
+        MEMPOOL_METRICS.pool_bytes.set(self.total_bytes as i64);
+    }
+
+    /// Serialized size of all pooled transactions.
+    pub fn size_bytes(&self) -> usize {
+        self.total_bytes
     }
 
     /// Number of queued (future nonce) transactions.
@@ -580,6 +916,39 @@ impl Mempool {
     }
 }
 
+/// Accounts and UTxOs touched by the transactions chosen so far.
+#[derive(Default)]
+struct AccessSet {
+    reads: HashSet<Address>,
+    writes: HashSet<Address>,
+    inputs: HashSet<UtxoId>,
+}
+
+impl AccessSet {
+    /// [`Transaction::conflicts_with`] against every transaction added.
+    fn conflicts(&self, tx: &Transaction) -> bool {
+        tx.writes
+            .iter()
+            .any(|a| self.writes.contains(a) || self.reads.contains(a))
+            || tx.reads.iter().any(|a| self.writes.contains(a))
+            || tx.inputs.iter().any(|input| self.inputs.contains(input))
+    }
+
+    fn add(&mut self, tx: &Transaction) {
+        self.reads.extend(tx.reads.iter().copied());
+        self.writes.extend(tx.writes.iter().copied());
+        self.inputs.extend(tx.inputs.iter().cloned());
+    }
+}
+
+fn tx_size(tx: &Transaction) -> usize {
+    bincode::serialized_size(tx).map_or(0, |n| n as usize)
+}
+
+fn fee_per_gas(tx: &Transaction) -> u128 {
+    tx.fee / u128::from(tx.gas_limit.max(1))
+}
+
 impl Default for Mempool {
     fn default() -> Self {
         Self::with_defaults()
@@ -617,6 +986,14 @@ mod tests {
         tx
     }
 
+    fn create_test_tx_writing(kp: &Keypair, nonce: u64, fee: u128, account: u8) -> Transaction {
+        let mut tx = create_test_tx_with_keypair(kp, nonce, fee);
+        tx.writes
+            .insert(Address::from_slice(&[account; 20]).unwrap());
+        tx.signature = Signature::from_bytes(kp.sign(tx.hash().as_bytes()));
+        tx
+    }
+
     fn create_test_tx(nonce: u64, fee: u128) -> Transaction {
         let kp = Keypair::generate();
         create_test_tx_with_keypair(&kp, nonce, fee)
@@ -649,6 +1026,187 @@ mod tests {
         assert_eq!(txs[2].fee, 110_000);
     }
 
+    #[test]
+    fn test_underpriced_txs_wait_for_base_fee() {
+        let mut mempool = Mempool::with_defaults();
+        // 5 and 10 per gas at 21_000 gas.
+        let cheap = create_test_tx(0, 105_000);
+        let rich = create_test_tx(0, 210_000);
+        mempool.add_transaction(cheap.clone()).unwrap();
+        mempool.add_transaction(rich.clone()).unwrap();
+
+        mempool.set_base_fee(6);
+        let txs = mempool.get_transactions(10, 1_000_000);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash(), rich.hash());
+        assert_eq!(mempool.len(), 2, "underpriced tx stays pooled");
+
+        mempool.remove_transactions(&[rich.hash()]);
+        mempool.set_base_fee(5);
+        let txs = mempool.get_transactions(10, 1_000_000);
+        assert_eq!(txs.len(), 1);
+        assert_eq!(txs[0].hash(), cheap.hash());
+    }
+
+    #[test]
+    fn test_admission_floor_follows_base_fee() {
+        let mut mempool = Mempool::with_defaults();
+        mempool.set_base_fee(10);
+        assert_eq!(mempool.admission_floor(), 5);
+
+        // 4 and 5 per gas at 21_000 gas.
+        let err = mempool
+            .add_transaction(create_test_tx(0, 84_000))
+            .unwrap_err();
+        assert!(err.to_string().contains("below admission floor"));
+        mempool.add_transaction(create_test_tx(0, 105_000)).unwrap();
+        assert_eq!(mempool.len(), 1);
+    }
+
+    #[test]
+    fn test_full_pool_evicts_only_for_better_payers() {
+        let mut mempool = Mempool::with_defaults();
+        mempool.max_txs = 2;
+        let cheap = create_test_tx(0, 105_000);
+        mempool.add_transaction(cheap.clone()).unwrap();
+        mempool.add_transaction(create_test_tx(0, 210_000)).unwrap();
+
+        let err = mempool
+            .add_transaction(create_test_tx(0, 105_000))
+            .unwrap_err();
+        assert!(err.to_string().contains("mempool full"));
+
+        mempool.add_transaction(create_test_tx(0, 315_000)).unwrap();
+        assert_eq!(mempool.len(), 2);
+        let fees: Vec<_> = mempool
+            .get_transactions(10, 1_000_000)
+            .iter()
+            .map(|tx| tx.fee)
+            .collect();
+        assert_eq!(fees, vec![315_000, 210_000], "cheapest was evicted");
+    }
+
+    #[test]
+    fn test_byte_cap_bounds_pool() {
+        let mut mempool = Mempool::with_defaults();
+        let first = create_test_tx(0, 105_000);
+        let size = tx_size(&first);
+        mempool.max_bytes = size * 2;
+
+        mempool.add_transaction(first.clone()).unwrap();
+        mempool.add_transaction(create_test_tx(0, 210_000)).unwrap();
+        assert_eq!(mempool.size_bytes(), size * 2);
+        mempool.add_transaction(create_test_tx(0, 315_000)).unwrap();
+        assert_eq!(mempool.len(), 2);
+        assert!(mempool.size_bytes() <= size * 2);
+
+        mempool.remove_transactions(&[first.hash()]);
+        assert_eq!(mempool.len(), 2, "the evicted tx was already gone");
+    }
+
+    #[test]
+    fn test_per_sender_total_limit() {
+        let mut mempool = Mempool::with_defaults();
+        let kp = Keypair::generate();
+        for nonce in 0..MAX_TXS_PER_SENDER as u64 {
+            mempool.rate_limits.clear();
+            mempool
+                .add_transaction(create_test_tx_with_keypair(&kp, nonce, 60_000))
+                .unwrap();
+        }
+
+        mempool.rate_limits.clear();
+        let overflow = create_test_tx_with_keypair(&kp, MAX_TXS_PER_SENDER as u64, 60_000);
+        let err = mempool.add_transaction(overflow).unwrap_err();
+        assert!(err.to_string().contains("too many transactions for sender"));
+        mempool.add_transaction(create_test_tx(0, 60_000)).unwrap();
+    }
+
+    #[test]
+    fn test_build_block_prefers_non_conflicting_set() {
+        let mut mempool = Mempool::with_defaults();
+        let (a, b, c) = (
+            Keypair::generate(),
+            Keypair::generate(),
+            Keypair::generate(),
+        );
+        let hot_rich = create_test_tx_writing(&a, 0, 420_000, 1);
+        let hot = create_test_tx_writing(&b, 0, 315_000, 1);
+        let cold = create_test_tx_writing(&c, 0, 210_000, 2);
+        for tx in [&hot_rich, &hot, &cold] {
+            mempool.add_transaction(tx.clone()).unwrap();
+        }
+
+        let hashes = |txs: Vec<Transaction>| txs.iter().map(|tx| tx.hash()).collect::<Vec<_>>();
+        assert_eq!(
+            hashes(mempool.build_block(42_000, usize::MAX)),
+            vec![hot_rich.hash(), cold.hash()],
+            "the cheaper independent tx beats the conflicting one"
+        );
+        assert_eq!(
+            hashes(mempool.build_block(1_000_000, usize::MAX)),
+            vec![hot_rich.hash(), cold.hash(), hot.hash()],
+            "spare capacity still takes conflicting txs"
+        );
+        let one_tx = tx_size(&cold);
+        assert_eq!(mempool.build_block(1_000_000, one_tx).len(), 1);
+        assert_eq!(mempool.len(), 3, "building a block does not drain the pool");
+    }
+
+    #[test]
+    fn test_build_block_keeps_sender_nonce_order() {
+        let mut mempool = Mempool::with_defaults();
+        let kp = Keypair::generate();
+        let first = create_test_tx_with_keypair(&kp, 0, 105_000);
+        let second = create_test_tx_with_keypair(&kp, 1, 420_000);
+        mempool.add_transaction(first.clone()).unwrap();
+        mempool.add_transaction(second.clone()).unwrap();
+        mempool.add_transaction(create_test_tx(0, 210_000)).unwrap();
+
+        let block = mempool.build_block(1_000_000, usize::MAX);
+        assert_eq!(block.len(), 3);
+        let position = |hash| block.iter().position(|tx| tx.hash() == hash).unwrap();
+        assert!(position(first.hash()) < position(second.hash()));
+
+        mempool.set_base_fee(6);
+        let block = mempool.build_block(1_000_000, usize::MAX);
+        assert_eq!(block.len(), 1, "nonce 1 waits on its underpriced nonce 0");
+    }
+
+    #[test]
+    fn test_status_events_follow_pool_changes() {
+        let mut mempool = Mempool::with_defaults();
+        mempool.max_txs = 1;
+        let mut events = mempool.subscribe_status();
+        let kp = Keypair::generate();
+        let original = create_test_tx_with_keypair(&kp, 0, 105_000);
+        let replacement = create_test_tx_with_keypair(&kp, 0, 210_000);
+        let rich = create_test_tx(0, 420_000);
+
+        mempool.add_transaction(original.clone()).unwrap();
+        mempool.add_transaction(replacement.clone()).unwrap();
+        mempool.add_transaction(rich.clone()).unwrap();
+        mempool.notify_status(rich.hash(), TxLifecycle::Finalized { slot: 3 });
+
+        let mut next = || events.try_recv().unwrap();
+        assert_eq!(next().status, TxLifecycle::Pending);
+        assert_eq!(
+            next(),
+            TxStatusEvent {
+                tx_hash: original.hash(),
+                status: TxLifecycle::Replaced {
+                    by: replacement.hash()
+                },
+            }
+        );
+        assert_eq!(next().status, TxLifecycle::Pending);
+        let evicted = next();
+        assert_eq!(evicted.tx_hash, replacement.hash());
+        assert!(matches!(evicted.status, TxLifecycle::Dropped { .. }));
+        assert_eq!(next().tx_hash, rich.hash());
+        assert!(next().status.is_terminal());
+    }
+
     #[test]
     fn test_gas_limit() {
         let mut mempool = Mempool::with_defaults();
@@ -849,7 +1407,8 @@ mod tests {
         assert_eq!(mempool.queued_len(), 1);
 
         // Evict should remove the queued tx first (lower priority than pending)
-        mempool.evict_lowest_fee();
+        let (lowest, _) = mempool.eviction_candidate().unwrap();
+        mempool.evict(lowest);
         assert_eq!(mempool.queued_len(), 0, "eviction should clean queued map");
         assert_eq!(mempool.len(), 1, "only pending tx should remain");
     }
@@ -879,13 +1438,124 @@ mod tests {
         assert_eq!(mempool.queued_len(), 2);
         assert_eq!(mempool.len(), 4);
 
-        mempool.evict_lowest_fee();
+        let (lowest, _) = mempool.eviction_candidate().unwrap();
+        mempool.evict(lowest);
 
         // Should evict kp2's queued tx (lower fee: 60k < 200k)
         assert_eq!(mempool.queued_len(), 1);
         assert_eq!(mempool.len(), 3);
     }
 
+    #[test]
+    fn test_eviction_compares_pending_with_queued() {
+        let mut mempool = Mempool::with_defaults();
+        mempool.max_txs = 2;
+        let kp1 = Keypair::generate();
+        let kp2 = Keypair::generate();
+
+        // A cheap pending tx and a well-paying queued one.
+        let cheap_pending = create_test_tx_with_keypair(&kp1, 0, 60_000);
+        let rich_queued = create_test_tx_with_keypair(&kp2, 3, 400_000);
+        mempool.add_transaction(cheap_pending.clone()).unwrap();
+        mempool.add_transaction(rich_queued.clone()).unwrap();
+
+        assert_eq!(
+            mempool.eviction_candidate().map(|(hash, _)| hash),
+            Some(cheap_pending.hash())
+        );
+        mempool
+            .add_transaction(create_test_tx_with_keypair(&kp2, 0, 200_000))
+            .unwrap();
+        assert!(!mempool.by_hash.contains_key(&cheap_pending.hash()));
+        assert!(mempool.by_hash.contains_key(&rich_queued.hash()));
+        assert!(mempool
+            .must_include_transactions(u64::MAX, 0)
+            .iter()
+            .all(|tx| tx.hash() != cheap_pending.hash()));
+    }
+
+    #[test]
+    fn test_eviction_keeps_sender_nonce_chains_intact() {
+        let mut mempool = Mempool::with_defaults();
+        mempool.max_txs = 2;
+        let kp = Keypair::generate();
+
+        // A cheap nonce 0 with a well-paying nonce 1 behind it.
+        let cheap_first = create_test_tx_with_keypair(&kp, 0, 60_000);
+        let rich_second = create_test_tx_with_keypair(&kp, 1, 400_000);
+        mempool.add_transaction(cheap_first.clone()).unwrap();
+        mempool.add_transaction(rich_second.clone()).unwrap();
+        assert_eq!(
+            mempool.eviction_candidate().map(|(hash, _)| hash),
+            Some(rich_second.hash()),
+            "only the top of the chain can go"
+        );
+
+        let err = mempool
+            .add_transaction(create_test_tx(0, 200_000))
+            .unwrap_err();
+        assert!(err.to_string().contains("mempool full"));
+        mempool.add_transaction(create_test_tx(0, 500_000)).unwrap();
+        assert!(mempool.by_hash.contains_key(&cheap_first.hash()));
+        assert!(!mempool.by_hash.contains_key(&rich_second.hash()));
+
+        // The evicted nonce is expected again.
+        mempool.max_txs = 3;
+        mempool.add_transaction(rich_second.clone()).unwrap();
+        assert_eq!(mempool.queued_len(), 0);
+        let block = mempool.build_block(1_000_000, usize::MAX);
+        assert_eq!(block.len(), 3);
+        assert!(block.iter().any(|tx| tx.hash() == rich_second.hash()));
+    }
+
+    #[test]
+    fn test_rejected_replacement_keeps_original() {
+        let mut mempool = Mempool::with_defaults();
+        mempool.max_txs = 2;
+        let mut events = mempool.subscribe_status();
+        let kp = Keypair::generate();
+        let original = create_test_tx_with_keypair(&kp, 3, 60_000);
+        mempool.add_transaction(original.clone()).unwrap();
+        mempool.add_transaction(create_test_tx(0, 500_000)).unwrap();
+
+        // Pays enough to replace, but loses to the full pool's cheapest.
+        mempool.max_txs = 1;
+        let replacement = create_test_tx_with_keypair(&kp, 3, 100_000);
+        let err = mempool.add_transaction(replacement).unwrap_err();
+        assert!(err.to_string().contains("mempool full"));
+        assert!(mempool.by_hash.contains_key(&original.hash()));
+        assert_eq!(mempool.queued_len(), 1);
+        assert_eq!(mempool.next_nonce.get(&original.sender), None);
+
+        let statuses: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.status)
+            .collect();
+        assert_eq!(statuses, vec![TxLifecycle::Pending, TxLifecycle::Pending]);
+    }
+
+    #[test]
+    fn test_replacing_pending_tx_keeps_later_nonces() {
+        let mut mempool = Mempool::with_defaults();
+        let kp = Keypair::generate();
+        for nonce in 0..3 {
+            mempool
+                .add_transaction(create_test_tx_with_keypair(&kp, nonce, 60_000))
+                .unwrap();
+        }
+        let replacement = create_test_tx_with_keypair(&kp, 0, 100_000);
+        mempool.add_transaction(replacement.clone()).unwrap();
+
+        assert_eq!(mempool.len(), 3);
+        assert_eq!(mempool.queued_len(), 0);
+        mempool
+            .add_transaction(create_test_tx_with_keypair(&kp, 3, 60_000))
+            .unwrap();
+        assert_eq!(mempool.queued_len(), 0, "nonce 3 is still next");
+        let block = mempool.build_block(1_000_000, usize::MAX);
+        assert_eq!(block.len(), 4);
+        assert_eq!(block[0].hash(), replacement.hash());
+    }
+
     #[test]
     fn test_advance_sender_nonce_only_moves_forward() {
         let kp = Keypair::generate();
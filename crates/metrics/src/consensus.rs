@@ -4,6 +4,8 @@ use prometheus::{register_histogram, register_int_counter, Histogram, IntCounter
 pub struct ConsensusMetrics {
     pub slots_finalized: IntCounter,
     pub fork_events: IntCounter,
+    pub reorgs: IntCounter,
+    pub reorg_depth: Histogram,
     pub finality_latency_ms: Histogram,
     pub blocks_produced: IntCounter,
     pub blocks_received: IntCounter,
@@ -25,6 +27,17 @@ impl ConsensusMetrics {
                 "Observed fork events"
             )
             .expect("register fork_events"),
+            reorgs: register_int_counter!(
+                "aether_consensus_reorgs_total",
+                "Fork-choice head switches to a block off the previous head's chain"
+            )
+            .expect("register reorgs"),
+            reorg_depth: register_histogram!(
+                "aether_consensus_reorg_depth",
+                "Blocks abandoned on the old chain per reorg",
+                vec![1.0, 2.0, 4.0, 8.0, 16.0, 32.0, 64.0]
+            )
+            .expect("register reorg_depth"),
             finality_latency_ms: register_histogram!(
                 "aether_consensus_finality_latency_ms",
                 "Latency from block production to finality"
@@ -70,6 +83,8 @@ mod tests {
     fn increments_counters() {
         CONSENSUS_METRICS.slots_finalized.inc();
         CONSENSUS_METRICS.fork_events.inc_by(2);
+        CONSENSUS_METRICS.reorgs.inc();
+        CONSENSUS_METRICS.reorg_depth.observe(2.0);
         CONSENSUS_METRICS.finality_latency_ms.observe(42.0);
         CONSENSUS_METRICS.blocks_produced.inc();
         CONSENSUS_METRICS.blocks_received.inc_by(3);
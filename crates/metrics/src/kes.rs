@@ -0,0 +1,64 @@
+use once_cell::sync::Lazy;
+use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+
+pub struct KesMetrics {
+    /// KES period the local hot key is at.
+    pub current_period: IntGauge,
+    /// Periods left before the key can no longer evolve.
+    pub periods_remaining: IntGauge,
+    /// 1 when the key is within the warning window of exhaustion (or past it).
+    pub expiry_alert: IntGauge,
+    /// Automatic evolutions at period boundaries.
+    pub evolutions: IntCounter,
+    /// Signing requests refused because the key is exhausted.
+    pub exhausted_refusals: IntCounter,
+}
+
+impl KesMetrics {
+    fn new() -> Self {
+        KesMetrics {
+            current_period: register_int_gauge!(
+                "aether_kes_current_period",
+                "KES period of the local hot key"
+            )
+            .expect("register current_period"),
+            periods_remaining: register_int_gauge!(
+                "aether_kes_periods_remaining",
+                "KES periods left before the hot key is exhausted"
+            )
+            .expect("register periods_remaining"),
+            expiry_alert: register_int_gauge!(
+                "aether_kes_expiry_alert",
+                "Whether the KES key is close to or past exhaustion (1) or not (0)"
+            )
+            .expect("register expiry_alert"),
+            evolutions: register_int_counter!(
+                "aether_kes_evolutions_total",
+                "KES key evolutions at period boundaries"
+            )
+            .expect("register evolutions"),
+            exhausted_refusals: register_int_counter!(
+                "aether_kes_exhausted_refusals_total",
+                "Signatures refused because the KES key is exhausted"
+            )
+            .expect("register exhausted_refusals"),
+        }
+    }
+}
+
+pub static KES_METRICS: Lazy<KesMetrics> = Lazy::new(KesMetrics::new);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn updates_gauges_and_counters() {
+        KES_METRICS.current_period.set(3);
+        KES_METRICS.periods_remaining.set(60);
+        KES_METRICS.expiry_alert.set(0);
+        KES_METRICS.evolutions.inc();
+        KES_METRICS.exhausted_refusals.inc();
+        assert_eq!(KES_METRICS.current_period.get(), 3);
+    }
+}
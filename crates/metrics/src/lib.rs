@@ -9,6 +9,7 @@
 // - Runtime: tx_execution_time, parallel_speedup, gas_per_tx
 // - P2P: peer_count, message_rate, bandwidth
 // - AI: jobs_completed, vcr_challenge_rate, provider_reputation
+// - KES: current_period, periods_remaining, expiry_alert
 //
 // USAGE:
 //   METRICS.slot_finalized.inc();
@@ -19,6 +20,7 @@ pub mod ai;
 pub mod consensus;
 pub mod da;
 pub mod exporter;
+pub mod kes;
 pub mod mempool;
 pub mod networking;
 pub mod node;
@@ -30,6 +32,7 @@ pub mod storage;
 pub use ai::AI_METRICS;
 pub use consensus::CONSENSUS_METRICS;
 pub use da::DA_METRICS;
+pub use kes::KES_METRICS;
 pub use mempool::MEMPOOL_METRICS;
 pub use networking::NET_METRICS;
 pub use node::NODE_METRICS;
@@ -1,5 +1,8 @@
 use once_cell::sync::Lazy;
-use prometheus::{register_int_counter, register_int_gauge, IntCounter, IntGauge};
+use prometheus::{
+    register_int_counter, register_int_counter_vec, register_int_gauge, IntCounter, IntCounterVec,
+    IntGauge,
+};
 
 pub struct MempoolMetrics {
     /// Current number of transactions in the mempool (pending + queued).
@@ -8,10 +11,16 @@ pub struct MempoolMetrics {
     pub pending_size: IntGauge,
     /// Current number of queued (future-nonce) transactions.
     pub queued_size: IntGauge,
+    /// Current serialized size of all pooled transactions.
+    pub pool_bytes: IntGauge,
     /// Total transactions admitted to the mempool.
     pub admitted_total: IntCounter,
     /// Total transactions evicted due to capacity limits.
     pub evictions_total: IntCounter,
+    /// Total transactions dropped after outliving the pool TTL.
+    pub expired_total: IntCounter,
+    /// Total transactions rejected for paying below the base-fee floor.
+    pub underpriced_total: IntCounter,
     /// Total transactions rejected due to per-sender rate limiting.
     pub rate_limited_total: IntCounter,
     /// Total transactions rejected (all reasons: duplicate, low nonce, bad sig, etc.).
@@ -22,6 +31,9 @@ pub struct MempoolMetrics {
     pub rbf_replacements_total: IntCounter,
     /// Total reorg events processed.
     pub reorgs_total: IntCounter,
+    /// Transactions turned away before reaching the pool, per admission
+    /// stage (envelope, state, signature).
+    pub admission_rejected: IntCounterVec,
 }
 
 impl MempoolMetrics {
@@ -45,6 +57,12 @@ impl MempoolMetrics {
             )
             .expect("register mempool queued_size"),
 
+            pool_bytes: register_int_gauge!(
+                "aether_mempool_bytes",
+                "Current serialized size of all pooled transactions"
+            )
+            .expect("register mempool pool_bytes"),
+
             admitted_total: register_int_counter!(
                 "aether_mempool_admitted_total",
                 "Total transactions admitted to the mempool"
@@ -57,6 +75,18 @@ impl MempoolMetrics {
             )
             .expect("register mempool evictions_total"),
 
+            expired_total: register_int_counter!(
+                "aether_mempool_expired_total",
+                "Total transactions dropped after outliving the pool TTL"
+            )
+            .expect("register mempool expired_total"),
+
+            underpriced_total: register_int_counter!(
+                "aether_mempool_underpriced_total",
+                "Total transactions rejected for paying below the base-fee floor"
+            )
+            .expect("register mempool underpriced_total"),
+
             rate_limited_total: register_int_counter!(
                 "aether_mempool_rate_limited_total",
                 "Total transactions rejected due to per-sender rate limiting"
@@ -86,6 +116,13 @@ impl MempoolMetrics {
                 "Total reorg events processed by the mempool"
             )
             .expect("register mempool reorgs_total"),
+
+            admission_rejected: register_int_counter_vec!(
+                "aether_mempool_admission_rejected_total",
+                "Transactions rejected by the admission pipeline, labeled by stage",
+                &["stage"]
+            )
+            .expect("register mempool admission_rejected"),
         }
     }
 }
@@ -101,16 +138,24 @@ mod tests {
         MEMPOOL_METRICS.pool_size.set(42);
         MEMPOOL_METRICS.pending_size.set(30);
         MEMPOOL_METRICS.queued_size.set(12);
+        MEMPOOL_METRICS.pool_bytes.set(4096);
         MEMPOOL_METRICS.admitted_total.inc();
         MEMPOOL_METRICS.evictions_total.inc();
+        MEMPOOL_METRICS.expired_total.inc();
+        MEMPOOL_METRICS.underpriced_total.inc();
         MEMPOOL_METRICS.rate_limited_total.inc();
         MEMPOOL_METRICS.rejected_total.inc();
         MEMPOOL_METRICS.removed_total.inc();
         MEMPOOL_METRICS.rbf_replacements_total.inc();
         MEMPOOL_METRICS.reorgs_total.inc();
+        MEMPOOL_METRICS
+            .admission_rejected
+            .with_label_values(&["signature"])
+            .inc();
 
         assert_eq!(MEMPOOL_METRICS.pool_size.get(), 42);
         assert_eq!(MEMPOOL_METRICS.pending_size.get(), 30);
         assert_eq!(MEMPOOL_METRICS.queued_size.get(), 12);
+        assert_eq!(MEMPOOL_METRICS.pool_bytes.get(), 4096);
     }
 }
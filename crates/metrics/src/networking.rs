@@ -17,6 +17,15 @@ pub struct NetworkingMetrics {
     pub quic_rtt_ms: Histogram,
     pub quic_streams_opened: IntCounter,
     pub quic_streams_closed: IntCounter,
+    pub quic_packets_lost: IntCounter,
+    pub quic_congestion_events: IntCounter,
+    pub quic_connections_reused: IntCounter,
+    pub quic_zero_rtt_accepted: IntCounter,
+    pub quic_zero_rtt_rejected: IntCounter,
+    pub quic_path_migrations: IntCounter,
+    pub quic_datagrams_sent: IntCounter,
+    pub quic_datagrams_received: IntCounter,
+    pub quic_datagram_fallbacks: IntCounter,
 
     // Message metrics
     pub messages_sent: IntCounter,
@@ -93,6 +102,60 @@ impl NetworkingMetrics {
             )
             .expect("register quic_streams_closed"),
 
+            quic_packets_lost: register_int_counter!(
+                "aether_net_quic_packets_lost_total",
+                "Total QUIC packets declared lost"
+            )
+            .expect("register quic_packets_lost"),
+
+            quic_congestion_events: register_int_counter!(
+                "aether_net_quic_congestion_events_total",
+                "Total QUIC congestion events"
+            )
+            .expect("register quic_congestion_events"),
+
+            quic_connections_reused: register_int_counter!(
+                "aether_net_quic_connections_reused_total",
+                "Sends that reused a pooled QUIC connection instead of dialing"
+            )
+            .expect("register quic_connections_reused"),
+
+            quic_zero_rtt_accepted: register_int_counter!(
+                "aether_net_quic_zero_rtt_accepted_total",
+                "QUIC connections resumed with 0-RTT data the server accepted"
+            )
+            .expect("register quic_zero_rtt_accepted"),
+
+            quic_zero_rtt_rejected: register_int_counter!(
+                "aether_net_quic_zero_rtt_rejected_total",
+                "QUIC connections whose 0-RTT data the server rejected"
+            )
+            .expect("register quic_zero_rtt_rejected"),
+
+            quic_path_migrations: register_int_counter!(
+                "aether_net_quic_path_migrations_total",
+                "QUIC connections that moved to a new network path"
+            )
+            .expect("register quic_path_migrations"),
+
+            quic_datagrams_sent: register_int_counter!(
+                "aether_net_quic_datagrams_sent_total",
+                "Shreds sent as unreliable QUIC datagrams"
+            )
+            .expect("register quic_datagrams_sent"),
+
+            quic_datagrams_received: register_int_counter!(
+                "aether_net_quic_datagrams_received_total",
+                "Shreds received as QUIC datagrams"
+            )
+            .expect("register quic_datagrams_received"),
+
+            quic_datagram_fallbacks: register_int_counter!(
+                "aether_net_quic_datagram_fallbacks_total",
+                "Shreds sent on streams because the peer lacks datagram support or the shred does not fit"
+            )
+            .expect("register quic_datagram_fallbacks"),
+
             messages_sent: register_int_counter!(
                 "aether_net_messages_sent_total",
                 "Total messages sent"
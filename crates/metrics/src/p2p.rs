@@ -1,5 +1,8 @@
 use once_cell::sync::Lazy;
-use prometheus::{register_int_counter, register_int_counter_vec, IntCounter, IntCounterVec};
+use prometheus::{
+    register_int_counter, register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
+    IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+};
 
 /// Per-topic gossipsub metrics for production observability.
 ///
@@ -16,6 +19,40 @@ pub struct P2PMetrics {
     pub messages_dropped_rate_limited: IntCounter,
     /// Peers banned (score below threshold).
     pub peers_banned: IntCounter,
+    /// Messages dropped from greylisted peers.
+    pub messages_dropped_greylisted: IntCounter,
+    /// Peers that fell below the greylist threshold.
+    pub peers_greylisted: IntCounter,
+    /// Reputation penalties applied, per violation kind.
+    pub peer_penalties: IntCounterVec,
+    /// Inbound peers holding a connection slot, per class (staked, unstaked).
+    pub inbound_peers_by_class: IntGaugeVec,
+    /// Inbound peers turned away because their class had no free slot.
+    pub inbound_rejected_by_class: IntCounterVec,
+    /// Inbound peers disconnected to make room for a staked peer.
+    pub inbound_evicted: IntCounter,
+    /// Messages dropped because the sender's bandwidth quota ran out, per
+    /// class.
+    pub messages_dropped_throttled: IntCounterVec,
+    /// Whether peers can dial this node: 0 unknown, 1 public, 2 behind NAT.
+    pub nat_status: IntGauge,
+    /// Topic validator results, per topic and result (accept, reject,
+    /// ignore).
+    pub messages_validated: IntCounterVec,
+    /// Messages dropped unvalidated because the validation queue was full,
+    /// per topic.
+    pub validation_queue_full: IntCounterVec,
+    /// Outbound messages sent zstd-compressed, per topic.
+    pub messages_compressed: IntCounterVec,
+    /// Bytes saved by compressing outbound messages, per topic.
+    pub compression_bytes_saved: IntCounterVec,
+    /// Outbound messages waiting for bandwidth, per traffic class (vote,
+    /// header, shred, tx).
+    pub outbound_queued: IntGaugeVec,
+    /// Outbound messages dropped because their class's queue was full.
+    pub outbound_dropped: IntCounterVec,
+    /// Outbound bytes handed to gossipsub, per traffic class.
+    pub outbound_bytes: IntCounterVec,
 }
 
 impl P2PMetrics {
@@ -48,6 +85,92 @@ impl P2PMetrics {
                 "Total peers banned due to low reputation score"
             )
             .expect("register peers_banned"),
+            messages_dropped_greylisted: register_int_counter!(
+                "aether_p2p_messages_dropped_greylisted_total",
+                "Messages dropped from greylisted peers"
+            )
+            .expect("register messages_dropped_greylisted"),
+            peers_greylisted: register_int_counter!(
+                "aether_p2p_peers_greylisted_total",
+                "Peers greylisted due to low reputation score"
+            )
+            .expect("register peers_greylisted"),
+            peer_penalties: register_int_counter_vec!(
+                "aether_p2p_peer_penalties_total",
+                "Reputation penalties applied to peers, labeled by violation",
+                &["violation"]
+            )
+            .expect("register peer_penalties"),
+            inbound_peers_by_class: register_int_gauge_vec!(
+                "aether_p2p_inbound_peers",
+                "Inbound peers holding a connection slot, labeled by stake class",
+                &["class"]
+            )
+            .expect("register inbound_peers_by_class"),
+            inbound_rejected_by_class: register_int_counter_vec!(
+                "aether_p2p_inbound_rejected_total",
+                "Inbound peers rejected for lack of a slot, labeled by stake class",
+                &["class"]
+            )
+            .expect("register inbound_rejected_by_class"),
+            inbound_evicted: register_int_counter!(
+                "aether_p2p_inbound_evicted_total",
+                "Inbound peers disconnected to make room for a staked peer"
+            )
+            .expect("register inbound_evicted"),
+            messages_dropped_throttled: register_int_counter_vec!(
+                "aether_p2p_messages_dropped_throttled_total",
+                "Messages dropped over the sender's bandwidth quota, labeled by stake class",
+                &["class"]
+            )
+            .expect("register messages_dropped_throttled"),
+            nat_status: register_int_gauge!(
+                "aether_p2p_nat_status",
+                "Reachability of this node: 0 unknown, 1 public, 2 behind NAT"
+            )
+            .expect("register nat_status"),
+            messages_validated: register_int_counter_vec!(
+                "aether_p2p_messages_validated_total",
+                "Gossip messages checked by topic validators, labeled by topic and result",
+                &["topic", "result"]
+            )
+            .expect("register messages_validated"),
+            validation_queue_full: register_int_counter_vec!(
+                "aether_p2p_validation_queue_full_total",
+                "Gossip messages dropped because the validation queue was full, labeled by topic",
+                &["topic"]
+            )
+            .expect("register validation_queue_full"),
+            messages_compressed: register_int_counter_vec!(
+                "aether_p2p_messages_compressed_total",
+                "Outbound gossip messages sent zstd-compressed, labeled by topic",
+                &["topic"]
+            )
+            .expect("register messages_compressed"),
+            compression_bytes_saved: register_int_counter_vec!(
+                "aether_p2p_compression_bytes_saved_total",
+                "Bytes saved by compressing outbound gossip messages, labeled by topic",
+                &["topic"]
+            )
+            .expect("register compression_bytes_saved"),
+            outbound_queued: register_int_gauge_vec!(
+                "aether_p2p_outbound_queued",
+                "Outbound gossip messages waiting for bandwidth, labeled by traffic class",
+                &["class"]
+            )
+            .expect("register outbound_queued"),
+            outbound_dropped: register_int_counter_vec!(
+                "aether_p2p_outbound_dropped_total",
+                "Outbound gossip messages dropped because their class queue was full, labeled by traffic class",
+                &["class"]
+            )
+            .expect("register outbound_dropped"),
+            outbound_bytes: register_int_counter_vec!(
+                "aether_p2p_outbound_bytes_total",
+                "Outbound gossip bytes released by the bandwidth scheduler, labeled by traffic class",
+                &["class"]
+            )
+            .expect("register outbound_bytes"),
         }
     }
 }
@@ -66,6 +189,10 @@ pub fn topic_label(topic: &str) -> &'static str {
         "shred"
     } else if topic.contains("/sync") {
         "sync"
+    } else if topic.contains("/round") {
+        "round"
+    } else if topic.contains("/vcr") {
+        "vcr"
     } else {
         "unknown"
     }
@@ -115,6 +242,8 @@ mod tests {
         assert_eq!(topic_label("/aether/1/vote"), "vote");
         assert_eq!(topic_label("/aether/1/shred"), "shred");
         assert_eq!(topic_label("/aether/1/sync"), "sync");
+        assert_eq!(topic_label("/aether/1/round"), "round");
+        assert_eq!(topic_label("/aether/1/vcr"), "vcr");
         assert_eq!(topic_label("/aether/1/unknown"), "unknown");
     }
 }
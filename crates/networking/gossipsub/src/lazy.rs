@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+/// Settings for episub-style lazy push.
+///
+/// On a lazy topic, a large message is pushed in full to only part of the
+/// mesh; the rest get an IHAVE with its ID and fetch it with IWANT if no
+/// one else delivers it first. A peer that already has a message says so
+/// with IDONTWANT, so its neighbours stop pushing it. The bigger the mesh,
+/// the more full copies this saves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazyPushConfig {
+    /// Topics lazy push applies to. Everything else is pushed eagerly.
+    pub topics: HashSet<String>,
+    /// Messages smaller than this are always pushed in full: an IHAVE and
+    /// IWANT round trip would cost more than it saves.
+    pub min_payload: usize,
+    /// Fraction of the mesh sent an IHAVE instead of the message.
+    pub gossip_factor: f64,
+    /// Mesh peers that always get the full message, however large the mesh.
+    pub min_eager: usize,
+    /// Recent lazy messages kept to answer IWANT.
+    pub history: usize,
+    /// Message IDs requested or served per IWANT.
+    pub max_iwant: usize,
+    /// Heartbeats to wait for an IWANT to be answered before asking
+    /// someone else and counting it against the peer that announced it.
+    pub iwant_timeout: u64,
+}
+
+impl LazyPushConfig {
+    /// Lazy push on `topics`, with default settings.
+    pub fn for_topics<I, S>(topics: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        LazyPushConfig {
+            topics: topics.into_iter().map(Into::into).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether a `len`-byte message on `topic` is pushed lazily.
+    pub fn applies(&self, topic: &str, len: usize) -> bool {
+        len >= self.min_payload && self.topics.contains(topic)
+    }
+
+    /// How many of `mesh_len` peers get the full message.
+    pub fn eager_count(&self, mesh_len: usize) -> usize {
+        let factor = self.gossip_factor.clamp(0.0, 1.0);
+        let lazy = (mesh_len as f64 * factor).floor() as usize;
+        mesh_len
+            .saturating_sub(lazy)
+            .max(self.min_eager)
+            .min(mesh_len)
+    }
+}
+
+impl Default for LazyPushConfig {
+    fn default() -> Self {
+        LazyPushConfig {
+            topics: HashSet::new(),
+            min_payload: 16 * 1024,
+            gossip_factor: 0.5,
+            min_eager: 2,
+            history: 128,
+            max_iwant: 32,
+            iwant_timeout: 3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{GossipOutcome, GossipRouter};
+    use libp2p::PeerId;
+    use std::collections::VecDeque;
+
+    #[test]
+    fn eager_count_respects_factor_and_floor() {
+        let config = LazyPushConfig::default();
+        assert_eq!(config.eager_count(0), 0);
+        assert_eq!(config.eager_count(1), 1);
+        assert_eq!(config.eager_count(3), 2);
+        assert_eq!(config.eager_count(8), 4);
+        assert_eq!(config.eager_count(12), 6);
+
+        let all_lazy = LazyPushConfig {
+            gossip_factor: 1.0,
+            ..config
+        };
+        assert_eq!(all_lazy.eager_count(12), 2);
+        assert!(!all_lazy.applies("shred", 1 << 20));
+        let shreds = LazyPushConfig::for_topics(["shred"]);
+        assert!(shreds.applies("shred", 1 << 20));
+        assert!(!shreds.applies("shred", 100));
+    }
+
+    enum Wire {
+        Full(String, Vec<u8>),
+        IHave(String, Vec<[u8; 32]>),
+        IWant(Vec<[u8; 32]>),
+        IDontWant(Vec<[u8; 32]>),
+    }
+
+    impl Wire {
+        fn bytes(&self) -> usize {
+            match self {
+                Wire::Full(_, data) => data.len(),
+                Wire::IHave(_, ids) | Wire::IWant(ids) | Wire::IDontWant(ids) => ids.len() * 32,
+            }
+        }
+    }
+
+    /// A network of `n` routers, each meshed with at least `degree` others
+    /// chosen by a seeded generator.
+    struct Sim {
+        peers: Vec<PeerId>,
+        routers: Vec<GossipRouter>,
+        queue: VecDeque<(usize, usize, Wire)>,
+        bytes: usize,
+    }
+
+    impl Sim {
+        fn new(n: usize, degree: usize, lazy: Option<LazyPushConfig>) -> Self {
+            let peers: Vec<PeerId> = (0..n).map(|_| PeerId::random()).collect();
+            let mut routers: Vec<GossipRouter> = (0..n)
+                .map(|_| match &lazy {
+                    Some(config) => GossipRouter::with_lazy_push(config.clone()),
+                    None => GossipRouter::new(),
+                })
+                .collect();
+            let mut seed = 0x2545_f491_4f6c_dd1du64;
+            for a in 0..n {
+                let mut linked = 0;
+                while linked < degree {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    let b = (seed % n as u64) as usize;
+                    if b == a {
+                        continue;
+                    }
+                    routers[a].mesh_mut().join("shred", peers[b]);
+                    routers[b].mesh_mut().join("shred", peers[a]);
+                    linked += 1;
+                }
+            }
+            Sim {
+                peers,
+                routers,
+                queue: VecDeque::new(),
+                bytes: 0,
+            }
+        }
+
+        fn index(&self, peer: &PeerId) -> usize {
+            self.peers.iter().position(|p| p == peer).unwrap()
+        }
+
+        fn send(&mut self, from: usize, to: &PeerId, wire: Wire) {
+            let to = self.index(to);
+            self.bytes += wire.bytes();
+            self.queue.push_back((from, to, wire));
+        }
+
+        fn fan_out(&mut self, node: usize, data: &[u8], outcome: GossipOutcome) {
+            for peer in &outcome.forwarded_to {
+                self.send(node, peer, Wire::Full("shred".into(), data.to_vec()));
+            }
+            for peer in &outcome.announce_to {
+                self.send(
+                    node,
+                    peer,
+                    Wire::IHave("shred".into(), vec![outcome.message_id]),
+                );
+            }
+            for peer in &outcome.dont_want_to {
+                self.send(node, peer, Wire::IDontWant(vec![outcome.message_id]));
+            }
+        }
+
+        /// Publish one message from node 0, deliver everything, and return
+        /// how many nodes got it.
+        fn run(&mut self, data: Vec<u8>) -> usize {
+            let outcome = self.routers[0].publish("shred", data.clone());
+            self.fan_out(0, &data, outcome);
+            let mut rounds = 0;
+            while let Some((from, to, wire)) = self.queue.pop_front() {
+                let sender = self.peers[from];
+                match wire {
+                    Wire::Full(topic, data) => {
+                        let outcome = self.routers[to].receive(&sender, &topic, data.clone());
+                        if outcome.delivered {
+                            self.fan_out(to, &data, outcome);
+                        }
+                    }
+                    Wire::IHave(topic, ids) => {
+                        let wanted = self.routers[to].handle_ihave(&sender, &topic, &ids);
+                        if !wanted.is_empty() {
+                            self.send(to, &sender, Wire::IWant(wanted));
+                        }
+                    }
+                    Wire::IWant(ids) => {
+                        for (topic, data) in self.routers[to].handle_iwant(&sender, &ids) {
+                            self.send(to, &sender, Wire::Full(topic, data));
+                        }
+                    }
+                    Wire::IDontWant(ids) => self.routers[to].handle_idontwant(&sender, &ids),
+                }
+                rounds += 1;
+                assert!(rounds < 1_000_000, "simulation did not settle");
+            }
+            self.routers
+                .iter()
+                .filter(|r| !r.delivered_messages("shred").is_empty())
+                .count()
+        }
+    }
+
+    #[test]
+    fn lazy_push_reaches_everyone_with_less_bandwidth() {
+        let payload = vec![7u8; 64 * 1024];
+        for (n, degree) in [(30, 4), (60, 6), (100, 8)] {
+            let mut eager = Sim::new(n, degree, None);
+            assert_eq!(eager.run(payload.clone()), n);
+
+            let mut lazy = Sim::new(n, degree, Some(LazyPushConfig::for_topics(["shred"])));
+            assert_eq!(lazy.run(payload.clone()), n, "lazy push left nodes out");
+            assert!(
+                lazy.bytes * 10 < eager.bytes * 7,
+                "n={n} degree={degree}: lazy {} bytes vs eager {}",
+                lazy.bytes,
+                eager.bytes
+            );
+        }
+    }
+
+    #[test]
+    fn small_messages_stay_eager() {
+        let mut eager = Sim::new(30, 4, None);
+        let mut lazy = Sim::new(30, 4, Some(LazyPushConfig::for_topics(["shred"])));
+        assert_eq!(eager.run(vec![1u8; 512]), 30);
+        assert_eq!(lazy.run(vec![1u8; 512]), 30);
+        assert_eq!(lazy.bytes, eager.bytes);
+    }
+}
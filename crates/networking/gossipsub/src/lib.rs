@@ -26,6 +26,11 @@
 // - D peers in mesh per topic (target: 8)
 // - Periodic GRAFT/PRUNE messages
 // - Peer scoring (deliver quickly, valid messages)
+// - Lazy push (episub) on chosen topics: large messages go in full to
+//   part of the mesh and as IHAVE to the rest, who fetch with IWANT;
+//   IDONTWANT stops neighbours pushing what we already have
+// - Peer reputation: decaying scores, weighted penalties per violation,
+//   greylist and ban thresholds, ban list persisted across restarts
 //
 // PSEUDOCODE:
 // ```
@@ -72,8 +77,11 @@
 // - Propagation metrics → Monitoring
 // ============================================================================
 
+pub mod lazy;
 pub mod mesh;
 pub mod router;
 pub mod scoring;
 
-pub use router::GossipRouter;
+pub use lazy::LazyPushConfig;
+pub use router::{GossipOutcome, GossipRouter};
+pub use scoring::{PeerReputation, PenaltyWeights, ReputationConfig, Standing, Violation};
@@ -3,6 +3,7 @@ use std::collections::{HashMap, HashSet, VecDeque};
 use libp2p::PeerId;
 use sha2::{Digest, Sha256};
 
+use crate::lazy::LazyPushConfig;
 use crate::mesh::Mesh;
 use crate::scoring::PeerScores;
 
@@ -13,6 +14,9 @@ const MAX_SEEN_CACHE: usize = 100_000;
 /// Maximum number of delivered message payloads retained per topic.
 const MAX_DELIVERED_PER_TOPIC: usize = 10_000;
 
+/// IDONTWANT message IDs remembered per peer.
+const MAX_DONT_WANT_PER_PEER: usize = 1_024;
+
 #[derive(Default)]
 pub struct GossipRouter {
     mesh: Mesh,
@@ -20,11 +24,38 @@ pub struct GossipRouter {
     seen: HashSet<[u8; 32]>,
     seen_order: VecDeque<[u8; 32]>,
     delivered: HashMap<String, VecDeque<Vec<u8>>>,
+    lazy: LazyPushConfig,
+    /// Recent lazy messages by ID, to answer IWANT.
+    history: HashMap<[u8; 32], (String, Vec<u8>)>,
+    history_order: VecDeque<[u8; 32]>,
+    /// Messages each peer told us it already has.
+    dont_want: HashMap<PeerId, VecDeque<[u8; 32]>>,
+    /// Outstanding IWANTs: who promised the message, and when we asked.
+    pending: HashMap<[u8; 32], (PeerId, u64)>,
+    heartbeats: u64,
 }
 
 pub struct GossipOutcome {
     pub delivered: bool,
+    pub message_id: [u8; 32],
+    /// Peers to send the full message.
     pub forwarded_to: Vec<PeerId>,
+    /// Peers to send an IHAVE for the message instead.
+    pub announce_to: Vec<PeerId>,
+    /// Peers to send an IDONTWANT, so they stop pushing the message to us.
+    pub dont_want_to: Vec<PeerId>,
+}
+
+impl GossipOutcome {
+    fn dropped(message_id: [u8; 32]) -> Self {
+        GossipOutcome {
+            delivered: false,
+            message_id,
+            forwarded_to: Vec::new(),
+            announce_to: Vec::new(),
+            dont_want_to: Vec::new(),
+        }
+    }
 }
 
 impl GossipRouter {
@@ -32,6 +63,18 @@ impl GossipRouter {
         GossipRouter::default()
     }
 
+    /// A router that pushes large messages on `config.topics` lazily.
+    pub fn with_lazy_push(config: LazyPushConfig) -> Self {
+        GossipRouter {
+            lazy: config,
+            ..Self::default()
+        }
+    }
+
+    pub fn lazy_push(&self) -> &LazyPushConfig {
+        &self.lazy
+    }
+
     pub fn mesh_mut(&mut self) -> &mut Mesh {
         &mut self.mesh
     }
@@ -46,32 +89,22 @@ impl GossipRouter {
     pub fn publish(&mut self, topic: &str, data: Vec<u8>) -> GossipOutcome {
         let id = Self::message_id(topic, &data);
         if !self.insert_seen(id) {
-            return GossipOutcome {
-                delivered: false,
-                forwarded_to: Vec::new(),
-            };
+            return GossipOutcome::dropped(id);
         }
 
         let peers = self.mesh.peers(topic);
         for peer in &peers {
             self.scores.record_success(peer);
         }
-        self.push_delivered(topic, data);
-
-        GossipOutcome {
-            delivered: true,
-            forwarded_to: peers,
-        }
+        self.spread(id, topic, data, peers, false)
     }
 
     pub fn receive(&mut self, from: &PeerId, topic: &str, data: Vec<u8>) -> GossipOutcome {
         let id = Self::message_id(topic, &data);
+        self.pending.remove(&id);
         if !self.insert_seen(id) {
             self.scores.record_failure(from);
-            return GossipOutcome {
-                delivered: false,
-                forwarded_to: Vec::new(),
-            };
+            return GossipOutcome::dropped(id);
         }
 
         self.scores.record_success(from);
@@ -81,12 +114,138 @@ impl GossipRouter {
             .into_iter()
             .filter(|peer| peer != from)
             .collect();
+        self.spread(id, topic, data, peers, true)
+    }
 
-        self.push_delivered(topic, data);
+    /// `from` announced `ids` on `topic`. Returns the ones to ask it for:
+    /// those we have not seen and are not already waiting on.
+    pub fn handle_ihave(&mut self, from: &PeerId, topic: &str, ids: &[[u8; 32]]) -> Vec<[u8; 32]> {
+        if !self.lazy.topics.contains(topic) {
+            return Vec::new();
+        }
+        let mut wanted = Vec::new();
+        for id in ids.iter().take(self.lazy.max_iwant) {
+            if self.seen.contains(id) || self.pending.contains_key(id) {
+                continue;
+            }
+            self.pending.insert(*id, (*from, self.heartbeats));
+            wanted.push(*id);
+        }
+        wanted
+    }
+
+    /// `from` asked for `ids`. Returns the topic and payload of each one
+    /// still in the history.
+    pub fn handle_iwant(&mut self, from: &PeerId, ids: &[[u8; 32]]) -> Vec<(String, Vec<u8>)> {
+        let served: Vec<_> = ids
+            .iter()
+            .take(self.lazy.max_iwant)
+            .filter_map(|id| self.history.get(id).cloned())
+            .collect();
+        if !served.is_empty() {
+            self.scores.record_success(from);
+        }
+        served
+    }
+
+    /// `from` already has `ids`; stop pushing them to it.
+    pub fn handle_idontwant(&mut self, from: &PeerId, ids: &[[u8; 32]]) {
+        let known = self.dont_want.entry(*from).or_default();
+        for id in ids {
+            known.push_back(*id);
+        }
+        while known.len() > MAX_DONT_WANT_PER_PEER {
+            known.pop_front();
+        }
+    }
+
+    /// Expire IWANTs that went unanswered, so the next IHAVE for them is
+    /// followed up, and mark down the peers that broke their promise.
+    pub fn heartbeat(&mut self) {
+        self.heartbeats += 1;
+        let deadline = self.heartbeats.saturating_sub(self.lazy.iwant_timeout);
+        let scores = &mut self.scores;
+        self.pending.retain(|_, (peer, asked)| {
+            let waiting = *asked > deadline;
+            if !waiting {
+                scores.record_failure(peer);
+            }
+            waiting
+        });
+    }
 
+    /// Forget lazy push state kept for `peer`.
+    pub fn remove_peer(&mut self, peer: &PeerId) {
+        self.dont_want.remove(peer);
+        self.pending
+            .retain(|_, (promised_by, _)| promised_by != peer);
+    }
+
+    /// Deliver a new message and split `peers` into those sent it in full
+    /// and, for lazy messages, those only told about it.
+    fn spread(
+        &mut self,
+        id: [u8; 32],
+        topic: &str,
+        data: Vec<u8>,
+        peers: Vec<PeerId>,
+        received: bool,
+    ) -> GossipOutcome {
+        if !self.lazy.applies(topic, data.len()) {
+            self.push_delivered(topic, data);
+            return GossipOutcome {
+                delivered: true,
+                message_id: id,
+                forwarded_to: peers,
+                announce_to: Vec::new(),
+                dont_want_to: Vec::new(),
+            };
+        }
+
+        let dont_want_to = if received { peers.clone() } else { Vec::new() };
+        let mut peers: Vec<PeerId> = peers
+            .into_iter()
+            .filter(|peer| {
+                !self
+                    .dont_want
+                    .get(peer)
+                    .is_some_and(|ids| ids.contains(&id))
+            })
+            .collect();
+        // Push in full to the best-scoring peers; they are the likeliest
+        // to pass it on promptly.
+        peers.sort_by(|a, b| {
+            self.scores
+                .score(b)
+                .total_cmp(&self.scores.score(a))
+                .then_with(|| a.cmp(b))
+        });
+        let announce_to = peers.split_off(self.lazy.eager_count(peers.len()));
+
+        self.remember(id, topic, &data);
+        self.push_delivered(topic, data);
         GossipOutcome {
             delivered: true,
+            message_id: id,
             forwarded_to: peers,
+            announce_to,
+            dont_want_to,
+        }
+    }
+
+    /// Keep a lazy message to answer IWANT, evicting the oldest.
+    fn remember(&mut self, id: [u8; 32], topic: &str, data: &[u8]) {
+        if self
+            .history
+            .insert(id, (topic.to_string(), data.to_vec()))
+            .is_none()
+        {
+            self.history_order.push_back(id);
+        }
+        while self.history.len() > self.lazy.history {
+            if let Some(old) = self.history_order.pop_front() {
+                self.history.remove(&old);
+            }
         }
     }
 
@@ -141,6 +300,60 @@ mod tests {
         assert!(outcome.forwarded_to.is_empty());
     }
 
+    #[test]
+    fn lazy_push_announces_and_serves_iwant() {
+        let mut config = LazyPushConfig::for_topics(["shred"]);
+        config.min_payload = 8;
+        config.min_eager = 1;
+        let mut router = GossipRouter::with_lazy_push(config);
+        let peers: Vec<PeerId> = (0..4).map(|_| PeerId::random()).collect();
+        for peer in &peers {
+            router.mesh_mut().join("shred", *peer);
+        }
+
+        let outcome = router.receive(&peers[0], "shred", b"large shred".to_vec());
+        assert_eq!(outcome.forwarded_to.len() + outcome.announce_to.len(), 3);
+        assert_eq!(outcome.forwarded_to.len(), 2);
+        assert_eq!(outcome.dont_want_to.len(), 3);
+        assert!(!outcome.dont_want_to.contains(&peers[0]));
+
+        let served = router.handle_iwant(&outcome.announce_to[0], &[outcome.message_id]);
+        assert_eq!(served, vec![("shred".to_string(), b"large shred".to_vec())]);
+        assert!(router.handle_iwant(&peers[1], &[[9u8; 32]]).is_empty());
+
+        // Peers that said they have the message are skipped entirely.
+        router.handle_idontwant(
+            &peers[1],
+            &[GossipRouter::message_id("shred", b"second shred")],
+        );
+        let outcome = router.publish("shred", b"second shred".to_vec());
+        assert!(!outcome.forwarded_to.contains(&peers[1]));
+        assert!(!outcome.announce_to.contains(&peers[1]));
+        assert_eq!(outcome.forwarded_to.len() + outcome.announce_to.len(), 3);
+    }
+
+    #[test]
+    fn unanswered_iwant_is_retried_after_timeout() {
+        let mut router = GossipRouter::with_lazy_push(LazyPushConfig::for_topics(["shred"]));
+        let (liar, honest) = (PeerId::random(), PeerId::random());
+        let id = GossipRouter::message_id("shred", b"missing");
+
+        assert_eq!(router.handle_ihave(&liar, "shred", &[id]), vec![id]);
+        // Already asked someone.
+        assert!(router.handle_ihave(&honest, "shred", &[id]).is_empty());
+        // Not a lazy topic.
+        assert!(router.handle_ihave(&honest, "tx", &[id]).is_empty());
+
+        for _ in 0..router.lazy_push().iwant_timeout {
+            router.heartbeat();
+        }
+        assert!(router.scores.score(&liar) < 0.0);
+        assert_eq!(router.handle_ihave(&honest, "shred", &[id]), vec![id]);
+
+        router.receive(&honest, "shred", b"missing".to_vec());
+        assert!(router.handle_ihave(&liar, "shred", &[id]).is_empty());
+    }
+
     #[test]
     fn seen_cache_evicts_oldest() {
         let mut router = GossipRouter::new();
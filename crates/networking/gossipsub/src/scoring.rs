@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::path::Path;
 
 use libp2p::PeerId;
 
@@ -102,6 +103,323 @@ impl PeerScores {
     }
 }
 
+/// Misbehaviour a peer can be penalized for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Violation {
+    /// A shred that fails its Merkle proof or does not decode.
+    InvalidShred,
+    /// A vote for a slot that is already past.
+    LateVote,
+    /// Messages beyond the peer's rate limit.
+    Spam,
+    /// A message over its topic's size limit.
+    Oversized,
+    /// Any other message that fails to decode or validate.
+    InvalidMessage,
+}
+
+impl Violation {
+    /// Short name for metric labels and logs.
+    pub fn label(self) -> &'static str {
+        match self {
+            Violation::InvalidShred => "invalid_shred",
+            Violation::LateVote => "late_vote",
+            Violation::Spam => "spam",
+            Violation::Oversized => "oversized",
+            Violation::InvalidMessage => "invalid_message",
+        }
+    }
+}
+
+/// Score deducted for each kind of violation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PenaltyWeights {
+    pub invalid_shred: f64,
+    pub late_vote: f64,
+    pub spam: f64,
+    pub oversized: f64,
+    pub invalid_message: f64,
+}
+
+impl Default for PenaltyWeights {
+    fn default() -> Self {
+        PenaltyWeights {
+            invalid_shred: 25.0,
+            // Honest validators on slow links vote late now and then.
+            late_vote: 2.0,
+            spam: 20.0,
+            oversized: 10.0,
+            invalid_message: 10.0,
+        }
+    }
+}
+
+impl PenaltyWeights {
+    pub fn weight(&self, violation: Violation) -> f64 {
+        match violation {
+            Violation::InvalidShred => self.invalid_shred,
+            Violation::LateVote => self.late_vote,
+            Violation::Spam => self.spam,
+            Violation::Oversized => self.oversized,
+            Violation::InvalidMessage => self.invalid_message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReputationConfig {
+    pub weights: PenaltyWeights,
+    /// Added for each valid message.
+    pub reward: f64,
+    /// Ceiling on a score, so good behaviour cannot bank unlimited credit.
+    pub max_score: f64,
+    /// Seconds for a score to decay halfway back to zero.
+    pub half_life_secs: u64,
+    /// Below this a peer's messages are ignored, but it stays connected.
+    pub greylist_threshold: f64,
+    /// Below this a peer is disconnected and banned.
+    pub ban_threshold: f64,
+    pub ban_duration_secs: u64,
+    pub max_tracked_peers: usize,
+    pub max_banned_peers: usize,
+}
+
+impl Default for ReputationConfig {
+    fn default() -> Self {
+        ReputationConfig {
+            weights: PenaltyWeights::default(),
+            reward: 0.5,
+            max_score: 20.0,
+            half_life_secs: 600,
+            greylist_threshold: -50.0,
+            ban_threshold: -100.0,
+            ban_duration_secs: 3600,
+            max_tracked_peers: MAX_TRACKED_PEERS,
+            max_banned_peers: 4_096,
+        }
+    }
+}
+
+/// What the network should do with a peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Standing {
+    Good,
+    /// Connected, but its messages are dropped until its score recovers.
+    Greylisted,
+    /// Disconnected and refused until the ban expires.
+    Banned,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Reputation {
+    score: f64,
+    /// Unix seconds `score` was last brought up to date.
+    updated: u64,
+}
+
+impl Reputation {
+    fn decayed(&self, now: u64, half_life_secs: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.updated) as f64;
+        self.score * 0.5f64.powf(elapsed / half_life_secs.max(1) as f64)
+    }
+}
+
+const BAN_LIST_HEADER: &str = "# aether ban list v1: <peer id> <expiry unix secs>";
+
+/// Long-lived peer reputation: scores that decay toward zero, weighted
+/// penalties per violation, and a ban list that survives restarts.
+///
+/// Times are unix seconds passed in by the caller. Scores are kept across
+/// disconnects, so a peer cannot shed a bad score by reconnecting.
+#[derive(Debug, Default)]
+pub struct PeerReputation {
+    config: ReputationConfig,
+    scores: HashMap<PeerId, Reputation>,
+    bans: HashMap<PeerId, u64>,
+}
+
+impl PeerReputation {
+    pub fn new(config: ReputationConfig) -> Self {
+        PeerReputation {
+            config,
+            scores: HashMap::new(),
+            bans: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &ReputationConfig {
+        &self.config
+    }
+
+    pub fn score(&self, peer: &PeerId, now: u64) -> f64 {
+        self.scores
+            .get(peer)
+            .map_or(0.0, |rep| rep.decayed(now, self.config.half_life_secs))
+    }
+
+    pub fn standing(&self, peer: &PeerId, now: u64) -> Standing {
+        if self.is_banned(peer, now) {
+            Standing::Banned
+        } else if self.score(peer, now) < self.config.greylist_threshold {
+            Standing::Greylisted
+        } else {
+            Standing::Good
+        }
+    }
+
+    pub fn record_valid(&mut self, peer: &PeerId, now: u64) -> Standing {
+        self.adjust(peer, self.config.reward, now)
+    }
+
+    pub fn penalize(&mut self, peer: &PeerId, violation: Violation, now: u64) -> Standing {
+        self.adjust(peer, -self.config.weights.weight(violation), now)
+    }
+
+    /// Add `delta` to the peer's decayed score, banning it if the result
+    /// falls below the ban threshold.
+    pub fn adjust(&mut self, peer: &PeerId, delta: f64, now: u64) -> Standing {
+        if self.is_banned(peer, now) {
+            return Standing::Banned;
+        }
+        let score = (self.score(peer, now) + delta).min(self.config.max_score);
+        if score < self.config.ban_threshold {
+            self.scores.remove(peer);
+            self.ban(
+                *peer,
+                now.saturating_add(self.config.ban_duration_secs),
+                now,
+            );
+            return Standing::Banned;
+        }
+        self.ensure_capacity(peer, now);
+        self.scores.insert(
+            *peer,
+            Reputation {
+                score,
+                updated: now,
+            },
+        );
+        self.standing(peer, now)
+    }
+
+    pub fn is_banned(&self, peer: &PeerId, now: u64) -> bool {
+        self.bans.get(peer).is_some_and(|&until| now < until)
+    }
+
+    /// Ban `peer` until unix time `until`, keeping the later expiry if it
+    /// is already banned.
+    pub fn ban(&mut self, peer: PeerId, until: u64, now: u64) {
+        let expiry = self.bans.entry(peer).or_insert(until);
+        *expiry = (*expiry).max(until);
+        if self.bans.len() > self.config.max_banned_peers {
+            self.prune_bans(now);
+        }
+    }
+
+    pub fn unban(&mut self, peer: &PeerId) -> bool {
+        self.bans.remove(peer).is_some()
+    }
+
+    pub fn banned_count(&self, now: u64) -> usize {
+        self.bans.values().filter(|&&until| now < until).count()
+    }
+
+    /// Forget expired bans and scores that have decayed to nothing.
+    pub fn prune(&mut self, now: u64) {
+        self.prune_bans(now);
+        let half_life = self.config.half_life_secs;
+        self.scores
+            .retain(|_, rep| rep.decayed(now, half_life).abs() >= 0.01);
+    }
+
+    /// Write the unexpired bans to `path`, replacing it atomically.
+    pub fn save_bans(&self, path: &Path, now: u64) -> anyhow::Result<()> {
+        let mut contents = String::from(BAN_LIST_HEADER);
+        contents.push('\n');
+        for (peer, until) in &self.bans {
+            if now < *until {
+                contents.push_str(&format!("{peer} {until}\n"));
+            }
+        }
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, contents)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Merge bans saved by [`save_bans`](Self::save_bans), returning how
+    /// many are still in force. A missing file is an empty list; lines that
+    /// do not parse are skipped rather than failing startup.
+    pub fn load_bans(&mut self, path: &Path, now: u64) -> anyhow::Result<usize> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+        let mut loaded = 0;
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let parsed = match (fields.next(), fields.next()) {
+                (Some(peer), Some(until)) => peer.parse::<PeerId>().ok().zip(until.parse().ok()),
+                _ => None,
+            };
+            if let Some((peer, until)) = parsed {
+                if now < until {
+                    self.ban(peer, until, now);
+                    loaded += 1;
+                }
+            }
+        }
+        Ok(loaded)
+    }
+
+    /// Drop expired bans, then the soonest-to-expire ones while over the cap.
+    fn prune_bans(&mut self, now: u64) {
+        self.bans.retain(|_, &mut until| until > now);
+        if self.bans.len() > self.config.max_banned_peers {
+            let mut entries: Vec<(PeerId, u64)> = self
+                .bans
+                .iter()
+                .map(|(&peer, &until)| (peer, until))
+                .collect();
+            entries.sort_by_key(|&(_, until)| until);
+            let excess = self.bans.len() - self.config.max_banned_peers;
+            for (peer, _) in entries.into_iter().take(excess) {
+                self.bans.remove(&peer);
+            }
+        }
+    }
+
+    /// Make room for a new peer by forgetting the score closest to zero,
+    /// which carries the least information, rather than the lowest, which
+    /// would let a misbehaving peer wipe its record by flooding identities.
+    fn ensure_capacity(&mut self, peer: &PeerId, now: u64) {
+        if self.scores.contains_key(peer) || self.scores.len() < self.config.max_tracked_peers {
+            return;
+        }
+        let half_life = self.config.half_life_secs;
+        if let Some(least) = self
+            .scores
+            .iter()
+            .min_by(|a, b| {
+                let a = a.1.decayed(now, half_life).abs();
+                let b = b.1.decayed(now, half_life).abs();
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(peer, _)| *peer)
+        {
+            self.scores.remove(&least);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,6 +474,125 @@ mod tests {
         scores.record_success(&peers[0]);
         assert_eq!(scores.len(), MAX_TRACKED_PEERS);
     }
+
+    #[test]
+    fn penalties_are_weighted_and_decay() {
+        let mut reputation = PeerReputation::default();
+        let half_life = reputation.config().half_life_secs;
+        let (shredder, late) = (PeerId::random(), PeerId::random());
+
+        reputation.penalize(&shredder, Violation::InvalidShred, 1_000);
+        reputation.penalize(&late, Violation::LateVote, 1_000);
+        assert_eq!(reputation.score(&shredder, 1_000), -25.0);
+        assert_eq!(reputation.score(&late, 1_000), -2.0);
+        assert_eq!(reputation.score(&shredder, 1_000 + half_life), -12.5);
+
+        for _ in 0..100 {
+            reputation.record_valid(&late, 1_000);
+        }
+        assert_eq!(reputation.score(&late, 1_000), 20.0);
+    }
+
+    #[test]
+    fn greylists_then_bans_until_expiry() {
+        let mut reputation = PeerReputation::default();
+        let peer = PeerId::random();
+        let now = 1_000;
+
+        assert_eq!(
+            reputation.penalize(&peer, Violation::Spam, now),
+            Standing::Good
+        );
+        reputation.penalize(&peer, Violation::Spam, now);
+        assert_eq!(
+            reputation.penalize(&peer, Violation::Spam, now),
+            Standing::Greylisted
+        );
+        for _ in 0..2 {
+            reputation.penalize(&peer, Violation::Spam, now);
+        }
+        assert_eq!(
+            reputation.penalize(&peer, Violation::Spam, now),
+            Standing::Banned
+        );
+        assert_eq!(reputation.record_valid(&peer, now), Standing::Banned);
+        assert_eq!(reputation.banned_count(now), 1);
+
+        let expiry = now + reputation.config().ban_duration_secs;
+        assert!(reputation.is_banned(&peer, expiry - 1));
+        assert_eq!(reputation.standing(&peer, expiry), Standing::Good);
+        reputation.prune(expiry);
+        assert_eq!(reputation.banned_count(expiry), 0);
+    }
+
+    #[test]
+    fn ban_list_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("p2p").join("banned_peers");
+        let (active, expired) = (PeerId::random(), PeerId::random());
+
+        let mut reputation = PeerReputation::default();
+        reputation.ban(active, 5_000, 1_000);
+        reputation.ban(expired, 1_500, 1_000);
+        reputation.save_bans(&path, 2_000).unwrap();
+
+        let mut contents = std::fs::read_to_string(&path).unwrap();
+        contents.push_str("not-a-peer 9999\n\n");
+        std::fs::write(&path, contents).unwrap();
+
+        let mut restarted = PeerReputation::default();
+        assert_eq!(restarted.load_bans(&path, 2_000).unwrap(), 1);
+        assert!(restarted.is_banned(&active, 2_000));
+        assert!(!restarted.is_banned(&expired, 2_000));
+
+        let missing = dir.path().join("missing");
+        assert_eq!(restarted.load_bans(&missing, 2_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn prune_removes_expired_bans() {
+        let mut reputation = PeerReputation::default();
+        let now = 10_000;
+        for _ in 0..10 {
+            reputation.ban(PeerId::random(), now - 1, now);
+        }
+        let active = PeerId::random();
+        reputation.ban(active, now + 3600, now);
+
+        assert_eq!(reputation.bans.len(), 11);
+        reputation.prune(now);
+        assert_eq!(reputation.bans.len(), 1);
+        assert!(reputation.is_banned(&active, now));
+    }
+
+    #[test]
+    fn ban_list_evicts_soonest_expiry_over_cap() {
+        let mut reputation = PeerReputation::default();
+        let cap = reputation.config().max_banned_peers;
+        let now = 10_000;
+        let first = PeerId::random();
+        reputation.ban(first, now + 3600, now);
+        for i in 1..(cap + 100) {
+            reputation.ban(PeerId::random(), now + 3600 + i as u64, now);
+        }
+        assert_eq!(reputation.bans.len(), cap);
+        assert!(!reputation.is_banned(&first, now));
+    }
+
+    #[test]
+    fn ban_via_score_prunes_expired_entries() {
+        let mut reputation = PeerReputation::default();
+        let now = 10_000;
+        for _ in 0..reputation.config().max_banned_peers {
+            reputation.ban(PeerId::random(), now - 1, now);
+        }
+
+        let peer = PeerId::random();
+        assert_eq!(reputation.adjust(&peer, -101.0, now), Standing::Banned);
+        // The new ban pushes the list over the cap, pruning every expired entry.
+        assert_eq!(reputation.bans.len(), 1);
+        assert!(reputation.is_banned(&peer, now));
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,136 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Duration;
+
+struct Entry<E> {
+    at: Duration,
+    seq: u64,
+    event: E,
+}
+
+impl<E> PartialEq for Entry<E> {
+    fn eq(&self, other: &Self) -> bool {
+        (self.at, self.seq) == (other.at, other.seq)
+    }
+}
+
+impl<E> Eq for Entry<E> {}
+
+impl<E> PartialOrd for Entry<E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<E> Ord for Entry<E> {
+    /// Reversed, so the max-heap yields the earliest entry.
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.at, other.seq).cmp(&(self.at, self.seq))
+    }
+}
+
+/// A virtual clock and the events scheduled on it.
+///
+/// Time only moves when an event is taken off the queue, and jumps
+/// straight to it. Events due at the same instant come out in the order
+/// they were scheduled.
+pub struct EventQueue<E> {
+    now: Duration,
+    seq: u64,
+    heap: BinaryHeap<Entry<E>>,
+}
+
+impl<E> Default for EventQueue<E> {
+    fn default() -> Self {
+        EventQueue {
+            now: Duration::ZERO,
+            seq: 0,
+            heap: BinaryHeap::new(),
+        }
+    }
+}
+
+impl<E> EventQueue<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Virtual time since the simulation started.
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Schedule `event` at `at`, or now if `at` has passed.
+    pub fn schedule_at(&mut self, at: Duration, event: E) {
+        let at = at.max(self.now);
+        self.heap.push(Entry {
+            at,
+            seq: self.seq,
+            event,
+        });
+        self.seq += 1;
+    }
+
+    pub fn schedule_in(&mut self, delay: Duration, event: E) {
+        self.schedule_at(self.now + delay, event);
+    }
+
+    /// When the next event is due.
+    pub fn next_at(&self) -> Option<Duration> {
+        self.heap.peek().map(|entry| entry.at)
+    }
+
+    /// Take the next event, moving the clock to it.
+    pub fn pop(&mut self) -> Option<(Duration, E)> {
+        let entry = self.heap.pop()?;
+        self.now = entry.at;
+        Some((entry.at, entry.event))
+    }
+
+    /// Drop every pending event and move the clock to `at`, if later.
+    pub fn clear_until(&mut self, at: Duration) -> usize {
+        let dropped = self.heap.len();
+        self.heap.clear();
+        self.now = self.now.max(at);
+        dropped
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_come_out_in_time_then_insertion_order() {
+        let mut queue = EventQueue::new();
+        let ms = Duration::from_millis;
+        queue.schedule_at(ms(30), "c");
+        queue.schedule_at(ms(10), "a");
+        queue.schedule_at(ms(30), "d");
+        queue.schedule_at(ms(20), "b");
+        assert_eq!(queue.next_at(), Some(ms(10)));
+
+        let mut order = Vec::new();
+        while let Some((at, event)) = queue.pop() {
+            assert_eq!(queue.now(), at);
+            order.push(event);
+        }
+        assert_eq!(order, vec!["a", "b", "c", "d"]);
+
+        // The past is not reachable.
+        queue.schedule_at(ms(5), "late");
+        assert_eq!(queue.pop(), Some((ms(30), "late")));
+        queue.schedule_in(ms(5), "soon");
+        assert_eq!(queue.clear_until(ms(100)), 1);
+        assert!(queue.is_empty());
+        assert_eq!(queue.now(), ms(100));
+    }
+}
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use anyhow::{bail, Result};
+
+/// One direction of a link between two nodes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Link {
+    /// One-way delay.
+    pub latency: Duration,
+    /// Extra delay drawn uniformly from `0..=jitter` per message.
+    pub jitter: Duration,
+    /// Probability a message is lost, from 0 to 1.
+    pub loss: f64,
+}
+
+impl Link {
+    pub fn new(latency: Duration) -> Self {
+        Link {
+            latency,
+            jitter: Duration::ZERO,
+            loss: 0.0,
+        }
+    }
+
+    pub fn with_jitter(self, jitter: Duration) -> Self {
+        Link { jitter, ..self }
+    }
+
+    pub fn with_loss(self, loss: f64) -> Self {
+        Link {
+            loss: loss.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// A link that loses everything.
+    pub fn down() -> Self {
+        Link::new(Duration::ZERO).with_loss(1.0)
+    }
+}
+
+/// Latency and loss between every pair of nodes.
+#[derive(Clone, Debug)]
+pub struct Conditions {
+    nodes: usize,
+    /// Row-major `nodes × nodes` matrix: `links[from * nodes + to]`.
+    links: Vec<Link>,
+}
+
+impl Conditions {
+    /// Every link the same.
+    pub fn uniform(nodes: usize, link: Link) -> Self {
+        Conditions {
+            nodes,
+            links: vec![link; nodes * nodes],
+        }
+    }
+
+    /// Nodes placed in regions, `region_of[node]`, with `matrix[a][b]` the
+    /// link from region `a` to region `b`. The diagonal is the link
+    /// between nodes of the same region.
+    pub fn regions(region_of: &[usize], matrix: &[Vec<Link>]) -> Result<Self> {
+        if matrix.iter().any(|row| row.len() != matrix.len()) {
+            bail!("region matrix must be square");
+        }
+        if let Some(region) = region_of.iter().find(|r| **r >= matrix.len()) {
+            bail!(
+                "region {region} outside the {n}x{n} matrix",
+                n = matrix.len()
+            );
+        }
+        let nodes = region_of.len();
+        let links = region_of
+            .iter()
+            .flat_map(|from| region_of.iter().map(move |to| matrix[*from][*to]))
+            .collect();
+        Ok(Conditions { nodes, links })
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes == 0
+    }
+
+    pub fn link(&self, from: usize, to: usize) -> Link {
+        self.links[from * self.nodes + to]
+    }
+
+    /// Set the link from `from` to `to` only.
+    pub fn set_link(&mut self, from: usize, to: usize, link: Link) {
+        self.links[from * self.nodes + to] = link;
+    }
+
+    /// Set the link both ways between `a` and `b`.
+    pub fn set_pair(&mut self, a: usize, b: usize, link: Link) {
+        self.set_link(a, b, link);
+        self.set_link(b, a, link);
+    }
+
+    /// Cut `node` off from everyone.
+    pub fn isolate(&mut self, node: usize) {
+        for other in 0..self.nodes {
+            if other != node {
+                self.set_pair(node, other, Link::down());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_fill_the_matrix() {
+        let ms = Duration::from_millis;
+        let local = Link::new(ms(5));
+        let far = Link::new(ms(80)).with_loss(0.01);
+        let conditions =
+            Conditions::regions(&[0, 0, 1], &[vec![local, far], vec![far, local]]).unwrap();
+        assert_eq!(conditions.len(), 3);
+        assert_eq!(conditions.link(0, 1), local);
+        assert_eq!(conditions.link(1, 2), far);
+        assert_eq!(conditions.link(2, 0), far);
+
+        assert!(Conditions::regions(&[0, 2], &[vec![local, far], vec![far, local]]).is_err());
+        assert!(Conditions::regions(&[0], &[vec![local, far]]).is_err());
+
+        let mut conditions = conditions;
+        conditions.isolate(2);
+        assert_eq!(conditions.link(0, 2).loss, 1.0);
+        assert_eq!(conditions.link(2, 1).loss, 1.0);
+        assert_eq!(conditions.link(0, 1), local);
+    }
+}
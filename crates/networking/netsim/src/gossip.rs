@@ -0,0 +1,376 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use aether_gossipsub::{GossipOutcome, GossipRouter, LazyPushConfig};
+use anyhow::{bail, Result};
+use libp2p::identity::Keypair;
+use libp2p::PeerId;
+use rand::Rng;
+
+use crate::conditions::Conditions;
+use crate::network::{Event, SimNetwork};
+
+/// Bytes an IHAVE, IWANT or IDONTWANT spends per message ID.
+const ID_BYTES: usize = 32;
+
+#[derive(Clone, Debug)]
+pub enum Wire {
+    Full(Vec<u8>),
+    IHave(Vec<[u8; 32]>),
+    IWant(Vec<[u8; 32]>),
+    IDontWant(Vec<[u8; 32]>),
+}
+
+impl Wire {
+    fn bytes(&self) -> usize {
+        match self {
+            Wire::Full(data) => data.len(),
+            Wire::IHave(ids) | Wire::IWant(ids) | Wire::IDontWant(ids) => ids.len() * ID_BYTES,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GossipSimConfig {
+    pub nodes: usize,
+    /// Mesh peers each node picks; links are two-way, so most nodes end up
+    /// with about twice this many.
+    pub degree: usize,
+    pub topic: String,
+    /// Lazy push settings, or `None` to push everything eagerly.
+    pub lazy: Option<LazyPushConfig>,
+    /// How often routers expire unanswered IWANTs.
+    pub heartbeat: Duration,
+    pub seed: u64,
+}
+
+impl Default for GossipSimConfig {
+    fn default() -> Self {
+        GossipSimConfig {
+            nodes: 300,
+            degree: 4,
+            topic: "shred".into(),
+            lazy: None,
+            heartbeat: Duration::from_millis(700),
+            seed: 0,
+        }
+    }
+}
+
+/// How one published message spread.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Propagation {
+    /// When each node first had the message, counted from publication.
+    pub delivered_at: Vec<Option<Duration>>,
+    /// Copies of the full message received, including duplicates.
+    pub full_copies: u64,
+    /// Bytes sent on the wire for this message.
+    pub bytes: u64,
+}
+
+impl Propagation {
+    pub fn reached(&self) -> usize {
+        self.delivered_at.iter().flatten().count()
+    }
+
+    pub fn coverage(&self) -> f64 {
+        self.reached() as f64 / self.delivered_at.len().max(1) as f64
+    }
+
+    /// Full copies beyond the one each reached node needed.
+    pub fn duplicates(&self) -> u64 {
+        self.full_copies.saturating_sub(self.reached() as u64)
+    }
+
+    /// The delivery time below which fraction `p` of the reached nodes
+    /// got the message.
+    pub fn latency_percentile(&self, p: f64) -> Option<Duration> {
+        let mut times: Vec<Duration> = self.delivered_at.iter().flatten().copied().collect();
+        if times.is_empty() {
+            return None;
+        }
+        times.sort();
+        let rank = (p.clamp(0.0, 1.0) * (times.len() - 1) as f64).round() as usize;
+        Some(times[rank])
+    }
+
+    pub fn max_latency(&self) -> Option<Duration> {
+        self.delivered_at.iter().flatten().max().copied()
+    }
+}
+
+/// A gossip network of real [`GossipRouter`]s on a random mesh.
+pub struct GossipSim {
+    config: GossipSimConfig,
+    peers: Vec<PeerId>,
+    index: HashMap<PeerId, usize>,
+    routers: Vec<GossipRouter>,
+    net: SimNetwork<Wire>,
+}
+
+impl GossipSim {
+    pub fn new(config: GossipSimConfig, conditions: Conditions) -> Result<Self> {
+        if conditions.len() != config.nodes {
+            bail!(
+                "conditions cover {} nodes, config has {}",
+                conditions.len(),
+                config.nodes
+            );
+        }
+        if config.degree >= config.nodes {
+            bail!(
+                "mesh degree {} needs more than {} nodes",
+                config.degree,
+                config.nodes
+            );
+        }
+        let peers: Vec<PeerId> = (0..config.nodes).map(peer_id).collect();
+        let index = peers.iter().enumerate().map(|(i, p)| (*p, i)).collect();
+        let mut routers: Vec<GossipRouter> = (0..config.nodes)
+            .map(|_| match &config.lazy {
+                Some(lazy) => GossipRouter::with_lazy_push(lazy.clone()),
+                None => GossipRouter::new(),
+            })
+            .collect();
+
+        let mut net = SimNetwork::new(conditions, config.seed);
+        for a in 0..config.nodes {
+            let mut linked = 0;
+            while linked < config.degree {
+                let b = net.rng().gen_range(0..config.nodes);
+                if b == a {
+                    continue;
+                }
+                routers[a].mesh_mut().join(&config.topic, peers[b]);
+                routers[b].mesh_mut().join(&config.topic, peers[a]);
+                linked += 1;
+            }
+        }
+
+        Ok(GossipSim {
+            config,
+            peers,
+            index,
+            routers,
+            net,
+        })
+    }
+
+    pub fn peer_id(&self, node: usize) -> PeerId {
+        self.peers[node]
+    }
+
+    pub fn router(&self, node: usize) -> &GossipRouter {
+        &self.routers[node]
+    }
+
+    pub fn network(&self) -> &SimNetwork<Wire> {
+        &self.net
+    }
+
+    pub fn network_mut(&mut self) -> &mut SimNetwork<Wire> {
+        &mut self.net
+    }
+
+    /// Publish `data` from `origin` and run until `horizon` has passed.
+    /// Anything still in flight then is dropped.
+    pub fn publish(&mut self, origin: usize, data: Vec<u8>, horizon: Duration) -> Propagation {
+        let start = self.net.now();
+        let deadline = start + horizon;
+        let bytes_before = self.net.stats().bytes;
+        let mut run = Propagation {
+            delivered_at: vec![None; self.config.nodes],
+            full_copies: 0,
+            bytes: 0,
+        };
+
+        let topic = self.config.topic.clone();
+        let outcome = self.routers[origin].publish(&topic, data.clone());
+        if outcome.delivered {
+            run.delivered_at[origin] = Some(Duration::ZERO);
+        }
+        self.fan_out(origin, &data, outcome);
+        if self.config.lazy.is_some() {
+            for node in 0..self.config.nodes {
+                self.net.timer(node, self.config.heartbeat);
+            }
+        }
+
+        while let Some((at, event)) = self.net.step_until(deadline) {
+            match event {
+                Event::Deliver { from, to, message } => {
+                    let sender = self.peers[from];
+                    match message {
+                        Wire::Full(data) => {
+                            run.full_copies += 1;
+                            let outcome = self.routers[to].receive(&sender, &topic, data.clone());
+                            if outcome.delivered {
+                                run.delivered_at[to].get_or_insert(at - start);
+                                self.fan_out(to, &data, outcome);
+                            }
+                        }
+                        Wire::IHave(ids) => {
+                            let wanted = self.routers[to].handle_ihave(&sender, &topic, &ids);
+                            if !wanted.is_empty() {
+                                self.send(to, from, Wire::IWant(wanted));
+                            }
+                        }
+                        Wire::IWant(ids) => {
+                            for (_, data) in self.routers[to].handle_iwant(&sender, &ids) {
+                                self.send(to, from, Wire::Full(data));
+                            }
+                        }
+                        Wire::IDontWant(ids) => self.routers[to].handle_idontwant(&sender, &ids),
+                    }
+                }
+                Event::Timer { node } => {
+                    self.routers[node].heartbeat();
+                    if at + self.config.heartbeat <= deadline {
+                        self.net.timer(node, self.config.heartbeat);
+                    }
+                }
+            }
+        }
+        self.net.settle(deadline);
+        run.bytes = self.net.stats().bytes - bytes_before;
+        run
+    }
+
+    /// Send what a router decided, in node order so the run does not
+    /// depend on hash map iteration.
+    fn fan_out(&mut self, node: usize, data: &[u8], outcome: GossipOutcome) {
+        let id = outcome.message_id;
+        for peer in self.sorted(&outcome.forwarded_to) {
+            self.send(node, peer, Wire::Full(data.to_vec()));
+        }
+        for peer in self.sorted(&outcome.announce_to) {
+            self.send(node, peer, Wire::IHave(vec![id]));
+        }
+        for peer in self.sorted(&outcome.dont_want_to) {
+            self.send(node, peer, Wire::IDontWant(vec![id]));
+        }
+    }
+
+    fn sorted(&self, peers: &[PeerId]) -> Vec<usize> {
+        let mut nodes: Vec<usize> = peers.iter().map(|peer| self.index[peer]).collect();
+        nodes.sort_unstable();
+        nodes
+    }
+
+    fn send(&mut self, from: usize, to: usize, wire: Wire) {
+        let bytes = wire.bytes();
+        self.net.send(from, to, wire, bytes);
+    }
+}
+
+/// A peer ID fixed by the node's index, so runs are reproducible.
+pub(crate) fn peer_id(node: usize) -> PeerId {
+    let mut secret = [0u8; 32];
+    secret[..8].copy_from_slice(&(node as u64 + 1).to_le_bytes());
+    Keypair::ed25519_from_bytes(secret)
+        .expect("any 32 bytes are an ed25519 secret")
+        .public()
+        .to_peer_id()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::Link;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn sim(config: GossipSimConfig, link: Link) -> GossipSim {
+        let conditions = Conditions::uniform(config.nodes, link);
+        GossipSim::new(config, conditions).unwrap()
+    }
+
+    #[test]
+    fn gossip_reaches_hundreds_of_nodes_in_a_few_hops() {
+        let mut sim = sim(GossipSimConfig::default(), Link::new(ms(40)));
+        let run = sim.publish(0, vec![1u8; 2048], Duration::from_secs(5));
+        assert_eq!(run.reached(), 300);
+        // log_8(300) is under 3; a few more hops for the unlucky.
+        assert!(run.max_latency().unwrap() <= ms(40 * 8));
+        assert!(run.latency_percentile(0.5).unwrap() <= ms(40 * 4));
+        assert!(run.duplicates() > 0);
+
+        // A second message is routed independently of the first.
+        let again = sim.publish(123, vec![2u8; 2048], Duration::from_secs(5));
+        assert_eq!(again.delivered_at[123], Some(Duration::ZERO));
+        assert_eq!(again.reached(), 300);
+    }
+
+    #[test]
+    fn runs_are_deterministic() {
+        let config = GossipSimConfig {
+            nodes: 120,
+            seed: 42,
+            ..GossipSimConfig::default()
+        };
+        let link = Link::new(ms(30)).with_jitter(ms(40)).with_loss(0.05);
+        let first = sim(config.clone(), link).publish(5, vec![9u8; 512], Duration::from_secs(3));
+        let second = sim(config.clone(), link).publish(5, vec![9u8; 512], Duration::from_secs(3));
+        assert_eq!(first, second);
+
+        let other_seed = GossipSimConfig { seed: 43, ..config };
+        let third = sim(other_seed, link).publish(5, vec![9u8; 512], Duration::from_secs(3));
+        assert_ne!(first.delivered_at, third.delivered_at);
+    }
+
+    #[test]
+    fn mesh_redundancy_rides_out_loss() {
+        let mut sim = sim(
+            GossipSimConfig::default(),
+            Link::new(ms(40)).with_jitter(ms(20)).with_loss(0.2),
+        );
+        let run = sim.publish(0, vec![3u8; 1024], Duration::from_secs(5));
+        assert!(run.coverage() > 0.99, "coverage {}", run.coverage());
+        assert!(sim.network().stats().dropped > 0);
+    }
+
+    #[test]
+    fn lazy_push_trades_a_round_trip_for_bandwidth() {
+        let payload = vec![5u8; 64 * 1024];
+        let link = Link::new(ms(40));
+        let eager = sim(GossipSimConfig::default(), link).publish(
+            0,
+            payload.clone(),
+            Duration::from_secs(5),
+        );
+        let lazy_config = GossipSimConfig {
+            lazy: Some(LazyPushConfig::for_topics(["shred"])),
+            ..GossipSimConfig::default()
+        };
+        let lazy = sim(lazy_config, link).publish(0, payload, Duration::from_secs(5));
+
+        assert_eq!(eager.reached(), 300);
+        assert_eq!(lazy.reached(), 300);
+        assert!(
+            lazy.bytes * 10 < eager.bytes * 7,
+            "lazy {} eager {}",
+            lazy.bytes,
+            eager.bytes
+        );
+        assert!(lazy.duplicates() < eager.duplicates());
+        assert!(lazy.latency_percentile(0.95) >= eager.latency_percentile(0.95));
+    }
+
+    #[test]
+    fn partitioned_nodes_are_not_reached() {
+        let config = GossipSimConfig {
+            nodes: 50,
+            ..GossipSimConfig::default()
+        };
+        let mut conditions = Conditions::uniform(50, Link::new(ms(10)));
+        conditions.isolate(7);
+        conditions.isolate(8);
+        let mut sim = GossipSim::new(config, conditions).unwrap();
+        let run = sim.publish(0, vec![1u8; 64], Duration::from_secs(2));
+        assert_eq!(run.reached(), 48);
+        assert_eq!(run.delivered_at[7], None);
+        assert_eq!(run.delivered_at[8], None);
+    }
+}
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::clock::EventQueue;
+use crate::conditions::Conditions;
+
+/// Something that happens to a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<M> {
+    /// `message` from `from` arrives at `to`.
+    Deliver { from: usize, to: usize, message: M },
+    /// A timer `node` set goes off.
+    Timer { node: usize },
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrafficStats {
+    pub sent: u64,
+    pub dropped: u64,
+    pub delivered: u64,
+    /// Bytes of every message sent, lost or not.
+    pub bytes: u64,
+}
+
+/// Nodes exchanging messages of type `M` over simulated links.
+///
+/// Sending a message samples the link's loss and jitter from the
+/// simulation's seeded generator and schedules its delivery on the
+/// virtual clock.
+pub struct SimNetwork<M> {
+    conditions: Conditions,
+    queue: EventQueue<Event<M>>,
+    rng: StdRng,
+    stats: TrafficStats,
+    bytes_sent: Vec<u64>,
+}
+
+impl<M> SimNetwork<M> {
+    pub fn new(conditions: Conditions, seed: u64) -> Self {
+        let nodes = conditions.len();
+        SimNetwork {
+            conditions,
+            queue: EventQueue::new(),
+            rng: StdRng::seed_from_u64(seed),
+            stats: TrafficStats::default(),
+            bytes_sent: vec![0; nodes],
+        }
+    }
+
+    pub fn now(&self) -> Duration {
+        self.queue.now()
+    }
+
+    pub fn nodes(&self) -> usize {
+        self.conditions.len()
+    }
+
+    pub fn conditions(&self) -> &Conditions {
+        &self.conditions
+    }
+
+    /// Change the links mid-run; messages already in flight keep the
+    /// delivery time they were given.
+    pub fn conditions_mut(&mut self) -> &mut Conditions {
+        &mut self.conditions
+    }
+
+    /// The simulation's generator, for callers that need their own draws
+    /// to be reproducible too.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    pub fn stats(&self) -> &TrafficStats {
+        &self.stats
+    }
+
+    pub fn bytes_sent(&self, node: usize) -> u64 {
+        self.bytes_sent[node]
+    }
+
+    /// Send a `bytes`-byte `message`. Returns whether it will arrive.
+    pub fn send(&mut self, from: usize, to: usize, message: M, bytes: usize) -> bool {
+        self.stats.sent += 1;
+        self.stats.bytes += bytes as u64;
+        self.bytes_sent[from] += bytes as u64;
+        let link = self.conditions.link(from, to);
+        if from != to && link.loss > 0.0 && self.rng.gen_bool(link.loss) {
+            self.stats.dropped += 1;
+            return false;
+        }
+        let jitter = if link.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            self.rng.gen_range(Duration::ZERO..=link.jitter)
+        };
+        let delay = if from == to {
+            Duration::ZERO
+        } else {
+            link.latency + jitter
+        };
+        self.queue
+            .schedule_in(delay, Event::Deliver { from, to, message });
+        true
+    }
+
+    /// Wake `node` after `delay`.
+    pub fn timer(&mut self, node: usize, delay: Duration) {
+        self.queue.schedule_in(delay, Event::Timer { node });
+    }
+
+    /// The next event, advancing the clock to it.
+    pub fn step(&mut self) -> Option<(Duration, Event<M>)> {
+        let (at, event) = self.queue.pop()?;
+        if matches!(event, Event::Deliver { .. }) {
+            self.stats.delivered += 1;
+        }
+        Some((at, event))
+    }
+
+    /// The next event due no later than `deadline`.
+    pub fn step_until(&mut self, deadline: Duration) -> Option<(Duration, Event<M>)> {
+        if self.queue.next_at()? > deadline {
+            return None;
+        }
+        self.step()
+    }
+
+    /// Drop everything still in flight and move the clock to `at`.
+    /// Returns how many events were dropped.
+    pub fn settle(&mut self, at: Duration) -> usize {
+        self.queue.clear_until(at)
+    }
+
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::Link;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn drain(net: &mut SimNetwork<u32>) -> Vec<(Duration, u32)> {
+        std::iter::from_fn(|| net.step())
+            .filter_map(|(at, event)| match event {
+                Event::Deliver { message, .. } => Some((at, message)),
+                Event::Timer { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn latency_and_loss_follow_the_links() {
+        let mut conditions = Conditions::uniform(3, Link::new(ms(10)));
+        conditions.set_link(0, 2, Link::new(ms(50)));
+        conditions.set_link(2, 0, Link::down());
+        let mut net = SimNetwork::new(conditions, 1);
+
+        assert!(net.send(0, 2, 1, 100));
+        assert!(net.send(0, 1, 2, 100));
+        assert!(!net.send(2, 0, 3, 100));
+        net.timer(1, ms(20));
+        assert_eq!(drain(&mut net), vec![(ms(10), 2), (ms(50), 1)]);
+        assert_eq!(
+            net.stats(),
+            &TrafficStats {
+                sent: 3,
+                dropped: 1,
+                delivered: 2,
+                bytes: 300,
+            }
+        );
+        assert_eq!(net.bytes_sent(0), 200);
+    }
+
+    #[test]
+    fn same_seed_same_run() {
+        let run = |seed| {
+            let link = Link::new(ms(20)).with_jitter(ms(30)).with_loss(0.3);
+            let mut net = SimNetwork::new(Conditions::uniform(4, link), seed);
+            for i in 0..200 {
+                net.send(i % 4, (i + 1) % 4, i as u32, 1);
+            }
+            drain(&mut net)
+        };
+        let first = run(7);
+        assert_eq!(first, run(7));
+        assert_ne!(first, run(8));
+        // Roughly the configured loss.
+        assert!((110..170).contains(&first.len()), "{}", first.len());
+    }
+}
@@ -0,0 +1,260 @@
+use std::collections::BTreeSet;
+use std::time::Duration;
+
+use aether_gossipsub::{PeerReputation, ReputationConfig, Standing, Violation};
+use anyhow::{bail, Result};
+use libp2p::PeerId;
+use rand::Rng;
+
+use crate::conditions::Conditions;
+use crate::gossip::peer_id;
+use crate::network::{Event, SimNetwork};
+
+/// How a simulated peer behaves towards its neighbours.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Behaviour {
+    Honest,
+    /// Each message is a `violation` with probability `rate`.
+    Faulty {
+        rate: f64,
+        violation: Violation,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct ScoringSimConfig {
+    pub nodes: usize,
+    /// Neighbours each node picks; links are two-way.
+    pub degree: usize,
+    /// How often each node sends every neighbour a message.
+    pub interval: Duration,
+    pub reputation: ReputationConfig,
+    /// Unix time the virtual clock starts at, for reputation decay.
+    pub start_unix: u64,
+    pub seed: u64,
+}
+
+impl Default for ScoringSimConfig {
+    fn default() -> Self {
+        ScoringSimConfig {
+            nodes: 200,
+            degree: 4,
+            interval: Duration::from_millis(500),
+            reputation: ReputationConfig::default(),
+            start_unix: 1_700_000_000,
+            seed: 0,
+        }
+    }
+}
+
+/// Who thought what of whom at the end of a run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoringReport {
+    /// When each node was first banned by any neighbour.
+    pub first_banned_at: Vec<Option<Duration>>,
+    /// How many neighbours ban each node at the end.
+    pub banned_by: Vec<usize>,
+    /// How many neighbours greylist each node at the end.
+    pub greylisted_by: Vec<usize>,
+    /// Messages dropped because the receiver had banned the sender.
+    pub refused: u64,
+}
+
+impl ScoringReport {
+    /// Nodes banned by at least one neighbour.
+    pub fn banned(&self) -> BTreeSet<usize> {
+        (0..self.banned_by.len())
+            .filter(|node| self.banned_by[*node] > 0)
+            .collect()
+    }
+}
+
+/// Peer scoring dynamics: every node keeps a real [`PeerReputation`] of
+/// its neighbours and judges each message they send, rewarding valid ones
+/// and penalizing violations, with time from the virtual clock.
+pub struct ScoringSim {
+    config: ScoringSimConfig,
+    behaviours: Vec<Behaviour>,
+    neighbours: Vec<BTreeSet<usize>>,
+    reputations: Vec<PeerReputation>,
+    peers: Vec<PeerId>,
+    net: SimNetwork<Option<Violation>>,
+}
+
+impl ScoringSim {
+    pub fn new(
+        config: ScoringSimConfig,
+        conditions: Conditions,
+        behaviours: Vec<Behaviour>,
+    ) -> Result<Self> {
+        if conditions.len() != config.nodes || behaviours.len() != config.nodes {
+            bail!(
+                "{} nodes configured, but conditions cover {} and behaviours {}",
+                config.nodes,
+                conditions.len(),
+                behaviours.len()
+            );
+        }
+        if config.degree >= config.nodes {
+            bail!(
+                "degree {} needs more than {} nodes",
+                config.degree,
+                config.nodes
+            );
+        }
+        let mut net = SimNetwork::new(conditions, config.seed);
+        let mut neighbours = vec![BTreeSet::new(); config.nodes];
+        for a in 0..config.nodes {
+            while neighbours[a].len() < config.degree {
+                let b = net.rng().gen_range(0..config.nodes);
+                if b != a {
+                    neighbours[a].insert(b);
+                    neighbours[b].insert(a);
+                }
+            }
+        }
+        let reputations = (0..config.nodes)
+            .map(|_| PeerReputation::new(config.reputation.clone()))
+            .collect();
+        let peers = (0..config.nodes).map(peer_id).collect();
+        Ok(ScoringSim {
+            config,
+            behaviours,
+            neighbours,
+            reputations,
+            peers,
+            net,
+        })
+    }
+
+    pub fn neighbours(&self, node: usize) -> &BTreeSet<usize> {
+        &self.neighbours[node]
+    }
+
+    /// Run for `duration` of virtual time.
+    pub fn run(&mut self, duration: Duration) -> ScoringReport {
+        let nodes = self.config.nodes;
+        let start = self.net.now();
+        let deadline = start + duration;
+        let mut first_banned_at = vec![None; nodes];
+        let mut refused = 0;
+        for node in 0..nodes {
+            self.net.timer(node, Duration::ZERO);
+        }
+
+        while let Some((at, event)) = self.net.step_until(deadline) {
+            match event {
+                Event::Timer { node } => {
+                    self.send_round(node);
+                    self.net.timer(node, self.config.interval);
+                }
+                Event::Deliver { from, to, message } => {
+                    let now = self.unix(at);
+                    let sender = self.peers[from];
+                    let reputation = &mut self.reputations[to];
+                    if reputation.is_banned(&sender, now) {
+                        refused += 1;
+                        continue;
+                    }
+                    let standing = match message {
+                        Some(violation) => reputation.penalize(&sender, violation, now),
+                        None => reputation.record_valid(&sender, now),
+                    };
+                    if standing == Standing::Banned {
+                        first_banned_at[from].get_or_insert(at - start);
+                    }
+                }
+            }
+        }
+        self.net.settle(deadline);
+
+        let now = self.unix(deadline);
+        let judged = |node: usize, standing: Standing| {
+            self.neighbours[node]
+                .iter()
+                .filter(|judge| {
+                    self.reputations[**judge].standing(&self.peers[node], now) == standing
+                })
+                .count()
+        };
+        ScoringReport {
+            first_banned_at,
+            banned_by: (0..nodes).map(|n| judged(n, Standing::Banned)).collect(),
+            greylisted_by: (0..nodes)
+                .map(|n| judged(n, Standing::Greylisted))
+                .collect(),
+            refused,
+        }
+    }
+
+    fn send_round(&mut self, node: usize) {
+        let neighbours: Vec<usize> = self.neighbours[node].iter().copied().collect();
+        for to in neighbours {
+            let message = match self.behaviours[node] {
+                Behaviour::Faulty { rate, violation } if self.net.rng().gen_bool(rate) => {
+                    Some(violation)
+                }
+                _ => None,
+            };
+            self.net.send(node, to, message, 1);
+        }
+    }
+
+    fn unix(&self, at: Duration) -> u64 {
+        self.config.start_unix + at.as_secs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::Link;
+
+    fn sim(behaviours: Vec<Behaviour>, link: Link) -> ScoringSim {
+        let config = ScoringSimConfig {
+            nodes: behaviours.len(),
+            ..ScoringSimConfig::default()
+        };
+        let conditions = Conditions::uniform(behaviours.len(), link);
+        ScoringSim::new(config, conditions, behaviours).unwrap()
+    }
+
+    #[test]
+    fn spammers_are_banned_and_honest_peers_are_not() {
+        let mut behaviours = vec![Behaviour::Honest; 200];
+        let faulty = [3, 50, 199];
+        for node in faulty {
+            behaviours[node] = Behaviour::Faulty {
+                rate: 0.5,
+                violation: Violation::InvalidShred,
+            };
+        }
+        let mut sim = sim(behaviours, Link::new(Duration::from_millis(50)));
+        let report = sim.run(Duration::from_secs(60));
+
+        assert_eq!(report.banned(), faulty.into_iter().collect());
+        for node in faulty {
+            assert_eq!(report.banned_by[node], sim.neighbours(node).len());
+            assert!(report.first_banned_at[node].unwrap() < Duration::from_secs(10));
+        }
+        assert!(report.refused > 0);
+        assert!(report.greylisted_by.iter().all(|n| *n == 0));
+    }
+
+    #[test]
+    fn occasional_late_votes_are_forgiven() {
+        let mut behaviours = vec![Behaviour::Honest; 100];
+        // A slow validator: one vote in ten arrives late.
+        behaviours[10] = Behaviour::Faulty {
+            rate: 0.1,
+            violation: Violation::LateVote,
+        };
+        let mut sim = sim(
+            behaviours,
+            Link::new(Duration::from_millis(80)).with_loss(0.05),
+        );
+        let report = sim.run(Duration::from_secs(300));
+        assert!(report.banned().is_empty());
+        assert_eq!(report.greylisted_by[10], 0);
+    }
+}
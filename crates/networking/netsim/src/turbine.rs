@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use aether_crypto_primitives::Keypair;
+use aether_da_shreds::Shred;
+use aether_da_turbine::topology::TurbineTopology;
+use aether_da_turbine::{TurbineBroadcaster, TurbineReceiver};
+use aether_types::{ErasureParams, Slot, H256};
+use anyhow::{bail, Result};
+
+use crate::conditions::Conditions;
+use crate::network::{Event, SimNetwork};
+
+#[derive(Clone, Debug)]
+pub struct TurbineSimConfig {
+    /// Validators including the leader, which is node 0.
+    pub nodes: usize,
+    /// Children per node in each shred's tree.
+    pub fanout: usize,
+    pub data_shards: usize,
+    pub parity_shards: usize,
+    pub seed: u64,
+}
+
+impl Default for TurbineSimConfig {
+    fn default() -> Self {
+        TurbineSimConfig {
+            nodes: 200,
+            fanout: 8,
+            data_shards: 10,
+            parity_shards: 2,
+            seed: 0,
+        }
+    }
+}
+
+/// How one block went down the trees.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TurbineRun {
+    /// When each node could rebuild the block, counted from broadcast.
+    pub reconstructed_at: Vec<Option<Duration>>,
+    /// When each node could start on the front of the block, streamed
+    /// from the data shreds that had arrived in order.
+    pub first_bytes_at: Vec<Option<Duration>>,
+    /// Shreds the leader made of the block.
+    pub shreds_sent: usize,
+    /// Shreds each node received, duplicates included.
+    pub shreds_received: Vec<usize>,
+    /// Most shreds any one node sent on for any one shred index.
+    pub max_fanout: usize,
+    /// Layers below the leader in the deepest shred tree.
+    pub depth: usize,
+}
+
+impl TurbineRun {
+    pub fn reconstructed(&self) -> usize {
+        self.reconstructed_at.iter().flatten().count()
+    }
+
+    pub fn max_latency(&self) -> Option<Duration> {
+        self.reconstructed_at.iter().flatten().max().copied()
+    }
+
+    /// Shreds that never reached `node` down the tree.
+    pub fn shreds_missed(&self, node: usize) -> usize {
+        self.shreds_sent.saturating_sub(self.shreds_received[node])
+    }
+}
+
+/// Turbine block propagation: the leader shreds a block and sends each
+/// shred down its own tree, shuffled per shred as in Solana, so every
+/// node is near the root of some trees and the load evens out. Each node
+/// forwards what it receives to its children and rebuilds the block with
+/// a real [`TurbineReceiver`].
+pub struct TurbineSim {
+    config: TurbineSimConfig,
+    broadcaster: TurbineBroadcaster,
+    net: SimNetwork<Shred>,
+}
+
+impl TurbineSim {
+    pub fn new(config: TurbineSimConfig, conditions: Conditions) -> Result<Self> {
+        if conditions.len() != config.nodes {
+            bail!(
+                "conditions cover {} nodes, config has {}",
+                conditions.len(),
+                config.nodes
+            );
+        }
+        if config.nodes < 2 || config.fanout == 0 {
+            bail!("turbine needs a leader, one other node and a non-zero fanout");
+        }
+        let broadcaster = TurbineBroadcaster::new(
+            config.data_shards,
+            config.parity_shards,
+            1,
+            Keypair::generate(),
+        )?;
+        let net = SimNetwork::new(conditions, config.seed);
+        Ok(TurbineSim {
+            config,
+            broadcaster,
+            net,
+        })
+    }
+
+    pub fn network(&self) -> &SimNetwork<Shred> {
+        &self.net
+    }
+
+    pub fn network_mut(&mut self) -> &mut SimNetwork<Shred> {
+        &mut self.net
+    }
+
+    pub fn coding(&self) -> ErasureParams {
+        self.broadcaster.coding()
+    }
+
+    /// Shred later blocks with `params`, as a leader does when the rate
+    /// changes at an epoch boundary.
+    pub fn set_coding(&mut self, params: ErasureParams) -> Result<()> {
+        self.broadcaster.set_coding(params)
+    }
+
+    /// The tree shred `index` of `slot` travels down: the leader on top,
+    /// then the other nodes, equally staked, shuffled by a seed of the slot
+    /// and index.
+    pub fn shred_tree(&self, slot: Slot, index: u32) -> TurbineTopology {
+        let nodes: Vec<(String, u128)> = (1..self.config.nodes)
+            .map(|node| (node.to_string(), 1))
+            .collect();
+        let mut seed = self.config.seed.to_le_bytes().to_vec();
+        seed.extend_from_slice(&slot.to_le_bytes());
+        seed.extend_from_slice(&index.to_le_bytes());
+        TurbineTopology::stake_weighted("0", &nodes, self.config.fanout, &seed)
+    }
+
+    /// Broadcast `payload` as the block for `slot` and run until `horizon`
+    /// has passed.
+    pub fn broadcast(
+        &mut self,
+        slot: Slot,
+        payload: &[u8],
+        horizon: Duration,
+    ) -> Result<TurbineRun> {
+        let nodes = self.config.nodes;
+        let start = self.net.now();
+        let deadline = start + horizon;
+        let mut block_id = [0u8; 32];
+        block_id[..8].copy_from_slice(&slot.to_le_bytes());
+        let block_id = H256::from(block_id);
+        let coding = self.broadcaster.coding();
+        let shreds = self.broadcaster.make_shreds(slot, block_id, payload)?;
+
+        let trees: Vec<TurbineTopology> = shreds
+            .iter()
+            .map(|shred| self.shred_tree(slot, shred.index))
+            .collect();
+        let children: Vec<HashMap<usize, Vec<usize>>> = trees
+            .iter()
+            .map(|tree| {
+                (0..nodes)
+                    .map(|node| {
+                        let kids = tree
+                            .children(&node.to_string())
+                            .iter()
+                            .filter_map(|child| child.parse().ok())
+                            .collect();
+                        (node, kids)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut receivers = (0..nodes)
+            .map(|_| TurbineReceiver::new(self.config.data_shards, self.config.parity_shards))
+            .collect::<Result<Vec<_>>>()?;
+        let mut run = TurbineRun {
+            reconstructed_at: vec![None; nodes],
+            first_bytes_at: vec![None; nodes],
+            shreds_sent: shreds.len(),
+            shreds_received: vec![0; nodes],
+            max_fanout: children
+                .iter()
+                .flat_map(|tree| tree.values().map(Vec::len))
+                .max()
+                .unwrap_or(0),
+            depth: trees
+                .iter()
+                .map(|tree| tree.layers().len().saturating_sub(1))
+                .max()
+                .unwrap_or(0),
+        };
+        run.reconstructed_at[0] = Some(Duration::ZERO);
+        run.first_bytes_at[0] = Some(Duration::ZERO);
+        let mut streamed = vec![Vec::new(); nodes];
+
+        for shred in shreds {
+            self.forward(0, &children[shred.index as usize], shred);
+        }
+        while let Some((at, event)) = self.net.step_until(deadline) {
+            let Event::Deliver { to, message, .. } = event else {
+                continue;
+            };
+            run.shreds_received[to] += 1;
+            let tree = &children[message.index as usize];
+            if run.reconstructed_at[to].is_none() {
+                let chunk = receivers[to].ingest_shred_streaming(message.clone(), coding)?;
+                if let Some(chunk) = chunk {
+                    run.first_bytes_at[to].get_or_insert(at - start);
+                    streamed[to].extend_from_slice(&chunk.data);
+                    if chunk.last && streamed[to] == payload {
+                        run.reconstructed_at[to] = Some(at - start);
+                    }
+                }
+            }
+            self.forward(to, tree, message);
+        }
+        self.net.settle(deadline);
+        Ok(run)
+    }
+
+    fn forward(&mut self, node: usize, tree: &HashMap<usize, Vec<usize>>, shred: Shred) {
+        let bytes = shred.payload.len();
+        for child in tree.get(&node).into_iter().flatten() {
+            self.net.send(node, *child, shred.clone(), bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conditions::Link;
+    use aether_da_turbine::{AdaptiveCoding, CodingPolicy};
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    fn lossy() -> Conditions {
+        Conditions::uniform(200, Link::new(ms(30)).with_loss(0.05))
+    }
+
+    fn block() -> Vec<u8> {
+        (0..64 * 1024).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn trees_respect_fanout_and_stay_shallow() {
+        let config = TurbineSimConfig::default();
+        let sim =
+            TurbineSim::new(config.clone(), Conditions::uniform(200, Link::new(ms(30)))).unwrap();
+        let tree = sim.shred_tree(1, 0);
+        // 1 + 8 + 64 + 127: three layers below the leader.
+        assert_eq!(tree.layers().len(), 4);
+        assert_eq!(tree.children("0").len(), 8);
+        let placed: usize = tree.layers().iter().map(Vec::len).sum();
+        assert_eq!(placed, 200);
+
+        // Each shred has its own tree, and the same inputs give the same one.
+        assert_ne!(sim.shred_tree(1, 0).layers(), sim.shred_tree(1, 1).layers());
+        assert_eq!(sim.shred_tree(1, 3).layers(), sim.shred_tree(1, 3).layers());
+    }
+
+    #[test]
+    fn every_node_rebuilds_the_block_within_the_tree_depth() {
+        let mut sim = TurbineSim::new(
+            TurbineSimConfig::default(),
+            Conditions::uniform(200, Link::new(ms(30))),
+        )
+        .unwrap();
+        let run = sim.broadcast(1, &block(), Duration::from_secs(2)).unwrap();
+        assert_eq!(run.reconstructed(), 200);
+        assert_eq!(run.depth, 3);
+        assert!(run.max_fanout <= 8);
+        assert_eq!(run.max_latency(), Some(ms(30 * 3)));
+        // Nodes nearer the leader in shred 0's tree than in the last tree
+        // they need start on the block a hop or more sooner.
+        let head_start = (1..200)
+            .filter(|node| run.first_bytes_at[*node] < run.reconstructed_at[*node])
+            .count();
+        assert!(head_start > 50, "{head_start}");
+        assert!((1..200).all(|node| run.first_bytes_at[node] <= run.reconstructed_at[node]));
+        // One copy of each of the 12 shreds per node, leader aside.
+        assert!(run.shreds_received[1..].iter().all(|n| *n == 12));
+        // Nobody sends more than the leader: a fanout's worth per shred.
+        let leader = sim.network().bytes_sent(0);
+        assert!((1..200).all(|node| sim.network().bytes_sent(node) <= leader));
+    }
+
+    #[test]
+    fn parity_covers_light_loss() {
+        let config = TurbineSimConfig {
+            seed: 9,
+            ..TurbineSimConfig::default()
+        };
+        let lossy = Link::new(ms(30)).with_jitter(ms(10)).with_loss(0.02);
+        let mut sim = TurbineSim::new(config.clone(), Conditions::uniform(200, lossy)).unwrap();
+        let run = sim.broadcast(7, &block(), Duration::from_secs(2)).unwrap();
+        assert!(run.reconstructed() >= 190, "{}", run.reconstructed());
+
+        // Same seed, same outcome.
+        let mut again = TurbineSim::new(config, Conditions::uniform(200, lossy)).unwrap();
+        assert_eq!(
+            again
+                .broadcast(7, &block(), Duration::from_secs(2))
+                .unwrap(),
+            run
+        );
+    }
+
+    #[test]
+    fn adaptive_coding_raises_parity_under_loss() {
+        let mut sim = TurbineSim::new(TurbineSimConfig::default(), lossy()).unwrap();
+        let mut coding = AdaptiveCoding::new(CodingPolicy::default());
+        let mut before = 0;
+        for slot in 0..5 {
+            let run = sim
+                .broadcast(slot, &block(), Duration::from_secs(2))
+                .unwrap();
+            for node in 1..200 {
+                coding.record_block(run.shreds_sent, run.shreds_missed(node));
+            }
+            before += run.reconstructed();
+        }
+        // 5% per hop, mostly three hops deep: about 13% of shreds miss a node.
+        let next = coding.on_epoch();
+        assert_eq!(next, ErasureParams::new(10, 4));
+
+        sim.set_coding(next).unwrap();
+        let mut after = 0;
+        for slot in 5..10 {
+            let run = sim
+                .broadcast(slot, &block(), Duration::from_secs(2))
+                .unwrap();
+            assert_eq!(run.shreds_sent, 14);
+            after += run.reconstructed();
+        }
+        assert!(after > before, "{after} <= {before}");
+        assert!(after >= 5 * 194, "{after}");
+    }
+}
@@ -1,5 +1,6 @@
 use std::net::SocketAddr;
 
+use aether_metrics::NET_METRICS;
 use anyhow::{Context, Result};
 use bytes::Bytes;
 use quinn::{Connection, RecvStream, SendStream};
@@ -10,6 +11,9 @@ use tracing::debug;
 /// Provides send/receive primitives for validator communication.
 /// Uses unidirectional streams for one-way messages (most common)
 /// and bidirectional streams for request/response patterns.
+///
+/// Cloning is cheap and yields a handle to the same connection.
+#[derive(Clone)]
 pub struct QuicConnection {
     inner: Connection,
 }
@@ -24,6 +28,16 @@ impl QuicConnection {
         self.inner.remote_address()
     }
 
+    /// Identifier that stays the same when the connection changes path
+    pub fn id(&self) -> usize {
+        self.inner.stable_id()
+    }
+
+    /// Whether the connection has been closed, by either side or by timeout
+    pub fn is_closed(&self) -> bool {
+        self.inner.close_reason().is_some()
+    }
+
     /// Send a message on a unidirectional stream
     ///
     /// Opens a new stream, writes the data, and closes it.
@@ -35,14 +49,19 @@ impl QuicConnection {
             .open_uni()
             .await
             .context("Failed to open uni stream")?;
+        NET_METRICS.quic_streams_opened.inc();
 
         let data = data.into();
-        stream
-            .write_all(&data)
-            .await
-            .context("Failed to write to stream")?;
-
-        stream.finish().await.context("Failed to finish stream")?;
+        let sent = async {
+            stream
+                .write_all(&data)
+                .await
+                .context("Failed to write to stream")?;
+            stream.finish().await.context("Failed to finish stream")
+        }
+        .await;
+        NET_METRICS.quic_streams_closed.inc();
+        sent?;
 
         debug!("Sent {} bytes to {}", data.len(), self.remote());
 
@@ -59,19 +78,24 @@ impl QuicConnection {
             .open_bi()
             .await
             .context("Failed to open bi stream")?;
+        NET_METRICS.quic_streams_opened.inc();
 
-        // Send request
         let data = data.into();
-        send.write_all(&data)
-            .await
-            .context("Failed to write request")?;
-        send.finish().await.context("Failed to finish send")?;
-
-        // Receive response
-        let response = recv
-            .read_to_end(4_000_000) // 4MB max response
-            .await
-            .context("Failed to read response")?;
+        let response = async {
+            // Send request
+            send.write_all(&data)
+                .await
+                .context("Failed to write request")?;
+            send.finish().await.context("Failed to finish send")?;
+
+            // Receive response
+            recv.read_to_end(4_000_000) // 4MB max response
+                .await
+                .context("Failed to read response")
+        }
+        .await;
+        NET_METRICS.quic_streams_closed.inc();
+        let response = response?;
 
         debug!(
             "Sent {} bytes, received {} bytes from {}",
@@ -85,26 +109,36 @@ impl QuicConnection {
 
     /// Accept an incoming unidirectional stream
     pub async fn accept_uni(&self) -> Result<RecvStream> {
-        self.inner
+        let stream = self
+            .inner
             .accept_uni()
             .await
-            .context("Failed to accept uni stream")
+            .context("Failed to accept uni stream")?;
+        NET_METRICS.quic_streams_opened.inc();
+        Ok(stream)
     }
 
     /// Accept an incoming bidirectional stream
     pub async fn accept_bi(&self) -> Result<(SendStream, RecvStream)> {
-        self.inner
+        let streams = self
+            .inner
             .accept_bi()
             .await
-            .context("Failed to accept bi stream")
+            .context("Failed to accept bi stream")?;
+        NET_METRICS.quic_streams_opened.inc();
+        Ok(streams)
     }
 
     /// Read all data from a stream (up to 4MB)
+    ///
+    /// The stream counts as closed once read, whether or not it succeeded.
     pub async fn read_stream(stream: &mut RecvStream) -> Result<Vec<u8>> {
-        stream
+        let data = stream
             .read_to_end(4_000_000)
             .await
-            .context("Failed to read stream")
+            .context("Failed to read stream");
+        NET_METRICS.quic_streams_closed.inc();
+        data
     }
 
     /// Close the connection gracefully
@@ -115,22 +149,27 @@ impl QuicConnection {
     /// Get connection statistics for monitoring
     pub fn stats(&self) -> ConnectionStats {
         let stats = self.inner.stats();
-        // Note: Quinn doesn't expose total bytes, only UDP datagrams
-        // For now, use 0 as placeholder until we add our own tracking
+        // UDP payload bytes, so QUIC framing and retransmissions included
         ConnectionStats {
-            bytes_sent: 0,
-            bytes_received: 0,
+            bytes_sent: stats.udp_tx.bytes,
+            bytes_received: stats.udp_rx.bytes,
             rtt: stats.path.rtt,
+            lost_packets: stats.path.lost_packets,
+            congestion_events: stats.path.congestion_events,
         }
     }
 }
 
 /// Connection statistics for monitoring
-#[derive(Debug, Clone)]
+///
+/// Counters are totals over the life of the connection.
+#[derive(Debug, Clone, Default)]
 pub struct ConnectionStats {
     pub bytes_sent: u64,
     pub bytes_received: u64,
     pub rtt: std::time::Duration,
+    pub lost_packets: u64,
+    pub congestion_events: u64,
 }
 
 #[cfg(test)]
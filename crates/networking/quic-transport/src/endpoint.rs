@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
+use aether_metrics::NET_METRICS;
 use anyhow::{Context, Result};
 use quinn::{ClientConfig, Endpoint, ServerConfig, TransportConfig};
 use tracing::{debug, info};
@@ -57,7 +58,27 @@ impl QuicEndpoint {
             .context("Failed to get local address")
     }
 
+    /// Move the endpoint to a new local address
+    ///
+    /// Open connections migrate to the new path; peers see our packets
+    /// arrive from the new address and validate it before using it.
+    pub fn rebind(&self, bind_addr: SocketAddr) -> Result<()> {
+        let socket = std::net::UdpSocket::bind(bind_addr).context("Failed to bind UDP socket")?;
+        self.inner
+            .rebind(socket)
+            .context("Failed to rebind QUIC endpoint")?;
+        NET_METRICS.quic_path_migrations.inc();
+        info!("QUIC endpoint moved to {}", self.local_addr()?);
+        Ok(())
+    }
+
     /// Connect to a remote peer
+    ///
+    /// With a session ticket from an earlier connection to the same peer,
+    /// the connection is resumed with 0-RTT and usable at once: the first
+    /// messages ride along with the handshake. They can be replayed by an
+    /// attacker, which is harmless for the idempotent votes and shreds sent
+    /// this way.
     pub async fn connect(&self, remote: SocketAddr) -> Result<QuicConnection> {
         debug!("Connecting to {}", remote);
 
@@ -67,9 +88,23 @@ impl QuicEndpoint {
             .connect(remote, "validator.aether.local")
             .context("Failed to initiate connection")?;
 
-        let connection = connecting.await.context("Connection handshake failed")?;
+        let connection = match connecting.into_0rtt() {
+            Ok((connection, accepted)) => {
+                tokio::spawn(async move {
+                    if accepted.await {
+                        NET_METRICS.quic_zero_rtt_accepted.inc();
+                    } else {
+                        NET_METRICS.quic_zero_rtt_rejected.inc();
+                    }
+                });
+                debug!("Resuming connection to {} with 0-RTT", remote);
+                connection
+            }
+            Err(connecting) => connecting.await.context("Connection handshake failed")?,
+        };
 
         info!("Connected to {}", remote);
+        NET_METRICS.connections_total.inc();
 
         Ok(QuicConnection::new(connection))
     }
@@ -81,10 +116,12 @@ impl QuicEndpoint {
         match connecting.await {
             Ok(connection) => {
                 info!("Accepted connection from {}", connection.remote_address());
+                NET_METRICS.connections_total.inc();
                 Some(QuicConnection::new(connection))
             }
             Err(e) => {
                 tracing::warn!("Failed to accept connection: {}", e);
+                NET_METRICS.connection_errors.inc();
                 None
             }
         }
@@ -105,10 +142,14 @@ fn configure_server(cert: rustls::Certificate, key: rustls::PrivateKey) -> Resul
         .context("Failed to configure TLS")?;
 
     server_crypto.alpn_protocols = vec![b"aether/1".to_vec()];
-    server_crypto.max_early_data_size = 0; // Disable 0-RTT for now
+    // Accept 0-RTT data from clients resuming an earlier session
+    server_crypto.max_early_data_size = u32::MAX;
 
     let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
     server_config.transport_config(Arc::new(create_transport_config()));
+    // Follow clients whose address changes mid-connection (NAT rebinding,
+    // switching networks) instead of dropping them
+    server_config.migration(true);
 
     Ok(server_config)
 }
@@ -126,6 +167,7 @@ fn configure_client(cert: rustls::Certificate) -> Result<ClientConfig> {
         .with_no_client_auth();
 
     client_crypto.alpn_protocols = vec![b"aether/1".to_vec()];
+    client_crypto.enable_early_data = true;
 
     let mut client_config = ClientConfig::new(Arc::new(client_crypto));
     client_config.transport_config(Arc::new(create_transport_config()));
@@ -133,14 +175,19 @@ fn configure_client(cert: rustls::Certificate) -> Result<ClientConfig> {
     Ok(client_config)
 }
 
+/// Datagrams buffered per connection in each direction: a 2MB block's
+/// worth of shreds with room to spare.
+const DATAGRAM_BUFFER: usize = 4 * 1024 * 1024;
+
 /// Create optimized transport configuration for low-latency validator traffic
 ///
 /// Key optimizations:
 /// - 10MB stream/connection windows for high throughput
-/// - 5s keep-alive to detect dead connections quickly
+/// - 5s keep-alive to detect dead connections quickly and keep idle
+///   pooled connections (and their NAT mappings) open
 /// - 30s idle timeout for fast cleanup
 /// - 1000 max concurrent streams for high fan-out (Turbine)
-fn create_transport_config() -> TransportConfig {
+pub(crate) fn create_transport_config() -> TransportConfig {
     let mut config = TransportConfig::default();
 
     // Large windows for high throughput
@@ -155,8 +202,10 @@ fn create_transport_config() -> TransportConfig {
         config.max_idle_timeout(Some(timeout));
     }
 
-    // Disable datagram (we use streams for reliability)
-    config.datagram_receive_buffer_size(None);
+    // Offer the DATAGRAM extension for traffic that is better lost than
+    // late (shreds); everything else uses streams for reliability
+    config.datagram_receive_buffer_size(Some(DATAGRAM_BUFFER));
+    config.datagram_send_buffer_size(DATAGRAM_BUFFER);
 
     config
 }
@@ -49,6 +49,26 @@
 //                 handle_message(data)
 // ```
 //
+// CONNECTION POOL:
+// One connection per peer, shared by every sender. The first message
+// dials (with 0-RTT when an earlier session left a ticket), later ones
+// open streams on the same connection, and keep-alives hold it open while
+// idle. Peers that change address keep their connection (path migration),
+// and our own endpoint can move with `rebind`. Pool maintenance reports
+// RTT, loss, traffic and stream counts to NET_METRICS.
+//
+// VALIDATOR MESH:
+// Votes and shreds between validators travel over a private mesh kept
+// apart from public gossip. Every mesh certificate is self-signed with the
+// validator's own Ed25519 key and both ends present one; a handshake only
+// completes if the peer's key is in the current validator set and, when
+// dialing, is the validator we meant to reach. Leaving the set drops the
+// connection.
+//
+// Shreds that fit in one QUIC DATAGRAM frame are sent unreliably: erasure
+// coding covers losses, so retransmission would only delay them. Peers
+// that do not negotiate the extension, and larger shreds, get uni streams.
+//
 // OUTPUTS:
 // - Reliable message delivery → P2P layer
 // - Connection metrics → Monitoring
@@ -56,5 +76,9 @@
 
 pub mod connection;
 pub mod endpoint;
+pub mod mesh;
+pub mod pool;
 
 pub use endpoint::QuicEndpoint;
+pub use mesh::{MeshConfig, MeshMessage, MeshMessageKind, ValidatorMesh};
+pub use pool::{ConnectionPool, PoolConfig};
@@ -0,0 +1,820 @@
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::SystemTime;
+
+use aether_crypto_primitives::{ed25519, Keypair};
+use aether_metrics::NET_METRICS;
+use anyhow::{anyhow, bail, Context, Result};
+use bytes::Bytes;
+use quinn::{ClientConfig, Connection, Endpoint, SendDatagramError, ServerConfig, TransportConfig};
+use rustls::client::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::server::{ClientCertVerified, ClientCertVerifier};
+use rustls::{
+    Certificate, CertificateError, DigitallySignedStruct, DistinguishedName, PrivateKey,
+    ServerName, SignatureScheme,
+};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::endpoint::create_transport_config;
+
+/// ALPN for the validator mesh, distinct from the public transport's so
+/// the two can never be confused on a shared port.
+const MESH_ALPN: &[u8] = b"aether-mesh/1";
+
+/// Name the mesh certificates are issued for. Identity comes from the key,
+/// not the name, so every validator uses the same one.
+const MESH_SERVER_NAME: &str = "validator.aether.local";
+
+/// Largest message accepted on the mesh: one shred with headroom.
+pub const MAX_MESH_MESSAGE: usize = 1024 * 1024;
+
+/// Inbound messages buffered before readers wait for the node to catch up.
+const INBOUND_CAPACITY: usize = 4096;
+
+/// DER SubjectPublicKeyInfo header of an Ed25519 key, followed by the 32
+/// key bytes.
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// PKCS#8 v1 header of an Ed25519 private key, followed by the 32-byte seed.
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// What a mesh message carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshMessageKind {
+    Vote,
+    Shred,
+}
+
+impl MeshMessageKind {
+    fn tag(self) -> u8 {
+        match self {
+            MeshMessageKind::Vote => 1,
+            MeshMessageKind::Shred => 2,
+        }
+    }
+
+    fn from_tag(tag: u8) -> Option<Self> {
+        match tag {
+            1 => Some(MeshMessageKind::Vote),
+            2 => Some(MeshMessageKind::Shred),
+            _ => None,
+        }
+    }
+}
+
+/// A message from another validator, with the key that authenticated the
+/// connection it arrived on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeshMessage {
+    pub from: [u8; 32],
+    pub kind: MeshMessageKind,
+    pub payload: Vec<u8>,
+}
+
+/// The validator keys allowed on the mesh, shared with the TLS verifiers
+/// so a change applies to the next handshake.
+type Members = Arc<RwLock<HashSet<[u8; 32]>>>;
+
+/// Settings for [`ValidatorMesh`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeshConfig {
+    /// Offer the QUIC DATAGRAM extension and send shreds that fit in one
+    /// datagram unreliably. Erasure coding already covers lost shreds, so
+    /// retransmitting them only adds latency. Shreds to peers without the
+    /// extension, and shreds too large for a datagram, go on streams.
+    pub datagrams: bool,
+}
+
+impl Default for MeshConfig {
+    fn default() -> Self {
+        MeshConfig { datagrams: true }
+    }
+}
+
+/// A mutually authenticated QUIC overlay between validators, for votes and
+/// shreds, kept apart from public gossip.
+///
+/// Each validator's TLS certificate is self-signed with its validator
+/// Ed25519 key, and both sides present one. A peer is admitted only if
+/// that key is in the current validator set, and its handshake signature
+/// is checked against the key itself rather than whatever else the
+/// certificate claims, so a certificate naming someone else's key does
+/// not pass. Nobody outside the set can open a connection, let alone
+/// inject votes.
+pub struct ValidatorMesh {
+    endpoint: Endpoint,
+    public_key: [u8; 32],
+    certificate: Certificate,
+    private_key: PrivateKey,
+    members: Members,
+    peers: Arc<Mutex<HashMap<[u8; 32], Connection>>>,
+    inbound: mpsc::Sender<MeshMessage>,
+    datagrams: bool,
+    transport: Arc<TransportConfig>,
+}
+
+impl ValidatorMesh {
+    /// Listen on `bind_addr` as `validator`, admitting `members`. Returns
+    /// the mesh and the receiver for messages from other validators.
+    pub async fn bind(
+        bind_addr: SocketAddr,
+        validator: &Keypair,
+        members: impl IntoIterator<Item = [u8; 32]>,
+    ) -> Result<(Self, mpsc::Receiver<MeshMessage>)> {
+        Self::bind_with(bind_addr, validator, members, MeshConfig::default()).await
+    }
+
+    /// [`bind`](Self::bind) with explicit settings.
+    pub async fn bind_with(
+        bind_addr: SocketAddr,
+        validator: &Keypair,
+        members: impl IntoIterator<Item = [u8; 32]>,
+        config: MeshConfig,
+    ) -> Result<(Self, mpsc::Receiver<MeshMessage>)> {
+        let public_key: [u8; 32] = validator
+            .public_key()
+            .try_into()
+            .map_err(|_| anyhow!("validator key is not 32 bytes"))?;
+        let (certificate, private_key) = validator_certificate(validator)?;
+        let members: Members = Arc::new(RwLock::new(members.into_iter().collect()));
+
+        let mut server_crypto = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_client_cert_verifier(Arc::new(MemberVerifier {
+                members: members.clone(),
+                pinned: None,
+            }))
+            .with_single_cert(vec![certificate.clone()], private_key.clone())
+            .context("Failed to configure mesh TLS")?;
+        server_crypto.alpn_protocols = vec![MESH_ALPN.to_vec()];
+        let mut transport = create_transport_config();
+        if !config.datagrams {
+            transport.datagram_receive_buffer_size(None);
+            transport.datagram_send_buffer_size(0);
+        }
+        let transport = Arc::new(transport);
+        let mut server_config = ServerConfig::with_crypto(Arc::new(server_crypto));
+        server_config.transport_config(transport.clone());
+
+        let endpoint = Endpoint::server(server_config, bind_addr)
+            .context("Failed to bind validator mesh endpoint")?;
+        info!("Validator mesh listening on {}", endpoint.local_addr()?);
+
+        let (inbound, receiver) = mpsc::channel(INBOUND_CAPACITY);
+        Ok((
+            ValidatorMesh {
+                endpoint,
+                public_key,
+                certificate,
+                private_key,
+                members,
+                peers: Arc::new(Mutex::new(HashMap::new())),
+                inbound,
+                datagrams: config.datagrams,
+                transport,
+            },
+            receiver,
+        ))
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.endpoint
+            .local_addr()
+            .context("Failed to get local address")
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    /// Replace the validator set, disconnecting anyone no longer in it.
+    pub fn set_members(&self, members: impl IntoIterator<Item = [u8; 32]>) {
+        let members: HashSet<[u8; 32]> = members.into_iter().collect();
+        lock(&self.peers).retain(|key, connection| {
+            let keep = members.contains(key);
+            if !keep {
+                connection.close(0u32.into(), b"left validator set");
+            }
+            keep
+        });
+        *self.members.write().unwrap_or_else(|e| e.into_inner()) = members;
+    }
+
+    /// Connect to the validator with key `validator` at `remote`. The
+    /// handshake fails unless the other end proves it holds that key.
+    pub async fn connect(&self, remote: SocketAddr, validator: [u8; 32]) -> Result<()> {
+        if lock(&self.peers).contains_key(&validator) {
+            return Ok(());
+        }
+        let mut client_crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(MemberVerifier {
+                members: self.members.clone(),
+                pinned: Some(validator),
+            }))
+            .with_client_auth_cert(vec![self.certificate.clone()], self.private_key.clone())
+            .context("Failed to configure mesh client TLS")?;
+        client_crypto.alpn_protocols = vec![MESH_ALPN.to_vec()];
+        let mut client_config = ClientConfig::new(Arc::new(client_crypto));
+        client_config.transport_config(self.transport.clone());
+
+        let connection = self
+            .endpoint
+            .connect_with(client_config, remote, MESH_SERVER_NAME)
+            .context("Failed to initiate mesh connection")?
+            .await
+            .context("Mesh handshake failed")?;
+        debug!("Mesh connected to validator at {}", remote);
+        self.register(validator, connection);
+        Ok(())
+    }
+
+    /// Accept the next validator connection, returning its key. `None` once
+    /// the endpoint is closed.
+    pub async fn accept(&self) -> Option<Result<[u8; 32]>> {
+        let connecting = self.endpoint.accept().await?;
+        Some(self.finish_accept(connecting).await)
+    }
+
+    async fn finish_accept(&self, connecting: quinn::Connecting) -> Result<[u8; 32]> {
+        let connection = connecting.await.context("Mesh handshake failed")?;
+        let validator = peer_key(&connection)
+            .ok_or_else(|| anyhow!("mesh peer presented no validator certificate"))?;
+        debug!(
+            "Mesh accepted validator from {}",
+            connection.remote_address()
+        );
+        self.register(validator, connection);
+        Ok(validator)
+    }
+
+    /// Send one message to `validator` over an existing connection.
+    pub async fn send(
+        &self,
+        validator: &[u8; 32],
+        kind: MeshMessageKind,
+        payload: &[u8],
+    ) -> Result<()> {
+        let connection = lock(&self.peers)
+            .get(validator)
+            .cloned()
+            .ok_or_else(|| anyhow!("not connected to that validator"))?;
+        send_on(&connection, self.datagrams, kind, payload).await
+    }
+
+    /// Send one message to every connected validator, returning how many
+    /// it reached.
+    pub async fn broadcast(&self, kind: MeshMessageKind, payload: &[u8]) -> usize {
+        let connections: Vec<([u8; 32], Connection)> = lock(&self.peers)
+            .iter()
+            .map(|(key, connection)| (*key, connection.clone()))
+            .collect();
+        let mut sent = 0;
+        for (key, connection) in connections {
+            match send_on(&connection, self.datagrams, kind, payload).await {
+                Ok(()) => sent += 1,
+                Err(e) => debug!(peer = %short_hex(&key), err = %e, "mesh send failed"),
+            }
+        }
+        sent
+    }
+
+    /// Keys of the validators currently connected.
+    pub fn connected(&self) -> Vec<[u8; 32]> {
+        lock(&self.peers).keys().copied().collect()
+    }
+
+    /// Largest shred that goes to `validator` as a datagram, or `None` if
+    /// shreds to it go on streams because one side has no datagram support.
+    pub fn max_datagram_shred(&self, validator: &[u8; 32]) -> Option<usize> {
+        if !self.datagrams {
+            return None;
+        }
+        let max = lock(&self.peers).get(validator)?.max_datagram_size()?;
+        max.checked_sub(1)
+    }
+
+    pub fn close(&self) {
+        self.endpoint.close(0u32.into(), b"mesh shutdown");
+    }
+
+    /// Track `connection` as the one to `validator` and start reading from
+    /// it. A newer connection replaces an older one.
+    fn register(&self, validator: [u8; 32], connection: Connection) {
+        if let Some(old) = lock(&self.peers).insert(validator, connection.clone()) {
+            old.close(0u32.into(), b"replaced");
+        }
+        let peers = self.peers.clone();
+        let inbound = self.inbound.clone();
+        tokio::spawn(async move {
+            tokio::join!(
+                read_messages(validator, &connection, inbound.clone()),
+                read_datagrams(validator, &connection, inbound),
+            );
+            let mut peers = lock(&peers);
+            if peers
+                .get(&validator)
+                .is_some_and(|c| c.stable_id() == connection.stable_id())
+            {
+                peers.remove(&validator);
+            }
+        });
+    }
+}
+
+async fn send_on(
+    connection: &Connection,
+    datagrams: bool,
+    kind: MeshMessageKind,
+    payload: &[u8],
+) -> Result<()> {
+    if payload.len() + 1 > MAX_MESH_MESSAGE {
+        bail!("mesh message too large: {} bytes", payload.len());
+    }
+    if kind == MeshMessageKind::Shred && datagrams {
+        if send_datagram(connection, kind, payload)? {
+            return Ok(());
+        }
+        NET_METRICS.quic_datagram_fallbacks.inc();
+    }
+    let mut stream = connection
+        .open_uni()
+        .await
+        .context("Failed to open uni stream")?;
+    stream
+        .write_all(&[kind.tag()])
+        .await
+        .context("Failed to write to stream")?;
+    stream
+        .write_all(payload)
+        .await
+        .context("Failed to write to stream")?;
+    stream.finish().await.context("Failed to finish stream")?;
+    Ok(())
+}
+
+/// Send a message as one unreliable QUIC datagram. Returns false, having
+/// sent nothing, if the peer did not negotiate datagrams or the message
+/// does not fit in one.
+fn send_datagram(connection: &Connection, kind: MeshMessageKind, payload: &[u8]) -> Result<bool> {
+    let Some(max) = connection.max_datagram_size() else {
+        return Ok(false);
+    };
+    if payload.len() + 1 > max {
+        return Ok(false);
+    }
+    let mut datagram = Vec::with_capacity(payload.len() + 1);
+    datagram.push(kind.tag());
+    datagram.extend_from_slice(payload);
+    match connection.send_datagram(Bytes::from(datagram)) {
+        Ok(()) => {
+            NET_METRICS.quic_datagrams_sent.inc();
+            Ok(true)
+        }
+        Err(SendDatagramError::ConnectionLost(e)) => Err(e).context("Failed to send datagram"),
+        // The path MTU shrank, or support vanished: use a stream instead.
+        Err(_) => Ok(false),
+    }
+}
+
+/// Deliver shreds the peer sent as datagrams. Only shreds may travel this
+/// way; anything else must not be lost, so a peer sending it is broken.
+async fn read_datagrams(
+    validator: [u8; 32],
+    connection: &Connection,
+    inbound: mpsc::Sender<MeshMessage>,
+) {
+    while let Ok(datagram) = connection.read_datagram().await {
+        let Some((&tag, payload)) = datagram.split_first() else {
+            continue;
+        };
+        if MeshMessageKind::from_tag(tag) != Some(MeshMessageKind::Shred) {
+            warn!(peer = %short_hex(&validator), tag, "dropping mesh datagram that is not a shred");
+            continue;
+        }
+        NET_METRICS.quic_datagrams_received.inc();
+        let message = MeshMessage {
+            from: validator,
+            kind: MeshMessageKind::Shred,
+            payload: payload.to_vec(),
+        };
+        if inbound.send(message).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn read_messages(
+    validator: [u8; 32],
+    connection: &Connection,
+    inbound: mpsc::Sender<MeshMessage>,
+) {
+    loop {
+        let mut stream = match connection.accept_uni().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                debug!(peer = %short_hex(&validator), err = %e, "mesh connection closed");
+                return;
+            }
+        };
+        let data = match stream.read_to_end(MAX_MESH_MESSAGE).await {
+            Ok(data) => data,
+            Err(e) => {
+                warn!(peer = %short_hex(&validator), err = %e, "dropping unreadable mesh message");
+                continue;
+            }
+        };
+        let Some((&tag, payload)) = data.split_first() else {
+            continue;
+        };
+        let Some(kind) = MeshMessageKind::from_tag(tag) else {
+            warn!(peer = %short_hex(&validator), tag, "dropping mesh message of unknown kind");
+            continue;
+        };
+        let message = MeshMessage {
+            from: validator,
+            kind,
+            payload: payload.to_vec(),
+        };
+        if inbound.send(message).await.is_err() {
+            return;
+        }
+    }
+}
+
+/// A self-signed certificate for `validator`'s own Ed25519 key.
+fn validator_certificate(validator: &Keypair) -> Result<(Certificate, PrivateKey)> {
+    let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+    pkcs8.extend_from_slice(validator.secret_key().expose_secret());
+    let key_pair = rcgen::KeyPair::from_der(&pkcs8).context("Failed to load validator key")?;
+    let mut params = rcgen::CertificateParams::new(vec![MESH_SERVER_NAME.to_string()]);
+    params.alg = &rcgen::PKCS_ED25519;
+    params.key_pair = Some(key_pair);
+    let cert = rcgen::Certificate::from_params(params).context("Failed to generate certificate")?;
+    let cert_der = cert.serialize_der().context("Failed to serialize cert")?;
+    Ok((Certificate(cert_der), PrivateKey(pkcs8)))
+}
+
+/// The Ed25519 key a certificate carries. `None` unless there is exactly
+/// one, so a certificate cannot smuggle a second key in elsewhere.
+fn certificate_key(cert: &Certificate) -> Option<[u8; 32]> {
+    let mut starts = cert
+        .0
+        .windows(ED25519_SPKI_PREFIX.len())
+        .enumerate()
+        .filter(|(_, window)| *window == ED25519_SPKI_PREFIX)
+        .map(|(i, _)| i + ED25519_SPKI_PREFIX.len());
+    let start = starts.next()?;
+    if starts.next().is_some() {
+        return None;
+    }
+    cert.0.get(start..start + 32)?.try_into().ok()
+}
+
+fn peer_key(connection: &Connection) -> Option<[u8; 32]> {
+    let certificates = connection
+        .peer_identity()?
+        .downcast::<Vec<Certificate>>()
+        .ok()?;
+    certificate_key(certificates.first()?)
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn short_hex(key: &[u8; 32]) -> String {
+    key[..8].iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Admits certificates whose key is a current member, and, when dialing,
+/// only the one key we meant to reach.
+struct MemberVerifier {
+    members: Members,
+    pinned: Option<[u8; 32]>,
+}
+
+impl MemberVerifier {
+    fn check(&self, cert: &Certificate) -> Result<(), rustls::Error> {
+        let key = certificate_key(cert).ok_or(rustls::Error::InvalidCertificate(
+            CertificateError::BadEncoding,
+        ))?;
+        if self.pinned.is_some_and(|pinned| pinned != key) {
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::NotValidForName,
+            ));
+        }
+        let members = self.members.read().unwrap_or_else(|e| e.into_inner());
+        if !members.contains(&key) {
+            return Err(rustls::Error::InvalidCertificate(
+                CertificateError::UnknownIssuer,
+            ));
+        }
+        Ok(())
+    }
+
+    /// Check the handshake signature with the certificate's key directly.
+    fn verify_signature(
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        if dss.scheme != SignatureScheme::ED25519 {
+            return Err(rustls::Error::PeerIncompatible(
+                rustls::PeerIncompatible::NoSignatureSchemesInCommon,
+            ));
+        }
+        let key = certificate_key(cert).ok_or(rustls::Error::InvalidCertificate(
+            CertificateError::BadEncoding,
+        ))?;
+        ed25519::verify(&key, message, dss.signature())
+            .map_err(|_| rustls::Error::InvalidCertificate(CertificateError::BadSignature))?;
+        Ok(HandshakeSignatureValid::assertion())
+    }
+}
+
+impl ServerCertVerifier for MemberVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _server_name: &ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        self.check(end_entity)?;
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &Certificate,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::PeerIncompatible(
+            rustls::PeerIncompatible::Tls13RequiredForQuic,
+        ))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Self::verify_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+
+    fn request_scts(&self) -> bool {
+        false
+    }
+}
+
+impl ClientCertVerifier for MemberVerifier {
+    fn client_auth_mandatory(&self) -> bool {
+        true
+    }
+
+    fn client_auth_root_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &Certificate,
+        _intermediates: &[Certificate],
+        _now: SystemTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.check(end_entity)?;
+        Ok(ClientCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &Certificate,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Err(rustls::Error::PeerIncompatible(
+            rustls::PeerIncompatible::Tls13RequiredForQuic,
+        ))
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &Certificate,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Self::verify_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        vec![SignatureScheme::ED25519]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn key_of(keypair: &Keypair) -> [u8; 32] {
+        keypair.public_key().try_into().unwrap()
+    }
+
+    async fn bind(
+        keypair: &Keypair,
+        members: &[[u8; 32]],
+    ) -> Option<(ValidatorMesh, mpsc::Receiver<MeshMessage>)> {
+        match ValidatorMesh::bind(
+            "127.0.0.1:0".parse().unwrap(),
+            keypair,
+            members.iter().copied(),
+        )
+        .await
+        {
+            Ok(mesh) => Some(mesh),
+            Err(err) => {
+                eprintln!("Skipping validator mesh test: {err}");
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn certificate_carries_validator_key() {
+        let keypair = Keypair::generate();
+        let (cert, _) = validator_certificate(&keypair).unwrap();
+        assert_eq!(certificate_key(&cert), Some(key_of(&keypair)));
+
+        // A second key anywhere in the certificate makes it ambiguous.
+        let mut doubled = cert.0.clone();
+        doubled.extend_from_slice(&ED25519_SPKI_PREFIX);
+        doubled.extend_from_slice(&[7u8; 32]);
+        assert_eq!(certificate_key(&Certificate(doubled)), None);
+    }
+
+    #[tokio::test]
+    async fn validators_exchange_votes_and_shreds() {
+        let (a, b) = (Keypair::generate(), Keypair::generate());
+        let members = [key_of(&a), key_of(&b)];
+        let Some((server, mut server_rx)) = bind(&a, &members).await else {
+            return;
+        };
+        let Some((client, mut client_rx)) = bind(&b, &members).await else {
+            return;
+        };
+        let addr = server.local_addr().unwrap();
+
+        let (accepted, connected) = tokio::join!(server.accept(), client.connect(addr, key_of(&a)));
+        connected.unwrap();
+        assert_eq!(accepted.unwrap().unwrap(), key_of(&b));
+
+        client
+            .send(&key_of(&a), MeshMessageKind::Vote, b"vote")
+            .await
+            .unwrap();
+        let message = server_rx.recv().await.unwrap();
+        assert_eq!(
+            message,
+            MeshMessage {
+                from: key_of(&b),
+                kind: MeshMessageKind::Vote,
+                payload: b"vote".to_vec(),
+            }
+        );
+
+        assert_eq!(server.broadcast(MeshMessageKind::Shred, b"shred").await, 1);
+        let message = client_rx.recv().await.unwrap();
+        assert_eq!(
+            (message.from, message.kind),
+            (key_of(&a), MeshMessageKind::Shred)
+        );
+    }
+
+    #[tokio::test]
+    async fn outsiders_and_impostors_are_refused() {
+        let (a, b, outsider) = (
+            Keypair::generate(),
+            Keypair::generate(),
+            Keypair::generate(),
+        );
+        let members = [key_of(&a), key_of(&b)];
+        let Some((server, _rx)) = bind(&a, &members).await else {
+            return;
+        };
+        let addr = server.local_addr().unwrap();
+        let server = Arc::new(server);
+        let acceptor = server.clone();
+        tokio::spawn(async move { while acceptor.accept().await.is_some() {} });
+
+        // Not in the validator set: the server refuses the client cert.
+        let Some((stranger, _)) = bind(&outsider, &members).await else {
+            return;
+        };
+        // Under TLS 1.3 the client may finish before the server rejects
+        // its certificate, so check the server's side.
+        let _ = tokio::time::timeout(Duration::from_secs(5), stranger.connect(addr, key_of(&a)))
+            .await
+            .unwrap();
+        assert!(never_admitted(&server, &key_of(&outsider)).await);
+
+        // Dialing a as if it were b: the client refuses the server cert.
+        let Some((member, _)) = bind(&b, &members).await else {
+            return;
+        };
+        let wrong = member.connect(addr, key_of(&b)).await;
+        assert!(wrong.is_err());
+        assert!(member.connected().is_empty());
+
+        // Dropping a validator from the set cuts it off.
+        member.connect(addr, key_of(&a)).await.unwrap();
+        server.set_members([key_of(&a)]);
+        assert!(!server.connected().contains(&key_of(&b)));
+    }
+
+    #[tokio::test]
+    async fn shreds_ride_datagrams_when_both_sides_offer_them() {
+        let (a, b, c) = (
+            Keypair::generate(),
+            Keypair::generate(),
+            Keypair::generate(),
+        );
+        let members = [key_of(&a), key_of(&b), key_of(&c)];
+        let Some((leader, _)) = bind(&a, &members).await else {
+            return;
+        };
+        let Some((modern, mut modern_rx)) = bind(&b, &members).await else {
+            return;
+        };
+        let Ok((legacy, mut legacy_rx)) = ValidatorMesh::bind_with(
+            "127.0.0.1:0".parse().unwrap(),
+            &c,
+            members,
+            MeshConfig { datagrams: false },
+        )
+        .await
+        else {
+            return;
+        };
+        let leader = Arc::new(leader);
+        let acceptor = leader.clone();
+        tokio::spawn(async move { while acceptor.accept().await.is_some() {} });
+        let addr = leader.local_addr().unwrap();
+        modern.connect(addr, key_of(&a)).await.unwrap();
+        legacy.connect(addr, key_of(&a)).await.unwrap();
+        while leader.connected().len() < 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let max = leader
+            .max_datagram_shred(&key_of(&b))
+            .expect("both sides offer datagrams");
+        assert!(max >= 1000);
+        assert_eq!(leader.max_datagram_shred(&key_of(&c)), None);
+        assert_eq!(legacy.max_datagram_shred(&key_of(&a)), None);
+
+        // Small shreds go as datagrams to b and on streams to c; one too
+        // big for a datagram goes on a stream to both.
+        let small = vec![1u8; max];
+        let large = vec![2u8; max + 1];
+        let datagrams_before = NET_METRICS.quic_datagrams_received.get();
+        for shred in [&small, &large] {
+            assert_eq!(leader.broadcast(MeshMessageKind::Shred, shred).await, 2);
+        }
+        for rx in [&mut modern_rx, &mut legacy_rx] {
+            let mut received = Vec::new();
+            for _ in 0..2 {
+                let message = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+                assert_eq!(message.kind, MeshMessageKind::Shred);
+                received.push(message.payload);
+            }
+            received.sort_by_key(Vec::len);
+            assert_eq!(received, vec![small.clone(), large.clone()]);
+        }
+        assert!(NET_METRICS.quic_datagrams_received.get() > datagrams_before);
+    }
+
+    /// Whether `key` is still absent from the server's peers once the
+    /// handshake has had time to settle.
+    async fn never_admitted(server: &ValidatorMesh, key: &[u8; 32]) -> bool {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        !server.connected().contains(key)
+    }
+}
@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use aether_metrics::NET_METRICS;
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use tracing::debug;
+
+use crate::connection::{ConnectionStats, QuicConnection};
+use crate::endpoint::QuicEndpoint;
+
+/// Settings for [`ConnectionPool`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolConfig {
+    /// Connections unused this long are closed. Until then keep-alives
+    /// hold them open, so the next message skips the handshake.
+    pub max_idle: Duration,
+    /// How long to wait for a new connection before giving up.
+    pub dial_timeout: Duration,
+}
+
+impl Default for PoolConfig {
+    fn default() -> Self {
+        PoolConfig {
+            max_idle: Duration::from_secs(120),
+            dial_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+struct Pooled {
+    conn: QuicConnection,
+    last_used: Instant,
+    /// Stats at the last [`ConnectionPool::maintain`], to turn lifetime
+    /// totals into increments for the metrics.
+    reported: ConnectionStats,
+}
+
+/// One QUIC connection per peer, shared by everything sending to it.
+///
+/// The first send to a peer dials it (resuming with 0-RTT where a session
+/// ticket allows); later sends open streams on the same connection.
+/// Concurrent first sends wait on a single dial. Connections the peer
+/// opened can be added too, so replies go back the same way.
+///
+/// Connections are keyed by remote address. Call [`maintain`](Self::maintain)
+/// periodically: it closes idle connections, drops dead ones, follows peers
+/// whose address changed, and reports RTT, loss and traffic to
+/// `NET_METRICS`.
+pub struct ConnectionPool {
+    endpoint: QuicEndpoint,
+    config: PoolConfig,
+    conns: Mutex<HashMap<SocketAddr, Pooled>>,
+    dialing: Mutex<HashMap<SocketAddr, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl ConnectionPool {
+    pub fn new(endpoint: QuicEndpoint, config: PoolConfig) -> Self {
+        ConnectionPool {
+            endpoint,
+            config,
+            conns: Mutex::new(HashMap::new()),
+            dialing: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn endpoint(&self) -> &QuicEndpoint {
+        &self.endpoint
+    }
+
+    pub fn config(&self) -> &PoolConfig {
+        &self.config
+    }
+
+    /// Number of pooled connections, live or not yet found dead.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The connection to `remote`, dialing it if there is none.
+    pub async fn get(&self, remote: SocketAddr) -> Result<QuicConnection> {
+        if let Some(conn) = self.reuse(remote) {
+            return Ok(conn);
+        }
+
+        let gate = self
+            .dialing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(remote)
+            .or_default()
+            .clone();
+        let _dialing = gate.lock().await;
+        // Someone else may have dialed while we waited.
+        if let Some(conn) = self.reuse(remote) {
+            return Ok(conn);
+        }
+
+        let dialed = tokio::time::timeout(self.config.dial_timeout, self.endpoint.connect(remote))
+            .await
+            .context("Connection timed out")
+            .and_then(|conn| conn);
+        self.dialing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&remote);
+        let conn = match dialed {
+            Ok(conn) => conn,
+            Err(e) => {
+                NET_METRICS.connection_errors.inc();
+                return Err(e);
+            }
+        };
+        self.insert(conn.clone());
+        Ok(conn)
+    }
+
+    /// Send `data` to `remote` on a pooled connection. If the connection
+    /// turns out to be dead, redial once and retry.
+    pub async fn send(&self, remote: SocketAddr, data: impl Into<Bytes>) -> Result<()> {
+        let data = data.into();
+        let conn = self.get(remote).await?;
+        match conn.send(data.clone()).await {
+            Ok(()) => Ok(()),
+            Err(e) if conn.is_closed() => {
+                debug!("Connection to {} lost ({:#}), redialing", remote, e);
+                self.remove(remote);
+                self.get(remote).await?.send(data).await
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Pool a connection, such as one the peer opened to us.
+    pub fn insert(&self, conn: QuicConnection) {
+        let pooled = Pooled {
+            conn,
+            last_used: Instant::now(),
+            reported: ConnectionStats::default(),
+        };
+        let replaced = self.lock().insert(pooled.conn.remote(), pooled);
+        if let Some(old) = replaced {
+            if !old.conn.is_closed() {
+                old.conn.close("replaced");
+            }
+        }
+        self.update_active();
+    }
+
+    /// Close and forget the connection to `remote`.
+    pub fn remove(&self, remote: SocketAddr) {
+        if let Some(pooled) = self.lock().remove(&remote) {
+            pooled.conn.close("removed from pool");
+        }
+        self.update_active();
+    }
+
+    /// Close idle connections, drop dead ones, re-key connections whose
+    /// peer moved to a new address, and report their stats.
+    pub fn maintain(&self) {
+        let now = Instant::now();
+        let mut conns = self.lock();
+        let mut moved = Vec::new();
+        conns.retain(|addr, pooled| {
+            if pooled.conn.is_closed() {
+                return false;
+            }
+            if now.duration_since(pooled.last_used) >= self.config.max_idle {
+                debug!("Closing idle connection to {}", addr);
+                pooled.conn.close("idle");
+                return false;
+            }
+            report(pooled);
+            if pooled.conn.remote() != *addr {
+                moved.push(*addr);
+            }
+            true
+        });
+        for old in moved {
+            if let Some(pooled) = conns.remove(&old) {
+                let new = pooled.conn.remote();
+                debug!("Connection to {} migrated to {}", old, new);
+                NET_METRICS.quic_path_migrations.inc();
+                conns.insert(new, pooled);
+            }
+        }
+        NET_METRICS.connections_active.set(conns.len() as i64);
+    }
+
+    /// Close every connection.
+    pub fn close(&self) {
+        for (_, pooled) in self.lock().drain() {
+            pooled.conn.close("pool shutdown");
+        }
+        self.update_active();
+    }
+
+    fn reuse(&self, remote: SocketAddr) -> Option<QuicConnection> {
+        let mut conns = self.lock();
+        let pooled = conns.get_mut(&remote)?;
+        if pooled.conn.is_closed() {
+            conns.remove(&remote);
+            return None;
+        }
+        pooled.last_used = Instant::now();
+        NET_METRICS.quic_connections_reused.inc();
+        Some(pooled.conn.clone())
+    }
+
+    fn update_active(&self) {
+        NET_METRICS.connections_active.set(self.len() as i64);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<SocketAddr, Pooled>> {
+        self.conns.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Feed what a connection did since it was last reported to the metrics.
+fn report(pooled: &mut Pooled) {
+    let stats = pooled.conn.stats();
+    let last = &pooled.reported;
+    NET_METRICS
+        .quic_rtt_ms
+        .observe(stats.rtt.as_secs_f64() * 1000.0);
+    NET_METRICS
+        .quic_bytes_sent
+        .inc_by(stats.bytes_sent.saturating_sub(last.bytes_sent));
+    NET_METRICS
+        .quic_bytes_received
+        .inc_by(stats.bytes_received.saturating_sub(last.bytes_received));
+    NET_METRICS
+        .quic_packets_lost
+        .inc_by(stats.lost_packets.saturating_sub(last.lost_packets));
+    NET_METRICS.quic_congestion_events.inc_by(
+        stats
+            .congestion_events
+            .saturating_sub(last.congestion_events),
+    );
+    pooled.reported = stats;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::endpoint::generate_self_signed_cert;
+
+    async fn endpoints(n: usize) -> Option<Vec<QuicEndpoint>> {
+        let (cert, key) = generate_self_signed_cert().unwrap();
+        let mut endpoints = Vec::new();
+        for _ in 0..n {
+            match QuicEndpoint::new_with_cert(
+                "127.0.0.1:0".parse().unwrap(),
+                cert.clone(),
+                key.clone(),
+            )
+            .await
+            {
+                Ok(endpoint) => endpoints.push(endpoint),
+                Err(err) => {
+                    eprintln!("Skipping QUIC pool test: {err}");
+                    return None;
+                }
+            }
+        }
+        Some(endpoints)
+    }
+
+    /// Accept connections on `server` and forward every message received.
+    fn serve(server: QuicEndpoint) -> tokio::sync::mpsc::Receiver<(usize, Vec<u8>)> {
+        let (tx, rx) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            while let Some(conn) = server.accept().await {
+                let tx = tx.clone();
+                tokio::spawn(async move {
+                    while let Ok(mut stream) = conn.accept_uni().await {
+                        let data = QuicConnection::read_stream(&mut stream).await.unwrap();
+                        if tx.send((conn.id(), data)).await.is_err() {
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+        rx
+    }
+
+    #[tokio::test]
+    async fn reuses_one_connection_per_peer() {
+        let Some(endpoints) = endpoints(2).await else {
+            return;
+        };
+        let server_addr = endpoints[0].local_addr().unwrap();
+        let mut received = serve(endpoints[0].clone());
+        let pool = Arc::new(ConnectionPool::new(
+            endpoints[1].clone(),
+            PoolConfig::default(),
+        ));
+
+        // Concurrent first sends share a single dial.
+        let sends: Vec<_> = (0..8u8)
+            .map(|i| {
+                let pool = pool.clone();
+                tokio::spawn(async move { pool.send(server_addr, vec![i]).await })
+            })
+            .collect();
+        for send in sends {
+            send.await.unwrap().unwrap();
+        }
+        assert_eq!(pool.len(), 1);
+
+        let mut connections = std::collections::HashSet::new();
+        let mut messages = Vec::new();
+        for _ in 0..8 {
+            let (conn, data) = received.recv().await.unwrap();
+            connections.insert(conn);
+            messages.extend(data);
+        }
+        messages.sort();
+        assert_eq!(messages, (0..8).collect::<Vec<u8>>());
+        assert_eq!(connections.len(), 1, "server saw more than one connection");
+
+        // A dead connection is replaced on the next send.
+        let first = pool.get(server_addr).await.unwrap();
+        first.close("test");
+        pool.send(server_addr, vec![9]).await.unwrap();
+        let (conn, data) = received.recv().await.unwrap();
+        assert_eq!(data, vec![9]);
+        assert!(!connections.contains(&conn));
+        assert_ne!(pool.get(server_addr).await.unwrap().id(), first.id());
+    }
+
+    #[tokio::test]
+    async fn closes_idle_connections() {
+        let Some(endpoints) = endpoints(2).await else {
+            return;
+        };
+        let server_addr = endpoints[0].local_addr().unwrap();
+        let _received = serve(endpoints[0].clone());
+        let pool = ConnectionPool::new(
+            endpoints[1].clone(),
+            PoolConfig {
+                max_idle: Duration::from_millis(50),
+                ..PoolConfig::default()
+            },
+        );
+        let conn = pool.get(server_addr).await.unwrap();
+        pool.maintain();
+        assert_eq!(pool.len(), 1);
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        pool.maintain();
+        assert!(pool.is_empty());
+        assert!(conn.is_closed());
+    }
+
+    #[tokio::test]
+    async fn resumes_with_zero_rtt() {
+        let Some(endpoints) = endpoints(2).await else {
+            return;
+        };
+        let server_addr = endpoints[0].local_addr().unwrap();
+        let mut received = serve(endpoints[0].clone());
+        let client = endpoints[1].clone();
+
+        // The first connection earns a session ticket.
+        let conn = client.connect(server_addr).await.unwrap();
+        conn.send(b"first".to_vec()).await.unwrap();
+        assert_eq!(received.recv().await.unwrap().1, b"first");
+        conn.close("done");
+
+        let accepted = NET_METRICS.quic_zero_rtt_accepted.get();
+        let conn = client.connect(server_addr).await.unwrap();
+        conn.send(b"early".to_vec()).await.unwrap();
+        assert_eq!(received.recv().await.unwrap().1, b"early");
+        for _ in 0..100 {
+            if NET_METRICS.quic_zero_rtt_accepted.get() > accepted {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("second connection did not resume with 0-RTT");
+    }
+
+    #[tokio::test]
+    async fn follows_peer_to_new_address() {
+        let Some(endpoints) = endpoints(2).await else {
+            return;
+        };
+        let (server, client) = (endpoints[0].clone(), endpoints[1].clone());
+        let server_addr = server.local_addr().unwrap();
+        let server_pool = Arc::new(ConnectionPool::new(server.clone(), PoolConfig::default()));
+        let accepting = {
+            let server_pool = server_pool.clone();
+            tokio::spawn(async move {
+                let conn = server.accept().await.unwrap();
+                server_pool.insert(conn.clone());
+                let mut messages = Vec::new();
+                for _ in 0..2 {
+                    let mut stream = conn.accept_uni().await.unwrap();
+                    messages.push(QuicConnection::read_stream(&mut stream).await.unwrap());
+                }
+                messages
+            })
+        };
+
+        let conn = client.connect(server_addr).await.unwrap();
+        conn.send(b"before".to_vec()).await.unwrap();
+        let old_addr = client.local_addr().unwrap();
+        client.rebind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let new_addr = client.local_addr().unwrap();
+        assert_ne!(old_addr, new_addr);
+        conn.send(b"after".to_vec()).await.unwrap();
+
+        let messages = accepting.await.unwrap();
+        assert_eq!(messages, vec![b"before".to_vec(), b"after".to_vec()]);
+        server_pool.maintain();
+        assert_eq!(server_pool.len(), 1);
+        let pooled = server_pool.reuse(new_addr).expect("connection re-keyed");
+        assert_eq!(pooled.remote(), new_addr);
+        assert!(server_pool.reuse(old_addr).is_none());
+    }
+}
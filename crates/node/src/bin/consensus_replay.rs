@@ -0,0 +1,106 @@
+//! Consensus replay tool: feeds a trace recorded with
+//! `AETHER_CONSENSUS_TRACE` into a fresh engine built from the same genesis,
+//! and reports where finality advanced and where the engine now disagrees
+//! with the recording.
+//!
+//! Usage:
+//!   consensus-replay --trace /data/node1/consensus.trace --genesis /data/genesis/genesis.json
+
+use aether_consensus::replay;
+use aether_node::{create_hybrid_consensus_with_all_keys, GenesisConfig};
+use aether_types::ChainConfig;
+use anyhow::{Context, Result};
+use std::path::PathBuf;
+
+fn main() -> Result<()> {
+    let args: Vec<String> = std::env::args().collect();
+
+    let mut trace_path: Option<PathBuf> = None;
+    let mut genesis_path: Option<PathBuf> = None;
+    let mut network = "devnet".to_string();
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--trace" | "-t" => {
+                i += 1;
+                trace_path = Some(PathBuf::from(&args[i]));
+            }
+            "--genesis" | "-g" => {
+                i += 1;
+                genesis_path = Some(PathBuf::from(&args[i]));
+            }
+            "--network" => {
+                i += 1;
+                network = args[i].clone();
+            }
+            "--help" | "-h" => {
+                eprintln!("Usage: consensus-replay --trace <FILE> --genesis <FILE> [OPTIONS]");
+                eprintln!();
+                eprintln!("Options:");
+                eprintln!("  -t, --trace <FILE>    Trace written by a node run with AETHER_CONSENSUS_TRACE");
+                eprintln!("  -g, --genesis <FILE>  Genesis the node started from");
+                eprintln!("  --network <NAME>      Network preset: devnet|testnet|mainnet (default: devnet)");
+                std::process::exit(0);
+            }
+            other => {
+                eprintln!("Unknown argument: {other}");
+                std::process::exit(1);
+            }
+        }
+        i += 1;
+    }
+    let trace_path = trace_path.context("--trace is required")?;
+    let genesis_path = genesis_path.context("--genesis is required")?;
+
+    let chain_config = match network.as_str() {
+        "mainnet" => ChainConfig::mainnet(),
+        "testnet" => ChainConfig::testnet(),
+        _ => ChainConfig::devnet(),
+    };
+
+    let genesis_bytes = std::fs::read(&genesis_path)
+        .with_context(|| format!("failed to read {}", genesis_path.display()))?;
+    let genesis: GenesisConfig =
+        serde_json::from_slice(&genesis_bytes).context("failed to parse genesis JSON")?;
+    genesis.validate()?;
+    let result = genesis.build();
+    // No local key: replay only feeds inputs, it never proposes or votes.
+    let mut engine = create_hybrid_consensus_with_all_keys(
+        result.validator_set,
+        genesis.vrf_pubkeys(),
+        genesis.bls_pubkeys(),
+        None,
+        chain_config.consensus.tau,
+        chain_config.chain.epoch_slots,
+    )?;
+
+    let trace = std::fs::File::open(&trace_path)
+        .with_context(|| format!("failed to open {}", trace_path.display()))?;
+    let report = replay(&mut engine, std::io::BufReader::new(trace))?;
+
+    println!("Replayed {} inputs", report.inputs);
+    println!(
+        "  current slot: {}, finalized slot: {}",
+        report.current_slot, report.finalized_slot
+    );
+    match report.finalized_at.last() {
+        Some((index, slot)) => println!(
+            "  last finality: slot {slot} at input {index}, {} inputs since",
+            report.inputs_since_finality()
+        ),
+        None => println!("  nothing finalized"),
+    }
+
+    if report.divergences.is_empty() {
+        println!("\nEvery outcome matched the recording.");
+        return Ok(());
+    }
+    println!("\n{} divergences:", report.divergences.len());
+    for d in report.divergences.iter().take(20) {
+        println!("  input {}: {:?}", d.index, d.input);
+        println!("    recorded: {:?}", d.recorded);
+        println!("    replayed: {:?}", d.replayed);
+    }
+    std::process::exit(2);
+}
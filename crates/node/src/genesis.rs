@@ -122,6 +122,7 @@ impl GenesisConfig {
                     proof: vec![],
                 },
                 timestamp: self.timestamp,
+                erasure: aether_types::ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: None,
@@ -0,0 +1,294 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use aether_da_shreds::serialization::deserialize_shred;
+use aether_da_shreds::validation::validate_shred;
+use aether_mempool::TxPrecheck;
+use aether_p2p::{TopicValidator, Verdict, Violation};
+use aether_types::{Address, EpochInfo, Slot, Transaction, Vote};
+
+use crate::network_handler::{deserialize_bounded, MAX_SHRED_SIZE, MAX_TX_SIZE, MAX_VOTE_SIZE};
+
+/// Votes this many slots behind the current one are not worth forwarding.
+const MAX_VOTE_AGE_SLOTS: u64 = 32;
+
+/// Shreds this many slots behind the current one are not worth forwarding.
+const MAX_SHRED_AGE_SLOTS: u64 = 32;
+
+/// What the gossip validators need to know about the chain, updated by
+/// the node as slots and epochs pass and read from the validation workers.
+#[derive(Clone, Default)]
+pub struct GossipValidationState {
+    inner: Arc<RwLock<ChainView>>,
+}
+
+#[derive(Default)]
+struct ChainView {
+    slot: Slot,
+    epoch: Option<u64>,
+    /// Ed25519 keys of the validators that may produce blocks, and so sign
+    /// shreds, this epoch.
+    producers: Vec<Vec<u8>>,
+    bls_keys: HashMap<Address, Vec<u8>>,
+    /// Which producer signed each recent slot's shreds, so later shreds of
+    /// the slot are checked against one key instead of all of them.
+    slot_signers: BTreeMap<Slot, usize>,
+}
+
+impl GossipValidationState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_slot(&self, slot: Slot) {
+        let mut view = self.write();
+        view.slot = slot;
+        let oldest = slot.saturating_sub(MAX_SHRED_AGE_SLOTS);
+        view.slot_signers = view.slot_signers.split_off(&oldest);
+    }
+
+    /// The epoch the validator set was last taken from.
+    pub fn epoch(&self) -> Option<u64> {
+        self.read().epoch
+    }
+
+    /// Take the validator set of `info`'s epoch, with each validator's
+    /// registered BLS key as looked up by `bls_key`.
+    pub fn set_epoch(&self, info: &EpochInfo, bls_key: impl Fn(&Address) -> Option<Vec<u8>>) {
+        let active = info.validators.iter().filter(|v| v.active && v.stake > 0);
+        let producers = active
+            .clone()
+            .map(|v| v.pubkey.as_bytes().to_vec())
+            .collect();
+        let bls_keys = active
+            .filter_map(|v| {
+                let address = v.pubkey.to_address();
+                bls_key(&address).map(|key| (address, key))
+            })
+            .collect();
+        let mut view = self.write();
+        view.epoch = Some(info.epoch);
+        view.producers = producers;
+        view.bls_keys = bls_keys;
+        view.slot_signers.clear();
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, ChainView> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, ChainView> {
+        self.inner.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Transactions must decode and pass the mempool's stateless checks:
+/// chain ID, signature, fee.
+pub fn tx_validator(precheck: TxPrecheck) -> impl TopicValidator {
+    move |data: &[u8]| {
+        let Some(tx) = deserialize_bounded::<Transaction>(data, MAX_TX_SIZE) else {
+            return Verdict::Reject(Violation::InvalidMessage);
+        };
+        match precheck.check(&tx) {
+            Ok(()) => Verdict::Accept,
+            Err(e) => {
+                tracing::trace!(err = %e, "gossiped transaction fails pre-check");
+                Verdict::Reject(Violation::InvalidMessage)
+            }
+        }
+    }
+}
+
+/// Votes must carry a valid BLS signature from a validator of the current
+/// epoch. Old votes and votes from validators we hold no BLS key for are
+/// dropped without blame: we may simply be behind.
+pub fn vote_validator(state: GossipValidationState) -> impl TopicValidator {
+    move |data: &[u8]| {
+        let Some(vote) = deserialize_bounded::<Vote>(data, MAX_VOTE_SIZE) else {
+            return Verdict::Reject(Violation::InvalidMessage);
+        };
+        let bls_key = {
+            let view = state.read();
+            if vote.slot.saturating_add(MAX_VOTE_AGE_SLOTS) < view.slot {
+                return Verdict::Ignore;
+            }
+            match view.bls_keys.get(&vote.validator.to_address()) {
+                Some(key) => key.clone(),
+                None => return Verdict::Ignore,
+            }
+        };
+        let mut message = Vec::with_capacity(40);
+        message.extend_from_slice(vote.block_hash.as_bytes());
+        message.extend_from_slice(&vote.slot.to_le_bytes());
+        match aether_crypto_bls::keypair::verify(&bls_key, &message, vote.signature.as_bytes()) {
+            Ok(true) => Verdict::Accept,
+            _ => Verdict::Reject(Violation::InvalidMessage),
+        }
+    }
+}
+
+/// Shreds must be signed by a block producer of the current epoch. The
+/// shred does not say which one, so the first shred of a slot is tried
+/// against each producer and the rest against whoever signed it.
+pub fn shred_validator(state: GossipValidationState) -> impl TopicValidator {
+    move |data: &[u8]| {
+        if data.len() > MAX_SHRED_SIZE {
+            return Verdict::Reject(Violation::Oversized);
+        }
+        let Ok(shred) = deserialize_shred(data) else {
+            return Verdict::Reject(Violation::InvalidShred);
+        };
+        let (slot, producers, known_signer) = {
+            let view = state.read();
+            if shred.slot.saturating_add(MAX_SHRED_AGE_SLOTS) < view.slot {
+                return Verdict::Ignore;
+            }
+            if view.producers.is_empty() {
+                return Verdict::Ignore;
+            }
+            (
+                view.slot,
+                view.producers.clone(),
+                view.slot_signers.get(&shred.slot).copied(),
+            )
+        };
+        let candidates = known_signer
+            .into_iter()
+            .chain((0..producers.len()).filter(|i| Some(*i) != known_signer));
+        for index in candidates {
+            if validate_shred(&shred, slot, MAX_SHRED_AGE_SLOTS, &producers[index]).is_ok() {
+                if known_signer != Some(index) {
+                    state.write().slot_signers.insert(shred.slot, index);
+                }
+                return Verdict::Accept;
+            }
+        }
+        Verdict::Reject(Violation::InvalidShred)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_bls::BlsKeypair;
+    use aether_crypto_primitives::Keypair;
+    use aether_da_shreds::serialization::serialize_shred;
+    use aether_da_shreds::shred::{Shred, ShredVariant};
+    use aether_types::{PublicKey, Signature, ValidatorInfo, H256};
+
+    fn epoch_of(validators: &[&Keypair]) -> EpochInfo {
+        EpochInfo {
+            epoch: 1,
+            start_slot: 0,
+            end_slot: 100,
+            randomness: H256::zero(),
+            validators: validators
+                .iter()
+                .map(|kp| ValidatorInfo {
+                    pubkey: PublicKey::from_bytes(kp.public_key()),
+                    stake: 1_000,
+                    commission: 0,
+                    active: true,
+                })
+                .collect(),
+            total_stake: 1_000 * validators.len() as u128,
+        }
+    }
+
+    fn signed_shred(leader: &Keypair, slot: Slot, index: u32) -> Vec<u8> {
+        let payload = vec![index as u8; 64];
+        let payload_hash = Shred::hash_payload(&payload);
+        let signature = Signature::from_bytes(leader.sign(&Shred::build_signing_message(
+            slot,
+            index,
+            &payload_hash,
+        )));
+        let shred = Shred::new(
+            ShredVariant::Data,
+            slot,
+            index,
+            1,
+            0,
+            H256::zero(),
+            payload,
+            signature,
+        );
+        serialize_shred(&shred).unwrap()
+    }
+
+    #[test]
+    fn shreds_need_a_producer_signature() {
+        let producers: Vec<Keypair> = (0..3).map(|_| Keypair::generate()).collect();
+        let state = GossipValidationState::new();
+        state.set_epoch(&epoch_of(&producers.iter().collect::<Vec<_>>()), |_| None);
+        state.set_slot(10);
+        let validator = shred_validator(state.clone());
+
+        assert_eq!(
+            validator.validate(&signed_shred(&producers[2], 10, 0)),
+            Verdict::Accept
+        );
+        assert_eq!(state.read().slot_signers.get(&10), Some(&2));
+        assert_eq!(
+            validator.validate(&signed_shred(&producers[2], 10, 1)),
+            Verdict::Accept
+        );
+
+        let outsider = Keypair::generate();
+        assert_eq!(
+            validator.validate(&signed_shred(&outsider, 10, 2)),
+            Verdict::Reject(Violation::InvalidShred)
+        );
+        assert_eq!(
+            validator.validate(b"not a shred"),
+            Verdict::Reject(Violation::InvalidShred)
+        );
+
+        state.set_slot(100);
+        assert_eq!(
+            validator.validate(&signed_shred(&producers[0], 10, 3)),
+            Verdict::Ignore
+        );
+    }
+
+    #[test]
+    fn votes_need_a_bls_signature() {
+        let validator_key = Keypair::generate();
+        let bls = BlsKeypair::generate();
+        let state = GossipValidationState::new();
+        let bls_public = bls.public_key();
+        state.set_epoch(&epoch_of(&[&validator_key]), |_| Some(bls_public.clone()));
+        state.set_slot(5);
+        let validator = vote_validator(state.clone());
+
+        let mut vote = Vote {
+            slot: 5,
+            block_hash: H256::from([7u8; 32]),
+            validator: PublicKey::from_bytes(validator_key.public_key()),
+            signature: Signature::from_bytes(vec![]),
+            stake: 1_000,
+        };
+        let mut message = vote.block_hash.as_bytes().to_vec();
+        message.extend_from_slice(&vote.slot.to_le_bytes());
+        vote.signature = Signature::from_bytes(bls.sign(&message));
+        let encoded = bincode::serialize(&vote).unwrap();
+        assert_eq!(validator.validate(&encoded), Verdict::Accept);
+
+        let mut forged = vote.clone();
+        forged.block_hash = H256::from([8u8; 32]);
+        assert_eq!(
+            validator.validate(&bincode::serialize(&forged).unwrap()),
+            Verdict::Reject(Violation::InvalidMessage)
+        );
+
+        let mut stranger = vote.clone();
+        stranger.validator = PublicKey::from_bytes(Keypair::generate().public_key());
+        assert_eq!(
+            validator.validate(&bincode::serialize(&stranger).unwrap()),
+            Verdict::Ignore
+        );
+
+        state.set_slot(500);
+        assert_eq!(validator.validate(&encoded), Verdict::Ignore);
+    }
+}
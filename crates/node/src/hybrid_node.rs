@@ -44,8 +44,8 @@ impl ValidatorKeypair {
     /// Save keys to a JSON file on disk.
     pub fn save_to_file(&self, path: &Path) -> Result<()> {
         let keyfile = KeyFile {
-            ed25519_secret: to_hex(&self.ed25519.secret_key()),
-            bls_secret: to_hex(&self.bls.secret_key()),
+            ed25519_secret: to_hex(self.ed25519.secret_key().expose_secret()),
+            bls_secret: to_hex(self.bls.secret_key().expose_secret()),
             vrf_secret: to_hex(&self.vrf.secret_bytes()),
         };
         let json = serde_json::to_string_pretty(&keyfile)?;
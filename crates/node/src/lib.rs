@@ -7,14 +7,17 @@
 pub mod feature_gates;
 pub mod fork_choice;
 pub mod genesis;
+pub mod gossip_validation;
 pub mod hybrid_node;
 pub mod network_handler;
 pub mod node;
 pub mod poh;
+pub mod programs;
 pub mod sync;
 
 pub use feature_gates::FeatureGateRegistry;
 pub use genesis::GenesisConfig;
+pub use gossip_validation::GossipValidationState;
 pub use hybrid_node::{
     create_hybrid_consensus, create_hybrid_consensus_with_all_keys,
     create_hybrid_consensus_with_vrf_keys, validator_info_from_keypair, ValidatorKeypair,
@@ -22,3 +25,4 @@ pub use hybrid_node::{
 pub use network_handler::{decode_network_event, NodeMessage, OutboundMessage, SyncRequest};
 pub use node::{compute_receipts_root, compute_transactions_root, Node};
 pub use poh::{PohMetrics, PohRecorder};
+pub use programs::WasmPrograms;
@@ -3,21 +3,34 @@ use std::path::Path;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
+use aether_consensus::PacemakerConfig;
+use aether_crypto_primitives::Keypair;
+use aether_ledger::FeeHistory;
+use aether_mempool::{AdmissionPipeline, TxStatusEvent};
 use aether_metrics::exporter::start_metrics_exporter;
+use aether_node::gossip_validation::{shred_validator, tx_validator, vote_validator};
 use aether_node::SyncRequest;
 use aether_node::{
-    create_hybrid_consensus, create_hybrid_consensus_with_all_keys, validator_info_from_keypair,
-    GenesisConfig, Node, OutboundMessage, ValidatorKeypair,
+    create_hybrid_consensus, create_hybrid_consensus_with_all_keys, decode_network_event,
+    validator_info_from_keypair, GenesisConfig, Node, NodeMessage, OutboundMessage,
+    ValidatorKeypair,
+};
+use aether_p2p::network::{
+    NetworkEvent, P2PNetwork, TOPIC_ROUND, TOPIC_SHRED, TOPIC_SYNC, TOPIC_TX, TOPIC_VOTE,
+};
+use aether_p2p::{PeerRole, StakeTable};
+use aether_rpc_json::{JsonRpcServer, RpcBackend, SubscriptionManager};
+use aether_types::{
+    Address, Block, ChainConfig, FinalityPath, Transaction, TransactionReceipt, H256,
 };
-use aether_p2p::network::{P2PNetwork, TOPIC_SYNC, TOPIC_VOTE};
-use aether_rpc_json::{JsonRpcServer, RpcBackend};
-use aether_types::{Address, Block, ChainConfig, Transaction, TransactionReceipt, H256};
 use anyhow::{Context, Result};
 use serde_json::{json, Value};
-use tokio::sync::{mpsc, watch};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::{broadcast, mpsc, watch};
 
 struct NodeRpcBackend {
     node: Arc<RwLock<Node>>,
+    admission: AdmissionPipeline,
 }
 
 impl NodeRpcBackend {
@@ -38,8 +51,14 @@ impl RpcBackend for NodeRpcBackend {
     fn send_raw_transaction(&self, tx_bytes: Vec<u8>) -> Result<H256> {
         let tx: Transaction =
             bincode::deserialize(&tx_bytes).context("failed to decode transaction bytes")?;
+        let admitted = self
+            .admission
+            .admit(vec![tx])
+            .into_iter()
+            .next()
+            .context("admission returned no result")??;
         let mut node = self.write_node()?;
-        node.submit_transaction(tx)
+        node.submit_admitted(admitted)
     }
 
     fn get_block_by_number(&self, block_number: u64, _full_tx: bool) -> Result<Option<Block>> {
@@ -102,6 +121,59 @@ impl RpcBackend for NodeRpcBackend {
         }))
     }
 
+    fn get_evidence(&self, from_slot: u64) -> Result<Value> {
+        let node = self.read_node()?;
+        Ok(serde_json::to_value(node.evidence(from_slot))?)
+    }
+
+    fn get_leader_schedule(&self, epoch: u64) -> Result<Value> {
+        let node = self.read_node()?;
+        Ok(serde_json::to_value(node.leader_schedule(epoch))?)
+    }
+
+    fn get_upcoming_leaders(&self, count: usize) -> Result<Value> {
+        let node = self.read_node()?;
+        let leaders: Vec<Value> = node
+            .upcoming_leaders(count)
+            .into_iter()
+            .map(|(slot, leader)| json!({ "slot": slot, "leader": leader }))
+            .collect();
+        Ok(Value::Array(leaders))
+    }
+
+    fn get_finality_certificates(&self, from_slot: u64) -> Result<Value> {
+        let node = self.read_node()?;
+        Ok(serde_json::to_value(node.finality_certificates(from_slot))?)
+    }
+
+    fn get_finality_path(&self, slot: u64) -> Result<Option<FinalityPath>> {
+        let node = self.read_node()?;
+        Ok(node.finality_path(slot))
+    }
+
+    fn get_base_fee(&self) -> Result<u128> {
+        let node = self.read_node()?;
+        Ok(node.base_fee())
+    }
+
+    fn get_max_priority_fee(&self) -> Result<u128> {
+        let node = self.read_node()?;
+        Ok(node.suggested_priority_fee())
+    }
+
+    fn get_fee_history(&self, block_count: usize, percentiles: &[u8]) -> Result<FeeHistory> {
+        let node = self.read_node()?;
+        Ok(node.fee_history(block_count, percentiles))
+    }
+
+    fn trace_transaction(&self, tx_hash: H256) -> Result<Option<Value>> {
+        let node = self.read_node()?;
+        match node.trace_transaction(tx_hash)? {
+            Some(trace) => Ok(Some(serde_json::to_value(trace)?)),
+            None => Ok(None),
+        }
+    }
+
     fn allows_airdrop(&self) -> bool {
         self.read_node()
             .map(|node| node.allows_airdrop())
@@ -122,7 +194,7 @@ const MAX_EVENTS_PER_TICK: usize = 256;
 
 async fn run_slot_loop(
     node: Arc<RwLock<Node>>,
-    mut net_rx: mpsc::Receiver<aether_p2p::network::NetworkEvent>,
+    mut net_rx: mpsc::Receiver<NetworkEvent>,
     slot_ms: u64,
     mut shutdown_rx: watch::Receiver<bool>,
 ) -> Result<()> {
@@ -185,14 +257,102 @@ async fn run_slot_loop(
     }
 }
 
+/// Relay transaction status events from the node to RPC WebSocket clients.
+async fn forward_tx_status(
+    mut rx: broadcast::Receiver<TxStatusEvent>,
+    subscriptions: Arc<SubscriptionManager>,
+) {
+    loop {
+        match rx.recv().await {
+            Ok(event) => subscriptions.notify_tx_status(event.tx_hash, &event.status),
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "tx status forwarder lagging, events dropped");
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// How often validator records are republished and the DHT searched for
+/// block producers.
+const DISCOVERY_INTERVAL: Duration = Duration::from_secs(60);
+/// Nearest block producers to stay connected to.
+const BLOCK_PRODUCER_PEERS: usize = 8;
+
+/// Gossiped transactions validated together by the admission pipeline.
+const ADMISSION_BATCH: usize = 256;
+
+/// Admission loop: validates gossiped transactions in batches on the
+/// blocking pool, then takes the node lock only to pool the survivors.
+async fn run_tx_admission(
+    node: Arc<RwLock<Node>>,
+    pipeline: AdmissionPipeline,
+    mut tx_rx: mpsc::Receiver<NetworkEvent>,
+    mut shutdown_rx: watch::Receiver<bool>,
+) -> Result<()> {
+    loop {
+        let first = tokio::select! {
+            _ = shutdown_rx.changed() => {
+                tracing::info!("Tx admission loop received shutdown signal, stopping");
+                return Ok(());
+            }
+            event = tx_rx.recv() => match event {
+                Some(event) => event,
+                None => return Ok(()),
+            },
+        };
+        let mut events = vec![first];
+        while events.len() < ADMISSION_BATCH {
+            match tx_rx.try_recv() {
+                Ok(event) => events.push(event),
+                Err(_) => break,
+            }
+        }
+        let txs: Vec<Transaction> = events
+            .into_iter()
+            .filter_map(|event| match decode_network_event(event) {
+                Some(NodeMessage::TransactionReceived(tx)) => Some(tx),
+                _ => None,
+            })
+            .collect();
+
+        let batch = pipeline.clone();
+        let results = match tokio::task::spawn_blocking(move || batch.admit(txs)).await {
+            Ok(results) => results,
+            Err(e) => {
+                tracing::error!(err = %e, "tx admission batch panicked");
+                continue;
+            }
+        };
+        let admitted: Vec<_> = results
+            .into_iter()
+            .filter_map(|result| {
+                result
+                    .map_err(|e| tracing::debug!(err = %e, "Tx rejected"))
+                    .ok()
+            })
+            .collect();
+        if admitted.is_empty() {
+            continue;
+        }
+        node.write()
+            .map_err(|_| anyhow::anyhow!("node lock poisoned"))?
+            .add_admitted_transactions(admitted);
+    }
+}
+
 /// P2P outbound loop: reads OutboundMessages from node and publishes to network.
+/// Inbound transactions go to the admission loop, everything else to the
+/// slot loop.
 async fn run_p2p_outbound(
     mut p2p: P2PNetwork,
     mut outbound_rx: mpsc::Receiver<OutboundMessage>,
-    net_tx: mpsc::Sender<aether_p2p::network::NetworkEvent>,
+    net_tx: mpsc::Sender<NetworkEvent>,
+    tx_tx: mpsc::Sender<NetworkEvent>,
     mut shutdown_rx: watch::Receiver<bool>,
 ) -> Result<()> {
     let mut inbound_event_drops: u64 = 0;
+    let mut discovery = tokio::time::interval(DISCOVERY_INTERVAL);
 
     loop {
         tokio::select! {
@@ -201,10 +361,26 @@ async fn run_p2p_outbound(
                 tracing::info!("P2P outbound loop received shutdown signal, stopping");
                 return Ok(());
             }
+            // Advertise ourselves and find block producers beyond the bootstrap peers
+            _ = discovery.tick() => {
+                if let Err(e) = p2p.publish_validator_record(vec![PeerRole::BlockProducer]) {
+                    tracing::debug!("not publishing validator record: {e}");
+                }
+                p2p.discover_validators();
+                let dialed = p2p.connect_block_producers(BLOCK_PRODUCER_PEERS);
+                if dialed > 0 {
+                    tracing::info!(dialed, "Connecting to discovered block producers");
+                }
+            }
             // Poll for inbound P2P events
             event = p2p.poll() => {
                 if let Some(event) = event {
-                    if net_tx.try_send(event).is_err() {
+                    let inbound = if matches!(event, NetworkEvent::TransactionReceived(_)) {
+                        &tx_tx
+                    } else {
+                        &net_tx
+                    };
+                    if inbound.try_send(event).is_err() {
                         inbound_event_drops += 1;
                         tracing::warn!(
                             total_drops = inbound_event_drops,
@@ -250,6 +426,28 @@ async fn run_p2p_outbound(
                             }
                         }
                     }
+                    Some(OutboundMessage::BroadcastRoundSync(sync)) => {
+                        match bincode::serialize(&sync) {
+                            Ok(data) => {
+                                if let Err(e) = p2p.publish(TOPIC_ROUND, data) {
+                                    tracing::warn!("failed to broadcast round sync: {e}");
+                                }
+                            }
+                            Err(e) => {
+                                tracing::error!("failed to serialize round sync: {e}");
+                            }
+                        }
+                    }
+                    Some(OutboundMessage::ConnectValidators(validators)) => {
+                        let keys: Vec<[u8; 32]> = validators
+                            .iter()
+                            .filter_map(|pk| pk.as_bytes().try_into().ok())
+                            .collect();
+                        let dialed = p2p.connect_validators(&keys);
+                        if dialed > 0 {
+                            tracing::debug!(dialed, "Connecting to upcoming leaders");
+                        }
+                    }
                     None => break, // Channel closed
                 }
             }
@@ -303,6 +501,7 @@ async fn main() -> Result<()> {
     );
 
     let db_path = env::var("AETHER_NODE_DB_PATH").unwrap_or_else(|_| "./data/node1".to_string());
+    let ban_list_path = std::path::Path::new(&db_path).join("banned_peers");
 
     // Load or generate validator keypair
     let key_path =
@@ -325,43 +524,62 @@ async fn main() -> Result<()> {
     };
     let validator_address = validator_keypair.address();
 
-    // Build consensus from genesis file (multi-validator) or single-validator mode
+    // Build consensus from genesis file (multi-validator) or single-validator mode.
+    // The validator set also decides which peers get staked P2P slots.
+    let stake_table;
+    let mut hybrid = if let Ok(genesis_path) = env::var("AETHER_GENESIS_PATH") {
+        tracing::info!(path = %genesis_path, "Loading genesis config");
+        let genesis_bytes = std::fs::read(&genesis_path)
+            .with_context(|| format!("failed to read genesis file: {genesis_path}"))?;
+        let genesis: GenesisConfig = serde_json::from_slice(&genesis_bytes)
+            .with_context(|| "failed to parse genesis JSON")?;
+        genesis.validate()?;
+
+        let result = genesis.build();
+        let vrf_pubkeys = genesis.vrf_pubkeys();
+        let bls_pubkeys = genesis.bls_pubkeys();
+
+        tracing::info!(
+            validators = result.validator_set.len(),
+            total_stake = result.total_stake,
+            "Genesis config loaded"
+        );
+
+        stake_table = StakeTable::from_validators(&result.validator_set);
+        create_hybrid_consensus_with_all_keys(
+            result.validator_set,
+            vrf_pubkeys,
+            bls_pubkeys,
+            Some(&validator_keypair),
+            chain_config.consensus.tau,
+            chain_config.chain.epoch_slots,
+        )?
+    } else {
+        // Single-validator quick-start mode
+        let validators = vec![validator_info_from_keypair(&validator_keypair, 1_000_000)];
+        stake_table = StakeTable::from_validators(&validators);
+        create_hybrid_consensus(
+            validators,
+            Some(&validator_keypair),
+            chain_config.consensus.tau,
+            chain_config.chain.epoch_slots,
+        )?
+    };
+    hybrid.set_pacemaker_config(PacemakerConfig::from(&chain_config.consensus));
+    hybrid.set_fast_finality(chain_config.consensus.fast_finality);
+    let consensus: Box<dyn aether_consensus::ConsensusEngine> = Box::new(hybrid);
+
+    // Record every consensus input so a finality stall can be replayed
+    // offline with `consensus-replay`.
     let consensus: Box<dyn aether_consensus::ConsensusEngine> =
-        if let Ok(genesis_path) = env::var("AETHER_GENESIS_PATH") {
-            tracing::info!(path = %genesis_path, "Loading genesis config");
-            let genesis_bytes = std::fs::read(&genesis_path)
-                .with_context(|| format!("failed to read genesis file: {genesis_path}"))?;
-            let genesis: GenesisConfig = serde_json::from_slice(&genesis_bytes)
-                .with_context(|| "failed to parse genesis JSON")?;
-            genesis.validate()?;
-
-            let result = genesis.build();
-            let vrf_pubkeys = genesis.vrf_pubkeys();
-            let bls_pubkeys = genesis.bls_pubkeys();
-
-            tracing::info!(
-                validators = result.validator_set.len(),
-                total_stake = result.total_stake,
-                "Genesis config loaded"
-            );
-
-            Box::new(create_hybrid_consensus_with_all_keys(
-                result.validator_set,
-                vrf_pubkeys,
-                bls_pubkeys,
-                Some(&validator_keypair),
-                chain_config.consensus.tau,
-                chain_config.chain.epoch_slots,
+        if let Ok(trace_path) = env::var("AETHER_CONSENSUS_TRACE") {
+            tracing::info!(path = %trace_path, "Recording consensus inputs");
+            Box::new(aether_consensus::RecordingEngine::to_file(
+                consensus,
+                Path::new(&trace_path),
             )?)
         } else {
-            // Single-validator quick-start mode
-            let validators = vec![validator_info_from_keypair(&validator_keypair, 1_000_000)];
-            Box::new(create_hybrid_consensus(
-                validators,
-                Some(&validator_keypair),
-                chain_config.consensus.tau,
-                chain_config.chain.epoch_slots,
-            )?)
+            consensus
         };
 
     let rpc_port: u16 = env::var("AETHER_RPC_PORT")
@@ -377,6 +595,20 @@ async fn main() -> Result<()> {
         .and_then(|s| s.parse().ok())
         .unwrap_or(9090);
 
+    // Initialize P2P network. It keeps a copy of the validator key to sign
+    // its stake proof and the validator record it publishes in the DHT.
+    let record_signer =
+        Keypair::from_bytes(validator_keypair.ed25519.secret_key().expose_secret())?;
+    let mut p2p = P2PNetwork::new_random_validator(Box::new(record_signer))?;
+    p2p.set_stake_table(stake_table);
+    match p2p.load_ban_list(&ban_list_path) {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(path = %ban_list_path.display(), "Restored {n} peer bans"),
+        Err(e) => {
+            tracing::warn!(path = %ban_list_path.display(), err = %e, "failed to load ban list")
+        }
+    }
+
     let mut node = Node::new(
         db_path,
         consensus,
@@ -385,6 +617,17 @@ async fn main() -> Result<()> {
         chain_config.clone(),
     )?;
 
+    if let Ok(dir) = env::var("AETHER_WASM_PROGRAMS_DIR") {
+        let loaded = node.load_wasm_programs(Path::new(&dir))?;
+        tracing::info!(dir = %dir, "Loaded {loaded} WASM programs");
+    }
+
+    // Check gossiped transactions, votes and shreds before they are delivered
+    // or forwarded, so invalid data is never re-gossiped.
+    p2p.set_topic_validator(TOPIC_TX, tx_validator(node.tx_precheck()));
+    p2p.set_topic_validator(TOPIC_VOTE, vote_validator(node.gossip_validation()));
+    p2p.set_topic_validator(TOPIC_SHRED, shred_validator(node.gossip_validation()));
+
     // Seed validator with genesis balance (only on first run)
     let genesis_balance = chain_config.tokens.swr_initial_supply;
     if node.get_account(validator_address)?.is_none() {
@@ -404,14 +647,20 @@ async fn main() -> Result<()> {
     // peer message floods. Events are dropped (not blocked) when full.
     const P2P_INBOUND_CAPACITY: usize = 4096;
     let (net_tx, net_rx) = mpsc::channel(P2P_INBOUND_CAPACITY);
+    let (tx_tx, tx_rx) = mpsc::channel(P2P_INBOUND_CAPACITY);
 
     // Shutdown coordination: a watch channel that signals all tasks to stop.
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
     let shared_node = Arc::new(RwLock::new(node));
 
+    let admission = shared_node
+        .read()
+        .map_err(|_| anyhow::anyhow!("node lock poisoned"))?
+        .admission_pipeline();
     let backend = NodeRpcBackend {
         node: shared_node.clone(),
+        admission: admission.clone(),
     };
 
     // Create RPC shutdown signal from the watch channel
@@ -426,8 +675,16 @@ async fn main() -> Result<()> {
         }
     });
 
-    // Initialize P2P network
-    let mut p2p = P2PNetwork::new_random()?;
+    // Stream transaction lifecycle events to WebSocket subscribers.
+    let tx_status_rx = shared_node
+        .read()
+        .map_err(|_| anyhow::anyhow!("node lock poisoned"))?
+        .subscribe_tx_status();
+    tokio::spawn(forward_tx_status(
+        tx_status_rx,
+        rpc_server.subscription_manager(),
+    ));
+
     let listen_addr = format!("/ip4/0.0.0.0/tcp/{}", p2p_port);
     p2p.start(&listen_addr).await?;
     let peer_id = p2p.peer_id_str();
@@ -465,7 +722,19 @@ async fn main() -> Result<()> {
         shutdown_rx.clone(),
     ));
     let rpc_task = tokio::spawn(async move { rpc_server.run().await });
-    let p2p_task = tokio::spawn(run_p2p_outbound(p2p, outbound_rx, net_tx, shutdown_rx));
+    let admission_task = tokio::spawn(run_tx_admission(
+        shared_node.clone(),
+        admission,
+        tx_rx,
+        shutdown_rx.clone(),
+    ));
+    let p2p_task = tokio::spawn(run_p2p_outbound(
+        p2p,
+        outbound_rx,
+        net_tx,
+        tx_tx,
+        shutdown_rx,
+    ));
     let metrics_addr: std::net::SocketAddr = ([0, 0, 0, 0], metrics_port).into();
     let metrics_task = tokio::spawn(async move {
         if let Err(e) = start_metrics_exporter(metrics_addr).await {
@@ -494,6 +763,12 @@ async fn main() -> Result<()> {
                 Err(e) => return Err(anyhow::anyhow!("p2p task failed: {e}")),
             }
         }
+        res = admission_task => {
+            match res {
+                Ok(inner) => inner?,
+                Err(e) => return Err(anyhow::anyhow!("tx admission task failed: {e}")),
+            }
+        }
         _ = metrics_task => {}
         _ = tokio::signal::ctrl_c() => {
             tracing::info!("Received SIGINT, initiating graceful shutdown...");
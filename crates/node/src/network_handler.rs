@@ -1,5 +1,5 @@
 use aether_p2p::network::NetworkEvent;
-use aether_types::{Block, Slot, Transaction, Vote};
+use aether_types::{Block, PublicKey, RoundSync, Slot, Transaction, Vote};
 use bincode::Options;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +15,8 @@ pub enum NodeMessage {
         from_slot: Slot,
         to_slot: Slot,
     },
+    /// A validator's pacemaker timed out; it reports its slot.
+    RoundSyncReceived(RoundSync),
     PeerConnected,
     PeerDisconnected,
 }
@@ -31,6 +33,10 @@ pub enum OutboundMessage {
         from_slot: Slot,
         to_slot: Slot,
     },
+    /// Dial these validators if we know where they listen (upcoming leaders).
+    ConnectValidators(Vec<PublicKey>),
+    /// Tell peers our slot after a pacemaker timeout.
+    BroadcastRoundSync(RoundSync),
 }
 
 /// Wire format for sync request messages on the `/aether/1/sync` topic.
@@ -45,13 +51,17 @@ pub struct SyncRequest {
 /// defense layer).  Keeping them in sync avoids silent drops where one layer
 /// accepts a message the other rejects.
 const MAX_BLOCK_SIZE: usize = 2 * 1024 * 1024; // 2 MB — matches gossipsub max_transmit_size
-const MAX_VOTE_SIZE: usize = 8 * 1024; // 8 KB
-const MAX_TX_SIZE: usize = 64 * 1024; // 64 KB
-const MAX_SHRED_SIZE: usize = 256 * 1024; // 256 KB — RS(10,2) on 2 MB block ≈ 210 KB per shred
+pub(crate) const MAX_VOTE_SIZE: usize = 8 * 1024; // 8 KB
+pub(crate) const MAX_TX_SIZE: usize = 64 * 1024; // 64 KB
+pub(crate) const MAX_SHRED_SIZE: usize = 256 * 1024; // 256 KB — RS(10,2) on 2 MB block ≈ 210 KB per shred
 const MAX_SYNC_SIZE: usize = 1024; // 1 KB
+const MAX_ROUND_SYNC_SIZE: usize = 8 * 1024; // 8 KB
 
 /// Deserialize with a bincode size limit to prevent DoS via deeply nested structures.
-fn deserialize_bounded<T: serde::de::DeserializeOwned>(data: &[u8], max_size: usize) -> Option<T> {
+pub(crate) fn deserialize_bounded<T: serde::de::DeserializeOwned>(
+    data: &[u8],
+    max_size: usize,
+) -> Option<T> {
     if data.len() > max_size {
         return None;
     }
@@ -84,6 +94,9 @@ pub fn decode_network_event(event: NetworkEvent) -> Option<NodeMessage> {
                 }
             })
         }
+        NetworkEvent::RoundSyncReceived(data) if data.len() <= MAX_ROUND_SYNC_SIZE => {
+            deserialize_bounded(&data, MAX_ROUND_SYNC_SIZE).map(NodeMessage::RoundSyncReceived)
+        }
         // Shreds are size-checked but forwarded raw to the DA layer (no deserialization here).
         NetworkEvent::ShredReceived(data) if data.len() <= MAX_SHRED_SIZE => {
             tracing::trace!(
@@ -144,6 +157,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_decode_round_sync_event() {
+        let sync = RoundSync {
+            slot: 9,
+            high_qc: None,
+            validator: PublicKey::from_bytes(vec![1u8; 32]),
+            signature: Signature::from_bytes(vec![0u8; 96]),
+        };
+        let data = bincode::serialize(&sync).unwrap();
+        let event = NetworkEvent::RoundSyncReceived(data);
+        match decode_network_event(event) {
+            Some(NodeMessage::RoundSyncReceived(s)) => assert_eq!(s.slot, 9),
+            other => panic!("expected round sync, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_decode_transaction_event() {
         let tx = Transaction {
@@ -220,6 +249,13 @@ mod tests {
         assert!(decode_network_event(event).is_none());
     }
 
+    #[test]
+    fn test_oversized_round_sync_rejected() {
+        let oversized = vec![0u8; MAX_ROUND_SYNC_SIZE + 1];
+        let event = NetworkEvent::RoundSyncReceived(oversized);
+        assert!(decode_network_event(event).is_none());
+    }
+
     #[test]
     fn test_limits_match_p2p_layer() {
         // These limits must stay in sync with aether_p2p::network constants.
@@ -237,6 +273,11 @@ mod tests {
             "shred limit out of sync with p2p"
         );
         assert_eq!(MAX_SYNC_SIZE, 1024, "sync limit out of sync with p2p");
+        assert_eq!(
+            MAX_ROUND_SYNC_SIZE,
+            8 * 1024,
+            "round sync limit out of sync with p2p"
+        );
     }
 
     #[test]
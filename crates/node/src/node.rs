@@ -1,30 +1,40 @@
+use aether_consensus::evidence_pool::MAX_EVIDENCE_PER_BLOCK;
 use aether_consensus::slashing::{self as slash_verify, SlashProof, SlashType, Vote as SlashVote};
-use aether_consensus::{ConsensusEngine, SlashingDetector};
+use aether_consensus::{ConsensusEngine, EvidenceEntry, EvidencePool, Reorg};
 use aether_crypto_bls::BlsKeypair;
 use aether_crypto_primitives::Keypair;
-use aether_ledger::{EmissionSchedule, FeeMarket, Ledger};
-use aether_mempool::Mempool;
+use aether_ledger::fee_market::BlockFeeResult;
+use aether_ledger::{
+    BlockFeeSample, EmissionSchedule, FeeHistory, FeeMarket, GasPriceOracle, Ledger,
+};
+use aether_mempool::{AdmissionPipeline, AdmittedTx, Mempool, TxPrecheck, TxStatusEvent};
 use aether_p2p::network::NetworkEvent;
 use aether_program_staking::StakingState;
+use aether_runtime::ExecutionTrace;
 use aether_state_snapshots::generate_snapshot;
 use aether_state_storage::{
-    database::pruning, Storage, StorageBatch, CF_BLOCKS, CF_METADATA, CF_RECEIPTS, CF_STAKING,
+    database::pruning, Storage, StorageBatch, CF_ACCOUNTS, CF_BLOCKS, CF_METADATA, CF_RECEIPTS,
+    CF_STAKING,
 };
 use aether_types::{
-    Account, Address, Block, ChainConfig, PublicKey, Slot, Transaction, TransactionReceipt, Vote,
-    H256,
+    Account, Address, Block, ChainConfig, FinalityCertificate, FinalityPath, PublicKey, RoundSync,
+    Slot, Transaction, TransactionReceipt, TxLifecycle, Vote, H256,
 };
+use aether_verifiers_tee::TeeVerifier;
 use anyhow::{bail, Context, Result};
 use sha2::{Digest, Sha256};
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 use tokio::time;
 
 use aether_metrics::{CONSENSUS_METRICS, NODE_METRICS, STORAGE_METRICS};
 
+use crate::gossip_validation::GossipValidationState;
+use crate::programs::WasmPrograms;
+
 /// Overflow-safe (a * b) / c using 256-bit intermediate product.
 /// Avoids silent truncation when a*b overflows u128 (e.g. emission * stake).
 fn mul_div(a: u128, b: u128, c: u128) -> u128 {
@@ -87,11 +97,21 @@ const MAX_ORPHAN_BLOCKS: usize = 256;
 const MAX_CLOCK_DRIFT_SECS: u64 = 15;
 
 const MAX_BLOCK_GAS_LIMIT: u64 = 10_000_000;
+/// Serialized transaction bytes per produced block, leaving headroom under
+/// the 2 MB gossip limit for the header and encoding overhead.
+const MAX_BLOCK_TX_BYTES: usize = 1536 * 1024;
+
+/// Recent blocks the gas price oracle draws fee estimates from.
+const GAS_ORACLE_BLOCKS: usize = 64;
 
 /// Minimum interval between serving sync block-range responses.
 /// Prevents a peer from flooding sync requests and consuming all outbound bandwidth.
 const SYNC_RESPONSE_COOLDOWN: Duration = Duration::from_secs(2);
 
+/// Upcoming leader slots whose proposers we dial ahead of time, so their
+/// blocks reach us over a warm connection.
+const LEADER_PRECONNECT_SLOTS: usize = 8;
+
 type LoadedBlocks = (
     BTreeMap<Slot, H256>,
     HashMap<H256, Block>,
@@ -110,6 +130,8 @@ pub struct Node {
     poh: PohRecorder,
     last_poh_metrics: Option<PohMetrics>,
     fee_market: FeeMarket,
+    /// Priority fees paid in recent blocks, for fee estimation.
+    gas_oracle: GasPriceOracle,
     emission_schedule: EmissionSchedule,
     current_epoch: u64,
     fork_choice: ForkChoice,
@@ -126,18 +148,23 @@ pub struct Node {
     outbound_buffer: VecDeque<OutboundMessage>,
     /// Consecutive timeout counter for circuit breaker.
     consecutive_timeouts: u32,
-    /// Detects double-signing and other slashable offenses from incoming votes.
-    slashing_detector: SlashingDetector,
+    /// Signed votes per (validator, slot) and the equivocation evidence found
+    /// among them, proposed in our blocks and served to watchtowers over RPC.
+    evidence_pool: EvidencePool,
     /// Tracks (validator, slot) pairs that have already been slashed to prevent
     /// double-slashing the same offense via both vote-time detection and block evidence.
     slashed_offenses: HashSet<(Address, u64)>,
     /// Slots at which this validator has already cast a vote, preventing
     /// accidental double-votes when multiple blocks arrive for the same slot.
     voted_slots: HashSet<u64>,
+    /// Leaders last asked for by `ConnectValidators`, to skip repeats.
+    preconnected_leaders: Vec<PublicKey>,
     /// Tracks sync state (synced, syncing, stalled).
     sync_manager: SyncManager,
     /// Number of connected peers (updated externally via `set_peer_count`).
     peer_count: usize,
+    /// Slot and validator set shared with the P2P layer's gossip validators.
+    gossip_validation: GossipValidationState,
     /// Orphan blocks waiting for their parent to arrive, keyed by parent hash.
     orphan_blocks: HashMap<H256, Vec<Block>>,
     /// Total number of orphan blocks buffered (across all parent hashes).
@@ -162,6 +189,18 @@ pub struct Node {
     /// committed at a slot wins; competing blocks are kept in memory for vote/QC
     /// purposes but their state is not written to disk until the chain is replayed.
     committed_at_slot: HashMap<Slot, H256>,
+    /// Heaviest-attested head above the latest finalized block, shared by
+    /// every consensus engine. Rooted at the recovered tip, or at the first
+    /// block seen on a fresh chain.
+    chain_head: Option<aether_consensus::ForkChoice>,
+    /// Finalized slot up to which status subscribers were told of finality.
+    finality_notified_slot: Slot,
+    /// WASM program code and the VM that replays calls to it for tracing.
+    /// Locked separately so a trace can run under the node's read lock.
+    wasm_programs: Mutex<WasmPrograms>,
+    /// Checks TEE quotes in compute receipts. Accepts devnet-signed
+    /// simulated quotes only when the chain is configured in dev mode.
+    tee_verifier: TeeVerifier,
 }
 
 impl Node {
@@ -172,9 +211,10 @@ impl Node {
         bls_key: Option<BlsKeypair>,
         chain_config: Arc<ChainConfig>,
     ) -> Result<Self> {
+        let module_cache_dir = db_path.as_ref().join("wasm_modules");
         let storage = Storage::open(db_path).context("failed to open storage")?;
         let ledger = Ledger::new(storage).context("failed to initialize ledger")?;
-        let mempool = Mempool::new(
+        let mut mempool = Mempool::new(
             chain_config.fees.clone(),
             chain_config.chain.chain_id_numeric,
         );
@@ -231,11 +271,18 @@ impl Node {
             chain_config.chain.block_bytes_max,
             chain_config.fees.min_base_fee,
         );
+        mempool.set_base_fee(fee_market.base_fee);
         let emission_schedule = EmissionSchedule::new(
             chain_config.tokens.swr_initial_supply,
             chain_config.chain.slot_ms,
             chain_config.chain.epoch_slots,
         );
+        let finality_notified_slot = consensus.finalized_slot();
+        let mut tee_verifier = TeeVerifier::new();
+        if chain_config.chain.dev_mode {
+            tracing::warn!("dev mode: accepting simulated TEE quotes signed with the devnet key");
+            tee_verifier.enable_dev_mode();
+        }
         Ok(Node {
             chain_config,
             ledger,
@@ -247,6 +294,7 @@ impl Node {
             poh: PohRecorder::new(),
             last_poh_metrics: None,
             fee_market,
+            gas_oracle: GasPriceOracle::new(GAS_ORACLE_BLOCKS),
             emission_schedule,
             current_epoch: 0,
             fork_choice: ForkChoice::new(),
@@ -259,11 +307,13 @@ impl Node {
             broadcast_tx: None,
             outbound_buffer: VecDeque::new(),
             consecutive_timeouts: 0,
-            slashing_detector: SlashingDetector::new(),
+            evidence_pool: EvidencePool::default(),
             slashed_offenses: HashSet::new(),
             voted_slots: HashSet::new(),
+            preconnected_leaders: Vec::new(),
             sync_manager: SyncManager::new(10),
             peer_count: 0,
+            gossip_validation: GossipValidationState::new(),
             orphan_blocks: HashMap::new(),
             orphan_count: 0,
             outbound_drops: 0,
@@ -271,6 +321,13 @@ impl Node {
             snapshot_dir: None,
             last_voted_slot: None,
             committed_at_slot: HashMap::new(),
+            chain_head: latest_block_slot
+                .map(|slot| aether_consensus::ForkChoice::new(latest_block_hash, slot)),
+            finality_notified_slot,
+            wasm_programs: Mutex::new(
+                WasmPrograms::new()?.with_module_cache_dir(&module_cache_dir)?,
+            ),
+            tee_verifier,
         })
     }
 
@@ -459,6 +516,36 @@ impl Node {
         Ok(tx_hash)
     }
 
+    /// Like `submit_transaction` for a transaction that already passed the
+    /// admission pipeline.
+    pub fn submit_admitted(&mut self, tx: AdmittedTx) -> Result<H256> {
+        let tx_hash = tx.tx().hash();
+        let broadcast = tx.tx().clone();
+        self.mempool.add_admitted(tx)?;
+        self.broadcast(OutboundMessage::BroadcastTransaction(broadcast));
+        Ok(tx_hash)
+    }
+
+    /// Pool admitted transactions received from gossip. Gossip forwards
+    /// them itself, so they are not broadcast again.
+    pub fn add_admitted_transactions(&mut self, txs: Vec<AdmittedTx>) {
+        for tx in txs {
+            if let Err(e) = self.mempool.add_admitted(tx) {
+                tracing::debug!(err = %e, "Tx rejected");
+            }
+        }
+    }
+
+    /// Admission checks for incoming transactions. Nonces and balances are
+    /// read from committed state, so the checks run without the node lock.
+    pub fn admission_pipeline(&self) -> AdmissionPipeline {
+        let storage = self.ledger.storage().clone();
+        AdmissionPipeline::new(self.mempool.precheck(), move |address: &Address| {
+            let bytes = storage.get(CF_ACCOUNTS, address.as_bytes()).ok()??;
+            bincode::deserialize::<Account>(&bytes).ok()
+        })
+    }
+
     pub async fn run(&mut self) -> Result<()> {
         self.running = true;
 
@@ -499,6 +586,7 @@ impl Node {
             self.process_epoch_transition(epoch)?;
         }
         self.current_epoch = epoch;
+        self.refresh_gossip_validation(slot, epoch);
 
         // Check pacemaker timeout — if no quorum reached, advance to prevent deadlock
         if self.consensus.is_timed_out() {
@@ -516,6 +604,7 @@ impl Node {
                 "Slot timeout — advancing via pacemaker"
             );
             self.consensus.on_timeout();
+            self.broadcast_round_sync();
         } else {
             self.consecutive_timeouts = 0;
         }
@@ -540,6 +629,8 @@ impl Node {
             }
         }
 
+        self.preconnect_leaders();
+
         // Check if any slot can be finalized
         self.check_finality();
 
@@ -552,6 +643,26 @@ impl Node {
         Ok(())
     }
 
+    /// Ask the network layer to dial the next few leaders other than us,
+    /// whenever that set changes.
+    fn preconnect_leaders(&mut self) {
+        let me = self
+            .validator_key
+            .as_ref()
+            .map(|keypair| PublicKey::from_bytes(keypair.public_key()));
+        let mut leaders: Vec<PublicKey> = Vec::new();
+        for (_, leader) in self.consensus.upcoming_leaders(LEADER_PRECONNECT_SLOTS) {
+            if Some(&leader) != me.as_ref() && !leaders.contains(&leader) {
+                leaders.push(leader);
+            }
+        }
+        if leaders.is_empty() || leaders == self.preconnected_leaders {
+            return;
+        }
+        self.preconnected_leaders = leaders.clone();
+        self.broadcast(OutboundMessage::ConnectValidators(leaders));
+    }
+
     /// Drive the state sync protocol: detect if behind, request blocks,
     /// apply buffered blocks in order, and handle stalls.
     fn drive_sync(&mut self, current_slot: Slot) {
@@ -783,12 +894,13 @@ impl Node {
             }
         }
 
-        // Prune fork choice, slashing detector, and slashed-offenses set for finalized slots.
+        // Prune fork choice, evidence pool records, and slashed-offenses set for finalized slots.
         // slashed_offenses is keyed by slot; entries older than finalized are safe to remove
         // because finalized blocks cannot be re-submitted as new evidence.
         let finalized = self.consensus.finalized_slot();
+        self.notify_finalized(finalized);
         self.fork_choice.prune_before(finalized);
-        self.slashing_detector.prune_before(finalized);
+        self.evidence_pool.prune_before(finalized);
         self.committed_at_slot.retain(|&slot, _| slot >= finalized);
         self.slashed_offenses.retain(|&(_, slot)| slot >= finalized);
         self.voted_slots.retain(|&slot| slot >= finalized);
@@ -811,10 +923,17 @@ impl Node {
                 .mempool
                 .must_include_transactions(slot, self.fee_market.base_fee);
             let forced_count = forced.len();
-            let remaining_capacity = 1000usize.saturating_sub(forced_count);
-            let regular = self
-                .mempool
-                .get_transactions(remaining_capacity, MAX_BLOCK_GAS_LIMIT);
+            let forced_gas: u64 = forced.iter().map(|tx| tx.gas_limit).sum();
+            let forced_bytes: usize = forced
+                .iter()
+                .map(|tx| bincode::serialized_size(tx).map_or(0, |n| n as usize))
+                .sum();
+            let forced_hashes: HashSet<H256> = forced.iter().map(Transaction::hash).collect();
+            let mut regular = self.mempool.build_block(
+                MAX_BLOCK_GAS_LIMIT.saturating_sub(forced_gas),
+                MAX_BLOCK_TX_BYTES.saturating_sub(forced_bytes),
+            );
+            regular.retain(|tx| !forced_hashes.contains(&tx.hash()));
             if forced_count > 0 {
                 tracing::info!(forced_count, "Forced inclusion txs");
                 let mut all = forced;
@@ -889,6 +1008,11 @@ impl Node {
         block.header.state_root = state_root;
         block.header.transactions_root = transactions_root;
         block.header.receipts_root = receipts_root;
+        // Carry equivocation evidence no block has yet, so validators that
+        // missed the conflicting votes apply the slash too.
+        block.slash_evidence = self
+            .evidence_pool
+            .for_block(self.consensus.finalized_slot(), MAX_EVIDENCE_PER_BLOCK);
 
         let block_hash = block.hash();
         tracing::info!(?block_hash, %state_root, "Block produced");
@@ -919,13 +1043,7 @@ impl Node {
         // vice versa), corrupting the node on restart.
         // Fee distribution is also folded in so proposer rewards are never lost if
         // the process crashes after the overlay commit but before the credit write.
-        let total_fees: u128 = transactions
-            .iter()
-            .fold(0u128, |acc, tx| acc.saturating_add(tx.fee));
-        let gas_used: u64 = transactions
-            .iter()
-            .fold(0u64, |acc, tx| acc.saturating_add(tx.gas_limit));
-        let fee_result = self.fee_market.process_block(gas_used, total_fees);
+        let fee_result = self.settle_block_fees(slot, &transactions);
 
         let mut batch = self.ledger.prepare_overlay_batch(&overlay)?;
         let block_batch = self.build_block_batch(&block, block_hash, &stored_receipts)?;
@@ -942,6 +1060,12 @@ impl Node {
         self.ledger.record_spent_utxos(&mut batch, &overlay, slot);
         self.ledger.write_batch(batch)?;
         STORAGE_METRICS.blocks_persisted.inc();
+        for evidence in &block.slash_evidence {
+            if let Some(vote) = &evidence.vote1 {
+                self.evidence_pool
+                    .mark_included(&evidence.validator, vote.slot);
+            }
+        }
 
         // Record block production metrics
         CONSENSUS_METRICS.blocks_produced.inc();
@@ -958,6 +1082,7 @@ impl Node {
 
         self.fork_choice.add_block(slot, block_hash);
         self.fork_choice.mark_committed(slot);
+        self.track_head_block(block_hash, block.header.parent_hash, slot);
         // Record that this slot's state is now durably committed — mirrors the
         // guard in on_block_received that prevents a fork block from overwriting
         // already-committed state and corrupting the UTXO set.
@@ -976,6 +1101,7 @@ impl Node {
         // that were never in this node's local pool.
         let tx_hashes: Vec<H256> = transactions.iter().map(|tx| tx.hash()).collect();
         self.mempool.remove_transactions(&tx_hashes);
+        self.notify_included(&tx_hashes, slot, block_hash);
         for tx in &transactions {
             self.mempool
                 .advance_sender_nonce(tx.sender, tx.nonce.saturating_add(1));
@@ -1054,7 +1180,10 @@ impl Node {
         };
 
         match self.consensus.add_vote(vote.clone()) {
-            Ok(()) => tracing::info!(slot, ?block_hash, "Vote submitted"),
+            Ok(()) => {
+                tracing::info!(slot, ?block_hash, "Vote submitted");
+                self.track_head_vote(&vote);
+            }
             Err(e) => tracing::warn!(slot, err = %e, "Vote failed"),
         }
 
@@ -1229,7 +1358,8 @@ impl Node {
             let _bls_span = tracing::debug_span!(
                 "verify_bls_aggregate",
                 slot = block.header.slot,
-                signers = agg_vote.signers.len(),
+                epoch = agg_vote.epoch,
+                signers = agg_vote.signers.count(),
             )
             .entered();
             // The QC must reference this block's parent — it certifies that
@@ -1241,41 +1371,41 @@ impl Node {
                     block.header.parent_hash
                 );
             }
-            if agg_vote.signers.is_empty() {
+            // Signers are a bitfield over the referenced epoch's validator
+            // set, so each validator counts at most once; a bitfield sized
+            // for any other set is rejected by the expansion.
+            let validator_set = self
+                .consensus
+                .validator_set(agg_vote.epoch)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "aggregated vote references unknown epoch {}",
+                        agg_vote.epoch
+                    )
+                })?;
+            let signers = validator_set
+                .signers(&agg_vote.signers)
+                .context("invalid signer bitfield in aggregated vote")?;
+            if signers.is_empty() {
                 bail!("aggregated vote has no signers");
             }
-            // Reject duplicate signers: without this check an attacker controlling a
-            // single validator could list it N times, inflating voted_stake to N*stake
-            // and bypassing the 2/3 quorum requirement. The BLS aggregate would still
-            // verify because agg(sig, sig, ...) = N*sig verifies against N*pk.
-            {
-                let mut seen = HashSet::with_capacity(agg_vote.signers.len());
-                for signer in &agg_vote.signers {
-                    if !seen.insert(signer.to_address()) {
-                        bail!(
-                            "duplicate signer {:?} in aggregated vote",
-                            signer.to_address()
-                        );
-                    }
-                }
-            }
             // Reconstruct the vote message: block_hash || slot (same as vote_on_block)
             let mut vote_msg = Vec::new();
             vote_msg.extend_from_slice(agg_vote.block_hash.as_bytes());
             vote_msg.extend_from_slice(&agg_vote.slot.to_le_bytes());
 
-            // Look up BLS public keys and compute voted stake from our local
+            // Look up BLS public keys and take voted stake from the epoch's
             // validator set. NEVER trust agg_vote.total_stake from the block —
             // an attacker could set it to any value to bypass quorum checks.
-            let mut bls_pubkeys = Vec::with_capacity(agg_vote.signers.len());
+            let mut bls_pubkeys = Vec::with_capacity(signers.len());
             let mut voted_stake: u128 = 0;
-            for signer in &agg_vote.signers {
-                let addr = signer.to_address();
+            for signer in &signers {
+                let addr = signer.pubkey.to_address();
                 let bls_pk = self.consensus.get_bls_pubkey(&addr).ok_or_else(|| {
                     anyhow::anyhow!("no BLS pubkey registered for signer {:?}", addr)
                 })?;
                 bls_pubkeys.push(bls_pk);
-                voted_stake = voted_stake.saturating_add(self.consensus.validator_stake(&addr));
+                voted_stake = voted_stake.saturating_add(signer.stake);
             }
             let agg_pk = aether_crypto_bls::aggregate_public_keys(&bls_pubkeys)
                 .map_err(|e| anyhow::anyhow!("failed to aggregate signer pubkeys: {e}"))?;
@@ -1290,11 +1420,11 @@ impl Node {
                 bail!("invalid BLS aggregate signature in block");
             }
 
-            // Verify quorum: locally-computed voted stake must be >= 2/3 of total.
+            // Verify quorum: voted stake must be >= 2/3 of the epoch's stake.
             // Use the overflow-safe has_quorum() which handles large u128 stakes
             // via checked_mul — bare `total_stake * 2` would overflow for stakes
             // near u128::MAX, making the check trivially pass.
-            let total_stake = self.consensus.total_stake();
+            let total_stake = validator_set.total_stake;
             if total_stake > 0 && !aether_consensus::has_quorum(voted_stake, total_stake) {
                 bail!(
                     "insufficient quorum: voted stake {} < required 2/3 of {}",
@@ -1367,6 +1497,7 @@ impl Node {
         if is_fork {
             CONSENSUS_METRICS.fork_events.inc();
         }
+        self.track_head_block(block_hash, block.header.parent_hash, block.header.slot);
 
         let is_canonical = new_canonical == Some(block_hash);
 
@@ -1408,15 +1539,7 @@ impl Node {
             // ATOMIC COMMIT: overlay state + block + receipts + fee distribution in one WriteBatch.
             // Fee distribution is folded in so proposer rewards are never lost if the process
             // crashes after the overlay commit but before the credit write.
-            let total_fees: u128 = block
-                .transactions
-                .iter()
-                .fold(0u128, |acc, tx| acc.saturating_add(tx.fee));
-            let gas_used: u64 = block
-                .transactions
-                .iter()
-                .fold(0u64, |acc, tx| acc.saturating_add(tx.gas_limit));
-            let fee_result = self.fee_market.process_block(gas_used, total_fees);
+            let fee_result = self.settle_block_fees(block.header.slot, &block.transactions);
 
             let mut batch = self.ledger.prepare_overlay_batch(&overlay)?;
             let block_batch = self.build_block_batch(&block, block_hash, &stored_receipts)?;
@@ -1437,6 +1560,10 @@ impl Node {
             // persisted in the same WriteBatch. This prevents a crash between block
             // commit and slash application from losing slash effects.
             for evidence in &block.slash_evidence {
+                if let Some(v1) = &evidence.vote1 {
+                    self.evidence_pool
+                        .mark_included(&evidence.validator, v1.slot);
+                }
                 let (v1, v2, etype) =
                     match (&evidence.vote1, &evidence.vote2, &evidence.evidence_type) {
                         (Some(v1), Some(v2), Some(etype)) => (v1, v2, etype),
@@ -1584,6 +1711,12 @@ impl Node {
         // Record block parent for 2-chain finality tracking
         self.consensus
             .record_block(block_hash, block.header.parent_hash, block.header.slot);
+        // Lock on the parent's QC before voting, even if we missed its votes.
+        if let Some(ref agg_vote) = block.aggregated_vote {
+            if let Err(e) = self.consensus.record_qc(agg_vote) {
+                tracing::warn!(err = %e, slot = block.header.slot, "failed to record parent QC");
+            }
+        }
 
         for sr in &stored_receipts {
             self.receipts.insert(sr.tx_hash, sr.clone());
@@ -1593,6 +1726,7 @@ impl Node {
         // mempool rejects replays of transactions included in received blocks.
         let tx_hashes: Vec<H256> = block.transactions.iter().map(|tx| tx.hash()).collect();
         self.mempool.remove_transactions(&tx_hashes);
+        self.notify_included(&tx_hashes, block.header.slot, block_hash);
         for tx in &block.transactions {
             self.mempool
                 .advance_sender_nonce(tx.sender, tx.nonce.saturating_add(1));
@@ -1643,6 +1777,29 @@ impl Node {
         self.peer_count = count;
     }
 
+    /// Chain view for the P2P layer's vote and shred validators, kept
+    /// current as slots and epochs advance.
+    pub fn gossip_validation(&self) -> GossipValidationState {
+        self.gossip_validation.clone()
+    }
+
+    /// The mempool's stateless transaction checks, for validating gossip.
+    pub fn tx_precheck(&self) -> TxPrecheck {
+        self.mempool.precheck()
+    }
+
+    fn refresh_gossip_validation(&self, slot: Slot, epoch: u64) {
+        self.gossip_validation.set_slot(slot);
+        if self.gossip_validation.epoch() == Some(epoch) {
+            return;
+        }
+        if let Some(info) = self.consensus.validator_set(epoch) {
+            let consensus = &self.consensus;
+            self.gossip_validation
+                .set_epoch(&info, |address| consensus.get_bls_pubkey(address));
+        }
+    }
+
     /// Returns outbound message drop count (backpressure indicator).
     pub fn outbound_drops(&self) -> u64 {
         self.outbound_drops
@@ -1656,6 +1813,43 @@ impl Node {
     /// Remove orphan blocks whose slot is at or before `min_slot`.
     /// Blocks at finalized or earlier slots can never be applied, so keeping
     /// them just wastes memory and lets an attacker permanently fill the buffer.
+    fn notify_included(&self, tx_hashes: &[H256], slot: Slot, block_hash: H256) {
+        for hash in tx_hashes {
+            self.mempool
+                .notify_status(*hash, TxLifecycle::Included { slot, block_hash });
+        }
+    }
+
+    /// Report finality of the transactions in blocks finalized since the
+    /// last call, and store how each of those blocks became final.
+    fn notify_finalized(&mut self, finalized: Slot) {
+        if finalized <= self.finality_notified_slot {
+            return;
+        }
+        let mut batch = StorageBatch::new();
+        for (&slot, hash) in self
+            .blocks_by_slot
+            .range(self.finality_notified_slot + 1..=finalized)
+        {
+            if let Some(path) = self.consensus.finality_path(slot) {
+                match bincode::serialize(&path) {
+                    Ok(bytes) => batch.put(CF_METADATA, finality_path_key(slot), bytes),
+                    Err(e) => tracing::warn!(slot, error = %e, "failed to encode finality path"),
+                }
+            }
+            if let Some(block) = self.blocks_by_hash.get(hash) {
+                for tx in &block.transactions {
+                    self.mempool
+                        .notify_status(tx.hash(), TxLifecycle::Finalized { slot });
+                }
+            }
+        }
+        if let Err(e) = self.ledger.write_batch(batch) {
+            tracing::warn!(error = %e, "failed to persist finality paths");
+        }
+        self.finality_notified_slot = finalized;
+    }
+
     fn prune_stale_orphans(&mut self, min_slot: u64) {
         if min_slot == 0 {
             return;
@@ -1682,6 +1876,23 @@ impl Node {
     // Vote Reception (Phase C)
     // ========================================================================
 
+    /// Report our slot and highest QC after a timeout, so validators split
+    /// from us by a partition or clock skew can converge on one slot.
+    fn broadcast_round_sync(&mut self) {
+        match self.consensus.round_sync() {
+            Ok(Some(sync)) => self.broadcast(OutboundMessage::BroadcastRoundSync(sync)),
+            Ok(None) => {}
+            Err(e) => tracing::warn!(err = %e, "failed to sign round sync"),
+        }
+    }
+
+    /// Handle a peer's round sync: consensus skips ahead once validators
+    /// holding more than a third of the stake report a later slot.
+    pub fn on_round_sync_received(&mut self, sync: RoundSync) -> Result<()> {
+        self.consensus.on_round_sync(&sync)?;
+        Ok(())
+    }
+
     /// Handle a vote received from the P2P network.
     /// Checks for double-signing before processing. If a validator votes for two
     /// different blocks in the same slot, they are slashed (5% of stake).
@@ -1692,16 +1903,9 @@ impl Node {
             validator = ?vote.validator.to_address(),
         )
         .entered();
-        let validator_address = vote.validator.to_address();
 
         // Check for double-signing before accepting the vote
-        if let Some(proof) = self.slashing_detector.record_vote(
-            validator_address,
-            vote.validator.clone(),
-            vote.slot,
-            vote.block_hash,
-            vote.signature.clone(),
-        ) {
+        if let Some(proof) = self.evidence_pool.add_vote(&vote) {
             // Double-sign detected — apply slash to both consensus vote weights AND
             // staking state (the authoritative bond accounting). Use a dedup set so
             // block-evidence processing cannot slash the same (validator, slot) twice.
@@ -1748,11 +1952,118 @@ impl Node {
             }
         }
 
-        self.consensus.add_vote(vote)?;
+        self.consensus.add_vote(vote.clone())?;
+        self.track_head_vote(&vote);
         self.check_finality();
         Ok(())
     }
 
+    // ========================================================================
+    // Fork Choice
+    // ========================================================================
+
+    fn track_head_block(&mut self, block_hash: H256, parent_hash: H256, slot: Slot) {
+        let Some(chain_head) = self.chain_head.as_mut() else {
+            self.chain_head = Some(aether_consensus::ForkChoice::new(block_hash, slot));
+            return;
+        };
+        match chain_head.on_block(block_hash, parent_hash, slot) {
+            Ok(Some(reorg)) => self.on_reorg(reorg),
+            Ok(None) => {}
+            Err(e) => tracing::debug!(slot, ?block_hash, err = %e, "fork choice skipped block"),
+        }
+    }
+
+    fn track_head_vote(&mut self, vote: &Vote) {
+        let reorg = self.chain_head.as_mut().and_then(|chain_head| {
+            chain_head.on_vote(
+                vote.validator.to_address(),
+                vote.block_hash,
+                vote.slot,
+                vote.stake,
+            )
+        });
+        if let Some(reorg) = reorg {
+            self.on_reorg(reorg);
+        }
+    }
+
+    /// The head moved off the chain it was on. Committed state is never
+    /// rolled back (see `committed_at_slot`), so a reorg across committed
+    /// blocks leaves this node on the abandoned branch until it resyncs.
+    fn on_reorg(&mut self, reorg: Reorg) {
+        let mut abandoned_committed = 0u64;
+        let mut at = reorg.old_head;
+        while at != reorg.common_ancestor {
+            let Some(block) = self.blocks_by_hash.get(&at) else {
+                break;
+            };
+            if self.committed_at_slot.get(&block.header.slot) == Some(&at) {
+                abandoned_committed += 1;
+            }
+            at = block.header.parent_hash;
+        }
+        if abandoned_committed > 0 {
+            tracing::error!(
+                old_head = ?reorg.old_head,
+                new_head = ?reorg.new_head,
+                depth = reorg.depth,
+                abandoned_committed,
+                "fork choice abandoned committed blocks — state needs a resync"
+            );
+        } else {
+            tracing::info!(
+                old_head = ?reorg.old_head,
+                new_head = ?reorg.new_head,
+                depth = reorg.depth,
+                "fork choice switched to an uncommitted branch"
+            );
+        }
+    }
+
+    /// Head picked by fork choice: the heaviest-attested block above the
+    /// latest finalized one.
+    pub fn fork_choice_head(&self) -> Option<H256> {
+        self.chain_head.as_ref().map(|c| c.head())
+    }
+
+    /// Equivocation evidence pooled for slots from `from_slot` on, for
+    /// watchtowers to corroborate.
+    pub fn evidence(&self, from_slot: u64) -> Vec<EvidenceEntry> {
+        self.evidence_pool.entries(from_slot)
+    }
+
+    /// Leader rotation of `epoch`; empty when the engine elects leaders
+    /// privately.
+    pub fn leader_schedule(&self, epoch: u64) -> Vec<PublicKey> {
+        self.consensus.leader_schedule(epoch)
+    }
+
+    /// Known leaders of up to `n` slots after the current one.
+    pub fn upcoming_leaders(&self, n: usize) -> Vec<(Slot, PublicKey)> {
+        self.consensus.upcoming_leaders(n)
+    }
+
+    /// Checkpoint finality certificates for slots from `from_slot` on, for
+    /// light clients and bridges.
+    pub fn finality_certificates(&self, from_slot: u64) -> Vec<FinalityCertificate> {
+        self.consensus.finality_certificates(from_slot)
+    }
+
+    /// Whether `slot` was finalized in one round or by the two-chain rule.
+    /// Consensus only remembers recent slots; older ones are read back from
+    /// what was stored with the block.
+    pub fn finality_path(&self, slot: Slot) -> Option<FinalityPath> {
+        self.consensus.finality_path(slot).or_else(|| {
+            self.ledger
+                .storage()
+                .get(CF_METADATA, &finality_path_key(slot))
+                .ok()
+                .flatten()
+                .and_then(|bytes| bincode::deserialize(&bytes).ok())
+        })
+    }
+
     // ========================================================================
     // Network Event Dispatch
     // ========================================================================
@@ -1793,6 +2104,12 @@ impl Node {
                     tracing::debug!(err = %e, "Tx rejected");
                 }
             }
+            Some(NodeMessage::RoundSyncReceived(sync)) => {
+                let _msg_span = tracing::debug_span!("msg_round_sync", slot = sync.slot).entered();
+                if let Err(e) = self.on_round_sync_received(sync) {
+                    tracing::debug!(err = %e, "Round sync rejected");
+                }
+            }
             Some(NodeMessage::BlockRangeRequested { from_slot, to_slot }) => {
                 let _msg_span =
                     tracing::debug_span!("msg_block_range_req", from_slot, to_slot,).entered();
@@ -1884,8 +2201,10 @@ impl Node {
                         .observe(latency_ms as f64);
 
                     if block.header.vrf_proof.output != [0u8; 32] {
-                        self.consensus
-                            .update_epoch_randomness(&block.header.vrf_proof.output);
+                        self.consensus.update_epoch_randomness(
+                            block.header.slot,
+                            &block.header.vrf_proof.output,
+                        );
                     }
                 }
 
@@ -1898,6 +2217,17 @@ impl Node {
                             "fork_choice: could not finalize unknown block"
                         );
                     }
+                    let finalized = self
+                        .chain_head
+                        .as_mut()
+                        .map(|chain_head| chain_head.on_finalized(hash));
+                    match finalized {
+                        Some(Ok(Some(reorg))) => self.on_reorg(reorg),
+                        Some(Err(e)) => {
+                            tracing::debug!(slot, ?hash, err = %e, "fork choice kept its root")
+                        }
+                        _ => {}
+                    }
                 }
             }
         }
@@ -1940,6 +2270,11 @@ impl Node {
         self.ledger.state_root()
     }
 
+    /// Lifecycle events of transactions, from admission to finality.
+    pub fn subscribe_tx_status(&self) -> tokio::sync::broadcast::Receiver<TxStatusEvent> {
+        self.mempool.subscribe_status()
+    }
+
     pub fn mempool_size(&self) -> usize {
         self.mempool.len()
     }
@@ -2037,10 +2372,82 @@ impl Node {
         self.ledger.get_account(&address)
     }
 
+    fn wasm_programs(&self) -> Result<std::sync::MutexGuard<'_, WasmPrograms>> {
+        self.wasm_programs
+            .lock()
+            .map_err(|_| anyhow::anyhow!("WASM program lock poisoned"))
+    }
+
+    /// Make WASM `code` callable at `program_id`.
+    pub fn register_wasm_program(&self, program_id: H256, code: Vec<u8>) -> Result<()> {
+        self.wasm_programs()?.register(program_id, code)
+    }
+
+    /// Register every `<hex id>.wasm` program in `dir`.
+    pub fn load_wasm_programs(&self, dir: &Path) -> Result<usize> {
+        self.wasm_programs()?.load_dir(dir)
+    }
+
+    /// Re-run an included transaction's WASM program call with tracing on;
+    /// `None` if the transaction is unknown. The replay reads balances from
+    /// the latest committed state, not the state the transaction saw.
+    pub fn trace_transaction(&self, tx_hash: H256) -> Result<Option<ExecutionTrace>> {
+        let Some(receipt) = self.get_transaction_receipt(tx_hash) else {
+            return Ok(None);
+        };
+        let block = self
+            .get_block_by_hash(receipt.block_hash)
+            .with_context(|| format!("block of transaction {tx_hash:?} is missing"))?;
+        let Some(tx) = block.transactions.iter().find(|tx| tx.hash() == tx_hash) else {
+            return Ok(None);
+        };
+        let trace = self.wasm_programs()?.trace(
+            tx,
+            block.header.slot,
+            block.header.timestamp,
+            |address| self.ledger.get_account(address),
+        )?;
+        Ok(Some(trace))
+    }
+
     pub fn base_fee(&self) -> u128 {
         self.fee_market.base_fee
     }
 
+    /// Priority fee per gas likely to get a transaction into the next block.
+    pub fn suggested_priority_fee(&self) -> u128 {
+        self.gas_oracle.suggest_priority_fee()
+    }
+
+    pub fn fee_history(&self, block_count: usize, percentiles: &[u8]) -> FeeHistory {
+        self.gas_oracle
+            .fee_history(block_count, percentiles, self.fee_market.base_fee)
+    }
+
+    /// Split a block's fees into burn and rewards, sample its priority fees
+    /// for the gas oracle and move the base fee on to the next block.
+    fn settle_block_fees(&mut self, slot: Slot, transactions: &[Transaction]) -> BlockFeeResult {
+        let total_fees: u128 = transactions
+            .iter()
+            .fold(0u128, |acc, tx| acc.saturating_add(tx.fee));
+        let gas_used: u64 = transactions
+            .iter()
+            .fold(0u64, |acc, tx| acc.saturating_add(tx.gas_limit));
+        self.gas_oracle.record_block(BlockFeeSample {
+            slot,
+            base_fee: self.fee_market.base_fee,
+            gas_used,
+            max_gas: self.fee_market.max_gas,
+            priority_fees: transactions
+                .iter()
+                .filter_map(|tx| self.fee_market.priority_fee_per_gas(tx.fee, tx.gas_limit))
+                .collect(),
+        });
+        let fee_result = self.fee_market.process_block(gas_used, total_fees);
+        self.mempool.set_base_fee(fee_result.next_base_fee);
+        fee_result
+    }
+
     /// Mutable access to the in-memory staking state.
     ///
     /// Used by tests and the genesis bootstrap path to register validators
@@ -2054,6 +2461,11 @@ impl Node {
         &self.staking_state
     }
 
+    /// The TEE verifier configured for this chain at startup.
+    pub fn tee_verifier(&self) -> &TeeVerifier {
+        &self.tee_verifier
+    }
+
     /// Serialize current staking state into a batch for atomic persistence.
     fn persist_staking_state_to_batch(&self, batch: &mut StorageBatch) -> Result<()> {
         let bytes =
@@ -2070,6 +2482,11 @@ impl Node {
     }
 }
 
+/// Metadata key for the finality path of the block at `slot`.
+fn finality_path_key(slot: Slot) -> Vec<u8> {
+    format!("finality:{slot}").into_bytes()
+}
+
 // ============================================================================
 // Block Header Root Computation (Phase D)
 // ============================================================================
@@ -2154,6 +2571,27 @@ mod tests {
         assert!(second_metrics.average_duration_ms >= 0.0);
     }
 
+    #[test]
+    fn dev_mode_chains_accept_simulated_tee_quotes() {
+        for (config, dev_mode) in [
+            (ChainConfig::devnet(), true),
+            (ChainConfig::testnet(), false),
+        ] {
+            let temp_dir = TempDir::new().unwrap();
+            let keypair = Keypair::generate();
+            let validators = vec![validator_info_from_key(&keypair)];
+            let node = Node::new(
+                temp_dir.path(),
+                Box::new(SimpleConsensus::new(validators)),
+                Some(keypair),
+                None,
+                Arc::new(config),
+            )
+            .unwrap();
+            assert_eq!(node.tee_verifier().dev_mode(), dev_mode);
+        }
+    }
+
     #[test]
     fn outbound_buffer_is_capped() {
         let temp_dir = TempDir::new().unwrap();
@@ -2864,7 +3302,7 @@ mod tests {
 
         // Phase 2: reopen from same DB — state should be intact
         {
-            let keypair2 = Keypair::from_bytes(&key_bytes).unwrap();
+            let keypair2 = Keypair::from_bytes(key_bytes.expose_secret()).unwrap();
             let validators2 = vec![validator_info_from_key(&keypair2)];
             let consensus = Box::new(SimpleConsensus::new(validators2));
             let node = Node::new(
@@ -2922,7 +3360,7 @@ mod tests {
 
         // Phase 2: reopen from the same DB — staking state must survive.
         {
-            let keypair2 = Keypair::from_bytes(&key_bytes).unwrap();
+            let keypair2 = Keypair::from_bytes(key_bytes.expose_secret()).unwrap();
             let validators2 = vec![validator_info_from_key(&keypair2)];
             let consensus = Box::new(SimpleConsensus::new(validators2));
             let node = Node::new(
@@ -2994,7 +3432,7 @@ mod tests {
 
         // Phase 2: reopen — slashed state must survive.
         {
-            let keypair2 = Keypair::from_bytes(&key_bytes).unwrap();
+            let keypair2 = Keypair::from_bytes(key_bytes.expose_secret()).unwrap();
             let validators2 = vec![validator_info_from_key(&keypair2)];
             let consensus = Box::new(SimpleConsensus::new(validators2));
             let node = Node::new(
@@ -4409,7 +4847,7 @@ mod tests {
     }
 
     #[test]
-    fn duplicate_signer_in_aggregated_vote_is_rejected() {
+    fn signer_bitfield_for_other_validator_set_is_rejected() {
         let temp_dir = TempDir::new().unwrap();
         let keypair = Keypair::generate();
         let validators = vec![validator_info_from_key(&keypair)];
@@ -4445,7 +4883,15 @@ mod tests {
             slot: 1,
             block_hash: parent_hash,
             aggregated_signature: vec![0u8; 96],
-            signers: vec![signer_pk.clone(), signer_pk],
+            epoch: 0,
+            // Two bits against a one-validator set: a bitfield cannot name
+            // a signer twice, so inflating stake means widening the field.
+            signers: {
+                let mut signers = aether_types::SignerBitfield::new(2);
+                signers.set(0).unwrap();
+                signers.set(1).unwrap();
+                signers
+            },
             total_stake: 200_000_000,
         };
 
@@ -4465,12 +4911,64 @@ mod tests {
         let result = node.on_block_received(child);
         assert!(
             result.is_err(),
-            "block with duplicate signers must be rejected"
+            "block with a mis-sized signer bitfield must be rejected"
         );
         let msg = result.unwrap_err().to_string();
         assert!(
-            msg.contains("duplicate signer"),
-            "error must mention duplicate signer, got: {msg}"
+            msg.contains("signer bitfield"),
+            "error must mention the signer bitfield, got: {msg}"
         );
     }
+
+    #[test]
+    fn admission_pipeline_checks_committed_balances() {
+        let temp_dir = TempDir::new().unwrap();
+        let keypair = Keypair::generate();
+        let validators = vec![validator_info_from_key(&keypair)];
+        let consensus = Box::new(SimpleConsensus::new(validators));
+        let mut node = Node::new(
+            temp_dir.path(),
+            consensus,
+            Some(keypair),
+            None,
+            Arc::new(ChainConfig::devnet()),
+        )
+        .unwrap();
+
+        let signed = |kp: &Keypair| {
+            let sender_pubkey = PublicKey::from_bytes(kp.public_key());
+            let mut tx = Transaction {
+                nonce: 0,
+                chain_id: ChainConfig::devnet().chain.chain_id_numeric,
+                sender: sender_pubkey.to_address(),
+                sender_pubkey,
+                inputs: vec![],
+                outputs: vec![],
+                reads: HashSet::new(),
+                writes: HashSet::new(),
+                program_id: None,
+                data: vec![],
+                gas_limit: 21_000,
+                fee: 60_000,
+                signature: aether_types::Signature::from_bytes(vec![]),
+            };
+            tx.signature = aether_types::Signature::from_bytes(kp.sign(tx.hash().as_bytes()));
+            tx
+        };
+        let funded = Keypair::generate();
+        let funded_tx = signed(&funded);
+        node.seed_account(&funded_tx.sender, 1_000_000).unwrap();
+        let unfunded_tx = signed(&Keypair::generate());
+
+        let mut results = node
+            .admission_pipeline()
+            .admit(vec![funded_tx, unfunded_tx])
+            .into_iter();
+        let admitted = results.next().unwrap().expect("funded sender is admitted");
+        let rejection = results.next().unwrap().unwrap_err();
+        assert_eq!(rejection.stage, aether_mempool::AdmissionStage::State);
+
+        node.add_admitted_transactions(vec![admitted]);
+        assert_eq!(node.mempool_size(), 1);
+    }
 }
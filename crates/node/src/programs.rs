@@ -0,0 +1,230 @@
+// ============================================================================
+// WASM PROGRAMS - Deployed program code and the VM that runs it
+// ============================================================================
+// Program code is registered by program id, either directly or from a
+// directory of `<hex id>.wasm` files at startup, and compiled when
+// registered so the first call does not pay for it. The VM re-runs an
+// included transaction against committed state with tracing on, which is
+// what backs `debug_traceTransaction`.
+// ============================================================================
+
+use aether_runtime::{
+    host_functions, CapabilityTable, ExecutionContext, ExecutionTrace, HostFunctions, ModuleCache,
+    WasmVm,
+};
+use aether_types::{Account, Address, Transaction, H256};
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Most gas a single program execution may use.
+pub const MAX_PROGRAM_GAS: u64 = 10_000_000;
+
+pub struct WasmPrograms {
+    vm: WasmVm,
+    code: HashMap<H256, Vec<u8>>,
+}
+
+/// A program runs as the address formed by the last 20 bytes of its id.
+pub fn program_address(program_id: &H256) -> Address {
+    Address::from_slice(&program_id.as_bytes()[12..]).expect("20-byte slice")
+}
+
+impl WasmPrograms {
+    pub fn new() -> Result<Self> {
+        Ok(WasmPrograms {
+            vm: WasmVm::new(MAX_PROGRAM_GAS)?,
+            code: HashMap::new(),
+        })
+    }
+
+    /// Keep compiled programs under `dir` so a restart reloads them
+    /// instead of compiling again.
+    pub fn with_module_cache_dir(mut self, dir: &Path) -> Result<Self> {
+        let modules = ModuleCache::default()
+            .with_disk_dir(dir)
+            .with_context(|| format!("creating {}", dir.display()))?;
+        self.vm = self.vm.with_module_cache(modules);
+        Ok(self)
+    }
+
+    /// Make `code` runnable at `program_id`, compiling it now.
+    pub fn register(&mut self, program_id: H256, code: Vec<u8>) -> Result<()> {
+        WasmVm::validate_deterministic(&code).with_context(|| format!("program {program_id:?}"))?;
+        if self.vm.prewarm([code.as_slice()]) == 0 {
+            bail!("program {program_id:?} failed to compile");
+        }
+        self.code.insert(program_id, code);
+        Ok(())
+    }
+
+    /// Register every `<hex id>.wasm` file in `dir`; returns how many.
+    pub fn load_dir(&mut self, dir: &Path) -> Result<usize> {
+        let entries =
+            std::fs::read_dir(dir).with_context(|| format!("reading {}", dir.display()))?;
+        let mut loaded = 0;
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("wasm") {
+                continue;
+            }
+            let Some(program_id) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| hex::decode(stem.trim_start_matches("0x")).ok())
+                .and_then(|bytes| H256::from_slice(&bytes).ok())
+            else {
+                tracing::warn!(path = %path.display(), "skipping program not named by its id");
+                continue;
+            };
+            let code =
+                std::fs::read(&path).with_context(|| format!("reading {}", path.display()))?;
+            self.register(program_id, code)?;
+            loaded += 1;
+        }
+        Ok(loaded)
+    }
+
+    pub fn contains(&self, program_id: &H256) -> bool {
+        self.code.contains_key(program_id)
+    }
+
+    /// Re-run `tx` with tracing on, as included at `block_number`, against
+    /// the balances `account` returns for its sender and declared accounts.
+    pub fn trace(
+        &mut self,
+        tx: &Transaction,
+        block_number: u64,
+        timestamp: u64,
+        account: impl Fn(&Address) -> Result<Option<Account>>,
+    ) -> Result<ExecutionTrace> {
+        let Some(program_id) = tx.program_id else {
+            bail!("transaction does not call a program");
+        };
+        let Some(code) = self.code.get(&program_id) else {
+            bail!("program {program_id:?} is not a WASM program");
+        };
+
+        let gas_limit = tx.gas_limit.min(self.vm.gas_limit());
+        let mut host = HostFunctions::new(gas_limit, host_functions::ExecutionContext::default())
+            .with_capabilities(CapabilityTable::from_transaction(tx))
+            .with_tracing();
+        for address in tx.reads.iter().chain(&tx.writes).chain([&tx.sender]) {
+            if let Some(account) = account(address)? {
+                host.balances.insert(*address, account.balance);
+            }
+        }
+
+        let context = ExecutionContext {
+            contract_address: program_address(&program_id),
+            caller: tx.sender,
+            value: 0,
+            gas_limit,
+            block_number,
+            timestamp,
+        };
+        let result = self
+            .vm
+            .execute_with_host(code, &context, &tx.data, &mut host)?;
+        result.trace.context("traced execution returned no trace")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_types::{PublicKey, Signature};
+    use std::collections::HashSet;
+
+    fn program_tx(program_id: H256) -> Transaction {
+        let sender_pubkey = PublicKey::from_bytes(vec![7u8; 32]);
+        Transaction {
+            nonce: 0,
+            chain_id: 900,
+            sender: sender_pubkey.to_address(),
+            sender_pubkey,
+            inputs: vec![],
+            outputs: vec![],
+            reads: HashSet::new(),
+            writes: HashSet::new(),
+            program_id: Some(program_id),
+            data: b"key".to_vec(),
+            gas_limit: 100_000,
+            fee: 0,
+            signature: Signature::from_bytes(vec![]),
+        }
+    }
+
+    #[test]
+    fn traces_a_registered_program() {
+        let program_id = H256::from_slice(&[0x42; 32]).unwrap();
+        let code = wat::parse_str(
+            r#"(module
+                (import "env" "storage_write" (func $sw (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                    (drop (call $sw (local.get $ptr) (local.get $len) (local.get $ptr) (local.get $len)))
+                    i32.const 0
+                )
+            )"#,
+        )
+        .unwrap();
+        let mut programs = WasmPrograms::new().unwrap();
+        programs.register(program_id, code.clone()).unwrap();
+        assert!(programs
+            .vm
+            .module_cache()
+            .contains(&ModuleCache::code_hash(&code)));
+
+        let trace = programs
+            .trace(&program_tx(program_id), 7, 1_000, |_| Ok(None))
+            .unwrap();
+        assert_eq!(trace.host_calls.len(), 1);
+        assert_eq!(trace.host_calls[0].name, "storage_write");
+        assert!(trace.gas_used > 0);
+
+        let other = H256::from_slice(&[0x43; 32]).unwrap();
+        assert!(programs
+            .trace(&program_tx(other), 7, 1_000, |_| Ok(None))
+            .is_err());
+    }
+
+    #[test]
+    fn load_dir_compiles_into_the_module_cache_dir() {
+        let programs_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let code = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "execute") (param i32 i32) (result i32) i32.const 0)
+            )"#,
+        )
+        .unwrap();
+        let program_id = H256::from_slice(&[0x42; 32]).unwrap();
+        std::fs::write(
+            programs_dir
+                .path()
+                .join(format!("{}.wasm", hex::encode(program_id.as_bytes()))),
+            &code,
+        )
+        .unwrap();
+
+        let mut programs = WasmPrograms::new()
+            .unwrap()
+            .with_module_cache_dir(cache_dir.path())
+            .unwrap();
+        assert_eq!(programs.load_dir(programs_dir.path()).unwrap(), 1);
+        assert!(programs.contains(&program_id));
+        let artifact = cache_dir
+            .path()
+            .join(format!("{:?}.cwasm", ModuleCache::code_hash(&code)));
+        assert!(artifact.exists());
+
+        let mut restarted = WasmPrograms::new()
+            .unwrap()
+            .with_module_cache_dir(cache_dir.path())
+            .unwrap();
+        restarted.register(program_id, code).unwrap();
+        assert_eq!(restarted.vm.module_cache().stats().disk_hits, 1);
+    }
+}
@@ -13,8 +13,8 @@ use aether_node::{
     ValidatorKeypair,
 };
 use aether_types::{
-    Address, AggregatedVote, Block, BlockHeader, ChainConfig, PublicKey, Signature, SlashEvidence,
-    Slot, Transaction, ValidatorInfo, Vote, VrfProof, H256,
+    Address, AggregatedVote, Block, BlockHeader, ChainConfig, ErasureParams, PublicKey, Signature,
+    SignerBitfield, SlashEvidence, Slot, Transaction, ValidatorInfo, Vote, VrfProof, H256,
 };
 use std::collections::HashSet;
 use std::sync::Arc;
@@ -142,8 +142,10 @@ impl TestNetwork {
                     OutboundMessage::BroadcastTransaction(tx) => {
                         self.pending_txs.push(tx);
                     }
-                    OutboundMessage::RequestBlockRange { .. } => {
-                        // Sync requests are ignored in adversarial test harness
+                    OutboundMessage::RequestBlockRange { .. }
+                    | OutboundMessage::ConnectValidators(_)
+                    | OutboundMessage::BroadcastRoundSync(_) => {
+                        // Sync requests, round syncs and dials are ignored in adversarial test harness
                     }
                 }
             }
@@ -189,6 +191,7 @@ fn test_forged_block_rejected() {
                 proof: vec![0xBB; 80],
             },
             timestamp: 9999,
+            erasure: ErasureParams::default(),
         },
         transactions: vec![],
         aggregated_vote: None,
@@ -553,6 +556,7 @@ fn test_reject_wrong_protocol_version() {
                 proof: vec![],
             },
             timestamp: 0,
+            erasure: ErasureParams::default(),
         },
         transactions: vec![],
         aggregated_vote: None,
@@ -602,6 +606,7 @@ fn test_reject_slot_monotonicity_violation() {
                     proof: vec![],
                 },
                 timestamp: 0,
+                erasure: ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: None,
@@ -648,6 +653,7 @@ fn test_reject_invalid_receipts_root() {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            erasure: ErasureParams::default(),
         },
         transactions: vec![],
         aggregated_vote: None,
@@ -695,6 +701,7 @@ fn test_reject_block_missing_quorum_certificate() {
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_secs(),
+                erasure: ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: None, // Missing QC!
@@ -738,7 +745,12 @@ fn test_reject_block_qc_wrong_parent() {
         let agg_vote = AggregatedVote {
             block_hash: wrong_hash, // Wrong! Should be parent_hash
             slot: 1,
-            signers: vec![PublicKey::from_bytes(vec![1u8; 32])],
+            epoch: 0,
+            signers: {
+                let mut signers = SignerBitfield::new(4);
+                signers.set(0).unwrap();
+                signers
+            },
             aggregated_signature: vec![0u8; 96],
             total_stake: 1000,
         };
@@ -757,6 +769,7 @@ fn test_reject_block_qc_wrong_parent() {
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap()
                     .as_secs(),
+                erasure: ErasureParams::default(),
             },
             transactions: vec![],
             aggregated_vote: Some(agg_vote),
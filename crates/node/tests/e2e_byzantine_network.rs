@@ -164,7 +164,9 @@ impl ByzantineTestNetwork {
                     OutboundMessage::BroadcastTransaction(tx) => {
                         self.pending_txs.push(tx);
                     }
-                    OutboundMessage::RequestBlockRange { .. } => {}
+                    OutboundMessage::RequestBlockRange { .. }
+                    | OutboundMessage::ConnectValidators(_)
+                    | OutboundMessage::BroadcastRoundSync(_) => {}
                 }
             }
         }
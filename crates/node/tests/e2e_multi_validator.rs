@@ -149,8 +149,10 @@ impl TestNetwork {
                     OutboundMessage::BroadcastTransaction(tx) => {
                         self.pending_txs.push(tx);
                     }
-                    OutboundMessage::RequestBlockRange { .. } => {
-                        // Sync requests are ignored in multi-validator test harness
+                    OutboundMessage::RequestBlockRange { .. }
+                    | OutboundMessage::ConnectValidators(_)
+                    | OutboundMessage::BroadcastRoundSync(_) => {
+                        // Sync requests, round syncs and dials are ignored in multi-validator test harness
                     }
                 }
             }
@@ -233,8 +235,10 @@ impl TestNetwork {
                         OutboundMessage::BroadcastTransaction(tx) => {
                             pending_txs.push((idx, tx));
                         }
-                        OutboundMessage::RequestBlockRange { .. } => {
-                            // Sync requests are ignored in partitioned test harness
+                        OutboundMessage::RequestBlockRange { .. }
+                        | OutboundMessage::ConnectValidators(_)
+                        | OutboundMessage::BroadcastRoundSync(_) => {
+                            // Sync requests, round syncs and dials are ignored in partitioned test harness
                         }
                     }
                 }
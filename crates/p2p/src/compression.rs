@@ -0,0 +1,318 @@
+use std::collections::HashSet;
+use std::io::{self, Read};
+use std::sync::{Arc, RwLock};
+
+use aether_metrics::p2p::topic_label;
+use aether_metrics::P2P_METRICS;
+use libp2p::gossipsub::{DataTransform, Message, RawMessage, TopicHash};
+use libp2p::PeerId;
+
+/// Token a peer puts in its identify agent version to say it can read
+/// zstd-compressed gossip.
+pub(crate) const ZSTD_CAPABILITY: &str = "zstd/1";
+
+/// Every zstd frame starts with this, so compressed payloads need no
+/// extra framing and peers without the capability see the same bytes
+/// they always did.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Whether an identify agent version advertises zstd support.
+pub(crate) fn advertises_zstd(agent_version: &str) -> bool {
+    agent_version
+        .split_whitespace()
+        .any(|token| token == ZSTD_CAPABILITY)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompressionConfig {
+    /// Payloads smaller than this are always sent as is.
+    pub threshold: usize,
+    /// zstd compression level.
+    pub level: i32,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        CompressionConfig {
+            threshold: 1024,
+            level: 3,
+        }
+    }
+}
+
+#[derive(Default)]
+struct PeerCapabilities {
+    zstd: HashSet<PeerId>,
+    /// Topics whose every known subscriber reads zstd.
+    compressed_topics: HashSet<TopicHash>,
+}
+
+/// Transparent zstd compression of gossip payloads.
+///
+/// Plugged into gossipsub as its data transform: large outbound payloads
+/// are compressed before signing, and inbound ones are decompressed before
+/// anything else sees them. A topic is only compressed while every peer
+/// subscribed to it has advertised [`ZSTD_CAPABILITY`]; gossipsub forwards
+/// messages unchanged, so during a rollout a compressed message can still
+/// reach an old peer two hops away.
+///
+/// Decompression stops at the topic's size limit, so a small message
+/// cannot expand into an oversized one.
+#[derive(Clone)]
+pub struct GossipCompression {
+    config: CompressionConfig,
+    max_size: fn(&str) -> usize,
+    peers: Arc<RwLock<PeerCapabilities>>,
+}
+
+impl GossipCompression {
+    /// Compression with `config`, decompressing at most `max_size(topic)`
+    /// bytes per message.
+    pub fn new(config: CompressionConfig, max_size: fn(&str) -> usize) -> Self {
+        GossipCompression {
+            config,
+            max_size,
+            peers: Arc::default(),
+        }
+    }
+
+    pub fn config(&self) -> &CompressionConfig {
+        &self.config
+    }
+
+    /// Record whether `peer` reads zstd.
+    pub fn set_capable(&self, peer: PeerId, capable: bool) {
+        let mut peers = self.write();
+        if capable {
+            peers.zstd.insert(peer);
+        } else {
+            peers.zstd.remove(&peer);
+        }
+    }
+
+    pub fn is_capable(&self, peer: &PeerId) -> bool {
+        self.read().zstd.contains(peer)
+    }
+
+    pub fn remove_peer(&self, peer: &PeerId) {
+        self.write().zstd.remove(peer);
+    }
+
+    /// Decide whether to compress `topic` given the peers a publish will
+    /// reach. An empty topic stays uncompressed: there is no one to ask.
+    pub fn refresh_topic<'a>(
+        &self,
+        topic: &TopicHash,
+        subscribers: impl IntoIterator<Item = &'a PeerId>,
+    ) {
+        let mut peers = self.write();
+        let mut subscribers = subscribers.into_iter().peekable();
+        let compress =
+            subscribers.peek().is_some() && subscribers.all(|peer| peers.zstd.contains(peer));
+        if compress {
+            peers.compressed_topics.insert(topic.clone());
+        } else {
+            peers.compressed_topics.remove(topic);
+        }
+    }
+
+    pub fn compresses(&self, topic: &TopicHash) -> bool {
+        self.read().compressed_topics.contains(topic)
+    }
+
+    fn decompress(&self, topic: &TopicHash, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        if !data.starts_with(&ZSTD_MAGIC) {
+            return Ok(data);
+        }
+        let limit = (self.max_size)(topic.as_str());
+        let mut out = Vec::with_capacity(data.len().saturating_mul(4).min(limit));
+        let decoded = zstd::stream::read::Decoder::new(data.as_slice())
+            .and_then(|decoder| decoder.take(limit as u64 + 1).read_to_end(&mut out));
+        match decoded {
+            Ok(_) if out.len() > limit => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("decompressed message exceeds {limit} bytes"),
+            )),
+            Ok(_) => Ok(out),
+            // Not a zstd frame after all: hand it on and let the topic's
+            // decoder judge it.
+            Err(_) => Ok(data),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, PeerCapabilities> {
+        self.peers.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, PeerCapabilities> {
+        self.peers.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl DataTransform for GossipCompression {
+    fn inbound_transform(&self, raw: RawMessage) -> Result<Message, io::Error> {
+        let data = self.decompress(&raw.topic, raw.data)?;
+        Ok(Message {
+            source: raw.source,
+            data,
+            sequence_number: raw.sequence_number,
+            topic: raw.topic,
+        })
+    }
+
+    fn outbound_transform(&self, topic: &TopicHash, data: Vec<u8>) -> Result<Vec<u8>, io::Error> {
+        if data.len() < self.config.threshold || !self.compresses(topic) {
+            return Ok(data);
+        }
+        let compressed = zstd::encode_all(data.as_slice(), self.config.level)?;
+        if compressed.len() >= data.len() {
+            return Ok(data);
+        }
+        let label = topic_label(topic.as_str());
+        P2P_METRICS
+            .messages_compressed
+            .with_label_values(&[label])
+            .inc();
+        P2P_METRICS
+            .compression_bytes_saved
+            .with_label_values(&[label])
+            .inc_by((data.len() - compressed.len()) as u64);
+        Ok(compressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(topic: &str) -> usize {
+        match topic {
+            "small" => 1024,
+            _ => 1 << 20,
+        }
+    }
+
+    fn raw(topic: &str, data: Vec<u8>) -> RawMessage {
+        RawMessage {
+            source: None,
+            data,
+            sequence_number: None,
+            topic: TopicHash::from_raw(topic),
+            signature: None,
+            key: None,
+            validated: false,
+        }
+    }
+
+    fn shred_like(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i / 64) as u8).collect()
+    }
+
+    #[test]
+    fn compresses_only_when_every_subscriber_can_read_it() {
+        let compression = GossipCompression::new(CompressionConfig::default(), limit);
+        let topic = TopicHash::from_raw("shred");
+        let (new, old) = (PeerId::random(), PeerId::random());
+        compression.set_capable(new, true);
+        let payload = shred_like(170 * 1024);
+
+        compression.refresh_topic(&topic, [&new, &old]);
+        assert!(!compression.compresses(&topic));
+        let sent = compression
+            .outbound_transform(&topic, payload.clone())
+            .unwrap();
+        assert_eq!(sent, payload);
+
+        compression.refresh_topic(&topic, [&new]);
+        let sent = compression
+            .outbound_transform(&topic, payload.clone())
+            .unwrap();
+        assert!(sent.len() * 4 < payload.len());
+        let received = compression.inbound_transform(raw("shred", sent)).unwrap();
+        assert_eq!(received.data, payload);
+
+        // Small payloads are not worth it.
+        let small = vec![7u8; 100];
+        let sent = compression
+            .outbound_transform(&topic, small.clone())
+            .unwrap();
+        assert_eq!(sent, small);
+
+        compression.remove_peer(&new);
+        compression.refresh_topic(&topic, [&new]);
+        assert!(!compression.compresses(&topic));
+        compression.refresh_topic(&topic, []);
+        assert!(!compression.compresses(&topic));
+    }
+
+    #[test]
+    fn incompressible_payloads_are_sent_as_is() {
+        let compression = GossipCompression::new(CompressionConfig::default(), limit);
+        let topic = TopicHash::from_raw("tx");
+        let peer = PeerId::random();
+        compression.set_capable(peer, true);
+        compression.refresh_topic(&topic, [&peer]);
+
+        let mut seed = 0x9e37_79b9_7f4a_7c15u64;
+        let noise: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect();
+        assert_eq!(
+            compression
+                .outbound_transform(&topic, noise.clone())
+                .unwrap(),
+            noise
+        );
+    }
+
+    #[test]
+    fn decompression_stops_at_topic_limit() {
+        let compression = GossipCompression::new(CompressionConfig::default(), limit);
+        let bomb = zstd::encode_all(vec![0u8; 64 * 1024].as_slice(), 3).unwrap();
+        assert!(bomb.len() < 1024);
+        assert!(compression
+            .inbound_transform(raw("small", bomb.clone()))
+            .is_err());
+        assert_eq!(
+            compression
+                .inbound_transform(raw("big", bomb))
+                .unwrap()
+                .data
+                .len(),
+            64 * 1024
+        );
+
+        // Uncompressed payloads, and ones that only look compressed, pass
+        // through untouched.
+        let plain = b"plain bincode".to_vec();
+        assert_eq!(
+            compression
+                .inbound_transform(raw("small", plain.clone()))
+                .unwrap()
+                .data,
+            plain
+        );
+        let mut fake = ZSTD_MAGIC.to_vec();
+        fake.extend_from_slice(b"garbage");
+        assert_eq!(
+            compression
+                .inbound_transform(raw("small", fake.clone()))
+                .unwrap()
+                .data,
+            fake
+        );
+    }
+
+    #[test]
+    fn capability_is_read_from_agent_version() {
+        assert!(advertises_zstd("aether-p2p/0.1.0 zstd/1"));
+        assert!(advertises_zstd("aether-p2p/0.1.0 zstd/1 stake-proof/abcd"));
+        assert!(!advertises_zstd("aether-p2p/0.1.0"));
+        assert!(!advertises_zstd("aether-p2p/0.1.0 zstd/10"));
+    }
+}
@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+
+use aether_crypto_primitives::ed25519;
+use aether_crypto_primitives::signer::Signer;
+use anyhow::{bail, Context, Result};
+use libp2p::kad::{KBucketKey, RecordKey};
+use libp2p::{Multiaddr, PeerId};
+use serde::{Deserialize, Serialize};
+
+use crate::stake_admission::StakeTable;
+
+const RECORD_DOMAIN: &[u8] = b"aether-p2p-validator-record-v1";
+
+/// DHT keys of validator records start with this, followed by the peer ID.
+const RECORD_KEY_PREFIX: &[u8] = b"/aether/1/validator/";
+
+/// Addresses a record may list.
+pub const MAX_RECORD_ADDRESSES: usize = 8;
+
+/// Largest encoded record accepted from the DHT.
+pub const MAX_RECORD_SIZE: usize = 2048;
+
+/// Records kept at once; one per validator peer.
+const MAX_RECORDS: usize = 4096;
+
+/// What a peer offers the network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PeerRole {
+    BlockProducer,
+    AiWorker,
+    Rpc,
+}
+
+/// Where to reach a validator and what it does, signed by its validator
+/// key and published in the Kademlia DHT under its peer ID.
+///
+/// The stake epoch ties the record to a validator set: records from
+/// validators without stake, or from more than an epoch ago, are ignored,
+/// so a departed validator's addresses age out on their own.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorRecord {
+    pub validator: [u8; 32],
+    pub peer_id: PeerId,
+    pub addresses: Vec<Multiaddr>,
+    pub roles: Vec<PeerRole>,
+    pub stake_epoch: u64,
+    pub signature: Vec<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct WireRecord {
+    validator: [u8; 32],
+    peer_id: Vec<u8>,
+    addresses: Vec<Vec<u8>>,
+    roles: Vec<PeerRole>,
+    stake_epoch: u64,
+    signature: Vec<u8>,
+}
+
+impl ValidatorRecord {
+    pub fn sign<S: Signer + ?Sized>(
+        signer: &S,
+        peer_id: PeerId,
+        addresses: Vec<Multiaddr>,
+        roles: Vec<PeerRole>,
+        stake_epoch: u64,
+    ) -> Result<Self> {
+        let validator = signer
+            .public_key()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("validator key is not 32 bytes"))?;
+        let mut record = ValidatorRecord {
+            validator,
+            peer_id,
+            addresses,
+            roles,
+            stake_epoch,
+            signature: Vec::new(),
+        };
+        record.addresses.truncate(MAX_RECORD_ADDRESSES);
+        record.signature = signer.sign(&record.signing_payload())?;
+        Ok(record)
+    }
+
+    pub fn verify(&self) -> bool {
+        ed25519::verify(&self.validator, &self.signing_payload(), &self.signature).is_ok()
+    }
+
+    pub fn has_role(&self, role: PeerRole) -> bool {
+        self.roles.contains(&role)
+    }
+
+    /// The DHT key this record is stored under.
+    pub fn key(&self) -> RecordKey {
+        record_key(&self.peer_id)
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        bincode::serialize(&self.wire(self.signature.clone()))
+            .expect("validator record serialization cannot fail")
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() > MAX_RECORD_SIZE {
+            bail!("record is {} bytes (max {MAX_RECORD_SIZE})", bytes.len());
+        }
+        let wire: WireRecord = bincode::deserialize(bytes).context("malformed record")?;
+        if wire.addresses.len() > MAX_RECORD_ADDRESSES {
+            bail!("record lists {} addresses", wire.addresses.len());
+        }
+        let addresses = wire
+            .addresses
+            .into_iter()
+            .map(Multiaddr::try_from)
+            .collect::<Result<_, _>>()
+            .context("malformed address")?;
+        Ok(ValidatorRecord {
+            validator: wire.validator,
+            peer_id: PeerId::from_bytes(&wire.peer_id).context("malformed peer ID")?,
+            addresses,
+            roles: wire.roles,
+            stake_epoch: wire.stake_epoch,
+            signature: wire.signature,
+        })
+    }
+
+    fn wire(&self, signature: Vec<u8>) -> WireRecord {
+        WireRecord {
+            validator: self.validator,
+            peer_id: self.peer_id.to_bytes(),
+            addresses: self.addresses.iter().map(Multiaddr::to_vec).collect(),
+            roles: self.roles.clone(),
+            stake_epoch: self.stake_epoch,
+            signature,
+        }
+    }
+
+    fn signing_payload(&self) -> Vec<u8> {
+        let body = bincode::serialize(&self.wire(Vec::new()))
+            .expect("validator record serialization cannot fail");
+        let mut payload = Vec::with_capacity(RECORD_DOMAIN.len() + body.len());
+        payload.extend_from_slice(RECORD_DOMAIN);
+        payload.extend_from_slice(&body);
+        payload
+    }
+}
+
+pub fn record_key(peer_id: &PeerId) -> RecordKey {
+    let mut key = RECORD_KEY_PREFIX.to_vec();
+    key.extend_from_slice(&peer_id.to_bytes());
+    RecordKey::new(&key)
+}
+
+/// Whether `key` names a validator record, rather than something else
+/// peers might try to store in our DHT.
+pub fn is_record_key(key: &RecordKey) -> bool {
+    key.as_ref().starts_with(RECORD_KEY_PREFIX)
+}
+
+/// Decode the record stored under `key` and check it deserves to be
+/// believed: signed by its validator, stored under its own peer ID,
+/// current, and from a validator that has stake.
+pub fn validate_record(
+    key: &RecordKey,
+    value: &[u8],
+    table: &StakeTable,
+) -> Result<ValidatorRecord> {
+    let record = ValidatorRecord::decode(value)?;
+    if record.key() != *key {
+        bail!("record for {} stored under another key", record.peer_id);
+    }
+    if !record.verify() {
+        bail!("record signature does not verify");
+    }
+    let epoch = table.epoch();
+    if record.stake_epoch + 1 < epoch || record.stake_epoch > epoch + 1 {
+        bail!(
+            "record is for epoch {}, current epoch is {epoch}",
+            record.stake_epoch
+        );
+    }
+    if table.stake_of(&record.validator) == 0 {
+        bail!("record is from a validator without stake");
+    }
+    Ok(record)
+}
+
+/// Validator records learned from the DHT.
+#[derive(Default)]
+pub struct ValidatorDirectory {
+    records: HashMap<PeerId, ValidatorRecord>,
+}
+
+impl ValidatorDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep `record` unless we already hold a newer one for its peer.
+    pub fn insert(&mut self, record: ValidatorRecord) {
+        if let Some(existing) = self.records.get(&record.peer_id) {
+            if existing.stake_epoch > record.stake_epoch {
+                return;
+            }
+        }
+        if self.records.len() >= MAX_RECORDS && !self.records.contains_key(&record.peer_id) {
+            let oldest = self
+                .records
+                .values()
+                .min_by_key(|r| r.stake_epoch)
+                .map(|r| r.peer_id);
+            if let Some(oldest) = oldest {
+                self.records.remove(&oldest);
+            }
+        }
+        self.records.insert(record.peer_id, record);
+    }
+
+    pub fn get(&self, peer_id: &PeerId) -> Option<&ValidatorRecord> {
+        self.records.get(peer_id)
+    }
+
+    /// Newest record published by `validator`'s key.
+    pub fn by_validator(&self, validator: &[u8; 32]) -> Option<&ValidatorRecord> {
+        self.records
+            .values()
+            .filter(|r| r.validator == *validator)
+            .max_by_key(|r| r.stake_epoch)
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Drop records that `table` no longer vouches for: stale epochs and
+    /// validators that have left the set.
+    pub fn retain_current(&mut self, table: &StakeTable) {
+        let epoch = table.epoch();
+        self.records
+            .retain(|_, r| r.stake_epoch + 1 >= epoch && table.stake_of(&r.validator) > 0);
+    }
+
+    /// Up to `n` records offering `role`, nearest to `local` in the
+    /// Kademlia XOR metric. Each node gets a different neighbourhood, so
+    /// connections spread over the validator set instead of piling onto
+    /// the same few peers.
+    pub fn nearest(&self, local: &PeerId, role: PeerRole, n: usize) -> Vec<&ValidatorRecord> {
+        let local = KBucketKey::from(*local);
+        let mut records: Vec<&ValidatorRecord> = self
+            .records
+            .values()
+            .filter(|r| r.has_role(role) && r.peer_id != *local.preimage())
+            .collect();
+        records.sort_by_key(|r| local.distance(&KBucketKey::from(r.peer_id)));
+        records.truncate(n);
+        records
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_primitives::Keypair;
+
+    fn validator(table: &mut Vec<([u8; 32], u128)>) -> Keypair {
+        let keypair = Keypair::generate();
+        table.push((keypair.public_key().try_into().unwrap(), 100));
+        keypair
+    }
+
+    fn record(keypair: &Keypair, roles: Vec<PeerRole>, epoch: u64) -> ValidatorRecord {
+        ValidatorRecord::sign(
+            keypair,
+            PeerId::random(),
+            vec!["/ip4/81.2.69.160/tcp/9000".parse().unwrap()],
+            roles,
+            epoch,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn record_roundtrips_and_validates() {
+        let mut stakes = Vec::new();
+        let keypair = validator(&mut stakes);
+        let table = StakeTable::new(stakes).at_epoch(7);
+        let record = record(&keypair, vec![PeerRole::BlockProducer], 7);
+
+        let encoded = record.encode();
+        assert_eq!(ValidatorRecord::decode(&encoded).unwrap(), record);
+        assert!(is_record_key(&record.key()));
+        assert_eq!(
+            validate_record(&record.key(), &encoded, &table).unwrap(),
+            record
+        );
+
+        // Stored under someone else's peer ID.
+        assert!(validate_record(&record_key(&PeerId::random()), &encoded, &table).is_err());
+
+        // Tampered addresses.
+        let mut forged = record.clone();
+        forged.addresses = vec!["/ip4/8.8.8.8/tcp/1".parse().unwrap()];
+        assert!(validate_record(&forged.key(), &forged.encode(), &table).is_err());
+
+        // Too old, or from outside the validator set.
+        let stale = self::record(&keypair, vec![], 5);
+        assert!(validate_record(&stale.key(), &stale.encode(), &table).is_err());
+        let outsider = self::record(&Keypair::generate(), vec![], 7);
+        assert!(validate_record(&outsider.key(), &outsider.encode(), &table).is_err());
+
+        assert!(ValidatorRecord::decode(&vec![0u8; MAX_RECORD_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn lookup_by_validator_prefers_newest_record() {
+        let mut stakes = Vec::new();
+        let keypair = validator(&mut stakes);
+        let mut directory = ValidatorDirectory::new();
+        directory.insert(record(&keypair, vec![PeerRole::BlockProducer], 3));
+        let newer = record(&keypair, vec![PeerRole::BlockProducer], 4);
+        directory.insert(newer.clone());
+        directory.insert(record(&Keypair::generate(), vec![], 4));
+
+        let key: [u8; 32] = keypair.public_key().try_into().unwrap();
+        assert_eq!(directory.by_validator(&key), Some(&newer));
+        assert!(directory.by_validator(&[0u8; 32]).is_none());
+    }
+
+    #[test]
+    fn nearest_block_producers_by_xor_distance() {
+        let mut stakes = Vec::new();
+        let keypairs: Vec<Keypair> = (0..6).map(|_| validator(&mut stakes)).collect();
+        let mut directory = ValidatorDirectory::new();
+        for (i, keypair) in keypairs.iter().enumerate() {
+            let roles = if i % 3 == 0 {
+                vec![PeerRole::AiWorker]
+            } else {
+                vec![PeerRole::BlockProducer, PeerRole::Rpc]
+            };
+            directory.insert(record(keypair, roles, 3));
+        }
+
+        let local = PeerId::random();
+        let nearest = directory.nearest(&local, PeerRole::BlockProducer, 3);
+        assert_eq!(nearest.len(), 3);
+        assert!(nearest.iter().all(|r| r.has_role(PeerRole::BlockProducer)));
+        let local_key = KBucketKey::from(local);
+        let distances: Vec<_> = nearest
+            .iter()
+            .map(|r| local_key.distance(&KBucketKey::from(r.peer_id)))
+            .collect();
+        assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(
+            directory.nearest(&local, PeerRole::BlockProducer, 10).len(),
+            4
+        );
+
+        // A newer epoch replaces the record; an older one does not.
+        let peer = nearest[0].peer_id;
+        let mut newer = directory.get(&peer).unwrap().clone();
+        newer.stake_epoch = 4;
+        directory.insert(newer);
+        let mut older = directory.get(&peer).unwrap().clone();
+        older.stake_epoch = 2;
+        directory.insert(older);
+        assert_eq!(directory.get(&peer).unwrap().stake_epoch, 4);
+
+        directory.retain_current(&StakeTable::new(stakes).at_epoch(5));
+        assert_eq!(directory.len(), 1);
+    }
+}
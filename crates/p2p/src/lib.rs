@@ -6,7 +6,9 @@
 // ARCHITECTURE:
 // - libp2p for networking stack
 // - Gossipsub for pub/sub messaging
-// - Kademlia DHT for peer discovery
+// - Kademlia DHT for peer discovery: validators publish signed records
+//   (addresses, roles, stake epoch) and nodes dial the nearest block
+//   producers rather than relying on bootstrap peers alone
 // - QUIC for transport (low latency, multiplexing)
 // - Noise protocol for encryption
 //
@@ -17,15 +19,30 @@
 // - /aether/shred: Data availability shreds
 //
 // PEER MANAGEMENT:
-// - Scoring system (reputation)
-// - Ban misbehaving peers
-// - Connection limits
-// - NAT traversal
+// - Scoring system (reputation): decaying scores, weighted penalties per
+//   violation (invalid shred, late vote, spam, oversized)
+// - Greylist low-scoring peers, ban and disconnect the worst; bans persist
+//   across restarts via `load_ban_list`
+// - Connection limits, with stake-weighted inbound quotas: validators
+//   prove their stake in the identify handshake and get reserved slots and
+//   bandwidth; unstaked peers share the rest
+// - NAT traversal: UPnP port mapping, AutoNAT dial-back probes and the
+//   addresses peers observe us at decide reachability; private nodes listen
+//   through circuit relays on public peers (every node serves as one) and
+//   upgrade relayed connections by DCUtR hole punching
 //
 // MESSAGE FLOW:
-// 1. Local node publishes to topic
-// 2. Gossipsub forwards to subscribed peers
-// 3. Peers validate and re-broadcast
+// 1. Local node publishes to topic. Under congestion a token-bucket
+//    scheduler releases votes before blocks before shreds before txs, with
+//    per-class rate caps; anything queued too long goes next regardless, so
+//    low-priority traffic is never starved. Payloads over 1 KB are
+//    zstd-compressed when every subscriber advertised support in its
+//    identify handshake
+// 2. Gossipsub forwards to subscribed peers, which decompress no further
+//    than the topic's size limit
+// 3. Peers validate and re-broadcast: each topic's validator (tx
+//    pre-check, vote signature, shred leader signature) runs on a bounded
+//    worker pool, and gossipsub forwards only what it accepts
 // 4. Deduplication prevents loops
 // 5. Handler processes new messages
 //
@@ -36,13 +53,26 @@
 // ============================================================================
 
 pub mod compact_block;
+pub mod compression;
 pub mod dandelion;
+pub mod discovery;
 pub mod gossip;
+pub mod nat;
 pub mod network;
 pub mod peer_diversity;
+pub mod scheduler;
+pub mod stake_admission;
+pub mod validation;
 
+pub use aether_gossipsub::{Standing, Violation};
 pub use compact_block::{compress_message, decompress_message, CompactBlock};
+pub use compression::CompressionConfig;
+pub use discovery::{PeerRole, ValidatorRecord};
 pub use gossip::GossipManager;
 pub use libp2p::PeerId;
+pub use nat::NatStatus;
 pub use network::{P2PNetwork, PeerInfo};
 pub use peer_diversity::PeerDiversityGuard;
+pub use scheduler::{ClassLimit, SchedulerConfig, TrafficClass};
+pub use stake_admission::{AdmissionConfig, PeerClass, StakeAdmission, StakeProof, StakeTable};
+pub use validation::{TopicValidator, ValidationConfig, Verdict};
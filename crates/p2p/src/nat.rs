@@ -0,0 +1,392 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::net::IpAddr;
+
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+
+/// Distinct peers that must report the same address before we believe it.
+pub const DEFAULT_CONFIRMATIONS: usize = 3;
+
+/// Candidate addresses tracked at once; peers can report anything.
+const MAX_CANDIDATES: usize = 64;
+
+/// Relays we hold reservations on while private, so one going away does
+/// not cut us off.
+pub const MAX_RELAYS: usize = 2;
+
+/// Whether other nodes can dial us.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NatStatus {
+    /// Not enough evidence yet.
+    Unknown,
+    /// Reachable: a gateway mapped a port for us, a peer on a public
+    /// address dialed in, or AutoNAT dial-backs succeeded.
+    Public,
+    /// Behind a NAT that rewrites our port and would not map one.
+    Private,
+}
+
+impl NatStatus {
+    /// Value of the `aether_p2p_nat_status` gauge.
+    pub fn gauge(self) -> i64 {
+        match self {
+            NatStatus::Unknown => 0,
+            NatStatus::Public => 1,
+            NatStatus::Private => 2,
+        }
+    }
+}
+
+/// Works out our external address and reachability from what the network
+/// tells us, and picks the relays to listen through while private.
+///
+/// Peers report the address they see us at in every identify exchange. An
+/// address reported by `confirmations` distinct peers is taken as our
+/// external one. If its port is not one we listen on, a NAT is rewriting
+/// it and nobody can dial us unless a UPnP mapping succeeds; an inbound
+/// connection from a public address proves otherwise. AutoNAT's dial-back
+/// probes, once confident, settle the question either way.
+///
+/// While private we reserve a slot on up to [`MAX_RELAYS`] public peers
+/// that run a circuit relay, so others can reach us through them and then
+/// upgrade to a direct connection by hole punching.
+pub struct Reachability {
+    confirmations: usize,
+    listen_ports: HashSet<u16>,
+    observations: HashMap<Multiaddr, HashSet<PeerId>>,
+    order: VecDeque<Multiaddr>,
+    external: Option<Multiaddr>,
+    port_mapped: bool,
+    mapping_unavailable: bool,
+    dialed_from_public: bool,
+    /// AutoNAT's verdict: `Some(true)` if peers could dial us back.
+    probed: Option<bool>,
+    /// Public relay peers we could listen through.
+    relay_candidates: HashMap<PeerId, Multiaddr>,
+    /// Relays we listen through or are asking to.
+    relays: HashSet<PeerId>,
+}
+
+impl Reachability {
+    pub fn new(confirmations: usize) -> Self {
+        Reachability {
+            confirmations: confirmations.max(1),
+            listen_ports: HashSet::new(),
+            observations: HashMap::new(),
+            order: VecDeque::new(),
+            external: None,
+            port_mapped: false,
+            mapping_unavailable: false,
+            dialed_from_public: false,
+            probed: None,
+            relay_candidates: HashMap::new(),
+            relays: HashSet::new(),
+        }
+    }
+
+    pub fn status(&self) -> NatStatus {
+        if self.port_mapped || self.dialed_from_public || self.probed == Some(true) {
+            NatStatus::Public
+        } else if self.probed == Some(false)
+            || self.external.as_ref().is_some_and(|addr| {
+                self.mapping_unavailable || !self.listen_ports.contains(&port_of(addr).unwrap_or(0))
+            })
+        {
+            NatStatus::Private
+        } else {
+            NatStatus::Unknown
+        }
+    }
+
+    /// Our confirmed external address, if any.
+    pub fn external_address(&self) -> Option<&Multiaddr> {
+        self.external.as_ref()
+    }
+
+    pub fn on_listen_addr(&mut self, addr: &Multiaddr) {
+        // A relayed address carries the relay's port, not ours.
+        if is_relayed(addr) {
+            return;
+        }
+        if let Some(port) = port_of(addr) {
+            self.listen_ports.insert(port);
+        }
+    }
+
+    /// `peer` says it sees us at `addr`. Returns the address the first time
+    /// enough distinct peers agree on it.
+    pub fn on_observed(&mut self, peer: PeerId, addr: Multiaddr) -> Option<Multiaddr> {
+        if !ip_of(&addr).is_some_and(is_public) || self.external.as_ref() == Some(&addr) {
+            return None;
+        }
+        if !self.observations.contains_key(&addr) {
+            if self.order.len() >= MAX_CANDIDATES {
+                if let Some(oldest) = self.order.pop_front() {
+                    self.observations.remove(&oldest);
+                }
+            }
+            self.order.push_back(addr.clone());
+        }
+        let observers = self.observations.entry(addr.clone()).or_default();
+        observers.insert(peer);
+        if observers.len() < self.confirmations {
+            return None;
+        }
+        self.external = Some(addr.clone());
+        Some(addr)
+    }
+
+    /// An inbound connection arrived from `remote`.
+    pub fn on_inbound(&mut self, remote: &Multiaddr) {
+        if ip_of(remote).is_some_and(is_public) {
+            self.dialed_from_public = true;
+        }
+    }
+
+    /// The gateway mapped a port and `addr` is now reachable.
+    pub fn on_port_mapped(&mut self, addr: Multiaddr) {
+        self.port_mapped = true;
+        self.mapping_unavailable = false;
+        self.external = Some(addr);
+    }
+
+    /// There is no usable gateway, or it stopped renewing our mapping.
+    pub fn on_mapping_failed(&mut self) {
+        self.port_mapped = false;
+        self.mapping_unavailable = true;
+    }
+
+    /// AutoNAT's confident verdict changed: `Some(true)` if peers dialed us
+    /// back, `Some(false)` if they could not, `None` if it lost confidence.
+    pub fn on_probed(&mut self, reachable: Option<bool>) {
+        self.probed = reachable;
+    }
+
+    /// `peer` runs a circuit relay and listens on `addrs`.
+    pub fn on_relay_found(&mut self, peer: PeerId, addrs: &[Multiaddr]) {
+        let Some(addr) = addrs
+            .iter()
+            .find(|addr| !is_relayed(addr) && ip_of(addr).is_some_and(is_public))
+        else {
+            return;
+        };
+        if self.relay_candidates.len() >= MAX_CANDIDATES
+            && !self.relay_candidates.contains_key(&peer)
+        {
+            return;
+        }
+        self.relay_candidates.insert(peer, addr.clone());
+    }
+
+    /// The next relay to listen through, as a circuit address, if we are
+    /// private and hold fewer than [`MAX_RELAYS`]. The relay counts as held
+    /// until [`on_relay_closed`](Self::on_relay_closed).
+    pub fn next_relay(&mut self) -> Option<(PeerId, Multiaddr)> {
+        if self.status() != NatStatus::Private || self.relays.len() >= MAX_RELAYS {
+            return None;
+        }
+        let (peer, addr) = self
+            .relay_candidates
+            .iter()
+            .find(|(peer, _)| !self.relays.contains(peer))
+            .map(|(peer, addr)| (*peer, addr.clone()))?;
+        self.relays.insert(peer);
+        let circuit = addr.with(Protocol::P2p(peer)).with(Protocol::P2pCircuit);
+        Some((peer, circuit))
+    }
+
+    /// We stopped listening through `peer`: the reservation failed, expired
+    /// or the relay went away. It is not tried again until it is found anew.
+    pub fn on_relay_closed(&mut self, peer: &PeerId) {
+        self.relays.remove(peer);
+        self.relay_candidates.remove(peer);
+    }
+
+    /// Relays we listen through or are asking to.
+    pub fn relays(&self) -> impl Iterator<Item = &PeerId> {
+        self.relays.iter()
+    }
+}
+
+impl Default for Reachability {
+    fn default() -> Self {
+        Self::new(DEFAULT_CONFIRMATIONS)
+    }
+}
+
+fn ip_of(addr: &Multiaddr) -> Option<IpAddr> {
+    addr.iter().find_map(|protocol| match protocol {
+        Protocol::Ip4(ip) => Some(IpAddr::V4(ip)),
+        Protocol::Ip6(ip) => Some(IpAddr::V6(ip)),
+        _ => None,
+    })
+}
+
+fn is_relayed(addr: &Multiaddr) -> bool {
+    addr.iter().any(|protocol| protocol == Protocol::P2pCircuit)
+}
+
+fn port_of(addr: &Multiaddr) -> Option<u16> {
+    addr.iter().find_map(|protocol| match protocol {
+        Protocol::Tcp(port) | Protocol::Udp(port) => Some(port),
+        _ => None,
+    })
+}
+
+/// Whether `ip` could be reached from the internet at large.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                // 100.64.0.0/10, carrier-grade NAT
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                // fc00::/7 unique local, fe80::/10 link local
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> Multiaddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn external_address_needs_distinct_confirmations() {
+        let mut reachability = Reachability::new(2);
+        reachability.on_listen_addr(&addr("/ip4/0.0.0.0/tcp/9000"));
+        let seen = addr("/ip4/93.184.216.34/tcp/9000");
+        let peer = PeerId::random();
+
+        assert_eq!(reachability.on_observed(peer, seen.clone()), None);
+        assert_eq!(reachability.on_observed(peer, seen.clone()), None);
+        assert_eq!(
+            reachability.on_observed(PeerId::random(), seen.clone()),
+            Some(seen.clone())
+        );
+        assert_eq!(
+            reachability.on_observed(PeerId::random(), seen.clone()),
+            None
+        );
+        assert_eq!(reachability.external_address(), Some(&seen));
+        // The port survived, so this could be a public host.
+        assert_eq!(reachability.status(), NatStatus::Unknown);
+
+        // Private addresses say nothing about the outside view.
+        for _ in 0..3 {
+            assert_eq!(
+                reachability.on_observed(PeerId::random(), addr("/ip4/192.168.1.4/tcp/9000")),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn rewritten_port_means_private_until_mapped() {
+        let mut reachability = Reachability::new(1);
+        reachability.on_listen_addr(&addr("/ip4/0.0.0.0/tcp/9000"));
+        reachability.on_observed(PeerId::random(), addr("/ip4/81.2.69.160/tcp/51234"));
+        assert_eq!(reachability.status(), NatStatus::Private);
+
+        let mapped = addr("/ip4/81.2.69.160/tcp/9000");
+        reachability.on_port_mapped(mapped.clone());
+        assert_eq!(reachability.status(), NatStatus::Public);
+        assert_eq!(reachability.external_address(), Some(&mapped));
+
+        reachability.on_mapping_failed();
+        assert_eq!(reachability.status(), NatStatus::Private);
+        reachability.on_inbound(&addr("/ip4/10.0.0.5/tcp/4000"));
+        assert_eq!(reachability.status(), NatStatus::Private);
+        reachability.on_inbound(&addr("/ip4/8.8.4.4/tcp/4000"));
+        assert_eq!(reachability.status(), NatStatus::Public);
+    }
+
+    #[test]
+    fn autonat_verdict_overrides_the_port_heuristic() {
+        let mut reachability = Reachability::new(1);
+        reachability.on_listen_addr(&addr("/ip4/0.0.0.0/tcp/9000"));
+        reachability.on_observed(PeerId::random(), addr("/ip4/81.2.69.160/tcp/9000"));
+        assert_eq!(reachability.status(), NatStatus::Unknown);
+
+        reachability.on_probed(Some(false));
+        assert_eq!(reachability.status(), NatStatus::Private);
+        reachability.on_probed(Some(true));
+        assert_eq!(reachability.status(), NatStatus::Public);
+        reachability.on_probed(None);
+        assert_eq!(reachability.status(), NatStatus::Unknown);
+    }
+
+    #[test]
+    fn private_nodes_listen_through_public_relays() {
+        let mut reachability = Reachability::new(1);
+        let relay = PeerId::random();
+        reachability.on_relay_found(PeerId::random(), &[addr("/ip4/10.0.0.7/tcp/9000")]);
+        reachability.on_relay_found(
+            relay,
+            &[
+                addr("/ip4/127.0.0.1/tcp/9000"),
+                addr("/ip4/93.184.216.34/tcp/9000"),
+            ],
+        );
+        assert_eq!(reachability.next_relay(), None, "not known to be private");
+
+        reachability.on_probed(Some(false));
+        let (peer, circuit) = reachability.next_relay().unwrap();
+        assert_eq!(peer, relay);
+        assert_eq!(
+            circuit,
+            addr(&format!(
+                "/ip4/93.184.216.34/tcp/9000/p2p/{relay}/p2p-circuit"
+            ))
+        );
+        assert_eq!(reachability.next_relay(), None, "private relay is skipped");
+
+        // Listening through the relay says nothing about our own ports.
+        reachability.on_listen_addr(&circuit);
+        assert!(reachability.listen_ports.is_empty());
+
+        for _ in 0..MAX_RELAYS + 1 {
+            reachability.on_relay_found(PeerId::random(), &[addr("/ip4/8.8.8.8/tcp/4001")]);
+        }
+        assert!(reachability.next_relay().is_some());
+        assert_eq!(reachability.next_relay(), None, "holding MAX_RELAYS");
+        reachability.on_relay_closed(&relay);
+        assert!(reachability.next_relay().is_some());
+        assert!(!reachability.relays().any(|peer| *peer == relay));
+    }
+
+    #[test]
+    fn classifies_public_ips() {
+        for ip in ["8.8.8.8", "2001:4860::8888"] {
+            assert!(is_public(ip.parse().unwrap()), "{ip}");
+        }
+        for ip in [
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.0.1",
+            "127.0.0.1",
+            "100.64.3.3",
+            "169.254.0.1",
+            "::1",
+            "fd00::1",
+            "fe80::1",
+        ] {
+            assert!(!is_public(ip.parse().unwrap()), "{ip}");
+        }
+    }
+}
@@ -1,18 +1,35 @@
+use crate::compression::{self, CompressionConfig, GossipCompression, ZSTD_CAPABILITY};
+use crate::discovery::{self, PeerRole, ValidatorDirectory, ValidatorRecord};
+use crate::nat::{NatStatus, Reachability};
+use crate::scheduler::{OutboundScheduler, SchedulerConfig, TrafficClass};
+use crate::stake_admission::{Admission, AdmissionConfig, StakeAdmission, StakeProof, StakeTable};
+use crate::validation::{TopicValidator, Validated, ValidationConfig, ValidationPool, Verdict};
+use aether_crypto_primitives::signer::Signer;
+use aether_gossipsub::{PeerReputation, ReputationConfig, Standing, Violation};
 use aether_metrics::p2p::topic_label;
 use aether_metrics::{NET_METRICS, P2P_METRICS};
 use aether_types::{Block, Transaction};
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use libp2p::connection_limits::{self, ConnectionLimits};
 use libp2p::futures::StreamExt;
 use libp2p::{
-    gossipsub::{self, IdentTopic, MessageAuthenticity, ValidationMode},
+    autonat,
+    core::{transport::ListenerId, ConnectedPoint},
+    dcutr,
+    gossipsub::{
+        self, IdentTopic, MessageAcceptance, MessageAuthenticity, MessageId, ValidationMode,
+    },
     identify,
     identity::Keypair,
-    kad, noise,
-    swarm::{NetworkBehaviour, SwarmEvent},
-    tcp, yamux, Multiaddr, PeerId, Swarm, SwarmBuilder,
+    kad::{self, store::RecordStore},
+    noise, relay,
+    swarm::{dial_opts::DialOpts, NetworkBehaviour, SwarmEvent},
+    tcp, upnp, yamux, Multiaddr, PeerId, Swarm, SwarmBuilder,
 };
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
@@ -22,6 +39,10 @@ pub const TOPIC_BLOCK: &str = "/aether/1/block";
 pub const TOPIC_VOTE: &str = "/aether/1/vote";
 pub const TOPIC_SHRED: &str = "/aether/1/shred";
 pub const TOPIC_SYNC: &str = "/aether/1/sync";
+pub const TOPIC_ROUND: &str = "/aether/1/round";
+/// Trace announcements, challenges and openings between workers and
+/// watchtowers. Only nodes taking part subscribe.
+pub const TOPIC_VCR: &str = "/aether/1/vcr";
 
 /// Per-topic maximum message sizes (bytes).
 /// Transactions are small (~1-2 KB typical, 64 KB generous max).
@@ -35,6 +56,8 @@ const MAX_BLOCK_SIZE: usize = 2 * 1024 * 1024; // 2 MB
 const MAX_VOTE_SIZE: usize = 8 * 1024; // 8 KB
 const MAX_SHRED_SIZE: usize = 256 * 1024; // 256 KB — RS(10,2) on 2 MB block ≈ 210 KB per shred
 const MAX_SYNC_MSG_SIZE: usize = 1024; // 1 KB (slot range requests are small)
+const MAX_ROUND_SYNC_SIZE: usize = 8 * 1024; // 8 KB — a slot, a QC and a signature
+const MAX_VCR_MSG_SIZE: usize = 256 * 1024; // 256 KB — per-layer commitments or openings
 
 /// Maximum total established connections (inbound + outbound).
 const MAX_ESTABLISHED_TOTAL: u32 = 256;
@@ -45,6 +68,9 @@ const MAX_ESTABLISHED_OUTBOUND: u32 = 128;
 /// Maximum established connections per single peer (prevents resource hogging).
 const MAX_ESTABLISHED_PER_PEER: u32 = 4;
 
+/// Identify agent version; validators append their stake proof to it.
+const AGENT_VERSION: &str = concat!("aether-p2p/", env!("CARGO_PKG_VERSION"));
+
 /// Events emitted by the P2P network to the node.
 #[derive(Debug)]
 pub enum NetworkEvent {
@@ -53,6 +79,8 @@ pub enum NetworkEvent {
     VoteReceived(Vec<u8>),
     ShredReceived(Vec<u8>),
     SyncRequestReceived(Vec<u8>),
+    RoundSyncReceived(Vec<u8>),
+    VcrReceived(Vec<u8>),
     PeerConnected(PeerId),
     PeerDisconnected(PeerId),
 }
@@ -60,23 +88,28 @@ pub enum NetworkEvent {
 /// Composite libp2p behaviour for Aether.
 #[derive(NetworkBehaviour)]
 struct AetherBehaviour {
-    gossipsub: gossipsub::Behaviour,
+    gossipsub: gossipsub::Behaviour<GossipCompression>,
     kademlia: kad::Behaviour<kad::store::MemoryStore>,
     identify: identify::Behaviour,
     connection_limits: connection_limits::Behaviour,
+    upnp: upnp::tokio::Behaviour,
+    /// Dial-back probes of our reachability.
+    autonat: autonat::Behaviour,
+    /// Serves circuit relay reservations to private peers.
+    relay: relay::Behaviour,
+    /// Listens through a relay while we are private.
+    relay_client: relay::client::Behaviour,
+    /// Upgrades relayed connections to direct ones by hole punching.
+    dcutr: dcutr::Behaviour,
 }
 
-/// Production P2P network using libp2p.
-/// Ban duration for misbehaving peers (1 hour).
-const BAN_DURATION_SECS: u64 = 3600;
-
-const MAX_BANNED_PEERS: usize = 4096;
-
 const RATE_LIMIT_TOKENS: u32 = 100;
 const RATE_LIMIT_REFILL_INTERVAL: Duration = Duration::from_secs(1);
-const RATE_LIMIT_PENALTY: i32 = -20;
 const MAX_RATE_LIMITERS: usize = 1024;
 
+/// Delivered payloads whose sender is remembered for `report_payload`.
+const MAX_RECENT_SOURCES: usize = 4096;
+
 struct PeerRateLimiter {
     tokens: u32,
     last_refill: Instant,
@@ -108,6 +141,14 @@ impl PeerRateLimiter {
     }
 }
 
+/// A gossip message waiting on its topic validator.
+struct PendingMessage {
+    id: MessageId,
+    source: PeerId,
+    event_fn: fn(Vec<u8>) -> NetworkEvent,
+}
+
+/// Production P2P network using libp2p.
 pub struct P2PNetwork {
     swarm: Swarm<AetherBehaviour>,
     local_peer_id: PeerId,
@@ -115,9 +156,38 @@ pub struct P2PNetwork {
     event_tx: mpsc::Sender<NetworkEvent>,
     event_rx: mpsc::Receiver<NetworkEvent>,
     peers: HashMap<PeerId, PeerInfo>,
-    /// Banned peers with expiry timestamps. Peers cannot reconnect until ban expires.
-    banned_peers: HashMap<PeerId, u64>,
+    /// Peer scores and bans. Banned peers cannot reconnect until the ban
+    /// expires; greylisted ones stay connected but are not listened to.
+    reputation: PeerReputation,
+    /// Where bans are persisted, if anywhere.
+    ban_list_path: Option<PathBuf>,
+    /// Sender of each recently delivered payload, keyed by its hash, so the
+    /// node can blame a peer for a message it rejects after delivery.
+    recent_sources: HashMap<[u8; 32], PeerId>,
+    recent_order: VecDeque<[u8; 32]>,
     rate_limiters: HashMap<PeerId, PeerRateLimiter>,
+    /// Stake-weighted inbound slots and bandwidth quotas.
+    admission: StakeAdmission,
+    /// Inbound peers whose identify, and so whose stake proof, has not
+    /// arrived yet. They are admitted or turned away once it does.
+    pending_inbound: HashSet<PeerId>,
+    /// Our external address and whether peers can dial us.
+    reachability: Reachability,
+    /// Circuit listeners through relays, by the relay they go through.
+    relay_listeners: HashMap<ListenerId, PeerId>,
+    /// Key that signs our stake proof and validator record, if we are a
+    /// validator.
+    validator_signer: Option<Box<dyn Signer>>,
+    /// Validator records found in the DHT.
+    directory: ValidatorDirectory,
+    /// Per-topic validators. Gossipsub holds each message until its
+    /// validator accepts it, so invalid data is never re-gossiped.
+    validation: ValidationPool<PendingMessage>,
+    /// zstd compression of large payloads, shared with gossipsub's data
+    /// transform.
+    compression: GossipCompression,
+    /// Outbound messages waiting for bandwidth, released by priority.
+    outbound: OutboundScheduler<(IdentTopic, Vec<u8>)>,
 }
 
 #[derive(Clone, Debug)]
@@ -131,12 +201,33 @@ pub struct PeerInfo {
 impl P2PNetwork {
     /// Create a new P2P network with a random keypair.
     pub fn new(keypair: Keypair) -> Result<Self> {
+        Self::build(keypair, None)
+    }
+
+    /// Create a network for a validator, advertising a proof that this
+    /// peer speaks for `validator`'s key so that other nodes admit it to
+    /// their staked connection slots.
+    pub fn new_validator(keypair: Keypair, validator: Box<dyn Signer>) -> Result<Self> {
+        Self::build(keypair, Some(validator))
+    }
+
+    /// [`new_validator`](Self::new_validator) with a random keypair.
+    pub fn new_random_validator(validator: Box<dyn Signer>) -> Result<Self> {
+        Self::new_validator(Keypair::generate_ed25519(), validator)
+    }
+
+    fn build(keypair: Keypair, validator_signer: Option<Box<dyn Signer>>) -> Result<Self> {
         let local_peer_id = PeerId::from(keypair.public());
+        let stake_proof = validator_signer
+            .as_deref()
+            .map(|signer| StakeProof::sign(signer, &local_peer_id))
+            .transpose()?;
 
         // Configure gossipsub
         let gossipsub_config = gossipsub::ConfigBuilder::default()
             .heartbeat_interval(Duration::from_secs(1))
             .validation_mode(ValidationMode::Strict)
+            .validate_messages()
             .mesh_n(8)
             .mesh_n_low(4)
             .mesh_n_high(12)
@@ -147,21 +238,32 @@ impl P2PNetwork {
             .build()
             .map_err(|e| anyhow::anyhow!("gossipsub config error: {}", e))?;
 
-        let gossipsub = gossipsub::Behaviour::new(
+        let compression = GossipCompression::new(CompressionConfig::default(), max_size_for_topic);
+        let gossipsub = gossipsub::Behaviour::new_with_transform(
             MessageAuthenticity::Signed(keypair.clone()),
             gossipsub_config,
+            None,
+            compression.clone(),
         )
         .map_err(|e| anyhow::anyhow!("gossipsub init error: {}", e))?;
 
-        // Configure Kademlia
+        // Configure Kademlia. Peers may only store validator records, and
+        // only once we have checked them.
         let store = kad::store::MemoryStore::new(local_peer_id);
-        let kademlia = kad::Behaviour::new(local_peer_id, store);
-
-        // Configure Identify
-        let identify = identify::Behaviour::new(identify::Config::new(
-            "/aether/1.0.0".to_string(),
-            keypair.public(),
-        ));
+        let mut kad_config = kad::Config::new(kad::PROTOCOL_NAME);
+        kad_config.set_record_filtering(kad::StoreInserts::FilterBoth);
+        let kademlia = kad::Behaviour::with_config(local_peer_id, store, kad_config);
+
+        // Configure Identify, which also carries our capabilities and stake proof
+        let base_version = format!("{AGENT_VERSION} {ZSTD_CAPABILITY}");
+        let agent_version = match &stake_proof {
+            Some(proof) => proof.agent_version(&base_version),
+            None => base_version,
+        };
+        let identify = identify::Behaviour::new(
+            identify::Config::new("/aether/1.0.0".to_string(), keypair.public())
+                .with_agent_version(agent_version),
+        );
 
         let limits = ConnectionLimits::default()
             .with_max_established(Some(MAX_ESTABLISHED_TOTAL))
@@ -169,13 +271,6 @@ impl P2PNetwork {
             .with_max_established_outgoing(Some(MAX_ESTABLISHED_OUTBOUND))
             .with_max_established_per_peer(Some(MAX_ESTABLISHED_PER_PEER));
 
-        let behaviour = AetherBehaviour {
-            gossipsub,
-            kademlia,
-            identify,
-            connection_limits: connection_limits::Behaviour::new(limits),
-        };
-
         let swarm = SwarmBuilder::with_existing_identity(keypair)
             .with_tokio()
             .with_tcp(
@@ -183,7 +278,20 @@ impl P2PNetwork {
                 noise::Config::new,
                 yamux::Config::default,
             )?
-            .with_behaviour(|_| Ok(behaviour))
+            .with_relay_client(noise::Config::new, yamux::Config::default)?
+            .with_behaviour(|_, relay_client| {
+                Ok(AetherBehaviour {
+                    gossipsub,
+                    kademlia,
+                    identify,
+                    connection_limits: connection_limits::Behaviour::new(limits),
+                    upnp: upnp::tokio::Behaviour::default(),
+                    autonat: autonat::Behaviour::new(local_peer_id, autonat::Config::default()),
+                    relay: relay::Behaviour::new(local_peer_id, relay::Config::default()),
+                    relay_client,
+                    dcutr: dcutr::Behaviour::new(local_peer_id),
+                })
+            })
             .map_err(|e| anyhow::anyhow!("swarm build error: {}", e))?
             .with_swarm_config(|c| c.with_idle_connection_timeout(Duration::from_secs(60)))
             .build();
@@ -197,8 +305,23 @@ impl P2PNetwork {
             event_tx,
             event_rx,
             peers: HashMap::new(),
-            banned_peers: HashMap::new(),
+            reputation: PeerReputation::new(ReputationConfig::default()),
+            ban_list_path: None,
+            recent_sources: HashMap::new(),
+            recent_order: VecDeque::new(),
             rate_limiters: HashMap::new(),
+            admission: StakeAdmission::new(AdmissionConfig {
+                max_inbound: MAX_ESTABLISHED_INBOUND as usize,
+                ..AdmissionConfig::default()
+            }),
+            pending_inbound: HashSet::new(),
+            reachability: Reachability::default(),
+            relay_listeners: HashMap::new(),
+            validator_signer,
+            directory: ValidatorDirectory::new(),
+            validation: ValidationPool::new(ValidationConfig::default()),
+            compression,
+            outbound: OutboundScheduler::new(SchedulerConfig::default()),
         })
     }
 
@@ -229,6 +352,7 @@ impl P2PNetwork {
         self.subscribe(TOPIC_VOTE)?;
         self.subscribe(TOPIC_SHRED)?;
         self.subscribe(TOPIC_SYNC)?;
+        self.subscribe(TOPIC_ROUND)?;
 
         Ok(())
     }
@@ -253,6 +377,10 @@ impl P2PNetwork {
     /// Validates outbound message size against per-topic limits before sending.
     /// This prevents our node from broadcasting messages that peers will reject
     /// and penalize us for.
+    ///
+    /// The message then goes through the outbound scheduler: it is sent at
+    /// once if there is bandwidth for it, and otherwise waits behind
+    /// higher-priority traffic until [`poll`](Self::poll) releases it.
     pub fn publish(&mut self, topic_str: &str, data: Vec<u8>) -> Result<()> {
         let topic = self
             .topics
@@ -276,10 +404,70 @@ impl P2PNetwork {
             ));
         }
 
-        self.swarm
-            .behaviour_mut()
-            .gossipsub
-            .publish(topic.clone(), data)
+        let class = TrafficClass::for_topic(topic_str);
+        let queued = self
+            .outbound
+            .push(class, size, (topic.clone(), data), Instant::now());
+        if queued.is_err() {
+            P2P_METRICS
+                .outbound_dropped
+                .with_label_values(&[class.label()])
+                .inc();
+            return Err(anyhow!(
+                "outbound {} queue full, dropping message to {}",
+                class.label(),
+                topic_str
+            ));
+        }
+        P2P_METRICS
+            .outbound_queued
+            .with_label_values(&[class.label()])
+            .inc();
+        self.flush_outbound();
+        Ok(())
+    }
+
+    /// Replace the outbound scheduler's rates and queue limits. Meant for
+    /// startup: messages still queued are dropped.
+    pub fn set_outbound_config(&mut self, config: SchedulerConfig) {
+        for class in TrafficClass::ALL {
+            P2P_METRICS
+                .outbound_queued
+                .with_label_values(&[class.label()])
+                .sub(self.outbound.queued(class) as i64);
+        }
+        self.outbound = OutboundScheduler::new(config);
+    }
+
+    /// Hand gossipsub every queued message the bandwidth budget allows.
+    fn flush_outbound(&mut self) {
+        while let Some((class, (topic, data))) = self.outbound.pop(Instant::now()) {
+            let label = class.label();
+            P2P_METRICS
+                .outbound_queued
+                .with_label_values(&[label])
+                .dec();
+            P2P_METRICS
+                .outbound_bytes
+                .with_label_values(&[label])
+                .inc_by(data.len() as u64);
+            if let Err(e) = self.send_gossip(topic, data) {
+                tracing::debug!(class = label, err = %e, "queued gossip not sent");
+            }
+        }
+    }
+
+    fn send_gossip(&mut self, topic: IdentTopic, data: Vec<u8>) -> Result<()> {
+        let size = data.len();
+        let hash = topic.hash();
+        let gossipsub = &mut self.swarm.behaviour_mut().gossipsub;
+        let subscribers = gossipsub
+            .all_peers()
+            .filter(|(_, topics)| topics.contains(&&hash))
+            .map(|(peer, _)| peer);
+        self.compression.refresh_topic(&hash, subscribers);
+        gossipsub
+            .publish(topic, data)
             .map_err(|e| anyhow::anyhow!("publish error: {}", e))?;
         NET_METRICS.messages_sent.inc();
         NET_METRICS.message_size_bytes.observe(size as f64);
@@ -351,27 +539,56 @@ impl P2PNetwork {
         old_rx
     }
 
-    /// Poll the swarm for events. Call this in a loop from the node.
+    /// Poll the swarm for events. Call this in a loop from the node; it
+    /// also sends outbound messages as bandwidth frees up.
     pub async fn poll(&mut self) -> Option<NetworkEvent> {
         loop {
-            match self.swarm.select_next_some().await {
+            let outbound_ready = self.outbound.next_ready();
+            let event = tokio::select! {
+                event = self.swarm.select_next_some() => event,
+                _ = tokio::time::sleep_until(
+                    outbound_ready.unwrap_or_else(Instant::now).into()
+                ), if outbound_ready.is_some() => {
+                    self.flush_outbound();
+                    continue;
+                }
+                Some(validated) = self.validation.next() => {
+                    match self.on_validated(validated) {
+                        Some(event) => return Some(event),
+                        None => continue,
+                    }
+                }
+            };
+            match event {
                 SwarmEvent::Behaviour(AetherBehaviourEvent::Gossipsub(
                     gossipsub::Event::Message {
                         message,
+                        message_id,
                         propagation_source,
-                        ..
                     },
                 )) => {
                     // Drop messages from banned peers that arrived before disconnect
                     if self.is_banned(&propagation_source) {
                         P2P_METRICS.messages_dropped_banned.inc();
+                        self.settle(&message_id, &propagation_source, MessageAcceptance::Ignore);
                         let _ = self.swarm.disconnect_peer_id(propagation_source);
                         continue;
                     }
 
+                    if self
+                        .reputation
+                        .standing(&propagation_source, current_timestamp())
+                        == Standing::Greylisted
+                    {
+                        P2P_METRICS.messages_dropped_greylisted.inc();
+                        self.settle(&message_id, &propagation_source, MessageAcceptance::Ignore);
+                        continue;
+                    }
+
                     if !self.check_rate_limit(&propagation_source) {
                         P2P_METRICS.messages_dropped_rate_limited.inc();
-                        self.update_peer_score(&propagation_source, RATE_LIMIT_PENALTY);
+                        self.settle(&message_id, &propagation_source, MessageAcceptance::Ignore);
+                        self.report_violation(&propagation_source, Violation::Spam);
                         continue;
                     }
 
@@ -382,20 +599,26 @@ impl P2PNetwork {
                     // Per-topic message size validation.
                     // Uses exact topic matching (not substring) to prevent
                     // misclassification of similarly-named topics.
-                    let (max_size, event_fn): (usize, fn(Vec<u8>) -> NetworkEvent) =
-                        if topic == TOPIC_TX {
-                            (MAX_TX_SIZE, NetworkEvent::TransactionReceived)
-                        } else if topic == TOPIC_BLOCK {
-                            (MAX_BLOCK_SIZE, NetworkEvent::BlockReceived)
-                        } else if topic == TOPIC_VOTE {
-                            (MAX_VOTE_SIZE, NetworkEvent::VoteReceived)
-                        } else if topic == TOPIC_SHRED {
-                            (MAX_SHRED_SIZE, NetworkEvent::ShredReceived)
-                        } else if topic == TOPIC_SYNC {
-                            (MAX_SYNC_MSG_SIZE, NetworkEvent::SyncRequestReceived)
-                        } else {
-                            continue;
-                        };
+                    let (max_size, event_fn): (usize, fn(Vec<u8>) -> NetworkEvent) = if topic
+                        == TOPIC_TX
+                    {
+                        (MAX_TX_SIZE, NetworkEvent::TransactionReceived)
+                    } else if topic == TOPIC_BLOCK {
+                        (MAX_BLOCK_SIZE, NetworkEvent::BlockReceived)
+                    } else if topic == TOPIC_VOTE {
+                        (MAX_VOTE_SIZE, NetworkEvent::VoteReceived)
+                    } else if topic == TOPIC_SHRED {
+                        (MAX_SHRED_SIZE, NetworkEvent::ShredReceived)
+                    } else if topic == TOPIC_SYNC {
+                        (MAX_SYNC_MSG_SIZE, NetworkEvent::SyncRequestReceived)
+                    } else if topic == TOPIC_ROUND {
+                        (MAX_ROUND_SYNC_SIZE, NetworkEvent::RoundSyncReceived)
+                    } else if topic == TOPIC_VCR {
+                        (MAX_VCR_MSG_SIZE, NetworkEvent::VcrReceived)
+                    } else {
+                        self.settle(&message_id, &propagation_source, MessageAcceptance::Ignore);
+                        continue;
+                    };
 
                     let label = topic_label(&topic);
 
@@ -411,51 +634,170 @@ impl P2PNetwork {
                             .messages_dropped_oversized
                             .with_label_values(&[label])
                             .inc();
-                        self.update_peer_score(&propagation_source, -10);
+                        self.settle(&message_id, &propagation_source, MessageAcceptance::Reject);
+                        self.report_violation(&propagation_source, Violation::Oversized);
                         continue;
                     }
 
-                    let event = event_fn(data);
-                    NET_METRICS.messages_received.inc();
-                    NET_METRICS.message_size_bytes.observe(size as f64);
-                    P2P_METRICS
-                        .messages_received_by_topic
-                        .with_label_values(&[label])
-                        .inc();
+                    if !self
+                        .admission
+                        .allow_bytes(&propagation_source, size, Instant::now())
+                    {
+                        let class = self.admission.class(&propagation_source);
+                        P2P_METRICS
+                            .messages_dropped_throttled
+                            .with_label_values(&[class.label()])
+                            .inc();
+                        self.settle(&message_id, &propagation_source, MessageAcceptance::Ignore);
+                        continue;
+                    }
 
-                    return Some(event);
+                    let pending = PendingMessage {
+                        id: message_id,
+                        source: propagation_source,
+                        event_fn,
+                    };
+                    let (pending, data) = match self.validation.submit(&topic, data, pending) {
+                        Ok(()) => continue,
+                        Err(rejected) => rejected,
+                    };
+                    if self.validation.has_validator(&topic) {
+                        tracing::debug!(topic = %topic, "validation queue full, dropping message");
+                        P2P_METRICS
+                            .validation_queue_full
+                            .with_label_values(&[label])
+                            .inc();
+                        self.settle(&pending.id, &pending.source, MessageAcceptance::Ignore);
+                        continue;
+                    }
+                    // No validator for this topic: the checks above are all.
+                    self.settle(&pending.id, &pending.source, MessageAcceptance::Accept);
+                    return Some(self.deliver(&pending, &topic, data));
                 }
-                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
-                    // Check if peer is banned
-                    if let Some(&ban_expiry) = self.banned_peers.get(&peer_id) {
-                        if current_timestamp() < ban_expiry {
-                            // Still banned — disconnect immediately
-                            let _ = self.swarm.disconnect_peer_id(peer_id);
-                            continue;
-                        } else {
-                            // Ban expired — remove from list
-                            self.banned_peers.remove(&peer_id);
+                SwarmEvent::ConnectionEstablished {
+                    peer_id, endpoint, ..
+                } => {
+                    // Still banned — disconnect immediately
+                    if self.is_banned(&peer_id) {
+                        let _ = self.swarm.disconnect_peer_id(peer_id);
+                        continue;
+                    }
+                    if let ConnectedPoint::Listener { send_back_addr, .. } = &endpoint {
+                        self.update_reachability(|r| r.on_inbound(send_back_addr));
+                        if !self.admission.is_admitted(&peer_id) {
+                            self.pending_inbound.insert(peer_id);
                         }
                     }
 
+                    // A reconnecting peer keeps the score it left with.
+                    let now = current_timestamp();
                     let info = PeerInfo {
                         id: peer_id.to_string(),
                         address: String::new(),
-                        score: 0,
-                        connected_at: current_timestamp(),
+                        score: self.reputation.score(&peer_id, now).round() as i32,
+                        connected_at: now,
                     };
                     self.peers.insert(peer_id, info);
                     NET_METRICS.connections_total.inc();
                     NET_METRICS.peers_connected.set(self.peers.len() as i64);
                     return Some(NetworkEvent::PeerConnected(peer_id));
                 }
-                SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                SwarmEvent::ConnectionClosed {
+                    peer_id,
+                    num_established,
+                    ..
+                } => {
                     self.peers.remove(&peer_id);
                     self.rate_limiters.remove(&peer_id);
+                    if num_established == 0 {
+                        self.pending_inbound.remove(&peer_id);
+                        self.admission.remove(&peer_id);
+                        self.compression.remove_peer(&peer_id);
+                        self.update_occupancy_metrics();
+                    }
                     NET_METRICS.peers_connected.set(self.peers.len() as i64);
                     return Some(NetworkEvent::PeerDisconnected(peer_id));
                 }
+                SwarmEvent::Behaviour(AetherBehaviourEvent::Identify(
+                    identify::Event::Received { peer_id, info, .. },
+                )) => {
+                    self.on_identified(peer_id, &info.agent_version);
+                    if info.protocols.contains(&relay::HOP_PROTOCOL_NAME) {
+                        self.update_reachability(|r| r.on_relay_found(peer_id, &info.listen_addrs));
+                    }
+                    if let Some(addr) = self.reachability.on_observed(peer_id, info.observed_addr) {
+                        tracing::info!(%addr, "peers agree on our external address");
+                        self.swarm.add_external_address(addr);
+                        self.update_reachability(|_| {});
+                    }
+                    continue;
+                }
+                SwarmEvent::Behaviour(AetherBehaviourEvent::Autonat(
+                    autonat::Event::StatusChanged { new, .. },
+                )) => {
+                    let reachable = match new {
+                        autonat::NatStatus::Public(_) => Some(true),
+                        autonat::NatStatus::Private => Some(false),
+                        autonat::NatStatus::Unknown => None,
+                    };
+                    self.update_reachability(|r| r.on_probed(reachable));
+                    continue;
+                }
+                SwarmEvent::Behaviour(AetherBehaviourEvent::RelayClient(
+                    relay::client::Event::ReservationReqAccepted {
+                        relay_peer_id,
+                        renewal: false,
+                        ..
+                    },
+                )) => {
+                    tracing::info!(relay = %relay_peer_id, "listening through relay");
+                    continue;
+                }
+                SwarmEvent::Behaviour(AetherBehaviourEvent::Dcutr(event)) => {
+                    match event.result {
+                        Ok(_) => {
+                            tracing::debug!(peer = %event.remote_peer_id, "hole punch succeeded")
+                        }
+                        Err(e) => {
+                            tracing::debug!(peer = %event.remote_peer_id, error = %e, "hole punch failed")
+                        }
+                    }
+                    continue;
+                }
+                SwarmEvent::ListenerClosed { listener_id, .. } => {
+                    if let Some(relay) = self.relay_listeners.remove(&listener_id) {
+                        tracing::info!(%relay, "relay reservation closed");
+                        self.update_reachability(|r| r.on_relay_closed(&relay));
+                    }
+                    continue;
+                }
+                SwarmEvent::Behaviour(AetherBehaviourEvent::Kademlia(event)) => {
+                    self.on_kademlia_event(event);
+                    continue;
+                }
+                SwarmEvent::Behaviour(AetherBehaviourEvent::Upnp(event)) => {
+                    match event {
+                        upnp::Event::NewExternalAddr(addr) => {
+                            tracing::info!(%addr, "UPnP mapped external address");
+                            self.update_reachability(|r| r.on_port_mapped(addr));
+                        }
+                        upnp::Event::ExpiredExternalAddr(addr) => {
+                            tracing::warn!(%addr, "UPnP mapping expired");
+                            self.update_reachability(Reachability::on_mapping_failed);
+                        }
+                        upnp::Event::GatewayNotFound => {
+                            tracing::debug!("no UPnP gateway found");
+                            self.update_reachability(Reachability::on_mapping_failed);
+                        }
+                        upnp::Event::NonRoutableGateway => {
+                            tracing::debug!("UPnP gateway is itself behind a NAT");
+                            self.update_reachability(Reachability::on_mapping_failed);
+                        }
+                    }
+                    continue;
+                }
                 SwarmEvent::NewListenAddr { address, .. } => {
+                    self.reachability.on_listen_addr(&address);
                     tracing::info!("Listening on {}/p2p/{}", address, self.local_peer_id);
                     continue;
                 }
@@ -464,22 +806,440 @@ impl P2PNetwork {
         }
     }
 
-    /// Update a peer's reputation score.
+    /// Check messages on `topic` with `validator` before delivering or
+    /// forwarding them. Runs on the validation worker pool.
+    pub fn set_topic_validator(&mut self, topic: &str, validator: impl TopicValidator + 'static) {
+        self.validation.set_validator(topic, Arc::new(validator));
+    }
+
+    /// Act on a validator's verdict: tell gossipsub whether to forward the
+    /// message, and deliver it or blame its sender.
+    fn on_validated(&mut self, validated: Validated<PendingMessage>) -> Option<NetworkEvent> {
+        let Validated {
+            ticket,
+            topic,
+            data,
+            verdict,
+        } = validated;
+        P2P_METRICS
+            .messages_validated
+            .with_label_values(&[topic_label(&topic), verdict.label()])
+            .inc();
+        match verdict {
+            Verdict::Accept => {
+                self.settle(&ticket.id, &ticket.source, MessageAcceptance::Accept);
+                Some(self.deliver(&ticket, &topic, data))
+            }
+            Verdict::Reject(violation) => {
+                tracing::debug!(
+                    peer = %ticket.source,
+                    topic = %topic,
+                    violation = violation.label(),
+                    "topic validator rejected message"
+                );
+                self.settle(&ticket.id, &ticket.source, MessageAcceptance::Reject);
+                self.report_violation(&ticket.source, violation);
+                None
+            }
+            Verdict::Ignore => {
+                self.settle(&ticket.id, &ticket.source, MessageAcceptance::Ignore);
+                None
+            }
+        }
+    }
+
+    /// Report a validation result to gossipsub, which holds every message
+    /// until it hears one.
+    fn settle(&mut self, id: &MessageId, source: &PeerId, acceptance: MessageAcceptance) {
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .gossipsub
+            .report_message_validation_result(id, source, acceptance)
+        {
+            tracing::debug!(err = %e, "failed to forward validated message");
+        }
+    }
+
+    /// Hand an accepted message to the node.
+    fn deliver(&mut self, pending: &PendingMessage, topic: &str, data: Vec<u8>) -> NetworkEvent {
+        let source = pending.source;
+        let size = data.len();
+        self.remember_source(&data, source);
+        self.rescore(&source, |reputation, now| {
+            reputation.record_valid(&source, now)
+        });
+        NET_METRICS.messages_received.inc();
+        NET_METRICS.message_size_bytes.observe(size as f64);
+        P2P_METRICS
+            .messages_received_by_topic
+            .with_label_values(&[topic_label(topic)])
+            .inc();
+        (pending.event_fn)(data)
+    }
+
+    /// Adjust a peer's reputation score directly, banning it if the score
+    /// falls below the ban threshold.
     pub fn update_peer_score(&mut self, peer_id: &PeerId, delta: i32) {
-        if let Some(info) = self.peers.get_mut(peer_id) {
-            info.score += delta;
-            if info.score < -100 {
-                // Ban for BAN_DURATION_SECS, disconnect
+        self.rescore(peer_id, |reputation, now| {
+            reputation.adjust(peer_id, f64::from(delta), now)
+        });
+    }
+
+    /// Penalize a peer for `violation`, greylisting or banning it once its
+    /// score falls far enough.
+    pub fn report_violation(&mut self, peer_id: &PeerId, violation: Violation) -> Standing {
+        P2P_METRICS
+            .peer_penalties
+            .with_label_values(&[violation.label()])
+            .inc();
+        self.rescore(peer_id, |reputation, now| {
+            reputation.penalize(peer_id, violation, now)
+        })
+    }
+
+    /// Penalize whoever sent `payload`, for problems the node only finds
+    /// after the message was delivered: a shred that fails its proof, a
+    /// vote for a slot already past. `None` if the sender is no longer
+    /// remembered.
+    pub fn report_payload(&mut self, payload: &[u8], violation: Violation) -> Option<Standing> {
+        let peer_id = *self.recent_sources.get(&payload_id(payload))?;
+        Some(self.report_violation(&peer_id, violation))
+    }
+
+    pub fn reputation(&self) -> &PeerReputation {
+        &self.reputation
+    }
+
+    /// Switch to a new epoch's validator stake. Connected peers are
+    /// reclassified; their slots are kept.
+    pub fn set_stake_table(&mut self, table: StakeTable) {
+        self.admission.set_stake_table(table);
+        self.directory.retain_current(self.admission.stake_table());
+        self.update_occupancy_metrics();
+    }
+
+    pub fn admission(&self) -> &StakeAdmission {
+        &self.admission
+    }
+
+    /// Record any stake proof a peer presented and settle its inbound
+    /// slot, evicting or disconnecting as quotas require.
+    fn on_identified(&mut self, peer_id: PeerId, agent_version: &str) {
+        self.compression
+            .set_capable(peer_id, compression::advertises_zstd(agent_version));
+        if let Some(proof) = StakeProof::from_agent_version(agent_version) {
+            if !self.admission.record_proof(&peer_id, &proof) {
+                tracing::warn!(peer = %peer_id, "stake proof does not verify");
+                self.report_violation(&peer_id, Violation::InvalidMessage);
+            }
+        }
+        // Outbound peers were chosen by us and take no inbound slot.
+        if !self.pending_inbound.remove(&peer_id) && !self.admission.is_admitted(&peer_id) {
+            return;
+        }
+        match self.admission.admit_inbound(peer_id) {
+            Admission::Accept => {}
+            Admission::Evict(victim) => {
+                tracing::debug!(peer = %victim, "evicting peer for a staked one");
+                P2P_METRICS.inbound_evicted.inc();
+                let _ = self.swarm.disconnect_peer_id(victim);
+            }
+            Admission::Reject => {
+                let class = self.admission.class(&peer_id);
+                tracing::debug!(peer = %peer_id, class = class.label(), "no inbound slot");
+                P2P_METRICS
+                    .inbound_rejected_by_class
+                    .with_label_values(&[class.label()])
+                    .inc();
+                let _ = self.swarm.disconnect_peer_id(peer_id);
+            }
+        }
+        self.update_occupancy_metrics();
+    }
+
+    /// Sign our validator record for the current stake epoch and publish it
+    /// to the DHT, listing the addresses we can be reached at.
+    pub fn publish_validator_record(&mut self, roles: Vec<PeerRole>) -> Result<()> {
+        let signer = self
+            .validator_signer
+            .as_deref()
+            .ok_or_else(|| anyhow!("not a validator node"))?;
+        let mut addresses: Vec<Multiaddr> = self.swarm.external_addresses().cloned().collect();
+        for addr in self.swarm.listeners() {
+            let unspecified = addr.iter().any(|p| {
+                matches!(p, libp2p::multiaddr::Protocol::Ip4(ip) if ip.is_unspecified())
+                    || matches!(p, libp2p::multiaddr::Protocol::Ip6(ip) if ip.is_unspecified())
+            });
+            if !unspecified && !addresses.contains(addr) {
+                addresses.push(addr.clone());
+            }
+        }
+        if addresses.is_empty() {
+            anyhow::bail!("no address to publish");
+        }
+        let epoch = self.admission.stake_table().epoch();
+        let record = ValidatorRecord::sign(signer, self.local_peer_id, addresses, roles, epoch)?;
+        self.swarm
+            .behaviour_mut()
+            .kademlia
+            .put_record(
+                kad::Record::new(record.key(), record.encode()),
+                kad::Quorum::One,
+            )
+            .map_err(|e| anyhow!("failed to store validator record: {e:?}"))?;
+        Ok(())
+    }
+
+    /// Look for validator records: walk the DHT towards our own peer ID and
+    /// fetch the record of every peer met on the way.
+    pub fn discover_validators(&mut self) {
+        let kademlia = &mut self.swarm.behaviour_mut().kademlia;
+        // No known peers yet just means there is nothing to refresh.
+        let _ = kademlia.bootstrap();
+        kademlia.get_closest_peers(self.local_peer_id);
+    }
+
+    /// Up to `n` known block producers nearest to us in the DHT's metric.
+    pub fn nearest_block_producers(&self, n: usize) -> Vec<&ValidatorRecord> {
+        self.directory
+            .nearest(&self.local_peer_id, PeerRole::BlockProducer, n)
+    }
+
+    /// Dial whichever of the `n` nearest block producers we are not already
+    /// connected to, returning how many dials were started.
+    pub fn connect_block_producers(&mut self, n: usize) -> usize {
+        let targets: Vec<(PeerId, Vec<Multiaddr>)> = self
+            .nearest_block_producers(n)
+            .into_iter()
+            .filter(|r| !self.peers.contains_key(&r.peer_id) && !self.is_banned(&r.peer_id))
+            .map(|r| (r.peer_id, r.addresses.clone()))
+            .collect();
+        self.dial_validators(targets)
+    }
+
+    /// Dial the validators behind `keys` (upcoming leaders, say) that have
+    /// a record in the directory and no connection yet, returning how many
+    /// dials were started.
+    pub fn connect_validators(&mut self, keys: &[[u8; 32]]) -> usize {
+        let targets: Vec<(PeerId, Vec<Multiaddr>)> = keys
+            .iter()
+            .filter_map(|key| self.directory.by_validator(key))
+            .filter(|r| {
+                r.peer_id != self.local_peer_id
+                    && !self.peers.contains_key(&r.peer_id)
+                    && !self.is_banned(&r.peer_id)
+            })
+            .map(|r| (r.peer_id, r.addresses.clone()))
+            .collect();
+        self.dial_validators(targets)
+    }
+
+    fn dial_validators(&mut self, targets: Vec<(PeerId, Vec<Multiaddr>)>) -> usize {
+        let mut dialed = 0;
+        for (peer_id, addresses) in targets {
+            for addr in &addresses {
+                self.swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .add_address(&peer_id, addr.clone());
+            }
+            let opts = DialOpts::peer_id(peer_id).addresses(addresses).build();
+            match self.swarm.dial(opts) {
+                Ok(()) => dialed += 1,
+                Err(e) => {
+                    tracing::debug!(peer = %peer_id, err = %e, "failed to dial validator")
+                }
+            }
+        }
+        dialed
+    }
+
+    pub fn validator_directory(&self) -> &ValidatorDirectory {
+        &self.directory
+    }
+
+    fn on_kademlia_event(&mut self, event: kad::Event) {
+        match event {
+            kad::Event::InboundRequest {
+                request:
+                    kad::InboundRequest::PutRecord {
+                        source,
+                        record: Some(record),
+                        ..
+                    },
+            } => match discovery::validate_record(
+                &record.key,
+                &record.value,
+                self.admission.stake_table(),
+            ) {
+                Ok(validator_record) => {
+                    if let Err(e) = self.swarm.behaviour_mut().kademlia.store_mut().put(record) {
+                        tracing::debug!(err = ?e, "failed to store validator record");
+                    }
+                    self.directory.insert(validator_record);
+                }
+                Err(e) => {
+                    tracing::debug!(peer = %source, err = %e, "rejecting DHT record");
+                    self.report_violation(&source, Violation::InvalidMessage);
+                }
+            },
+            kad::Event::OutboundQueryProgressed {
+                result: kad::QueryResult::GetClosestPeers(Ok(found)),
+                ..
+            } => {
+                let epoch = self.admission.stake_table().epoch();
+                for peer in found.peers {
+                    let peer_id = peer.peer_id;
+                    let current = self
+                        .directory
+                        .get(&peer_id)
+                        .is_some_and(|r| r.stake_epoch >= epoch);
+                    if !current {
+                        self.swarm
+                            .behaviour_mut()
+                            .kademlia
+                            .get_record(discovery::record_key(&peer_id));
+                    }
+                }
+            }
+            kad::Event::OutboundQueryProgressed {
+                result: kad::QueryResult::GetRecord(Ok(kad::GetRecordOk::FoundRecord(found))),
+                ..
+            } => {
+                let record = found.record;
+                match discovery::validate_record(
+                    &record.key,
+                    &record.value,
+                    self.admission.stake_table(),
+                ) {
+                    Ok(validator_record) => {
+                        for addr in &validator_record.addresses {
+                            self.swarm
+                                .behaviour_mut()
+                                .kademlia
+                                .add_address(&validator_record.peer_id, addr.clone());
+                        }
+                        self.directory.insert(validator_record);
+                    }
+                    Err(e) => {
+                        tracing::debug!(err = %e, "ignoring invalid validator record");
+                        if let Some(peer_id) = found.peer {
+                            self.report_violation(&peer_id, Violation::InvalidMessage);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether peers can dial us, as far as we can tell.
+    pub fn nat_status(&self) -> NatStatus {
+        self.reachability.status()
+    }
+
+    /// Our external address, from a UPnP mapping or agreeing peers.
+    pub fn external_address(&self) -> Option<&Multiaddr> {
+        self.reachability.external_address()
+    }
+
+    /// Apply a reachability update, report any change in status, and
+    /// listen through relays while we are private.
+    fn update_reachability(&mut self, update: impl FnOnce(&mut Reachability)) {
+        let before = self.reachability.status();
+        update(&mut self.reachability);
+        let after = self.reachability.status();
+        if before != after {
+            tracing::info!(?before, ?after, "NAT status changed");
+            if after == NatStatus::Private {
+                tracing::warn!(
+                    "this node is behind a NAT and is only reachable through relays; \
+                     forward the P2P port or enable UPnP on the gateway"
+                );
+            }
+            P2P_METRICS.nat_status.set(after.gauge());
+        }
+        while let Some((relay, circuit)) = self.reachability.next_relay() {
+            match self.swarm.listen_on(circuit.clone()) {
+                Ok(listener) => {
+                    self.relay_listeners.insert(listener, relay);
+                }
+                Err(e) => {
+                    tracing::debug!(%circuit, error = %e, "cannot listen through relay");
+                    self.reachability.on_relay_closed(&relay);
+                }
+            }
+        }
+    }
+
+    fn update_occupancy_metrics(&self) {
+        let occupancy = self.admission.occupancy();
+        P2P_METRICS
+            .inbound_peers_by_class
+            .with_label_values(&["staked"])
+            .set(occupancy.staked as i64);
+        P2P_METRICS
+            .inbound_peers_by_class
+            .with_label_values(&["unstaked"])
+            .set(occupancy.unstaked as i64);
+    }
+
+    /// Restore bans saved at `path` and keep it up to date as peers are
+    /// banned, so a restart does not let them straight back in.
+    pub fn load_ban_list(&mut self, path: impl Into<PathBuf>) -> Result<usize> {
+        let path = path.into();
+        let loaded = self.reputation.load_bans(&path, current_timestamp())?;
+        self.ban_list_path = Some(path);
+        Ok(loaded)
+    }
+
+    /// Apply a reputation update and act on any change in standing.
+    fn rescore(
+        &mut self,
+        peer_id: &PeerId,
+        update: impl FnOnce(&mut PeerReputation, u64) -> Standing,
+    ) -> Standing {
+        let now = current_timestamp();
+        let before = self.reputation.standing(peer_id, now);
+        let after = update(&mut self.reputation, now);
+        match (before, after) {
+            (Standing::Banned, _) => {}
+            (_, Standing::Banned) => {
                 P2P_METRICS.peers_banned.inc();
-                let ban_expiry = current_timestamp() + BAN_DURATION_SECS;
-                self.banned_peers.insert(*peer_id, ban_expiry);
                 let _ = self.swarm.disconnect_peer_id(*peer_id);
                 self.peers.remove(peer_id);
                 self.rate_limiters.remove(peer_id);
-                // Prevent unbounded growth of the ban list.
-                if self.banned_peers.len() > MAX_BANNED_PEERS {
-                    self.prune_banned_peers();
-                }
+                self.persist_bans(now);
+            }
+            (Standing::Good, Standing::Greylisted) => {
+                P2P_METRICS.peers_greylisted.inc();
+                tracing::info!(peer = %peer_id, "greylisting peer");
+            }
+            _ => {}
+        }
+        if let Some(info) = self.peers.get_mut(peer_id) {
+            info.score = self.reputation.score(peer_id, now).round() as i32;
+        }
+        after
+    }
+
+    fn persist_bans(&self, now: u64) {
+        if let Some(path) = &self.ban_list_path {
+            if let Err(e) = self.reputation.save_bans(path, now) {
+                tracing::warn!(path = %path.display(), err = %e, "failed to persist ban list");
+            }
+        }
+    }
+
+    fn remember_source(&mut self, payload: &[u8], peer_id: PeerId) {
+        let id = payload_id(payload);
+        if self.recent_sources.insert(id, peer_id).is_none() {
+            self.recent_order.push_back(id);
+        }
+        while self.recent_order.len() > MAX_RECENT_SOURCES {
+            if let Some(old) = self.recent_order.pop_front() {
+                self.recent_sources.remove(&old);
             }
         }
     }
@@ -499,48 +1259,30 @@ impl P2PNetwork {
 
     /// Check if a peer is currently banned.
     pub fn is_banned(&self, peer_id: &PeerId) -> bool {
-        self.banned_peers
-            .get(peer_id)
-            .is_some_and(|&expiry| current_timestamp() < expiry)
+        self.reputation.is_banned(peer_id, current_timestamp())
     }
 
     /// Get count of currently banned peers.
     pub fn banned_count(&self) -> usize {
-        let now = current_timestamp();
-        self.banned_peers
-            .values()
-            .filter(|&&expiry| now < expiry)
-            .count()
+        self.reputation.banned_count(current_timestamp())
     }
+}
 
-    /// Remove expired bans and, if still over capacity, evict oldest bans.
-    fn prune_banned_peers(&mut self) {
-        let now = current_timestamp();
-        // Phase 1: remove all expired bans.
-        self.banned_peers.retain(|_, &mut expiry| expiry > now);
-        // Phase 2: if still over cap, evict soonest-to-expire entries.
-        if self.banned_peers.len() > MAX_BANNED_PEERS {
-            let mut entries: Vec<(PeerId, u64)> =
-                self.banned_peers.iter().map(|(&k, &v)| (k, v)).collect();
-            // Sort by expiry ascending — evict soonest-to-expire first.
-            entries.sort_by_key(|&(_, expiry)| expiry);
-            let to_remove = self.banned_peers.len() - MAX_BANNED_PEERS;
-            for (peer_id, _) in entries.into_iter().take(to_remove) {
-                self.banned_peers.remove(&peer_id);
-            }
-        }
-    }
+fn payload_id(payload: &[u8]) -> [u8; 32] {
+    Sha256::digest(payload).into()
 }
 
 /// Map a topic string to its per-topic maximum message size.
 /// Returns the gossipsub global max (2 MB) for unknown topics as a safe fallback.
-fn max_size_for_topic(topic: &str) -> usize {
+pub(crate) fn max_size_for_topic(topic: &str) -> usize {
     match topic {
         TOPIC_TX => MAX_TX_SIZE,
         TOPIC_BLOCK => MAX_BLOCK_SIZE,
         TOPIC_VOTE => MAX_VOTE_SIZE,
         TOPIC_SHRED => MAX_SHRED_SIZE,
         TOPIC_SYNC => MAX_SYNC_MSG_SIZE,
+        TOPIC_ROUND => MAX_ROUND_SYNC_SIZE,
+        TOPIC_VCR => MAX_VCR_MSG_SIZE,
         _ => MAX_BLOCK_SIZE,
     }
 }
@@ -556,6 +1298,8 @@ fn current_timestamp() -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::scheduler::ClassLimit;
+    use crate::stake_admission::PeerClass;
 
     #[test]
     fn test_network_creation() {
@@ -639,8 +1383,9 @@ mod tests {
             assert_eq!(network.banned_count(), 0);
 
             // Ban the peer
-            let ban_expiry = current_timestamp() + BAN_DURATION_SECS;
-            network.banned_peers.insert(peer_id, ban_expiry);
+            let now = current_timestamp();
+            let ban_expiry = now + network.reputation.config().ban_duration_secs;
+            network.reputation.ban(peer_id, ban_expiry, now);
 
             assert!(network.is_banned(&peer_id));
             assert_eq!(network.banned_count(), 1);
@@ -682,8 +1427,9 @@ mod tests {
             let banned_peer_id = PeerId::random();
 
             // Ban the peer
-            let ban_expiry = current_timestamp() + BAN_DURATION_SECS;
-            network.banned_peers.insert(banned_peer_id, ban_expiry);
+            let now = current_timestamp();
+            let ban_expiry = now + network.reputation.config().ban_duration_secs;
+            network.reputation.ban(banned_peer_id, ban_expiry, now);
 
             // Attempt to dial banned peer — should fail
             let addr = format!("/ip4/127.0.0.1/tcp/9999/p2p/{}", banned_peer_id);
@@ -740,9 +1486,8 @@ mod tests {
             let peer_id = PeerId::random();
 
             // Ban with an already-expired timestamp
-            network
-                .banned_peers
-                .insert(peer_id, current_timestamp() - 1);
+            let now = current_timestamp();
+            network.reputation.ban(peer_id, now - 1, now);
 
             // Should not be considered banned
             assert!(!network.is_banned(&peer_id));
@@ -757,47 +1502,6 @@ mod tests {
         });
     }
 
-    #[test]
-    fn test_prune_banned_peers_removes_expired() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let mut network = P2PNetwork::new_random().unwrap();
-            let now = current_timestamp();
-
-            // Insert some expired and some active bans
-            for _ in 0..10 {
-                network.banned_peers.insert(PeerId::random(), now - 1); // expired
-            }
-            let active_peer = PeerId::random();
-            network.banned_peers.insert(active_peer, now + 3600); // active
-
-            assert_eq!(network.banned_peers.len(), 11);
-            network.prune_banned_peers();
-            // Only the active ban should remain
-            assert_eq!(network.banned_peers.len(), 1);
-            assert!(network.banned_peers.contains_key(&active_peer));
-        });
-    }
-
-    #[test]
-    fn test_prune_banned_peers_evicts_oldest_when_over_cap() {
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        rt.block_on(async {
-            let mut network = P2PNetwork::new_random().unwrap();
-            let now = current_timestamp();
-
-            // Fill beyond MAX_BANNED_PEERS with active bans
-            for i in 0..(MAX_BANNED_PEERS + 100) {
-                network
-                    .banned_peers
-                    .insert(PeerId::random(), now + 3600 + i as u64);
-            }
-            assert_eq!(network.banned_peers.len(), MAX_BANNED_PEERS + 100);
-            network.prune_banned_peers();
-            assert_eq!(network.banned_peers.len(), MAX_BANNED_PEERS);
-        });
-    }
-
     #[test]
     fn test_rate_limiter_allows_up_to_limit() {
         let mut limiter = PeerRateLimiter::new();
@@ -869,10 +1573,11 @@ mod tests {
                 network.check_rate_limit(&peer_id);
             }
             assert!(!network.check_rate_limit(&peer_id));
-            network.update_peer_score(&peer_id, RATE_LIMIT_PENALTY);
+            network.report_violation(&peer_id, Violation::Spam);
 
             let score = network.peers.get(&peer_id).map(|p| p.score).unwrap_or(0);
-            assert_eq!(score, RATE_LIMIT_PENALTY);
+            let penalty = network.reputation.config().weights.spam;
+            assert_eq!(f64::from(score), -penalty);
         });
     }
 
@@ -904,36 +1609,218 @@ mod tests {
         });
     }
 
+    fn connected_peer(network: &mut P2PNetwork) -> PeerId {
+        let peer_id = PeerId::random();
+        network.peers.insert(
+            peer_id,
+            PeerInfo {
+                id: peer_id.to_string(),
+                address: String::new(),
+                score: 0,
+                connected_at: current_timestamp(),
+            },
+        );
+        peer_id
+    }
+
     #[test]
-    fn test_ban_list_bounded_via_update_peer_score() {
+    fn test_greylist_keeps_peer_and_ban_persists() {
         let rt = tokio::runtime::Runtime::new().unwrap();
         rt.block_on(async {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("banned_peers");
             let mut network = P2PNetwork::new_random().unwrap();
+            assert_eq!(network.load_ban_list(&path).unwrap(), 0);
+            let peer_id = connected_peer(&mut network);
 
-            // Pre-fill banned_peers to just at the cap with expired entries
-            let now = current_timestamp();
-            for _ in 0..MAX_BANNED_PEERS {
-                network.banned_peers.insert(PeerId::random(), now - 1);
+            let mut standing = Standing::Good;
+            while standing == Standing::Good {
+                standing = network.report_violation(&peer_id, Violation::Spam);
             }
-            assert_eq!(network.banned_peers.len(), MAX_BANNED_PEERS);
+            assert_eq!(standing, Standing::Greylisted);
+            assert_eq!(network.peer_count(), 1, "greylisted peers stay connected");
+            assert!(!path.exists(), "nothing to persist before a ban");
 
-            // Add a connected peer and trigger a ban via low score
-            let peer_id = PeerId::random();
-            network.peers.insert(
-                peer_id,
-                PeerInfo {
-                    id: peer_id.to_string(),
-                    address: String::new(),
-                    score: 0,
-                    connected_at: now,
-                },
+            while standing != Standing::Banned {
+                standing = network.report_violation(&peer_id, Violation::InvalidShred);
+            }
+            assert_eq!(network.peer_count(), 0);
+
+            // A restarted node still refuses the peer.
+            let mut restarted = P2PNetwork::new_random().unwrap();
+            assert_eq!(restarted.load_ban_list(&path).unwrap(), 1);
+            assert!(restarted.is_banned(&peer_id));
+        });
+    }
+
+    #[test]
+    fn test_identify_settles_inbound_slots() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut network = P2PNetwork::new_random().unwrap();
+            let validator = aether_crypto_primitives::Keypair::generate();
+            let key: [u8; 32] = validator.public_key().try_into().unwrap();
+            network.set_stake_table(StakeTable::new([(key, 1_000)]));
+
+            let staked = PeerId::random();
+            let proof = StakeProof::sign(&validator, &staked).unwrap();
+            network.pending_inbound.insert(staked);
+            network.on_identified(staked, &proof.agent_version(AGENT_VERSION));
+            assert!(network.admission.is_admitted(&staked));
+            assert_eq!(network.admission.class(&staked), PeerClass::Staked);
+
+            // A copied proof admits the copier only as unstaked, and costs it.
+            let copier = PeerId::random();
+            network.pending_inbound.insert(copier);
+            network.on_identified(copier, &proof.agent_version(AGENT_VERSION));
+            assert_eq!(network.admission.class(&copier), PeerClass::Unstaked);
+            assert!(network.reputation.score(&copier, current_timestamp()) < 0.0);
+
+            // Unstaked peers stop at their share of the slots.
+            let unstaked_slots =
+                network.admission.config().max_inbound - network.admission.config().reserved_staked;
+            for _ in 1..unstaked_slots {
+                let peer = PeerId::random();
+                network.pending_inbound.insert(peer);
+                network.on_identified(peer, AGENT_VERSION);
+                assert!(network.admission.is_admitted(&peer));
+            }
+            let late = PeerId::random();
+            network.pending_inbound.insert(late);
+            network.on_identified(late, AGENT_VERSION);
+            assert!(!network.admission.is_admitted(&late));
+            assert!(network.pending_inbound.is_empty());
+
+            // Outbound peers are never counted.
+            let outbound = PeerId::random();
+            network.on_identified(outbound, AGENT_VERSION);
+            assert!(!network.admission.is_admitted(&outbound));
+        });
+    }
+
+    #[test]
+    fn test_dht_accepts_only_valid_validator_records() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut network = P2PNetwork::new_random().unwrap();
+            let validator = aether_crypto_primitives::Keypair::generate();
+            let key: [u8; 32] = validator.public_key().try_into().unwrap();
+            network.set_stake_table(StakeTable::new([(key, 1_000)]).at_epoch(2));
+
+            let record = ValidatorRecord::sign(
+                &validator,
+                PeerId::random(),
+                vec!["/ip4/127.0.0.1/tcp/9000".parse().unwrap()],
+                vec![PeerRole::BlockProducer],
+                2,
+            )
+            .unwrap();
+            let put =
+                |source: PeerId, key: kad::RecordKey, value: Vec<u8>| kad::Event::InboundRequest {
+                    request: kad::InboundRequest::PutRecord {
+                        source,
+                        connection: libp2p::swarm::ConnectionId::new_unchecked(0),
+                        record: Some(kad::Record::new(key, value)),
+                    },
+                };
+
+            let honest = PeerId::random();
+            network.on_kademlia_event(put(honest, record.key(), record.encode()));
+            assert_eq!(network.validator_directory().len(), 1);
+            assert!(network
+                .swarm
+                .behaviour_mut()
+                .kademlia
+                .store_mut()
+                .get(&record.key())
+                .is_some());
+
+            // Anything else is refused and held against the sender.
+            let spammer = PeerId::random();
+            let junk = kad::RecordKey::new(&b"/not/a/validator");
+            network.on_kademlia_event(put(spammer, junk.clone(), b"junk".to_vec()));
+            assert!(network
+                .swarm
+                .behaviour_mut()
+                .kademlia
+                .store_mut()
+                .get(&junk)
+                .is_none());
+            assert!(network.reputation.score(&spammer, current_timestamp()) < 0.0);
+
+            assert_eq!(network.nearest_block_producers(4).len(), 1);
+            assert_eq!(network.connect_block_producers(4), 1);
+        });
+    }
+
+    #[test]
+    fn test_report_payload_blames_sender() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut network = P2PNetwork::new_random().unwrap();
+            let peer_id = connected_peer(&mut network);
+            network.remember_source(b"shred", peer_id);
+
+            assert_eq!(
+                network.report_payload(b"shred", Violation::InvalidShred),
+                Some(Standing::Good)
             );
-            network.update_peer_score(&peer_id, -101);
+            let penalty = network.reputation.config().weights.invalid_shred;
+            assert_eq!(
+                network.reputation.score(&peer_id, current_timestamp()),
+                -penalty
+            );
+            assert_eq!(network.report_payload(b"vote", Violation::LateVote), None);
 
-            // The new ban is added (len = MAX+1), triggering prune which
-            // removes all expired entries, leaving only the new active ban.
-            assert!(network.is_banned(&peer_id));
-            assert_eq!(network.banned_peers.len(), 1);
+            for i in 0..MAX_RECENT_SOURCES {
+                network.remember_source(&i.to_le_bytes(), peer_id);
+            }
+            assert_eq!(network.recent_sources.len(), MAX_RECENT_SOURCES);
+            assert_eq!(
+                network.report_payload(b"shred", Violation::InvalidShred),
+                None
+            );
+        });
+    }
+
+    #[test]
+    fn test_topic_validator_verdicts() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut network = P2PNetwork::new_random().unwrap();
+            network.set_topic_validator(TOPIC_VOTE, |data: &[u8]| match data {
+                b"good" => Verdict::Accept,
+                b"late" => Verdict::Ignore,
+                _ => Verdict::Reject(Violation::InvalidMessage),
+            });
+            let peer_id = connected_peer(&mut network);
+
+            for data in [b"good".to_vec(), b"late".to_vec(), b"forged".to_vec()] {
+                let pending = PendingMessage {
+                    id: MessageId::new(&data),
+                    source: peer_id,
+                    event_fn: NetworkEvent::VoteReceived,
+                };
+                assert!(network.validation.submit(TOPIC_VOTE, data, pending).is_ok());
+            }
+            let mut delivered = Vec::new();
+            for _ in 0..3 {
+                let validated = network.validation.next().await.unwrap();
+                if let Some(NetworkEvent::VoteReceived(data)) = network.on_validated(validated) {
+                    delivered.push(data);
+                }
+            }
+            assert_eq!(delivered, vec![b"good".to_vec()]);
+
+            // Only the rejected message counted against the sender.
+            let now = current_timestamp();
+            let config = network.reputation.config().clone();
+            assert_eq!(
+                network.reputation.score(&peer_id, now),
+                config.reward - config.weights.invalid_message
+            );
+            assert!(network.recent_sources.contains_key(&payload_id(b"good")));
+            assert!(!network.recent_sources.contains_key(&payload_id(b"late")));
         });
     }
 
@@ -944,6 +1831,8 @@ mod tests {
         assert_eq!(max_size_for_topic(TOPIC_VOTE), MAX_VOTE_SIZE);
         assert_eq!(max_size_for_topic(TOPIC_SHRED), MAX_SHRED_SIZE);
         assert_eq!(max_size_for_topic(TOPIC_SYNC), MAX_SYNC_MSG_SIZE);
+        assert_eq!(max_size_for_topic(TOPIC_ROUND), MAX_ROUND_SYNC_SIZE);
+        assert_eq!(max_size_for_topic(TOPIC_VCR), MAX_VCR_MSG_SIZE);
         // Unknown topics fall back to the global max (2 MB)
         assert_eq!(max_size_for_topic("/aether/1/unknown"), MAX_BLOCK_SIZE);
     }
@@ -1006,6 +1895,36 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_publish_queues_behind_bandwidth() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        rt.block_on(async {
+            let mut network = P2PNetwork::new_random().unwrap();
+            network.subscribe(TOPIC_TX).unwrap();
+            let tight = ClassLimit {
+                rate: 1,
+                burst: 1,
+                queue: 1,
+            };
+            network.set_outbound_config(SchedulerConfig {
+                link_rate: 1,
+                link_burst: 1,
+                tx: tight.clone(),
+                ..SchedulerConfig::default()
+            });
+
+            // The first message spends the link; the next waits for it.
+            network.publish(TOPIC_TX, vec![1, 2, 3]).unwrap();
+            assert_eq!(network.outbound.queued(TrafficClass::Tx), 0);
+            network.publish(TOPIC_TX, vec![4, 5, 6]).unwrap();
+            assert_eq!(network.outbound.queued(TrafficClass::Tx), 1);
+
+            let result = network.publish(TOPIC_TX, vec![7, 8, 9]);
+            assert!(result.unwrap_err().to_string().contains("queue full"));
+            assert_eq!(network.outbound.queued(TrafficClass::Tx), 1);
+        });
+    }
+
     #[test]
     fn test_publish_rejects_unsubscribed_topic() {
         let rt = tokio::runtime::Runtime::new().unwrap();
@@ -1021,7 +1940,15 @@ mod tests {
     fn test_topic_matching_exact() {
         // Verify that our topic constants don't accidentally match each other
         // when using exact equality (the old `contains()` approach was fragile).
-        let topics = [TOPIC_TX, TOPIC_BLOCK, TOPIC_VOTE, TOPIC_SHRED, TOPIC_SYNC];
+        let topics = [
+            TOPIC_TX,
+            TOPIC_BLOCK,
+            TOPIC_VOTE,
+            TOPIC_SHRED,
+            TOPIC_SYNC,
+            TOPIC_ROUND,
+            TOPIC_VCR,
+        ];
         for (i, a) in topics.iter().enumerate() {
             for (j, b) in topics.iter().enumerate() {
                 if i == j {
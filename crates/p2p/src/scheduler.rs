@@ -0,0 +1,466 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::network::{TOPIC_BLOCK, TOPIC_ROUND, TOPIC_SHRED, TOPIC_SYNC, TOPIC_TX, TOPIC_VOTE};
+
+/// Outbound traffic classes, highest priority first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum TrafficClass {
+    /// Consensus votes: late votes are worthless.
+    Vote,
+    /// Blocks and sync requests: what peers need to follow the head.
+    Header,
+    /// Data availability shreds.
+    Shred,
+    /// Transactions, and anything not classified.
+    Tx,
+}
+
+impl TrafficClass {
+    pub const ALL: [TrafficClass; 4] = [
+        TrafficClass::Vote,
+        TrafficClass::Header,
+        TrafficClass::Shred,
+        TrafficClass::Tx,
+    ];
+
+    pub fn for_topic(topic: &str) -> Self {
+        match topic {
+            TOPIC_VOTE | TOPIC_ROUND => TrafficClass::Vote,
+            TOPIC_BLOCK | TOPIC_SYNC => TrafficClass::Header,
+            TOPIC_SHRED => TrafficClass::Shred,
+            TOPIC_TX => TrafficClass::Tx,
+            _ => TrafficClass::Tx,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TrafficClass::Vote => "vote",
+            TrafficClass::Header => "header",
+            TrafficClass::Shred => "shred",
+            TrafficClass::Tx => "tx",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Rate and queue limits for one traffic class.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClassLimit {
+    /// Sustained bytes per second.
+    pub rate: u64,
+    /// Bytes that may go out at once after a quiet period.
+    pub burst: u64,
+    /// Messages waiting before new ones are refused.
+    pub queue: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchedulerConfig {
+    /// Total outbound bytes per second. Once the link is saturated,
+    /// classes are served in priority order.
+    pub link_rate: u64,
+    pub link_burst: u64,
+    pub vote: ClassLimit,
+    pub header: ClassLimit,
+    pub shred: ClassLimit,
+    pub tx: ClassLimit,
+    /// A message queued this long is sent ahead of higher classes, so
+    /// heavy vote or block traffic cannot starve transactions forever.
+    pub max_wait: Duration,
+}
+
+impl SchedulerConfig {
+    pub fn limit(&self, class: TrafficClass) -> &ClassLimit {
+        match class {
+            TrafficClass::Vote => &self.vote,
+            TrafficClass::Header => &self.header,
+            TrafficClass::Shred => &self.shred,
+            TrafficClass::Tx => &self.tx,
+        }
+    }
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        const MIB: u64 = 1024 * 1024;
+        SchedulerConfig {
+            link_rate: 16 * MIB,
+            link_burst: 4 * MIB,
+            vote: ClassLimit {
+                rate: 2 * MIB,
+                burst: MIB / 4,
+                queue: 4096,
+            },
+            header: ClassLimit {
+                rate: 8 * MIB,
+                burst: 4 * MIB,
+                queue: 256,
+            },
+            shred: ClassLimit {
+                rate: 12 * MIB,
+                burst: 2 * MIB,
+                queue: 8192,
+            },
+            tx: ClassLimit {
+                rate: 4 * MIB,
+                burst: MIB,
+                queue: 8192,
+            },
+            max_wait: Duration::from_millis(500),
+        }
+    }
+}
+
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    /// May go negative: a message larger than the burst is let through
+    /// once the bucket is full and paid off afterwards.
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, burst: u64, now: Instant) -> Self {
+        let capacity = burst.max(1) as f64;
+        TokenBucket {
+            rate: rate.max(1) as f64,
+            capacity,
+            tokens: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+    }
+
+    fn needed(&self, bytes: usize) -> f64 {
+        (bytes as f64).min(self.capacity)
+    }
+
+    fn can_take(&self, bytes: usize) -> bool {
+        self.tokens >= self.needed(bytes)
+    }
+
+    fn take(&mut self, bytes: usize) {
+        self.tokens -= bytes as f64;
+    }
+
+    /// When, absent other spending, `bytes` can be taken.
+    fn ready_at(&self, bytes: usize) -> Instant {
+        let missing = self.needed(bytes) - self.tokens;
+        if missing <= 0.0 {
+            return self.updated;
+        }
+        self.updated + Duration::from_secs_f64(missing / self.rate)
+    }
+}
+
+struct Queued<T> {
+    item: T,
+    bytes: usize,
+    queued_at: Instant,
+}
+
+struct ClassQueue<T> {
+    bucket: TokenBucket,
+    limit: usize,
+    queue: VecDeque<Queued<T>>,
+}
+
+/// Orders outbound messages under congestion: votes before headers before
+/// shreds before transactions.
+///
+/// Every class has its own token bucket capping its rate, and a shared
+/// link bucket models the uplink. While the link has room every message
+/// goes out at once; when it does not, the highest class that can pay goes
+/// first, except that a message queued longer than
+/// [`SchedulerConfig::max_wait`] goes ahead of everything.
+pub struct OutboundScheduler<T> {
+    config: SchedulerConfig,
+    link: TokenBucket,
+    classes: Vec<ClassQueue<T>>,
+}
+
+impl<T> OutboundScheduler<T> {
+    pub fn new(config: SchedulerConfig) -> Self {
+        Self::new_at(config, Instant::now())
+    }
+
+    fn new_at(config: SchedulerConfig, now: Instant) -> Self {
+        let classes = TrafficClass::ALL
+            .iter()
+            .map(|class| {
+                let limit = config.limit(*class);
+                ClassQueue {
+                    bucket: TokenBucket::new(limit.rate, limit.burst, now),
+                    limit: limit.queue,
+                    queue: VecDeque::new(),
+                }
+            })
+            .collect();
+        OutboundScheduler {
+            link: TokenBucket::new(config.link_rate, config.link_burst, now),
+            config,
+            classes,
+        }
+    }
+
+    pub fn config(&self) -> &SchedulerConfig {
+        &self.config
+    }
+
+    /// Messages waiting in `class`.
+    pub fn queued(&self, class: TrafficClass) -> usize {
+        self.classes[class.index()].queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.classes.iter().all(|c| c.queue.is_empty())
+    }
+
+    /// Queue a `bytes`-byte message. Hands it back if its class's queue
+    /// is full.
+    pub fn push(
+        &mut self,
+        class: TrafficClass,
+        bytes: usize,
+        item: T,
+        now: Instant,
+    ) -> Result<(), T> {
+        let class = &mut self.classes[class.index()];
+        if class.queue.len() >= class.limit {
+            return Err(item);
+        }
+        class.queue.push_back(Queued {
+            item,
+            bytes,
+            queued_at: now,
+        });
+        Ok(())
+    }
+
+    /// The next message that may go out now, if any.
+    pub fn pop(&mut self, now: Instant) -> Option<(TrafficClass, T)> {
+        self.link.refill(now);
+        for class in &mut self.classes {
+            class.bucket.refill(now);
+        }
+
+        let affordable = TrafficClass::ALL.into_iter().filter(|class| {
+            let queue = &self.classes[class.index()];
+            queue
+                .queue
+                .front()
+                .is_some_and(|head| queue.bucket.can_take(head.bytes))
+        });
+        let head_of = |class: &TrafficClass| &self.classes[class.index()].queue[0];
+        let starving = affordable
+            .clone()
+            .filter(|class| {
+                now.saturating_duration_since(head_of(class).queued_at) >= self.config.max_wait
+            })
+            .min_by_key(|class| head_of(class).queued_at);
+        let class = starving.or_else(|| affordable.clone().next())?;
+
+        let bytes = head_of(&class).bytes;
+        if !self.link.can_take(bytes) {
+            return None;
+        }
+        self.link.take(bytes);
+        let queue = &mut self.classes[class.index()];
+        queue.bucket.take(bytes);
+        let queued = queue.queue.pop_front()?;
+        Some((class, queued.item))
+    }
+
+    /// When the next queued message can go out, or `None` if nothing is
+    /// queued. May be in the past.
+    pub fn next_ready(&self) -> Option<Instant> {
+        self.classes
+            .iter()
+            .filter_map(|class| {
+                let head = class.queue.front()?;
+                Some(
+                    class
+                        .bucket
+                        .ready_at(head.bytes)
+                        .max(self.link.ready_at(head.bytes)),
+                )
+            })
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KIB: u64 = 1024;
+
+    fn limit(rate: u64, burst: u64) -> ClassLimit {
+        ClassLimit {
+            rate,
+            burst,
+            queue: 16,
+        }
+    }
+
+    /// An 8 KiB/s link with generous class limits: one 1 KiB message
+    /// every 125ms once the burst is spent.
+    fn congested() -> SchedulerConfig {
+        SchedulerConfig {
+            link_rate: 8 * KIB,
+            link_burst: 8 * KIB,
+            vote: limit(100 * KIB, 100 * KIB),
+            header: limit(100 * KIB, 100 * KIB),
+            shred: limit(100 * KIB, 100 * KIB),
+            tx: limit(100 * KIB, 100 * KIB),
+            max_wait: Duration::from_secs(60),
+        }
+    }
+
+    fn drain<T>(scheduler: &mut OutboundScheduler<T>, now: Instant) -> Vec<T> {
+        std::iter::from_fn(|| scheduler.pop(now).map(|(_, item)| item)).collect()
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn classes_follow_topics() {
+        assert_eq!(TrafficClass::for_topic(TOPIC_VOTE), TrafficClass::Vote);
+        assert_eq!(TrafficClass::for_topic(TOPIC_ROUND), TrafficClass::Vote);
+        assert_eq!(TrafficClass::for_topic(TOPIC_BLOCK), TrafficClass::Header);
+        assert_eq!(TrafficClass::for_topic(TOPIC_SYNC), TrafficClass::Header);
+        assert_eq!(TrafficClass::for_topic(TOPIC_SHRED), TrafficClass::Shred);
+        assert_eq!(TrafficClass::for_topic(TOPIC_TX), TrafficClass::Tx);
+        assert_eq!(TrafficClass::for_topic("/aether/1/other"), TrafficClass::Tx);
+    }
+
+    #[test]
+    fn congestion_serves_higher_classes_first() {
+        let start = Instant::now();
+        let mut scheduler = OutboundScheduler::new_at(congested(), start);
+        // An idle link sends at once; this fills it.
+        scheduler
+            .push(TrafficClass::Shred, 8 * 1024, "filler", start)
+            .unwrap();
+        assert_eq!(drain(&mut scheduler, start), vec!["filler"]);
+
+        for (class, item) in [
+            (TrafficClass::Tx, "tx"),
+            (TrafficClass::Shred, "shred"),
+            (TrafficClass::Header, "header"),
+            (TrafficClass::Vote, "vote"),
+        ] {
+            scheduler.push(class, 1024, item, start).unwrap();
+        }
+        assert!(scheduler.pop(start).is_none());
+        assert_eq!(scheduler.next_ready(), Some(start + ms(125)));
+
+        let mut sent = Vec::new();
+        for step in 1..=4 {
+            sent.extend(drain(&mut scheduler, start + ms(125 * step)));
+        }
+        assert_eq!(sent, vec!["vote", "header", "shred", "tx"]);
+        assert!(scheduler.is_empty());
+        assert_eq!(scheduler.next_ready(), None);
+    }
+
+    #[test]
+    fn class_rates_cap_each_class() {
+        let start = Instant::now();
+        let config = SchedulerConfig {
+            link_rate: 1024 * KIB,
+            link_burst: 1024 * KIB,
+            vote: limit(2 * KIB, 2 * KIB),
+            ..congested()
+        };
+        let mut scheduler = OutboundScheduler::new_at(config, start);
+        for i in 0..4 {
+            scheduler.push(TrafficClass::Vote, 1024, i, start).unwrap();
+        }
+        scheduler.push(TrafficClass::Tx, 1024, 100, start).unwrap();
+
+        // The vote burst covers two votes; the tx is not held back by the
+        // votes left waiting on their own bucket.
+        assert_eq!(drain(&mut scheduler, start), vec![0, 1, 100]);
+        assert_eq!(scheduler.queued(TrafficClass::Vote), 2);
+        assert_eq!(scheduler.next_ready(), Some(start + ms(500)));
+        assert_eq!(drain(&mut scheduler, start + ms(500)), vec![2]);
+        assert_eq!(drain(&mut scheduler, start + ms(1000)), vec![3]);
+    }
+
+    #[test]
+    fn long_waits_beat_priority() {
+        let start = Instant::now();
+        let config = SchedulerConfig {
+            max_wait: ms(250),
+            ..congested()
+        };
+        let mut scheduler = OutboundScheduler::new_at(config, start);
+        scheduler
+            .push(TrafficClass::Tx, 8 * 1024, "tx", start)
+            .unwrap();
+        scheduler
+            .push(TrafficClass::Vote, 8 * 1024, "vote-0", start)
+            .unwrap();
+        assert_eq!(drain(&mut scheduler, start), vec!["vote-0"]);
+
+        // Votes keep arriving as fast as the link drains, but once the tx
+        // has waited max_wait it goes next.
+        let mut sent = Vec::new();
+        for (step, vote) in ["vote-1", "vote-2", "vote-3", "vote-4"]
+            .into_iter()
+            .enumerate()
+        {
+            let now = start + ms(1000 * (step as u64 + 1));
+            scheduler
+                .push(TrafficClass::Vote, 8 * 1024, vote, now)
+                .unwrap();
+            sent.extend(drain(&mut scheduler, now));
+        }
+        assert_eq!(sent, vec!["tx", "vote-1", "vote-2", "vote-3"]);
+    }
+
+    #[test]
+    fn oversized_messages_and_full_queues() {
+        let start = Instant::now();
+        let mut scheduler = OutboundScheduler::new_at(congested(), start);
+
+        // Bigger than the link burst: sent once the link bucket is full,
+        // then paid off before anything else goes.
+        scheduler
+            .push(TrafficClass::Header, 32 * 1024, "block", start)
+            .unwrap();
+        scheduler
+            .push(TrafficClass::Vote, 1024, "vote", start)
+            .unwrap();
+        assert_eq!(drain(&mut scheduler, start), vec!["vote"]);
+        assert_eq!(scheduler.next_ready(), Some(start + ms(125)));
+        assert_eq!(drain(&mut scheduler, start + ms(125)), vec!["block"]);
+
+        scheduler
+            .push(TrafficClass::Vote, 1024, "late", start + ms(125))
+            .unwrap();
+        assert!(scheduler.pop(start + ms(2000)).is_none());
+        assert_eq!(scheduler.next_ready(), Some(start + ms(3250)));
+        assert_eq!(drain(&mut scheduler, start + ms(3250)), vec!["late"]);
+
+        let mut scheduler = OutboundScheduler::new_at(congested(), start);
+        for i in 0..16 {
+            scheduler.push(TrafficClass::Tx, 1, i, start).unwrap();
+        }
+        assert_eq!(scheduler.push(TrafficClass::Tx, 1, 16, start), Err(16));
+    }
+}
@@ -0,0 +1,544 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
+use aether_crypto_primitives::ed25519;
+use aether_crypto_primitives::signer::{Signer, SignerError};
+use aether_types::ValidatorInfo;
+use libp2p::PeerId;
+
+const PROOF_DOMAIN: &[u8] = b"aether-p2p-stake-proof-v1";
+
+/// Marks the proof inside an identify agent version.
+const AGENT_PROOF_TAG: &str = " stake-proof/";
+
+/// A validator key's signature over a libp2p peer ID, claiming the peer
+/// speaks for that validator's stake.
+///
+/// Carried in the identify handshake. The connection is already
+/// authenticated by noise, so a proof can only ever vouch for the peer it
+/// names: replaying it from another peer ID fails verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakeProof {
+    pub validator: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+impl StakeProof {
+    pub fn sign<S: Signer + ?Sized>(signer: &S, peer_id: &PeerId) -> Result<Self, SignerError> {
+        let validator = signer
+            .public_key()
+            .try_into()
+            .map_err(|_| SignerError::InvalidResponse("public key is not 32 bytes".into()))?;
+        let signature = signer
+            .sign(&signing_payload(peer_id))?
+            .try_into()
+            .map_err(|_| SignerError::InvalidResponse("signature is not 64 bytes".into()))?;
+        Ok(StakeProof {
+            validator,
+            signature,
+        })
+    }
+
+    pub fn verify(&self, peer_id: &PeerId) -> bool {
+        ed25519::verify(&self.validator, &signing_payload(peer_id), &self.signature).is_ok()
+    }
+
+    /// `base` with this proof appended, for the identify agent version.
+    pub fn agent_version(&self, base: &str) -> String {
+        let mut bytes = Vec::with_capacity(96);
+        bytes.extend_from_slice(&self.validator);
+        bytes.extend_from_slice(&self.signature);
+        format!("{base}{AGENT_PROOF_TAG}{}", hex::encode(bytes))
+    }
+
+    /// The proof a peer advertised in its agent version, if any.
+    pub fn from_agent_version(agent_version: &str) -> Option<Self> {
+        let (_, encoded) = agent_version.split_once(AGENT_PROOF_TAG)?;
+        let bytes = hex::decode(encoded.trim()).ok()?;
+        if bytes.len() != 96 {
+            return None;
+        }
+        Some(StakeProof {
+            validator: bytes[..32].try_into().ok()?,
+            signature: bytes[32..].try_into().ok()?,
+        })
+    }
+}
+
+fn signing_payload(peer_id: &PeerId) -> Vec<u8> {
+    let peer_id = peer_id.to_bytes();
+    let mut payload = Vec::with_capacity(PROOF_DOMAIN.len() + peer_id.len());
+    payload.extend_from_slice(PROOF_DOMAIN);
+    payload.extend_from_slice(&peer_id);
+    payload
+}
+
+/// Stake per validator key for the current epoch.
+#[derive(Clone, Debug, Default)]
+pub struct StakeTable {
+    epoch: u64,
+    stakes: HashMap<[u8; 32], u128>,
+    total: u128,
+}
+
+impl StakeTable {
+    pub fn new(stakes: impl IntoIterator<Item = ([u8; 32], u128)>) -> Self {
+        let stakes: HashMap<[u8; 32], u128> = stakes.into_iter().collect();
+        let total = stakes.values().fold(0u128, |sum, s| sum.saturating_add(*s));
+        StakeTable {
+            epoch: 0,
+            stakes,
+            total,
+        }
+    }
+
+    #[must_use]
+    pub fn at_epoch(mut self, epoch: u64) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Active validators' stake. Keys that are not Ed25519-sized are
+    /// skipped, since they could never sign a proof.
+    pub fn from_validators(validators: &[ValidatorInfo]) -> Self {
+        Self::new(validators.iter().filter(|v| v.active).filter_map(|v| {
+            let key: [u8; 32] = v.pubkey.as_bytes().try_into().ok()?;
+            Some((key, v.stake))
+        }))
+    }
+
+    pub fn stake_of(&self, validator: &[u8; 32]) -> u128 {
+        self.stakes.get(validator).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u128 {
+        self.total
+    }
+
+    pub fn len(&self) -> usize {
+        self.stakes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stakes.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PeerClass {
+    Staked,
+    Unstaked,
+}
+
+impl PeerClass {
+    pub fn label(self) -> &'static str {
+        match self {
+            PeerClass::Staked => "staked",
+            PeerClass::Unstaked => "unstaked",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AdmissionConfig {
+    /// Inbound connection slots in all.
+    pub max_inbound: usize,
+    /// Slots only staked peers may fill; unstaked peers get the rest.
+    pub reserved_staked: usize,
+    /// Least stake that makes a peer count as staked.
+    pub min_stake: u128,
+    /// Bytes per second shared by all unstaked peers together.
+    pub unstaked_bandwidth: u64,
+    /// Bytes per second split among staked peers by stake.
+    pub staked_bandwidth: u64,
+    /// Floor on a staked peer's share, so a small validator is never
+    /// throttled below what an unstaked peer could get.
+    pub min_staked_peer_bandwidth: u64,
+    /// Burst every bucket allows, at least one maximum-size message.
+    pub burst: u64,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        AdmissionConfig {
+            max_inbound: 128,
+            reserved_staked: 64,
+            min_stake: 1,
+            unstaked_bandwidth: 8 * 1024 * 1024,
+            staked_bandwidth: 64 * 1024 * 1024,
+            min_staked_peer_bandwidth: 1024 * 1024,
+            burst: 4 * 1024 * 1024,
+        }
+    }
+}
+
+/// What to do with an inbound peer once it is classified.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Admission {
+    Accept,
+    /// Accept, after disconnecting this peer to free its slot.
+    Evict(PeerId),
+    Reject,
+}
+
+/// Inbound connection occupancy by class.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Occupancy {
+    pub staked: usize,
+    pub unstaked: usize,
+}
+
+struct ByteBucket {
+    tokens: f64,
+    capacity: f64,
+    rate: f64,
+    last_refill: Instant,
+}
+
+impl ByteBucket {
+    fn new(rate: u64, burst: u64, now: Instant) -> Self {
+        let capacity = rate.max(burst) as f64;
+        ByteBucket {
+            tokens: capacity,
+            capacity,
+            rate: rate as f64,
+            last_refill: now,
+        }
+    }
+
+    fn try_take(&mut self, bytes: usize, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() * self.rate).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens >= bytes as f64 {
+            self.tokens -= bytes as f64;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Stake-weighted quotas on inbound connections and gossip bandwidth.
+///
+/// Validators prove their stake with a [`StakeProof`]; everyone else is
+/// unstaked. Unstaked peers share `max_inbound - reserved_staked` slots and
+/// one bandwidth bucket, so however many identities an attacker brings
+/// they cannot crowd validators out. A staked peer may take any free slot,
+/// and when none is free displaces an unstaked peer, or failing that a
+/// peer with less stake. Each staked peer gets its own bucket sized by its
+/// share of the total stake.
+pub struct StakeAdmission {
+    config: AdmissionConfig,
+    table: StakeTable,
+    /// Validator key each peer has proved, whether or not it has stake.
+    validators: HashMap<PeerId, [u8; 32]>,
+    inbound: HashMap<PeerId, PeerClass>,
+    unstaked_bucket: ByteBucket,
+    staked_buckets: HashMap<PeerId, ByteBucket>,
+}
+
+impl StakeAdmission {
+    pub fn new(config: AdmissionConfig) -> Self {
+        let unstaked_bucket =
+            ByteBucket::new(config.unstaked_bandwidth, config.burst, Instant::now());
+        StakeAdmission {
+            config,
+            table: StakeTable::default(),
+            validators: HashMap::new(),
+            inbound: HashMap::new(),
+            unstaked_bucket,
+            staked_buckets: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &AdmissionConfig {
+        &self.config
+    }
+
+    /// Switch to a new epoch's stake, reclassifying admitted peers. Peers
+    /// that lost their stake keep their connection but now count, and are
+    /// throttled, as unstaked.
+    pub fn set_stake_table(&mut self, table: StakeTable) {
+        self.table = table;
+        self.staked_buckets.clear();
+        let peers: Vec<PeerId> = self.inbound.keys().copied().collect();
+        for peer in peers {
+            let class = self.class(&peer);
+            self.inbound.insert(peer, class);
+        }
+    }
+
+    pub fn stake_table(&self) -> &StakeTable {
+        &self.table
+    }
+
+    /// Record the validator `peer` proved to be. Returns false, recording
+    /// nothing, if the proof does not verify for `peer`.
+    pub fn record_proof(&mut self, peer: &PeerId, proof: &StakeProof) -> bool {
+        if !proof.verify(peer) {
+            return false;
+        }
+        if self.validators.insert(*peer, proof.validator) != Some(proof.validator) {
+            self.staked_buckets.remove(peer);
+        }
+        true
+    }
+
+    pub fn stake_of(&self, peer: &PeerId) -> u128 {
+        self.validators
+            .get(peer)
+            .map_or(0, |validator| self.table.stake_of(validator))
+    }
+
+    pub fn class(&self, peer: &PeerId) -> PeerClass {
+        if self.stake_of(peer) >= self.config.min_stake.max(1) {
+            PeerClass::Staked
+        } else {
+            PeerClass::Unstaked
+        }
+    }
+
+    /// Decide whether inbound `peer` may keep its connection, admitting it
+    /// if so. Safe to repeat: a peer already admitted in its current class
+    /// is accepted again, and one that has since proved stake is moved
+    /// over to the staked slots.
+    pub fn admit_inbound(&mut self, peer: PeerId) -> Admission {
+        let class = self.class(&peer);
+        let previous = self.inbound.remove(&peer);
+        if previous == Some(class) {
+            self.inbound.insert(peer, class);
+            return Admission::Accept;
+        }
+
+        let occupancy = self.occupancy();
+        let total = occupancy.staked + occupancy.unstaked;
+        let unstaked_slots = self
+            .config
+            .max_inbound
+            .saturating_sub(self.config.reserved_staked);
+
+        let admission = match class {
+            PeerClass::Unstaked if occupancy.unstaked >= unstaked_slots => Admission::Reject,
+            _ if total < self.config.max_inbound => Admission::Accept,
+            PeerClass::Unstaked => Admission::Reject,
+            PeerClass::Staked => match self.eviction_candidate(self.stake_of(&peer)) {
+                Some(victim) => Admission::Evict(victim),
+                None => Admission::Reject,
+            },
+        };
+        match admission {
+            Admission::Accept => {
+                self.inbound.insert(peer, class);
+            }
+            Admission::Evict(victim) => {
+                self.remove(&victim);
+                self.inbound.insert(peer, class);
+            }
+            Admission::Reject => {}
+        }
+        admission
+    }
+
+    /// An unstaked peer if there is one, otherwise the staked peer with
+    /// the least stake, provided it has less than `stake`.
+    fn eviction_candidate(&self, stake: u128) -> Option<PeerId> {
+        if let Some((peer, _)) = self
+            .inbound
+            .iter()
+            .find(|(_, class)| **class == PeerClass::Unstaked)
+        {
+            return Some(*peer);
+        }
+        self.inbound
+            .keys()
+            .map(|peer| (self.stake_of(peer), *peer))
+            .filter(|(peer_stake, _)| *peer_stake < stake)
+            .min()
+            .map(|(_, peer)| peer)
+    }
+
+    /// Forget a disconnected peer.
+    pub fn remove(&mut self, peer: &PeerId) {
+        self.inbound.remove(peer);
+        self.validators.remove(peer);
+        self.staked_buckets.remove(peer);
+    }
+
+    pub fn is_admitted(&self, peer: &PeerId) -> bool {
+        self.inbound.contains_key(peer)
+    }
+
+    pub fn occupancy(&self) -> Occupancy {
+        let staked = self
+            .inbound
+            .values()
+            .filter(|class| **class == PeerClass::Staked)
+            .count();
+        Occupancy {
+            staked,
+            unstaked: self.inbound.len() - staked,
+        }
+    }
+
+    /// Charge `bytes` received from `peer` to its bandwidth quota,
+    /// returning false if the quota is exhausted and the message should be
+    /// dropped.
+    pub fn allow_bytes(&mut self, peer: &PeerId, bytes: usize, now: Instant) -> bool {
+        match self.class(peer) {
+            PeerClass::Unstaked => self.unstaked_bucket.try_take(bytes, now),
+            PeerClass::Staked => {
+                let rate = self.staked_rate(peer);
+                let burst = self.config.burst;
+                self.staked_buckets
+                    .entry(*peer)
+                    .or_insert_with(|| ByteBucket::new(rate, burst, now))
+                    .try_take(bytes, now)
+            }
+        }
+    }
+
+    /// `peer`'s share of the staked bandwidth, by stake.
+    fn staked_rate(&self, peer: &PeerId) -> u64 {
+        let total = self.table.total().max(1);
+        let share = u128::from(self.config.staked_bandwidth) * self.stake_of(peer) / total;
+        u64::try_from(share)
+            .unwrap_or(u64::MAX)
+            .max(self.config.min_staked_peer_bandwidth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_primitives::Keypair;
+    use std::time::Duration;
+
+    fn validator(stake: u128) -> (Keypair, PeerId, ([u8; 32], u128)) {
+        let keypair = Keypair::generate();
+        let key: [u8; 32] = keypair.public_key().try_into().unwrap();
+        (keypair, PeerId::random(), (key, stake))
+    }
+
+    fn small_config() -> AdmissionConfig {
+        AdmissionConfig {
+            max_inbound: 4,
+            reserved_staked: 2,
+            ..AdmissionConfig::default()
+        }
+    }
+
+    #[test]
+    fn proof_binds_validator_to_peer() {
+        let (keypair, peer, _) = validator(0);
+        let proof = StakeProof::sign(&keypair, &peer).unwrap();
+        assert!(proof.verify(&peer));
+        assert!(!proof.verify(&PeerId::random()));
+
+        let agent = proof.agent_version("rust-libp2p/0.44");
+        assert!(agent.starts_with("rust-libp2p/0.44 "));
+        assert_eq!(StakeProof::from_agent_version(&agent), Some(proof));
+        assert_eq!(StakeProof::from_agent_version("rust-libp2p/0.44"), None);
+        assert_eq!(
+            StakeProof::from_agent_version("x stake-proof/deadbeef"),
+            None
+        );
+    }
+
+    #[test]
+    fn unstaked_peers_cannot_fill_reserved_slots() {
+        let mut admission = StakeAdmission::new(small_config());
+        let (keypair, staked, entry) = validator(100);
+        admission.set_stake_table(StakeTable::new([entry]));
+
+        let unstaked: Vec<PeerId> = (0..3).map(|_| PeerId::random()).collect();
+        assert_eq!(admission.admit_inbound(unstaked[0]), Admission::Accept);
+        assert_eq!(admission.admit_inbound(unstaked[1]), Admission::Accept);
+        assert_eq!(admission.admit_inbound(unstaked[2]), Admission::Reject);
+
+        // Before proving stake the validator is just another unstaked peer.
+        assert_eq!(admission.admit_inbound(staked), Admission::Reject);
+        let proof = StakeProof::sign(&keypair, &staked).unwrap();
+        assert!(!admission.record_proof(&PeerId::random(), &proof));
+        assert!(admission.record_proof(&staked, &proof));
+        assert_eq!(admission.admit_inbound(staked), Admission::Accept);
+        assert_eq!(admission.admit_inbound(staked), Admission::Accept);
+        assert_eq!(
+            admission.occupancy(),
+            Occupancy {
+                staked: 1,
+                unstaked: 2
+            }
+        );
+    }
+
+    #[test]
+    fn staked_peers_displace_unstaked_then_smaller_stake() {
+        let mut admission = StakeAdmission::new(small_config());
+        let validators: Vec<_> = [10, 20, 30, 40, 5, 15].into_iter().map(validator).collect();
+        admission.set_stake_table(StakeTable::new(validators.iter().map(|v| v.2)));
+        for (keypair, peer, _) in &validators {
+            admission.record_proof(peer, &StakeProof::sign(keypair, peer).unwrap());
+        }
+
+        let unstaked = PeerId::random();
+        assert_eq!(admission.admit_inbound(unstaked), Admission::Accept);
+        for (_, peer, _) in &validators[..3] {
+            assert_eq!(admission.admit_inbound(*peer), Admission::Accept);
+        }
+        assert_eq!(
+            admission.admit_inbound(validators[3].1),
+            Admission::Evict(unstaked)
+        );
+        // Full of validators: the smallest stake makes way for a larger one
+        // but not for a smaller one.
+        assert_eq!(admission.admit_inbound(validators[4].1), Admission::Reject);
+        assert_eq!(
+            admission.admit_inbound(validators[5].1),
+            Admission::Evict(validators[0].1)
+        );
+        assert!(!admission.is_admitted(&validators[0].1));
+
+        // A validator that leaves the set is demoted in place.
+        admission.set_stake_table(StakeTable::new(validators[2..].iter().map(|v| v.2)));
+        assert_eq!(admission.class(&validators[1].1), PeerClass::Unstaked);
+        assert_eq!(
+            admission.occupancy(),
+            Occupancy {
+                staked: 3,
+                unstaked: 1
+            }
+        );
+    }
+
+    #[test]
+    fn unstaked_bandwidth_is_shared_and_staked_is_weighted() {
+        let config = AdmissionConfig {
+            unstaked_bandwidth: 1_000,
+            staked_bandwidth: 100_000,
+            min_staked_peer_bandwidth: 0,
+            burst: 0,
+            ..small_config()
+        };
+        let mut admission = StakeAdmission::new(config);
+        let (big_key, big, big_entry) = validator(3);
+        let (small_key, small, small_entry) = validator(1);
+        admission.set_stake_table(StakeTable::new([big_entry, small_entry]));
+        admission.record_proof(&big, &StakeProof::sign(&big_key, &big).unwrap());
+        admission.record_proof(&small, &StakeProof::sign(&small_key, &small).unwrap());
+
+        let now = Instant::now();
+        let (a, b) = (PeerId::random(), PeerId::random());
+        assert!(admission.allow_bytes(&a, 600, now));
+        assert!(!admission.allow_bytes(&b, 600, now));
+        assert!(admission.allow_bytes(&b, 600, now + Duration::from_millis(300)));
+
+        assert!(admission.allow_bytes(&big, 75_000, now));
+        assert!(!admission.allow_bytes(&small, 75_000, now));
+        assert!(admission.allow_bytes(&small, 25_000, now));
+    }
+}
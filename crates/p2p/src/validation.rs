@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use aether_gossipsub::Violation;
+use tokio::sync::{mpsc, Mutex};
+
+/// What a topic validator decided about a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Valid: deliver it and forward it to the mesh.
+    Accept,
+    /// Invalid: drop it, never forward it, and penalize the sender.
+    Reject(Violation),
+    /// Not provably invalid but not worth passing on (a stale vote, a
+    /// shred for a slot we cannot check yet): drop it without penalty.
+    Ignore,
+}
+
+impl Verdict {
+    pub fn label(self) -> &'static str {
+        match self {
+            Verdict::Accept => "accept",
+            Verdict::Reject(_) => "reject",
+            Verdict::Ignore => "ignore",
+        }
+    }
+}
+
+/// Checks messages on one topic before they are delivered or re-gossiped.
+///
+/// Validators run on the blocking thread pool, so they may do real work
+/// (signature checks, decoding) without stalling the swarm.
+pub trait TopicValidator: Send + Sync {
+    fn validate(&self, data: &[u8]) -> Verdict;
+}
+
+impl<F> TopicValidator for F
+where
+    F: Fn(&[u8]) -> Verdict + Send + Sync,
+{
+    fn validate(&self, data: &[u8]) -> Verdict {
+        self(data)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationConfig {
+    /// Messages validated at once.
+    pub workers: usize,
+    /// Messages waiting for a worker before new ones are dropped.
+    pub queue: usize,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> Self {
+        ValidationConfig {
+            workers: 4,
+            queue: 1024,
+        }
+    }
+}
+
+/// A message that has been through its topic's validator. `ticket` is
+/// whatever the submitter needs to act on the verdict.
+pub struct Validated<T> {
+    pub ticket: T,
+    pub topic: String,
+    pub data: Vec<u8>,
+    pub verdict: Verdict,
+}
+
+struct Job<T> {
+    ticket: T,
+    topic: String,
+    data: Vec<u8>,
+    validator: Arc<dyn TopicValidator>,
+}
+
+/// Runs per-topic validators on a fixed number of workers fed by a bounded
+/// queue. When the queue is full the submitter is told, so a flood of
+/// messages costs us dropped messages rather than unbounded memory.
+///
+/// Workers start on first use, so a pool can be built outside a runtime.
+pub struct ValidationPool<T> {
+    config: ValidationConfig,
+    validators: HashMap<String, Arc<dyn TopicValidator>>,
+    jobs: Option<mpsc::Sender<Job<T>>>,
+    results_tx: mpsc::Sender<Validated<T>>,
+    results: mpsc::Receiver<Validated<T>>,
+}
+
+impl<T: Send + 'static> ValidationPool<T> {
+    pub fn new(config: ValidationConfig) -> Self {
+        let config = ValidationConfig {
+            workers: config.workers.max(1),
+            queue: config.queue.max(1),
+        };
+        let (results_tx, results) = mpsc::channel(config.queue + config.workers);
+        ValidationPool {
+            config,
+            validators: HashMap::new(),
+            jobs: None,
+            results_tx,
+            results,
+        }
+    }
+
+    pub fn config(&self) -> &ValidationConfig {
+        &self.config
+    }
+
+    /// Check messages on `topic` with `validator`, replacing any before.
+    pub fn set_validator(&mut self, topic: &str, validator: Arc<dyn TopicValidator>) {
+        self.validators.insert(topic.to_string(), validator);
+    }
+
+    pub fn has_validator(&self, topic: &str) -> bool {
+        self.validators.contains_key(topic)
+    }
+
+    /// Queue `data` for `topic`'s validator. Hands the message back if
+    /// there is no validator for the topic or the queue is full.
+    pub fn submit(&mut self, topic: &str, data: Vec<u8>, ticket: T) -> Result<(), (T, Vec<u8>)> {
+        let Some(validator) = self.validators.get(topic).cloned() else {
+            return Err((ticket, data));
+        };
+        let job = Job {
+            ticket,
+            topic: topic.to_string(),
+            data,
+            validator,
+        };
+        let jobs = match &self.jobs {
+            Some(jobs) => jobs,
+            None => self.jobs.insert(self.spawn_workers()),
+        };
+        jobs.try_send(job).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(job) | mpsc::error::TrySendError::Closed(job) => {
+                (job.ticket, job.data)
+            }
+        })
+    }
+
+    /// The next validated message. Pending forever while nothing is queued.
+    pub async fn next(&mut self) -> Option<Validated<T>> {
+        self.results.recv().await
+    }
+
+    fn spawn_workers(&self) -> mpsc::Sender<Job<T>> {
+        let (jobs, queue) = mpsc::channel::<Job<T>>(self.config.queue);
+        let queue = Arc::new(Mutex::new(queue));
+        for _ in 0..self.config.workers {
+            let queue = queue.clone();
+            let results = self.results_tx.clone();
+            tokio::spawn(async move {
+                loop {
+                    let Some(job) = queue.lock().await.recv().await else {
+                        return;
+                    };
+                    let Job {
+                        ticket,
+                        topic,
+                        data,
+                        validator,
+                    } = job;
+                    let checked = tokio::task::spawn_blocking(move || {
+                        let verdict = validator.validate(&data);
+                        (verdict, data)
+                    })
+                    .await;
+                    // A validator that panics has told us nothing.
+                    let (verdict, data) = checked.unwrap_or_else(|e| {
+                        tracing::error!(topic = %topic, err = %e, "topic validator panicked");
+                        (Verdict::Ignore, Vec::new())
+                    });
+                    let validated = Validated {
+                        ticket,
+                        topic,
+                        data,
+                        verdict,
+                    };
+                    if results.send(validated).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        jobs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn by_first_byte(data: &[u8]) -> Verdict {
+        match data.first() {
+            Some(0) => Verdict::Accept,
+            Some(1) => Verdict::Reject(Violation::InvalidMessage),
+            _ => Verdict::Ignore,
+        }
+    }
+
+    #[tokio::test]
+    async fn validates_per_topic() {
+        let mut pool = ValidationPool::new(ValidationConfig::default());
+        pool.set_validator("vote", Arc::new(by_first_byte));
+        assert!(pool.has_validator("vote"));
+
+        // No validator: handed straight back.
+        assert_eq!(pool.submit("tx", vec![0], 7u32), Err((7, vec![0])));
+
+        for (ticket, data) in [(1u32, vec![0, 9]), (2, vec![1]), (3, vec![5])] {
+            pool.submit("vote", data, ticket).unwrap();
+        }
+        let mut verdicts = Vec::new();
+        for _ in 0..3 {
+            let validated = pool.next().await.unwrap();
+            assert_eq!(validated.topic, "vote");
+            verdicts.push((validated.ticket, validated.verdict, validated.data));
+        }
+        verdicts.sort_by_key(|(ticket, _, _)| *ticket);
+        assert_eq!(
+            verdicts,
+            vec![
+                (1, Verdict::Accept, vec![0, 9]),
+                (2, Verdict::Reject(Violation::InvalidMessage), vec![1]),
+                (3, Verdict::Ignore, vec![5]),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn full_queue_hands_back_work() {
+        let started = Arc::new(AtomicUsize::new(0));
+        let release = Arc::new(std::sync::Barrier::new(2));
+        let validator = {
+            let (started, release) = (started.clone(), release.clone());
+            move |_: &[u8]| {
+                started.fetch_add(1, Ordering::SeqCst);
+                release.wait();
+                Verdict::Accept
+            }
+        };
+        let mut pool = ValidationPool::new(ValidationConfig {
+            workers: 1,
+            queue: 2,
+        });
+        pool.set_validator("shred", Arc::new(validator));
+
+        // One message occupies the worker, two fill the queue.
+        pool.submit("shred", vec![0], 0u32).unwrap();
+        while started.load(Ordering::SeqCst) == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        pool.submit("shred", vec![1], 1).unwrap();
+        pool.submit("shred", vec![2], 2).unwrap();
+        assert_eq!(pool.submit("shred", vec![3], 3), Err((3, vec![3])));
+
+        for expected in 0..3 {
+            let barrier = release.clone();
+            tokio::task::spawn_blocking(move || barrier.wait());
+            assert_eq!(pool.next().await.unwrap().ticket, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn panicking_validator_ignores_message() {
+        let mut pool = ValidationPool::new(ValidationConfig::default());
+        pool.set_validator(
+            "block",
+            Arc::new(|_: &[u8]| -> Verdict { panic!("malformed") }),
+        );
+        pool.submit("block", vec![1, 2, 3], ()).unwrap();
+        assert_eq!(pool.next().await.unwrap().verdict, Verdict::Ignore);
+    }
+}
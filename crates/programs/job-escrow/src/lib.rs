@@ -25,10 +25,31 @@
 // - Challenge period (10 slots)
 // - Reputation scoring
 // - Slashing for invalid results
+//
+// OPTIMISTIC TEE:
+// A bonded provider may submit a receipt that only commits to its TEE quote
+// (see `aether_verifiers_vcr::optimistic`). Anyone holding the quote can post
+// it as a `QuoteFraudProof` during the challenge period; if it fails
+// verification the requester is refunded, the provider's bond is slashed
+// (part of it to the challenger) and the provider is barred.
+//
+// MILESTONES:
+// A job split across providers (a sharded batch) is paid shard by shard:
+// the requester releases part of the escrow to each shard's provider as it
+// delivers, and the job completes once nothing is left escrowed.
+//
+// WATCHTOWERS:
+// A watchtower whose spot check of a submitted result failed files its
+// `FraudReport` during the challenge period. That disputes the job as a
+// requester challenge would, and the report stays on record for
+// `resolve_dispute`.
 // ============================================================================
 
 use aether_types::{Address, H256};
-use aether_verifiers_vcr::{VcrValidator, VerifiableComputeReceipt};
+pub use aether_verifiers_kzg::FraudReport;
+use aether_verifiers_vcr::{
+    CounterVcr, DisputeOutcome, QuoteVerdict, SpotCheck, VcrValidator, VerifiableComputeReceipt,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -52,19 +73,47 @@ pub struct Job {
     pub input_hash: H256,
     pub output_hash: Option<H256>,
     pub vcr_proof: Option<Vec<u8>>,
+    /// Payment still escrowed for the job.
     pub payment: u128,
+    /// Paid out so far through `release_milestone`.
+    #[serde(default)]
+    pub released: u128,
     pub status: JobStatus,
     pub posted_slot: u64,
     pub deadline_slot: u64,
     pub challenge_end_slot: Option<u64>,
 }
 
+/// Fraud-proof transaction: the full quote behind an optimistic receipt's
+/// commitment. Carried as JSON in the data of a job escrow transaction.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuoteFraudProof {
+    pub job_id: H256,
+    pub quote: Vec<u8>,
+}
+
+/// What an accepted quote fraud proof took from the provider.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuoteFraudSlash {
+    pub provider: Address,
+    /// Whole bond removed from the provider.
+    pub slashed: u128,
+    /// Share of `slashed` credited to the challenger; the rest is burned.
+    pub reward: u128,
+    pub reason: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct JobEscrowState {
     pub jobs: HashMap<H256, Job>,
     pub provider_reputation: HashMap<Address, i32>,
     pub requester_escrow: HashMap<Address, u128>,
     pub provider_claimable: HashMap<Address, u128>,
+    #[serde(default)]
+    pub provider_bonds: HashMap<Address, u128>,
+    /// Watchtower fraud reports behind disputed jobs, with their reporter.
+    #[serde(default)]
+    pub fraud_reports: HashMap<H256, (Address, FraudReport)>,
     pub total_jobs: u64,
     pub completed_jobs: u64,
 }
@@ -76,6 +125,8 @@ impl JobEscrowState {
             provider_reputation: HashMap::new(),
             requester_escrow: HashMap::new(),
             provider_claimable: HashMap::new(),
+            provider_bonds: HashMap::new(),
+            fraud_reports: HashMap::new(),
             total_jobs: 0,
             completed_jobs: 0,
         }
@@ -110,6 +161,7 @@ impl JobEscrowState {
             output_hash: None,
             vcr_proof: None,
             payment,
+            released: 0,
             status: JobStatus::Posted,
             posted_slot: current_slot,
             deadline_slot: current_slot
@@ -140,6 +192,49 @@ impl JobEscrowState {
     /// cannot bypass it.
     pub const MIN_PROVIDER_REPUTATION: i32 = -50;
 
+    /// Reputation a provider loses when a dispute goes against its result.
+    pub const DISPUTE_LOSS_PENALTY: i32 = 10;
+
+    /// Bond a provider needs before its optimistic receipts are paid out,
+    /// matching the default `AiMeshParams::vcr_bond_minimum`.
+    pub const MIN_OPTIMISTIC_BOND: u128 = 10_000_000;
+
+    /// Share of a slashed bond paid to the challenger, in basis points.
+    pub const QUOTE_FRAUD_REWARD_BPS: u128 = 5_000;
+
+    /// Add to a provider's bond.
+    pub fn deposit_bond(&mut self, provider: Address, amount: u128) -> Result<(), String> {
+        if amount == 0 {
+            return Err("bond deposit must be non-zero".to_string());
+        }
+        let bond = self.provider_bonds.entry(provider).or_insert(0);
+        *bond = bond.checked_add(amount).ok_or("provider bond overflow")?;
+        Ok(())
+    }
+
+    /// Withdraw from a provider's bond. Refused while any of its results can
+    /// still be challenged.
+    pub fn withdraw_bond(&mut self, provider: Address, amount: u128) -> Result<(), String> {
+        let pending = self.jobs.values().any(|job| {
+            job.provider == Some(provider)
+                && matches!(job.status, JobStatus::Submitted | JobStatus::Disputed)
+        });
+        if pending {
+            return Err("provider has results awaiting settlement".to_string());
+        }
+        let bond = self
+            .provider_bonds
+            .get_mut(&provider)
+            .ok_or("provider has no bond")?;
+        *bond = bond
+            .checked_sub(amount)
+            .ok_or("insufficient provider bond")?;
+        if *bond == 0 {
+            self.provider_bonds.remove(&provider);
+        }
+        Ok(())
+    }
+
     /// Provider accepts job
     pub fn accept_job(&mut self, job_id: H256, provider: Address) -> Result<(), String> {
         // Reject providers whose reputation is too low.
@@ -235,28 +330,31 @@ impl JobEscrowState {
             let proof_bytes = job.vcr_proof.as_deref().ok_or("missing VCR proof")?;
             let receipt: VerifiableComputeReceipt = serde_json::from_slice(proof_bytes)
                 .map_err(|e| format!("invalid VCR proof encoding: {e}"))?;
-            vcr_validator
-                .verify(&receipt)
-                .map_err(|e| format!("VCR proof verification failed: {e}"))?;
-
             let provider = job.provider.ok_or("job has no provider")?;
+            if receipt.quote_commitment().is_some() {
+                // The quote went unchallenged; the bond is what backs it.
+                let bond = self.provider_bonds.get(&provider).copied().unwrap_or(0);
+                if bond < Self::MIN_OPTIMISTIC_BOND {
+                    return Err(format!(
+                        "optimistic VCR needs a provider bond of {}, have {bond}",
+                        Self::MIN_OPTIMISTIC_BOND
+                    ));
+                }
+                vcr_validator
+                    .verify_optimistic(&receipt)
+                    .map_err(|e| format!("VCR proof verification failed: {e}"))?;
+            } else {
+                vcr_validator
+                    .verify(&receipt)
+                    .map_err(|e| format!("VCR proof verification failed: {e}"))?;
+            }
+
             let requester = job.requester;
             let payment = job.payment;
             (requester, provider, payment)
         };
 
-        let escrowed = self
-            .requester_escrow
-            .get_mut(&requester)
-            .ok_or("missing requester escrow balance")?;
-        if *escrowed < payment {
-            return Err("insufficient requester escrow balance".to_string());
-        }
-        *escrowed = escrowed.checked_sub(payment).ok_or("escrow underflow")?;
-        let remove_requester_escrow = *escrowed == 0;
-        if remove_requester_escrow {
-            self.requester_escrow.remove(&requester);
-        }
+        self.release_requester_escrow(requester, payment)?;
         let claimable = self.provider_claimable.entry(provider).or_insert(0);
         *claimable = claimable
             .checked_add(payment)
@@ -294,6 +392,217 @@ impl JobEscrowState {
         Ok(())
     }
 
+    /// Dispute a submitted result on a watchtower's fraud report.
+    ///
+    /// Anyone but the provider may report, during the challenge period,
+    /// against the worker that signed the job's receipt.
+    pub fn report_fraud(
+        &mut self,
+        report: &FraudReport,
+        reporter: Address,
+        current_slot: u64,
+    ) -> Result<(), String> {
+        let job = self.jobs.get_mut(&report.vcr_id).ok_or("job not found")?;
+        if job.status != JobStatus::Submitted {
+            return Err("cannot report fraud on job".to_string());
+        }
+        if job
+            .challenge_end_slot
+            .is_some_and(|challenge_end| current_slot > challenge_end)
+        {
+            return Err("challenge period ended".to_string());
+        }
+        if job.provider == Some(reporter) {
+            return Err("provider cannot report its own result".to_string());
+        }
+        let proof_bytes = job.vcr_proof.as_deref().ok_or("missing VCR proof")?;
+        let receipt: VerifiableComputeReceipt = serde_json::from_slice(proof_bytes)
+            .map_err(|e| format!("invalid VCR proof encoding: {e}"))?;
+        if report.worker_id != receipt.worker_id {
+            return Err("report names a different worker".to_string());
+        }
+
+        job.status = JobStatus::Disputed;
+        self.fraud_reports
+            .insert(report.vcr_id, (reporter, report.clone()));
+        Ok(())
+    }
+
+    /// Settle a disputed job from the challenger's counter-proof and the
+    /// spot-check evidence.
+    ///
+    /// If the original result stands the job returns to `Submitted` and is
+    /// paid out through `verify_job`. Otherwise the requester is refunded, the
+    /// job is cancelled and the provider loses reputation.
+    pub fn resolve_dispute(
+        &mut self,
+        job_id: H256,
+        counter: &CounterVcr,
+        spot: &SpotCheck,
+        vcr_validator: &VcrValidator,
+    ) -> Result<DisputeOutcome, String> {
+        let job = self.jobs.get_mut(&job_id).ok_or("job not found")?;
+        if job.status != JobStatus::Disputed {
+            return Err("job not disputed".to_string());
+        }
+        let proof_bytes = job.vcr_proof.as_deref().ok_or("missing VCR proof")?;
+        let receipt: VerifiableComputeReceipt = serde_json::from_slice(proof_bytes)
+            .map_err(|e| format!("invalid VCR proof encoding: {e}"))?;
+        let resolution = vcr_validator
+            .resolve(&receipt, counter, spot)
+            .map_err(|e| format!("malformed dispute: {e}"))?;
+
+        if resolution.outcome == DisputeOutcome::OriginalUpheld {
+            job.status = JobStatus::Submitted;
+            return Ok(resolution.outcome);
+        }
+
+        let requester = job.requester;
+        let payment = job.payment;
+        let provider = job.provider.ok_or("job has no provider")?;
+        self.release_requester_escrow(requester, payment)?;
+        let job = self.jobs.get_mut(&job_id).ok_or("job not found")?;
+        job.status = JobStatus::Cancelled;
+        let rep = self.provider_reputation.entry(provider).or_insert(0);
+        *rep = rep.saturating_sub(Self::DISPUTE_LOSS_PENALTY);
+        Ok(resolution.outcome)
+    }
+
+    /// Reveal the quote behind a submitted optimistic receipt.
+    ///
+    /// Anyone but the provider may do so during the challenge period. If the
+    /// quote fails verification the requester is refunded, the job is
+    /// cancelled, the provider's bond is slashed and its reputation drops to
+    /// the floor. A quote that verifies, or that does not match the receipt's
+    /// commitment, is rejected and nothing changes.
+    pub fn submit_quote_fraud_proof(
+        &mut self,
+        proof: &QuoteFraudProof,
+        challenger: Address,
+        current_slot: u64,
+        vcr_validator: &VcrValidator,
+    ) -> Result<QuoteFraudSlash, String> {
+        let job = self.jobs.get(&proof.job_id).ok_or("job not found")?;
+        if job.status != JobStatus::Submitted {
+            return Err("job not submitted".to_string());
+        }
+        if job
+            .challenge_end_slot
+            .is_some_and(|challenge_end| current_slot > challenge_end)
+        {
+            return Err("challenge period ended".to_string());
+        }
+        let provider = job.provider.ok_or("job has no provider")?;
+        if challenger == provider {
+            return Err("provider cannot challenge its own result".to_string());
+        }
+        let proof_bytes = job.vcr_proof.as_deref().ok_or("missing VCR proof")?;
+        let receipt: VerifiableComputeReceipt = serde_json::from_slice(proof_bytes)
+            .map_err(|e| format!("invalid VCR proof encoding: {e}"))?;
+        let reason = match vcr_validator
+            .check_quote_fraud(&receipt, &proof.quote)
+            .map_err(|e| format!("malformed fraud proof: {e}"))?
+        {
+            QuoteVerdict::Valid => return Err("quote is valid; no fraud".to_string()),
+            QuoteVerdict::Fraudulent(reason) => reason,
+        };
+
+        let requester = job.requester;
+        let payment = job.payment;
+        self.release_requester_escrow(requester, payment)?;
+        let job = self.jobs.get_mut(&proof.job_id).ok_or("job not found")?;
+        job.status = JobStatus::Cancelled;
+
+        let slashed = self.provider_bonds.remove(&provider).unwrap_or(0);
+        let reward = slashed
+            .checked_mul(Self::QUOTE_FRAUD_REWARD_BPS)
+            .ok_or("slash reward overflow")?
+            / 10_000;
+        if reward > 0 {
+            let claimable = self.provider_claimable.entry(challenger).or_insert(0);
+            *claimable = claimable
+                .checked_add(reward)
+                .ok_or("challenger claimable overflow")?;
+        }
+        let rep = self.provider_reputation.entry(provider).or_insert(0);
+        *rep = (*rep).min(Self::MIN_PROVIDER_REPUTATION);
+
+        Ok(QuoteFraudSlash {
+            provider,
+            slashed,
+            reward,
+            reason,
+        })
+    }
+
+    /// Pay `amount` of a job's escrow to `provider` as a milestone, e.g. one
+    /// shard of a batch job.
+    ///
+    /// Only the requester releases milestones, and only before a result is
+    /// submitted. Once the whole payment is released the job completes.
+    pub fn release_milestone(
+        &mut self,
+        job_id: H256,
+        caller: Address,
+        provider: Address,
+        amount: u128,
+    ) -> Result<(), String> {
+        let job = self.jobs.get(&job_id).ok_or("job not found")?;
+        if caller != job.requester {
+            return Err("not job requester".to_string());
+        }
+        if provider == job.requester {
+            return Err("provider cannot be the same address as the job requester".to_string());
+        }
+        if !matches!(job.status, JobStatus::Posted | JobStatus::Accepted) {
+            return Err("job is not awaiting milestones".to_string());
+        }
+        if amount == 0 {
+            return Err("milestone must be non-zero".to_string());
+        }
+        if amount > job.payment {
+            return Err(format!(
+                "milestone {amount} exceeds the {} left in escrow",
+                job.payment
+            ));
+        }
+
+        self.release_requester_escrow(caller, amount)?;
+        let claimable = self.provider_claimable.entry(provider).or_insert(0);
+        *claimable = claimable
+            .checked_add(amount)
+            .ok_or("provider claimable overflow")?;
+        let job = self.jobs.get_mut(&job_id).ok_or("job not found")?;
+        job.payment -= amount;
+        job.released = job
+            .released
+            .checked_add(amount)
+            .ok_or("released overflow")?;
+        if job.payment == 0 {
+            job.status = JobStatus::Completed;
+            self.completed_jobs = self
+                .completed_jobs
+                .checked_add(1)
+                .ok_or("completed_jobs overflow")?;
+        }
+        Ok(())
+    }
+
+    fn release_requester_escrow(&mut self, requester: Address, amount: u128) -> Result<(), String> {
+        let escrowed = self
+            .requester_escrow
+            .get_mut(&requester)
+            .ok_or("missing requester escrow balance")?;
+        if *escrowed < amount {
+            return Err("insufficient requester escrow balance".to_string());
+        }
+        *escrowed = escrowed.checked_sub(amount).ok_or("escrow underflow")?;
+        if *escrowed == 0 {
+            self.requester_escrow.remove(&requester);
+        }
+        Ok(())
+    }
+
     /// Cancel job (refund requester)
     pub fn cancel_job(&mut self, job_id: H256, caller: Address) -> Result<(), String> {
         let (requester, payment) = {
@@ -312,18 +621,7 @@ impl JobEscrowState {
             (requester, payment)
         };
 
-        let escrowed = self
-            .requester_escrow
-            .get_mut(&requester)
-            .ok_or("missing requester escrow balance")?;
-        if *escrowed < payment {
-            return Err("insufficient requester escrow balance".to_string());
-        }
-        *escrowed = escrowed.checked_sub(payment).ok_or("escrow underflow")?;
-        let remove_requester_escrow = *escrowed == 0;
-        if remove_requester_escrow {
-            self.requester_escrow.remove(&requester);
-        }
+        self.release_requester_escrow(requester, payment)?;
         let job = self.jobs.get_mut(&job_id).ok_or("job not found")?;
         job.status = JobStatus::Cancelled;
 
@@ -345,6 +643,10 @@ impl JobEscrowState {
     pub fn claimable_balance_of(&self, provider: &Address) -> u128 {
         self.provider_claimable.get(provider).copied().unwrap_or(0)
     }
+
+    pub fn bond_of(&self, provider: &Address) -> u128 {
+        self.provider_bonds.get(provider).copied().unwrap_or(0)
+    }
 }
 
 impl Default for JobEscrowState {
@@ -357,7 +659,7 @@ impl Default for JobEscrowState {
 mod tests {
     use super::*;
     use aether_crypto_primitives::Keypair;
-    use aether_verifiers_tee::{AttestationReport, TeeType};
+    use aether_verifiers_tee::SimulatedSigner;
 
     fn addr(n: u8) -> Address {
         Address::from_slice(&[n; 20]).unwrap()
@@ -365,20 +667,26 @@ mod tests {
 
     /// Build a valid serialized VCR for use in tests.
     fn make_valid_vcr_bytes(job_id: H256) -> Vec<u8> {
+        let (_, vcr) = make_vcr(job_id, &SimulatedSigner::devnet());
+        serde_json::to_vec(&vcr).unwrap()
+    }
+
+    /// Build an optimistic VCR whose quote is signed by `quote_key`; returns
+    /// the serialized receipt and the withheld quote.
+    fn make_optimistic_vcr_bytes(job_id: H256, quote_key: &SimulatedSigner) -> (Vec<u8>, Vec<u8>) {
+        let (worker, mut vcr) = make_vcr(job_id, quote_key);
+        let quote = vcr.commit_quote();
+        vcr.signature = worker.sign(&vcr.signing_message());
+        (serde_json::to_vec(&vcr).unwrap(), quote)
+    }
+
+    fn make_vcr(job_id: H256, quote_key: &SimulatedSigner) -> (Keypair, VerifiableComputeReceipt) {
         use std::time::{SystemTime, UNIX_EPOCH};
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs();
         let worker = Keypair::generate();
-        let report = AttestationReport {
-            tee_type: TeeType::Simulation,
-            measurement: vec![1u8; 48],
-            nonce: vec![2u8; 32],
-            timestamp: now,
-            signature: vec![3u8; 64],
-            cert_chain: vec![vec![4u8; 16]],
-        };
         let kzg = aether_crypto_kzg::KzgVerifier::new_insecure_test(16);
         let mut coeffs = [[0u8; 32]; 2];
         coeffs[0][0] = 3;
@@ -388,6 +696,7 @@ mod tests {
         z[0] = 4;
         let proof = kzg.create_proof(&coeffs, &z).unwrap();
         let mut vcr = VerifiableComputeReceipt {
+            version: aether_verifiers_vcr::VCR_VERSION,
             job_id,
             worker_id: worker.public_key(),
             model_hash: H256::zero(),
@@ -397,29 +706,21 @@ mod tests {
             trace_proof: proof.proof,
             trace_evaluation: proof.evaluation,
             trace_point: z.to_vec(),
-            tee_attestation: serde_json::to_vec(&report).unwrap(),
+            tee_attestation: Vec::new(),
+            code_hash: vec![3u8; 32],
+            seed: 7,
             timestamp: now,
+            gas_used: 1_000,
+            extensions: Vec::new(),
             signature: Vec::new(),
         };
-        // Sign using the same signing_message logic exposed via verify
-        // (we replicate the hash construction used inside VerifiableComputeReceipt)
-        use sha2::{Digest, Sha256};
-        let mut hasher = Sha256::new();
-        hasher.update(b"VCR-v1");
-        hasher.update(vcr.job_id.as_bytes());
-        hasher.update(&vcr.worker_id);
-        hasher.update(vcr.model_hash.as_bytes());
-        hasher.update(vcr.input_hash.as_bytes());
-        hasher.update(vcr.output_hash.as_bytes());
-        hasher.update(&vcr.trace_commitment);
-        hasher.update(&vcr.trace_proof);
-        hasher.update(&vcr.trace_evaluation);
-        hasher.update(&vcr.trace_point);
-        hasher.update(&vcr.tee_attestation);
-        hasher.update(vcr.timestamp.to_le_bytes());
-        let msg: Vec<u8> = hasher.finalize().to_vec();
+        let report = quote_key
+            .quote(&[1u8; 48], &vcr.report_data(), now)
+            .unwrap();
+        vcr.tee_attestation = serde_json::to_vec(&report).unwrap();
+        let msg = vcr.signing_message();
         vcr.signature = worker.sign(&msg);
-        serde_json::to_vec(&vcr).unwrap()
+        (worker, vcr)
     }
 
     #[test]
@@ -457,7 +758,11 @@ mod tests {
         let mut state = JobEscrowState::new();
         let job_id = H256::zero();
         let vcr_bytes = make_valid_vcr_bytes(job_id);
-        let validator = VcrValidator::new_for_test();
+        let vcr: VerifiableComputeReceipt = serde_json::from_slice(&vcr_bytes).unwrap();
+        let mut validator = VcrValidator::new_for_test();
+        validator
+            .register_worker(vcr.worker_id.clone(), &vcr.worker_id)
+            .unwrap();
 
         state
             .post_job(job_id, addr(1), H256::zero(), H256::zero(), 1000, 100, 1000)
@@ -484,6 +789,73 @@ mod tests {
         assert_eq!(state.get_provider_reputation(&addr(2)), 1);
     }
 
+    #[test]
+    fn test_layer_opening_is_checked_on_chain() {
+        use aether_crypto_kzg::{aggregate_openings, scalar_from_i64, KzgVerifier};
+        use aether_verifiers_vcr::LayerOpening;
+
+        let kzg = KzgVerifier::new_insecure_test(16);
+        let layers: Vec<Vec<[u8; 32]>> = (0..4i64)
+            .map(|j| (0..3).map(|k| scalar_from_i64(j * 5 + k)).collect())
+            .collect();
+        let commitments: Vec<_> = layers.iter().map(|l| kzg.commit(l).unwrap()).collect();
+        let receipt = |job_id: H256, forge: bool| {
+            let (worker, mut vcr) = make_vcr(job_id, &SimulatedSigner::devnet());
+            let point: [u8; 32] = vcr.trace_point.as_slice().try_into().unwrap();
+            let mut opening = aggregate_openings(&kzg, &commitments, &layers, &point).unwrap();
+            if forge {
+                opening.evaluations[1] = scalar_from_i64(-1);
+            }
+            vcr.set_layer_opening(&LayerOpening {
+                commitments: commitments.clone(),
+                opening,
+            });
+            vcr.signature = worker.sign(&vcr.signing_message());
+            vcr
+        };
+
+        let mut state = JobEscrowState::new();
+        let mut validator = VcrValidator::new_for_test();
+        for (n, forge) in [(1u8, true), (2, false)] {
+            let job_id = H256::from([n; 32]);
+            let vcr = receipt(job_id, forge);
+            validator
+                .register_worker(vcr.worker_id.clone(), &vcr.worker_id)
+                .unwrap();
+            state
+                .post_job(job_id, addr(1), H256::zero(), H256::zero(), 1000, 100, 1000)
+                .unwrap();
+            state.accept_job(job_id, addr(2)).unwrap();
+            state
+                .submit_result(
+                    job_id,
+                    addr(2),
+                    H256::zero(),
+                    serde_json::to_vec(&vcr).unwrap(),
+                    150,
+                )
+                .unwrap();
+
+            // The escrow holds the aggregate with the receipt.
+            let stored: VerifiableComputeReceipt =
+                serde_json::from_slice(state.get_job(&job_id).unwrap().vcr_proof.as_ref().unwrap())
+                    .unwrap();
+            assert_eq!(
+                stored.layer_opening().unwrap().unwrap().commitments.len(),
+                4
+            );
+        }
+
+        let err = state
+            .verify_job(H256::from([1u8; 32]), 200, &validator)
+            .unwrap_err();
+        assert!(err.contains("aggregated layer opening"), "{err}");
+        assert!(state
+            .verify_job(H256::from([2u8; 32]), 200, &validator)
+            .unwrap()
+            .is_some());
+    }
+
     #[test]
     fn test_verify_job_rejects_invalid_vcr() {
         let mut state = JobEscrowState::new();
@@ -588,6 +960,270 @@ mod tests {
         state.cancel_job(job_id, addr(1)).unwrap();
         assert_eq!(state.escrowed_balance_of(&addr(1)), 0);
     }
+
+    #[test]
+    fn test_milestones_pay_shards_from_escrow() {
+        let mut state = JobEscrowState::new();
+        let job_id = H256::from_slice(&[1u8; 32]).unwrap();
+        state
+            .post_job(job_id, addr(1), H256::zero(), H256::zero(), 500, 100, 1000)
+            .unwrap();
+
+        assert!(state
+            .release_milestone(job_id, addr(2), addr(2), 100)
+            .is_err());
+        assert!(state
+            .release_milestone(job_id, addr(1), addr(1), 100)
+            .is_err());
+        assert!(state
+            .release_milestone(job_id, addr(1), addr(2), 0)
+            .is_err());
+
+        state
+            .release_milestone(job_id, addr(1), addr(2), 200)
+            .unwrap();
+        assert_eq!(state.claimable_balance_of(&addr(2)), 200);
+        assert_eq!(state.escrowed_balance_of(&addr(1)), 300);
+        let err = state
+            .release_milestone(job_id, addr(1), addr(3), 301)
+            .unwrap_err();
+        assert!(err.contains("exceeds"), "{err}");
+
+        // Cancelling refunds only what is still escrowed.
+        let mut cancelled = state.clone();
+        cancelled.cancel_job(job_id, addr(1)).unwrap();
+        assert_eq!(cancelled.escrowed_balance_of(&addr(1)), 0);
+        assert_eq!(cancelled.claimable_balance_of(&addr(2)), 200);
+
+        state
+            .release_milestone(job_id, addr(1), addr(3), 300)
+            .unwrap();
+        let job = state.get_job(&job_id).unwrap();
+        assert_eq!((job.payment, job.released), (0, 500));
+        assert_eq!(job.status, JobStatus::Completed);
+        assert_eq!(state.completed_jobs, 1);
+        assert_eq!(state.escrowed_balance_of(&addr(1)), 0);
+        assert!(state
+            .release_milestone(job_id, addr(1), addr(3), 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_resolve_dispute_refunds_requester_when_counter_upheld() {
+        use aether_verifiers_kzg::{KzgChallenge, KzgOpeningResponse, Opening};
+
+        let mut state = JobEscrowState::new();
+        let job_id = H256::zero();
+        let vcr_bytes = make_valid_vcr_bytes(job_id);
+        let vcr: VerifiableComputeReceipt = serde_json::from_slice(&vcr_bytes).unwrap();
+        let validator = VcrValidator::new_for_test();
+
+        state
+            .post_job(job_id, addr(1), H256::zero(), H256::zero(), 1000, 100, 1000)
+            .unwrap();
+        state.accept_job(job_id, addr(2)).unwrap();
+        state
+            .submit_result(job_id, addr(2), H256::zero(), vcr_bytes, 150)
+            .unwrap();
+
+        // The provider committed to 3 + x; the challenger and the referee to 4 + x.
+        let kzg = aether_crypto_kzg::KzgVerifier::new_insecure_test(16);
+        let open = |constant: u8| {
+            let mut coeffs = [[0u8; 32]; 2];
+            coeffs[0][0] = constant;
+            coeffs[1][0] = 1;
+            // Trace point 0 opens at x = 0.
+            let z = aether_crypto_kzg::scalar_from_i64(0);
+            let opening = Opening {
+                layer_idx: 0,
+                point_idx: 0,
+                point: z.to_vec(),
+                commitment: kzg.commit(&coeffs).unwrap(),
+                proof: kzg.create_proof(&coeffs, &z).unwrap(),
+            };
+            KzgOpeningResponse::new(job_id, vec![opening])
+        };
+        let (original, honest) = (open(3), open(4));
+        let counter = CounterVcr {
+            job_id,
+            output_hash: H256::from_slice(&[9u8; 32]).unwrap(),
+            trace_commitment: honest.openings[0].commitment.commitment.clone(),
+            code_hash: Vec::new(),
+            tee_quote: None,
+            slot_hash: Vec::new(),
+        };
+        let spot = SpotCheck {
+            challenge: KzgChallenge {
+                vcr_id: job_id,
+                layer_indices: vec![0],
+                point_indices: vec![vec![0]],
+                deadline_slot: 0,
+            },
+            expected: vec![honest.openings[0].proof.evaluation.clone()],
+            original,
+            counter: honest,
+        };
+        assert_eq!(
+            spot.original.openings[0].commitment.commitment,
+            vcr.trace_commitment
+        );
+
+        // Only disputed jobs can be resolved.
+        let err = state
+            .resolve_dispute(job_id, &counter, &spot, &validator)
+            .unwrap_err();
+        assert!(err.contains("not disputed"), "unexpected error: {err}");
+
+        state.challenge_job(job_id, addr(1)).unwrap();
+        let outcome = state
+            .resolve_dispute(job_id, &counter, &spot, &validator)
+            .unwrap();
+        assert_eq!(outcome, DisputeOutcome::CounterUpheld);
+        assert_eq!(state.get_job(&job_id).unwrap().status, JobStatus::Cancelled);
+        assert_eq!(state.escrowed_balance_of(&addr(1)), 0);
+        assert_eq!(state.claimable_balance_of(&addr(2)), 0);
+        assert_eq!(
+            state.get_provider_reputation(&addr(2)),
+            -JobEscrowState::DISPUTE_LOSS_PENALTY
+        );
+    }
+
+    /// Post, accept and submit `vcr_bytes` for a job from addr(1) to addr(2).
+    fn submitted_job(vcr_bytes: Vec<u8>) -> (JobEscrowState, VcrValidator, H256) {
+        let job_id = H256::zero();
+        let vcr: VerifiableComputeReceipt = serde_json::from_slice(&vcr_bytes).unwrap();
+        let mut validator = VcrValidator::new_for_test();
+        validator
+            .register_worker(vcr.worker_id.clone(), &vcr.worker_id)
+            .unwrap();
+        let mut state = JobEscrowState::new();
+        state
+            .post_job(job_id, addr(1), H256::zero(), H256::zero(), 1000, 100, 1000)
+            .unwrap();
+        state.accept_job(job_id, addr(2)).unwrap();
+        state
+            .submit_result(job_id, addr(2), H256::zero(), vcr_bytes, 150)
+            .unwrap();
+        (state, validator, job_id)
+    }
+
+    #[test]
+    fn test_optimistic_result_needs_bond() {
+        let (vcr_bytes, quote) =
+            make_optimistic_vcr_bytes(H256::zero(), &SimulatedSigner::devnet());
+        let (mut state, validator, job_id) = submitted_job(vcr_bytes);
+
+        let err = state.verify_job(job_id, 200, &validator).unwrap_err();
+        assert!(err.contains("bond"), "{err}");
+        state
+            .deposit_bond(addr(2), JobEscrowState::MIN_OPTIMISTIC_BOND)
+            .unwrap();
+        assert!(state.withdraw_bond(addr(2), 1).is_err());
+
+        // An honest quote does not make a fraud proof.
+        let proof = QuoteFraudProof { job_id, quote };
+        let err = state
+            .submit_quote_fraud_proof(&proof, addr(3), 155, &validator)
+            .unwrap_err();
+        assert!(err.contains("no fraud"), "{err}");
+
+        state.verify_job(job_id, 200, &validator).unwrap();
+        assert_eq!(state.claimable_balance_of(&addr(2)), 1000);
+        state.withdraw_bond(addr(2), 1).unwrap();
+        assert_eq!(
+            state.bond_of(&addr(2)),
+            JobEscrowState::MIN_OPTIMISTIC_BOND - 1
+        );
+    }
+
+    #[test]
+    fn test_quote_fraud_proof_slashes_provider() {
+        let (vcr_bytes, quote) =
+            make_optimistic_vcr_bytes(H256::zero(), &SimulatedSigner::from_seed(&[9u8; 32]));
+        let (mut state, validator, job_id) = submitted_job(vcr_bytes);
+        let bond = JobEscrowState::MIN_OPTIMISTIC_BOND;
+        state.deposit_bond(addr(2), bond).unwrap();
+
+        // Only the committed bytes count, and only from someone else.
+        let mut wrong = QuoteFraudProof {
+            job_id,
+            quote: quote.clone(),
+        };
+        wrong.quote.push(0);
+        let err = state
+            .submit_quote_fraud_proof(&wrong, addr(3), 155, &validator)
+            .unwrap_err();
+        assert!(err.contains("malformed"), "{err}");
+        let proof = QuoteFraudProof { job_id, quote };
+        assert!(state
+            .submit_quote_fraud_proof(&proof, addr(2), 155, &validator)
+            .is_err());
+        // Nor after the challenge period.
+        assert!(state
+            .submit_quote_fraud_proof(&proof, addr(3), 161, &validator)
+            .is_err());
+
+        // The proof travels as transaction data.
+        let proof: QuoteFraudProof =
+            serde_json::from_slice(&serde_json::to_vec(&proof).unwrap()).unwrap();
+        let slash = state
+            .submit_quote_fraud_proof(&proof, addr(3), 160, &validator)
+            .unwrap();
+        assert_eq!(slash.provider, addr(2));
+        assert_eq!(slash.slashed, bond);
+        assert_eq!(slash.reward, bond / 2);
+        assert_eq!(state.claimable_balance_of(&addr(3)), bond / 2);
+        assert_eq!(state.bond_of(&addr(2)), 0);
+        assert_eq!(state.get_job(&job_id).unwrap().status, JobStatus::Cancelled);
+        assert_eq!(state.escrowed_balance_of(&addr(1)), 0);
+        assert_eq!(state.claimable_balance_of(&addr(2)), 0);
+        assert_eq!(
+            state.get_provider_reputation(&addr(2)),
+            JobEscrowState::MIN_PROVIDER_REPUTATION
+        );
+        assert!(state.verify_job(job_id, 200, &validator).is_err());
+    }
+
+    fn fraud_report(job_id: H256, worker_id: Vec<u8>) -> FraudReport {
+        use aether_verifiers_kzg::{FraudReason, IssuedChallenge, KzgChallenge};
+        FraudReport {
+            vcr_id: job_id,
+            worker_id,
+            challenge: IssuedChallenge {
+                challenge: KzgChallenge {
+                    vcr_id: job_id,
+                    layer_indices: vec![0],
+                    point_indices: vec![vec![1]],
+                    deadline_slot: 155,
+                },
+                watchtower: [4u8; 32],
+            },
+            response: None,
+            reason: FraudReason::NoResponse,
+            slot: 156,
+        }
+    }
+
+    #[test]
+    fn test_watchtower_fraud_report_disputes_job() {
+        let vcr_bytes = make_valid_vcr_bytes(H256::zero());
+        let vcr: VerifiableComputeReceipt = serde_json::from_slice(&vcr_bytes).unwrap();
+        let (mut state, validator, job_id) = submitted_job(vcr_bytes);
+
+        // Against the receipt's worker, from someone else, in time.
+        let report = fraud_report(job_id, vec![7u8; 32]);
+        let err = state.report_fraud(&report, addr(3), 156).unwrap_err();
+        assert!(err.contains("different worker"), "{err}");
+        let report = fraud_report(job_id, vcr.worker_id.clone());
+        assert!(state.report_fraud(&report, addr(2), 156).is_err());
+        assert!(state.report_fraud(&report, addr(3), 161).is_err());
+
+        state.report_fraud(&report, addr(3), 156).unwrap();
+        assert_eq!(state.get_job(&job_id).unwrap().status, JobStatus::Disputed);
+        assert_eq!(state.fraud_reports[&job_id].0, addr(3));
+        assert!(state.verify_job(job_id, 200, &validator).is_err());
+        assert!(state.report_fraud(&report, addr(4), 157).is_err());
+    }
 }
 
 #[cfg(test)]
@@ -13,6 +13,9 @@
 // - aeth_getAccount: Get account state
 // - aeth_getSlotNumber: Get current slot
 // - aeth_getFinalizedSlot: Get last finalized slot
+// - aeth_getEvidence: Get pooled equivocation evidence (for watchtowers)
+// - aeth_getFinalityCertificates: Get checkpoint finality proofs (for light clients)
+// - aeth_getLeaderSchedule / aeth_getUpcomingLeaders: Leader lookahead (for explorers)
 //
 // ENDPOINT: http://localhost:8545
 // ============================================================================
@@ -21,4 +24,5 @@ pub mod server;
 
 pub use server::{
     JsonRpcError, JsonRpcRequest, JsonRpcResponse, JsonRpcServer, RateLimiter, RpcBackend,
+    SubscriptionManager,
 };
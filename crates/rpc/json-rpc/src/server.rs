@@ -1,7 +1,8 @@
+use aether_ledger::FeeHistory;
 use aether_metrics::RPC_METRICS;
 use aether_types::{
-    Address, Block, PublicKey, Signature, Transaction, TransactionReceipt, TransferPayload, H256,
-    TRANSFER_PROGRAM_ID,
+    Address, Block, FinalityPath, PublicKey, Signature, Transaction, TransactionReceipt,
+    TransferPayload, TxLifecycle, H256, TRANSFER_PROGRAM_ID,
 };
 use anyhow::Result;
 use futures::{SinkExt, StreamExt};
@@ -167,6 +168,44 @@ pub trait RpcBackend: Send + Sync {
     fn get_sync_status(&self) -> Result<Value> {
         Ok(json!({"syncing": false}))
     }
+    /// Equivocation evidence pooled for slots from `from_slot` on.
+    fn get_evidence(&self, _from_slot: u64) -> Result<Value> {
+        Ok(json!([]))
+    }
+    /// Finality certificates emitted for slots from `from_slot` on.
+    fn get_finality_certificates(&self, _from_slot: u64) -> Result<Value> {
+        Ok(json!([]))
+    }
+    /// Whether `slot` was finalized in one round or by the two-chain rule.
+    fn get_finality_path(&self, _slot: u64) -> Result<Option<FinalityPath>> {
+        Ok(None)
+    }
+    /// Leader rotation of `epoch`, empty if leaders are not public.
+    fn get_leader_schedule(&self, _epoch: u64) -> Result<Value> {
+        Ok(json!([]))
+    }
+    /// Known leaders of up to `count` upcoming slots.
+    fn get_upcoming_leaders(&self, _count: usize) -> Result<Value> {
+        Ok(json!([]))
+    }
+    /// Base fee per gas the next block will charge.
+    fn get_base_fee(&self) -> Result<u128> {
+        Ok(0)
+    }
+    /// Priority fee per gas suggested from recent blocks.
+    fn get_max_priority_fee(&self) -> Result<u128> {
+        Ok(0)
+    }
+    /// Fees of up to `block_count` recent blocks, with priority fees at
+    /// each of `percentiles`.
+    fn get_fee_history(&self, _block_count: usize, _percentiles: &[u8]) -> Result<FeeHistory> {
+        Ok(FeeHistory::default())
+    }
+    /// Execution trace of a transaction, re-run with tracing on; `None` if
+    /// the transaction is unknown.
+    fn trace_transaction(&self, _tx_hash: H256) -> Result<Option<Value>> {
+        Err(anyhow::anyhow!("transaction tracing not enabled"))
+    }
     fn allows_airdrop(&self) -> bool {
         false
     }
@@ -181,8 +220,13 @@ pub enum SubscriptionTopic {
     NewBlocks,
     NewTransactions,
     Finality,
+    /// Lifecycle of individual transactions a client asked to watch.
+    TxStatus,
 }
 
+/// Transactions a single WebSocket client may watch at once.
+const MAX_WATCHED_TXS: usize = 1024;
+
 /// Event broadcast to WebSocket subscribers.
 #[derive(Debug, Clone, Serialize)]
 pub struct SubscriptionEvent {
@@ -232,6 +276,18 @@ impl SubscriptionManager {
         }
     }
 
+    /// Broadcast a transaction status change. Only clients watching
+    /// `tx_hash` via `aeth_subscribeTxStatus` receive it.
+    pub fn notify_tx_status(&self, tx_hash: H256, status: &TxLifecycle) {
+        let event = SubscriptionEvent {
+            topic: "txStatus".to_string(),
+            data: tx_status_json(tx_hash, status),
+        };
+        if let Err(e) = self.sender.send(event) {
+            tracing::debug!("No active subscribers for tx status event: {e}");
+        }
+    }
+
     /// Get a new subscriber receiver.
     pub fn subscribe(&self) -> broadcast::Receiver<SubscriptionEvent> {
         self.sender.subscribe()
@@ -463,6 +519,100 @@ async fn handle_rejection(err: warp::Rejection) -> Result<impl Reply, warp::Reje
     }
 }
 
+fn hex_hash(hash: H256) -> String {
+    format!("0x{}", hex::encode(hash.as_bytes()))
+}
+
+fn tx_status_json(tx_hash: H256, status: &TxLifecycle) -> Value {
+    let mut data = match status {
+        TxLifecycle::Pending => json!({ "status": "pending" }),
+        TxLifecycle::Included { slot, block_hash } => json!({
+            "status": "included",
+            "slot": slot,
+            "blockHash": hex_hash(*block_hash),
+        }),
+        TxLifecycle::Finalized { slot } => json!({ "status": "finalized", "slot": slot }),
+        TxLifecycle::Dropped { reason } => json!({ "status": "dropped", "reason": reason }),
+        TxLifecycle::Replaced { by } => json!({ "status": "replaced", "by": hex_hash(*by) }),
+    };
+    data["txHash"] = json!(hex_hash(tx_hash));
+    data
+}
+
+/// Apply a client's `aeth_subscribeTxStatus` / `aeth_unsubscribeTxStatus`
+/// request to the set of transactions it watches.
+fn handle_ws_command(text: &str, watched: &mut HashSet<H256>) -> JsonRpcResponse {
+    let request: JsonRpcRequest = match serde_json::from_str(text) {
+        Ok(request) => request,
+        Err(e) => {
+            return JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                result: None,
+                error: Some(JsonRpcError {
+                    code: -32700,
+                    message: format!("Parse error: {e}"),
+                    data: None,
+                }),
+                id: Value::Null,
+            }
+        }
+    };
+
+    let result = match request.method.as_str() {
+        "aeth_subscribeTxStatus" => parse_tx_hash(&request.params).and_then(|hash| {
+            if watched.len() >= MAX_WATCHED_TXS && !watched.contains(&hash) {
+                return Err(JsonRpcError {
+                    code: -32005,
+                    message: format!("at most {MAX_WATCHED_TXS} transactions can be watched"),
+                    data: None,
+                });
+            }
+            watched.insert(hash);
+            Ok(json!(true))
+        }),
+        "aeth_unsubscribeTxStatus" => {
+            parse_tx_hash(&request.params).map(|hash| json!(watched.remove(&hash)))
+        }
+        method => Err(JsonRpcError {
+            code: -32601,
+            message: format!("Method not found: {method}"),
+            data: None,
+        }),
+    };
+
+    let (result, error) = match result {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e)),
+    };
+    JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        result,
+        error,
+        id: request.id,
+    }
+}
+
+/// Whether a client gets `event`. Status events go only to clients watching
+/// the transaction, and a terminal status ends the watch.
+fn should_forward(event: &SubscriptionEvent, watched: &mut HashSet<H256>) -> bool {
+    if event.topic != "txStatus" {
+        return true;
+    }
+    let Ok(hash) = parse_tx_hash(std::slice::from_ref(&event.data["txHash"])) else {
+        return false;
+    };
+    if !watched.contains(&hash) {
+        return false;
+    }
+    if matches!(
+        event.data["status"].as_str(),
+        Some("finalized" | "dropped" | "replaced")
+    ) {
+        watched.remove(&hash);
+    }
+    true
+}
+
 async fn handle_ws_connection(
     ws: WebSocket,
     subs: Arc<SubscriptionManager>,
@@ -479,13 +629,37 @@ async fn handle_ws_connection(
 
     let (mut ws_tx, mut ws_rx) = ws.split();
     let mut rx = subs.subscribe();
+    let watched = Arc::new(std::sync::Mutex::new(HashSet::new()));
+    let (reply_tx, mut reply_rx) = tokio::sync::mpsc::channel::<JsonRpcResponse>(16);
 
-    // Spawn task to forward subscription events to this WebSocket client
+    // Spawn task to forward subscription events and command replies to this
+    // WebSocket client
     let timeout_duration = Duration::from_secs(300); // 5 minute idle timeout
+    let send_watched = watched.clone();
     let send_task = tokio::spawn(async move {
         loop {
-            match tokio::time::timeout(timeout_duration, rx.recv()).await {
-                Ok(Ok(event)) => {
+            let next = tokio::select! {
+                event = rx.recv() => event.map(|event| {
+                    let mut watched = send_watched.lock().unwrap_or_else(|e| e.into_inner());
+                    should_forward(&event, &mut watched).then_some(event)
+                }),
+                Some(reply) = reply_rx.recv() => {
+                    let Ok(msg) = serde_json::to_string(&reply) else {
+                        continue;
+                    };
+                    if ws_tx.send(Message::text(msg)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                _ = tokio::time::sleep(timeout_duration) => {
+                    // Idle timeout reached, close connection
+                    tracing::info!("WebSocket idle timeout reached, closing connection");
+                    break;
+                }
+            };
+            match next {
+                Ok(Some(event)) => {
                     let msg = match serde_json::to_string(&event) {
                         Ok(msg) => msg,
                         Err(e) => {
@@ -497,12 +671,11 @@ async fn handle_ws_connection(
                         break; // Client disconnected
                     }
                 }
-                Ok(Err(_)) => break, // Channel closed
-                Err(_) => {
-                    // Idle timeout reached, close connection
-                    tracing::info!("WebSocket idle timeout reached, closing connection");
-                    break;
+                Ok(None) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(skipped, "WebSocket client lagging, events dropped");
                 }
+                Err(broadcast::error::RecvError::Closed) => break,
             }
         }
     });
@@ -512,7 +685,16 @@ async fn handle_ws_connection(
         if msg.is_close() {
             break;
         }
-        // For now, just accept the connection. Future: parse subscribe/unsubscribe messages.
+        let Ok(text) = msg.to_str() else {
+            continue;
+        };
+        let reply = {
+            let mut watched = watched.lock().unwrap_or_else(|e| e.into_inner());
+            handle_ws_command(text, &mut watched)
+        };
+        if reply_tx.send(reply).await.is_err() {
+            break;
+        }
     }
 
     send_task.abort();
@@ -585,6 +767,16 @@ async fn process_rpc_request<B: RpcBackend>(
         "aeth_getAccount" => handle_get_account(&req.params, backend).await,
         "aeth_getSlotNumber" => handle_get_slot_number(backend).await,
         "aeth_getFinalizedSlot" => handle_get_finalized_slot(backend).await,
+        "aeth_getEvidence" => handle_get_evidence(&req.params, backend).await,
+        "aeth_getFinalityCertificates" => {
+            handle_get_finality_certificates(&req.params, backend).await
+        }
+        "aeth_getLeaderSchedule" => handle_get_leader_schedule(&req.params, backend).await,
+        "aeth_getUpcomingLeaders" => handle_get_upcoming_leaders(&req.params, backend).await,
+        "aeth_gasPrice" => handle_gas_price(backend).await,
+        "aeth_maxPriorityFeePerGas" => handle_max_priority_fee(backend).await,
+        "aeth_feeHistory" => handle_fee_history(&req.params, backend).await,
+        "debug_traceTransaction" => handle_trace_transaction(&req.params, backend).await,
         "aeth_requestAirdrop" => handle_request_airdrop(&req.params, backend).await,
         "aeth_health" => handle_health(backend).await,
         _ => Err(JsonRpcError {
@@ -791,7 +983,27 @@ async fn handle_get_block_by_number<B: RpcBackend>(
             data: None,
         })?;
 
-    Ok(json!(block))
+    block_json(block, &*backend)
+}
+
+/// A block as JSON with a `finality` field: how its slot became final
+/// (`"fast"` or `"two_chain"`), or null while it is not.
+fn block_json<B: RpcBackend>(block: Option<Block>, backend: &B) -> Result<Value, JsonRpcError> {
+    let Some(block) = block else {
+        return Ok(Value::Null);
+    };
+    let finality = backend
+        .get_finality_path(block.header.slot)
+        .map_err(|e| JsonRpcError {
+            code: -32000,
+            message: format!("Failed to get finality path: {}", e),
+            data: None,
+        })?;
+    let mut value = json!(block);
+    if let Value::Object(fields) = &mut value {
+        fields.insert("finality".to_string(), json!(finality));
+    }
+    Ok(value)
 }
 
 fn parse_address(value: &str, field: &str) -> Result<Address, JsonRpcError> {
@@ -884,13 +1096,10 @@ async fn handle_get_block_by_hash<B: RpcBackend>(
             data: None,
         })?;
 
-    Ok(json!(block))
+    block_json(block, &*backend)
 }
 
-async fn handle_get_transaction_receipt<B: RpcBackend>(
-    params: &[Value],
-    backend: Arc<RwLock<B>>,
-) -> Result<Value, JsonRpcError> {
+fn parse_tx_hash(params: &[Value]) -> Result<H256, JsonRpcError> {
     if params.is_empty() {
         return Err(JsonRpcError {
             code: -32602,
@@ -914,12 +1123,18 @@ async fn handle_get_transaction_receipt<B: RpcBackend>(
         data: None,
     })?;
 
-    let tx_hash = H256::from_slice(&hash_bytes).map_err(|e| JsonRpcError {
+    H256::from_slice(&hash_bytes).map_err(|e| JsonRpcError {
         code: -32602,
         message: format!("Invalid hash length for '{}': {}", hash_hex, e),
         data: None,
-    })?;
+    })
+}
 
+async fn handle_get_transaction_receipt<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    let tx_hash = parse_tx_hash(params)?;
     let backend = backend.read().await;
     let receipt = backend
         .get_transaction_receipt(tx_hash)
@@ -932,6 +1147,24 @@ async fn handle_get_transaction_receipt<B: RpcBackend>(
     Ok(json!(receipt))
 }
 
+/// Host calls, gas per program and state accesses of an executed transaction.
+async fn handle_trace_transaction<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    let tx_hash = parse_tx_hash(params)?;
+    let backend = backend.read().await;
+    let trace = backend
+        .trace_transaction(tx_hash)
+        .map_err(|e| JsonRpcError {
+            code: -32000,
+            message: format!("Failed to trace transaction: {}", e),
+            data: None,
+        })?;
+
+    Ok(trace.unwrap_or(Value::Null))
+}
+
 async fn handle_get_state_root<B: RpcBackend>(
     params: &[Value],
     backend: Arc<RwLock<B>>,
@@ -1023,6 +1256,165 @@ async fn handle_get_finalized_slot<B: RpcBackend>(
     Ok(json!(slot))
 }
 
+/// Leading integer parameter, `default` when absent or null.
+fn parse_u64_param(params: &[Value], default: u64, expected: &str) -> Result<u64, JsonRpcError> {
+    match params.first() {
+        None | Some(Value::Null) => Ok(default),
+        Some(value) => value.as_u64().ok_or_else(|| JsonRpcError {
+            code: -32602,
+            message: format!("Invalid parameter type: expected {expected}, got {value}"),
+            data: None,
+        }),
+    }
+}
+
+/// Optional leading slot parameter; absent or null means from genesis.
+fn parse_from_slot(params: &[Value]) -> Result<u64, JsonRpcError> {
+    parse_u64_param(params, 0, "slot number")
+}
+
+async fn handle_get_evidence<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    let from_slot = parse_from_slot(params)?;
+    let backend = backend.read().await;
+    backend.get_evidence(from_slot).map_err(|e| JsonRpcError {
+        code: -32000,
+        message: format!("Failed to get evidence: {}", e),
+        data: None,
+    })
+}
+
+async fn handle_get_finality_certificates<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    let from_slot = parse_from_slot(params)?;
+    let backend = backend.read().await;
+    backend
+        .get_finality_certificates(from_slot)
+        .map_err(|e| JsonRpcError {
+            code: -32000,
+            message: format!("Failed to get finality certificates: {}", e),
+            data: None,
+        })
+}
+
+async fn handle_get_leader_schedule<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    if matches!(params.first(), None | Some(Value::Null)) {
+        return Err(JsonRpcError {
+            code: -32602,
+            message: "Missing parameter: epoch".to_string(),
+            data: None,
+        });
+    }
+    let epoch = parse_u64_param(params, 0, "epoch number")?;
+    let backend = backend.read().await;
+    backend
+        .get_leader_schedule(epoch)
+        .map_err(|e| JsonRpcError {
+            code: -32000,
+            message: format!("Failed to get leader schedule: {}", e),
+            data: None,
+        })
+}
+
+/// Upcoming leaders returned when the caller does not say how many.
+const DEFAULT_UPCOMING_LEADERS: u64 = 16;
+/// Most upcoming leaders one call returns.
+const MAX_UPCOMING_LEADERS: u64 = 256;
+
+async fn handle_get_upcoming_leaders<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    let count = parse_u64_param(params, DEFAULT_UPCOMING_LEADERS, "leader count")?
+        .min(MAX_UPCOMING_LEADERS);
+    let backend = backend.read().await;
+    backend
+        .get_upcoming_leaders(count as usize)
+        .map_err(|e| JsonRpcError {
+            code: -32000,
+            message: format!("Failed to get upcoming leaders: {}", e),
+            data: None,
+        })
+}
+
+fn fee_error(e: impl std::fmt::Display) -> JsonRpcError {
+    JsonRpcError {
+        code: -32000,
+        message: format!("Failed to get fee data: {}", e),
+        data: None,
+    }
+}
+
+/// Base fee plus the suggested priority fee, per gas.
+async fn handle_gas_price<B: RpcBackend>(backend: Arc<RwLock<B>>) -> Result<Value, JsonRpcError> {
+    let backend = backend.read().await;
+    let base_fee = backend.get_base_fee().map_err(fee_error)?;
+    let priority_fee = backend.get_max_priority_fee().map_err(fee_error)?;
+    serde_json::to_value(base_fee.saturating_add(priority_fee)).map_err(fee_error)
+}
+
+async fn handle_max_priority_fee<B: RpcBackend>(
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    let backend = backend.read().await;
+    let priority_fee = backend.get_max_priority_fee().map_err(fee_error)?;
+    serde_json::to_value(priority_fee).map_err(fee_error)
+}
+
+/// Most blocks one `aeth_feeHistory` call covers.
+const MAX_FEE_HISTORY_BLOCKS: u64 = 1024;
+/// Most reward percentiles one `aeth_feeHistory` call asks for.
+const MAX_FEE_HISTORY_PERCENTILES: usize = 100;
+
+/// Params: block count, then an optional array of percentiles in 0..=100.
+async fn handle_fee_history<B: RpcBackend>(
+    params: &[Value],
+    backend: Arc<RwLock<B>>,
+) -> Result<Value, JsonRpcError> {
+    if matches!(params.first(), None | Some(Value::Null)) {
+        return Err(JsonRpcError {
+            code: -32602,
+            message: "Missing parameter: block count".to_string(),
+            data: None,
+        });
+    }
+    let block_count = parse_u64_param(params, 0, "block count")?.min(MAX_FEE_HISTORY_BLOCKS);
+    let invalid_percentiles = || {
+        JsonRpcError {
+        code: -32602,
+        message: format!(
+            "Invalid percentiles: expected at most {MAX_FEE_HISTORY_PERCENTILES} integers in 0..=100"
+        ),
+        data: None,
+    }
+    };
+    let percentiles: Vec<u8> = match params.get(1) {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(values)) if values.len() <= MAX_FEE_HISTORY_PERCENTILES => values
+            .iter()
+            .map(|v| {
+                v.as_u64()
+                    .filter(|p| *p <= 100)
+                    .map(|p| p as u8)
+                    .ok_or_else(invalid_percentiles)
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err(invalid_percentiles()),
+    };
+    let backend = backend.read().await;
+    let history = backend
+        .get_fee_history(block_count as usize, &percentiles)
+        .map_err(fee_error)?;
+    serde_json::to_value(history).map_err(fee_error)
+}
+
 async fn handle_request_airdrop<B: RpcBackend>(
     params: &[Value],
     backend: Arc<RwLock<B>>,
@@ -1172,6 +1564,290 @@ mod tests {
         assert_eq!(req.jsonrpc, "2.0");
     }
 
+    #[tokio::test]
+    async fn test_get_evidence_defaults_to_empty_and_checks_params() {
+        let backend = Arc::new(RwLock::new(MockBackend::default()));
+        let req = |params| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "aeth_getEvidence".to_string(),
+            params,
+            id: json!(1),
+        };
+
+        let response = process_rpc_request(req(vec![json!(5)]), backend.clone(), 100_u64).await;
+        assert_eq!(response.result, Some(json!([])));
+        let response = process_rpc_request(req(vec![json!("x")]), backend, 100_u64).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+    }
+
+    #[tokio::test]
+    async fn test_leader_schedule_requires_epoch() {
+        let backend = Arc::new(RwLock::new(MockBackend::default()));
+        let req = |method: &str, params| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: json!(1),
+        };
+
+        let response = process_rpc_request(
+            req("aeth_getLeaderSchedule", vec![]),
+            backend.clone(),
+            100_u64,
+        )
+        .await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let response = process_rpc_request(
+            req("aeth_getLeaderSchedule", vec![json!(3)]),
+            backend.clone(),
+            100_u64,
+        )
+        .await;
+        assert_eq!(response.result, Some(json!([])));
+        let response =
+            process_rpc_request(req("aeth_getUpcomingLeaders", vec![]), backend, 100_u64).await;
+        assert_eq!(response.result, Some(json!([])));
+    }
+
+    #[tokio::test]
+    async fn test_fee_endpoints() {
+        let backend = Arc::new(RwLock::new(MockBackend::default()));
+        let req = |method: &str, params| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: json!(1),
+        };
+
+        let response = process_rpc_request(req("aeth_gasPrice", vec![]), backend.clone(), 1).await;
+        assert_eq!(response.result, Some(json!(0)));
+        let response =
+            process_rpc_request(req("aeth_maxPriorityFeePerGas", vec![]), backend.clone(), 1).await;
+        assert_eq!(response.result, Some(json!(0)));
+
+        let response =
+            process_rpc_request(req("aeth_feeHistory", vec![]), backend.clone(), 1).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let response = process_rpc_request(
+            req("aeth_feeHistory", vec![json!(4), json!([10, 101])]),
+            backend.clone(),
+            1,
+        )
+        .await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let response = process_rpc_request(
+            req("aeth_feeHistory", vec![json!(4), json!([10, 90])]),
+            backend,
+            1,
+        )
+        .await;
+        let history = response.result.unwrap();
+        assert_eq!(history["baseFeePerGas"], json!([]));
+        assert_eq!(history["oldestSlot"], json!(0));
+    }
+
+    #[tokio::test]
+    async fn test_trace_transaction_is_opt_in() {
+        let backend = Arc::new(RwLock::new(MockBackend::default()));
+        let req = |params| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "debug_traceTransaction".to_string(),
+            params,
+            id: json!(1),
+        };
+
+        let response = process_rpc_request(req(vec![]), backend.clone(), 1).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+        let hash = format!("0x{}", "ab".repeat(32));
+        let response = process_rpc_request(req(vec![json!(hash)]), backend, 1).await;
+        let error = response.error.unwrap();
+        assert_eq!(error.code, -32000);
+        assert!(error.message.contains("not enabled"));
+    }
+
+    /// Replays one known transaction's WASM program with tracing on, the
+    /// way the node backend does.
+    struct MockTracingBackend {
+        vm: std::sync::Mutex<aether_runtime::WasmVm>,
+        program: Vec<u8>,
+        tx_hash: H256,
+    }
+
+    impl RpcBackend for MockTracingBackend {
+        fn send_raw_transaction(&self, _tx_bytes: Vec<u8>) -> Result<H256> {
+            Ok(H256::zero())
+        }
+
+        fn get_block_by_number(&self, _block_number: u64, _full_tx: bool) -> Result<Option<Block>> {
+            Ok(None)
+        }
+
+        fn get_block_by_hash(&self, _block_hash: H256, _full_tx: bool) -> Result<Option<Block>> {
+            Ok(None)
+        }
+
+        fn get_transaction_receipt(&self, _tx_hash: H256) -> Result<Option<TransactionReceipt>> {
+            Ok(None)
+        }
+
+        fn get_state_root(&self, _block_ref: Option<String>) -> Result<H256> {
+            Ok(H256::zero())
+        }
+
+        fn get_account(
+            &self,
+            _address: Address,
+            _block_ref: Option<String>,
+        ) -> Result<Option<Value>> {
+            Ok(None)
+        }
+
+        fn get_slot_number(&self) -> Result<u64> {
+            Ok(0)
+        }
+
+        fn get_finalized_slot(&self) -> Result<u64> {
+            Ok(0)
+        }
+
+        fn trace_transaction(&self, tx_hash: H256) -> Result<Option<Value>> {
+            use aether_runtime::{host_functions, ExecutionContext, HostFunctions};
+
+            if tx_hash != self.tx_hash {
+                return Ok(None);
+            }
+            let context = ExecutionContext {
+                contract_address: Address::from_slice(&[1u8; 20]).unwrap(),
+                caller: Address::from_slice(&[2u8; 20]).unwrap(),
+                value: 0,
+                gas_limit: 100_000,
+                block_number: 1,
+                timestamp: 1000,
+            };
+            let mut host = HostFunctions::new(
+                context.gas_limit,
+                host_functions::ExecutionContext::default(),
+            )
+            .with_tracing();
+            let result = self.vm.lock().unwrap().execute_with_host(
+                &self.program,
+                &context,
+                b"key",
+                &mut host,
+            )?;
+            Ok(result.trace.map(serde_json::to_value).transpose()?)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_transaction_returns_execution_trace() {
+        let program = wat::parse_str(
+            r#"(module
+                (import "env" "storage_write" (func $sw (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                    (drop (call $sw (local.get $ptr) (local.get $len) (local.get $ptr) (local.get $len)))
+                    i32.const 0
+                )
+            )"#,
+        )
+        .unwrap();
+        let tx_hash = H256::from_slice(&[0xab; 32]).unwrap();
+        let backend = Arc::new(RwLock::new(MockTracingBackend {
+            vm: std::sync::Mutex::new(aether_runtime::WasmVm::new(100_000).unwrap()),
+            program,
+            tx_hash,
+        }));
+        let req = |hash: String| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "debug_traceTransaction".to_string(),
+            params: vec![json!(hash)],
+            id: json!(1),
+        };
+
+        let response =
+            process_rpc_request(req(format!("0x{}", "ab".repeat(32))), backend.clone(), 1).await;
+        assert!(response.error.is_none(), "{:?}", response.error);
+        let trace = response.result.unwrap();
+        let calls = trace["hostCalls"].as_array().unwrap();
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0]["name"], "storage_write");
+        // 5000 base plus 20 per value byte.
+        assert_eq!(calls[0]["gas"], 5060);
+        assert_eq!(calls[0]["error"], Value::Null);
+        assert_eq!(trace["stateAccesses"][0]["kind"], "storageWrite");
+        assert_eq!(trace["stateAccesses"][0]["key"], json!([107, 101, 121]));
+        assert!(trace["gasUsed"].as_u64().unwrap() > 5060);
+        assert_eq!(trace["sections"][0]["success"], true);
+
+        let response = process_rpc_request(req(format!("0x{}", "cd".repeat(32))), backend, 1).await;
+        assert_eq!(response.result, Some(Value::Null));
+    }
+
+    #[test]
+    fn test_ws_tx_status_watch() {
+        let subs = SubscriptionManager::new();
+        let mut rx = subs.subscribe();
+        let mut watched = HashSet::new();
+        let hash = H256::from_slice(&[0xab; 32]).unwrap();
+        let other = H256::from_slice(&[0xcd; 32]).unwrap();
+        let command = |method: &str| {
+            json!({"jsonrpc": "2.0", "method": method, "params": [hex_hash(hash)], "id": 7})
+                .to_string()
+        };
+
+        let reply = handle_ws_command(&command("aeth_subscribeTxStatus"), &mut watched);
+        assert_eq!(reply.result, Some(json!(true)));
+        assert_eq!(reply.id, json!(7));
+        let reply = handle_ws_command("not json", &mut watched);
+        assert_eq!(reply.error.unwrap().code, -32700);
+
+        subs.notify_tx_status(other, &TxLifecycle::Pending);
+        subs.notify_tx_status(
+            hash,
+            &TxLifecycle::Included {
+                slot: 4,
+                block_hash: other,
+            },
+        );
+        subs.notify_tx_status(hash, &TxLifecycle::Finalized { slot: 4 });
+        subs.notify_tx_status(hash, &TxLifecycle::Finalized { slot: 4 });
+
+        let skipped = rx.try_recv().unwrap();
+        assert!(!should_forward(&skipped, &mut watched));
+        let included = rx.try_recv().unwrap();
+        assert!(should_forward(&included, &mut watched));
+        assert_eq!(included.data["status"], "included");
+        assert_eq!(included.data["blockHash"], hex_hash(other));
+        assert_eq!(included.data["txHash"], hex_hash(hash));
+        assert!(should_forward(&rx.try_recv().unwrap(), &mut watched));
+        assert!(
+            !should_forward(&rx.try_recv().unwrap(), &mut watched),
+            "a terminal status ends the watch"
+        );
+
+        watched.insert(hash);
+        let reply = handle_ws_command(&command("aeth_unsubscribeTxStatus"), &mut watched);
+        assert_eq!(reply.result, Some(json!(true)));
+        assert!(watched.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_finality_certificates_defaults_to_empty() {
+        let backend = Arc::new(RwLock::new(MockBackend::default()));
+        let req = |params| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "aeth_getFinalityCertificates".to_string(),
+            params,
+            id: json!(1),
+        };
+
+        let response = process_rpc_request(req(vec![]), backend.clone(), 100_u64).await;
+        assert_eq!(response.result, Some(json!([])));
+        let response = process_rpc_request(req(vec![json!(-1)]), backend, 100_u64).await;
+        assert_eq!(response.error.unwrap().code, -32602);
+    }
+
     #[tokio::test]
     async fn test_get_slot_number() {
         let backend = Arc::new(RwLock::new(MockBackend::default()));
@@ -1320,6 +1996,102 @@ mod tests {
         assert_eq!(result["sync"]["syncing"], false);
     }
 
+    struct MockFinalizedBackend;
+
+    impl RpcBackend for MockFinalizedBackend {
+        fn send_raw_transaction(&self, _tx_bytes: Vec<u8>) -> Result<H256> {
+            Ok(H256::zero())
+        }
+
+        fn get_block_by_number(&self, block_number: u64, _full_tx: bool) -> Result<Option<Block>> {
+            let vrf_proof = aether_types::VrfProof {
+                output: [0u8; 32],
+                proof: vec![],
+            };
+            Ok(Some(Block::new(
+                block_number,
+                H256::zero(),
+                Address::from_slice(&[1u8; 20]).unwrap(),
+                vrf_proof,
+                vec![],
+            )))
+        }
+
+        fn get_block_by_hash(&self, _block_hash: H256, _full_tx: bool) -> Result<Option<Block>> {
+            Ok(None)
+        }
+
+        fn get_transaction_receipt(&self, _tx_hash: H256) -> Result<Option<TransactionReceipt>> {
+            Ok(None)
+        }
+
+        fn get_state_root(&self, _block_ref: Option<String>) -> Result<H256> {
+            Ok(H256::zero())
+        }
+
+        fn get_account(
+            &self,
+            _address: Address,
+            _block_ref: Option<String>,
+        ) -> Result<Option<Value>> {
+            Ok(None)
+        }
+
+        fn get_slot_number(&self) -> Result<u64> {
+            Ok(5)
+        }
+
+        fn get_finalized_slot(&self) -> Result<u64> {
+            Ok(3)
+        }
+
+        fn get_finality_path(&self, slot: u64) -> Result<Option<FinalityPath>> {
+            Ok(match slot {
+                0..=2 => Some(FinalityPath::Fast),
+                3 => Some(FinalityPath::TwoChain),
+                _ => None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_block_reports_finality_path() {
+        let backend = Arc::new(RwLock::new(MockFinalizedBackend));
+        let req = |slot: &str| JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "aeth_getBlockByNumber".to_string(),
+            params: vec![json!(slot), json!(false)],
+            id: json!(1),
+        };
+
+        for (slot, finality) in [
+            ("2", json!("fast")),
+            ("3", json!("two_chain")),
+            ("4", Value::Null),
+        ] {
+            let response = process_rpc_request(req(slot), backend.clone(), 100_u64).await;
+            assert_eq!(
+                response.result.unwrap()["finality"],
+                finality,
+                "slot {slot}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_block_missing_is_null() {
+        let backend = Arc::new(RwLock::new(MockBackend::default()));
+        let req = JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: "aeth_getBlockByNumber".to_string(),
+            params: vec![json!("7"), json!(false)],
+            id: json!(1),
+        };
+
+        let response = process_rpc_request(req, backend, 100_u64).await;
+        assert_eq!(response.result, Some(Value::Null));
+    }
+
     struct MockSyncingBackend;
 
     impl RpcBackend for MockSyncingBackend {
@@ -1,6 +1,14 @@
-use aether_types::{Address, H256};
-use anyhow::Result;
-use std::collections::HashMap;
+use crate::tracer::{ExecutionTrace, GasSection, HostCallTrace, StateAccess};
+use crate::vm::{gas_costs, Log};
+use aether_crypto_kzg::KzgVerifier;
+use aether_types::{Address, Transaction, H256};
+use anyhow::{bail, Result};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Maximum nesting of [`HostFunctions::call_program`]; the top-level
+/// program runs at depth 0.
+pub const MAX_CALL_DEPTH: usize = 4;
 
 /// Execution context injected into host functions for each contract call.
 #[derive(Debug, Clone)]
@@ -22,6 +30,77 @@ impl Default for ExecutionContext {
     }
 }
 
+/// Accounts a transaction may touch, from its declared R/W sets.
+///
+/// Writable accounts are also readable. Nested calls inherit the table, so
+/// a callee can never write more than the transaction declared.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityTable {
+    readable: HashSet<Address>,
+    writable: HashSet<Address>,
+}
+
+impl CapabilityTable {
+    pub fn new(readable: HashSet<Address>, writable: HashSet<Address>) -> Self {
+        CapabilityTable { readable, writable }
+    }
+
+    pub fn from_transaction(tx: &Transaction) -> Self {
+        Self::new(tx.reads.clone(), tx.writes.clone())
+    }
+
+    pub fn can_read(&self, account: &Address) -> bool {
+        self.readable.contains(account) || self.writable.contains(account)
+    }
+
+    pub fn can_write(&self, account: &Address) -> bool {
+        self.writable.contains(account)
+    }
+}
+
+/// A program reachable through [`HostFunctions::call_program`].
+///
+/// It runs on the caller's host with its own context and gas window and
+/// returns its output bytes; an error reverts everything it did.
+pub trait Program: Send + Sync {
+    fn invoke(&self, host: &mut HostFunctions, input: &[u8]) -> Result<Vec<u8>>;
+}
+
+impl<F> Program for F
+where
+    F: Fn(&mut HostFunctions, &[u8]) -> Result<Vec<u8>> + Send + Sync,
+{
+    fn invoke(&self, host: &mut HostFunctions, input: &[u8]) -> Result<Vec<u8>> {
+        self(host, input)
+    }
+}
+
+/// Outcome of a cross-program call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallOutcome {
+    /// False if the callee returned an error; its changes were reverted.
+    pub success: bool,
+    pub return_data: Vec<u8>,
+    /// Gas the callee used, already charged to the caller.
+    pub gas_used: u64,
+}
+
+/// A state change recorded so a failed call can be undone.
+enum JournalEntry {
+    Storage {
+        slot: (Address, Vec<u8>),
+        prev: Option<Vec<u8>>,
+    },
+    Account {
+        account: Address,
+        prev: Option<Vec<u8>>,
+    },
+    Balance {
+        account: Address,
+        prev: Option<u128>,
+    },
+}
+
 /// Host Functions for WASM Contracts
 ///
 /// These functions are imported into the WASM environment and allow
@@ -32,20 +111,40 @@ impl Default for ExecutionContext {
 /// - Memory access is bounds-checked
 /// - State changes are atomic
 /// - No access to host filesystem/network
+/// - Account access is limited to the transaction's capability table
+/// - Nested calls are depth-limited and run on forwarded gas
 ///
+/// Tracing is opt-in: see [`HostFunctions::with_tracing`].
 pub struct HostFunctions {
-    /// Contract storage (key -> value)
-    storage: HashMap<Vec<u8>, Vec<u8>>,
+    /// Contract storage, scoped per program (program, key) -> value
+    storage: HashMap<(Address, Vec<u8>), Vec<u8>>,
 
     /// Account balances
     pub balances: HashMap<Address, u128>,
 
+    /// Account data, gated by `capabilities`
+    pub accounts: HashMap<Address, Vec<u8>>,
+
+    /// Logs emitted so far, tagged with the emitting program
+    logs: Vec<(Address, Log)>,
+
+    /// Prior values of everything written inside a nested call, so a failed
+    /// callee is unwound in proportion to what it wrote
+    journal: Vec<JournalEntry>,
+
+    capabilities: CapabilityTable,
+    programs: HashMap<Address, Arc<dyn Program>>,
+    depth: usize,
+
     /// Gas meter
     gas_used: u64,
     gas_limit: u64,
 
     /// Execution context (block number, timestamp, caller, etc.)
     context: ExecutionContext,
+
+    /// Present only when tracing was requested
+    trace: Option<ExecutionTrace>,
 }
 
 impl HostFunctions {
@@ -54,12 +153,62 @@ impl HostFunctions {
         HostFunctions {
             storage: HashMap::new(),
             balances: HashMap::new(),
+            accounts: HashMap::new(),
+            logs: Vec::new(),
+            journal: Vec::new(),
+            capabilities: CapabilityTable::default(),
+            programs: HashMap::new(),
+            depth: 0,
             gas_used: 0,
             gas_limit,
             context,
+            trace: None,
         }
     }
 
+    /// Grant account access; without this no account is readable or writable.
+    pub fn with_capabilities(mut self, capabilities: CapabilityTable) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Record host calls, gas per invocation and state accesses.
+    pub fn with_tracing(mut self) -> Self {
+        self.trace = Some(ExecutionTrace::default());
+        self
+    }
+
+    /// The trace so far, closed with the top-level program's gas; `None`
+    /// unless built [`with_tracing`](Self::with_tracing).
+    pub fn take_trace(&mut self) -> Option<ExecutionTrace> {
+        self.close_trace(self.gas_used, self.gas_used <= self.gas_limit)
+    }
+
+    /// Like [`take_trace`](Self::take_trace), for a top-level program whose
+    /// gas was metered elsewhere (the VM's fuel).
+    pub(crate) fn close_trace(&mut self, gas_used: u64, success: bool) -> Option<ExecutionTrace> {
+        let mut trace = self.trace.take()?;
+        trace.sections.push(GasSection {
+            program: self.context.contract_address,
+            depth: self.depth,
+            gas_used,
+            success,
+        });
+        trace.gas_used = gas_used;
+        Some(trace)
+    }
+
+    /// Run as `context`'s program, e.g. when the VM hands this host to a
+    /// WASM entry point.
+    pub(crate) fn set_context(&mut self, context: ExecutionContext) {
+        self.context = context;
+    }
+
+    /// Make `program` callable at `id`.
+    pub fn register_program(&mut self, id: Address, program: Arc<dyn Program>) {
+        self.programs.insert(id, program);
+    }
+
     /// Create with default/zeroed context (convenience for tests).
     pub fn new_for_test(gas_limit: u64) -> Self {
         Self::new(gas_limit, ExecutionContext::default())
@@ -68,109 +217,397 @@ impl HostFunctions {
     /// Read from contract storage
     /// Cost: 200 gas
     pub fn storage_read(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
-        self.charge_gas(200)?;
-        Ok(self.storage.get(key).cloned())
+        self.traced("storage_read", |host| {
+            host.charge_gas(200)?;
+            let program = host.context.contract_address;
+            host.trace_access(StateAccess::StorageRead {
+                program,
+                key: key.to_vec(),
+            });
+            Ok(host.storage.get(&(program, key.to_vec())).cloned())
+        })
     }
 
     /// Write to contract storage
     /// Cost: 5000 gas (expensive to incentivize minimal storage)
     pub fn storage_write(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<()> {
-        self.charge_gas(5000)?;
+        self.traced("storage_write", |host| {
+            host.charge_gas(5000)?;
+
+            let program = host.context.contract_address;
+            host.trace_access(StateAccess::StorageWrite {
+                program,
+                key: key.clone(),
+            });
+
+            // Charge extra for new keys
+            let slot = (program, key);
+            if !host.storage.contains_key(&slot) {
+                host.charge_gas(20000)?; // New storage slot
+            }
+
+            host.write_slot(slot, value);
+            Ok(())
+        })
+    }
 
-        // Charge extra for new keys
-        if !self.storage.contains_key(&key) {
-            self.charge_gas(20000)?; // New storage slot
-        }
+    /// Read an account's data; it must be in the read or write set
+    /// Cost: 200 gas + 1 gas per byte returned
+    pub fn account_read(&mut self, account: &Address) -> Result<Option<Vec<u8>>> {
+        self.traced("account_read", |host| {
+            host.charge_gas(gas_costs::STORAGE_READ)?;
+            if !host.capabilities.can_read(account) {
+                bail!("account {account:?} is not in the declared read or write set");
+            }
+            host.trace_access(StateAccess::AccountRead { account: *account });
+            let data = host.accounts.get(account).cloned();
+            let len = data.as_ref().map_or(0, Vec::len) as u64;
+            host.charge_gas(gas_costs::MEMORY_BYTE.saturating_mul(len))?;
+            Ok(data)
+        })
+    }
 
-        self.storage.insert(key, value);
-        Ok(())
+    /// Replace an account's data; it must be in the write set
+    /// Cost: 5000 gas + 1 gas per byte written
+    pub fn account_write(&mut self, account: &Address, data: Vec<u8>) -> Result<()> {
+        self.traced("account_write", |host| {
+            let bytes = gas_costs::MEMORY_BYTE.saturating_mul(data.len() as u64);
+            host.charge_gas(gas_costs::STORAGE_WRITE.saturating_add(bytes))?;
+            if !host.capabilities.can_write(account) {
+                bail!("account {account:?} is not in the declared write set");
+            }
+            host.trace_access(StateAccess::AccountWrite { account: *account });
+            let prev = host.accounts.insert(*account, data);
+            host.record(|| JournalEntry::Account {
+                account: *account,
+                prev,
+            });
+            Ok(())
+        })
+    }
+
+    /// Invoke another program with `input`, forwarding up to `gas`
+    /// Cost: 700 gas + whatever the callee uses
+    ///
+    /// The callee sees this program as its caller and gets at most the gas
+    /// left after the call cost. If it fails, its storage, account, balance
+    /// and log changes are rolled back and `success` is false; the gas it
+    /// burned is still charged.
+    pub fn call_program(
+        &mut self,
+        program_id: &Address,
+        input: &[u8],
+        gas: u64,
+    ) -> Result<CallOutcome> {
+        self.traced("call_program", |host| {
+            host.charge_gas(gas_costs::CALL)?;
+            if host.depth >= MAX_CALL_DEPTH {
+                bail!("call depth limit {MAX_CALL_DEPTH} exceeded");
+            }
+            let program = match host.programs.get(program_id) {
+                Some(program) => program.clone(),
+                None => bail!("unknown program {program_id:?}"),
+            };
+
+            let forwarded = gas.min(host.gas_limit.saturating_sub(host.gas_used));
+            let checkpoint = (host.journal.len(), host.logs.len());
+            let callee_context = ExecutionContext {
+                caller: host.context.contract_address,
+                contract_address: *program_id,
+                ..host.context.clone()
+            };
+            let caller_context = std::mem::replace(&mut host.context, callee_context);
+            let caller_gas = (host.gas_used, host.gas_limit);
+            host.gas_used = 0;
+            host.gas_limit = forwarded;
+            host.depth += 1;
+
+            let result = program.invoke(host, input);
+
+            let callee_gas = host.gas_used.min(forwarded);
+            host.depth -= 1;
+            if result.is_err() {
+                host.revert_to(checkpoint);
+            } else if host.depth == 0 {
+                // Nothing above the top level can be reverted any more.
+                host.journal.clear();
+            }
+            (host.gas_used, host.gas_limit) = caller_gas;
+            host.context = caller_context;
+            if let Some(trace) = &mut host.trace {
+                trace.sections.push(GasSection {
+                    program: *program_id,
+                    depth: host.depth + 1,
+                    gas_used: callee_gas,
+                    success: result.is_ok(),
+                });
+            }
+            host.charge_gas(callee_gas)?;
+
+            match result {
+                Ok(return_data) => Ok(CallOutcome {
+                    success: true,
+                    return_data,
+                    gas_used: callee_gas,
+                }),
+                Err(e) => {
+                    tracing::debug!(program = ?program_id, error = %e, "cross-program call failed");
+                    Ok(CallOutcome {
+                        success: false,
+                        return_data: Vec::new(),
+                        gas_used: callee_gas,
+                    })
+                }
+            }
+        })
     }
 
     /// Get account balance
     /// Cost: 100 gas
     pub fn get_balance(&mut self, address: &Address) -> Result<u128> {
-        self.charge_gas(100)?;
-        Ok(self.balances.get(address).copied().unwrap_or(0))
+        self.traced("get_balance", |host| {
+            host.charge_gas(100)?;
+            host.trace_access(StateAccess::BalanceRead { account: *address });
+            Ok(host.balances.get(address).copied().unwrap_or(0))
+        })
     }
 
     /// Transfer value to another account
     /// Cost: 9000 gas
     pub fn transfer(&mut self, from: &Address, to: &Address, amount: u128) -> Result<()> {
-        self.charge_gas(9000)?;
+        self.traced("transfer", |host| {
+            host.charge_gas(9000)?;
+            host.trace_access(StateAccess::Transfer {
+                from: *from,
+                to: *to,
+            });
 
-        let from_balance = self.balances.get(from).copied().unwrap_or(0);
-        if from_balance < amount {
-            anyhow::bail!("insufficient balance");
-        }
+            let from_balance = host.balances.get(from).copied().unwrap_or(0);
+            if from_balance < amount {
+                anyhow::bail!("insufficient balance");
+            }
 
-        let to_balance = self.balances.get(to).copied().unwrap_or(0);
+            let to_balance = host.balances.get(to).copied().unwrap_or(0);
 
-        self.balances.insert(
-            *from,
-            from_balance
+            let new_from_balance = from_balance
                 .checked_sub(amount)
-                .ok_or_else(|| anyhow::anyhow!("balance underflow"))?,
-        );
-        let new_to_balance = to_balance
-            .checked_add(amount)
-            .ok_or_else(|| anyhow::anyhow!("balance overflow"))?;
-        self.balances.insert(*to, new_to_balance);
-
-        Ok(())
+                .ok_or_else(|| anyhow::anyhow!("balance underflow"))?;
+            let new_to_balance = to_balance
+                .checked_add(amount)
+                .ok_or_else(|| anyhow::anyhow!("balance overflow"))?;
+            host.write_balance(*from, new_from_balance);
+            host.write_balance(*to, new_to_balance);
+
+            Ok(())
+        })
     }
 
     /// Compute SHA256 hash
     /// Cost: 60 gas + 12 gas per word
     #[allow(clippy::manual_div_ceil)]
     pub fn sha256(&mut self, data: &[u8]) -> Result<H256> {
-        let words = (data.len() + 31) / 32;
-        self.charge_gas(60u64.saturating_add(12u64.saturating_mul(words as u64)))?;
+        self.traced("sha256", |host| {
+            let words = (data.len() + 31) / 32;
+            host.charge_gas(60u64.saturating_add(12u64.saturating_mul(words as u64)))?;
+
+            use sha2::{Digest, Sha256};
+            let hash = Sha256::digest(data);
+            Ok(H256::from(<[u8; 32]>::from(hash)))
+        })
+    }
+
+    /// Verify a packed KZG opening: commitment || z || y || proof
+    /// Cost: 50000 gas (two pairings), charged even if the opening fails
+    pub fn kzg_verify(&mut self, verifier: &KzgVerifier, input: &[u8]) -> Result<bool> {
+        self.traced("kzg_verify", |host| {
+            host.charge_gas(crate::vm::gas_costs::KZG_VERIFY)?;
+            verifier.verify_packed(input)
+        })
+    }
 
-        use sha2::{Digest, Sha256};
-        let hash = Sha256::digest(data);
-        Ok(H256::from(<[u8; 32]>::from(hash)))
+    /// Verify an ed25519 signature over `message`
+    /// Cost: 3000 gas + 1 gas per message byte, charged even if it fails
+    pub fn crypto_verify_sig(
+        &mut self,
+        public_key: &[u8],
+        message: &[u8],
+        signature: &[u8],
+    ) -> Result<bool> {
+        self.traced("crypto_verify_sig", |host| {
+            let bytes = gas_costs::MEMORY_BYTE.saturating_mul(message.len() as u64);
+            host.charge_gas(gas_costs::SIG_VERIFY.saturating_add(bytes))?;
+            Ok(aether_crypto_primitives::ed25519::verify(public_key, message, signature).is_ok())
+        })
     }
 
     /// Emit a log event
     /// Cost: 375 gas + 8 gas per byte
     pub fn emit_log(&mut self, topics: Vec<H256>, data: Vec<u8>) -> Result<()> {
-        self.charge_gas(375u64.saturating_add(8u64.saturating_mul(data.len() as u64)))?;
+        self.traced("emit_log", |host| {
+            host.charge_gas(375u64.saturating_add(8u64.saturating_mul(data.len() as u64)))?;
 
-        // In production: store logs for receipts
-        tracing::debug!(topics = ?topics, data_len = data.len(), "contract log emitted");
+            tracing::debug!(topics = ?topics, data_len = data.len(), "contract log emitted");
+            host.logs
+                .push((host.context.contract_address, Log { topics, data }));
 
-        Ok(())
+            Ok(())
+        })
+    }
+
+    /// Logs emitted so far, with the program that emitted each, for receipts.
+    pub fn logs(&self) -> &[(Address, Log)] {
+        &self.logs
     }
 
     /// Get current block number
     /// Cost: 2 gas
     pub fn block_number(&mut self) -> Result<u64> {
-        self.charge_gas(2)?;
-        Ok(self.context.block_number)
+        self.traced("block_number", |host| {
+            host.charge_gas(2)?;
+            Ok(host.context.block_number)
+        })
     }
 
     /// Get current timestamp
     /// Cost: 2 gas
     pub fn timestamp(&mut self) -> Result<u64> {
-        self.charge_gas(2)?;
-        Ok(self.context.timestamp)
+        self.traced("timestamp", |host| {
+            host.charge_gas(2)?;
+            Ok(host.context.timestamp)
+        })
     }
 
     /// Get caller address
     /// Cost: 2 gas
     pub fn caller(&mut self) -> Result<Address> {
-        self.charge_gas(2)?;
-        Ok(self.context.caller)
+        self.traced("caller", |host| {
+            host.charge_gas(2)?;
+            Ok(host.context.caller)
+        })
     }
 
     /// Get contract address
     /// Cost: 2 gas
     pub fn address(&mut self) -> Result<Address> {
-        self.charge_gas(2)?;
-        Ok(self.context.contract_address)
+        self.traced("address", |host| {
+            host.charge_gas(2)?;
+            Ok(host.context.contract_address)
+        })
+    }
+
+    /// Context of the running program, without charging gas.
+    pub(crate) fn context(&self) -> &ExecutionContext {
+        &self.context
+    }
+
+    /// Raw storage slot of the running program, without charging gas.
+    pub(crate) fn own_slot(&self, key: &[u8]) -> Option<&Vec<u8>> {
+        self.storage
+            .get(&(self.context.contract_address, key.to_vec()))
+    }
+
+    pub(crate) fn set_own_slot(&mut self, key: &[u8], value: Vec<u8>) {
+        self.write_slot((self.context.contract_address, key.to_vec()), value);
+    }
+
+    fn write_slot(&mut self, slot: (Address, Vec<u8>), value: Vec<u8>) {
+        let prev = self.storage.insert(slot.clone(), value);
+        self.record(|| JournalEntry::Storage { slot, prev });
+    }
+
+    fn write_balance(&mut self, account: Address, balance: u128) {
+        let prev = self.balances.insert(account, balance);
+        self.record(|| JournalEntry::Balance { account, prev });
+    }
+
+    /// Journal a write; top-level writes are never reverted here.
+    fn record(&mut self, entry: impl FnOnce() -> JournalEntry) {
+        if self.depth > 0 {
+            self.journal.push(entry());
+        }
+    }
+
+    /// Undo every write and log since `(journal_len, log_count)`.
+    fn revert_to(&mut self, (journal_len, log_count): (usize, usize)) {
+        for entry in self.journal.drain(journal_len..).rev() {
+            match entry {
+                JournalEntry::Storage { slot, prev } => restore(&mut self.storage, slot, prev),
+                JournalEntry::Account { account, prev } => {
+                    restore(&mut self.accounts, account, prev)
+                }
+                JournalEntry::Balance { account, prev } => {
+                    restore(&mut self.balances, account, prev)
+                }
+            }
+        }
+        self.logs.truncate(log_count);
+    }
+
+    /// Run a host call, recording it when tracing.
+    fn traced<T>(
+        &mut self,
+        name: &'static str,
+        call: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> Result<T> {
+        let index = match &mut self.trace {
+            Some(trace) => {
+                trace.host_calls.push(HostCallTrace {
+                    name,
+                    depth: self.depth,
+                    program: self.context.contract_address,
+                    gas: 0,
+                    error: None,
+                });
+                trace.host_calls.len() - 1
+            }
+            None => return call(self),
+        };
+        let gas_before = self.gas_used;
+        let result = call(self);
+        let gas = self.gas_used.saturating_sub(gas_before);
+        if let Some(entry) = self.trace.as_mut().map(|t| &mut t.host_calls[index]) {
+            entry.gas = gas;
+            entry.error = result.as_ref().err().map(|e| e.to_string());
+        }
+        result
+    }
+
+    pub(crate) fn trace_access(&mut self, access: StateAccess) {
+        if let Some(trace) = &mut self.trace {
+            trace.state_accesses.push(access);
+        }
+    }
+
+    /// Record a host call metered outside this host, by the VM.
+    pub(crate) fn trace_call(&mut self, name: &'static str, gas: u64, error: Option<String>) {
+        if let Some(trace) = &mut self.trace {
+            trace.host_calls.push(HostCallTrace {
+                name,
+                depth: self.depth,
+                program: self.context.contract_address,
+                gas,
+                error,
+            });
+        }
     }
 
-    fn charge_gas(&mut self, amount: u64) -> Result<()> {
+    /// Run `call` on a gas window of `budget` for a caller that meters gas
+    /// itself (the VM's fuel); returns its result and the gas it used.
+    pub(crate) fn metered<T>(
+        &mut self,
+        budget: u64,
+        call: impl FnOnce(&mut Self) -> Result<T>,
+    ) -> (Result<T>, u64) {
+        let outer = (self.gas_used, self.gas_limit);
+        self.gas_used = 0;
+        self.gas_limit = budget;
+        let result = call(self);
+        let used = self.gas_used.min(budget);
+        (self.gas_used, self.gas_limit) = outer;
+        (result, used)
+    }
+
+    pub(crate) fn charge_gas(&mut self, amount: u64) -> Result<()> {
         self.gas_used = self
             .gas_used
             .checked_add(amount)
@@ -188,6 +625,13 @@ impl HostFunctions {
     }
 }
 
+fn restore<K: std::hash::Hash + Eq, V>(map: &mut HashMap<K, V>, key: K, prev: Option<V>) {
+    match prev {
+        Some(value) => map.insert(key, value),
+        None => map.remove(&key),
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -261,6 +705,26 @@ mod tests {
         assert_eq!(hash.as_bytes().len(), 32);
     }
 
+    #[test]
+    fn test_kzg_verify() {
+        let verifier = KzgVerifier::new_insecure_test(4);
+        let coeffs: Vec<[u8; 32]> = (1..=3).map(aether_crypto_kzg::scalar_from_i64).collect();
+        let z = aether_crypto_kzg::scalar_from_i64(2);
+        let proof = verifier.create_proof(&coeffs, &z).unwrap();
+        let mut input = verifier.commit(&coeffs).unwrap().commitment;
+        input.extend_from_slice(&z);
+        input.extend_from_slice(&proof.evaluation);
+        input.extend_from_slice(&proof.proof);
+
+        let mut host = HostFunctions::new_for_test(120_000);
+        assert!(host.kzg_verify(&verifier, &input).unwrap());
+        assert_eq!(host.gas_used(), 50_000);
+        input[48] ^= 1;
+        assert!(!host.kzg_verify(&verifier, &input).unwrap());
+        // Out of gas for a third check.
+        assert!(host.kzg_verify(&verifier, &input).is_err());
+    }
+
     #[test]
     fn test_transfer_overflow() {
         let mut host = HostFunctions::new_for_test(100_000);
@@ -305,4 +769,228 @@ mod tests {
         assert_eq!(host.caller().unwrap(), caller);
         assert_eq!(host.address().unwrap(), contract);
     }
+
+    fn addr(b: u8) -> Address {
+        Address::from_slice(&[b; 20]).unwrap()
+    }
+
+    fn host_with_caps(gas_limit: u64, reads: &[u8], writes: &[u8]) -> HostFunctions {
+        let ctx = ExecutionContext {
+            contract_address: addr(0xc0),
+            ..ExecutionContext::default()
+        };
+        let caps = CapabilityTable::new(
+            reads.iter().map(|&b| addr(b)).collect(),
+            writes.iter().map(|&b| addr(b)).collect(),
+        );
+        HostFunctions::new(gas_limit, ctx).with_capabilities(caps)
+    }
+
+    #[test]
+    fn test_account_access_follows_capabilities() {
+        let mut host = host_with_caps(1_000_000, &[1], &[2]);
+        host.accounts.insert(addr(1), b"one".to_vec());
+        host.accounts.insert(addr(3), b"three".to_vec());
+
+        assert_eq!(host.account_read(&addr(1)).unwrap(), Some(b"one".to_vec()));
+        assert_eq!(host.account_read(&addr(2)).unwrap(), None);
+        assert!(host.account_read(&addr(3)).is_err(), "undeclared read");
+
+        host.account_write(&addr(2), b"two".to_vec()).unwrap();
+        assert_eq!(host.accounts[&addr(2)], b"two".to_vec());
+        assert!(host.account_write(&addr(1), vec![]).is_err(), "read-only");
+        assert!(host.account_write(&addr(3), vec![]).is_err(), "undeclared");
+        assert_eq!(host.accounts[&addr(1)], b"one".to_vec());
+    }
+
+    #[test]
+    fn test_call_program_switches_context_and_charges_callee_gas() {
+        let mut host = host_with_caps(1_000_000, &[], &[]);
+        let callee = addr(0xca);
+        host.register_program(
+            callee,
+            Arc::new(|host: &mut HostFunctions, input: &[u8]| {
+                assert_eq!(host.caller()?, addr(0xc0));
+                host.storage_write(b"k".to_vec(), input.to_vec())?;
+                host.emit_log(vec![], b"hi".to_vec())?;
+                Ok(b"done".to_vec())
+            }),
+        );
+
+        let outcome = host.call_program(&callee, b"v", u64::MAX).unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.return_data, b"done".to_vec());
+        assert_eq!(outcome.gas_used, 2 + 25_000 + 375 + 16);
+        assert_eq!(host.gas_used(), gas_costs::CALL + outcome.gas_used);
+
+        // Storage is per program; the caller's namespace is untouched.
+        assert_eq!(host.storage_read(b"k").unwrap(), None);
+        assert_eq!(host.logs().len(), 1);
+        assert_eq!(host.logs()[0].0, callee);
+        assert_eq!(host.address().unwrap(), addr(0xc0));
+    }
+
+    #[test]
+    fn test_failed_call_reverts_callee_changes() {
+        let mut host = host_with_caps(1_000_000, &[], &[7]);
+        let callee = addr(0xca);
+        host.register_program(
+            callee,
+            Arc::new(|host: &mut HostFunctions, _: &[u8]| {
+                host.account_write(&addr(7), b"dirty".to_vec())?;
+                host.emit_log(vec![], vec![])?;
+                bail!("abort")
+            }),
+        );
+
+        let outcome = host.call_program(&callee, &[], u64::MAX).unwrap();
+        assert!(!outcome.success);
+        assert!(outcome.gas_used > 0, "burned gas is still charged");
+        assert!(host.accounts.is_empty());
+        assert!(host.logs().is_empty());
+        assert!(
+            host.call_program(&addr(0xee), &[], 0).is_err(),
+            "unknown program"
+        );
+    }
+
+    #[test]
+    fn test_failed_call_unwinds_nested_successes() {
+        let mut host = host_with_caps(1_000_000, &[], &[7]);
+        host.balances.insert(addr(1), 100);
+        host.set_own_slot(b"k", b"top".to_vec());
+        let (outer, inner) = (addr(0xca), addr(0xcb));
+        host.register_program(
+            inner,
+            Arc::new(|host: &mut HostFunctions, _: &[u8]| {
+                host.transfer(&addr(1), &addr(2), 40)?;
+                host.storage_write(b"k".to_vec(), b"inner".to_vec())?;
+                Ok(vec![])
+            }),
+        );
+        host.register_program(
+            outer,
+            Arc::new(move |host: &mut HostFunctions, _: &[u8]| {
+                host.account_write(&addr(7), b"dirty".to_vec())?;
+                assert!(host.call_program(&inner, &[], u64::MAX)?.success);
+                bail!("abort")
+            }),
+        );
+
+        assert!(!host.call_program(&outer, &[], u64::MAX).unwrap().success);
+        assert!(host.accounts.is_empty());
+        assert_eq!(host.balances.get(&addr(1)), Some(&100));
+        assert_eq!(host.balances.get(&addr(2)), None);
+        assert_eq!(host.own_slot(b"k"), Some(&b"top".to_vec()));
+        assert!(
+            host.journal.is_empty(),
+            "journal is dropped at the top level"
+        );
+    }
+
+    #[test]
+    fn test_tracing_records_calls_sections_and_accesses() {
+        let mut host = host_with_caps(1_000_000, &[], &[7]).with_tracing();
+        let callee = addr(0xca);
+        host.register_program(
+            callee,
+            Arc::new(|host: &mut HostFunctions, _: &[u8]| {
+                host.account_write(&addr(7), b"x".to_vec())?;
+                bail!("abort")
+            }),
+        );
+
+        host.storage_write(b"k".to_vec(), vec![1]).unwrap();
+        let outcome = host.call_program(&callee, &[], u64::MAX).unwrap();
+        let trace = host.take_trace().unwrap();
+
+        let calls: Vec<_> = trace
+            .host_calls
+            .iter()
+            .map(|c| (c.name, c.depth, c.gas))
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("storage_write", 0, 25_000),
+                ("call_program", 0, gas_costs::CALL + outcome.gas_used),
+                ("account_write", 1, outcome.gas_used),
+            ]
+        );
+        assert_eq!(trace.sections.len(), 2);
+        assert_eq!(trace.sections[0].program, callee);
+        assert!(!trace.sections[0].success);
+        assert_eq!(trace.sections[1].gas_used, host.gas_used());
+        assert_eq!(trace.gas_used, host.gas_used());
+        assert_eq!(
+            trace.state_accesses,
+            vec![
+                StateAccess::StorageWrite {
+                    program: addr(0xc0),
+                    key: b"k".to_vec()
+                },
+                StateAccess::AccountWrite { account: addr(7) },
+            ]
+        );
+
+        assert!(host.take_trace().is_none(), "trace is taken once");
+        assert!(HostFunctions::new_for_test(10).take_trace().is_none());
+    }
+
+    #[test]
+    fn test_call_program_forwards_limited_gas() {
+        let mut host = host_with_caps(100_000, &[], &[]);
+        let callee = addr(0xca);
+        host.register_program(
+            callee,
+            Arc::new(|host: &mut HostFunctions, _: &[u8]| loop {
+                host.storage_read(b"k")?;
+            }),
+        );
+
+        let outcome = host.call_program(&callee, &[], 10_000).unwrap();
+        assert!(!outcome.success);
+        assert_eq!(outcome.gas_used, 10_000);
+        assert_eq!(host.gas_used(), gas_costs::CALL + 10_000);
+        // The caller keeps the rest of its gas.
+        assert!(host.storage_read(b"k").is_ok());
+    }
+
+    #[test]
+    fn test_call_depth_is_limited() {
+        let mut host = host_with_caps(10_000_000, &[], &[]);
+        let recursive = addr(0xca);
+        host.register_program(
+            recursive,
+            Arc::new(move |host: &mut HostFunctions, _: &[u8]| {
+                // Report how many further levels were reachable.
+                match host.call_program(&recursive, &[], u64::MAX) {
+                    Ok(inner) => Ok(vec![inner.return_data[0] + 1]),
+                    Err(_) => Ok(vec![0]),
+                }
+            }),
+        );
+
+        let outcome = host.call_program(&recursive, &[], u64::MAX).unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.return_data, vec![(MAX_CALL_DEPTH - 1) as u8]);
+    }
+
+    #[test]
+    fn test_crypto_verify_sig() {
+        let keypair = aether_crypto_primitives::Ed25519Keypair::generate();
+        let signature = keypair.sign(b"msg");
+        let mut host = HostFunctions::new_for_test(100_000);
+
+        assert!(host
+            .crypto_verify_sig(&keypair.public_key(), b"msg", &signature)
+            .unwrap());
+        assert!(!host
+            .crypto_verify_sig(&keypair.public_key(), b"other", &signature)
+            .unwrap());
+        assert!(!host
+            .crypto_verify_sig(&[0u8; 3], b"msg", &signature)
+            .unwrap());
+        assert_eq!(host.gas_used(), 3 * gas_costs::SIG_VERIFY + 3 + 5 + 3);
+    }
 }
@@ -5,41 +5,61 @@
 //
 // FEATURES:
 // - WASM VM using Wasmtime
-// - Deterministic execution
-// - Gas metering per instruction
+// - Deterministic execution (float/SIMD/thread opcodes rejected at load)
+// - Gas metering per instruction (fuel = the fee formula's steps)
 // - Host functions for blockchain interaction
 // - Memory and stack limits
-// - Parallel execution scheduling (R/W sets)
+// - Parallel execution scheduling (R/W-set conflict graph, buffered writes)
+// - Compiled module cache by code hash (memory, optional disk), pre-warming
+// - Opt-in execution tracing (host calls, gas per call, state accesses)
 //
 // HOST FUNCTIONS:
 // - storage_read/storage_write: Contract storage
+// - account_read/account_write: Account data, gated by the tx's R/W sets
 // - get_balance/transfer: Account operations
+// - call_program: Cross-program invocation (depth 4, forwarded gas)
+// - crypto_verify_sig: ed25519 signature check
 // - sha256: Cryptographic hashing
+// - kzg_verify: KZG opening check for dispute programs
 // - emit_log: Event logging
 // - block_number/timestamp/caller/address: Context info
 //
+// PRECOMPILES (0x..01-0x..05, ABI in aether-codecs):
+// - AIC token, job escrow, governance, staking, AMM
+// - Flat gas price per entry point
+//
 // GAS COSTS (per spec):
 // - Base: 100
 // - Memory: 1 per byte
 // - Storage read: 200
 // - Storage write: 5000 (+ 20000 for new slot)
 // - Transfer: 9000
+// - Call program: 700 + callee gas
+// - Signature verify: 3000 + 1 per message byte
 // - SHA256: 60 + 12 per word
 // - Log: 375 + 8 per byte
+// - KZG verify: 50000 (two pairings at 25000)
 //
 // EXECUTION FLOW:
 // 1. Load WASM module
-// 2. Validate bytecode
+// 2. Validate bytecode (no nondeterministic opcodes)
+//    (skipped, with compilation, when the code hash is cached)
 // 3. Instantiate with gas limit
 // 4. Inject host functions
 // 5. Execute entry point
-// 6. Return result + gas used
+// 6. Return status, gas used and peak memory
 // ============================================================================
 
 pub mod host_functions;
+pub mod module_cache;
+pub mod precompiles;
 pub mod scheduler;
+pub mod tracer;
 pub mod vm;
 
-pub use host_functions::HostFunctions;
-pub use scheduler::ParallelScheduler;
-pub use vm::{gas_costs, ExecutionContext, ExecutionResult, Log, WasmVm};
+pub use host_functions::{CallOutcome, CapabilityTable, HostFunctions, Program, MAX_CALL_DEPTH};
+pub use module_cache::{ModuleCache, ModuleCacheStats};
+pub use precompiles::{Precompile, PrecompileRegistry};
+pub use scheduler::{ConflictGraph, ParallelScheduler};
+pub use tracer::{ExecutionTrace, GasSection, HostCallTrace, StateAccess};
+pub use vm::{gas_costs, ExecutionContext, ExecutionResult, ExecutionStatus, Log, WasmVm};
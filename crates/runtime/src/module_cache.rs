@@ -0,0 +1,476 @@
+use aether_types::H256;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use wasmtime::{Engine, Module};
+
+/// Compiled code kept in memory by default: 64 MB.
+pub const DEFAULT_MODULE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Compiled modules keyed by the SHA-256 of their bytecode.
+///
+/// Compiling is the bulk of the cost of running a small program, so a
+/// program executed again reuses its machine code. Memory use is bounded
+/// by `max_bytes` of compiled code, evicting the least recently used
+/// module first. With a directory, compiled artifacts also survive
+/// restarts and an evicted module is reloaded from disk instead of
+/// recompiled. The directory is bounded by `max_bytes` of artifacts too,
+/// and each artifact is checked against a SHA-256 sidecar before it is
+/// loaded as native code.
+pub struct ModuleCache {
+    modules: HashMap<H256, CachedModule>,
+    max_bytes: usize,
+    bytes: usize,
+    /// Bumped on every lookup; orders entries for eviction.
+    clock: u64,
+    dir: Option<PathBuf>,
+    /// Artifacts in `dir`, by path.
+    disk: HashMap<PathBuf, DiskArtifact>,
+    disk_bytes: u64,
+    stats: ModuleCacheStats,
+}
+
+struct CachedModule {
+    module: Module,
+    size: usize,
+    last_used: u64,
+}
+
+struct DiskArtifact {
+    size: u64,
+    last_used: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ModuleCacheStats {
+    pub hits: u64,
+    /// Misses served from the on-disk cache without compiling.
+    pub disk_hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl ModuleCache {
+    pub fn new(max_bytes: usize) -> Self {
+        ModuleCache {
+            modules: HashMap::new(),
+            max_bytes,
+            bytes: 0,
+            clock: 0,
+            dir: None,
+            disk: HashMap::new(),
+            disk_bytes: 0,
+            stats: ModuleCacheStats::default(),
+        }
+    }
+
+    /// Persist compiled artifacts under `dir`, which is created (or
+    /// restricted) to be accessible by the node's user only: artifacts are
+    /// loaded as native code without recompiling.
+    pub fn with_disk_dir(mut self, dir: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let dir = dir.into();
+        create_private_dir(&dir)?;
+
+        // Artifacts left by an earlier run, least recently written first.
+        let mut found = Vec::new();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "cwasm") {
+                let metadata = fs::metadata(&path)?;
+                found.push((metadata.modified().ok(), metadata.len(), path));
+            }
+        }
+        found.sort();
+        for (_, size, path) in found {
+            self.clock += 1;
+            self.disk_bytes += size;
+            self.disk.insert(
+                path,
+                DiskArtifact {
+                    size,
+                    last_used: self.clock,
+                },
+            );
+        }
+        self.dir = Some(dir);
+        self.trim_disk();
+        Ok(self)
+    }
+
+    pub fn code_hash(wasm: &[u8]) -> H256 {
+        H256::from(<[u8; 32]>::from(Sha256::digest(wasm)))
+    }
+
+    /// The module for `code_hash`, from memory or else from disk.
+    pub fn get(&mut self, engine: &Engine, code_hash: &H256) -> Option<Module> {
+        self.clock += 1;
+        if let Some(entry) = self.modules.get_mut(code_hash) {
+            entry.last_used = self.clock;
+            self.stats.hits += 1;
+            return Some(entry.module.clone());
+        }
+
+        let module = self.load(engine, code_hash);
+        match &module {
+            Some(module) => {
+                self.stats.disk_hits += 1;
+                self.remember(*code_hash, module.clone());
+            }
+            None => self.stats.misses += 1,
+        }
+        module
+    }
+
+    /// Cache a freshly compiled module, writing it to disk if enabled.
+    pub fn insert(&mut self, code_hash: H256, module: &Module) {
+        if let Some(path) = self.artifact_path(&code_hash) {
+            // Write then rename, so a crash mid-write never leaves a
+            // truncated artifact where `load` will find it. The digest goes
+            // first: an artifact without a matching one is never loaded.
+            let partial = path.with_extension(format!("cwasm.{}.tmp", std::process::id()));
+            let written = module.serialize().and_then(|bytes| {
+                fs::write(digest_path(&path), Sha256::digest(&bytes))?;
+                fs::write(&partial, &bytes)?;
+                fs::rename(&partial, &path)?;
+                Ok(bytes.len() as u64)
+            });
+            match written {
+                Ok(size) => {
+                    self.clock += 1;
+                    let artifact = DiskArtifact {
+                        size,
+                        last_used: self.clock,
+                    };
+                    if let Some(old) = self.disk.insert(path, artifact) {
+                        self.disk_bytes -= old.size;
+                    }
+                    self.disk_bytes += size;
+                    self.trim_disk();
+                }
+                Err(e) => {
+                    let _ = fs::remove_file(&partial);
+                    tracing::warn!(
+                        path = %path.display(),
+                        error = %e,
+                        "failed to persist compiled module"
+                    );
+                }
+            }
+        }
+        self.remember(code_hash, module.clone());
+    }
+
+    pub fn contains(&self, code_hash: &H256) -> bool {
+        self.modules.contains_key(code_hash)
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Compiled code held in memory.
+    pub fn size_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Compiled artifacts held on disk.
+    pub fn disk_size_bytes(&self) -> u64 {
+        self.disk_bytes
+    }
+
+    pub fn stats(&self) -> ModuleCacheStats {
+        self.stats
+    }
+
+    fn remember(&mut self, code_hash: H256, module: Module) {
+        let size = module.image_range().len();
+        if size > self.max_bytes {
+            return;
+        }
+        self.clock += 1;
+        let entry = CachedModule {
+            module,
+            size,
+            last_used: self.clock,
+        };
+        if let Some(old) = self.modules.insert(code_hash, entry) {
+            self.bytes -= old.size;
+        }
+        self.bytes += size;
+
+        while self.bytes > self.max_bytes {
+            let Some(oldest) = self
+                .modules
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(hash, _)| *hash)
+            else {
+                break;
+            };
+            if let Some(evicted) = self.modules.remove(&oldest) {
+                self.bytes -= evicted.size;
+                self.stats.evictions += 1;
+            }
+        }
+    }
+
+    /// Drop the least recently used artifacts until the directory holds
+    /// at most `max_bytes`.
+    fn trim_disk(&mut self) {
+        while self.disk_bytes > self.max_bytes as u64 {
+            let Some(oldest) = self
+                .disk
+                .iter()
+                .min_by_key(|(_, artifact)| artifact.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            self.forget_artifact(&oldest);
+        }
+    }
+
+    fn forget_artifact(&mut self, path: &Path) {
+        if let Some(artifact) = self.disk.remove(path) {
+            self.disk_bytes -= artifact.size;
+        }
+        let _ = fs::remove_file(path);
+        let _ = fs::remove_file(digest_path(path));
+    }
+
+    fn load(&mut self, engine: &Engine, code_hash: &H256) -> Option<Module> {
+        let path = self.artifact_path(code_hash)?;
+        if !path.exists() {
+            return None;
+        }
+        // Read once, so the bytes checked are the bytes loaded.
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::debug!(path = %path.display(), error = %e, "unreadable compiled module");
+                return None;
+            }
+        };
+        let expected = fs::read(digest_path(&path)).unwrap_or_default();
+        if expected != Sha256::digest(&bytes).as_slice() {
+            tracing::warn!(
+                path = %path.display(),
+                "compiled module does not match its digest, recompiling"
+            );
+            self.forget_artifact(&path);
+            return None;
+        }
+        // SAFETY: the directory is private to the node (see `with_disk_dir`)
+        // and the bytes match the digest written when this cache serialized
+        // them. Wasmtime rejects artifacts from another version or engine
+        // configuration.
+        match unsafe { Module::deserialize(engine, &bytes) } {
+            Ok(module) => {
+                self.clock += 1;
+                if let Some(artifact) = self.disk.get_mut(&path) {
+                    artifact.last_used = self.clock;
+                }
+                Some(module)
+            }
+            Err(e) => {
+                tracing::debug!(
+                    path = %path.display(),
+                    error = %e,
+                    "stale compiled module, recompiling"
+                );
+                None
+            }
+        }
+    }
+
+    fn artifact_path(&self, code_hash: &H256) -> Option<PathBuf> {
+        self.dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{code_hash:?}.cwasm")))
+    }
+}
+
+/// Where the SHA-256 of the artifact at `path` is kept.
+fn digest_path(path: &Path) -> PathBuf {
+    path.with_extension("sha256")
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+    // An existing directory keeps its mode unless tightened here.
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)
+}
+
+impl Default for ModuleCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MODULE_CACHE_BYTES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile(engine: &Engine, body: &str) -> (H256, Module) {
+        let wasm = wat::parse_str(format!(
+            "(module (func (export \"execute\") (param i32 i32) (result i32) {body}))"
+        ))
+        .unwrap();
+        (
+            ModuleCache::code_hash(&wasm),
+            Module::new(engine, &wasm).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_hit_after_insert() {
+        let engine = Engine::default();
+        let mut cache = ModuleCache::default();
+        let (hash, module) = compile(&engine, "i32.const 0");
+
+        assert!(cache.get(&engine, &hash).is_none());
+        cache.insert(hash, &module);
+        assert!(cache.get(&engine, &hash).is_some());
+        assert_eq!(
+            cache.stats(),
+            ModuleCacheStats {
+                hits: 1,
+                disk_hits: 0,
+                misses: 1,
+                evictions: 0,
+            }
+        );
+        assert_eq!(cache.size_bytes(), module.image_range().len());
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used_by_size() {
+        let engine = Engine::default();
+        let (a, module_a) = compile(&engine, "i32.const 0");
+        let (b, module_b) = compile(&engine, "i32.const 1");
+        let (c, module_c) = compile(&engine, "i32.const 2");
+        let size = module_a.image_range().len();
+        assert!(size > 0);
+        let mut cache = ModuleCache::new(size * 2 + size / 2);
+
+        cache.insert(a, &module_a);
+        cache.insert(b, &module_b);
+        assert!(
+            cache.get(&engine, &a).is_some(),
+            "a is now more recent than b"
+        );
+        cache.insert(c, &module_c);
+
+        assert!(cache.contains(&a));
+        assert!(!cache.contains(&b));
+        assert!(cache.contains(&c));
+        assert!(cache.size_bytes() <= size * 2 + size / 2);
+        assert_eq!(cache.stats().evictions, 1);
+    }
+
+    #[test]
+    fn test_disk_cache_survives_restart() {
+        let engine = Engine::default();
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, module) = compile(&engine, "i32.const 7");
+
+        let mut cache = ModuleCache::default().with_disk_dir(dir.path()).unwrap();
+        cache.insert(hash, &module);
+        drop(cache);
+        let mut files: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files,
+            vec![format!("{hash:?}.cwasm"), format!("{hash:?}.sha256")],
+            "no partial writes left"
+        );
+
+        let mut restarted = ModuleCache::default().with_disk_dir(dir.path()).unwrap();
+        assert!(restarted.get(&engine, &hash).is_some());
+        assert_eq!(restarted.stats().disk_hits, 1);
+        assert!(restarted.contains(&hash));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_disk_dir_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let parent = tempfile::tempdir().unwrap();
+        let dir = parent.path().join("modules");
+        ModuleCache::default().with_disk_dir(&dir).unwrap();
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+        ModuleCache::default().with_disk_dir(&dir).unwrap();
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700, "an existing dir is tightened");
+    }
+
+    #[test]
+    fn test_tampered_artifact_is_recompiled() {
+        let engine = Engine::default();
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, module) = compile(&engine, "i32.const 9");
+        let mut cache = ModuleCache::default().with_disk_dir(dir.path()).unwrap();
+        cache.insert(hash, &module);
+        drop(cache);
+
+        let artifact = dir.path().join(format!("{hash:?}.cwasm"));
+        let mut bytes = fs::read(&artifact).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        fs::write(&artifact, bytes).unwrap();
+
+        let mut restarted = ModuleCache::default().with_disk_dir(dir.path()).unwrap();
+        assert!(restarted.get(&engine, &hash).is_none());
+        assert_eq!(restarted.stats().misses, 1);
+        assert!(!artifact.exists(), "the tampered artifact is removed");
+        assert_eq!(restarted.disk_size_bytes(), 0);
+    }
+
+    #[test]
+    fn test_disk_cache_is_bounded() {
+        let engine = Engine::default();
+        let dir = tempfile::tempdir().unwrap();
+        let (a, module_a) = compile(&engine, "i32.const 0");
+        let (b, module_b) = compile(&engine, "i32.const 1");
+        let artifact_size = module_a.serialize().unwrap().len();
+        let mut cache = ModuleCache::new(artifact_size + artifact_size / 2)
+            .with_disk_dir(dir.path())
+            .unwrap();
+
+        cache.insert(a, &module_a);
+        cache.insert(b, &module_b);
+        assert!(!dir.path().join(format!("{a:?}.cwasm")).exists());
+        assert!(!dir.path().join(format!("{a:?}.sha256")).exists());
+        assert!(dir.path().join(format!("{b:?}.cwasm")).exists());
+        assert!(cache.disk_size_bytes() <= (artifact_size + artifact_size / 2) as u64);
+
+        // A restart with a smaller budget trims what is already there.
+        drop(cache);
+        let restarted = ModuleCache::new(1).with_disk_dir(dir.path()).unwrap();
+        assert_eq!(restarted.disk_size_bytes(), 0);
+        assert!(!dir.path().join(format!("{b:?}.cwasm")).exists());
+    }
+}
@@ -0,0 +1,713 @@
+use crate::host_functions::{HostFunctions, Program};
+use aether_codecs::abi::{self, aic_token, amm, governance, job_escrow, staking};
+use aether_codecs::{decode_bincode, encode_bincode, CanonicalReader, CanonicalWriter};
+use aether_program_aic_token::AicTokenState;
+use aether_program_amm::LiquidityPool;
+use aether_program_governance::GovernanceState;
+use aether_program_job_escrow::{FraudReport, JobEscrowState};
+use aether_program_staking::StakingState;
+use aether_types::{Address, H256};
+use anyhow::{anyhow, bail, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Storage key under which each precompile keeps its whole state.
+const STATE_KEY: &[u8] = b"state";
+
+/// A native program exposed at a fixed address to `call_program`.
+///
+/// Call data follows [`aether_codecs::abi`]. Each entry point has a flat gas
+/// price that covers decoding, execution and persisting the program state;
+/// views never write state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Precompile {
+    AicToken,
+    JobEscrow,
+    Governance,
+    Staking,
+    Amm,
+}
+
+impl Precompile {
+    pub const ALL: [Precompile; 5] = [
+        Precompile::AicToken,
+        Precompile::JobEscrow,
+        Precompile::Governance,
+        Precompile::Staking,
+        Precompile::Amm,
+    ];
+
+    /// `0x00..01` through `0x00..05`, in [`Precompile::ALL`] order.
+    pub fn address(self) -> Address {
+        let mut bytes = [0u8; 20];
+        bytes[19] = match self {
+            Precompile::AicToken => 0x01,
+            Precompile::JobEscrow => 0x02,
+            Precompile::Governance => 0x03,
+            Precompile::Staking => 0x04,
+            Precompile::Amm => 0x05,
+        };
+        Address::from(bytes)
+    }
+
+    pub fn from_address(address: &Address) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.address() == *address)
+    }
+
+    /// Gas charged for `selector`, or `None` if the program has no such
+    /// entry point.
+    pub fn gas_price(self, selector: u16) -> Option<u64> {
+        let price = match (self, selector) {
+            (Precompile::AicToken, aic_token::TRANSFER) => 9_000,
+            (Precompile::AicToken, aic_token::APPROVE) => 6_000,
+            (Precompile::AicToken, aic_token::TRANSFER_FROM) => 12_000,
+            (Precompile::AicToken, aic_token::MINT) => 9_000,
+            (Precompile::AicToken, aic_token::BURN) => 7_000,
+            (Precompile::AicToken, aic_token::BALANCE_OF | aic_token::ALLOWANCE) => 400,
+
+            (Precompile::JobEscrow, job_escrow::POST_JOB) => 30_000,
+            (Precompile::JobEscrow, job_escrow::ACCEPT_JOB) => 15_000,
+            (Precompile::JobEscrow, job_escrow::CHALLENGE_JOB) => 15_000,
+            (Precompile::JobEscrow, job_escrow::CANCEL_JOB) => 12_000,
+            (Precompile::JobEscrow, job_escrow::DEPOSIT_BOND) => 10_000,
+            (Precompile::JobEscrow, job_escrow::WITHDRAW_BOND) => 20_000,
+            (Precompile::JobEscrow, job_escrow::RELEASE_MILESTONE) => 20_000,
+            // Decodes the report and the stored receipt.
+            (Precompile::JobEscrow, job_escrow::REPORT_FRAUD) => 40_000,
+            (Precompile::JobEscrow, job_escrow::ESCROWED_BALANCE_OF | job_escrow::BOND_OF) => 400,
+
+            (Precompile::Governance, governance::VOTE) => 15_000,
+            (Precompile::Governance, governance::FINALIZE) => 20_000,
+            (Precompile::Governance, governance::CANCEL) => 10_000,
+            // Delegation recomputes every account's effective power.
+            (Precompile::Governance, governance::DELEGATE | governance::UNDELEGATE) => 40_000,
+            (Precompile::Governance, governance::VOTING_POWER) => 400,
+
+            (Precompile::Staking, staking::REGISTER_VALIDATOR) => 50_000,
+            (Precompile::Staking, staking::DELEGATE) => 20_000,
+            (Precompile::Staking, staking::UNBOND) => 25_000,
+            (Precompile::Staking, staking::UNJAIL) => 15_000,
+            (Precompile::Staking, staking::TOTAL_STAKED) => 400,
+
+            (Precompile::Amm, amm::CREATE_POOL) => 40_000,
+            (Precompile::Amm, amm::ADD_LIQUIDITY | amm::REMOVE_LIQUIDITY) => 30_000,
+            (Precompile::Amm, amm::SWAP_A_TO_B | amm::SWAP_B_TO_A) => 25_000,
+            (Precompile::Amm, amm::PRICE) => 800,
+
+            _ => return None,
+        };
+        Some(price)
+    }
+}
+
+/// Installs the native programs on a host at their [`Precompile`] addresses.
+///
+/// Program state lives in each precompile's own storage namespace and is
+/// created empty on first use. Token amounts are the programs' own ledgers,
+/// separate from native balances.
+#[derive(Debug, Clone)]
+pub struct PrecompileRegistry {
+    aic_mint_authority: Address,
+}
+
+impl PrecompileRegistry {
+    pub fn new(aic_mint_authority: Address) -> Self {
+        PrecompileRegistry { aic_mint_authority }
+    }
+
+    pub fn install(&self, host: &mut HostFunctions) {
+        for precompile in Precompile::ALL {
+            let program = NativeProgram {
+                precompile,
+                aic_mint_authority: self.aic_mint_authority,
+            };
+            host.register_program(precompile.address(), Arc::new(program));
+        }
+    }
+}
+
+struct NativeProgram {
+    precompile: Precompile,
+    aic_mint_authority: Address,
+}
+
+impl Program for NativeProgram {
+    fn invoke(&self, host: &mut HostFunctions, input: &[u8]) -> Result<Vec<u8>> {
+        let (selector, args) = abi::decode_call(input)?;
+        let price = self
+            .precompile
+            .gas_price(selector)
+            .ok_or_else(|| anyhow!("{:?} has no entry point {selector:#06x}", self.precompile))?;
+        host.charge_gas(price)?;
+
+        match self.precompile {
+            Precompile::AicToken => with_state(
+                host,
+                selector,
+                || AicTokenState::new(self.aic_mint_authority),
+                |state, caller, _| call_aic_token(state, caller, selector, args),
+            ),
+            Precompile::JobEscrow => with_state(
+                host,
+                selector,
+                JobEscrowState::new,
+                |state, caller, slot| call_job_escrow(state, caller, slot, selector, args),
+            ),
+            Precompile::Governance => with_state(
+                host,
+                selector,
+                GovernanceState::new,
+                |state, caller, slot| call_governance(state, caller, slot, selector, args),
+            ),
+            Precompile::Staking => {
+                with_state(host, selector, StakingState::new, |state, caller, slot| {
+                    call_staking(state, caller, slot, selector, args)
+                })
+            }
+            Precompile::Amm => with_state(host, selector, HashMap::new, |pools, _, _| {
+                call_amm(pools, selector, args)
+            }),
+        }
+    }
+}
+
+/// Run `f` against the program's decoded state with the caller and current
+/// slot, writing the state back unless `selector` is a view.
+fn with_state<S, F>(
+    host: &mut HostFunctions,
+    selector: u16,
+    init: impl FnOnce() -> S,
+    f: F,
+) -> Result<Vec<u8>>
+where
+    S: Serialize + DeserializeOwned,
+    F: FnOnce(&mut S, Address, u64) -> Result<Vec<u8>>,
+{
+    let mut state = match host.own_slot(STATE_KEY) {
+        Some(bytes) => decode_bincode(bytes)?,
+        None => init(),
+    };
+    let context = host.context();
+    let output = f(&mut state, context.caller, context.block_number)?;
+    if !abi::is_view(selector) {
+        host.set_own_slot(STATE_KEY, encode_bincode(&state)?);
+    }
+    Ok(output)
+}
+
+fn take_address(args: &mut CanonicalReader<'_>) -> Result<Address> {
+    Ok(Address::from(args.take_fixed::<20>()?))
+}
+
+fn take_hash(args: &mut CanonicalReader<'_>) -> Result<H256> {
+    Ok(H256::from(args.take_fixed::<32>()?))
+}
+
+fn encode_u128(value: u128) -> Vec<u8> {
+    let mut writer = CanonicalWriter::new();
+    writer.put_u128(value);
+    writer.finish()
+}
+
+fn call_aic_token(
+    state: &mut AicTokenState,
+    caller: Address,
+    selector: u16,
+    mut args: CanonicalReader<'_>,
+) -> Result<Vec<u8>> {
+    let output = match selector {
+        aic_token::TRANSFER => {
+            let (to, amount) = (take_address(&mut args)?, args.take_u128()?);
+            state
+                .transfer(caller, to, amount)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        aic_token::APPROVE => {
+            let (spender, amount) = (take_address(&mut args)?, args.take_u128()?);
+            state
+                .approve(caller, spender, amount)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        aic_token::TRANSFER_FROM => {
+            let from = take_address(&mut args)?;
+            let (to, amount) = (take_address(&mut args)?, args.take_u128()?);
+            state
+                .transfer_from(caller, from, to, amount)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        aic_token::MINT => {
+            let (to, amount) = (take_address(&mut args)?, args.take_u128()?);
+            state.mint(caller, to, amount).map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        aic_token::BURN => {
+            let amount = args.take_u128()?;
+            state
+                .burn(caller, caller, amount)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        aic_token::BALANCE_OF => encode_u128(state.balance_of(&take_address(&mut args)?)),
+        aic_token::ALLOWANCE => {
+            let owner = take_address(&mut args)?;
+            encode_u128(state.allowance_of(&owner, &take_address(&mut args)?))
+        }
+        _ => bail!("unknown AIC token entry point {selector:#06x}"),
+    };
+    args.finish()?;
+    Ok(output)
+}
+
+fn call_job_escrow(
+    state: &mut JobEscrowState,
+    caller: Address,
+    slot: u64,
+    selector: u16,
+    mut args: CanonicalReader<'_>,
+) -> Result<Vec<u8>> {
+    let output = match selector {
+        job_escrow::POST_JOB => {
+            let job_id = take_hash(&mut args)?;
+            let model_hash = take_hash(&mut args)?;
+            let input_hash = take_hash(&mut args)?;
+            let payment = args.take_u128()?;
+            let deadline_slots = args.take_u64()?;
+            state
+                .post_job(
+                    job_id,
+                    caller,
+                    model_hash,
+                    input_hash,
+                    payment,
+                    slot,
+                    deadline_slots,
+                )
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::ACCEPT_JOB => {
+            state
+                .accept_job(take_hash(&mut args)?, caller)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::CHALLENGE_JOB => {
+            state
+                .challenge_job(take_hash(&mut args)?, caller)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::CANCEL_JOB => {
+            state
+                .cancel_job(take_hash(&mut args)?, caller)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::DEPOSIT_BOND => {
+            state
+                .deposit_bond(caller, args.take_u128()?)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::WITHDRAW_BOND => {
+            state
+                .withdraw_bond(caller, args.take_u128()?)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::REPORT_FRAUD => {
+            let report: FraudReport = decode_bincode(args.take_bytes()?)?;
+            state
+                .report_fraud(&report, caller, slot)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::RELEASE_MILESTONE => {
+            let job_id = take_hash(&mut args)?;
+            let provider = take_address(&mut args)?;
+            let amount = args.take_u128()?;
+            state
+                .release_milestone(job_id, caller, provider, amount)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        job_escrow::ESCROWED_BALANCE_OF => {
+            encode_u128(state.escrowed_balance_of(&take_address(&mut args)?))
+        }
+        job_escrow::BOND_OF => encode_u128(state.bond_of(&take_address(&mut args)?)),
+        _ => bail!("unknown job escrow entry point {selector:#06x}"),
+    };
+    args.finish()?;
+    Ok(output)
+}
+
+fn call_governance(
+    state: &mut GovernanceState,
+    caller: Address,
+    slot: u64,
+    selector: u16,
+    mut args: CanonicalReader<'_>,
+) -> Result<Vec<u8>> {
+    let output = match selector {
+        governance::VOTE => {
+            let proposal_id = take_hash(&mut args)?;
+            let vote_for = match args.take_u8()? {
+                0 => false,
+                1 => true,
+                other => bail!("vote_for must be 0 or 1, got {other}"),
+            };
+            state
+                .vote(proposal_id, caller, vote_for, slot)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        governance::FINALIZE => {
+            state
+                .finalize(take_hash(&mut args)?, slot)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        governance::CANCEL => {
+            state
+                .cancel(take_hash(&mut args)?, caller)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        governance::DELEGATE => {
+            state
+                .delegate(caller, take_address(&mut args)?)
+                .map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        governance::UNDELEGATE => {
+            state.undelegate(caller).map_err(anyhow::Error::msg)?;
+            Vec::new()
+        }
+        governance::VOTING_POWER => {
+            encode_u128(state.effective_voting_power(&take_address(&mut args)?))
+        }
+        _ => bail!("unknown governance entry point {selector:#06x}"),
+    };
+    args.finish()?;
+    Ok(output)
+}
+
+fn call_staking(
+    state: &mut StakingState,
+    caller: Address,
+    slot: u64,
+    selector: u16,
+    mut args: CanonicalReader<'_>,
+) -> Result<Vec<u8>> {
+    let output = match selector {
+        staking::REGISTER_VALIDATOR => {
+            let initial_stake = args.take_u128()?;
+            let commission_bps = args.take_u16()?;
+            let reward_address = take_address(&mut args)?;
+            state.register_validator(
+                caller,
+                caller,
+                initial_stake,
+                commission_bps,
+                reward_address,
+            )?;
+            Vec::new()
+        }
+        staking::DELEGATE => {
+            let (validator, amount) = (take_address(&mut args)?, args.take_u128()?);
+            state.delegate(caller, caller, validator, amount)?;
+            Vec::new()
+        }
+        staking::UNBOND => {
+            let (validator, amount) = (take_address(&mut args)?, args.take_u128()?);
+            state.unbond(caller, caller, validator, amount, slot)?;
+            Vec::new()
+        }
+        staking::UNJAIL => {
+            state.unjail(caller, caller, slot)?;
+            Vec::new()
+        }
+        staking::TOTAL_STAKED => encode_u128(state.get_total_staked()),
+        _ => bail!("unknown staking entry point {selector:#06x}"),
+    };
+    args.finish()?;
+    Ok(output)
+}
+
+fn call_amm(
+    pools: &mut HashMap<H256, LiquidityPool>,
+    selector: u16,
+    mut args: CanonicalReader<'_>,
+) -> Result<Vec<u8>> {
+    let pool_id = take_hash(&mut args)?;
+    if selector == amm::CREATE_POOL {
+        let token_a = take_address(&mut args)?;
+        let token_b = take_address(&mut args)?;
+        let fee_bps = args.take_u32()?;
+        args.finish()?;
+        if pools.contains_key(&pool_id) {
+            bail!("pool {pool_id:?} already exists");
+        }
+        let pool =
+            LiquidityPool::new(pool_id, token_a, token_b, fee_bps).map_err(anyhow::Error::msg)?;
+        pools.insert(pool_id, pool);
+        return Ok(Vec::new());
+    }
+
+    let pool = pools
+        .get_mut(&pool_id)
+        .ok_or_else(|| anyhow!("pool {pool_id:?} not found"))?;
+    let output = match selector {
+        amm::ADD_LIQUIDITY => {
+            let (amount_a, amount_b) = (args.take_u128()?, args.take_u128()?);
+            let min_lp = args.take_u128()?;
+            encode_u128(
+                pool.add_liquidity(amount_a, amount_b, min_lp)
+                    .map_err(anyhow::Error::msg)?,
+            )
+        }
+        amm::REMOVE_LIQUIDITY => {
+            let lp_tokens = args.take_u128()?;
+            let (min_a, min_b) = (args.take_u128()?, args.take_u128()?);
+            let (amount_a, amount_b) = pool
+                .remove_liquidity(lp_tokens, min_a, min_b)
+                .map_err(anyhow::Error::msg)?;
+            let mut writer = CanonicalWriter::new();
+            writer.put_u128(amount_a).put_u128(amount_b);
+            writer.finish()
+        }
+        amm::SWAP_A_TO_B => {
+            let (amount_in, min_out) = (args.take_u128()?, args.take_u128()?);
+            encode_u128(
+                pool.swap_a_to_b(amount_in, min_out)
+                    .map_err(anyhow::Error::msg)?,
+            )
+        }
+        amm::SWAP_B_TO_A => {
+            let (amount_in, min_out) = (args.take_u128()?, args.take_u128()?);
+            encode_u128(
+                pool.swap_b_to_a(amount_in, min_out)
+                    .map_err(anyhow::Error::msg)?,
+            )
+        }
+        amm::PRICE => encode_u128(pool.get_price().map_err(anyhow::Error::msg)?),
+        _ => bail!("unknown AMM entry point {selector:#06x}"),
+    };
+    args.finish()?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host_functions::ExecutionContext;
+    use aether_codecs::encode_call;
+
+    fn addr(b: u8) -> Address {
+        Address::from([b; 20])
+    }
+
+    fn host(caller: Address) -> HostFunctions {
+        let ctx = ExecutionContext {
+            block_number: 10,
+            caller,
+            contract_address: caller,
+            ..ExecutionContext::default()
+        };
+        let mut host = HostFunctions::new(10_000_000, ctx);
+        PrecompileRegistry::new(addr(0xa1)).install(&mut host);
+        host
+    }
+
+    fn balance_of(host: &mut HostFunctions, account: Address) -> u128 {
+        let call = encode_call(aic_token::BALANCE_OF, |w| {
+            w.put_fixed(account.as_bytes());
+        });
+        let outcome = host
+            .call_program(&Precompile::AicToken.address(), &call, u64::MAX)
+            .unwrap();
+        assert!(outcome.success);
+        let mut reader = CanonicalReader::new(&outcome.return_data);
+        reader.take_u128().unwrap()
+    }
+
+    #[test]
+    fn test_addresses_are_distinct_and_priced() {
+        for precompile in Precompile::ALL {
+            assert_eq!(
+                Precompile::from_address(&precompile.address()),
+                Some(precompile)
+            );
+        }
+        assert_eq!(Precompile::from_address(&addr(0x01)), None);
+        assert_eq!(
+            Precompile::AicToken.gas_price(aic_token::TRANSFER),
+            Some(9_000)
+        );
+        assert_eq!(Precompile::Staking.gas_price(aic_token::ALLOWANCE), None);
+    }
+
+    #[test]
+    fn test_aic_token_mint_and_transfer_via_call_program() {
+        let token = Precompile::AicToken.address();
+        let mut authority = host(addr(0xa1));
+        let mint = encode_call(aic_token::MINT, |w| {
+            w.put_fixed(addr(0xa1).as_bytes()).put_u128(1_000);
+        });
+        let outcome = authority.call_program(&token, &mint, u64::MAX).unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.gas_used, 9_000);
+
+        let transfer = encode_call(aic_token::TRANSFER, |w| {
+            w.put_fixed(addr(0xb2).as_bytes()).put_u128(400);
+        });
+        assert!(
+            authority
+                .call_program(&token, &transfer, u64::MAX)
+                .unwrap()
+                .success
+        );
+        assert_eq!(balance_of(&mut authority, addr(0xa1)), 600);
+        assert_eq!(balance_of(&mut authority, addr(0xb2)), 400);
+    }
+
+    #[test]
+    fn test_failed_entry_point_reverts_and_rejects_bad_input() {
+        let token = Precompile::AicToken.address();
+        let mut stranger = host(addr(0xc3));
+        let mint = encode_call(aic_token::MINT, |w| {
+            w.put_fixed(addr(0xc3).as_bytes()).put_u128(1);
+        });
+        let outcome = stranger.call_program(&token, &mint, u64::MAX).unwrap();
+        assert!(!outcome.success, "only the mint authority may mint");
+        assert_eq!(balance_of(&mut stranger, addr(0xc3)), 0);
+
+        let trailing = encode_call(aic_token::BALANCE_OF, |w| {
+            w.put_fixed(&[0u8; 21]);
+        });
+        assert!(
+            !stranger
+                .call_program(&token, &trailing, u64::MAX)
+                .unwrap()
+                .success
+        );
+        let unknown = encode_call(0x7f, |_| {});
+        assert!(
+            !stranger
+                .call_program(&token, &unknown, u64::MAX)
+                .unwrap()
+                .success
+        );
+    }
+
+    #[test]
+    fn test_fraud_report_needs_a_submitted_job() {
+        let escrow = Precompile::JobEscrow.address();
+        let mut tower = host(addr(0xe5));
+        let garbage = encode_call(job_escrow::REPORT_FRAUD, |w| {
+            w.put_bytes(&[1, 2, 3]);
+        });
+        let outcome = tower.call_program(&escrow, &garbage, u64::MAX).unwrap();
+        assert!(!outcome.success);
+
+        let report = FraudReport {
+            vcr_id: H256::from([7u8; 32]),
+            worker_id: vec![9u8; 32],
+            challenge: aether_verifiers_kzg::IssuedChallenge {
+                challenge: aether_verifiers_kzg::KzgChallenge {
+                    vcr_id: H256::from([7u8; 32]),
+                    layer_indices: vec![0],
+                    point_indices: vec![vec![0]],
+                    deadline_slot: 5,
+                },
+                watchtower: [4u8; 32],
+            },
+            response: None,
+            reason: aether_verifiers_kzg::FraudReason::NoResponse,
+            slot: 6,
+        };
+        let call = encode_call(job_escrow::REPORT_FRAUD, |w| {
+            w.put_bytes(&encode_bincode(&report).unwrap());
+        });
+        let outcome = tower.call_program(&escrow, &call, u64::MAX).unwrap();
+        assert!(!outcome.success, "no such job");
+        assert_eq!(outcome.gas_used, 40_000);
+    }
+
+    #[test]
+    fn test_milestone_release_pays_from_escrow() {
+        let escrow = Precompile::JobEscrow.address();
+        let requester = addr(0xd4);
+        let mut host = host(requester);
+        let post = encode_call(job_escrow::POST_JOB, |w| {
+            w.put_fixed(&[1u8; 32])
+                .put_fixed(&[2u8; 32])
+                .put_fixed(&[3u8; 32])
+                .put_u128(500)
+                .put_u64(100);
+        });
+        assert!(host.call_program(&escrow, &post, u64::MAX).unwrap().success);
+
+        let release = |amount: u128| {
+            encode_call(job_escrow::RELEASE_MILESTONE, |w| {
+                w.put_fixed(&[1u8; 32])
+                    .put_fixed(addr(0xb2).as_bytes())
+                    .put_u128(amount);
+            })
+        };
+        let outcome = host.call_program(&escrow, &release(200), u64::MAX).unwrap();
+        assert!(outcome.success);
+        assert_eq!(outcome.gas_used, 20_000);
+        assert!(
+            !host
+                .call_program(&escrow, &release(301), u64::MAX)
+                .unwrap()
+                .success
+        );
+
+        let escrowed = encode_call(job_escrow::ESCROWED_BALANCE_OF, |w| {
+            w.put_fixed(requester.as_bytes());
+        });
+        let outcome = host.call_program(&escrow, &escrowed, u64::MAX).unwrap();
+        let mut reader = CanonicalReader::new(&outcome.return_data);
+        assert_eq!(reader.take_u128().unwrap(), 300);
+    }
+
+    #[test]
+    fn test_amm_pool_lifecycle() {
+        let pool = Precompile::Amm.address();
+        let mut host = host(addr(0xd4));
+        let id = [9u8; 32];
+        let create = encode_call(amm::CREATE_POOL, |w| {
+            w.put_fixed(&id)
+                .put_fixed(addr(1).as_bytes())
+                .put_fixed(addr(2).as_bytes())
+                .put_u32(30);
+        });
+        assert!(host.call_program(&pool, &create, u64::MAX).unwrap().success);
+        assert!(
+            !host.call_program(&pool, &create, u64::MAX).unwrap().success,
+            "duplicate pool"
+        );
+
+        let add = encode_call(amm::ADD_LIQUIDITY, |w| {
+            w.put_fixed(&id)
+                .put_u128(1_000_000)
+                .put_u128(2_000_000)
+                .put_u128(0);
+        });
+        assert!(host.call_program(&pool, &add, u64::MAX).unwrap().success);
+
+        let price = encode_call(amm::PRICE, |w| {
+            w.put_fixed(&id);
+        });
+        let outcome = host.call_program(&pool, &price, u64::MAX).unwrap();
+        assert_eq!(outcome.gas_used, 800);
+        let mut reader = CanonicalReader::new(&outcome.return_data);
+        assert_eq!(reader.take_u128().unwrap(), 2_000_000);
+    }
+}
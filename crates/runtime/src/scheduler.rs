@@ -1,7 +1,93 @@
-use aether_types::Transaction;
+use aether_types::{Address, Transaction, UtxoId};
 use anyhow::Result;
 use rayon::prelude::*;
-use std::collections::HashSet;
+use std::collections::HashMap;
+
+/// Conflict graph over a transaction list, built from the declared R/W
+/// sets and UTxO inputs. Node `i` is `transactions[i]`; an edge joins two
+/// transactions that must not run concurrently (see [`ParallelScheduler`]).
+#[derive(Debug, Clone, Default)]
+pub struct ConflictGraph {
+    neighbors: Vec<Vec<usize>>,
+}
+
+impl ConflictGraph {
+    pub fn build(transactions: &[Transaction]) -> Self {
+        let n = transactions.len();
+        let mut writers: HashMap<&Address, Vec<usize>> = HashMap::new();
+        let mut readers: HashMap<&Address, Vec<usize>> = HashMap::new();
+        let mut spenders: HashMap<&UtxoId, Vec<usize>> = HashMap::new();
+        for (i, tx) in transactions.iter().enumerate() {
+            for addr in &tx.writes {
+                writers.entry(addr).or_default().push(i);
+            }
+            for addr in &tx.reads {
+                readers.entry(addr).or_default().push(i);
+            }
+            for input in &tx.inputs {
+                spenders.entry(input).or_default().push(i);
+            }
+        }
+
+        let mut neighbors = vec![Vec::new(); n];
+        let mut link = |a: usize, b: usize| {
+            if a != b {
+                neighbors[a].push(b);
+                neighbors[b].push(a);
+            }
+        };
+        for (addr, ws) in &writers {
+            // Write-write
+            for (k, &a) in ws.iter().enumerate() {
+                for &b in &ws[k + 1..] {
+                    link(a, b);
+                }
+            }
+            // Write-read, either direction
+            if let Some(rs) = readers.get(addr) {
+                for &a in ws {
+                    for &b in rs {
+                        link(a, b);
+                    }
+                }
+            }
+        }
+        for users in spenders.values() {
+            for (k, &a) in users.iter().enumerate() {
+                for &b in &users[k + 1..] {
+                    link(a, b);
+                }
+            }
+        }
+
+        for adj in &mut neighbors {
+            adj.sort_unstable();
+            adj.dedup();
+        }
+        ConflictGraph { neighbors }
+    }
+
+    pub fn len(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.neighbors.is_empty()
+    }
+
+    /// Transactions conflicting with `i`, in ascending index order.
+    pub fn neighbors(&self, i: usize) -> &[usize] {
+        &self.neighbors[i]
+    }
+
+    pub fn conflicts(&self, a: usize, b: usize) -> bool {
+        self.neighbors[a].binary_search(&b).is_ok()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.neighbors.iter().map(Vec::len).sum::<usize>() / 2
+    }
+}
 
 /// Parallel Scheduler for Transaction Execution
 ///
@@ -10,9 +96,13 @@ use std::collections::HashSet;
 ///
 /// Algorithm:
 /// 1. Build conflict graph from R/W sets
-/// 2. Greedy coloring to partition into independent batches
+/// 2. Repeatedly take a greedy maximal independent set: every transaction
+///    whose earlier conflicting transactions are all in previous batches
 /// 3. Execute each batch in parallel (batches are sequential)
 ///
+/// Conflicting transactions therefore keep their block order, so any
+/// schedule produces the same state as executing the block serially.
+///
 /// Conflict Rule:
 /// tx_a conflicts with tx_b if:
 /// - W(a) ∩ W(b) ≠ ∅ (write-write)
@@ -32,91 +122,46 @@ impl ParallelScheduler {
 
     /// Partition transactions into non-conflicting batches.
     pub fn schedule(&self, transactions: &[Transaction]) -> Vec<Vec<Transaction>> {
-        if transactions.is_empty() {
-            return vec![];
-        }
-
-        let mut batches: Vec<Vec<Transaction>> = vec![];
-        let mut remaining: Vec<Transaction> = transactions.to_vec();
-
-        while !remaining.is_empty() {
-            let mut current_batch = vec![];
-            let mut used_indices = HashSet::new();
-
-            for (i, tx) in remaining.iter().enumerate() {
-                if used_indices.contains(&i) {
-                    continue;
-                }
-
-                let mut conflicts = false;
-                for batch_tx in &current_batch {
-                    if tx.conflicts_with(batch_tx) {
-                        conflicts = true;
-                        break;
-                    }
-                }
+        self.schedule_indices(transactions)
+            .into_iter()
+            .map(|batch| batch.into_iter().map(|i| transactions[i].clone()).collect())
+            .collect()
+    }
 
-                if !conflicts && !Self::has_pending_dependencies(tx, i, &remaining, &used_indices) {
-                    current_batch.push(tx.clone());
-                    used_indices.insert(i);
+    /// Partition transactions into non-conflicting batches of indices into
+    /// `transactions`, each batch in ascending order.
+    pub fn schedule_indices(&self, transactions: &[Transaction]) -> Vec<Vec<usize>> {
+        let graph = ConflictGraph::build(transactions);
 
-                    if current_batch.len() >= self.max_batch_size {
-                        break;
+        // Earlier conflicting transactions not yet scheduled. A transaction
+        // is ready once this drops to zero; two ready transactions never
+        // conflict, since the later one would still be waiting on the other.
+        let mut waiting: Vec<usize> = (0..graph.len())
+            .map(|i| graph.neighbors(i).partition_point(|&j| j < i))
+            .collect();
+        let mut ready: Vec<usize> = (0..graph.len()).filter(|&i| waiting[i] == 0).collect();
+
+        let mut batches = Vec::new();
+        while !ready.is_empty() {
+            let take = ready.len().min(self.max_batch_size.max(1));
+            let batch: Vec<usize> = ready.drain(..take).collect();
+            for &i in &batch {
+                for &j in graph.neighbors(i) {
+                    if j > i {
+                        waiting[j] -= 1;
+                        if waiting[j] == 0 {
+                            ready.push(j);
+                        }
                     }
                 }
             }
-
-            remaining = remaining
-                .into_iter()
-                .enumerate()
-                .filter(|(i, _)| !used_indices.contains(i))
-                .map(|(_, tx)| tx)
-                .collect();
-
-            if !current_batch.is_empty() {
-                batches.push(current_batch);
-            } else {
-                break;
-            }
+            ready.sort_unstable();
+            batches.push(batch);
         }
 
         batches
     }
 
-    /// Check if tx at `idx` depends on an EARLIER unscheduled tx.
-    /// This enforces ordering: a tx that reads from an address written by
-    /// an earlier tx must wait for the writer to be scheduled first.
-    fn has_pending_dependencies(
-        tx: &Transaction,
-        idx: usize,
-        remaining: &[Transaction],
-        used_indices: &HashSet<usize>,
-    ) -> bool {
-        // Read-after-write: this tx reads addr X, an earlier tx writes X
-        for addr in &tx.reads {
-            for (j, other) in remaining.iter().enumerate() {
-                if j >= idx || used_indices.contains(&j) {
-                    continue; // Only check EARLIER transactions
-                }
-                if other.writes.contains(addr) {
-                    return true;
-                }
-            }
-        }
-        // Write-after-read: this tx writes addr X, an earlier tx reads X
-        for addr in &tx.writes {
-            for (j, other) in remaining.iter().enumerate() {
-                if j >= idx || used_indices.contains(&j) {
-                    continue; // Only check EARLIER transactions
-                }
-                if other.reads.contains(addr) {
-                    return true;
-                }
-            }
-        }
-        false
-    }
-
     /// Execute batches with rayon parallelism.
     ///
     /// Batches execute sequentially (they have inter-batch dependencies).
@@ -180,6 +225,40 @@ impl ParallelScheduler {
         Ok(all_results)
     }
 
+    /// Schedule and execute `transactions` against `state` with buffered
+    /// writes.
+    ///
+    /// Within a batch every transaction runs on rayon against the same
+    /// snapshot of `state` and returns its writes instead of applying them.
+    /// Once the batch finishes, `apply` commits the buffers in transaction
+    /// order. Batches never contain conflicting transactions, so the result
+    /// matches serial execution in block order.
+    pub fn execute_buffered<S, W, F, A>(
+        &self,
+        transactions: &[Transaction],
+        state: &mut S,
+        executor: F,
+        mut apply: A,
+    ) -> Result<()>
+    where
+        S: Sync,
+        W: Send,
+        F: Fn(&S, &Transaction) -> Result<W> + Sync,
+        A: FnMut(&mut S, W) -> Result<()>,
+    {
+        for batch in self.schedule_indices(transactions) {
+            let snapshot = &*state;
+            let writes: Vec<W> = batch
+                .par_iter()
+                .map(|&i| executor(snapshot, &transactions[i]))
+                .collect::<Result<_>>()?;
+            for w in writes {
+                apply(state, w)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Calculate potential speedup.
     pub fn speedup_estimate(&self, transactions: &[Transaction]) -> f64 {
         if transactions.is_empty() {
@@ -200,7 +279,8 @@ impl Default for ParallelScheduler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use aether_types::{Address, PublicKey, Signature};
+    use aether_types::{PublicKey, Signature};
+    use sha2::{Digest, Sha256};
     use std::collections::HashSet;
     use std::sync::atomic::{AtomicU64, Ordering};
     use std::sync::Arc;
@@ -411,4 +491,151 @@ mod tests {
         let total: u128 = results.iter().flat_map(|batch| batch.iter()).sum();
         assert_eq!(total, 1000 * 10); // 10 txs * 1000 fee each
     }
+
+    #[test]
+    fn test_conflict_graph_edges() {
+        let mut spend_a = create_test_tx(vec![], vec![]);
+        let mut spend_b = create_test_tx(vec![], vec![]);
+        let utxo = UtxoId {
+            tx_hash: aether_types::H256::zero(),
+            output_index: 0,
+        };
+        spend_a.inputs.push(utxo.clone());
+        spend_b.inputs.push(utxo);
+
+        let txs = vec![
+            create_test_tx(vec![], vec![1]),  // 0
+            create_test_tx(vec![], vec![1]),  // 1: W-W with 0
+            create_test_tx(vec![1], vec![2]), // 2: R-W with 0 and 1
+            create_test_tx(vec![3], vec![4]), // 3: independent
+            spend_a,                          // 4
+            spend_b,                          // 5: same input as 4
+        ];
+        let graph = ConflictGraph::build(&txs);
+
+        assert_eq!(graph.len(), 6);
+        assert_eq!(graph.neighbors(0), &[1, 2]);
+        assert_eq!(graph.neighbors(2), &[0, 1]);
+        assert!(graph.neighbors(3).is_empty());
+        assert!(graph.conflicts(4, 5));
+        assert!(!graph.conflicts(3, 4));
+        assert_eq!(graph.edge_count(), 4);
+    }
+
+    #[test]
+    fn test_schedule_keeps_order_of_conflicting_writers() {
+        let scheduler = ParallelScheduler::new();
+
+        // tx1 waits on tx0; tx2 writes the same slot as tx1 and must not
+        // jump ahead of it even though it does not touch tx0's keys.
+        let txs = vec![
+            create_test_tx(vec![], vec![1]),
+            create_test_tx(vec![1], vec![2]),
+            create_test_tx(vec![], vec![2]),
+        ];
+
+        assert_eq!(
+            scheduler.schedule_indices(&txs),
+            vec![vec![0], vec![1], vec![2]]
+        );
+    }
+
+    #[test]
+    fn test_schedule_respects_max_batch_size() {
+        let scheduler = ParallelScheduler { max_batch_size: 4 };
+        let txs: Vec<Transaction> = (0..10u8).map(|i| create_test_tx(vec![], vec![i])).collect();
+
+        let sizes: Vec<usize> = scheduler
+            .schedule_indices(&txs)
+            .iter()
+            .map(Vec::len)
+            .collect();
+        assert_eq!(sizes, vec![4, 4, 2]);
+    }
+
+    /// Toy account state keyed by address; the root hashes entries in key order.
+    type State = HashMap<Address, u64>;
+
+    fn state_root(state: &State) -> [u8; 32] {
+        let mut entries: Vec<_> = state.iter().collect();
+        entries.sort_by_key(|(addr, _)| addr.as_bytes());
+        let mut hasher = Sha256::new();
+        for (addr, value) in entries {
+            hasher.update(addr.as_bytes());
+            hasher.update(value.to_le_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    /// Reads every declared key and writes a value derived from them; only
+    /// touches the transaction's declared R/W sets.
+    fn run_tx(state: &State, tx: &Transaction) -> Vec<(Address, u64)> {
+        let seen = tx
+            .reads
+            .iter()
+            .chain(&tx.writes)
+            .map(|a| state.get(a).copied().unwrap_or(0))
+            .fold(0u64, u64::wrapping_add);
+        tx.writes
+            .iter()
+            .map(|a| {
+                let salt = a.as_bytes()[0] as u64;
+                (*a, seen.wrapping_mul(31).wrapping_add(tx.nonce ^ salt))
+            })
+            .collect()
+    }
+
+    fn random_block(seed: u64, len: usize) -> Vec<Transaction> {
+        let mut rng = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+        let mut next = move |bound: u64| {
+            rng ^= rng << 13;
+            rng ^= rng >> 7;
+            rng ^= rng << 17;
+            rng % bound
+        };
+        (0..len)
+            .map(|n| {
+                let reads = (0..next(3)).map(|_| next(16) as u8).collect();
+                let writes = (0..1 + next(2)).map(|_| next(16) as u8).collect();
+                let mut tx = create_test_tx(reads, writes);
+                tx.nonce = n as u64;
+                tx
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parallel_and_serial_state_roots_match() {
+        let scheduler = ParallelScheduler::new();
+
+        for seed in 0..50 {
+            let txs = random_block(seed, 64);
+
+            let mut serial = State::new();
+            for tx in &txs {
+                for (addr, value) in run_tx(&serial, tx) {
+                    serial.insert(addr, value);
+                }
+            }
+
+            let mut parallel = State::new();
+            scheduler
+                .execute_buffered(
+                    &txs,
+                    &mut parallel,
+                    |state, tx| Ok(run_tx(state, tx)),
+                    |state, writes| {
+                        state.extend(writes);
+                        Ok(())
+                    },
+                )
+                .unwrap();
+
+            assert_eq!(
+                state_root(&serial),
+                state_root(&parallel),
+                "seed {seed}: parallel execution diverged from serial"
+            );
+        }
+    }
 }
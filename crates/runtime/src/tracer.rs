@@ -0,0 +1,55 @@
+use aether_types::Address;
+use serde::Serialize;
+
+/// What one transaction did, recorded when a host is built with
+/// [`HostFunctions::with_tracing`](crate::HostFunctions::with_tracing).
+///
+/// Entries from calls that were later reverted stay in the trace, so a
+/// failed job shows how far it got.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionTrace {
+    /// Host calls in the order they started; nested calls follow their
+    /// `call_program`.
+    pub host_calls: Vec<HostCallTrace>,
+    /// Gas of each program invocation, innermost first, ending with the
+    /// top-level program.
+    pub sections: Vec<GasSection>,
+    pub state_accesses: Vec<StateAccess>,
+    pub gas_used: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostCallTrace {
+    pub name: &'static str,
+    /// Call depth of the program making the call; the top level is 0.
+    pub depth: usize,
+    pub program: Address,
+    /// Gas charged, including a callee's gas for `call_program`.
+    pub gas: u64,
+    /// Why the call failed, if it did.
+    pub error: Option<String>,
+}
+
+/// Gas used by one program invocation, including its nested calls.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasSection {
+    pub program: Address,
+    pub depth: usize,
+    pub gas_used: u64,
+    /// False if the invocation failed and its changes were rolled back.
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum StateAccess {
+    StorageRead { program: Address, key: Vec<u8> },
+    StorageWrite { program: Address, key: Vec<u8> },
+    AccountRead { account: Address },
+    AccountWrite { account: Address },
+    BalanceRead { account: Address },
+    Transfer { from: Address, to: Address },
+}
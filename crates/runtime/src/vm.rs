@@ -1,7 +1,12 @@
-use aether_types::{Address, H256};
+use crate::host_functions::{self, HostFunctions};
+use crate::module_cache::ModuleCache;
+use crate::tracer::{ExecutionTrace, StateAccess};
+use aether_crypto_kzg::{KzgVerifier, PACKED_OPENING_LEN};
+use aether_types::{Address, FeeParams, H256};
 use anyhow::{bail, Result};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
+use wasmparser::{Validator, WasmFeatures};
 use wasmtime::*;
 
 /// Maximum WASM linear memory: 16 MB (256 pages × 64 KB).
@@ -20,9 +25,21 @@ const MAX_TABLE_ELEMENTS: u32 = 10_000;
 /// Uses Wasmtime with fuel-based gas metering, deterministic configuration
 /// (no SIMD, no threads, no floating point), and host function bindings
 /// for blockchain state interaction.
+///
+/// Gas is Wasmtime fuel: one unit per instruction plus the host function
+/// costs in [`gas_costs`]. It is the
+/// compute-step term of the fee formula `a + b*bytes + c*steps + d*mem`;
+/// peak linear memory is reported separately for the `d*mem` term (see
+/// [`ExecutionResult::fee`]).
+///
+/// Compiled modules are cached by code hash, so only the first execution
+/// of a program pays for validation and compilation.
 pub struct WasmVm {
     engine: Engine,
     gas_limit: u64,
+    /// Setup for the `kzg_verify` host function; without one it always fails.
+    kzg: Option<Arc<KzgVerifier>>,
+    modules: ModuleCache,
 }
 
 #[derive(Debug, Clone)]
@@ -35,13 +52,52 @@ pub struct ExecutionContext {
     pub timestamp: u64,
 }
 
+/// How an execution ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionStatus {
+    /// The entry point returned 0.
+    Success,
+    /// The entry point returned this non-zero code.
+    Reverted(i32),
+    /// Gas ran out; all of it is charged.
+    OutOfGas,
+    /// The module trapped: unreachable, out-of-bounds access, stack
+    /// overflow, integer division by zero and so on.
+    Trapped(String),
+    /// The module exports none of `execute`, `main` or `_start`.
+    NoEntryPoint,
+}
+
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
+    /// `status == ExecutionStatus::Success`.
     pub success: bool,
+    pub status: ExecutionStatus,
     pub gas_used: u64,
+    /// Peak linear memory in bytes.
+    pub memory_bytes: u64,
     pub return_data: Vec<u8>,
     pub logs: Vec<Log>,
     pub storage_changes: HashMap<Vec<u8>, Vec<u8>>,
+    /// Host calls, gas and state accesses, if the host was built
+    /// [`with_tracing`](HostFunctions::with_tracing).
+    pub trace: Option<ExecutionTrace>,
+}
+
+impl ExecutionResult {
+    /// Fee for this execution under `a + b*bytes + c*steps + d*mem`, with
+    /// `tx_bytes` the serialized transaction size, gas as steps and peak
+    /// memory as mem. `None` on overflow.
+    pub fn fee(&self, params: &FeeParams, tx_bytes: usize) -> Option<u128> {
+        let bytes = params.b.checked_mul(tx_bytes as u128)?;
+        let steps = params.c.checked_mul(self.gas_used as u128)?;
+        let mem = params.d.checked_mul(self.memory_bytes as u128)?;
+        params
+            .a
+            .checked_add(bytes)?
+            .checked_add(steps)?
+            .checked_add(mem)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,6 +112,8 @@ const MAX_STORAGE_VAL_LEN: usize = 4096;
 const MAX_LOG_DATA_LEN: usize = 4096;
 const MAX_LOG_COUNT: usize = 100;
 const MAX_RETURN_DATA_LEN: usize = 4096;
+const MAX_CALL_INPUT_LEN: usize = 4096;
+const MAX_SIG_MESSAGE_LEN: usize = 4096;
 
 /// Shared state accessible to host functions during execution.
 struct HostState {
@@ -63,11 +121,16 @@ struct HostState {
     logs: Vec<Log>,
     return_data: Vec<u8>,
     context: ExecutionContext,
+    /// The transaction's host; records the trace when tracing.
+    host: HostFunctions,
 }
 
 /// Store data that wraps host state and enforces resource limits.
 struct StoreData {
     host: Arc<Mutex<HostState>>,
+    kzg: Option<Arc<KzgVerifier>>,
+    /// Largest linear memory the module was allowed to grow to.
+    peak_memory: usize,
 }
 
 impl ResourceLimiter for StoreData {
@@ -77,7 +140,11 @@ impl ResourceLimiter for StoreData {
         desired: usize,
         _maximum: Option<usize>,
     ) -> Result<bool> {
-        Ok(desired <= MAX_MEMORY_BYTES && desired >= current)
+        let allowed = desired <= MAX_MEMORY_BYTES && desired >= current;
+        if allowed {
+            self.peak_memory = self.peak_memory.max(desired);
+        }
+        Ok(allowed)
     }
 
     fn table_growing(&mut self, current: u32, desired: u32, _maximum: Option<u32>) -> Result<bool> {
@@ -95,6 +162,7 @@ impl WasmVm {
         config.wasm_threads(false);
         config.wasm_bulk_memory(true);
         config.wasm_multi_value(true);
+        config.wasm_multi_memory(false);
         // Limit maximum WASM memory to 16MB (256 pages × 64KB) to prevent OOM
         config.static_memory_maximum_size(16 * 1024 * 1024);
         config.cranelift_opt_level(OptLevel::Speed);
@@ -106,7 +174,67 @@ impl WasmVm {
         let engine = Engine::new(&config)
             .map_err(|e| anyhow::anyhow!("failed to create Wasmtime engine: {e}"))?;
 
-        Ok(WasmVm { engine, gas_limit })
+        Ok(WasmVm {
+            engine,
+            gas_limit,
+            kzg: None,
+            modules: ModuleCache::default(),
+        })
+    }
+
+    /// Replace the default in-memory module cache, e.g. with one backed by
+    /// a directory.
+    pub fn with_module_cache(mut self, modules: ModuleCache) -> Self {
+        self.modules = modules;
+        self
+    }
+
+    pub fn module_cache(&self) -> &ModuleCache {
+        &self.modules
+    }
+
+    /// Compile hot programs ahead of their first execution, e.g. at
+    /// startup. Returns how many are ready; invalid modules are skipped.
+    pub fn prewarm<'a>(&mut self, programs: impl IntoIterator<Item = &'a [u8]>) -> usize {
+        programs
+            .into_iter()
+            .filter(|wasm| match self.load_module(wasm) {
+                Ok(_) => true,
+                Err(e) => {
+                    tracing::warn!(error = %e, "skipping program that failed to pre-compile");
+                    false
+                }
+            })
+            .count()
+    }
+
+    /// The compiled module for `wasm_bytes`, validating and compiling it
+    /// only if it is not cached.
+    fn load_module(&mut self, wasm_bytes: &[u8]) -> Result<Module> {
+        // Validate WASM magic number
+        if wasm_bytes.len() < 4 || &wasm_bytes[0..4] != b"\0asm" {
+            bail!("invalid WASM magic number");
+        }
+
+        if wasm_bytes.len() > 1024 * 1024 {
+            bail!("WASM module too large (max 1MB)");
+        }
+
+        let code_hash = ModuleCache::code_hash(wasm_bytes);
+        if let Some(module) = self.modules.get(&self.engine, &code_hash) {
+            return Ok(module);
+        }
+
+        Self::validate_deterministic(wasm_bytes)?;
+        let module = Module::new(&self.engine, wasm_bytes)?;
+        self.modules.insert(code_hash, &module);
+        Ok(module)
+    }
+
+    /// Enable the `kzg_verify` host function with the chain's trusted setup.
+    pub fn with_kzg_verifier(mut self, verifier: Arc<KzgVerifier>) -> Self {
+        self.kzg = Some(verifier);
+        self
     }
 
     /// Execute WASM bytecode with the given context and input.
@@ -115,6 +243,24 @@ impl WasmVm {
         wasm_bytes: &[u8],
         context: &ExecutionContext,
         input: &[u8],
+    ) -> Result<ExecutionResult> {
+        let mut host = HostFunctions::new(
+            context.gas_limit,
+            host_functions::ExecutionContext::default(),
+        );
+        self.execute_with_host(wasm_bytes, context, input, &mut host)
+    }
+
+    /// Execute on `host`, which supplies the state beyond the program's own
+    /// storage and, if built with tracing, yields
+    /// [`ExecutionResult::trace`]. The host runs as `context`'s program and
+    /// is handed back with whatever the execution left in it.
+    pub fn execute_with_host(
+        &mut self,
+        wasm_bytes: &[u8],
+        context: &ExecutionContext,
+        input: &[u8],
+        host: &mut HostFunctions,
     ) -> Result<ExecutionResult> {
         if context.gas_limit > self.gas_limit {
             bail!(
@@ -124,28 +270,42 @@ impl WasmVm {
             );
         }
 
-        // Validate WASM magic number
-        if wasm_bytes.len() < 4 || &wasm_bytes[0..4] != b"\0asm" {
-            bail!("invalid WASM magic number");
-        }
-
-        if wasm_bytes.len() > 1024 * 1024 {
-            bail!("WASM module too large (max 1MB)");
-        }
-
-        // Compile the module
-        let module = Module::new(&self.engine, wasm_bytes)?;
+        let module = self.load_module(wasm_bytes)?;
 
-        // Create store with fuel (gas)
+        host.set_context(host_functions::ExecutionContext {
+            block_number: context.block_number,
+            timestamp: context.timestamp,
+            caller: context.caller,
+            contract_address: context.contract_address,
+        });
+        let placeholder = HostFunctions::new(0, host_functions::ExecutionContext::default());
         let host_state = Arc::new(Mutex::new(HostState {
             storage: HashMap::new(),
             logs: Vec::new(),
             return_data: Vec::new(),
             context: context.clone(),
+            host: std::mem::replace(host, placeholder),
         }));
 
+        let result = self.run(&module, context, input, &host_state);
+
+        let mut state = host_state.lock().unwrap_or_else(PoisonError::into_inner);
+        std::mem::swap(host, &mut state.host);
+        result
+    }
+
+    fn run(
+        &self,
+        module: &Module,
+        context: &ExecutionContext,
+        input: &[u8],
+        host_state: &Arc<Mutex<HostState>>,
+    ) -> Result<ExecutionResult> {
+        // Create store with fuel (gas)
         let store_data = StoreData {
             host: host_state.clone(),
+            kzg: self.kzg.clone(),
+            peak_memory: 0,
         };
         let mut store = Store::new(&self.engine, store_data);
         store.limiter(|data| data);
@@ -155,8 +315,16 @@ impl WasmVm {
         let mut linker = Linker::new(&self.engine);
         Self::register_host_functions(&mut linker, host_state.clone())?;
 
-        // Instantiate the module
-        let instance = linker.instantiate(&mut store, &module)?;
+        // Instantiate the module. A trap in the start function is an
+        // execution outcome; anything else (unknown import, limits) is an error.
+        let instance = match linker.instantiate(&mut store, module) {
+            Ok(instance) => instance,
+            Err(e) if e.downcast_ref::<Trap>().is_some() => {
+                let status = Self::status_of(Err(e));
+                return Self::finish(status, &store, context, host_state);
+            }
+            Err(e) => return Err(e),
+        };
 
         // Get memory export (if any)
         let memory = instance.get_memory(&mut store, "memory");
@@ -172,75 +340,119 @@ impl WasmVm {
         // Call the entry point
         let func = instance.get_typed_func::<(i32, i32), i32>(&mut store, "execute");
 
-        let success = match func {
+        let status = match func {
             Ok(f) => {
                 let input_len: i32 = input.len().try_into().map_err(|_| {
                     anyhow::anyhow!("input too large for WASM (max {} bytes)", i32::MAX)
                 })?;
                 match f.call(&mut store, (0, input_len)) {
-                    Ok(result_code) => result_code == 0,
+                    Ok(result_code) => Self::status_of_code(result_code),
+                    // Out-of-fuel means out-of-gas: do NOT retry another entry point.
+                    // A retry would start a new call with 0 remaining fuel, bypassing
+                    // the gas limit entirely.
+                    Err(e) if Self::is_out_of_fuel(&e) => ExecutionStatus::OutOfGas,
                     Err(e) => {
-                        // Out-of-fuel means out-of-gas: do NOT retry another entry point.
-                        // A retry would start a new call with 0 remaining fuel, bypassing
-                        // the gas limit entirely.
-                        let oof = e
-                            .downcast_ref::<wasmtime::Trap>()
-                            .map(|t| *t == wasmtime::Trap::OutOfFuel)
-                            .unwrap_or_else(|| e.to_string().contains("fuel"));
-                        if oof {
-                            false
-                        } else {
-                            // Non-gas error — try simpler entry point with no args.
-                            // SECURITY: Reset HostState so mutations from the failed
-                            // `execute` call (storage writes, logs, return_data) do not
-                            // leak into the fallback entry point.
-                            if let Ok(mut state) = host_state.lock() {
-                                state.storage.clear();
-                                state.logs.clear();
-                                state.return_data.clear();
-                            }
-                            let simple_func =
-                                instance.get_typed_func::<(), i32>(&mut store, "main");
-                            match simple_func {
-                                Ok(f) => f.call(&mut store, ()).map(|r| r == 0).unwrap_or(false),
-                                Err(_) => false,
-                            }
+                        // Non-gas error — try simpler entry point with no args.
+                        // SECURITY: Reset HostState so mutations from the failed
+                        // `execute` call (storage writes, logs, return_data) do not
+                        // leak into the fallback entry point.
+                        if let Ok(mut state) = host_state.lock() {
+                            state.storage.clear();
+                            state.logs.clear();
+                            state.return_data.clear();
+                        }
+                        match instance.get_typed_func::<(), i32>(&mut store, "main") {
+                            Ok(f) => Self::status_of(f.call(&mut store, ())),
+                            Err(_) => ExecutionStatus::Trapped(e.to_string()),
                         }
                     }
                 }
             }
             Err(_) => {
                 // Try "main" with no args
-                let main_func = instance.get_typed_func::<(), i32>(&mut store, "main");
-                match main_func {
-                    Ok(f) => f.call(&mut store, ()).map(|r| r == 0).unwrap_or(false),
+                match instance.get_typed_func::<(), i32>(&mut store, "main") {
+                    Ok(f) => Self::status_of(f.call(&mut store, ())),
                     Err(_) => {
                         // Try _start (WASI-style)
-                        let start_func = instance.get_typed_func::<(), ()>(&mut store, "_start");
-                        match start_func {
-                            Ok(f) => f.call(&mut store, ()).is_ok(),
-                            Err(_) => false, // Module has no entry point, treat as failure
+                        match instance.get_typed_func::<(), ()>(&mut store, "_start") {
+                            Ok(f) => Self::status_of(f.call(&mut store, ()).map(|()| 0)),
+                            Err(_) => ExecutionStatus::NoEntryPoint,
                         }
                     }
                 }
             }
         };
 
-        // Calculate gas used
+        Self::finish(status, &store, context, host_state)
+    }
+
+    /// Reject modules that use float, SIMD or thread instructions before
+    /// compiling them. Float results differ across hardware (NaN bit
+    /// patterns), so a contract touching them could split consensus.
+    pub fn validate_deterministic(wasm_bytes: &[u8]) -> Result<()> {
+        let features = WasmFeatures {
+            floats: false,
+            saturating_float_to_int: false,
+            simd: false,
+            relaxed_simd: false,
+            threads: false,
+            multi_memory: false,
+            memory64: false,
+            ..WasmFeatures::default()
+        };
+        Validator::new_with_features(features)
+            .validate_all(wasm_bytes)
+            .map_err(|e| anyhow::anyhow!("nondeterministic or invalid WASM: {e}"))?;
+        Ok(())
+    }
+
+    fn is_out_of_fuel(e: &anyhow::Error) -> bool {
+        e.downcast_ref::<Trap>()
+            .map(|t| *t == Trap::OutOfFuel)
+            .unwrap_or_else(|| e.to_string().contains("fuel"))
+    }
+
+    fn status_of_code(code: i32) -> ExecutionStatus {
+        if code == 0 {
+            ExecutionStatus::Success
+        } else {
+            ExecutionStatus::Reverted(code)
+        }
+    }
+
+    fn status_of(call: Result<i32>) -> ExecutionStatus {
+        match call {
+            Ok(code) => Self::status_of_code(code),
+            Err(e) if Self::is_out_of_fuel(&e) => ExecutionStatus::OutOfGas,
+            Err(e) => ExecutionStatus::Trapped(e.to_string()),
+        }
+    }
+
+    /// Assemble the result from the store's fuel and memory and the host state.
+    fn finish(
+        status: ExecutionStatus,
+        store: &Store<StoreData>,
+        context: &ExecutionContext,
+        host_state: &Arc<Mutex<HostState>>,
+    ) -> Result<ExecutionResult> {
         let remaining_fuel = store.get_fuel().unwrap_or(0);
         let gas_used = context.gas_limit.saturating_sub(remaining_fuel);
 
-        // Collect results from host state
-        let state = host_state
+        let mut state = host_state
             .lock()
             .map_err(|_| anyhow::anyhow!("host state mutex poisoned"))?;
+        let success = status == ExecutionStatus::Success;
+        let trace = state.host.close_trace(gas_used, success);
 
         Ok(ExecutionResult {
             success,
+            status,
             gas_used,
+            memory_bytes: store.data().peak_memory as u64,
             return_data: state.return_data.clone(),
             logs: state.logs.clone(),
             storage_changes: state.storage.clone(),
+            trace,
         })
     }
 
@@ -256,14 +468,8 @@ impl WasmVm {
             "storage_read",
             |mut caller: Caller<'_, StoreData>, key_ptr: i32, key_len: i32, val_ptr: i32| -> i32 {
                 // Charge fuel for storage_read (200 fuel units)
-                match caller.get_fuel() {
-                    Ok(fuel) if fuel >= 200 => {
-                        if caller.set_fuel(fuel.saturating_sub(200)).is_err() {
-                            return -1; // Fuel deduction failed
-                        }
-                    }
-                    Ok(_) => return -1,  // Insufficient fuel
-                    Err(_) => return -1, // Fuel system unavailable
+                if !charge(&mut caller, "storage_read", 200) {
+                    return -1;
                 }
 
                 // Reject negative or oversized pointer/length values.
@@ -290,10 +496,15 @@ impl WasmVm {
                 };
 
                 let value = {
-                    let state = match caller.data().host.lock() {
+                    let mut state = match caller.data().host.lock() {
                         Ok(s) => s,
                         Err(_) => return -1,
                     };
+                    let program = state.context.contract_address;
+                    state.host.trace_access(StateAccess::StorageRead {
+                        program,
+                        key: key.clone(),
+                    });
                     state.storage.get(&key).cloned()
                 };
                 match value {
@@ -336,14 +547,8 @@ impl WasmVm {
                 // astronomically wrong gas costs via u64 wrapping.
                 let val_cost = (val_len as u64).saturating_mul(20);
                 let fuel_cost = 5000u64.saturating_add(val_cost);
-                match caller.get_fuel() {
-                    Ok(fuel) if fuel >= fuel_cost => {
-                        if caller.set_fuel(fuel.saturating_sub(fuel_cost)).is_err() {
-                            return -1;
-                        }
-                    }
-                    Ok(_) => return -1,
-                    Err(_) => return -1,
+                if !charge(&mut caller, "storage_write", fuel_cost) {
+                    return -1;
                 }
 
                 let memory = match caller.get_export("memory") {
@@ -374,6 +579,11 @@ impl WasmVm {
                 if state.storage.len() >= MAX_STORAGE_ENTRIES && !state.storage.contains_key(&key) {
                     return -1; // Storage limit exceeded
                 }
+                let program = state.context.contract_address;
+                state.host.trace_access(StateAccess::StorageWrite {
+                    program,
+                    key: key.clone(),
+                });
                 state.storage.insert(key, value);
                 0
             },
@@ -398,14 +608,8 @@ impl WasmVm {
                 // Charge fuel after validation so negative values can't wrap.
                 let log_byte_cost = (data_len as u64).saturating_mul(8);
                 let fuel_cost = 375u64.saturating_add(log_byte_cost);
-                match caller.get_fuel() {
-                    Ok(fuel) if fuel >= fuel_cost => {
-                        if caller.set_fuel(fuel.saturating_sub(fuel_cost)).is_err() {
-                            return -1;
-                        }
-                    }
-                    Ok(_) => return -1,
-                    Err(_) => return -1,
+                if !charge(&mut caller, "emit_log", fuel_cost) {
+                    return -1;
                 }
 
                 let memory = match caller.get_export("memory") {
@@ -453,14 +657,8 @@ impl WasmVm {
 
                 // Charge fuel after validation so negative values can't wrap.
                 let fuel_cost = 100u64.saturating_add(len as u64);
-                match caller.get_fuel() {
-                    Ok(fuel) if fuel >= fuel_cost => {
-                        if caller.set_fuel(fuel.saturating_sub(fuel_cost)).is_err() {
-                            return -1;
-                        }
-                    }
-                    Ok(_) => return -1,
-                    Err(_) => return -1,
+                if !charge(&mut caller, "set_return", fuel_cost) {
+                    return -1;
                 }
 
                 let memory = match caller.get_export("memory") {
@@ -485,6 +683,236 @@ impl WasmVm {
             },
         )?;
 
+        // env.kzg_verify(input_ptr: i32, input_len: i32) -> i32
+        // Input: commitment (48) || z (32) || y (32) || proof (48).
+        // Returns 1 if the opening holds, 0 if it does not or is malformed,
+        // -1 on a bad call. Gas cost: KZG_VERIFY (two pairings), charged
+        // before the check so failed openings pay too.
+        linker.func_wrap(
+            "env",
+            "kzg_verify",
+            |mut caller: Caller<'_, StoreData>, input_ptr: i32, input_len: i32| -> i32 {
+                if input_ptr < 0 || input_len as usize != PACKED_OPENING_LEN {
+                    return -1;
+                }
+
+                let fuel_cost = gas_costs::KZG_VERIFY;
+                if !charge(&mut caller, "kzg_verify", fuel_cost) {
+                    return -1;
+                }
+
+                let verifier = match &caller.data().kzg {
+                    Some(verifier) => verifier.clone(),
+                    None => return -1,
+                };
+                let memory = match caller.get_export("memory") {
+                    Some(Extern::Memory(m)) => m,
+                    _ => return -1,
+                };
+
+                let data = memory.data(&caller);
+                let start = input_ptr as usize;
+                let end = match start.checked_add(PACKED_OPENING_LEN) {
+                    Some(e) if e <= data.len() => e,
+                    _ => return -1,
+                };
+                match verifier.verify_packed(&data[start..end]) {
+                    Ok(true) => 1,
+                    Ok(false) | Err(_) => 0,
+                }
+            },
+        )?;
+
+        // env.account_read(addr_ptr: i32, out_ptr: i32, out_cap: i32) -> i32
+        // Reads the data of the 20-byte address at addr_ptr, which must be in
+        // the transaction's read or write set. Returns its length (0 if
+        // absent), of which the first out_cap bytes are copied to out_ptr;
+        // -1 on a bad or disallowed call.
+        // Gas cost: 200 + 1 per byte returned.
+        linker.func_wrap(
+            "env",
+            "account_read",
+            |mut caller: Caller<'_, StoreData>, addr_ptr: i32, out_ptr: i32, out_cap: i32| -> i32 {
+                if addr_ptr < 0 || out_ptr < 0 || out_cap < 0 {
+                    return -1;
+                }
+
+                let memory = match caller.get_export("memory") {
+                    Some(Extern::Memory(m)) => m,
+                    _ => return -1,
+                };
+                let Some(account) = read_bytes(memory.data(&caller), addr_ptr, 20)
+                    .and_then(|bytes| Address::from_slice(bytes).ok())
+                else {
+                    return -1;
+                };
+
+                let Some(value) = with_host(&mut caller, |host| host.account_read(&account)) else {
+                    return -1;
+                };
+                let value = value.unwrap_or_default();
+                let copied = value.len().min(out_cap as usize);
+                let out = memory.data_mut(&mut caller);
+                let start = out_ptr as usize;
+                match start.checked_add(copied) {
+                    Some(end) if end <= out.len() => {
+                        out[start..end].copy_from_slice(&value[..copied])
+                    }
+                    _ => return -1,
+                }
+                value.len() as i32
+            },
+        )?;
+
+        // env.account_write(addr_ptr: i32, data_ptr: i32, data_len: i32) -> i32
+        // Replaces the data of the 20-byte address at addr_ptr, which must be
+        // in the transaction's write set. Returns 0, or -1 on a bad or
+        // disallowed call. Gas cost: 5000 + 1 per byte written.
+        linker.func_wrap(
+            "env",
+            "account_write",
+            |mut caller: Caller<'_, StoreData>,
+             addr_ptr: i32,
+             data_ptr: i32,
+             data_len: i32|
+             -> i32 {
+                if addr_ptr < 0 || data_ptr < 0 || data_len < 0 {
+                    return -1;
+                }
+                if data_len as usize > MAX_STORAGE_VAL_LEN {
+                    return -1;
+                }
+
+                let memory = match caller.get_export("memory") {
+                    Some(Extern::Memory(m)) => m,
+                    _ => return -1,
+                };
+                let data = memory.data(&caller);
+                let Some(account) = read_bytes(data, addr_ptr, 20)
+                    .and_then(|bytes| Address::from_slice(bytes).ok())
+                else {
+                    return -1;
+                };
+                let Some(value) = read_bytes(data, data_ptr, data_len as usize) else {
+                    return -1;
+                };
+                let value = value.to_vec();
+
+                match with_host(&mut caller, |host| host.account_write(&account, value)) {
+                    Some(()) => 0,
+                    None => -1,
+                }
+            },
+        )?;
+
+        // env.crypto_verify_sig(pk_ptr: i32, msg_ptr: i32, msg_len: i32,
+        //                       sig_ptr: i32) -> i32
+        // Checks the 64-byte ed25519 signature at sig_ptr by the 32-byte key
+        // at pk_ptr over the message. Returns 1 if it holds, 0 if not, -1 on
+        // a bad call. Gas cost: SIG_VERIFY + 1 per message byte, charged
+        // even if the signature is invalid.
+        linker.func_wrap(
+            "env",
+            "crypto_verify_sig",
+            |mut caller: Caller<'_, StoreData>,
+             pk_ptr: i32,
+             msg_ptr: i32,
+             msg_len: i32,
+             sig_ptr: i32|
+             -> i32 {
+                if pk_ptr < 0 || msg_ptr < 0 || msg_len < 0 || sig_ptr < 0 {
+                    return -1;
+                }
+                if msg_len as usize > MAX_SIG_MESSAGE_LEN {
+                    return -1;
+                }
+
+                let memory = match caller.get_export("memory") {
+                    Some(Extern::Memory(m)) => m,
+                    _ => return -1,
+                };
+                let data = memory.data(&caller);
+                let (Some(public_key), Some(message), Some(signature)) = (
+                    read_bytes(data, pk_ptr, 32),
+                    read_bytes(data, msg_ptr, msg_len as usize),
+                    read_bytes(data, sig_ptr, 64),
+                ) else {
+                    return -1;
+                };
+                let (public_key, message, signature) =
+                    (public_key.to_vec(), message.to_vec(), signature.to_vec());
+
+                match with_host(&mut caller, |host| {
+                    host.crypto_verify_sig(&public_key, &message, &signature)
+                }) {
+                    Some(valid) => valid as i32,
+                    None => -1,
+                }
+            },
+        )?;
+
+        // env.call_program(id_ptr: i32, input_ptr: i32, input_len: i32,
+        //                  gas: i64, out_ptr: i32, out_cap: i32) -> i32
+        // Calls the program at the 20-byte address at id_ptr, forwarding up
+        // to `gas`. Returns the length of its return data, of which the
+        // first out_cap bytes are copied to out_ptr; -2 if the callee failed
+        // (its changes are reverted); -1 on a bad call. Gas cost: CALL plus
+        // whatever the callee uses.
+        linker.func_wrap(
+            "env",
+            "call_program",
+            |mut caller: Caller<'_, StoreData>,
+             id_ptr: i32,
+             input_ptr: i32,
+             input_len: i32,
+             gas: i64,
+             out_ptr: i32,
+             out_cap: i32|
+             -> i32 {
+                if id_ptr < 0 || input_ptr < 0 || input_len < 0 || gas < 0 {
+                    return -1;
+                }
+                if out_ptr < 0 || out_cap < 0 || input_len as usize > MAX_CALL_INPUT_LEN {
+                    return -1;
+                }
+
+                let memory = match caller.get_export("memory") {
+                    Some(Extern::Memory(m)) => m,
+                    _ => return -1,
+                };
+                let data = memory.data(&caller);
+                let Some(program_id) =
+                    read_bytes(data, id_ptr, 20).and_then(|bytes| Address::from_slice(bytes).ok())
+                else {
+                    return -1;
+                };
+                let Some(input) = read_bytes(data, input_ptr, input_len as usize) else {
+                    return -1;
+                };
+                let input = input.to_vec();
+
+                let Some(outcome) = with_host(&mut caller, |host| {
+                    host.call_program(&program_id, &input, gas as u64)
+                }) else {
+                    return -1;
+                };
+                if !outcome.success {
+                    return -2;
+                }
+
+                let copied = outcome.return_data.len().min(out_cap as usize);
+                let out = memory.data_mut(&mut caller);
+                let start = out_ptr as usize;
+                match start.checked_add(copied) {
+                    Some(end) if end <= out.len() => {
+                        out[start..end].copy_from_slice(&outcome.return_data[..copied])
+                    }
+                    _ => return -1,
+                }
+                outcome.return_data.len() as i32
+            },
+        )?;
+
         // env.block_number() -> i64
         linker.func_wrap(
             "env",
@@ -516,6 +944,44 @@ impl WasmVm {
     }
 }
 
+/// Charge `cost` fuel for the host call `name`, recording the call when
+/// tracing. False if the fuel is not there.
+fn charge(caller: &mut Caller<'_, StoreData>, name: &'static str, cost: u64) -> bool {
+    let charged = match caller.get_fuel() {
+        Ok(fuel) if fuel >= cost => caller.set_fuel(fuel - cost).is_ok(),
+        _ => false,
+    };
+    if let Ok(mut state) = caller.data().host.lock() {
+        let error = (!charged).then(|| "out of gas".to_string());
+        state
+            .host
+            .trace_call(name, if charged { cost } else { 0 }, error);
+    }
+    charged
+}
+
+/// Run `call` on the transaction's host with the remaining fuel as its gas
+/// window, then burn what it used. The host records the call when tracing.
+/// None if the call failed.
+fn with_host<T>(
+    caller: &mut Caller<'_, StoreData>,
+    call: impl FnOnce(&mut HostFunctions) -> Result<T>,
+) -> Option<T> {
+    let fuel = caller.get_fuel().ok()?;
+    let (result, used) = {
+        let mut state = caller.data().host.lock().ok()?;
+        state.host.metered(fuel, call)
+    };
+    caller.set_fuel(fuel - used).ok()?;
+    result.ok()
+}
+
+/// `len` bytes of guest memory at `ptr`, if in bounds.
+fn read_bytes(data: &[u8], ptr: i32, len: usize) -> Option<&[u8]> {
+    let start = usize::try_from(ptr).ok()?;
+    data.get(start..start.checked_add(len)?)
+}
+
 /// Gas costs for different operations.
 pub mod gas_costs {
     pub const BASE: u64 = 100;
@@ -525,6 +991,15 @@ pub mod gas_costs {
     pub const LOG: u64 = 375;
     pub const SHA256: u64 = 60;
     pub const TRANSFER: u64 = 9000;
+    /// Cross-program call, before the callee's own gas.
+    pub const CALL: u64 = 700;
+    /// One ed25519 signature check.
+    pub const SIG_VERIFY: u64 = 3000;
+    /// One BLS12-381 pairing (Miller loop plus its share of the final
+    /// exponentiation), ~1ms of verifier time.
+    pub const PAIRING: u64 = 25_000;
+    /// KZG opening check: a two-pairing product.
+    pub const KZG_VERIFY: u64 = 2 * PAIRING;
 }
 
 #[cfg(test)]
@@ -600,6 +1075,43 @@ mod tests {
         assert!(result.gas_used > 0, "should consume some gas");
     }
 
+    #[test]
+    fn test_repeated_execution_reuses_compiled_module() {
+        let mut vm = WasmVm::new(1_000_000).unwrap();
+        let context = ExecutionContext {
+            contract_address: Address::from_slice(&[1u8; 20]).unwrap(),
+            caller: Address::from_slice(&[2u8; 20]).unwrap(),
+            value: 0,
+            gas_limit: 1_000_000,
+            block_number: 1,
+            timestamp: 1000,
+        };
+        let wasm = wat::parse_str(
+            r#"(module (func (export "execute") (param i32 i32) (result i32) i32.const 0))"#,
+        )
+        .unwrap();
+        let float = wat::parse_str(
+            r#"(module (func (export "execute") (param i32 i32) (result i32)
+                f32.const 1.0
+                drop
+                i32.const 0))"#,
+        )
+        .unwrap();
+
+        assert_eq!(vm.prewarm([&wasm[..], &float[..]]), 1);
+        let first = vm.execute(&wasm, &context, &[]).unwrap();
+        let second = vm.execute(&wasm, &context, &[]).unwrap();
+        assert_eq!(first.gas_used, second.gas_used);
+
+        let stats = vm.module_cache().stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(
+            stats.misses, 2,
+            "one compile plus the rejected float module"
+        );
+        assert!(vm.execute(&float, &context, &[]).is_err(), "never cached");
+    }
+
     #[test]
     fn test_execute_wasm_with_storage() {
         let mut vm = WasmVm::new(1_000_000).unwrap();
@@ -644,6 +1156,212 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_execute_with_traced_host() {
+        let mut vm = WasmVm::new(1_000_000).unwrap();
+        let context = status_context(1_000_000);
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "env" "storage_write" (func $sw (param i32 i32 i32 i32) (result i32)))
+                (import "env" "storage_read" (func $sr (param i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "key")
+                (data (i32.const 3) "value")
+                (func (export "execute") (param i32 i32) (result i32)
+                    (drop (call $sw (i32.const 0) (i32.const 3) (i32.const 3) (i32.const 5)))
+                    (drop (call $sr (i32.const 0) (i32.const 3) (i32.const 64)))
+                    i32.const 0
+                )
+            )
+            "#,
+        )
+        .unwrap();
+
+        let mut host = HostFunctions::new_for_test(context.gas_limit).with_tracing();
+        let result = vm
+            .execute_with_host(&wasm, &context, b"", &mut host)
+            .unwrap();
+        assert!(result.success);
+        let trace = result.trace.expect("traced host");
+        let calls: Vec<(&str, u64)> = trace
+            .host_calls
+            .iter()
+            .map(|call| (call.name, call.gas))
+            .collect();
+        assert_eq!(calls, vec![("storage_write", 5100), ("storage_read", 200)]);
+        assert_eq!(
+            trace.state_accesses,
+            vec![
+                StateAccess::StorageWrite {
+                    program: context.contract_address,
+                    key: b"key".to_vec()
+                },
+                StateAccess::StorageRead {
+                    program: context.contract_address,
+                    key: b"key".to_vec()
+                },
+            ]
+        );
+        assert_eq!(trace.gas_used, result.gas_used);
+        assert_eq!(trace.sections.len(), 1);
+        assert!(trace.sections[0].success);
+
+        // Without tracing there is nothing to return.
+        assert!(vm.execute(&wasm, &context, b"").unwrap().trace.is_none());
+    }
+
+    #[test]
+    fn test_call_program_from_wasm() {
+        let callee = Address::from([0xca; 20]);
+        let (payer, payee) = (Address::from([1; 20]), Address::from([2; 20]));
+        let wasm = wat::parse_str(format!(
+            r#"
+            (module
+                (import "env" "call_program"
+                    (func $call (param i32 i32 i32 i64 i32 i32) (result i32)))
+                (import "env" "set_return" (func $ret (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 64) "{}")
+                (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                    (local $n i32)
+                    (local.set $n (call $call (i32.const 64) (local.get $ptr) (local.get $len)
+                        (i64.const 100000) (i32.const 128) (i32.const 16)))
+                    (if (i32.lt_s (local.get $n) (i32.const 0))
+                        (then (return (i32.sub (i32.const 0) (local.get $n)))))
+                    (drop (call $ret (i32.const 128) (local.get $n)))
+                    i32.const 0
+                )
+            )
+            "#,
+            "\\ca".repeat(20)
+        ))
+        .unwrap();
+
+        let mut vm = WasmVm::new(1_000_000).unwrap();
+        let context = status_context(1_000_000);
+        let mut host = HostFunctions::new_for_test(context.gas_limit);
+        host.balances.insert(payer, 100);
+        host.register_program(
+            callee,
+            Arc::new(move |host: &mut HostFunctions, input: &[u8]| {
+                host.transfer(&payer, &payee, 10)?;
+                if input == b"fail" {
+                    bail!("abort");
+                }
+                Ok(input.iter().rev().copied().collect())
+            }),
+        );
+
+        let result = vm
+            .execute_with_host(&wasm, &context, b"abc", &mut host)
+            .unwrap();
+        assert!(result.success, "{:?}", result.status);
+        assert_eq!(result.return_data, b"cba");
+        assert!(result.gas_used > gas_costs::CALL + gas_costs::TRANSFER);
+        assert_eq!(host.balances[&payer], 90);
+        assert_eq!(host.balances[&payee], 10);
+
+        // A failing callee is reported as -2 and its transfer undone.
+        let result = vm
+            .execute_with_host(&wasm, &context, b"fail", &mut host)
+            .unwrap();
+        assert_eq!(result.status, ExecutionStatus::Reverted(2));
+        assert_eq!(host.balances[&payer], 90);
+        assert_eq!(host.balances[&payee], 10);
+
+        // Without the program registered the call itself is rejected.
+        let result = vm.execute(&wasm, &context, b"abc").unwrap();
+        assert_eq!(result.status, ExecutionStatus::Reverted(1));
+    }
+
+    #[test]
+    fn test_account_host_functions() {
+        let account = Address::from([7; 20]);
+        let wasm = wat::parse_str(format!(
+            r#"
+            (module
+                (import "env" "account_write" (func $aw (param i32 i32 i32) (result i32)))
+                (import "env" "account_read" (func $ar (param i32 i32 i32) (result i32)))
+                (import "env" "set_return" (func $ret (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 64) "{}")
+                (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                    (if (i32.lt_s (call $aw (i32.const 64) (local.get $ptr) (local.get $len))
+                            (i32.const 0))
+                        (then (return (i32.const 1))))
+                    (drop (call $ret (i32.const 128)
+                        (call $ar (i32.const 64) (i32.const 128) (i32.const 64))))
+                    i32.const 0
+                )
+            )
+            "#,
+            "\\07".repeat(20)
+        ))
+        .unwrap();
+        let mut vm = WasmVm::new(1_000_000).unwrap();
+        let context = status_context(1_000_000);
+
+        // The plain entry point declares no accounts, so the write is refused.
+        let result = vm.execute(&wasm, &context, b"data").unwrap();
+        assert_eq!(result.status, ExecutionStatus::Reverted(1));
+
+        let capabilities =
+            host_functions::CapabilityTable::new(Default::default(), [account].into());
+        let mut host =
+            HostFunctions::new_for_test(context.gas_limit).with_capabilities(capabilities);
+        let result = vm
+            .execute_with_host(&wasm, &context, b"data", &mut host)
+            .unwrap();
+        assert!(result.success, "{:?}", result.status);
+        assert_eq!(result.return_data, b"data");
+        assert_eq!(host.accounts[&account], b"data");
+        assert!(result.gas_used > gas_costs::STORAGE_WRITE + gas_costs::STORAGE_READ);
+    }
+
+    #[test]
+    fn test_crypto_verify_sig_host_function() {
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "env" "crypto_verify_sig"
+                    (func $verify (param i32 i32 i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                ;; input: public key (32) || signature (64) || message
+                (func (export "execute") (param $ptr i32) (param $len i32) (result i32)
+                    (i32.sub (i32.const 1)
+                        (call $verify (i32.const 0) (i32.const 96)
+                            (i32.sub (local.get $len) (i32.const 96)) (i32.const 32)))
+                )
+            )
+            "#,
+        )
+        .unwrap();
+        let keypair = aether_crypto_primitives::Keypair::generate();
+        let message = b"hello aether";
+        let signed = |message: &[u8]| {
+            let mut input = keypair.public_key().to_vec();
+            input.extend(keypair.sign(message));
+            input.extend_from_slice(message);
+            input
+        };
+        let mut vm = WasmVm::new(1_000_000).unwrap();
+        let context = status_context(1_000_000);
+
+        let result = vm.execute(&wasm, &context, &signed(message)).unwrap();
+        assert!(result.success, "{:?}", result.status);
+        assert!(result.gas_used >= gas_costs::SIG_VERIFY + message.len() as u64);
+
+        let mut forged = signed(message);
+        *forged.last_mut().unwrap() ^= 1;
+        let result = vm.execute(&wasm, &context, &forged).unwrap();
+        assert_eq!(result.status, ExecutionStatus::Reverted(1));
+
+        // Too short for a key and signature: a bad call.
+        let result = vm.execute(&wasm, &context, &[0; 8]).unwrap();
+        assert_eq!(result.status, ExecutionStatus::Reverted(2));
+    }
+
     #[test]
     fn test_execute_wasm_with_logging() {
         let mut vm = WasmVm::new(1_000_000).unwrap();
@@ -1029,6 +1747,200 @@ mod tests {
             Ok(r) => assert!(!r.success, "large table allocation must not succeed"),
         }
     }
+
+    #[test]
+    fn test_kzg_verify_host_function() {
+        let kzg = Arc::new(KzgVerifier::new_insecure_test(4));
+        let context = ExecutionContext {
+            contract_address: Address::from_slice(&[1u8; 20]).unwrap(),
+            caller: Address::from_slice(&[2u8; 20]).unwrap(),
+            value: 0,
+            gas_limit: 1_000_000,
+            block_number: 1,
+            timestamp: 1000,
+        };
+
+        // Succeeds iff kzg_verify(input) returns 1.
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (import "env" "kzg_verify" (func $kzg (param i32 i32) (result i32)))
+                (memory (export "memory") 1)
+                (func (export "execute") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    call $kzg
+                    i32.const 1
+                    i32.ne
+                )
+            )
+            "#,
+        )
+        .unwrap();
+
+        let coeffs: Vec<[u8; 32]> = (1..=3).map(aether_crypto_kzg::scalar_from_i64).collect();
+        let z = aether_crypto_kzg::scalar_from_i64(5);
+        let proof = kzg.create_proof(&coeffs, &z).unwrap();
+        let mut input = kzg.commit(&coeffs).unwrap().commitment;
+        input.extend_from_slice(&z);
+        input.extend_from_slice(&proof.evaluation);
+        input.extend_from_slice(&proof.proof);
+
+        let mut vm = WasmVm::new(1_000_000)
+            .unwrap()
+            .with_kzg_verifier(kzg.clone());
+        let result = vm.execute(&wasm, &context, &input).unwrap();
+        assert!(result.success, "valid opening should verify");
+        assert!(result.gas_used >= gas_costs::KZG_VERIFY);
+
+        let mut wrong = input.clone();
+        wrong[48 + 32] ^= 1;
+        assert!(!vm.execute(&wasm, &context, &wrong).unwrap().success);
+        assert!(!vm.execute(&wasm, &context, &input[..100]).unwrap().success);
+
+        // Not enough gas for the pairings.
+        let poor = ExecutionContext {
+            gas_limit: gas_costs::KZG_VERIFY - 1,
+            ..context.clone()
+        };
+        assert!(!vm.execute(&wasm, &poor, &input).unwrap().success);
+
+        // No trusted setup configured.
+        let mut bare = WasmVm::new(1_000_000).unwrap();
+        assert!(!bare.execute(&wasm, &context, &input).unwrap().success);
+    }
+
+    fn status_context(gas_limit: u64) -> ExecutionContext {
+        ExecutionContext {
+            contract_address: Address::from_slice(&[1u8; 20]).unwrap(),
+            caller: Address::from_slice(&[2u8; 20]).unwrap(),
+            value: 0,
+            gas_limit,
+            block_number: 1,
+            timestamp: 1000,
+        }
+    }
+
+    fn run(wat_src: &str, gas_limit: u64) -> Result<ExecutionResult> {
+        let wasm = wat::parse_str(wat_src).unwrap();
+        WasmVm::new(gas_limit)
+            .unwrap()
+            .execute(&wasm, &status_context(gas_limit), b"")
+    }
+
+    #[test]
+    fn test_float_opcodes_rejected() {
+        let err = run(
+            r#"(module
+                (func (export "execute") (param i32 i32) (result i32)
+                    f32.const 1.5
+                    i32.trunc_f32_s
+                )
+            )"#,
+            1_000_000,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("floating-point"), "got: {err}");
+
+        // Float types in signatures are rejected even without float opcodes.
+        assert!(run(
+            r#"(module (func (export "execute") (param f64 i32) (result i32) i32.const 0))"#,
+            1_000_000,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_execution_status_variants() {
+        let ok = run(
+            r#"(module (func (export "execute") (param i32 i32) (result i32) i32.const 0))"#,
+            1_000_000,
+        )
+        .unwrap();
+        assert_eq!(ok.status, ExecutionStatus::Success);
+        assert!(ok.success);
+
+        let reverted = run(
+            r#"(module (func (export "execute") (param i32 i32) (result i32) i32.const 7))"#,
+            1_000_000,
+        )
+        .unwrap();
+        assert_eq!(reverted.status, ExecutionStatus::Reverted(7));
+        assert!(!reverted.success);
+
+        let oog = run(
+            r#"(module (func (export "execute") (param i32 i32) (result i32)
+                (loop $l (br $l))
+                i32.const 0))"#,
+            1_000,
+        )
+        .unwrap();
+        assert_eq!(oog.status, ExecutionStatus::OutOfGas);
+        assert_eq!(oog.gas_used, 1_000, "out of gas charges the whole limit");
+
+        let trapped = run(
+            r#"(module (func (export "execute") (param i32 i32) (result i32) unreachable))"#,
+            1_000_000,
+        )
+        .unwrap();
+        assert!(matches!(trapped.status, ExecutionStatus::Trapped(_)));
+
+        let start_trap = run(r#"(module (func $s unreachable) (start $s))"#, 1_000_000).unwrap();
+        assert!(matches!(start_trap.status, ExecutionStatus::Trapped(_)));
+
+        let none = run("(module)", 1_000_000).unwrap();
+        assert_eq!(none.status, ExecutionStatus::NoEntryPoint);
+    }
+
+    #[test]
+    fn test_peak_memory_reported() {
+        let result = run(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "execute") (param i32 i32) (result i32)
+                    (drop (memory.grow (i32.const 2)))
+                    ;; Beyond the 16 MB cap: refused, so not counted.
+                    (drop (memory.grow (i32.const 1024)))
+                    i32.const 0
+                )
+            )"#,
+            1_000_000,
+        )
+        .unwrap();
+        assert!(result.success);
+        assert_eq!(result.memory_bytes, 3 * 64 * 1024);
+
+        let no_memory = run(
+            r#"(module (func (export "execute") (param i32 i32) (result i32) i32.const 0))"#,
+            1_000_000,
+        )
+        .unwrap();
+        assert_eq!(no_memory.memory_bytes, 0);
+    }
+
+    #[test]
+    fn test_fee_follows_formula() {
+        let result = run(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "execute") (param i32 i32) (result i32) i32.const 0)
+            )"#,
+            1_000_000,
+        )
+        .unwrap();
+        let mut params = aether_types::ChainConfig::devnet().fees;
+        params.a = 10_000;
+        params.b = 5;
+        params.c = 2;
+        params.d = 1;
+        let expected = 10_000 + 5 * 200 + 2 * result.gas_used as u128 + 64 * 1024;
+        assert_eq!(result.fee(&params, 200), Some(expected));
+
+        let mut huge = params.clone();
+        huge.a = u128::MAX;
+        huge.c = 1;
+        assert_eq!(result.fee(&huge, 0), None);
+    }
 }
 
 #[cfg(test)]
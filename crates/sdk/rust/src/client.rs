@@ -456,7 +456,7 @@ fn parse_http_response(response: &str) -> Result<(&str, &str), AetherSdkError> {
     Ok((status_line, body))
 }
 
-fn parse_h256_hex(value: &str) -> Result<aether_types::H256, AetherSdkError> {
+pub(crate) fn parse_h256_hex(value: &str) -> Result<aether_types::H256, AetherSdkError> {
     let bytes = hex::decode(value.trim_start_matches("0x"))
         .map_err(|_| AetherSdkError::invalid_response(format!("invalid tx hash hex: {value}")))?;
     aether_types::H256::from_slice(&bytes)
@@ -466,6 +466,7 @@ fn parse_h256_hex(value: &str) -> Result<aether_types::H256, AetherSdkError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Signer;
     use aether_crypto_primitives::Keypair;
     use aether_types::{Address, PublicKey, Signature, H256};
     use serde_json::json;
@@ -493,6 +494,57 @@ mod tests {
         assert_eq!(decoded_tx.hash(), tx.hash());
     }
 
+    #[test]
+    fn builds_with_external_signers() {
+        use aether_crypto_primitives::remote_signer::serve_connection;
+        use aether_crypto_primitives::{RemoteSigner, SignerError};
+
+        let client = AetherClient::new("http://localhost:8545");
+        let recipient = Address::from_slice(&[2u8; 20]).unwrap();
+
+        // The key lives in a signer on the other end of a socket.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        std::thread::spawn(move || {
+            let keypair = Keypair::from_bytes(&[4u8; 32]).unwrap();
+            let (stream, _) = listener.accept().unwrap();
+            let _ = serve_connection(stream, &keypair);
+        });
+        let remote = RemoteSigner::connect(addr).unwrap();
+        let tx = client
+            .transfer()
+            .to(recipient)
+            .amount(1_000)
+            .build(&remote, 1)
+            .unwrap();
+        assert!(tx.verify_signature().is_ok());
+        let boxed: Box<dyn Signer> = Box::new(Keypair::from_bytes(&[4u8; 32]).unwrap());
+        let local = client
+            .transfer()
+            .to(recipient)
+            .amount(1_000)
+            .build(&boxed, 1)
+            .unwrap();
+        assert_eq!(local.hash(), tx.hash());
+
+        struct Declining;
+        impl Signer for Declining {
+            fn public_key(&self) -> Vec<u8> {
+                vec![1; 32]
+            }
+            fn sign(&self, _message: &[u8]) -> Result<Vec<u8>, SignerError> {
+                Err(SignerError::Rejected)
+            }
+        }
+        let err = client
+            .transfer()
+            .to(recipient)
+            .amount(1_000)
+            .build(&Declining, 1)
+            .unwrap_err();
+        assert!(matches!(err, AetherSdkError::Signer(_)), "{err}");
+    }
+
     #[tokio::test]
     async fn submit_returns_error_for_unreachable_endpoint() {
         let client = AetherClient::new("http://127.0.0.1:1");
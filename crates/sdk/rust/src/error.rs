@@ -12,6 +12,11 @@ pub enum AetherSdkError {
     #[error("invalid signature: {0}")]
     InvalidSignature(String),
 
+    /// The signer failed or refused to sign (device rejection, unreachable
+    /// remote signer).
+    #[error("signer error: {0}")]
+    Signer(String),
+
     /// The transaction fee is too low or the fee calculation overflowed.
     #[error("invalid fee: {0}")]
     InvalidFee(String),
@@ -9,6 +9,7 @@
 //   - RPC client
 //   - Contract calls
 //   - AI job submission
+//   - Transaction status streaming
 //
 // EXAMPLE:
 // ```
@@ -30,6 +31,7 @@ pub mod client;
 pub mod error;
 pub mod job_builder;
 pub mod transaction_builder;
+pub mod tx_status;
 pub mod types;
 
 pub use client::AetherClient;
@@ -37,5 +39,9 @@ pub use error::AetherSdkError;
 pub use job_builder::JobBuilder;
 pub use types::{NodeHealth, RpcAccount, RpcBlock, RpcReceipt};
 
+pub use aether_types::TxLifecycle;
+
+pub use aether_crypto_primitives::{DerivationPath, Mnemonic, Signer, SignerError};
+
 #[cfg(test)]
 mod proptest_tests;
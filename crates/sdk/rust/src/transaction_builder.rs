@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use aether_crypto_primitives::Keypair;
+use aether_crypto_primitives::Signer;
 use aether_types::{Address, PublicKey, Signature, Transaction};
 
 use crate::error::AetherSdkError;
@@ -64,8 +64,15 @@ impl TransferBuilder {
         self
     }
 
-    /// Build and sign the transfer transaction.
-    pub fn build(self, keypair: &Keypair, nonce: u64) -> Result<Transaction, AetherSdkError> {
+    /// Build the transfer transaction and have `signer` sign it.
+    ///
+    /// Any [`Signer`] works: an in-process `Keypair`, a Ledger device or a
+    /// remote signer, so the private key need not be in this process.
+    pub fn build<S: Signer + ?Sized>(
+        self,
+        signer: &S,
+        nonce: u64,
+    ) -> Result<Transaction, AetherSdkError> {
         let recipient = self
             .recipient
             .ok_or_else(|| AetherSdkError::build("missing recipient"))?;
@@ -80,7 +87,7 @@ impl TransferBuilder {
         };
 
         let payload_bytes = bincode::serialize(&payload).map_err(AetherSdkError::serialization)?;
-        let sender_pubkey = PublicKey::from_bytes(keypair.public_key());
+        let sender_pubkey = PublicKey::from_bytes(signer.public_key());
         let sender_address = sender_pubkey.to_address();
 
         let mut writes = HashSet::new();
@@ -103,7 +110,9 @@ impl TransferBuilder {
         };
 
         let message = tx.hash();
-        let signature = keypair.sign(message.as_bytes());
+        let signature = signer
+            .sign(message.as_bytes())
+            .map_err(|e| AetherSdkError::Signer(e.to_string()))?;
         if signature.len() != 64 {
             return Err(AetherSdkError::InvalidSignature(format!(
                 "invalid signature length: {}",
@@ -0,0 +1,128 @@
+//! Transaction status streaming over the node's WebSocket endpoint.
+//!
+//! Instead of polling `aeth_getTransactionReceipt`, send
+//! [`subscribe_message`] for a transaction hash on the `/ws` connection and
+//! feed every text frame received to [`parse_event`]. The node pushes each
+//! status change until a terminal one (see [`TxLifecycle::is_terminal`]),
+//! after which it stops watching the hash.
+
+use aether_types::{TxLifecycle, H256};
+use serde_json::{json, Value};
+
+use crate::client::parse_h256_hex;
+use crate::error::AetherSdkError;
+
+/// Request watching `tx_hash` on a WebSocket connection.
+pub fn subscribe_message(tx_hash: H256) -> String {
+    status_request("aeth_subscribeTxStatus", tx_hash)
+}
+
+/// Stop watching `tx_hash` before it reaches a terminal status.
+pub fn unsubscribe_message(tx_hash: H256) -> String {
+    status_request("aeth_unsubscribeTxStatus", tx_hash)
+}
+
+fn status_request(method: &str, tx_hash: H256) -> String {
+    json!({
+        "jsonrpc": "2.0",
+        "method": method,
+        "params": [format!("0x{}", hex::encode(tx_hash.as_bytes()))],
+        "id": 1,
+    })
+    .to_string()
+}
+
+/// Decode a WebSocket frame into a status update. Returns `None` for
+/// frames that are not `txStatus` events, such as subscription replies or
+/// block notifications.
+pub fn parse_event(text: &str) -> Result<Option<(H256, TxLifecycle)>, AetherSdkError> {
+    let frame: Value = serde_json::from_str(text).map_err(|e| {
+        AetherSdkError::invalid_response(format!("failed to decode websocket frame: {e}"))
+    })?;
+    if frame["topic"] != "txStatus" {
+        return Ok(None);
+    }
+
+    let data = &frame["data"];
+    let field = |name: &str| {
+        data[name].as_str().ok_or_else(|| {
+            AetherSdkError::invalid_response(format!("txStatus event missing {name}"))
+        })
+    };
+    let slot = || {
+        data["slot"]
+            .as_u64()
+            .ok_or_else(|| AetherSdkError::invalid_response("txStatus event missing slot"))
+    };
+
+    let tx_hash = parse_h256_hex(field("txHash")?)?;
+    let status = match field("status")? {
+        "pending" => TxLifecycle::Pending,
+        "included" => TxLifecycle::Included {
+            slot: slot()?,
+            block_hash: parse_h256_hex(field("blockHash")?)?,
+        },
+        "finalized" => TxLifecycle::Finalized { slot: slot()? },
+        "dropped" => TxLifecycle::Dropped {
+            reason: field("reason")?.to_string(),
+        },
+        "replaced" => TxLifecycle::Replaced {
+            by: parse_h256_hex(field("by")?)?,
+        },
+        other => {
+            return Err(AetherSdkError::invalid_response(format!(
+                "unknown tx status: {other}"
+            )))
+        }
+    };
+    Ok(Some((tx_hash, status)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(hash: H256) -> String {
+        format!("0x{}", hex::encode(hash.as_bytes()))
+    }
+
+    #[test]
+    fn test_subscribe_message_names_hash() {
+        let hash = H256::from_slice(&[0x11; 32]).unwrap();
+        let msg: Value = serde_json::from_str(&subscribe_message(hash)).unwrap();
+        assert_eq!(msg["method"], "aeth_subscribeTxStatus");
+        assert_eq!(msg["params"][0], hex(hash));
+    }
+
+    #[test]
+    fn test_parse_event() {
+        let hash = H256::from_slice(&[0x11; 32]).unwrap();
+        let block = H256::from_slice(&[0x22; 32]).unwrap();
+        let frame = json!({
+            "topic": "txStatus",
+            "data": {
+                "txHash": hex(hash),
+                "status": "included",
+                "slot": 9,
+                "blockHash": hex(block),
+            },
+        });
+        assert_eq!(
+            parse_event(&frame.to_string()).unwrap(),
+            Some((
+                hash,
+                TxLifecycle::Included {
+                    slot: 9,
+                    block_hash: block,
+                }
+            ))
+        );
+
+        let reply = json!({"jsonrpc": "2.0", "result": true, "id": 1});
+        assert_eq!(parse_event(&reply.to_string()).unwrap(), None);
+
+        let truncated =
+            json!({"topic": "txStatus", "data": {"txHash": hex(hash), "status": "finalized"}});
+        assert!(parse_event(&truncated.to_string()).is_err());
+    }
+}
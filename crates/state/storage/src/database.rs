@@ -21,6 +21,8 @@ pub const CF_STAKING: &str = "staking";
 
 type DbIterator<'a> = Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
 
+/// Handle to the node database. Clones share the same database.
+#[derive(Clone)]
 pub struct Storage {
     db: Arc<DB>,
     #[allow(dead_code)]
@@ -349,8 +351,9 @@ pub mod pruning {
                 }
             }
 
-            // Delete the slot index entry itself.
+            // Delete the slot index entry itself, and the block's finality path.
             batch.delete(CF_METADATA, key_bytes.to_vec());
+            batch.delete(CF_METADATA, format!("finality:{slot}").into_bytes());
             pruned += 1;
         }
 
@@ -484,7 +487,7 @@ mod tests {
 
     #[test]
     fn test_pruning_blocks_and_receipts() {
-        use aether_types::{Address, Block, BlockHeader, VrfProof, H256};
+        use aether_types::{Address, Block, BlockHeader, ErasureParams, VrfProof, H256};
 
         let temp_dir = TempDir::new().unwrap();
         let storage = Storage::open(temp_dir.path()).unwrap();
@@ -508,6 +511,7 @@ mod tests {
                         proof: vec![],
                     },
                     timestamp: 0,
+                    erasure: ErasureParams::default(),
                 },
                 transactions: vec![],
                 aggregated_vote: None,
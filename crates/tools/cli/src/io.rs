@@ -37,7 +37,7 @@ pub fn write_key_file(path: &Path, keypair: &Keypair) -> Result<()> {
     let address = Address::from_slice(&address_bytes)
         .map_err(|e| anyhow::anyhow!("failed to derive address from keypair: {e}"))?;
     let payload = KeyFile {
-        secret_key: format!("0x{}", hex::encode(secret_key)),
+        secret_key: format!("0x{}", hex::encode(secret_key.expose_secret())),
         public_key: format!("0x{}", hex::encode(public_key)),
         address: format!("0x{}", hex::encode(address.as_bytes())),
     };
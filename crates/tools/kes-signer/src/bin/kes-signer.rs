@@ -0,0 +1,46 @@
+use std::env;
+use std::net::SocketAddr;
+
+use aether_crypto_kes::{KesKeyStore, PassphraseSealer};
+use aether_kes_signer::{KesSigner, RemoteSignerServer};
+use anyhow::Context;
+use tonic::transport::Server;
+use tracing::info;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt().with_target(false).init();
+
+    let key_path = env::var("AETHER_KES_KEY").context("AETHER_KES_KEY must name the sealed key")?;
+    let passphrase =
+        env::var("AETHER_KES_PASSPHRASE").context("AETHER_KES_PASSPHRASE must be set")?;
+    let watermark_path =
+        env::var("AETHER_KES_WATERMARK").unwrap_or_else(|_| format!("{key_path}.watermark"));
+    let slots_per_period: u64 = env::var("AETHER_KES_SLOTS_PER_PERIOD")
+        .unwrap_or_else(|_| "172800".to_string())
+        .parse()
+        .context("invalid AETHER_KES_SLOTS_PER_PERIOD")?;
+
+    let store = KesKeyStore::new(
+        &key_path,
+        Box::new(PassphraseSealer::new(passphrase.as_bytes())),
+    );
+    let signer = KesSigner::open(store, watermark_path, slots_per_period)?;
+    info!(
+        period = signer.current_period(),
+        last_slot = ?signer.last_signed_slot(),
+        "loaded KES key"
+    );
+
+    let addr: SocketAddr = env::var("AETHER_KES_SIGNER_ADDR")
+        .unwrap_or_else(|_| "127.0.0.1:7400".to_string())
+        .parse()?;
+    info!(%addr, "starting remote signer");
+
+    Server::builder()
+        .add_service(RemoteSignerServer::new(signer))
+        .serve(addr)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,33 @@
+// ============================================================================
+// AETHER KES REMOTE SIGNER - Block signing off the validator host
+// ============================================================================
+// PURPOSE: Keep a validator's evolving KES key on a separate hardened host
+//
+// The validator node never holds the KES key. When it leads a slot it asks
+// the signer over gRPC (aether.signer.v1.RemoteSigner/SignBlock) for a
+// signature over (slot, block hash). The signer decides on its own state,
+// not on the caller's word:
+//
+// - The claimed period must be the KES period of the slot.
+// - Slots only move forward: a slot below the last signed one is refused,
+//   and the last slot is only ever signed for the same block hash again.
+// - The slot watermark and the evolved key are both persisted before the
+//   signature leaves the host, so a crash or restart cannot sign a second
+//   block for a slot or reuse an erased period.
+//
+// A compromised validator host can therefore at worst get one signature per
+// future slot, never an equivocation or a signature for a past period.
+//
+// MODULES:
+//   proto   - protobuf messages and the RemoteSigner service definition
+//   signer  - KesSigner, the period and double-sign policy
+//   service - tonic server and client
+// ============================================================================
+
+pub mod proto;
+pub mod service;
+pub mod signer;
+
+pub use aether_crypto_kes::block_signing_message;
+pub use service::{RemoteSignerClient, RemoteSignerServer};
+pub use signer::{KesSigner, SignerError};
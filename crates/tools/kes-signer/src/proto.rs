@@ -0,0 +1,119 @@
+//! Wire messages of the remote signer, hand-written prost types for:
+//!
+//! ```proto
+//! syntax = "proto3";
+//! package aether.signer.v1;
+//!
+//! service RemoteSigner {
+//!   rpc GetVerificationKey(GetVerificationKeyRequest) returns (VerificationKeyResponse);
+//!   rpc SignBlock(SignBlockRequest) returns (SignBlockResponse);
+//! }
+//!
+//! message GetVerificationKeyRequest {}
+//! message VerificationKeyResponse {
+//!   bytes root = 1;
+//!   uint32 max_periods = 2;
+//!   uint32 current_period = 3;
+//! }
+//! message SignBlockRequest {
+//!   uint64 slot = 1;
+//!   uint32 period = 2;
+//!   bytes block_hash = 3;
+//! }
+//! message KesSignature {
+//!   uint32 period = 1;
+//!   bytes signature = 2;
+//!   bytes leaf_pubkey = 3;
+//!   repeated bytes auth_path = 4;
+//! }
+//! message SignBlockResponse { KesSignature signature = 1; }
+//! ```
+
+use aether_crypto_kes::{KesSignature as Signature, KesVerificationKey};
+use anyhow::{anyhow, Result};
+
+pub const SERVICE_NAME: &str = "aether.signer.v1.RemoteSigner";
+pub const GET_VERIFICATION_KEY_PATH: &str = "/aether.signer.v1.RemoteSigner/GetVerificationKey";
+pub const SIGN_BLOCK_PATH: &str = "/aether.signer.v1.RemoteSigner/SignBlock";
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetVerificationKeyRequest {}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VerificationKeyResponse {
+    #[prost(bytes = "vec", tag = "1")]
+    pub root: Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    pub max_periods: u32,
+    #[prost(uint32, tag = "3")]
+    pub current_period: u32,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignBlockRequest {
+    #[prost(uint64, tag = "1")]
+    pub slot: u64,
+    #[prost(uint32, tag = "2")]
+    pub period: u32,
+    #[prost(bytes = "vec", tag = "3")]
+    pub block_hash: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct KesSignature {
+    #[prost(uint32, tag = "1")]
+    pub period: u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub signature: Vec<u8>,
+    #[prost(bytes = "vec", tag = "3")]
+    pub leaf_pubkey: Vec<u8>,
+    #[prost(bytes = "vec", repeated, tag = "4")]
+    pub auth_path: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignBlockResponse {
+    #[prost(message, optional, tag = "1")]
+    pub signature: Option<KesSignature>,
+}
+
+impl VerificationKeyResponse {
+    pub fn verification_key(&self) -> Result<KesVerificationKey> {
+        let root = fixed32(&self.root, "verification key root")?;
+        Ok(KesVerificationKey::new(root, self.max_periods))
+    }
+}
+
+impl From<&Signature> for KesSignature {
+    fn from(sig: &Signature) -> Self {
+        KesSignature {
+            period: sig.period,
+            signature: sig.signature.clone(),
+            leaf_pubkey: sig.leaf_pubkey.to_vec(),
+            auth_path: sig.auth_path.iter().map(|node| node.to_vec()).collect(),
+        }
+    }
+}
+
+impl TryFrom<KesSignature> for Signature {
+    type Error = anyhow::Error;
+
+    fn try_from(sig: KesSignature) -> Result<Self> {
+        Ok(Signature {
+            period: sig.period,
+            signature: sig.signature,
+            leaf_pubkey: fixed32(&sig.leaf_pubkey, "leaf public key")?,
+            auth_path: sig
+                .auth_path
+                .iter()
+                .map(|node| fixed32(node, "auth path node"))
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+fn fixed32(bytes: &[u8], what: &str) -> Result<[u8; 32]> {
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("{what} must be 32 bytes, got {}", bytes.len()))
+}
@@ -0,0 +1,284 @@
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use aether_crypto_kes::{KesError, KesSignature, KesVerificationKey};
+use parking_lot::Mutex;
+use tonic::body::BoxBody;
+use tonic::codec::ProstCodec;
+use tonic::codegen::{empty_body, http, Body, BoxFuture, Service, StdError};
+use tonic::server::{Grpc, NamedService, UnaryService};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Request, Response, Status};
+
+use crate::proto::{
+    self, GetVerificationKeyRequest, SignBlockRequest, SignBlockResponse, VerificationKeyResponse,
+    GET_VERIFICATION_KEY_PATH, SERVICE_NAME, SIGN_BLOCK_PATH,
+};
+use crate::signer::{KesSigner, SignerError};
+
+// ============================================================================
+// SERVER
+// ============================================================================
+
+/// The `RemoteSigner` gRPC service around a `KesSigner`. Requests are
+/// signed one at a time; each one persists state before it answers.
+#[derive(Clone)]
+pub struct RemoteSignerServer {
+    signer: Arc<Mutex<KesSigner>>,
+}
+
+impl RemoteSignerServer {
+    pub fn new(signer: KesSigner) -> Self {
+        RemoteSignerServer {
+            signer: Arc::new(Mutex::new(signer)),
+        }
+    }
+
+    async fn get_verification_key(
+        &self,
+        _request: Request<GetVerificationKeyRequest>,
+    ) -> Result<Response<VerificationKeyResponse>, Status> {
+        let signer = self.signer.lock();
+        let vk = signer.verification_key();
+        Ok(Response::new(VerificationKeyResponse {
+            root: vk.root().to_vec(),
+            max_periods: vk.max_periods(),
+            current_period: signer.current_period(),
+        }))
+    }
+
+    async fn sign_block(
+        &self,
+        request: Request<SignBlockRequest>,
+    ) -> Result<Response<SignBlockResponse>, Status> {
+        let request = request.into_inner();
+        let block_hash: [u8; 32] = request
+            .block_hash
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("block hash must be 32 bytes"))?;
+
+        // Signing syncs files to disk; keep it off the async workers.
+        let signer = Arc::clone(&self.signer);
+        let signature = tokio::task::spawn_blocking(move || {
+            signer
+                .lock()
+                .sign_block(request.slot, request.period, &block_hash)
+        })
+        .await
+        .map_err(|e| Status::internal(format!("signing task failed: {e}")))?
+        .map_err(|e| {
+            tracing::warn!(
+                slot = request.slot,
+                period = request.period,
+                "refused to sign: {e}"
+            );
+            to_status(e)
+        })?;
+
+        tracing::info!(slot = request.slot, period = request.period, "signed block");
+        Ok(Response::new(SignBlockResponse {
+            signature: Some(proto::KesSignature::from(&signature)),
+        }))
+    }
+}
+
+fn to_status(err: SignerError) -> Status {
+    let code = match &err {
+        SignerError::PeriodMismatch { .. } => Code::InvalidArgument,
+        SignerError::StaleSlot { .. } | SignerError::DoubleSign { .. } => Code::FailedPrecondition,
+        SignerError::Kes(KesError::PeriodOutOfRange { .. }) => Code::OutOfRange,
+        SignerError::Kes(KesError::PeriodRegression { .. } | KesError::KeyErased { .. }) => {
+            Code::FailedPrecondition
+        }
+        SignerError::Kes(_) | SignerError::Storage(_) => Code::Internal,
+    };
+    Status::new(code, err.to_string())
+}
+
+impl NamedService for RemoteSignerServer {
+    const NAME: &'static str = SERVICE_NAME;
+}
+
+struct GetVerificationKeySvc(RemoteSignerServer);
+
+impl UnaryService<GetVerificationKeyRequest> for GetVerificationKeySvc {
+    type Response = VerificationKeyResponse;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+
+    fn call(&mut self, request: Request<GetVerificationKeyRequest>) -> Self::Future {
+        let server = self.0.clone();
+        Box::pin(async move { server.get_verification_key(request).await })
+    }
+}
+
+struct SignBlockSvc(RemoteSignerServer);
+
+impl UnaryService<SignBlockRequest> for SignBlockSvc {
+    type Response = SignBlockResponse;
+    type Future = BoxFuture<Response<Self::Response>, Status>;
+
+    fn call(&mut self, request: Request<SignBlockRequest>) -> Self::Future {
+        let server = self.0.clone();
+        Box::pin(async move { server.sign_block(request).await })
+    }
+}
+
+impl<B> Service<http::Request<B>> for RemoteSignerServer
+where
+    B: Body + Send + 'static,
+    B::Error: Into<StdError> + Send + 'static,
+{
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<B>) -> Self::Future {
+        let server = self.clone();
+        match request.uri().path() {
+            GET_VERIFICATION_KEY_PATH => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(GetVerificationKeySvc(server), request).await)
+            }),
+            SIGN_BLOCK_PATH => Box::pin(async move {
+                let mut grpc = Grpc::new(ProstCodec::default());
+                Ok(grpc.unary(SignBlockSvc(server), request).await)
+            }),
+            _ => Box::pin(async move {
+                Ok(http::Response::builder()
+                    .status(200)
+                    .header("grpc-status", (Code::Unimplemented as i32).to_string())
+                    .header(http::header::CONTENT_TYPE, "application/grpc")
+                    .body(empty_body())
+                    .expect("static response"))
+            }),
+        }
+    }
+}
+
+// ============================================================================
+// CLIENT
+// ============================================================================
+
+/// Validator-side handle on a remote signer.
+#[derive(Clone)]
+pub struct RemoteSignerClient {
+    inner: tonic::client::Grpc<Channel>,
+}
+
+impl RemoteSignerClient {
+    /// Connect to a signer at `endpoint`, e.g. `http://10.0.0.5:7400`.
+    pub async fn connect(endpoint: impl Into<String>) -> anyhow::Result<Self> {
+        let channel = Endpoint::from_shared(endpoint.into())?.connect().await?;
+        Ok(Self::new(channel))
+    }
+
+    pub fn new(channel: Channel) -> Self {
+        RemoteSignerClient {
+            inner: tonic::client::Grpc::new(channel),
+        }
+    }
+
+    /// The signer's KES verification key and the period its key is at.
+    pub async fn verification_key(&mut self) -> Result<(KesVerificationKey, u32), Status> {
+        let response: VerificationKeyResponse = self
+            .unary(GET_VERIFICATION_KEY_PATH, GetVerificationKeyRequest {})
+            .await?;
+        let vk = response
+            .verification_key()
+            .map_err(|e| Status::internal(format!("malformed signer response: {e}")))?;
+        Ok((vk, response.current_period))
+    }
+
+    /// Request a KES signature over `block_signing_message(slot, block_hash)`.
+    pub async fn sign_block(
+        &mut self,
+        slot: u64,
+        period: u32,
+        block_hash: &[u8; 32],
+    ) -> Result<KesSignature, Status> {
+        let response: SignBlockResponse = self
+            .unary(
+                SIGN_BLOCK_PATH,
+                SignBlockRequest {
+                    slot,
+                    period,
+                    block_hash: block_hash.to_vec(),
+                },
+            )
+            .await?;
+        response
+            .signature
+            .ok_or_else(|| Status::internal("signer returned no signature"))?
+            .try_into()
+            .map_err(|e| Status::internal(format!("malformed signer response: {e}")))
+    }
+
+    async fn unary<Req, Resp>(&mut self, path: &'static str, request: Req) -> Result<Resp, Status>
+    where
+        Req: prost::Message + Send + Sync + 'static,
+        Resp: prost::Message + Default + Send + Sync + 'static,
+    {
+        self.inner
+            .ready()
+            .await
+            .map_err(|e| Status::unavailable(format!("signer unavailable: {e}")))?;
+        let response = self
+            .inner
+            .unary(
+                Request::new(request),
+                http::uri::PathAndQuery::from_static(path),
+                ProstCodec::default(),
+            )
+            .await?;
+        Ok(response.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_signing_message;
+    use crate::signer::tests::open_signer;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::Server;
+
+    async fn serve(signer: KesSigner) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(RemoteSignerServer::new(signer))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn client_signs_through_the_service() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint = serve(open_signer(dir.path())).await;
+        let mut client = RemoteSignerClient::connect(endpoint).await.unwrap();
+
+        let (vk, period) = client.verification_key().await.unwrap();
+        assert_eq!(period, 0);
+        assert_eq!(vk.max_periods(), 8);
+
+        let sig = client.sign_block(35, 3, &[7u8; 32]).await.unwrap();
+        assert!(sig.verify(&vk, &block_signing_message(35, &[7u8; 32])));
+        assert_eq!(client.verification_key().await.unwrap().1, 3);
+
+        let err = client.sign_block(35, 3, &[8u8; 32]).await.unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        let err = client.sign_block(36, 2, &[8u8; 32]).await.unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        let err = client.sign_block(90, 9, &[8u8; 32]).await.unwrap_err();
+        assert_eq!(err.code(), Code::OutOfRange);
+    }
+}
@@ -0,0 +1,272 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use aether_crypto_kes::storage::write_atomic;
+use aether_crypto_kes::{
+    block_signing_message, period_for_slot, KesError, KesKey, KesKeyStore, KesSignature,
+    KesVerificationKey,
+};
+use anyhow::{bail, Context};
+use thiserror::Error;
+
+const WATERMARK_MAGIC: &[u8; 4] = b"AKSW";
+const WATERMARK_LEN: usize = 4 + 8 + 32;
+
+#[derive(Debug, Error)]
+pub enum SignerError {
+    #[error("slot {slot} is in period {expected}, not {claimed}")]
+    PeriodMismatch {
+        slot: u64,
+        claimed: u32,
+        expected: u32,
+    },
+
+    #[error("slot {slot} is below the last signed slot {last}")]
+    StaleSlot { slot: u64, last: u64 },
+
+    #[error("slot {slot} was already signed for a different block")]
+    DoubleSign { slot: u64 },
+
+    #[error(transparent)]
+    Kes(#[from] KesError),
+
+    #[error("signer storage: {0:#}")]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Highest slot signed so far and the block signed at it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Watermark {
+    slot: u64,
+    block_hash: [u8; 32],
+}
+
+/// The signing policy of the remote signer: one block per slot, slots and
+/// periods only moving forward, and all of it on disk before a signature
+/// is returned.
+pub struct KesSigner {
+    store: KesKeyStore,
+    key: KesKey,
+    slots_per_period: u64,
+    watermark_path: PathBuf,
+    watermark: Option<Watermark>,
+}
+
+impl KesSigner {
+    /// Load the sealed key from `store` and the slot watermark from
+    /// `watermark_path`. A missing watermark file means nothing has been
+    /// signed yet.
+    pub fn open(
+        store: KesKeyStore,
+        watermark_path: impl Into<PathBuf>,
+        slots_per_period: u64,
+    ) -> anyhow::Result<Self> {
+        if slots_per_period == 0 {
+            bail!("slots per period must be positive");
+        }
+        let key = store.load()?;
+        let watermark_path = watermark_path.into();
+        let watermark = match fs::read(&watermark_path) {
+            Ok(bytes) => Some(decode_watermark(&bytes)?),
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => {
+                return Err(e).with_context(|| format!("reading {}", watermark_path.display()))
+            }
+        };
+        Ok(KesSigner {
+            store,
+            key,
+            slots_per_period,
+            watermark_path,
+            watermark,
+        })
+    }
+
+    #[must_use]
+    pub fn verification_key(&self) -> KesVerificationKey {
+        self.key.verification_key()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn current_period(&self) -> u32 {
+        self.key.current_period()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn max_periods(&self) -> u32 {
+        self.key.max_periods()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn last_signed_slot(&self) -> Option<u64> {
+        self.watermark.map(|w| w.slot)
+    }
+
+    /// Sign `block_hash` for `slot`, claimed to be in KES period `period`.
+    ///
+    /// Asking again for the block already signed at the last slot returns a
+    /// signature again, so a validator retrying after a lost response is not
+    /// locked out of its own slot.
+    pub fn sign_block(
+        &mut self,
+        slot: u64,
+        period: u32,
+        block_hash: &[u8; 32],
+    ) -> Result<KesSignature, SignerError> {
+        let expected = period_for_slot(slot, self.slots_per_period);
+        if period != expected {
+            return Err(SignerError::PeriodMismatch {
+                slot,
+                claimed: period,
+                expected,
+            });
+        }
+        if let Some(last) = self.watermark {
+            if slot < last.slot {
+                return Err(SignerError::StaleSlot {
+                    slot,
+                    last: last.slot,
+                });
+            }
+            if slot == last.slot && *block_hash != last.block_hash {
+                return Err(SignerError::DoubleSign { slot });
+            }
+        }
+        if period >= self.key.max_periods() {
+            return Err(KesError::PeriodOutOfRange {
+                requested: period,
+                max_periods: self.key.max_periods(),
+            }
+            .into());
+        }
+        if period < self.key.current_period() {
+            return Err(KesError::PeriodRegression {
+                current: self.key.current_period(),
+                requested: period,
+            }
+            .into());
+        }
+
+        let watermark = Watermark {
+            slot,
+            block_hash: *block_hash,
+        };
+        if self.watermark != Some(watermark) {
+            write_atomic(&self.watermark_path, &encode_watermark(&watermark))?;
+            self.watermark = Some(watermark);
+        }
+        let signature = self.store.sign(
+            &mut self.key,
+            period,
+            &block_signing_message(slot, block_hash),
+        )?;
+        Ok(signature)
+    }
+}
+
+fn encode_watermark(watermark: &Watermark) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(WATERMARK_LEN);
+    bytes.extend_from_slice(WATERMARK_MAGIC);
+    bytes.extend_from_slice(&watermark.slot.to_le_bytes());
+    bytes.extend_from_slice(&watermark.block_hash);
+    bytes
+}
+
+fn decode_watermark(bytes: &[u8]) -> anyhow::Result<Watermark> {
+    if bytes.len() != WATERMARK_LEN || &bytes[..4] != WATERMARK_MAGIC {
+        bail!("corrupt signer watermark file");
+    }
+    Ok(Watermark {
+        slot: u64::from_le_bytes(bytes[4..12].try_into().expect("8 bytes")),
+        block_hash: bytes[12..].try_into().expect("32 bytes"),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use aether_crypto_kes::PassphraseSealer;
+    use std::path::Path;
+
+    pub(crate) fn open_signer(dir: &Path) -> KesSigner {
+        let store = || {
+            KesKeyStore::new(
+                dir.join("kes.key"),
+                Box::new(PassphraseSealer::with_iterations(b"pass", 1_000)),
+            )
+        };
+        if store().stored_period().unwrap().is_none() {
+            store().save(&KesKey::from_seed([5u8; 32], 8)).unwrap();
+        }
+        KesSigner::open(store(), dir.join("kes.watermark"), 10).unwrap()
+    }
+
+    #[test]
+    fn signs_blocks_for_their_period() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = open_signer(dir.path());
+        let vk = signer.verification_key();
+
+        let sig = signer.sign_block(25, 2, &[1u8; 32]).unwrap();
+        assert_eq!(sig.period, 2);
+        assert!(sig.verify(&vk, &block_signing_message(25, &[1u8; 32])));
+        assert!(!sig.verify(&vk, &block_signing_message(26, &[1u8; 32])));
+        assert_eq!(signer.current_period(), 2);
+
+        assert!(matches!(
+            signer.sign_block(31, 2, &[2u8; 32]),
+            Err(SignerError::PeriodMismatch { expected: 3, .. })
+        ));
+        assert!(matches!(
+            signer.sign_block(80, 8, &[2u8; 32]),
+            Err(SignerError::Kes(KesError::PeriodOutOfRange { .. }))
+        ));
+    }
+
+    #[test]
+    fn refuses_double_signing_and_stale_slots() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = open_signer(dir.path());
+        signer.sign_block(25, 2, &[1u8; 32]).unwrap();
+
+        assert!(matches!(
+            signer.sign_block(25, 2, &[2u8; 32]),
+            Err(SignerError::DoubleSign { slot: 25 })
+        ));
+        assert!(matches!(
+            signer.sign_block(24, 2, &[2u8; 32]),
+            Err(SignerError::StaleSlot { slot: 24, last: 25 })
+        ));
+        // A retry for the same block is answered.
+        assert!(signer.sign_block(25, 2, &[1u8; 32]).is_ok());
+        assert!(signer.sign_block(26, 2, &[2u8; 32]).is_ok());
+    }
+
+    #[test]
+    fn policy_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut signer = open_signer(dir.path());
+        signer.sign_block(45, 4, &[1u8; 32]).unwrap();
+        drop(signer);
+
+        let mut restarted = open_signer(dir.path());
+        assert_eq!(restarted.last_signed_slot(), Some(45));
+        assert_eq!(restarted.current_period(), 4);
+        assert!(matches!(
+            restarted.sign_block(45, 4, &[2u8; 32]),
+            Err(SignerError::DoubleSign { slot: 45 })
+        ));
+        assert!(restarted.sign_block(50, 5, &[2u8; 32]).is_ok());
+
+        fs::write(dir.path().join("kes.watermark"), b"junk").unwrap();
+        let store = KesKeyStore::new(
+            dir.path().join("kes.key"),
+            Box::new(PassphraseSealer::with_iterations(b"pass", 1_000)),
+        );
+        assert!(KesSigner::open(store, dir.path().join("kes.watermark"), 10).is_err());
+    }
+}
@@ -41,7 +41,7 @@ impl std::fmt::Display for KeyType {
 pub fn generate_key(key_type: KeyType, output_path: &Path) -> Result<KeyFile> {
     let keypair = Keypair::generate();
     let public_key_hex = hex::encode(keypair.public_key());
-    let secret_key_hex = hex::encode(keypair.secret_key());
+    let secret_key_hex = hex::encode(keypair.secret_key().expose_secret());
 
     let key_file = KeyFile {
         version: 1,
@@ -80,7 +80,7 @@ pub fn import_key(key_type: KeyType, secret_hex: &str, output_path: &Path) -> Re
     let keypair = Keypair::from_bytes(&secret_bytes)
         .map_err(|e| anyhow::anyhow!("invalid secret key: {}", e))?;
     let public_key_hex = hex::encode(keypair.public_key());
-    let secret_key_hex = hex::encode(keypair.secret_key());
+    let secret_key_hex = hex::encode(keypair.secret_key().expose_secret());
 
     let key_file = KeyFile {
         version: 1,
@@ -171,7 +171,7 @@ mod tests {
 
         // Generate a key first to get valid secret bytes
         let original = Keypair::generate();
-        let secret_hex = hex::encode(original.secret_key());
+        let secret_hex = hex::encode(original.secret_key().expose_secret());
 
         let imported = import_key(KeyType::Ed25519, &secret_hex, &path).unwrap();
         assert_eq!(imported.public_key_hex, hex::encode(original.public_key()));
@@ -291,7 +291,7 @@ mod tests {
             fn import_0x_prefix_invariant(key_type in key_type_strategy()) {
                 let tmp = TempDir::new().unwrap();
                 let keypair = Keypair::generate();
-                let secret_hex = hex::encode(keypair.secret_key());
+                let secret_hex = hex::encode(keypair.secret_key().expose_secret());
 
                 let path1 = tmp.path().join("no_prefix.key");
                 let kf1 = import_key(key_type, &secret_hex, &path1).unwrap();
@@ -309,7 +309,7 @@ mod tests {
             fn import_preserves_public_key(key_type in key_type_strategy()) {
                 let tmp = TempDir::new().unwrap();
                 let keypair = Keypair::generate();
-                let secret_hex = hex::encode(keypair.secret_key());
+                let secret_hex = hex::encode(keypair.secret_key().expose_secret());
                 let expected_pub = hex::encode(keypair.public_key());
 
                 let path = tmp.path().join("imported.key");
@@ -1,4 +1,5 @@
-use crate::primitives::{Address, PublicKey, Signature, Slot, H256};
+use crate::consensus::SignerBitfield;
+use crate::primitives::{Address, Epoch, PublicKey, Signature, Slot, H256};
 use crate::transaction::Transaction;
 use serde::{Deserialize, Serialize};
 
@@ -76,6 +77,38 @@ pub struct BlockHeader {
     pub proposer: Address,
     pub vrf_proof: VrfProof,
     pub timestamp: u64,
+    /// Reed-Solomon parameters the proposer shredded this block with, so
+    /// receivers can decode it when the rate changes between epochs.
+    #[serde(default)]
+    pub erasure: ErasureParams,
+}
+
+/// Reed-Solomon coding of a block's shreds: any `data_shards` of the
+/// `data_shards + parity_shards` shreds rebuild it.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct ErasureParams {
+    pub data_shards: u16,
+    pub parity_shards: u16,
+}
+
+impl ErasureParams {
+    pub const fn new(data_shards: u16, parity_shards: u16) -> Self {
+        ErasureParams {
+            data_shards,
+            parity_shards,
+        }
+    }
+
+    pub fn total_shards(&self) -> usize {
+        self.data_shards as usize + self.parity_shards as usize
+    }
+}
+
+impl Default for ErasureParams {
+    /// RS(12, 10), matching the genesis `erasure_k` / `erasure_r`.
+    fn default() -> Self {
+        ErasureParams::new(10, 2)
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -89,7 +122,9 @@ pub struct AggregatedVote {
     pub slot: Slot,
     pub block_hash: H256,
     pub aggregated_signature: Vec<u8>,
-    pub signers: Vec<PublicKey>,
+    /// Epoch whose validator set `signers` indexes.
+    pub epoch: Epoch,
+    pub signers: SignerBitfield,
     pub total_stake: u128,
 }
 
@@ -122,6 +157,7 @@ impl Block {
                     .duration_since(std::time::UNIX_EPOCH)
                     .unwrap_or_default()
                     .as_secs(),
+                erasure: ErasureParams::default(),
             },
             transactions,
             aggregated_vote: None,
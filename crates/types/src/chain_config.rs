@@ -73,6 +73,10 @@ pub struct ChainParams {
     /// Set to 0 to disable pruning. Default: 10.
     #[serde(default = "default_retention_epochs")]
     pub retention_epochs: u64,
+    /// Development chain: TEE verifiers accept devnet-signed simulated
+    /// quotes, so workers can run without TEE hardware. Default: false.
+    #[serde(default)]
+    pub dev_mode: bool,
 }
 
 fn default_retention_epochs() -> u64 {
@@ -108,10 +112,30 @@ pub struct ConsensusParams {
     pub leak_downtime: String,
     /// Unbonding delay in slots.
     pub unbonding_delay_slots: u64,
-    /// HotStuff round timeout in ms.
+    /// HotStuff round timeout in ms. Consecutive timeouts double it.
     pub round_timeout_ms: u64,
+    /// Ceiling in ms the round timeout backs off to.
+    #[serde(default = "default_max_round_timeout_ms")]
+    pub max_round_timeout_ms: u64,
     /// View change timeout in ms.
     pub view_change_timeout_ms: u64,
+    /// Finalize a block in one round once more than 90% of stake votes for
+    /// it. Validators then sit out branches that leave their last vote until
+    /// it is certified or outvoted, which can stall a network with faults.
+    #[serde(default)]
+    pub fast_finality: bool,
+    /// Slots per KES period. Validator hot keys evolve at every boundary,
+    /// counted from the genesis slot.
+    #[serde(default = "default_kes_slots_per_period")]
+    pub kes_slots_per_period: u64,
+}
+
+fn default_max_round_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_kes_slots_per_period() -> u64 {
+    172_800 // 24 hours at 500ms slots
 }
 
 impl ConsensusParams {
@@ -307,6 +331,9 @@ impl ChainConfig {
         if self.chain.block_bytes_max == 0 {
             bail!("block_bytes_max must be > 0");
         }
+        if self.chain.dev_mode && self.chain.chain_id_numeric == 1 {
+            bail!("dev_mode must not be enabled on mainnet");
+        }
 
         // Consensus params
         if !(0.0..=1.0).contains(&self.consensus.tau) {
@@ -314,6 +341,9 @@ impl ChainConfig {
         }
         self.consensus.quorum_fraction()?;
         self.consensus.slash_double_rate()?;
+        if self.consensus.kes_slots_per_period == 0 {
+            bail!("kes_slots_per_period must be > 0");
+        }
 
         // Fee params
         if self.fees.target_utilization <= 0.0 || self.fees.target_utilization > 1.0 {
@@ -356,6 +386,7 @@ impl ChainConfig {
                 block_bytes_max: 2_000_000,
                 epoch_slots: 43_200,
                 retention_epochs: 10,
+                dev_mode: true,
             },
             consensus: ConsensusParams {
                 tau: 0.8,
@@ -364,7 +395,10 @@ impl ChainConfig {
                 leak_downtime: "0.00001".into(),
                 unbonding_delay_slots: 172_800,
                 round_timeout_ms: 2000,
+                max_round_timeout_ms: default_max_round_timeout_ms(),
                 view_change_timeout_ms: 5000,
+                fast_finality: false,
+                kes_slots_per_period: default_kes_slots_per_period(),
             },
             fees: FeeParams {
                 a: 10_000,
@@ -417,6 +451,7 @@ impl ChainConfig {
         let mut config = Self::devnet();
         config.chain.chain_id = "aether-testnet-1".into();
         config.chain.chain_id_numeric = 100;
+        config.chain.dev_mode = false;
         config
     }
 
@@ -425,6 +460,7 @@ impl ChainConfig {
         let mut config = Self::devnet();
         config.chain.chain_id = "aether-mainnet-1".into();
         config.chain.chain_id_numeric = 1;
+        config.chain.dev_mode = false;
         // Mainnet: larger epoch, more conservative fees
         config.chain.epoch_slots = 86_400; // ~12 hours
         config.consensus.unbonding_delay_slots = 345_600; // 48 hours
@@ -460,6 +496,16 @@ mod tests {
         assert_eq!(config.chain.chain_id_numeric, 1);
     }
 
+    #[test]
+    fn test_dev_mode_only_off_mainnet() {
+        assert!(ChainConfig::devnet().chain.dev_mode);
+        assert!(!ChainConfig::testnet().chain.dev_mode);
+
+        let mut config = ChainConfig::mainnet();
+        config.chain.dev_mode = true;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_quorum_parsing() {
         let config = ChainConfig::devnet();
@@ -555,6 +601,8 @@ erasure_r = 2
         assert_eq!(config.chain.chain_id_numeric, 900);
         assert_eq!(config.fees.a, 10_000);
         assert_eq!(config.tokens.swr_decimals, 6);
+        // Omitted KES parameters take their defaults.
+        assert_eq!(config.consensus.kes_slots_per_period, 172_800);
     }
 
     #[test]
@@ -570,6 +618,10 @@ erasure_r = 2
         let mut config = ChainConfig::devnet();
         config.chain.slot_ms = 0;
         assert!(config.validate().is_err());
+
+        let mut config = ChainConfig::devnet();
+        config.consensus.kes_slots_per_period = 0;
+        assert!(config.validate().is_err());
     }
 
     #[test]
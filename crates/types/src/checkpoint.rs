@@ -0,0 +1,309 @@
+use crate::consensus::SignerBitfield;
+use crate::primitives::{Epoch, Slot, H256};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const COMMITMENT_DOMAIN: &[u8] = b"aether-validator-set-v1";
+
+/// One validator as the set commitment sees it: the BLS key its votes are
+/// checked against and its stake for the epoch.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidatorSetLeaf {
+    pub bls_pubkey: Vec<u8>,
+    pub stake: u128,
+}
+
+impl ValidatorSetLeaf {
+    pub fn hash(&self) -> H256 {
+        let mut hasher = Sha256::new();
+        hasher.update([LEAF_PREFIX]);
+        hasher.update((self.bls_pubkey.len() as u32).to_le_bytes());
+        hasher.update(&self.bls_pubkey);
+        hasher.update(self.stake.to_le_bytes());
+        H256::from_slice(&hasher.finalize()).expect("SHA256 produces 32 bytes")
+    }
+}
+
+fn hash_pair(left: &H256, right: &H256) -> H256 {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    H256::from_slice(&hasher.finalize()).expect("SHA256 produces 32 bytes")
+}
+
+/// The root a light client trusts for an epoch: binds the epoch number, the
+/// set size and total stake to the Merkle root over the validators, so a
+/// certificate cannot claim a smaller set or a different quorum threshold.
+pub fn validator_set_commitment(
+    epoch: Epoch,
+    validator_count: usize,
+    total_stake: u128,
+    merkle_root: H256,
+) -> H256 {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMITMENT_DOMAIN);
+    hasher.update(epoch.to_le_bytes());
+    hasher.update((validator_count as u64).to_le_bytes());
+    hasher.update(total_stake.to_le_bytes());
+    hasher.update(merkle_root.as_bytes());
+    H256::from_slice(&hasher.finalize()).expect("SHA256 produces 32 bytes")
+}
+
+/// Check that `leaf` sits at `index` under `merkle_root`.
+pub fn verify_validator_leaf(
+    merkle_root: &H256,
+    index: usize,
+    leaf: &ValidatorSetLeaf,
+    path: &[H256],
+) -> bool {
+    let mut node = leaf.hash();
+    let mut position = index;
+    for sibling in path {
+        node = if position % 2 == 0 {
+            hash_pair(&node, sibling)
+        } else {
+            hash_pair(sibling, &node)
+        };
+        position /= 2;
+    }
+    position == 0 && node == *merkle_root
+}
+
+/// Merkle tree over an epoch's validators, in epoch order. Leaves are padded
+/// with zero hashes to the next power of two so every proof has the same
+/// length.
+#[derive(Clone, Debug)]
+pub struct ValidatorSetTree {
+    epoch: Epoch,
+    leaves: Vec<ValidatorSetLeaf>,
+    total_stake: u128,
+    /// `layers[0]` are the padded leaf hashes, the last layer is the root.
+    layers: Vec<Vec<H256>>,
+}
+
+impl ValidatorSetTree {
+    pub fn new(epoch: Epoch, leaves: Vec<ValidatorSetLeaf>) -> Self {
+        let total_stake = leaves
+            .iter()
+            .fold(0u128, |acc, leaf| acc.saturating_add(leaf.stake));
+        let width = leaves.len().max(1).next_power_of_two();
+        let mut layer: Vec<H256> = leaves.iter().map(ValidatorSetLeaf::hash).collect();
+        layer.resize(width, H256::zero());
+        let mut layers = vec![layer];
+        while layers.last().map_or(0, Vec::len) > 1 {
+            let next = layers
+                .last()
+                .expect("non-empty")
+                .chunks(2)
+                .map(|pair| hash_pair(&pair[0], &pair[1]))
+                .collect();
+            layers.push(next);
+        }
+        ValidatorSetTree {
+            epoch,
+            leaves,
+            total_stake,
+            layers,
+        }
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn len(&self) -> usize {
+        self.leaves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.leaves.is_empty()
+    }
+
+    pub fn total_stake(&self) -> u128 {
+        self.total_stake
+    }
+
+    pub fn leaf(&self, index: usize) -> Option<&ValidatorSetLeaf> {
+        self.leaves.get(index)
+    }
+
+    pub fn merkle_root(&self) -> H256 {
+        self.layers.last().expect("at least one layer")[0]
+    }
+
+    /// The commitment a light client is configured with for this epoch.
+    pub fn commitment(&self) -> H256 {
+        validator_set_commitment(
+            self.epoch,
+            self.leaves.len(),
+            self.total_stake,
+            self.merkle_root(),
+        )
+    }
+
+    /// Sibling hashes from leaf `index` up to the root.
+    pub fn proof(&self, index: usize) -> Option<Vec<H256>> {
+        if index >= self.leaves.len() {
+            return None;
+        }
+        let mut position = index;
+        let mut path = Vec::with_capacity(self.layers.len() - 1);
+        for layer in &self.layers[..self.layers.len() - 1] {
+            path.push(layer[position ^ 1]);
+            position /= 2;
+        }
+        Some(path)
+    }
+}
+
+/// A signer's entry in the validator set, proven against the certificate's
+/// Merkle root.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerProof {
+    pub leaf: ValidatorSetLeaf,
+    pub path: Vec<H256>,
+}
+
+/// Compact proof that `block_hash` at `slot` was finalized: the aggregated
+/// BLS signature of a 2/3 stake quorum over `block_hash || slot`, who signed,
+/// and a Merkle proof for each signer against the epoch's validator set.
+/// Checking one needs nothing but the set commitment for `epoch`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FinalityCertificate {
+    pub epoch: Epoch,
+    pub slot: Slot,
+    pub block_hash: H256,
+    pub aggregated_signature: Vec<u8>,
+    pub signers: SignerBitfield,
+    pub total_stake: u128,
+    pub validator_set_root: H256,
+    /// One per set bit in `signers`, in index order.
+    pub signer_proofs: Vec<SignerProof>,
+}
+
+impl FinalityCertificate {
+    /// Build a certificate for the validators of `tree` named by `signers`.
+    pub fn new(
+        tree: &ValidatorSetTree,
+        slot: Slot,
+        block_hash: H256,
+        aggregated_signature: Vec<u8>,
+        signers: SignerBitfield,
+    ) -> Result<Self> {
+        signers.validate()?;
+        if signers.len() != tree.len() {
+            bail!(
+                "signer bitfield covers {} validators, epoch {} has {}",
+                signers.len(),
+                tree.epoch(),
+                tree.len()
+            );
+        }
+        let signer_proofs = signers
+            .indices()
+            .map(|index| SignerProof {
+                leaf: tree.leaf(index).expect("index within set").clone(),
+                path: tree.proof(index).expect("index within set"),
+            })
+            .collect();
+        Ok(FinalityCertificate {
+            epoch: tree.epoch(),
+            slot,
+            block_hash,
+            aggregated_signature,
+            signers,
+            total_stake: tree.total_stake(),
+            validator_set_root: tree.merkle_root(),
+            signer_proofs,
+        })
+    }
+
+    /// The set commitment this certificate claims to be signed under.
+    pub fn commitment(&self) -> H256 {
+        validator_set_commitment(
+            self.epoch,
+            self.signers.len(),
+            self.total_stake,
+            self.validator_set_root,
+        )
+    }
+
+    /// The message every signer's vote covered.
+    pub fn signing_message(&self) -> Vec<u8> {
+        let mut msg = self.block_hash.as_bytes().to_vec();
+        msg.extend_from_slice(&self.slot.to_le_bytes());
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<ValidatorSetLeaf> {
+        (1..=n)
+            .map(|i| ValidatorSetLeaf {
+                bls_pubkey: vec![i; 48],
+                stake: i as u128 * 10,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_against_the_root() {
+        for n in [1u8, 2, 5, 8] {
+            let tree = ValidatorSetTree::new(4, leaves(n));
+            let root = tree.merkle_root();
+            for index in 0..tree.len() {
+                let path = tree.proof(index).unwrap();
+                assert!(verify_validator_leaf(
+                    &root,
+                    index,
+                    tree.leaf(index).unwrap(),
+                    &path
+                ));
+                // Same leaf claimed at another position fails.
+                assert!(!verify_validator_leaf(
+                    &root,
+                    index ^ 1,
+                    tree.leaf(index).unwrap(),
+                    &path
+                ));
+            }
+            assert!(tree.proof(tree.len()).is_none());
+        }
+    }
+
+    #[test]
+    fn commitment_binds_epoch_and_stake() {
+        let tree = ValidatorSetTree::new(4, leaves(3));
+        assert_eq!(tree.total_stake(), 60);
+        assert_ne!(
+            tree.commitment(),
+            ValidatorSetTree::new(5, leaves(3)).commitment()
+        );
+        let mut heavier = leaves(3);
+        heavier[0].stake += 1;
+        assert_ne!(
+            tree.commitment(),
+            ValidatorSetTree::new(4, heavier).commitment()
+        );
+
+        let mut signers = SignerBitfield::new(3);
+        signers.set(0).unwrap();
+        signers.set(2).unwrap();
+        let cert =
+            FinalityCertificate::new(&tree, 410, H256::from([7u8; 32]), vec![1], signers).unwrap();
+        assert_eq!(cert.commitment(), tree.commitment());
+        assert_eq!(cert.signer_proofs.len(), 2);
+        assert_eq!(cert.signer_proofs[1].leaf.stake, 30);
+        assert!(
+            FinalityCertificate::new(&tree, 410, H256::zero(), vec![], SignerBitfield::new(4))
+                .is_err()
+        );
+    }
+}
@@ -1,4 +1,6 @@
-use crate::primitives::{PublicKey, Signature, Slot, H256};
+use crate::block::AggregatedVote;
+use crate::primitives::{Address, PublicKey, Signature, Slot, H256};
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -10,6 +12,38 @@ pub struct Vote {
     pub stake: u128,
 }
 
+/// A validator's current slot, broadcast when its pacemaker times out so
+/// validators split by a partition or clock skew converge on one slot.
+/// Carries the sender's highest QC so a validator that rejoins also learns
+/// what the others have locked on.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoundSync {
+    pub slot: Slot,
+    pub high_qc: Option<AggregatedVote>,
+    pub validator: PublicKey,
+    /// BLS signature over [`RoundSync::signing_message`].
+    pub signature: Signature,
+}
+
+impl RoundSync {
+    /// Bytes the sender signs: a domain tag and the slot. The QC carries its
+    /// own aggregate signature.
+    pub fn signing_message(slot: Slot) -> Vec<u8> {
+        let mut msg = b"aether-round-sync".to_vec();
+        msg.extend_from_slice(&slot.to_le_bytes());
+        msg
+    }
+}
+
+/// How a slot became final: in one round, because more than 90% of stake
+/// voted for its block, or through the regular two-chain commit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FinalityPath {
+    Fast,
+    TwoChain,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ValidatorInfo {
     pub pubkey: PublicKey,
@@ -27,3 +61,202 @@ pub struct EpochInfo {
     pub validators: Vec<ValidatorInfo>,
     pub total_stake: u128,
 }
+
+/// Which validators of an epoch signed an aggregate. Bit `i` (least
+/// significant first within each byte) stands for `EpochInfo::validators[i]`,
+/// so a certificate names its signers in `len / 8` bytes instead of a list of
+/// public keys.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignerBitfield {
+    len: u32,
+    bits: Vec<u8>,
+}
+
+impl SignerBitfield {
+    /// An empty bitfield over a validator set of `len`.
+    pub fn new(len: usize) -> Self {
+        SignerBitfield {
+            len: len as u32,
+            bits: vec![0u8; len.div_ceil(8)],
+        }
+    }
+
+    /// Size of the validator set the bitfield indexes.
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Mark validator `index`; returns false if it was already set.
+    pub fn set(&mut self, index: usize) -> Result<bool> {
+        if index >= self.len() {
+            bail!(
+                "signer index {index} out of range for {} validators",
+                self.len
+            );
+        }
+        let mask = 1u8 << (index % 8);
+        let was_set = self.bits[index / 8] & mask != 0;
+        self.bits[index / 8] |= mask;
+        Ok(!was_set)
+    }
+
+    pub fn get(&self, index: usize) -> bool {
+        index < self.len() && self.bits[index / 8] & (1u8 << (index % 8)) != 0
+    }
+
+    /// Number of signers.
+    pub fn count(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    /// Indices of the signers, ascending.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(|&index| self.get(index))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+
+    /// Reject encodings that are not the canonical one for `len`: wrong
+    /// byte count or bits set past the end.
+    pub fn validate(&self) -> Result<()> {
+        if self.bits.len() != self.len().div_ceil(8) {
+            bail!(
+                "signer bitfield has {} bytes for {} validators",
+                self.bits.len(),
+                self.len
+            );
+        }
+        let tail = self.len % 8;
+        if tail != 0 && self.bits.last().is_some_and(|last| last >> tail != 0) {
+            bail!("signer bitfield has bits set past validator {}", self.len);
+        }
+        Ok(())
+    }
+}
+
+impl EpochInfo {
+    /// Position of `address` in the epoch's validator set.
+    pub fn validator_index(&self, address: &Address) -> Option<usize> {
+        self.validators
+            .iter()
+            .position(|v| v.pubkey.to_address() == *address)
+    }
+
+    /// Bitfield for `signers`, each of which must be in the set once.
+    pub fn signer_bitfield<'a>(
+        &self,
+        signers: impl IntoIterator<Item = &'a Address>,
+    ) -> Result<SignerBitfield> {
+        let mut bitfield = SignerBitfield::new(self.validators.len());
+        for signer in signers {
+            let index = self.validator_index(signer).ok_or_else(|| {
+                anyhow::anyhow!("{signer:?} is not a validator in epoch {}", self.epoch)
+            })?;
+            if !bitfield.set(index)? {
+                bail!("duplicate signer {signer:?}");
+            }
+        }
+        Ok(bitfield)
+    }
+
+    /// Expand `bitfield` back into the validators it names.
+    pub fn signers(&self, bitfield: &SignerBitfield) -> Result<Vec<&ValidatorInfo>> {
+        bitfield.validate()?;
+        if bitfield.len() != self.validators.len() {
+            bail!(
+                "signer bitfield covers {} validators, epoch {} has {}",
+                bitfield.len(),
+                self.epoch,
+                self.validators.len()
+            );
+        }
+        Ok(bitfield
+            .indices()
+            .map(|index| &self.validators[index])
+            .collect())
+    }
+
+    /// Total epoch stake of the validators `bitfield` names.
+    pub fn signed_stake(&self, bitfield: &SignerBitfield) -> Result<u128> {
+        Ok(self
+            .signers(bitfield)?
+            .iter()
+            .map(|v| v.stake)
+            .fold(0u128, u128::saturating_add))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn epoch(n: u8) -> EpochInfo {
+        let validators: Vec<ValidatorInfo> = (1..=n)
+            .map(|i| ValidatorInfo {
+                pubkey: PublicKey::from_bytes(vec![i; 32]),
+                stake: i as u128 * 100,
+                commission: 0,
+                active: true,
+            })
+            .collect();
+        EpochInfo {
+            epoch: 3,
+            start_slot: 300,
+            end_slot: 399,
+            randomness: H256::zero(),
+            total_stake: validators.iter().map(|v| v.stake).sum(),
+            validators,
+        }
+    }
+
+    #[test]
+    fn bitfield_roundtrips_signers_and_stake() {
+        let info = epoch(10);
+        let signers = [
+            info.validators[0].pubkey.to_address(),
+            info.validators[8].pubkey.to_address(),
+            info.validators[9].pubkey.to_address(),
+        ];
+        let bitfield = info.signer_bitfield(&signers).unwrap();
+        assert_eq!(bitfield.as_bytes(), &[0b0000_0001, 0b0000_0011]);
+        assert_eq!(bitfield.count(), 3);
+        assert_eq!(bitfield.indices().collect::<Vec<_>>(), vec![0, 8, 9]);
+
+        let expanded: Vec<Address> = info
+            .signers(&bitfield)
+            .unwrap()
+            .iter()
+            .map(|v| v.pubkey.to_address())
+            .collect();
+        assert_eq!(expanded, signers);
+        assert_eq!(info.signed_stake(&bitfield).unwrap(), 100 + 900 + 1000);
+    }
+
+    #[test]
+    fn rejects_unknown_duplicate_and_malformed_signers() {
+        let info = epoch(10);
+        let a = info.validators[1].pubkey.to_address();
+        assert!(info.signer_bitfield(&[a, a]).is_err());
+        let stranger = PublicKey::from_bytes(vec![0xEE; 32]).to_address();
+        assert!(info.signer_bitfield(&[stranger]).is_err());
+
+        // Sized for another validator set.
+        assert!(info.signed_stake(&SignerBitfield::new(9)).is_err());
+        // Bits past the end, or the wrong number of bytes.
+        let mut bitfield = SignerBitfield::new(10);
+        bitfield.bits[1] = 0b0000_0100;
+        assert!(info.signed_stake(&bitfield).is_err());
+        bitfield.bits = vec![0u8; 3];
+        assert!(info.signed_stake(&bitfield).is_err());
+        assert!(SignerBitfield::new(10).set(10).is_err());
+    }
+}
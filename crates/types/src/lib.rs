@@ -9,6 +9,8 @@
 // - Signature: Cryptographic signature
 // - Block, Transaction, UTxO, Account
 // - Slot, Epoch
+// - MultisigAccount: M-of-N sender whose policy stands in for a public key
+// - FinalityCertificate: checkpoint proof checkable against a validator-set root
 //
 // All types implement:
 // - Serialize/Deserialize (serde)
@@ -19,25 +21,30 @@
 pub mod account;
 pub mod block;
 pub mod chain_config;
+pub mod checkpoint;
 pub mod consensus;
+pub mod multisig;
 pub mod primitives;
 pub mod transaction;
 
 pub use account::{Account, Utxo};
 pub use block::{
-    AggregatedVote, Block, BlockHeader, SlashEvidence, SlashEvidenceType, SlashVote, VrfProof,
-    PROTOCOL_VERSION,
+    AggregatedVote, Block, BlockHeader, ErasureParams, SlashEvidence, SlashEvidenceType, SlashVote,
+    VrfProof, PROTOCOL_VERSION,
 };
 pub use chain_config::{
     AiMeshParams, ChainConfig, ChainId, ChainParams, ConsensusParams, FeeParams, NetworkingParams,
     RentParams, RewardParams, TokenParams, WellKnownAddresses,
 };
-pub use consensus::{EpochInfo, ValidatorInfo, Vote};
+pub use checkpoint::{FinalityCertificate, SignerProof, ValidatorSetLeaf, ValidatorSetTree};
+pub use consensus::{EpochInfo, FinalityPath, RoundSync, SignerBitfield, ValidatorInfo, Vote};
+pub use multisig::MultisigAccount;
 pub use primitives::{Address, Epoch, PublicKey, Signature, Slot, H160, H256};
 #[cfg(test)]
 mod proptest_tests;
 
 pub use transaction::{
-    BlobTransaction, Transaction, TransactionReceipt, TransactionStatus, TransferPayload, UtxoId,
-    UtxoOutput, BLOB_RETENTION_SLOTS, MAX_BLOBS_PER_TX, MAX_BLOB_SIZE, TRANSFER_PROGRAM_ID,
+    BlobTransaction, Transaction, TransactionReceipt, TransactionStatus, TransferPayload,
+    TxLifecycle, UtxoId, UtxoOutput, BLOB_RETENTION_SLOTS, JOB_ESCROW_PROGRAM_ID, MAX_BLOBS_PER_TX,
+    MAX_BLOB_SIZE, TRANSFER_PROGRAM_ID,
 };
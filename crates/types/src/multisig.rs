@@ -0,0 +1,140 @@
+use crate::primitives::{Address, PublicKey, Signature};
+use crate::transaction::Transaction;
+use aether_crypto_primitives::multisig::{
+    MultisigError, MultisigPolicy, MultisigSignature, SignatureCollector,
+};
+
+/// An account controlled by M of N Ed25519 keys rather than one, for
+/// treasuries and team wallets without a contract.
+///
+/// The account has no key of its own: transactions from it carry the
+/// encoded policy as `sender_pubkey`, so its address is derived the same
+/// way as any other and needs no extra state or transaction fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultisigAccount {
+    policy: MultisigPolicy,
+}
+
+impl MultisigAccount {
+    pub fn new(threshold: u8, signers: &[PublicKey]) -> Result<Self, MultisigError> {
+        let keys: Vec<&[u8]> = signers.iter().map(PublicKey::as_bytes).collect();
+        Ok(MultisigAccount {
+            policy: MultisigPolicy::new(threshold, &keys)?,
+        })
+    }
+
+    /// The account a transaction's `sender_pubkey` names, if it is a
+    /// multisig one.
+    pub fn from_sender_pubkey(pubkey: &PublicKey) -> Result<Self, MultisigError> {
+        Ok(MultisigAccount {
+            policy: MultisigPolicy::decode(pubkey.as_bytes())?,
+        })
+    }
+
+    pub fn policy(&self) -> &MultisigPolicy {
+        &self.policy
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.policy.threshold()
+    }
+
+    /// What goes in `sender_pubkey` of transactions from this account.
+    pub fn sender_pubkey(&self) -> PublicKey {
+        PublicKey::from_bytes(self.policy.encode())
+    }
+
+    pub fn address(&self) -> Address {
+        self.sender_pubkey().to_address()
+    }
+
+    /// Start collecting cosigner approvals for `tx`, which must already be
+    /// final apart from its signature.
+    pub fn collector(&self, tx: &Transaction) -> SignatureCollector {
+        SignatureCollector::new(self.policy.clone(), tx.hash().as_bytes())
+    }
+
+    /// The transaction signature carrying `signature`.
+    pub fn transaction_signature(signature: &MultisigSignature) -> Signature {
+        Signature::from_bytes(signature.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction::TESTNET_CHAIN_ID;
+    use aether_crypto_primitives::Keypair;
+    use std::collections::HashSet;
+
+    fn treasury_transfer(account: &MultisigAccount) -> Transaction {
+        Transaction {
+            nonce: 0,
+            chain_id: TESTNET_CHAIN_ID,
+            sender: account.address(),
+            sender_pubkey: account.sender_pubkey(),
+            inputs: vec![],
+            outputs: vec![],
+            reads: HashSet::new(),
+            writes: HashSet::new(),
+            program_id: None,
+            data: vec![],
+            gas_limit: 21_000,
+            fee: 100,
+            signature: Signature::from_bytes(vec![]),
+        }
+    }
+
+    fn two_of_three() -> (Vec<Keypair>, MultisigAccount) {
+        let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::generate()).collect();
+        let signers: Vec<PublicKey> = keypairs
+            .iter()
+            .map(|kp| PublicKey::from_bytes(kp.public_key()))
+            .collect();
+        (keypairs, MultisigAccount::new(2, &signers).unwrap())
+    }
+
+    #[test]
+    fn threshold_signed_transaction_verifies() {
+        let (keypairs, account) = two_of_three();
+        let mut tx = treasury_transfer(&account);
+        assert!(tx.is_multisig());
+
+        let mut collector = account.collector(&tx);
+        assert_eq!(collector.payload(), tx.signing_payload());
+        collector.sign_with(&keypairs[0]).unwrap();
+
+        // One approval is not enough.
+        tx.signature = MultisigAccount::transaction_signature(collector.partial());
+        assert!(tx.verify_signature().is_err());
+
+        collector.sign_with(&keypairs[2]).unwrap();
+        tx.signature = MultisigAccount::transaction_signature(&collector.finish().unwrap());
+        tx.verify_signature().unwrap();
+
+        assert_eq!(
+            MultisigAccount::from_sender_pubkey(&tx.sender_pubkey).unwrap(),
+            account
+        );
+    }
+
+    #[test]
+    fn approvals_do_not_carry_over_to_other_transactions() {
+        let (keypairs, account) = two_of_three();
+        let tx = treasury_transfer(&account);
+        let mut collector = account.collector(&tx);
+        collector.sign_with(&keypairs[0]).unwrap();
+        collector.sign_with(&keypairs[1]).unwrap();
+
+        let mut other = treasury_transfer(&account);
+        other.nonce = 1;
+        other.signature = MultisigAccount::transaction_signature(&collector.finish().unwrap());
+        assert!(other.verify_signature().is_err());
+
+        // Nor does the policy's address accept a different policy.
+        let mut forged = tx.clone();
+        forged.sender_pubkey = two_of_three().1.sender_pubkey();
+        forged.signature = MultisigAccount::transaction_signature(&collector.finish().unwrap());
+        assert!(forged.verify_signature().is_err());
+    }
+}
@@ -1,10 +1,21 @@
 use crate::chain_config::FeeParams;
+use crate::multisig::MultisigAccount;
 use crate::primitives::{Address, PublicKey, Signature, H256};
 use aether_crypto_primitives::ed25519;
+use aether_crypto_primitives::multisig::{
+    MultisigPolicy, MultisigSignature, MAX_MULTISIG_SIGNATURE_LEN,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 pub const TRANSFER_PROGRAM_ID: H256 = H256([1u8; 32]);
+/// The job escrow precompile (`0x00..02`) as a program id: its address
+/// left-padded to 32 bytes. Call data follows `aether_codecs::abi::job_escrow`.
+pub const JOB_ESCROW_PROGRAM_ID: H256 = H256({
+    let mut id = [0u8; 32];
+    id[31] = 0x02;
+    id
+});
 
 // Legacy chain ID constants -- prefer ChainConfig presets for new code.
 pub const MAINNET_CHAIN_ID: u64 = 1;
@@ -64,6 +75,35 @@ pub enum TransactionStatus {
     Failed { reason: String },
 }
 
+/// Where a transaction is between submission and finality, as streamed
+/// to status subscribers. Unlike [`TransactionStatus`], which records how
+/// execution went, this tracks the transaction's way into the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxLifecycle {
+    /// Accepted into the mempool.
+    Pending,
+    /// Executed in `block_hash` at `slot`.
+    Included { slot: u64, block_hash: H256 },
+    /// The block that included it at `slot` is final.
+    Finalized { slot: u64 },
+    /// Left the mempool without being included, e.g. evicted or expired.
+    Dropped { reason: String },
+    /// Superseded by the same-nonce transaction `by`, which pays more.
+    Replaced { by: H256 },
+}
+
+impl TxLifecycle {
+    /// No further status follows this one.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TxLifecycle::Finalized { .. }
+                | TxLifecycle::Dropped { .. }
+                | TxLifecycle::Replaced { .. }
+        )
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Log {
     pub address: Address,
@@ -95,14 +135,36 @@ impl Transaction {
         Ok(())
     }
 
+    /// Whether the sender is a [`MultisigAccount`], whose `sender_pubkey`
+    /// is an encoded policy rather than a single key.
+    pub fn is_multisig(&self) -> bool {
+        MultisigPolicy::is_encoded(self.sender_pubkey.as_bytes())
+    }
+
+    /// The bytes the sender signs: the transaction hash for a single key,
+    /// or the policy-bound payload every cosigner of a multisig sender
+    /// signs.
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let hash = self.hash();
+        match MultisigAccount::from_sender_pubkey(&self.sender_pubkey) {
+            Ok(account) => account.policy().signing_payload(hash.as_bytes()),
+            Err(_) => hash.as_bytes().to_vec(),
+        }
+    }
+
     pub fn verify_signature(&self) -> anyhow::Result<()> {
         if self.signature.as_bytes().is_empty() {
             anyhow::bail!("signature is empty");
         }
         // M3: Bound signature size to prevent DoS via oversized signatures
-        if self.signature.as_bytes().len() > 128 {
+        let max_len = if self.is_multisig() {
+            MAX_MULTISIG_SIGNATURE_LEN
+        } else {
+            128
+        };
+        if self.signature.as_bytes().len() > max_len {
             anyhow::bail!(
-                "signature too large: {} bytes (max 128)",
+                "signature too large: {} bytes (max {max_len})",
                 self.signature.as_bytes().len()
             );
         }
@@ -116,6 +178,17 @@ impl Transaction {
         // Get the message to verify (transaction hash without signature)
         let msg = self.hash();
 
+        if self.is_multisig() {
+            let account = MultisigAccount::from_sender_pubkey(&self.sender_pubkey)
+                .map_err(|e| anyhow::anyhow!("invalid multisig sender: {e}"))?;
+            let signature = MultisigSignature::decode(self.signature.as_bytes())
+                .map_err(|e| anyhow::anyhow!("invalid multisig signature: {e}"))?;
+            return account
+                .policy()
+                .verify(msg.as_bytes(), &signature)
+                .map_err(|e| anyhow::anyhow!("multisig verification failed: {e}"));
+        }
+
         ed25519::verify(
             self.sender_pubkey.as_bytes(),
             msg.as_bytes(),
@@ -154,6 +227,8 @@ impl Transaction {
         Ok(self.fee)
     }
 
+    /// Inputs for batch Ed25519 verification. Meaningless for multisig
+    /// senders, which must go through `verify_signature`.
     pub fn ed25519_tuple(&self) -> (Vec<u8>, Vec<u8>, Vec<u8>) {
         let msg = self.hash();
         (
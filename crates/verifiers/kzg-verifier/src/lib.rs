@@ -1,11 +1,17 @@
 pub mod challenge;
 pub mod error;
 pub mod opening;
+pub mod transcript;
 pub mod verify;
 pub mod watchtower;
 
 pub use challenge::KzgChallenge;
 pub use error::{Result, VerifierError};
 pub use opening::{KzgOpeningResponse, Opening};
+pub use transcript::{derive_challenge, Transcript, CHALLENGE_DOMAIN};
 pub use verify::verify_kzg_openings;
-pub use watchtower::build_challenge;
+pub use watchtower::{
+    build_challenge, CommittedLayer, EventSource, FraudReason, FraudReport, IssuedChallenge,
+    TraceAnnouncement, Watchtower, WatchtowerConfig, WatchtowerDuty, WatchtowerEvent,
+    WatchtowerMessage, WatchtowerSink,
+};
@@ -0,0 +1,179 @@
+// ============================================================================
+// FIAT–SHAMIR TRANSCRIPT - Challenge positions nobody gets to choose
+// ============================================================================
+// Which trace points get opened must not be up to the watchtower (it could
+// pick points a colluding worker knows are safe) nor to the worker (it would
+// only commit honestly where it expects to be asked). Both sides instead run
+// the same transcript:
+//
+//   absorb   vcr id, every layer commitment, then the randomness of the block
+//            that included them
+//   squeeze  layer positions, then point positions per chosen layer
+//
+// The commitments are fixed before the block randomness exists, so the worker
+// cannot grind them against the challenge, and anyone holding the
+// announcement can recompute the challenge and reject one that differs.
+//
+// Every absorb and squeeze is length-prefixed and labelled; each squeeze is
+// fed back into the state, so later challenges depend on earlier ones.
+// ============================================================================
+
+use anyhow::{bail, ensure, Result};
+use sha2::{Digest, Sha256};
+
+use crate::challenge::KzgChallenge;
+use crate::watchtower::TraceAnnouncement;
+use aether_types::H256;
+
+/// Domain of the transcript VCR trace challenges are derived from.
+pub const CHALLENGE_DOMAIN: &[u8] = b"AETHER-KZG-CHALLENGE-v1";
+
+const ABSORB_TAG: u8 = 0x00;
+const SQUEEZE_TAG: u8 = 0x01;
+
+#[derive(Clone)]
+pub struct Transcript {
+    state: Sha256,
+}
+
+impl Transcript {
+    pub fn new(domain: &[u8]) -> Self {
+        let mut transcript = Transcript {
+            state: Sha256::new(),
+        };
+        transcript.append(b"domain", domain);
+        transcript
+    }
+
+    pub fn append(&mut self, label: &[u8], data: &[u8]) {
+        self.state.update([ABSORB_TAG]);
+        self.state.update((label.len() as u64).to_le_bytes());
+        self.state.update(label);
+        self.state.update((data.len() as u64).to_le_bytes());
+        self.state.update(data);
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append(label, &value.to_le_bytes());
+    }
+
+    /// 32 challenge bytes; also absorbed, so the next squeeze differs.
+    pub fn challenge_bytes(&mut self, label: &[u8]) -> [u8; 32] {
+        let output: [u8; 32] = self
+            .state
+            .clone()
+            .chain_update([SQUEEZE_TAG])
+            .chain_update((label.len() as u64).to_le_bytes())
+            .chain_update(label)
+            .finalize()
+            .into();
+        self.append(b"squeeze", &output);
+        output
+    }
+
+    /// `min(k, n)` distinct indices below `n`, in the order drawn.
+    pub fn challenge_indices(&mut self, label: &[u8], n: usize, k: usize) -> Vec<usize> {
+        let want = k.min(n);
+        let mut chosen = Vec::with_capacity(want);
+        while chosen.len() < want {
+            let bytes = self.challenge_bytes(label);
+            let mut word = [0u8; 16];
+            word.copy_from_slice(&bytes[..16]);
+            // A 128-bit draw keeps the modulo bias below 2^-64 for any n.
+            let index = (u128::from_le_bytes(word) % n as u128) as usize;
+            if !chosen.contains(&index) {
+                chosen.push(index);
+            }
+        }
+        chosen
+    }
+}
+
+/// The challenge for `announcement` under the randomness of the block that
+/// included it: `sample_layers` layers, `sample_points` points in each.
+/// Workers and verifiers call this with the same inputs and must agree.
+pub fn derive_challenge(
+    announcement: &TraceAnnouncement,
+    randomness: &H256,
+    sample_layers: usize,
+    sample_points: usize,
+    deadline_slot: u64,
+) -> Result<KzgChallenge> {
+    ensure!(!announcement.layers.is_empty(), "VCR commits to no layers");
+    ensure!(
+        sample_layers > 0 && sample_points > 0,
+        "must sample at least one layer and point"
+    );
+    if let Some(layer) = announcement.layers.iter().find(|layer| layer.len == 0) {
+        bail!("layer {} commits to no points", layer.layer_idx);
+    }
+
+    let mut transcript = Transcript::new(CHALLENGE_DOMAIN);
+    transcript.append(b"vcr_id", announcement.vcr_id.as_bytes());
+    transcript.append_u64(b"layers", announcement.layers.len() as u64);
+    for layer in &announcement.layers {
+        transcript.append_u64(b"layer_idx", layer.layer_idx.into());
+        transcript.append_u64(b"layer_len", layer.len.into());
+        transcript.append(b"commitment", &layer.commitment.commitment);
+    }
+    transcript.append(b"randomness", randomness.as_bytes());
+
+    let mut positions =
+        transcript.challenge_indices(b"layer", announcement.layers.len(), sample_layers);
+    positions.sort_unstable();
+    let mut layer_indices = Vec::with_capacity(positions.len());
+    let mut point_indices = Vec::with_capacity(positions.len());
+    for position in positions {
+        let layer = &announcement.layers[position];
+        let mut points: Vec<u32> = transcript
+            .challenge_indices(b"point", layer.len as usize, sample_points)
+            .into_iter()
+            .map(|point| point as u32)
+            .collect();
+        points.sort_unstable();
+        layer_indices.push(layer.layer_idx);
+        point_indices.push(points);
+    }
+    Ok(KzgChallenge {
+        vcr_id: announcement.vcr_id,
+        layer_indices,
+        point_indices,
+        deadline_slot,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn challenges_depend_on_everything_absorbed() {
+        let squeeze = |label: &[u8], data: &[u8]| {
+            let mut transcript = Transcript::new(b"test");
+            transcript.append(label, data);
+            transcript.challenge_bytes(b"c")
+        };
+        assert_eq!(squeeze(b"a", b"bc"), squeeze(b"a", b"bc"));
+        // Length prefixes keep label/data boundaries apart.
+        assert_ne!(squeeze(b"a", b"bc"), squeeze(b"ab", b"c"));
+        assert_ne!(squeeze(b"a", b"bc"), squeeze(b"a", b"bd"));
+
+        let mut transcript = Transcript::new(b"test");
+        let first = transcript.challenge_bytes(b"c");
+        assert_ne!(first, transcript.challenge_bytes(b"c"));
+        assert_ne!(first, Transcript::new(b"other").challenge_bytes(b"c"));
+    }
+
+    #[test]
+    fn indices_are_distinct_and_in_range() {
+        let mut transcript = Transcript::new(b"test");
+        let mut indices = transcript.challenge_indices(b"i", 10, 10);
+        indices.sort_unstable();
+        assert_eq!(indices, (0..10).collect::<Vec<_>>());
+
+        let indices = transcript.challenge_indices(b"i", 1000, 8);
+        assert_eq!(indices.len(), 8);
+        assert!(indices.iter().all(|&i| i < 1000));
+        assert!(transcript.challenge_indices(b"i", 0, 3).is_empty());
+    }
+}
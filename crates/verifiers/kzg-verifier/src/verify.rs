@@ -29,7 +29,7 @@ pub fn verify_kzg_openings(
     }
 
     let mut seen = HashSet::new();
-
+    let mut points = Vec::with_capacity(response.openings.len());
     for opening in &response.openings {
         validate_opening(challenge, opening, &mut seen)?;
         let point: [u8; 32] = opening
@@ -37,8 +37,28 @@ pub fn verify_kzg_openings(
             .as_slice()
             .try_into()
             .map_err(|_| VerifierError::InvalidChallenge("point must be 32 bytes"))?;
+        points.push(point);
+    }
+
+    // One pairing check for the whole response; only on failure is each
+    // opening checked on its own, to name the bad one.
+    let commitments: Vec<_> = response
+        .openings
+        .iter()
+        .map(|opening| opening.commitment.clone())
+        .collect();
+    let proofs: Vec<_> = response
+        .openings
+        .iter()
+        .map(|opening| opening.proof.clone())
+        .collect();
+    if let Ok(true) = verifier.batch_verify(&commitments, &proofs, &points) {
+        return Ok(());
+    }
+
+    for (opening, point) in response.openings.iter().zip(&points) {
         let valid = verifier
-            .verify(&opening.commitment, &opening.proof, &point)
+            .verify(&opening.commitment, &opening.proof, point)
             .map_err(|err| VerifierError::InvalidProof {
                 layer: opening.layer_idx,
                 point: opening.point_idx,
@@ -143,6 +163,22 @@ mod tests {
         assert!(verify_kzg_openings(&verifier, &challenge, &response).is_ok());
     }
 
+    #[test]
+    fn names_the_invalid_opening() {
+        let verifier = KzgVerifier::new_insecure_test(1024);
+        let challenge = sample_challenge();
+        let mut openings = make_valid_openings(&verifier);
+        openings[1].proof.evaluation[0] ^= 1;
+        let response = KzgOpeningResponse::new(H256::zero(), openings);
+
+        match verify_kzg_openings(&verifier, &challenge, &response) {
+            Err(VerifierError::InvalidProof {
+                layer: 0, point: 1, ..
+            }) => {}
+            other => panic!("expected invalid proof at layer 0 point 1, got {other:?}"),
+        }
+    }
+
     #[test]
     fn detects_mismatch() {
         let verifier = KzgVerifier::new_insecure_test(1024);
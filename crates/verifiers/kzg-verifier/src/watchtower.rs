@@ -1,11 +1,51 @@
+// ============================================================================
+// WATCHTOWER - Spot-checking committed traces
+// ============================================================================
+// A watchtower follows newly committed VCR traces (from the firehose or
+// gossip, through any `EventSource`) and challenges each one:
+//
+//   NewVcr    -> derive layers/points from the commitments and the block
+//                randomness, submit the challenge, start the response deadline
+//   Response  -> openings must be for the announced commitments, at
+//                x = point_idx, and verify; otherwise submit a fraud report
+//   Slot      -> challenges past their deadline become NoResponse reports
+//
+// Sampling is not the watchtower's choice: points come from the Fiat–Shamir
+// transcript in `transcript`, which the worker and any verifier run too, and
+// `IssuedChallenge::verify_sampling` rejects a challenge that differs, so a
+// watchtower cannot quietly pick points a colluding worker knows are safe.
+//
+// Nor is the choice of traces: with a `WatchtowerDuty` configured, a tower
+// only challenges traces where stake-weighted `sortition` over the block
+// randomness, the VCR id and its own id gives it a seat. The draw is public,
+// so anyone can refuse a challenge from a tower without a seat.
+//
+// Workers and towers exchange announcements, challenges and openings as
+// `WatchtowerMessage`s over gossip; fraud reports go on chain through the
+// job escrow program.
+// ============================================================================
+
+use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+
+use aether_crypto_kzg::{scalar_from_i64, KzgCommitment, KzgVerifier};
+use aether_crypto_vrf::{sortition, Tau};
+use anyhow::{bail, ensure};
 use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 use crate::challenge::KzgChallenge;
+use crate::opening::KzgOpeningResponse;
+use crate::transcript::derive_challenge;
+use crate::verify::verify_kzg_openings;
 use aether_types::H256;
 
 /// Build a random challenge selecting a subset of layers and evaluation points.
 ///
 /// The function is deterministic when a seed is provided which keeps tests stable.
+/// Points are the caller's choice; challenges that others must accept come
+/// from `derive_challenge`.
 pub fn build_challenge(
     vcr_id: H256,
     total_layers: u32,
@@ -52,6 +92,336 @@ pub fn build_challenge(
     })
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommittedLayer {
+    pub layer_idx: u32,
+    /// Number of evaluation points (activations) in the layer.
+    pub len: u32,
+    pub commitment: KzgCommitment,
+}
+
+/// A VCR's trace commitments as seen on the firehose.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceAnnouncement {
+    pub vcr_id: H256,
+    pub worker_id: Vec<u8>,
+    pub layers: Vec<CommittedLayer>,
+    /// Randomness of the block that included the commitments.
+    pub randomness: H256,
+}
+
+#[derive(Debug, Clone)]
+pub struct WatchtowerConfig {
+    pub sample_layers: usize,
+    pub sample_points: usize,
+    /// Slots the worker has to answer a challenge.
+    pub response_slots: u64,
+    /// Which traces this tower watches; `None` watches all of them.
+    pub duty: Option<WatchtowerDuty>,
+}
+
+/// A watchtower's stake and the expected number of towers per trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchtowerDuty {
+    pub stake: u128,
+    pub total_stake: u128,
+    pub tau: Tau,
+}
+
+impl WatchtowerDuty {
+    /// Seats `watchtower` holds on `announcement`'s trace.
+    pub fn seats(&self, announcement: &TraceAnnouncement, watchtower: &[u8; 32]) -> u64 {
+        let mut hasher = Sha256::new();
+        hasher.update(b"aether-watchtower-duty");
+        hasher.update(announcement.randomness.as_bytes());
+        hasher.update(announcement.vcr_id.as_bytes());
+        hasher.update(watchtower);
+        let draw: [u8; 32] = hasher.finalize().into();
+        sortition(&draw, self.stake, self.total_stake, self.tau)
+    }
+}
+
+impl Default for WatchtowerConfig {
+    fn default() -> Self {
+        WatchtowerConfig {
+            sample_layers: 2,
+            sample_points: 4,
+            response_slots: 5,
+            duty: None,
+        }
+    }
+}
+
+/// A challenge and the watchtower that issued it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedChallenge {
+    pub challenge: KzgChallenge,
+    pub watchtower: [u8; 32],
+}
+
+impl IssuedChallenge {
+    /// Check that the challenged points are exactly what the transcript over
+    /// `announcement` selects under `config`.
+    pub fn verify_sampling(
+        &self,
+        announcement: &TraceAnnouncement,
+        config: &WatchtowerConfig,
+    ) -> anyhow::Result<()> {
+        let expected = derive_challenge(
+            announcement,
+            &announcement.randomness,
+            config.sample_layers,
+            config.sample_points,
+            self.challenge.deadline_slot,
+        )?;
+        ensure!(
+            expected == self.challenge,
+            "challenge points do not match the transcript"
+        );
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FraudReason {
+    NoResponse,
+    InvalidOpenings(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FraudReport {
+    pub vcr_id: H256,
+    pub worker_id: Vec<u8>,
+    pub challenge: IssuedChallenge,
+    /// The failing response, if there was one.
+    pub response: Option<KzgOpeningResponse>,
+    pub reason: FraudReason,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone)]
+pub enum WatchtowerEvent {
+    NewVcr(TraceAnnouncement),
+    Response(KzgOpeningResponse),
+    /// The chain reached this slot.
+    Slot(u64),
+}
+
+/// What workers and watchtowers gossip about committed traces.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum WatchtowerMessage {
+    /// A worker's trace commitments for a VCR.
+    Announcement(TraceAnnouncement),
+    /// A tower's challenge, for the worker to answer.
+    Challenge(IssuedChallenge),
+    /// A worker's openings for a challenge.
+    Response(KzgOpeningResponse),
+}
+
+impl WatchtowerMessage {
+    /// The event this message is for a watchtower. Other towers'
+    /// challenges are for workers, so they are none.
+    pub fn into_event(self) -> Option<WatchtowerEvent> {
+        match self {
+            WatchtowerMessage::Announcement(announcement) => {
+                Some(WatchtowerEvent::NewVcr(announcement))
+            }
+            WatchtowerMessage::Challenge(_) => None,
+            WatchtowerMessage::Response(response) => Some(WatchtowerEvent::Response(response)),
+        }
+    }
+}
+
+/// Where a watchtower reads VCRs, responses and slot ticks from.
+pub trait EventSource {
+    /// Next event, blocking; `None` when the feed has closed.
+    fn next_event(&mut self) -> Option<WatchtowerEvent>;
+}
+
+impl EventSource for Receiver<WatchtowerEvent> {
+    fn next_event(&mut self) -> Option<WatchtowerEvent> {
+        self.recv().ok()
+    }
+}
+
+/// Where challenges and fraud reports are submitted.
+pub trait WatchtowerSink {
+    fn submit_challenge(&mut self, challenge: &IssuedChallenge) -> anyhow::Result<()>;
+    fn submit_fraud_report(&mut self, report: &FraudReport) -> anyhow::Result<()>;
+}
+
+struct Pending {
+    announcement: TraceAnnouncement,
+    issued: IssuedChallenge,
+}
+
+pub struct Watchtower {
+    config: WatchtowerConfig,
+    id: [u8; 32],
+    verifier: KzgVerifier,
+    current_slot: u64,
+    pending: HashMap<H256, Pending>,
+}
+
+impl Watchtower {
+    pub fn new(config: WatchtowerConfig, id: [u8; 32], verifier: KzgVerifier) -> Self {
+        Watchtower {
+            config,
+            id,
+            verifier,
+            current_slot: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Process events until the source closes.
+    pub fn run(
+        &mut self,
+        source: &mut dyn EventSource,
+        sink: &mut dyn WatchtowerSink,
+    ) -> anyhow::Result<()> {
+        while let Some(event) = source.next_event() {
+            self.handle(event, sink)?;
+        }
+        Ok(())
+    }
+
+    pub fn handle(
+        &mut self,
+        event: WatchtowerEvent,
+        sink: &mut dyn WatchtowerSink,
+    ) -> anyhow::Result<()> {
+        match event {
+            WatchtowerEvent::NewVcr(announcement) => self.observe(announcement, sink),
+            WatchtowerEvent::Response(response) => self.on_response(response, sink),
+            WatchtowerEvent::Slot(slot) => self.advance_to(slot, sink),
+        }
+    }
+
+    /// Challenge a newly committed trace, if this tower has duty on it.
+    /// Malformed announcements are rejected without a challenge.
+    pub fn observe(
+        &mut self,
+        announcement: TraceAnnouncement,
+        sink: &mut dyn WatchtowerSink,
+    ) -> anyhow::Result<()> {
+        if self.pending.contains_key(&announcement.vcr_id) {
+            return Ok(());
+        }
+        if let Some(duty) = &self.config.duty {
+            if duty.seats(&announcement, &self.id) == 0 {
+                return Ok(());
+            }
+        }
+        let deadline = self.current_slot + self.config.response_slots;
+        let issued = IssuedChallenge {
+            challenge: derive_challenge(
+                &announcement,
+                &announcement.randomness,
+                self.config.sample_layers,
+                self.config.sample_points,
+                deadline,
+            )?,
+            watchtower: self.id,
+        };
+        sink.submit_challenge(&issued)?;
+        self.pending.insert(
+            announcement.vcr_id,
+            Pending {
+                announcement,
+                issued,
+            },
+        );
+        Ok(())
+    }
+
+    /// Check a worker's answer; responses to unknown challenges are ignored.
+    pub fn on_response(
+        &mut self,
+        response: KzgOpeningResponse,
+        sink: &mut dyn WatchtowerSink,
+    ) -> anyhow::Result<()> {
+        let Some(pending) = self.pending.remove(&response.vcr_id) else {
+            return Ok(());
+        };
+        if let Err(err) = self.check_response(&pending, &response) {
+            sink.submit_fraud_report(&FraudReport {
+                vcr_id: response.vcr_id,
+                worker_id: pending.announcement.worker_id,
+                challenge: pending.issued,
+                response: Some(response),
+                reason: FraudReason::InvalidOpenings(format!("{err:#}")),
+                slot: self.current_slot,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Report every challenge whose deadline has passed unanswered.
+    pub fn advance_to(&mut self, slot: u64, sink: &mut dyn WatchtowerSink) -> anyhow::Result<()> {
+        self.current_slot = self.current_slot.max(slot);
+        let mut expired: Vec<H256> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| p.issued.challenge.deadline_slot < self.current_slot)
+            .map(|(id, _)| *id)
+            .collect();
+        expired.sort_by_key(|id| id.0);
+        for vcr_id in expired {
+            let pending = self.pending.remove(&vcr_id).expect("collected above");
+            sink.submit_fraud_report(&FraudReport {
+                vcr_id,
+                worker_id: pending.announcement.worker_id,
+                challenge: pending.issued,
+                response: None,
+                reason: FraudReason::NoResponse,
+                slot: self.current_slot,
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn check_response(
+        &self,
+        pending: &Pending,
+        response: &KzgOpeningResponse,
+    ) -> anyhow::Result<()> {
+        if self.current_slot > pending.issued.challenge.deadline_slot {
+            bail!(
+                "response arrived after slot {}",
+                pending.issued.challenge.deadline_slot
+            );
+        }
+        for opening in &response.openings {
+            let Some(layer) = pending
+                .announcement
+                .layers
+                .iter()
+                .find(|layer| layer.layer_idx == opening.layer_idx)
+            else {
+                bail!("opening for uncommitted layer {}", opening.layer_idx);
+            };
+            ensure!(
+                opening.commitment == layer.commitment,
+                "layer {} opened against a different commitment",
+                opening.layer_idx
+            );
+            ensure!(
+                opening.point == scalar_from_i64(opening.point_idx as i64),
+                "layer {} point {} opened at the wrong x",
+                opening.layer_idx,
+                opening.point_idx
+            );
+        }
+        verify_kzg_openings(&self.verifier, &pending.issued.challenge, response)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -88,4 +458,222 @@ mod tests {
         let result = build_challenge(H256::zero(), 8, 16, 3, 0, Some(1));
         assert!(result.is_err());
     }
+
+    use crate::opening::Opening;
+    use aether_crypto_kzg::{interpolate, ScalarBytes};
+    use std::sync::mpsc::channel;
+
+    /// A worker's trace: per-layer activations and their interpolations.
+    struct Trace {
+        kzg: KzgVerifier,
+        layers: Vec<(u32, Vec<ScalarBytes>)>,
+    }
+
+    impl Trace {
+        fn new(layers: &[(u32, &[i64])]) -> Self {
+            let layers = layers
+                .iter()
+                .map(|(idx, values)| {
+                    let evals: Vec<ScalarBytes> =
+                        values.iter().map(|&v| scalar_from_i64(v)).collect();
+                    (*idx, interpolate(&evals).unwrap())
+                })
+                .collect();
+            Trace {
+                kzg: KzgVerifier::new_insecure_test(16),
+                layers,
+            }
+        }
+
+        fn announce(&self, vcr: u8) -> TraceAnnouncement {
+            TraceAnnouncement {
+                vcr_id: H256::from_slice(&[vcr; 32]).unwrap(),
+                worker_id: vec![9u8; 32],
+                layers: self
+                    .layers
+                    .iter()
+                    .map(|(idx, coeffs)| CommittedLayer {
+                        layer_idx: *idx,
+                        len: coeffs.len() as u32,
+                        commitment: self.kzg.commit(coeffs).unwrap(),
+                    })
+                    .collect(),
+                randomness: H256::from_slice(&[0x5a; 32]).unwrap(),
+            }
+        }
+
+        fn respond(&self, challenge: &KzgChallenge) -> KzgOpeningResponse {
+            let openings = challenge
+                .iter_points()
+                .map(|(layer_idx, point_idx)| {
+                    let (_, coeffs) = self.layers.iter().find(|(i, _)| *i == layer_idx).unwrap();
+                    let z = scalar_from_i64(point_idx as i64);
+                    Opening {
+                        layer_idx,
+                        point_idx,
+                        point: z.to_vec(),
+                        commitment: self.kzg.commit(coeffs).unwrap(),
+                        proof: self.kzg.create_proof(coeffs, &z).unwrap(),
+                    }
+                })
+                .collect();
+            KzgOpeningResponse::new(challenge.vcr_id, openings)
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder {
+        challenges: Vec<IssuedChallenge>,
+        reports: Vec<FraudReport>,
+    }
+
+    impl WatchtowerSink for Recorder {
+        fn submit_challenge(&mut self, challenge: &IssuedChallenge) -> anyhow::Result<()> {
+            self.challenges.push(challenge.clone());
+            Ok(())
+        }
+
+        fn submit_fraud_report(&mut self, report: &FraudReport) -> anyhow::Result<()> {
+            self.reports.push(report.clone());
+            Ok(())
+        }
+    }
+
+    fn trace() -> Trace {
+        Trace::new(&[
+            (0, &[1, 2, 3, 4, 5, 6]),
+            (3, &[-7, 8, 0, 2]),
+            (5, &[11, 12, 13, 14, 15]),
+        ])
+    }
+
+    fn watchtower() -> Watchtower {
+        Watchtower::new(
+            WatchtowerConfig::default(),
+            [4u8; 32],
+            KzgVerifier::new_insecure_test(16),
+        )
+    }
+
+    #[test]
+    fn honest_worker_is_not_reported() {
+        let trace = trace();
+        let mut tower = watchtower();
+        let mut sink = Recorder::default();
+        let (tx, mut rx) = channel();
+
+        tx.send(WatchtowerEvent::NewVcr(trace.announce(1))).unwrap();
+        drop(tx);
+        tower.run(&mut rx, &mut sink).unwrap();
+        let issued = sink.challenges[0].clone();
+        assert_eq!(issued.challenge.layer_indices.len(), 2);
+        assert_eq!(issued.challenge.deadline_slot, 5);
+
+        tower
+            .handle(
+                WatchtowerEvent::Response(trace.respond(&issued.challenge)),
+                &mut sink,
+            )
+            .unwrap();
+        tower.handle(WatchtowerEvent::Slot(100), &mut sink).unwrap();
+        assert!(sink.reports.is_empty());
+        assert_eq!(tower.pending_len(), 0);
+    }
+
+    #[test]
+    fn reports_wrong_values_and_missing_responses() {
+        let (trace, forged) = (
+            trace(),
+            Trace::new(&[(0, &[0; 6]), (3, &[0; 4]), (5, &[0; 5])]),
+        );
+        let mut tower = watchtower();
+        let mut sink = Recorder::default();
+
+        // Openings of a different trace do not match the commitments.
+        tower.observe(trace.announce(1), &mut sink).unwrap();
+        let response = forged.respond(&sink.challenges[0].challenge);
+        tower.on_response(response, &mut sink).unwrap();
+        assert!(matches!(
+            &sink.reports[0].reason,
+            FraudReason::InvalidOpenings(msg) if msg.contains("different commitment")
+        ));
+
+        // Silence past the deadline.
+        tower.observe(trace.announce(2), &mut sink).unwrap();
+        tower.advance_to(5, &mut sink).unwrap();
+        assert_eq!(sink.reports.len(), 1);
+        tower.advance_to(6, &mut sink).unwrap();
+        assert_eq!(sink.reports[1].reason, FraudReason::NoResponse);
+        assert_eq!(sink.reports[1].vcr_id, H256::from_slice(&[2; 32]).unwrap());
+    }
+
+    #[test]
+    fn duty_follows_stake_weighted_sortition() {
+        let trace = trace();
+        let duty = WatchtowerDuty {
+            stake: 250,
+            total_stake: 1000,
+            tau: Tau::seats(1),
+        };
+        let mut tower = Watchtower::new(
+            WatchtowerConfig {
+                duty: Some(duty),
+                ..WatchtowerConfig::default()
+            },
+            [4u8; 32],
+            KzgVerifier::new_insecure_test(16),
+        );
+        let mut sink = Recorder::default();
+
+        let mut on_duty = 0;
+        for vcr in 0..64 {
+            let announcement = trace.announce(vcr);
+            on_duty += duty.seats(&announcement, &[4u8; 32]).min(1);
+            tower.observe(announcement, &mut sink).unwrap();
+        }
+        // A quarter of the stake watches roughly a quarter of the traces.
+        assert_eq!(sink.challenges.len() as u64, on_duty);
+        assert!((4..=32).contains(&on_duty), "{on_duty} of 64");
+
+        // Another tower draws different traces.
+        let others: Vec<u64> = (0..64)
+            .map(|vcr| duty.seats(&trace.announce(vcr), &[5u8; 32]))
+            .collect();
+        let mine: Vec<u64> = (0..64)
+            .map(|vcr| duty.seats(&trace.announce(vcr), &[4u8; 32]))
+            .collect();
+        assert_ne!(mine, others);
+    }
+
+    #[test]
+    fn sampling_is_verifiable() {
+        let trace = trace();
+        let announcement = trace.announce(1);
+        let mut tower = watchtower();
+        let mut sink = Recorder::default();
+        tower.observe(announcement.clone(), &mut sink).unwrap();
+        let issued = sink.challenges.pop().unwrap();
+        let config = WatchtowerConfig::default();
+        issued.verify_sampling(&announcement, &config).unwrap();
+
+        let mut easy = issued.clone();
+        easy.challenge.point_indices[0] = vec![0];
+        assert!(easy.verify_sampling(&announcement, &config).is_err());
+        assert!(issued.verify_sampling(&trace.announce(2), &config).is_err());
+
+        // The worker derives the same points on its own; other block
+        // randomness gives other points.
+        let derived = derive_challenge(
+            &announcement,
+            &announcement.randomness,
+            config.sample_layers,
+            config.sample_points,
+            issued.challenge.deadline_slot,
+        )
+        .unwrap();
+        assert_eq!(derived, issued.challenge);
+        let mut reseeded = announcement.clone();
+        reseeded.randomness = H256::from_slice(&[0xa5; 32]).unwrap();
+        assert!(issued.verify_sampling(&reseeded, &config).is_err());
+    }
 }
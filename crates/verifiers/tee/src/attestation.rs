@@ -1,5 +1,13 @@
-use anyhow::{bail, Result};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
+
+use crate::collateral::{self, Collateral, CollateralPolicy};
+#[cfg(feature = "nitro")]
+use crate::nitro;
+use crate::pcr::{MeasurementRegistry, MeasurementSetId};
+use crate::simulated;
+use crate::snp;
 
 /// TEE Attestation Verification
 ///
@@ -26,7 +34,7 @@ pub enum TeeType {
     SevSnp,     // AMD SEV-SNP
     IntelTdx,   // Intel TDX
     AwsNitro,   // AWS Nitro Enclaves
-    Simulation, // For testing only
+    Simulation, // Devnet-signed quotes, dev-mode chains only
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +45,50 @@ pub struct AttestationReport {
     pub timestamp: u64,           // Unix timestamp
     pub signature: Vec<u8>,       // TEE signature
     pub cert_chain: Vec<Vec<u8>>, // Certificate chain
+    /// Raw hardware report the fields above were taken from; for SEV-SNP
+    /// the signed ATTESTATION_REPORT, checked against `cert_chain`.
+    #[serde(default)]
+    pub quote: Vec<u8>,
+}
+
+/// Size of the user-supplied `report_data` field in SEV-SNP and TDX reports.
+pub const REPORT_DATA_LEN: usize = 64;
+
+/// Job values a worker binds into its quote's `report_data`.
+///
+/// Binding them means a quote cannot be replayed for a different job, input,
+/// model or worker build, and cannot predate the slot whose hash it carries
+/// (see `freshness`). Validators recompute the digest from the VCR and
+/// compare it with the report's nonce.
+#[derive(Debug, Clone, Copy)]
+pub struct ReportDataBinding<'a> {
+    pub job_id: &'a [u8],
+    pub input_hash: &'a [u8],
+    pub model_hash: &'a [u8],
+    pub code_hash: &'a [u8],
+    pub slot_hash: &'a [u8],
+    pub seed: u64,
+}
+
+impl ReportDataBinding<'_> {
+    /// `SHA-512(domain || len-prefixed fields || seed)`, sized to fill the
+    /// hardware `report_data` field exactly.
+    pub fn report_data(&self) -> [u8; REPORT_DATA_LEN] {
+        let mut hasher = Sha512::new();
+        hasher.update(b"AETHER-TEE-REPORT-DATA-v2");
+        for field in [
+            self.job_id,
+            self.input_hash,
+            self.model_hash,
+            self.code_hash,
+            self.slot_hash,
+        ] {
+            hasher.update((field.len() as u64).to_le_bytes());
+            hasher.update(field);
+        }
+        hasher.update(self.seed.to_le_bytes());
+        hasher.finalize().into()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -49,6 +101,16 @@ pub struct TeeVerifier {
 
     /// Root certificates for each TEE type
     root_certs: std::collections::HashMap<TeeType, Vec<u8>>,
+
+    /// Latest revocation/TCB collateral for each TEE type
+    collateral: std::collections::HashMap<TeeType, Collateral>,
+
+    /// Governance grace periods for stale collateral
+    collateral_policy: CollateralPolicy,
+
+    /// Key simulated quotes must be signed with; `None` unless the chain
+    /// runs in dev mode
+    simulation_key: Option<Vec<u8>>,
 }
 
 impl TeeVerifier {
@@ -57,6 +119,9 @@ impl TeeVerifier {
             approved_measurements: Vec::new(),
             max_age_secs: 60, // 1 minute
             root_certs: std::collections::HashMap::new(),
+            collateral: std::collections::HashMap::new(),
+            collateral_policy: CollateralPolicy::default(),
+            simulation_key: None,
         }
     }
 
@@ -67,14 +132,72 @@ impl TeeVerifier {
         }
     }
 
-    /// Set root certificate for TEE type
+    /// Pin the root certificate for a TEE type. For SEV-SNP this overrides
+    /// the ARKs shipped in `snp::AMD_ARKS`, whatever the product; for Nitro
+    /// it overrides `AWS_NITRO_ROOT_SHA256`.
     pub fn set_root_cert(&mut self, tee_type: TeeType, cert: Vec<u8>) {
         self.root_certs.insert(tee_type, cert);
     }
 
+    /// Install collateral (e.g. from a `CollateralCache`); hardware quotes
+    /// are rejected until collateral for their TEE type is present
+    pub fn set_collateral(&mut self, collateral: Collateral) {
+        self.collateral
+            .insert(collateral.tee_type.clone(), collateral);
+    }
+
+    /// Set how old (in seconds) a report's timestamp may be
+    pub fn set_max_age_secs(&mut self, max_age_secs: u64) {
+        self.max_age_secs = max_age_secs;
+    }
+
+    /// Set the grace periods governance voted for
+    pub fn set_collateral_policy(&mut self, policy: CollateralPolicy) {
+        self.collateral_policy = policy;
+    }
+
+    /// Accept simulated quotes signed with the devnet key. Only for chains
+    /// configured in dev mode: anyone can produce these quotes.
+    pub fn enable_dev_mode(&mut self) {
+        self.simulation_key = Some(simulated::devnet_public_key());
+    }
+
+    /// Whether simulated quotes are accepted.
+    pub fn dev_mode(&self) -> bool {
+        self.simulation_key.is_some()
+    }
+
     /// Verify attestation report
     pub fn verify(&self, report: &AttestationReport, current_time: u64) -> Result<()> {
-        // 1. Check freshness — reject future-dated reports
+        // 1. Check freshness
+        self.check_freshness(report, current_time)?;
+
+        // 2. Check measurement is approved
+        if !self.approved_measurements.contains(&report.measurement) {
+            bail!("measurement not approved");
+        }
+
+        self.verify_hardware(report, current_time)
+    }
+
+    /// Like `verify`, but the measurement must belong to a set governance
+    /// has approved in `registry` as of `slot` instead of the local
+    /// whitelist. Returns the id of that set.
+    pub fn verify_registered(
+        &self,
+        report: &AttestationReport,
+        registry: &MeasurementRegistry,
+        slot: u64,
+        current_time: u64,
+    ) -> Result<MeasurementSetId> {
+        self.check_freshness(report, current_time)?;
+        let set_id = registry.check_report(report, slot)?;
+        self.verify_hardware(report, current_time)?;
+        Ok(set_id)
+    }
+
+    /// Reject future-dated reports and reports older than `max_age_secs`.
+    fn check_freshness(&self, report: &AttestationReport, current_time: u64) -> Result<()> {
         if report.timestamp > current_time {
             bail!(
                 "attestation timestamp {} is in the future (current: {})",
@@ -89,15 +212,15 @@ impl TeeVerifier {
                 self.max_age_secs
             );
         }
+        Ok(())
+    }
 
-        // 2. Check measurement is approved
-        if !self.approved_measurements.contains(&report.measurement) {
-            bail!("measurement not approved");
-        }
-
+    /// Signature chain and TEE-specific checks.
+    fn verify_hardware(&self, report: &AttestationReport, current_time: u64) -> Result<()> {
         // 3. Verify signature chain
-        if report.tee_type != TeeType::Simulation {
-            self.verify_signature_chain(report)?;
+        match report.tee_type {
+            TeeType::Simulation => self.verify_simulated_quote(report)?,
+            _ => self.verify_signature_chain(report, current_time)?,
         }
 
         // 4. TEE-specific verification
@@ -106,23 +229,42 @@ impl TeeVerifier {
             TeeType::IntelTdx => self.verify_intel_tdx(report)?,
             TeeType::AwsNitro => self.verify_aws_nitro(report)?,
             TeeType::Simulation => {
-                // Skip verification for testing
-                #[cfg(debug_assertions)]
-                eprintln!("WARN: Using simulation TEE (testing only)");
+                tracing::warn!("accepted a simulated TEE quote (dev mode only)");
             }
         }
 
         Ok(())
     }
 
-    fn verify_signature_chain(&self, report: &AttestationReport) -> Result<()> {
-        let _root_cert = self
-            .root_certs
-            .get(&report.tee_type)
-            .ok_or_else(|| anyhow::anyhow!("no root cert for TEE type {:?}", report.tee_type))?;
+    fn verify_signature_chain(&self, report: &AttestationReport, current_time: u64) -> Result<()> {
+        let pinned = self.root_certs.get(&report.tee_type);
+
+        // Nitro documents carry their own certificate bundle.
+        #[cfg(feature = "nitro")]
+        if report.tee_type == TeeType::AwsNitro {
+            let root = pinned.map_or(nitro::AWS_NITRO_ROOT_SHA256, |root| {
+                nitro::root_fingerprint(root)
+            });
+            return self.verify_nitro_quote(report, &root, current_time);
+        }
 
-        if report.cert_chain.is_empty() {
+        let Some(chain_root) = report.cert_chain.last() else {
             bail!("empty certificate chain");
+        };
+        if report.tee_type == TeeType::SevSnp {
+            let ark = match pinned {
+                Some(ark) => ark.as_slice(),
+                None => {
+                    let product = snp::AmdProduct::of_ark(chain_root)?;
+                    product.shipped_ark().with_context(|| {
+                        format!("no ARK shipped or pinned for AMD {}", product.name())
+                    })?
+                }
+            };
+            return self.verify_sev_snp_quote(report, ark, current_time);
+        }
+        if pinned.is_none() {
+            bail!("no root cert for TEE type {:?}", report.tee_type);
         }
         if report.signature.is_empty() {
             bail!("attestation report has empty signature");
@@ -133,12 +275,81 @@ impl TeeVerifier {
         );
     }
 
-    fn verify_sev_snp(&self, report: &AttestationReport) -> Result<()> {
-        // AMD SEV-SNP specific verification
-        // - Check VCEK certificate
-        // - Verify measurement includes firmware version
-        // - Check policy (debug, migration flags)
+    /// Simulated quotes carry the devnet-signed quote in `signature`; like
+    /// hardware quotes, the unsigned fields must repeat the signed ones.
+    fn verify_simulated_quote(&self, report: &AttestationReport) -> Result<()> {
+        let Some(key) = &self.simulation_key else {
+            bail!("simulated TEE quotes are only accepted on dev-mode chains");
+        };
+        let quote = simulated::verify_quote(&report.signature, key)?;
+        if report.measurement.as_slice() != quote.measurement.as_slice() {
+            bail!("measurement does not match the signed simulated quote");
+        }
+        if report.nonce.as_slice() != quote.report_data.as_slice() {
+            bail!("nonce does not match the signed simulated quote report_data");
+        }
+        if report.timestamp != quote.timestamp {
+            bail!("timestamp does not match the signed simulated quote");
+        }
+        Ok(())
+    }
+
+    /// The SEV-SNP report in `quote` is signed by the VCEK, so `signature`
+    /// is unused. The unsigned `measurement` and `nonce` must repeat what
+    /// the hardware signed, since later checks read them.
+    fn verify_sev_snp_quote(
+        &self,
+        report: &AttestationReport,
+        ark: &[u8],
+        current_time: u64,
+    ) -> Result<()> {
+        let snp = snp::verify_report(&report.quote, &report.cert_chain, ark, None, current_time)?;
+        if report.measurement.as_slice() != snp.measurement.as_slice() {
+            bail!("measurement does not match the signed SEV-SNP report");
+        }
+        if report.nonce.as_slice() != snp.report_data.as_slice() {
+            bail!("nonce does not match the signed SEV-SNP report_data");
+        }
+        let collateral = self
+            .collateral
+            .get(&TeeType::SevSnp)
+            .ok_or_else(|| anyhow::anyhow!("no revocation collateral for SevSnp"))?;
+        collateral::check_snp(
+            collateral,
+            &self.collateral_policy,
+            &report.cert_chain,
+            &snp.reported_tcb,
+            current_time,
+        )
+    }
+
+    /// For Nitro `signature` carries the raw attestation document. PCR0 and
+    /// `user_data` must match the unsigned fields, and the document's own
+    /// timestamp must be as fresh as the report claims to be.
+    #[cfg(feature = "nitro")]
+    fn verify_nitro_quote(
+        &self,
+        report: &AttestationReport,
+        root: &[u8; 32],
+        current_time: u64,
+    ) -> Result<()> {
+        let document = nitro::verify_document(&report.signature, root, current_time)?;
+        if document.pcrs.get(&0) != Some(&report.measurement) {
+            bail!("measurement does not match the signed Nitro PCR0");
+        }
+        if document.user_data.as_deref() != Some(report.nonce.as_slice()) {
+            bail!("nonce does not match the signed Nitro user_data");
+        }
+        let issued = document.timestamp_ms / 1000;
+        if issued > current_time || current_time - issued > self.max_age_secs {
+            bail!("Nitro attestation document issued at {issued} is not fresh");
+        }
+        Ok(())
+    }
 
+    /// The VCEK chain, report signature, TCB and guest policy were checked
+    /// with the quote; this only checks the measurement's shape.
+    fn verify_sev_snp(&self, report: &AttestationReport) -> Result<()> {
         if report.measurement.len() != 48 {
             bail!("invalid SEV-SNP measurement length (expected 48)");
         }
@@ -159,12 +370,10 @@ impl TeeVerifier {
         Ok(())
     }
 
+    /// The document's chain to the pinned AWS root, its signature, PCR0
+    /// and `user_data` were checked with the quote; this only checks the
+    /// measurement's shape.
     fn verify_aws_nitro(&self, report: &AttestationReport) -> Result<()> {
-        // AWS Nitro Enclaves verification
-        // - Verify PCR values
-        // - Check attestation document signature
-        // - Validate certificate chain to AWS root
-
         if report.measurement.len() != 48 {
             bail!("invalid Nitro measurement length (expected 48)");
         }
@@ -173,6 +382,24 @@ impl TeeVerifier {
     }
 }
 
+/// Verify `report` and check that it commits to `expected_report_data`, the
+/// digest the validator recomputed from the job (see `ReportDataBinding`).
+///
+/// A quote that verifies but carries different report data was produced for
+/// another job or build and must not be accepted for this one.
+pub fn verify_tee_quote(
+    verifier: &TeeVerifier,
+    report: &AttestationReport,
+    expected_report_data: &[u8; REPORT_DATA_LEN],
+    current_time: u64,
+) -> Result<()> {
+    verifier.verify(report, current_time)?;
+    if report.nonce.as_slice() != expected_report_data.as_slice() {
+        bail!("attestation report_data does not match the expected job binding");
+    }
+    Ok(())
+}
+
 impl Default for TeeVerifier {
     fn default() -> Self {
         Self::new()
@@ -182,22 +409,26 @@ impl Default for TeeVerifier {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::simulated::SimulatedSigner;
 
     fn create_test_report() -> AttestationReport {
-        AttestationReport {
-            tee_type: TeeType::Simulation,
-            measurement: vec![1u8; 48],
-            nonce: vec![2u8; 32],
-            timestamp: 1000,
-            signature: vec![3u8; 64],
-            cert_chain: vec![vec![4u8; 100]],
-        }
+        let mut report = SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &[2u8; 64], 1000)
+            .unwrap();
+        report.cert_chain = vec![vec![4u8; 100]];
+        report
     }
 
-    #[test]
-    fn test_verify_simulation() {
+    fn dev_verifier() -> TeeVerifier {
         let mut verifier = TeeVerifier::new();
         verifier.add_approved_measurement(vec![1u8; 48]);
+        verifier.enable_dev_mode();
+        verifier
+    }
+
+    #[test]
+    fn test_verify_simulation() {
+        let verifier = dev_verifier();
 
         let report = create_test_report();
 
@@ -205,9 +436,38 @@ mod tests {
     }
 
     #[test]
-    fn test_reject_old_attestation() {
+    fn test_simulation_requires_dev_mode() {
         let mut verifier = TeeVerifier::new();
         verifier.add_approved_measurement(vec![1u8; 48]);
+        assert!(!verifier.dev_mode());
+        let err = verifier.verify(&create_test_report(), 1010).unwrap_err();
+        assert!(err.to_string().contains("dev-mode"), "{err}");
+
+        // In dev mode the quote still has to be signed by the devnet key
+        // and match the report's fields.
+        let verifier = dev_verifier();
+        assert!(verifier.dev_mode());
+        let mut forged = create_test_report();
+        forged.signature = vec![3u8; 64];
+        assert!(verifier.verify(&forged, 1010).is_err());
+
+        let mut foreign = SimulatedSigner::from_seed(&[9u8; 32])
+            .quote(&[1u8; 48], &[2u8; 64], 1000)
+            .unwrap();
+        assert!(verifier.verify(&foreign, 1010).is_err());
+        foreign = create_test_report();
+        foreign.nonce = vec![5u8; 64];
+        assert!(verifier.verify(&foreign, 1010).is_err());
+
+        let mut restamped = create_test_report();
+        restamped.timestamp = 1005;
+        let err = verifier.verify(&restamped, 1010).unwrap_err();
+        assert!(err.to_string().contains("timestamp"), "{err}");
+    }
+
+    #[test]
+    fn test_reject_old_attestation() {
+        let verifier = dev_verifier();
 
         let report = create_test_report();
 
@@ -239,8 +499,7 @@ mod tests {
 
     #[test]
     fn test_future_dated_attestation_rejected() {
-        let mut verifier = TeeVerifier::new();
-        verifier.add_approved_measurement(vec![1u8; 48]);
+        let verifier = dev_verifier();
 
         let mut report = create_test_report();
         let current_time = 1000;
@@ -277,10 +536,10 @@ mod tests {
     fn test_non_simulation_attestation_fails_closed() {
         let mut verifier = TeeVerifier::new();
         verifier.add_approved_measurement(vec![1u8; 48]);
-        verifier.set_root_cert(TeeType::SevSnp, vec![0xAA; 64]);
+        verifier.set_root_cert(TeeType::IntelTdx, vec![0xAA; 64]);
 
         let mut report = create_test_report();
-        report.tee_type = TeeType::SevSnp;
+        report.tee_type = TeeType::IntelTdx;
         report.measurement = vec![1u8; 48];
 
         let err = verifier.verify(&report, 1010).unwrap_err();
@@ -290,19 +549,281 @@ mod tests {
             "expected fail-closed error, got: {msg}"
         );
     }
+
+    fn sev_snp_report() -> (TeeVerifier, AttestationReport) {
+        let quote = include_bytes!("../testdata/snp/report.bin").to_vec();
+        let snp = snp::SnpReport::parse(&quote).unwrap();
+        let mut verifier = TeeVerifier::new();
+        verifier.add_approved_measurement(snp.measurement.to_vec());
+        verifier.set_root_cert(
+            TeeType::SevSnp,
+            include_bytes!("../testdata/snp/ark.der").to_vec(),
+        );
+        verifier.set_collateral(crate::collateral::tests::snp_collateral());
+        let report = AttestationReport {
+            tee_type: TeeType::SevSnp,
+            measurement: snp.measurement.to_vec(),
+            nonce: snp.report_data.to_vec(),
+            timestamp: 1_800_000_000,
+            signature: Vec::new(),
+            cert_chain: vec![
+                include_bytes!("../testdata/snp/vcek.der").to_vec(),
+                include_bytes!("../testdata/snp/ask.der").to_vec(),
+                include_bytes!("../testdata/snp/ark.der").to_vec(),
+            ],
+            quote,
+        };
+        (verifier, report)
+    }
+
+    #[test]
+    fn test_sev_snp_quote_verifies() {
+        let (verifier, report) = sev_snp_report();
+        let expected = ReportDataBinding {
+            job_id: b"job-1",
+            input_hash: &[1u8; 32],
+            model_hash: &[2u8; 32],
+            code_hash: &[3u8; 32],
+            slot_hash: &[4u8; 32],
+            seed: 7,
+        }
+        .report_data();
+        verify_tee_quote(&verifier, &report, &expected, 1_800_000_010).unwrap();
+
+        // The unsigned fields must repeat the signed report.
+        let mut approved = verifier.clone();
+        approved.add_approved_measurement(vec![1u8; 48]);
+        let mut wrong_measurement = report.clone();
+        wrong_measurement.measurement = vec![1u8; 48];
+        let err = approved
+            .verify(&wrong_measurement, 1_800_000_010)
+            .unwrap_err();
+        assert!(err.to_string().contains("signed SEV-SNP report"), "{err}");
+
+        let mut wrong_nonce = report.clone();
+        wrong_nonce.nonce = vec![0u8; 64];
+        assert!(verifier.verify(&wrong_nonce, 1_800_000_010).is_err());
+
+        let mut tampered = report.clone();
+        tampered.quote[0x90] ^= 1;
+        assert!(verifier.verify(&tampered, 1_800_000_010).is_err());
+
+        let mut truncated = report.clone();
+        truncated.quote.truncate(snp::SNP_REPORT_LEN - 1);
+        assert!(verifier.verify(&truncated, 1_800_000_010).is_err());
+
+        // The raw report has its own field; one left in `signature` is not read.
+        let mut misplaced = report.clone();
+        misplaced.signature = std::mem::take(&mut misplaced.quote);
+        assert!(verifier.verify(&misplaced, 1_800_000_010).is_err());
+
+        // Revoked VCEKs fail, and so does a verifier with no collateral.
+        let mut revoked = verifier.clone();
+        let mut collateral = crate::collateral::tests::snp_collateral();
+        collateral.crls[1] = include_bytes!("../testdata/snp/ask_crl_revoked.der").to_vec();
+        revoked.set_collateral(collateral);
+        let err = revoked.verify(&report, 1_800_000_010).unwrap_err();
+        assert!(err.to_string().contains("revoked"), "{err}");
+        let mut bare = verifier;
+        bare.collateral.clear();
+        let err = bare.verify(&report, 1_800_000_010).unwrap_err();
+        assert!(err.to_string().contains("collateral"), "{err}");
+    }
+
+    #[test]
+    fn test_sev_snp_defaults_to_the_shipped_ark() {
+        // Without a pin the chain has to end in the ARK shipped for its
+        // product, which the test ARK (named ARK-Milan) is not.
+        let (mut verifier, report) = sev_snp_report();
+        verifier.root_certs.clear();
+        let err = verifier.verify(&report, 1_800_000_010).unwrap_err();
+        assert!(err.to_string().contains("pinned"), "{err}");
+
+        let mut unnamed = report;
+        let ask = unnamed.cert_chain[1].clone();
+        unnamed.cert_chain[2] = ask;
+        let err = verifier.verify(&unnamed, 1_800_000_010).unwrap_err();
+        assert!(err.to_string().contains("unknown AMD product"), "{err}");
+    }
+
+    #[cfg(feature = "nitro")]
+    #[test]
+    fn test_nitro_document_verifies() {
+        let document = include_bytes!("../testdata/nitro/document.cbor").to_vec();
+        let mut verifier = TeeVerifier::new();
+        verifier.add_approved_measurement(vec![0xA0; 48]);
+        verifier.set_root_cert(
+            TeeType::AwsNitro,
+            include_bytes!("../testdata/nitro/root.der").to_vec(),
+        );
+        let expected = ReportDataBinding {
+            job_id: b"job-1",
+            input_hash: &[1u8; 32],
+            model_hash: &[2u8; 32],
+            code_hash: &[3u8; 32],
+            slot_hash: &[4u8; 32],
+            seed: 7,
+        }
+        .report_data();
+        // What the worker's Nitro backend sends: no separate chain.
+        let report = AttestationReport {
+            tee_type: TeeType::AwsNitro,
+            measurement: vec![0xA0; 48],
+            nonce: expected.to_vec(),
+            timestamp: 1_800_000_000,
+            signature: document,
+            cert_chain: Vec::new(),
+            quote: Vec::new(),
+        };
+        verify_tee_quote(&verifier, &report, &expected, 1_800_000_000).unwrap();
+
+        let mut wrong_pcr = report.clone();
+        wrong_pcr.measurement = vec![0xA1; 48];
+        verifier.add_approved_measurement(vec![0xA1; 48]);
+        let err = verifier.verify(&wrong_pcr, 1_800_000_000).unwrap_err();
+        assert!(err.to_string().contains("PCR0"), "{err}");
+
+        let mut wrong_nonce = report.clone();
+        wrong_nonce.nonce = vec![0u8; 64];
+        assert!(verifier.verify(&wrong_nonce, 1_800_000_000).is_err());
+
+        // The report timestamp cannot outrun the document's signed one.
+        let mut restamped = report.clone();
+        restamped.timestamp = 1_800_000_100;
+        let err = verifier.verify(&restamped, 1_800_000_100).unwrap_err();
+        assert!(err.to_string().contains("not fresh"), "{err}");
+
+        // Unpinned, the document must chain to the real AWS root.
+        verifier.root_certs.clear();
+        let err = verifier.verify(&report, 1_800_000_000).unwrap_err();
+        assert!(err.to_string().contains("pinned AWS Nitro root"), "{err}");
+    }
+
+    #[test]
+    fn test_registry_approves_measurements() {
+        use crate::pcr::{MeasurementSet, ValidityWindow};
+
+        let (mut verifier, report) = sev_snp_report();
+        verifier.approved_measurements.clear();
+        let mut registry = MeasurementRegistry::new();
+        let id = registry.stage(MeasurementSet::new(
+            TeeType::SevSnp,
+            report.measurement.clone(),
+        ));
+        let now = 1_800_000_010;
+        assert!(verifier
+            .verify_registered(&report, &registry, 50, now)
+            .is_err());
+
+        let (parameter, value) = MeasurementRegistry::approve_parameter(
+            &id,
+            ValidityWindow {
+                from_slot: 0,
+                until_slot: Some(100),
+            },
+        );
+        registry
+            .apply_parameter_change(&parameter, value, 10)
+            .unwrap();
+        assert_eq!(
+            verifier
+                .verify_registered(&report, &registry, 50, now)
+                .unwrap(),
+            id
+        );
+        assert!(verifier
+            .verify_registered(&report, &registry, 100, now)
+            .is_err());
+        // Registry approval does not skip the hardware checks.
+        let mut tampered = report.clone();
+        tampered.quote[0x50] ^= 1;
+        assert!(verifier
+            .verify_registered(&tampered, &registry, 50, now)
+            .is_err());
+    }
+
+    #[test]
+    fn quote_must_carry_expected_report_data() {
+        let verifier = dev_verifier();
+        let expected = ReportDataBinding {
+            job_id: b"job",
+            input_hash: &[1u8; 32],
+            model_hash: &[2u8; 32],
+            code_hash: &[3u8; 32],
+            slot_hash: &[4u8; 32],
+            seed: 7,
+        }
+        .report_data();
+
+        let signer = SimulatedSigner::devnet();
+        let report = signer.quote(&[1u8; 48], &expected, 1000).unwrap();
+        assert!(verify_tee_quote(&verifier, &report, &expected, 1010).is_ok());
+
+        let mut other = expected;
+        other[0] ^= 1;
+        let other = signer.quote(&[1u8; 48], &other, 1000).unwrap();
+        let err = verify_tee_quote(&verifier, &other, &expected, 1010).unwrap_err();
+        assert!(err.to_string().contains("report_data"), "{err}");
+
+        // The binding is only checked on an otherwise valid report.
+        assert!(verify_tee_quote(&verifier, &report, &expected, 5000).is_err());
+    }
+
+    #[test]
+    fn report_data_binds_every_field() {
+        let base = ReportDataBinding {
+            job_id: b"job",
+            input_hash: &[1u8; 32],
+            model_hash: &[2u8; 32],
+            code_hash: &[3u8; 32],
+            slot_hash: &[4u8; 32],
+            seed: 7,
+        };
+        let expected = base.report_data();
+        assert_eq!(expected, base.report_data());
+
+        let variants = [
+            ReportDataBinding {
+                job_id: b"jox",
+                ..base
+            },
+            ReportDataBinding {
+                input_hash: &[9u8; 32],
+                ..base
+            },
+            ReportDataBinding {
+                model_hash: &[9u8; 32],
+                ..base
+            },
+            ReportDataBinding {
+                code_hash: &[9u8; 32],
+                ..base
+            },
+            ReportDataBinding {
+                slot_hash: &[9u8; 32],
+                ..base
+            },
+            ReportDataBinding { seed: 8, ..base },
+        ];
+        for variant in variants {
+            assert_ne!(variant.report_data(), expected);
+        }
+    }
 }
 
 #[cfg(test)]
 mod proptests {
     use super::*;
+    use crate::simulated::SimulatedSigner;
     use proptest::prelude::*;
 
     fn arb_measurement() -> impl Strategy<Value = Vec<u8>> {
         prop::collection::vec(any::<u8>(), 48)
     }
 
-    fn arb_nonce() -> impl Strategy<Value = Vec<u8>> {
-        prop::collection::vec(any::<u8>(), 32)
+    fn arb_nonce() -> impl Strategy<Value = [u8; REPORT_DATA_LEN]> {
+        prop::collection::vec(any::<u8>(), REPORT_DATA_LEN)
+            .prop_map(|nonce| nonce.try_into().unwrap())
     }
 
     proptest! {
@@ -317,15 +838,11 @@ mod proptests {
             let current_time = ts + age;
             let mut verifier = TeeVerifier::new();
             verifier.add_approved_measurement(measurement.clone());
+            verifier.enable_dev_mode();
 
-            let report = AttestationReport {
-                tee_type: TeeType::Simulation,
-                measurement: measurement.clone(),
-                nonce,
-                timestamp: ts,
-                signature: vec![1u8; 64],
-                cert_chain: vec![vec![0u8; 32]],
-            };
+            let report = SimulatedSigner::devnet()
+                .quote(&measurement, &nonce, ts)
+                .unwrap();
 
             prop_assert!(
                 verifier.verify(&report, current_time).is_ok(),
@@ -351,6 +868,7 @@ mod proptests {
                 timestamp: ts,
                 signature: vec![1u8; 64],
                 cert_chain: vec![],
+                quote: Vec::new(),
             };
 
             prop_assert!(
@@ -379,6 +897,7 @@ mod proptests {
                 timestamp: ts,
                 signature: vec![1u8; 64],
                 cert_chain: vec![],
+                quote: Vec::new(),
             };
 
             prop_assert!(
@@ -404,6 +923,7 @@ mod proptests {
                 timestamp: current_time + future_offset,
                 signature: vec![1u8; 64],
                 cert_chain: vec![],
+                quote: Vec::new(),
             };
 
             prop_assert!(
@@ -427,6 +947,7 @@ mod proptests {
                 timestamp: ts,
                 signature: vec![1u8; 64],
                 cert_chain: vec![vec![0u8; 32]],
+                quote: Vec::new(),
             };
 
             prop_assert!(
@@ -475,6 +996,7 @@ mod proptests {
                 timestamp: ts,
                 signature: vec![1u8; 64],
                 cert_chain: vec![],
+                quote: Vec::new(),
             };
 
             prop_assert!(
@@ -490,15 +1012,11 @@ mod proptests {
             let max_age = 60u64;
             let mut verifier = TeeVerifier::new();
             verifier.add_approved_measurement(measurement.clone());
+            verifier.enable_dev_mode();
 
-            let report = AttestationReport {
-                tee_type: TeeType::Simulation,
-                measurement,
-                nonce: vec![0u8; 32],
-                timestamp: ts,
-                signature: vec![1u8; 64],
-                cert_chain: vec![],
-            };
+            let report = SimulatedSigner::devnet()
+                .quote(&measurement, &[0u8; REPORT_DATA_LEN], ts)
+                .unwrap();
 
             // current_time - timestamp == max_age_secs → condition is `> max_age` → not triggered
             prop_assert!(
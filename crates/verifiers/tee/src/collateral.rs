@@ -0,0 +1,440 @@
+// ============================================================================
+// TEE COLLATERAL - Revocation lists and TCB status
+// ============================================================================
+// A quote that chains to the vendor root can still be unacceptable: the
+// vendor may have revoked the signing key, or the firmware TCB it reports
+// may have known vulnerabilities. Collateral is what the vendor publishes
+// about both:
+//
+//   crls           DER CRLs; for SEV-SNP the ARK's (revoked ASKs) and the
+//                  ASK's (revoked VCEKs), each signed by its issuer
+//   tcb_minimum    lowest TCB the vendor still supports, in force since
+//                  tcb_effective_at
+//
+// FETCHING:
+// - `CollateralSource` fetches collateral; online sources (AMD KDS) live in
+//   the node, `BundleSource` serves pre-fetched bundles for offline hosts
+// - `CollateralCache` refetches once an entry is `refresh_secs` old and keeps
+//   serving the old copy while the source is unreachable
+//
+// GRACE PERIODS (governance, `CollateralPolicy`):
+// - crl_grace_secs: how long past its nextUpdate a CRL is still trusted
+// - tcb_grace_secs: how long after a TCB minimum takes effect quotes below it
+//   are still accepted, so operators have time to patch
+// ============================================================================
+
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
+use x509_parser::revocation_list::CertificateRevocationList;
+
+use crate::attestation::TeeType;
+use crate::snp::{self, TcbVersion};
+
+/// Prefix of the governance parameters `CollateralPolicy` handles.
+pub const COLLATERAL_PARAM_PREFIX: &str = "tee.collateral.";
+
+/// Vendor revocation and TCB status for one TEE type.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Collateral {
+    pub tee_type: TeeType,
+    /// DER CRLs, each signed by the certificate whose issuances it covers.
+    pub crls: Vec<Vec<u8>>,
+    pub tcb_minimum: TcbVersion,
+    /// Unix time `tcb_minimum` took effect.
+    pub tcb_effective_at: u64,
+    /// Unix time the collateral was fetched.
+    pub fetched_at: u64,
+}
+
+/// Grace periods set by governance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollateralPolicy {
+    pub crl_grace_secs: u64,
+    pub tcb_grace_secs: u64,
+}
+
+impl Default for CollateralPolicy {
+    fn default() -> Self {
+        CollateralPolicy {
+            crl_grace_secs: 24 * 3600,      // 1 day
+            tcb_grace_secs: 30 * 24 * 3600, // 30 days
+        }
+    }
+}
+
+impl CollateralPolicy {
+    /// Apply an executed ParameterChange. Returns `Ok(false)` for parameters
+    /// outside `COLLATERAL_PARAM_PREFIX`.
+    pub fn apply_parameter_change(&mut self, parameter: &str, value: u128) -> Result<bool> {
+        let Some(name) = parameter.strip_prefix(COLLATERAL_PARAM_PREFIX) else {
+            return Ok(false);
+        };
+        let value = u64::try_from(value).context("grace period out of range")?;
+        match name {
+            "crl_grace_secs" => self.crl_grace_secs = value,
+            "tcb_grace_secs" => self.tcb_grace_secs = value,
+            _ => bail!("unknown collateral parameter {parameter}"),
+        }
+        Ok(true)
+    }
+}
+
+/// Where collateral comes from.
+pub trait CollateralSource {
+    fn fetch(&self, tee_type: &TeeType) -> Result<Collateral>;
+}
+
+/// Offline source: bundles fetched elsewhere and copied to this host.
+#[derive(Debug, Clone, Default)]
+pub struct BundleSource {
+    bundles: HashMap<TeeType, Collateral>,
+}
+
+impl BundleSource {
+    pub fn new(bundles: Vec<Collateral>) -> Self {
+        BundleSource {
+            bundles: bundles
+                .into_iter()
+                .map(|bundle| (bundle.tee_type.clone(), bundle))
+                .collect(),
+        }
+    }
+
+    /// Load a JSON array of `Collateral`.
+    pub fn from_json(json: &[u8]) -> Result<Self> {
+        let bundles: Vec<Collateral> =
+            serde_json::from_slice(json).context("invalid collateral bundle")?;
+        Ok(Self::new(bundles))
+    }
+}
+
+impl CollateralSource for BundleSource {
+    fn fetch(&self, tee_type: &TeeType) -> Result<Collateral> {
+        self.bundles
+            .get(tee_type)
+            .cloned()
+            .with_context(|| format!("no pre-fetched collateral for {tee_type:?}"))
+    }
+}
+
+pub struct CollateralCache<S> {
+    source: S,
+    refresh_secs: u64,
+    entries: HashMap<TeeType, Collateral>,
+}
+
+impl<S: CollateralSource> CollateralCache<S> {
+    pub fn new(source: S, refresh_secs: u64) -> Self {
+        CollateralCache {
+            source,
+            refresh_secs,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Collateral for `tee_type`, refetched once `refresh_secs` old. If the
+    /// refetch fails the cached copy is returned; whether it is still usable
+    /// is decided by `check_snp` against the CRL's nextUpdate.
+    pub fn get(&mut self, tee_type: &TeeType, now: u64) -> Result<&Collateral> {
+        let fresh = self
+            .entries
+            .get(tee_type)
+            .is_some_and(|cached| now.saturating_sub(cached.fetched_at) < self.refresh_secs);
+        if !fresh {
+            match self.source.fetch(tee_type) {
+                Ok(collateral) => {
+                    ensure!(
+                        collateral.tee_type == *tee_type,
+                        "source returned {:?} collateral for {tee_type:?}",
+                        collateral.tee_type
+                    );
+                    self.entries.insert(tee_type.clone(), collateral);
+                }
+                Err(e) if !self.entries.contains_key(tee_type) => return Err(e),
+                Err(_) => {}
+            }
+        }
+        Ok(&self.entries[tee_type])
+    }
+}
+
+/// Reject an SEV-SNP quote whose ASK or VCEK is revoked or whose reported
+/// TCB is below the minimum past its grace period. `chain` is the
+/// `[VCEK, ASK, ARK]` chain `snp::verify_report` already validated.
+pub fn check_snp(
+    collateral: &Collateral,
+    policy: &CollateralPolicy,
+    chain: &[Vec<u8>],
+    reported_tcb: &TcbVersion,
+    now: u64,
+) -> Result<()> {
+    ensure!(
+        collateral.tee_type == TeeType::SevSnp,
+        "expected SevSnp collateral, got {:?}",
+        collateral.tee_type
+    );
+    ensure!(chain.len() == 3, "SEV-SNP chain must be [VCEK, ASK, ARK]");
+    let certs = chain
+        .iter()
+        .map(|der| {
+            X509Certificate::from_der(der)
+                .map(|(_, cert)| cert)
+                .map_err(|e| anyhow::anyhow!("malformed certificate: {e}"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (vcek, ask, ark) = (&certs[0], &certs[1], &certs[2]);
+
+    // Every CRL must come from the ASK or ARK; the ARK's is mandatory so
+    // a revoked ASK cannot go unnoticed.
+    let now_ts = i64::try_from(now).context("time out of range")?;
+    let mut covered_ark = false;
+    for der in &collateral.crls {
+        let (_, crl) = CertificateRevocationList::from_der(der)
+            .map_err(|e| anyhow::anyhow!("malformed CRL: {e}"))?;
+        let (issuer_name, issuer, subject_name, subject) =
+            if crl.issuer().as_raw() == ark.subject().as_raw() {
+                covered_ark = true;
+                ("ARK", ark, "ASK", ask)
+            } else if crl.issuer().as_raw() == ask.subject().as_raw() {
+                ("ASK", ask, "VCEK", vcek)
+            } else {
+                bail!("CRL issued by neither the ARK nor the ASK");
+            };
+        snp::verify_issuer_signature(
+            issuer,
+            &crl.signature_algorithm,
+            crl.tbs_cert_list.as_ref(),
+            &crl.signature_value.data,
+        )
+        .with_context(|| format!("{issuer_name} CRL signature"))?;
+
+        ensure!(
+            crl.last_update().timestamp() <= now_ts,
+            "{issuer_name} CRL is not valid yet"
+        );
+        let next_update = crl
+            .next_update()
+            .with_context(|| format!("{issuer_name} CRL has no nextUpdate"))?
+            .timestamp();
+        let grace = i64::try_from(policy.crl_grace_secs).unwrap_or(i64::MAX);
+        ensure!(
+            now_ts <= next_update.saturating_add(grace),
+            "{issuer_name} CRL expired at {next_update}; refresh collateral"
+        );
+
+        if crl
+            .iter_revoked_certificates()
+            .any(|revoked| revoked.serial() == &subject.serial)
+        {
+            bail!("{subject_name} certificate is revoked");
+        }
+    }
+    ensure!(covered_ark, "collateral has no ARK CRL");
+
+    if !reported_tcb.meets(&collateral.tcb_minimum) {
+        let deadline = collateral
+            .tcb_effective_at
+            .saturating_add(policy.tcb_grace_secs);
+        ensure!(
+            now < deadline,
+            "reported TCB {reported_tcb:?} is below the minimum {:?} since {deadline}",
+            collateral.tcb_minimum
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    pub(crate) const ARK_CRL: &[u8] = include_bytes!("../testdata/snp/ark_crl.der");
+    pub(crate) const ASK_CRL: &[u8] = include_bytes!("../testdata/snp/ask_crl.der");
+    const ARK_CRL_REVOKED: &[u8] = include_bytes!("../testdata/snp/ark_crl_revoked.der");
+    const ASK_CRL_REVOKED: &[u8] = include_bytes!("../testdata/snp/ask_crl_revoked.der");
+
+    // The CRLs run 2027-01-01 through 2027-02-01.
+    const NOW: u64 = 1_800_000_000; // 2027-01-15
+    const NEXT_UPDATE: u64 = 1_801_440_000; // 2027-02-01
+
+    /// The reported TCB of the fixture report.
+    const REPORTED: TcbVersion = TcbVersion {
+        boot_loader: 3,
+        tee: 0,
+        snp: 8,
+        microcode: 115,
+    };
+
+    fn chain() -> Vec<Vec<u8>> {
+        vec![
+            include_bytes!("../testdata/snp/vcek.der").to_vec(),
+            include_bytes!("../testdata/snp/ask.der").to_vec(),
+            include_bytes!("../testdata/snp/ark.der").to_vec(),
+        ]
+    }
+
+    /// Collateral for the SEV-SNP fixtures that accepts the fixture report.
+    pub(crate) fn snp_collateral() -> Collateral {
+        Collateral {
+            tee_type: TeeType::SevSnp,
+            crls: vec![ARK_CRL.to_vec(), ASK_CRL.to_vec()],
+            tcb_minimum: REPORTED,
+            tcb_effective_at: 1_790_000_000,
+            fetched_at: NOW,
+        }
+    }
+
+    #[test]
+    fn accepts_current_collateral() {
+        let policy = CollateralPolicy::default();
+        check_snp(&snp_collateral(), &policy, &chain(), &REPORTED, NOW).unwrap();
+
+        // The ASK CRL is optional, the ARK's is not.
+        let mut ark_only = snp_collateral();
+        ark_only.crls.pop();
+        check_snp(&ark_only, &policy, &chain(), &REPORTED, NOW).unwrap();
+        ark_only.crls.clear();
+        let err = check_snp(&ark_only, &policy, &chain(), &REPORTED, NOW).unwrap_err();
+        assert!(err.to_string().contains("no ARK CRL"), "{err}");
+    }
+
+    #[test]
+    fn rejects_revoked_certificates() {
+        let policy = CollateralPolicy::default();
+        for (crls, revoked) in [
+            (vec![ARK_CRL_REVOKED, ASK_CRL], "ASK"),
+            (vec![ARK_CRL, ASK_CRL_REVOKED], "VCEK"),
+        ] {
+            let collateral = Collateral {
+                crls: crls.into_iter().map(<[u8]>::to_vec).collect(),
+                ..snp_collateral()
+            };
+            let err = check_snp(&collateral, &policy, &chain(), &REPORTED, NOW).unwrap_err();
+            assert_eq!(err.to_string(), format!("{revoked} certificate is revoked"));
+        }
+
+        // A CRL cannot be forged or swapped for another issuer's.
+        let mut forged = snp_collateral();
+        let last = forged.crls[0].len() - 1;
+        forged.crls[0][last] ^= 1;
+        assert!(check_snp(&forged, &policy, &chain(), &REPORTED, NOW).is_err());
+        let mut foreign = snp_collateral();
+        foreign.crls[0] = include_bytes!("../testdata/snp/vcek.der").to_vec();
+        assert!(check_snp(&foreign, &policy, &chain(), &REPORTED, NOW).is_err());
+    }
+
+    #[test]
+    fn stale_crls_expire_after_the_grace_period() {
+        let policy = CollateralPolicy {
+            crl_grace_secs: 3600,
+            ..CollateralPolicy::default()
+        };
+        let collateral = snp_collateral();
+        check_snp(
+            &collateral,
+            &policy,
+            &chain(),
+            &REPORTED,
+            NEXT_UPDATE + 3600,
+        )
+        .unwrap();
+        let err = check_snp(
+            &collateral,
+            &policy,
+            &chain(),
+            &REPORTED,
+            NEXT_UPDATE + 3601,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("refresh collateral"), "{err}");
+        // Nor is a CRL usable before it was issued.
+        assert!(check_snp(&collateral, &policy, &chain(), &REPORTED, NOW - 30 * 86_400).is_err());
+    }
+
+    #[test]
+    fn outdated_tcb_is_accepted_only_during_grace() {
+        let policy = CollateralPolicy {
+            tcb_grace_secs: 1_000,
+            ..CollateralPolicy::default()
+        };
+        let raised = Collateral {
+            tcb_minimum: TcbVersion {
+                snp: REPORTED.snp + 1,
+                ..REPORTED
+            },
+            tcb_effective_at: NOW,
+            ..snp_collateral()
+        };
+        check_snp(&raised, &policy, &chain(), &REPORTED, NOW + 999).unwrap();
+        let err = check_snp(&raised, &policy, &chain(), &REPORTED, NOW + 1_000).unwrap_err();
+        assert!(err.to_string().contains("below the minimum"), "{err}");
+
+        let mut tighter = policy;
+        assert!(tighter
+            .apply_parameter_change("tee.collateral.tcb_grace_secs", 0)
+            .unwrap());
+        assert!(check_snp(&raised, &tighter, &chain(), &REPORTED, NOW).is_err());
+        assert!(!tighter.apply_parameter_change("fee_rate", 0).unwrap());
+        assert!(tighter
+            .apply_parameter_change("tee.collateral.unknown", 0)
+            .is_err());
+    }
+
+    struct FlakySource {
+        online: Cell<bool>,
+        fetches: Cell<u32>,
+    }
+
+    impl CollateralSource for FlakySource {
+        fn fetch(&self, _tee_type: &TeeType) -> Result<Collateral> {
+            ensure!(self.online.get(), "KDS unreachable");
+            self.fetches.set(self.fetches.get() + 1);
+            Ok(Collateral {
+                fetched_at: NOW + u64::from(self.fetches.get()) * 100,
+                ..snp_collateral()
+            })
+        }
+    }
+
+    #[test]
+    fn cache_refreshes_and_survives_outages() {
+        let source = FlakySource {
+            online: Cell::new(false),
+            fetches: Cell::new(0),
+        };
+        let mut cache = CollateralCache::new(source, 100);
+        assert!(cache.get(&TeeType::SevSnp, NOW).is_err());
+
+        cache.source.online.set(true);
+        assert_eq!(
+            cache.get(&TeeType::SevSnp, NOW).unwrap().fetched_at,
+            NOW + 100
+        );
+        cache.get(&TeeType::SevSnp, NOW + 199).unwrap();
+        assert_eq!(cache.source.fetches.get(), 1);
+        assert_eq!(
+            cache.get(&TeeType::SevSnp, NOW + 200).unwrap().fetched_at,
+            NOW + 200
+        );
+
+        // Offline, the last copy keeps being served.
+        cache.source.online.set(false);
+        assert_eq!(
+            cache.get(&TeeType::SevSnp, NOW + 1_000).unwrap().fetched_at,
+            NOW + 200
+        );
+    }
+
+    #[test]
+    fn offline_bundles_load_from_json() {
+        let json = serde_json::to_vec(&vec![snp_collateral()]).unwrap();
+        let source = BundleSource::from_json(&json).unwrap();
+        assert_eq!(source.fetch(&TeeType::SevSnp).unwrap(), snp_collateral());
+        assert!(source.fetch(&TeeType::IntelTdx).is_err());
+        assert!(BundleSource::from_json(b"{}").is_err());
+    }
+}
@@ -0,0 +1,198 @@
+// ============================================================================
+// QUOTE FRESHNESS - Slot-hash anchors and replay tracking
+// ============================================================================
+// A report's `max_age_secs` check trusts the timestamp the worker claims.
+// Quotes additionally commit to a recent slot hash (`ReportDataBinding::
+// slot_hash`), which cannot be known before that slot, so a quote can be no
+// older than its anchor slot whatever the worker's clock says.
+//
+// QuoteFreshness keeps the last `window_slots` slot hashes. A quote anchored
+// to a hash outside that window (or to one that never existed) is rejected.
+// Inside the window each quote may back a single job: the hash of every
+// accepted quote is remembered with its job until the anchor leaves the
+// window, after which the anchor check alone rejects it.
+// ============================================================================
+
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::attestation::AttestationReport;
+
+pub type QuoteHash = [u8; 32];
+
+/// Identifies a quote by its raw hardware evidence and the data it binds.
+pub fn quote_hash(report: &AttestationReport) -> QuoteHash {
+    let mut hasher = Sha256::new();
+    hasher.update(b"AETHER-TEE-QUOTE-HASH-v1");
+    hasher.update((report.signature.len() as u64).to_le_bytes());
+    hasher.update(&report.signature);
+    hasher.update((report.quote.len() as u64).to_le_bytes());
+    hasher.update(&report.quote);
+    hasher.update(&report.nonce);
+    hasher.finalize().into()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SeenQuote {
+    job_id: Vec<u8>,
+    anchor_slot: u64,
+}
+
+/// Recent slot hashes and the quotes accepted against them.
+#[derive(Debug, Clone)]
+pub struct QuoteFreshness {
+    window_slots: u64,
+    slot_hashes: BTreeMap<u64, [u8; 32]>,
+    by_hash: HashMap<[u8; 32], u64>,
+    seen: HashMap<QuoteHash, SeenQuote>,
+}
+
+impl QuoteFreshness {
+    pub fn new(window_slots: u64) -> Self {
+        QuoteFreshness {
+            window_slots: window_slots.max(1),
+            slot_hashes: BTreeMap::new(),
+            by_hash: HashMap::new(),
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Record the hash of `slot` as the chain advances; hashes and quotes
+    /// anchored before the window are dropped.
+    pub fn record_slot_hash(&mut self, slot: u64, hash: [u8; 32]) {
+        if let Some(old) = self.slot_hashes.insert(slot, hash) {
+            self.by_hash.remove(&old);
+        }
+        self.by_hash.insert(hash, slot);
+
+        let Some(&latest) = self.slot_hashes.keys().next_back() else {
+            return;
+        };
+        let oldest = latest.saturating_sub(self.window_slots - 1);
+        let live = self.slot_hashes.split_off(&oldest);
+        for hash in std::mem::replace(&mut self.slot_hashes, live).into_values() {
+            self.by_hash.remove(&hash);
+        }
+        self.seen.retain(|_, quote| quote.anchor_slot >= oldest);
+    }
+
+    /// Newest recorded slot.
+    pub fn latest_slot(&self) -> Option<u64> {
+        self.slot_hashes.keys().next_back().copied()
+    }
+
+    /// Slot `slot_hash` belongs to, if it is within the window.
+    pub fn anchor_slot(&self, slot_hash: &[u8]) -> Result<u64> {
+        let Ok(hash) = <[u8; 32]>::try_from(slot_hash) else {
+            bail!("quote slot hash must be 32 bytes, got {}", slot_hash.len());
+        };
+        match self.by_hash.get(&hash) {
+            Some(&slot) => Ok(slot),
+            None => bail!(
+                "quote is not anchored to any of the last {} slot hashes",
+                self.window_slots
+            ),
+        }
+    }
+
+    /// Check that `report`, anchored to `slot_hash`, may back `job_id`
+    /// without recording it. Returns the anchor slot.
+    pub fn check(
+        &self,
+        report: &AttestationReport,
+        job_id: &[u8],
+        slot_hash: &[u8],
+    ) -> Result<u64> {
+        let anchor_slot = self.anchor_slot(slot_hash)?;
+        if let Some(seen) = self.seen.get(&quote_hash(report)) {
+            if seen.job_id != job_id {
+                bail!(
+                    "quote was already used for another job (anchored at slot {})",
+                    seen.anchor_slot
+                );
+            }
+        }
+        Ok(anchor_slot)
+    }
+
+    /// Remember that `report` backed `job_id`.
+    pub fn record(&mut self, report: &AttestationReport, job_id: &[u8], anchor_slot: u64) {
+        self.seen
+            .entry(quote_hash(report))
+            .or_insert_with(|| SeenQuote {
+                job_id: job_id.to_vec(),
+                anchor_slot,
+            });
+    }
+
+    /// Number of quotes being tracked.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulated::SimulatedSigner;
+
+    fn slot_hash(slot: u64) -> [u8; 32] {
+        Sha256::digest(slot.to_le_bytes()).into()
+    }
+
+    fn quote(nonce: u8) -> AttestationReport {
+        SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &[nonce; 64], 1_000)
+            .unwrap()
+    }
+
+    #[test]
+    fn anchors_expire_with_the_window() {
+        let mut freshness = QuoteFreshness::new(4);
+        for slot in 10..=13 {
+            freshness.record_slot_hash(slot, slot_hash(slot));
+        }
+        assert_eq!(freshness.anchor_slot(&slot_hash(10)).unwrap(), 10);
+        assert!(freshness.anchor_slot(&slot_hash(14)).is_err());
+        assert!(freshness.anchor_slot(&[0u8; 31]).is_err());
+
+        freshness.record_slot_hash(14, slot_hash(14));
+        assert_eq!(freshness.latest_slot(), Some(14));
+        let err = freshness.anchor_slot(&slot_hash(10)).unwrap_err();
+        assert!(err.to_string().contains("last 4 slot hashes"), "{err}");
+        assert_eq!(freshness.anchor_slot(&slot_hash(11)).unwrap(), 11);
+    }
+
+    #[test]
+    fn quotes_back_one_job_until_their_anchor_expires() {
+        let mut freshness = QuoteFreshness::new(4);
+        freshness.record_slot_hash(10, slot_hash(10));
+        let report = quote(1);
+
+        let slot = freshness.check(&report, b"job-1", &slot_hash(10)).unwrap();
+        freshness.record(&report, b"job-1", slot);
+        // Re-verifying for the same job is fine; another job is not.
+        freshness.check(&report, b"job-1", &slot_hash(10)).unwrap();
+        let err = freshness
+            .check(&report, b"job-2", &slot_hash(10))
+            .unwrap_err();
+        assert!(err.to_string().contains("another job"), "{err}");
+        // A different quote is independent.
+        freshness
+            .check(&quote(2), b"job-2", &slot_hash(10))
+            .unwrap();
+
+        for slot in 11..=13 {
+            freshness.record_slot_hash(slot, slot_hash(slot));
+        }
+        assert_eq!(freshness.len(), 1);
+        freshness.record_slot_hash(14, slot_hash(14));
+        assert!(freshness.is_empty());
+        assert!(freshness.check(&report, b"job-2", &slot_hash(10)).is_err());
+    }
+}
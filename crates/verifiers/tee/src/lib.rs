@@ -4,9 +4,10 @@
 // PURPOSE: Verify that AI workers run in genuine TEEs
 //
 // SUPPORTED TEES:
-// - AMD SEV-SNP: Secure Encrypted Virtualization
+// - AMD SEV-SNP: Secure Encrypted Virtualization (snp: report + VCEK chain)
 // - Intel TDX: Trust Domain Extensions
-// - AWS Nitro: Nitro Enclaves
+// - AWS Nitro: Nitro Enclaves (nitro, behind the `nitro` feature)
+// - Simulation: devnet-signed quotes (simulated), dev-mode chains only
 //
 // ATTESTATION FLOW:
 // 1. Worker boots in TEE
@@ -16,9 +17,10 @@
 // 5. Worker sends report to validators
 // 6. Validators verify:
 //    - Signature chain (root CA → TEE cert → report)
+//    - Signing certs not revoked, TCB not outdated (collateral)
 //    - Measurement matches approved build
-//    - Timestamp is fresh (<60s)
-//    - Nonce prevents replay
+//    - Timestamp is fresh (<60s), quote anchored to a recent slot hash
+//    - Nonce prevents replay; each quote backs one job (freshness)
 //
 // SECURITY PROPERTIES:
 // - Code integrity: Measurement proves exact code running
@@ -27,11 +29,34 @@
 // - Non-repudiation: TEE signs attestation
 //
 // INTEGRATION:
+// - Governance approves measurement sets in the registry (pcr)
 // - Job escrow checks attestation before assigning work
 // - Staking slashes workers with invalid attestations
 // - Reputation tracks attestation failures
 // ============================================================================
 
 pub mod attestation;
+pub mod collateral;
+pub mod freshness;
+#[cfg(feature = "nitro")]
+pub mod nitro;
+pub mod pcr;
+pub mod simulated;
+pub mod snp;
 
-pub use attestation::{AttestationReport, TeeType, TeeVerifier};
+pub use attestation::{
+    verify_tee_quote, AttestationReport, ReportDataBinding, TeeType, TeeVerifier, REPORT_DATA_LEN,
+};
+pub use collateral::{
+    check_snp, BundleSource, Collateral, CollateralCache, CollateralPolicy, CollateralSource,
+    COLLATERAL_PARAM_PREFIX,
+};
+pub use freshness::{quote_hash, QuoteFreshness, QuoteHash};
+#[cfg(feature = "nitro")]
+pub use nitro::{NitroDocument, AWS_NITRO_ROOT_SHA256};
+pub use pcr::{
+    ApprovedSet, MeasurementRegistry, MeasurementSet, MeasurementSetId, ValidityWindow,
+    MEASUREMENT_PARAM_PREFIX,
+};
+pub use simulated::{devnet_public_key, SimulatedQuote, SimulatedSigner, SIMULATED_QUOTE_LEN};
+pub use snp::{AmdProduct, SnpReport, TcbVersion, AMD_ARKS, SNP_REPORT_LEN};
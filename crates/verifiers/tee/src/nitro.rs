@@ -0,0 +1,309 @@
+// ============================================================================
+// AWS NITRO ENCLAVES - Attestation document verification
+// ============================================================================
+// The Nitro Secure Module returns an attestation document: a COSE_Sign1
+// (RFC 9052) whose payload is a CBOR map
+//
+//   module_id, digest ("SHA384"), timestamp (ms), pcrs {index: 48 bytes},
+//   certificate (enclave leaf, DER), cabundle [root, ..., intermediate],
+//   public_key?, user_data?, nonce?
+//
+// signed with ES384 by the key in `certificate`.
+//
+// TRUST CHAIN:
+//   cabundle[0]       AWS Nitro root, its SHA-256 must match the pinned one
+//   cabundle[1..]     regional / zonal / instance intermediates
+//   certificate       per-enclave leaf, valid for a few hours
+//
+// All links are ECDSA P-384 with SHA-384. Workers put the job binding in
+// `user_data`; PCR0 is the enclave image measurement.
+//
+// The root is pinned by fingerprint: `AWS_NITRO_ROOT_SHA256` unless the
+// operator pins another root with `TeeVerifier::set_root_cert`.
+// ============================================================================
+
+use anyhow::{bail, ensure, Context, Result};
+use ciborium::value::Value;
+use ring::signature::{UnparsedPublicKey, ECDSA_P384_SHA384_FIXED};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use x509_parser::certificate::X509Certificate;
+use x509_parser::prelude::FromDer;
+
+use crate::snp::verify_issuer_signature;
+
+/// COSE algorithm identifier for ECDSA P-384 with SHA-384.
+const COSE_ALG_ES384: i128 = -35;
+
+/// CBOR tag marking a COSE_Sign1 structure.
+const COSE_SIGN1_TAG: u64 = 18;
+
+/// Length of a SHA-384 PCR.
+const PCR_LEN: usize = 48;
+
+/// SHA-256 of the DER AWS Nitro Enclaves root (AWS_NitroEnclaves_Root-G1),
+/// as published in the Nitro Enclaves user guide.
+pub const AWS_NITRO_ROOT_SHA256: [u8; 32] = [
+    0x64, 0x1a, 0x03, 0x21, 0xa3, 0xe2, 0x44, 0xef, 0xe4, 0x56, 0x46, 0x31, 0x95, 0xd6, 0x06, 0x31,
+    0x7e, 0xd7, 0xcd, 0xcc, 0x3c, 0x17, 0x56, 0xe0, 0x98, 0x93, 0xf3, 0xc6, 0x8f, 0x79, 0xbb, 0x5b,
+];
+
+/// Fingerprint a DER root certificate for `verify_document`.
+pub fn root_fingerprint(der: &[u8]) -> [u8; 32] {
+    Sha256::digest(der).into()
+}
+
+/// The signed contents of an attestation document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NitroDocument {
+    pub module_id: String,
+    /// NSM time of issue, in milliseconds since the epoch.
+    pub timestamp_ms: u64,
+    pub pcrs: BTreeMap<u8, Vec<u8>>,
+    pub user_data: Option<Vec<u8>>,
+    pub nonce: Option<Vec<u8>>,
+    pub public_key: Option<Vec<u8>>,
+}
+
+/// Verify a raw attestation document against the root with fingerprint
+/// `pinned_root` at unix time `now` and return its payload.
+pub fn verify_document(raw: &[u8], pinned_root: &[u8; 32], now: u64) -> Result<NitroDocument> {
+    let value: Value =
+        ciborium::de::from_reader(raw).context("attestation document is not CBOR")?;
+    let value = match value {
+        Value::Tag(COSE_SIGN1_TAG, inner) => *inner,
+        other => other,
+    };
+    let [protected, _unprotected, payload, signature]: [Value; 4] = value
+        .into_array()
+        .ok()
+        .and_then(|items| items.try_into().ok())
+        .context("attestation document is not a COSE_Sign1")?;
+    let (protected, payload, signature) = match (protected, payload, signature) {
+        (Value::Bytes(p), Value::Bytes(m), Value::Bytes(s)) => (p, m, s),
+        _ => bail!("malformed COSE_Sign1 fields"),
+    };
+
+    let headers: Value =
+        ciborium::de::from_reader(protected.as_slice()).context("malformed protected header")?;
+    let alg = headers
+        .as_map()
+        .and_then(|map| lookup_int(map, 1))
+        .and_then(|alg| alg.as_integer())
+        .map(i128::from);
+    ensure!(
+        alg == Some(COSE_ALG_ES384),
+        "attestation document must be signed with ES384"
+    );
+
+    let fields: Value =
+        ciborium::de::from_reader(payload.as_slice()).context("malformed document payload")?;
+    let fields = fields.as_map().context("document payload is not a map")?;
+    ensure!(
+        text_field(fields, "digest")? == "SHA384",
+        "unsupported PCR digest"
+    );
+
+    let leaf_der = bytes_field(fields, "certificate")?;
+    let cabundle = lookup_text(fields, "cabundle")
+        .and_then(Value::as_array)
+        .context("document has no cabundle")?
+        .iter()
+        .map(|cert| cert.as_bytes().context("cabundle entry is not bytes"))
+        .collect::<Result<Vec<_>>>()?;
+    ensure!(!cabundle.is_empty(), "empty cabundle");
+    ensure!(
+        &root_fingerprint(cabundle[0]) == pinned_root,
+        "cabundle root does not match the pinned AWS Nitro root"
+    );
+    let mut chain: Vec<&[u8]> = cabundle.iter().map(|der| der.as_slice()).collect();
+    chain.push(leaf_der);
+    let leaf = verify_chain(&chain, now)?;
+
+    // Sig_structure = ["Signature1", protected, external_aad, payload]
+    let mut signed = Vec::new();
+    ciborium::ser::into_writer(
+        &Value::Array(vec![
+            Value::Text("Signature1".into()),
+            Value::Bytes(protected),
+            Value::Bytes(Vec::new()),
+            Value::Bytes(payload.clone()),
+        ]),
+        &mut signed,
+    )?;
+    UnparsedPublicKey::new(
+        &ECDSA_P384_SHA384_FIXED,
+        &leaf.public_key().subject_public_key.data,
+    )
+    .verify(&signed, &signature)
+    .map_err(|_| anyhow::anyhow!("attestation document signature is invalid"))?;
+
+    let mut pcrs = BTreeMap::new();
+    for (index, value) in lookup_text(fields, "pcrs")
+        .and_then(Value::as_map)
+        .context("document has no PCRs")?
+    {
+        let index = index
+            .as_integer()
+            .and_then(|i| u8::try_from(i).ok())
+            .context("malformed PCR index")?;
+        let value = value.as_bytes().context("malformed PCR value")?;
+        ensure!(
+            value.len() == PCR_LEN,
+            "PCR{index} is {} bytes",
+            value.len()
+        );
+        pcrs.insert(index, value.clone());
+    }
+
+    Ok(NitroDocument {
+        module_id: text_field(fields, "module_id")?.to_string(),
+        timestamp_ms: lookup_text(fields, "timestamp")
+            .and_then(Value::as_integer)
+            .and_then(|t| u64::try_from(t).ok())
+            .context("document has no timestamp")?,
+        pcrs,
+        user_data: optional_bytes(fields, "user_data")?,
+        nonce: optional_bytes(fields, "nonce")?,
+        public_key: optional_bytes(fields, "public_key")?,
+    })
+}
+
+/// Walk `chain` from the root down, checking validity at `now`, CA flags
+/// and each signature; returns the leaf.
+fn verify_chain<'a>(chain: &[&'a [u8]], now: u64) -> Result<X509Certificate<'a>> {
+    let now = i64::try_from(now).context("time out of range")?;
+    let mut certs = Vec::with_capacity(chain.len());
+    for (depth, der) in chain.iter().enumerate() {
+        let (rest, cert) = X509Certificate::from_der(der)
+            .map_err(|e| anyhow::anyhow!("malformed certificate at depth {depth}: {e}"))?;
+        ensure!(rest.is_empty(), "trailing bytes after certificate {depth}");
+        let validity = cert.validity();
+        ensure!(
+            validity.not_before.timestamp() <= now && now <= validity.not_after.timestamp(),
+            "certificate at depth {depth} is not valid at {now}"
+        );
+        let issuer = certs.last().unwrap_or(&cert);
+        ensure!(issuer.is_ca(), "issuer of certificate {depth} is not a CA");
+        ensure!(
+            cert.issuer().as_raw() == issuer.subject().as_raw(),
+            "certificate {depth} is not issued by its predecessor"
+        );
+        verify_issuer_signature(
+            issuer,
+            &cert.signature_algorithm,
+            cert.tbs_certificate.as_ref(),
+            &cert.signature_value.data,
+        )
+        .with_context(|| format!("certificate {depth} signature"))?;
+        certs.push(cert);
+    }
+    certs.pop().context("empty certificate chain")
+}
+
+fn lookup_text<'a>(map: &'a [(Value, Value)], key: &str) -> Option<&'a Value> {
+    map.iter()
+        .find(|(k, _)| k.as_text() == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn lookup_int(map: &[(Value, Value)], key: i128) -> Option<&Value> {
+    map.iter()
+        .find(|(k, _)| k.as_integer().map(i128::from) == Some(key))
+        .map(|(_, v)| v)
+}
+
+fn text_field<'a>(map: &'a [(Value, Value)], key: &str) -> Result<&'a str> {
+    lookup_text(map, key)
+        .and_then(Value::as_text)
+        .with_context(|| format!("document has no {key}"))
+}
+
+fn bytes_field<'a>(map: &'a [(Value, Value)], key: &str) -> Result<&'a [u8]> {
+    lookup_text(map, key)
+        .and_then(Value::as_bytes)
+        .map(Vec::as_slice)
+        .with_context(|| format!("document has no {key}"))
+}
+
+fn optional_bytes(map: &[(Value, Value)], key: &str) -> Result<Option<Vec<u8>>> {
+    match lookup_text(map, key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Bytes(bytes)) => Ok(Some(bytes.clone())),
+        Some(_) => bail!("document {key} is not bytes"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::ReportDataBinding;
+
+    // Generated by testdata/nitro/generate.py. The enclave certificate is
+    // valid 2027-01-15 07:00 to 10:00 UTC.
+    const ROOT: &[u8] = include_bytes!("../testdata/nitro/root.der");
+    const DOCUMENT: &[u8] = include_bytes!("../testdata/nitro/document.cbor");
+
+    const NOW: u64 = 1_800_000_000; // 2027-01-15 08:00 UTC
+
+    #[test]
+    fn verifies_document_against_pinned_root() {
+        let document = verify_document(DOCUMENT, &root_fingerprint(ROOT), NOW).unwrap();
+        assert_eq!(
+            document.module_id,
+            "i-0123456789abcdef0-enc0123456789abcdef"
+        );
+        assert_eq!(document.timestamp_ms, (NOW - 10) * 1000);
+        assert_eq!(document.pcrs.len(), 16);
+        assert_eq!(document.pcrs[&0], vec![0xA0; 48]);
+        assert_eq!(document.pcrs[&2], vec![0xA2; 48]);
+        let expected = ReportDataBinding {
+            job_id: b"job-1",
+            input_hash: &[1u8; 32],
+            model_hash: &[2u8; 32],
+            code_hash: &[3u8; 32],
+            slot_hash: &[4u8; 32],
+            seed: 7,
+        }
+        .report_data();
+        assert_eq!(document.user_data, Some(expected.to_vec()));
+        assert_eq!(document.nonce, None);
+    }
+
+    #[test]
+    fn rejects_tampering_and_foreign_roots() {
+        // Flip one byte of PCR0 inside the signed payload.
+        let pcr0 = DOCUMENT.windows(48).position(|w| w == [0xA0; 48]).unwrap();
+        let mut tampered = DOCUMENT.to_vec();
+        tampered[pcr0] ^= 1;
+        let err = verify_document(&tampered, &root_fingerprint(ROOT), NOW).unwrap_err();
+        assert!(err.to_string().contains("signature"), "{err}");
+
+        let mut bad_signature = DOCUMENT.to_vec();
+        let last = bad_signature.len() - 1;
+        bad_signature[last] ^= 1;
+        assert!(verify_document(&bad_signature, &root_fingerprint(ROOT), NOW).is_err());
+
+        for foreign in [root_fingerprint(&ROOT[1..]), AWS_NITRO_ROOT_SHA256] {
+            let err = verify_document(DOCUMENT, &foreign, NOW).unwrap_err();
+            assert!(err.to_string().contains("pinned"), "{err}");
+        }
+
+        assert!(verify_document(
+            &DOCUMENT[..DOCUMENT.len() - 1],
+            &root_fingerprint(ROOT),
+            NOW
+        )
+        .is_err());
+        assert!(verify_document(b"\x80", &root_fingerprint(ROOT), NOW).is_err());
+    }
+
+    #[test]
+    fn enclave_certificate_must_be_current() {
+        // 06:59 and 10:01 UTC fall outside the enclave certificate.
+        for now in [NOW - 3_660, NOW + 7_260] {
+            let err = verify_document(DOCUMENT, &root_fingerprint(ROOT), now).unwrap_err();
+            assert!(err.to_string().contains("depth 2"), "{err}");
+        }
+    }
+}
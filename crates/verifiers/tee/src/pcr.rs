@@ -0,0 +1,429 @@
+// ============================================================================
+// MEASUREMENT REGISTRY - Governance-approved TEE builds
+// ============================================================================
+// Which worker builds may run jobs is a protocol decision, so the approved
+// measurements live on chain and change only through governance. A build is
+// described by a measurement set: the registers its quotes must carry
+// (SEV-SNP MEASUREMENT, TDX MRTD/RTMRs, Nitro PCRs), keyed by index.
+//
+// LIFECYCLE:
+// 1. Anyone stages a set; it is content-addressed, so staging grants nothing
+// 2. A ParameterChange proposal names the set:
+//      tee.measurements.approve.<set id hex>   value = from << 64 | until
+//      tee.measurements.revoke.<set id hex>    value = grace slots
+// 3. When the proposal executes, `apply_parameter_change` updates the set's
+//    validity window [from, until) in slots; 0 means "at execution" for
+//    `from` and "no expiry" for `until`
+//
+// Revocation keeps a grace period so workers can roll to the next build
+// before quotes from the old one stop verifying.
+//
+// The on-chain verifier (`TeeVerifier::verify_registered`) and the mesh
+// coordinator at worker registration both query the same registry.
+// ============================================================================
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::attestation::{AttestationReport, TeeType};
+
+/// Content address of a `MeasurementSet`.
+pub type MeasurementSetId = [u8; 32];
+
+/// Prefix of the governance parameters the registry handles.
+pub const MEASUREMENT_PARAM_PREFIX: &str = "tee.measurements.";
+
+/// Register index of the launch measurement `AttestationReport` carries.
+pub const PRIMARY_REGISTER: u8 = 0;
+
+/// The register values one approved build produces.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MeasurementSet {
+    pub tee_type: TeeType,
+    /// Index -> value. Index 0 is the launch measurement (SEV-SNP
+    /// MEASUREMENT, TDX MRTD, Nitro PCR0).
+    pub registers: BTreeMap<u8, Vec<u8>>,
+}
+
+impl MeasurementSet {
+    /// A set pinning only the launch measurement.
+    pub fn new(tee_type: TeeType, measurement: Vec<u8>) -> Self {
+        MeasurementSet {
+            tee_type,
+            registers: BTreeMap::from([(PRIMARY_REGISTER, measurement)]),
+        }
+    }
+
+    pub fn with_register(mut self, index: u8, value: Vec<u8>) -> Self {
+        self.registers.insert(index, value);
+        self
+    }
+
+    pub fn id(&self) -> MeasurementSetId {
+        let mut hasher = Sha256::new();
+        hasher.update(b"AETHER-TEE-MEASUREMENT-SET-v1");
+        hasher.update([tee_tag(&self.tee_type)]);
+        for (index, value) in &self.registers {
+            hasher.update([*index]);
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value);
+        }
+        hasher.finalize().into()
+    }
+
+    /// Whether a quote with `registers` was produced by this build: every
+    /// register the set pins must be present and equal.
+    pub fn matches(&self, tee_type: &TeeType, registers: &BTreeMap<u8, Vec<u8>>) -> bool {
+        self.tee_type == *tee_type
+            && self
+                .registers
+                .iter()
+                .all(|(index, value)| registers.get(index) == Some(value))
+    }
+}
+
+fn tee_tag(tee_type: &TeeType) -> u8 {
+    match tee_type {
+        TeeType::SevSnp => 1,
+        TeeType::IntelTdx => 2,
+        TeeType::AwsNitro => 3,
+        TeeType::Simulation => 0xFF,
+    }
+}
+
+/// Slots `[from_slot, until_slot)` a set is approved for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidityWindow {
+    pub from_slot: u64,
+    /// `None` until governance sets an expiry or revokes the set.
+    pub until_slot: Option<u64>,
+}
+
+impl ValidityWindow {
+    pub fn contains(&self, slot: u64) -> bool {
+        slot >= self.from_slot && self.until_slot.map_or(true, |until| slot < until)
+    }
+
+    fn to_param_value(self) -> u128 {
+        (u128::from(self.from_slot) << 64) | u128::from(self.until_slot.unwrap_or(0))
+    }
+
+    fn from_param_value(value: u128, execution_slot: u64) -> Self {
+        let from_slot = (value >> 64) as u64;
+        let until_slot = value as u64;
+        ValidityWindow {
+            from_slot: from_slot.max(execution_slot),
+            until_slot: (until_slot != 0).then_some(until_slot),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApprovedSet {
+    pub set: MeasurementSet,
+    pub window: ValidityWindow,
+    /// Slot of the proposal execution that last changed the window.
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MeasurementRegistry {
+    staged: HashMap<MeasurementSetId, MeasurementSet>,
+    approved: HashMap<MeasurementSetId, ApprovedSet>,
+}
+
+impl MeasurementRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publish a set so a proposal can refer to it by id.
+    pub fn stage(&mut self, set: MeasurementSet) -> MeasurementSetId {
+        let id = set.id();
+        self.staged.insert(id, set);
+        id
+    }
+
+    /// ParameterChange `(parameter, value)` approving `id` for `window`;
+    /// `from_slot` 0 means from execution.
+    pub fn approve_parameter(id: &MeasurementSetId, window: ValidityWindow) -> (String, u128) {
+        (
+            format!("{MEASUREMENT_PARAM_PREFIX}approve.{}", hex::encode(id)),
+            window.to_param_value(),
+        )
+    }
+
+    /// ParameterChange `(parameter, value)` revoking `id` `grace_slots`
+    /// after execution.
+    pub fn revoke_parameter(id: &MeasurementSetId, grace_slots: u64) -> (String, u128) {
+        (
+            format!("{MEASUREMENT_PARAM_PREFIX}revoke.{}", hex::encode(id)),
+            u128::from(grace_slots),
+        )
+    }
+
+    /// Apply an executed ParameterChange at `slot`. Returns `Ok(false)` for
+    /// parameters outside `MEASUREMENT_PARAM_PREFIX`.
+    pub fn apply_parameter_change(
+        &mut self,
+        parameter: &str,
+        value: u128,
+        slot: u64,
+    ) -> Result<bool> {
+        let Some(change) = parameter.strip_prefix(MEASUREMENT_PARAM_PREFIX) else {
+            return Ok(false);
+        };
+        let (action, id) = change
+            .split_once('.')
+            .with_context(|| format!("malformed measurement parameter {parameter}"))?;
+        let id: MeasurementSetId = hex::decode(id)
+            .ok()
+            .and_then(|id| id.try_into().ok())
+            .with_context(|| format!("malformed measurement set id in {parameter}"))?;
+
+        match action {
+            "approve" => {
+                let window = ValidityWindow::from_param_value(value, slot);
+                if window
+                    .until_slot
+                    .is_some_and(|until| until <= window.from_slot)
+                {
+                    bail!("measurement set approval window is empty");
+                }
+                let set = match self.approved.get(&id) {
+                    Some(approved) => approved.set.clone(),
+                    None => self
+                        .staged
+                        .remove(&id)
+                        .context("approving a measurement set that was never staged")?,
+                };
+                self.approved.insert(
+                    id,
+                    ApprovedSet {
+                        set,
+                        window,
+                        updated_at: slot,
+                    },
+                );
+            }
+            "revoke" => {
+                let approved = self
+                    .approved
+                    .get_mut(&id)
+                    .context("revoking a measurement set that is not approved")?;
+                let grace = u64::try_from(value).context("revocation grace out of range")?;
+                let until = slot.saturating_add(grace);
+                approved.window.until_slot =
+                    Some(approved.window.until_slot.map_or(until, |u| u.min(until)));
+                approved.updated_at = slot;
+            }
+            _ => bail!("unknown measurement parameter {parameter}"),
+        }
+        Ok(true)
+    }
+
+    pub fn get(&self, id: &MeasurementSetId) -> Option<&ApprovedSet> {
+        self.approved.get(id)
+    }
+
+    /// Sets approved at `slot`.
+    pub fn active_at(&self, slot: u64) -> impl Iterator<Item = (&MeasurementSetId, &ApprovedSet)> {
+        self.approved
+            .iter()
+            .filter(move |(_, approved)| approved.window.contains(slot))
+    }
+
+    /// The approved set a quote with `registers` belongs to at `slot`.
+    pub fn find(
+        &self,
+        tee_type: &TeeType,
+        registers: &BTreeMap<u8, Vec<u8>>,
+        slot: u64,
+    ) -> Option<MeasurementSetId> {
+        self.active_at(slot)
+            .find(|(_, approved)| approved.set.matches(tee_type, registers))
+            .map(|(id, _)| *id)
+    }
+
+    /// Check a report's launch measurement against the sets approved at
+    /// `slot`. Sets pinning further registers never match here.
+    pub fn check_report(&self, report: &AttestationReport, slot: u64) -> Result<MeasurementSetId> {
+        let registers = BTreeMap::from([(PRIMARY_REGISTER, report.measurement.clone())]);
+        self.find(&report.tee_type, &registers, slot)
+            .context("measurement not approved by governance at this slot")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_program_governance::{GovernanceState, ProposalType};
+    use aether_types::{Address, H256};
+
+    fn report(measurement: Vec<u8>) -> AttestationReport {
+        AttestationReport {
+            tee_type: TeeType::SevSnp,
+            measurement,
+            nonce: vec![0u8; 64],
+            timestamp: 0,
+            signature: Vec::new(),
+            cert_chain: Vec::new(),
+            quote: Vec::new(),
+        }
+    }
+
+    /// Run a ParameterChange through governance and apply what executes.
+    fn pass(registry: &mut MeasurementRegistry, (parameter, value): (String, u128), slot: u64) {
+        let mut governance = GovernanceState::new();
+        let voter = Address::from_slice(&[1u8; 20]).unwrap();
+        governance
+            .update_voting_power(voter, 2_000_000_000_000)
+            .unwrap();
+        let id = H256::from_slice(&[slot as u8; 32]).unwrap();
+        governance
+            .propose(
+                id,
+                voter,
+                ProposalType::ParameterChange { parameter, value },
+                "measurement registry update".into(),
+                0,
+            )
+            .unwrap();
+        governance.vote(id, voter, true, 1).unwrap();
+        governance
+            .finalize(id, governance.voting_period_slots + 1)
+            .unwrap();
+        let executed = governance.execute(id, slot).unwrap();
+        let ProposalType::ParameterChange { parameter, value } = executed else {
+            unreachable!()
+        };
+        assert!(registry
+            .apply_parameter_change(&parameter, value, slot)
+            .unwrap());
+    }
+
+    #[test]
+    fn governance_approves_and_revokes_sets() {
+        let mut registry = MeasurementRegistry::new();
+        let old = registry.stage(MeasurementSet::new(TeeType::SevSnp, vec![1u8; 48]));
+        let new = registry.stage(MeasurementSet::new(TeeType::SevSnp, vec![2u8; 48]));
+        assert!(registry.check_report(&report(vec![1u8; 48]), 0).is_err());
+
+        let slot = 200_000;
+        let open = ValidityWindow {
+            from_slot: 0,
+            until_slot: None,
+        };
+        pass(
+            &mut registry,
+            MeasurementRegistry::approve_parameter(&old, open),
+            slot,
+        );
+        assert_eq!(
+            registry.check_report(&report(vec![1u8; 48]), slot).unwrap(),
+            old
+        );
+        assert!(registry
+            .check_report(&report(vec![1u8; 48]), slot - 1)
+            .is_err());
+
+        // The new build starts later; the old one is revoked with a grace period.
+        let window = ValidityWindow {
+            from_slot: slot + 1_000,
+            until_slot: None,
+        };
+        pass(
+            &mut registry,
+            MeasurementRegistry::approve_parameter(&new, window),
+            slot + 10,
+        );
+        pass(
+            &mut registry,
+            MeasurementRegistry::revoke_parameter(&old, 5_000),
+            slot + 20,
+        );
+        assert!(registry
+            .check_report(&report(vec![2u8; 48]), slot + 999)
+            .is_err());
+        assert_eq!(
+            registry
+                .check_report(&report(vec![2u8; 48]), slot + 1_000)
+                .unwrap(),
+            new
+        );
+        assert!(registry
+            .check_report(&report(vec![1u8; 48]), slot + 5_019)
+            .is_ok());
+        assert!(registry
+            .check_report(&report(vec![1u8; 48]), slot + 5_020)
+            .is_err());
+        assert_eq!(registry.active_at(slot + 5_020).count(), 1);
+
+        // Other TEE types never match.
+        let mut tdx = report(vec![2u8; 48]);
+        tdx.tee_type = TeeType::IntelTdx;
+        assert!(registry.check_report(&tdx, slot + 1_000).is_err());
+    }
+
+    #[test]
+    fn parameter_changes_are_validated() {
+        let mut registry = MeasurementRegistry::new();
+        assert!(!registry.apply_parameter_change("fee_rate", 1, 0).unwrap());
+
+        let set = MeasurementSet::new(TeeType::AwsNitro, vec![1u8; 48]);
+        let (approve, _) = MeasurementRegistry::approve_parameter(&set.id(), {
+            ValidityWindow {
+                from_slot: 0,
+                until_slot: None,
+            }
+        });
+        // Unstaged sets, unknown actions and malformed ids are rejected.
+        assert!(registry.apply_parameter_change(&approve, 0, 10).is_err());
+        let id = registry.stage(set);
+        assert!(registry
+            .apply_parameter_change("tee.measurements.bless.00", 0, 10)
+            .is_err());
+        assert!(registry
+            .apply_parameter_change("tee.measurements.approve.zz", 0, 10)
+            .is_err());
+        let (revoke, grace) = MeasurementRegistry::revoke_parameter(&id, 0);
+        assert!(registry.apply_parameter_change(&revoke, grace, 10).is_err());
+
+        // An expiry at or before the start leaves nothing to approve.
+        let (_, expired) = MeasurementRegistry::approve_parameter(
+            &id,
+            ValidityWindow {
+                from_slot: 0,
+                until_slot: Some(10),
+            },
+        );
+        assert!(registry
+            .apply_parameter_change(&approve, expired, 10)
+            .is_err());
+        assert!(registry.apply_parameter_change(&approve, 0, 10).unwrap());
+        assert_eq!(registry.get(&id).unwrap().window.from_slot, 10);
+    }
+
+    #[test]
+    fn sets_match_every_pinned_register() {
+        let set = MeasurementSet::new(TeeType::AwsNitro, vec![0u8; 48])
+            .with_register(1, vec![1u8; 48])
+            .with_register(2, vec![2u8; 48]);
+        let mut registers: BTreeMap<u8, Vec<u8>> = set.registers.clone();
+        registers.insert(8, vec![8u8; 48]);
+        assert!(set.matches(&TeeType::AwsNitro, &registers));
+        assert!(!set.matches(&TeeType::SevSnp, &registers));
+
+        registers.insert(2, vec![9u8; 48]);
+        assert!(!set.matches(&TeeType::AwsNitro, &registers));
+        registers.remove(&2);
+        assert!(!set.matches(&TeeType::AwsNitro, &registers));
+
+        assert_ne!(
+            set.id(),
+            MeasurementSet::new(TeeType::AwsNitro, vec![0u8; 48]).id()
+        );
+    }
+}
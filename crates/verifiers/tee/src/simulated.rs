@@ -0,0 +1,196 @@
+// ============================================================================
+// SIMULATED TEE - Devnet quotes without TEE hardware
+// ============================================================================
+// A simulated quote has the same shape as a hardware one (a launch
+// measurement, 64 bytes of report_data and a signed timestamp) so the whole
+// VCR pipeline can run end to end on a laptop. It is signed with a devnet
+// Ed25519 key derived from a published seed:
+//
+//   offset  size  field
+//   0x00    4     version (LE, currently 1)
+//   0x04    48    measurement
+//   0x34    64    report_data
+//   0x74    8     timestamp (LE unix seconds)
+//   0x7C    64    Ed25519 signature over domain || bytes 0x00..0x7C
+//
+// Anyone can sign with the devnet key, so these quotes prove nothing.
+// `TeeVerifier` refuses them unless the chain is configured in dev mode.
+// ============================================================================
+
+use anyhow::{bail, ensure, Result};
+use ring::signature::{self, Ed25519KeyPair, KeyPair, UnparsedPublicKey};
+
+use crate::attestation::{AttestationReport, TeeType, REPORT_DATA_LEN};
+
+/// Size of a serialized simulated quote.
+pub const SIMULATED_QUOTE_LEN: usize = 0x7C + 64;
+
+/// Measurement length, matching the SHA-384 hardware measurements.
+pub const SIMULATED_MEASUREMENT_LEN: usize = 48;
+
+const QUOTE_VERSION: u32 = 1;
+const SIGNED_LEN: usize = 0x7C;
+const DOMAIN: &[u8] = b"AETHER-SIMULATED-QUOTE-v1";
+
+/// Published seed of the devnet quote key. Not a secret.
+const DEVNET_SEED: [u8; 32] = *b"aether devnet simulated TEE key!";
+
+/// The signed fields of a simulated quote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulatedQuote {
+    pub version: u32,
+    pub measurement: [u8; SIMULATED_MEASUREMENT_LEN],
+    pub report_data: [u8; REPORT_DATA_LEN],
+    pub timestamp: u64,
+}
+
+impl SimulatedQuote {
+    fn signed_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(SIGNED_LEN);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.measurement);
+        out.extend_from_slice(&self.report_data);
+        out.extend_from_slice(&self.timestamp.to_le_bytes());
+        out
+    }
+
+    fn parse(raw: &[u8]) -> Result<Self> {
+        ensure!(
+            raw.len() == SIMULATED_QUOTE_LEN,
+            "simulated quote is {} bytes, expected {SIMULATED_QUOTE_LEN}",
+            raw.len()
+        );
+        let version = u32::from_le_bytes(raw[0x00..0x04].try_into()?);
+        if version != QUOTE_VERSION {
+            bail!("unsupported simulated quote version {version}");
+        }
+        Ok(SimulatedQuote {
+            version,
+            measurement: raw[0x04..0x34].try_into()?,
+            report_data: raw[0x34..0x74].try_into()?,
+            timestamp: u64::from_le_bytes(raw[0x74..0x7C].try_into()?),
+        })
+    }
+}
+
+fn message(signed: &[u8]) -> Vec<u8> {
+    [DOMAIN, signed].concat()
+}
+
+/// Produces simulated quotes.
+pub struct SimulatedSigner {
+    key: Ed25519KeyPair,
+}
+
+impl SimulatedSigner {
+    /// Signer holding the well-known devnet key.
+    pub fn devnet() -> Self {
+        Self::from_seed(&DEVNET_SEED)
+    }
+
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        SimulatedSigner {
+            key: Ed25519KeyPair::from_seed_unchecked(seed)
+                .expect("any 32-byte seed is a valid Ed25519 key"),
+        }
+    }
+
+    pub fn public_key(&self) -> Vec<u8> {
+        self.key.public_key().as_ref().to_vec()
+    }
+
+    /// Quote `measurement` and `report_data` at `timestamp`. The raw quote
+    /// goes in `signature`, as Nitro attestation documents do.
+    pub fn quote(
+        &self,
+        measurement: &[u8],
+        report_data: &[u8; REPORT_DATA_LEN],
+        timestamp: u64,
+    ) -> Result<AttestationReport> {
+        let quote = SimulatedQuote {
+            version: QUOTE_VERSION,
+            measurement: measurement.try_into().map_err(|_| {
+                anyhow::anyhow!(
+                    "simulated measurement must be {SIMULATED_MEASUREMENT_LEN} bytes, got {}",
+                    measurement.len()
+                )
+            })?,
+            report_data: *report_data,
+            timestamp,
+        };
+        let mut raw = quote.signed_bytes();
+        let signature = self.key.sign(&message(&raw));
+        raw.extend_from_slice(signature.as_ref());
+
+        Ok(AttestationReport {
+            tee_type: TeeType::Simulation,
+            measurement: measurement.to_vec(),
+            nonce: report_data.to_vec(),
+            timestamp,
+            signature: raw,
+            cert_chain: Vec::new(),
+            quote: Vec::new(),
+        })
+    }
+}
+
+/// Public half of the devnet quote key.
+pub fn devnet_public_key() -> Vec<u8> {
+    SimulatedSigner::devnet().public_key()
+}
+
+/// Check a raw simulated quote's signature under `public_key` and return
+/// its fields.
+pub fn verify_quote(raw: &[u8], public_key: &[u8]) -> Result<SimulatedQuote> {
+    let quote = SimulatedQuote::parse(raw)?;
+    UnparsedPublicKey::new(&signature::ED25519, public_key)
+        .verify(&message(&raw[..SIGNED_LEN]), &raw[SIGNED_LEN..])
+        .map_err(|_| anyhow::anyhow!("simulated quote signature is invalid"))?;
+    Ok(quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quotes_are_deterministic_and_verify() {
+        let signer = SimulatedSigner::devnet();
+        let report = signer.quote(&[1u8; 48], &[2u8; 64], 1_000).unwrap();
+        assert_eq!(report.signature.len(), SIMULATED_QUOTE_LEN);
+        assert_eq!(
+            report.signature,
+            SimulatedSigner::devnet()
+                .quote(&[1u8; 48], &[2u8; 64], 1_000)
+                .unwrap()
+                .signature
+        );
+
+        let quote = verify_quote(&report.signature, &devnet_public_key()).unwrap();
+        assert_eq!(quote.measurement, [1u8; 48]);
+        assert_eq!(quote.report_data, [2u8; 64]);
+        assert_eq!(quote.timestamp, 1_000);
+    }
+
+    #[test]
+    fn rejects_tampering_and_other_keys() {
+        let report = SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &[2u8; 64], 1_000)
+            .unwrap();
+        let devnet = devnet_public_key();
+
+        for offset in [0x04, 0x34, 0x74, SIMULATED_QUOTE_LEN - 1] {
+            let mut tampered = report.signature.clone();
+            tampered[offset] ^= 1;
+            assert!(verify_quote(&tampered, &devnet).is_err(), "offset {offset}");
+        }
+        assert!(verify_quote(&report.signature[1..], &devnet).is_err());
+
+        let other = SimulatedSigner::from_seed(&[9u8; 32]).public_key();
+        assert!(verify_quote(&report.signature, &other).is_err());
+
+        assert!(SimulatedSigner::devnet()
+            .quote(&[1u8; 32], &[2u8; 64], 1_000)
+            .is_err());
+    }
+}
@@ -0,0 +1,540 @@
+// ============================================================================
+// AMD SEV-SNP - Attestation report parsing and VCEK chain validation
+// ============================================================================
+// A SEV-SNP guest asks the AMD secure processor for an ATTESTATION_REPORT
+// (SNP ABI spec, table 22): a fixed 1184-byte structure carrying the launch
+// measurement, the guest's 64 bytes of REPORT_DATA and the platform TCB,
+// signed with the chip's VCEK (Versioned Chip Endorsement Key).
+//
+// TRUST CHAIN:
+//   ARK (AMD Root Key, RSA-4096, self-signed)    pinned by the verifier
+//     └─ ASK (AMD SEV Key, RSA-4096)             RSASSA-PSS / SHA-384
+//          └─ VCEK (ECDSA P-384, per chip + TCB) RSASSA-PSS / SHA-384
+//               └─ report                        ECDSA P-384 / SHA-384
+//
+// The VCEK is derived from the chip's fused secret and its TCB, so its
+// certificate names both: the hwID extension must equal the report's
+// CHIP_ID and the SPL extensions must equal its REPORTED_TCB. Otherwise a
+// report from a patched-down firmware could ride on a current VCEK.
+//
+// The chain is passed as [VCEK, ASK, ARK], all DER. The ARK must match the
+// pinned root byte for byte; nothing is fetched from AMD's KDS here. Each
+// EPYC generation has its own ARK, named `ARK-<product>`: unless the
+// operator pins one, the verifier uses the ARK shipped for the product the
+// chain's ARK names (`AMD_ARKS`).
+//
+// SIGNATURE:
+//   bytes 0x000..0x2A0  signed region
+//   bytes 0x2A0..0x2E8  R, 72 bytes little-endian (P-384 uses the low 48)
+//   bytes 0x2E8..0x330  S, same layout
+// ============================================================================
+
+use anyhow::{bail, ensure, Context, Result};
+use ring::signature::{
+    UnparsedPublicKey, VerificationAlgorithm, ECDSA_P384_SHA384_ASN1, ECDSA_P384_SHA384_FIXED,
+    RSA_PSS_2048_8192_SHA384,
+};
+use serde::{Deserialize, Serialize};
+use x509_parser::certificate::X509Certificate;
+use x509_parser::oid_registry::{
+    OID_KEY_TYPE_EC_PUBLIC_KEY, OID_NIST_EC_P384, OID_PKCS1_RSAENCRYPTION, OID_PKCS1_RSASSAPSS,
+    OID_SIG_ECDSA_WITH_SHA384,
+};
+use x509_parser::prelude::FromDer;
+use x509_parser::x509::AlgorithmIdentifier;
+
+use crate::attestation::REPORT_DATA_LEN;
+
+/// Size of an SEV-SNP ATTESTATION_REPORT.
+pub const SNP_REPORT_LEN: usize = 0x4A0;
+
+/// Bytes covered by the report signature.
+const SIGNED_LEN: usize = 0x2A0;
+
+/// SIGNATURE_ALGO value for ECDSA P-384 with SHA-384, the only one defined.
+const SIG_ALGO_ECDSA_P384_SHA384: u32 = 1;
+
+/// Reports older than version 2 predate the current layout.
+const MIN_REPORT_VERSION: u32 = 2;
+
+/// Guest policy bit allowing the hypervisor to debug (read) guest memory.
+pub const POLICY_DEBUG: u64 = 1 << 19;
+
+const OID_AMD_BOOT_LOADER_SPL: &str = "1.3.6.1.4.1.3704.1.3.1";
+const OID_AMD_TEE_SPL: &str = "1.3.6.1.4.1.3704.1.3.2";
+const OID_AMD_SNP_SPL: &str = "1.3.6.1.4.1.3704.1.3.3";
+const OID_AMD_UCODE_SPL: &str = "1.3.6.1.4.1.3704.1.3.8";
+const OID_AMD_HWID: &str = "1.3.6.1.4.1.3704.1.4";
+
+/// EPYC generations with SEV-SNP; each has its own ARK.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AmdProduct {
+    Milan,
+    Genoa,
+    Turin,
+}
+
+/// ARKs verified against by default, one per product, DER as served by
+/// AMD's KDS at `https://kdsintf.amd.com/vcek/v1/<product>/cert_chain`.
+/// Chains for a product without an entry are rejected unless the operator
+/// pins an ARK with `TeeVerifier::set_root_cert`.
+pub const AMD_ARKS: &[(AmdProduct, &[u8])] = &[];
+
+impl AmdProduct {
+    pub const ALL: [AmdProduct; 3] = [AmdProduct::Milan, AmdProduct::Genoa, AmdProduct::Turin];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AmdProduct::Milan => "Milan",
+            AmdProduct::Genoa => "Genoa",
+            AmdProduct::Turin => "Turin",
+        }
+    }
+
+    /// The product an ARK certificate is for, from its `ARK-<product>`
+    /// common name. Says nothing about whether the ARK is genuine.
+    pub fn of_ark(der: &[u8]) -> Result<Self> {
+        let (_, cert) = X509Certificate::from_der(der)
+            .map_err(|e| anyhow::anyhow!("malformed ARK certificate: {e}"))?;
+        let name = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .context("ARK certificate has no common name")?;
+        Self::ALL
+            .into_iter()
+            .find(|product| name.strip_prefix("ARK-") == Some(product.name()))
+            .with_context(|| format!("unknown AMD product in ARK name {name:?}"))
+    }
+
+    /// The ARK shipped for this product in `AMD_ARKS`, if any.
+    pub fn shipped_ark(self) -> Option<&'static [u8]> {
+        AMD_ARKS
+            .iter()
+            .find(|(product, _)| *product == self)
+            .map(|(_, ark)| *ark)
+    }
+}
+
+/// Security patch levels packed into a TCB_VERSION.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TcbVersion {
+    pub boot_loader: u8,
+    pub tee: u8,
+    pub snp: u8,
+    pub microcode: u8,
+}
+
+impl TcbVersion {
+    /// Whether every component is at or above `minimum`'s.
+    pub fn meets(&self, minimum: &TcbVersion) -> bool {
+        self.boot_loader >= minimum.boot_loader
+            && self.tee >= minimum.tee
+            && self.snp >= minimum.snp
+            && self.microcode >= minimum.microcode
+    }
+
+    fn from_bytes(raw: &[u8]) -> Self {
+        TcbVersion {
+            boot_loader: raw[0],
+            tee: raw[1],
+            snp: raw[6],
+            microcode: raw[7],
+        }
+    }
+}
+
+/// The fields of an ATTESTATION_REPORT validators act on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnpReport {
+    pub version: u32,
+    pub guest_svn: u32,
+    pub policy: u64,
+    pub vmpl: u32,
+    pub current_tcb: TcbVersion,
+    pub report_data: [u8; REPORT_DATA_LEN],
+    pub measurement: [u8; 48],
+    pub host_data: [u8; 32],
+    pub id_key_digest: [u8; 48],
+    pub report_id: [u8; 32],
+    pub reported_tcb: TcbVersion,
+    pub chip_id: [u8; 64],
+    pub committed_tcb: TcbVersion,
+    pub launch_tcb: TcbVersion,
+    /// Signature as big-endian `R || S`, ready for a fixed-width verifier.
+    signature: [u8; 96],
+}
+
+impl SnpReport {
+    /// Parse a raw report. Only the layout is checked, not the signature.
+    pub fn parse(raw: &[u8]) -> Result<Self> {
+        ensure!(
+            raw.len() == SNP_REPORT_LEN,
+            "SEV-SNP report is {} bytes, expected {SNP_REPORT_LEN}",
+            raw.len()
+        );
+        let version = u32_at(raw, 0x00);
+        ensure!(
+            version >= MIN_REPORT_VERSION,
+            "unsupported SEV-SNP report version {version}"
+        );
+        let signature_algo = u32_at(raw, 0x34);
+        ensure!(
+            signature_algo == SIG_ALGO_ECDSA_P384_SHA384,
+            "unsupported SEV-SNP signature algorithm {signature_algo}"
+        );
+
+        let mut signature = [0u8; 96];
+        for (half, offset) in signature.chunks_mut(48).zip([0x2A0, 0x2E8]) {
+            let component = &raw[offset..offset + 72];
+            ensure!(
+                component[48..].iter().all(|&b| b == 0),
+                "SEV-SNP signature component exceeds 384 bits"
+            );
+            for (dst, src) in half.iter_mut().zip(component[..48].iter().rev()) {
+                *dst = *src;
+            }
+        }
+
+        Ok(SnpReport {
+            version,
+            guest_svn: u32_at(raw, 0x04),
+            policy: u64::from_le_bytes(raw[0x08..0x10].try_into().unwrap()),
+            vmpl: u32_at(raw, 0x30),
+            current_tcb: TcbVersion::from_bytes(&raw[0x38..0x40]),
+            report_data: raw[0x50..0x90].try_into().unwrap(),
+            measurement: raw[0x90..0xC0].try_into().unwrap(),
+            host_data: raw[0xC0..0xE0].try_into().unwrap(),
+            id_key_digest: raw[0xE0..0x110].try_into().unwrap(),
+            report_id: raw[0x140..0x160].try_into().unwrap(),
+            reported_tcb: TcbVersion::from_bytes(&raw[0x180..0x188]),
+            chip_id: raw[0x1A0..0x1E0].try_into().unwrap(),
+            committed_tcb: TcbVersion::from_bytes(&raw[0x1E0..0x1E8]),
+            launch_tcb: TcbVersion::from_bytes(&raw[0x1F8..0x200]),
+            signature,
+        })
+    }
+}
+
+fn u32_at(raw: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(raw[offset..offset + 4].try_into().unwrap())
+}
+
+/// Validate `[VCEK, ASK, ARK]` against the pinned ARK at unix time `now`
+/// and return the parsed VCEK.
+pub fn verify_cert_chain<'a>(
+    chain: &'a [Vec<u8>],
+    pinned_ark: &[u8],
+    now: u64,
+) -> Result<X509Certificate<'a>> {
+    ensure!(
+        chain.len() == 3,
+        "SEV-SNP chain must be [VCEK, ASK, ARK], got {} certificates",
+        chain.len()
+    );
+    ensure!(
+        chain[2].as_slice() == pinned_ark,
+        "ARK does not match the pinned AMD root"
+    );
+
+    let mut certs = Vec::with_capacity(3);
+    for (der, name) in chain.iter().zip(["VCEK", "ASK", "ARK"]) {
+        let (rest, cert) = X509Certificate::from_der(der)
+            .map_err(|e| anyhow::anyhow!("malformed {name} certificate: {e}"))?;
+        ensure!(rest.is_empty(), "trailing bytes after {name} certificate");
+        let validity = cert.validity();
+        let now = i64::try_from(now).context("time out of range")?;
+        ensure!(
+            validity.not_before.timestamp() <= now && now <= validity.not_after.timestamp(),
+            "{name} certificate is not valid at {now}"
+        );
+        certs.push((name, cert));
+    }
+
+    // ARK signs itself and the ASK; the ASK signs the VCEK.
+    for (subject, issuer) in [(2, 2), (1, 2), (0, 1)] {
+        let (subject_name, subject_cert) = &certs[subject];
+        let (issuer_name, issuer_cert) = &certs[issuer];
+        ensure!(issuer_cert.is_ca(), "{issuer_name} certificate is not a CA");
+        ensure!(
+            subject_cert.issuer().as_raw() == issuer_cert.subject().as_raw(),
+            "{subject_name} is not issued by {issuer_name}"
+        );
+        verify_cert_signature(subject_cert, issuer_cert)
+            .with_context(|| format!("{subject_name} signature by {issuer_name}"))?;
+    }
+
+    let (_, vcek) = certs.swap_remove(0);
+    let key = vcek.public_key();
+    ensure!(
+        key.algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY
+            && key
+                .algorithm
+                .parameters
+                .as_ref()
+                .and_then(|p| p.as_oid().ok())
+                .is_some_and(|curve| curve == OID_NIST_EC_P384),
+        "VCEK key is not ECDSA P-384"
+    );
+    Ok(vcek)
+}
+
+fn verify_cert_signature(subject: &X509Certificate, issuer: &X509Certificate) -> Result<()> {
+    verify_issuer_signature(
+        issuer,
+        &subject.signature_algorithm,
+        subject.tbs_certificate.as_ref(),
+        &subject.signature_value.data,
+    )
+}
+
+/// Check `signature` over `signed` with `issuer`'s key, for the algorithms
+/// AMD signs certificates and CRLs with.
+pub(crate) fn verify_issuer_signature(
+    issuer: &X509Certificate,
+    signature_algorithm: &AlgorithmIdentifier,
+    signed: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    let signature_oid = &signature_algorithm.algorithm;
+    let key = issuer.public_key();
+    let algorithm: &dyn VerificationAlgorithm = if *signature_oid == OID_PKCS1_RSASSAPSS
+        && key.algorithm.algorithm == OID_PKCS1_RSAENCRYPTION
+    {
+        // ring only accepts the parameters AMD uses: SHA-384, MGF1-SHA-384
+        // and a 48-byte salt. Anything else fails the signature check.
+        &RSA_PSS_2048_8192_SHA384
+    } else if *signature_oid == OID_SIG_ECDSA_WITH_SHA384
+        && key.algorithm.algorithm == OID_KEY_TYPE_EC_PUBLIC_KEY
+    {
+        &ECDSA_P384_SHA384_ASN1
+    } else {
+        bail!("unsupported signature algorithm {signature_oid}");
+    };
+    UnparsedPublicKey::new(algorithm, &key.subject_public_key.data)
+        .verify(signed, signature)
+        .map_err(|_| anyhow::anyhow!("invalid signature"))
+}
+
+/// Check that the VCEK was issued for the chip and TCB the report claims.
+fn verify_vcek_binding(vcek: &X509Certificate, report: &SnpReport) -> Result<()> {
+    let extension = |oid: &str| {
+        vcek.extensions()
+            .iter()
+            .find(|ext| ext.oid.to_id_string() == oid)
+            .map(|ext| ext.value)
+            .with_context(|| format!("VCEK lacks extension {oid}"))
+    };
+
+    // AMD stores hwID as the raw 64 bytes; accept a DER OCTET STRING too.
+    let hwid = extension(OID_AMD_HWID)?;
+    let hwid = match hwid {
+        [0x04, 0x40, rest @ ..] if rest.len() == 64 => rest,
+        raw => raw,
+    };
+    ensure!(
+        hwid == report.chip_id.as_slice(),
+        "VCEK was issued for a different chip"
+    );
+
+    let tcb = report.reported_tcb;
+    for (oid, expected, name) in [
+        (OID_AMD_BOOT_LOADER_SPL, tcb.boot_loader, "boot loader"),
+        (OID_AMD_TEE_SPL, tcb.tee, "TEE"),
+        (OID_AMD_SNP_SPL, tcb.snp, "SNP"),
+        (OID_AMD_UCODE_SPL, tcb.microcode, "microcode"),
+    ] {
+        let spl = der_small_uint(extension(oid)?)
+            .with_context(|| format!("malformed VCEK {name} SPL"))?;
+        ensure!(
+            spl == expected,
+            "VCEK {name} SPL {spl} does not match reported TCB {expected}"
+        );
+    }
+    Ok(())
+}
+
+/// A DER INTEGER in 0..=255.
+fn der_small_uint(der: &[u8]) -> Result<u8> {
+    match der {
+        [0x02, 0x01, value] if *value < 0x80 => Ok(*value),
+        [0x02, 0x02, 0x00, value] if *value >= 0x80 => Ok(*value),
+        _ => bail!("expected a DER INTEGER below 256"),
+    }
+}
+
+/// Verify a raw report end to end: chain to the pinned ARK, VCEK chip and
+/// TCB binding, report signature, guest policy and, when given, the
+/// expected REPORT_DATA.
+pub fn verify_report(
+    raw: &[u8],
+    chain: &[Vec<u8>],
+    pinned_ark: &[u8],
+    expected_report_data: Option<&[u8; REPORT_DATA_LEN]>,
+    now: u64,
+) -> Result<SnpReport> {
+    let report = SnpReport::parse(raw)?;
+    let vcek = verify_cert_chain(chain, pinned_ark, now)?;
+    verify_vcek_binding(&vcek, &report)?;
+
+    UnparsedPublicKey::new(
+        &ECDSA_P384_SHA384_FIXED,
+        &vcek.public_key().subject_public_key.data,
+    )
+    .verify(&raw[..SIGNED_LEN], &report.signature)
+    .map_err(|_| anyhow::anyhow!("SEV-SNP report signature is invalid"))?;
+
+    if report.policy & POLICY_DEBUG != 0 {
+        bail!("SEV-SNP guest policy allows debugging");
+    }
+    if let Some(expected) = expected_report_data {
+        ensure!(
+            &report.report_data == expected,
+            "SEV-SNP report_data does not match the expected job binding"
+        );
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attestation::ReportDataBinding;
+
+    // Generated by testdata/snp/generate.py: a test ARK -> ASK -> VCEK chain
+    // valid 2025-01-01 through 2050-01-01, and a report signed by the VCEK.
+    const ARK: &[u8] = include_bytes!("../testdata/snp/ark.der");
+    const ASK: &[u8] = include_bytes!("../testdata/snp/ask.der");
+    const VCEK: &[u8] = include_bytes!("../testdata/snp/vcek.der");
+    const REPORT: &[u8] = include_bytes!("../testdata/snp/report.bin");
+
+    const NOW: u64 = 1_800_000_000; // 2027-01-15
+
+    fn chain() -> Vec<Vec<u8>> {
+        vec![VCEK.to_vec(), ASK.to_vec(), ARK.to_vec()]
+    }
+
+    fn expected_report_data() -> [u8; REPORT_DATA_LEN] {
+        ReportDataBinding {
+            job_id: b"job-1",
+            input_hash: &[1u8; 32],
+            model_hash: &[2u8; 32],
+            code_hash: &[3u8; 32],
+            slot_hash: &[4u8; 32],
+            seed: 7,
+        }
+        .report_data()
+    }
+
+    #[test]
+    fn parses_report_fields() {
+        let report = SnpReport::parse(REPORT).unwrap();
+        assert_eq!(report.version, 2);
+        assert_eq!(report.guest_svn, 1);
+        assert_eq!(report.policy, 0x30000);
+        assert_eq!(report.vmpl, 0);
+        assert_eq!(report.measurement, [0x5A; 48]);
+        assert_eq!(report.report_data, expected_report_data());
+        let tcb = TcbVersion {
+            boot_loader: 3,
+            tee: 0,
+            snp: 8,
+            microcode: 115,
+        };
+        assert_eq!(report.reported_tcb, tcb);
+        assert_eq!(report.current_tcb, tcb);
+        assert_eq!(report.chip_id.to_vec(), (0..64).collect::<Vec<u8>>());
+
+        assert!(SnpReport::parse(&REPORT[..SNP_REPORT_LEN - 1]).is_err());
+        let mut v1 = REPORT.to_vec();
+        v1[0] = 1;
+        assert!(SnpReport::parse(&v1).is_err());
+        let mut wide_r = REPORT.to_vec();
+        wide_r[0x2A0 + 50] = 1;
+        assert!(SnpReport::parse(&wide_r).is_err());
+    }
+
+    #[test]
+    fn verifies_report_against_pinned_root() {
+        let expected = expected_report_data();
+        let report = verify_report(REPORT, &chain(), ARK, Some(&expected), NOW).unwrap();
+        assert_eq!(report.measurement, [0x5A; 48]);
+        assert!(verify_report(REPORT, &chain(), ARK, None, NOW).is_ok());
+
+        let err = verify_report(REPORT, &chain(), ARK, Some(&[0u8; 64]), NOW).unwrap_err();
+        assert!(err.to_string().contains("report_data"), "{err}");
+    }
+
+    #[test]
+    fn rejects_tampered_reports() {
+        // Every signed field is covered: measurement, report_data, policy.
+        for offset in [0x08, 0x50, 0x90, 0x29F] {
+            let mut raw = REPORT.to_vec();
+            raw[offset] ^= 1;
+            let err = verify_report(&raw, &chain(), ARK, None, NOW).unwrap_err();
+            assert!(err.to_string().contains("signature"), "{offset:#x}: {err}");
+        }
+        let mut raw = REPORT.to_vec();
+        raw[0x2A0] ^= 1;
+        assert!(verify_report(&raw, &chain(), ARK, None, NOW).is_err());
+
+        // A TCB the VCEK was not issued for, or another chip's ID.
+        let mut raw = REPORT.to_vec();
+        raw[0x186] -= 1;
+        let err = verify_report(&raw, &chain(), ARK, None, NOW).unwrap_err();
+        assert!(err.to_string().contains("SNP SPL"), "{err}");
+        let mut raw = REPORT.to_vec();
+        raw[0x1A0] ^= 1;
+        let err = verify_report(&raw, &chain(), ARK, None, NOW).unwrap_err();
+        assert!(err.to_string().contains("different chip"), "{err}");
+    }
+
+    #[test]
+    fn rejects_broken_chains() {
+        let err = verify_report(REPORT, &chain(), ASK, None, NOW).unwrap_err();
+        assert!(err.to_string().contains("pinned"), "{err}");
+
+        // A self-consistent chain whose ARK is not the pinned one.
+        let rogue = vec![VCEK.to_vec(), ASK.to_vec(), ASK.to_vec()];
+        assert!(verify_report(REPORT, &rogue, ASK, None, NOW).is_err());
+
+        let swapped = vec![ASK.to_vec(), VCEK.to_vec(), ARK.to_vec()];
+        assert!(verify_report(REPORT, &swapped, ARK, None, NOW).is_err());
+        assert!(verify_report(REPORT, &chain()[..2], ARK, None, NOW).is_err());
+
+        let mut bad_vcek = chain();
+        let last = bad_vcek[0].len() - 1;
+        bad_vcek[0][last] ^= 1;
+        let err = verify_report(REPORT, &bad_vcek, ARK, None, NOW).unwrap_err();
+        assert!(format!("{err:#}").contains("VCEK signature"), "{err:#}");
+
+        for now in [1_700_000_000, 2_600_000_000] {
+            let err = verify_report(REPORT, &chain(), ARK, None, now).unwrap_err();
+            assert!(err.to_string().contains("not valid at"), "{err}");
+        }
+    }
+
+    #[test]
+    fn names_the_product_of_an_ark() {
+        assert_eq!(AmdProduct::of_ark(ARK).unwrap(), AmdProduct::Milan);
+        let err = AmdProduct::of_ark(ASK).unwrap_err();
+        assert!(err.to_string().contains("SEV-Milan"), "{err}");
+        assert!(AmdProduct::of_ark(&ARK[..ARK.len() / 2]).is_err());
+
+        // Shipped ARKs are real AMD roots, never the test one.
+        for (product, ark) in AMD_ARKS {
+            assert_eq!(AmdProduct::of_ark(ark).unwrap(), *product);
+            assert_ne!(*ark, ARK);
+        }
+    }
+
+    #[test]
+    fn small_uint_decoding() {
+        assert_eq!(der_small_uint(&[0x02, 0x01, 0x08]).unwrap(), 8);
+        assert_eq!(der_small_uint(&[0x02, 0x02, 0x00, 0xC8]).unwrap(), 200);
+        assert!(der_small_uint(&[0x02, 0x01, 0x80]).is_err());
+        assert!(der_small_uint(&[0x02, 0x02, 0x00, 0x08]).is_err());
+        assert!(der_small_uint(&[0x04, 0x01, 0x08]).is_err());
+    }
+}
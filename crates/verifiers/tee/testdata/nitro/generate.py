@@ -0,0 +1,123 @@
+#!/usr/bin/env python3
+"""Regenerate the AWS Nitro test vectors in this directory.
+
+Builds a throwaway root -> intermediate -> enclave certificate chain shaped
+like AWS's (ECDSA P-384, ecdsa-with-SHA384) and an attestation document:
+a COSE_Sign1 (ES384) over the CBOR payload the Nitro Secure Module emits,
+with user_data set to the job binding the tests expect. Nothing here chains
+to the real AWS Nitro root; the verifier is pointed at root.der instead.
+
+    python3 generate.py   # needs the `cryptography` package
+"""
+
+import datetime
+import hashlib
+import struct
+from pathlib import Path
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec
+from cryptography.hazmat.primitives.asymmetric.utils import decode_dss_signature
+from cryptography.x509.oid import NameOID
+
+OUT = Path(__file__).parent
+UTC = datetime.timezone.utc
+CA_NOT_BEFORE = datetime.datetime(2025, 1, 1, tzinfo=UTC)
+CA_NOT_AFTER = datetime.datetime(2050, 1, 1, tzinfo=UTC)
+# Enclave certificates live for hours; the tests run at 2027-01-15 08:00.
+LEAF_NOT_BEFORE = datetime.datetime(2027, 1, 15, 7, tzinfo=UTC)
+LEAF_NOT_AFTER = datetime.datetime(2027, 1, 15, 10, tzinfo=UTC)
+TIMESTAMP_MS = (1_800_000_000 - 10) * 1000
+
+
+def cbor(value):
+    """Minimal canonical CBOR encoder for the types a document uses."""
+
+    def head(major, n):
+        if n < 24:
+            return bytes([major << 5 | n])
+        for info, fmt in [(24, ">B"), (25, ">H"), (26, ">I"), (27, ">Q")]:
+            if n < 1 << (8 * struct.calcsize(fmt)):
+                return bytes([major << 5 | info]) + struct.pack(fmt, n)
+        raise ValueError(n)
+
+    if value is None:
+        return b"\xf6"
+    if isinstance(value, int):
+        return head(0, value) if value >= 0 else head(1, -1 - value)
+    if isinstance(value, bytes):
+        return head(2, len(value)) + value
+    if isinstance(value, str):
+        return head(3, len(value.encode())) + value.encode()
+    if isinstance(value, list):
+        return head(4, len(value)) + b"".join(cbor(v) for v in value)
+    if isinstance(value, dict):
+        return head(5, len(value)) + b"".join(cbor(k) + cbor(v) for k, v in value.items())
+    raise TypeError(value)
+
+
+def cert(subject, issuer, public_key, signing_key, ca, not_before, not_after):
+    return (
+        x509.CertificateBuilder()
+        .subject_name(x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, subject)]))
+        .issuer_name(x509.Name([x509.NameAttribute(NameOID.COMMON_NAME, issuer)]))
+        .public_key(public_key)
+        .serial_number(x509.random_serial_number())
+        .not_valid_before(not_before)
+        .not_valid_after(not_after)
+        .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True)
+        .sign(signing_key, hashes.SHA384())
+    )
+
+
+def report_data():
+    """ReportDataBinding { job_id: b"job-1", input/model/code/slot hash: [1/2/3/4; 32], seed: 7 }."""
+    h = hashlib.sha512(b"AETHER-TEE-REPORT-DATA-v2")
+    for field in [b"job-1", bytes([1] * 32), bytes([2] * 32), bytes([3] * 32), bytes([4] * 32)]:
+        h.update(struct.pack("<Q", len(field)))
+        h.update(field)
+    h.update(struct.pack("<Q", 7))
+    return h.digest()
+
+
+def main():
+    root_key = ec.generate_private_key(ec.SECP384R1())
+    zonal_key = ec.generate_private_key(ec.SECP384R1())
+    enclave_key = ec.generate_private_key(ec.SECP384R1())
+
+    root = cert("aws.nitro-enclaves", "aws.nitro-enclaves", root_key.public_key(),
+                root_key, True, CA_NOT_BEFORE, CA_NOT_AFTER)
+    zonal = cert("zonal.aws.nitro-enclaves", "aws.nitro-enclaves", zonal_key.public_key(),
+                 root_key, True, CA_NOT_BEFORE, CA_NOT_AFTER)
+    enclave = cert("i-0123456789abcdef0-enc0123456789abcdef.aws.nitro-enclaves",
+                   "zonal.aws.nitro-enclaves", enclave_key.public_key(), zonal_key,
+                   False, LEAF_NOT_BEFORE, LEAF_NOT_AFTER)
+    der = lambda c: c.public_bytes(serialization.Encoding.DER)  # noqa: E731
+
+    pcrs = {i: bytes([0] * 48) for i in range(16)}
+    for i in range(3):
+        pcrs[i] = bytes([0xA0 + i] * 48)
+    payload = cbor({
+        "module_id": "i-0123456789abcdef0-enc0123456789abcdef",
+        "digest": "SHA384",
+        "timestamp": TIMESTAMP_MS,
+        "pcrs": pcrs,
+        "certificate": der(enclave),
+        "cabundle": [der(root), der(zonal)],
+        "public_key": None,
+        "user_data": report_data(),
+        "nonce": None,
+    })
+    protected = cbor({1: -35})  # alg: ES384
+    sig_structure = cbor(["Signature1", protected, b"", payload])
+    r, s = decode_dss_signature(enclave_key.sign(sig_structure, ec.ECDSA(hashes.SHA384())))
+    signature = r.to_bytes(48, "big") + s.to_bytes(48, "big")
+    document = b"\xd2" + cbor([protected, {}, payload, signature])  # tag 18
+
+    (OUT / "root.der").write_bytes(der(root))
+    (OUT / "document.cbor").write_bytes(document)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,154 @@
+#!/usr/bin/env python3
+"""Regenerate the SEV-SNP test vectors in this directory.
+
+Builds a throwaway ARK -> ASK -> VCEK chain shaped like AMD's (RSA-PSS
+SHA-384 CA keys, a P-384 VCEK carrying the TCB and hwID extensions), a
+version 2 attestation report signed by the VCEK and CRLs: ark_crl.der and
+ask_crl.der revoke an unrelated serial, ark_crl_revoked.der the ASK and
+ask_crl_revoked.der the VCEK. Nothing here
+chains to a real AMD root; the verifier is pointed at ark.der as its pinned
+root.
+
+    python3 generate.py   # needs the `cryptography` package
+"""
+
+import datetime
+import hashlib
+import struct
+from pathlib import Path
+
+from cryptography import x509
+from cryptography.hazmat.primitives import hashes, serialization
+from cryptography.hazmat.primitives.asymmetric import ec, padding, rsa
+from cryptography.hazmat.primitives.asymmetric.utils import decode_dss_signature
+from cryptography.x509.oid import NameOID
+
+OUT = Path(__file__).parent
+NOT_BEFORE = datetime.datetime(2025, 1, 1, tzinfo=datetime.timezone.utc)
+NOT_AFTER = datetime.datetime(2050, 1, 1, tzinfo=datetime.timezone.utc)
+CRL_THIS_UPDATE = datetime.datetime(2027, 1, 1, tzinfo=datetime.timezone.utc)
+CRL_NEXT_UPDATE = datetime.datetime(2027, 2, 1, tzinfo=datetime.timezone.utc)
+PSS = padding.PSS(mgf=padding.MGF1(hashes.SHA384()), salt_length=48)
+
+BOOT_LOADER, TEE, SNP, MICROCODE = 3, 0, 8, 115
+CHIP_ID = bytes(range(64))
+MEASUREMENT = bytes([0x5A] * 48)
+POLICY = 0x30000  # SMT allowed, reserved bit 17 set, debug off
+
+
+def name(cn):
+    return x509.Name(
+        [
+            x509.NameAttribute(NameOID.ORGANIZATIONAL_UNIT_NAME, "Engineering"),
+            x509.NameAttribute(NameOID.ORGANIZATION_NAME, "Advanced Micro Devices"),
+            x509.NameAttribute(NameOID.COMMON_NAME, cn),
+        ]
+    )
+
+
+SERIALS = {"ARK-Milan": 0xA1, "SEV-Milan": 0xA2, "SEV-VCEK": 0xA3}
+
+
+def cert(subject, issuer, public_key, signing_key, ca, extensions=()):
+    builder = (
+        x509.CertificateBuilder()
+        .subject_name(name(subject))
+        .issuer_name(name(issuer))
+        .public_key(public_key)
+        .serial_number(SERIALS[subject])
+        .not_valid_before(NOT_BEFORE)
+        .not_valid_after(NOT_AFTER)
+        .add_extension(x509.BasicConstraints(ca=ca, path_length=None), critical=True)
+    )
+    for oid, value in extensions:
+        builder = builder.add_extension(
+            x509.UnrecognizedExtension(x509.ObjectIdentifier(oid), value), critical=False
+        )
+    return builder.sign(signing_key, hashes.SHA384(), rsa_padding=PSS)
+
+
+def crl(issuer, revoked_serials, signing_key):
+    builder = (
+        x509.CertificateRevocationListBuilder()
+        .issuer_name(name(issuer))
+        .last_update(CRL_THIS_UPDATE)
+        .next_update(CRL_NEXT_UPDATE)
+    )
+    for serial in revoked_serials:
+        builder = builder.add_revoked_certificate(
+            x509.RevokedCertificateBuilder()
+            .serial_number(serial)
+            .revocation_date(CRL_THIS_UPDATE)
+            .build()
+        )
+    crl = builder.sign(signing_key, hashes.SHA384(), rsa_padding=PSS)
+    return crl.public_bytes(serialization.Encoding.DER)
+
+
+def der_integer(value):
+    return bytes([0x02, 0x01, value]) if value < 0x80 else bytes([0x02, 0x02, 0x00, value])
+
+
+def report_data():
+    """ReportDataBinding { job_id: b"job-1", input/model/code/slot hash: [1/2/3/4; 32], seed: 7 }."""
+    h = hashlib.sha512(b"AETHER-TEE-REPORT-DATA-v2")
+    for field in [b"job-1", bytes([1] * 32), bytes([2] * 32), bytes([3] * 32), bytes([4] * 32)]:
+        h.update(struct.pack("<Q", len(field)))
+        h.update(field)
+    h.update(struct.pack("<Q", 7))
+    return h.digest()
+
+
+def main():
+    ark_key = rsa.generate_private_key(public_exponent=65537, key_size=4096)
+    ask_key = rsa.generate_private_key(public_exponent=65537, key_size=4096)
+    vcek_key = ec.generate_private_key(ec.SECP384R1())
+
+    ark = cert("ARK-Milan", "ARK-Milan", ark_key.public_key(), ark_key, True)
+    ask = cert("SEV-Milan", "ARK-Milan", ask_key.public_key(), ark_key, True)
+    vcek = cert(
+        "SEV-VCEK",
+        "SEV-Milan",
+        vcek_key.public_key(),
+        ask_key,
+        False,
+        [
+            ("1.3.6.1.4.1.3704.1.3.1", der_integer(BOOT_LOADER)),
+            ("1.3.6.1.4.1.3704.1.3.2", der_integer(TEE)),
+            ("1.3.6.1.4.1.3704.1.3.3", der_integer(SNP)),
+            ("1.3.6.1.4.1.3704.1.3.8", der_integer(MICROCODE)),
+            ("1.3.6.1.4.1.3704.1.4", CHIP_ID),
+        ],
+    )
+
+    tcb = bytes([BOOT_LOADER, TEE, 0, 0, 0, 0, SNP, MICROCODE])
+    report = bytearray(0x4A0)
+    struct.pack_into("<IIQ", report, 0x00, 2, 1, POLICY)
+    struct.pack_into("<II", report, 0x30, 0, 1)  # VMPL 0, ECDSA P-384 with SHA-384
+    report[0x38:0x40] = tcb  # current
+    report[0x50:0x90] = report_data()
+    report[0x90:0xC0] = MEASUREMENT
+    report[0x180:0x188] = tcb  # reported
+    report[0x1A0:0x1E0] = CHIP_ID
+    report[0x1E0:0x1E8] = tcb  # committed
+    report[0x1F8:0x200] = tcb  # launch
+
+    r, s = decode_dss_signature(vcek_key.sign(bytes(report[:0x2A0]), ec.ECDSA(hashes.SHA384())))
+    report[0x2A0:0x2E8] = r.to_bytes(72, "little")
+    report[0x2E8:0x330] = s.to_bytes(72, "little")
+
+    for file, data in [
+        ("ark.der", ark.public_bytes(serialization.Encoding.DER)),
+        ("ask.der", ask.public_bytes(serialization.Encoding.DER)),
+        ("vcek.der", vcek.public_bytes(serialization.Encoding.DER)),
+        ("report.bin", bytes(report)),
+        ("ark_crl.der", crl("ARK-Milan", [0x1234], ark_key)),
+        ("ark_crl_revoked.der", crl("ARK-Milan", [0x1234, SERIALS["SEV-Milan"]], ark_key)),
+        ("ask_crl.der", crl("SEV-Milan", [0x1234], ask_key)),
+        ("ask_crl_revoked.der", crl("SEV-Milan", [0x1234, SERIALS["SEV-VCEK"]], ask_key)),
+    ]:
+        (OUT / file).write_bytes(data)
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,622 @@
+// ============================================================================
+// VCR CHALLENGE LIFECYCLE
+// ============================================================================
+// Every accepted VCR gets a challenge window. During the window anyone may
+// post a bonded KZG challenge; the worker then has `response_slots` to open
+// the challenged trace points. Outcomes:
+//
+//   window expires unchallenged          -> Accept
+//   challenge answered with valid proofs -> Accept (challenger bond forfeit)
+//   invalid openings or no response      -> Slash  (challenger rewarded)
+//
+// Verdicts are queued as `ChallengeEvent`s; job escrow drains them to release
+// or withhold payment. Windows are per (job, worker), so several workers
+// replicating one job are challenged independently.
+// ============================================================================
+
+use crate::{TraceVerifier, VerifiableComputeReceipt};
+use aether_crypto_kzg::scalar_from_i64;
+use aether_types::{Address, Slot, H256};
+use aether_verifiers_kzg::{KzgChallenge, KzgOpeningResponse};
+use anyhow::{bail, ensure, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default challenger bond: 10 AIC, the same as an optimistic job bond, so
+/// a frivolous challenge costs as much as the work it holds up.
+pub const DEFAULT_MIN_CHALLENGE_BOND: u128 = 10_000_000;
+
+/// Default cap on the trace points a single challenge may request.
+pub const DEFAULT_MAX_CHALLENGE_POINTS: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct ChallengeConfig {
+    /// Slots after opening during which a VCR may be challenged.
+    pub window_slots: u64,
+    /// Slots the worker has to answer a challenge.
+    pub response_slots: u64,
+    /// Smallest bond a challenger must lock.
+    pub min_bond: u128,
+    /// Most trace points one challenge may ask the worker to open.
+    pub max_points: usize,
+}
+
+impl Default for ChallengeConfig {
+    fn default() -> Self {
+        ChallengeConfig {
+            window_slots: 10,
+            response_slots: 5,
+            min_bond: DEFAULT_MIN_CHALLENGE_BOND,
+            max_points: DEFAULT_MAX_CHALLENGE_POINTS,
+        }
+    }
+}
+
+/// Every opening must be against `commitment` and at the evaluation point
+/// of the trace index it claims, so a worker cannot answer a challenged
+/// index with an opening somewhere else.
+pub(crate) fn check_opening_binding(
+    commitment: &[u8],
+    response: &KzgOpeningResponse,
+) -> Result<()> {
+    for opening in &response.openings {
+        ensure!(
+            opening.commitment.commitment == commitment,
+            "opening for layer {} point {} is not against the committed trace",
+            opening.layer_idx,
+            opening.point_idx
+        );
+        ensure!(
+            opening.point == scalar_from_i64(opening.point_idx as i64),
+            "layer {} point {} opened at the wrong x",
+            opening.layer_idx,
+            opening.point_idx
+        );
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Verdict {
+    /// The VCR stands. A challenger that lost forfeits `forfeited_bond`.
+    Accept {
+        challenger: Option<Address>,
+        forfeited_bond: u128,
+    },
+    /// The worker failed to defend the VCR; the challenger gets its bond back.
+    Slash {
+        worker_id: Vec<u8>,
+        challenger: Address,
+        bond: u128,
+        reason: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChallengeEvent {
+    Opened {
+        vcr_id: H256,
+        worker_id: Vec<u8>,
+        deadline_slot: Slot,
+    },
+    Challenged {
+        vcr_id: H256,
+        worker_id: Vec<u8>,
+        challenger: Address,
+        bond: u128,
+        response_deadline: Slot,
+    },
+    Responded {
+        vcr_id: H256,
+        worker_id: Vec<u8>,
+    },
+    Resolved {
+        vcr_id: H256,
+        worker_id: Vec<u8>,
+        verdict: Verdict,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct PendingChallenge {
+    challenger: Address,
+    bond: u128,
+    challenge: KzgChallenge,
+    response_deadline: Slot,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    trace_commitment: Vec<u8>,
+    /// Number of evaluation points in the committed trace.
+    trace_len: u32,
+    window_end: Slot,
+    challenge: Option<PendingChallenge>,
+}
+
+/// A VCR is identified by its job and the worker that produced it.
+type EntryKey = (H256, Vec<u8>);
+
+/// Tracks open challenge windows and pending challenges by (job, worker).
+pub struct ChallengeManager {
+    config: ChallengeConfig,
+    entries: HashMap<EntryKey, Entry>,
+    events: Vec<ChallengeEvent>,
+}
+
+impl ChallengeManager {
+    pub fn new(config: ChallengeConfig) -> Self {
+        ChallengeManager {
+            config,
+            entries: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Open the challenge window for a verified VCR whose committed trace
+    /// has `trace_len` evaluation points.
+    pub fn open(
+        &mut self,
+        vcr: &VerifiableComputeReceipt,
+        trace_len: u32,
+        current_slot: Slot,
+    ) -> Result<()> {
+        ensure!(trace_len > 0, "committed trace of {} is empty", vcr.job_id);
+        let key = (vcr.job_id, vcr.worker_id.clone());
+        if self.entries.contains_key(&key) {
+            bail!("challenge window already open for {}", vcr.job_id);
+        }
+        let window_end = current_slot
+            .checked_add(self.config.window_slots)
+            .ok_or_else(|| anyhow::anyhow!("slot overflow opening challenge window"))?;
+        self.entries.insert(
+            key,
+            Entry {
+                trace_commitment: vcr.trace_commitment.clone(),
+                trace_len,
+                window_end,
+                challenge: None,
+            },
+        );
+        self.events.push(ChallengeEvent::Opened {
+            vcr_id: vcr.job_id,
+            worker_id: vcr.worker_id.clone(),
+            deadline_slot: window_end,
+        });
+        Ok(())
+    }
+
+    /// Post a bonded challenge against `worker_id`'s open VCR. One challenge
+    /// may be pending per VCR at a time.
+    pub fn challenge(
+        &mut self,
+        worker_id: &[u8],
+        challenger: Address,
+        bond: u128,
+        challenge: KzgChallenge,
+        current_slot: Slot,
+    ) -> Result<()> {
+        let vcr_id = challenge.vcr_id;
+        let response_slots = self.config.response_slots;
+        if bond < self.config.min_bond {
+            bail!(
+                "challenge bond {bond} below minimum {}",
+                self.config.min_bond
+            );
+        }
+        challenge.validate()?;
+        ensure!(
+            challenge.expected_openings() <= self.config.max_points,
+            "challenge requests {} points, at most {} allowed",
+            challenge.expected_openings(),
+            self.config.max_points
+        );
+        let entry = self
+            .entries
+            .get_mut(&(vcr_id, worker_id.to_vec()))
+            .ok_or_else(|| anyhow::anyhow!("no open challenge window for {vcr_id}"))?;
+        if let Some((layer, point)) = challenge
+            .iter_points()
+            .find(|&(_, point)| point >= entry.trace_len)
+        {
+            bail!(
+                "layer {layer} point {point} is outside the committed trace of {} points",
+                entry.trace_len
+            );
+        }
+        if current_slot > entry.window_end {
+            bail!(
+                "challenge window for {vcr_id} closed at slot {}",
+                entry.window_end
+            );
+        }
+        if entry.challenge.is_some() {
+            bail!("{vcr_id} already has a pending challenge");
+        }
+
+        let response_deadline = current_slot
+            .checked_add(response_slots)
+            .ok_or_else(|| anyhow::anyhow!("slot overflow computing response deadline"))?;
+        entry.challenge = Some(PendingChallenge {
+            challenger,
+            bond,
+            challenge,
+            response_deadline,
+        });
+        self.events.push(ChallengeEvent::Challenged {
+            vcr_id,
+            worker_id: worker_id.to_vec(),
+            challenger,
+            bond,
+            response_deadline,
+        });
+        Ok(())
+    }
+
+    /// Check the worker's openings and resolve the challenge either way.
+    /// Errors only for responses that cannot be attributed to a live challenge.
+    pub fn respond(
+        &mut self,
+        worker_id: &[u8],
+        response: &KzgOpeningResponse,
+        current_slot: Slot,
+        verifier: &dyn TraceVerifier,
+    ) -> Result<Verdict> {
+        let vcr_id = response.vcr_id;
+        let key = (vcr_id, worker_id.to_vec());
+        let entry = self
+            .entries
+            .get(&key)
+            .ok_or_else(|| anyhow::anyhow!("no open challenge window for {vcr_id}"))?;
+        let pending = entry
+            .challenge
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("{vcr_id} has no pending challenge"))?;
+        ensure!(
+            current_slot <= pending.response_deadline,
+            "response for {vcr_id} arrived after slot {}",
+            pending.response_deadline
+        );
+        self.events.push(ChallengeEvent::Responded {
+            vcr_id,
+            worker_id: worker_id.to_vec(),
+        });
+
+        let outcome = check_opening_binding(&entry.trace_commitment, response)
+            .and_then(|()| verifier.verify_openings(&pending.challenge, response));
+        let verdict = match outcome {
+            Ok(()) => Verdict::Accept {
+                challenger: Some(pending.challenger),
+                forfeited_bond: pending.bond,
+            },
+            Err(e) => Verdict::Slash {
+                worker_id: worker_id.to_vec(),
+                challenger: pending.challenger,
+                bond: pending.bond,
+                reason: format!("{e:#}"),
+            },
+        };
+        self.resolve(key, verdict.clone());
+        Ok(verdict)
+    }
+
+    /// Resolve every window and challenge whose deadline passed before
+    /// `current_slot`. Returns the number of verdicts issued.
+    pub fn tick(&mut self, current_slot: Slot) -> usize {
+        let mut expired: Vec<(EntryKey, Verdict)> = self
+            .entries
+            .iter()
+            .filter_map(|(key, entry)| {
+                let verdict = match &entry.challenge {
+                    Some(pending) if current_slot > pending.response_deadline => Verdict::Slash {
+                        worker_id: key.1.clone(),
+                        challenger: pending.challenger,
+                        bond: pending.bond,
+                        reason: "no response before deadline".to_string(),
+                    },
+                    None if current_slot > entry.window_end => Verdict::Accept {
+                        challenger: None,
+                        forfeited_bond: 0,
+                    },
+                    _ => return None,
+                };
+                Some((key.clone(), verdict))
+            })
+            .collect();
+        // Stable event order regardless of map iteration.
+        expired.sort_by(|(a, _), (b, _)| (a.0.as_bytes(), &a.1).cmp(&(b.0.as_bytes(), &b.1)));
+        let resolved = expired.len();
+        for (key, verdict) in expired {
+            self.resolve(key, verdict);
+        }
+        resolved
+    }
+
+    fn resolve(&mut self, key: EntryKey, verdict: Verdict) {
+        self.entries.remove(&key);
+        let (vcr_id, worker_id) = key;
+        self.events.push(ChallengeEvent::Resolved {
+            vcr_id,
+            worker_id,
+            verdict,
+        });
+    }
+
+    pub fn is_open(&self, vcr_id: &H256, worker_id: &[u8]) -> bool {
+        self.entries.contains_key(&(*vcr_id, worker_id.to_vec()))
+    }
+
+    /// Take all events emitted since the last call.
+    pub fn drain_events(&mut self) -> Vec<ChallengeEvent> {
+        std::mem::take(&mut self.events)
+    }
+}
+
+impl Default for ChallengeManager {
+    fn default() -> Self {
+        Self::new(ChallengeConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_kzg::KzgVerifier;
+    use aether_verifiers_kzg::Opening;
+
+    fn addr(n: u8) -> Address {
+        Address::from_slice(&[n; 20]).unwrap()
+    }
+
+    /// A VCR whose trace is `3 + x` under the test KZG setup, plus that setup.
+    fn committed_vcr() -> (VerifiableComputeReceipt, KzgVerifier, Vec<[u8; 32]>) {
+        let kzg = KzgVerifier::new_insecure_test(16);
+        let mut coeffs = vec![[0u8; 32]; 2];
+        coeffs[0][0] = 3;
+        coeffs[1][0] = 1;
+        let commitment = kzg.commit(&coeffs).unwrap();
+        let vcr = VerifiableComputeReceipt {
+            version: crate::VCR_VERSION,
+            job_id: H256::from_slice(&[7u8; 32]).unwrap(),
+            worker_id: vec![9u8; 32],
+            model_hash: H256::zero(),
+            input_hash: H256::zero(),
+            output_hash: H256::zero(),
+            trace_commitment: commitment.commitment,
+            trace_proof: Vec::new(),
+            trace_evaluation: Vec::new(),
+            trace_point: Vec::new(),
+            tee_attestation: Vec::new(),
+            code_hash: Vec::new(),
+            seed: 0,
+            timestamp: 0,
+            gas_used: 0,
+            extensions: Vec::new(),
+            signature: Vec::new(),
+        };
+        (vcr, kzg, coeffs)
+    }
+
+    const BOND: u128 = DEFAULT_MIN_CHALLENGE_BOND;
+    const TRACE_LEN: u32 = 4;
+
+    fn kzg_challenge(vcr_id: H256) -> KzgChallenge {
+        KzgChallenge {
+            vcr_id,
+            layer_indices: vec![0],
+            point_indices: vec![vec![0]],
+            deadline_slot: 0,
+        }
+    }
+
+    fn opening_at(kzg: &KzgVerifier, coeffs: &[[u8; 32]], point_idx: u32, x: i64) -> Opening {
+        let z = scalar_from_i64(x);
+        Opening {
+            layer_idx: 0,
+            point_idx,
+            point: z.to_vec(),
+            commitment: kzg.commit(coeffs).unwrap(),
+            proof: kzg.create_proof(coeffs, &z).unwrap(),
+        }
+    }
+
+    fn response(vcr_id: H256, kzg: &KzgVerifier, coeffs: &[[u8; 32]]) -> KzgOpeningResponse {
+        KzgOpeningResponse::new(vcr_id, vec![opening_at(kzg, coeffs, 0, 0)])
+    }
+
+    #[test]
+    fn unchallenged_window_accepts() {
+        let (vcr, _, _) = committed_vcr();
+        let mut manager = ChallengeManager::default();
+        manager.open(&vcr, TRACE_LEN, 100).unwrap();
+        assert!(manager.open(&vcr, TRACE_LEN, 100).is_err());
+
+        assert_eq!(manager.tick(110), 0);
+        assert_eq!(manager.tick(111), 1);
+        assert!(!manager.is_open(&vcr.job_id, &vcr.worker_id));
+        assert_eq!(
+            manager.drain_events(),
+            vec![
+                ChallengeEvent::Opened {
+                    vcr_id: vcr.job_id,
+                    worker_id: vcr.worker_id.clone(),
+                    deadline_slot: 110
+                },
+                ChallengeEvent::Resolved {
+                    vcr_id: vcr.job_id,
+                    worker_id: vcr.worker_id.clone(),
+                    verdict: Verdict::Accept {
+                        challenger: None,
+                        forfeited_bond: 0
+                    }
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn windows_are_per_worker() {
+        let (vcr, kzg, coeffs) = committed_vcr();
+        let mut replica = vcr.clone();
+        replica.worker_id = vec![8u8; 32];
+        let mut manager = ChallengeManager::default();
+        manager.open(&vcr, TRACE_LEN, 100).unwrap();
+        manager.open(&replica, TRACE_LEN, 100).unwrap();
+
+        manager
+            .challenge(
+                &replica.worker_id,
+                addr(1),
+                BOND,
+                kzg_challenge(vcr.job_id),
+                101,
+            )
+            .unwrap();
+        // The other worker's window is untouched and cannot answer for it.
+        assert!(manager
+            .respond(
+                &vcr.worker_id,
+                &response(vcr.job_id, &kzg, &coeffs),
+                102,
+                &kzg
+            )
+            .is_err());
+        manager
+            .respond(
+                &replica.worker_id,
+                &response(vcr.job_id, &kzg, &coeffs),
+                102,
+                &kzg,
+            )
+            .unwrap();
+        assert!(manager.is_open(&vcr.job_id, &vcr.worker_id));
+        assert!(!manager.is_open(&vcr.job_id, &replica.worker_id));
+    }
+
+    #[test]
+    fn valid_response_forfeits_bond() {
+        let (vcr, kzg, coeffs) = committed_vcr();
+        let worker = vcr.worker_id.clone();
+        let mut manager = ChallengeManager::default();
+        manager.open(&vcr, TRACE_LEN, 100).unwrap();
+        manager
+            .challenge(&worker, addr(1), BOND, kzg_challenge(vcr.job_id), 105)
+            .unwrap();
+        assert!(manager
+            .challenge(&worker, addr(2), BOND, kzg_challenge(vcr.job_id), 105)
+            .is_err());
+
+        let verdict = manager
+            .respond(&worker, &response(vcr.job_id, &kzg, &coeffs), 108, &kzg)
+            .unwrap();
+        assert_eq!(
+            verdict,
+            Verdict::Accept {
+                challenger: Some(addr(1)),
+                forfeited_bond: BOND
+            }
+        );
+        assert!(!manager.is_open(&vcr.job_id, &worker));
+    }
+
+    #[test]
+    fn bad_or_missing_response_slashes() {
+        let (vcr, kzg, coeffs) = committed_vcr();
+        let worker = vcr.worker_id.clone();
+        let mut manager = ChallengeManager::default();
+
+        // Openings against a different polynomial than the committed trace.
+        manager.open(&vcr, TRACE_LEN, 100).unwrap();
+        manager
+            .challenge(&worker, addr(1), BOND, kzg_challenge(vcr.job_id), 101)
+            .unwrap();
+        let mut other = coeffs.clone();
+        other[0][0] = 4;
+        let verdict = manager
+            .respond(&worker, &response(vcr.job_id, &kzg, &other), 102, &kzg)
+            .unwrap();
+        assert!(
+            matches!(verdict, Verdict::Slash { ref reason, .. } if reason.contains("committed trace"))
+        );
+
+        // Silence past the response deadline.
+        manager.open(&vcr, TRACE_LEN, 200).unwrap();
+        manager
+            .challenge(&worker, addr(1), BOND, kzg_challenge(vcr.job_id), 201)
+            .unwrap();
+        assert_eq!(manager.tick(206), 0);
+        assert_eq!(manager.tick(207), 1);
+        let events = manager.drain_events();
+        assert!(matches!(
+            events.last(),
+            Some(ChallengeEvent::Resolved {
+                verdict: Verdict::Slash { bond: BOND, .. },
+                ..
+            })
+        ));
+        assert!(manager
+            .respond(&worker, &response(vcr.job_id, &kzg, &coeffs), 207, &kzg)
+            .is_err());
+    }
+
+    #[test]
+    fn wrong_point_opening_slashes() {
+        let (vcr, kzg, coeffs) = committed_vcr();
+        let worker = vcr.worker_id.clone();
+        let mut manager = ChallengeManager::default();
+        manager.open(&vcr, TRACE_LEN, 100).unwrap();
+        manager
+            .challenge(&worker, addr(1), BOND, kzg_challenge(vcr.job_id), 101)
+            .unwrap();
+
+        // A valid proof for the committed trace, but at x = 5 rather than
+        // at the challenged index 0.
+        let wrong = KzgOpeningResponse::new(vcr.job_id, vec![opening_at(&kzg, &coeffs, 0, 5)]);
+        let verdict = manager.respond(&worker, &wrong, 102, &kzg).unwrap();
+        assert!(matches!(verdict, Verdict::Slash { ref reason, .. } if reason.contains("wrong x")));
+    }
+
+    #[test]
+    fn rejects_invalid_challenges() {
+        let (vcr, _, _) = committed_vcr();
+        let worker = vcr.worker_id.clone();
+        let mut manager = ChallengeManager::new(ChallengeConfig {
+            max_points: 2,
+            ..ChallengeConfig::default()
+        });
+        assert!(manager
+            .challenge(&worker, addr(1), BOND, kzg_challenge(vcr.job_id), 0)
+            .is_err());
+        assert!(manager.open(&vcr, 0, 100).is_err());
+
+        manager.open(&vcr, TRACE_LEN, 100).unwrap();
+        assert!(manager
+            .challenge(&worker, addr(1), BOND - 1, kzg_challenge(vcr.job_id), 101)
+            .is_err());
+        assert!(manager
+            .challenge(&worker, addr(1), BOND, kzg_challenge(vcr.job_id), 111)
+            .is_err());
+        assert!(manager
+            .challenge(&[1u8; 32], addr(1), BOND, kzg_challenge(vcr.job_id), 101)
+            .is_err());
+        let mut malformed = kzg_challenge(vcr.job_id);
+        malformed.point_indices.clear();
+        assert!(manager
+            .challenge(&worker, addr(1), BOND, malformed, 101)
+            .is_err());
+
+        let mut out_of_range = kzg_challenge(vcr.job_id);
+        out_of_range.point_indices = vec![vec![TRACE_LEN]];
+        let err = manager
+            .challenge(&worker, addr(1), BOND, out_of_range, 101)
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the committed trace"));
+
+        let mut too_many = kzg_challenge(vcr.job_id);
+        too_many.point_indices = vec![vec![0, 1, 2]];
+        assert!(manager
+            .challenge(&worker, addr(1), BOND, too_many, 101)
+            .is_err());
+        assert!(manager.is_open(&vcr.job_id, &worker));
+    }
+}
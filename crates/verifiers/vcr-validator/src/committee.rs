@@ -0,0 +1,167 @@
+// ============================================================================
+// VCR COMMITTEES - Who runs a redundant job
+// ============================================================================
+// A quorum job is only as honest as the workers running it, so neither the
+// requester nor the coordinator gets to pick them. Each eligible worker draws
+//
+//   ticket = H("aether-vcr-committee" || epoch_randomness || job_id || worker_id)
+//
+// and the K lowest tickets form the committee. The epoch randomness is the
+// VRF-derived seed fixed before the epoch began, and the job id is fixed
+// when the job is posted, so the draw is known to everyone and cannot be
+// steered by either party. Reordering or padding the eligible list does not
+// change who is drawn; registering extra identities only buys more tickets.
+//
+// `VcrValidator::verify_committee_quorum` accepts a quorum only from the
+// committee drawn for its job.
+// ============================================================================
+
+use aether_types::H256;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+
+const COMMITTEE_DOMAIN: &[u8] = b"aether-vcr-committee";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Committee {
+    job_id: H256,
+    /// Members in ticket order.
+    members: Vec<Vec<u8>>,
+}
+
+impl Committee {
+    /// Draw `size` workers from `eligible` for `job_id`. Duplicate ids count
+    /// once; fails if fewer than `size` distinct workers are eligible.
+    pub fn select<'a>(
+        epoch_randomness: &H256,
+        job_id: H256,
+        eligible: impl IntoIterator<Item = &'a [u8]>,
+        size: usize,
+    ) -> Result<Self> {
+        if size == 0 {
+            bail!("committee size must be positive");
+        }
+        let eligible: BTreeSet<&[u8]> = eligible.into_iter().collect();
+        if eligible.len() < size {
+            bail!(
+                "not enough eligible workers for committee: {} < {}",
+                eligible.len(),
+                size
+            );
+        }
+        let mut tickets: Vec<([u8; 32], &[u8])> = eligible
+            .into_iter()
+            .map(|worker| (ticket(epoch_randomness, &job_id, worker), worker))
+            .collect();
+        tickets.sort_unstable();
+        Ok(Committee {
+            job_id,
+            members: tickets
+                .into_iter()
+                .take(size)
+                .map(|(_, worker)| worker.to_vec())
+                .collect(),
+        })
+    }
+
+    pub fn job_id(&self) -> H256 {
+        self.job_id
+    }
+
+    pub fn members(&self) -> &[Vec<u8>] {
+        &self.members
+    }
+
+    pub fn contains(&self, worker_id: &[u8]) -> bool {
+        self.members.iter().any(|member| member == worker_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+fn ticket(epoch_randomness: &H256, job_id: &H256, worker_id: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(COMMITTEE_DOMAIN);
+    hasher.update(epoch_randomness.as_bytes());
+    hasher.update(job_id.as_bytes());
+    hasher.update((worker_id.len() as u32).to_le_bytes());
+    hasher.update(worker_id);
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workers(n: u8) -> Vec<Vec<u8>> {
+        (0..n).map(|i| vec![i; 32]).collect()
+    }
+
+    fn h(byte: u8) -> H256 {
+        H256::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn select(randomness: u8, job: u8, eligible: &[Vec<u8>], size: usize) -> Committee {
+        Committee::select(
+            &h(randomness),
+            h(job),
+            eligible.iter().map(Vec::as_slice),
+            size,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn selection_is_deterministic_and_order_independent() {
+        let eligible = workers(20);
+        let committee = select(1, 2, &eligible, 5);
+        assert_eq!(committee.len(), 5);
+        assert_eq!(committee.job_id(), h(2));
+        assert!(committee.members().iter().all(|m| eligible.contains(m)));
+
+        let mut shuffled = eligible.clone();
+        shuffled.reverse();
+        // Duplicates do not buy extra tickets.
+        shuffled.extend(eligible.iter().take(10).cloned());
+        assert_eq!(select(1, 2, &shuffled, 5), committee);
+
+        // Each member is drawn regardless of who else is eligible.
+        let members = committee.members().to_vec();
+        assert_eq!(select(1, 2, &members, 5).members(), committee.members());
+    }
+
+    #[test]
+    fn job_and_epoch_change_the_draw() {
+        let eligible = workers(20);
+        let committee = select(1, 2, &eligible, 5);
+        assert_ne!(select(1, 3, &eligible, 5).members(), committee.members());
+        assert_ne!(select(9, 2, &eligible, 5).members(), committee.members());
+
+        // Over many jobs every worker serves, at roughly K/N of them.
+        let mut served = vec![0u32; 20];
+        for job in 0..=255u8 {
+            for member in select(1, job, &eligible, 5).members() {
+                served[member[0] as usize] += 1;
+            }
+        }
+        // Expected 64 of 256 each.
+        assert!(served.iter().all(|&n| (32..=96).contains(&n)), "{served:?}");
+    }
+
+    #[test]
+    fn rejects_impossible_sizes() {
+        let eligible = workers(3);
+        let ids = || eligible.iter().map(Vec::as_slice);
+        assert!(Committee::select(&h(1), h(2), ids(), 0).is_err());
+        assert!(Committee::select(&h(1), h(2), ids(), 4).is_err());
+        assert_eq!(Committee::select(&h(1), h(2), ids(), 3).unwrap().len(), 3);
+    }
+}
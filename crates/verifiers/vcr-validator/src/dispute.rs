@@ -0,0 +1,401 @@
+// ============================================================================
+// VCR DISPUTES - Counter-proofs and spot-check resolution
+// ============================================================================
+// A challenger who disagrees with a receipt posts a `CounterVcr`: its own
+// output hash and trace commitment for the same job, optionally backed by a
+// TEE quote. Resolution is by KZG spot check: both sides open their traces at
+// the sampled points, and the referee compares the opened values with the
+// ones it obtained by re-executing only those layers.
+//
+//   side correct = openings verify against its own commitment
+//                  AND opened values equal the referee's values
+//
+//   original correct, counter not -> OriginalUpheld
+//   counter correct, original not -> CounterUpheld
+//   neither                       -> BothInvalid
+//   both (spot check inconclusive)-> OriginalUpheld
+// ============================================================================
+
+use crate::challenge::check_opening_binding;
+use crate::{VcrValidator, VerifiableComputeReceipt};
+use aether_types::H256;
+use aether_verifiers_kzg::{KzgChallenge, KzgOpeningResponse};
+use aether_verifiers_tee::{AttestationReport, ReportDataBinding};
+use anyhow::{bail, ensure, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A challenger's alternative result for a disputed job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterVcr {
+    pub job_id: H256,
+    pub output_hash: H256,
+    pub trace_commitment: Vec<u8>,
+    /// Build hash of the challenger's worker, bound into `tee_quote`.
+    #[serde(default)]
+    pub code_hash: Vec<u8>,
+    /// JSON-encoded AttestationReport, if the challenger ran in a TEE.
+    #[serde(default)]
+    pub tee_quote: Option<Vec<u8>>,
+    /// Slot hash the challenger's quote is anchored to.
+    #[serde(default)]
+    pub slot_hash: Vec<u8>,
+}
+
+/// Openings from both sides at the sampled points, plus the referee's values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpotCheck {
+    pub challenge: KzgChallenge,
+    pub original: KzgOpeningResponse,
+    pub counter: KzgOpeningResponse,
+    /// Evaluation bytes per challenged point, in `challenge.iter_points()`
+    /// order, from re-executing the challenged layers.
+    pub expected: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisputeOutcome {
+    OriginalUpheld,
+    CounterUpheld,
+    BothInvalid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisputeResolution {
+    pub outcome: DisputeOutcome,
+    pub reason: String,
+}
+
+impl VcrValidator {
+    /// Decide a dispute between `original` and `counter`. Errors mean the
+    /// dispute itself is malformed and nothing should change hands.
+    pub fn resolve(
+        &self,
+        original: &VerifiableComputeReceipt,
+        counter: &CounterVcr,
+        spot: &SpotCheck,
+    ) -> Result<DisputeResolution> {
+        ensure!(
+            counter.job_id == original.job_id,
+            "counter-proof is for job {}, not {}",
+            counter.job_id,
+            original.job_id
+        );
+        ensure!(
+            counter.output_hash != original.output_hash
+                || counter.trace_commitment != original.trace_commitment,
+            "counter-proof does not dispute the output or trace"
+        );
+        ensure!(
+            spot.challenge.vcr_id == original.job_id,
+            "spot check was sampled for a different job"
+        );
+        spot.challenge.validate()?;
+        ensure!(
+            spot.expected.len() == spot.challenge.expected_openings(),
+            "spot check has {} reference values for {} points",
+            spot.expected.len(),
+            spot.challenge.expected_openings()
+        );
+
+        if let Some(quote) = &counter.tee_quote {
+            if let Err(e) = self.verify_counter_quote(original, counter, quote) {
+                return Ok(DisputeResolution {
+                    outcome: DisputeOutcome::OriginalUpheld,
+                    reason: format!("counter-proof TEE quote rejected: {e:#}"),
+                });
+            }
+        }
+
+        let original_check = self.spot_check(&original.trace_commitment, &spot.original, spot);
+        let counter_check = self.spot_check(&counter.trace_commitment, &spot.counter, spot);
+        let (outcome, reason) = match (original_check, counter_check) {
+            (Ok(()), Err(e)) => (DisputeOutcome::OriginalUpheld, format!("counter: {e:#}")),
+            (Err(e), Ok(())) => (DisputeOutcome::CounterUpheld, format!("original: {e:#}")),
+            (Err(a), Err(b)) => (
+                DisputeOutcome::BothInvalid,
+                format!("original: {a:#}; counter: {b:#}"),
+            ),
+            (Ok(()), Ok(())) => (
+                DisputeOutcome::OriginalUpheld,
+                "spot check inconclusive; original stands".to_string(),
+            ),
+        };
+        Ok(DisputeResolution { outcome, reason })
+    }
+
+    fn verify_counter_quote(
+        &self,
+        original: &VerifiableComputeReceipt,
+        counter: &CounterVcr,
+        quote: &[u8],
+    ) -> Result<()> {
+        let report: AttestationReport =
+            serde_json::from_slice(quote).context("invalid counter-proof TEE quote")?;
+        let report_data = ReportDataBinding {
+            job_id: original.job_id.as_bytes(),
+            input_hash: original.input_hash.as_bytes(),
+            model_hash: original.model_hash.as_bytes(),
+            code_hash: &counter.code_hash,
+            slot_hash: &counter.slot_hash,
+            seed: original.seed,
+        }
+        .report_data();
+        self.tee_verifier
+            .verify_quote(&report, &report_data, crate::current_timestamp())
+    }
+
+    /// One side's openings must be against its commitment, verify, and open
+    /// to the referee's values.
+    fn spot_check(
+        &self,
+        commitment: &[u8],
+        response: &KzgOpeningResponse,
+        spot: &SpotCheck,
+    ) -> Result<()> {
+        check_opening_binding(commitment, response)?;
+        self.trace_verifier
+            .verify_openings(&spot.challenge, response)?;
+
+        for ((layer, point), expected) in spot.challenge.iter_points().zip(&spot.expected) {
+            let opening = response
+                .openings
+                .iter()
+                .find(|o| o.layer_idx == layer && o.point_idx == point)
+                .ok_or_else(|| {
+                    anyhow::anyhow!("missing opening for layer {layer} point {point}")
+                })?;
+            if &opening.proof.evaluation != expected {
+                bail!("layer {layer} point {point} opens to a value the referee did not compute");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{TraceVerifier, WorkerRegistry};
+    use aether_crypto_kzg::{scalar_from_i64, KzgVerifier};
+    use aether_verifiers_kzg::Opening;
+    use aether_verifiers_tee::TeeVerifier;
+
+    struct Trace {
+        kzg: KzgVerifier,
+        coeffs: Vec<[u8; 32]>,
+    }
+
+    impl Trace {
+        fn new(constant: u8) -> Self {
+            let mut coeffs = vec![[0u8; 32]; 2];
+            coeffs[0][0] = constant;
+            coeffs[1][0] = 1;
+            Trace {
+                kzg: KzgVerifier::new_insecure_test(16),
+                coeffs,
+            }
+        }
+
+        fn commitment(&self) -> Vec<u8> {
+            self.kzg.commit(&self.coeffs).unwrap().commitment
+        }
+
+        fn open(&self, job_id: H256) -> KzgOpeningResponse {
+            self.open_at(job_id, 0)
+        }
+
+        /// Open trace point 0, but evaluated at `x`.
+        fn open_at(&self, job_id: H256, x: i64) -> KzgOpeningResponse {
+            let z = scalar_from_i64(x);
+            let opening = Opening {
+                layer_idx: 0,
+                point_idx: 0,
+                point: z.to_vec(),
+                commitment: self.kzg.commit(&self.coeffs).unwrap(),
+                proof: self.kzg.create_proof(&self.coeffs, &z).unwrap(),
+            };
+            KzgOpeningResponse::new(job_id, vec![opening])
+        }
+    }
+
+    fn original(trace: &Trace) -> VerifiableComputeReceipt {
+        VerifiableComputeReceipt {
+            version: crate::VCR_VERSION,
+            job_id: H256::from_slice(&[7u8; 32]).unwrap(),
+            worker_id: vec![9u8; 32],
+            model_hash: H256::zero(),
+            input_hash: H256::zero(),
+            output_hash: H256::from_slice(&[1u8; 32]).unwrap(),
+            trace_commitment: trace.commitment(),
+            trace_proof: Vec::new(),
+            trace_evaluation: Vec::new(),
+            trace_point: Vec::new(),
+            tee_attestation: Vec::new(),
+            code_hash: Vec::new(),
+            seed: 0,
+            timestamp: 0,
+            gas_used: 0,
+            extensions: Vec::new(),
+            signature: Vec::new(),
+        }
+    }
+
+    fn counter(trace: &Trace, job_id: H256) -> CounterVcr {
+        CounterVcr {
+            job_id,
+            output_hash: H256::from_slice(&[2u8; 32]).unwrap(),
+            trace_commitment: trace.commitment(),
+            code_hash: Vec::new(),
+            tee_quote: None,
+            slot_hash: Vec::new(),
+        }
+    }
+
+    /// Spot check where the referee's value is `truth`'s evaluation.
+    fn spot(job_id: H256, original: &Trace, counter: &Trace, truth: &Trace) -> SpotCheck {
+        SpotCheck {
+            challenge: KzgChallenge {
+                vcr_id: job_id,
+                layer_indices: vec![0],
+                point_indices: vec![vec![0]],
+                deadline_slot: 0,
+            },
+            original: original.open(job_id),
+            counter: counter.open(job_id),
+            expected: vec![truth.open(job_id).openings[0].proof.evaluation.clone()],
+        }
+    }
+
+    fn validator() -> VcrValidator {
+        VcrValidator::new(
+            Box::new(KzgVerifier::new_insecure_test(16)) as Box<dyn TraceVerifier>,
+            Box::new(TeeVerifier::new()),
+            WorkerRegistry::new(),
+            1,
+            10,
+        )
+    }
+
+    #[test]
+    fn referee_values_pick_the_winner() {
+        let (honest, cheat) = (Trace::new(3), Trace::new(4));
+        let validator = validator();
+
+        let vcr = original(&cheat);
+        let resolution = validator
+            .resolve(
+                &vcr,
+                &counter(&honest, vcr.job_id),
+                &spot(vcr.job_id, &cheat, &honest, &honest),
+            )
+            .unwrap();
+        assert_eq!(resolution.outcome, DisputeOutcome::CounterUpheld);
+
+        let vcr = original(&honest);
+        let resolution = validator
+            .resolve(
+                &vcr,
+                &counter(&cheat, vcr.job_id),
+                &spot(vcr.job_id, &honest, &cheat, &honest),
+            )
+            .unwrap();
+        assert_eq!(resolution.outcome, DisputeOutcome::OriginalUpheld);
+
+        let other = Trace::new(5);
+        let resolution = validator
+            .resolve(
+                &vcr,
+                &counter(&cheat, vcr.job_id),
+                &spot(vcr.job_id, &honest, &cheat, &other),
+            )
+            .unwrap();
+        assert_eq!(resolution.outcome, DisputeOutcome::BothInvalid);
+    }
+
+    #[test]
+    fn openings_must_match_each_sides_commitment() {
+        let (honest, cheat) = (Trace::new(3), Trace::new(4));
+        let validator = validator();
+        let vcr = original(&cheat);
+
+        // The original side opens the honest trace instead of its own.
+        let spot = spot(vcr.job_id, &honest, &honest, &honest);
+        let resolution = validator
+            .resolve(&vcr, &counter(&honest, vcr.job_id), &spot)
+            .unwrap();
+        assert_eq!(resolution.outcome, DisputeOutcome::CounterUpheld);
+        assert!(
+            resolution.reason.contains("committed trace"),
+            "{}",
+            resolution.reason
+        );
+    }
+
+    #[test]
+    fn openings_must_be_at_the_challenged_point() {
+        let (honest, cheat) = (Trace::new(3), Trace::new(4));
+        let validator = validator();
+        let vcr = original(&cheat);
+
+        // The original side opens its own trace, but at x = 1 where `4 + x`
+        // happens to equal the honest value at the challenged point x = 2.
+        let mut spot = spot(vcr.job_id, &cheat, &honest, &honest);
+        spot.challenge.point_indices = vec![vec![2]];
+        spot.original = cheat.open_at(vcr.job_id, 1);
+        spot.original.openings[0].point_idx = 2;
+        spot.counter = honest.open_at(vcr.job_id, 2);
+        spot.counter.openings[0].point_idx = 2;
+        spot.expected = vec![spot.counter.openings[0].proof.evaluation.clone()];
+        assert_eq!(spot.original.openings[0].proof.evaluation, spot.expected[0]);
+
+        let resolution = validator
+            .resolve(&vcr, &counter(&honest, vcr.job_id), &spot)
+            .unwrap();
+        assert_eq!(resolution.outcome, DisputeOutcome::CounterUpheld);
+        assert!(
+            resolution.reason.contains("wrong x"),
+            "{}",
+            resolution.reason
+        );
+    }
+
+    #[test]
+    fn malformed_disputes_are_errors() {
+        let (honest, cheat) = (Trace::new(3), Trace::new(4));
+        let validator = validator();
+        let vcr = original(&honest);
+        let spot = spot(vcr.job_id, &honest, &cheat, &honest);
+
+        let mut same = counter(&honest, vcr.job_id);
+        same.output_hash = vcr.output_hash;
+        assert!(validator.resolve(&vcr, &same, &spot).is_err());
+
+        let other_job = counter(&cheat, H256::zero());
+        assert!(validator.resolve(&vcr, &other_job, &spot).is_err());
+
+        let mut short = spot.clone();
+        short.expected.clear();
+        assert!(validator
+            .resolve(&vcr, &counter(&cheat, vcr.job_id), &short)
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_counter_quote_upholds_original() {
+        let (honest, cheat) = (Trace::new(3), Trace::new(4));
+        let validator = validator();
+        let vcr = original(&cheat);
+        let mut counter = counter(&honest, vcr.job_id);
+        counter.tee_quote = Some(b"not a quote".to_vec());
+
+        let resolution = validator
+            .resolve(&vcr, &counter, &spot(vcr.job_id, &cheat, &honest, &honest))
+            .unwrap();
+        assert_eq!(resolution.outcome, DisputeOutcome::OriginalUpheld);
+        assert!(
+            resolution.reason.contains("TEE quote"),
+            "{}",
+            resolution.reason
+        );
+    }
+}
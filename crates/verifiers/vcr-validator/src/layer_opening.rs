@@ -0,0 +1,98 @@
+// ============================================================================
+// LAYER OPENINGS - Every committed layer opened with one proof
+// ============================================================================
+// `trace_proof` opens one polynomial at the receipt's challenge point. A
+// receipt may also commit to each of its 8–16 layer polynomials and open all
+// of them at that same point with one `AggregatedOpening` (critical
+// extension `EXT_LAYER_OPENING`, so it is signed and cannot be dropped):
+//
+//   count u32 || commitments (48 each) || aggregated opening (bytes)
+//
+// Policies that check the trace verify it with one pairing, whatever the
+// layer count. Job escrow keeps the receipt, so the aggregate is on chain for
+// the whole challenge period.
+// ============================================================================
+
+use crate::{VcrExtension, VerifiableComputeReceipt, EXTENSION_CRITICAL};
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use aether_crypto_kzg::{AggregatedOpening, KzgCommitment, MAX_AGGREGATED_LAYERS};
+use anyhow::{ensure, Context, Result};
+
+/// Extension carrying the receipt's `LayerOpening`.
+pub const EXT_LAYER_OPENING: u16 = EXTENSION_CRITICAL | 0x0005;
+
+/// Per-layer trace commitments and their aggregated opening.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LayerOpening {
+    pub commitments: Vec<KzgCommitment>,
+    /// Opening of `commitments`, in order, at the receipt's `trace_point`.
+    pub opening: AggregatedOpening,
+}
+
+impl LayerOpening {
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::new();
+        writer.put_u32(self.commitments.len() as u32);
+        for commitment in &self.commitments {
+            writer.put_fixed(&commitment.commitment);
+        }
+        writer.put_bytes(&self.opening.encode());
+        writer.finish()
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Result<Self> {
+        let mut reader = CanonicalReader::new(data);
+        let count = reader.take_u32()? as usize;
+        ensure!(
+            (1..=MAX_AGGREGATED_LAYERS).contains(&count),
+            "layer opening must cover 1..={MAX_AGGREGATED_LAYERS} layers, got {count}"
+        );
+        let commitments = (0..count)
+            .map(|_| {
+                Ok(KzgCommitment {
+                    commitment: reader.take_fixed::<48>()?.to_vec(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let opening = AggregatedOpening::decode(reader.take_bytes()?)?;
+        reader.finish()?;
+        ensure!(
+            opening.evaluations.len() == count,
+            "{} evaluations for {count} layer commitments",
+            opening.evaluations.len()
+        );
+        Ok(LayerOpening {
+            commitments,
+            opening,
+        })
+    }
+}
+
+impl VerifiableComputeReceipt {
+    /// The aggregated layer opening recorded in the receipt, if any.
+    pub fn layer_opening(&self) -> Result<Option<LayerOpening>> {
+        self.extensions
+            .iter()
+            .find(|ext| ext.tag == EXT_LAYER_OPENING)
+            .map(|ext| LayerOpening::decode(&ext.data).context("invalid layer opening extension"))
+            .transpose()
+    }
+
+    /// Record `opening`, keeping extensions sorted. Sign afterwards.
+    pub fn set_layer_opening(&mut self, opening: &LayerOpening) {
+        let data = opening.encode();
+        match self
+            .extensions
+            .binary_search_by_key(&EXT_LAYER_OPENING, |ext| ext.tag)
+        {
+            Ok(idx) => self.extensions[idx].data = data,
+            Err(idx) => self.extensions.insert(
+                idx,
+                VcrExtension {
+                    tag: EXT_LAYER_OPENING,
+                    data,
+                },
+            ),
+        }
+    }
+}
@@ -10,22 +10,119 @@
 // 4. Metadata: Model hash, timestamp, worker ID
 //
 // VERIFICATION PROCESS:
-// 1. Check TEE attestation (worker ran in TEE)
+// 1. Check TEE attestation (worker ran in TEE, quote bound to this job)
 // 2. Verify KZG commitment (trace matches claimed output)
-// 3. Challenge mechanism (spot-check trace validity)
+// 3. Challenge mechanism (spot-check trace validity, see `challenge`)
 // 4. Worker signature verification
+//
+// SIGNATURES:
+// Workers sign SHA-256 of `canonical_preimage()`: a versioned domain tag,
+// fixed-width fields as raw bytes and every variable-length field behind a
+// u32 little-endian length, so no two receipts share a preimage.
+//
+// ENCODING:
+// `encode()` is the same canonical body followed by the signature. New
+// optional data travels in tagged extensions, which older validators keep
+// (and sign over) without understanding; tags with the critical bit set must
+// be understood or the receipt is rejected.
+//
+// POLICIES:
+// Which of the checks above apply is the receipt's `VerificationPolicy`
+// (see `policy`), bounded below per model by the validator. The signing key
+// is looked up in the validator's `WorkerRegistry`; unknown workers are
+// rejected even if the signature is internally consistent.
+//
+// TRACE ROOTS:
+// Traces longer than one KZG commitment are committed in chunks under a
+// Merkle root carried in the receipt (see `trace_root`). A receipt may also
+// open every layer at its challenge point with one aggregated proof (see
+// `layer_opening`).
+//
+// OPTIMISTIC TEE:
+// A receipt may commit to its quote instead of carrying it; the quote is
+// then checked only if a challenger posts it as a fraud proof (see
+// `optimistic`).
+//
+// COMMITTEES:
+// The workers of a quorum job are drawn from the registry by epoch
+// randomness and job id (see `committee`), and only their receipts count.
 // ============================================================================
 
-use aether_crypto_kzg::{KzgCommitment, KzgProof, KzgVerifier};
+pub mod challenge;
+pub mod committee;
+pub mod dispute;
+pub mod layer_opening;
+pub mod optimistic;
+pub mod policy;
+pub mod replay;
+pub mod trace_root;
+
+pub use challenge::{
+    ChallengeConfig, ChallengeEvent, ChallengeManager, Verdict, DEFAULT_MAX_CHALLENGE_POINTS,
+    DEFAULT_MIN_CHALLENGE_BOND,
+};
+pub use committee::Committee;
+pub use dispute::{CounterVcr, DisputeOutcome, DisputeResolution, SpotCheck};
+pub use layer_opening::{LayerOpening, EXT_LAYER_OPENING};
+pub use optimistic::{quote_commitment, QuoteStore, QuoteVerdict, EXT_QUOTE_COMMITMENT};
+pub use policy::{VerificationPolicy, EXT_VERIFICATION_POLICY};
+pub use replay::{FreshnessConfig, ReplayGuard, EXT_QUOTE_ANCHOR};
+pub use trace_root::{TraceRoot, EXT_TRACE_ROOT};
+
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use aether_crypto_kzg::{
+    verify_aggregated_opening, AggregatedOpening, KzgCommitment, KzgProof, KzgVerifier,
+};
 use aether_crypto_primitives::ed25519;
-use aether_types::H256;
-use aether_verifiers_tee::{AttestationReport, TeeVerifier};
+use aether_types::{Slot, H256};
+use aether_verifiers_kzg::{verify_kzg_openings, KzgChallenge, KzgOpeningResponse, Opening};
+use aether_verifiers_tee::{
+    verify_tee_quote, AttestationReport, ReportDataBinding, TeeVerifier, REPORT_DATA_LEN,
+};
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Domain tag at the start of every VCR signing preimage.
+pub const VCR_DOMAIN: &[u8] = b"AETHER-VCR-v2";
+
+/// Newest receipt format this validator understands.
+pub const VCR_VERSION: u16 = 1;
+
+/// Extension tags with this bit set must be understood by the validator.
+pub const EXTENSION_CRITICAL: u16 = 0x8000;
+
+/// Extension tags this validator interprets.
+const KNOWN_EXTENSIONS: &[u16] = &[
+    EXT_VERIFICATION_POLICY,
+    EXT_QUOTE_ANCHOR,
+    EXT_QUOTE_COMMITMENT,
+    EXT_TRACE_ROOT,
+    EXT_LAYER_OPENING,
+];
+
+/// Tagged data added to the receipt format after version 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VcrExtension {
+    pub tag: u16,
+    pub data: Vec<u8>,
+}
+
+impl VcrExtension {
+    pub fn is_critical(&self) -> bool {
+        self.tag & EXTENSION_CRITICAL != 0
+    }
+}
+
+fn default_version() -> u16 {
+    VCR_VERSION
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerifiableComputeReceipt {
+    #[serde(default = "default_version")]
+    pub version: u16,
     pub job_id: H256,
     pub worker_id: Vec<u8>,
     pub model_hash: H256,
@@ -39,10 +136,119 @@ pub struct VerifiableComputeReceipt {
     #[serde(default)]
     pub trace_point: Vec<u8>, // Challenge point (32 bytes)
     pub tee_attestation: Vec<u8>,  // JSON-encoded AttestationReport
+    #[serde(default)]
+    pub code_hash: Vec<u8>, // Worker build hash bound into the quote
+    #[serde(default)]
+    pub seed: u64, // Job seed bound into the quote
     pub timestamp: u64,
+    #[serde(default)]
+    pub gas_used: u64, // Metered gas reported by the worker
+    #[serde(default)]
+    pub extensions: Vec<VcrExtension>, // Sorted by tag, no duplicates
     pub signature: Vec<u8>, // Ed25519 signature from worker public key
 }
 
+/// Checks a worker's TEE quote against the report data recomputed from the
+/// receipt.
+pub trait AttestationVerifier: Send + Sync {
+    fn verify_quote(
+        &self,
+        report: &AttestationReport,
+        expected_report_data: &[u8; REPORT_DATA_LEN],
+        current_time: u64,
+    ) -> Result<()>;
+}
+
+impl AttestationVerifier for TeeVerifier {
+    fn verify_quote(
+        &self,
+        report: &AttestationReport,
+        expected_report_data: &[u8; REPORT_DATA_LEN],
+        current_time: u64,
+    ) -> Result<()> {
+        verify_tee_quote(self, report, expected_report_data, current_time)
+    }
+}
+
+/// Checks sampled openings of a receipt's trace commitment.
+pub trait TraceVerifier: Send + Sync {
+    fn verify_openings(
+        &self,
+        challenge: &KzgChallenge,
+        response: &KzgOpeningResponse,
+    ) -> Result<()>;
+
+    /// Check one opening of every layer in `commitments` at a shared point.
+    fn verify_aggregated(
+        &self,
+        commitments: &[KzgCommitment],
+        opening: &AggregatedOpening,
+    ) -> Result<()>;
+}
+
+impl TraceVerifier for KzgVerifier {
+    fn verify_openings(
+        &self,
+        challenge: &KzgChallenge,
+        response: &KzgOpeningResponse,
+    ) -> Result<()> {
+        verify_kzg_openings(self, challenge, response).map_err(Into::into)
+    }
+
+    fn verify_aggregated(
+        &self,
+        commitments: &[KzgCommitment],
+        opening: &AggregatedOpening,
+    ) -> Result<()> {
+        if !verify_aggregated_opening(self, commitments, opening)? {
+            bail!("aggregated opening does not verify");
+        }
+        Ok(())
+    }
+}
+
+/// Ed25519 signing keys of admitted workers, by worker ID.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerRegistry {
+    keys: HashMap<Vec<u8>, [u8; 32]>,
+}
+
+impl WorkerRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admit a worker, or rotate the key of an admitted one.
+    pub fn register(&mut self, worker_id: Vec<u8>, public_key: &[u8]) -> Result<()> {
+        let key: [u8; 32] = public_key
+            .try_into()
+            .context("worker public key must be 32 bytes")?;
+        self.keys.insert(worker_id, key);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, worker_id: &[u8]) -> bool {
+        self.keys.remove(worker_id).is_some()
+    }
+
+    pub fn public_key(&self, worker_id: &[u8]) -> Option<&[u8; 32]> {
+        self.keys.get(worker_id)
+    }
+
+    /// Admitted worker ids, in no particular order.
+    pub fn worker_ids(&self) -> impl Iterator<Item = &[u8]> {
+        self.keys.keys().map(Vec::as_slice)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
 pub struct VcrValidator {
     /// Minimum quorum size for consensus
     quorum_size: usize,
@@ -51,18 +257,25 @@ pub struct VcrValidator {
     challenge_window: u64,
 
     /// TEE attestation verifier
-    tee_verifier: TeeVerifier,
+    tee_verifier: Box<dyn AttestationVerifier>,
 
     /// KZG verifier for trace checks
-    kzg_verifier: KzgVerifier,
+    trace_verifier: Box<dyn TraceVerifier>,
+
+    /// Keys receipts must be signed with
+    workers: WorkerRegistry,
+
+    /// Minimum policy per model; others need `VerificationPolicy::default()`
+    model_policies: HashMap<H256, VerificationPolicy>,
 }
 
 impl VcrValidator {
     /// Create a VCR validator with explicit configuration.
     /// Use `new_for_test()` for development/testing only.
     pub fn new(
-        kzg_verifier: KzgVerifier,
-        tee_verifier: TeeVerifier,
+        trace_verifier: Box<dyn TraceVerifier>,
+        tee_verifier: Box<dyn AttestationVerifier>,
+        workers: WorkerRegistry,
         quorum_size: usize,
         challenge_window: u64,
     ) -> Self {
@@ -70,45 +283,132 @@ impl VcrValidator {
             quorum_size,
             challenge_window,
             tee_verifier,
-            kzg_verifier,
+            trace_verifier,
+            workers,
+            model_policies: HashMap::new(),
         }
     }
 
     /// Create a VCR validator for development/testing with insecure defaults.
     /// WARNING: Do NOT use in production — uses test KZG parameters and
-    /// accepts the default simulation TEE measurement.
+    /// accepts devnet-signed simulated quotes with the default simulation
+    /// measurement. Workers still have to be admitted with `register_worker`.
     pub fn new_for_test() -> Self {
         let mut tee_verifier = TeeVerifier::new();
         tee_verifier.add_approved_measurement(vec![1u8; 48]);
+        tee_verifier.enable_dev_mode();
 
         VcrValidator {
             quorum_size: 3,
             challenge_window: 10,
-            tee_verifier,
-            kzg_verifier: KzgVerifier::new_insecure_test(1024),
+            tee_verifier: Box::new(tee_verifier),
+            trace_verifier: Box::new(KzgVerifier::new_insecure_test(1024)),
+            workers: WorkerRegistry::new(),
+            model_policies: HashMap::new(),
         }
     }
 
-    pub fn approve_measurement(&mut self, measurement: Vec<u8>) {
-        self.tee_verifier.add_approved_measurement(measurement);
+    pub fn register_worker(&mut self, worker_id: Vec<u8>, public_key: &[u8]) -> Result<()> {
+        self.workers.register(worker_id, public_key)
+    }
+
+    pub fn workers(&self) -> &WorkerRegistry {
+        &self.workers
     }
 
-    /// Verify a single VCR
+    /// Set the weakest policy receipts for `model_hash` may use.
+    pub fn set_model_policy(&mut self, model_hash: H256, policy: VerificationPolicy) {
+        self.model_policies.insert(model_hash, policy);
+    }
+
+    pub fn required_policy(&self, model_hash: &H256) -> VerificationPolicy {
+        self.model_policies
+            .get(model_hash)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Draw the quorum-size committee for `job_id` from the registered
+    /// workers.
+    pub fn select_committee(&self, epoch_randomness: &H256, job_id: H256) -> Result<Committee> {
+        Committee::select(
+            epoch_randomness,
+            job_id,
+            self.workers.worker_ids(),
+            self.quorum_size,
+        )
+    }
+
+    /// Verify a quorum whose receipts must all come from `committee`, for
+    /// the committee's job.
+    pub fn verify_committee_quorum(
+        &self,
+        vcrs: &[VerifiableComputeReceipt],
+        committee: &Committee,
+    ) -> Result<()> {
+        for vcr in vcrs {
+            if vcr.job_id != committee.job_id() {
+                bail!("VCR is for a different job than the committee");
+            }
+            if !committee.contains(&vcr.worker_id) {
+                bail!("worker is not on the committee for this job");
+            }
+        }
+        self.verify_quorum(vcrs)
+    }
+
+    /// Verify a single VCR. Receipts whose policy requires a quorum are
+    /// rejected here and must go through `verify_quorum`.
     pub fn verify(&self, vcr: &VerifiableComputeReceipt) -> Result<()> {
-        // 1. Verify basic fields
+        let policy = self.check_policy(vcr)?;
+        if policy.requires_quorum() {
+            bail!("VCR policy {policy:?} requires quorum verification");
+        }
+        self.verify_receipt(vcr, policy)
+    }
+
+    /// Format checks, and the receipt's policy if it is allowed for its model.
+    fn check_policy(&self, vcr: &VerifiableComputeReceipt) -> Result<VerificationPolicy> {
         if vcr.worker_id.len() != 32 {
             bail!("worker ID must be a 32-byte ed25519 public key");
         }
+        vcr.check_format()?;
+        let policy = vcr.policy()?;
+        let required = self.required_policy(&vcr.model_hash);
+        if !policy.covers(required) {
+            bail!("VCR policy {policy:?} is weaker than {required:?} required for this model");
+        }
+        Ok(policy)
+    }
 
-        // 2. Verify TEE attestation
-        self.verify_attestation(vcr)?;
-
-        // 3. Verify KZG commitment opening
-        self.verify_trace_opening(vcr)?;
-
-        // 4. Verify worker signature
-        self.verify_signature(vcr)?;
+    /// The per-receipt checks `policy` calls for.
+    fn verify_receipt(
+        &self,
+        vcr: &VerifiableComputeReceipt,
+        policy: VerificationPolicy,
+    ) -> Result<()> {
+        if policy.requires_tee() {
+            self.verify_attestation(vcr)?;
+        }
+        if policy.requires_trace() {
+            self.verify_trace_opening(vcr)?;
+            self.verify_layer_opening(vcr)?;
+        }
+        self.verify_signature(vcr)
+    }
 
+    /// Verify a VCR submitted for settlement: fresh, not seen before for
+    /// this (job, worker) pair, and valid. Only valid receipts are recorded,
+    /// so a forged receipt cannot block the real one.
+    pub fn verify_unique(
+        &self,
+        vcr: &VerifiableComputeReceipt,
+        guard: &mut ReplayGuard,
+        current_slot: Slot,
+    ) -> Result<()> {
+        guard.check(vcr, current_slot, current_timestamp())?;
+        self.verify(vcr)?;
+        guard.record(vcr, current_slot);
         Ok(())
     }
 
@@ -164,7 +464,8 @@ impl VcrValidator {
             if vcr.output_hash != majority_output {
                 continue;
             }
-            self.verify(vcr)?;
+            let policy = self.check_policy(vcr)?;
+            self.verify_receipt(vcr, policy)?;
             verified_count += 1;
         }
 
@@ -185,39 +486,39 @@ impl VcrValidator {
             .context("invalid tee_attestation payload (expected JSON AttestationReport)")?;
         let now = current_timestamp();
         self.tee_verifier
-            .verify(&report, now)
+            .verify_quote(&report, &vcr.report_data(), now)
             .context("TEE attestation verification failed")
     }
 
     fn verify_trace_opening(&self, vcr: &VerifiableComputeReceipt) -> Result<()> {
-        let commitment = KzgCommitment {
-            commitment: vcr.trace_commitment.clone(),
-        };
-        let proof = KzgProof {
-            proof: vcr.trace_proof.clone(),
-            evaluation: vcr.trace_evaluation.clone(),
-        };
+        let (challenge, response) = vcr.trace_opening();
+        self.trace_verifier
+            .verify_openings(&challenge, &response)
+            .context("KZG trace proof verification failed")
+    }
 
-        let point: [u8; 32] = vcr
-            .trace_point
-            .as_slice()
-            .try_into()
-            .context("trace_point must be 32 bytes")?;
-        let valid = self
-            .kzg_verifier
-            .verify(&commitment, &proof, &point)
-            .context("KZG trace proof verification failed")?;
-        anyhow::ensure!(valid, "KZG trace proof verification returned false");
-        Ok(())
+    /// Every layer must open at the receipt's own challenge point.
+    fn verify_layer_opening(&self, vcr: &VerifiableComputeReceipt) -> Result<()> {
+        let Some(layers) = vcr.layer_opening()? else {
+            return Ok(());
+        };
+        if layers.opening.point.as_slice() != vcr.trace_point.as_slice() {
+            bail!("layer opening is not at the receipt's trace point");
+        }
+        self.trace_verifier
+            .verify_aggregated(&layers.commitments, &layers.opening)
+            .context("aggregated layer opening failed")
     }
 
     fn verify_signature(&self, vcr: &VerifiableComputeReceipt) -> Result<()> {
+        let Some(public_key) = self.workers.public_key(&vcr.worker_id) else {
+            bail!("worker {} is not registered", hex_prefix(&vcr.worker_id));
+        };
         if vcr.signature.is_empty() {
             bail!("empty signature");
         }
 
-        let message = vcr.signing_message()?;
-        ed25519::verify(&vcr.worker_id, &message, &vcr.signature)
+        ed25519::verify(public_key, &vcr.signing_message(), &vcr.signature)
             .map_err(|e| anyhow::anyhow!("signature verification failed: {e}"))
     }
 
@@ -231,23 +532,169 @@ impl VcrValidator {
 }
 
 impl VerifiableComputeReceipt {
-    /// Compute the deterministic signing message using direct hash construction.
-    /// This avoids bincode's non-canonical serialization which could differ across versions.
-    fn signing_message(&self) -> Result<Vec<u8>> {
-        let mut hasher = Sha256::new();
-        hasher.update(b"VCR-v1"); // Version domain separator
-        hasher.update(self.job_id.as_bytes());
-        hasher.update(&self.worker_id);
-        hasher.update(self.model_hash.as_bytes());
-        hasher.update(self.input_hash.as_bytes());
-        hasher.update(self.output_hash.as_bytes());
-        hasher.update(&self.trace_commitment);
-        hasher.update(&self.trace_proof);
-        hasher.update(&self.trace_evaluation);
-        hasher.update(&self.trace_point);
-        hasher.update(&self.tee_attestation);
-        hasher.update(self.timestamp.to_le_bytes());
-        Ok(hasher.finalize().to_vec())
+    /// Quote `report_data` a worker must have produced for this receipt.
+    pub fn report_data(&self) -> [u8; REPORT_DATA_LEN] {
+        ReportDataBinding {
+            job_id: self.job_id.as_bytes(),
+            input_hash: self.input_hash.as_bytes(),
+            model_hash: self.model_hash.as_bytes(),
+            code_hash: &self.code_hash,
+            slot_hash: self.quote_slot_hash(),
+            seed: self.seed,
+        }
+        .report_data()
+    }
+
+    /// The receipt's trace opening as a single-point challenge and response
+    /// for the KZG opening verifier.
+    pub fn trace_opening(&self) -> (KzgChallenge, KzgOpeningResponse) {
+        let challenge = KzgChallenge {
+            vcr_id: self.job_id,
+            layer_indices: vec![0],
+            point_indices: vec![vec![0]],
+            deadline_slot: 0,
+        };
+        let opening = Opening {
+            layer_idx: 0,
+            point_idx: 0,
+            point: self.trace_point.clone(),
+            commitment: KzgCommitment {
+                commitment: self.trace_commitment.clone(),
+            },
+            proof: KzgProof {
+                proof: self.trace_proof.clone(),
+                evaluation: self.trace_evaluation.clone(),
+            },
+        };
+        (
+            challenge,
+            KzgOpeningResponse::new(self.job_id, vec![opening]),
+        )
+    }
+
+    /// Canonical byte encoding of every field except the signature.
+    pub fn canonical_preimage(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::with_capacity(320 + self.tee_attestation.len());
+        writer.put_fixed(VCR_DOMAIN);
+        self.write_body(&mut writer);
+        writer.finish()
+    }
+
+    fn write_body(&self, writer: &mut CanonicalWriter) {
+        writer
+            .put_u16(self.version)
+            .put_fixed(self.job_id.as_bytes())
+            .put_bytes(&self.worker_id)
+            .put_fixed(self.model_hash.as_bytes())
+            .put_fixed(self.input_hash.as_bytes())
+            .put_fixed(self.output_hash.as_bytes())
+            .put_bytes(&self.trace_commitment)
+            .put_bytes(&self.trace_proof)
+            .put_bytes(&self.trace_evaluation)
+            .put_bytes(&self.trace_point)
+            .put_bytes(&self.tee_attestation)
+            .put_bytes(&self.code_hash)
+            .put_u64(self.seed)
+            .put_u64(self.timestamp)
+            .put_u64(self.gas_used)
+            .put_u32(self.extensions.len() as u32);
+        for extension in &self.extensions {
+            writer.put_u16(extension.tag).put_bytes(&extension.data);
+        }
+    }
+
+    /// Canonical binary encoding: the signed body followed by the signature.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::with_capacity(320 + self.tee_attestation.len());
+        self.write_body(&mut writer);
+        writer.put_bytes(&self.signature);
+        writer.finish()
+    }
+
+    /// Decode `encode()` output. Unknown optional extensions are kept so the
+    /// signature still covers them; unknown critical ones are rejected.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = CanonicalReader::new(bytes);
+        let h256 =
+            |reader: &mut CanonicalReader| -> Result<H256> { Ok(H256(reader.take_fixed()?)) };
+        let version = reader.take_u16()?;
+        if version == 0 || version > VCR_VERSION {
+            bail!("unsupported VCR version {version} (max {VCR_VERSION})");
+        }
+        let mut vcr = VerifiableComputeReceipt {
+            version,
+            job_id: h256(&mut reader)?,
+            worker_id: reader.take_bytes()?.to_vec(),
+            model_hash: h256(&mut reader)?,
+            input_hash: h256(&mut reader)?,
+            output_hash: h256(&mut reader)?,
+            trace_commitment: reader.take_bytes()?.to_vec(),
+            trace_proof: reader.take_bytes()?.to_vec(),
+            trace_evaluation: reader.take_bytes()?.to_vec(),
+            trace_point: reader.take_bytes()?.to_vec(),
+            tee_attestation: reader.take_bytes()?.to_vec(),
+            code_hash: reader.take_bytes()?.to_vec(),
+            seed: reader.take_u64()?,
+            timestamp: reader.take_u64()?,
+            gas_used: reader.take_u64()?,
+            extensions: Vec::new(),
+            signature: Vec::new(),
+        };
+        let count = reader.take_u32()?;
+        for _ in 0..count {
+            vcr.extensions.push(VcrExtension {
+                tag: reader.take_u16()?,
+                data: reader.take_bytes()?.to_vec(),
+            });
+        }
+        vcr.signature = reader.take_bytes()?.to_vec();
+        reader.finish()?;
+        vcr.check_format()?;
+        Ok(vcr)
+    }
+
+    /// Version and extension rules shared by decoding and verification.
+    fn check_format(&self) -> Result<()> {
+        if self.version == 0 || self.version > VCR_VERSION {
+            bail!(
+                "unsupported VCR version {} (max {VCR_VERSION})",
+                self.version
+            );
+        }
+        for pair in self.extensions.windows(2) {
+            if pair[0].tag >= pair[1].tag {
+                bail!("VCR extensions must be sorted by tag without duplicates");
+            }
+        }
+        if let Some(ext) = self
+            .extensions
+            .iter()
+            .find(|ext| ext.is_critical() && !KNOWN_EXTENSIONS.contains(&ext.tag))
+        {
+            bail!("unknown critical VCR extension {:#06x}", ext.tag);
+        }
+        if self
+            .extensions
+            .iter()
+            .any(|ext| ext.tag == EXT_QUOTE_ANCHOR && ext.data.len() != 32)
+        {
+            bail!("quote anchor extension must be a 32-byte slot hash");
+        }
+        if self
+            .extensions
+            .iter()
+            .any(|ext| ext.tag == EXT_QUOTE_COMMITMENT && ext.data.len() != 32)
+        {
+            bail!("quote commitment extension must be a 32-byte hash");
+        }
+        self.trace_root()?;
+        self.layer_opening()?;
+        Ok(())
+    }
+
+    /// The 32-byte message the worker signs: SHA-256 of the canonical preimage.
+    pub fn signing_message(&self) -> Vec<u8> {
+        Sha256::digest(self.canonical_preimage()).to_vec()
     }
 }
 
@@ -257,6 +704,10 @@ impl Default for VcrValidator {
     }
 }
 
+fn hex_prefix(bytes: &[u8]) -> String {
+    bytes.iter().take(8).map(|b| format!("{b:02x}")).collect()
+}
+
 fn current_timestamp() -> u64 {
     use std::time::{SystemTime, UNIX_EPOCH};
     SystemTime::now()
@@ -269,18 +720,20 @@ fn current_timestamp() -> u64 {
 mod tests {
     use super::*;
     use aether_crypto_primitives::Keypair;
-    use aether_verifiers_tee::TeeType;
+    use aether_verifiers_tee::SimulatedSigner;
 
-    fn create_test_vcr(worker: &Keypair, output: u8) -> VerifiableComputeReceipt {
-        let report = AttestationReport {
-            tee_type: TeeType::Simulation,
-            measurement: vec![1u8; 48],
-            nonce: vec![2u8; 32],
-            timestamp: current_timestamp(),
-            signature: vec![3u8; 64],
-            cert_chain: vec![vec![4u8; 16]],
-        };
+    /// Test validator with every receipt's worker admitted under its own key.
+    fn validator_for(vcrs: &[VerifiableComputeReceipt]) -> VcrValidator {
+        let mut validator = VcrValidator::new_for_test();
+        for vcr in vcrs {
+            validator
+                .register_worker(vcr.worker_id.clone(), &vcr.worker_id)
+                .ok();
+        }
+        validator
+    }
 
+    fn create_test_vcr(worker: &Keypair, output: u8) -> VerifiableComputeReceipt {
         // Create valid KZG commitment/proof using the real verifier
         let kzg = aether_crypto_kzg::KzgVerifier::new_insecure_test(16);
         let mut coeffs = [[0u8; 32]; 2];
@@ -292,6 +745,7 @@ mod tests {
         let proof = kzg.create_proof(&coeffs, &z).unwrap();
 
         let mut vcr = VerifiableComputeReceipt {
+            version: VCR_VERSION,
             job_id: H256::zero(),
             worker_id: worker.public_key(),
             model_hash: H256::zero(),
@@ -301,29 +755,35 @@ mod tests {
             trace_proof: proof.proof,
             trace_evaluation: proof.evaluation,
             trace_point: z.to_vec(),
-            tee_attestation: serde_json::to_vec(&report).unwrap(),
+            tee_attestation: Vec::new(),
+            code_hash: vec![3u8; 32],
+            seed: 7,
             timestamp: current_timestamp(),
+            gas_used: 1_000,
+            extensions: Vec::new(),
             signature: Vec::new(),
         };
 
-        let msg = vcr.signing_message().unwrap();
+        let report = SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &vcr.report_data(), vcr.timestamp)
+            .unwrap();
+        vcr.tee_attestation = serde_json::to_vec(&report).unwrap();
+        let msg = vcr.signing_message();
         vcr.signature = worker.sign(&msg);
         vcr
     }
 
     #[test]
     fn test_verify_single_vcr() {
-        let validator = VcrValidator::new_for_test();
         let worker = Keypair::generate();
         let vcr = create_test_vcr(&worker, 5);
 
+        let validator = validator_for(std::slice::from_ref(&vcr));
         assert!(validator.verify(&vcr).is_ok());
     }
 
     #[test]
     fn test_quorum_consensus() {
-        let validator = VcrValidator::new_for_test();
-
         // 3 workers, all agree
         let vcrs = vec![
             create_test_vcr(&Keypair::generate(), 5),
@@ -331,26 +791,24 @@ mod tests {
             create_test_vcr(&Keypair::generate(), 5),
         ];
 
+        let validator = validator_for(&vcrs);
         assert!(validator.verify_quorum(&vcrs).is_ok());
     }
 
     #[test]
     fn test_insufficient_quorum() {
-        let validator = VcrValidator::new_for_test();
-
         // Only 2 workers (need 3)
         let vcrs = vec![
             create_test_vcr(&Keypair::generate(), 5),
             create_test_vcr(&Keypair::generate(), 5),
         ];
 
+        let validator = validator_for(&vcrs);
         assert!(validator.verify_quorum(&vcrs).is_err());
     }
 
     #[test]
     fn test_no_consensus() {
-        let validator = VcrValidator::new_for_test();
-
         // 3 workers, no agreement
         let vcrs = vec![
             create_test_vcr(&Keypair::generate(), 5),
@@ -358,13 +816,12 @@ mod tests {
             create_test_vcr(&Keypair::generate(), 7),
         ];
 
+        let validator = validator_for(&vcrs);
         assert!(validator.verify_quorum(&vcrs).is_err());
     }
 
     #[test]
     fn test_mismatched_job_ids() {
-        let validator = VcrValidator::new_for_test();
-
         let mut vcrs = vec![
             create_test_vcr(&Keypair::generate(), 5),
             create_test_vcr(&Keypair::generate(), 5),
@@ -374,6 +831,7 @@ mod tests {
         // Change job_id of second VCR
         vcrs[1].job_id = H256::from_slice(&[1u8; 32]).unwrap();
 
+        let validator = validator_for(&vcrs);
         assert!(validator.verify_quorum(&vcrs).is_err());
     }
 
@@ -381,7 +839,6 @@ mod tests {
     fn test_quorum_not_poisoned_by_dissenter() {
         // A dissenting VCR with an invalid signature should NOT cause the
         // quorum to fail — only majority-agreeing VCRs are verified.
-        let validator = VcrValidator::new_for_test();
 
         let mut vcrs = vec![
             create_test_vcr(&Keypair::generate(), 5), // agrees
@@ -395,6 +852,7 @@ mod tests {
         vcrs.push(bad_vcr);
 
         // Should succeed — the bad dissenter is ignored
+        let validator = validator_for(&vcrs);
         assert!(
             validator.verify_quorum(&vcrs).is_ok(),
             "valid quorum should not be poisoned by invalid dissenter"
@@ -403,7 +861,6 @@ mod tests {
 
     #[test]
     fn test_quorum_rejects_sybil_duplicate_workers() {
-        let validator = VcrValidator::new_for_test();
         let worker = Keypair::generate();
 
         // Same worker submits 3 identical VCRs — Sybil attack
@@ -413,6 +870,7 @@ mod tests {
             create_test_vcr(&worker, 5),
         ];
 
+        let validator = validator_for(&vcrs);
         let err = validator.verify_quorum(&vcrs).unwrap_err();
         assert!(
             err.to_string().contains("duplicate worker"),
@@ -421,11 +879,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_committee_quorum_rejects_handpicked_workers() {
+        let workers: Vec<Keypair> = (0..8).map(|_| Keypair::generate()).collect();
+        let vcrs: Vec<VerifiableComputeReceipt> =
+            workers.iter().map(|w| create_test_vcr(w, 5)).collect();
+        let validator = validator_for(&vcrs);
+
+        let randomness = H256::from_slice(&[7u8; 32]).unwrap();
+        let committee = validator
+            .select_committee(&randomness, H256::zero())
+            .unwrap();
+        assert_eq!(committee.len(), 3);
+        let (drawn, others): (Vec<_>, Vec<_>) = vcrs
+            .iter()
+            .cloned()
+            .partition(|vcr| committee.contains(&vcr.worker_id));
+        assert!(validator
+            .verify_committee_quorum(&drawn, &committee)
+            .is_ok());
+
+        // Workers off the committee cannot stand in, even with valid receipts.
+        assert!(validator.verify_quorum(&others[..3]).is_ok());
+        let err = validator
+            .verify_committee_quorum(&others[..3], &committee)
+            .unwrap_err();
+        assert!(err.to_string().contains("not on the committee"), "{err}");
+
+        let other_job = validator
+            .select_committee(&randomness, H256::from_slice(&[1u8; 32]).unwrap())
+            .unwrap();
+        assert!(validator
+            .verify_committee_quorum(&drawn, &other_job)
+            .is_err());
+    }
+
     #[test]
     fn test_quorum_finds_true_majority() {
         // If vcrs[0] is in the minority, the quorum should still find
         // and use the actual majority output.
-        let validator = VcrValidator::new_for_test();
 
         let vcrs = vec![
             create_test_vcr(&Keypair::generate(), 99), // minority (first!)
@@ -434,6 +926,7 @@ mod tests {
             create_test_vcr(&Keypair::generate(), 5),  // majority
         ];
 
+        let validator = validator_for(&vcrs);
         assert!(
             validator.verify_quorum(&vcrs).is_ok(),
             "should succeed using the actual majority, not vcrs[0]"
@@ -442,33 +935,534 @@ mod tests {
 
     #[test]
     fn test_rejects_bad_signature() {
-        let validator = VcrValidator::new_for_test();
         let worker = Keypair::generate();
         let mut vcr = create_test_vcr(&worker, 5);
         vcr.signature[0] ^= 0x01;
 
+        let validator = validator_for(std::slice::from_ref(&vcr));
         assert!(validator.verify(&vcr).is_err());
     }
+
+    #[test]
+    fn test_rejects_tampered_fields() {
+        let worker = Keypair::generate();
+        let vcr = create_test_vcr(&worker, 5);
+        let validator = validator_for(std::slice::from_ref(&vcr));
+        validator.verify_signature(&vcr).unwrap();
+
+        type Tamper = fn(&mut VerifiableComputeReceipt);
+        let tampers: Vec<(&str, Tamper)> = vec![
+            ("job_id", |v| {
+                v.job_id = H256::from_slice(&[9u8; 32]).unwrap()
+            }),
+            ("model_hash", |v| {
+                v.model_hash = H256::from_slice(&[9u8; 32]).unwrap()
+            }),
+            ("input_hash", |v| {
+                v.input_hash = H256::from_slice(&[9u8; 32]).unwrap()
+            }),
+            ("output_hash", |v| {
+                v.output_hash = H256::from_slice(&[9u8; 32]).unwrap()
+            }),
+            ("trace_commitment", |v| v.trace_commitment[0] ^= 1),
+            ("trace_proof", |v| v.trace_proof[0] ^= 1),
+            ("trace_evaluation", |v| v.trace_evaluation[0] ^= 1),
+            ("trace_point", |v| v.trace_point[0] ^= 1),
+            ("tee_attestation", |v| v.tee_attestation.push(b' ')),
+            ("timestamp", |v| v.timestamp += 1),
+            ("gas_used", |v| v.gas_used += 1),
+        ];
+        for (field, tamper) in tampers {
+            let mut tampered = vcr.clone();
+            tamper(&mut tampered);
+            let err = validator.verify_signature(&tampered).unwrap_err();
+            assert!(
+                err.to_string().contains("signature verification failed"),
+                "tampered {field}: {err}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_unregistered_and_rotated_workers() {
+        let worker = Keypair::generate();
+        let vcr = create_test_vcr(&worker, 5);
+
+        let err = VcrValidator::new_for_test().verify(&vcr).unwrap_err();
+        assert!(err.to_string().contains("not registered"), "{err}");
+
+        // A rotated key no longer accepts receipts signed with the old one.
+        let mut validator = validator_for(std::slice::from_ref(&vcr));
+        validator
+            .register_worker(vcr.worker_id.clone(), &Keypair::generate().public_key())
+            .unwrap();
+        assert!(validator.verify(&vcr).is_err());
+
+        assert!(validator
+            .register_worker(vcr.worker_id.clone(), &[0u8; 31])
+            .is_err());
+    }
+
+    #[test]
+    fn test_preimage_is_unambiguous() {
+        let vcr = create_test_vcr(&Keypair::generate(), 5);
+        assert!(vcr.canonical_preimage().starts_with(VCR_DOMAIN));
+
+        // Shifting a byte across a field boundary keeps the concatenation the
+        // same but must change the preimage.
+        let mut shifted = vcr.clone();
+        let byte = shifted.trace_evaluation.remove(0);
+        shifted.trace_proof.push(byte);
+        assert_ne!(vcr.canonical_preimage(), shifted.canonical_preimage());
+        assert_ne!(vcr.signing_message(), shifted.signing_message());
+    }
+
+    /// Accepts every quote, remembering the report data it was asked about.
+    #[derive(Clone, Default)]
+    struct RecordingTee(std::sync::Arc<std::sync::Mutex<Vec<[u8; REPORT_DATA_LEN]>>>);
+
+    impl AttestationVerifier for RecordingTee {
+        fn verify_quote(
+            &self,
+            _report: &AttestationReport,
+            expected_report_data: &[u8; REPORT_DATA_LEN],
+            _current_time: u64,
+        ) -> Result<()> {
+            self.0.lock().unwrap().push(*expected_report_data);
+            Ok(())
+        }
+    }
+
+    struct RejectingTrace;
+
+    impl TraceVerifier for RejectingTrace {
+        fn verify_openings(&self, _: &KzgChallenge, response: &KzgOpeningResponse) -> Result<()> {
+            bail!("rejected {} openings", response.openings.len())
+        }
+
+        fn verify_aggregated(
+            &self,
+            commitments: &[KzgCommitment],
+            _: &AggregatedOpening,
+        ) -> Result<()> {
+            bail!("rejected {} layers", commitments.len())
+        }
+    }
+
+    #[test]
+    fn test_injected_verifiers() {
+        let worker = Keypair::generate();
+        let vcr = create_test_vcr(&worker, 5);
+        let mut workers = WorkerRegistry::new();
+        workers
+            .register(vcr.worker_id.clone(), &worker.public_key())
+            .unwrap();
+
+        let tee = RecordingTee::default();
+        let validator = VcrValidator::new(
+            Box::new(RejectingTrace),
+            Box::new(tee.clone()),
+            workers,
+            1,
+            10,
+        );
+        let err = validator.verify(&vcr).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("rejected 1 openings"),
+            "{err:#}"
+        );
+        assert_eq!(*tee.0.lock().unwrap(), vec![vcr.report_data()]);
+    }
+
+    #[test]
+    fn test_rejects_quote_bound_to_other_job() {
+        let worker = Keypair::generate();
+        let mut vcr = create_test_vcr(&worker, 5);
+        let validator = validator_for(std::slice::from_ref(&vcr));
+
+        // Re-signed, so only the report-data binding can fail.
+        vcr.seed += 1;
+        vcr.signature = worker.sign(&vcr.signing_message());
+        let err = validator.verify(&vcr).unwrap_err();
+        assert!(format!("{err:#}").contains("report_data"), "{err:#}");
+    }
+
+    /// `create_test_vcr` with its quote anchored to `slot_hash`.
+    fn anchored_vcr(worker: &Keypair, job: u8, slot_hash: H256) -> VerifiableComputeReceipt {
+        let mut vcr = create_test_vcr(worker, 5);
+        vcr.job_id = H256::from_slice(&[job; 32]).unwrap();
+        vcr.set_quote_slot_hash(slot_hash);
+        let report = SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &vcr.report_data(), vcr.timestamp)
+            .unwrap();
+        vcr.tee_attestation = serde_json::to_vec(&report).unwrap();
+        vcr.signature = worker.sign(&vcr.signing_message());
+        vcr
+    }
+
+    #[test]
+    fn test_verify_unique_rejects_resubmission() {
+        let worker = Keypair::generate();
+        let slot_hash = H256::from_slice(&[4u8; 32]).unwrap();
+        let vcr = anchored_vcr(&worker, 0, slot_hash);
+        let validator = validator_for(std::slice::from_ref(&vcr));
+        let mut guard = ReplayGuard::default();
+        guard.record_slot_hash(10, slot_hash);
+
+        let mut forged = vcr.clone();
+        forged.signature[0] ^= 1;
+        assert!(validator.verify_unique(&forged, &mut guard, 10).is_err());
+        validator.verify_unique(&vcr, &mut guard, 10).unwrap();
+        let err = validator.verify_unique(&vcr, &mut guard, 11).unwrap_err();
+        assert!(err.to_string().contains("already accepted"), "{err}");
+
+        let mut stale = anchored_vcr(&worker, 8, slot_hash);
+        stale.timestamp -= 3600;
+        stale.signature = worker.sign(&stale.signing_message());
+        assert!(validator.verify_unique(&stale, &mut guard, 12).is_err());
+    }
+
+    #[test]
+    fn test_verify_unique_requires_recent_quote_anchor() {
+        let worker = Keypair::generate();
+        let validator = validator_for(&[create_test_vcr(&worker, 5)]);
+        let mut guard = ReplayGuard::new(FreshnessConfig {
+            quote_window_slots: 4,
+            ..FreshnessConfig::default()
+        });
+        let slot_hash = |slot: u8| H256::from_slice(&[slot; 32]).unwrap();
+        guard.record_slot_hash(10, slot_hash(10));
+
+        // The quote has to carry a slot hash, and one the guard has seen.
+        let err = validator
+            .verify_unique(&create_test_vcr(&worker, 5), &mut guard, 10)
+            .unwrap_err();
+        assert!(err.to_string().contains("not anchored"), "{err}");
+        let unknown = anchored_vcr(&worker, 1, slot_hash(99));
+        assert!(validator.verify_unique(&unknown, &mut guard, 10).is_err());
+
+        // The anchor is bound into the quote: swapping it breaks the binding.
+        let mut swapped = anchored_vcr(&worker, 1, slot_hash(99));
+        swapped.set_quote_slot_hash(slot_hash(10));
+        swapped.signature = worker.sign(&swapped.signing_message());
+        let err = validator
+            .verify_unique(&swapped, &mut guard, 10)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("report_data"), "{err:#}");
+
+        let vcr = anchored_vcr(&worker, 1, slot_hash(10));
+        validator.verify_unique(&vcr, &mut guard, 10).unwrap();
+
+        // The same quote cannot back a second job, even from another worker.
+        let other = Keypair::generate();
+        let mut reused = anchored_vcr(&other, 2, slot_hash(10));
+        reused.tee_attestation = vcr.tee_attestation.clone();
+        reused.signature = other.sign(&reused.signing_message());
+        let err = guard.check(&reused, 11, current_timestamp()).unwrap_err();
+        assert!(err.to_string().contains("another job"), "{err}");
+
+        // Once the anchor slot leaves the window, the quote is too old.
+        for slot in 11..=13 {
+            guard.record_slot_hash(slot, slot_hash(slot as u8));
+        }
+        let late = anchored_vcr(&worker, 3, slot_hash(10));
+        validator.verify_unique(&late, &mut guard, 13).unwrap();
+        guard.record_slot_hash(14, slot_hash(14));
+        let late = anchored_vcr(&worker, 4, slot_hash(10));
+        let err = validator.verify_unique(&late, &mut guard, 14).unwrap_err();
+        assert!(err.to_string().contains("slot hashes"), "{err}");
+    }
+
+    fn optimistic_vcr(
+        worker: &Keypair,
+        quote_key: &SimulatedSigner,
+    ) -> (VerifiableComputeReceipt, Vec<u8>) {
+        let mut vcr = anchored_vcr(worker, 1, H256::from_slice(&[4u8; 32]).unwrap());
+        let report = quote_key
+            .quote(&[1u8; 48], &vcr.report_data(), vcr.timestamp)
+            .unwrap();
+        vcr.tee_attestation = serde_json::to_vec(&report).unwrap();
+        let quote = vcr.commit_quote();
+        vcr.signature = worker.sign(&vcr.signing_message());
+        (vcr, quote)
+    }
+
+    #[test]
+    fn test_optimistic_receipts_defer_the_quote() {
+        let worker = Keypair::generate();
+        let (vcr, quote) = optimistic_vcr(&worker, &SimulatedSigner::devnet());
+        let validator = validator_for(std::slice::from_ref(&vcr));
+
+        assert!(vcr.tee_attestation.is_empty());
+        assert_eq!(vcr.quote_commitment(), Some(quote_commitment(&quote)));
+        assert_eq!(
+            VerifiableComputeReceipt::decode(&vcr.encode())
+                .unwrap()
+                .quote_commitment(),
+            vcr.quote_commitment()
+        );
+        validator.verify_optimistic(&vcr).unwrap();
+        // The full path still needs the quote in the receipt.
+        assert!(validator.verify(&vcr).is_err());
+        // A receipt carrying its quote is not optimistic.
+        let full = create_test_vcr(&worker, 5);
+        assert!(validator.verify_optimistic(&full).is_err());
+
+        // The commitment is signed over.
+        let mut swapped = vcr.clone();
+        swapped.tee_attestation = b"other".to_vec();
+        swapped.commit_quote();
+        assert!(validator.verify_optimistic(&swapped).is_err());
+
+        // Optimistic receipts still need a live anchor.
+        let mut guard = ReplayGuard::default();
+        let err = guard.check(&vcr, 10, current_timestamp()).unwrap_err();
+        assert!(err.to_string().contains("slot hashes"), "{err}");
+        guard.record_slot_hash(10, H256::from_slice(&[4u8; 32]).unwrap());
+        guard.check(&vcr, 10, current_timestamp()).unwrap();
+    }
+
+    #[test]
+    fn test_quote_fraud_check() {
+        let worker = Keypair::generate();
+        let (honest, quote) = optimistic_vcr(&worker, &SimulatedSigner::devnet());
+        let validator = validator_for(std::slice::from_ref(&honest));
+        assert_eq!(
+            validator.check_quote_fraud(&honest, &quote).unwrap(),
+            QuoteVerdict::Valid
+        );
+        // Revealing anything but the committed bytes is a malformed proof.
+        let err = validator.check_quote_fraud(&honest, b"quote").unwrap_err();
+        assert!(err.to_string().contains("commitment"), "{err}");
+        assert!(validator
+            .check_quote_fraud(&create_test_vcr(&worker, 5), &quote)
+            .is_err());
+
+        // A quote signed by an unknown key passes the optimistic checks but
+        // fails once revealed.
+        let (forged, quote) = optimistic_vcr(&worker, &SimulatedSigner::from_seed(&[9u8; 32]));
+        validator.verify_optimistic(&forged).unwrap();
+        assert!(matches!(
+            validator.check_quote_fraud(&forged, &quote).unwrap(),
+            QuoteVerdict::Fraudulent(_)
+        ));
+
+        // So does a commitment to bytes that are not a quote at all.
+        let mut garbage = anchored_vcr(&worker, 2, H256::from_slice(&[4u8; 32]).unwrap());
+        garbage.tee_attestation = b"not a quote".to_vec();
+        let quote = garbage.commit_quote();
+        garbage.signature = worker.sign(&garbage.signing_message());
+        assert!(matches!(
+            validator.check_quote_fraud(&garbage, &quote).unwrap(),
+            QuoteVerdict::Fraudulent(_)
+        ));
+    }
+
+    #[test]
+    fn test_encoding_roundtrip() {
+        let vcr = create_test_vcr(&Keypair::generate(), 5);
+        let encoded = vcr.encode();
+        let decoded = VerifiableComputeReceipt::decode(&encoded).unwrap();
+        assert_eq!(decoded.encode(), encoded);
+        assert_eq!(decoded.signing_message(), vcr.signing_message());
+
+        assert!(VerifiableComputeReceipt::decode(&encoded[..encoded.len() - 1]).is_err());
+        let mut trailing = encoded.clone();
+        trailing.push(0);
+        assert!(VerifiableComputeReceipt::decode(&trailing).is_err());
+    }
+
+    #[test]
+    fn test_tolerates_unknown_optional_extensions() {
+        let worker = Keypair::generate();
+        let mut vcr = create_test_vcr(&worker, 5);
+        vcr.extensions.push(VcrExtension {
+            tag: 0x0042,
+            data: b"gpu attestation".to_vec(),
+        });
+        vcr.signature = worker.sign(&vcr.signing_message());
+
+        let decoded = VerifiableComputeReceipt::decode(&vcr.encode()).unwrap();
+        assert_eq!(decoded.extensions, vcr.extensions);
+        let validator = validator_for(std::slice::from_ref(&decoded));
+        validator.verify(&decoded).unwrap();
+
+        // Extensions are signed: stripping one invalidates the receipt.
+        let mut stripped = decoded.clone();
+        stripped.extensions.clear();
+        assert!(validator.verify(&stripped).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unsupported_format() {
+        let worker = Keypair::generate();
+        let vcr = create_test_vcr(&worker, 5);
+        let validator = validator_for(std::slice::from_ref(&vcr));
+
+        let mut critical = vcr.clone();
+        critical.extensions.push(VcrExtension {
+            tag: EXTENSION_CRITICAL | 0x0042,
+            data: Vec::new(),
+        });
+        critical.signature = worker.sign(&critical.signing_message());
+        let err = VerifiableComputeReceipt::decode(&critical.encode()).unwrap_err();
+        assert!(err.to_string().contains("critical"), "{err}");
+        assert!(validator.verify(&critical).is_err());
+
+        let mut unsorted = vcr.clone();
+        for tag in [2, 1] {
+            unsorted.extensions.push(VcrExtension {
+                tag,
+                data: Vec::new(),
+            });
+        }
+        assert!(VerifiableComputeReceipt::decode(&unsorted.encode()).is_err());
+
+        let mut newer = vcr.clone();
+        newer.version = VCR_VERSION + 1;
+        assert!(VerifiableComputeReceipt::decode(&newer.encode()).is_err());
+        assert!(validator.verify(&newer).is_err());
+    }
+
+    #[test]
+    fn test_policy_selects_checks() {
+        let worker = Keypair::generate();
+        let resign = |vcr: &mut VerifiableComputeReceipt| {
+            vcr.signature = worker.sign(&vcr.signing_message());
+        };
+
+        // A TeeOnly receipt skips the trace opening.
+        let mut tee_only = create_test_vcr(&worker, 5);
+        tee_only.set_policy(VerificationPolicy::TeeOnly);
+        tee_only.trace_commitment.clear();
+        resign(&mut tee_only);
+        let mut validator = validator_for(std::slice::from_ref(&tee_only));
+        let err = validator.verify(&tee_only).unwrap_err();
+        assert!(err.to_string().contains("weaker"), "{err}");
+        validator.set_model_policy(tee_only.model_hash, VerificationPolicy::TeeOnly);
+        validator.verify(&tee_only).unwrap();
+
+        // The policy is signed, and still needs the checks it names.
+        let mut downgraded = create_test_vcr(&worker, 5);
+        downgraded.trace_commitment.clear();
+        resign(&mut downgraded);
+        assert!(validator.verify(&downgraded).is_err());
+        let mut relabeled = tee_only.clone();
+        relabeled.set_policy(VerificationPolicy::TeePlusKzg);
+        assert!(validator.verify(&relabeled).is_err());
+
+        // Quorum policies only settle through `verify_quorum`.
+        let vcrs: Vec<_> = (0..3)
+            .map(|_| {
+                let worker = Keypair::generate();
+                let mut vcr = create_test_vcr(&worker, 5);
+                vcr.set_policy(VerificationPolicy::QuorumOnly);
+                vcr.tee_attestation.clear();
+                vcr.signature = worker.sign(&vcr.signing_message());
+                vcr
+            })
+            .collect();
+        let mut validator = validator_for(&vcrs);
+        validator.set_model_policy(H256::zero(), VerificationPolicy::QuorumOnly);
+        let err = validator.verify(&vcrs[0]).unwrap_err();
+        assert!(err.to_string().contains("quorum"), "{err}");
+        validator.verify_quorum(&vcrs).unwrap();
+
+        // A model that requires Full rejects receipts labelled anything less.
+        validator.set_model_policy(H256::zero(), VerificationPolicy::Full);
+        assert!(validator.verify_quorum(&vcrs).is_err());
+    }
+
+    #[test]
+    fn test_policy_extension_is_canonical() {
+        let worker = Keypair::generate();
+        let mut vcr = create_test_vcr(&worker, 5);
+        assert_eq!(vcr.policy().unwrap(), VerificationPolicy::TeePlusKzg);
+        vcr.extensions.push(VcrExtension {
+            tag: 0x0042,
+            data: Vec::new(),
+        });
+        vcr.set_policy(VerificationPolicy::Full);
+        vcr.set_policy(VerificationPolicy::TeeOnly);
+        assert_eq!(vcr.extensions.len(), 2);
+        vcr.signature = worker.sign(&vcr.signing_message());
+
+        let decoded = VerifiableComputeReceipt::decode(&vcr.encode()).unwrap();
+        assert_eq!(decoded.policy().unwrap(), VerificationPolicy::TeeOnly);
+
+        let mut bad = vcr.clone();
+        bad.extensions[1].data = vec![9];
+        assert!(bad.policy().is_err());
+    }
+
+    #[test]
+    fn test_layer_opening_checked_with_the_trace() {
+        let worker = Keypair::generate();
+        let mut vcr = create_test_vcr(&worker, 5);
+        let validator = validator_for(std::slice::from_ref(&vcr));
+        let kzg = aether_crypto_kzg::KzgVerifier::new_insecure_test(16);
+        let layers: Vec<Vec<[u8; 32]>> = (0..3i64)
+            .map(|j| {
+                (0..4)
+                    .map(|k| aether_crypto_kzg::scalar_from_i64(j - k))
+                    .collect()
+            })
+            .collect();
+        let commitments: Vec<KzgCommitment> =
+            layers.iter().map(|l| kzg.commit(l).unwrap()).collect();
+        let point: [u8; 32] = vcr.trace_point.as_slice().try_into().unwrap();
+        let opening =
+            aether_crypto_kzg::aggregate_openings(&kzg, &commitments, &layers, &point).unwrap();
+        let sign = |mut vcr: VerifiableComputeReceipt, opening: AggregatedOpening| {
+            vcr.set_layer_opening(&LayerOpening {
+                commitments: commitments.clone(),
+                opening,
+            });
+            vcr.signature = worker.sign(&vcr.signing_message());
+            vcr
+        };
+
+        vcr = sign(vcr, opening.clone());
+        let decoded = VerifiableComputeReceipt::decode(&vcr.encode()).unwrap();
+        assert_eq!(decoded.layer_opening().unwrap().unwrap().opening, opening);
+        validator.verify(&decoded).unwrap();
+
+        let mut wrong_value = opening.clone();
+        wrong_value.evaluations[2] = aether_crypto_kzg::scalar_from_i64(7);
+        assert!(validator.verify(&sign(vcr.clone(), wrong_value)).is_err());
+
+        let elsewhere = aether_crypto_kzg::aggregate_openings(
+            &kzg,
+            &commitments,
+            &layers,
+            &aether_crypto_kzg::scalar_from_i64(9),
+        )
+        .unwrap();
+        let err = validator.verify(&sign(vcr, elsewhere)).unwrap_err();
+        assert!(err.to_string().contains("trace point"), "{err}");
+    }
 }
 
 #[cfg(test)]
 mod proptests {
     use super::*;
     use aether_crypto_primitives::Keypair;
-    use aether_verifiers_tee::TeeType;
+    use aether_verifiers_tee::SimulatedSigner;
     use proptest::prelude::*;
 
+    /// Test validator with every receipt's worker admitted under its own key.
+    fn validator_for(vcrs: &[VerifiableComputeReceipt]) -> VcrValidator {
+        let mut validator = VcrValidator::new_for_test();
+        for vcr in vcrs {
+            validator
+                .register_worker(vcr.worker_id.clone(), &vcr.worker_id)
+                .ok();
+        }
+        validator
+    }
+
     /// Build a valid VCR signed by `worker` with specified output byte.
     fn make_vcr(worker: &Keypair, output: u8) -> VerifiableComputeReceipt {
-        let report = aether_verifiers_tee::AttestationReport {
-            tee_type: TeeType::Simulation,
-            measurement: vec![1u8; 48],
-            nonce: vec![2u8; 32],
-            timestamp: current_timestamp(),
-            signature: vec![3u8; 64],
-            cert_chain: vec![vec![4u8; 16]],
-        };
-
         let kzg = aether_crypto_kzg::KzgVerifier::new_insecure_test(16);
         let mut coeffs = [[0u8; 32]; 2];
         coeffs[0][0] = 3;
@@ -479,6 +1473,7 @@ mod proptests {
         let proof = kzg.create_proof(&coeffs, &z).unwrap();
 
         let mut vcr = VerifiableComputeReceipt {
+            version: VCR_VERSION,
             job_id: H256::zero(),
             worker_id: worker.public_key(),
             model_hash: H256::zero(),
@@ -488,12 +1483,20 @@ mod proptests {
             trace_proof: proof.proof,
             trace_evaluation: proof.evaluation,
             trace_point: z.to_vec(),
-            tee_attestation: serde_json::to_vec(&report).unwrap(),
+            tee_attestation: Vec::new(),
+            code_hash: vec![3u8; 32],
+            seed: 7,
             timestamp: current_timestamp(),
+            gas_used: 1_000,
+            extensions: Vec::new(),
             signature: Vec::new(),
         };
 
-        let msg = vcr.signing_message().unwrap();
+        let report = SimulatedSigner::devnet()
+            .quote(&[1u8; 48], &vcr.report_data(), vcr.timestamp)
+            .unwrap();
+        vcr.tee_attestation = serde_json::to_vec(&report).unwrap();
+        let msg = vcr.signing_message();
         vcr.signature = worker.sign(&msg);
         vcr
     }
@@ -504,9 +1507,9 @@ mod proptests {
         /// A valid VCR always passes single-VCR verification.
         #[test]
         fn valid_vcr_always_verifies(output in 1u8..=255u8) {
-            let validator = VcrValidator::new_for_test();
             let worker = Keypair::generate();
             let vcr = make_vcr(&worker, output);
+            let validator = validator_for(std::slice::from_ref(&vcr));
             prop_assert!(validator.verify(&vcr).is_ok());
         }
 
@@ -517,12 +1520,12 @@ mod proptests {
             byte_idx in 0usize..64usize,
             flip in 1u8..=255u8,
         ) {
-            let validator = VcrValidator::new_for_test();
             let worker = Keypair::generate();
             let mut vcr = make_vcr(&worker, output);
             // ensure the signature is long enough
             if byte_idx < vcr.signature.len() {
                 vcr.signature[byte_idx] ^= flip;
+                let validator = validator_for(std::slice::from_ref(&vcr));
                 prop_assert!(validator.verify(&vcr).is_err());
             }
         }
@@ -530,37 +1533,36 @@ mod proptests {
         /// A short (< 32-byte) worker ID is always rejected.
         #[test]
         fn short_worker_id_rejected(len in 0usize..32usize) {
-            let validator = VcrValidator::new_for_test();
             let worker = Keypair::generate();
             let mut vcr = make_vcr(&worker, 7);
             vcr.worker_id = vec![0u8; len];
+            let validator = validator_for(std::slice::from_ref(&vcr));
             prop_assert!(validator.verify(&vcr).is_err());
         }
 
         /// verify_quorum succeeds when all workers agree on the same output.
         #[test]
         fn quorum_succeeds_on_unanimous_agreement(n_workers in 3usize..=8usize) {
-            let validator = VcrValidator::new_for_test();
             let vcrs: Vec<_> = (0..n_workers)
                 .map(|_| make_vcr(&Keypair::generate(), 42))
                 .collect();
+            let validator = validator_for(&vcrs);
             prop_assert!(validator.verify_quorum(&vcrs).is_ok());
         }
 
         /// verify_quorum fails when fewer than quorum_size VCRs are provided.
         #[test]
         fn quorum_fails_below_threshold(n in 0usize..=2usize) {
-            let validator = VcrValidator::new_for_test(); // quorum_size = 3
             let vcrs: Vec<_> = (0..n)
                 .map(|_| make_vcr(&Keypair::generate(), 42))
                 .collect();
+            let validator = validator_for(&vcrs);
             prop_assert!(validator.verify_quorum(&vcrs).is_err());
         }
 
         /// Duplicate worker IDs are rejected as Sybil attacks.
         #[test]
         fn quorum_rejects_duplicate_worker_ids(output in 1u8..=255u8) {
-            let validator = VcrValidator::new_for_test();
             let worker = Keypair::generate();
             // Three VCRs from the same worker — Sybil attack
             let vcrs = vec![
@@ -568,6 +1570,7 @@ mod proptests {
                 make_vcr(&worker, output),
                 make_vcr(&worker, output),
             ];
+            let validator = validator_for(&vcrs);
             prop_assert!(validator.verify_quorum(&vcrs).is_err());
         }
 
@@ -576,8 +1579,8 @@ mod proptests {
         fn signing_message_is_deterministic(output in 1u8..=255u8) {
             let worker = Keypair::generate();
             let vcr = make_vcr(&worker, output);
-            let msg1 = vcr.signing_message().unwrap();
-            let msg2 = vcr.signing_message().unwrap();
+            let msg1 = vcr.signing_message();
+            let msg2 = vcr.signing_message();
             prop_assert_eq!(msg1, msg2);
         }
 
@@ -589,9 +1592,9 @@ mod proptests {
         ) {
             let worker = Keypair::generate();
             let mut vcr = make_vcr(&worker, output1);
-            let msg1 = vcr.signing_message().unwrap();
+            let msg1 = vcr.signing_message();
             vcr.output_hash = H256::from_slice(&[output2; 32]).unwrap();
-            let msg2 = vcr.signing_message().unwrap();
+            let msg2 = vcr.signing_message();
             prop_assert_ne!(msg1, msg2);
         }
 
@@ -601,7 +1604,6 @@ mod proptests {
             majority_output in 1u8..=100u8,
             minority_output in 101u8..=200u8,
         ) {
-            let validator = VcrValidator::new_for_test(); // quorum_size = 3
             let vcrs = vec![
                 make_vcr(&Keypair::generate(), majority_output),
                 make_vcr(&Keypair::generate(), majority_output),
@@ -609,19 +1611,20 @@ mod proptests {
                 make_vcr(&Keypair::generate(), minority_output), // dissenter
             ];
             // 3-of-4 agree — should still satisfy quorum
+            let validator = validator_for(&vcrs);
             prop_assert!(validator.verify_quorum(&vcrs).is_ok());
         }
 
         /// VCR with empty trace_commitment is rejected (KZG verification fails).
         #[test]
         fn empty_trace_commitment_rejected(output in 1u8..=255u8) {
-            let validator = VcrValidator::new_for_test();
             let worker = Keypair::generate();
             let mut vcr = make_vcr(&worker, output);
             vcr.trace_commitment = Vec::new();
             // Must re-sign after mutation so rejection is from KZG, not signature
-            let msg = vcr.signing_message().unwrap();
+            let msg = vcr.signing_message();
             vcr.signature = worker.sign(&msg);
+            let validator = validator_for(std::slice::from_ref(&vcr));
             prop_assert!(validator.verify(&vcr).is_err());
         }
     }
@@ -0,0 +1,183 @@
+// ============================================================================
+// OPTIMISTIC TEE VERIFICATION - Quote commitments and fraud checks
+// ============================================================================
+// A full hardware quote is kilobytes of certificates (SNP, Nitro) and costs
+// more to verify on chain than the rest of the receipt together. On the
+// optimistic path the receipt carries only a commitment to the quote
+// (critical extension `EXT_QUOTE_COMMITMENT`, SHA-256 of the exact
+// `tee_attestation` bytes) and `tee_attestation` stays empty:
+//
+//   worker:      quote -> commit_quote() -> sign -> post receipt on chain
+//                quote -> QuoteStore (off chain, served on request)
+//   validators:  verify_optimistic() - everything except the TEE check
+//   challenger:  fetch the quote by its hash, run check_quote_fraud() and,
+//                if it fails, post it as a fraud proof before the challenge
+//                window closes
+//
+// A fraud check is evaluated as of the receipt's timestamp, so a quote that
+// was valid when the work was done does not become fraudulent by ageing.
+// Withholding the quote is the provider's risk: the commitment is signed, so
+// a provider that cannot produce matching bytes cannot defend the receipt.
+// ============================================================================
+
+use crate::{VcrExtension, VcrValidator, VerifiableComputeReceipt, EXTENSION_CRITICAL};
+use aether_types::H256;
+use aether_verifiers_tee::AttestationReport;
+use anyhow::{bail, Result};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Extension carrying SHA-256 of the receipt's withheld `tee_attestation`.
+pub const EXT_QUOTE_COMMITMENT: u16 = EXTENSION_CRITICAL | 0x0003;
+
+/// Commitment to raw `tee_attestation` bytes.
+pub fn quote_commitment(quote: &[u8]) -> H256 {
+    H256::from_slice(&Sha256::digest(quote)).expect("SHA-256 output is 32 bytes")
+}
+
+/// Outcome of checking a revealed quote against an optimistic receipt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuoteVerdict {
+    /// The quote verifies; a fraud proof built on it must be rejected.
+    Valid,
+    /// The quote does not verify, with the reason.
+    Fraudulent(String),
+}
+
+impl VcrValidator {
+    /// Verify a receipt on the optimistic path: every check `verify` makes
+    /// except the TEE quote, which must be committed to and withheld.
+    pub fn verify_optimistic(&self, vcr: &VerifiableComputeReceipt) -> Result<()> {
+        let policy = self.check_policy(vcr)?;
+        if policy.requires_quorum() {
+            bail!("VCR policy {policy:?} requires quorum verification");
+        }
+        if !policy.requires_tee() {
+            bail!("VCR policy {policy:?} has no TEE quote to verify optimistically");
+        }
+        if vcr.quote_commitment().is_none() {
+            bail!("optimistic VCR carries no quote commitment");
+        }
+        if !vcr.tee_attestation.is_empty() {
+            bail!("optimistic VCR must not carry the quote itself");
+        }
+        if policy.requires_trace() {
+            self.verify_trace_opening(vcr)?;
+        }
+        self.verify_signature(vcr)
+    }
+
+    /// Check the quote revealed for an optimistic receipt. Errors mean the
+    /// fraud proof is malformed (wrong quote for the commitment, or not an
+    /// optimistic receipt); an invalid quote is `QuoteVerdict::Fraudulent`.
+    pub fn check_quote_fraud(
+        &self,
+        vcr: &VerifiableComputeReceipt,
+        quote: &[u8],
+    ) -> Result<QuoteVerdict> {
+        let Some(commitment) = vcr.quote_commitment() else {
+            bail!("VCR has no quote commitment");
+        };
+        if quote_commitment(quote) != commitment {
+            bail!("revealed quote does not match the receipt's commitment");
+        }
+
+        let report: AttestationReport = match serde_json::from_slice(quote) {
+            Ok(report) => report,
+            Err(e) => {
+                return Ok(QuoteVerdict::Fraudulent(format!(
+                    "committed quote is not an AttestationReport: {e}"
+                )))
+            }
+        };
+        match self
+            .tee_verifier
+            .verify_quote(&report, &vcr.report_data(), vcr.timestamp)
+        {
+            Ok(()) => Ok(QuoteVerdict::Valid),
+            Err(e) => Ok(QuoteVerdict::Fraudulent(format!("{e:#}"))),
+        }
+    }
+}
+
+impl VerifiableComputeReceipt {
+    /// Commitment to the withheld quote, if this is an optimistic receipt.
+    pub fn quote_commitment(&self) -> Option<H256> {
+        self.extensions
+            .iter()
+            .find(|ext| ext.tag == EXT_QUOTE_COMMITMENT)
+            .and_then(|ext| H256::from_slice(&ext.data).ok())
+    }
+
+    /// Move the quote out of the receipt, leaving a commitment to it, and
+    /// return it for off-chain storage. Sign afterwards.
+    pub fn commit_quote(&mut self) -> Vec<u8> {
+        let quote = std::mem::take(&mut self.tee_attestation);
+        let data = quote_commitment(&quote).as_bytes().to_vec();
+        match self
+            .extensions
+            .binary_search_by_key(&EXT_QUOTE_COMMITMENT, |ext| ext.tag)
+        {
+            Ok(idx) => self.extensions[idx].data = data,
+            Err(idx) => self.extensions.insert(
+                idx,
+                VcrExtension {
+                    tag: EXT_QUOTE_COMMITMENT,
+                    data,
+                },
+            ),
+        }
+        quote
+    }
+}
+
+/// Off-chain store of withheld quotes, keyed by commitment.
+#[derive(Debug, Clone, Default)]
+pub struct QuoteStore {
+    quotes: HashMap<H256, Vec<u8>>,
+}
+
+impl QuoteStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Store `quote` and return its commitment.
+    pub fn put(&mut self, quote: Vec<u8>) -> H256 {
+        let commitment = quote_commitment(&quote);
+        self.quotes.insert(commitment, quote);
+        commitment
+    }
+
+    pub fn get(&self, commitment: &H256) -> Option<&[u8]> {
+        self.quotes.get(commitment).map(Vec::as_slice)
+    }
+
+    pub fn remove(&mut self, commitment: &H256) -> Option<Vec<u8>> {
+        self.quotes.remove(commitment)
+    }
+
+    pub fn len(&self) -> usize {
+        self.quotes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.quotes.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn store_returns_quotes_by_commitment() {
+        let mut store = QuoteStore::new();
+        let commitment = store.put(b"quote-1".to_vec());
+        assert_eq!(commitment, quote_commitment(b"quote-1"));
+        assert_eq!(store.get(&commitment).unwrap(), b"quote-1");
+        assert!(store.get(&quote_commitment(b"quote-2")).is_none());
+        assert_eq!(store.remove(&commitment), Some(b"quote-1".to_vec()));
+        assert!(store.is_empty());
+    }
+}
@@ -0,0 +1,139 @@
+// ============================================================================
+// VERIFICATION POLICIES - How much checking a receipt gets
+// ============================================================================
+// Not every model is worth a KZG opening and a three-worker quorum. A job's
+// policy is recorded in the receipt (critical extension
+// `EXT_VERIFICATION_POLICY`, so it is signed and cannot be dropped) and the
+// validator applies exactly the checks that policy names:
+//
+//   policy       TEE quote   KZG opening   quorum
+//   TeeOnly         x
+//   TeePlusKzg      x            x
+//   QuorumOnly                                x
+//   Full            x            x            x
+//
+// Receipts without the extension predate policies and are TeePlusKzg. Each
+// model has a minimum policy on the validator; a receipt whose policy does
+// not include every check of that minimum is rejected.
+// ============================================================================
+
+use crate::{VcrExtension, VerifiableComputeReceipt, EXTENSION_CRITICAL};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Extension carrying the receipt's `VerificationPolicy` as one byte.
+pub const EXT_VERIFICATION_POLICY: u16 = EXTENSION_CRITICAL | 0x0001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum VerificationPolicy {
+    TeeOnly,
+    #[default]
+    TeePlusKzg,
+    QuorumOnly,
+    Full,
+}
+
+impl VerificationPolicy {
+    pub fn requires_tee(self) -> bool {
+        matches!(self, Self::TeeOnly | Self::TeePlusKzg | Self::Full)
+    }
+
+    pub fn requires_trace(self) -> bool {
+        matches!(self, Self::TeePlusKzg | Self::Full)
+    }
+
+    pub fn requires_quorum(self) -> bool {
+        matches!(self, Self::QuorumOnly | Self::Full)
+    }
+
+    /// Whether this policy performs every check `required` does.
+    pub fn covers(self, required: Self) -> bool {
+        (self.requires_tee() || !required.requires_tee())
+            && (self.requires_trace() || !required.requires_trace())
+            && (self.requires_quorum() || !required.requires_quorum())
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::TeeOnly => 0,
+            Self::TeePlusKzg => 1,
+            Self::QuorumOnly => 2,
+            Self::Full => 3,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        Ok(match byte {
+            0 => Self::TeeOnly,
+            1 => Self::TeePlusKzg,
+            2 => Self::QuorumOnly,
+            3 => Self::Full,
+            other => bail!("unknown verification policy {other}"),
+        })
+    }
+}
+
+impl VerifiableComputeReceipt {
+    /// The policy recorded in the receipt; `TeePlusKzg` if none is.
+    pub fn policy(&self) -> Result<VerificationPolicy> {
+        match self
+            .extensions
+            .iter()
+            .find(|ext| ext.tag == EXT_VERIFICATION_POLICY)
+        {
+            None => Ok(VerificationPolicy::default()),
+            Some(ext) => match ext.data.as_slice() {
+                [byte] => VerificationPolicy::from_byte(*byte),
+                _ => bail!("verification policy extension must be one byte"),
+            },
+        }
+    }
+
+    /// Record `policy`, keeping extensions sorted. Sign afterwards.
+    pub fn set_policy(&mut self, policy: VerificationPolicy) {
+        let data = vec![policy.to_byte()];
+        match self
+            .extensions
+            .binary_search_by_key(&EXT_VERIFICATION_POLICY, |ext| ext.tag)
+        {
+            Ok(idx) => self.extensions[idx].data = data,
+            Err(idx) => self.extensions.insert(
+                idx,
+                VcrExtension {
+                    tag: EXT_VERIFICATION_POLICY,
+                    data,
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use VerificationPolicy::*;
+
+    #[test]
+    fn coverage_is_by_checks_performed() {
+        for policy in [TeeOnly, TeePlusKzg, QuorumOnly, Full] {
+            assert!(policy.covers(policy));
+            assert!(Full.covers(policy));
+        }
+        assert!(TeePlusKzg.covers(TeeOnly));
+        assert!(!TeeOnly.covers(TeePlusKzg));
+        assert!(!QuorumOnly.covers(TeeOnly));
+        assert!(!TeePlusKzg.covers(QuorumOnly));
+        assert!(!QuorumOnly.covers(Full));
+    }
+
+    #[test]
+    fn policy_extension_roundtrip() {
+        for policy in [TeeOnly, TeePlusKzg, QuorumOnly, Full] {
+            assert_eq!(
+                VerificationPolicy::from_byte(policy.to_byte()).unwrap(),
+                policy
+            );
+        }
+        assert!(VerificationPolicy::from_byte(4).is_err());
+    }
+}
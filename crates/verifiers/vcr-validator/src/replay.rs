@@ -0,0 +1,272 @@
+// ============================================================================
+// VCR REPLAY PROTECTION
+// ============================================================================
+// A receipt is accepted once per (job_id, worker_id) and only while its
+// timestamp is within `max_drift_slots` of the validator's clock. Seen pairs
+// are remembered for at least twice the drift window, after which the
+// timestamp check alone rejects a replay, so the set stays bounded.
+//
+// A receipt's TEE quote must also be anchored to one of the last
+// `quote_window_slots` slot hashes (critical extension `EXT_QUOTE_ANCHOR`,
+// bound into the quote's report_data) and may back only one job while that
+// anchor is live; see `aether_verifiers_tee::freshness`. Optimistic
+// receipts, which only commit to their quote, need a live anchor too.
+// ============================================================================
+
+use crate::{VcrExtension, VerifiableComputeReceipt, EXTENSION_CRITICAL};
+use aether_types::{Slot, H256};
+use aether_verifiers_tee::{AttestationReport, QuoteFreshness};
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+
+/// Extension carrying the 32-byte slot hash the receipt's quote is bound to.
+pub const EXT_QUOTE_ANCHOR: u16 = EXTENSION_CRITICAL | 0x0002;
+
+type ReceiptKey = (H256, Vec<u8>);
+
+#[derive(Debug, Clone)]
+pub struct FreshnessConfig {
+    /// Slot duration, to express timestamp drift in slots.
+    pub slot_ms: u64,
+    /// How far a receipt timestamp may lag or lead the validator clock.
+    pub max_drift_slots: u64,
+    /// How long seen pairs are kept. Raised to cover the drift window.
+    pub retention_slots: u64,
+    /// How many recent slot hashes a quote may be anchored to.
+    pub quote_window_slots: u64,
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        FreshnessConfig {
+            slot_ms: 500,
+            max_drift_slots: 120,
+            retention_slots: 0,
+            quote_window_slots: 240,
+        }
+    }
+}
+
+impl FreshnessConfig {
+    fn retention(&self) -> u64 {
+        self.retention_slots
+            .max(self.max_drift_slots.saturating_mul(2).saturating_add(1))
+    }
+}
+
+/// Expiring set of accepted (job_id, worker_id) pairs, plus the recent
+/// slot hashes and quotes behind them.
+#[derive(Debug, Clone)]
+pub struct ReplayGuard {
+    config: FreshnessConfig,
+    seen: HashMap<ReceiptKey, Slot>,
+    expiries: BTreeMap<Slot, Vec<ReceiptKey>>,
+    quotes: QuoteFreshness,
+}
+
+impl ReplayGuard {
+    pub fn new(config: FreshnessConfig) -> Self {
+        ReplayGuard {
+            quotes: QuoteFreshness::new(config.quote_window_slots),
+            config,
+            seen: HashMap::new(),
+            expiries: BTreeMap::new(),
+        }
+    }
+
+    /// Record the hash of `slot`; call as the chain head advances. Quotes
+    /// must be anchored to one of the last `quote_window_slots` of these.
+    pub fn record_slot_hash(&mut self, slot: Slot, hash: H256) {
+        let mut bytes = [0u8; 32];
+        bytes.copy_from_slice(hash.as_bytes());
+        self.quotes.record_slot_hash(slot, bytes);
+    }
+
+    /// Reject receipts whose timestamp is outside the drift window around
+    /// `now` (unix seconds).
+    pub fn check_fresh(&self, vcr: &VerifiableComputeReceipt, now: u64) -> Result<()> {
+        let drift_ms = now.abs_diff(vcr.timestamp).saturating_mul(1000);
+        let drift_slots = drift_ms / self.config.slot_ms.max(1);
+        if drift_slots > self.config.max_drift_slots {
+            bail!(
+                "VCR timestamp {} is {drift_slots} slots from now (max {})",
+                vcr.timestamp,
+                self.config.max_drift_slots
+            );
+        }
+        Ok(())
+    }
+
+    /// Freshness and uniqueness checks, without recording the receipt.
+    pub fn check(
+        &mut self,
+        vcr: &VerifiableComputeReceipt,
+        current_slot: Slot,
+        now: u64,
+    ) -> Result<()> {
+        self.prune(current_slot);
+        self.check_fresh(vcr, now)?;
+        if let Some(slot) = self.seen.get(&(vcr.job_id, vcr.worker_id.clone())) {
+            bail!(
+                "VCR for job {} from this worker was already accepted at slot {slot}",
+                vcr.job_id
+            );
+        }
+        if let Some(report) = quote_of(vcr)? {
+            if vcr.quote_slot_hash().is_empty() {
+                bail!("VCR quote is not anchored to a slot hash");
+            }
+            self.quotes
+                .check(&report, vcr.job_id.as_bytes(), vcr.quote_slot_hash())?;
+        } else if vcr.quote_commitment().is_some() {
+            // The withheld quote is checked only on a fraud proof, but its
+            // anchor has to be live now.
+            if vcr.quote_slot_hash().is_empty() {
+                bail!("VCR quote is not anchored to a slot hash");
+            }
+            self.quotes.anchor_slot(vcr.quote_slot_hash())?;
+        }
+        Ok(())
+    }
+
+    /// Remember an accepted receipt until its retention expires.
+    pub fn record(&mut self, vcr: &VerifiableComputeReceipt, current_slot: Slot) {
+        let key = (vcr.job_id, vcr.worker_id.clone());
+        if self.seen.insert(key.clone(), current_slot).is_none() {
+            let expiry = current_slot.saturating_add(self.config.retention());
+            self.expiries.entry(expiry).or_default().push(key);
+        }
+        if let (Ok(Some(report)), Ok(anchor_slot)) = (
+            quote_of(vcr),
+            self.quotes.anchor_slot(vcr.quote_slot_hash()),
+        ) {
+            self.quotes
+                .record(&report, vcr.job_id.as_bytes(), anchor_slot);
+        }
+    }
+
+    fn prune(&mut self, current_slot: Slot) {
+        let live = self.expiries.split_off(&current_slot.saturating_add(1));
+        for key in std::mem::replace(&mut self.expiries, live)
+            .into_values()
+            .flatten()
+        {
+            self.seen.remove(&key);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+impl Default for ReplayGuard {
+    fn default() -> Self {
+        Self::new(FreshnessConfig::default())
+    }
+}
+
+/// The receipt's TEE quote, if it carries one.
+fn quote_of(vcr: &VerifiableComputeReceipt) -> Result<Option<AttestationReport>> {
+    if vcr.tee_attestation.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_slice(&vcr.tee_attestation)
+        .map(Some)
+        .context("invalid tee_attestation payload (expected JSON AttestationReport)")
+}
+
+impl VerifiableComputeReceipt {
+    /// Slot hash bound into the receipt's quote; empty if none is recorded.
+    pub fn quote_slot_hash(&self) -> &[u8] {
+        self.extensions
+            .iter()
+            .find(|ext| ext.tag == EXT_QUOTE_ANCHOR)
+            .map(|ext| ext.data.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Record the slot hash the quote is anchored to, keeping extensions
+    /// sorted. Quote and sign afterwards.
+    pub fn set_quote_slot_hash(&mut self, slot_hash: H256) {
+        let data = slot_hash.as_bytes().to_vec();
+        match self
+            .extensions
+            .binary_search_by_key(&EXT_QUOTE_ANCHOR, |ext| ext.tag)
+        {
+            Ok(idx) => self.extensions[idx].data = data,
+            Err(idx) => self.extensions.insert(
+                idx,
+                VcrExtension {
+                    tag: EXT_QUOTE_ANCHOR,
+                    data,
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(job: u8, worker: u8, timestamp: u64) -> VerifiableComputeReceipt {
+        VerifiableComputeReceipt {
+            version: crate::VCR_VERSION,
+            job_id: H256::from_slice(&[job; 32]).unwrap(),
+            worker_id: vec![worker; 32],
+            model_hash: H256::zero(),
+            input_hash: H256::zero(),
+            output_hash: H256::zero(),
+            trace_commitment: Vec::new(),
+            trace_proof: Vec::new(),
+            trace_evaluation: Vec::new(),
+            trace_point: Vec::new(),
+            tee_attestation: Vec::new(),
+            code_hash: Vec::new(),
+            seed: 0,
+            timestamp,
+            gas_used: 0,
+            extensions: Vec::new(),
+            signature: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn rejects_timestamps_outside_drift() {
+        // 500ms slots, 120 slots = 60s either side.
+        let guard = ReplayGuard::default();
+        let now = 10_000;
+        assert!(guard.check_fresh(&receipt(1, 1, now - 60), now).is_ok());
+        assert!(guard.check_fresh(&receipt(1, 1, now + 60), now).is_ok());
+        assert!(guard.check_fresh(&receipt(1, 1, now - 61), now).is_err());
+        assert!(guard.check_fresh(&receipt(1, 1, now + 61), now).is_err());
+    }
+
+    #[test]
+    fn rejects_replayed_pairs_until_expiry() {
+        let mut guard = ReplayGuard::new(FreshnessConfig {
+            max_drift_slots: 10,
+            ..FreshnessConfig::default()
+        });
+        let now = 1_000;
+        let vcr = receipt(1, 1, now);
+        guard.check(&vcr, 100, now).unwrap();
+        guard.record(&vcr, 100);
+
+        let err = guard.check(&vcr, 101, now).unwrap_err();
+        assert!(err.to_string().contains("already accepted"), "{err}");
+        // Same job from another worker, or another job, is independent.
+        assert!(guard.check(&receipt(1, 2, now), 101, now).is_ok());
+        assert!(guard.check(&receipt(2, 1, now), 101, now).is_ok());
+
+        // Retained for 2 * drift + 1 slots, then pruned.
+        assert!(guard.check(&vcr, 120, now).is_err());
+        assert!(guard.check(&vcr, 121, now).is_ok());
+        assert!(guard.is_empty());
+    }
+}
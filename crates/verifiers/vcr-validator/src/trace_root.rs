@@ -0,0 +1,183 @@
+// ============================================================================
+// TRACE ROOTS - Receipts for traces longer than one KZG commitment
+// ============================================================================
+// `trace_commitment` is a single KZG commitment and so covers at most the
+// setup's `max_degree + 1` trace values. Workers also commit the whole
+// flattened trace in chunks (`aether_crypto_kzg::kzg_commit`) and record the
+// Merkle root over the chunk commitments in the receipt (critical extension
+// `EXT_TRACE_ROOT`, so it is signed and cannot be dropped):
+//
+//   root (32 bytes) || len (u64) || chunk_size (u32)
+//
+// Any trace value can then be opened against the receipt with a
+// `ChunkOpening` from the worker.
+// ============================================================================
+
+use crate::{VcrExtension, VerifiableComputeReceipt, EXTENSION_CRITICAL};
+use aether_codecs::{CanonicalReader, CanonicalWriter};
+use aether_crypto_kzg::{
+    verify_chunk_opening, ChunkOpening, ChunkedCommitment, KzgVerifier, ScalarBytes,
+};
+use anyhow::{ensure, Context, Result};
+
+/// Extension carrying the receipt's `TraceRoot`.
+pub const EXT_TRACE_ROOT: u16 = EXTENSION_CRITICAL | 0x0004;
+
+/// What a receipt records of a `ChunkedCommitment`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceRoot {
+    pub root: [u8; 32],
+    /// Trace values committed.
+    pub len: u64,
+    pub chunk_size: u32,
+}
+
+impl From<&ChunkedCommitment> for TraceRoot {
+    fn from(committed: &ChunkedCommitment) -> Self {
+        TraceRoot {
+            root: committed.root,
+            len: committed.len,
+            chunk_size: committed.chunk_size,
+        }
+    }
+}
+
+impl TraceRoot {
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = CanonicalWriter::with_capacity(44);
+        writer
+            .put_fixed(&self.root)
+            .put_u64(self.len)
+            .put_u32(self.chunk_size);
+        writer.finish()
+    }
+
+    pub(crate) fn decode(data: &[u8]) -> Result<Self> {
+        let mut reader = CanonicalReader::new(data);
+        let root = TraceRoot {
+            root: reader.take_fixed()?,
+            len: reader.take_u64()?,
+            chunk_size: reader.take_u32()?,
+        };
+        reader.finish()?;
+        ensure!(root.len > 0, "trace root covers no values");
+        ensure!(
+            root.chunk_size > 0,
+            "trace root chunk size must be positive"
+        );
+        Ok(root)
+    }
+}
+
+impl VerifiableComputeReceipt {
+    /// The chunked trace root recorded in the receipt, if any.
+    pub fn trace_root(&self) -> Result<Option<TraceRoot>> {
+        self.extensions
+            .iter()
+            .find(|ext| ext.tag == EXT_TRACE_ROOT)
+            .map(|ext| TraceRoot::decode(&ext.data).context("invalid trace root extension"))
+            .transpose()
+    }
+
+    /// Record `root`, keeping extensions sorted. Sign afterwards.
+    pub fn set_trace_root(&mut self, root: TraceRoot) {
+        let data = root.encode();
+        match self
+            .extensions
+            .binary_search_by_key(&EXT_TRACE_ROOT, |ext| ext.tag)
+        {
+            Ok(idx) => self.extensions[idx].data = data,
+            Err(idx) => self.extensions.insert(
+                idx,
+                VcrExtension {
+                    tag: EXT_TRACE_ROOT,
+                    data,
+                },
+            ),
+        }
+    }
+
+    /// Check `opening` against the receipt's trace root and return the
+    /// opened trace value.
+    pub fn verify_trace_value(
+        &self,
+        kzg: &KzgVerifier,
+        opening: &ChunkOpening,
+    ) -> Result<ScalarBytes> {
+        let root = self
+            .trace_root()?
+            .context("receipt carries no trace root")?;
+        verify_chunk_opening(kzg, &root.root, root.len, root.chunk_size, opening)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aether_crypto_kzg::{kzg_commit, scalar_from_i64};
+    use aether_types::H256;
+
+    fn receipt() -> VerifiableComputeReceipt {
+        VerifiableComputeReceipt {
+            version: crate::VCR_VERSION,
+            job_id: H256::zero(),
+            worker_id: vec![1u8; 32],
+            model_hash: H256::zero(),
+            input_hash: H256::zero(),
+            output_hash: H256::zero(),
+            trace_commitment: Vec::new(),
+            trace_proof: Vec::new(),
+            trace_evaluation: Vec::new(),
+            trace_point: Vec::new(),
+            tee_attestation: Vec::new(),
+            code_hash: Vec::new(),
+            seed: 0,
+            timestamp: 0,
+            gas_used: 0,
+            extensions: Vec::new(),
+            signature: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn opens_long_traces_against_the_receipt() {
+        let kzg = KzgVerifier::new_insecure_test(3);
+        let values: Vec<ScalarBytes> = (0..10).map(|v| scalar_from_i64(v * 3 - 7)).collect();
+        let committed = kzg_commit(&kzg, &values, 4).unwrap();
+
+        let mut vcr = receipt();
+        assert_eq!(vcr.trace_root().unwrap(), None);
+        vcr.set_trace_root(TraceRoot::from(&committed));
+        let vcr = VerifiableComputeReceipt::decode(&vcr.encode()).unwrap();
+        assert_eq!(vcr.trace_root().unwrap(), Some(TraceRoot::from(&committed)));
+
+        let opening = committed.open(&kzg, &values, 9).unwrap();
+        assert_eq!(vcr.verify_trace_value(&kzg, &opening).unwrap(), values[9]);
+        assert!(receipt().verify_trace_value(&kzg, &opening).is_err());
+
+        // A root over a different trace does not accept the opening.
+        let mut other = values.clone();
+        other[0] = scalar_from_i64(1);
+        let mut forged = vcr.clone();
+        forged.set_trace_root(TraceRoot::from(&kzg_commit(&kzg, &other, 4).unwrap()));
+        assert!(forged.verify_trace_value(&kzg, &opening).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_roots() {
+        let mut vcr = receipt();
+        vcr.extensions.push(VcrExtension {
+            tag: EXT_TRACE_ROOT,
+            data: vec![0u8; 43],
+        });
+        assert!(VerifiableComputeReceipt::decode(&vcr.encode()).is_err());
+
+        vcr.extensions.clear();
+        vcr.set_trace_root(TraceRoot {
+            root: [1u8; 32],
+            len: 4,
+            chunk_size: 0,
+        });
+        assert!(VerifiableComputeReceipt::decode(&vcr.encode()).is_err());
+    }
+}